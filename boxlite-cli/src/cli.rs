@@ -2,12 +2,18 @@
 //! This module contains all CLI-related code including the main CLI structure,
 //! subcommands, and flag definitions.
 
-use boxlite::runtime::options::{PortProtocol, PortSpec, VolumeSpec};
-use boxlite::{BoxCommand, BoxOptions, BoxliteOptions, BoxliteRuntime};
+use crate::context::ContextStore;
+use crate::output::OutputMode;
+use boxlite::runtime::options::{
+    PortProtocol, PortSpec, SwapBacking, Ulimit, VolumeChown, VolumeSpec,
+};
+use boxlite::{
+    BoxCommand, BoxOptions, BoxliteOptions, BoxliteRuntime, ImagePullPolicy, NetworkMode,
+};
 use clap::{Args, Command, Parser, Subcommand, ValueEnum};
 use clap_complete::shells::{Bash, Fish, Zsh};
 use std::io::{IsTerminal, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Helper to parse CLI environment variables and apply them to BoxOptions
 pub fn apply_env_vars(env: &[String], opts: &mut BoxOptions) {
@@ -53,6 +59,12 @@ pub enum Commands {
     Run(crate::commands::run::RunArgs),
     /// Execute a command in a running box
     Exec(crate::commands::exec::ExecArgs),
+    /// Attach to a detached execution and wait for it to finish
+    Attach(crate::commands::attach::AttachArgs),
+    /// Print the recorded output of an execution
+    ExecLogs(crate::commands::exec_logs::ExecLogsArgs),
+    /// List the executions of a box
+    ExecLs(crate::commands::exec_ls::ExecLsArgs),
     /// Create a new box
     Create(crate::commands::create::CreateArgs),
 
@@ -78,12 +90,57 @@ pub enum Commands {
     /// List images
     Images(crate::commands::images::ImagesArgs),
 
+    /// Manage images (prewarm, pin, unpin, tag, rm)
+    Image(crate::commands::image::ImageArgs),
+
+    /// Create a tag TARGET that refers to SOURCE
+    Tag(crate::commands::image::TagArgs),
+
+    /// Remove one or more images
+    Rmi(crate::commands::image::RmiArgs),
+
+    /// Inspect box networking (top, inspect)
+    Network(crate::commands::network::NetworkArgs),
+
+    /// Manage projects (ls)
+    Project(crate::commands::project::ProjectArgs),
+
+    /// Manage local contexts: named homes, configs and projects (create, use, ls, rm)
+    Context(crate::commands::context::ContextArgs),
+
+    /// Display runtime information and resource reservations
+    Info(crate::commands::info::InfoArgs),
+
+    /// Manage host resources (df)
+    System(crate::commands::system::SystemArgs),
+
+    /// Check for leftovers of removed boxes
+    Doctor(crate::commands::doctor::DoctorArgs),
+
+    /// Validate the installation by booting a box and exercising it
+    SelfTest(crate::commands::self_test::SelfTestArgs),
+
     /// Display detailed information on a box
     Inspect(crate::commands::inspect::InspectArgs),
 
+    /// Display resource usage of running boxes
+    Stats(crate::commands::stats::StatsArgs),
+
     /// Copy files/folders between host and box
     Cp(crate::commands::cp::CpArgs),
 
+    /// Keep a host directory and a box directory in sync, both ways
+    Sync(crate::commands::sync::SyncArgs),
+
+    /// Install packages in a box with its package manager
+    Provision(crate::commands::provision::ProvisionArgs),
+
+    /// Print the JSON Schema of BoxOptions or BoxInfo for SDK code generation
+    Schema(crate::commands::schema::SchemaArgs),
+
+    /// Debug a running runtime (set-log-level)
+    Debug(crate::commands::debug::DebugArgs),
+
     /// Generate shell completion script (hidden from help)
     #[command(hide = true)]
     Completion(CompletionArgs),
@@ -124,9 +181,10 @@ pub struct GlobalFlags {
     #[arg(long, global = true)]
     pub debug: bool,
 
-    /// BoxLite home directory
-    #[arg(long, global = true, env = "BOXLITE_HOME")]
-    pub home: Option<std::path::PathBuf>,
+    /// BoxLite home directory; `list` accepts several (repeat the flag) and
+    /// shows the boxes of all of them
+    #[arg(long, global = true, env = "BOXLITE_HOME", value_name = "DIR")]
+    pub home: Vec<std::path::PathBuf>,
 
     /// Image registry to use (can be specified multiple times)
     #[arg(long, global = true, value_name = "REGISTRY")]
@@ -138,10 +196,77 @@ pub struct GlobalFlags {
     /// If not provided, uses default options (no config file is loaded from $BOXLITE_HOME).
     #[arg(long, global = true)]
     pub config: Option<String>,
+
+    /// Context to use instead of the current one (see `boxlite context`)
+    #[arg(long, global = true, env = "BOXLITE_CONTEXT", value_name = "NAME")]
+    pub context: Option<String>,
+
+    /// Project to create boxes in and to limit ls/rm to
+    #[arg(long, global = true, env = "BOXLITE_PROJECT")]
+    pub project: Option<String>,
+
+    /// When a box name isn't found, accept a unique case-insensitive match
+    /// and suggest similar names
+    #[arg(
+        long,
+        global = true,
+        env = "BOXLITE_FUZZY_NAMES",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    pub fuzzy_names: bool,
+
+    /// Output mode: `json` prints a single `{"result": ...}` or
+    /// `{"error": {"code", "message"}}` object on stdout and human text on stderr
+    #[arg(long, global = true, value_enum, default_value_t = OutputMode::Text)]
+    pub output: OutputMode,
 }
 
 impl GlobalFlags {
     pub fn create_runtime(&self) -> anyhow::Result<BoxliteRuntime> {
+        BoxliteRuntime::new(self.runtime_options()?).map_err(Into::into)
+    }
+
+    /// Fill in the home, config file and project of the context in effect
+    /// that weren't given as flags.
+    pub fn with_context(mut self) -> anyhow::Result<Self> {
+        let store = ContextStore::load()?;
+        let Some(context) = store.get(store.current_name(self.context.as_deref()))? else {
+            return Ok(self);
+        };
+        if self.home.is_empty() {
+            self.home.extend(context.home.clone());
+        }
+        if self.config.is_none() {
+            self.config = context.config.as_ref().map(|p| p.display().to_string());
+        }
+        if self.project.is_none() {
+            self.project = context.project.clone();
+        }
+        Ok(self)
+    }
+
+    /// The `--home` directory, if given.
+    pub fn home(&self) -> Option<&Path> {
+        self.home.first().map(PathBuf::as_path)
+    }
+
+    /// The flags for each `--home` given, for commands that read several
+    /// homes. Without `--home`, just these flags.
+    pub fn per_home(&self) -> Vec<GlobalFlags> {
+        if self.home.len() <= 1 {
+            return vec![self.clone()];
+        }
+        self.home
+            .iter()
+            .map(|home| GlobalFlags {
+                home: vec![home.clone()],
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// Runtime options from the config file and global flags.
+    pub fn runtime_options(&self) -> anyhow::Result<BoxliteOptions> {
         // Load config file if provided, otherwise use default options
         let mut options = if let Some(config_path) = &self.config {
             crate::config::load_config(Path::new(config_path))?
@@ -150,8 +275,19 @@ impl GlobalFlags {
         };
 
         // CLI --home override home_dir
-        if let Some(cli_home) = &self.home {
-            options.home_dir = cli_home.clone();
+        if self.home.len() > 1 {
+            anyhow::bail!(
+                "--home was given {} times; only `list` reads several homes",
+                self.home.len()
+            );
+        }
+        if let Some(cli_home) = self.home() {
+            options.home_dir = cli_home.to_path_buf();
+        }
+
+        // CLI --project overrides default_project
+        if let Some(project) = &self.project {
+            options.default_project = Some(project.clone());
         }
 
         // CLI --registry prepends to image_registries (highest priority)
@@ -164,7 +300,7 @@ impl GlobalFlags {
                 .collect();
         }
 
-        BoxliteRuntime::new(options).map_err(Into::into)
+        Ok(options)
     }
 }
 
@@ -186,6 +322,10 @@ pub struct ProcessFlags {
     #[arg(short = 'e', long = "env")]
     pub env: Vec<String>,
 
+    /// Read environment variables from a file (KEY=VALUE per line); -e wins
+    #[arg(long = "env-file", value_name = "FILE")]
+    pub env_file: Vec<PathBuf>,
+
     /// Working directory inside the box
     #[arg(short = 'w', long = "workdir")]
     pub workdir: Option<String>,
@@ -203,10 +343,24 @@ impl ProcessFlags {
         F: Fn(&str) -> Option<String>,
     {
         opts.working_dir = self.workdir.clone();
-        apply_env_vars_with_lookup(&self.env, opts, lookup);
+        apply_env_vars_with_lookup(&self.env_entries()?, opts, lookup);
         Ok(())
     }
 
+    /// `--env-file` entries followed by `-e` entries, so `-e` takes
+    /// precedence for a variable set by both.
+    fn env_entries(&self) -> anyhow::Result<Vec<String>> {
+        let mut entries = Vec::new();
+        for path in &self.env_file {
+            let content = std::fs::read_to_string(path).map_err(|e| {
+                anyhow::anyhow!("failed to read env file {}: {}", path.display(), e)
+            })?;
+            entries.extend(parse_env_file(&content));
+        }
+        entries.extend(self.env.iter().cloned());
+        Ok(entries)
+    }
+
     /// Validate process flags
     pub fn validate(&self, detach: bool) -> anyhow::Result<()> {
         // Check TTY mode only in non-detach mode
@@ -218,8 +372,8 @@ impl ProcessFlags {
     }
 
     /// Configures a BoxCommand with process flags (env, workdir, tty)
-    pub fn configure_command(&self, mut cmd: BoxCommand) -> BoxCommand {
-        for env_str in &self.env {
+    pub fn configure_command(&self, mut cmd: BoxCommand) -> anyhow::Result<BoxCommand> {
+        for env_str in &self.env_entries()? {
             if let Some((k, v)) = env_str.split_once('=') {
                 cmd = cmd.env(k, v);
             } else if let Ok(val) = std::env::var(env_str) {
@@ -235,10 +389,23 @@ impl ProcessFlags {
             cmd = cmd.tty(true);
         }
 
-        cmd
+        Ok(cmd)
     }
 }
 
+/// Entries of an env file, in the form `-e` takes.
+///
+/// Blank lines and `#` comments are skipped; values are taken literally,
+/// quotes included. A line with only a name takes the host's value.
+fn parse_env_file(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.trim_start().trim_end_matches('\r'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
 // ============================================================================
 // RESOURCE FLAGS
 // ============================================================================
@@ -252,6 +419,26 @@ pub struct ResourceFlags {
     /// Memory limit (in MiB)
     #[arg(long)]
     pub memory: Option<u32>,
+
+    /// Size of /dev/shm in the box (in MiB, default 64)
+    #[arg(long = "shm-size", value_name = "MiB")]
+    pub shm_size: Option<u32>,
+
+    /// Swap for the box (in MiB), used once memory is full
+    #[arg(long, value_name = "MiB")]
+    pub swap: Option<u32>,
+
+    /// Back --swap with compressed RAM (zram) instead of a swap file
+    #[arg(long, requires = "swap")]
+    pub swap_zram: bool,
+
+    /// Set a resource limit for processes in the box (e.g. nofile=1024:4096; repeatable)
+    #[arg(long, value_name = "NAME=SOFT[:HARD]", value_parser = parse_ulimit)]
+    pub ulimit: Vec<Ulimit>,
+
+    /// Set a kernel parameter in the box (e.g. vm.max_map_count=262144; repeatable)
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_sysctl)]
+    pub sysctl: Vec<(String, String)>,
 }
 
 impl ResourceFlags {
@@ -265,11 +452,37 @@ impl ResourceFlags {
         if let Some(mem) = self.memory {
             opts.memory_mib = Some(mem);
         }
+        if let Some(shm) = self.shm_size {
+            opts.shm_size_mib = Some(shm);
+        }
+        if let Some(swap) = self.swap {
+            opts.swap_mib = Some(swap);
+            opts.swap_backing = if self.swap_zram {
+                SwapBacking::Zram
+            } else {
+                SwapBacking::File
+            };
+        }
+        opts.sysctls.extend(self.sysctl.iter().cloned());
+        opts.ulimits.extend(self.ulimit.iter().cloned());
+    }
+}
+
+/// Parse a `--ulimit` value: `name=soft[:hard]`.
+fn parse_ulimit(s: &str) -> Result<Ulimit, String> {
+    s.parse().map_err(|e: boxlite::BoxliteError| e.to_string())
+}
+
+/// Parse a `--sysctl` value: `KEY=VALUE`.
+fn parse_sysctl(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid sysctl '{}' (expected KEY=VALUE)", s)),
     }
 }
 
 // ============================================================================
-// PUBLISH (PORT) FLAGS
+// PUBLISH (PORT) AND HOSTNAME FLAGS
 // ============================================================================
 
 #[derive(Args, Debug, Clone)]
@@ -277,6 +490,30 @@ pub struct PublishFlags {
     /// Publish a box port to the host (format: [hostPort:]boxPort[/tcp|udp], e.g. 18789:18789)
     #[arg(short = 'p', long = "publish", value_name = "PORT")]
     pub publish: Vec<String>,
+
+    /// Box hostname (default: the box name)
+    #[arg(long, value_name = "NAME")]
+    pub hostname: Option<String>,
+
+    /// Add an /etc/hosts entry in the box (repeatable)
+    #[arg(long = "add-host", value_name = "HOST:IP", value_parser = parse_add_host)]
+    pub add_host: Vec<(String, String)>,
+
+    /// Join a network; running boxes on it resolve each other by name (repeatable)
+    #[arg(long = "network", value_name = "NAME")]
+    pub network: Vec<String>,
+
+    /// Let the box reach services on the host's 127.0.0.1 as host.boxlite.internal
+    #[arg(long)]
+    pub host_loopback: bool,
+
+    /// Save the box's HTTP(S) responses in DIR, through a proxy on the gateway
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Answer the box's HTTP(S) requests from responses saved with --record
+    #[arg(long, value_name = "DIR")]
+    pub replay: Option<PathBuf>,
 }
 
 impl PublishFlags {
@@ -291,65 +528,37 @@ impl PublishFlags {
             }
             opts.ports.push(spec);
         }
+        opts.hostname = self.hostname.clone();
+        opts.extra_hosts = self.add_host.clone();
+        opts.networks = self.network.clone();
+        opts.host_loopback = self.host_loopback;
+        if let Some(dir) = &self.record {
+            opts.network_mode = NetworkMode::Record(std::path::absolute(dir)?);
+        }
+        if let Some(dir) = &self.replay {
+            opts.network_mode = NetworkMode::Replay(std::path::absolute(dir)?);
+        }
         Ok(())
     }
 }
 
+/// Parse an `--add-host` value: `HOST:IP`, where IP may be IPv6.
+fn parse_add_host(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((host, ip)) if !host.is_empty() && !ip.is_empty() => {
+            Ok((host.to_string(), ip.to_string()))
+        }
+        _ => Err(format!("invalid host entry '{}' (expected HOST:IP)", s)),
+    }
+}
+
 /// Parse a single publish spec: `[hostPort:]boxPort[/tcp|udp]`.
 /// - `boxPort` → host_port=None, guest_port=boxPort
 /// - `hostPort:boxPort` → host_port=Some(hostPort), guest_port=boxPort
 ///
 /// Only TCP is forwarded by the runtime today; UDP is accepted but not yet implemented.
 fn parse_publish_spec(s: &str) -> anyhow::Result<PortSpec> {
-    let s = s.trim();
-    if s.is_empty() {
-        anyhow::bail!("empty port spec");
-    }
-    let (rest, protocol) = match s.split_once('/') {
-        Some((r, proto)) => {
-            let p = if proto.eq_ignore_ascii_case("tcp") {
-                PortProtocol::Tcp
-            } else if proto.eq_ignore_ascii_case("udp") {
-                PortProtocol::Udp
-            } else {
-                anyhow::bail!("invalid protocol {:?}; use tcp or udp", proto)
-            };
-            (r.trim(), p)
-        }
-        None => (s, PortProtocol::Tcp),
-    };
-    let parts: Vec<&str> = rest.splitn(2, ':').map(str::trim).collect();
-    let (host_port, guest_port) = match parts.as_slice() {
-        [guest] => {
-            let g = parse_port(guest)?;
-            (None, g)
-        }
-        [host, guest] => {
-            let h = parse_port(host)?;
-            let g = parse_port(guest)?;
-            (Some(h), g)
-        }
-        _ => anyhow::bail!(
-            "invalid port spec {:?}; use hostPort:boxPort or boxPort[/tcp]",
-            s
-        ),
-    };
-    Ok(PortSpec {
-        host_port,
-        guest_port,
-        protocol,
-        host_ip: None,
-    })
-}
-
-fn parse_port(s: &str) -> anyhow::Result<u16> {
-    let n: u16 = s
-        .parse()
-        .map_err(|_| anyhow::anyhow!("invalid port number {:?}", s))?;
-    if n == 0 {
-        anyhow::bail!("port must be 1-65535");
-    }
-    Ok(n)
+    Ok(s.parse::<PortSpec>()?)
 }
 
 // ============================================================================
@@ -361,6 +570,17 @@ struct ParsedVolumeSpec {
     host_path: Option<String>,
     guest_path: String,
     read_only: bool,
+    /// Mount options besides ro/rw (noexec, nosuid, nodev, uid=, gid=)
+    options: Vec<String>,
+    chown: Option<VolumeChown>,
+}
+
+/// Options part of a volume spec.
+#[derive(Default)]
+struct ParsedVolumeOptions {
+    read_only: bool,
+    options: Vec<String>,
+    chown: Option<VolumeChown>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -368,6 +588,10 @@ pub struct VolumeFlags {
     /// Mount a volume (format: hostPath:boxPath[:options], or boxPath for anonymous volume, e.g. /data:/app/data, /data:ro)
     #[arg(short = 'v', long = "volume", value_name = "VOLUME")]
     pub volume: Vec<String>,
+
+    /// Volume driver for named volumes: a host part without a path separator (e.g. pgdata:/data) is passed to the driver as a volume name; host paths stay bind mounts
+    #[arg(long = "volume-driver", value_name = "DRIVER")]
+    pub volume_driver: Option<String>,
 }
 
 /// True if the segment is a single ASCII letter (Windows drive, e.g. "C" in "C:\path").
@@ -386,16 +610,42 @@ fn is_windows_absolute_path(path: &str) -> bool {
     b.len() >= 3 && b[0].is_ascii_alphabetic() && b[1] == b':' && (b[2] == b'\\' || b[2] == b'/')
 }
 
-/// Parse options string (e.g. "ro" or "rw,nocopy") and return read_only. Other options are ignored.
-fn parse_volume_read_only(opts: &str) -> bool {
-    opts.split(',').any(|o| o.trim().eq_ignore_ascii_case("ro"))
+/// True if the host part of a volume spec is a volume name rather than a path
+/// (no separator, e.g. `pgdata`), like Docker's named volumes.
+fn is_volume_name(host: &str) -> bool {
+    !host.contains(['/', '\\']) && !is_windows_absolute_path(host)
+}
+
+/// Parse options string (e.g. "ro,noexec" or "rw,nocopy") into read_only, the mount
+/// options the runtime applies (noexec, nosuid, nodev, uid=, gid=) and `U` (map the box
+/// user to the host directory's owner, like Podman's `U`). Other options are ignored.
+fn parse_volume_options(opts: &str) -> ParsedVolumeOptions {
+    let mut parsed = ParsedVolumeOptions::default();
+    for opt in opts.split(',').map(str::trim) {
+        if opt == "U" {
+            parsed.chown = Some(VolumeChown::HostUser);
+            continue;
+        }
+        let lower = opt.to_ascii_lowercase();
+        match lower.as_str() {
+            "ro" => parsed.read_only = true,
+            "noexec" | "nosuid" | "nodev" => parsed.options.push(lower),
+            _ if lower.starts_with("uid=") || lower.starts_with("gid=") => {
+                parsed.options.push(lower)
+            }
+            _ => {}
+        }
+    }
+    parsed
 }
 
 /// Parse a single volume spec.
 /// - Anonymous : `boxPath` or `boxPath:ro` (e.g. `/data`, `/data:ro`).
 /// - Bind mount: `hostPath:boxPath[:options]` (e.g. `/data:/app/data`, `/data:/app/data:ro`).
 ///
-/// Options: `ro` (read-only), `rw` (read-write, default). Other options are ignored.
+/// Options: `ro` (read-only), `rw` (read-write, default), `noexec`, `nosuid`, `nodev`,
+///   `uid=<n>[-<m>]`, `gid=<n>[-<m>]`, `U` (files the box user writes are owned by the host
+///   user), comma-separated. Other options are ignored.
 ///   Windows: host path may be a drive path like `C:\data`; the colon after the drive letter is not
 ///   treated as a separator (e.g. `C:\data:/app/data` → host=`C:\data`, guest=`/app/data`).
 fn parse_volume_spec(s: &str) -> anyhow::Result<ParsedVolumeSpec> {
@@ -405,7 +655,7 @@ fn parse_volume_spec(s: &str) -> anyhow::Result<ParsedVolumeSpec> {
     }
     let parts: Vec<&str> = s.split(':').map(str::trim).collect();
 
-    let (host_path, guest_path, read_only) = match parts.len() {
+    let (host_path, guest_path, opts) = match parts.len() {
         1 => {
             // Anonymous volume: box path only (e.g. /data)
             let guest = parts[0].to_string();
@@ -418,7 +668,7 @@ fn parse_volume_spec(s: &str) -> anyhow::Result<ParsedVolumeSpec> {
                     guest
                 );
             }
-            (None, guest, false)
+            (None, guest, ParsedVolumeOptions::default())
         }
         2 => {
             // Either anonymous with options (guest:ro) or bind (host:guest)
@@ -428,25 +678,33 @@ fn parse_volume_spec(s: &str) -> anyhow::Result<ParsedVolumeSpec> {
                 if guest.is_empty() {
                     anyhow::bail!("volume box path must be non-empty");
                 }
-                (None, guest, second.eq_ignore_ascii_case("ro"))
+                (None, guest, parse_volume_options(second))
             } else {
-                (Some(parts[0].to_string()), parts[1].to_string(), false)
+                (
+                    Some(parts[0].to_string()),
+                    parts[1].to_string(),
+                    ParsedVolumeOptions::default(),
+                )
             }
         }
         3 => {
             if is_windows_drive(parts[0]) {
                 let host = format!("{}:{}", parts[0], parts[1]);
-                (Some(host), parts[2].to_string(), false)
+                (
+                    Some(host),
+                    parts[2].to_string(),
+                    ParsedVolumeOptions::default(),
+                )
             } else {
-                let ro = parse_volume_read_only(parts[2]);
-                (Some(parts[0].to_string()), parts[1].to_string(), ro)
+                let opts = parse_volume_options(parts[2]);
+                (Some(parts[0].to_string()), parts[1].to_string(), opts)
             }
         }
         4.. => {
             if is_windows_drive(parts[0]) {
                 let host = format!("{}:{}", parts[0], parts[1]);
-                let ro = parse_volume_read_only(parts[3]);
-                (Some(host), parts[2].to_string(), ro)
+                let opts = parse_volume_options(parts[3]);
+                (Some(host), parts[2].to_string(), opts)
             } else {
                 anyhow::bail!(
                     "invalid volume spec {:?}; use hostPath:boxPath[:options] (e.g. /data:/app/data or C:\\data:/app/data:ro)",
@@ -473,7 +731,9 @@ fn parse_volume_spec(s: &str) -> anyhow::Result<ParsedVolumeSpec> {
     Ok(ParsedVolumeSpec {
         host_path,
         guest_path,
-        read_only,
+        read_only: opts.read_only,
+        options: opts.options,
+        chown: opts.chown,
     })
 }
 
@@ -504,7 +764,15 @@ impl VolumeFlags {
         let base = anonymous_volume_base(home);
         for s in self.volume.iter() {
             let spec = parse_volume_spec(s)?;
+            // Anonymous volumes and host paths are always local directories
+            let driver = spec
+                .host_path
+                .as_deref()
+                .filter(|host| is_volume_name(host))
+                .and(self.volume_driver.as_ref());
             let host_path = match spec.host_path {
+                // Driver volume: the name is resolved by the driver at start
+                Some(name) if driver.is_some() => name,
                 Some(host) => {
                     let mut path = host;
                     if std::path::Path::new(&path).is_relative() && !is_windows_absolute_path(&path)
@@ -530,6 +798,9 @@ impl VolumeFlags {
                 host_path,
                 guest_path: spec.guest_path,
                 read_only: spec.read_only,
+                options: spec.options,
+                chown: spec.chown,
+                driver: driver.cloned(),
             });
         }
         Ok(())
@@ -553,13 +824,93 @@ pub struct ManagementFlags {
     /// Automatically remove the box when it exits
     #[arg(long)]
     pub rm: bool,
+
+    /// Pull image before creating (always, missing, never)
+    #[arg(long, value_name = "POLICY", value_parser = parse_pull_policy)]
+    pub pull: Option<ImagePullPolicy>,
+
+    /// Mount a cache directory kept across boxes with the same --name at this box path (e.g. /cache)
+    #[arg(long, value_name = "PATH", requires = "name")]
+    pub persistent_cache: Option<String>,
+
+    /// Guest agent log filter (e.g. debug, trace); see `boxlite inspect --guest-logs`
+    #[arg(long, value_name = "LEVEL")]
+    pub guest_log_level: Option<String>,
+
+    /// Start this box after BOX, starting BOX first if needed (repeatable)
+    #[arg(long = "depends-on", value_name = "BOX")]
+    pub depends_on: Vec<String>,
+
+    /// Also stop dependencies no other running box needs when this box stops
+    #[arg(long, requires = "depends_on")]
+    pub stop_dependencies: bool,
+
+    /// Stop and remove the box this long after creation (e.g. 2h, 30m, 1h30m, 90s)
+    #[arg(long, value_name = "DURATION", value_parser = parse_ttl)]
+    pub ttl: Option<u64>,
 }
 
 impl ManagementFlags {
     pub fn apply_to(&self, opts: &mut BoxOptions) {
         opts.detach = self.detach;
         opts.auto_remove = self.rm;
+        if let Some(policy) = self.pull {
+            opts.pull_policy = policy;
+        }
+        opts.persistent_cache = self.persistent_cache.clone();
+        opts.guest_log_level = self.guest_log_level.clone();
+        opts.depends_on = self.depends_on.clone();
+        opts.stop_dependencies = self.stop_dependencies;
+        opts.ttl_secs = self.ttl;
+    }
+}
+
+/// Parse a `--pull` value. Accepts `missing` as an alias for `if-not-present`.
+fn parse_pull_policy(s: &str) -> Result<ImagePullPolicy, String> {
+    s.parse().map_err(|e: boxlite::BoxliteError| e.to_string())
+}
+
+/// Parse a `--ttl` duration into seconds: plain seconds or number-unit
+/// pairs with units `d`, `h`, `m` and `s` (e.g. `1h30m`).
+fn parse_ttl(s: &str) -> Result<u64, String> {
+    let invalid = || {
+        format!(
+            "invalid duration '{}' (expected e.g. 2h, 30m, 1h30m, 90)",
+            s
+        )
+    };
+    if let Ok(secs) = s.parse::<u64>() {
+        return if secs > 0 {
+            Ok(secs)
+        } else {
+            Err("duration must be greater than zero".into())
+        };
+    }
+
+    let mut total: u64 = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit = match rest[digits..].chars().next() {
+            Some('d') => 86_400,
+            Some('h') => 3_600,
+            Some('m') => 60,
+            Some('s') => 1,
+            _ => return Err(invalid()),
+        };
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        rest = &rest[digits + 1..];
+    }
+    if total == 0 {
+        return Err("duration must be greater than zero".into());
     }
+    Ok(total)
 }
 
 #[cfg(test)]
@@ -596,11 +947,57 @@ mod tests {
         assert!(!opts.env.iter().any(|(k, _)| k == "NON_EXISTENT_VAR"));
     }
 
+    #[test]
+    fn test_parse_env_file() {
+        let content = "# comment\nA=1\n\n  B=two words\r\nC\nD=\"quoted\"\n";
+        assert_eq!(
+            parse_env_file(content),
+            vec!["A=1", "B=two words", "C", "D=\"quoted\""]
+        );
+    }
+
+    #[test]
+    fn test_env_file_then_flags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("env");
+        std::fs::write(&path, "VAR=file\nONLY_FILE=1\n").unwrap();
+        let flags = ProcessFlags {
+            interactive: false,
+            tty: false,
+            env: vec!["VAR=flag".to_string()],
+            env_file: vec![path],
+            workdir: None,
+        };
+
+        let mut opts = BoxOptions::default();
+        flags.apply_to_with_lookup(&mut opts, |_| None).unwrap();
+        // The -e value comes last, so it is the one that takes effect
+        assert_eq!(
+            opts.env,
+            vec![
+                ("VAR".to_string(), "file".to_string()),
+                ("ONLY_FILE".to_string(), "1".to_string()),
+                ("VAR".to_string(), "flag".to_string()),
+            ]
+        );
+
+        let missing = ProcessFlags {
+            env_file: vec![dir.path().join("missing")],
+            ..flags
+        };
+        assert!(missing.configure_command(BoxCommand::new("true")).is_err());
+    }
+
     #[test]
     fn test_resource_flags_cpu_cap() {
         let flags = ResourceFlags {
             cpus: Some(1000),
             memory: None,
+            shm_size: None,
+            swap: None,
+            swap_zram: false,
+            ulimit: vec![],
+            sysctl: vec![],
         };
 
         let mut opts = BoxOptions::default();
@@ -609,6 +1006,47 @@ mod tests {
         assert_eq!(opts.cpus, Some(255));
     }
 
+    #[test]
+    fn test_resource_flags_swap() {
+        let flags = ResourceFlags {
+            cpus: None,
+            memory: Some(512),
+            shm_size: None,
+            swap: Some(1024),
+            swap_zram: true,
+            ulimit: vec![],
+            sysctl: vec![],
+        };
+
+        let mut opts = BoxOptions::default();
+        flags.apply_to(&mut opts);
+
+        assert_eq!(opts.swap_mib, Some(1024));
+        assert_eq!(opts.swap_backing, SwapBacking::Zram);
+    }
+
+    #[test]
+    fn test_resource_flags_sysctl_and_ulimit() {
+        let flags = ResourceFlags {
+            cpus: None,
+            memory: None,
+            shm_size: None,
+            swap: None,
+            swap_zram: false,
+            ulimit: vec![super::parse_ulimit("nofile=1024:4096").unwrap()],
+            sysctl: vec![super::parse_sysctl("vm.max_map_count=262144").unwrap()],
+        };
+
+        let mut opts = BoxOptions::default();
+        flags.apply_to(&mut opts);
+
+        assert_eq!(opts.sysctls["vm.max_map_count"], "262144");
+        assert_eq!(opts.ulimits, vec![Ulimit::new("nofile", 1024, 4096)]);
+        assert!(super::parse_ulimit("stack=1").is_err());
+        assert!(super::parse_sysctl("vm.max_map_count").is_err());
+        assert!(super::parse_sysctl("=1").is_err());
+    }
+
     #[test]
     fn test_parse_publish_spec_host_box() {
         let spec = super::parse_publish_spec("18789:18789").unwrap();
@@ -655,6 +1093,12 @@ mod tests {
     fn test_publish_flags_apply_to() {
         let flags = PublishFlags {
             publish: vec!["18789:18789".to_string(), "8080:80/tcp".to_string()],
+            hostname: None,
+            add_host: vec![],
+            network: vec![],
+            host_loopback: false,
+            record: None,
+            replay: None,
         };
         let mut opts = BoxOptions::default();
         flags.apply_to(&mut opts).unwrap();
@@ -665,6 +1109,37 @@ mod tests {
         assert_eq!(opts.ports[1].guest_port, 80);
     }
 
+    #[test]
+    fn test_publish_flags_replay_is_absolute() {
+        let flags = PublishFlags {
+            publish: vec![],
+            hostname: None,
+            add_host: vec![],
+            network: vec![],
+            host_loopback: false,
+            record: None,
+            replay: Some(PathBuf::from("cassettes")),
+        };
+        let mut opts = BoxOptions::default();
+        flags.apply_to(&mut opts).unwrap();
+        let expected = std::env::current_dir().unwrap().join("cassettes");
+        assert_eq!(opts.network_mode, NetworkMode::Replay(expected));
+    }
+
+    #[test]
+    fn test_parse_add_host() {
+        assert_eq!(
+            super::parse_add_host("db:10.0.0.5").unwrap(),
+            ("db".to_string(), "10.0.0.5".to_string())
+        );
+        assert_eq!(
+            super::parse_add_host("db6:fd00::5").unwrap(),
+            ("db6".to_string(), "fd00::5".to_string())
+        );
+        assert!(super::parse_add_host("db").is_err());
+        assert!(super::parse_add_host(":10.0.0.5").is_err());
+    }
+
     #[test]
     fn test_parse_volume_spec_host_guest() {
         let spec = super::parse_volume_spec("/data:/app/data").unwrap();
@@ -689,6 +1164,21 @@ mod tests {
         assert!(!spec.read_only);
     }
 
+    #[test]
+    fn test_parse_volume_spec_mount_options() {
+        let spec =
+            super::parse_volume_spec("/data:/app/data:ro,noexec,nocopy,uid=1000-1999").unwrap();
+        assert!(spec.read_only);
+        assert_eq!(spec.options, ["noexec", "uid=1000-1999"]);
+        assert!(spec.chown.is_none());
+
+        let spec = super::parse_volume_spec("/data:/app/data:U").unwrap();
+        assert_eq!(spec.chown, Some(VolumeChown::HostUser));
+
+        let spec = super::parse_volume_spec("/data:/app/data").unwrap();
+        assert!(spec.options.is_empty());
+    }
+
     #[test]
     fn test_parse_volume_spec_anonymous() {
         let spec = super::parse_volume_spec("/data").unwrap();
@@ -777,6 +1267,7 @@ mod tests {
                 "/host/data:/guest/data".to_string(),
                 "/readonly:/ro:ro".to_string(),
             ],
+            volume_driver: None,
         };
         let mut opts = BoxOptions::default();
         flags.apply_to(&mut opts, None).unwrap();
@@ -796,6 +1287,7 @@ mod tests {
                 r"C:\host\data:/guest/data".to_string(),
                 r"D:\readonly:/ro:ro".to_string(),
             ],
+            volume_driver: None,
         };
         let mut opts = BoxOptions::default();
         flags.apply_to(&mut opts, None).unwrap();
@@ -813,6 +1305,7 @@ mod tests {
         let base = std::env::temp_dir();
         let flags = VolumeFlags {
             volume: vec!["/data".to_string(), "/cache:ro".to_string()],
+            volume_driver: None,
         };
         let mut opts = BoxOptions::default();
         flags.apply_to(&mut opts, Some(&base)).unwrap();
@@ -828,4 +1321,38 @@ mod tests {
         assert!(opts.volumes[1].read_only);
         assert!(opts.volumes[1].host_path.contains("anonymous"));
     }
+
+    #[test]
+    fn test_volume_flags_apply_to_driver() {
+        let base = std::env::temp_dir();
+        let flags = VolumeFlags {
+            volume: vec![
+                "pgdata:/var/lib/postgresql".to_string(),
+                "/scratch".to_string(),
+                "/srv/config:/etc/app:ro".to_string(),
+            ],
+            volume_driver: Some("san".to_string()),
+        };
+        let mut opts = BoxOptions::default();
+        flags.apply_to(&mut opts, Some(&base)).unwrap();
+        // Named volume is left for the driver, not resolved as a relative path
+        assert_eq!(opts.volumes[0].host_path, "pgdata");
+        assert_eq!(opts.volumes[0].driver.as_deref(), Some("san"));
+        assert_eq!(opts.volumes[1].driver, None);
+        assert!(opts.volumes[1].host_path.contains("anonymous"));
+        // Host paths stay bind mounts
+        assert_eq!(opts.volumes[2].host_path, "/srv/config");
+        assert_eq!(opts.volumes[2].driver, None);
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("90"), Ok(90));
+        assert_eq!(parse_ttl("2h"), Ok(7200));
+        assert_eq!(parse_ttl("1h30m"), Ok(5400));
+        assert_eq!(parse_ttl("1d12h"), Ok(129_600));
+        for bad in ["0", "0s", "", "h", "1h30", "2x", "-5m"] {
+            assert!(parse_ttl(bad).is_err(), "{:?} should be rejected", bad);
+        }
+    }
 }
@@ -0,0 +1,36 @@
+use crate::cli::GlobalFlags;
+use crate::output;
+use crate::terminal::StreamManager;
+use crate::util::{names, to_shell_exit_code};
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct AttachArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target_box: String,
+
+    /// Execution ID printed by `exec --detach`
+    #[arg(index = 2, value_name = "EXECUTION_ID")]
+    pub execution_id: String,
+}
+
+/// Stream the output of a detached execution and exit with its exit code.
+///
+/// Signals received while attached (e.g. Ctrl-C) are forwarded to the execution.
+pub async fn execute(args: AttachArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let litebox = names::require_box(&rt, &args.target_box, global.fuzzy_names).await?;
+
+    let mut execution = litebox.attach_execution(&args.execution_id).await?;
+    let exit_code = StreamManager::new(&mut execution, false, false)
+        .start()
+        .await?;
+    output::set_result(&serde_json::json!({ "exit_code": exit_code }));
+
+    if exit_code != 0 {
+        output::exit(to_shell_exit_code(exit_code));
+    }
+
+    Ok(())
+}
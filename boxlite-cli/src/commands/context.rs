@@ -0,0 +1,211 @@
+use crate::cli::GlobalFlags;
+use crate::context::{Context, ContextStore, DEFAULT_CONTEXT};
+use crate::formatter::{self, OutputFormat};
+use crate::output::{self, outln};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use tabled::Tabled;
+
+/// Manage local contexts: named homes, configs and projects
+#[derive(Args, Debug)]
+pub struct ContextArgs {
+    #[command(subcommand)]
+    pub command: ContextCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ContextCommand {
+    /// Create a context
+    Create(ContextCreateArgs),
+    /// Make a context the one commands run in
+    Use(ContextUseArgs),
+    /// List contexts
+    #[command(visible_alias = "list")]
+    Ls(ContextLsArgs),
+    /// Remove one or more contexts
+    Rm(ContextRmArgs),
+}
+
+/// The context's home, config file and project are taken from the global
+/// `--home`, `--config` and `--project` flags.
+#[derive(Args, Debug)]
+pub struct ContextCreateArgs {
+    /// Context name
+    pub name: String,
+
+    /// Description of the context
+    #[arg(long)]
+    pub description: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ContextUseArgs {
+    /// Context name (`default` for no context)
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ContextLsArgs {
+    /// Only show context names
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ContextRmArgs {
+    /// Context names
+    #[arg(required = true)]
+    pub names: Vec<String>,
+}
+
+#[derive(Tabled, Serialize)]
+struct ContextPresenter {
+    #[tabled(skip)]
+    #[serde(rename = "Name")]
+    name: String,
+
+    /// Name, marked with `*` if current, like `docker context ls`
+    #[tabled(rename = "NAME")]
+    #[serde(skip)]
+    marked_name: String,
+
+    #[tabled(rename = "HOME")]
+    #[serde(rename = "Home")]
+    home: String,
+
+    #[tabled(rename = "PROJECT")]
+    #[serde(rename = "Project")]
+    project: String,
+
+    #[tabled(rename = "DESCRIPTION")]
+    #[serde(rename = "Description")]
+    description: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "Config")]
+    config: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "Current")]
+    current: bool,
+}
+
+impl ContextPresenter {
+    fn new(name: &str, context: &Context, current: bool) -> Self {
+        let path = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        };
+        Self {
+            name: name.to_string(),
+            marked_name: if current {
+                format!("{} *", name)
+            } else {
+                name.to_string()
+            },
+            home: path(&context.home),
+            project: context.project.clone().unwrap_or_default(),
+            description: context.description.clone().unwrap_or_default(),
+            config: path(&context.config),
+            current,
+        }
+    }
+}
+
+/// Contexts are managed outside of any context, so `global` is as parsed.
+pub async fn execute(args: ContextArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        ContextCommand::Create(args) => create(args, global),
+        ContextCommand::Use(args) => use_context(args),
+        ContextCommand::Ls(args) => list(args, global),
+        ContextCommand::Rm(args) => remove(args),
+    }
+}
+
+fn create(args: ContextCreateArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    if global.home.len() > 1 {
+        anyhow::bail!("a context has a single --home");
+    }
+    let mut store = ContextStore::load()?;
+    // Relative paths would change meaning with the working directory
+    let context = Context {
+        description: args.description,
+        home: global.home().map(std::path::absolute).transpose()?,
+        config: global
+            .config
+            .as_deref()
+            .map(std::path::absolute)
+            .transpose()?,
+        project: global.project.clone(),
+    };
+    store.create(&args.name, context)?;
+    store.save()?;
+    outln!("{}", args.name);
+    output::set_result(&args.name);
+    Ok(())
+}
+
+fn use_context(args: ContextUseArgs) -> anyhow::Result<()> {
+    let mut store = ContextStore::load()?;
+    store.use_context(&args.name)?;
+    store.save()?;
+    outln!("Current context is now \"{}\"", args.name);
+    output::set_result(&args.name);
+    Ok(())
+}
+
+fn list(args: ContextLsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let store = ContextStore::load()?;
+    let current = store.current_name(global.context.as_deref());
+    let default = Context::default();
+    let contexts = std::iter::once((DEFAULT_CONTEXT, &default)).chain(
+        store
+            .contexts
+            .iter()
+            .map(|(name, context)| (name.as_str(), context)),
+    );
+
+    if args.quiet {
+        let names: Vec<&str> = contexts.map(|(name, _)| name).collect();
+        for name in &names {
+            outln!("{}", name);
+        }
+        output::set_result(&names);
+        return Ok(());
+    }
+
+    let presenters: Vec<ContextPresenter> = contexts
+        .map(|(name, context)| ContextPresenter::new(name, context, name == current))
+        .collect();
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn remove(args: ContextRmArgs) -> anyhow::Result<()> {
+    let mut store = ContextStore::load()?;
+    for name in &args.names {
+        store.remove(name)?;
+    }
+    store.save()?;
+    for name in &args.names {
+        outln!("{}", name);
+    }
+    output::set_result(&args.names);
+    Ok(())
+}
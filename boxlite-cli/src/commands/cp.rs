@@ -1,6 +1,7 @@
 use crate::cli::GlobalFlags;
+use crate::util::names;
 use anyhow::{Result, anyhow};
-use boxlite::{CopyOptions, LiteBox};
+use boxlite::CopyOptions;
 use clap::Args;
 use std::path::PathBuf;
 
@@ -18,6 +19,11 @@ pub struct CpArgs {
     #[arg(long, default_value_t = true)]
     pub include_parent: bool,
 
+    /// Skip paths matched by .boxliteignore and .gitignore files in the copied directory
+    /// (host to box only)
+    #[arg(long, default_value_t = false)]
+    pub respect_ignore: bool,
+
     /// Source path (host path or BOX:PATH)
     #[arg(index = 1)]
     pub src: String,
@@ -36,6 +42,7 @@ pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
         follow_symlinks: args.follow_symlinks,
         overwrite: !args.no_overwrite,
         include_parent: args.include_parent,
+        respect_ignore_files: args.respect_ignore,
         ..Default::default()
     };
 
@@ -45,8 +52,8 @@ pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
             box_name,
             box_path,
         } => {
-            let handle = require_box(&rt, &box_name).await?;
-            let was_running = handle.info().status == boxlite::BoxStatus::Running;
+            let handle = names::require_box(&rt, &box_name, global.fuzzy_names).await?;
+            let was_running = handle.info().status.is_running();
             if !was_running {
                 handle.start().await?;
             }
@@ -64,8 +71,8 @@ pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
             box_path,
             host,
         } => {
-            let handle = require_box(&rt, &box_name).await?;
-            let was_running = handle.info().status == boxlite::BoxStatus::Running;
+            let handle = names::require_box(&rt, &box_name, global.fuzzy_names).await?;
+            let was_running = handle.info().status.is_running();
             if !was_running {
                 handle.start().await?;
             }
@@ -136,13 +143,6 @@ fn non_empty(path: &str, role: &str) -> Result<String> {
     }
 }
 
-async fn require_box(rt: &boxlite::BoxliteRuntime, name: &str) -> Result<LiteBox> {
-    match rt.get(name).await? {
-        Some(b) => Ok(b),
-        None => Err(anyhow!("box '{}' not found", name)),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
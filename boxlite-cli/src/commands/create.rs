@@ -1,5 +1,8 @@
 use crate::cli::{GlobalFlags, PublishFlags, ResourceFlags, VolumeFlags};
-use boxlite::{BoxOptions, RootfsSpec};
+use crate::formatter;
+use crate::output::{self, outln};
+use boxlite::runtime::options::PortProtocol;
+use boxlite::{BoxOptions, BoxPlan, ImagePlanStatus, LiteBox, RootfsSpec};
 use clap::Args;
 
 /// Create a new box
@@ -28,28 +31,120 @@ pub struct CreateArgs {
 
     #[command(flatten)]
     pub volume: VolumeFlags,
+
+    /// Run PROGRAM as the box's main process instead of the image ENTRYPOINT
+    #[arg(long, value_name = "PROGRAM")]
+    pub entrypoint: Option<String>,
+
+    /// Validate the options and print what would be created, without creating the box
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// With --dry-run, pull the image as the box's pull policy would
+    #[arg(long, requires = "dry_run")]
+    pub fetch_image: bool,
+
+    /// Arguments replacing the image CMD
+    #[arg(index = 2, trailing_var_arg = true)]
+    pub command: Vec<String>,
 }
 
 pub async fn execute(args: CreateArgs, global: &GlobalFlags) -> anyhow::Result<()> {
     let rt = global.create_runtime()?;
     let box_options = args.to_box_options(global)?;
 
+    if args.dry_run {
+        let plan = rt
+            .plan(box_options, args.management.name.clone(), args.fetch_image)
+            .await?;
+        print_plan(&plan);
+        output::set_result(&plan);
+        return Ok(());
+    }
+
     let litebox = rt.create(box_options, args.management.name.clone()).await?;
-    println!("{}", litebox.id());
+    print_warnings(&litebox);
+    outln!("{}", litebox.id());
+    output::set_result(&serde_json::json!({ "id": litebox.id().to_string() }));
 
     Ok(())
 }
 
+/// Print the non-fatal issues found when creating `litebox` to stderr.
+pub(crate) fn print_warnings(litebox: &LiteBox) {
+    for warning in litebox.info().warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+fn print_plan(plan: &BoxPlan) {
+    match &plan.name {
+        Some(name) => outln!("Would create box {} in project {}", name, plan.project),
+        None => outln!("Would create an unnamed box in project {}", plan.project),
+    }
+
+    let status = match plan.image_status {
+        ImagePlanStatus::Cached => "cached",
+        ImagePlanStatus::Refresh => "cached, checked for updates on start",
+        ImagePlanStatus::Pull => "pulled on start",
+        ImagePlanStatus::Pulled => "pulled",
+        ImagePlanStatus::Local => "local directory",
+    };
+    let size = plan
+        .image_size
+        .map(|size| format!(", {}", formatter::format_size(size)))
+        .unwrap_or_default();
+    outln!("  Image:    {} ({}{})", plan.image, status, size);
+    outln!("  CPUs:     {}", plan.cpus);
+    outln!("  Memory:   {} MiB", plan.memory_mib);
+    outln!("  Disk:     {} GB", plan.disk_size_gb);
+    for volume in &plan.volumes {
+        let mode = if volume.is_read_only() { "ro" } else { "rw" };
+        outln!(
+            "  Volume:   {} -> {} ({})",
+            volume.host_path,
+            volume.guest_path,
+            mode
+        );
+    }
+    for port in &plan.ports {
+        let protocol = match port.protocol {
+            PortProtocol::Tcp => "tcp",
+            PortProtocol::Udp => "udp",
+        };
+        // Without a host port the guest port is forwarded as is
+        let host_port = port
+            .host_port
+            .filter(|&p| p != 0)
+            .unwrap_or(port.guest_port);
+        outln!(
+            "  Port:     {} -> {}/{}",
+            host_port,
+            port.guest_port,
+            protocol
+        );
+    }
+    if !plan.depends_on.is_empty() {
+        outln!("  Depends:  {}", plan.depends_on.join(", "));
+    }
+}
+
 impl CreateArgs {
     fn to_box_options(&self, global: &GlobalFlags) -> anyhow::Result<BoxOptions> {
         let mut options = BoxOptions::default();
         self.resource.apply_to(&mut options);
         self.management.apply_to(&mut options);
         self.publish.apply_to(&mut options)?;
-        self.volume.apply_to(&mut options, global.home.as_deref())?;
+        self.volume.apply_to(&mut options, global.home())?;
         options.working_dir = self.workdir.clone();
         crate::cli::apply_env_vars(&self.env, &mut options);
         options.rootfs = RootfsSpec::Image(self.image.clone());
+        if let Some(entrypoint) = &self.entrypoint {
+            options.entrypoint = Some(vec![entrypoint.clone()]);
+        }
+        if !self.command.is_empty() {
+            options.cmd = Some(self.command.clone());
+        }
         Ok(options)
     }
 }
@@ -0,0 +1,42 @@
+use crate::cli::GlobalFlags;
+use crate::output::{self, outln};
+use clap::{Args, Subcommand};
+
+/// Debug a running runtime
+#[derive(Args, Debug)]
+pub struct DebugArgs {
+    #[command(subcommand)]
+    pub command: DebugCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DebugCommand {
+    /// Change the log filter of the runtime running on this home
+    SetLogLevel(SetLogLevelArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SetLogLevelArgs {
+    /// Log filter in RUST_LOG syntax (e.g. debug, info,boxlite::portal=trace)
+    #[arg(value_name = "FILTER")]
+    pub filter: String,
+}
+
+pub async fn execute(args: DebugArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        DebugCommand::SetLogLevel(args) => set_log_level(args, global),
+    }
+}
+
+fn set_log_level(args: SetLogLevelArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    // The runtime holds the home's lock, so don't open one here
+    let options = global.runtime_options()?;
+    let pid = boxlite::runtime::log_level::request_log_level(&options.home_dir, &args.filter)?;
+
+    outln!("Log filter of runtime (pid {}) set to {}", pid, args.filter);
+    output::set_result(&serde_json::json!({
+        "pid": pid,
+        "filter": args.filter,
+    }));
+    Ok(())
+}
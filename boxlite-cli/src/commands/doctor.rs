@@ -0,0 +1,81 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use boxlite::{CleanupLeak, LeakKind};
+use clap::Args;
+use serde::Serialize;
+use std::io::Write;
+use tabled::Tabled;
+
+/// Check the runtime's home for problems
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Look for disks, sockets, logs and database rows left by removed boxes
+    ///
+    /// The only check so far, and run when no check is given.
+    #[arg(long)]
+    pub leaks: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Tabled, Serialize)]
+struct LeakPresenter {
+    #[tabled(rename = "BOX")]
+    #[serde(rename = "Box")]
+    box_id: String,
+
+    #[tabled(rename = "KIND")]
+    #[serde(rename = "Kind")]
+    kind: String,
+
+    #[tabled(rename = "LOCATION")]
+    #[serde(rename = "Location")]
+    location: String,
+}
+
+impl From<CleanupLeak> for LeakPresenter {
+    fn from(leak: CleanupLeak) -> Self {
+        let kind = match leak.kind {
+            LeakKind::Socket => "socket",
+            LeakKind::Disk => "disk",
+            LeakKind::Log => "log",
+            LeakKind::File => "file",
+            LeakKind::Directory => "directory",
+            LeakKind::DbRow => "db row",
+        };
+        Self {
+            box_id: leak.box_id,
+            kind: kind.to_string(),
+            location: leak.location,
+        }
+    }
+}
+
+pub async fn execute(args: DoctorArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = OutputFormat::from_str(&args.format)?;
+    let rt = global.create_runtime()?;
+
+    let leaks: Vec<LeakPresenter> = rt
+        .find_leaks()
+        .await?
+        .into_iter()
+        .map(LeakPresenter::from)
+        .collect();
+
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &leaks,
+        format,
+        |writer, data| {
+            if data.is_empty() {
+                writeln!(writer, "No leaks found")?;
+            } else {
+                writeln!(writer, "{}", formatter::create_table(data))?;
+            }
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
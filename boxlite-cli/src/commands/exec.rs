@@ -1,18 +1,37 @@
 use crate::cli::{GlobalFlags, ProcessFlags};
+use crate::output::{self, outln};
 use crate::terminal::StreamManager;
-use crate::util::to_shell_exit_code;
-use boxlite::{BoxCommand, BoxliteRuntime, LiteBox};
+use crate::util::{names, to_shell_exit_code};
+use boxlite::{BoxCommand, BoxliteError, BoxliteResult, BoxliteRuntime, ExecLogOptions, LiteBox};
 use clap::Args;
+use std::future::Future;
+use std::time::Duration;
+
+/// Cap of the delay between retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
 
 #[derive(Args, Debug)]
 pub struct ExecArgs {
     #[command(flatten)]
     pub process: ProcessFlags,
 
-    /// Run command in the background (detached mode)
+    /// Run command in the background and print its execution ID
     #[arg(short = 'd', long)]
     pub detach: bool,
 
+    /// Record output to log files in the box directory (see `boxlite exec-logs`)
+    #[arg(long)]
+    pub record_output: bool,
+
+    /// Retry starting the command up to N times after transient failures
+    /// (guest agent not answering yet)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retry: u32,
+
+    /// Delay before the first retry in milliseconds, doubled every retry
+    #[arg(long, value_name = "MS", default_value_t = 500, requires = "retry")]
+    pub retry_delay: u64,
+
     /// Box ID or name
     #[arg(index = 1, value_name = "BOX")]
     pub target_box: String,
@@ -31,25 +50,36 @@ pub async fn execute(args: ExecArgs, global: &GlobalFlags) -> anyhow::Result<()>
 struct BoxExecutor {
     args: ExecArgs,
     rt: BoxliteRuntime,
+    fuzzy_names: bool,
 }
 
 impl BoxExecutor {
     fn new(args: ExecArgs, global: &GlobalFlags) -> anyhow::Result<Self> {
         let rt = global.create_runtime()?;
-        Ok(Self { args, rt })
+        Ok(Self {
+            args,
+            rt,
+            fuzzy_names: global.fuzzy_names,
+        })
     }
 
     async fn execute(&mut self) -> anyhow::Result<()> {
         self.args.process.validate(self.args.detach)?;
         let litebox = self.get_box().await?;
-        let cmd = self.prepare_command();
-        let mut execution = litebox.exec(cmd).await?;
+        let cmd = self.prepare_command()?;
 
-        // Detach mode: Exit immediately without waiting
+        // Detach mode: print the execution ID for `boxlite attach` and exit
         if self.args.detach {
+            let execution_id = self
+                .with_retry(|| litebox.exec_detached(cmd.clone()))
+                .await?;
+            outln!("{}", execution_id);
+            output::set_result(&serde_json::json!({ "execution_id": execution_id }));
             return Ok(());
         }
 
+        let mut execution = self.with_retry(|| litebox.exec(cmd.clone())).await?;
+
         // IO handle and signals
         let streamer = StreamManager::new(
             &mut execution,
@@ -58,23 +88,89 @@ impl BoxExecutor {
         );
 
         let exit_code = streamer.start().await?;
+        output::set_result(&serde_json::json!({ "exit_code": exit_code }));
 
         if exit_code != 0 {
-            std::process::exit(to_shell_exit_code(exit_code));
+            output::exit(to_shell_exit_code(exit_code));
         }
 
         Ok(())
     }
 
+    /// Run `start` until it succeeds, fails permanently, or `--retry` runs
+    /// out. Only starting the command is retried, never the command itself.
+    async fn with_retry<T, F, Fut>(&self, mut start: F) -> BoxliteResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = BoxliteResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match start().await {
+                Err(e) if attempt < self.args.retry && is_transient(&e) => {
+                    let delay = retry_delay(self.args.retry_delay, attempt);
+                    attempt += 1;
+                    eprintln!(
+                        "Exec failed: {}; retry {}/{} in {:?}",
+                        e, attempt, self.args.retry, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
     async fn get_box(&self) -> anyhow::Result<LiteBox> {
-        self.rt
-            .get(&self.args.target_box)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("No such box: {}", self.args.target_box))
+        names::require_box(&self.rt, &self.args.target_box, self.fuzzy_names).await
     }
 
-    fn prepare_command(&self) -> BoxCommand {
-        let cmd = BoxCommand::new(&self.args.command[0]).args(&self.args.command[1..]);
+    fn prepare_command(&self) -> anyhow::Result<BoxCommand> {
+        let mut cmd = BoxCommand::new(&self.args.command[0]).args(&self.args.command[1..]);
+        if self.args.record_output {
+            cmd = cmd.record_output(ExecLogOptions::default());
+        }
         self.args.process.configure_command(cmd)
     }
 }
+
+/// Failures a box that is still coming up can give before the command is
+/// sent to the guest, as opposed to a bad command or a missing box.
+///
+/// An RPC error may come after the guest spawned the process, so retrying
+/// it could run the command twice.
+fn is_transient(e: &BoxliteError) -> bool {
+    matches!(e, BoxliteError::RpcTransport(_) | BoxliteError::Portal(_))
+}
+
+/// Delay before retry `attempt` (from 0): `first_ms` doubled per attempt.
+fn retry_delay(first_ms: u64, attempt: u32) -> Duration {
+    Duration::from_millis(first_ms.saturating_mul(1 << attempt.min(16))).min(MAX_RETRY_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_cap() {
+        assert_eq!(retry_delay(500, 0), Duration::from_millis(500));
+        assert_eq!(retry_delay(500, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(500, 3), Duration::from_secs(4));
+        assert_eq!(retry_delay(500, 10), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u64::MAX, 40), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        assert!(is_transient(&BoxliteError::RpcTransport("refused".into())));
+        assert!(!is_transient(&BoxliteError::Rpc("stream reset".into())));
+        assert!(!is_transient(&BoxliteError::InvalidState(
+            "Container exited".into()
+        )));
+        assert!(!is_transient(&BoxliteError::NotFound("box".into())));
+        assert!(!is_transient(&BoxliteError::Execution(
+            "no such file".into()
+        )));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::cli::GlobalFlags;
+use crate::output::{self, outln};
+use crate::util::names;
+use clap::Args;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct ExecLogsArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target_box: String,
+
+    /// Execution ID of a command run with `exec --record-output`
+    #[arg(index = 2, value_name = "EXECUTION_ID")]
+    pub execution_id: String,
+
+    /// Print the log file paths instead of their contents
+    #[arg(long)]
+    pub paths: bool,
+}
+
+/// Print the recorded stdout/stderr of an execution.
+pub async fn execute(args: ExecLogsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let litebox = names::require_box(&rt, &args.target_box, global.fuzzy_names).await?;
+
+    let logs = litebox.execution_logs(&args.execution_id)?;
+
+    if args.paths {
+        for path in logs.stdout.iter().chain(&logs.stderr) {
+            outln!("{}", path.display());
+        }
+        output::set_result(&serde_json::json!({
+            "stdout": logs.stdout,
+            "stderr": logs.stderr,
+        }));
+        return Ok(());
+    }
+
+    if output::is_json() {
+        let read = |files: &[PathBuf]| -> anyhow::Result<String> {
+            let mut buf = Vec::new();
+            copy_files(files, &mut buf)?;
+            Ok(String::from_utf8_lossy(&buf).into_owned())
+        };
+        output::set_result(&serde_json::json!({
+            "stdout": read(&logs.stdout)?,
+            "stderr": read(&logs.stderr)?,
+        }));
+        return Ok(());
+    }
+
+    copy_files(&logs.stdout, &mut std::io::stdout().lock())?;
+    copy_files(&logs.stderr, &mut std::io::stderr().lock())?;
+    Ok(())
+}
+
+fn copy_files(files: &[PathBuf], out: &mut impl Write) -> anyhow::Result<()> {
+    for path in files {
+        let mut file = boxlite::ExecutionLogs::open(path)?;
+        std::io::copy(&mut file, out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
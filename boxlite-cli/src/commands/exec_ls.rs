@@ -0,0 +1,96 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use crate::util::names;
+use boxlite::ExecutionInfo;
+use clap::Args;
+use serde::Serialize;
+use std::io::Write;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct ExecLsArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target_box: String,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Tabled, Serialize)]
+struct ExecutionPresenter {
+    #[tabled(rename = "EXECUTION ID")]
+    #[serde(rename = "ID")]
+    id: String,
+
+    #[tabled(rename = "PID")]
+    #[serde(rename = "Pid")]
+    pid: u32,
+
+    #[tabled(rename = "COMMAND")]
+    #[serde(rename = "Command")]
+    command: String,
+
+    #[tabled(rename = "STATUS")]
+    #[serde(rename = "Status")]
+    status: String,
+
+    #[tabled(rename = "STARTED")]
+    #[serde(rename = "StartedAt")]
+    started: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "ExitCode")]
+    exit_code: Option<i32>,
+
+    #[tabled(skip)]
+    #[serde(rename = "ExitedAt")]
+    exited_at: Option<String>,
+}
+
+impl From<ExecutionInfo> for ExecutionPresenter {
+    fn from(info: ExecutionInfo) -> Self {
+        let status = match info.exit_code {
+            None => "running".to_string(),
+            Some(code) => format!("exited ({})", code),
+        };
+        Self {
+            id: info.id,
+            pid: info.pid,
+            command: std::iter::once(info.program)
+                .chain(info.args)
+                .collect::<Vec<_>>()
+                .join(" "),
+            status,
+            started: formatter::format_time(&info.started_at),
+            exit_code: info.exit_code,
+            exited_at: info.exited_at.map(|t| formatter::format_time(&t)),
+        }
+    }
+}
+
+/// List the executions of a box, from any process, running or recently exited.
+pub async fn execute(args: ExecLsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = OutputFormat::from_str(&args.format)?;
+    let rt = global.create_runtime()?;
+    let litebox = names::require_box(&rt, &args.target_box, global.fuzzy_names).await?;
+
+    let presenters: Vec<ExecutionPresenter> = litebox
+        .executions()
+        .await?
+        .into_iter()
+        .map(ExecutionPresenter::from)
+        .collect();
+
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
@@ -0,0 +1,353 @@
+use boxlite::ImagePrefetchStatus;
+use clap::{Args, Subcommand};
+use tabled::Tabled;
+
+use crate::cli::GlobalFlags;
+use crate::formatter;
+use crate::output::{self, outln};
+use crate::util::names;
+
+/// Manage images
+#[derive(Args, Debug)]
+pub struct ImageArgs {
+    #[command(subcommand)]
+    pub command: ImageCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImageCommand {
+    /// Pull images and prepare their root filesystems ahead of time
+    Prewarm(PrewarmArgs),
+
+    /// Keep frequently used images fresh in the background
+    Prefetch(PrefetchArgs),
+
+    /// Protect cached images from pruning
+    Pin(PinArgs),
+
+    /// Allow pinned images to be pruned again
+    Unpin(PinArgs),
+
+    /// Create a tag TARGET that refers to SOURCE
+    Tag(TagArgs),
+
+    /// Remove one or more images
+    #[command(visible_alias = "rmi")]
+    Rm(RmiArgs),
+
+    /// Show the files that differ between two images, or an image and a box
+    Diff(DiffArgs),
+
+    /// Show which layer of an image each file comes from
+    Provenance(ProvenanceArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PrewarmArgs {
+    /// Images to prewarm
+    #[arg(required = true, num_args = 1..)]
+    pub images: Vec<String>,
+
+    /// Also pin the images after prewarming
+    #[arg(long)]
+    pub pin: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PrefetchArgs {
+    /// Refresh frequently used images while runtimes are idle
+    #[arg(long, conflicts_with = "disable")]
+    pub enable: bool,
+
+    /// Stop refreshing images in the background
+    #[arg(long)]
+    pub disable: bool,
+
+    /// Refresh the frequently used images now
+    #[arg(long)]
+    pub now: bool,
+
+    /// Show whether prefetch is enabled, its last run and the images it refreshes
+    /// (default without other flags)
+    #[arg(long)]
+    pub status: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PinArgs {
+    /// Images to pin or unpin
+    #[arg(required = true, num_args = 1..)]
+    pub images: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TagArgs {
+    /// Cached image to tag
+    pub source: String,
+
+    /// New reference for the image
+    pub target: String,
+}
+
+#[derive(Args, Debug)]
+pub struct RmiArgs {
+    /// Images to remove
+    #[arg(required = true, num_args = 1..)]
+    pub images: Vec<String>,
+
+    /// Remove images even if they are pinned or used by boxes
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Cached image to compare from
+    #[arg(required_unless_present = "target")]
+    pub image: Option<String>,
+
+    /// Cached image to compare to
+    #[arg(required_unless_present = "target", conflicts_with = "target")]
+    pub other: Option<String>,
+
+    /// Compare the rootfs of a running box to IMAGE (default: the box's image)
+    #[arg(long = "box", value_name = "BOX")]
+    pub target: Option<String>,
+
+    /// Also show the digest of the layer each changed file comes from
+    #[arg(long)]
+    pub layers: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ProvenanceArgs {
+    /// Cached image to look in
+    pub image: String,
+
+    /// Files to find, as paths in the image
+    #[arg(required = true, num_args = 1..)]
+    pub paths: Vec<String>,
+}
+
+pub async fn execute(args: ImageArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        ImageCommand::Prewarm(args) => prewarm(args, global).await,
+        ImageCommand::Prefetch(args) => prefetch(args, global).await,
+        ImageCommand::Pin(args) => set_pinned(args, true, global).await,
+        ImageCommand::Unpin(args) => set_pinned(args, false, global).await,
+        ImageCommand::Tag(args) => tag(args, global).await,
+        ImageCommand::Rm(args) => remove(args, global).await,
+        ImageCommand::Diff(args) => diff(args, global).await,
+        ImageCommand::Provenance(args) => provenance(args, global).await,
+    }
+}
+
+async fn diff(args: DiffArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let diff = match (&args.target, &args.image, &args.other) {
+        (Some(target), image, _) => {
+            let litebox = names::require_box(&runtime, target, global.fuzzy_names).await?;
+            litebox.rootfs_diff(image.as_deref()).await?
+        }
+        (None, Some(image), Some(other)) => runtime.diff_images(image, other).await?,
+        _ => anyhow::bail!("expected two images, or an image and --box"),
+    };
+
+    // One list sorted by path, like `docker diff`
+    let mut changes: Vec<(char, &str)> = diff
+        .added
+        .iter()
+        .map(|path| ('A', path.as_str()))
+        .chain(diff.modified.iter().map(|path| ('M', path.as_str())))
+        .chain(diff.removed.iter().map(|path| ('D', path.as_str())))
+        .collect();
+    changes.sort_by_key(|&(_, path)| path);
+    for (kind, path) in changes {
+        match diff.provenance.get(path).filter(|_| args.layers) {
+            Some(layer) => outln!("{} /{}  {}", kind, path, layer),
+            None => outln!("{} /{}", kind, path),
+        }
+    }
+
+    output::set_result(&diff);
+    Ok(())
+}
+
+async fn provenance(args: ProvenanceArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let layers = runtime.file_provenance(&args.image, &args.paths).await?;
+
+    let mut active_error = false;
+    for path in &args.paths {
+        match layers.get(path) {
+            Some(layer) => outln!("{}  {}", layer, path),
+            None => {
+                eprintln!("Error: no file '{}' in image '{}'", path, args.image);
+                active_error = true;
+            }
+        }
+    }
+
+    output::set_result(&layers);
+
+    if active_error {
+        anyhow::bail!("Some files were not found");
+    }
+    Ok(())
+}
+
+pub async fn tag(args: TagArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let target = runtime.tag_image(&args.source, &args.target).await?;
+    outln!("Tagged: {}", target);
+    output::set_result(&serde_json::json!({
+        "source": args.source,
+        "target": target,
+    }));
+    Ok(())
+}
+
+pub async fn remove(args: RmiArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+
+    let mut active_error = false;
+    let mut untagged = Vec::new();
+    for image in args.images {
+        match runtime.remove_image(&image, args.force).await {
+            Ok(reference) => {
+                outln!("Untagged: {}", reference);
+                untagged.push(reference);
+            }
+            Err(e) => {
+                eprintln!("Error removing image '{}': {}", image, e);
+                active_error = true;
+            }
+        }
+    }
+
+    output::set_result(&untagged);
+
+    if active_error {
+        anyhow::bail!("Some images could not be removed");
+    }
+    Ok(())
+}
+
+async fn prewarm(args: PrewarmArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+
+    runtime.prewarm(&args.images).await?;
+
+    for image in &args.images {
+        if args.pin {
+            runtime.pin_image(image).await?;
+        }
+        outln!("{}", image);
+    }
+    output::set_result(&args.images);
+
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct PrefetchCandidatePresenter {
+    #[tabled(rename = "IMAGE")]
+    reference: String,
+    #[tabled(rename = "USES")]
+    use_count: u64,
+    #[tabled(rename = "LAST USED")]
+    last_used: String,
+    #[tabled(rename = "PULL POLICY")]
+    pull_policy: String,
+}
+
+async fn prefetch(args: PrefetchArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+
+    if args.enable || args.disable {
+        runtime.set_image_prefetch(args.enable)?;
+        outln!(
+            "Image prefetch {}",
+            if args.enable { "enabled" } else { "disabled" }
+        );
+    }
+    if args.now {
+        let refreshed = runtime.prefetch_images().await?;
+        outln!("Refreshed {} image(s)", refreshed);
+    }
+
+    let status = runtime.image_prefetch_status()?;
+    if args.status || !(args.enable || args.disable || args.now) {
+        print_prefetch_status(&status);
+    }
+    output::set_result(&status);
+    Ok(())
+}
+
+fn print_prefetch_status(status: &ImagePrefetchStatus) {
+    outln!(
+        "Prefetch: {}",
+        if status.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    match status.last_run_at {
+        Some(at) => outln!(
+            "Last run: {} ({} refreshed)",
+            formatter::format_time(&at),
+            status.last_refreshed
+        ),
+        None => outln!("Last run: never"),
+    }
+    if let Some(error) = &status.last_error {
+        outln!("Last error: {}", error);
+    }
+    if status.candidates.is_empty() {
+        outln!("No images used recently");
+        return;
+    }
+    let rows = status
+        .candidates
+        .iter()
+        .map(|candidate| PrefetchCandidatePresenter {
+            reference: candidate.reference.clone(),
+            use_count: candidate.use_count,
+            last_used: candidate
+                .last_used_at
+                .as_ref()
+                .map(formatter::format_time)
+                .unwrap_or_default(),
+            pull_policy: candidate.pull_policy.as_str().to_string(),
+        });
+    outln!("{}", formatter::create_table(rows));
+}
+
+async fn set_pinned(args: PinArgs, pinned: bool, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+
+    let mut active_error = false;
+    let mut updated = Vec::new();
+    for image in args.images {
+        let result = if pinned {
+            runtime.pin_image(&image).await
+        } else {
+            runtime.unpin_image(&image).await
+        };
+
+        if let Err(e) = result {
+            eprintln!("Error updating image '{}': {}", image, e);
+            active_error = true;
+        } else {
+            outln!("{}", image);
+            updated.push(image);
+        }
+    }
+    output::set_result(&updated);
+
+    if active_error {
+        anyhow::bail!("Some images could not be updated");
+    }
+    Ok(())
+}
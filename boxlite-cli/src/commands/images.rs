@@ -1,9 +1,12 @@
 use crate::cli::GlobalFlags;
-use crate::formatter::{self, OutputFormat};
+use crate::formatter::{self, GtmplWithJson, OutputFormat, value_from_serde_json};
+use crate::output::{self, outln};
 use boxlite::runtime::types::ImageInfo;
 use clap::Args;
 use serde::Serialize;
 use tabled::Tabled;
+use tabled::settings::Remove;
+use tabled::settings::location::ByColumnName;
 
 /// List images
 #[derive(Args, Debug)]
@@ -16,12 +19,52 @@ pub struct ImagesArgs {
     #[arg(short, long)]
     pub quiet: bool,
 
-    /// Output format (table, json, yaml)
+    /// Show full image digests
+    #[arg(long)]
+    pub digests: bool,
+
+    /// Filter output (e.g. 'dangling=true')
+    #[arg(short, long = "filter", value_name = "FILTER")]
+    pub filters: Vec<String>,
+
+    /// Output format: table, json, yaml, or a Go template (e.g. '{{.Repository}}:{{.Tag}}')
     #[arg(long, default_value = "table")]
     pub format: String,
 }
 
-/// Presenter for image output, used by both table and JSON/YAML formats.
+/// Parsed `--filter` flags.
+#[derive(Debug, Default, PartialEq)]
+struct ImageFilter {
+    /// Only images (not) used by any box and not pinned
+    dangling: Option<bool>,
+}
+
+impl ImageFilter {
+    fn parse(filters: &[String]) -> anyhow::Result<Self> {
+        let mut filter = Self::default();
+        for f in filters {
+            let (key, value) = f
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("invalid filter '{}', expected key=value", f))?;
+            match key {
+                "dangling" => {
+                    let dangling = value.parse::<bool>().map_err(|_| {
+                        anyhow::anyhow!("invalid filter '{}', dangling must be true or false", f)
+                    })?;
+                    filter.dangling = Some(dangling);
+                }
+                _ => anyhow::bail!("unsupported filter '{}'", key),
+            }
+        }
+        Ok(filter)
+    }
+
+    fn matches(&self, info: &ImageInfo) -> bool {
+        self.dangling.is_none_or(|d| info.is_dangling() == d)
+    }
+}
+
+/// Presenter for image output, used by table, JSON/YAML and template formats.
 #[derive(Tabled, Serialize)]
 struct ImagePresenter {
     #[tabled(rename = "REPOSITORY")]
@@ -30,57 +73,120 @@ struct ImagePresenter {
     #[tabled(rename = "TAG")]
     #[serde(rename = "Tag")]
     tag: String,
+    #[tabled(rename = "DIGEST")]
+    #[serde(rename = "Digest")]
+    digest: String,
     #[tabled(rename = "IMAGE ID")]
     #[serde(rename = "ID")]
     id: String,
     #[tabled(rename = "CREATED")]
     #[serde(rename = "CreatedAt")]
     created: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "Built")]
     #[tabled(skip)]
-    size: Option<String>,
+    built: Option<String>,
+    #[tabled(rename = "SIZE")]
+    #[serde(rename = "Size")]
+    size: String,
+    #[serde(rename = "DiskSize")]
+    #[tabled(skip)]
+    disk_size: String,
+    #[serde(rename = "InUseBy")]
+    #[tabled(skip)]
+    in_use_by: usize,
+    #[serde(rename = "Pinned")]
+    #[tabled(skip)]
+    pinned: bool,
 }
 
 impl From<&ImageInfo> for ImagePresenter {
     fn from(info: &ImageInfo) -> Self {
+        let size = |s: Option<boxlite::runtime::types::Bytes>| {
+            s.map(|s| formatter::format_size(s.as_bytes()))
+                .unwrap_or_else(|| "N/A".to_string())
+        };
         Self {
             repository: info.repository.clone(),
             tag: info.tag.clone(),
+            digest: info.id.clone(),
             id: get_short_id(&info.id),
             created: formatter::format_time(&info.cached_at),
-            size: info.size.map(|s| s.to_string()),
+            built: info.created.as_ref().map(formatter::format_time),
+            size: size(info.size),
+            disk_size: size(info.disk_size),
+            in_use_by: info.in_use_by,
+            pinned: info.pinned,
         }
     }
 }
 
 pub async fn execute(args: ImagesArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let filter = ImageFilter::parse(&args.filters)?;
     let rt = global.create_runtime()?;
-    let images = rt.list_images().await?;
+    let images: Vec<ImageInfo> = rt
+        .list_images()
+        .await?
+        .into_iter()
+        .filter(|info| filter.matches(info))
+        .collect();
 
     if args.quiet {
-        for info in images {
-            println!("{}", info.id);
+        let ids: Vec<String> = images.into_iter().map(|info| info.id).collect();
+        for id in &ids {
+            outln!("{}", id);
         }
+        output::set_result(&ids);
         return Ok(());
     }
 
     let presenters: Vec<ImagePresenter> = images.iter().map(Into::into).collect();
-    let format = OutputFormat::from_str(&args.format)?;
-    formatter::print_output(
+    write_images(
         &mut std::io::stdout().lock(),
         &presenters,
-        format,
-        |writer, data| {
-            print_images(writer, data)?;
-            Ok(())
-        },
-    )?;
+        &args.format,
+        args.digests,
+    )
+}
 
-    Ok(())
+fn write_images(
+    writer: &mut impl std::io::Write,
+    presenters: &[ImagePresenter],
+    format_str: &str,
+    digests: bool,
+) -> anyhow::Result<()> {
+    if format_str.contains("{{") && format_str.contains("}}") {
+        let gtmpl =
+            GtmplWithJson::parse(format_str).map_err(|e| anyhow::anyhow!("template: {}", e))?;
+        let mut lines = Vec::new();
+        for p in presenters {
+            let json = serde_json::to_value(p)?;
+            lines.push(gtmpl.render(value_from_serde_json(&json))?);
+        }
+        if output::is_json() {
+            output::set_result(&lines);
+        } else {
+            for line in lines {
+                writeln!(writer, "{}", line)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let format = OutputFormat::from_str(format_str)?;
+    formatter::print_output(writer, &presenters, format, |writer, data| {
+        print_images(writer, data, digests)
+    })
 }
 
-fn print_images(writer: &mut impl std::io::Write, images: &[ImagePresenter]) -> anyhow::Result<()> {
-    let table = formatter::create_table(images).to_string();
+fn print_images(
+    writer: &mut impl std::io::Write,
+    images: &[ImagePresenter],
+    digests: bool,
+) -> anyhow::Result<()> {
+    let mut table = formatter::create_table(images);
+    if !digests {
+        table.with(Remove::column(ByColumnName::new("DIGEST")));
+    }
     writeln!(writer, "{}", table)?;
     Ok(())
 }
@@ -105,4 +211,69 @@ mod tests {
         assert_eq!(get_short_id("short"), "short");
         assert_eq!(get_short_id("sha256:short"), "short");
     }
+
+    fn image(pinned: bool, in_use_by: usize) -> ImageInfo {
+        ImageInfo {
+            reference: "docker.io/library/alpine:latest".to_string(),
+            repository: "docker.io/library/alpine".to_string(),
+            tag: "latest".to_string(),
+            id: "sha256:1234567890abcdef1234".to_string(),
+            cached_at: chrono::Utc::now(),
+            created: None,
+            pinned,
+            size: Some(boxlite::runtime::types::Bytes::from_bytes(3_500_000)),
+            disk_size: None,
+            in_use_by,
+        }
+    }
+
+    #[test]
+    fn test_filter_dangling() {
+        let filter = ImageFilter::parse(&["dangling=true".to_string()]).unwrap();
+        assert!(filter.matches(&image(false, 0)));
+        assert!(!filter.matches(&image(true, 0)));
+        assert!(!filter.matches(&image(false, 2)));
+
+        let filter = ImageFilter::parse(&["dangling=false".to_string()]).unwrap();
+        assert!(filter.matches(&image(false, 1)));
+        assert!(ImageFilter::parse(&[]).unwrap().matches(&image(false, 0)));
+    }
+
+    #[test]
+    fn test_filter_rejects_invalid() {
+        assert!(ImageFilter::parse(&["dangling".to_string()]).is_err());
+        assert!(ImageFilter::parse(&["dangling=maybe".to_string()]).is_err());
+        assert!(ImageFilter::parse(&["label=x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_write_images_template_and_digests() {
+        let presenters = vec![ImagePresenter::from(&image(false, 1))];
+
+        let mut out = Vec::new();
+        write_images(
+            &mut out,
+            &presenters,
+            "{{.Repository}}:{{.Tag}} {{.Size}} {{.InUseBy}}",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "docker.io/library/alpine:latest 3.5MB 1\n"
+        );
+
+        let mut out = Vec::new();
+        write_images(&mut out, &presenters, "table", false).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.contains("SIZE") && !table.contains("DIGEST"));
+
+        let mut out = Vec::new();
+        write_images(&mut out, &presenters, "table", true).unwrap();
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .contains("sha256:1234567890abcdef1234")
+        );
+    }
 }
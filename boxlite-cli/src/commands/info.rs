@@ -0,0 +1,97 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use clap::Args;
+use serde::Serialize;
+use std::io::Write;
+
+/// Display runtime information
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Serialize)]
+struct InfoPresenter {
+    #[serde(rename = "Version")]
+    version: String,
+    #[serde(rename = "Boxes")]
+    boxes: usize,
+    #[serde(rename = "Running")]
+    running: usize,
+    #[serde(rename = "ReservedCpus")]
+    reserved_cpus: u64,
+    #[serde(rename = "CpuCapacity")]
+    cpu_capacity: Option<u64>,
+    #[serde(rename = "ReservedMemoryMiB")]
+    reserved_memory_mib: u64,
+    #[serde(rename = "MemoryCapacityMiB")]
+    memory_capacity_mib: Option<u64>,
+}
+
+pub async fn execute(args: InfoArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let boxes = rt.list_info().await?;
+    let metrics = rt.metrics().await;
+
+    let info = InfoPresenter {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        boxes: boxes.len(),
+        running: boxes.iter().filter(|b| b.status.is_running()).count(),
+        reserved_cpus: metrics.reserved_cpus(),
+        cpu_capacity: metrics.cpu_capacity(),
+        reserved_memory_mib: metrics.reserved_memory_mib(),
+        memory_capacity_mib: metrics.memory_capacity_mib(),
+    };
+
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &info,
+        format,
+        |writer, info| {
+            writeln!(writer, "Version: {}", info.version)?;
+            writeln!(writer, "Boxes: {} (running: {})", info.boxes, info.running)?;
+            writeln!(
+                writer,
+                "CPUs: {}",
+                reservation(info.reserved_cpus, info.cpu_capacity, "")
+            )?;
+            writeln!(
+                writer,
+                "Memory: {}",
+                reservation(info.reserved_memory_mib, info.memory_capacity_mib, " MiB")
+            )?;
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// "2 reserved of 8 (25%)", or "2 reserved (no limit)" without a capacity.
+fn reservation(reserved: u64, capacity: Option<u64>, unit: &str) -> String {
+    match capacity {
+        Some(capacity) if capacity > 0 => format!(
+            "{reserved}{unit} reserved of {capacity}{unit} ({:.0}%)",
+            reserved as f64 * 100.0 / capacity as f64
+        ),
+        _ => format!("{reserved}{unit} reserved (no limit)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservation() {
+        assert_eq!(reservation(2, Some(8), ""), "2 reserved of 8 (25%)");
+        assert_eq!(
+            reservation(3072, Some(2048), " MiB"),
+            "3072 MiB reserved of 2048 MiB (150%)"
+        );
+        assert_eq!(reservation(1, None, ""), "1 reserved (no limit)");
+    }
+}
@@ -2,9 +2,13 @@
 
 use crate::cli::GlobalFlags;
 use crate::formatter::{self, GtmplWithJson, OutputFormat, value_from_serde_json};
-use boxlite::{BoxInfo, BoxStateInfo};
+use crate::output::{self, outln};
+use boxlite::net::constants::{GATEWAY_IP, GUEST_IP, GUEST_MAC_STRING, HOST_IP, HOST_NAME};
+use boxlite::runtime::options::{NetworkSpec, PortProtocol};
+use boxlite::{BoxInfo, BoxOptions, BoxStateInfo, BoxTimings, GuestAgentInfo, StatusTransition};
 use clap::Args;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 
 /// Inspect one or more boxes
 #[derive(Args, Debug)]
@@ -20,6 +24,14 @@ pub struct InspectArgs {
     /// Output format: json, yaml, or a Go template (e.g. '{{.State}}', '{{.State.Status}}')
     #[arg(short, long, default_value = "json")]
     pub format: String,
+
+    /// Include the guest agent's last N log lines (default 100) for running boxes
+    #[arg(long, value_name = "LINES", num_args = 0..=1, default_missing_value = "100")]
+    pub guest_logs: Option<usize>,
+
+    /// Add SizeRw and SizeRootFs, scanned from the box's disk (slower)
+    #[arg(short, long)]
+    pub size: bool,
 }
 
 /// Single view for inspect: JSON/YAML
@@ -37,10 +49,138 @@ struct InspectPresenter {
     status: String,
     #[serde(rename = "State")]
     state: InspectStatePresenter,
+    /// Last status transitions, oldest first
+    #[serde(rename = "StateHistory")]
+    state_history: Vec<InspectTransitionPresenter>,
     #[serde(rename = "Cpus")]
     cpus: u8,
     #[serde(rename = "Memory")]
     memory: u64,
+    #[serde(rename = "DependsOn")]
+    depends_on: Vec<String>,
+    #[serde(rename = "Dependents")]
+    dependents: Vec<String>,
+    #[serde(rename = "Timings")]
+    timings: Option<InspectTimingsPresenter>,
+    #[serde(rename = "GuestAgent")]
+    guest_agent: Option<InspectGuestAgentPresenter>,
+    /// Non-fatal issues found when the box was created
+    #[serde(rename = "Warnings")]
+    warnings: Vec<String>,
+    #[serde(rename = "Config")]
+    config: InspectConfigPresenter,
+    #[serde(rename = "HostConfig")]
+    host_config: InspectHostConfigPresenter,
+    #[serde(rename = "Mounts")]
+    mounts: Vec<InspectMountPresenter>,
+    #[serde(rename = "NetworkSettings")]
+    network_settings: InspectNetworkSettingsPresenter,
+    #[serde(rename = "GuestLogs", skip_serializing_if = "Option::is_none")]
+    guest_logs: Option<Vec<String>>,
+    /// Bytes written on top of the image (with --size)
+    #[serde(rename = "SizeRw", skip_serializing_if = "Option::is_none")]
+    size_rw: Option<u64>,
+    /// Bytes in use in the rootfs, image included (with --size)
+    #[serde(rename = "SizeRootFs", skip_serializing_if = "Option::is_none")]
+    size_root_fs: Option<u64>,
+}
+
+/// Docker-style `Config`. `Cmd` and `Entrypoint` are the box's overrides;
+/// null means the image's own value is used.
+#[derive(Debug, Default, Serialize)]
+struct InspectConfigPresenter {
+    #[serde(rename = "Image")]
+    image: String,
+    #[serde(rename = "Env")]
+    env: Vec<String>,
+    #[serde(rename = "Cmd")]
+    cmd: Option<Vec<String>>,
+    #[serde(rename = "Entrypoint")]
+    entrypoint: Option<Vec<String>>,
+    #[serde(rename = "WorkingDir")]
+    working_dir: String,
+    #[serde(rename = "User")]
+    user: String,
+    #[serde(rename = "Labels")]
+    labels: HashMap<String, String>,
+}
+
+/// Docker-style `HostConfig`.
+#[derive(Debug, Default, Serialize)]
+struct InspectHostConfigPresenter {
+    /// Memory limit in bytes
+    #[serde(rename = "Memory")]
+    memory: u64,
+    #[serde(rename = "CpuCount")]
+    cpu_count: u64,
+    #[serde(rename = "Binds")]
+    binds: Vec<String>,
+    #[serde(rename = "NetworkMode")]
+    network_mode: String,
+    #[serde(rename = "PortBindings")]
+    port_bindings: BTreeMap<String, Vec<InspectPortBindingPresenter>>,
+    #[serde(rename = "AutoRemove")]
+    auto_remove: bool,
+    #[serde(rename = "Ulimits")]
+    ulimits: Vec<InspectUlimitPresenter>,
+}
+
+/// Docker-style entry of `HostConfig.Ulimits`.
+#[derive(Debug, Serialize)]
+struct InspectUlimitPresenter {
+    #[serde(rename = "Name")]
+    name: String,
+    /// Soft limit, -1 if unlimited
+    #[serde(rename = "Soft")]
+    soft: i64,
+    /// Hard limit, -1 if unlimited
+    #[serde(rename = "Hard")]
+    hard: i64,
+}
+
+/// Docker-style entry of `Mounts`.
+#[derive(Debug, Serialize)]
+struct InspectMountPresenter {
+    #[serde(rename = "Type")]
+    kind: String,
+    #[serde(rename = "Source")]
+    source: String,
+    #[serde(rename = "Destination")]
+    destination: String,
+    #[serde(rename = "Driver", skip_serializing_if = "Option::is_none")]
+    driver: Option<String>,
+    #[serde(rename = "Mode")]
+    mode: String,
+    #[serde(rename = "RW")]
+    rw: bool,
+}
+
+/// Docker-style `NetworkSettings`; addresses are only set while running.
+#[derive(Debug, Default, Serialize)]
+struct InspectNetworkSettingsPresenter {
+    #[serde(rename = "IPAddress")]
+    ip_address: String,
+    #[serde(rename = "Gateway")]
+    gateway: String,
+    #[serde(rename = "MacAddress")]
+    mac_address: String,
+    /// Name the box reaches the host by, on the built-in network
+    #[serde(rename = "HostAlias")]
+    host_alias: String,
+    /// Address `HostAlias` resolves to; the host's loopback behind it
+    #[serde(rename = "HostGateway")]
+    host_gateway: String,
+    /// Keyed by "<guest port>/<protocol>", e.g. "80/tcp"
+    #[serde(rename = "Ports")]
+    ports: BTreeMap<String, Vec<InspectPortBindingPresenter>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct InspectPortBindingPresenter {
+    #[serde(rename = "HostIp")]
+    host_ip: String,
+    #[serde(rename = "HostPort")]
+    host_port: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,6 +193,99 @@ struct InspectStatePresenter {
     pid: u32,
 }
 
+/// A status transition of the box.
+#[derive(Debug, Serialize)]
+struct InspectTransitionPresenter {
+    #[serde(rename = "At")]
+    at: String,
+    #[serde(rename = "From")]
+    from: String,
+    #[serde(rename = "To")]
+    to: String,
+    #[serde(rename = "Reason")]
+    reason: String,
+}
+
+impl From<&StatusTransition> for InspectTransitionPresenter {
+    fn from(t: &StatusTransition) -> Self {
+        Self {
+            at: t.at.to_rfc3339(),
+            from: t.from.as_str().to_string(),
+            to: t.to.as_str().to_string(),
+            reason: t.reason.clone(),
+        }
+    }
+}
+
+/// Stage durations of the last start, in milliseconds.
+#[derive(Debug, Serialize)]
+struct InspectTimingsPresenter {
+    #[serde(rename = "FilesystemMs")]
+    filesystem_ms: Option<u64>,
+    #[serde(rename = "RootfsMs")]
+    rootfs_ms: Option<u64>,
+    #[serde(rename = "GuestRootfsMs")]
+    guest_rootfs_ms: Option<u64>,
+    #[serde(rename = "ConfigMs")]
+    config_ms: Option<u64>,
+    #[serde(rename = "SpawnMs")]
+    spawn_ms: Option<u64>,
+    #[serde(rename = "GuestMs")]
+    guest_ms: Option<u64>,
+    #[serde(rename = "TotalMs")]
+    total_ms: Option<u64>,
+    #[serde(rename = "RootfsBuild", skip_serializing_if = "Option::is_none")]
+    rootfs_build: Option<InspectRootfsBuildPresenter>,
+}
+
+/// How the rootfs was built from the image layers, if it was.
+#[derive(Debug, Serialize)]
+struct InspectRootfsBuildPresenter {
+    #[serde(rename = "MaterializeMs")]
+    materialize_ms: u64,
+    #[serde(rename = "ReflinkedBytes")]
+    reflinked_bytes: u64,
+    #[serde(rename = "CopiedBytes")]
+    copied_bytes: u64,
+}
+
+impl From<&BoxTimings> for InspectTimingsPresenter {
+    fn from(t: &BoxTimings) -> Self {
+        Self {
+            filesystem_ms: t.filesystem_ms,
+            rootfs_ms: t.rootfs_ms,
+            guest_rootfs_ms: t.guest_rootfs_ms,
+            config_ms: t.config_ms,
+            spawn_ms: t.spawn_ms,
+            guest_ms: t.guest_ms,
+            total_ms: t.total_ms,
+            rootfs_build: t.rootfs_build.map(|b| InspectRootfsBuildPresenter {
+                materialize_ms: b.materialize_ms,
+                reflinked_bytes: b.reflinked_bytes,
+                copied_bytes: b.copied_bytes,
+            }),
+        }
+    }
+}
+
+/// Guest agent met at the last start.
+#[derive(Debug, Serialize)]
+struct InspectGuestAgentPresenter {
+    #[serde(rename = "Version")]
+    version: String,
+    #[serde(rename = "Capabilities")]
+    capabilities: Vec<String>,
+}
+
+impl From<&GuestAgentInfo> for InspectGuestAgentPresenter {
+    fn from(agent: &GuestAgentInfo) -> Self {
+        Self {
+            version: agent.version.clone(),
+            capabilities: agent.capabilities.clone(),
+        }
+    }
+}
+
 impl From<&BoxInfo> for InspectPresenter {
     fn from(info: &BoxInfo) -> Self {
         let state = BoxStateInfo::from(info);
@@ -67,8 +300,133 @@ impl From<&BoxInfo> for InspectPresenter {
                 running: state.running,
                 pid: state.pid.unwrap_or(0),
             },
+            state_history: Vec::new(),
             cpus: info.cpus,
             memory: info.memory_mib as u64 * 1024 * 1024,
+            depends_on: info.depends_on.clone(),
+            dependents: Vec::new(),
+            timings: info.timings.as_ref().map(Into::into),
+            guest_agent: info.guest_agent.as_ref().map(Into::into),
+            warnings: info.warnings.clone(),
+            config: InspectConfigPresenter {
+                image: info.image.clone(),
+                labels: info.labels.clone(),
+                ..Default::default()
+            },
+            host_config: InspectHostConfigPresenter {
+                memory: info.memory_mib as u64 * 1024 * 1024,
+                cpu_count: u64::from(info.cpus),
+                ..Default::default()
+            },
+            mounts: Vec::new(),
+            network_settings: InspectNetworkSettingsPresenter::default(),
+            guest_logs: None,
+            size_rw: None,
+            size_root_fs: None,
+        }
+    }
+}
+
+impl InspectPresenter {
+    /// Fill the Docker-style sections from the box's creation options.
+    fn apply_options(&mut self, options: &BoxOptions) {
+        let config = &mut self.config;
+        config.env = options
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        config.cmd = options.cmd.clone();
+        config.entrypoint = options.entrypoint.clone();
+        config.working_dir = options.working_dir.clone().unwrap_or_default();
+        config.user = options.user.clone().unwrap_or_default();
+
+        self.mounts = options
+            .volumes
+            .iter()
+            .map(|volume| InspectMountPresenter {
+                kind: if volume.driver.is_some() {
+                    "volume"
+                } else {
+                    "bind"
+                }
+                .to_string(),
+                source: volume.host_path.clone(),
+                destination: volume.guest_path.clone(),
+                driver: volume.driver.clone(),
+                mode: volume.effective_options().join(","),
+                rw: !volume.is_read_only(),
+            })
+            .collect();
+
+        let mut ports: BTreeMap<String, Vec<InspectPortBindingPresenter>> = BTreeMap::new();
+        for port in &options.ports {
+            let protocol = match port.protocol {
+                PortProtocol::Tcp => "tcp",
+                PortProtocol::Udp => "udp",
+            };
+            // Without a host port the guest port is forwarded as is
+            let host_port = port
+                .host_port
+                .filter(|&p| p != 0)
+                .unwrap_or(port.guest_port);
+            ports
+                .entry(format!("{}/{}", port.guest_port, protocol))
+                .or_default()
+                .push(InspectPortBindingPresenter {
+                    host_ip: port
+                        .host_ip
+                        .clone()
+                        .unwrap_or_else(|| "0.0.0.0".to_string()),
+                    host_port: host_port.to_string(),
+                });
+        }
+
+        let host_config = &mut self.host_config;
+        host_config.binds = options
+            .volumes
+            .iter()
+            .filter(|volume| volume.driver.is_none())
+            .map(|volume| {
+                let options = volume.effective_options();
+                if options == ["rw"] {
+                    format!("{}:{}", volume.host_path, volume.guest_path)
+                } else {
+                    format!(
+                        "{}:{}:{}",
+                        volume.host_path,
+                        volume.guest_path,
+                        options.join(",")
+                    )
+                }
+            })
+            .collect();
+        host_config.network_mode = match &options.network {
+            NetworkSpec::Isolated => "isolated".to_string(),
+            NetworkSpec::Custom(name) => name.clone(),
+        };
+        host_config.port_bindings = ports.clone();
+        host_config.auto_remove = options.auto_remove;
+        host_config.ulimits = options
+            .ulimits
+            .iter()
+            .map(|ulimit| InspectUlimitPresenter {
+                name: ulimit.name.clone(),
+                soft: i64::try_from(ulimit.soft).unwrap_or(-1),
+                hard: i64::try_from(ulimit.hard).unwrap_or(-1),
+            })
+            .collect();
+
+        let network = &mut self.network_settings;
+        network.ports = ports;
+        if options.host_loopback && matches!(options.network, NetworkSpec::Isolated) {
+            network.host_alias = HOST_NAME.to_string();
+            network.host_gateway = HOST_IP.to_string();
+        }
+        if self.state.running {
+            network.ip_address = GUEST_IP.to_string();
+            network.gateway = GATEWAY_IP.to_string();
+            network.mac_address = GUEST_MAC_STRING.to_string();
         }
     }
 }
@@ -87,11 +445,45 @@ pub async fn execute(args: InspectArgs, global: &GlobalFlags) -> anyhow::Result<
     let (infos, errs) = resolve_inspect_infos(&rt, &args).await?;
 
     if infos.is_empty() {
-        println!("[]");
+        outln!("[]");
         return Err(errs.into_iter().next().unwrap());
     }
 
-    let presenters: Vec<InspectPresenter> = infos.iter().map(InspectPresenter::from).collect();
+    let mut presenters: Vec<InspectPresenter> = infos.iter().map(InspectPresenter::from).collect();
+    for presenter in &mut presenters {
+        if let Some(handle) = rt.get(&presenter.id).await? {
+            presenter.apply_options(handle.options());
+            presenter.state_history = handle.history().iter().map(Into::into).collect();
+        }
+    }
+    let all = rt.list_info().await?;
+    for presenter in &mut presenters {
+        presenter.dependents = all
+            .iter()
+            .filter(|other| other.depends_on.contains(&presenter.id))
+            .map(|other| other.id.to_string())
+            .collect();
+    }
+    if let Some(tail) = args.guest_logs {
+        for (presenter, info) in presenters.iter_mut().zip(&infos) {
+            presenter.guest_logs = Some(fetch_guest_logs(&rt, info, tail).await);
+        }
+    }
+    if args.size {
+        for presenter in &mut presenters {
+            let Some(handle) = rt.get(&presenter.id).await? else {
+                continue;
+            };
+            match handle.rootfs_size() {
+                Ok(Some(size)) => {
+                    presenter.size_rw = Some(size.size_rw);
+                    presenter.size_root_fs = size.size_root_fs;
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Warning: failed to compute size of {}: {}", presenter.id, e),
+            }
+        }
+    }
     let mut stdout = std::io::stdout().lock();
     write_inspect_output(&presenters, &args.format, &mut stdout)?;
 
@@ -105,6 +497,26 @@ pub async fn execute(args: InspectArgs, global: &GlobalFlags) -> anyhow::Result<
     Ok(())
 }
 
+/// Fetch the guest agent's recent logs; empty (with a warning) if the box isn't running.
+async fn fetch_guest_logs(
+    rt: &boxlite::BoxliteRuntime,
+    info: &BoxInfo,
+    tail: usize,
+) -> Vec<String> {
+    if !info.status.is_running() {
+        return Vec::new();
+    }
+    let result = match rt.get(info.id.as_str()).await {
+        Ok(Some(handle)) => handle.guest_logs(Some(tail)).await,
+        Ok(None) => return Vec::new(),
+        Err(e) => Err(e),
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("Warning: failed to fetch guest logs for {}: {}", info.id, e);
+        Vec::new()
+    })
+}
+
 fn looks_like_template(s: &str) -> bool {
     s.contains("{{") && s.contains("}}")
 }
@@ -192,6 +604,7 @@ fn write_inspect_output<W: std::io::Write>(
                 let format = normalize_inspect_format(format_str);
                 let gtmpl = GtmplWithJson::parse(&format)
                     .map_err(|e| anyhow::anyhow!("template: {}", e))?;
+                let mut lines = Vec::new();
                 for p in presenters {
                     let json_val = serde_json::to_value(p)
                         .map_err(|e| anyhow::anyhow!("inspect serialization: {}", e))?;
@@ -211,7 +624,14 @@ fn write_inspect_output<W: std::io::Write>(
                         let ctx = value_from_serde_json(&json_val);
                         gtmpl.render(ctx)?
                     };
-                    writeln!(writer, "{}", out)?;
+                    lines.push(out);
+                }
+                if output::is_json() {
+                    output::set_result(&lines);
+                } else {
+                    for line in lines {
+                        writeln!(writer, "{}", line)?;
+                    }
                 }
             } else {
                 return Err(format_err);
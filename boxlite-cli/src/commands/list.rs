@@ -1,9 +1,13 @@
 use crate::cli::GlobalFlags;
 use crate::formatter::{self, OutputFormat};
-use boxlite::BoxInfo;
+use crate::output::{self, outln};
+use boxlite::{BoxFilter, BoxInfo, BoxStatus, BoxliteError, DiskUsage, RootfsSize};
+use chrono::Utc;
 use clap::Args;
 use serde::Serialize;
 use tabled::Tabled;
+use tabled::settings::Remove;
+use tabled::settings::location::ByColumnName;
 
 /// List boxes
 #[derive(Args, Debug)]
@@ -16,6 +20,16 @@ pub struct ListArgs {
     #[arg(short, long)]
     pub quiet: bool,
 
+    /// Filter output (`status=<status>` or `image=<reference>`); repeated
+    /// statuses match any of them
+    #[arg(short, long, value_name = "KEY=VALUE")]
+    pub filter: Vec<String>,
+
+    /// Display host disk usage: allocated (and virtual) size of box disks.
+    /// Also scans the disks for SizeRw and SizeRootFs, shown in JSON and YAML
+    #[arg(short, long)]
+    pub size: bool,
+
     /// Output format (table, json, yaml)
     #[arg(long, default_value = "table")]
     pub format: String,
@@ -42,6 +56,63 @@ struct BoxPresenter {
     #[tabled(rename = "NAMES")]
     #[serde(rename = "Names")]
     names: String,
+
+    #[tabled(rename = "EXPIRES IN")]
+    #[serde(skip)]
+    expires: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "ExpiresAt")]
+    expires_at: Option<String>,
+
+    #[tabled(skip)]
+    #[serde(rename = "Project")]
+    project: String,
+
+    #[tabled(rename = "SIZE")]
+    #[serde(skip)]
+    size: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "DiskAllocatedBytes", skip_serializing_if = "Option::is_none")]
+    disk_allocated_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "DiskVirtualBytes", skip_serializing_if = "Option::is_none")]
+    disk_virtual_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "SizeRw", skip_serializing_if = "Option::is_none")]
+    size_rw: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "SizeRootFs", skip_serializing_if = "Option::is_none")]
+    size_root_fs: Option<u64>,
+
+    #[tabled(rename = "HOME")]
+    #[serde(rename = "Home", skip_serializing_if = "String::is_empty")]
+    home: String,
+}
+
+impl BoxPresenter {
+    /// Add the box's disk usage, `docker ps --size` style.
+    fn with_disk_usage(mut self, usage: &DiskUsage) -> Self {
+        self.size = format!(
+            "{} (virtual {})",
+            formatter::format_size(usage.allocated_bytes()),
+            formatter::format_size(usage.virtual_bytes())
+        );
+        self.disk_allocated_bytes = Some(usage.allocated_bytes());
+        self.disk_virtual_bytes = Some(usage.virtual_bytes());
+        self
+    }
+
+    /// Add the `docker inspect --size` style rootfs sizes.
+    fn with_rootfs_size(mut self, size: Option<RootfsSize>) -> Self {
+        self.size_rw = size.map(|size| size.size_rw);
+        self.size_root_fs = size.and_then(|size| size.size_root_fs);
+        self
+    }
 }
 
 impl From<BoxInfo> for BoxPresenter {
@@ -52,34 +123,67 @@ impl From<BoxInfo> for BoxPresenter {
             status: format!("{:?}", info.status),
             created: formatter::format_time(&info.created_at),
             names: info.name.unwrap_or_default(),
+            expires: info
+                .expires_at
+                .map(|at| formatter::format_remaining(at - Utc::now()))
+                .unwrap_or_default(),
+            expires_at: info.expires_at.map(|at| formatter::format_time(&at)),
+            project: info.project,
+            size: String::new(),
+            disk_allocated_bytes: None,
+            disk_virtual_bytes: None,
+            size_rw: None,
+            size_root_fs: None,
+            home: String::new(),
         }
     }
 }
 
 pub async fn execute(args: ListArgs, global: &GlobalFlags) -> anyhow::Result<()> {
-    let rt = global.create_runtime()?;
-    let boxes = rt.list_info().await?;
+    let mut filter = parse_filters(&args.filter)?;
+    if !args.all && filter.statuses.is_empty() {
+        filter.statuses = vec![BoxStatus::Running, BoxStatus::Unresponsive];
+    }
 
-    let boxes: Vec<BoxInfo> = boxes
-        .into_iter()
-        .filter(|info| args.all || info.status.is_active())
-        .collect();
+    // With several --home, each home is opened in turn and its boxes are
+    // tagged with it. A home that can't be opened (e.g. its runtime is in
+    // use by another process) is skipped with a warning.
+    let homes = global.per_home();
+    let several = homes.len() > 1;
+    let mut presenters = Vec::new();
+    for home in &homes {
+        let dir = home.home().map(|dir| dir.display().to_string());
+        match list_home(&args, home, &filter).await {
+            Ok(listed) if several => {
+                presenters.extend(listed.into_iter().map(|presenter| BoxPresenter {
+                    home: dir.clone().unwrap_or_default(),
+                    ..presenter
+                }))
+            }
+            Ok(listed) => presenters.extend(listed),
+            Err(e) if several => {
+                eprintln!("Warning: skipping home {}: {}", dir.unwrap_or_default(), e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
 
     if args.quiet {
-        for info in boxes {
-            println!("{}", info.id);
+        let ids: Vec<String> = presenters.into_iter().map(|p| p.id).collect();
+        for id in &ids {
+            outln!("{}", id);
         }
+        output::set_result(&ids);
         return Ok(());
     }
 
-    let presenters: Vec<BoxPresenter> = boxes.into_iter().map(BoxPresenter::from).collect();
     let format = OutputFormat::from_str(&args.format)?;
     formatter::print_output(
         &mut std::io::stdout().lock(),
         &presenters,
         format,
         |writer, data| {
-            print_boxes(writer, data)?;
+            print_boxes(writer, data, args.size, several)?;
             Ok(())
         },
     )?;
@@ -87,8 +191,92 @@ pub async fn execute(args: ListArgs, global: &GlobalFlags) -> anyhow::Result<()>
     Ok(())
 }
 
-fn print_boxes(writer: &mut dyn std::io::Write, boxes: &[BoxPresenter]) -> anyhow::Result<()> {
-    let table = formatter::create_table(boxes).to_string();
+/// Boxes of the single home `global` opens.
+async fn list_home(
+    args: &ListArgs,
+    global: &GlobalFlags,
+    filter: &BoxFilter,
+) -> anyhow::Result<Vec<BoxPresenter>> {
+    let rt = global.create_runtime()?;
+    let boxes = rt.list_info_filtered(filter).await?;
+
+    let mut presenters = Vec::with_capacity(boxes.len());
+    for info in boxes {
+        let litebox = if args.size {
+            rt.get(info.id.as_str()).await?
+        } else {
+            None
+        };
+        let mut presenter = BoxPresenter::from(info);
+        if let Some(litebox) = litebox {
+            presenter = presenter.with_disk_usage(&litebox.disk_usage()?);
+            // Only qcow2 overlays record what the box wrote
+            match litebox.rootfs_size() {
+                Ok(size) => presenter = presenter.with_rootfs_size(size),
+                Err(BoxliteError::Unsupported(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        presenters.push(presenter);
+    }
+    Ok(presenters)
+}
+
+fn parse_filters(filters: &[String]) -> anyhow::Result<BoxFilter> {
+    let mut filter = BoxFilter::default();
+    for spec in filters {
+        match spec.split_once('=') {
+            Some(("status", value)) => filter.statuses.push(
+                value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Unknown box status: {}", value))?,
+            ),
+            Some(("image", value)) => filter.image = Some(value.to_string()),
+            _ => anyhow::bail!(
+                "Invalid filter '{}': expected status=<status> or image=<reference>",
+                spec
+            ),
+        }
+    }
+    Ok(filter)
+}
+
+fn print_boxes(
+    writer: &mut dyn std::io::Write,
+    boxes: &[BoxPresenter],
+    size: bool,
+    several_homes: bool,
+) -> anyhow::Result<()> {
+    let mut table = formatter::create_table(boxes);
+    if !size {
+        table.with(Remove::column(ByColumnName::new("SIZE")));
+    }
+    if !several_homes {
+        table.with(Remove::column(ByColumnName::new("HOME")));
+    }
     writeln!(writer, "{}", table)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filters() {
+        let filter = parse_filters(&[
+            "status=running".to_string(),
+            "status=stopped".to_string(),
+            "image=alpine:latest".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            filter.statuses,
+            vec![BoxStatus::Running, BoxStatus::Stopped]
+        );
+        assert_eq!(filter.image.as_deref(), Some("alpine:latest"));
+
+        assert!(parse_filters(&["status=sleeping".to_string()]).is_err());
+        assert!(parse_filters(&["name=web".to_string()]).is_err());
+    }
+}
@@ -1,12 +1,28 @@
+pub mod attach;
+pub mod context;
 pub mod cp;
 pub mod create;
+pub mod debug;
+pub mod doctor;
 pub mod exec;
+pub mod exec_logs;
+pub mod exec_ls;
+pub mod image;
 pub mod images;
+pub mod info;
 pub mod inspect;
 pub mod list;
+pub mod network;
+pub mod project;
+pub mod provision;
 pub mod pull;
 pub mod restart;
 pub mod rm;
 pub mod run;
+pub mod schema;
+pub mod self_test;
 pub mod start;
+pub mod stats;
 pub mod stop;
+pub mod sync;
+pub mod system;
@@ -0,0 +1,238 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use crate::output;
+use crate::util::names;
+use boxlite::runtime::options::PortProtocol;
+use boxlite::{NetConnection, NetworkMember};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::io::Write;
+use std::time::Duration;
+use tabled::Tabled;
+
+/// Inspect box networking and user-defined networks
+#[derive(Args, Debug)]
+pub struct NetworkArgs {
+    #[command(subcommand)]
+    pub command: NetworkCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NetworkCommand {
+    /// Show a box's active TCP/UDP connections, refreshed periodically
+    Top(NetworkTopArgs),
+    /// Show the boxes on a network and the names they resolve by
+    Inspect(NetworkInspectArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct NetworkTopArgs {
+    /// Box ID or name
+    #[arg(value_name = "BOX")]
+    pub target_box: String,
+
+    /// Seconds between refreshes
+    #[arg(short, long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    /// Print a single snapshot and exit
+    #[arg(long)]
+    pub once: bool,
+
+    /// Output format (table, json, yaml); json, yaml and `--output json` imply --once
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct NetworkInspectArgs {
+    /// Network name
+    #[arg(value_name = "NETWORK")]
+    pub network: String,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Tabled, Serialize)]
+struct ConnectionPresenter {
+    #[tabled(rename = "PROTO")]
+    #[serde(rename = "Protocol")]
+    protocol: String,
+
+    #[tabled(rename = "LOCAL ADDRESS")]
+    #[serde(rename = "LocalAddress")]
+    local_address: String,
+
+    #[tabled(rename = "REMOTE ADDRESS")]
+    #[serde(rename = "RemoteAddress")]
+    remote_address: String,
+
+    #[tabled(rename = "STATE")]
+    #[serde(rename = "State")]
+    state: String,
+
+    #[tabled(rename = "SENT")]
+    #[serde(skip)]
+    sent: String,
+
+    #[tabled(rename = "RECEIVED")]
+    #[serde(skip)]
+    received: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "BytesSent")]
+    bytes_sent: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "BytesReceived")]
+    bytes_received: Option<u64>,
+}
+
+impl From<NetConnection> for ConnectionPresenter {
+    fn from(conn: NetConnection) -> Self {
+        let size = |bytes: Option<u64>| bytes.map(formatter::format_size).unwrap_or("-".into());
+        Self {
+            protocol: conn.protocol.as_str().to_string(),
+            local_address: conn.local_address.to_string(),
+            remote_address: conn.remote_address.to_string(),
+            state: conn.state,
+            sent: size(conn.bytes_sent),
+            received: size(conn.bytes_received),
+            bytes_sent: conn.bytes_sent,
+            bytes_received: conn.bytes_received,
+        }
+    }
+}
+
+#[derive(Tabled, Serialize)]
+struct MemberPresenter {
+    #[tabled(rename = "BOX ID")]
+    #[serde(rename = "Id")]
+    id: String,
+
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[tabled(rename = "STATUS")]
+    #[serde(rename = "Status")]
+    status: String,
+
+    #[tabled(rename = "RECORD")]
+    #[serde(skip)]
+    record: String,
+
+    #[tabled(rename = "PORTS")]
+    #[serde(rename = "Ports")]
+    ports: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "Hostname")]
+    hostname: Option<String>,
+
+    #[tabled(skip)]
+    #[serde(rename = "Address")]
+    address: Option<String>,
+}
+
+impl From<NetworkMember> for MemberPresenter {
+    fn from(member: NetworkMember) -> Self {
+        let record = match (&member.hostname, &member.address) {
+            (Some(hostname), Some(address)) => format!("{} -> {}", hostname, address),
+            _ => "-".to_string(),
+        };
+        let ports = member
+            .ports
+            .iter()
+            .map(|port| {
+                let protocol = match port.protocol {
+                    PortProtocol::Tcp => "tcp",
+                    PortProtocol::Udp => "udp",
+                };
+                format!(
+                    "{}->{}/{}",
+                    port.host_port.unwrap_or(port.guest_port),
+                    port.guest_port,
+                    protocol
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self {
+            id: member.id,
+            name: member.name.unwrap_or_default(),
+            status: member.status.as_str().to_string(),
+            record,
+            ports,
+            hostname: member.hostname,
+            address: member.address,
+        }
+    }
+}
+
+pub async fn execute(args: NetworkArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        NetworkCommand::Top(args) => top(args, global).await,
+        NetworkCommand::Inspect(args) => inspect(args, global).await,
+    }
+}
+
+async fn inspect(args: NetworkInspectArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = OutputFormat::from_str(&args.format)?;
+    let rt = global.create_runtime()?;
+    let info = rt.inspect_network(&args.network).await?;
+
+    let presenters: Vec<MemberPresenter> = info
+        .members
+        .into_iter()
+        .map(MemberPresenter::from)
+        .collect();
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+async fn top(args: NetworkTopArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = OutputFormat::from_str(&args.format)?;
+    let rt = global.create_runtime()?;
+    let litebox = names::require_box(&rt, &args.target_box, global.fuzzy_names).await?;
+
+    let once = args.once || !matches!(format, OutputFormat::Table) || output::is_json();
+    loop {
+        let presenters: Vec<ConnectionPresenter> = litebox
+            .net_connections()
+            .await?
+            .into_iter()
+            .map(ConnectionPresenter::from)
+            .collect();
+
+        let mut stdout = std::io::stdout().lock();
+        if !once {
+            // Clear the screen and move the cursor home before each refresh
+            write!(stdout, "\x1b[2J\x1b[H")?;
+        }
+        formatter::print_output(&mut stdout, &presenters, format, |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        })?;
+        stdout.flush()?;
+        drop(stdout);
+
+        if once {
+            return Ok(());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(args.interval)) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use crate::output::{self, outln};
+use boxlite::ProjectInfo;
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::io::Write;
+use tabled::Tabled;
+
+/// Manage projects
+#[derive(Args, Debug)]
+pub struct ProjectArgs {
+    #[command(subcommand)]
+    pub command: ProjectCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProjectCommand {
+    /// List projects that have boxes
+    #[command(visible_alias = "list")]
+    Ls(ProjectLsArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ProjectLsArgs {
+    /// Only show project names
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Tabled, Serialize)]
+struct ProjectPresenter {
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[tabled(rename = "BOXES")]
+    #[serde(rename = "Boxes")]
+    boxes: usize,
+
+    #[tabled(rename = "RUNNING")]
+    #[serde(rename = "Running")]
+    running: usize,
+}
+
+impl From<ProjectInfo> for ProjectPresenter {
+    fn from(info: ProjectInfo) -> Self {
+        Self {
+            name: info.name,
+            boxes: info.boxes,
+            running: info.running,
+        }
+    }
+}
+
+pub async fn execute(args: ProjectArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        ProjectCommand::Ls(args) => list(args, global).await,
+    }
+}
+
+async fn list(args: ProjectLsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let projects = rt.list_projects().await?;
+
+    if args.quiet {
+        let names: Vec<String> = projects.into_iter().map(|info| info.name).collect();
+        for name in &names {
+            outln!("{}", name);
+        }
+        output::set_result(&names);
+        return Ok(());
+    }
+
+    let presenters: Vec<ProjectPresenter> =
+        projects.into_iter().map(ProjectPresenter::from).collect();
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use crate::output::{self, outln};
+use crate::util::names;
+use boxlite::ProvisionSpec;
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct ProvisionArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Package to install (repeatable)
+    #[arg(long = "pkg", value_name = "PACKAGE", required = true)]
+    pub packages: Vec<String>,
+
+    /// Seconds to wait for another provisioning of the box to finish
+    #[arg(long, value_name = "SECONDS")]
+    pub lock_timeout: Option<u64>,
+}
+
+pub async fn execute(args: ProvisionArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let litebox = names::require_box(&runtime, &args.target, global.fuzzy_names).await?;
+
+    let mut spec = ProvisionSpec::new().packages(args.packages.iter().cloned());
+    if let Some(secs) = args.lock_timeout {
+        spec = spec.lock_timeout(Duration::from_secs(secs));
+    }
+    let manager = litebox.provision(spec).await?;
+
+    outln!("Installed {} with {}", args.packages.join(" "), manager);
+    output::set_result(&serde_json::json!({
+        "package_manager": manager,
+        "packages": args.packages,
+    }));
+    Ok(())
+}
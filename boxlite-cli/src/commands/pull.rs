@@ -2,28 +2,35 @@ use anyhow::Result;
 use clap::Args;
 
 use crate::cli::GlobalFlags;
+use crate::output::{self, outln};
 
 #[derive(Args, Debug)]
 pub struct PullArgs {
     /// Image to pull
     pub image: String,
 
-    /// Quiet mode - only show digest
+    /// Quiet mode - only show digest, without progress bars
     #[arg(short, long)]
     pub quiet: bool,
 }
 
 pub async fn execute(args: PullArgs, global: &GlobalFlags) -> Result<()> {
     let runtime = global.create_runtime()?;
+    crate::progress::attach(&runtime, args.quiet);
 
     let image = runtime.pull_image(&args.image).await?;
     if args.quiet {
-        println!("{}", image.config_digest());
+        outln!("{}", image.config_digest());
     } else {
-        println!("Pulled: {}", image.reference());
-        println!("Digest: {}", image.config_digest());
-        println!("Layers: {}", image.layer_count());
+        outln!("Pulled: {}", image.reference());
+        outln!("Digest: {}", image.config_digest());
+        outln!("Layers: {}", image.layer_count());
     }
+    output::set_result(&serde_json::json!({
+        "reference": image.reference(),
+        "digest": image.config_digest(),
+        "layers": image.layer_count(),
+    }));
 
     Ok(())
 }
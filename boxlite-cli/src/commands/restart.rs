@@ -1,3 +1,5 @@
+use crate::output::{self, outln};
+use crate::util::names;
 use clap::Args;
 
 #[derive(Args, Debug)]
@@ -12,17 +14,20 @@ pub async fn execute(args: RestartArgs, global: &crate::cli::GlobalFlags) -> any
 
     let mut errors = Vec::new();
     let mut success_count = 0;
+    let mut done = Vec::new();
 
     for target in args.targets {
-        let litebox = match runtime.get(&target).await? {
+        let litebox = match names::get_box(&runtime, &target, global.fuzzy_names).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: No such box: {}", target);
+                let message = names::no_such_box(&runtime, &target, global.fuzzy_names).await;
+                eprintln!("Error: {}", message);
                 errors.push(format!("{}: not found", target));
                 continue;
             }
         };
 
+        let id = litebox.id().clone();
         if let Err(e) = litebox.stop().await {
             // If stop fails, we should NOT proceed to start, because resources might still be locked.
             eprintln!("Error restarting box '{}': {}", target, e);
@@ -32,7 +37,7 @@ pub async fn execute(args: RestartArgs, global: &crate::cli::GlobalFlags) -> any
 
         // After stop, handle is invalidated. Get a new handle.
         // Came across:Handle invalidated after stop(). Use runtime.get() to get a new handle.
-        let litebox = match runtime.get(&target).await? {
+        let litebox = match runtime.get(id.as_str()).await? {
             Some(b) => b,
             None => {
                 eprintln!("Error: Box disappeared after stop: {}", target);
@@ -45,11 +50,14 @@ pub async fn execute(args: RestartArgs, global: &crate::cli::GlobalFlags) -> any
             eprintln!("Error restarting box '{}': {}", target, e);
             errors.push(format!("{}: {}", target, e));
         } else {
-            println!("{}", target);
+            outln!("{}", target);
+            done.push(target);
             success_count += 1;
         }
     }
 
+    output::set_result(&done);
+
     if !errors.is_empty() {
         let error_summary = if success_count > 0 {
             format!(
@@ -1,3 +1,4 @@
+use crate::output::{self, outln};
 use clap::Args;
 
 #[derive(Args, Debug)]
@@ -42,14 +43,17 @@ pub async fn execute(args: RmArgs, global: &crate::cli::GlobalFlags) -> anyhow::
     };
 
     let mut active_error = false;
+    let mut removed = Vec::new();
     for target in targets {
         if let Err(e) = runtime.remove(&target, args.force).await {
             eprintln!("Error removing box '{}': {}", target, e);
             active_error = true;
         } else {
-            println!("{}", target);
+            outln!("{}", target);
+            removed.push(target);
         }
     }
+    output::set_result(&removed);
 
     if active_error {
         anyhow::bail!("Some boxes could not be removed");
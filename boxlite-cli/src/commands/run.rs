@@ -1,6 +1,7 @@
 use crate::cli::{
     GlobalFlags, ManagementFlags, ProcessFlags, PublishFlags, ResourceFlags, VolumeFlags,
 };
+use crate::output::{self, outln};
 use crate::terminal::StreamManager;
 use crate::util::to_shell_exit_code;
 use boxlite::BoxCommand;
@@ -25,6 +26,20 @@ pub struct RunArgs {
     #[command(flatten)]
     pub management: ManagementFlags,
 
+    /// Don't show image pull and boot progress
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Ask on the terminal before each new outbound connection of the box;
+    /// answers are read from stdin and remembered for the box
+    #[arg(long, conflicts_with_all = ["detach", "interactive", "tty"])]
+    pub prompt_connections: bool,
+
+    /// Run PROGRAM as the box's main process instead of the image
+    /// ENTRYPOINT, with COMMAND as its arguments (requires -d)
+    #[arg(long, value_name = "PROGRAM", requires = "detach")]
+    pub entrypoint: Option<String>,
+
     #[arg(index = 1)]
     pub image: String,
 
@@ -48,7 +63,11 @@ struct BoxRunner {
 impl BoxRunner {
     fn new(args: RunArgs, global: &GlobalFlags) -> anyhow::Result<Self> {
         let rt = global.create_runtime()?;
-        let home = global.home.clone();
+        crate::progress::attach(&rt, args.quiet);
+        if args.prompt_connections {
+            crate::connection_prompt::attach(&rt);
+        }
+        let home = global.home().map(std::path::Path::to_path_buf);
 
         Ok(Self { args, rt, home })
     }
@@ -59,13 +78,22 @@ impl BoxRunner {
 
         let litebox = self.create_box().await?;
 
+        // The overridden main process is the command: start the box and leave it running
+        if self.args.entrypoint.is_some() {
+            litebox.start().await?;
+            outln!("{}", litebox.id());
+            output::set_result(&serde_json::json!({ "id": litebox.id().to_string() }));
+            return Ok(());
+        }
+
         // Start execution
         let cmd = self.prepare_command();
         let mut execution = litebox.exec(cmd).await?;
 
         // Detach mode: Print ID and exit
         if self.args.management.detach {
-            println!("{}", litebox.id());
+            outln!("{}", litebox.id());
+            output::set_result(&serde_json::json!({ "id": litebox.id().to_string() }));
             return Ok(());
         }
 
@@ -77,9 +105,13 @@ impl BoxRunner {
         );
 
         let exit_code = streamer.start().await?;
+        output::set_result(&serde_json::json!({
+            "id": litebox.id().to_string(),
+            "exit_code": exit_code,
+        }));
         // Exit with box's exit code
         if exit_code != 0 {
-            std::process::exit(to_shell_exit_code(exit_code));
+            output::exit(to_shell_exit_code(exit_code));
         }
 
         Ok(())
@@ -100,12 +132,18 @@ impl BoxRunner {
             options.auto_remove = false;
         }
 
+        options.prompt_connections = self.args.prompt_connections;
         options.rootfs = RootfsSpec::Image(self.args.image.clone());
+        if let Some(entrypoint) = &self.args.entrypoint {
+            options.entrypoint = Some(vec![entrypoint.clone()]);
+            options.cmd = Some(self.args.command.clone());
+        }
 
         let litebox = self
             .rt
             .create(options, self.args.management.name.clone())
             .await?;
+        super::create::print_warnings(&litebox);
 
         Ok(litebox)
     }
@@ -0,0 +1,29 @@
+use crate::output::{self, outln};
+use boxlite::runtime::schema;
+use clap::{Args, ValueEnum};
+
+/// Print the JSON Schema of a type in the SDK JSON contract
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    /// Type to print the schema of
+    #[arg(value_enum)]
+    pub kind: SchemaKind,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SchemaKind {
+    /// Options for creating a box
+    BoxOptions,
+    /// Box metadata returned by list and inspect
+    BoxInfo,
+}
+
+pub async fn execute(args: SchemaArgs) -> anyhow::Result<()> {
+    let schema = match args.kind {
+        SchemaKind::BoxOptions => schema::box_options_schema(),
+        SchemaKind::BoxInfo => schema::box_info_schema(),
+    };
+    outln!("{}", serde_json::to_string_pretty(&schema)?);
+    output::set_result(&schema);
+    Ok(())
+}
@@ -0,0 +1,429 @@
+//! End-to-end check of the installation: boot a box, run commands in it,
+//! share a directory with it and look at its network.
+
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use crate::output::outln;
+use boxlite::net::constants::HOST_NAME;
+use boxlite::runtime::constants::images;
+use boxlite::runtime::options::VolumeSpec;
+use boxlite::{BoxCommand, BoxOptions, BoxliteRuntime, ImagePullPolicy, LiteBox, RootfsSpec};
+use clap::Args;
+use futures::StreamExt;
+use serde::Serialize;
+use std::future::Future;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tabled::Tabled;
+use tokio::io::AsyncBufReadExt;
+
+/// Directory the host shares with the test box
+const GUEST_DIR: &str = "/self-test";
+
+/// Validate the installation by booting a box and exercising it
+#[derive(Args, Debug)]
+pub struct SelfTestArgs {
+    /// Seconds each check may take
+    #[arg(long, default_value_t = 120, value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: u64,
+
+    /// Keep the test box afterwards for debugging
+    #[arg(long)]
+    pub keep: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not run because a check it depends on failed
+    Skip,
+}
+
+#[derive(Tabled, Serialize)]
+struct CheckPresenter {
+    #[tabled(rename = "CHECK")]
+    #[serde(rename = "Check")]
+    name: &'static str,
+
+    #[tabled(rename = "RESULT")]
+    #[serde(skip)]
+    result: String,
+
+    #[tabled(rename = "TIME")]
+    #[serde(skip)]
+    time: String,
+
+    #[tabled(rename = "DETAIL")]
+    #[serde(rename = "Detail")]
+    detail: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "Status")]
+    status: CheckStatus,
+
+    #[tabled(skip)]
+    #[serde(rename = "DurationMs")]
+    duration_ms: Option<u64>,
+}
+
+/// Checks run so far, in order.
+struct Matrix {
+    timeout: Duration,
+    checks: Vec<CheckPresenter>,
+}
+
+impl Matrix {
+    /// Run `check` under the timeout and record the outcome.
+    ///
+    /// Returns the check's value if it passed.
+    async fn run<T>(
+        &mut self,
+        name: &'static str,
+        check: impl Future<Output = anyhow::Result<(T, String)>>,
+    ) -> Option<T> {
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(self.timeout, check).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(anyhow::anyhow!(
+                "timed out after {}s",
+                self.timeout.as_secs()
+            )),
+        };
+        let elapsed = started.elapsed();
+        let (status, detail, value) = match outcome {
+            Ok((value, detail)) => (CheckStatus::Pass, detail, Some(value)),
+            Err(e) => (CheckStatus::Fail, format!("{:#}", e), None),
+        };
+        self.push(name, status, Some(elapsed), detail);
+        value
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &str) {
+        self.push(name, CheckStatus::Skip, None, reason.to_string());
+    }
+
+    fn push(
+        &mut self,
+        name: &'static str,
+        status: CheckStatus,
+        elapsed: Option<Duration>,
+        detail: String,
+    ) {
+        let result = match status {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Skip => "skip",
+        };
+        self.checks.push(CheckPresenter {
+            name,
+            result: result.to_string(),
+            time: elapsed
+                .map(|d| format!("{:.2}s", d.as_secs_f64()))
+                .unwrap_or_else(|| "-".to_string()),
+            detail,
+            status,
+            duration_ms: elapsed.map(|d| d.as_millis() as u64),
+        });
+    }
+
+    fn failed(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count()
+    }
+}
+
+pub async fn execute(args: SelfTestArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = OutputFormat::from_str(&args.format)?;
+    let mut matrix = Matrix {
+        timeout: Duration::from_secs(args.timeout),
+        checks: Vec::new(),
+    };
+
+    let token = ulid::Ulid::new().to_string().to_lowercase();
+    let host_dir = std::env::temp_dir().join(format!("boxlite-self-test-{}", token));
+    let litebox = run_box_checks(&mut matrix, global, &host_dir, &token).await;
+
+    if let Some((rt, litebox)) = litebox {
+        let _ = litebox.stop().await;
+        if !args.keep {
+            let _ = rt.remove(litebox.id().as_str(), true).await;
+        }
+    }
+    if !args.keep {
+        let _ = std::fs::remove_dir_all(&host_dir);
+    }
+
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &matrix.checks,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+
+    let failed = matrix.failed();
+    if failed > 0 {
+        anyhow::bail!("{} of {} checks failed", failed, matrix.checks.len());
+    }
+    if format == OutputFormat::Table {
+        outln!("All checks passed");
+    }
+    Ok(())
+}
+
+/// Run every check, skipping those whose prerequisite failed. Returns the
+/// test box, if one was created, for cleanup.
+async fn run_box_checks(
+    matrix: &mut Matrix,
+    global: &GlobalFlags,
+    host_dir: &Path,
+    token: &str,
+) -> Option<(BoxliteRuntime, LiteBox)> {
+    const BOX_CHECKS: [&str; 5] = ["boot", "exec", "volume-read", "volume-write", "network"];
+
+    let Some(rt) = matrix
+        .run("runtime", async {
+            let rt = global.create_runtime()?;
+            Ok((rt, "runtime initialized".to_string()))
+        })
+        .await
+    else {
+        matrix.skip("create", "runtime failed");
+        for name in BOX_CHECKS {
+            matrix.skip(name, "runtime failed");
+        }
+        return None;
+    };
+
+    let litebox = matrix
+        .run("create", async {
+            std::fs::create_dir_all(host_dir)?;
+            std::fs::write(host_dir.join("from-host"), token)?;
+            let options = self_test_options(host_dir);
+            let litebox = rt.create(options.clone(), None).await?;
+            let detail = format!("box {} from {}", litebox.id(), image_name(&options.rootfs));
+            Ok((litebox, detail))
+        })
+        .await;
+    let Some(litebox) = litebox else {
+        for name in BOX_CHECKS {
+            matrix.skip(name, "box creation failed");
+        }
+        return None;
+    };
+
+    let booted = matrix
+        .run("boot", async {
+            litebox.start().await?;
+            Ok(((), "guest agent ready".to_string()))
+        })
+        .await
+        .is_some();
+    if !booted {
+        for name in &BOX_CHECKS[1..] {
+            matrix.skip(name, "box did not boot");
+        }
+        return Some((rt, litebox));
+    }
+
+    matrix
+        .run("exec", async {
+            let stdout = run(&litebox, &format!("echo {}", token)).await?;
+            anyhow::ensure!(
+                stdout.trim() == token,
+                "unexpected output {:?}",
+                stdout.trim()
+            );
+            Ok(((), "canary command echoed its token".to_string()))
+        })
+        .await;
+
+    matrix
+        .run("volume-read", async {
+            let stdout = run(&litebox, &format!("cat {}/from-host", GUEST_DIR)).await?;
+            anyhow::ensure!(stdout.trim() == token, "box read {:?}", stdout.trim());
+            Ok(((), format!("box read a host file in {}", GUEST_DIR)))
+        })
+        .await;
+
+    matrix
+        .run("volume-write", async {
+            run(
+                &litebox,
+                &format!("echo {} > {}/from-box", token, GUEST_DIR),
+            )
+            .await?;
+            let written = std::fs::read_to_string(host_dir.join("from-box"))?;
+            anyhow::ensure!(written.trim() == token, "host read {:?}", written.trim());
+            Ok(((), "host read a file written by the box".to_string()))
+        })
+        .await;
+
+    matrix
+        .run("network", async {
+            let routes = run(&litebox, "cat /proc/net/route").await?;
+            let interface = default_route_interface(&routes)
+                .ok_or_else(|| anyhow::anyhow!("box has no default route"))?;
+            reach_host(&litebox, token).await?;
+            Ok((
+                (),
+                format!(
+                    "default route via {}, box reached the host over TCP",
+                    interface
+                ),
+            ))
+        })
+        .await;
+
+    Some((rt, litebox))
+}
+
+/// A box from the built-in image, so the test works offline. Builds without
+/// it fall back to the image the guest itself boots from, which needs no
+/// extra pull once any box has run.
+fn self_test_options(host_dir: &Path) -> BoxOptions {
+    let rootfs = if RootfsSpec::builtin_available() {
+        RootfsSpec::Builtin
+    } else {
+        RootfsSpec::Image(images::INIT_ROOTFS.to_string())
+    };
+    BoxOptions {
+        rootfs,
+        pull_policy: ImagePullPolicy::IfNotPresent,
+        host_loopback: true,
+        volumes: vec![VolumeSpec {
+            host_path: host_dir.display().to_string(),
+            guest_path: GUEST_DIR.to_string(),
+            read_only: false,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+fn image_name(rootfs: &RootfsSpec) -> &str {
+    match rootfs {
+        RootfsSpec::Image(image) => image,
+        RootfsSpec::RootfsPath(path) => path,
+        RootfsSpec::Builtin => images::BUILTIN,
+    }
+}
+
+/// Run a shell command, failing on a non-zero exit, and return its stdout.
+async fn run(litebox: &LiteBox, script: &str) -> anyhow::Result<String> {
+    let mut execution = litebox
+        .exec(BoxCommand::new("sh").args(["-c", script]))
+        .await?;
+    let mut stdout = String::new();
+    if let Some(mut stream) = execution.stdout() {
+        while let Some(chunk) = stream.next().await {
+            stdout.push_str(&chunk);
+        }
+    }
+    let result = execution.wait().await?;
+    anyhow::ensure!(
+        result.success(),
+        "`{}` exited with code {}",
+        script,
+        result.exit_code
+    );
+    Ok(stdout)
+}
+
+/// Have the box connect to a listener on the host's loopback and send
+/// `token` over it. Uses `nc` where the image has it (busybox), else bash's
+/// `/dev/tcp`.
+async fn reach_host(litebox: &LiteBox, token: &str) -> anyhow::Result<()> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let script = format!(
+        "if command -v nc >/dev/null; then echo {token} | nc {host} {port}; \
+         else bash -c 'echo {token} > /dev/tcp/{host}/{port}'; fi",
+        token = token,
+        host = HOST_NAME,
+        port = port
+    );
+
+    let received = async {
+        let (stream, _) = listener.accept().await?;
+        let mut line = String::new();
+        tokio::io::BufReader::new(stream)
+            .read_line(&mut line)
+            .await?;
+        // Dropping the connection lets nc exit
+        anyhow::Ok(line)
+    };
+    // If the box can't connect, its command fails and stops the accept
+    let (_, received) = tokio::try_join!(run(litebox, &script), received)?;
+    anyhow::ensure!(
+        received.trim() == token,
+        "host received {:?}",
+        received.trim()
+    );
+    Ok(())
+}
+
+/// Interface of the default route in `/proc/net/route`, if any.
+fn default_route_interface(routes: &str) -> Option<&str> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let interface = fields.next()?;
+        (fields.next()? == "00000000").then_some(interface)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_default_route_interface() {
+        let routes = "Iface\tDestination\tGateway\tFlags\n\
+                      eth0\t0002000A\t00000000\t0001\n\
+                      eth0\t00000000\t0202000A\t0003\n";
+        assert_eq!(default_route_interface(routes), Some("eth0"));
+        assert_eq!(
+            default_route_interface("Iface\tDestination\nlo\t0000007F\n"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_and_timed_out_checks_are_recorded() {
+        let mut matrix = Matrix {
+            timeout: Duration::from_millis(10),
+            checks: Vec::new(),
+        };
+        let passed = matrix
+            .run("ok", async { Ok((1, "fine".to_string())) })
+            .await;
+        let slow = matrix
+            .run("slow", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(((), String::new()))
+            })
+            .await;
+        matrix.skip("later", "slow failed");
+
+        assert_eq!(passed, Some(1));
+        assert!(slow.is_none());
+        let statuses: Vec<_> = matrix.checks.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            vec![CheckStatus::Pass, CheckStatus::Fail, CheckStatus::Skip]
+        );
+        assert_eq!(matrix.failed(), 1);
+        assert!(matrix.checks[1].detail.contains("timed out"));
+    }
+}
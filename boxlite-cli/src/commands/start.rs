@@ -1,3 +1,5 @@
+use crate::output::{self, outln};
+use crate::util::names;
 use clap::Args;
 
 #[derive(Args, Debug)]
@@ -12,12 +14,14 @@ pub async fn execute(args: StartArgs, global: &crate::cli::GlobalFlags) -> anyho
 
     let mut errors = Vec::new();
     let mut success_count = 0;
+    let mut done = Vec::new();
 
     for target in args.targets {
-        let litebox = match runtime.get(&target).await? {
+        let litebox = match names::get_box(&runtime, &target, global.fuzzy_names).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: No such box: {}", target);
+                let message = names::no_such_box(&runtime, &target, global.fuzzy_names).await;
+                eprintln!("Error: {}", message);
                 errors.push(format!("{}: not found", target));
                 continue;
             }
@@ -27,11 +31,14 @@ pub async fn execute(args: StartArgs, global: &crate::cli::GlobalFlags) -> anyho
             eprintln!("Error starting box '{}': {}", target, e);
             errors.push(format!("{}: {}", target, e));
         } else {
-            println!("{}", target);
+            outln!("{}", target);
+            done.push(target);
             success_count += 1;
         }
     }
 
+    output::set_result(&done);
+
     if !errors.is_empty() {
         let error_summary = if success_count > 0 {
             format!(
@@ -0,0 +1,494 @@
+//! Live resource usage of boxes, including their container cgroup counters.
+
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, GtmplWithJson, OutputFormat, value_from_serde_json};
+use crate::output;
+use crate::util::names;
+use boxlite::{BoxFilter, BoxMetrics, BoxStatus, LiteBox};
+use clap::Args;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
+use tabled::Tabled;
+
+/// Display resource usage of running boxes
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Box ID(s) or name(s) (default: all running boxes)
+    #[arg(value_name = "BOX", num_args = 0..)]
+    pub boxes: Vec<String>,
+
+    /// Seconds between refreshes
+    #[arg(short, long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    /// Print a single snapshot and exit
+    #[arg(long)]
+    pub no_stream: bool,
+
+    /// Output format (table, json, yaml) or a Go template; json, yaml and `--output json` imply
+    /// --no-stream. A template starting with `table ` prints a header row and aligns
+    /// tab-separated columns, e.g. 'table {{.Name}}\t{{.CpuPercent}}\t{{.MemoryBytes}}'
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Tabled, Serialize, Default)]
+struct StatsPresenter {
+    #[tabled(rename = "ID")]
+    #[serde(rename = "ID")]
+    id: String,
+
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[tabled(rename = "CPU %")]
+    #[serde(skip)]
+    cpu: String,
+
+    #[tabled(rename = "MEM USAGE")]
+    #[serde(skip)]
+    memory: String,
+
+    #[tabled(rename = "WORKING SET")]
+    #[serde(skip)]
+    working_set: String,
+
+    #[tabled(rename = "CACHE")]
+    #[serde(skip)]
+    cache: String,
+
+    #[tabled(rename = "PIDS")]
+    #[serde(skip)]
+    pids: String,
+
+    #[tabled(rename = "BLOCK I/O")]
+    #[serde(skip)]
+    block_io: String,
+
+    #[tabled(rename = "THROTTLED")]
+    #[serde(skip)]
+    throttled: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "CpuPercent")]
+    cpu_percent: Option<f32>,
+
+    #[tabled(skip)]
+    #[serde(rename = "MemoryBytes")]
+    memory_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "MemoryWorkingSetBytes")]
+    memory_working_set_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "MemoryCacheBytes")]
+    memory_cache_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "MemoryLimitHits")]
+    memory_limit_hits: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "OomKills")]
+    oom_kills: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "Pids")]
+    pids_current: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "IoReadBytes")]
+    io_read_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "IoWriteBytes")]
+    io_write_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "CpuThrottledPeriods")]
+    cpu_throttled_periods: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "CpuThrottledUsec")]
+    cpu_throttled_usec: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "SwapUsedBytes")]
+    swap_used_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "SwapOutBytes")]
+    swap_out_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "CommandsExecuted")]
+    commands_executed: u64,
+
+    #[tabled(skip)]
+    #[serde(rename = "ExecErrors")]
+    exec_errors: u64,
+
+    #[tabled(skip)]
+    #[serde(rename = "ExecsInFlight")]
+    execs_in_flight: u64,
+
+    #[tabled(skip)]
+    #[serde(rename = "Labeled")]
+    labeled: BTreeMap<String, LabelStats>,
+
+    #[tabled(skip)]
+    #[serde(rename = "BytesSent")]
+    bytes_sent: u64,
+
+    #[tabled(skip)]
+    #[serde(rename = "BytesReceived")]
+    bytes_received: u64,
+
+    #[tabled(skip)]
+    #[serde(rename = "NetworkBytesSent")]
+    network_bytes_sent: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "NetworkBytesReceived")]
+    network_bytes_received: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "NetworkTcpConnections")]
+    network_tcp_connections: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "NetworkTcpErrors")]
+    network_tcp_errors: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "CreateDurationMs")]
+    create_duration_ms: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "GuestBootDurationMs")]
+    guest_boot_duration_ms: Option<u64>,
+}
+
+/// Exec counters of one command label.
+#[derive(Serialize, Default)]
+struct LabelStats {
+    #[serde(rename = "CommandsExecuted")]
+    commands_executed: u64,
+    #[serde(rename = "ExecErrors")]
+    exec_errors: u64,
+}
+
+impl StatsPresenter {
+    fn new(litebox: &LiteBox, metrics: BoxMetrics) -> Self {
+        let size = |bytes: Option<u64>| bytes.map(formatter::format_size).unwrap_or("-".into());
+        let block_io = match (metrics.io_read_bytes_total, metrics.io_write_bytes_total) {
+            (Some(read), Some(write)) => format!(
+                "{} / {}",
+                formatter::format_size(read),
+                formatter::format_size(write)
+            ),
+            _ => "-".to_string(),
+        };
+        Self {
+            id: litebox.id().to_string(),
+            name: litebox.name().unwrap_or_default().to_string(),
+            cpu: metrics
+                .cpu_percent
+                .map(|cpu| format!("{:.2}%", cpu))
+                .unwrap_or("-".into()),
+            memory: size(metrics.memory_bytes),
+            working_set: size(metrics.memory_working_set_bytes),
+            cache: size(metrics.memory_cache_bytes),
+            pids: metrics
+                .pids_current
+                .map(|pids| pids.to_string())
+                .unwrap_or("-".into()),
+            block_io,
+            throttled: metrics
+                .cpu_throttled_periods_total
+                .map(|periods| periods.to_string())
+                .unwrap_or("-".into()),
+            cpu_percent: metrics.cpu_percent,
+            memory_bytes: metrics.memory_bytes,
+            memory_working_set_bytes: metrics.memory_working_set_bytes,
+            memory_cache_bytes: metrics.memory_cache_bytes,
+            memory_limit_hits: metrics.memory_limit_hits_total,
+            oom_kills: metrics.memory_oom_kills_total,
+            pids_current: metrics.pids_current,
+            io_read_bytes: metrics.io_read_bytes_total,
+            io_write_bytes: metrics.io_write_bytes_total,
+            cpu_throttled_periods: metrics.cpu_throttled_periods_total,
+            cpu_throttled_usec: metrics.cpu_throttled_usec_total,
+            swap_used_bytes: metrics.swap_used_bytes,
+            swap_out_bytes: metrics.swap_out_bytes_total,
+            commands_executed: metrics.commands_executed_total,
+            exec_errors: metrics.exec_errors_total,
+            execs_in_flight: metrics.execs_in_flight,
+            labeled: metrics
+                .labeled
+                .iter()
+                .map(|(label, counters)| {
+                    let stats = LabelStats {
+                        commands_executed: counters.commands_executed_total,
+                        exec_errors: counters.exec_errors_total,
+                    };
+                    (label.clone(), stats)
+                })
+                .collect(),
+            bytes_sent: metrics.bytes_sent_total,
+            bytes_received: metrics.bytes_received_total,
+            network_bytes_sent: metrics.network_bytes_sent,
+            network_bytes_received: metrics.network_bytes_received,
+            network_tcp_connections: metrics.network_tcp_connections,
+            network_tcp_errors: metrics.network_tcp_errors,
+            create_duration_ms: metrics.total_create_duration_ms.map(|ms| ms as u64),
+            guest_boot_duration_ms: metrics.guest_boot_duration_ms.map(|ms| ms as u64),
+        }
+    }
+}
+
+/// `--format` value: a built-in format or a Go template rendered once per box.
+enum StatsFormat {
+    Builtin(OutputFormat),
+    Template {
+        template: GtmplWithJson,
+        /// `table ` prefix: print a header row and align tab-separated columns
+        table: bool,
+    },
+}
+
+impl StatsFormat {
+    fn parse(format: &str) -> anyhow::Result<Self> {
+        if !(format.contains("{{") && format.contains("}}")) {
+            return Ok(Self::Builtin(OutputFormat::from_str(format)?));
+        }
+        let (body, table) = match format.strip_prefix("table ") {
+            Some(body) => (body, true),
+            None => (format, false),
+        };
+        // Shells pass `\t` literally; treat it as the column separator
+        let body = body.replace("\\t", "\t");
+        let template =
+            GtmplWithJson::parse(&body).map_err(|e| anyhow::anyhow!("template: {}", e))?;
+        Ok(Self::Template { template, table })
+    }
+
+    /// JSON and YAML print a single snapshot; tables and templates refresh.
+    fn streams(&self) -> bool {
+        !matches!(self, Self::Builtin(OutputFormat::Json | OutputFormat::Yaml))
+    }
+}
+
+fn write_stats(
+    writer: &mut impl Write,
+    presenters: &[StatsPresenter],
+    format: &StatsFormat,
+) -> anyhow::Result<()> {
+    let (template, table) = match format {
+        StatsFormat::Builtin(format) => {
+            return formatter::print_output(writer, &presenters, *format, |writer, data| {
+                writeln!(writer, "{}", formatter::create_table(data))?;
+                Ok(())
+            });
+        }
+        StatsFormat::Template { template, table } => (template, *table),
+    };
+
+    let mut lines = Vec::with_capacity(presenters.len() + 1);
+    if table {
+        lines.push(template.render(value_from_serde_json(&header_context()))?);
+    }
+    for presenter in presenters {
+        let json = serde_json::to_value(presenter)?;
+        lines.push(template.render(value_from_serde_json(&json))?);
+    }
+    if output::is_json() {
+        output::set_result(&lines);
+        return Ok(());
+    }
+    if table {
+        lines = align_columns(&lines);
+    }
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Context that renders every field as its column title, e.g. `CpuPercent` as `CPU PERCENT`.
+fn header_context() -> serde_json::Value {
+    let fields = match serde_json::to_value(StatsPresenter::default()) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => return serde_json::Value::Null,
+    };
+    fields
+        .keys()
+        .map(|key| (key.clone(), serde_json::Value::from(column_title(key))))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Split a field name at lower-to-upper case boundaries and upper-case it.
+fn column_title(key: &str) -> String {
+    let mut title = String::with_capacity(key.len() + 4);
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if c.is_uppercase() && prev_lower {
+            title.push(' ');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        title.extend(c.to_uppercase());
+    }
+    title
+}
+
+/// Pad tab-separated cells so columns line up, three spaces apart.
+fn align_columns(lines: &[String]) -> Vec<String> {
+    let rows: Vec<Vec<&str>> = lines.iter().map(|l| l.split('\t').collect()).collect();
+    let mut widths: Vec<usize> = Vec::new();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            let len = cell.chars().count();
+            match widths.get_mut(i) {
+                Some(width) => *width = (*width).max(len),
+                None => widths.push(len),
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let last = row.len().saturating_sub(1);
+            row.iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        cell.to_string()
+                    } else {
+                        format!("{:<width$}   ", cell, width = widths[i])
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect()
+}
+
+pub async fn execute(args: StatsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = StatsFormat::parse(&args.format)?;
+    let rt = global.create_runtime()?;
+
+    let named = !args.boxes.is_empty();
+    let boxes = if named {
+        let mut boxes = Vec::with_capacity(args.boxes.len());
+        for target in &args.boxes {
+            boxes.push(names::require_box(&rt, target, global.fuzzy_names).await?);
+        }
+        boxes
+    } else {
+        let filter = BoxFilter {
+            statuses: vec![BoxStatus::Running, BoxStatus::Unresponsive],
+            ..Default::default()
+        };
+        let mut boxes = Vec::new();
+        for info in rt.list_info_filtered(&filter).await? {
+            if let Some(litebox) = rt.get(info.id.as_str()).await? {
+                boxes.push(litebox);
+            }
+        }
+        boxes
+    };
+
+    let once = args.no_stream || !format.streams() || output::is_json();
+    loop {
+        let mut presenters = Vec::with_capacity(boxes.len());
+        for litebox in &boxes {
+            match litebox.metrics().await {
+                Ok(metrics) => presenters.push(StatsPresenter::new(litebox, metrics)),
+                // A listed box may have stopped since; a named one is an error
+                Err(e) if named => return Err(e.into()),
+                Err(_) => {}
+            }
+        }
+
+        let mut stdout = std::io::stdout().lock();
+        if !once {
+            // Clear the screen and move the cursor home before each refresh
+            write!(stdout, "\x1b[2J\x1b[H")?;
+        }
+        write_stats(&mut stdout, &presenters, &format)?;
+        stdout.flush()?;
+        drop(stdout);
+
+        if once {
+            return Ok(());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(args.interval)) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_titles_split_camel_case() {
+        assert_eq!(column_title("ID"), "ID");
+        assert_eq!(column_title("CpuPercent"), "CPU PERCENT");
+        assert_eq!(column_title("NetworkTcpErrors"), "NETWORK TCP ERRORS");
+    }
+
+    #[test]
+    fn table_template_renders_header_and_aligned_rows() {
+        let format = StatsFormat::parse("table {{.Name}}\\t{{.CommandsExecuted}}").unwrap();
+        let presenters = vec![StatsPresenter {
+            name: "web".into(),
+            commands_executed: 12,
+            ..Default::default()
+        }];
+        let mut out = Vec::new();
+        write_stats(&mut out, &presenters, &format).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "NAME   COMMANDS EXECUTED\nweb    12\n"
+        );
+    }
+
+    #[test]
+    fn json_includes_exec_and_network_counters() {
+        let json = serde_json::to_value(StatsPresenter {
+            network_tcp_connections: Some(3),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(json["CommandsExecuted"], 0);
+        assert_eq!(json["NetworkTcpConnections"], 3);
+        assert!(json["CpuPercent"].is_null());
+        assert!(json.get("cpu").is_none());
+    }
+
+    #[test]
+    fn json_includes_swap_and_label_counters() {
+        let json = serde_json::to_value(StatsPresenter {
+            swap_used_bytes: Some(4096),
+            labeled: BTreeMap::from([(
+                "build".to_string(),
+                LabelStats {
+                    commands_executed: 5,
+                    exec_errors: 1,
+                },
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(json["SwapUsedBytes"], 4096);
+        assert!(json["SwapOutBytes"].is_null());
+        assert_eq!(json["Labeled"]["build"]["CommandsExecuted"], 5);
+        assert_eq!(json["Labeled"]["build"]["ExecErrors"], 1);
+    }
+}
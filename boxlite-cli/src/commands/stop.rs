@@ -1,3 +1,5 @@
+use crate::output::{self, outln};
+use crate::util::names;
 use clap::Args;
 
 #[derive(Args, Debug)]
@@ -12,13 +14,15 @@ pub async fn execute(args: StopArgs, global: &crate::cli::GlobalFlags) -> anyhow
 
     let mut errors = Vec::new();
     let mut success_count = 0;
+    let mut done = Vec::new();
 
     for target in args.targets {
         // Get the box first
-        let litebox = match runtime.get(&target).await? {
+        let litebox = match names::get_box(&runtime, &target, global.fuzzy_names).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: No such box: {}", target);
+                let message = names::no_such_box(&runtime, &target, global.fuzzy_names).await;
+                eprintln!("Error: {}", message);
                 errors.push(format!("{}: not found", target));
                 continue;
             }
@@ -28,11 +32,14 @@ pub async fn execute(args: StopArgs, global: &crate::cli::GlobalFlags) -> anyhow
             eprintln!("Error stopping box '{}': {}", target, e);
             errors.push(format!("{}: {}", target, e));
         } else {
-            println!("{}", target);
+            outln!("{}", target);
+            done.push(target);
             success_count += 1;
         }
     }
 
+    output::set_result(&done);
+
     if !errors.is_empty() {
         let error_summary = if success_count > 0 {
             format!(
@@ -0,0 +1,202 @@
+//! Two-way sync of a host directory with a box directory.
+
+use crate::cli::GlobalFlags;
+use crate::formatter;
+use crate::output::{self, outln};
+use crate::util::names;
+use anyhow::{Result, anyhow};
+use boxlite::{ConflictPolicy, LiteBox, SyncOptions, SyncStatus};
+use clap::{Args, ValueEnum};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Keep a host directory and a box directory in sync, both ways
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Directories to sync, as HOST_DIR:BOX_DIR
+    #[arg(value_name = "HOST_DIR:BOX_DIR")]
+    pub dirs: String,
+
+    /// Which side wins when a file changed on both
+    #[arg(long, value_enum, default_value_t = ConflictArg::Newer)]
+    pub conflict: ConflictArg,
+
+    /// Seconds between two sync rounds
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    /// Skip paths matched by .boxliteignore and .gitignore files in the host directory
+    #[arg(long, default_value_t = false)]
+    pub respect_ignore: bool,
+
+    /// Sync once and exit instead of watching for changes
+    #[arg(long)]
+    pub once: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ConflictArg {
+    /// Keep the version modified last
+    Newer,
+    /// Keep the host version
+    Host,
+    /// Keep the box version
+    Guest,
+    /// Leave both and report the conflict
+    Skip,
+}
+
+impl From<ConflictArg> for ConflictPolicy {
+    fn from(arg: ConflictArg) -> Self {
+        match arg {
+            ConflictArg::Newer => ConflictPolicy::Newer,
+            ConflictArg::Host => ConflictPolicy::Host,
+            ConflictArg::Guest => ConflictPolicy::Guest,
+            ConflictArg::Skip => ConflictPolicy::Skip,
+        }
+    }
+}
+
+pub async fn execute(args: SyncArgs, global: &GlobalFlags) -> Result<()> {
+    let (host_dir, box_dir) = parse_dirs(&args.dirs)?;
+    let rt = global.create_runtime()?;
+    let handle = names::require_box(&rt, &args.target, global.fuzzy_names).await?;
+
+    let opts = SyncOptions::default()
+        .interval(Duration::from_secs(args.interval))
+        .conflict(args.conflict.into())
+        .respect_ignore_files(args.respect_ignore);
+
+    let was_running = handle.info().status.is_running();
+    let result = if args.once {
+        handle
+            .sync_once(&host_dir, &box_dir, opts)
+            .await
+            .map(|status| {
+                print_round(&status, &SyncStatus::default());
+                output::set_result(&status);
+            })
+            .map_err(Into::into)
+    } else {
+        watch(&handle, &host_dir, &box_dir, opts, args.interval).await
+    };
+    if !was_running {
+        handle.stop().await?;
+    }
+    result
+}
+
+/// Sync until Ctrl-C or the box stops, printing each round's changes.
+async fn watch(
+    handle: &LiteBox,
+    host_dir: &Path,
+    box_dir: &str,
+    opts: SyncOptions,
+    interval: u64,
+) -> Result<()> {
+    let sync = handle.sync(host_dir, box_dir, opts).await?;
+    outln!(
+        "Syncing {} with {} (Ctrl-C to stop)",
+        host_dir.display(),
+        box_dir
+    );
+
+    let mut reported = SyncStatus::default();
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+    let interrupted = loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break true,
+        }
+        let status = sync.status();
+        if status.rounds != reported.rounds || status.last_error != reported.last_error {
+            print_round(&status, &reported);
+            reported = status;
+        }
+        if !reported.active {
+            break false;
+        }
+    };
+
+    let status = sync.stop().await;
+    output::set_result(&status);
+    if interrupted {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "sync ended: {}",
+            status.last_error.as_deref().unwrap_or("box stopped")
+        ))
+    }
+}
+
+/// Print what changed since `previous`, if anything.
+fn print_round(status: &SyncStatus, previous: &SyncStatus) {
+    if let Some(e) = &status.last_error
+        && previous.last_error.as_ref() != Some(e)
+    {
+        outln!("Sync failed: {}", e);
+    }
+    let pushed = status.pushed - previous.pushed;
+    let pulled = status.pulled - previous.pulled;
+    let removed_in_guest = status.removed_in_guest - previous.removed_in_guest;
+    let removed_on_host = status.removed_on_host - previous.removed_on_host;
+    if pushed + removed_in_guest > 0 {
+        outln!(
+            "-> box: {} updated ({}), {} removed",
+            pushed,
+            formatter::format_size(status.bytes_pushed - previous.bytes_pushed),
+            removed_in_guest
+        );
+    }
+    if pulled + removed_on_host > 0 {
+        outln!(
+            "<- box: {} updated ({}), {} removed",
+            pulled,
+            formatter::format_size(status.bytes_pulled - previous.bytes_pulled),
+            removed_on_host
+        );
+    }
+    if !status.conflicts.is_empty() && status.conflicts != previous.conflicts {
+        outln!("Conflicts: {}", status.conflicts.join(", "));
+    }
+}
+
+/// Split `HOST_DIR:BOX_DIR` at the colon before the absolute box path.
+fn parse_dirs(input: &str) -> Result<(PathBuf, String)> {
+    let idx = input
+        .rfind(":/")
+        .ok_or_else(|| anyhow!("expected HOST_DIR:BOX_DIR with an absolute BOX_DIR"))?;
+    let (host, guest) = (&input[..idx], &input[idx + 1..]);
+    if host.is_empty() {
+        return Err(anyhow!("host directory cannot be empty"));
+    }
+    Ok((PathBuf::from(host), guest.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_host_and_box_dirs() {
+        let (host, guest) = parse_dirs("./src:/workspace").unwrap();
+        assert_eq!(host, PathBuf::from("./src"));
+        assert_eq!(guest, "/workspace");
+
+        let (host, guest) = parse_dirs("/tmp/a:b:/app/src").unwrap();
+        assert_eq!(host, PathBuf::from("/tmp/a:b"));
+        assert_eq!(guest, "/app/src");
+    }
+
+    #[test]
+    fn reject_relative_or_missing_dirs() {
+        assert!(parse_dirs("./src:workspace").is_err());
+        assert!(parse_dirs("./src").is_err());
+        assert!(parse_dirs(":/workspace").is_err());
+    }
+}
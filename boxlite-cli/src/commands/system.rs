@@ -0,0 +1,154 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::io::Write;
+use tabled::Tabled;
+
+/// Manage the runtime's host resources
+#[derive(Args, Debug)]
+pub struct SystemArgs {
+    #[command(subcommand)]
+    pub command: SystemCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SystemCommand {
+    /// Show host disk space used by images and boxes
+    Df(DfArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DfArgs {
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table")]
+    pub format: String,
+}
+
+#[derive(Tabled, Serialize)]
+struct DiskUsagePresenter {
+    #[tabled(rename = "TYPE")]
+    #[serde(rename = "Type")]
+    kind: String,
+
+    #[tabled(rename = "TOTAL")]
+    #[serde(rename = "TotalCount")]
+    total: usize,
+
+    #[tabled(rename = "ACTIVE")]
+    #[serde(rename = "Active")]
+    active: usize,
+
+    #[tabled(rename = "SIZE")]
+    #[serde(skip)]
+    size: String,
+
+    #[tabled(rename = "VIRTUAL")]
+    #[serde(skip)]
+    virtual_size: String,
+
+    #[tabled(rename = "RECLAIMABLE")]
+    #[serde(skip)]
+    reclaimable: String,
+
+    #[tabled(skip)]
+    #[serde(rename = "SizeBytes")]
+    size_bytes: u64,
+
+    #[tabled(skip)]
+    #[serde(rename = "VirtualBytes", skip_serializing_if = "Option::is_none")]
+    virtual_bytes: Option<u64>,
+
+    #[tabled(skip)]
+    #[serde(rename = "ReclaimableBytes")]
+    reclaimable_bytes: u64,
+}
+
+impl DiskUsagePresenter {
+    fn new(
+        kind: &str,
+        total: usize,
+        active: usize,
+        size_bytes: u64,
+        virtual_bytes: Option<u64>,
+        reclaimable_bytes: u64,
+    ) -> Self {
+        Self {
+            kind: kind.to_string(),
+            total,
+            active,
+            size: formatter::format_size(size_bytes),
+            virtual_size: virtual_bytes
+                .map(formatter::format_size)
+                .unwrap_or("-".into()),
+            reclaimable: formatter::format_size(reclaimable_bytes),
+            size_bytes,
+            virtual_bytes,
+            reclaimable_bytes,
+        }
+    }
+}
+
+pub async fn execute(args: SystemArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        SystemCommand::Df(args) => df(args, global).await,
+    }
+}
+
+async fn df(args: DfArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let format = OutputFormat::from_str(&args.format)?;
+    let rt = global.create_runtime()?;
+
+    let images = rt.list_images().await?;
+    let disk_size = |info: &boxlite::runtime::types::ImageInfo| {
+        info.disk_size.map(|s| s.as_bytes()).unwrap_or(0)
+    };
+    let images_row = DiskUsagePresenter::new(
+        "Images",
+        images.len(),
+        images.iter().filter(|info| info.in_use_by > 0).count(),
+        images.iter().map(disk_size).sum(),
+        None,
+        images
+            .iter()
+            .filter(|info| info.is_dangling())
+            .map(disk_size)
+            .sum(),
+    );
+
+    let boxes = rt.list_info().await?;
+    let (mut allocated, mut virtual_bytes, mut reclaimable, mut active) = (0, 0, 0, 0);
+    for info in &boxes {
+        let Some(litebox) = rt.get(info.id.as_str()).await? else {
+            continue;
+        };
+        let usage = litebox.disk_usage()?;
+        allocated += usage.allocated_bytes();
+        virtual_bytes += usage.virtual_bytes();
+        if info.status.is_running() {
+            active += 1;
+        } else {
+            reclaimable += usage.allocated_bytes();
+        }
+    }
+    let boxes_row = DiskUsagePresenter::new(
+        "Boxes",
+        boxes.len(),
+        active,
+        allocated,
+        Some(virtual_bytes),
+        reclaimable,
+    );
+
+    let rows = vec![images_row, boxes_row];
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &rows,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
@@ -61,6 +61,108 @@ mod tests {
         assert_eq!(config.image_registries, vec!["docker.io"]);
     }
 
+    #[test]
+    fn test_load_config_with_image_verification() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{"image_verification": {"mode": "enforce", "public_keys": ["/etc/boxlite/cosign.pub"]}}"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(
+            config.image_verification.mode,
+            boxlite::ImageVerificationMode::Enforce
+        );
+        assert_eq!(
+            config.image_verification.public_keys,
+            vec![PathBuf::from("/etc/boxlite/cosign.pub")]
+        );
+    }
+
+    #[test]
+    fn test_load_config_with_storage_driver() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{"storage_driver": "erofs"}"#).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(config.storage_driver, boxlite::StorageDriver::Erofs);
+    }
+
+    #[test]
+    fn test_load_config_with_registry_retry() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        fs::write(&config_path, r#"{"registry_retry": {"max_attempts": 2}}"#).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(config.registry_retry.max_attempts, 2);
+        // Unset fields keep their defaults
+        assert_eq!(config.registry_retry.initial_backoff_ms, 500);
+    }
+
+    #[test]
+    fn test_load_config_with_registry_tls() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{"registry_ca_bundle": "/etc/boxlite/ca.pem", "insecure_registries": ["localhost:5000"]}"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(
+            config.registry_ca_bundle,
+            Some(PathBuf::from("/etc/boxlite/ca.pem"))
+        );
+        assert_eq!(config.insecure_registries, vec!["localhost:5000"]);
+    }
+
+    #[test]
+    fn test_load_config_with_mirrors() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{"registry_mirrors": {"docker.io": ["mirror.gcr.io"]}, "blob_cache_dir": "/var/cache/boxlite"}"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(config.registry_mirrors["docker.io"], vec!["mirror.gcr.io"]);
+        assert_eq!(
+            config.blob_cache_dir,
+            Some(PathBuf::from("/var/cache/boxlite"))
+        );
+    }
+
+    #[test]
+    fn test_load_config_with_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{"hooks": {"post_start": [{"path": "/usr/local/bin/register", "args": ["--box"], "timeout_secs": 5}]}}"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        let hook = &config.hooks.post_start[0];
+        assert_eq!(hook.path, PathBuf::from("/usr/local/bin/register"));
+        assert_eq!(hook.args, vec!["--box"]);
+        assert_eq!(hook.timeout_secs, Some(5));
+        assert!(config.hooks.pre_create.is_empty());
+    }
+
+    #[test]
+    fn test_load_config_with_box_defaults() {
+        use boxlite::runtime::options::{ImagePullPolicy, SecurityPreset};
+
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{"default_security": "maximum", "default_pull_policy": "always"}"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(config.default_security, Some(SecurityPreset::Maximum));
+        assert_eq!(config.default_pull_policy, Some(ImagePullPolicy::Always));
+
+        fs::write(&config_path, r#"{"default_security": "paranoid"}"#).unwrap();
+        assert!(load_config(&config_path).is_err());
+    }
+
     #[test]
     fn test_load_empty_config() {
         let temp_dir = TempDir::new().unwrap();
@@ -71,6 +173,7 @@ mod tests {
         let config = load_config(&config_path).unwrap();
         // home_dir gets a default value, image_registries is empty
         assert!(config.image_registries.is_empty());
+        assert_eq!(config.storage_driver, boxlite::StorageDriver::Ext4);
     }
 
     #[test]
@@ -0,0 +1,36 @@
+//! Allow/deny prompts for outbound connections.
+//!
+//! Driven by `BoxliteRuntime::on_connection_prompt` for boxes run with
+//! `--prompt-connections`; asks on stderr and reads the answer from stdin.
+
+use boxlite::{BoxliteRuntime, ConnectionDecision, ConnectionRequest};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// Ask on the terminal about each new destination of boxes run by `rt`.
+pub fn attach(rt: &BoxliteRuntime) {
+    // One question at a time, even when several connections wait
+    let asking = Mutex::new(());
+    rt.on_connection_prompt(move |request| {
+        let _asking = asking.lock().unwrap_or_else(|e| e.into_inner());
+        ask(request).unwrap_or(ConnectionDecision::Deny)
+    });
+}
+
+fn ask(request: &ConnectionRequest) -> io::Result<ConnectionDecision> {
+    let name = request.box_name.as_deref().unwrap_or(&request.box_id);
+    eprint!(
+        "Box {} wants to connect to {} ({}). Allow? [y/N] ",
+        name,
+        request.address,
+        request.protocol.as_str()
+    );
+    io::stderr().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(if input.trim().eq_ignore_ascii_case("y") {
+        ConnectionDecision::Allow
+    } else {
+        ConnectionDecision::Deny
+    })
+}
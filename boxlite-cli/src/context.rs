@@ -0,0 +1,175 @@
+//! Named local CLI contexts.
+//!
+//! A context names the local home directory, config file and project commands
+//! run against, so switching between per-repo homes is `boxlite context use`
+//! instead of repeating `--home`/`--config`/`--project`. Contexts are kept
+//! in `contexts.json` under `$BOXLITE_CONFIG_DIR`, or the platform config
+//! dir (`~/.config/boxlite` on Linux).
+
+use anyhow::{Context as _, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the implicit context that changes nothing.
+pub const DEFAULT_CONTEXT: &str = "default";
+
+/// What a context sets; flags given on the command line take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Context {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+/// The contexts file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ContextStore {
+    /// Context used when `--context` isn't given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    #[serde(default)]
+    pub contexts: BTreeMap<String, Context>,
+}
+
+impl ContextStore {
+    /// Load the contexts file; empty if there is none yet.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&contexts_path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse contexts file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read contexts file {}", path.display()))
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&contexts_path()?)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content + "\n")
+            .with_context(|| format!("Failed to write contexts file {}", path.display()))
+    }
+
+    /// Name of the context in effect, given `--context`.
+    pub fn current_name<'a>(&'a self, flag: Option<&'a str>) -> &'a str {
+        flag.or(self.current.as_deref()).unwrap_or(DEFAULT_CONTEXT)
+    }
+
+    /// The context called `name`; `None` for the default context.
+    pub fn get(&self, name: &str) -> Result<Option<&Context>> {
+        if name == DEFAULT_CONTEXT {
+            return Ok(None);
+        }
+        match self.contexts.get(name) {
+            Some(context) => Ok(Some(context)),
+            None => bail!("context '{}' not found", name),
+        }
+    }
+
+    pub fn create(&mut self, name: &str, context: Context) -> Result<()> {
+        validate_name(name)?;
+        if self.contexts.contains_key(name) {
+            bail!("context '{}' already exists", name);
+        }
+        self.contexts.insert(name.to_string(), context);
+        Ok(())
+    }
+
+    /// Make `name` the context used without `--context`.
+    pub fn use_context(&mut self, name: &str) -> Result<()> {
+        self.get(name)?;
+        self.current = (name != DEFAULT_CONTEXT).then(|| name.to_string());
+        Ok(())
+    }
+
+    /// Remove a context; removing the current one switches back to default.
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        if self.contexts.remove(name).is_none() {
+            bail!("context '{}' not found", name);
+        }
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name == DEFAULT_CONTEXT {
+        bail!("'{}' is the built-in context", DEFAULT_CONTEXT);
+    }
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!(
+            "invalid context name '{}': use letters, digits, '-', '_' and '.'",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn contexts_path() -> Result<PathBuf> {
+    let dir = match std::env::var_os("BOXLITE_CONFIG_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::config_dir()
+            .context("no config directory; set BOXLITE_CONFIG_DIR")?
+            .join("boxlite"),
+    };
+    Ok(dir.join("contexts.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_use_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("contexts.json");
+
+        let mut store = ContextStore::load_from(&path).unwrap();
+        assert_eq!(store.current_name(None), DEFAULT_CONTEXT);
+        let repo = Context {
+            home: Some("/work/repo/.boxlite".into()),
+            ..Default::default()
+        };
+        store.create("repo", repo.clone()).unwrap();
+        assert!(store.create("repo", Context::default()).is_err());
+        assert!(store.create("default", Context::default()).is_err());
+        assert!(store.create("a/b", Context::default()).is_err());
+        store.use_context("repo").unwrap();
+        assert!(store.use_context("missing").is_err());
+        store.save_to(&path).unwrap();
+
+        let mut store = ContextStore::load_from(&path).unwrap();
+        assert_eq!(store.current_name(None), "repo");
+        assert_eq!(store.current_name(Some("default")), DEFAULT_CONTEXT);
+        assert_eq!(store.get("repo").unwrap(), Some(&repo));
+        assert_eq!(store.get(DEFAULT_CONTEXT).unwrap(), None);
+
+        store.remove("repo").unwrap();
+        assert_eq!(store.current, None);
+        assert!(store.remove("repo").is_err());
+    }
+}
@@ -206,6 +206,11 @@ where
     W: std::io::Write,
     F: FnOnce(&mut W, &T) -> Result<()>,
 {
+    // `--output json` reports the data as the command's result instead
+    if crate::output::is_json() {
+        crate::output::set_result(data);
+        return Ok(());
+    }
     match format {
         OutputFormat::Table => {
             table_printer(writer, data)?;
@@ -234,6 +239,44 @@ where
     t.format("%Y-%m-%d %H:%M:%S %Z").to_string()
 }
 
+/// Format a byte count for humans: decimal units, 3 significant digits
+/// (e.g., `7.81MB`), like `docker images`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "kB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    let int_digits = (size as u64).to_string().len();
+    let mut number = format!("{:.*}", 3usize.saturating_sub(int_digits), size);
+    if number.contains('.') {
+        number = number
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+    }
+    number + UNITS[unit]
+}
+
+/// Format the time left until a deadline, to the two largest units
+/// (e.g., `1h59m`, `4m10s`). A deadline that has passed is `now`.
+pub fn format_remaining(remaining: chrono::TimeDelta) -> String {
+    let secs = remaining.num_seconds();
+    if secs <= 0 {
+        return "now".to_string();
+    }
+    let (d, h, m, s) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+    match (d, h, m) {
+        (0, 0, 0) => format!("{}s", s),
+        (0, 0, _) => format!("{}m{}s", m, s),
+        (0, _, _) => format!("{}h{}m", h, m),
+        _ => format!("{}d{}h", d, h),
+    }
+}
+
 /// Create a standard table with Boxlite styling.
 pub fn create_table<T: Tabled>(data: impl IntoIterator<Item = T>) -> Table {
     let mut table = Table::new(data);
@@ -443,4 +486,24 @@ mod tests {
         assert_eq!(render_gtmpl(&json, "{{index . 1}}"), "20");
         assert_eq!(render_gtmpl(&json, "{{index . 2}}"), "30");
     }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0B");
+        assert_eq!(format_size(999), "999B");
+        assert_eq!(format_size(1500), "1.5kB");
+        assert_eq!(format_size(7_812_345), "7.81MB");
+        assert_eq!(format_size(123_456_789), "123MB");
+        assert_eq!(format_size(2_000_000_000), "2GB");
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        let secs = chrono::TimeDelta::seconds;
+        assert_eq!(format_remaining(secs(-5)), "now");
+        assert_eq!(format_remaining(secs(42)), "42s");
+        assert_eq!(format_remaining(secs(250)), "4m10s");
+        assert_eq!(format_remaining(secs(7_140)), "1h59m");
+        assert_eq!(format_remaining(secs(97_200)), "1d3h");
+    }
 }
@@ -1,7 +1,11 @@
 mod cli;
 mod commands;
 mod config;
+mod connection_prompt;
+mod context;
 mod formatter;
+mod output;
+mod progress;
 pub mod terminal;
 pub mod util;
 
@@ -23,11 +27,29 @@ fn main() {
         }
     }
 
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            // Help and version requests are printed as usual; usage errors are
+            // reported as JSON when `--output json` appears on the command line.
+            if output::json_requested(std::env::args()) && error.use_stderr() {
+                output::set_mode(output::OutputMode::Json);
+                process::exit(output::finish(Err(error.into())));
+            }
+            error.exit();
+        }
+    };
+    output::set_mode(cli.global.output);
 
     // Handle shell completion before starting tokio or tracing
     if let cli::Commands::Completion(args) = &cli.command {
         let mut cmd = Cli::command();
+        if output::is_json() {
+            let mut script = Vec::new();
+            cli::generate_completion(&args.shell, &mut cmd, "boxlite", &mut script);
+            output::set_result(&String::from_utf8_lossy(&script));
+            process::exit(output::finish(Ok(())));
+        }
         cli::generate_completion(&args.shell, &mut cmd, "boxlite", &mut std::io::stdout());
         process::exit(0);
     }
@@ -38,7 +60,9 @@ fn main() {
         .build()
         .expect("Failed to build tokio runtime");
 
-    let _ = rt.block_on(run_cli(cli));
+    let code = output::finish(rt.block_on(run_cli(cli)));
+    drop(rt);
+    process::exit(code);
 }
 
 async fn run_cli(cli: Cli) -> anyhow::Result<()> {
@@ -53,10 +77,18 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
         .with(fmt::layer().with_writer(std::io::stderr))
         .init();
 
-    let global = cli.global;
-    let result = match cli.command {
+    // Context commands manage the contexts, so they don't run in one
+    let global = if matches!(cli.command, cli::Commands::Context(_)) {
+        cli.global
+    } else {
+        cli.global.with_context()?
+    };
+    match cli.command {
         cli::Commands::Run(args) => commands::run::execute(args, &global).await,
         cli::Commands::Exec(args) => commands::exec::execute(args, &global).await,
+        cli::Commands::Attach(args) => commands::attach::execute(args, &global).await,
+        cli::Commands::ExecLogs(args) => commands::exec_logs::execute(args, &global).await,
+        cli::Commands::ExecLs(args) => commands::exec_ls::execute(args, &global).await,
         cli::Commands::Create(args) => commands::create::execute(args, &global).await,
         cli::Commands::List(args) => commands::list::execute(args, &global).await,
         cli::Commands::Rm(args) => commands::rm::execute(args, &global).await,
@@ -65,18 +97,26 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
         cli::Commands::Restart(args) => commands::restart::execute(args, &global).await,
         cli::Commands::Pull(args) => commands::pull::execute(args, &global).await,
         cli::Commands::Images(args) => commands::images::execute(args, &global).await,
+        cli::Commands::Image(args) => commands::image::execute(args, &global).await,
+        cli::Commands::Tag(args) => commands::image::tag(args, &global).await,
+        cli::Commands::Rmi(args) => commands::image::remove(args, &global).await,
+        cli::Commands::Network(args) => commands::network::execute(args, &global).await,
+        cli::Commands::Project(args) => commands::project::execute(args, &global).await,
+        cli::Commands::Context(args) => commands::context::execute(args, &global).await,
+        cli::Commands::Info(args) => commands::info::execute(args, &global).await,
+        cli::Commands::System(args) => commands::system::execute(args, &global).await,
+        cli::Commands::Doctor(args) => commands::doctor::execute(args, &global).await,
+        cli::Commands::SelfTest(args) => commands::self_test::execute(args, &global).await,
         cli::Commands::Inspect(args) => commands::inspect::execute(args, &global).await,
+        cli::Commands::Stats(args) => commands::stats::execute(args, &global).await,
         cli::Commands::Cp(args) => commands::cp::execute(args, &global).await,
+        cli::Commands::Sync(args) => commands::sync::execute(args, &global).await,
+        cli::Commands::Provision(args) => commands::provision::execute(args, &global).await,
+        cli::Commands::Schema(args) => commands::schema::execute(args).await,
+        cli::Commands::Debug(args) => commands::debug::execute(args, &global).await,
         // Handled in main() before tokio; never reaches run_cli
         cli::Commands::Completion(_) => {
             unreachable!("completion subcommand is handled before tokio in main()")
         }
-    };
-
-    if let Err(error) = result {
-        eprintln!("Error: {}", error);
-        process::exit(1);
     }
-
-    Ok(())
 }
@@ -0,0 +1,194 @@
+//! Global output mode (`--output text|json`).
+//!
+//! In JSON mode every command writes exactly one JSON object to stdout:
+//! `{"result": ...}` on success or `{"error": {"code": ..., "message": ...}}`
+//! on failure. Everything meant for humans (including the output of commands
+//! run in a box) goes to stderr instead.
+
+use boxlite::BoxliteError;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputMode {
+    /// Human-readable output
+    #[default]
+    Text,
+    /// A single JSON object on stdout; human text on stderr
+    Json,
+}
+
+static MODE: OnceLock<OutputMode> = OnceLock::new();
+static RESULT: Mutex<Option<serde_json::Value>> = Mutex::new(None);
+
+/// Set the output mode for this process. Only the first call has an effect.
+pub fn set_mode(mode: OutputMode) {
+    let _ = MODE.set(mode);
+}
+
+pub fn is_json() -> bool {
+    MODE.get() == Some(&OutputMode::Json)
+}
+
+/// Whether `--output json` appears in `args`.
+///
+/// Used to report argument parsing errors, before the flag's value is known.
+pub fn json_requested(args: impl IntoIterator<Item = String>) -> bool {
+    let mut prev = None;
+    for arg in args {
+        // Everything after `--` belongs to the command run in the box
+        if arg == "--" {
+            break;
+        }
+        if arg == "--output=json" || (prev.as_deref() == Some("--output") && arg == "json") {
+            return true;
+        }
+        prev = Some(arg);
+    }
+    false
+}
+
+/// Record the command's result, reported as `result` in JSON mode.
+///
+/// Later calls replace earlier ones. A no-op in text mode.
+pub fn set_result<T: Serialize + ?Sized>(value: &T) {
+    if !is_json() {
+        return;
+    }
+    let value = serde_json::to_value(value).unwrap_or_else(|e| {
+        serde_json::Value::String(format!("failed to serialize result: {}", e))
+    });
+    *RESULT.lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+}
+
+/// Print a line for humans: stdout in text mode, stderr in JSON mode.
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use outln;
+
+/// Report how the command went and return the process exit code.
+pub fn finish(result: anyhow::Result<()>) -> i32 {
+    match result {
+        Ok(()) => {
+            if is_json() {
+                let value = RESULT.lock().unwrap_or_else(|e| e.into_inner()).take();
+                print_json(&serde_json::json!({ "result": value }));
+            }
+            0
+        }
+        Err(error) => {
+            if is_json() {
+                print_json(&error_json(&error));
+            } else {
+                eprintln!("Error: {}", error);
+            }
+            1
+        }
+    }
+}
+
+/// Exit with `code`, first printing the recorded result in JSON mode.
+///
+/// For commands that exit with the status of a process run in the box.
+pub fn exit(code: i32) -> ! {
+    if is_json() {
+        let value = RESULT.lock().unwrap_or_else(|e| e.into_inner()).take();
+        print_json(&serde_json::json!({ "result": value }));
+    }
+    std::process::exit(code)
+}
+
+fn print_json(value: &serde_json::Value) {
+    println!("{}", value);
+}
+
+fn error_json(error: &anyhow::Error) -> serde_json::Value {
+    serde_json::json!({
+        "error": {
+            "code": error_code(error),
+            "message": error.to_string(),
+        }
+    })
+}
+
+/// Stable, machine-readable code of an error.
+pub fn error_code(error: &anyhow::Error) -> &'static str {
+    // Commands often add context on top of the runtime's error
+    for cause in error.chain() {
+        if cause.is::<clap::Error>() {
+            return "usage";
+        }
+        if let Some(e) = cause.downcast_ref::<BoxliteError>() {
+            return boxlite_error_code(e);
+        }
+    }
+    "error"
+}
+
+fn boxlite_error_code(e: &BoxliteError) -> &'static str {
+    match e {
+        BoxliteError::UnsupportedEngine => "unsupported_engine",
+        BoxliteError::Engine(_) => "engine",
+        BoxliteError::Config(_) => "config",
+        BoxliteError::Storage(_) => "storage",
+        BoxliteError::Image(_) => "image",
+        BoxliteError::Portal(_) => "portal",
+        BoxliteError::Network(_) => "network",
+        BoxliteError::Rpc(_) => "rpc",
+        BoxliteError::RpcTransport(_) => "rpc_transport",
+        BoxliteError::Internal(_) => "internal",
+        BoxliteError::Execution(_) => "execution",
+        BoxliteError::Unsupported(_) => "unsupported",
+        BoxliteError::NotFound(_) => "not_found",
+        BoxliteError::AlreadyExists(_) => "already_exists",
+        BoxliteError::InvalidState(_) => "invalid_state",
+        BoxliteError::Database(_) => "database",
+        BoxliteError::MetadataError(_) => "metadata",
+        BoxliteError::InvalidArgument(_) => "invalid_argument",
+        BoxliteError::Stopped(_) => "stopped",
+        BoxliteError::ResourceExhausted(_) => "resource_exhausted",
+        BoxliteError::WorkdirNotFound(_) => "workdir_not_found",
+        BoxliteError::TooManyExecs(_) => "too_many_execs",
+        BoxliteError::Cancelled(_) => "cancelled",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_json() {
+        let error = anyhow::Error::from(BoxliteError::NotFound("web".into()));
+        let value = error_json(&error);
+        assert_eq!(value["error"]["code"], "not_found");
+        assert_eq!(value["error"]["message"], "box not found: web");
+
+        let error = anyhow::anyhow!("No such box: web");
+        assert_eq!(error_code(&error), "error");
+
+        let error = anyhow::Error::from(BoxliteError::NotFound("web".into()))
+            .context("Failed to start box");
+        assert_eq!(error_code(&error), "not_found");
+    }
+
+    #[test]
+    fn test_json_requested() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert!(json_requested(args("boxlite --output json ls")));
+        assert!(json_requested(args("boxlite ls --output=json")));
+        assert!(!json_requested(args("boxlite --output text ls")));
+        assert!(!json_requested(args("boxlite exec web echo json")));
+        assert!(!json_requested(args(
+            "boxlite exec web -- tool --output json"
+        )));
+    }
+}
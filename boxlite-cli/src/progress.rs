@@ -0,0 +1,202 @@
+//! Progress bars for image pulls and box boot.
+//!
+//! Driven by `BoxliteRuntime::on_progress`; drawn on stderr and only shown
+//! when stdout is a terminal.
+
+use boxlite::{BoxliteRuntime, ProgressEvent};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+
+/// Show pull and boot progress for operations on `rt`, unless `quiet`,
+/// `--output json` or stdout isn't a terminal.
+pub fn attach(rt: &BoxliteRuntime, quiet: bool) {
+    if quiet || crate::output::is_json() || !std::io::stdout().is_terminal() {
+        return;
+    }
+    let display = Arc::new(ProgressDisplay::default());
+    rt.on_progress(move |event| display.handle(event));
+}
+
+struct ProgressDisplay {
+    multi: MultiProgress,
+    /// Layer bars by digest
+    layers: Mutex<HashMap<String, ProgressBar>>,
+    /// Boot bars by box ID
+    boots: Mutex<HashMap<String, ProgressBar>>,
+}
+
+impl Default for ProgressDisplay {
+    fn default() -> Self {
+        Self {
+            multi: MultiProgress::with_draw_target(ProgressDrawTarget::stderr()),
+            layers: Mutex::default(),
+            boots: Mutex::default(),
+        }
+    }
+}
+
+impl ProgressDisplay {
+    fn handle(&self, event: &ProgressEvent) {
+        match event {
+            ProgressEvent::PullStarted { image, layers } => {
+                let _ = self
+                    .multi
+                    .println(format!("Pulling {} ({} layers)", image, layers));
+            }
+            ProgressEvent::LayerProgress {
+                digest,
+                current,
+                total,
+                ..
+            } => {
+                let bar = self.layer_bar(digest, *total);
+                bar.set_position(*current);
+            }
+            ProgressEvent::LayerDone { digest, .. } => {
+                let bar = self.layer_bar(digest, 0);
+                bar.finish_with_message("done");
+            }
+            ProgressEvent::PullDone { .. } => {
+                for (_, bar) in self.layers.lock().unwrap().drain() {
+                    bar.finish_and_clear();
+                }
+            }
+            ProgressEvent::BootStep {
+                box_id,
+                name,
+                step,
+                steps,
+            } => {
+                let mut boots = self.boots.lock().unwrap();
+                let bar = boots.entry(box_id.clone()).or_insert_with(|| {
+                    let bar = self.multi.add(ProgressBar::new(*steps as u64));
+                    bar.set_style(
+                        ProgressStyle::with_template("{prefix} [{bar:20}] {pos}/{len} {msg}")
+                            .unwrap()
+                            .progress_chars("=> "),
+                    );
+                    bar.set_prefix(format!("Starting {}", short(box_id)));
+                    bar
+                });
+                bar.set_position(step.saturating_sub(1) as u64);
+                bar.set_message(boot_step_label(name));
+            }
+            ProgressEvent::BootDone { box_id } => {
+                if let Some(bar) = self.boots.lock().unwrap().remove(box_id) {
+                    bar.finish_and_clear();
+                }
+            }
+        }
+    }
+
+    fn layer_bar(&self, digest: &str, total: u64) -> ProgressBar {
+        let mut layers = self.layers.lock().unwrap();
+        layers
+            .entry(digest.to_string())
+            .or_insert_with(|| {
+                let bar = if total > 0 {
+                    let bar = self.multi.add(ProgressBar::new(total));
+                    bar.set_style(
+                        ProgressStyle::with_template(
+                            "{prefix} [{bar:30}] {bytes}/{total_bytes} {msg}",
+                        )
+                        .unwrap()
+                        .progress_chars("=> "),
+                    );
+                    bar
+                } else {
+                    let bar = self.multi.add(ProgressBar::no_length());
+                    bar.set_style(ProgressStyle::with_template("{prefix} {bytes} {msg}").unwrap());
+                    bar
+                };
+                bar.set_prefix(short(digest.trim_start_matches("sha256:")).to_string());
+                bar
+            })
+            .clone()
+    }
+}
+
+fn short(id: &str) -> &str {
+    &id[..id.len().min(12)]
+}
+
+/// Human-readable name of a boot step.
+fn boot_step_label(name: &str) -> &str {
+    match name {
+        "filesystem_setup" => "preparing box directory",
+        "container_rootfs_prep" => "preparing image",
+        "guest_rootfs_init" => "preparing guest",
+        "vmm_spawn" => "starting VM",
+        "vmm_attach" => "attaching to VM",
+        "guest_connect" => "waiting for guest",
+        "guest_init" => "starting container",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hidden() -> ProgressDisplay {
+        ProgressDisplay {
+            multi: MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pull_bars_are_cleared_when_done() {
+        let display = hidden();
+        let digest = format!("sha256:{}", "a".repeat(64));
+        display.handle(&ProgressEvent::PullStarted {
+            image: "alpine:latest".into(),
+            layers: 1,
+        });
+        display.handle(&ProgressEvent::LayerProgress {
+            image: "alpine:latest".into(),
+            digest: digest.clone(),
+            current: 512,
+            total: 1024,
+        });
+
+        let bar = display.layers.lock().unwrap()[&digest].clone();
+        assert_eq!(bar.position(), 512);
+        assert_eq!(bar.length(), Some(1024));
+        assert_eq!(bar.prefix(), "a".repeat(12));
+
+        display.handle(&ProgressEvent::LayerDone {
+            image: "alpine:latest".into(),
+            digest,
+        });
+        assert!(bar.is_finished());
+        display.handle(&ProgressEvent::PullDone {
+            image: "alpine:latest".into(),
+        });
+        assert!(display.layers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_boot_bar_follows_steps() {
+        let display = hidden();
+        display.handle(&ProgressEvent::BootStep {
+            box_id: "01HZY".into(),
+            name: "vmm_spawn".into(),
+            step: 3,
+            steps: 6,
+        });
+
+        let bar = display.boots.lock().unwrap()["01HZY"].clone();
+        assert_eq!(bar.position(), 2);
+        assert_eq!(bar.length(), Some(6));
+        assert_eq!(bar.message(), "starting VM");
+
+        display.handle(&ProgressEvent::BootDone {
+            box_id: "01HZY".into(),
+        });
+        assert!(display.boots.lock().unwrap().is_empty());
+        assert!(bar.is_finished());
+    }
+}
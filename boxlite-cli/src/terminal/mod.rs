@@ -7,10 +7,21 @@ use nix::sys::termios::{
 };
 use std::io::IsTerminal;
 use std::os::fd::{AsFd, AsRawFd};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::pin::Pin;
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::select;
 use tokio::signal::unix::{SignalKind, signal};
 
+/// Where the execution's stdout goes: the CLI's stdout, or its stderr under
+/// `--output json` so that stdout only carries the JSON result.
+fn process_stdout() -> Pin<Box<dyn AsyncWrite + Send>> {
+    if crate::output::is_json() {
+        Box::pin(tokio::io::stderr())
+    } else {
+        Box::pin(tokio::io::stdout())
+    }
+}
+
 /// RAII guard to restore terminal mode on drop
 pub struct RawModeGuard {
     original_termios: Option<Termios>,
@@ -96,11 +107,20 @@ impl<'a> StreamManager<'a> {
             None
         };
 
+        // Without a terminal on either side, pass whole lines so characters
+        // split across chunks aren't mangled. Interactive sessions keep raw
+        // chunks: prompts don't end with a newline.
+        let lines = !self.tty && !self.interactive;
+
         // stdout
-        let stdout_stream = self.execution.stdout();
+        let stdout_stream = if lines {
+            self.execution.stdout_lines().map(StreamExt::boxed)
+        } else {
+            self.execution.stdout().map(StreamExt::boxed)
+        };
         let stdout_handle = tokio::spawn(async move {
             if let Some(mut stream) = stdout_stream {
-                let mut stdout = tokio::io::stdout();
+                let mut stdout = process_stdout();
                 while let Some(chunk) = stream.next().await {
                     if let Err(e) = stdout.write_all(chunk.as_bytes()).await {
                         if e.kind() != std::io::ErrorKind::BrokenPipe {
@@ -114,12 +134,16 @@ impl<'a> StreamManager<'a> {
         });
 
         // stderr
-        let stderr_stream = self.execution.stderr();
+        let stderr_stream = if lines {
+            self.execution.stderr_lines().map(StreamExt::boxed)
+        } else {
+            self.execution.stderr().map(StreamExt::boxed)
+        };
         let tty_mode = self.tty;
         let stderr_handle = tokio::spawn(async move {
             if let Some(mut stream) = stderr_stream {
                 let mut stderr = tokio::io::stderr();
-                let mut stdout = tokio::io::stdout();
+                let mut stdout = process_stdout();
 
                 while let Some(chunk) = stream.next().await {
                     let res = if tty_mode {
@@ -186,6 +210,13 @@ impl<'a> StreamManager<'a> {
                 res = self.execution.wait(), if exit_status.is_none() => {
                     match res {
                         Ok(status) => {
+                            // The program couldn't be run, e.g. "foo: command
+                            // not found" with exit code 127
+                            if matches!(status.exit_code, 126 | 127)
+                                && let Some(message) = &status.error_message
+                            {
+                                eprintln!("{}", message);
+                            }
                             exit_status = Some(status);
                             if let Some(h) = stdin_handle.as_ref() {
                                 h.abort();
@@ -0,0 +1,147 @@
+//! Box name resolution for commands taking a box name or ID.
+//!
+//! The runtime resolves names strictly. With `--fuzzy-names` the CLI also
+//! accepts a name that only differs in case (when one box matches), and a
+//! failed lookup suggests the closest box names.
+
+use boxlite::{BoxliteRuntime, LiteBox};
+
+/// Most names suggested for a failed lookup.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Look up a box by name or ID.
+///
+/// With `fuzzy`, a name matching exactly one box case-insensitively
+/// resolves to that box.
+pub async fn get_box(
+    rt: &BoxliteRuntime,
+    target: &str,
+    fuzzy: bool,
+) -> anyhow::Result<Option<LiteBox>> {
+    if let Some(litebox) = rt.get(target).await? {
+        return Ok(Some(litebox));
+    }
+    if !fuzzy {
+        return Ok(None);
+    }
+
+    let names = box_names(rt).await?;
+    let mut same_case = names
+        .iter()
+        .filter(|name| name.eq_ignore_ascii_case(target));
+    match (same_case.next(), same_case.next()) {
+        (Some(name), None) => Ok(rt.get(name).await?),
+        _ => Ok(None),
+    }
+}
+
+/// Message for a box that could not be found, with close names when `fuzzy`.
+pub async fn no_such_box(rt: &BoxliteRuntime, target: &str, fuzzy: bool) -> String {
+    let message = format!("No such box: {}", target);
+    if !fuzzy {
+        return message;
+    }
+    let names = box_names(rt).await.unwrap_or_default();
+    match suggestions(target, &names).as_slice() {
+        [] => message,
+        [name] => format!("{} (did you mean '{}'?)", message, name),
+        names => format!(
+            "{} (did you mean one of: {}?)",
+            message,
+            names
+                .iter()
+                .map(|name| format!("'{}'", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Look up a box, failing with [`no_such_box`] when it doesn't exist.
+pub async fn require_box(
+    rt: &BoxliteRuntime,
+    target: &str,
+    fuzzy: bool,
+) -> anyhow::Result<LiteBox> {
+    match get_box(rt, target, fuzzy).await? {
+        Some(litebox) => Ok(litebox),
+        None => Err(anyhow::anyhow!(no_such_box(rt, target, fuzzy).await)),
+    }
+}
+
+async fn box_names(rt: &BoxliteRuntime) -> anyhow::Result<Vec<String>> {
+    Ok(rt
+        .list_info()
+        .await?
+        .into_iter()
+        .filter_map(|info| info.name)
+        .collect())
+}
+
+/// Names close to `target`, closest first.
+///
+/// Names are compared lowercased and without `-`/`_`, so `mybox` finds
+/// `my-box`; a name is close if at most a third of it differs.
+fn suggestions(target: &str, names: &[String]) -> Vec<String> {
+    let wanted = normalize(target);
+    let mut scored: Vec<(usize, &String)> = names
+        .iter()
+        .filter_map(|name| {
+            let candidate = normalize(name);
+            let distance = edit_distance(&wanted, &candidate);
+            let allowed = (wanted.chars().count().max(candidate.chars().count()) / 3).max(1);
+            (distance <= allowed).then_some((distance, name))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name.clone())
+        .collect()
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("web", "web"), 0);
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let names: Vec<String> = ["my-box", "My_Box2", "web", "database"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(suggestions("mybox", &names), vec!["my-box", "My_Box2"]);
+        assert_eq!(suggestions("databse", &names), vec!["database"]);
+        assert!(suggestions("zzz", &names).is_empty());
+    }
+}
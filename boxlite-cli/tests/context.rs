@@ -0,0 +1,74 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_context_sets_project() {
+    let mut ctx = common::boxlite();
+    let config_dir = tempfile::tempdir().unwrap();
+    let name = "context-project-box";
+
+    ctx.cmd
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["--project", "ctx-team", "context", "create", "ctx-team"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["context", "use", "ctx-team"])
+        .assert()
+        .success();
+
+    // Boxes are created in the context's project
+    ctx.new_cmd()
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args(["--project", "ctx-team", "ls", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name));
+    ctx.new_cmd()
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["--context", "default", "--project", "other", "ls", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name).not());
+
+    let output = ctx
+        .new_cmd()
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["context", "ls", "--format", "json"])
+        .output()
+        .unwrap();
+    let contexts: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(contexts[0]["Name"], "default");
+    assert_eq!(contexts[1]["Name"], "ctx-team");
+    assert_eq!(contexts[1]["Current"], true);
+    assert_eq!(contexts[1]["Project"], "ctx-team");
+
+    ctx.new_cmd()
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["rm", "--force", name])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["context", "rm", "ctx-team"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_context_use_unknown() {
+    let config_dir = tempfile::tempdir().unwrap();
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .env("BOXLITE_CONFIG_DIR", config_dir.path())
+        .args(["context", "use", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("context 'missing' not found"));
+}
@@ -134,3 +134,23 @@ fn test_create_with_volume_invalid_format() {
         .failure()
         .stderr(predicate::str::contains("absolute"));
 }
+
+#[test]
+fn test_create_with_invalid_pull_policy() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["create", "--pull", "sometimes", "alpine:latest"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid pull policy"));
+}
+
+#[test]
+fn test_create_with_persistent_cache_requires_name() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["create", "--persistent-cache", "/cache", "alpine:latest"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--name"));
+}
@@ -0,0 +1,32 @@
+//! Tests for `boxlite debug`, which talks to a runtime in another process.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn boxlite(home: &std::path::Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("boxlite"));
+    cmd.arg("--home").arg(home);
+    cmd
+}
+
+#[test]
+fn test_set_log_level_without_runtime() {
+    let home = tempfile::tempdir().unwrap();
+    boxlite(home.path())
+        .args(["debug", "set-log-level", "debug"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no runtime handling log level requests",
+        ));
+}
+
+#[test]
+fn test_set_log_level_invalid_filter() {
+    let home = tempfile::tempdir().unwrap();
+    boxlite(home.path())
+        .args(["debug", "set-log-level", "boxlite=loud"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid log filter"));
+}
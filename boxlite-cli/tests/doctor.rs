@@ -0,0 +1,28 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_doctor_leaks() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["doctor", "--leaks"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("No leaks found").or(predicate::str::contains("LOCATION")),
+        );
+}
+
+#[test]
+fn test_doctor_json() {
+    let mut ctx = common::boxlite();
+    let output = ctx
+        .cmd
+        .args(["doctor", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let leaks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(leaks.is_array());
+}
@@ -133,6 +133,93 @@ fn test_exec_env_override() {
     cleanup(&ctx, &box_id);
 }
 
+#[test]
+fn test_exec_env_overrides_image_env() {
+    let mut ctx = common::boxlite();
+
+    // python:alpine sets PYTHON_VERSION in its image config
+    ctx.cmd.args(["run", "-d", "python:alpine", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    ctx.new_cmd()
+        .args([
+            "exec",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "test -n \"$PYTHON_VERSION\"",
+        ])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args([
+            "exec",
+            "-e",
+            "PYTHON_VERSION=custom",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "echo $PYTHON_VERSION",
+        ])
+        .assert()
+        .success()
+        .stdout("custom\n");
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_env_file() {
+    let mut ctx = common::boxlite();
+    let dir = tempfile::tempdir().unwrap();
+    let env_file = dir.path().join("exec.env");
+    std::fs::write(
+        &env_file,
+        "# overrides the image\nPYTHON_VERSION=from_file\nVAR=from_file\n",
+    )
+    .unwrap();
+
+    ctx.cmd.args(["run", "-d", "python:alpine", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // -e takes precedence over the env file, which takes precedence over the image
+    ctx.new_cmd()
+        .arg("exec")
+        .arg("--env-file")
+        .arg(&env_file)
+        .args([
+            "-e",
+            "VAR=from_flag",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "echo $PYTHON_VERSION $VAR",
+        ])
+        .assert()
+        .success()
+        .stdout("from_file from_flag\n");
+
+    ctx.new_cmd()
+        .args([
+            "exec",
+            "--env-file",
+            "/nonexistent/env",
+            &box_id,
+            "--",
+            "true",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to read env file"));
+
+    cleanup(&ctx, &box_id);
+}
+
 #[test]
 fn test_exec_inherits_box_workdir() {
     let mut ctx = common::boxlite();
@@ -335,7 +422,158 @@ fn test_exec_detach() {
         .args(["exec", "-d", &box_id, "--", "sleep", "10"])
         .assert()
         .success()
-        .stdout(""); // Detach mode produces no output
+        .stdout(predicate::str::is_empty().not()); // Detach mode prints the execution ID
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_detach_then_attach() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = ctx
+        .new_cmd()
+        .args([
+            "exec",
+            "-d",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "sleep 2; echo done; exit 3",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let exec_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // Reattach from a separate process
+    ctx.new_cmd()
+        .args(["attach", &box_id, &exec_id])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("done"));
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_logs_after_detach() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = ctx
+        .new_cmd()
+        .args([
+            "exec",
+            "-d",
+            "--record-output",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "echo to-stdout; echo to-stderr >&2",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let exec_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // Wait for the command to finish
+    ctx.new_cmd()
+        .args(["attach", &box_id, &exec_id])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["exec-logs", &box_id, &exec_id])
+        .assert()
+        .success()
+        .stdout("to-stdout\n")
+        .stderr("to-stderr\n");
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_logs_without_recording() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    ctx.new_cmd()
+        .args(["exec-logs", &box_id, "no-such-execution"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no recorded output"));
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_ls_from_another_process() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = ctx
+        .new_cmd()
+        .args(["exec", "-d", &box_id, "--", "sh", "-c", "exit 4"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let exec_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // Finished executions are still listed, with their exit code
+    ctx.new_cmd()
+        .args(["attach", &box_id, &exec_id])
+        .assert()
+        .code(4);
+    let output = ctx
+        .new_cmd()
+        .args(["exec-ls", "--format", "json", &box_id])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let exec = v
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["ID"] == exec_id.as_str())
+        .expect("detached execution listed");
+    assert_eq!(exec["Command"], "sh -c exit 4");
+    assert_eq!(exec["ExitCode"], 4);
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_attach_unknown_execution() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    ctx.new_cmd()
+        .args(["attach", &box_id, "no-such-execution"])
+        .assert()
+        .failure();
 
     cleanup(&ctx, &box_id);
 }
@@ -352,7 +590,7 @@ fn test_exec_detach_with_tty_allowed() {
         .args(["exec", "-d", "-t", &box_id, "--", "echo", "test"])
         .assert()
         .success()
-        .stdout("");
+        .stdout(predicate::str::is_empty().not());
 
     cleanup(&ctx, &box_id);
 }
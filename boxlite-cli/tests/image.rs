@@ -0,0 +1,116 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_image_prewarm() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.timeout(std::time::Duration::from_secs(120));
+    ctx.cmd
+        .args(["image", "prewarm", "alpine:latest"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("alpine:latest"));
+}
+
+#[test]
+fn test_image_pin_and_unpin() {
+    let ctx = common::boxlite();
+    let _ = ctx.new_cmd().args(["pull", "alpine:latest"]).output();
+
+    ctx.new_cmd()
+        .args(["image", "pin", "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["images", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"Pinned\": true"));
+
+    ctx.new_cmd()
+        .args(["image", "unpin", "alpine:latest"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_image_pin_not_cached() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["image", "pin", "nonexistent/image:doesnotexist"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
+
+#[test]
+fn test_image_requires_subcommand() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.arg("image").assert().failure();
+}
+
+#[test]
+fn test_image_tag_and_rmi() {
+    let ctx = common::boxlite();
+    let _ = ctx.new_cmd().args(["pull", "alpine:latest"]).output();
+
+    ctx.new_cmd()
+        .args(["tag", "alpine:latest", "alpine:boxlite-test"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .arg("images")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("boxlite-test"));
+
+    ctx.new_cmd()
+        .args(["rmi", "alpine:boxlite-test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Untagged:"));
+
+    ctx.new_cmd()
+        .arg("images")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("boxlite-test").not());
+}
+
+#[test]
+fn test_image_rm_refuses_pinned() {
+    let ctx = common::boxlite();
+    let _ = ctx.new_cmd().args(["pull", "alpine:latest"]).output();
+    let _ = ctx
+        .new_cmd()
+        .args(["tag", "alpine:latest", "alpine:boxlite-pinned"])
+        .output();
+    let _ = ctx
+        .new_cmd()
+        .args(["image", "pin", "alpine:boxlite-pinned"])
+        .output();
+
+    ctx.new_cmd()
+        .args(["image", "rm", "alpine:boxlite-pinned"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pinned"));
+
+    ctx.new_cmd()
+        .args(["image", "rm", "--force", "alpine:boxlite-pinned"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_image_rm_not_cached() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["rmi", "nonexistent/image:doesnotexist"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
@@ -0,0 +1,25 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_info_shows_reservations() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .arg("info")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Boxes:"))
+        .stdout(predicate::str::contains("CPUs:"))
+        .stdout(predicate::str::contains("Memory:"));
+}
+
+#[test]
+fn test_info_format_json() {
+    let mut ctx = common::boxlite();
+    let output = ctx.cmd.args(["info", "--format", "json"]).output().unwrap();
+    assert!(output.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(v["ReservedCpus"].is_u64());
+    assert!(v["ReservedMemoryMiB"].is_u64());
+}
@@ -195,6 +195,43 @@ fn test_inspect_format_template_json_state() {
     ctx.cleanup_box(name);
 }
 
+/// {{json .Timings}} shows the stage breakdown of the last start
+#[test]
+fn test_inspect_format_template_json_timings() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-format-json-timings";
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--format", "{{json .Timings}}", name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    let v: serde_json::Value =
+        serde_json::from_str(&stdout).expect("{{json .Timings}} should produce valid JSON");
+    for key in [
+        "FilesystemMs",
+        "RootfsMs",
+        "GuestRootfsMs",
+        "ConfigMs",
+        "SpawnMs",
+        "GuestMs",
+        "TotalMs",
+    ] {
+        assert!(v[key].is_u64(), "{} should be set, got {}", key, stdout);
+    }
+
+    ctx.cleanup_box(name);
+}
+
 /// Invalid format (e.g. xml, foo)
 #[test]
 fn test_inspect_format_invalid() {
@@ -514,3 +551,119 @@ fn test_inspect_latest_and_ref_fail() {
         stderr
     );
 }
+
+/// --guest-logs includes the agent's recent log lines for a running box
+#[test]
+fn test_inspect_guest_logs_running_box() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-guest-logs";
+    ctx.cmd.args([
+        "run",
+        "-d",
+        "--name",
+        name,
+        "--guest-log-level",
+        "debug",
+        "alpine:latest",
+        "sleep",
+        "300",
+    ]);
+    ctx.cmd.assert().success();
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--guest-logs=20", name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let logs = v[0]["GuestLogs"]
+        .as_array()
+        .expect("GuestLogs should be an array");
+    assert!(!logs.is_empty(), "running box should have guest logs");
+    assert!(logs.len() <= 20, "tail should cap the line count");
+
+    ctx.cleanup_box(name);
+}
+
+/// --guest-logs on a box that isn't running gives an empty list
+#[test]
+fn test_inspect_guest_logs_not_running() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-guest-logs-created";
+    let _ = ctx
+        .cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .output();
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--guest-logs", name])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(v[0]["GuestLogs"], serde_json::json!([]));
+
+    // Without the flag the field is omitted
+    let output = ctx.new_cmd().args(["inspect", name]).output().unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(v[0].get("GuestLogs").is_none());
+
+    ctx.cleanup_box(name);
+}
+
+/// Docker-style Config, HostConfig, Mounts and NetworkSettings sections
+#[test]
+fn test_inspect_docker_sections() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-docker-sections";
+    let host_dir = tempfile::tempdir().unwrap();
+    let volume = format!("{}:/data:ro", host_dir.path().display());
+    ctx.cmd
+        .args(["create", "--name", name, "--cpus", "2", "--memory", "512"])
+        .args(["-e", "GREETING=hello", "-p", "18080:80", "-v", &volume])
+        .arg("alpine:latest")
+        .assert()
+        .success();
+
+    let output = ctx.new_cmd().args(["inspect", name]).output().unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let obj = &v[0];
+
+    assert_eq!(obj["HostConfig"]["CpuCount"], 2);
+    assert_eq!(obj["HostConfig"]["Memory"], 512u64 * 1024 * 1024);
+    assert!(
+        obj["Config"]["Env"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("GREETING=hello"))
+    );
+    // No override: the image's CMD applies
+    assert!(obj["Config"]["Cmd"].is_null());
+    assert_eq!(obj["Mounts"][0]["Destination"], "/data");
+    assert_eq!(obj["Mounts"][0]["RW"], false);
+    assert_eq!(
+        obj["NetworkSettings"]["Ports"]["80/tcp"][0]["HostPort"],
+        "18080"
+    );
+    assert_eq!(obj["NetworkSettings"]["HostAlias"], "host.boxlite.internal");
+
+    // Template paths work on the new sections too
+    ctx.new_cmd()
+        .args(["inspect", "--format", "{{.HostConfig.CpuCount}}", name])
+        .assert()
+        .success()
+        .stdout("2\n");
+
+    ctx.cleanup_box(name);
+}
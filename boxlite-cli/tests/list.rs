@@ -108,8 +108,116 @@ fn test_list_lifecycle() {
     ctx.cleanup_box(name);
 }
 
+#[test]
+fn test_list_shows_ttl_expiry() {
+    let mut ctx = common::boxlite();
+    let name = "list-ttl";
+
+    ctx.cmd
+        .args(["create", "--name", name, "--ttl", "2h", "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("EXPIRES IN"))
+        .stdout(predicate::str::contains("1h59m").or(predicate::str::contains("2h0m")));
+
+    ctx.new_cmd()
+        .args(["list", "-a", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"ExpiresAt\""));
+
+    ctx.cleanup_box(name);
+}
+
 #[test]
 fn test_list_alias_ls() {
     let mut ctx = common::boxlite();
     ctx.cmd.arg("ls").assert().success();
 }
+
+#[test]
+fn test_list_size() {
+    let mut ctx = common::boxlite();
+    let name = "list-size-test";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["ps", "--size"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SIZE"))
+        .stdout(predicate::str::contains("virtual"));
+    ctx.new_cmd()
+        .arg("ps")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SIZE").not());
+
+    let output = ctx
+        .new_cmd()
+        .args(["ps", "--size", "--format", "json"])
+        .output()
+        .unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entry = v
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["Names"] == name)
+        .unwrap();
+    // Sparse overlays: far less allocated than the guest sees
+    let allocated = entry["DiskAllocatedBytes"].as_u64().unwrap();
+    let virtual_bytes = entry["DiskVirtualBytes"].as_u64().unwrap();
+    assert!(allocated > 0 && allocated < virtual_bytes);
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_list_several_homes() {
+    let mut ctx = common::boxlite();
+    let name = "list-homes-test";
+    let other_home = tempfile::tempdir().unwrap();
+
+    let _ = ctx
+        .cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .output();
+
+    let output = ctx
+        .new_cmd()
+        .arg("--home")
+        .arg(other_home.path())
+        .args(["list", "-a", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let boxes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let listed = boxes
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["Names"] == name)
+        .expect("box of the first home not listed");
+    assert_eq!(listed["Home"], ctx.home.display().to_string());
+
+    // Other commands take a single home
+    ctx.new_cmd()
+        .arg("--home")
+        .arg(other_home.path())
+        .args(["create", "alpine:latest"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only `list` reads several homes"));
+
+    ctx.cleanup_box(name);
+}
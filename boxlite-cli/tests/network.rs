@@ -0,0 +1,47 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_network_top_once() {
+    let mut ctx = common::boxlite();
+    let name = "network-top-once";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"])
+        .assert()
+        .success();
+
+    // Keep a TCP connection open inside the box so there is a flow to list
+    ctx.new_cmd()
+        .args(["exec", "-d", name, "--", "sh", "-c"])
+        .arg("nc -l -p 8080 & sleep 1; nc 127.0.0.1 8080")
+        .assert()
+        .success();
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    ctx.new_cmd()
+        .args(["network", "top", "--once", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("REMOTE ADDRESS"))
+        .stdout(predicate::str::contains("127.0.0.1:8080"));
+
+    ctx.new_cmd()
+        .args(["network", "top", "--format", "json", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"Protocol\": \"tcp\""));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_network_top_nonexistent() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["network", "top", "--once", "no-such-box-123"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No such box"));
+}
@@ -0,0 +1,74 @@
+mod common;
+
+fn stdout_json(output: &std::process::Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).expect("stdout should be a single JSON object")
+}
+
+#[test]
+fn test_output_json_result() {
+    let mut ctx = common::boxlite();
+    let output = ctx.cmd.args(["--output", "json", "info"]).output().unwrap();
+    assert!(output.status.success());
+    let v = stdout_json(&output);
+    assert!(v["result"]["ReservedCpus"].is_u64());
+}
+
+#[test]
+fn test_output_json_error() {
+    let mut ctx = common::boxlite();
+    let output = ctx
+        .cmd
+        .args(["--output", "json", "start", "no-such-box"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let v = stdout_json(&output);
+    assert_eq!(v["error"]["code"], "error");
+    assert!(
+        v["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("no-such-box")
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No such box"));
+}
+
+#[test]
+fn test_output_json_usage_error() {
+    let mut ctx = common::boxlite();
+    let output = ctx
+        .cmd
+        .args(["--output", "json", "no-such-command"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let v = stdout_json(&output);
+    assert_eq!(v["error"]["code"], "usage");
+}
+
+#[test]
+fn test_output_json_create_and_exec() {
+    let mut ctx = common::boxlite();
+    let output = ctx
+        .cmd
+        .args(["--output", "json", "create", "alpine:latest"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let box_id = stdout_json(&output)["result"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // The command's own output goes to stderr; stdout only carries the result
+    let output = ctx
+        .new_cmd()
+        .args(["--output", "json", "exec", &box_id, "--", "echo", "hello"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(stdout_json(&output)["result"]["exit_code"], 0);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hello\n"));
+
+    ctx.cleanup_box(&box_id);
+}
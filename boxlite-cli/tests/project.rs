@@ -0,0 +1,50 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_project_scopes_list_and_rm() {
+    let mut ctx = common::boxlite();
+    let name = "project-boxlite-scoped";
+
+    ctx.cmd
+        .args(["--project", "team-a", "create", "--name", name])
+        .arg("alpine:latest")
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--project", "team-a", "ls", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name));
+    ctx.new_cmd()
+        .args(["--project", "team-b", "ls", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name).not());
+
+    // Boxes of other projects can't be removed
+    ctx.new_cmd()
+        .args(["--project", "team-b", "rm", name])
+        .assert()
+        .failure();
+
+    ctx.new_cmd()
+        .args(["project", "ls"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("RUNNING"))
+        .stdout(predicate::str::contains("team-a"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_project_rejects_invalid_name() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["--project", "../escape", "ls"])
+        .assert()
+        .failure();
+}
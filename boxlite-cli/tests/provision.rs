@@ -0,0 +1,81 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_provision_alpine() {
+    let mut ctx = common::boxlite();
+    let name = "provision-alpine";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    ctx.new_cmd()
+        .args(["provision", name, "--pkg", "jq"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("with apk"));
+
+    ctx.new_cmd()
+        .args(["exec", name, "--", "jq", "--version"])
+        .assert()
+        .success();
+
+    ctx.cleanup_box(name);
+}
+
+/// A lock left behind by provisioning that died doesn't hold up the next one.
+#[test]
+fn test_provision_ignores_stale_lock() {
+    let mut ctx = common::boxlite();
+    let name = "provision-stale-lock";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    let lock = "/tmp/.boxlite-provision.lock";
+    ctx.new_cmd()
+        .args(["exec", name, "--", "sh", "-c"])
+        .arg(format!(
+            "touch {lock} && mkdir {lock}.d && echo 999999 > {lock}.d/pid"
+        ))
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["provision", name, "--pkg", "jq", "--lock-timeout", "5"])
+        .assert()
+        .success();
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_provision_invalid_package() {
+    let mut ctx = common::boxlite();
+    let name = "provision-invalid";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    ctx.new_cmd()
+        .args(["provision", name, "--pkg=--allow-untrusted"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid package name"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_provision_requires_pkg() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.args(["provision", "some-box"]);
+    ctx.cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--pkg"));
+}
@@ -30,6 +30,14 @@ fn test_run_exit_code_125() {
     ctx.cmd.assert().code(125);
 }
 
+#[test]
+fn test_run_quiet_output() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["run", "--rm", "-q", "alpine:latest", "echo", "quiet"]);
+    ctx.cmd.assert().success().stdout("quiet\n");
+}
+
 // ============================================================================
 // Command Execution Error Tests
 // ============================================================================
@@ -756,6 +764,44 @@ fn test_run_invalid_image() {
     );
 }
 
+#[test]
+fn test_run_pull_never_uncached_image() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.args([
+        "run",
+        "--rm",
+        "--pull",
+        "never",
+        "nonexistent/image:doesnotexist",
+        "echo",
+        "hi",
+    ]);
+    ctx.cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pull policy is 'never'"));
+}
+
+#[test]
+fn test_run_pull_never_cached_image() {
+    let ctx = common::boxlite();
+    let _ = ctx.new_cmd().args(["pull", "alpine:latest"]).output();
+
+    ctx.new_cmd()
+        .args([
+            "run",
+            "--rm",
+            "--pull",
+            "never",
+            "alpine:latest",
+            "echo",
+            "hi",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hi"));
+}
+
 #[test]
 fn test_run_tty_error_in_pipe() {
     let mut ctx = common::boxlite();
@@ -767,3 +813,60 @@ fn test_run_tty_error_in_pipe() {
         .failure()
         .stderr(predicate::str::contains("input device is not a TTY"));
 }
+
+#[test]
+fn test_run_entrypoint_replaces_main_process() {
+    let ctx = common::boxlite();
+    let name = "run-entrypoint";
+
+    ctx.new_cmd()
+        .args([
+            "run",
+            "-d",
+            "--name",
+            name,
+            "--entrypoint",
+            "sleep",
+            "alpine:latest",
+            "300",
+        ])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args([
+            "exec",
+            name,
+            "--",
+            "sh",
+            "-c",
+            "tr '\\0' ' ' < /proc/1/cmdline",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sleep 300"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_run_entrypoint_requires_detach() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["run", "--entrypoint", "sleep", "alpine:latest", "300"]);
+    ctx.cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--detach"));
+}
+
+#[test]
+fn test_run_prompt_connections_conflicts_with_detach() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["run", "--prompt-connections", "-d", "alpine:latest", "true"]);
+    ctx.cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--prompt-connections"));
+}
@@ -0,0 +1,35 @@
+//! Tests for `boxlite schema`, which needs no runtime.
+
+use assert_cmd::Command;
+
+fn schema(kind: &str) -> serde_json::Value {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("boxlite"))
+        .args(["schema", kind])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_schema_box_options() {
+    let schema = schema("box-options");
+    assert_eq!(schema["title"], "BoxOptions");
+    assert!(schema["properties"]["rootfs"].is_object());
+    assert!(schema["properties"]["auto_remove"].is_object());
+}
+
+#[test]
+fn test_schema_box_info() {
+    let schema = schema("box-info");
+    assert_eq!(schema["title"], "BoxInfo");
+    assert!(schema["properties"]["status"].is_object());
+}
+
+#[test]
+fn test_schema_unknown_kind() {
+    Command::new(assert_cmd::cargo::cargo_bin!("boxlite"))
+        .args(["schema", "nope"])
+        .assert()
+        .failure();
+}
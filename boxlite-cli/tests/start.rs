@@ -45,3 +45,29 @@ fn test_start_unknown() {
         .failure()
         .stderr(predicate::str::contains("not found"));
 }
+
+#[test]
+fn test_start_starts_dependencies() {
+    let mut ctx = common::boxlite();
+    let db = "start-deps-db";
+    let app = "start-deps-app";
+
+    ctx.cmd.args(["create", "--name", db, "alpine:latest"]);
+    ctx.cmd.assert().success();
+    ctx.new_cmd()
+        .args(["create", "--name", app, "--depends-on", db, "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd().args(["start", app]).assert().success();
+    ctx.new_cmd()
+        .args(["inspect", "--format", "{{.State.Status}}", db])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("running"));
+
+    // db can't be removed while app depends on it
+    ctx.new_cmd().args(["rm", db]).assert().failure();
+
+    ctx.cleanup_boxes(&[app, db]);
+}
@@ -0,0 +1,57 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_stats_cgroup_details() {
+    let mut ctx = common::boxlite();
+    let name = "stats-cgroup-details";
+
+    // --memory gives the container its own cgroup
+    ctx.cmd
+        .args([
+            "run",
+            "-d",
+            "--name",
+            name,
+            "--memory",
+            "512",
+            "alpine:latest",
+            "sleep",
+            "300",
+        ])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["stats", "--no-stream", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("WORKING SET"))
+        .stdout(predicate::str::contains(name));
+
+    let output = ctx
+        .new_cmd()
+        .args(["stats", "--format", "json", name])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stats = &v[0];
+    assert_eq!(stats["Name"], name);
+    assert!(stats["MemoryWorkingSetBytes"].as_u64().unwrap() > 0);
+    assert!(stats["Pids"].as_u64().unwrap() >= 1);
+    assert!(stats["IoReadBytes"].is_u64());
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_stats_nonexistent() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["stats", "--no-stream", "no-such-box-123"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No such box"));
+}
@@ -71,3 +71,33 @@ fn test_stop_unknown() {
         .failure()
         .stderr(predicate::str::contains("not found"));
 }
+
+#[test]
+fn test_stop_fuzzy_names() {
+    let mut ctx = common::boxlite();
+    let name = "stop-fuzzy-box";
+
+    ctx.cmd.args(["create", "--name", name, "alpine:latest"]);
+    ctx.cmd.assert().success();
+
+    // Strict by default
+    ctx.new_cmd()
+        .args(["stop", "stopfuzzybox"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("did you mean").not());
+
+    ctx.new_cmd()
+        .args(["--fuzzy-names", "stop", "stopfuzzybox"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("did you mean 'stop-fuzzy-box'"));
+
+    // A name differing only in case resolves to the box
+    ctx.new_cmd()
+        .args(["--fuzzy-names", "stop", "STOP-FUZZY-BOX"])
+        .assert()
+        .success();
+
+    ctx.cleanup_box(name);
+}
@@ -0,0 +1,57 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_sync_once_both_ways() {
+    let mut ctx = common::boxlite();
+    let name = "sync-once";
+    let host_dir = tempfile::tempdir().unwrap();
+    std::fs::write(host_dir.path().join("from-host.txt"), "host").unwrap();
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    ctx.new_cmd()
+        .args([
+            "exec",
+            name,
+            "--",
+            "sh",
+            "-c",
+            "mkdir -p /work/sub && echo guest > /work/sub/from-guest.txt",
+        ])
+        .assert()
+        .success();
+
+    let dirs = format!("{}:/work", host_dir.path().display());
+    ctx.new_cmd()
+        .args(["sync", "--once", name, &dirs])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-> box: 1 updated"))
+        .stdout(predicate::str::contains("<- box: 1 updated"));
+
+    ctx.new_cmd()
+        .args(["exec", name, "--", "cat", "/work/from-host.txt"])
+        .assert()
+        .success()
+        .stdout("host");
+    assert_eq!(
+        std::fs::read_to_string(host_dir.path().join("sub/from-guest.txt")).unwrap(),
+        "guest\n"
+    );
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_sync_requires_absolute_box_dir() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.args(["sync", "some-box", "./src:work"]);
+    ctx.cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("absolute BOX_DIR"));
+}
@@ -0,0 +1,31 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_system_df() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["system", "df"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("RECLAIMABLE"))
+        .stdout(predicate::str::contains("Images"))
+        .stdout(predicate::str::contains("Boxes"));
+}
+
+#[test]
+fn test_system_df_json() {
+    let mut ctx = common::boxlite();
+    let output = ctx
+        .cmd
+        .args(["system", "df", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(v[0]["Type"], "Images");
+    assert_eq!(v[1]["Type"], "Boxes");
+    assert!(v[1]["VirtualBytes"].is_u64());
+    assert!(v[1]["ReclaimableBytes"].as_u64() <= v[1]["SizeBytes"].as_u64());
+}
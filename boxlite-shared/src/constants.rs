@@ -7,6 +7,9 @@ pub mod container {
     /// Default container hostname
     pub const DEFAULT_HOSTNAME: &str = "boxlite";
 
+    /// Default size of the container's /dev/shm, in MiB
+    pub const DEFAULT_SHM_SIZE_MIB: u32 = 64;
+
     /// Default RLIMIT_NOFILE soft limit
     pub const RLIMIT_NOFILE_SOFT: u64 = 1024;
 
@@ -53,4 +56,126 @@ pub mod mount_tags {
 
     /// Tag for shared container directory (contains overlayfs/ and rootfs/)
     pub const SHARED: &str = "BoxLiteShared";
+
+    /// Tag for the read-write execution output logs directory
+    pub const EXEC_LOGS: &str = "BoxLiteExecLogs";
+}
+
+/// Guest agent capabilities
+///
+/// Reported by the agent in `PingResponse.capabilities`, so the host can
+/// tell an older agent lacks an RPC before calling it.
+pub mod capabilities {
+    /// PTY executions and `Execution.ResizeTty`
+    pub const PTY: &str = "pty";
+
+    /// `Files.Upload` and `Files.Download`
+    pub const FILES: &str = "files";
+
+    /// `Execution.RelayFd` (passing host fds to executions)
+    pub const RELAY_FD: &str = "relay_fd";
+
+    /// `Execution.List`
+    pub const EXEC_LIST: &str = "exec_list";
+
+    /// `Guest.Logs`
+    pub const LOGS: &str = "logs";
+
+    /// `Guest.NetConnections`
+    pub const NET_CONNECTIONS: &str = "net_connections";
+
+    /// `Container.Stats`
+    pub const CONTAINER_STATS: &str = "container_stats";
+
+    /// `Files.ListTree` and `Files.Remove`
+    pub const FILE_SYNC: &str = "file_sync";
+
+    /// `Container.UpdateHosts`
+    pub const UPDATE_HOSTS: &str = "update_hosts";
+
+    /// `GuestInitRequest.swap` and swap counters in `Container.Stats`
+    pub const SWAP: &str = "swap";
+
+    /// `Container.Status`
+    pub const CONTAINER_STATUS: &str = "container_status";
+
+    /// `ExecRequest.output_log`
+    pub const EXEC_OUTPUT_LOG: &str = "exec_output_log";
+
+    /// `ExecRequest.limits`
+    pub const EXEC_LIMITS: &str = "exec_limits";
+
+    /// `ExecRequest.create_workdir`
+    pub const CREATE_WORKDIR: &str = "create_workdir";
+
+    /// `ExecRequest.retention_secs`
+    pub const EXEC_RETENTION: &str = "exec_retention";
+
+    /// Everything this version of the agent serves.
+    pub const ALL: &[&str] = &[
+        PTY,
+        FILES,
+        RELAY_FD,
+        EXEC_LIST,
+        LOGS,
+        NET_CONNECTIONS,
+        CONTAINER_STATS,
+        FILE_SYNC,
+        UPDATE_HOSTS,
+        SWAP,
+        CONTAINER_STATUS,
+        EXEC_OUTPUT_LOG,
+        EXEC_LIMITS,
+        CREATE_WORKDIR,
+        EXEC_RETENTION,
+    ];
+
+    /// Served by agents that predate capability reporting.
+    pub const LEGACY: &[&str] = &[PTY, FILES];
+}
+
+/// Kernel parameters a box may set (`BoxOptions.sysctls`)
+///
+/// Checked by the host when validating options and again by the guest
+/// before writing to `/proc/sys`.
+pub mod sysctls {
+    /// Parameters allowed by exact name
+    pub const ALLOWED: &[&str] = &[
+        "fs.aio-max-nr",
+        "fs.file-max",
+        "fs.inotify.max_user_instances",
+        "fs.inotify.max_user_watches",
+        "fs.nr_open",
+        "kernel.msgmax",
+        "kernel.msgmnb",
+        "kernel.msgmni",
+        "kernel.pid_max",
+        "kernel.sem",
+        "kernel.shmall",
+        "kernel.shmmax",
+        "kernel.shmmni",
+        "kernel.threads-max",
+        "vm.dirty_background_ratio",
+        "vm.dirty_ratio",
+        "vm.max_map_count",
+        "vm.overcommit_memory",
+        "vm.overcommit_ratio",
+        "vm.swappiness",
+    ];
+
+    /// Parameter namespaces allowed as a whole
+    pub const ALLOWED_PREFIXES: &[&str] = &["net.core.", "net.ipv4.", "net.ipv6."];
+
+    /// Whether `key` (dotted form, e.g. `net.core.somaxconn`) may be set.
+    pub fn is_allowed(key: &str) -> bool {
+        let well_formed = !key.is_empty()
+            && key.split('.').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            });
+        well_formed
+            && (ALLOWED.contains(&key) || ALLOWED_PREFIXES.iter().any(|p| key.starts_with(p)))
+    }
 }
@@ -70,6 +70,25 @@ pub enum BoxliteError {
     /// Resource (box or runtime) has been stopped/shutdown.
     #[error("stopped: {0}")]
     Stopped(String),
+
+    /// Not enough capacity left for the requested resources.
+    #[error("resource exhausted: {0}")]
+    ResourceExhausted(String),
+
+    /// Working directory of an execution doesn't exist.
+    #[error("working directory not found: {0}")]
+    WorkdirNotFound(String),
+
+    /// A box already runs as many executions as it allows.
+    #[error("too many executions: {0}")]
+    TooManyExecs(String),
+
+    /// An operation was abandoned because its box was stopped or its
+    /// runtime shut down while it ran, including while it waited to start
+    /// (e.g. for an execution slot). Unlike `Stopped`, the handle was still
+    /// valid when the operation was called.
+    #[error("cancelled: {0}")]
+    Cancelled(String),
 }
 
 // Implement From for common error types to enable `?` operator
@@ -43,6 +43,9 @@ pub mod dirs {
 
     /// Volumes directory name (contains user volumes)
     pub const VOLUMES: &str = "volumes";
+
+    /// Execution output logs directory name (read-write share, one subdir per execution)
+    pub const EXEC_LOGS: &str = "exec-logs";
 }
 
 /// Guest base path (FHS-compliant).
@@ -283,6 +283,44 @@ fn compile_seccomp_filters() {
     println!("cargo:warning=Seccomp compilation skipped (not Linux)");
 }
 
+/// Stages the static busybox behind `RootfsSpec::Builtin`, with the
+/// `builtin-image` feature.
+///
+/// Copies the binary named by `BOXLITE_BUSYBOX` to OUT_DIR/busybox, where
+/// it is embedded with include_bytes!(). It must be statically linked for
+/// the target architecture.
+fn embed_busybox() {
+    if env::var_os("CARGO_FEATURE_BUILTIN_IMAGE").is_none() {
+        return;
+    }
+    println!("cargo:rerun-if-env-changed=BOXLITE_BUSYBOX");
+
+    let out_dir = env::var("OUT_DIR").expect("Missing OUT_DIR");
+    let dest = Path::new(&out_dir).join("busybox");
+
+    let source = env::var("BOXLITE_BUSYBOX")
+        .ok()
+        .filter(|source| !source.is_empty())
+        .unwrap_or_else(|| {
+            panic!("The builtin-image feature needs BOXLITE_BUSYBOX=/path/to/static/busybox")
+        });
+    println!("cargo:rerun-if-changed={}", source);
+    let len = fs::metadata(&source)
+        .map(|metadata| metadata.len())
+        .unwrap_or_else(|e| panic!("Failed to stat busybox {}: {}", source, e));
+    if len == 0 {
+        panic!("Busybox {} is empty", source);
+    }
+    fs::copy(&source, &dest).unwrap_or_else(|e| {
+        panic!(
+            "Failed to copy busybox {} -> {}: {}",
+            source,
+            dest.display(),
+            e
+        )
+    });
+}
+
 /// Collects all FFI dependencies into a single runtime directory.
 /// This directory can be used by downstream crates (e.g., Python SDK) to
 /// bundle all required libraries and binaries together.
@@ -292,6 +330,7 @@ fn main() {
     // Compile seccomp filters at build time (even in stub mode)
     // This is fast and required for include_bytes!() to work
     compile_seccomp_filters();
+    embed_busybox();
 
     // Check for stub mode (for CI linting without building dependencies)
     // Set BOXLITE_DEPS_STUB=1 to skip all native dependency builds
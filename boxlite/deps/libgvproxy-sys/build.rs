@@ -36,6 +36,10 @@ fn build_gvproxy(source_dir: &Path, output_path: &Path) {
         output_path.to_str().expect("Invalid output path"),
         "main.go",
         "stats.go",
+        "prompt.go",
+        "recorder.go",
+        "filter.go",
+        "peers.go",
     ]);
 
     let build_status = build_cmd
@@ -101,6 +105,10 @@ fn main() {
     // Rebuild if Go sources change
     println!("cargo:rerun-if-changed=gvproxy-bridge/main.go");
     println!("cargo:rerun-if-changed=gvproxy-bridge/stats.go");
+    println!("cargo:rerun-if-changed=gvproxy-bridge/prompt.go");
+    println!("cargo:rerun-if-changed=gvproxy-bridge/recorder.go");
+    println!("cargo:rerun-if-changed=gvproxy-bridge/filter.go");
+    println!("cargo:rerun-if-changed=gvproxy-bridge/peers.go");
     println!("cargo:rerun-if-changed=gvproxy-bridge/go.mod");
 
     // Check for stub mode (for CI linting without building)
@@ -0,0 +1,78 @@
+package main
+
+import (
+	"bufio"
+	"encoding/binary"
+	"fmt"
+	"io"
+	"net"
+)
+
+// Largest frame a length prefix may announce: an Ethernet header (with a
+// VLAN tag) around the largest IP packet
+const maxFrameLen = 18 + 65535
+
+// frameFilter reports whether a frame the guest sent may reach the virtual
+// network
+type frameFilter func(frame []byte) bool
+
+// filterConn passes the frames the guest sends through filters, dropping
+// those one of them holds back.
+type filterConn struct {
+	net.Conn
+	filters []frameFilter
+	// Qemu frames are length-prefixed in a stream; VFKit sends one
+	// datagram per frame
+	reader  *bufio.Reader
+	pending []byte
+}
+
+func newFilterConn(conn net.Conn, filters []frameFilter, stream bool) *filterConn {
+	c := &filterConn{Conn: conn, filters: filters}
+	if stream {
+		c.reader = bufio.NewReader(conn)
+	}
+	return c
+}
+
+func (c *filterConn) Read(b []byte) (int, error) {
+	if c.reader == nil {
+		for {
+			n, err := c.Conn.Read(b)
+			if err != nil || c.admit(b[:n]) {
+				return n, err
+			}
+		}
+	}
+
+	for len(c.pending) == 0 {
+		var size [4]byte
+		if _, err := io.ReadFull(c.reader, size[:]); err != nil {
+			return 0, err
+		}
+		length := binary.BigEndian.Uint32(size[:])
+		if length > maxFrameLen {
+			return 0, fmt.Errorf("frame of %d bytes exceeds the %d byte limit", length, maxFrameLen)
+		}
+		frame := make([]byte, 4+int(length))
+		copy(frame, size[:])
+		if _, err := io.ReadFull(c.reader, frame[4:]); err != nil {
+			return 0, err
+		}
+		if c.admit(frame[4:]) {
+			c.pending = frame
+		}
+	}
+	n := copy(b, c.pending)
+	c.pending = c.pending[n:]
+	return n, nil
+}
+
+func (c *filterConn) admit(frame []byte) bool {
+	for _, filter := range c.filters {
+		if !filter(frame) {
+			return false
+		}
+	}
+	return true
+}
@@ -169,8 +169,15 @@ type PortMapping struct {
 // These are local DNS records served by the gateway's embedded DNS server.
 // Queries not matching any zone are forwarded to the host's system DNS.
 type DNSZone struct {
-	Name      string `json:"name"`       // Zone name (e.g., "myapp.local.", "." for root)
-	DefaultIP string `json:"default_ip"` // Default IP for unmatched queries in this zone
+	Name      string      `json:"name"`       // Zone name (e.g., "myapp.local.", "." for root)
+	DefaultIP string      `json:"default_ip"` // Default IP for unmatched queries in this zone, empty for none
+	Records   []DNSRecord `json:"records"`    // Names in the zone, relative to it
+}
+
+// DNSRecord is a name of a DNSZone and the IP it resolves to
+type DNSRecord struct {
+	Name string `json:"name"` // Name relative to the zone (e.g., "host")
+	IP   string `json:"ip"`
 }
 
 // GvproxyConfig matches the Rust structure (must stay in sync!)
@@ -180,12 +187,24 @@ type GvproxyConfig struct {
 	GatewayMac       string        `json:"gateway_mac"`
 	GuestIP          string        `json:"guest_ip"`
 	GuestMac         string        `json:"guest_mac"`
+	HostIP           string        `json:"host_ip"`
 	MTU              uint16        `json:"mtu"`
 	PortMappings     []PortMapping `json:"port_mappings"`
 	DNSZones         []DNSZone     `json:"dns_zones"`
 	DNSSearchDomains []string      `json:"dns_search_domains"`
 	Debug            bool          `json:"debug"`
 	CaptureFile      *string       `json:"capture_file,omitempty"`
+
+	// Socket the host answers connection prompts on (see prompt.go)
+	ConnectionPromptSocket *string `json:"connection_prompt_socket,omitempty"`
+
+	// HTTP proxy on the gateway recording or replaying responses (see recorder.go)
+	HTTPRecorder *HTTPRecorderConfig `json:"http_recorder,omitempty"`
+
+	// Addresses of the boxes on the same networks, and the file listing
+	// the ports each publishes (see peers.go)
+	PeerSubnet string  `json:"peer_subnet,omitempty"`
+	PeersFile  *string `json:"peers_file,omitempty"`
 }
 
 // GvproxyInstance tracks a running gvisor-tap-vsock instance
@@ -241,9 +260,17 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 	// These are local DNS records - queries not matching any zone are forwarded to host DNS
 	dnsZones := make([]types.Zone, len(config.DNSZones))
 	for i, zone := range config.DNSZones {
+		records := make([]types.Record, len(zone.Records))
+		for j, record := range zone.Records {
+			records[j] = types.Record{
+				Name: record.Name,
+				IP:   net.ParseIP(record.IP),
+			}
+		}
 		dnsZones[i] = types.Zone{
 			Name:      zone.Name,
 			DefaultIP: net.ParseIP(zone.DefaultIP),
+			Records:   records,
 		}
 	}
 
@@ -267,12 +294,66 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 		DNSSearchDomains:  config.DNSSearchDomains,
 	}
 
+	// Give the guest a virtual IP for the host's loopback
+	if config.HostIP != "" {
+		tapConfig.NAT[config.HostIP] = "127.0.0.1"
+		tapConfig.GatewayVirtualIPs = append(tapConfig.GatewayVirtualIPs, config.HostIP)
+	}
+
 	// Set CaptureFile if provided
 	if config.CaptureFile != nil && *config.CaptureFile != "" {
 		tapConfig.CaptureFile = *config.CaptureFile
 		logrus.WithField("capture_file", *config.CaptureFile).Info("Packet capture enabled")
 	}
 
+	// Give each network peer an address translated to the host's loopback,
+	// reachable on the ports it publishes only
+	var filters []frameFilter
+	if config.PeersFile != nil && *config.PeersFile != "" {
+		peers, err := newPeerFilter(*config.PeersFile, config.PeerSubnet)
+		if err != nil {
+			logrus.WithFields(logrus.Fields{"error": err, "subnet": config.PeerSubnet}).Error("Failed to set up network peers")
+			return -1
+		}
+		for _, addr := range peers.addresses() {
+			tapConfig.NAT[addr.String()] = "127.0.0.1"
+			tapConfig.GatewayVirtualIPs = append(tapConfig.GatewayVirtualIPs, addr.String())
+		}
+		filters = append(filters, peers.admit)
+		logrus.WithField("peers_file", *config.PeersFile).Info("Network peers enabled")
+	}
+
+	// Hold back new outbound connections until the host allows them
+	if config.ConnectionPromptSocket != nil && *config.ConnectionPromptSocket != "" {
+		prompter, err := newConnectionPrompter(*config.ConnectionPromptSocket, config.Subnet, config.HostIP)
+		if err != nil {
+			logrus.WithFields(logrus.Fields{"error": err, "subnet": config.Subnet}).Error("Failed to set up connection prompts")
+			return -1
+		}
+		filters = append(filters, prompter.admit)
+		logrus.WithField("socket", *config.ConnectionPromptSocket).Info("Connection prompts enabled")
+	}
+
+	// Record or replay HTTP(S) responses through a proxy on the gateway
+	var recorder *httpRecorder
+	if config.HTTPRecorder != nil {
+		var err error
+		recorder, err = newHTTPRecorder(*config.HTTPRecorder)
+		if err != nil {
+			logrus.WithFields(logrus.Fields{"error": err, "dir": config.HTTPRecorder.Dir}).Error("Failed to set up HTTP recorder")
+			return -1
+		}
+		if config.HTTPRecorder.Replay {
+			replay, err := replayFilter(config.GatewayIP, config.HTTPRecorder.Port)
+			if err != nil {
+				logrus.WithFields(logrus.Fields{"error": err, "gateway": config.GatewayIP}).Error("Failed to set up HTTP replay")
+				return -1
+			}
+			// First, so nothing is asked about a destination it drops
+			filters = append([]frameFilter{replay}, filters...)
+		}
+	}
+
 	// Add port forwards from config
 	// Format: "0.0.0.0:PORT" for TCP (default), or "udp:0.0.0.0:PORT" for UDP
 	// Do NOT use "tcp://" prefix - it causes "too many colons in address" error
@@ -364,6 +445,10 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 		instance.vn = vn
 		instance.vnMu.Unlock()
 
+		if recorder != nil {
+			go recorder.serve(ctx, vn, config.GatewayIP)
+		}
+
 		// Platform-specific packet handling
 		if runtime.GOOS == "darwin" {
 			// macOS: Handle VFKit datagram packets
@@ -383,8 +468,13 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 
 				logrus.WithFields(logrus.Fields{"id": id, "remote": wrappedConn.RemoteAddr().String()}).Info("VFKit connection accepted")
 
+				var guestConn net.Conn = wrappedConn
+				if len(filters) > 0 {
+					guestConn = newFilterConn(wrappedConn, filters, false)
+				}
+
 				// Handle the VFKit protocol with the wrapped connection
-				if err := vn.AcceptVfkit(ctx, wrappedConn); err != nil {
+				if err := vn.AcceptVfkit(ctx, guestConn); err != nil {
 					if ctx.Err() == nil {
 						logrus.WithFields(logrus.Fields{"error": err, "id": id}).Error("AcceptVfkit error")
 					}
@@ -409,6 +499,10 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 				// Close listener after first connection (one VM per gvproxy instance)
 				listener.Close()
 
+				if len(filters) > 0 {
+					acceptedConn = newFilterConn(acceptedConn, filters, true)
+				}
+
 				// Handle the Qemu protocol
 				if err := vn.AcceptQemu(ctx, acceptedConn); err != nil {
 					if ctx.Err() == nil {
@@ -0,0 +1,123 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"net/netip"
+	"os"
+	"sync"
+
+	logrus "github.com/sirupsen/logrus"
+)
+
+// Network peers.
+//
+// Boxes sharing a user-defined network reach each other through the ports
+// they publish on the host. Each peer has its own address in the peer
+// subnet, translated to the host's 127.0.0.1. Only TCP connections to a
+// port the peer publishes pass; everything else sent to the peer subnet is
+// dropped, so the rest of the host's loopback stays out of reach.
+//
+// The host rewrites the peers file as boxes on the network start, stop or
+// are removed, replacing the file rather than writing into it. It is read
+// again whenever it was replaced.
+
+// peersFile is the content of the peers file
+type peersFile struct {
+	Peers []peerEntry `json:"peers"`
+}
+
+// peerEntry is one peer: its address and the host ports it publishes
+type peerEntry struct {
+	IP    string   `json:"ip"`
+	Ports []uint16 `json:"ports"`
+}
+
+// peerFilter holds the peers of one box
+type peerFilter struct {
+	path   string
+	subnet netip.Prefix
+
+	mu      sync.Mutex
+	loaded  os.FileInfo // File the peers were read from, nil for none
+	allowed map[netip.AddrPort]bool
+}
+
+func newPeerFilter(path string, subnet string) (*peerFilter, error) {
+	prefix, err := netip.ParsePrefix(subnet)
+	if err != nil {
+		return nil, err
+	}
+	return &peerFilter{
+		path:    path,
+		subnet:  prefix.Masked(),
+		allowed: make(map[netip.AddrPort]bool),
+	}, nil
+}
+
+// addresses lists the peer subnet, every address a peer can have
+func (f *peerFilter) addresses() []netip.Addr {
+	var addrs []netip.Addr
+	for addr := f.subnet.Addr(); f.subnet.Contains(addr); addr = addr.Next() {
+		addrs = append(addrs, addr)
+	}
+	return addrs
+}
+
+// admit reports whether a frame from the guest may pass: anything not
+// opening a connection to the peer subnet, and TCP connections to the
+// ports peers publish.
+func (f *peerFilter) admit(frame []byte) bool {
+	protocol, dst, ok := outboundDestination(frame)
+	if !ok || !f.subnet.Contains(dst.Addr()) {
+		return true
+	}
+	if protocol != "tcp" {
+		return false
+	}
+
+	f.mu.Lock()
+	defer f.mu.Unlock()
+	f.reload()
+	return f.allowed[dst]
+}
+
+// reload reads the peers file again if it was replaced. A missing file
+// means no peers; an unreadable one keeps the peers read last.
+func (f *peerFilter) reload() {
+	info, err := os.Stat(f.path)
+	if errors.Is(err, os.ErrNotExist) {
+		f.loaded = nil
+		f.allowed = make(map[netip.AddrPort]bool)
+		return
+	}
+	if err != nil || (f.loaded != nil && os.SameFile(info, f.loaded) && info.ModTime().Equal(f.loaded.ModTime())) {
+		return
+	}
+
+	data, err := os.ReadFile(f.path)
+	if err != nil {
+		logrus.WithFields(logrus.Fields{"error": err, "path": f.path}).Warn("Failed to read network peers")
+		return
+	}
+	var peers peersFile
+	if err := json.Unmarshal(data, &peers); err != nil {
+		logrus.WithFields(logrus.Fields{"error": err, "path": f.path}).Warn("Failed to parse network peers")
+		return
+	}
+
+	allowed := make(map[netip.AddrPort]bool)
+	for _, peer := range peers.Peers {
+		addr, err := netip.ParseAddr(peer.IP)
+		if err != nil || !f.subnet.Contains(addr) {
+			logrus.WithField("ip", peer.IP).Warn("Ignoring network peer outside the peer subnet")
+			continue
+		}
+		for _, port := range peer.Ports {
+			allowed[netip.AddrPortFrom(addr, port)] = true
+		}
+	}
+	f.loaded = info
+	f.allowed = allowed
+	logrus.WithFields(logrus.Fields{"peers": len(peers.Peers), "path": f.path}).Debug("Loaded network peers")
+}
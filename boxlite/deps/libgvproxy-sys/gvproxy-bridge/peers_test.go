@@ -0,0 +1,90 @@
+package main
+
+import (
+	"encoding/binary"
+	"net/netip"
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+// testFrame builds an Ethernet frame carrying an IPv4 packet opening a
+// connection to dst: a TCP SYN for protocol 6, a datagram for 17.
+func testFrame(protocol byte, dst netip.AddrPort) []byte {
+	frame := make([]byte, 14+20+20)
+	binary.BigEndian.PutUint16(frame[12:14], 0x0800)
+	ip := frame[14:]
+	ip[0] = 0x45
+	ip[9] = protocol
+	copy(ip[12:16], []byte{192, 168, 127, 2})
+	addr := dst.Addr().As4()
+	copy(ip[16:20], addr[:])
+	transport := ip[20:]
+	binary.BigEndian.PutUint16(transport[2:4], dst.Port())
+	transport[13] = 0x02 // SYN
+	return frame
+}
+
+func writePeers(t *testing.T, path string, content string) {
+	t.Helper()
+	tmp := path + ".tmp"
+	if err := os.WriteFile(tmp, []byte(content), 0o600); err != nil {
+		t.Fatal(err)
+	}
+	if err := os.Rename(tmp, path); err != nil {
+		t.Fatal(err)
+	}
+}
+
+func TestPeerFilterAdmitsPublishedPortsOnly(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "peers.json")
+	filter, err := newPeerFilter(path, "192.168.127.128/26")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if got := len(filter.addresses()); got != 64 {
+		t.Fatalf("peer subnet has %d addresses, want 64", got)
+	}
+
+	peer := netip.MustParseAddr("192.168.127.128")
+	syn := testFrame(6, netip.AddrPortFrom(peer, 8080))
+	if filter.admit(syn) {
+		t.Fatal("admitted a peer with no peers file")
+	}
+
+	writePeers(t, path, `{"peers":[{"ip":"192.168.127.128","ports":[8080]}]}`)
+	if !filter.admit(syn) {
+		t.Fatal("dropped a connection to a published port")
+	}
+	if filter.admit(testFrame(6, netip.AddrPortFrom(peer, 22))) {
+		t.Fatal("admitted a connection to an unpublished port")
+	}
+	if filter.admit(testFrame(17, netip.AddrPortFrom(peer, 8080))) {
+		t.Fatal("admitted UDP to a peer")
+	}
+	other := netip.MustParseAddr("192.168.127.129")
+	if filter.admit(testFrame(6, netip.AddrPortFrom(other, 8080))) {
+		t.Fatal("admitted a connection to another peer's port")
+	}
+	if !filter.admit(testFrame(6, netip.MustParseAddrPort("93.184.216.34:443"))) {
+		t.Fatal("dropped a connection outside the peer subnet")
+	}
+
+	writePeers(t, path, `{"peers":[]}`)
+	if filter.admit(syn) {
+		t.Fatal("admitted a peer removed from the peers file")
+	}
+}
+
+func TestPeerFilterIgnoresAddressesOutsideSubnet(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "peers.json")
+	writePeers(t, path, `{"peers":[{"ip":"192.168.127.1","ports":[53]}]}`)
+	filter, err := newPeerFilter(path, "192.168.127.128/26")
+	if err != nil {
+		t.Fatal(err)
+	}
+	filter.reload()
+	if len(filter.allowed) != 0 {
+		t.Fatalf("loaded %d ports outside the peer subnet", len(filter.allowed))
+	}
+}
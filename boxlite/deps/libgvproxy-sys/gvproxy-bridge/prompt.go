@@ -0,0 +1,223 @@
+package main
+
+import (
+	"encoding/binary"
+	"encoding/json"
+	"net"
+	"net/netip"
+	"sync"
+	"time"
+
+	logrus "github.com/sirupsen/logrus"
+)
+
+// Outbound connection prompts.
+//
+// With a prompt socket configured, the frames the guest sends are filtered
+// before they reach the virtual network. The first packet to a destination
+// that has no decision yet (a TCP SYN, or any UDP datagram) is dropped while
+// the host is asked over the socket whether to allow it. The guest resends
+// it (TCP retransmits the SYN, UDP clients retry) and it passes once the
+// destination is allowed; packets to denied destinations keep being dropped.
+//
+// Destinations inside the virtual subnet, other than the host IP, are never
+// held back: they are the gateway's DNS and DHCP.
+
+// Time the host has to answer, including the user's think time
+const promptTimeout = 5 * time.Minute
+
+type decision int
+
+const (
+	undecided decision = iota
+	pending
+	allowed
+	denied
+)
+
+// promptRequest asks the host about one destination
+type promptRequest struct {
+	Protocol string `json:"protocol"` // "tcp" or "udp"
+	Address  string `json:"address"`  // "ip:port"
+}
+
+type promptResponse struct {
+	Allow bool `json:"allow"`
+}
+
+// connectionPrompter holds the decisions of one box
+type connectionPrompter struct {
+	socketPath string
+	subnet     netip.Prefix
+	hostIP     netip.Addr
+
+	mu        sync.Mutex
+	decisions map[string]decision
+}
+
+func newConnectionPrompter(socketPath string, subnet string, hostIP string) (*connectionPrompter, error) {
+	prefix, err := netip.ParsePrefix(subnet)
+	if err != nil {
+		return nil, err
+	}
+	// Empty when the guest has no host IP
+	host, _ := netip.ParseAddr(hostIP)
+	return &connectionPrompter{
+		socketPath: socketPath,
+		subnet:     prefix,
+		hostIP:     host,
+		decisions:  make(map[string]decision),
+	}, nil
+}
+
+// admit reports whether a frame from the guest may pass, asking the host
+// about destinations seen for the first time.
+func (p *connectionPrompter) admit(frame []byte) bool {
+	protocol, dst, ok := outboundDestination(frame)
+	if !ok {
+		return true
+	}
+	if p.subnet.Contains(dst.Addr()) && dst.Addr() != p.hostIP {
+		return true
+	}
+
+	key := protocol + "/" + dst.String()
+	p.mu.Lock()
+	d := p.decisions[key]
+	if d == undecided {
+		p.decisions[key] = pending
+		go p.ask(key, protocol, dst)
+	}
+	p.mu.Unlock()
+	return d == allowed
+}
+
+// ask gets the host's decision on a destination. If the host can't be
+// asked, the destination is left undecided and asked about again on the
+// next attempt.
+func (p *connectionPrompter) ask(key string, protocol string, dst netip.AddrPort) {
+	allow, err := p.request(protocol, dst)
+
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	if err != nil {
+		logrus.WithFields(logrus.Fields{"error": err, "destination": key}).Warn("Failed to ask host about connection, dropping it")
+		delete(p.decisions, key)
+		return
+	}
+	if allow {
+		p.decisions[key] = allowed
+	} else {
+		p.decisions[key] = denied
+	}
+	logrus.WithFields(logrus.Fields{"destination": key, "allow": allow}).Debug("Connection decided")
+}
+
+func (p *connectionPrompter) request(protocol string, dst netip.AddrPort) (bool, error) {
+	conn, err := net.DialTimeout("unix", p.socketPath, 5*time.Second)
+	if err != nil {
+		return false, err
+	}
+	defer conn.Close()
+	if err := conn.SetDeadline(time.Now().Add(promptTimeout)); err != nil {
+		return false, err
+	}
+
+	if err := json.NewEncoder(conn).Encode(promptRequest{Protocol: protocol, Address: dst.String()}); err != nil {
+		return false, err
+	}
+	var response promptResponse
+	if err := json.NewDecoder(conn).Decode(&response); err != nil {
+		return false, err
+	}
+	return response.Allow, nil
+}
+
+// outboundDestination returns the destination of a frame that opens a
+// connection: a TCP SYN or a UDP datagram to a unicast IPv4 or IPv6 address.
+func outboundDestination(frame []byte) (string, netip.AddrPort, bool) {
+	const ethernetHeaderLen = 14
+	if len(frame) < ethernetHeaderLen {
+		return "", netip.AddrPort{}, false
+	}
+	var (
+		dst       netip.Addr
+		protocol  byte
+		transport []byte
+		ok        bool
+	)
+	switch binary.BigEndian.Uint16(frame[12:14]) {
+	case 0x0800:
+		dst, protocol, transport, ok = ipv4Payload(frame[ethernetHeaderLen:])
+	case 0x86dd:
+		dst, protocol, transport, ok = ipv6Payload(frame[ethernetHeaderLen:])
+	}
+	if !ok || !dst.IsGlobalUnicast() || len(transport) < 4 {
+		return "", netip.AddrPort{}, false
+	}
+	port := binary.BigEndian.Uint16(transport[2:4])
+
+	switch protocol {
+	case 6: // TCP
+		const syn, ack = 0x02, 0x10
+		if len(transport) < 14 || transport[13]&(syn|ack) != syn {
+			return "", netip.AddrPort{}, false
+		}
+		return "tcp", netip.AddrPortFrom(dst, port), true
+	case 17: // UDP
+		return "udp", netip.AddrPortFrom(dst, port), true
+	}
+	return "", netip.AddrPort{}, false
+}
+
+// ipv4Payload returns the destination, transport protocol and transport
+// header of an IPv4 packet. Only the first fragment carries the header.
+func ipv4Payload(ip []byte) (netip.Addr, byte, []byte, bool) {
+	if len(ip) < 20 {
+		return netip.Addr{}, 0, nil, false
+	}
+	headerLen := int(ip[0]&0x0f) * 4
+	if binary.BigEndian.Uint16(ip[6:8])&0x1fff != 0 || headerLen < 20 || len(ip) < headerLen {
+		return netip.Addr{}, 0, nil, false
+	}
+	return netip.AddrFrom4([4]byte(ip[16:20])), ip[9], ip[headerLen:], true
+}
+
+// ipv6Payload returns the destination, transport protocol and transport
+// header of an IPv6 packet, skipping its extension headers.
+func ipv6Payload(ip []byte) (netip.Addr, byte, []byte, bool) {
+	const headerLen = 40
+	if len(ip) < headerLen {
+		return netip.Addr{}, 0, nil, false
+	}
+	dst := netip.AddrFrom16([16]byte(ip[24:40]))
+	next, rest := ip[6], ip[headerLen:]
+	// A legitimate packet has a handful of extension headers at most
+	for i := 0; i < 8; i++ {
+		var extLen int
+		switch next {
+		case 0, 43, 60: // Hop-by-hop, routing, destination options
+			if len(rest) < 2 {
+				return netip.Addr{}, 0, nil, false
+			}
+			extLen = (int(rest[1]) + 1) * 8
+		case 44: // Fragment
+			if len(rest) < 8 || binary.BigEndian.Uint16(rest[2:4])&0xfff8 != 0 {
+				return netip.Addr{}, 0, nil, false
+			}
+			extLen = 8
+		case 51: // Authentication header
+			if len(rest) < 2 {
+				return netip.Addr{}, 0, nil, false
+			}
+			extLen = (int(rest[1]) + 2) * 4
+		default:
+			return dst, next, rest, true
+		}
+		if len(rest) < extLen {
+			return netip.Addr{}, 0, nil, false
+		}
+		next, rest = rest[0], rest[extLen:]
+	}
+	return netip.Addr{}, 0, nil, false
+}
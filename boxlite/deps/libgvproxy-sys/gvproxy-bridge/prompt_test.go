@@ -0,0 +1,79 @@
+package main
+
+import (
+	"encoding/binary"
+	"net"
+	"net/netip"
+	"strings"
+	"testing"
+)
+
+// testFrame6 builds an Ethernet frame carrying an IPv6 packet opening a
+// connection to dst, behind a hop-by-hop options header.
+func testFrame6(protocol byte, dst netip.AddrPort) []byte {
+	frame := make([]byte, 14+40+8+20)
+	binary.BigEndian.PutUint16(frame[12:14], 0x86dd)
+	ip := frame[14:]
+	ip[0] = 0x60
+	ip[6] = 0 // Hop-by-hop options
+	addr := dst.Addr().As16()
+	copy(ip[24:40], addr[:])
+	hopByHop := ip[40:]
+	hopByHop[0] = protocol
+	transport := ip[48:]
+	binary.BigEndian.PutUint16(transport[2:4], dst.Port())
+	transport[13] = 0x02 // SYN
+	return frame
+}
+
+func TestOutboundDestinationIPv6(t *testing.T) {
+	dst := netip.MustParseAddrPort("[2606:4700::1111]:443")
+	protocol, got, ok := outboundDestination(testFrame6(6, dst))
+	if !ok || protocol != "tcp" || got != dst {
+		t.Fatalf("got %s %s %v, want tcp %s", protocol, got, ok, dst)
+	}
+
+	protocol, got, ok = outboundDestination(testFrame6(17, dst))
+	if !ok || protocol != "udp" || got != dst {
+		t.Fatalf("got %s %s %v, want udp %s", protocol, got, ok, dst)
+	}
+
+	// Neighbor discovery and link-local traffic never ask the host
+	if _, _, ok := outboundDestination(testFrame6(6, netip.MustParseAddrPort("[fe80::1]:443"))); ok {
+		t.Fatal("link-local destination was treated as outbound")
+	}
+
+	// A truncated extension header chain is not a connection
+	if _, _, ok := outboundDestination(testFrame6(6, dst)[:14+44]); ok {
+		t.Fatal("truncated packet was treated as outbound")
+	}
+}
+
+func TestPrompterHoldsBackIPv6(t *testing.T) {
+	prompter, err := newConnectionPrompter("/nonexistent.sock", "192.168.127.0/24", "192.168.127.254")
+	if err != nil {
+		t.Fatal(err)
+	}
+	dst := netip.MustParseAddrPort("[2606:4700::1111]:443")
+	if prompter.admit(testFrame6(6, dst)) {
+		t.Fatal("IPv6 connection passed before the host allowed it")
+	}
+}
+
+func TestFilterConnRejectsOversizedFrames(t *testing.T) {
+	guest, host := net.Pipe()
+	defer guest.Close()
+	defer host.Close()
+
+	go func() {
+		var size [4]byte
+		binary.BigEndian.PutUint32(size[:], 1<<30)
+		_, _ = guest.Write(size[:])
+	}()
+
+	conn := newFilterConn(host, nil, true)
+	_, err := conn.Read(make([]byte, 1500))
+	if err == nil || !strings.Contains(err.Error(), "exceeds") {
+		t.Fatalf("got %v, want an oversized frame error", err)
+	}
+}
@@ -0,0 +1,532 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"crypto/ecdsa"
+	"crypto/elliptic"
+	"crypto/rand"
+	"crypto/sha256"
+	"crypto/tls"
+	"crypto/x509"
+	"crypto/x509/pkix"
+	"encoding/binary"
+	"encoding/hex"
+	"encoding/json"
+	"encoding/pem"
+	"errors"
+	"fmt"
+	"io"
+	"math/big"
+	"net"
+	"net/http"
+	"net/netip"
+	"os"
+	"path/filepath"
+	"sync"
+	"time"
+
+	"github.com/containers/gvisor-tap-vsock/pkg/virtualnetwork"
+	logrus "github.com/sirupsen/logrus"
+)
+
+// HTTP(S) record and replay.
+//
+// The recorder is an HTTP proxy on the gateway, which the guest is pointed
+// at with HTTP_PROXY and HTTPS_PROXY. In record mode it forwards requests
+// and saves each response in the recording directory; in replay mode it
+// answers from that directory and never reaches the network. CONNECT
+// tunnels are terminated with a certificate for the requested host, signed
+// by a CA created when the proxy starts, so HTTPS is recorded too.
+//
+// A response is saved as <key>-<n>.json, where key hashes the method, URL
+// and body of the request and n counts identical requests during the run:
+// a request repeated gets its responses back in order, and the last one once
+// they run out. Recordings may hold credentials, so only the user can read
+// them.
+//
+// In record mode responses stream to the guest as they arrive and are saved
+// once complete; a body over maxRecordedBody is passed through unsaved. In
+// replay mode the guest can reach nothing but the proxy (see replayFilter).
+
+// Largest request or response body the recorder keeps
+const maxRecordedBody = 64 << 20
+
+// HTTPRecorderConfig matches the Rust HttpRecorderConfig
+type HTTPRecorderConfig struct {
+	Replay bool   `json:"replay"`  // Answer from Dir instead of the network
+	Dir    string `json:"dir"`     // Recorded responses
+	CACert string `json:"ca_cert"` // Where to write the CA certificate
+	Port   uint16 `json:"port"`    // Proxy port on the gateway
+}
+
+// Headers of one connection, not forwarded or recorded
+var hopHeaders = []string{
+	"Connection",
+	"Keep-Alive",
+	"Proxy-Authenticate",
+	"Proxy-Authorization",
+	"Proxy-Connection",
+	"Te",
+	"Trailer",
+	"Transfer-Encoding",
+	"Upgrade",
+}
+
+// recordedResponse is one saved response
+type recordedResponse struct {
+	Method string      `json:"method"`
+	URL    string      `json:"url"`
+	Status int         `json:"status"`
+	Header http.Header `json:"header"`
+	Body   []byte      `json:"body"`
+}
+
+type httpRecorder struct {
+	config  HTTPRecorderConfig
+	ca      *x509.Certificate
+	caKey   *ecdsa.PrivateKey
+	leafKey *ecdsa.PrivateKey // Shared by the certificates of all hosts
+	client  *http.Client
+
+	mu     sync.Mutex
+	leaves map[string]*tls.Certificate
+	seen   map[string]int // Requests seen per key
+}
+
+func newHTTPRecorder(config HTTPRecorderConfig) (*httpRecorder, error) {
+	if config.Replay {
+		if _, err := os.Stat(config.Dir); err != nil {
+			return nil, err
+		}
+	} else if err := os.MkdirAll(config.Dir, 0o700); err != nil {
+		return nil, err
+	}
+
+	caKey, err := ecdsa.GenerateKey(elliptic.P256(), rand.Reader)
+	if err != nil {
+		return nil, err
+	}
+	leafKey, err := ecdsa.GenerateKey(elliptic.P256(), rand.Reader)
+	if err != nil {
+		return nil, err
+	}
+	serial, err := newSerial()
+	if err != nil {
+		return nil, err
+	}
+	template := &x509.Certificate{
+		SerialNumber:          serial,
+		Subject:               pkix.Name{CommonName: "BoxLite HTTP recorder"},
+		NotBefore:             time.Now().Add(-time.Hour),
+		NotAfter:              time.Now().AddDate(10, 0, 0),
+		KeyUsage:              x509.KeyUsageCertSign | x509.KeyUsageDigitalSignature,
+		BasicConstraintsValid: true,
+		IsCA:                  true,
+	}
+	der, err := x509.CreateCertificate(rand.Reader, template, template, &caKey.PublicKey, caKey)
+	if err != nil {
+		return nil, err
+	}
+	ca, err := x509.ParseCertificate(der)
+	if err != nil {
+		return nil, err
+	}
+	if err := os.MkdirAll(filepath.Dir(config.CACert), 0o755); err != nil {
+		return nil, err
+	}
+	caPEM := pem.EncodeToMemory(&pem.Block{Type: "CERTIFICATE", Bytes: der})
+	if err := os.WriteFile(config.CACert, caPEM, 0o644); err != nil {
+		return nil, err
+	}
+
+	transport := http.DefaultTransport.(*http.Transport).Clone()
+	transport.Proxy = nil
+	return &httpRecorder{
+		config:  config,
+		ca:      ca,
+		caKey:   caKey,
+		leafKey: leafKey,
+		client: &http.Client{
+			Transport: transport,
+			// Redirects are recorded and followed by the guest
+			CheckRedirect: func(*http.Request, []*http.Request) error {
+				return http.ErrUseLastResponse
+			},
+		},
+		leaves: make(map[string]*tls.Certificate),
+		seen:   make(map[string]int),
+	}, nil
+}
+
+// serve runs the proxy on the gateway until ctx is done.
+func (r *httpRecorder) serve(ctx context.Context, vn *virtualnetwork.VirtualNetwork, gatewayIP string) {
+	address := fmt.Sprintf("%s:%d", gatewayIP, r.config.Port)
+	listener, err := vn.Listen("tcp", address)
+	if err != nil {
+		logrus.WithFields(logrus.Fields{"error": err, "address": address}).Error("Failed to listen for HTTP recorder")
+		return
+	}
+	server := &http.Server{Handler: r}
+	go func() {
+		<-ctx.Done()
+		server.Close()
+	}()
+
+	logrus.WithFields(logrus.Fields{"address": address, "dir": r.config.Dir, "replay": r.config.Replay}).Info("HTTP recorder listening")
+	if err := server.Serve(listener); err != nil && !errors.Is(err, http.ErrServerClosed) {
+		logrus.WithError(err).Error("HTTP recorder stopped")
+	}
+}
+
+func (r *httpRecorder) ServeHTTP(w http.ResponseWriter, req *http.Request) {
+	if req.Method == http.MethodConnect {
+		r.intercept(w, req)
+		return
+	}
+	if !req.URL.IsAbs() {
+		http.Error(w, "boxlite recorder: not a proxy request", http.StatusBadRequest)
+		return
+	}
+
+	resp := r.exchange(req)
+	defer resp.body.Close()
+	for name, values := range resp.header {
+		w.Header()[name] = values
+	}
+	w.WriteHeader(resp.status)
+	copyFlushing(w, resp.body)
+}
+
+// copyFlushing writes body to w, flushing after each read so a streamed
+// response reaches the guest as it arrives.
+func copyFlushing(w http.ResponseWriter, body io.Reader) {
+	controller := http.NewResponseController(w)
+	buf := make([]byte, 32<<10)
+	for {
+		n, err := body.Read(buf)
+		if n > 0 {
+			if _, err := w.Write(buf[:n]); err != nil {
+				return
+			}
+			controller.Flush()
+		}
+		if err != nil {
+			return
+		}
+	}
+}
+
+// intercept terminates a CONNECT tunnel and answers the HTTPS requests
+// sent through it.
+func (r *httpRecorder) intercept(w http.ResponseWriter, req *http.Request) {
+	hijacker, ok := w.(http.Hijacker)
+	if !ok {
+		http.Error(w, "boxlite recorder: can't take over the connection", http.StatusInternalServerError)
+		return
+	}
+	conn, buffered, err := hijacker.Hijack()
+	if err != nil {
+		logrus.WithError(err).Warn("HTTP recorder failed to take over CONNECT")
+		return
+	}
+	defer conn.Close()
+	if _, err := conn.Write([]byte("HTTP/1.1 200 Connection established\r\n\r\n")); err != nil {
+		return
+	}
+
+	host, _, err := net.SplitHostPort(req.Host)
+	if err != nil {
+		host = req.Host
+	}
+	tlsConn := tls.Server(&bufferedConn{Conn: conn, reader: buffered.Reader}, &tls.Config{
+		GetCertificate: func(hello *tls.ClientHelloInfo) (*tls.Certificate, error) {
+			if hello.ServerName != "" {
+				return r.leaf(hello.ServerName)
+			}
+			return r.leaf(host)
+		},
+		NextProtos: []string{"http/1.1"},
+	})
+	if err := tlsConn.Handshake(); err != nil {
+		logrus.WithFields(logrus.Fields{"error": err, "host": req.Host}).Debug("HTTP recorder TLS handshake failed")
+		return
+	}
+
+	reader := bufio.NewReader(tlsConn)
+	for {
+		inner, err := http.ReadRequest(reader)
+		if err != nil {
+			return
+		}
+		inner.URL.Scheme = "https"
+		inner.URL.Host = inner.Host
+		if inner.URL.Host == "" {
+			inner.URL.Host = req.Host
+		}
+
+		resp := r.exchange(inner)
+		response := &http.Response{
+			StatusCode:    resp.status,
+			ProtoMajor:    1,
+			ProtoMinor:    1,
+			Header:        resp.header.Clone(),
+			Body:          resp.body,
+			ContentLength: resp.length,
+			Request:       inner,
+		}
+		// Without a length the body is written chunked, as it is read
+		err = response.Write(tlsConn)
+		resp.body.Close()
+		if err != nil || inner.Close {
+			return
+		}
+	}
+}
+
+// proxyResponse is the answer to one request: recorded, or read from the
+// network while it is recorded
+type proxyResponse struct {
+	status int
+	header http.Header
+	body   io.ReadCloser
+	length int64 // -1 when not known ahead
+}
+
+func replayed(rec *recordedResponse) *proxyResponse {
+	return &proxyResponse{
+		status: rec.Status,
+		header: rec.Header,
+		body:   io.NopCloser(bytes.NewReader(rec.Body)),
+		length: int64(len(rec.Body)),
+	}
+}
+
+// exchange gets the response to req, from the network or the recording.
+func (r *httpRecorder) exchange(req *http.Request) *proxyResponse {
+	body, err := io.ReadAll(io.LimitReader(req.Body, maxRecordedBody+1))
+	if err != nil {
+		return replayed(failure(http.StatusBadRequest, err.Error()))
+	}
+	if len(body) > maxRecordedBody {
+		return replayed(failure(http.StatusRequestEntityTooLarge, "boxlite recorder: request body too large to record"))
+	}
+	url := req.URL.String()
+	sum := sha256.Sum256([]byte(req.Method + " " + url + "\n" + string(body)))
+	key := hex.EncodeToString(sum[:16])
+
+	r.mu.Lock()
+	n := r.seen[key]
+	r.seen[key] = n + 1
+	r.mu.Unlock()
+
+	if r.config.Replay {
+		for i := n; i >= 0; i-- {
+			if recorded, err := loadRecorded(r.path(key, i)); err == nil {
+				return replayed(recorded)
+			}
+		}
+		logrus.WithFields(logrus.Fields{"method": req.Method, "url": url}).Warn("No recorded response")
+		return replayed(failure(http.StatusBadGateway, fmt.Sprintf("boxlite recorder: no recorded response for %s %s", req.Method, url)))
+	}
+
+	out, err := http.NewRequestWithContext(req.Context(), req.Method, url, bytes.NewReader(body))
+	if err != nil {
+		return replayed(failure(http.StatusBadRequest, err.Error()))
+	}
+	out.Header = req.Header.Clone()
+	removeHopHeaders(out.Header)
+	resp, err := r.client.Do(out)
+	if err != nil {
+		return replayed(failure(http.StatusBadGateway, err.Error()))
+	}
+
+	header := resp.Header.Clone()
+	removeHopHeaders(header)
+	header.Del("Content-Length")
+	return &proxyResponse{
+		status: resp.StatusCode,
+		header: header,
+		body: &recordingBody{
+			ReadCloser: resp.Body,
+			recorded: &recordedResponse{
+				Method: req.Method,
+				URL:    url,
+				Status: resp.StatusCode,
+				Header: header,
+			},
+			path: r.path(key, n),
+		},
+		length: resp.ContentLength,
+	}
+}
+
+// recordingBody passes a response body through, saving the response once
+// the body was read to the end. Past maxRecordedBody it stops keeping the
+// body and the response is not saved.
+type recordingBody struct {
+	io.ReadCloser
+	recorded *recordedResponse
+	path     string
+
+	buf  bytes.Buffer
+	done bool // Saved, or too large to save
+}
+
+func (b *recordingBody) Read(p []byte) (int, error) {
+	n, err := b.ReadCloser.Read(p)
+	if b.done {
+		return n, err
+	}
+	if b.buf.Len()+n > maxRecordedBody {
+		logrus.WithField("url", b.recorded.URL).Warn("Response body too large to record, passing it through")
+		b.done = true
+		b.buf = bytes.Buffer{}
+		return n, err
+	}
+	b.buf.Write(p[:n])
+	if err == io.EOF {
+		b.done = true
+		b.recorded.Body = b.buf.Bytes()
+		if err := b.recorded.save(b.path); err != nil {
+			logrus.WithFields(logrus.Fields{"error": err, "url": b.recorded.URL}).Warn("Failed to save recorded response")
+		}
+	}
+	return n, err
+}
+
+func (r *httpRecorder) path(key string, n int) string {
+	return filepath.Join(r.config.Dir, fmt.Sprintf("%s-%d.json", key, n))
+}
+
+// leaf returns the certificate presented for host.
+func (r *httpRecorder) leaf(host string) (*tls.Certificate, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	if cert, ok := r.leaves[host]; ok {
+		return cert, nil
+	}
+
+	serial, err := newSerial()
+	if err != nil {
+		return nil, err
+	}
+	template := &x509.Certificate{
+		SerialNumber: serial,
+		Subject:      pkix.Name{CommonName: host},
+		NotBefore:    time.Now().Add(-time.Hour),
+		NotAfter:     time.Now().AddDate(1, 0, 0),
+		KeyUsage:     x509.KeyUsageDigitalSignature,
+		ExtKeyUsage:  []x509.ExtKeyUsage{x509.ExtKeyUsageServerAuth},
+	}
+	if ip := net.ParseIP(host); ip != nil {
+		template.IPAddresses = []net.IP{ip}
+	} else {
+		template.DNSNames = []string{host}
+	}
+	der, err := x509.CreateCertificate(rand.Reader, template, r.ca, &r.leafKey.PublicKey, r.caKey)
+	if err != nil {
+		return nil, err
+	}
+	cert := &tls.Certificate{
+		Certificate: [][]byte{der, r.ca.Raw},
+		PrivateKey:  r.leafKey,
+	}
+	r.leaves[host] = cert
+	return cert, nil
+}
+
+func (rec *recordedResponse) save(path string) error {
+	content, err := json.MarshalIndent(rec, "", "  ")
+	if err != nil {
+		return err
+	}
+	return os.WriteFile(path, content, 0o600)
+}
+
+func loadRecorded(path string) (*recordedResponse, error) {
+	content, err := os.ReadFile(path)
+	if err != nil {
+		return nil, err
+	}
+	var rec recordedResponse
+	if err := json.Unmarshal(content, &rec); err != nil {
+		return nil, err
+	}
+	return &rec, nil
+}
+
+// replayFilter keeps a replaying guest off the network. Of the connections
+// it opens only those to the proxy pass, and DHCP to the gateway; any other
+// IPv4 packet to an address outside the virtual network's broadcast and
+// multicast, and all IPv6, is dropped.
+func replayFilter(gatewayIP string, proxyPort uint16) (frameFilter, error) {
+	gateway, err := netip.ParseAddr(gatewayIP)
+	if err != nil {
+		return nil, err
+	}
+	return func(frame []byte) bool {
+		const ethernetHeaderLen = 14
+		if len(frame) < ethernetHeaderLen {
+			return false
+		}
+		switch binary.BigEndian.Uint16(frame[12:14]) {
+		case 0x0800: // IPv4
+		case 0x86dd: // IPv6
+			return false
+		default: // ARP
+			return true
+		}
+		if len(frame) < ethernetHeaderLen+20 {
+			return false
+		}
+		dst := netip.AddrFrom4([4]byte(frame[ethernetHeaderLen+16 : ethernetHeaderLen+20]))
+		if !dst.IsGlobalUnicast() {
+			return true
+		}
+		if dst != gateway {
+			return false
+		}
+		protocol, to, ok := outboundDestination(frame)
+		if !ok {
+			return true
+		}
+		switch protocol {
+		case "tcp":
+			return to.Port() == proxyPort
+		case "udp":
+			return to.Port() == 67 // DHCP
+		}
+		return false
+	}, nil
+}
+
+// failure is a response of the proxy itself, never saved
+func failure(status int, message string) *recordedResponse {
+	return &recordedResponse{
+		Status: status,
+		Header: http.Header{"Content-Type": {"text/plain; charset=utf-8"}},
+		Body:   []byte(message + "\n"),
+	}
+}
+
+func removeHopHeaders(header http.Header) {
+	for _, name := range hopHeaders {
+		header.Del(name)
+	}
+}
+
+func newSerial() (*big.Int, error) {
+	return rand.Int(rand.Reader, new(big.Int).Lsh(big.NewInt(1), 128))
+}
+
+// bufferedConn reads what the HTTP server buffered before the hijack first
+type bufferedConn struct {
+	net.Conn
+	reader *bufio.Reader
+}
+
+func (c *bufferedConn) Read(b []byte) (int, error) {
+	return c.reader.Read(b)
+}
@@ -0,0 +1,117 @@
+package main
+
+import (
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"net/netip"
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func newTestRecorder(t *testing.T, dir string, replay bool) *httpRecorder {
+	t.Helper()
+	recorder, err := newHTTPRecorder(HTTPRecorderConfig{
+		Replay: replay,
+		Dir:    dir,
+		CACert: filepath.Join(t.TempDir(), "ca.pem"),
+		Port:   3128,
+	})
+	if err != nil {
+		t.Fatal(err)
+	}
+	return recorder
+}
+
+func proxyGet(t *testing.T, recorder *httpRecorder, url string) (int, string) {
+	t.Helper()
+	w := httptest.NewRecorder()
+	recorder.ServeHTTP(w, httptest.NewRequest(http.MethodGet, url, nil))
+	body, err := io.ReadAll(w.Result().Body)
+	if err != nil {
+		t.Fatal(err)
+	}
+	return w.Code, string(body)
+}
+
+func TestRecorderReplaysRecordedResponses(t *testing.T) {
+	dir := filepath.Join(t.TempDir(), "recordings")
+	calls := 0
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		calls++
+		w.Write([]byte("hello"))
+	}))
+	url := server.URL + "/greeting"
+
+	if status, body := proxyGet(t, newTestRecorder(t, dir, false), url); status != http.StatusOK || body != "hello" {
+		t.Fatalf("recorded %d %q, want 200 \"hello\"", status, body)
+	}
+	server.Close()
+
+	entries, err := os.ReadDir(dir)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(entries) != 1 {
+		t.Fatalf("saved %d recordings, want 1", len(entries))
+	}
+	info, err := entries[0].Info()
+	if err != nil {
+		t.Fatal(err)
+	}
+	if mode := info.Mode().Perm(); mode != 0o600 {
+		t.Fatalf("recording has mode %o, want 600", mode)
+	}
+
+	replay := newTestRecorder(t, dir, true)
+	for i := 0; i < 2; i++ {
+		if status, body := proxyGet(t, replay, url); status != http.StatusOK || body != "hello" {
+			t.Fatalf("replayed %d %q, want 200 \"hello\"", status, body)
+		}
+	}
+	if calls != 1 {
+		t.Fatalf("server called %d times, want 1", calls)
+	}
+	if status, _ := proxyGet(t, replay, server.URL+"/other"); status != http.StatusBadGateway {
+		t.Fatalf("unrecorded request answered %d, want 502", status)
+	}
+}
+
+func TestReplayFilterAdmitsOnlyTheProxy(t *testing.T) {
+	filter, err := replayFilter("192.168.127.1", 3128)
+	if err != nil {
+		t.Fatal(err)
+	}
+	gateway := netip.MustParseAddr("192.168.127.1")
+
+	if !filter(testFrame(6, netip.AddrPortFrom(gateway, 3128))) {
+		t.Fatal("dropped a connection to the proxy")
+	}
+	if !filter(testFrame(17, netip.AddrPortFrom(gateway, 67))) {
+		t.Fatal("dropped DHCP")
+	}
+	if filter(testFrame(17, netip.AddrPortFrom(gateway, 53))) {
+		t.Fatal("admitted DNS")
+	}
+	if filter(testFrame(6, netip.AddrPortFrom(gateway, 22))) {
+		t.Fatal("admitted another port of the gateway")
+	}
+	if filter(testFrame(6, netip.MustParseAddrPort("93.184.216.34:443"))) {
+		t.Fatal("admitted a connection to the network")
+	}
+	if filter(testFrame(6, netip.MustParseAddrPort("192.168.127.254:8080"))) {
+		t.Fatal("admitted a connection to the host")
+	}
+
+	ipv6 := make([]byte, 14+40)
+	ipv6[12], ipv6[13] = 0x86, 0xdd
+	if filter(ipv6) {
+		t.Fatal("admitted IPv6")
+	}
+	arp := make([]byte, 14+28)
+	arp[12], arp[13] = 0x08, 0x06
+	if !filter(arp) {
+		t.Fatal("dropped ARP")
+	}
+}
@@ -10,7 +10,8 @@
 //!
 //! The shim creates the network backend (gvproxy) from network_config if present.
 //! This ensures networking survives detach operations - the gvproxy lives in the
-//! shim subprocess, not the main boxlite process.
+//! shim subprocess, not the main boxlite process. Boxes using a network driver
+//! arrive with `network_backend_endpoint` already set and no network_config.
 
 use std::path::Path;
 use std::thread;
@@ -204,9 +205,10 @@ fn main() -> BoxliteResult<()> {
         tracing::debug!("Leaked gvproxy instance for VM lifetime");
     }
 
-    // Save detach/parent_pid/transport before config is moved into engine.create()
+    // Save detach/parent_pid/expires_at/transport before config is moved into engine.create()
     let detach = config.detach;
     let parent_pid = config.parent_pid;
+    let expires_at = config.expires_at;
     let transport = config.transport.clone();
 
     // Initialize engine options with defaults
@@ -229,15 +231,18 @@ fn main() -> BoxliteResult<()> {
 
     tracing::info!("Box instance created, handing over process control to Box");
 
-    // Start parent watchdog if detach=false
-    // Watchdog monitors parent process and exits gracefully when parent dies
-    if !detach {
-        start_parent_watchdog(parent_pid, transport);
+    // Start watchdog if detach=false or the box has a TTL
+    // Watchdog exits gracefully when the parent dies or the box expires
+    let watched_parent = (!detach).then_some(parent_pid);
+    if watched_parent.is_some() || expires_at.is_some() {
+        start_watchdog(watched_parent, expires_at, transport);
         tracing::info!(
-            parent_pid = parent_pid,
-            "Parent watchdog started (detach=false)"
+            parent_pid = ?watched_parent,
+            expires_at = ?expires_at,
+            "Watchdog started"
         );
-    } else {
+    }
+    if detach {
         tracing::info!("Running in detached mode (detach=true)");
     }
 
@@ -261,9 +266,10 @@ const GRACEFUL_SHUTDOWN_TIMEOUT_SECS: u64 = 5;
 /// Timeout for guest RPC shutdown (filesystem sync) in seconds.
 const GUEST_SHUTDOWN_TIMEOUT_SECS: u64 = 3;
 
-/// Start a watchdog thread that monitors the parent process.
+/// Start a watchdog thread that monitors the parent process and box expiry.
 ///
-/// If the parent process exits (clean exit or crash), this triggers shutdown:
+/// If the parent process exits (clean exit or crash), or `expires_at` passes
+/// (the box's TTL), this triggers shutdown:
 /// 1. Calls Guest.Shutdown() RPC to flush filesystems (sync)
 /// 2. Sends SIGTERM for graceful shutdown
 /// 3. Waits for timeout
@@ -272,19 +278,33 @@ const GUEST_SHUTDOWN_TIMEOUT_SECS: u64 = 3;
 /// Step 1 is critical: without it, qcow2 COW disk buffers may not be flushed,
 /// leading to ext4 filesystem corruption on the next restart.
 ///
-/// This ensures orphan boxes don't accumulate when `detach=false`.
-fn start_parent_watchdog(parent_pid: u32, transport: boxlite_shared::Transport) {
+/// This ensures orphan boxes don't accumulate when `detach=false`, and that
+/// expired boxes stop even when no runtime is running.
+fn start_watchdog(
+    parent_pid: Option<u32>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    transport: boxlite_shared::Transport,
+) {
     thread::spawn(move || {
         let self_pid = std::process::id();
 
         loop {
             thread::sleep(Duration::from_secs(1));
 
-            if !is_process_alive(parent_pid) {
-                tracing::info!(
-                    parent_pid = parent_pid,
-                    "Parent process exited, initiating graceful shutdown"
-                );
+            let parent_exited = parent_pid.is_some_and(|pid| !is_process_alive(pid));
+            let expired = expires_at.is_some_and(|at| at <= chrono::Utc::now());
+            if parent_exited || expired {
+                if parent_exited {
+                    tracing::info!(
+                        parent_pid = ?parent_pid,
+                        "Parent process exited, initiating graceful shutdown"
+                    );
+                } else {
+                    tracing::info!(
+                        expires_at = ?expires_at,
+                        "Box TTL expired, initiating graceful shutdown"
+                    );
+                }
 
                 // Step 1: Gracefully shut down guest (sync filesystems)
                 match tokio::runtime::Builder::new_current_thread()
@@ -0,0 +1,460 @@
+//! Synchronous API.
+//!
+//! [`BoxliteRuntime`], [`LiteBox`] and [`Execution`] here mirror the async
+//! types of the same name, with every `async fn` turned into a blocking call.
+//! Each runtime owns a Tokio runtime that the calls block on, so callers need
+//! no async executor of their own.
+//!
+//! The methods must not be called from inside an async context (they panic
+//! there, like `tokio::runtime::Runtime::block_on`). Async code should use
+//! [`crate::BoxliteRuntime`] or [`crate::AsyncRuntime`] instead.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use boxlite::blocking::BoxliteRuntime;
+//! use boxlite::{BoxCommand, BoxOptions, BoxliteOptions};
+//!
+//! let runtime = BoxliteRuntime::new(BoxliteOptions::default())?;
+//! let litebox = runtime.create(BoxOptions::default(), None)?;
+//!
+//! let mut execution = litebox.exec(BoxCommand::new("echo").arg("hello"))?;
+//! for line in execution.stdout().into_iter().flatten() {
+//!     print!("{}", line);
+//! }
+//! assert!(execution.wait()?.success());
+//!
+//! runtime.remove(litebox.id().as_str(), true)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+
+use crate::disk::{DiskUsage, RootfsSize};
+use crate::litebox::{self, BoxCommand, CopyOptions, ExecResult, ExecutionId, ExecutionLogs};
+use crate::metrics::{BoxMetrics, RuntimeMetrics};
+use crate::net::NetConnection;
+use crate::runtime::networks::NetworkInfo;
+use crate::runtime::options::{BoxOptions, BoxliteOptions};
+use crate::runtime::plan::BoxPlan;
+use crate::runtime::run_once::RunOutcome;
+use crate::runtime::shutdown::ShutdownReport;
+use crate::runtime::types::{BoxFilter, BoxID, BoxInfo, ImageInfo, ProjectInfo};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Synchronous [`crate::BoxliteRuntime`].
+///
+/// Cheap to clone; clones share the runtime and its Tokio runtime.
+#[derive(Clone)]
+pub struct BoxliteRuntime {
+    inner: crate::BoxliteRuntime,
+    rt: Arc<Runtime>,
+}
+
+impl BoxliteRuntime {
+    /// Create a runtime with the provided options.
+    ///
+    /// Fails like [`crate::BoxliteRuntime::new`], or when the Tokio runtime
+    /// can't be started.
+    pub fn new(options: BoxliteOptions) -> BoxliteResult<Self> {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("boxlite-blocking")
+            .enable_all()
+            .build()
+            .map_err(|e| BoxliteError::Internal(format!("Failed to start Tokio runtime: {}", e)))?;
+        // Tasks started while the runtime initializes belong on `rt` too
+        let inner = {
+            let _guard = rt.enter();
+            crate::BoxliteRuntime::new(options)?
+        };
+        Ok(Self {
+            inner,
+            rt: Arc::new(rt),
+        })
+    }
+
+    /// Create a runtime with default options.
+    pub fn with_defaults() -> BoxliteResult<Self> {
+        Self::new(BoxliteOptions::default())
+    }
+
+    /// The wrapped async runtime.
+    pub fn inner(&self) -> &crate::BoxliteRuntime {
+        &self.inner
+    }
+
+    /// Block on `future` using this runtime's Tokio runtime.
+    ///
+    /// This reaches the async APIs the blocking types don't mirror, such as
+    /// `create_many` or image pinning.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.rt.block_on(future)
+    }
+
+    fn wrap(&self, litebox: crate::LiteBox) -> LiteBox {
+        LiteBox {
+            inner: litebox,
+            rt: Arc::clone(&self.rt),
+        }
+    }
+
+    /// See [`crate::BoxliteRuntime::create`].
+    pub fn create(&self, options: BoxOptions, name: Option<String>) -> BoxliteResult<LiteBox> {
+        let litebox = self.block_on(self.inner.create(options, name))?;
+        Ok(self.wrap(litebox))
+    }
+
+    /// See [`crate::BoxliteRuntime::get_or_create`].
+    pub fn get_or_create(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+    ) -> BoxliteResult<(LiteBox, bool)> {
+        let (litebox, created) = self.block_on(self.inner.get_or_create(options, name))?;
+        Ok((self.wrap(litebox), created))
+    }
+
+    /// See [`crate::BoxliteRuntime::plan`].
+    pub fn plan(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        pull: bool,
+    ) -> BoxliteResult<BoxPlan> {
+        self.block_on(self.inner.plan(options, name, pull))
+    }
+
+    /// See [`crate::BoxliteRuntime::run_once`].
+    pub fn run_once(
+        &self,
+        options: BoxOptions,
+        command: BoxCommand,
+        timeout: Option<Duration>,
+    ) -> BoxliteResult<RunOutcome> {
+        self.block_on(self.inner.run_once(options, command, timeout))
+    }
+
+    /// See [`crate::BoxliteRuntime::fork`].
+    pub fn fork(&self, id_or_name: &str, count: usize) -> BoxliteResult<Vec<LiteBox>> {
+        let forks = self.block_on(self.inner.fork(id_or_name, count))?;
+        Ok(forks
+            .into_iter()
+            .map(|litebox| self.wrap(litebox))
+            .collect())
+    }
+
+    /// See [`crate::BoxliteRuntime::get`].
+    pub fn get(&self, id_or_name: &str) -> BoxliteResult<Option<LiteBox>> {
+        let litebox = self.block_on(self.inner.get(id_or_name))?;
+        Ok(litebox.map(|litebox| self.wrap(litebox)))
+    }
+
+    /// See [`crate::BoxliteRuntime::get_info`].
+    pub fn get_info(&self, id_or_name: &str) -> BoxliteResult<Option<BoxInfo>> {
+        self.block_on(self.inner.get_info(id_or_name))
+    }
+
+    /// See [`crate::BoxliteRuntime::list_info`].
+    pub fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
+        self.block_on(self.inner.list_info())
+    }
+
+    /// See [`crate::BoxliteRuntime::list_info_filtered`].
+    pub fn list_info_filtered(&self, filter: &BoxFilter) -> BoxliteResult<Vec<BoxInfo>> {
+        self.block_on(self.inner.list_info_filtered(filter))
+    }
+
+    /// See [`crate::BoxliteRuntime::list_projects`].
+    pub fn list_projects(&self) -> BoxliteResult<Vec<ProjectInfo>> {
+        self.block_on(self.inner.list_projects())
+    }
+
+    /// See [`crate::BoxliteRuntime::inspect_network`].
+    pub fn inspect_network(&self, name: &str) -> BoxliteResult<NetworkInfo> {
+        self.block_on(self.inner.inspect_network(name))
+    }
+
+    /// See [`crate::BoxliteRuntime::exists`].
+    pub fn exists(&self, id_or_name: &str) -> BoxliteResult<bool> {
+        self.block_on(self.inner.exists(id_or_name))
+    }
+
+    /// See [`crate::BoxliteRuntime::metrics`].
+    pub fn metrics(&self) -> RuntimeMetrics {
+        self.block_on(self.inner.metrics())
+    }
+
+    /// See [`crate::BoxliteRuntime::remove`].
+    pub fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        self.block_on(self.inner.remove(id_or_name, force))
+    }
+
+    /// Pull an image into the local store; see [`crate::BoxliteRuntime::pull_image`].
+    pub fn pull_image(&self, image_ref: &str) -> BoxliteResult<()> {
+        self.block_on(self.inner.pull_image(image_ref)).map(|_| ())
+    }
+
+    /// See [`crate::BoxliteRuntime::list_images`].
+    pub fn list_images(&self) -> BoxliteResult<Vec<ImageInfo>> {
+        self.block_on(self.inner.list_images())
+    }
+
+    /// See [`crate::BoxliteRuntime::shutdown`].
+    pub fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<ShutdownReport> {
+        self.block_on(self.inner.shutdown(timeout))
+    }
+}
+
+/// Synchronous [`crate::LiteBox`].
+pub struct LiteBox {
+    inner: crate::LiteBox,
+    rt: Arc<Runtime>,
+}
+
+impl LiteBox {
+    pub fn id(&self) -> &BoxID {
+        self.inner.id()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// Get box info without triggering VM initialization.
+    pub fn info(&self) -> BoxInfo {
+        self.inner.info()
+    }
+
+    /// Options the box was created with.
+    pub fn options(&self) -> &BoxOptions {
+        self.inner.options()
+    }
+
+    /// The wrapped async handle.
+    pub fn inner(&self) -> &crate::LiteBox {
+        &self.inner
+    }
+
+    /// See [`crate::LiteBox::start`].
+    pub fn start(&self) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.start())
+    }
+
+    /// See [`crate::LiteBox::exec`].
+    pub fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution> {
+        let execution = self.rt.block_on(self.inner.exec(command))?;
+        Ok(Execution::new(execution, &self.rt))
+    }
+
+    /// See [`crate::LiteBox::exec_detached`].
+    pub fn exec_detached(&self, command: BoxCommand) -> BoxliteResult<ExecutionId> {
+        self.rt.block_on(self.inner.exec_detached(command))
+    }
+
+    /// See [`crate::LiteBox::attach_execution`].
+    pub fn attach_execution(&self, execution_id: &str) -> BoxliteResult<Execution> {
+        let execution = self
+            .rt
+            .block_on(self.inner.attach_execution(execution_id))?;
+        Ok(Execution::new(execution, &self.rt))
+    }
+
+    /// See [`crate::LiteBox::execution_logs`].
+    pub fn execution_logs(&self, execution_id: &str) -> BoxliteResult<ExecutionLogs> {
+        self.inner.execution_logs(execution_id)
+    }
+
+    /// See [`crate::LiteBox::disk_usage`].
+    pub fn disk_usage(&self) -> BoxliteResult<DiskUsage> {
+        self.inner.disk_usage()
+    }
+
+    /// See [`crate::LiteBox::rootfs_size`].
+    pub fn rootfs_size(&self) -> BoxliteResult<Option<RootfsSize>> {
+        self.inner.rootfs_size()
+    }
+
+    /// See [`crate::LiteBox::guest_logs`].
+    pub fn guest_logs(&self, tail: Option<usize>) -> BoxliteResult<Vec<String>> {
+        self.rt.block_on(self.inner.guest_logs(tail))
+    }
+
+    /// See [`crate::LiteBox::net_connections`].
+    pub fn net_connections(&self) -> BoxliteResult<Vec<NetConnection>> {
+        self.rt.block_on(self.inner.net_connections())
+    }
+
+    /// See [`crate::LiteBox::metrics`].
+    pub fn metrics(&self) -> BoxliteResult<BoxMetrics> {
+        self.rt.block_on(self.inner.metrics())
+    }
+
+    /// See [`crate::LiteBox::stop`].
+    pub fn stop(&self) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.stop())
+    }
+
+    /// See [`crate::LiteBox::copy_into`].
+    pub fn copy_into(
+        &self,
+        host_src: impl AsRef<Path>,
+        container_dst: impl AsRef<str>,
+        opts: CopyOptions,
+    ) -> BoxliteResult<()> {
+        self.rt
+            .block_on(self.inner.copy_into(host_src, container_dst, opts))
+    }
+
+    /// See [`crate::LiteBox::copy_out`].
+    pub fn copy_out(
+        &self,
+        container_src: impl AsRef<str>,
+        host_dst: impl AsRef<Path>,
+        opts: CopyOptions,
+    ) -> BoxliteResult<()> {
+        self.rt
+            .block_on(self.inner.copy_out(container_src, host_dst, opts))
+    }
+}
+
+/// Synchronous [`crate::Execution`].
+pub struct Execution {
+    inner: litebox::Execution,
+    rt: Arc<Runtime>,
+}
+
+impl Execution {
+    fn new(inner: litebox::Execution, rt: &Arc<Runtime>) -> Self {
+        Self {
+            inner,
+            rt: Arc::clone(rt),
+        }
+    }
+
+    /// Get the execution ID.
+    pub fn id(&self) -> &ExecutionId {
+        self.inner.id()
+    }
+
+    /// Take the stdin stream (can only be called once).
+    pub fn stdin(&mut self) -> Option<ExecStdin> {
+        let inner = self.inner.stdin()?;
+        Some(ExecStdin {
+            inner,
+            rt: Arc::clone(&self.rt),
+        })
+    }
+
+    /// Take the stdout lines (can only be called once).
+    pub fn stdout(&mut self) -> Option<OutputLines<litebox::ExecStdout>> {
+        let stream = self.inner.stdout()?;
+        Some(OutputLines::new(stream, &self.rt))
+    }
+
+    /// Take the stderr lines (can only be called once).
+    pub fn stderr(&mut self) -> Option<OutputLines<litebox::ExecStderr>> {
+        let stream = self.inner.stderr()?;
+        Some(OutputLines::new(stream, &self.rt))
+    }
+
+    /// Take the stdout lines, framed by [`crate::ExecLines`] (can only be
+    /// called once, shares the stream with [`Execution::stdout`]).
+    pub fn stdout_lines(&mut self) -> Option<OutputLines<litebox::ExecLines>> {
+        let stream = self.inner.stdout_lines()?;
+        Some(OutputLines::new(stream, &self.rt))
+    }
+
+    /// Take the stderr lines, framed by [`crate::ExecLines`] (can only be
+    /// called once, shares the stream with [`Execution::stderr`]).
+    pub fn stderr_lines(&mut self) -> Option<OutputLines<litebox::ExecLines>> {
+        let stream = self.inner.stderr_lines()?;
+        Some(OutputLines::new(stream, &self.rt))
+    }
+
+    /// Take stdout and stderr as one iterator of chunks in guest order
+    /// (can only be called once). See [`crate::Execution::output`].
+    pub fn output(&mut self) -> Option<OutputLines<litebox::ExecOutputStream>> {
+        let stream = self.inner.output()?;
+        Some(OutputLines::new(stream, &self.rt))
+    }
+
+    /// See [`crate::Execution::wait`].
+    pub fn wait(&mut self) -> BoxliteResult<ExecResult> {
+        self.rt.block_on(self.inner.wait())
+    }
+
+    /// See [`crate::Execution::kill`].
+    pub fn kill(&mut self) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.kill())
+    }
+
+    /// See [`crate::Execution::signal`].
+    pub fn signal(&self, signal: i32) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.signal(signal))
+    }
+
+    /// See [`crate::Execution::resize_tty`].
+    pub fn resize_tty(&self, rows: u32, cols: u32) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.resize_tty(rows, cols))
+    }
+}
+
+/// Synchronous [`crate::ExecStdin`].
+pub struct ExecStdin {
+    inner: litebox::ExecStdin,
+    rt: Arc<Runtime>,
+}
+
+impl ExecStdin {
+    /// Write data to stdin.
+    pub fn write(&mut self, data: &[u8]) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.write(data))
+    }
+
+    /// Write all data to stdin.
+    pub fn write_all(&mut self, data: &[u8]) -> BoxliteResult<()> {
+        self.rt.block_on(self.inner.write_all(data))
+    }
+
+    /// Close stdin stream, signaling EOF to the process.
+    pub fn close(&mut self) {
+        self.inner.close()
+    }
+
+    /// Check if stdin is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+/// Output of an execution as a blocking iterator; ends when the stream closes.
+pub struct OutputLines<S> {
+    stream: S,
+    rt: Arc<Runtime>,
+}
+
+impl<S> OutputLines<S> {
+    fn new(stream: S, rt: &Arc<Runtime>) -> Self {
+        Self {
+            stream,
+            rt: Arc::clone(rt),
+        }
+    }
+}
+
+impl<S> Iterator for OutputLines<S>
+where
+    S: futures::Stream + Unpin,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<S::Item> {
+        self.rt.block_on(self.stream.next())
+    }
+}
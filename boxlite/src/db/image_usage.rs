@@ -0,0 +1,275 @@
+//! Image usage statistics and background prefetch settings.
+//!
+//! Every box created from an image bumps that image's use count. The
+//! prefetcher reads the most used references back to keep them warm.
+
+use rusqlite::{OptionalExtension, params};
+
+use boxlite_shared::errors::BoxliteResult;
+
+use crate::runtime::options::ImagePullPolicy;
+
+use super::{Database, db_err};
+
+/// How often boxes were created from an image reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageUsage {
+    /// Image reference as given in `RootfsSpec::Image`
+    pub reference: String,
+    /// Boxes created from the reference
+    pub use_count: u64,
+    /// When the last of them was created (RFC 3339)
+    pub last_used_at: String,
+    /// Pull policy of the last box created from the reference
+    pub pull_policy: ImagePullPolicy,
+}
+
+/// Persisted prefetch setting and outcome of the last round.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefetchState {
+    pub enabled: bool,
+    /// When the last round started (RFC 3339)
+    pub last_run_at: Option<String>,
+    /// Images pulled or found up to date in the last round
+    pub last_refreshed: u32,
+    /// First error of the last round, if any image failed
+    pub last_error: Option<String>,
+}
+
+/// Image usage and prefetch storage wrapping Database.
+#[derive(Clone)]
+pub struct ImageUsageStore {
+    db: Database,
+}
+
+impl ImageUsageStore {
+    /// Create a new ImageUsageStore from a Database.
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Count one more box created from `reference` with `pull_policy`.
+    pub fn record_use(
+        &self,
+        reference: &str,
+        pull_policy: ImagePullPolicy,
+        used_at: &str,
+    ) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+        db_err!(conn.execute(
+            r#"
+            INSERT INTO image_usage (reference, use_count, last_used_at, pull_policy)
+            VALUES (?1, 1, ?2, ?3)
+            ON CONFLICT(reference) DO UPDATE SET
+                use_count = use_count + 1,
+                last_used_at = excluded.last_used_at,
+                pull_policy = excluded.pull_policy
+            "#,
+            params![reference, used_at, pull_policy.as_str()],
+        ))?;
+        Ok(())
+    }
+
+    /// Drop the counts of `references`, e.g. of a removed image, so it is
+    /// not prefetched back.
+    pub fn forget(&self, references: &[&str]) -> BoxliteResult<usize> {
+        let conn = self.db.conn();
+        let mut removed = 0;
+        for reference in references {
+            removed += db_err!(conn.execute(
+                "DELETE FROM image_usage WHERE reference = ?1",
+                params![reference],
+            ))?;
+        }
+        Ok(removed)
+    }
+
+    /// Drop the counts of references last used before `before` (RFC 3339).
+    pub fn prune(&self, before: &str) -> BoxliteResult<usize> {
+        let conn = self.db.conn();
+        let removed = db_err!(conn.execute(
+            "DELETE FROM image_usage WHERE last_used_at < ?1",
+            params![before],
+        ))?;
+        Ok(removed)
+    }
+
+    /// Most used references last used at or after `since` (RFC 3339), most used first.
+    pub fn most_used(&self, since: &str, limit: usize) -> BoxliteResult<Vec<ImageUsage>> {
+        let conn = self.db.conn();
+        let mut stmt = db_err!(conn.prepare(
+            r#"
+            SELECT reference, use_count, last_used_at, pull_policy
+            FROM image_usage
+            WHERE last_used_at >= ?1
+            ORDER BY use_count DESC, last_used_at DESC
+            LIMIT ?2
+            "#
+        ))?;
+        let rows = db_err!(stmt.query_map(params![since, limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        }))?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            let (reference, use_count, last_used_at, pull_policy) = db_err!(row)?;
+            result.push(ImageUsage {
+                reference,
+                use_count: use_count as u64,
+                last_used_at,
+                // Rows are only written from a valid policy
+                pull_policy: pull_policy.parse().unwrap_or_default(),
+            });
+        }
+        Ok(result)
+    }
+
+    /// Prefetch setting and last round; disabled if never set.
+    pub fn prefetch_state(&self) -> BoxliteResult<PrefetchState> {
+        let conn = self.db.conn();
+        let row = db_err!(
+            conn.query_row(
+                "SELECT enabled, last_run_at, last_refreshed, last_error FROM image_prefetch WHERE id = 1",
+                [],
+                |row| {
+                    Ok(PrefetchState {
+                        enabled: row.get::<_, i32>(0)? != 0,
+                        last_run_at: row.get(1)?,
+                        last_refreshed: row.get(2)?,
+                        last_error: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+        )?;
+        Ok(row.unwrap_or_default())
+    }
+
+    /// Turn background prefetch on or off.
+    pub fn set_prefetch_enabled(&self, enabled: bool) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+        db_err!(conn.execute(
+            r#"
+            INSERT INTO image_prefetch (id, enabled) VALUES (1, ?1)
+            ON CONFLICT(id) DO UPDATE SET enabled = excluded.enabled
+            "#,
+            params![if enabled { 1 } else { 0 }],
+        ))?;
+        Ok(())
+    }
+
+    /// Claim a prefetch round: set `last_run_at` to `now` unless another
+    /// process started one at or after `not_before`.
+    ///
+    /// Returns false if the round was already claimed.
+    pub fn claim_prefetch_round(&self, now: &str, not_before: &str) -> BoxliteResult<bool> {
+        let conn = self.db.conn();
+        let rows_affected = db_err!(conn.execute(
+            r#"
+            INSERT INTO image_prefetch (id, last_run_at) VALUES (1, ?1)
+            ON CONFLICT(id) DO UPDATE SET last_run_at = excluded.last_run_at
+            WHERE last_run_at IS NULL OR last_run_at < ?2
+            "#,
+            params![now, not_before],
+        ))?;
+        Ok(rows_affected > 0)
+    }
+
+    /// Store the outcome of the round claimed last.
+    pub fn finish_prefetch_round(&self, refreshed: u32, error: Option<&str>) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+        db_err!(conn.execute(
+            "UPDATE image_prefetch SET last_refreshed = ?1, last_error = ?2 WHERE id = 1",
+            params![refreshed, error],
+        ))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_store() -> (ImageUsageStore, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        (ImageUsageStore::new(db), dir)
+    }
+
+    #[test]
+    fn most_used_orders_by_count_and_skips_stale() {
+        let (store, _dir) = create_test_store();
+        let policy = ImagePullPolicy::IfNotPresent;
+        store
+            .record_use("alpine:latest", policy, "2026-01-01T00:00:00Z")
+            .unwrap();
+        store
+            .record_use("python:3.12", policy, "2026-01-02T00:00:00Z")
+            .unwrap();
+        store
+            .record_use(
+                "python:3.12",
+                ImagePullPolicy::Always,
+                "2026-01-03T00:00:00Z",
+            )
+            .unwrap();
+        store
+            .record_use("old:1", policy, "2025-01-01T00:00:00Z")
+            .unwrap();
+
+        let used = store.most_used("2026-01-01T00:00:00Z", 10).unwrap();
+        let refs: Vec<_> = used.iter().map(|u| u.reference.as_str()).collect();
+        assert_eq!(refs, vec!["python:3.12", "alpine:latest"]);
+        assert_eq!(used[0].use_count, 2);
+        assert_eq!(used[0].pull_policy, ImagePullPolicy::Always);
+
+        assert_eq!(store.most_used("2020-01-01T00:00:00Z", 1).unwrap().len(), 1);
+
+        assert_eq!(store.prune("2026-01-01T00:00:00Z").unwrap(), 1);
+        assert_eq!(
+            store
+                .forget(&["alpine:latest", "docker.io/library/alpine:latest"])
+                .unwrap(),
+            1
+        );
+        let refs: Vec<_> = store
+            .most_used("2020-01-01T00:00:00Z", 10)
+            .unwrap()
+            .into_iter()
+            .map(|u| u.reference)
+            .collect();
+        assert_eq!(refs, vec!["python:3.12"]);
+    }
+
+    #[test]
+    fn prefetch_rounds_are_claimed_once() {
+        let (store, _dir) = create_test_store();
+        assert_eq!(store.prefetch_state().unwrap(), PrefetchState::default());
+
+        store.set_prefetch_enabled(true).unwrap();
+        assert!(
+            store
+                .claim_prefetch_round("2026-01-01T06:00:00Z", "2026-01-01T00:00:00Z")
+                .unwrap()
+        );
+        // A second process within the interval doesn't run another round
+        assert!(
+            !store
+                .claim_prefetch_round("2026-01-01T07:00:00Z", "2026-01-01T01:00:00Z")
+                .unwrap()
+        );
+        store.finish_prefetch_round(2, Some("pull failed")).unwrap();
+
+        let state = store.prefetch_state().unwrap();
+        assert!(state.enabled);
+        assert_eq!(state.last_run_at.as_deref(), Some("2026-01-01T06:00:00Z"));
+        assert_eq!(state.last_refreshed, 2);
+        assert_eq!(state.last_error.as_deref(), Some("pull failed"));
+    }
+}
@@ -27,6 +27,9 @@ pub struct CachedImage {
 
     /// Whether all layers are fully downloaded
     pub complete: bool,
+
+    /// Whether the image is pinned (protected from pruning)
+    pub pinned: bool,
 }
 
 /// Image index storage wrapping Database.
@@ -49,17 +52,17 @@ impl ImageIndexStore {
     pub fn get(&self, reference: &str) -> BoxliteResult<Option<CachedImage>> {
         let conn = self.db.conn();
 
-        let row: Option<(String, String, String, String, i32)> = db_err!(
+        let row: Option<(String, String, String, String, i32, i32)> = db_err!(
             conn.query_row(
-                "SELECT manifest_digest, config_digest, layers, cached_at, complete FROM image_index WHERE reference = ?1",
+                "SELECT manifest_digest, config_digest, layers, cached_at, complete, pinned FROM image_index WHERE reference = ?1",
                 params![reference],
-                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
             )
             .optional()
         )?;
 
         match row {
-            Some((manifest_digest, config_digest, layers_json, cached_at, complete)) => {
+            Some((manifest_digest, config_digest, layers_json, cached_at, complete, pinned)) => {
                 let layers: Vec<String> = serde_json::from_str(&layers_json).map_err(|e| {
                     BoxliteError::Database(format!("Failed to deserialize layers: {}", e))
                 })?;
@@ -69,6 +72,7 @@ impl ImageIndexStore {
                     layers,
                     cached_at,
                     complete: complete != 0,
+                    pinned: pinned != 0,
                 }))
             }
             None => Ok(None),
@@ -76,6 +80,8 @@ impl ImageIndexStore {
     }
 
     /// Add or update cached image.
+    ///
+    /// The pinned flag is only set on insert; re-pulling a pinned image keeps it pinned.
     pub fn upsert(&self, reference: &str, image: &CachedImage) -> BoxliteResult<()> {
         let conn = self.db.conn();
 
@@ -84,8 +90,8 @@ impl ImageIndexStore {
 
         db_err!(conn.execute(
             r#"
-            INSERT INTO image_index (reference, manifest_digest, config_digest, layers, cached_at, complete)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            INSERT INTO image_index (reference, manifest_digest, config_digest, layers, cached_at, complete, pinned)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             ON CONFLICT(reference) DO UPDATE SET
                 manifest_digest = excluded.manifest_digest,
                 config_digest = excluded.config_digest,
@@ -99,15 +105,27 @@ impl ImageIndexStore {
                 image.config_digest,
                 layers_json,
                 image.cached_at,
-                if image.complete { 1 } else { 0 }
+                if image.complete { 1 } else { 0 },
+                if image.pinned { 1 } else { 0 }
             ],
         ))?;
 
         Ok(())
     }
 
+    /// Set or clear the pinned flag for a cached image.
+    ///
+    /// Returns false if the image is not in the index.
+    pub fn set_pinned(&self, reference: &str, pinned: bool) -> BoxliteResult<bool> {
+        let conn = self.db.conn();
+        let rows_affected = db_err!(conn.execute(
+            "UPDATE image_index SET pinned = ?2 WHERE reference = ?1",
+            params![reference, if pinned { 1 } else { 0 }]
+        ))?;
+        Ok(rows_affected > 0)
+    }
+
     /// Remove cached image from index.
-    #[allow(dead_code)]
     pub fn remove(&self, reference: &str) -> BoxliteResult<bool> {
         let conn = self.db.conn();
         let rows_affected = db_err!(conn.execute(
@@ -136,7 +154,7 @@ impl ImageIndexStore {
         let conn = self.db.conn();
         let mut stmt = db_err!(conn.prepare(
             r#"
-            SELECT reference, manifest_digest, config_digest, layers, cached_at, complete 
+            SELECT reference, manifest_digest, config_digest, layers, cached_at, complete, pinned
             FROM image_index 
             ORDER BY cached_at DESC
             "#
@@ -149,6 +167,7 @@ impl ImageIndexStore {
             let layers_json: String = row.get(3)?;
             let cached_at: String = row.get(4)?;
             let complete: i32 = row.get(5)?;
+            let pinned: i32 = row.get(6)?;
             Ok((
                 reference,
                 manifest_digest,
@@ -156,13 +175,21 @@ impl ImageIndexStore {
                 layers_json,
                 cached_at,
                 complete,
+                pinned,
             ))
         }))?;
 
         let mut result = Vec::new();
         for row in rows {
-            let (reference, manifest_digest, config_digest, layers_json, cached_at, complete) =
-                db_err!(row)?;
+            let (
+                reference,
+                manifest_digest,
+                config_digest,
+                layers_json,
+                cached_at,
+                complete,
+                pinned,
+            ) = db_err!(row)?;
             let layers: Vec<String> = serde_json::from_str(&layers_json).map_err(|e| {
                 BoxliteError::Database(format!("Failed to deserialize layers: {}", e))
             })?;
@@ -175,6 +202,7 @@ impl ImageIndexStore {
                     layers,
                     cached_at,
                     complete: complete != 0,
+                    pinned: pinned != 0,
                 },
             ));
         }
@@ -205,6 +233,7 @@ mod tests {
             layers: vec!["sha256:layer1".to_string(), "sha256:layer2".to_string()],
             cached_at: "2025-10-24T12:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         store.upsert("python:alpine", &image).unwrap();
@@ -228,6 +257,7 @@ mod tests {
             layers: vec!["sha256:layer1".to_string()],
             cached_at: "2025-10-24T12:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         store.upsert("python:alpine", &image1).unwrap();
@@ -238,6 +268,7 @@ mod tests {
             layers: vec!["sha256:layer2".to_string()],
             cached_at: "2025-10-25T12:00:00Z".to_string(),
             complete: false,
+            pinned: false,
         };
 
         store.upsert("python:alpine", &image2).unwrap();
@@ -266,6 +297,7 @@ mod tests {
             layers: vec![],
             cached_at: "2025-10-24T12:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         store.upsert("python:alpine", &image).unwrap();
@@ -292,6 +324,7 @@ mod tests {
             layers: vec![],
             cached_at: "2025-10-24T12:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         store.upsert("python:alpine", &image).unwrap();
@@ -316,6 +349,7 @@ mod tests {
             layers: vec!["sha256:layer1".to_string()],
             cached_at: "2026-01-21T10:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         let image2 = CachedImage {
@@ -324,6 +358,7 @@ mod tests {
             layers: vec!["sha256:layer2".to_string()],
             cached_at: "2026-01-21T14:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         let image3 = CachedImage {
@@ -332,6 +367,7 @@ mod tests {
             layers: vec!["sha256:layer3".to_string()],
             cached_at: "2026-01-21T08:00:00Z".to_string(),
             complete: true,
+            pinned: false,
         };
 
         store.upsert("alpine:latest", &image1).unwrap();
@@ -346,4 +382,31 @@ mod tests {
         assert_eq!(images[1].0, "alpine:latest"); // 10:00
         assert_eq!(images[2].0, "nginx:latest"); // 08:00
     }
+
+    #[test]
+    fn test_set_pinned_survives_upsert() {
+        let (store, _dir) = create_test_db();
+
+        let image = CachedImage {
+            manifest_digest: "sha256:abc123".to_string(),
+            config_digest: "sha256:config123".to_string(),
+            layers: vec![],
+            cached_at: "2025-10-24T12:00:00Z".to_string(),
+            complete: true,
+            pinned: false,
+        };
+
+        assert!(!store.set_pinned("python:alpine", true).unwrap());
+
+        store.upsert("python:alpine", &image).unwrap();
+        assert!(store.set_pinned("python:alpine", true).unwrap());
+        assert!(store.get("python:alpine").unwrap().unwrap().pinned);
+
+        // Re-pulling the image must not drop the pin
+        store.upsert("python:alpine", &image).unwrap();
+        assert!(store.get("python:alpine").unwrap().unwrap().pinned);
+
+        assert!(store.set_pinned("python:alpine", false).unwrap());
+        assert!(!store.get("python:alpine").unwrap().unwrap().pinned);
+    }
 }
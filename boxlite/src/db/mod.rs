@@ -7,11 +7,15 @@
 //! Uses JSON blob pattern for flexibility with queryable columns for performance.
 
 mod boxes;
+mod image_usage;
 mod images;
 mod schema;
 
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 use chrono::Utc;
 use parking_lot::{Mutex, MutexGuard};
@@ -19,7 +23,10 @@ use rusqlite::{Connection, OptionalExtension};
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
+use crate::runtime::options::DbDurability;
+
 pub use boxes::BoxStore;
+pub use image_usage::{ImageUsage, ImageUsageStore};
 pub use images::{CachedImage, ImageIndexStore};
 
 /// Helper macro to convert rusqlite errors to BoxliteError.
@@ -31,6 +38,56 @@ macro_rules! db_err {
 
 pub(crate) use db_err;
 
+/// How often `DbDurability::Batched` commits the grouped writes.
+const BATCH_COMMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Savepoint around one caller's writes in the batched transaction.
+const BATCH_SAVEPOINT_BEGIN: &str = "SAVEPOINT batched_write";
+const BATCH_SAVEPOINT_RELEASE: &str = "RELEASE batched_write";
+const BATCH_SAVEPOINT_ROLLBACK: &str = "ROLLBACK TO batched_write; RELEASE batched_write";
+
+/// The database connection, held until dropped (see [`Database::conn`]).
+pub(crate) struct DbConn<'a> {
+    conn: MutexGuard<'a, Connection>,
+    /// Whether the caller's writes are in a savepoint of the batch
+    savepoint: bool,
+}
+
+impl std::ops::Deref for DbConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl std::ops::DerefMut for DbConn<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+impl Drop for DbConn<'_> {
+    fn drop(&mut self) {
+        if !self.savepoint {
+            return;
+        }
+        // Some errors (e.g. SQLITE_FULL) roll back the whole transaction
+        if self.conn.is_autocommit() {
+            tracing::warn!("Batched database transaction was rolled back by an error");
+            return;
+        }
+        let end = if std::thread::panicking() {
+            BATCH_SAVEPOINT_ROLLBACK
+        } else {
+            BATCH_SAVEPOINT_RELEASE
+        };
+        if let Err(e) = self.conn.execute_batch(end) {
+            tracing::warn!(error = %e, "Failed to end batched database write");
+        }
+    }
+}
+
 /// SQLite database handle.
 ///
 /// Thread-safe via `parking_lot::Mutex`. Domain-specific stores
@@ -38,11 +95,18 @@ pub(crate) use db_err;
 #[derive(Clone)]
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    /// Commits grouped writes (`DbDurability::Batched` only)
+    batcher: Option<Arc<BatchCommitter>>,
 }
 
 impl Database {
-    /// Open or create the database.
+    /// Open or create the database, syncing every write to disk.
     pub fn open(db_path: &Path) -> BoxliteResult<Self> {
+        Self::open_with_durability(db_path, DbDurability::Full)
+    }
+
+    /// Open or create the database with the given durability.
+    pub fn open_with_durability(db_path: &Path, durability: DbDurability) -> BoxliteResult<Self> {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -51,28 +115,67 @@ impl Database {
 
         // SQLite configuration (matches Podman patterns)
         // - WAL mode: Better concurrent read performance
-        // - FULL sync: Maximum durability (fsync after each transaction)
+        // - FULL sync: Maximum durability (fsync after each transaction);
+        //   NORMAL only syncs at checkpoints
         // - Foreign keys: Referential integrity
         // - Busy timeout: 100s to handle long operations (Podman uses 100s)
-        db_err!(conn.execute_batch(
+        let synchronous = match durability {
+            DbDurability::Full => "FULL",
+            DbDurability::Normal | DbDurability::Batched => "NORMAL",
+        };
+        db_err!(conn.execute_batch(&format!(
             "
             PRAGMA journal_mode=WAL;
-            PRAGMA synchronous=FULL;
+            PRAGMA synchronous={};
             PRAGMA foreign_keys=ON;
             PRAGMA busy_timeout=100000;
-            "
-        ))?;
+            ",
+            synchronous
+        )))?;
 
         Self::init_schema(&conn)?;
 
-        Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
-        })
+        let conn = Arc::new(Mutex::new(conn));
+        let batcher = match durability {
+            DbDurability::Batched => Some(Arc::new(BatchCommitter::start(conn.clone())?)),
+            DbDurability::Full | DbDurability::Normal => None,
+        };
+        Ok(Self { conn, batcher })
     }
 
     /// Acquire the database connection.
-    pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
-        self.conn.lock()
+    ///
+    /// With batched durability the connection is always inside a
+    /// transaction, so the caller's writes are grouped with others until the
+    /// next commit. Each caller's writes get their own savepoint within it,
+    /// rolled back if the caller panics, so they can't leave half-done work
+    /// for the next commit. Stores needing atomic multi-statement writes use
+    /// savepoints, which work either way.
+    pub(crate) fn conn(&self) -> DbConn<'_> {
+        let conn = self.conn.lock();
+        let mut savepoint = false;
+        if self.batcher.is_some() {
+            let begin = if conn.is_autocommit() {
+                conn.execute_batch("BEGIN")
+            } else {
+                Ok(())
+            };
+            match begin.and_then(|()| conn.execute_batch(BATCH_SAVEPOINT_BEGIN)) {
+                Ok(()) => savepoint = true,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to begin batched database transaction")
+                }
+            }
+        }
+        DbConn { conn, savepoint }
+    }
+
+    /// Commit grouped writes now. Does nothing unless durability is batched.
+    pub(crate) fn flush(&self) -> BoxliteResult<()> {
+        if self.batcher.is_some() {
+            commit(&self.conn.lock())?;
+        }
+        Ok(())
     }
 
     /// Initialize database schema.
@@ -175,6 +278,71 @@ impl Database {
             current = 4;
         }
 
+        // Migration 4 -> 5: Add pinned column to image_index
+        if current == 4 {
+            tracing::info!("Running migration 4 -> 5: Adding pinned column to image_index");
+
+            // Tables created by migration 3 -> 4 already have the column
+            let has_pinned: bool = db_err!(conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('image_index') WHERE name = 'pinned'",
+                [],
+                |row| row.get::<_, i64>(0),
+            ))? > 0;
+            if !has_pinned {
+                db_err!(conn.execute_batch(
+                    "ALTER TABLE image_index ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;"
+                ))?;
+            }
+
+            current = 5;
+        }
+
+        // Migration 5 -> 6: Add project column to box_config
+        if current == 5 {
+            tracing::info!("Running migration 5 -> 6: Adding project column to box_config");
+
+            db_err!(conn.execute_batch(
+                "ALTER TABLE box_config ADD COLUMN project TEXT NOT NULL DEFAULT 'default';"
+            ))?;
+            db_err!(conn.execute_batch(
+                "CREATE INDEX IF NOT EXISTS idx_box_config_project ON box_config(project, created_at);"
+            ))?;
+
+            current = 6;
+        }
+
+        // Migration 6 -> 7: Add image column to box_config
+        if current == 6 {
+            tracing::info!("Running migration 6 -> 7: Adding image column to box_config");
+
+            db_err!(conn.execute_batch(
+                "ALTER TABLE box_config ADD COLUMN image TEXT NOT NULL DEFAULT '';"
+            ))?;
+            // Same value as BoxConfig::image()
+            db_err!(conn.execute_batch(
+                r#"
+                UPDATE box_config SET image = COALESCE(
+                    json_extract(json, '$.options.rootfs.Image'),
+                    'rootfs:' || json_extract(json, '$.options.rootfs.RootfsPath'),
+                    ''
+                );
+                CREATE INDEX IF NOT EXISTS idx_box_config_image ON box_config(image);
+                "#
+            ))?;
+
+            current = 7;
+        }
+
+        // Migration 7 -> 8: Add image_usage and image_prefetch tables
+        if current == 7 {
+            tracing::info!("Running migration 7 -> 8: Adding image usage and prefetch tables");
+
+            db_err!(conn.execute_batch(schema::IMAGE_USAGE_TABLE))?;
+            db_err!(conn.execute_batch(schema::IMAGE_PREFETCH_TABLE))?;
+
+            current = 8;
+        }
+
         // Update schema version
         let now = Utc::now().to_rfc3339();
         db_err!(conn.execute(
@@ -187,6 +355,57 @@ impl Database {
     }
 }
 
+/// Commit the open transaction, if any.
+fn commit(conn: &Connection) -> BoxliteResult<()> {
+    if conn.is_autocommit() {
+        return Ok(());
+    }
+    db_err!(conn.execute_batch("COMMIT"))
+}
+
+/// Thread committing batched writes every `BATCH_COMMIT_INTERVAL`, and once
+/// more when the last `Database` handle is dropped.
+struct BatchCommitter {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BatchCommitter {
+    fn start(conn: Arc<Mutex<Connection>>) -> BoxliteResult<Self> {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = std::thread::Builder::new()
+            .name("boxlite-db-commit".into())
+            .spawn(move || {
+                loop {
+                    let stopping = !matches!(
+                        stopped.recv_timeout(BATCH_COMMIT_INTERVAL),
+                        Err(RecvTimeoutError::Timeout)
+                    );
+                    // Retried at the next tick if it fails (e.g. SQLITE_BUSY)
+                    if let Err(e) = commit(&conn.lock()) {
+                        tracing::warn!(error = %e, "Failed to commit batched database writes");
+                    }
+                    if stopping {
+                        return;
+                    }
+                }
+            })?;
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for BatchCommitter {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,4 +417,108 @@ mod tests {
         let db_path = temp_dir.path().join("test.db");
         let _db = Database::open(&db_path).unwrap();
     }
+
+    #[test]
+    fn test_migration_adds_image_column() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(schema::SCHEMA_VERSION_TABLE).unwrap();
+        conn.execute_batch(
+            r#"
+            INSERT INTO schema_version (id, version, updated_at) VALUES (1, 6, '');
+            CREATE TABLE box_config (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT UNIQUE,
+                project TEXT NOT NULL DEFAULT 'default',
+                created_at INTEGER NOT NULL,
+                json TEXT NOT NULL
+            );
+            INSERT INTO box_config (id, created_at, json)
+                VALUES ('a', 0, '{"options": {"rootfs": {"Image": "alpine:latest"}}}');
+            INSERT INTO box_config (id, created_at, json)
+                VALUES ('b', 0, '{"options": {"rootfs": {"RootfsPath": "/srv/rootfs"}}}');
+            "#,
+        )
+        .unwrap();
+
+        Database::run_migrations(&conn, 6).unwrap();
+
+        let images: Vec<String> = conn
+            .prepare("SELECT image FROM box_config ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(images, vec!["alpine:latest", "rootfs:/srv/rootfs"]);
+    }
+
+    fn synchronous(db: &Database) -> i64 {
+        db.conn()
+            .query_row("PRAGMA synchronous", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    fn alive_rows(db_path: &Path) -> i64 {
+        let conn = Connection::open(db_path).unwrap();
+        conn.query_row("SELECT COUNT(*) FROM alive", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_durability_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        // SQLite reports FULL as 2 and NORMAL as 1
+        let db = Database::open(&db_path).unwrap();
+        assert_eq!(synchronous(&db), 2);
+        drop(db);
+        let db = Database::open_with_durability(&db_path, DbDurability::Normal).unwrap();
+        assert_eq!(synchronous(&db), 1);
+        assert!(db.conn().is_autocommit());
+    }
+
+    #[test]
+    fn test_batched_writes_are_committed() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::open_with_durability(&db_path, DbDurability::Batched).unwrap();
+
+        let insert = "INSERT INTO alive (id, boot_id, started_at) VALUES (1, 'boot', 0)";
+        db.conn().execute(insert, []).unwrap();
+        // Visible to the runtime at once, and to others after a flush
+        let count: i64 = db
+            .conn()
+            .query_row("SELECT COUNT(*) FROM alive", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        db.flush().unwrap();
+        assert_eq!(alive_rows(&db_path), 1);
+
+        // Dropping the last handle commits what's left
+        db.conn().execute("DELETE FROM alive", []).unwrap();
+        drop(db);
+        assert_eq!(alive_rows(&db_path), 0);
+    }
+
+    #[test]
+    fn test_batched_write_is_isolated() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::open_with_durability(&db_path, DbDurability::Batched).unwrap();
+
+        let insert = "INSERT INTO alive (id, boot_id, started_at) VALUES (1, 'boot', 0)";
+        db.conn().execute(insert, []).unwrap();
+
+        // A caller panicking halfway leaves nothing for the next commit
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let conn = db.conn();
+            conn.execute("DELETE FROM alive", []).unwrap();
+            panic!("interrupted write");
+        }));
+        assert!(result.is_err());
+
+        db.flush().unwrap();
+        assert_eq!(alive_rows(&db_path), 1);
+    }
 }
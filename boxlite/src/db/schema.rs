@@ -7,7 +7,7 @@
 //! Each table has queryable columns for efficient filtering + JSON blob for full data.
 
 /// Current schema version.
-pub const SCHEMA_VERSION: i32 = 4;
+pub const SCHEMA_VERSION: i32 = 8;
 
 /// Schema version tracking table.
 pub const SCHEMA_VERSION_TABLE: &str = r#"
@@ -21,18 +21,22 @@ CREATE TABLE IF NOT EXISTS schema_version (
 /// BoxConfig table schema.
 ///
 /// Stores immutable box configuration. JSON blob contains full BoxConfig struct.
-/// Queryable columns: id, name, created_at (for sorting/filtering).
+/// Queryable columns: id, name, project, image, created_at (for sorting/filtering).
 /// Name is UNIQUE but allows NULL (multiple unnamed boxes are allowed).
 pub const BOX_CONFIG_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS box_config (
     id TEXT PRIMARY KEY NOT NULL,
     name TEXT UNIQUE,
+    project TEXT NOT NULL DEFAULT 'default',
+    image TEXT NOT NULL DEFAULT '',
     created_at INTEGER NOT NULL,
     json TEXT NOT NULL
 );
 
 CREATE INDEX IF NOT EXISTS idx_box_config_created_at ON box_config(created_at);
 CREATE INDEX IF NOT EXISTS idx_box_config_name ON box_config(name);
+CREATE INDEX IF NOT EXISTS idx_box_config_project ON box_config(project, created_at);
+CREATE INDEX IF NOT EXISTS idx_box_config_image ON box_config(image);
 "#;
 
 /// BoxState table schema.
@@ -67,6 +71,7 @@ CREATE TABLE IF NOT EXISTS alive (
 ///
 /// Stores cached image metadata. Maps image references to their cached metadata.
 /// Queryable columns for efficient lookup + layers stored as JSON array.
+/// Pinned images are protected from pruning.
 pub const IMAGE_INDEX_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS image_index (
     reference TEXT PRIMARY KEY NOT NULL,
@@ -74,12 +79,42 @@ CREATE TABLE IF NOT EXISTS image_index (
     config_digest TEXT NOT NULL,
     layers TEXT NOT NULL,
     cached_at TEXT NOT NULL,
-    complete INTEGER NOT NULL DEFAULT 0
+    complete INTEGER NOT NULL DEFAULT 0,
+    pinned INTEGER NOT NULL DEFAULT 0
 );
 
 CREATE INDEX IF NOT EXISTS idx_image_index_manifest_digest ON image_index(manifest_digest);
 "#;
 
+/// Image usage table schema.
+///
+/// Counts the boxes created from each image reference, with the pull policy
+/// they used last. Feeds the background image prefetcher.
+pub const IMAGE_USAGE_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS image_usage (
+    reference TEXT PRIMARY KEY NOT NULL,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    last_used_at TEXT NOT NULL,
+    pull_policy TEXT NOT NULL DEFAULT 'if-not-present'
+);
+
+CREATE INDEX IF NOT EXISTS idx_image_usage_use_count ON image_usage(use_count);
+"#;
+
+/// Image prefetch settings table schema.
+///
+/// Single row: whether background prefetch is enabled and how its last
+/// round went.
+pub const IMAGE_PREFETCH_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS image_prefetch (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    enabled INTEGER NOT NULL DEFAULT 0,
+    last_run_at TEXT,
+    last_refreshed INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+"#;
+
 /// Get all schema creation statements.
 pub fn all_schemas() -> Vec<&'static str> {
     vec![
@@ -88,5 +123,7 @@ pub fn all_schemas() -> Vec<&'static str> {
         BOX_STATE_TABLE,
         ALIVE_TABLE,
         IMAGE_INDEX_TABLE,
+        IMAGE_USAGE_TABLE,
+        IMAGE_PREFETCH_TABLE,
     ]
 }
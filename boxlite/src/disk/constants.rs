@@ -17,6 +17,15 @@ pub mod qcow2 {
     pub const BLOCK_SIZE: usize = 512;
 }
 
+/// EROFS layer image configuration
+pub mod erofs {
+    /// Maximum image layers for the erofs storage driver.
+    ///
+    /// Each layer is its own virtio-blk device, and the guest has 26 device
+    /// names (vda..vdz) shared with the rootfs, upper and guest disks.
+    pub const MAX_LAYERS: usize = 20;
+}
+
 /// Ext4 filesystem configuration
 pub mod ext4 {
     /// Ext4 block size in bytes
@@ -41,4 +50,42 @@ pub mod ext4 {
 
     /// Default fallback directory size if calculation fails (in bytes)
     pub const DEFAULT_DIR_SIZE_BYTES: u64 = 64 * 1024 * 1024;
+
+    /// Oldest e2fsprogs release whose mke2fs supports `-d` (populate from directory)
+    pub const MIN_MKE2FS_VERSION: (u32, u32) = (1, 43);
+
+    /// mke2fs profile used instead of the host's `/etc/mke2fs.conf`.
+    ///
+    /// Distributions and Homebrew ship different defaults (e.g. `orphan_file`,
+    /// `metadata_csum_seed`), so images built on different hosts could differ
+    /// or be unmountable by the guest kernel. Pinning the profile makes
+    /// `create_ext4_from_dir` produce the same feature set on every host.
+    pub const MKE2FS_PROFILE: &str = "\
+[defaults]
+\tbase_features = sparse_super,large_file,filetype,resize_inode,dir_index,ext_attr
+\tdefault_mntopts = acl,user_xattr
+\tblocksize = 4096
+\tinode_size = 256
+\tinode_ratio = 16384
+
+[fs_types]
+\text4 = {
+\t\tfeatures = has_journal,extent,huge_file,flex_bg,metadata_csum,64bit,dir_nlink,extra_isize
+\t}
+\tsmall = {
+\t\tinode_ratio = 4096
+\t}
+";
+
+    /// `s_feature_compat` bits expected from [`MKE2FS_PROFILE`]:
+    /// has_journal | ext_attr | resize_inode | dir_index
+    pub const EXPECTED_FEATURE_COMPAT: u32 = 0x0004 | 0x0008 | 0x0010 | 0x0020;
+
+    /// `s_feature_incompat` bits expected from [`MKE2FS_PROFILE`]:
+    /// filetype | extents | 64bit | flex_bg
+    pub const EXPECTED_FEATURE_INCOMPAT: u32 = 0x0002 | 0x0040 | 0x0080 | 0x0200;
+
+    /// `s_feature_ro_compat` bits expected from [`MKE2FS_PROFILE`]:
+    /// sparse_super | large_file | huge_file | dir_nlink | extra_isize | metadata_csum
+    pub const EXPECTED_FEATURE_RO_COMPAT: u32 = 0x0001 | 0x0002 | 0x0008 | 0x0020 | 0x0040 | 0x0400;
 }
@@ -0,0 +1,310 @@
+//! EROFS image creation for the erofs storage driver.
+//!
+//! Each image layer becomes one read-only erofs image that the guest stacks
+//! with overlayfs. Layer directories keep their aufs-style `.wh.` markers
+//! (see `ImageStorage::extract_layer`); `mkfs.erofs --aufs` converts them into
+//! the overlayfs whiteout device nodes and opaque xattrs overlayfs expects.
+//!
+//! A rootless extraction can't set ownership, create device nodes or set
+//! `security.*` xattrs, so such layers are streamed to mkfs.erofs as a tar
+//! archive carrying the metadata recorded in xattrs instead.
+
+use crate::util;
+use boxlite_shared::{BoxliteError, BoxliteResult};
+use std::collections::HashMap;
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use walkdir::WalkDir;
+
+use super::{Disk, DiskFormat};
+use crate::images::{
+    CONTAINERS_OVERRIDE_XATTR, OverrideFileType, OverrideStat, unshadow_xattr_name,
+};
+
+/// Get the path to the mkfs.erofs binary.
+fn get_mkfs_erofs_path() -> BoxliteResult<PathBuf> {
+    util::find_binary("mkfs.erofs").map_err(|e| {
+        BoxliteError::Storage(format!(
+            "mkfs.erofs binary not found (install erofs-utils to use the erofs storage driver): {}",
+            e
+        ))
+    })
+}
+
+/// Build the mkfs.erofs arguments for one layer directory.
+///
+/// With `from_tar` the layer is read as a tar archive from stdin.
+fn mkfs_erofs_args(source: &Path, output_path: &Path, from_tar: bool) -> Vec<String> {
+    let mut args = vec![
+        // Turn .wh.<name> / .wh..wh..opq into overlayfs whiteouts
+        "--aufs".to_string(),
+        "--quiet".to_string(),
+    ];
+    if from_tar {
+        args.push("--tar=f".to_string());
+    }
+    args.push(output_path.to_string_lossy().into_owned());
+    if !from_tar {
+        args.push(source.to_string_lossy().into_owned());
+    }
+    args
+}
+
+/// Xattrs to store in the image for one path, under their real names.
+///
+/// Shadowed privileged xattrs are restored and `user.containers.override_stat`
+/// is dropped; its content goes into the tar header instead.
+fn layer_xattrs(path: &Path) -> std::io::Result<(Option<OverrideStat>, Vec<(String, Vec<u8>)>)> {
+    let mut stat = None;
+    let mut xattrs = Vec::new();
+    for name in xattr::list(path)? {
+        let Some(name) = name.to_str() else { continue };
+        if name == CONTAINERS_OVERRIDE_XATTR {
+            stat = OverrideStat::read_xattr(path)?;
+            continue;
+        }
+        let Some(value) = xattr::get(path, name)? else {
+            continue;
+        };
+        let real = unshadow_xattr_name(name).unwrap_or(name);
+        xattrs.push((real.to_string(), value));
+    }
+    Ok((stat, xattrs))
+}
+
+/// Write a layer directory as a tar archive for `mkfs.erofs --tar`.
+///
+/// Ownership, mode and device nodes come from `user.containers.override_stat`
+/// where it was recorded; other paths are owned by root, as with a rootful
+/// extraction of a root-owned layer.
+fn write_layer_tar<W: Write>(source: &Path, out: W) -> std::io::Result<()> {
+    let mut builder = tar::Builder::new(out);
+    // Hard links within the layer, by (dev, ino) of the first path seen
+    let mut links: HashMap<(u64, u64), PathBuf> = HashMap::new();
+
+    for entry in WalkDir::new(source).min_depth(1).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+        let Ok(name) = path.strip_prefix(source) else {
+            continue;
+        };
+        let meta = std::fs::symlink_metadata(path)?;
+        let file_type = meta.file_type();
+        if file_type.is_socket() {
+            // Tar can't hold sockets, and they are meaningless in an image
+            continue;
+        }
+
+        let (stat, xattrs) = layer_xattrs(path)?;
+        if !xattrs.is_empty() {
+            let records: Vec<(String, &[u8])> = xattrs
+                .iter()
+                .map(|(key, value)| (format!("SCHILY.xattr.{}", key), value.as_slice()))
+                .collect();
+            builder.append_pax_extensions(records.iter().map(|(k, v)| (k.as_str(), *v)))?;
+        }
+
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(meta.mtime().max(0) as u64);
+        match &stat {
+            Some(stat) => {
+                header.set_uid(stat.uid as u64);
+                header.set_gid(stat.gid as u64);
+                header.set_mode(stat.mode & 0o7777);
+            }
+            None => {
+                header.set_uid(0);
+                header.set_gid(0);
+                header.set_mode(meta.mode() & 0o7777);
+            }
+        }
+
+        // Device nodes were extracted as placeholder files
+        let device = stat.as_ref().and_then(|stat| match stat.file_type {
+            OverrideFileType::Char { major, minor } => Some((tar::EntryType::Char, major, minor)),
+            OverrideFileType::Block { major, minor } => Some((tar::EntryType::Block, major, minor)),
+            _ => None,
+        });
+        if let Some((kind, major, minor)) = device {
+            header.set_entry_type(kind);
+            header.set_device_major(major)?;
+            header.set_device_minor(minor)?;
+            header.set_size(0);
+            builder.append_data(&mut header, name, std::io::empty())?;
+        } else if file_type.is_dir() {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            builder.append_data(&mut header, name, std::io::empty())?;
+        } else if file_type.is_symlink() {
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, name, std::fs::read_link(path)?)?;
+        } else if file_type.is_fifo() {
+            header.set_entry_type(tar::EntryType::Fifo);
+            header.set_size(0);
+            builder.append_data(&mut header, name, std::io::empty())?;
+        } else if let Some(first) = (meta.nlink() > 1)
+            .then(|| links.get(&(meta.dev(), meta.ino())))
+            .flatten()
+        {
+            header.set_entry_type(tar::EntryType::Link);
+            header.set_size(0);
+            builder.append_link(&mut header, name, first)?;
+        } else {
+            if meta.nlink() > 1 {
+                links.insert((meta.dev(), meta.ino()), name.to_path_buf());
+            }
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(meta.len());
+            builder.append_data(&mut header, name, std::fs::File::open(path)?)?;
+        }
+    }
+
+    builder.into_inner()?.flush()
+}
+
+/// Run mkfs.erofs with the layer streamed to its stdin as a tar archive.
+fn run_mkfs_with_tar(command: &mut Command, source: &Path) -> std::io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdin = child.stdin.take().expect("stdin is piped");
+
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(|| write_layer_tar(source, stdin));
+        let output = child.wait_with_output()?;
+        let written = writer
+            .join()
+            .unwrap_or_else(|_| Err(std::io::Error::other("tar writer panicked")));
+        match written {
+            // A failed mkfs.erofs closes the pipe; report its own error
+            Err(e) if output.status.success() => Err(e),
+            _ => Ok(output),
+        }
+    })
+}
+
+/// Create an uncompressed erofs image from an extracted layer directory.
+///
+/// A layer extracted rootless is built from a tar stream so the image gets
+/// the ownership, modes and device nodes recorded in its xattrs.
+///
+/// Returns a non-persistent Disk (will be cleaned up on drop).
+pub fn create_erofs_from_dir(source: &Path, output_path: &Path) -> BoxliteResult<Disk> {
+    let mkfs = get_mkfs_erofs_path()?;
+    let rootless = unsafe { libc::geteuid() } != 0;
+
+    let mut command = Command::new(&mkfs);
+    command.args(mkfs_erofs_args(source, output_path, rootless));
+    let output = if rootless {
+        run_mkfs_with_tar(&mut command, source)
+    } else {
+        command.output()
+    };
+    let output = output.map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to run mkfs.erofs ({}): {}",
+            mkfs.display(),
+            e
+        ))
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BoxliteError::Storage(format!(
+            "mkfs.erofs failed for {} with exit code {:?}: {}",
+            source.display(),
+            output.status.code(),
+            stderr
+        )));
+    }
+
+    Ok(Disk::new(
+        output_path.to_path_buf(),
+        DiskFormat::Erofs,
+        false,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mkfs_erofs_args() {
+        let args = mkfs_erofs_args(Path::new("/layers/a"), Path::new("/out/a.erofs"), false);
+        assert_eq!(args, vec!["--aufs", "--quiet", "/out/a.erofs", "/layers/a"]);
+
+        // The tar archive comes from stdin
+        let args = mkfs_erofs_args(Path::new("/layers/a"), Path::new("/out/a.erofs"), true);
+        assert_eq!(args, vec!["--aufs", "--quiet", "--tar=f", "/out/a.erofs"]);
+    }
+
+    #[test]
+    fn test_layer_tar_applies_override_stat() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join("bin")).unwrap();
+        std::fs::write(root.join("bin/su"), b"su").unwrap();
+        std::fs::write(root.join("null"), b"").unwrap();
+        std::fs::write(root.join("plain"), b"plain").unwrap();
+
+        let setuid = OverrideStat::new(0, 0, 0o4755, OverrideFileType::File);
+        if setuid.write_xattr(&root.join("bin/su")).is_err() {
+            // Filesystem without user xattrs
+            return;
+        }
+        OverrideStat::new(0, 0, 0o666, OverrideFileType::Char { major: 1, minor: 3 })
+            .write_xattr(&root.join("null"))
+            .unwrap();
+        OverrideStat::new(1000, 100, 0o755, OverrideFileType::Dir)
+            .write_xattr(&root.join("bin"))
+            .unwrap();
+
+        let mut archive = Vec::new();
+        write_layer_tar(root, &mut archive).unwrap();
+
+        let mut archive = tar::Archive::new(archive.as_slice());
+        let mut seen = HashMap::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let xattrs: Vec<String> = entry
+                .pax_extensions()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .map(|ext| ext.unwrap().key().unwrap().to_string())
+                .collect();
+            assert!(
+                xattrs
+                    .iter()
+                    .all(|key| !key.ends_with(CONTAINERS_OVERRIDE_XATTR))
+            );
+
+            let header = entry.header();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            if header.entry_type() == tar::EntryType::Char {
+                assert_eq!(header.device_major().unwrap(), Some(1));
+                assert_eq!(header.device_minor().unwrap(), Some(3));
+            }
+            let info = (
+                header.entry_type(),
+                header.uid().unwrap(),
+                header.gid().unwrap(),
+                header.mode().unwrap(),
+            );
+            seen.insert(path.trim_end_matches('/').to_string(), info);
+        }
+
+        assert_eq!(seen["bin"], (tar::EntryType::Directory, 1000, 100, 0o755));
+        assert_eq!(seen["bin/su"], (tar::EntryType::Regular, 0, 0, 0o4755));
+        assert_eq!(seen["null"], (tar::EntryType::Char, 0, 0, 0o666));
+        // Without recorded metadata the host user's ownership is replaced
+        assert_eq!(seen["plain"].1, 0);
+        assert_eq!(seen["plain"].2, 0);
+    }
+}
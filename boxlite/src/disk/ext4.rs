@@ -5,19 +5,199 @@ use std::process::Command;
 use walkdir::WalkDir;
 
 use super::constants::ext4::{
-    BLOCK_SIZE, DEFAULT_DIR_SIZE_BYTES, INODE_SIZE, JOURNAL_OVERHEAD_BYTES, MIN_DISK_SIZE_BYTES,
-    SIZE_MULTIPLIER_DEN, SIZE_MULTIPLIER_NUM,
+    BLOCK_SIZE, DEFAULT_DIR_SIZE_BYTES, EXPECTED_FEATURE_COMPAT, EXPECTED_FEATURE_INCOMPAT,
+    EXPECTED_FEATURE_RO_COMPAT, INODE_SIZE, JOURNAL_OVERHEAD_BYTES, MIN_DISK_SIZE_BYTES,
+    MIN_MKE2FS_VERSION, MKE2FS_PROFILE, SIZE_MULTIPLIER_DEN, SIZE_MULTIPLIER_NUM,
 };
 use super::{Disk, DiskFormat};
+use crate::images::{
+    CONTAINERS_OVERRIDE_XATTR, OverrideFileType, OverrideStat, unshadow_xattr_name,
+};
 
 /// Get the path to the mke2fs binary.
-fn get_mke2fs_path() -> PathBuf {
-    util::find_binary("mke2fs").expect("mke2fs binary not found")
+fn get_mke2fs_path() -> BoxliteResult<PathBuf> {
+    util::find_binary("mke2fs")
+        .map_err(|e| BoxliteError::Storage(format!("mke2fs binary not found: {}", e)))
 }
 
 /// Get the path to the debugfs binary.
-fn get_debugfs_path() -> PathBuf {
-    util::find_binary("debugfs").expect("debugfs binary not found")
+fn get_debugfs_path() -> BoxliteResult<PathBuf> {
+    util::find_binary("debugfs")
+        .map_err(|e| BoxliteError::Storage(format!("debugfs binary not found: {}", e)))
+}
+
+/// Parse `(major, minor)` from `mke2fs -V` output, e.g. `mke2fs 1.47.0 (5-Feb-2023)`.
+fn parse_mke2fs_version(output: &str) -> Option<(u32, u32)> {
+    let version = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("mke2fs "))?
+        .split_whitespace()
+        .next()?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Check that `mke2fs` is new enough for `-d`.
+///
+/// Hosts may resolve a system mke2fs instead of the bundled one; old releases
+/// fail later with confusing flag errors.
+fn check_mke2fs_version(mke2fs: &Path) -> BoxliteResult<()> {
+    let output = Command::new(mke2fs).arg("-V").output().map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to run mke2fs ({}): {}",
+            mke2fs.display(),
+            e
+        ))
+    })?;
+
+    // mke2fs prints its version to stderr
+    let text = String::from_utf8_lossy(&output.stderr);
+    match parse_mke2fs_version(&text) {
+        Some(version) if version >= MIN_MKE2FS_VERSION => Ok(()),
+        Some((major, minor)) => Err(BoxliteError::Storage(format!(
+            "mke2fs {}.{} at {} is too old, {}.{} or newer is required",
+            major,
+            minor,
+            mke2fs.display(),
+            MIN_MKE2FS_VERSION.0,
+            MIN_MKE2FS_VERSION.1
+        ))),
+        None => {
+            tracing::warn!(
+                "Could not determine mke2fs version from {}: {}",
+                mke2fs.display(),
+                text.trim()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Fields of the ext4 superblock needed to check a created image and to
+/// tell how full a filesystem is.
+///
+/// The on-disk format is little-endian regardless of host architecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ext4Superblock {
+    block_size: u64,
+    blocks_count: u64,
+    free_blocks_count: u64,
+    inode_size: u16,
+    feature_compat: u32,
+    feature_incompat: u32,
+    feature_ro_compat: u32,
+}
+
+impl Ext4Superblock {
+    /// The superblock starts 1024 bytes into the device.
+    const OFFSET: u64 = 1024;
+    const LEN: usize = 1024;
+    const MAGIC: u16 = 0xEF53;
+    /// `s_feature_incompat` bit: block counts have a high 32-bit half
+    const INCOMPAT_64BIT: u32 = 0x0080;
+
+    fn parse(raw: &[u8]) -> BoxliteResult<Self> {
+        if raw.len() < Self::LEN {
+            return Err(BoxliteError::Storage("ext4 superblock is truncated".into()));
+        }
+        let le16 = |off: usize| u16::from_le_bytes([raw[off], raw[off + 1]]);
+        let le32 =
+            |off: usize| u32::from_le_bytes([raw[off], raw[off + 1], raw[off + 2], raw[off + 3]]);
+
+        let magic = le16(0x38);
+        if magic != Self::MAGIC {
+            return Err(BoxliteError::Storage(format!(
+                "not an ext4 filesystem (magic {:#06x})",
+                magic
+            )));
+        }
+
+        let feature_incompat = le32(0x60);
+        let le64 = |lo: usize, hi: usize| {
+            let high = if feature_incompat & Self::INCOMPAT_64BIT != 0 {
+                u64::from(le32(hi)) << 32
+            } else {
+                0
+            };
+            high | u64::from(le32(lo))
+        };
+
+        Ok(Self {
+            block_size: 1024u64 << le32(0x18),
+            blocks_count: le64(0x04, 0x150),
+            free_blocks_count: le64(0x0C, 0x158),
+            inode_size: le16(0x58),
+            feature_compat: le32(0x5C),
+            feature_incompat,
+            feature_ro_compat: le32(0x64),
+        })
+    }
+
+    fn read(image_path: &Path) -> BoxliteResult<Self> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut raw = vec![0u8; Self::LEN];
+        let mut file = std::fs::File::open(image_path).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to open {}: {}", image_path.display(), e))
+        })?;
+        file.seek(SeekFrom::Start(Self::OFFSET))
+            .and_then(|_| file.read_exact(&mut raw))
+            .map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to read superblock of {}: {}",
+                    image_path.display(),
+                    e
+                ))
+            })?;
+        Self::parse(&raw)
+    }
+
+    /// Check the image has exactly the layout `MKE2FS_PROFILE` asks for.
+    ///
+    /// Unknown incompat features would make the image unmountable by the guest.
+    fn verify(&self) -> BoxliteResult<()> {
+        let mut problems = Vec::new();
+        if self.block_size != BLOCK_SIZE {
+            problems.push(format!("block size {}", self.block_size));
+        }
+        if u64::from(self.inode_size) != INODE_SIZE {
+            problems.push(format!("inode size {}", self.inode_size));
+        }
+        if self.feature_incompat != EXPECTED_FEATURE_INCOMPAT {
+            problems.push(format!("incompat features {:#x}", self.feature_incompat));
+        }
+        if self.feature_compat & EXPECTED_FEATURE_COMPAT != EXPECTED_FEATURE_COMPAT {
+            problems.push(format!("compat features {:#x}", self.feature_compat));
+        }
+        if self.feature_ro_compat & EXPECTED_FEATURE_RO_COMPAT != EXPECTED_FEATURE_RO_COMPAT {
+            problems.push(format!("ro_compat features {:#x}", self.feature_ro_compat));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(BoxliteError::Storage(format!(
+                "mke2fs produced an unexpected ext4 layout: {}",
+                problems.join(", ")
+            )))
+        }
+    }
+}
+
+/// Bytes in use in an ext4 filesystem, metadata and journal included.
+///
+/// `read_at` reads from the device holding the filesystem. The free block
+/// count is the one last written to the superblock, which a mounted
+/// filesystem only updates from time to time.
+pub(crate) fn used_bytes(
+    read_at: impl FnOnce(u64, &mut [u8]) -> BoxliteResult<()>,
+) -> BoxliteResult<u64> {
+    let mut raw = vec![0u8; Ext4Superblock::LEN];
+    read_at(Ext4Superblock::OFFSET, &mut raw)?;
+    let sb = Ext4Superblock::parse(&raw)?;
+    Ok(sb.blocks_count.saturating_sub(sb.free_blocks_count) * sb.block_size)
 }
 
 /// Calculate the total size needed for a directory tree on ext4.
@@ -107,7 +287,17 @@ pub fn create_ext4_from_dir(source: &Path, output_path: &Path) -> BoxliteResult<
         BoxliteError::Storage(format!("Invalid source path: {}", source.display()))
     })?;
 
-    let mke2fs = get_mke2fs_path();
+    let mke2fs = get_mke2fs_path()?;
+    check_mke2fs_version(&mke2fs)?;
+
+    // Pin the mke2fs profile so the host's /etc/mke2fs.conf can't change features
+    let profile = tempfile::NamedTempFile::new()
+        .and_then(|mut f| {
+            use std::io::Write;
+            f.write_all(MKE2FS_PROFILE.as_bytes())?;
+            Ok(f)
+        })
+        .map_err(|e| BoxliteError::Storage(format!("Failed to write mke2fs profile: {}", e)))?;
 
     // Use mke2fs with -d to populate from directory
     // https://man7.org/linux/man-pages/man8/mke2fs.8.html
@@ -116,6 +306,7 @@ pub fn create_ext4_from_dir(source: &Path, output_path: &Path) -> BoxliteResult<
     // -m 0: no reserved blocks (default 5% is wasted for containers)
     // -E root_owner=0:0: set root ownership (important for containers)
     let output = Command::new(&mke2fs)
+        .env("MKE2FS_CONFIG", profile.path())
         .args([
             "-t",
             "ext4",
@@ -150,8 +341,10 @@ pub fn create_ext4_from_dir(source: &Path, output_path: &Path) -> BoxliteResult<
         )));
     }
 
-    // Fix ownership of all files to 0:0 using debugfs
-    fix_ownership_with_debugfs(output_path, source)?;
+    Ext4Superblock::read(output_path)?.verify()?;
+
+    // Restore ownership, modes, device nodes and privileged xattrs using debugfs
+    fix_metadata_with_debugfs(output_path, source)?;
 
     Ok(Disk::new(
         output_path.to_path_buf(),
@@ -160,23 +353,130 @@ pub fn create_ext4_from_dir(source: &Path, output_path: &Path) -> BoxliteResult<
     ))
 }
 
-/// Fix ownership of all files in ext4 image to 0:0 using debugfs.
+/// Image metadata for one path that mke2fs can't copy from a rootless extraction.
+#[derive(Debug, Default)]
+struct RootlessMetadata {
+    /// Intended ownership/mode/type from `user.containers.override_stat`
+    override_stat: Option<OverrideStat>,
+    /// Privileged xattrs stored under shadow names: (real name, shadow name, value)
+    shadow_xattrs: Vec<(String, String, Vec<u8>)>,
+}
+
+impl RootlessMetadata {
+    fn read(path: &Path) -> Self {
+        let mut meta = Self::default();
+        let Ok(names) = xattr::list(path) else {
+            return meta;
+        };
+
+        for name in names {
+            let Some(name) = name.to_str() else { continue };
+            if name == CONTAINERS_OVERRIDE_XATTR {
+                meta.override_stat = OverrideStat::read_xattr(path).ok().flatten();
+            } else if let Some(real) = unshadow_xattr_name(name)
+                && let Ok(Some(value)) = xattr::get(path, name)
+            {
+                meta.shadow_xattrs
+                    .push((real.to_string(), name.to_string(), value));
+            }
+        }
+        meta
+    }
+}
+
+/// Build the debugfs commands that restore `meta` on `ext4_path`.
+///
+/// `value_file` returns the path of a file holding an xattr value
+/// (debugfs reads binary values from files).
+fn debugfs_metadata_commands(
+    ext4_path: &str,
+    meta: &RootlessMetadata,
+    mut value_file: impl FnMut(&[u8]) -> BoxliteResult<PathBuf>,
+) -> BoxliteResult<Vec<String>> {
+    let quoted = format!("\"{}\"", ext4_path);
+    let mut commands = Vec::new();
+
+    match &meta.override_stat {
+        Some(stat) => {
+            // Device nodes were extracted as placeholder files
+            match stat.file_type {
+                OverrideFileType::Char { major, minor } => {
+                    commands.push(format!("rm {}", quoted));
+                    commands.push(format!("mknod {} c {} {}", quoted, major, minor));
+                }
+                OverrideFileType::Block { major, minor } => {
+                    commands.push(format!("rm {}", quoted));
+                    commands.push(format!("mknod {} b {} {}", quoted, major, minor));
+                }
+                _ => {}
+            }
+            commands.push(format!("sif {} uid {}", quoted, stat.uid));
+            commands.push(format!("sif {} gid {}", quoted, stat.gid));
+            // Full inode mode: type bits plus setuid/setgid/sticky and permissions
+            commands.push(format!(
+                "sif {} mode 0{:o}",
+                quoted,
+                stat.file_type.type_bits() | (stat.mode & 0o7777)
+            ));
+            if !matches!(
+                stat.file_type,
+                OverrideFileType::Char { .. } | OverrideFileType::Block { .. }
+            ) {
+                commands.push(format!("ea_rm {} {}", quoted, CONTAINERS_OVERRIDE_XATTR));
+            }
+        }
+        None => {
+            commands.push(format!("sif {} uid 0", quoted));
+            commands.push(format!("sif {} gid 0", quoted));
+        }
+    }
+
+    for (real, shadow, value) in &meta.shadow_xattrs {
+        let file = value_file(value)?;
+        commands.push(format!(
+            "ea_set -f \"{}\" {} {}",
+            file.display(),
+            quoted,
+            real
+        ));
+        commands.push(format!("ea_rm {} {}", quoted, shadow));
+    }
+
+    Ok(commands)
+}
+
+/// Restore image metadata in the ext4 image using debugfs.
 ///
-/// mke2fs -E root_owner=0:0 only sets the root inode.
-/// This function fixes all other files/directories.
-fn fix_ownership_with_debugfs(image_path: &Path, source_dir: &Path) -> BoxliteResult<()> {
-    // Skip if already running as root - mke2fs creates files with current uid/gid
+/// mke2fs -d copies files with the current uid/gid, and a rootless extraction
+/// can't set ownership, create device nodes, or set `security.*` xattrs. The
+/// intended values are recorded in xattrs (see `OverrideStat` and shadow
+/// xattrs), which are applied here. Paths without recorded metadata get 0:0.
+fn fix_metadata_with_debugfs(image_path: &Path, source_dir: &Path) -> BoxliteResult<()> {
+    // Skip if already running as root - extraction applied everything directly
     let current_uid = unsafe { libc::getuid() };
     let current_gid = unsafe { libc::getgid() };
     if current_uid == 0 && current_gid == 0 {
-        tracing::debug!("Running as root, skipping debugfs ownership fix");
+        tracing::debug!("Running as root, skipping debugfs metadata fix");
         return Ok(());
     }
 
     let start = std::time::Instant::now();
 
-    // Collect all paths relative to source_dir
-    let mut paths = Vec::new();
+    // Scratch files for binary xattr values
+    let values_dir = tempfile::tempdir()
+        .map_err(|e| BoxliteError::Storage(format!("Failed to create temp dir: {}", e)))?;
+    let mut value_count = 0usize;
+    let mut write_value = |value: &[u8]| -> BoxliteResult<PathBuf> {
+        value_count += 1;
+        let path = values_dir.path().join(value_count.to_string());
+        std::fs::write(&path, value).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to write xattr value file: {}", e))
+        })?;
+        Ok(path)
+    };
+
+    let mut commands = String::new();
+    let mut path_count = 0usize;
     for entry in WalkDir::new(source_dir).follow_links(false) {
         let entry =
             entry.map_err(|e| BoxliteError::Storage(format!("Failed to walk directory: {}", e)))?;
@@ -194,24 +494,20 @@ fn fix_ownership_with_debugfs(image_path: &Path, source_dir: &Path) -> BoxliteRe
 
         // Convert to absolute path in ext4 (starting with /)
         let ext4_path = format!("/{}", rel_path.display());
-        paths.push(ext4_path);
+        let meta = RootlessMetadata::read(entry.path());
+        for command in debugfs_metadata_commands(&ext4_path, &meta, &mut write_value)? {
+            commands.push_str(&command);
+            commands.push('\n');
+        }
+        path_count += 1;
     }
 
-    if paths.is_empty() {
-        tracing::debug!("No files to fix ownership for");
+    if path_count == 0 {
+        tracing::debug!("No files to fix metadata for");
         return Ok(());
     }
 
-    // Build debugfs commands to set uid=0 and gid=0 for each file
-    // Using sif (set inode field) command: sif <path> <field> <value>
-    let mut commands = String::new();
-    for path in &paths {
-        // sif sets inode field by path
-        commands.push_str(&format!("sif {} uid 0\n", path));
-        commands.push_str(&format!("sif {} gid 0\n", path));
-    }
-
-    let debugfs = get_debugfs_path();
+    let debugfs = get_debugfs_path()?;
 
     // Run debugfs with commands via stdin
     let mut child = Command::new(&debugfs)
@@ -240,17 +536,181 @@ fn fix_ownership_with_debugfs(image_path: &Path, source_dir: &Path) -> BoxliteRe
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         tracing::warn!(
-            "debugfs ownership fix had errors (took {:?}): {}",
+            "debugfs metadata fix had errors (took {:?}): {}",
             duration,
             stderr
         );
     } else {
-        tracing::info!(
-            "Fixed ownership of {} files to 0:0 in {:?}",
-            paths.len(),
-            duration
-        );
+        tracing::info!("Fixed metadata of {} files in {:?}", path_count, duration);
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a raw superblock with the given fields (little-endian on disk).
+    fn raw_superblock(log_block_size: u32, incompat: u32) -> Vec<u8> {
+        let mut raw = vec![0u8; Ext4Superblock::LEN];
+        raw[0x18..0x1C].copy_from_slice(&log_block_size.to_le_bytes());
+        raw[0x38..0x3A].copy_from_slice(&Ext4Superblock::MAGIC.to_le_bytes());
+        raw[0x58..0x5A].copy_from_slice(&256u16.to_le_bytes());
+        raw[0x5C..0x60].copy_from_slice(&EXPECTED_FEATURE_COMPAT.to_le_bytes());
+        raw[0x60..0x64].copy_from_slice(&incompat.to_le_bytes());
+        raw[0x64..0x68].copy_from_slice(&EXPECTED_FEATURE_RO_COMPAT.to_le_bytes());
+        raw
+    }
+
+    #[test]
+    fn test_parse_mke2fs_version() {
+        assert_eq!(
+            parse_mke2fs_version(
+                "mke2fs 1.47.0 (5-Feb-2023)\n\tUsing EXT2FS Library version 1.47.0"
+            ),
+            Some((1, 47))
+        );
+        assert_eq!(
+            parse_mke2fs_version("mke2fs 1.42.9 (28-Dec-2013)"),
+            Some((1, 42))
+        );
+        assert_eq!(parse_mke2fs_version("command not found"), None);
+    }
+
+    #[test]
+    fn test_superblock_parse_is_little_endian() {
+        let sb = Ext4Superblock::parse(&raw_superblock(2, EXPECTED_FEATURE_INCOMPAT)).unwrap();
+        assert_eq!(sb.block_size, 4096);
+        assert_eq!(sb.inode_size, 256);
+        assert_eq!(sb.feature_incompat, EXPECTED_FEATURE_INCOMPAT);
+        assert!(sb.verify().is_ok());
+    }
+
+    #[test]
+    fn test_used_bytes_reads_64bit_counts() {
+        let mut raw = raw_superblock(2, EXPECTED_FEATURE_INCOMPAT);
+        raw[0x04..0x08].copy_from_slice(&1000u32.to_le_bytes());
+        raw[0x0C..0x10].copy_from_slice(&400u32.to_le_bytes());
+        raw[0x150..0x154].copy_from_slice(&1u32.to_le_bytes());
+
+        let used = used_bytes(|offset, buf| {
+            assert_eq!(offset, Ext4Superblock::OFFSET);
+            buf.copy_from_slice(&raw);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(used, ((1u64 << 32) + 600) * 4096);
+    }
+
+    #[test]
+    fn test_superblock_rejects_unexpected_features() {
+        // inline_data (0x8000) is not in the pinned profile
+        let sb =
+            Ext4Superblock::parse(&raw_superblock(2, EXPECTED_FEATURE_INCOMPAT | 0x8000)).unwrap();
+        assert!(sb.verify().is_err());
+
+        let mut raw = raw_superblock(2, EXPECTED_FEATURE_INCOMPAT);
+        raw[0x38] = 0;
+        assert!(Ext4Superblock::parse(&raw).is_err());
+    }
+
+    /// Same assertions on every host architecture: the pinned profile must yield
+    /// the same filesystem layout on Apple Silicon and x86_64 Linux.
+    #[test]
+    fn test_create_ext4_layout_is_host_independent() {
+        if get_mke2fs_path().is_err() || get_debugfs_path().is_err() {
+            eprintln!("Skipping: mke2fs/debugfs runtime binaries not available");
+            return;
+        }
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("rootfs");
+        std::fs::create_dir_all(source.join("etc")).unwrap();
+        std::fs::write(source.join("etc/hostname"), "box\n").unwrap();
+
+        let output = temp_dir.path().join("rootfs.ext4");
+        let disk = create_ext4_from_dir(&source, &output).unwrap();
+
+        let sb = Ext4Superblock::read(disk.path()).unwrap();
+        assert_eq!(sb.block_size, BLOCK_SIZE);
+        assert_eq!(sb.feature_incompat, EXPECTED_FEATURE_INCOMPAT);
+        sb.verify().unwrap();
+    }
+
+    fn no_values(_: &[u8]) -> BoxliteResult<PathBuf> {
+        panic!("unexpected xattr value");
+    }
+
+    #[test]
+    fn test_commands_without_metadata_default_to_root() {
+        let commands =
+            debugfs_metadata_commands("/etc/hosts", &RootlessMetadata::default(), no_values)
+                .unwrap();
+        assert_eq!(
+            commands,
+            vec!["sif \"/etc/hosts\" uid 0", "sif \"/etc/hosts\" gid 0"]
+        );
+    }
+
+    #[test]
+    fn test_commands_keep_setuid_and_ownership() {
+        let meta = RootlessMetadata {
+            override_stat: Some(OverrideStat::new(0, 0, 0o4755, OverrideFileType::File)),
+            shadow_xattrs: vec![],
+        };
+        let commands = debugfs_metadata_commands("/bin/bbsuid", &meta, no_values).unwrap();
+        assert!(commands.contains(&"sif \"/bin/bbsuid\" mode 0104755".to_string()));
+        assert!(commands.contains(&format!(
+            "ea_rm \"/bin/bbsuid\" {}",
+            CONTAINERS_OVERRIDE_XATTR
+        )));
+    }
+
+    #[test]
+    fn test_commands_recreate_device_nodes() {
+        let meta = RootlessMetadata {
+            override_stat: Some(OverrideStat::new(
+                0,
+                0,
+                0o666,
+                OverrideFileType::Char { major: 1, minor: 3 },
+            )),
+            shadow_xattrs: vec![],
+        };
+        let commands = debugfs_metadata_commands("/dev/null", &meta, no_values).unwrap();
+        assert_eq!(commands[0], "rm \"/dev/null\"");
+        assert_eq!(commands[1], "mknod \"/dev/null\" c 1 3");
+        assert!(commands.contains(&"sif \"/dev/null\" mode 020666".to_string()));
+    }
+
+    #[test]
+    fn test_commands_restore_file_capabilities() {
+        let meta = RootlessMetadata {
+            override_stat: None,
+            shadow_xattrs: vec![(
+                "security.capability".to_string(),
+                "user.boxlite.shadow.security.capability".to_string(),
+                vec![0x01, 0x00, 0x00, 0x02],
+            )],
+        };
+        let mut values = Vec::new();
+        let commands = debugfs_metadata_commands("/bin/ping", &meta, |v| {
+            values.push(v.to_vec());
+            Ok(PathBuf::from("/tmp/value-1"))
+        })
+        .unwrap();
+
+        assert_eq!(values, vec![vec![0x01, 0x00, 0x00, 0x02]]);
+        assert!(
+            commands.contains(
+                &"ea_set -f \"/tmp/value-1\" \"/bin/ping\" security.capability".to_string()
+            )
+        );
+        assert!(
+            commands.contains(
+                &"ea_rm \"/bin/ping\" user.boxlite.shadow.security.capability".to_string()
+            )
+        );
+    }
+}
@@ -2,13 +2,19 @@
 //!
 //! Provides a disk wrapper that automatically cleans up on drop.
 
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
 /// Disk image format.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum DiskFormat {
     /// Ext4 filesystem disk image.
     Ext4,
+    /// EROFS read-only filesystem image (one per image layer).
+    Erofs,
     /// QCOW2 (QEMU Copy-On-Write v2).
     Qcow2,
 }
@@ -18,11 +24,45 @@ impl DiskFormat {
     pub fn as_str(&self) -> &'static str {
         match self {
             DiskFormat::Ext4 => "ext4",
+            DiskFormat::Erofs => "erofs",
             DiskFormat::Qcow2 => "qcow2",
         }
     }
+
+    /// Detect the format of the image at `path` from its magic numbers;
+    /// `None` if it is none of these.
+    pub(crate) fn probe(path: &Path) -> std::io::Result<Option<DiskFormat>> {
+        let mut file = std::fs::File::open(path)?;
+        let mut magic = [0u8; 4];
+        if file.read_exact(&mut magic).is_err() {
+            return Ok(None);
+        }
+        if magic == *b"QFI\xfb" {
+            return Ok(Some(DiskFormat::Qcow2));
+        }
+
+        // Both superblocks start 1024 bytes in
+        let mut superblock = [0u8; 60];
+        file.seek(SeekFrom::Start(1024))?;
+        if file.read_exact(&mut superblock).is_err() {
+            return Ok(None);
+        }
+        if superblock[..4] == EROFS_MAGIC.to_le_bytes() {
+            Ok(Some(DiskFormat::Erofs))
+        } else if superblock[56..58] == EXT4_MAGIC.to_le_bytes() {
+            Ok(Some(DiskFormat::Ext4))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
+/// `s_magic` of an ext2/3/4 superblock.
+const EXT4_MAGIC: u16 = 0xEF53;
+
+/// `magic` of an erofs superblock.
+const EROFS_MAGIC: u32 = 0xE0F5_E1E2;
+
 /// RAII-managed disk image.
 ///
 /// Automatically deletes the disk file when dropped (unless persistent=true).
@@ -38,7 +78,7 @@ impl Disk {
     ///
     /// # Arguments
     /// * `path` - Path to the disk file
-    /// * `format` - Disk format (Ext4 or Qcow2)
+    /// * `format` - Disk format (Ext4, Erofs or Qcow2)
     /// * `persistent` - If true, disk won't be deleted on drop
     pub fn new(path: PathBuf, format: DiskFormat, persistent: bool) -> Self {
         Self {
@@ -90,3 +130,36 @@ impl Drop for Disk {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_disk_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, bytes: &[(usize, &[u8])]| {
+            let mut image = vec![0u8; 4096];
+            for (offset, data) in bytes {
+                image[*offset..*offset + data.len()].copy_from_slice(data);
+            }
+            let path = dir.path().join(name);
+            std::fs::write(&path, image).unwrap();
+            path
+        };
+
+        let qcow2 = write("disk.qcow2", &[(0, b"QFI\xfb")]);
+        let ext4 = write("disk.ext4", &[(1080, &EXT4_MAGIC.to_le_bytes())]);
+        let erofs = write("layer.erofs", &[(1024, &EROFS_MAGIC.to_le_bytes())]);
+        let raw = write("disk.raw", &[]);
+        assert_eq!(DiskFormat::probe(&qcow2).unwrap(), Some(DiskFormat::Qcow2));
+        assert_eq!(DiskFormat::probe(&ext4).unwrap(), Some(DiskFormat::Ext4));
+        assert_eq!(DiskFormat::probe(&erofs).unwrap(), Some(DiskFormat::Erofs));
+        assert_eq!(DiskFormat::probe(&raw).unwrap(), None);
+
+        let short = dir.path().join("short");
+        std::fs::write(&short, b"QF").unwrap();
+        assert_eq!(DiskFormat::probe(&short).unwrap(), None);
+        assert!(DiskFormat::probe(&dir.path().join("missing")).is_err());
+    }
+}
@@ -2,15 +2,28 @@
 //!
 //! This module provides disk image creation and management:
 //! - `Disk` - RAII wrapper for disk image files
-//! - `DiskFormat` - Disk format types (Ext4, Qcow2)
+//! - `DiskFormat` - Disk format types (Ext4, Erofs, Qcow2)
 //! - `create_ext4_from_dir` - Create ext4 filesystem from directory
+//! - `create_erofs_from_dir` - Create read-only erofs layer image from directory
 //! - `Qcow2Helper` - QCOW2 copy-on-write disk creation
+//! - `reflink_disk` - Raw disk reflink copies (reflink storage driver)
+//! - `DiskUsage` - Allocated vs virtual size of disk images
+//! - `RootfsSize` - Data written by a box and space used in its rootfs
 
 pub mod constants;
+mod erofs;
 pub(crate) mod ext4;
 mod image;
 mod qcow2;
+mod reflink;
+mod scan;
+mod usage;
 
+pub use erofs::create_erofs_from_dir;
 pub use ext4::create_ext4_from_dir;
 pub use image::{Disk, DiskFormat};
 pub use qcow2::{BackingFormat, Qcow2Helper};
+pub(crate) use reflink::{can_reflink, reflink_disk};
+pub use scan::RootfsSize;
+pub(crate) use scan::{rootfs_digest, rootfs_size};
+pub use usage::{DiskFileUsage, DiskUsage};
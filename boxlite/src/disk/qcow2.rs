@@ -14,16 +14,26 @@ use super::constants::qcow2::{BLOCK_SIZE, CLUSTER_BITS, DEFAULT_DISK_SIZE_GB, RE
 use super::{Disk, DiskFormat};
 
 /// Parsed qcow2 header information.
-#[allow(dead_code)]
 #[derive(Debug)]
-struct Qcow2HeaderInfo {
-    #[allow(dead_code)]
-    version: u32,
-    size: u64,
-    #[allow(dead_code)]
-    cluster_bits: u32,
+pub(crate) struct Qcow2HeaderInfo {
+    pub(crate) size: u64,
+    /// Between 9 and 21, as QEMU requires.
+    pub(crate) cluster_bits: u32,
+    /// Offset of the backing file name, 0 without one.
+    pub(crate) backing_file_offset: u64,
+    /// Length of the backing file name.
+    pub(crate) backing_file_size: u32,
+    /// Entries of the L1 table.
+    pub(crate) l1_size: u32,
+    pub(crate) l1_table_offset: u64,
+    /// Features a reader must understand (v3; 0 for v2).
+    pub(crate) incompatible_features: u64,
 }
 
+/// `incompatible_features` bit of an image whose data is in an external
+/// data file.
+const INCOMPAT_DATA_FILE: u64 = 1 << 2;
+
 /// Helper for qcow2 disk operations.
 pub struct Qcow2Helper;
 
@@ -43,12 +53,30 @@ impl Qcow2Helper {
     /// * `persistent` - If true, disk won't be deleted on drop (used for base disks)
     #[allow(dead_code)]
     pub fn create_disk(&self, disk_path: &Path, persistent: bool) -> BoxliteResult<Disk> {
-        self.create_disk_native(disk_path, persistent)
+        self.create_disk_native(
+            disk_path,
+            DEFAULT_DISK_SIZE_GB * 1024 * 1024 * 1024,
+            persistent,
+        )
+    }
+
+    /// Create an empty sparse qcow2 disk image with the given virtual size in bytes.
+    pub fn create_disk_with_size(
+        &self,
+        disk_path: &Path,
+        size_bytes: u64,
+        persistent: bool,
+    ) -> BoxliteResult<Disk> {
+        self.create_disk_native(disk_path, size_bytes, persistent)
     }
 
     /// Create a qcow2 disk image using native Rust implementation (qcow2-rs).
-    #[allow(dead_code)]
-    fn create_disk_native(&self, disk_path: &Path, persistent: bool) -> BoxliteResult<Disk> {
+    fn create_disk_native(
+        &self,
+        disk_path: &Path,
+        size_bytes: u64,
+        persistent: bool,
+    ) -> BoxliteResult<Disk> {
         // Ensure parent directory exists
         if let Some(parent) = disk_path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| {
@@ -70,13 +98,11 @@ impl Qcow2Helper {
         }
 
         tracing::info!(
-            "Creating qcow2 disk: {} ({}GB sparse)",
+            "Creating qcow2 disk: {} ({}MB sparse)",
             disk_path.display(),
-            DEFAULT_DISK_SIZE_GB
+            size_bytes / (1024 * 1024)
         );
 
-        let size_bytes = DEFAULT_DISK_SIZE_GB * 1024 * 1024 * 1024;
-
         // Calculate required metadata size
         let (rc_table, rc_block, _l1_table) = Qcow2Header::calculate_meta_params(
             size_bytes,
@@ -247,15 +273,35 @@ impl Qcow2Helper {
     }
 
     /// Get the virtual size of a qcow2 disk image.
-    #[allow(dead_code)]
     pub fn qcow2_virtual_size(path: &Path) -> BoxliteResult<u64> {
         let header = Self::read_qcow2_header(path)?;
         Ok(header.size)
     }
 
+    /// Check that a qcow2 image holds all of its data itself.
+    ///
+    /// A backing file or an external data file is opened from a path stored
+    /// in the image, which would let the image pull in any file the shim can
+    /// read; such images must be flattened first.
+    pub fn check_self_contained(path: &Path) -> BoxliteResult<()> {
+        let header = Self::read_qcow2_header(path)?;
+        if header.backing_file_offset != 0 {
+            return Err(BoxliteError::Config(format!(
+                "qcow2 image {} has a backing file; flatten it with `qemu-img convert -O qcow2` first",
+                path.display()
+            )));
+        }
+        if header.incompatible_features & INCOMPAT_DATA_FILE != 0 {
+            return Err(BoxliteError::Config(format!(
+                "qcow2 image {} keeps its data in an external data file; flatten it with `qemu-img convert -O qcow2` first",
+                path.display()
+            )));
+        }
+        Ok(())
+    }
+
     /// Read qcow2 header from disk file.
-    #[allow(dead_code)]
-    fn read_qcow2_header(path: &Path) -> BoxliteResult<Qcow2HeaderInfo> {
+    pub(crate) fn read_qcow2_header(path: &Path) -> BoxliteResult<Qcow2HeaderInfo> {
         use std::io::Read;
 
         let mut file = std::fs::File::open(path).map_err(|e| {
@@ -288,11 +334,32 @@ impl Qcow2Helper {
             header[31],
         ]);
         let cluster_bits = u32::from_be_bytes([header[20], header[21], header[22], header[23]]);
+        if !(9..=21).contains(&cluster_bits) {
+            return Err(BoxliteError::Storage(format!(
+                "Invalid qcow2 cluster bits in {}: {}",
+                path.display(),
+                cluster_bits
+            )));
+        }
+        let backing_file_offset = u64::from_be_bytes(header[8..16].try_into().unwrap());
+        let backing_file_size = u32::from_be_bytes(header[16..20].try_into().unwrap());
+        let l1_size = u32::from_be_bytes(header[36..40].try_into().unwrap());
+        let l1_table_offset = u64::from_be_bytes(header[40..48].try_into().unwrap());
+        // v2 headers end at byte 72
+        let incompatible_features = if version >= 3 {
+            u64::from_be_bytes(header[72..80].try_into().unwrap())
+        } else {
+            0
+        };
 
         Ok(Qcow2HeaderInfo {
-            version,
             size,
             cluster_bits,
+            backing_file_offset,
+            backing_file_size,
+            l1_size,
+            l1_table_offset,
+            incompatible_features,
         })
     }
 
@@ -568,7 +635,6 @@ pub enum BackingFormat {
     /// Raw disk image (ext4, etc.)
     Raw,
     /// Qcow2 disk image.
-    #[allow(dead_code)]
     Qcow2,
 }
 
@@ -581,3 +647,64 @@ impl BackingFormat {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cow_child_over_qcow2_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let helper = Qcow2Helper::new();
+        let base_path = dir.path().join("base.qcow2");
+        let base = helper
+            .create_disk_with_size(&base_path, 256 * 1024 * 1024, true)
+            .unwrap();
+        Qcow2Helper::check_self_contained(base.path()).unwrap();
+
+        let child_path = dir.path().join("disk.qcow2");
+        let child = helper
+            .create_cow_child_disk(
+                base.path(),
+                BackingFormat::Qcow2,
+                &child_path,
+                Qcow2Helper::qcow2_virtual_size(base.path()).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            Qcow2Helper::qcow2_virtual_size(child.path()).unwrap(),
+            256 * 1024 * 1024
+        );
+
+        let content = std::fs::read(child.path()).unwrap();
+        let backing = base_path.canonicalize().unwrap();
+        let backing = backing.to_string_lossy();
+        assert_eq!(&content[512..512 + backing.len()], backing.as_bytes());
+        // Backing format extension
+        assert_eq!(&content[104..108], &0xE2792ACAu32.to_be_bytes());
+        assert_eq!(&content[112..117], b"qcow2");
+
+        // A box's disk can't itself be used as a disk image
+        assert!(matches!(
+            Qcow2Helper::check_self_contained(child.path()),
+            Err(BoxliteError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_external_data_file_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data-file.qcow2");
+        Qcow2Helper::new()
+            .create_disk_with_size(&path, 64 * 1024 * 1024, true)
+            .unwrap();
+
+        let mut content = std::fs::read(&path).unwrap();
+        content[72..80].copy_from_slice(&INCOMPAT_DATA_FILE.to_be_bytes());
+        std::fs::write(&path, content).unwrap();
+        assert!(matches!(
+            Qcow2Helper::check_self_contained(&path),
+            Err(BoxliteError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! Raw disk reflink copies for `StorageDriver::Reflink`.
+//!
+//! Filesystems with reflinks (btrfs, XFS, ZFS 2.2+ with block cloning, APFS)
+//! copy a file instantly: the copy shares every block with the original
+//! until one of them is written. The reflink driver copies the cached base
+//! ext4 image into each box this way in place of a qcow2 overlay, so the VM
+//! reads and writes a plain raw disk while creation stays as cheap as an
+//! overlay. Forks copy it the same way (see `runtime::fork`).
+//!
+//! These are file-level copies, not filesystem snapshots: no btrfs subvolume
+//! or ZFS dataset is created.
+
+use std::path::Path;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use super::{Disk, DiskFormat};
+use crate::rootfs::reflink_file;
+
+/// Whether files in `dir` can be reflinked, checked with a test copy.
+pub(crate) fn can_reflink(dir: &Path) -> bool {
+    match probe(dir) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!(dir = %dir.display(), "Can't reflink files: {}", e);
+            false
+        }
+    }
+}
+
+/// Reflink `base` into a raw disk at `dst` of at least `size` bytes.
+///
+/// A larger size only extends the file; the guest grows the filesystem on
+/// first boot. The returned disk is removed on drop until leaked.
+pub(crate) fn reflink_disk(base: &Path, dst: &Path, size: u64) -> BoxliteResult<Disk> {
+    reflink_file(base, dst).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to reflink {} to {}: {}",
+            base.display(),
+            dst.display(),
+            e
+        ))
+    })?;
+    let disk = Disk::new(dst.to_path_buf(), DiskFormat::Ext4, false);
+
+    let resize = || -> std::io::Result<()> {
+        let file = std::fs::OpenOptions::new().write(true).open(dst)?;
+        if file.metadata()?.len() < size {
+            file.set_len(size)?;
+        }
+        Ok(())
+    };
+    resize()
+        .map_err(|e| BoxliteError::Storage(format!("Failed to resize {}: {}", dst.display(), e)))?;
+
+    Ok(disk)
+}
+
+fn probe(dir: &Path) -> std::io::Result<()> {
+    let temp = tempfile::tempdir_in(dir)?;
+    let src = temp.path().join("probe");
+    std::fs::write(&src, [0u8; 4096])?;
+    reflink_file(&src, &temp.path().join("copy"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reflink_disk_leaves_nothing_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.ext4");
+        std::fs::write(&base, vec![7u8; 8192]).unwrap();
+        let dst = dir.path().join("disk.raw");
+
+        // The temp dir may or may not support reflinks, but no copy is left
+        // behind when it doesn't
+        if reflink_disk(&base, &dst, 16384).is_err() {
+            assert!(!dst.exists());
+        }
+        assert!(reflink_disk(&dir.path().join("missing"), &dst, 16384).is_err());
+        assert!(!dst.exists());
+    }
+
+    #[test]
+    #[ignore] // Requires TMPDIR on a filesystem with reflinks, e.g. a loop-mounted btrfs or XFS image
+    fn test_reflink_disk_extends_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(
+            can_reflink(dir.path()),
+            "{} can't reflink files",
+            dir.path().display()
+        );
+        let base = dir.path().join("base.ext4");
+        std::fs::write(&base, vec![7u8; 8192]).unwrap();
+        let dst = dir.path().join("disk.raw");
+
+        let disk = reflink_disk(&base, &dst, 16384).unwrap();
+        assert_eq!(disk.format(), DiskFormat::Ext4);
+        let data = std::fs::read(disk.path()).unwrap();
+        assert_eq!(data.len(), 16384);
+        assert!(data[..8192].iter().all(|&b| b == 7));
+        assert!(data[8192..].iter().all(|&b| b == 0));
+    }
+}
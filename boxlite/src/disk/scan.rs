@@ -0,0 +1,522 @@
+//! Allocation scans of a box's container disk.
+//!
+//! `st_blocks` (see [`super::usage`]) tells how much host space a qcow2
+//! overlay takes, its own metadata included. How much the guest changed is
+//! read from the overlay's L2 tables instead, by counting the clusters that
+//! hold data. How full the container filesystem is comes from its ext4
+//! superblock, read through the overlay and its backing file like the guest
+//! would. What the guest changed is identified by hashing those data
+//! clusters, for the exec cache.
+
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::ext4;
+use super::qcow2::Qcow2Helper;
+
+const QCOW2_MAGIC: u32 = 0x514649fb;
+
+/// Largest L1 table read, as QEMU allows (32 MiB).
+const MAX_L1_SIZE: u32 = (32 << 20) / 8;
+
+/// Bits 9-55 of L1 and standard L2 entries: host offset of the table or cluster.
+const OFFSET_MASK: u64 = 0x00ff_ffff_ffff_fe00;
+
+/// L2 entry flag: the cluster is compressed.
+const L2_COMPRESSED: u64 = 1 << 62;
+
+/// L2 entry flag (standard clusters): the cluster reads as zeros.
+const L2_ZERO: u64 = 1;
+
+/// `incompatible_features` bits this reader doesn't handle: external data
+/// file and extended L2 entries.
+const UNSUPPORTED_INCOMPAT: u64 = (1 << 2) | (1 << 4);
+
+/// Longest backing chain followed.
+const MAX_BACKING_DEPTH: usize = 8;
+
+/// Size of a box's container rootfs, like `docker inspect --size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RootfsSize {
+    /// Bytes the box wrote on top of its image (Docker's `SizeRw`): the
+    /// data clusters allocated in the container disk overlay
+    pub size_rw: u64,
+    /// Bytes in use in the container filesystem, image included (Docker's
+    /// `SizeRootFs`), as of the guest's last superblock update. `None` with
+    /// the erofs storage driver, whose image layers sit on separate disks.
+    pub size_root_fs: Option<u64>,
+}
+
+/// Scan the container disk overlay at `overlay`.
+///
+/// Fails with `Unsupported` if it isn't a qcow2 image. `ext4_rootfs` says whether the overlay holds the whole container
+/// filesystem (ext4 storage driver), so that `size_root_fs` can be read.
+pub(crate) fn rootfs_size(overlay: &Path, ext4_rootfs: bool) -> BoxliteResult<RootfsSize> {
+    let mut image = Qcow2Image::open(overlay, 0)?;
+    let size_rw = image.data_bytes()?;
+    let size_root_fs = if ext4_rootfs {
+        Some(ext4::used_bytes(|offset, buf| image.read_at(offset, buf))?)
+    } else {
+        None
+    };
+    Ok(RootfsSize {
+        size_rw,
+        size_root_fs,
+    })
+}
+
+/// Content digest of the container disk overlay at `overlay`, as hex.
+///
+/// Covers the guest clusters the overlay holds, their data included, and
+/// what it is backed by: qcow2 backing files are hashed the same way, raw
+/// ones (the read-only image disks) by path, size and modification time.
+/// Two overlays with the same digest show the guest the same disk.
+pub(crate) fn rootfs_digest(overlay: &Path) -> BoxliteResult<String> {
+    let image = Qcow2Image::open(overlay, 0)?;
+    let mut hasher = Sha256::new();
+    image.digest(&mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Read-only view of a disk image as the guest sees it.
+enum DiskReader {
+    Raw { file: File, path: PathBuf },
+    Qcow2(Box<Qcow2Image>),
+}
+
+impl DiskReader {
+    fn open(path: &Path, depth: usize) -> BoxliteResult<Self> {
+        let file = open(path)?;
+        if is_qcow2(&file) {
+            Ok(Self::Qcow2(Box::new(Qcow2Image::open(path, depth)?)))
+        } else {
+            Ok(Self::Raw {
+                file,
+                path: path.to_path_buf(),
+            })
+        }
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> BoxliteResult<()> {
+        match self {
+            Self::Raw { file, path } => {
+                // Past the end of a raw backing file reads as zeros
+                let mut filled = 0;
+                while filled < buf.len() {
+                    let n = file
+                        .read_at(&mut buf[filled..], offset + filled as u64)
+                        .map_err(|e| io_error(path, e))?;
+                    if n == 0 {
+                        break;
+                    }
+                    filled += n;
+                }
+                buf[filled..].fill(0);
+                Ok(())
+            }
+            Self::Qcow2(image) => image.read_at(offset, buf),
+        }
+    }
+
+    fn digest(&self, hasher: &mut Sha256) -> BoxliteResult<()> {
+        match self {
+            Self::Raw { file, path } => {
+                let metadata = file.metadata().map_err(|e| io_error(path, e))?;
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .unwrap_or_default();
+                hasher.update(b"raw");
+                hasher.update(path.as_os_str().as_encoded_bytes());
+                hasher.update(metadata.len().to_le_bytes());
+                hasher.update(modified.as_nanos().to_le_bytes());
+                Ok(())
+            }
+            Self::Qcow2(image) => image.digest(hasher),
+        }
+    }
+}
+
+/// Where a guest cluster's data comes from.
+enum Cluster {
+    /// At this offset in the image file
+    Data(u64),
+    Zero,
+    /// From the backing file, or zeros without one
+    Unallocated,
+}
+
+struct Qcow2Image {
+    file: File,
+    path: PathBuf,
+    cluster_bits: u32,
+    l1: Vec<u64>,
+    backing: Option<DiskReader>,
+}
+
+impl Qcow2Image {
+    fn open(path: &Path, depth: usize) -> BoxliteResult<Self> {
+        let file = open(path)?;
+        if !is_qcow2(&file) {
+            return Err(BoxliteError::Unsupported(format!(
+                "{} is not a qcow2 image",
+                path.display()
+            )));
+        }
+        let header = Qcow2Helper::read_qcow2_header(path)?;
+        if header.incompatible_features & UNSUPPORTED_INCOMPAT != 0 {
+            return Err(BoxliteError::Unsupported(format!(
+                "{} uses unsupported qcow2 features {:#x}",
+                path.display(),
+                header.incompatible_features
+            )));
+        }
+        if header.l1_size > MAX_L1_SIZE {
+            return Err(BoxliteError::Storage(format!(
+                "{} has an L1 table of {} entries, more than {}",
+                path.display(),
+                header.l1_size,
+                MAX_L1_SIZE
+            )));
+        }
+
+        let cluster_bits = header.cluster_bits;
+        let mut raw_l1 = vec![0u8; header.l1_size as usize * 8];
+        file.read_exact_at(&mut raw_l1, header.l1_table_offset)
+            .map_err(|e| io_error(path, e))?;
+        let l1 = raw_l1
+            .chunks_exact(8)
+            .map(|entry| u64::from_be_bytes(entry.try_into().unwrap()))
+            .collect();
+
+        let backing = match (header.backing_file_offset, header.backing_file_size) {
+            (0, _) | (_, 0) => None,
+            (offset, len) => {
+                if depth >= MAX_BACKING_DEPTH {
+                    return Err(BoxliteError::Storage(format!(
+                        "backing chain of {} is too deep",
+                        path.display()
+                    )));
+                }
+                let mut name = vec![0u8; len as usize];
+                file.read_exact_at(&mut name, offset)
+                    .map_err(|e| io_error(path, e))?;
+                let name = PathBuf::from(String::from_utf8_lossy(&name).into_owned());
+                // Relative backing files are relative to the overlay
+                let backing_path = match path.parent() {
+                    Some(dir) if name.is_relative() => dir.join(name),
+                    _ => name,
+                };
+                Some(DiskReader::open(&backing_path, depth + 1)?)
+            }
+        };
+
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            cluster_bits,
+            l1,
+            backing,
+        })
+    }
+
+    fn cluster_size(&self) -> u64 {
+        1 << self.cluster_bits
+    }
+
+    /// Bytes of the guest disk stored in this image rather than its backing
+    /// file. Zero clusters hold no data and are not counted.
+    fn data_bytes(&self) -> BoxliteResult<u64> {
+        let mut clusters = 0u64;
+        let mut table = vec![0u8; self.cluster_size() as usize];
+        for l1_entry in &self.l1 {
+            let l2_offset = l1_entry & OFFSET_MASK;
+            if l2_offset == 0 {
+                continue;
+            }
+            self.file
+                .read_exact_at(&mut table, l2_offset)
+                .map_err(|e| io_error(&self.path, e))?;
+            clusters += table
+                .chunks_exact(8)
+                .map(|entry| u64::from_be_bytes(entry.try_into().unwrap()))
+                .filter(|&entry| {
+                    entry & L2_COMPRESSED != 0 || (entry & L2_ZERO == 0 && entry & OFFSET_MASK != 0)
+                })
+                .count() as u64;
+        }
+        Ok(clusters * self.cluster_size())
+    }
+
+    /// Hash the backing file, then the index and data of every cluster
+    /// this image holds, in guest order.
+    fn digest(&self, hasher: &mut Sha256) -> BoxliteResult<()> {
+        match &self.backing {
+            Some(backing) => backing.digest(hasher)?,
+            None => hasher.update(b"none"),
+        }
+        hasher.update(b"qcow2");
+        hasher.update(self.cluster_bits.to_le_bytes());
+
+        let entries_per_table = self.cluster_size() / 8;
+        let mut table = vec![0u8; self.cluster_size() as usize];
+        let mut data = vec![0u8; self.cluster_size() as usize];
+        for (l1_index, l1_entry) in self.l1.iter().enumerate() {
+            let l2_offset = l1_entry & OFFSET_MASK;
+            if l2_offset == 0 {
+                continue;
+            }
+            self.file
+                .read_exact_at(&mut table, l2_offset)
+                .map_err(|e| io_error(&self.path, e))?;
+            for (l2_index, entry) in table.chunks_exact(8).enumerate() {
+                let entry = u64::from_be_bytes(entry.try_into().unwrap());
+                let index = l1_index as u64 * entries_per_table + l2_index as u64;
+                if entry & L2_COMPRESSED != 0 {
+                    return Err(BoxliteError::Unsupported(format!(
+                        "{} has compressed clusters",
+                        self.path.display()
+                    )));
+                }
+                if entry & L2_ZERO != 0 {
+                    hasher.update(index.to_le_bytes());
+                    hasher.update(b"z");
+                } else if entry & OFFSET_MASK != 0 {
+                    self.file
+                        .read_exact_at(&mut data, entry & OFFSET_MASK)
+                        .map_err(|e| io_error(&self.path, e))?;
+                    hasher.update(index.to_le_bytes());
+                    hasher.update(b"d");
+                    hasher.update(&data);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cluster(&self, index: u64) -> BoxliteResult<Cluster> {
+        let entries_per_table = self.cluster_size() / 8;
+        let Some(l1_entry) = self.l1.get((index / entries_per_table) as usize) else {
+            return Ok(Cluster::Unallocated);
+        };
+        let l2_offset = l1_entry & OFFSET_MASK;
+        if l2_offset == 0 {
+            return Ok(Cluster::Unallocated);
+        }
+
+        let mut raw = [0u8; 8];
+        self.file
+            .read_exact_at(&mut raw, l2_offset + (index % entries_per_table) * 8)
+            .map_err(|e| io_error(&self.path, e))?;
+        let entry = u64::from_be_bytes(raw);
+        if entry & L2_COMPRESSED != 0 {
+            return Err(BoxliteError::Unsupported(format!(
+                "{} has compressed clusters",
+                self.path.display()
+            )));
+        }
+        Ok(if entry & L2_ZERO != 0 {
+            Cluster::Zero
+        } else if entry & OFFSET_MASK != 0 {
+            Cluster::Data(entry & OFFSET_MASK)
+        } else {
+            Cluster::Unallocated
+        })
+    }
+
+    fn read_at(&mut self, mut offset: u64, buf: &mut [u8]) -> BoxliteResult<()> {
+        let cluster_size = self.cluster_size();
+        let mut rest = buf;
+        while !rest.is_empty() {
+            let in_cluster = offset & (cluster_size - 1);
+            let len = rest.len().min((cluster_size - in_cluster) as usize);
+            let (chunk, tail) = rest.split_at_mut(len);
+            match self.cluster(offset >> self.cluster_bits)? {
+                Cluster::Data(host_offset) => self
+                    .file
+                    .read_exact_at(chunk, host_offset + in_cluster)
+                    .map_err(|e| io_error(&self.path, e))?,
+                Cluster::Zero => chunk.fill(0),
+                Cluster::Unallocated => match &mut self.backing {
+                    Some(backing) => backing.read_at(offset, chunk)?,
+                    None => chunk.fill(0),
+                },
+            }
+            offset += len as u64;
+            rest = tail;
+        }
+        Ok(())
+    }
+}
+
+fn is_qcow2(file: &File) -> bool {
+    let mut magic = [0u8; 4];
+    file.read_exact_at(&mut magic, 0).is_ok() && u32::from_be_bytes(magic) == QCOW2_MAGIC
+}
+
+fn open(path: &Path) -> BoxliteResult<File> {
+    File::open(path).map_err(|e| io_error(path, e))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> BoxliteError {
+    BoxliteError::Storage(format!("failed to read {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{BackingFormat, Qcow2Helper};
+
+    /// Point the guest cluster `index` of `overlay` at a new data cluster
+    /// holding `data`, appended to the file.
+    fn write_cluster(overlay: &Path, index: u64, data: &[u8]) {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(overlay)
+            .unwrap();
+        let image = Qcow2Image::open(overlay, 0).unwrap();
+        let cluster_size = image.cluster_size();
+        let entries_per_table = cluster_size / 8;
+
+        // Append an L2 table if needed, then the data cluster
+        let l1_index = index / entries_per_table;
+        let mut l2_offset = image.l1[l1_index as usize] & OFFSET_MASK;
+        let mut end = file
+            .metadata()
+            .unwrap()
+            .len()
+            .next_multiple_of(cluster_size);
+        if l2_offset == 0 {
+            l2_offset = end;
+            end += cluster_size;
+            file.write_all_at(&vec![0u8; cluster_size as usize], l2_offset)
+                .unwrap();
+            let l1_offset = {
+                let mut raw = [0u8; 8];
+                file.read_exact_at(&mut raw, 40).unwrap();
+                u64::from_be_bytes(raw)
+            };
+            file.write_all_at(&l2_offset.to_be_bytes(), l1_offset + l1_index * 8)
+                .unwrap();
+        }
+        let mut cluster = vec![0u8; cluster_size as usize];
+        cluster[..data.len()].copy_from_slice(data);
+        file.write_all_at(&cluster, end).unwrap();
+        file.write_all_at(
+            &end.to_be_bytes(),
+            l2_offset + (index % entries_per_table) * 8,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn scan_counts_overlay_data_and_reads_through_backing() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.raw");
+        std::fs::write(&base, b"base-data").unwrap();
+        let overlay = dir.path().join("disk.qcow2");
+        Qcow2Helper::new()
+            .create_cow_child_disk(&base, BackingFormat::Raw, &overlay, 1 << 30)
+            .unwrap()
+            .leak();
+
+        let mut image = Qcow2Image::open(&overlay, 0).unwrap();
+        assert_eq!(image.data_bytes().unwrap(), 0);
+        let mut buf = [0u8; 9];
+        image.read_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"base-data");
+
+        write_cluster(&overlay, 0, b"overlay");
+        write_cluster(&overlay, 3, b"more");
+        let mut image = Qcow2Image::open(&overlay, 0).unwrap();
+        assert_eq!(image.data_bytes().unwrap(), 2 * image.cluster_size());
+        image.read_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"overlay\0\0");
+        // Past the end of the raw backing file
+        image.read_at(image.cluster_size(), &mut buf).unwrap();
+        assert_eq!(buf, [0u8; 9]);
+    }
+
+    #[test]
+    fn rootfs_digest_follows_overlay_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.raw");
+        std::fs::write(&base, b"base-data").unwrap();
+        let overlay = dir.path().join("disk.qcow2");
+        Qcow2Helper::new()
+            .create_cow_child_disk(&base, BackingFormat::Raw, &overlay, 1 << 30)
+            .unwrap()
+            .leak();
+
+        let empty = rootfs_digest(&overlay).unwrap();
+        assert_eq!(empty.len(), 64);
+        assert_eq!(rootfs_digest(&overlay).unwrap(), empty);
+
+        write_cluster(&overlay, 1, b"one");
+        let one = rootfs_digest(&overlay).unwrap();
+        assert_ne!(one, empty);
+
+        // Same data at another guest offset is another disk
+        let other = dir.path().join("other.qcow2");
+        Qcow2Helper::new()
+            .create_cow_child_disk(&base, BackingFormat::Raw, &other, 1 << 30)
+            .unwrap()
+            .leak();
+        write_cluster(&other, 2, b"one");
+        assert_ne!(rootfs_digest(&other).unwrap(), one);
+    }
+
+    #[test]
+    fn rootfs_size_without_ext4() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.raw");
+        std::fs::write(&base, [0u8; 4096]).unwrap();
+        let overlay = dir.path().join("disk.qcow2");
+        Qcow2Helper::new()
+            .create_cow_child_disk(&base, BackingFormat::Raw, &overlay, 1 << 30)
+            .unwrap()
+            .leak();
+
+        let size = rootfs_size(&overlay, false).unwrap();
+        assert_eq!(size.size_rw, 0);
+        assert_eq!(size.size_root_fs, None);
+        // No superblock in the base
+        assert!(rootfs_size(&overlay, true).is_err());
+    }
+
+    #[test]
+    fn open_rejects_bad_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.raw");
+        std::fs::write(&base, [0u8; 4096]).unwrap();
+        assert!(matches!(
+            rootfs_size(&base, false),
+            Err(BoxliteError::Unsupported(_))
+        ));
+
+        let overlay = dir.path().join("disk.qcow2");
+        Qcow2Helper::new()
+            .create_cow_child_disk(&base, BackingFormat::Raw, &overlay, 1 << 30)
+            .unwrap()
+            .leak();
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&overlay)
+            .unwrap();
+
+        file.write_all_at(&(MAX_L1_SIZE + 1).to_be_bytes(), 36)
+            .unwrap();
+        assert!(Qcow2Image::open(&overlay, 0).is_err());
+
+        file.write_all_at(&1u32.to_be_bytes(), 36).unwrap();
+        file.write_all_at(&40u32.to_be_bytes(), 20).unwrap();
+        assert!(Qcow2Image::open(&overlay, 0).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Host space taken by disk images.
+//!
+//! A box's disks are sparse: a qcow2 overlay only grows as the guest writes,
+//! and holes in raw images take no blocks. Their virtual size (what the guest
+//! sees, e.g. `disk_size_gb`) says little about the host, so usage is read
+//! from the blocks the host filesystem allocated (`st_blocks`, in 512-byte
+//! units on Linux and macOS).
+
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::Serialize;
+
+use super::Qcow2Helper;
+
+/// Usage of one disk image file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiskFileUsage {
+    /// Disk image on the host
+    pub path: PathBuf,
+    /// Size of the disk as the guest sees it, in bytes
+    pub virtual_bytes: u64,
+    /// Bytes allocated for the file on the host
+    pub allocated_bytes: u64,
+}
+
+impl DiskFileUsage {
+    /// Read the usage of `path`, or `None` if it doesn't exist.
+    ///
+    /// The virtual size of a qcow2 image comes from its header; for raw
+    /// images it is the file length.
+    pub(crate) fn read(path: &Path) -> BoxliteResult<Option<Self>> {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(BoxliteError::Storage(format!(
+                    "failed to stat {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        let virtual_bytes = if path.extension().is_some_and(|ext| ext == "qcow2") {
+            Qcow2Helper::qcow2_virtual_size(path)?
+        } else {
+            metadata.len()
+        };
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            virtual_bytes,
+            allocated_bytes: metadata.blocks() * 512,
+        }))
+    }
+}
+
+/// Host disk usage of a box.
+///
+/// Covers the box's own disk overlays. The base images they are backed by
+/// are shared between boxes and counted with the images.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    /// Disk images of the box; empty until the box first starts
+    pub disks: Vec<DiskFileUsage>,
+}
+
+impl DiskUsage {
+    /// Bytes allocated on the host for all disks.
+    pub fn allocated_bytes(&self) -> u64 {
+        self.disks.iter().map(|d| d.allocated_bytes).sum()
+    }
+
+    /// Sum of the virtual sizes of all disks.
+    pub fn virtual_bytes(&self) -> u64 {
+        self.disks.iter().map(|d| d.virtual_bytes).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_file_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.raw");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+
+        let usage = DiskFileUsage::read(&path).unwrap().unwrap();
+        assert_eq!(usage.virtual_bytes, 64 * 1024 * 1024);
+        assert!(usage.allocated_bytes < usage.virtual_bytes);
+
+        assert_eq!(
+            DiskFileUsage::read(&dir.path().join("missing")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_qcow2_virtual_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.qcow2");
+        let _disk = Qcow2Helper::new()
+            .create_disk_with_size(&path, 8 * 1024 * 1024 * 1024, true)
+            .unwrap();
+
+        let usage = DiskUsage {
+            disks: vec![DiskFileUsage::read(&path).unwrap().unwrap()],
+        };
+        assert_eq!(usage.virtual_bytes(), 8 * 1024 * 1024 * 1024);
+        assert!(usage.allocated_bytes() < 16 * 1024 * 1024);
+    }
+}
@@ -0,0 +1,155 @@
+//! Layer compression formats and media-type dispatch.
+//!
+//! OCI layers are tarballs, optionally gzip- or zstd-compressed. eStargz layers
+//! are gzip tarballs with a trailing table of contents, so they need no special
+//! decoder. Foreign (non-distributable) layers use the same encodings but are
+//! fetched from the URLs in their descriptor rather than from the registry.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression applied to a layer tarball.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl LayerCompression {
+    /// Resolve the compression from a layer media type.
+    ///
+    /// Fails for media types that aren't tar layers (e.g. encrypted layers),
+    /// so unsupported images are rejected before anything is downloaded.
+    pub fn from_media_type(media_type: &str) -> BoxliteResult<Self> {
+        match media_type {
+            "application/vnd.oci.image.layer.v1.tar"
+            | "application/vnd.oci.image.layer.nondistributable.v1.tar"
+            | "application/vnd.docker.image.rootfs.diff.tar" => Ok(Self::None),
+            "application/vnd.oci.image.layer.v1.tar+gzip"
+            | "application/vnd.oci.image.layer.nondistributable.v1.tar+gzip"
+            | "application/vnd.docker.image.rootfs.diff.tar.gzip"
+            | "application/vnd.docker.image.rootfs.foreign.diff.tar.gzip" => Ok(Self::Gzip),
+            "application/vnd.oci.image.layer.v1.tar+zstd"
+            | "application/vnd.oci.image.layer.nondistributable.v1.tar+zstd" => Ok(Self::Zstd),
+            other => Err(BoxliteError::Image(format!(
+                "unsupported layer media type: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Detect the compression from the first bytes of a layer blob.
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(&ZSTD_MAGIC) {
+            Self::Zstd
+        } else if header.starts_with(&GZIP_MAGIC) {
+            Self::Gzip
+        } else {
+            Self::None
+        }
+    }
+
+    /// Wrap `file` in the matching decoder.
+    pub fn decoder(self, file: File) -> BoxliteResult<Box<dyn Read>> {
+        let reader = BufReader::new(file);
+        Ok(match self {
+            Self::None => Box::new(reader),
+            Self::Gzip => Box::new(GzDecoder::new(reader)),
+            Self::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(reader).map_err(
+                |e| BoxliteError::Storage(format!("Failed to initialize zstd decoder: {}", e)),
+            )?),
+        })
+    }
+}
+
+/// Whether a layer is foreign/non-distributable (must be fetched from its URLs).
+pub fn is_foreign_layer(media_type: &str) -> bool {
+    media_type.contains(".nondistributable.") || media_type.contains(".foreign.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_media_type() {
+        let cases = [
+            (
+                "application/vnd.oci.image.layer.v1.tar",
+                LayerCompression::None,
+            ),
+            (
+                "application/vnd.oci.image.layer.v1.tar+gzip",
+                LayerCompression::Gzip,
+            ),
+            (
+                "application/vnd.oci.image.layer.v1.tar+zstd",
+                LayerCompression::Zstd,
+            ),
+            (
+                "application/vnd.docker.image.rootfs.diff.tar.gzip",
+                LayerCompression::Gzip,
+            ),
+            (
+                "application/vnd.docker.image.rootfs.foreign.diff.tar.gzip",
+                LayerCompression::Gzip,
+            ),
+            (
+                "application/vnd.oci.image.layer.nondistributable.v1.tar+zstd",
+                LayerCompression::Zstd,
+            ),
+        ];
+        for (media_type, expected) in cases {
+            assert_eq!(
+                LayerCompression::from_media_type(media_type).unwrap(),
+                expected,
+                "{}",
+                media_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_media_type_rejects_unknown() {
+        assert!(
+            LayerCompression::from_media_type(
+                "application/vnd.oci.image.layer.v1.tar+gzip+encrypted"
+            )
+            .is_err()
+        );
+        assert!(LayerCompression::from_media_type("application/json").is_err());
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            LayerCompression::detect(&[0x1f, 0x8b, 0x08, 0x00]),
+            LayerCompression::Gzip
+        );
+        assert_eq!(
+            LayerCompression::detect(&[0x28, 0xb5, 0x2f, 0xfd]),
+            LayerCompression::Zstd
+        );
+        assert_eq!(LayerCompression::detect(b"etc/"), LayerCompression::None);
+        assert_eq!(LayerCompression::detect(&[]), LayerCompression::None);
+    }
+
+    #[test]
+    fn test_is_foreign_layer() {
+        assert!(is_foreign_layer(
+            "application/vnd.docker.image.rootfs.foreign.diff.tar.gzip"
+        ));
+        assert!(is_foreign_layer(
+            "application/vnd.oci.image.layer.nondistributable.v1.tar+gzip"
+        ));
+        assert!(!is_foreign_layer(
+            "application/vnd.oci.image.layer.v1.tar+gzip"
+        ));
+    }
+}
@@ -1,11 +1,21 @@
 //! Archive helpers (containerd-style apply).
 //!
 //! Mirrors containerd's layout: `tar` module contains the streaming layer apply,
-//! `time` provides time helpers, `override_stat` provides rootless container support.
+//! `compression` dispatches on layer media types, `time` provides time helpers,
+//! `override_stat` provides rootless container support.
 
+mod compression;
 mod override_stat;
 mod tar;
 mod time;
 
+pub(crate) use compression::{LayerCompression, is_foreign_layer};
+pub(crate) use override_stat::{
+    CONTAINERS_OVERRIDE_XATTR, OverrideFileType, OverrideStat, unshadow_xattr_name,
+};
+pub(crate) use tar::{
+    OPAQUE_WHITEOUT, is_aufs_metadata, is_estargz_metadata, normalize_entry_path,
+    open_layer_tarball,
+};
 #[allow(unused_imports)]
-pub use tar::extract_layer_tarball_streaming;
+pub use tar::{WhiteoutMode, extract_layer_tarball, extract_layer_tarball_streaming};
@@ -19,6 +19,23 @@ use std::path::Path;
 /// The xattr name used by containers/storage for override stat.
 pub const CONTAINERS_OVERRIDE_XATTR: &str = "user.containers.override_stat";
 
+/// Prefix for privileged xattrs (e.g. `security.capability`) stored rootless.
+///
+/// Unprivileged processes can't set `security.*` xattrs, so they are kept as
+/// `user.boxlite.shadow.<name>` and restored under their real name when the
+/// rootfs is packed into a disk image.
+pub const SHADOW_XATTR_PREFIX: &str = "user.boxlite.shadow.";
+
+/// Shadow name for a privileged xattr.
+pub fn shadow_xattr_name(name: &str) -> String {
+    format!("{}{}", SHADOW_XATTR_PREFIX, name)
+}
+
+/// Real name of a shadowed xattr, or `None` if `name` isn't a shadow.
+pub fn unshadow_xattr_name(name: &str) -> Option<&str> {
+    name.strip_prefix(SHADOW_XATTR_PREFIX)
+}
+
 /// File type for override stat encoding.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OverrideFileType {
@@ -32,6 +49,19 @@ pub enum OverrideFileType {
 }
 
 impl OverrideFileType {
+    /// Inode type bits (`S_IFMT`) for this file type.
+    pub fn type_bits(&self) -> u32 {
+        match self {
+            Self::File => 0o100000,
+            Self::Dir => 0o040000,
+            Self::Symlink => 0o120000,
+            Self::Pipe => 0o010000,
+            Self::Socket => 0o140000,
+            Self::Block { .. } => 0o060000,
+            Self::Char { .. } => 0o020000,
+        }
+    }
+
     /// Convert from tar entry type.
     pub fn from_tar_entry(entry_type: tar::EntryType, major: u32, minor: u32) -> Self {
         match entry_type {
@@ -191,6 +221,14 @@ mod tests {
         assert_eq!(stat.format(), "0:0:0666:char-1-3");
     }
 
+    #[test]
+    fn test_shadow_xattr_name_roundtrip() {
+        let shadow = shadow_xattr_name("security.capability");
+        assert_eq!(shadow, "user.boxlite.shadow.security.capability");
+        assert_eq!(unshadow_xattr_name(&shadow), Some("security.capability"));
+        assert_eq!(unshadow_xattr_name("user.other"), None);
+    }
+
     #[test]
     fn test_parse_file() {
         let stat = OverrideStat::parse("1000:1000:0644:file").unwrap();
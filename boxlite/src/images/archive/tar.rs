@@ -2,13 +2,12 @@
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use filetime::{FileTime, set_file_times, set_symlink_file_times};
-use flate2::read::GzDecoder;
 #[cfg(target_os = "linux")]
 use libc::c_uint;
 use std::collections::HashSet;
 use std::ffi::CString;
 use std::fs::{self, OpenOptions, Permissions};
-use std::io::{self, BufReader, Read};
+use std::io::{self, Read};
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
 use std::path::{Component, Path, PathBuf};
@@ -17,59 +16,63 @@ use tar::{Archive, Entry, EntryType};
 use tracing::{debug, trace, warn};
 use walkdir::WalkDir;
 
-use super::override_stat::{OverrideFileType, OverrideStat};
+use super::compression::LayerCompression;
+use super::override_stat::{OverrideFileType, OverrideStat, shadow_xattr_name};
 use super::time::{bound_time, latest_time};
 
-/// Apply a gzip-compressed OCI layer tarball into `dest`, preserving metadata.
+/// What to do with `.wh.*` whiteout markers found in a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhiteoutMode {
+    /// Delete the whited-out paths from `dest` (layers applied in order onto one tree).
+    Apply,
+    /// Keep the markers as empty files (per-layer cache, merged later).
+    Preserve,
+}
+
+/// Apply an OCI layer tarball into `dest`, preserving metadata.
+///
+/// The compression (none, gzip or zstd) is detected from the blob's magic bytes.
 pub fn extract_layer_tarball_streaming(tarball_path: &Path, dest: &Path) -> BoxliteResult<u64> {
-    let file = fs::File::open(tarball_path).map_err(|e| {
-        BoxliteError::Storage(format!(
-            "Failed to open layer tarball {}: {}",
-            tarball_path.display(),
-            e
-        ))
-    })?;
+    extract_layer_tarball(tarball_path, dest, WhiteoutMode::Apply)
+}
 
-    // Detect compression format by reading first 2 bytes
-    let mut header = [0u8; 2];
-    {
-        let file_ref = &file;
-        use std::io::Read;
-        file_ref
-            .take(2)
-            .read_exact(&mut header)
-            .map_err(|e| BoxliteError::Storage(format!("Failed to read layer header: {}", e)))?;
-    }
+/// Extract an OCI layer tarball into `dest` with the given whiteout handling.
+pub fn extract_layer_tarball(
+    tarball_path: &Path,
+    dest: &Path,
+    whiteouts: WhiteoutMode,
+) -> BoxliteResult<u64> {
+    let reader = open_layer_tarball(tarball_path)?;
+    apply_oci_layer(reader, dest, whiteouts)
+}
 
-    // Gzip magic number: 0x1f 0x8b
-    let reader: Box<dyn Read> = if header == [0x1f, 0x8b] {
-        // Gzip-compressed
-        debug!("Detected gzip compression for {}", tarball_path.display());
-        let file = fs::File::open(tarball_path).map_err(|e| {
+/// Open a layer tarball as a tar stream, decompressing it as needed.
+pub(crate) fn open_layer_tarball(tarball_path: &Path) -> BoxliteResult<Box<dyn Read>> {
+    let open = || {
+        fs::File::open(tarball_path).map_err(|e| {
             BoxliteError::Storage(format!(
-                "Failed to reopen layer tarball {}: {}",
+                "Failed to open layer tarball {}: {}",
                 tarball_path.display(),
                 e
             ))
-        })?;
-        Box::new(GzDecoder::new(BufReader::new(file)))
-    } else {
-        // Uncompressed
-        debug!(
-            "Detected uncompressed tarball for {}",
-            tarball_path.display()
-        );
-        let file = fs::File::open(tarball_path).map_err(|e| {
-            BoxliteError::Storage(format!(
-                "Failed to reopen layer tarball {}: {}",
-                tarball_path.display(),
-                e
-            ))
-        })?;
-        Box::new(BufReader::new(file))
+        })
     };
 
-    apply_oci_layer(reader, dest)
+    // Detect compression format from the magic bytes (zstd needs 4)
+    let mut header = Vec::with_capacity(4);
+    open()?
+        .take(4)
+        .read_to_end(&mut header)
+        .map_err(|e| BoxliteError::Storage(format!("Failed to read layer header: {}", e)))?;
+
+    let compression = LayerCompression::detect(&header);
+    debug!(
+        "Detected {:?} compression for {}",
+        compression,
+        tarball_path.display()
+    );
+
+    compression.decoder(open()?)
 }
 
 /// Ownership metadata for chown/xattr operations.
@@ -192,8 +195,38 @@ struct DeferredHardlink {
     meta: EntryMetadata,
 }
 
-/// Apply an OCI layer tar stream into `dest`, handling whiteouts inline.
-pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
+/// eStargz bookkeeping files that live at the layer root, not in the image.
+const ESTARGZ_METADATA: [&str; 3] = [
+    "stargz.index.json",
+    ".prefetch.landmark",
+    ".no.prefetch.landmark",
+];
+
+pub(crate) fn is_estargz_metadata(path: &Path) -> bool {
+    path.to_str().is_some_and(|p| ESTARGZ_METADATA.contains(&p))
+}
+
+/// Opaque directory marker.
+pub(crate) const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+/// aufs bookkeeping (e.g. `.wh..wh.plnk` hardlink dirs) that some builders leak
+/// into layers. Only the opaque marker is meaningful in OCI layers.
+pub(crate) fn is_aufs_metadata(path: &Path) -> bool {
+    path.components().any(|c| {
+        c.as_os_str()
+            .to_str()
+            .is_some_and(|name| name.starts_with(".wh..wh.") && name != OPAQUE_WHITEOUT)
+    })
+}
+
+/// Apply an OCI layer tar stream into `dest`.
+///
+/// With `WhiteoutMode::Apply`, whiteouts are handled inline (no second pass).
+pub fn apply_oci_layer<R: Read>(
+    reader: R,
+    dest: &Path,
+    whiteouts: WhiteoutMode,
+) -> BoxliteResult<u64> {
     fs::create_dir_all(dest).map_err(|e| {
         BoxliteError::Storage(format!(
             "Failed to create destination directory {}: {}",
@@ -232,6 +265,16 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
             continue;
         }
 
+        if is_estargz_metadata(&normalized) {
+            debug!("Skipping eStargz metadata entry: {}", normalized.display());
+            continue;
+        }
+
+        if is_aufs_metadata(&normalized) {
+            debug!("Skipping aufs metadata entry: {}", normalized.display());
+            continue;
+        }
+
         let full_path = dest.join(&normalized);
         let entry_type = entry.header().entry_type();
         let mode = entry.header().mode().unwrap_or(0o755);
@@ -270,8 +313,9 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
         );
 
         // Whiteout handling (inline, no second pass)
-        let whiteout_handled = handle_whiteout(&full_path, &mut unpacked_paths, entry_type)?;
-        if whiteout_handled {
+        if whiteouts == WhiteoutMode::Apply
+            && handle_whiteout(&full_path, &mut unpacked_paths, entry_type)?
+        {
             continue;
         }
 
@@ -297,8 +341,9 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
                     ))
                 })?;
                 let target_path = resolve_hardlink_target(dest, &target)?;
-                // Try to create hardlink, defer if target doesn't exist yet
-                if target_path.exists() {
+                // Try to create hardlink, defer if target doesn't exist yet.
+                // The target may come from a lower layer already applied to dest.
+                if path_exists(&target_path) {
                     create_hardlink(&full_path, &target_path)?;
                 } else {
                     trace!(
@@ -387,7 +432,7 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
 
     // Retry deferred hardlinks - targets may exist now after full extraction
     for deferred in deferred_hardlinks {
-        if deferred.target_path.exists() {
+        if path_exists(&deferred.target_path) {
             trace!(
                 "Creating deferred hardlink {} -> {}",
                 deferred.link_path.display(),
@@ -411,8 +456,9 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
             // Target file doesn't exist - this can happen when:
             // 1. Target was deleted by whiteout processing
             // 2. pnpm hardlink optimization where target was removed
+            // 3. Target lives in a lower layer and this layer is extracted alone
             // This is not necessarily an error - skip the hardlink
-            trace!(
+            debug!(
                 "Skipping deferred hardlink {} -> {} (target does not exist, possibly removed by whiteout)",
                 deferred.link_path.display(),
                 deferred.target_path.display()
@@ -439,7 +485,8 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
     Ok(total_size)
 }
 
-fn normalize_entry_path(path: &Path) -> Option<PathBuf> {
+/// Resolve a tar entry path relative to the layer root; `None` if it escapes.
+pub(crate) fn normalize_entry_path(path: &Path) -> Option<PathBuf> {
     let mut components = Vec::new();
     for comp in path.components() {
         match comp {
@@ -597,7 +644,7 @@ fn handle_whiteout(
         None => return Ok(false),
     };
 
-    if base == ".wh..wh..opq" {
+    if base == OPAQUE_WHITEOUT {
         let dir = path
             .parent()
             .ok_or_else(|| BoxliteError::Storage("Opaque marker without parent".into()))?;
@@ -610,12 +657,15 @@ fn handle_whiteout(
             .parent()
             .ok_or_else(|| BoxliteError::Storage("Whiteout without parent directory".into()))?;
         let target = parent.join(target_name);
-        if target.exists() {
-            if target.is_dir() {
+        // symlink_metadata: never follow a whited-out symlink, and still
+        // remove it when it dangles
+        if let Ok(meta) = fs::symlink_metadata(&target) {
+            if meta.is_dir() {
                 fs::remove_dir_all(&target).ok();
             } else {
                 fs::remove_file(&target).ok();
             }
+            unpacked.retain(|p| !p.starts_with(&target));
             debug!("Whiteout removed {}", target.display());
         }
         return Ok(true);
@@ -624,33 +674,46 @@ fn handle_whiteout(
     Ok(false)
 }
 
+/// Hide lower-layer contents of `dir`, keeping everything this layer unpacked.
+///
+/// Directories that are (implicit) parents of unpacked entries are kept but
+/// cleared recursively. Symlinks are removed, never followed.
 fn apply_opaque_whiteout(dir: &Path, unpacked: &HashSet<PathBuf>) -> BoxliteResult<()> {
-    if !dir.exists() {
-        return Ok(());
-    }
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            trace!("Skipping opaque whiteout in {}: {}", dir.display(), e);
+            return Ok(());
+        }
+    };
 
-    for entry in WalkDir::new(dir).min_depth(1).into_iter() {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(e) => {
-                trace!("Skipping walk entry in {}: {}", dir.display(), e);
-                continue;
-            }
-        };
+    for entry in entries.flatten() {
         let target = entry.path();
-        if unpacked.contains(target) {
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+
+        if is_dir && unpacked.iter().any(|p| p.starts_with(&target)) {
+            apply_opaque_whiteout(&target, unpacked)?;
             continue;
         }
-        if target.is_dir() {
-            fs::remove_dir_all(target).ok();
+        if unpacked.contains(&target) {
+            continue;
+        }
+
+        if is_dir {
+            fs::remove_dir_all(&target).ok();
         } else {
-            fs::remove_file(target).ok();
+            fs::remove_file(&target).ok();
         }
         debug!("Opaque whiteout removed {}", target.display());
     }
     Ok(())
 }
 
+/// Whether `path` exists, without following a trailing symlink.
+fn path_exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
 fn remove_existing_if_needed(path: &Path, entry_type: EntryType) -> BoxliteResult<()> {
     match fs::symlink_metadata(path) {
         Ok(metadata) => {
@@ -723,7 +786,7 @@ fn create_regular_file<R: Read>(entry: &mut Entry<R>, path: &Path, mode: u32) ->
             BoxliteError::Storage(format!("Failed to create file {}: {}", path.display(), e))
         })?;
 
-    io::copy(entry, &mut file).map_err(|e| {
+    copy_sparse(entry, &mut file).map_err(|e| {
         BoxliteError::Storage(format!(
             "Failed to copy file data to {}: {}",
             path.display(),
@@ -733,6 +796,42 @@ fn create_regular_file<R: Read>(entry: &mut Entry<R>, path: &Path, mode: u32) ->
     Ok(())
 }
 
+/// Block size used to detect holes when writing file data.
+const SPARSE_BLOCK_SIZE: usize = 4096;
+
+/// Copy `reader` into `file`, seeking over all-zero blocks so holes stay holes.
+///
+/// Covers both GNU sparse entries (expanded to zeros by the tar reader) and
+/// regular entries whose data contains runs of zeros.
+fn copy_sparse<R: Read>(reader: &mut R, file: &mut fs::File) -> io::Result<u64> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut buf = vec![0u8; 16 * SPARSE_BLOCK_SIZE];
+    let mut total = 0u64;
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for block in buf[..n].chunks(SPARSE_BLOCK_SIZE) {
+            if block.iter().all(|&b| b == 0) {
+                file.seek(SeekFrom::Current(block.len() as i64))?;
+            } else {
+                file.write_all(block)?;
+            }
+        }
+        total += n as u64;
+    }
+
+    // A trailing hole isn't materialized by seeking; set the length explicitly
+    file.set_len(total)?;
+    Ok(total)
+}
+
 fn create_hardlink(path: &Path, target: &Path) -> BoxliteResult<()> {
     fs::hard_link(target, path).map_err(|e| {
         BoxliteError::Storage(format!(
@@ -764,7 +863,25 @@ fn create_special_device(
     is_root: bool,
 ) -> BoxliteResult<()> {
     if !is_root {
-        trace!("Skipping device node {} (requires root)", path.display());
+        // mknod requires root. Leave an empty placeholder: the device type is
+        // recorded in the override_stat xattr and recreated when building disks.
+        trace!(
+            "Creating placeholder for device node {} (requires root)",
+            path.display()
+        );
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to create device placeholder {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
         return Ok(());
     }
 
@@ -908,8 +1025,8 @@ fn apply_xattrs(
     is_root: bool,
 ) -> BoxliteResult<()> {
     for (key, value) in xattrs {
-        // trusted.* and security.* require root privileges
-        if key.starts_with("trusted.") || (!is_root && key.starts_with("security.")) {
+        // trusted.* requires root privileges
+        if key.starts_with("trusted.") && !is_root {
             trace!(
                 "Skipping privileged xattr {} on {} (requires root)",
                 key,
@@ -918,7 +1035,16 @@ fn apply_xattrs(
             continue;
         }
 
-        let res = setxattr_nofollow(path, key, value);
+        // security.* (file capabilities, SELinux labels) also requires root.
+        // Rootless: keep it under a user.* shadow name so disk creation can
+        // restore it.
+        let key = if key.starts_with("security.") && !is_root {
+            shadow_xattr_name(key)
+        } else {
+            key.clone()
+        };
+
+        let res = setxattr_nofollow(path, &key, value);
         match res {
             Ok(()) => {}
             Err(e) if e.raw_os_error() == Some(libc::ENOTSUP) => {
@@ -1060,6 +1186,11 @@ mod tests {
         encoder.finish().unwrap()
     }
 
+    /// Helper to create a zstd-compressed tar archive
+    fn create_zstd_tar(data: &[u8]) -> Vec<u8> {
+        zstd::stream::encode_all(data, 0).unwrap()
+    }
+
     struct TestEntry {
         path: String,
         entry_type: TestEntryType,
@@ -1548,6 +1679,267 @@ mod tests {
         assert_eq!(content, "test content");
     }
 
+    #[test]
+    fn test_zstd_compression_detection() {
+        // Test that zstd compression is auto-detected
+        let temp_dir = tempfile::tempdir().unwrap();
+        let tar_path = temp_dir.path().join("test.tar.zst");
+
+        let entries = vec![TestEntry {
+            path: "file.txt".to_string(),
+            entry_type: TestEntryType::File {
+                content: b"zstd content".to_vec(),
+            },
+        }];
+
+        let tar_data = create_test_tar(entries);
+        std::fs::write(&tar_path, create_zstd_tar(&tar_data)).unwrap();
+
+        let dest_dir = temp_dir.path().join("extracted");
+        extract_layer_tarball_streaming(&tar_path, &dest_dir).unwrap();
+
+        let content = std::fs::read_to_string(dest_dir.join("file.txt")).unwrap();
+        assert_eq!(content, "zstd content");
+    }
+
+    #[test]
+    fn test_estargz_metadata_skipped() {
+        // eStargz layers are gzip tarballs with a TOC and landmark files at the root
+        let temp_dir = tempfile::tempdir().unwrap();
+        let tar_path = temp_dir.path().join("test.tar.gz");
+
+        let entries = vec![
+            TestEntry {
+                path: ".prefetch.landmark".to_string(),
+                entry_type: TestEntryType::File { content: vec![0xf] },
+            },
+            TestEntry {
+                path: "file.txt".to_string(),
+                entry_type: TestEntryType::File {
+                    content: b"stargz content".to_vec(),
+                },
+            },
+            TestEntry {
+                path: "stargz.index.json".to_string(),
+                entry_type: TestEntryType::File {
+                    content: br#"{"version":1,"entries":[]}"#.to_vec(),
+                },
+            },
+        ];
+
+        let tar_data = create_test_tar(entries);
+        std::fs::write(&tar_path, create_gzipped_tar(&tar_data)).unwrap();
+
+        let dest_dir = temp_dir.path().join("extracted");
+        extract_layer_tarball_streaming(&tar_path, &dest_dir).unwrap();
+
+        assert!(dest_dir.join("file.txt").exists());
+        assert!(!dest_dir.join("stargz.index.json").exists());
+        assert!(!dest_dir.join(".prefetch.landmark").exists());
+    }
+
+    /// Write `entries` as a tar layer and extract it into `dest`.
+    fn apply_test_layer(dir: &Path, name: &str, entries: Vec<TestEntry>, dest: &Path) {
+        let tar_path = dir.join(name);
+        std::fs::write(&tar_path, create_test_tar(entries)).unwrap();
+        extract_layer_tarball_streaming(&tar_path, dest).unwrap();
+    }
+
+    fn file_entry(path: &str, content: &[u8]) -> TestEntry {
+        TestEntry {
+            path: path.to_string(),
+            entry_type: TestEntryType::File {
+                content: content.to_vec(),
+            },
+        }
+    }
+
+    fn dir_entry(path: &str) -> TestEntry {
+        TestEntry {
+            path: path.to_string(),
+            entry_type: TestEntryType::Directory,
+        }
+    }
+
+    #[test]
+    fn test_whiteout_removes_symlink_not_target() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dest = temp_dir.path().join("rootfs");
+
+        apply_test_layer(
+            temp_dir.path(),
+            "layer0.tar",
+            vec![
+                dir_entry("data"),
+                file_entry("data/keep.txt", b"keep"),
+                TestEntry {
+                    path: "link".to_string(),
+                    entry_type: TestEntryType::Symlink {
+                        target: "data".to_string(),
+                    },
+                },
+                TestEntry {
+                    path: "dangling".to_string(),
+                    entry_type: TestEntryType::Symlink {
+                        target: "missing".to_string(),
+                    },
+                },
+            ],
+            &dest,
+        );
+        apply_test_layer(
+            temp_dir.path(),
+            "layer1.tar",
+            vec![file_entry(".wh.link", b""), file_entry(".wh.dangling", b"")],
+            &dest,
+        );
+
+        // The whited-out symlinks are gone, the directory they pointed at is intact
+        assert!(std::fs::symlink_metadata(dest.join("link")).is_err());
+        assert!(std::fs::symlink_metadata(dest.join("dangling")).is_err());
+        assert!(dest.join("data/keep.txt").exists());
+        assert!(!dest.join(".wh.link").exists());
+    }
+
+    #[test]
+    fn test_opaque_whiteout_keeps_entries_from_same_layer() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dest = temp_dir.path().join("rootfs");
+
+        apply_test_layer(
+            temp_dir.path(),
+            "layer0.tar",
+            vec![
+                dir_entry("app"),
+                dir_entry("app/lib"),
+                file_entry("app/lib/old.so", b"old"),
+                file_entry("app/config", b"old"),
+            ],
+            &dest,
+        );
+        // Opaque marker after the new entries; app/lib is only an implicit parent
+        apply_test_layer(
+            temp_dir.path(),
+            "layer1.tar",
+            vec![
+                file_entry("app/lib/new.so", b"new"),
+                file_entry("app/.wh..wh..opq", b""),
+            ],
+            &dest,
+        );
+
+        assert!(dest.join("app/lib/new.so").exists());
+        assert!(!dest.join("app/lib/old.so").exists());
+        assert!(!dest.join("app/config").exists());
+        assert!(!dest.join("app/.wh..wh..opq").exists());
+    }
+
+    #[test]
+    fn test_aufs_metadata_skipped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dest = temp_dir.path().join("rootfs");
+
+        apply_test_layer(
+            temp_dir.path(),
+            "layer0.tar",
+            vec![
+                file_entry("file.txt", b"content"),
+                dir_entry(".wh..wh.plnk"),
+                file_entry(".wh..wh.plnk/123.456", b"aufs"),
+                file_entry(".wh..wh.aufs", b""),
+            ],
+            &dest,
+        );
+
+        assert!(dest.join("file.txt").exists());
+        assert!(!dest.join(".wh..wh.plnk").exists());
+        assert!(!dest.join(".wh..wh.aufs").exists());
+    }
+
+    #[test]
+    fn test_preserve_whiteouts_keeps_markers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let tar_path = temp_dir.path().join("layer.tar");
+        let dest = temp_dir.path().join("layer");
+
+        let entries = vec![
+            dir_entry("etc"),
+            file_entry("etc/.wh.passwd-", b""),
+            file_entry("etc/.wh..wh..opq", b""),
+            file_entry("etc/hosts", b"127.0.0.1 localhost"),
+        ];
+        std::fs::write(&tar_path, create_test_tar(entries)).unwrap();
+        extract_layer_tarball(&tar_path, &dest, WhiteoutMode::Preserve).unwrap();
+
+        // Markers stay for the merge step to interpret
+        assert!(dest.join("etc/.wh.passwd-").exists());
+        assert!(dest.join("etc/.wh..wh..opq").exists());
+        assert!(dest.join("etc/hosts").exists());
+    }
+
+    #[test]
+    fn test_hardlink_to_lower_layer_target() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dest = temp_dir.path().join("rootfs");
+
+        apply_test_layer(
+            temp_dir.path(),
+            "layer0.tar",
+            vec![dir_entry("bin"), file_entry("bin/busybox", b"busybox")],
+            &dest,
+        );
+        apply_test_layer(
+            temp_dir.path(),
+            "layer1.tar",
+            vec![TestEntry {
+                path: "bin/sh".to_string(),
+                entry_type: TestEntryType::Hardlink {
+                    target: "bin/busybox".to_string(),
+                },
+            }],
+            &dest,
+        );
+
+        use std::os::unix::fs::MetadataExt;
+        let target = std::fs::metadata(dest.join("bin/busybox")).unwrap();
+        let link = std::fs::metadata(dest.join("bin/sh")).unwrap();
+        assert_eq!(target.ino(), link.ino());
+    }
+
+    #[test]
+    fn test_zero_runs_written_as_holes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dest = temp_dir.path().join("rootfs");
+
+        let mut content = vec![0u8; 4 * 1024 * 1024];
+        content.extend_from_slice(b"tail");
+
+        apply_test_layer(
+            temp_dir.path(),
+            "layer0.tar",
+            vec![
+                file_entry("sparse.img", &content),
+                file_entry("trailing-hole.img", &[0u8; 64 * 1024]),
+            ],
+            &dest,
+        );
+
+        let sparse = dest.join("sparse.img");
+        assert_eq!(std::fs::read(&sparse).unwrap(), content);
+
+        use std::os::unix::fs::MetadataExt;
+        let meta = std::fs::metadata(&sparse).unwrap();
+        assert_eq!(meta.len(), content.len() as u64);
+        assert!(
+            meta.blocks() * 512 < meta.len() / 2,
+            "expected holes, allocated {} bytes",
+            meta.blocks() * 512
+        );
+
+        let trailing = std::fs::metadata(dest.join("trailing-hole.img")).unwrap();
+        assert_eq!(trailing.len(), 64 * 1024);
+    }
+
     #[test]
     fn test_uncompressed_tar_detection() {
         // Test that uncompressed tar is handled
@@ -0,0 +1,138 @@
+//! Pull-through blob cache shared across runtime homes.
+//!
+//! Layer and config blobs downloaded by any runtime are also stored under
+//! `{cache_dir}/{algorithm}/{hex}`. Later pulls copy blobs from here instead
+//! of the registry, so CI machines running many homes fetch each blob once.
+//! Blobs are verified against their digest when copied out, and entries are
+//! written atomically (temp file + rename), so concurrent runtimes can share
+//! the directory.
+
+use crate::images::storage::StagedDownload;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub(crate) struct BlobCache {
+    dir: PathBuf,
+}
+
+impl BlobCache {
+    pub(crate) fn new(dir: PathBuf) -> BoxliteResult<Self> {
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "failed to create blob cache {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self { dir })
+    }
+
+    /// Cache path of a blob, or `None` for malformed digests.
+    fn blob_path(&self, digest: &str) -> Option<PathBuf> {
+        let (algorithm, hex) = digest.split_once(':')?;
+        let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric());
+        (valid(algorithm) && valid(hex)).then(|| self.dir.join(algorithm).join(hex))
+    }
+
+    pub(crate) fn contains(&self, digest: &str) -> bool {
+        self.blob_path(digest).is_some_and(|p| p.exists())
+    }
+
+    /// Copy a cached blob into `staged` and commit it.
+    ///
+    /// Returns false if the blob couldn't be used; corrupt entries are removed.
+    pub(crate) async fn restore(&self, digest: &str, mut staged: StagedDownload) -> bool {
+        let Some(path) = self.blob_path(digest) else {
+            staged.abort().await;
+            return false;
+        };
+
+        let copied = match tokio::fs::File::open(&path).await {
+            Ok(mut src) => tokio::io::copy(&mut src, staged.file()).await.is_ok(),
+            Err(_) => false,
+        };
+        if !copied {
+            staged.abort().await;
+            return false;
+        }
+
+        match staged.commit().await {
+            Ok(true) => true,
+            Ok(false) => {
+                tracing::warn!("Removing corrupt blob cache entry: {}", path.display());
+                let _ = tokio::fs::remove_file(&path).await;
+                false
+            }
+            Err(e) => {
+                tracing::warn!("Failed to restore {} from blob cache: {}", digest, e);
+                false
+            }
+        }
+    }
+
+    /// Add a verified blob to the cache. Failures are logged and ignored.
+    pub(crate) async fn insert(&self, digest: &str, src: &Path) {
+        let Some(path) = self.blob_path(digest) else {
+            return;
+        };
+        if path.exists() {
+            return;
+        }
+        if let Err(e) = Self::insert_file(src, &path).await {
+            tracing::warn!("Failed to add {} to blob cache: {}", digest, e);
+        }
+    }
+
+    async fn insert_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let temp = dest.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+        // Hard link when on the same filesystem, copy otherwise
+        if tokio::fs::hard_link(src, &temp).await.is_err() {
+            tokio::fs::copy(src, &temp).await?;
+        }
+        let result = tokio::fs::rename(&temp, dest).await;
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_path() {
+        let cache = BlobCache {
+            dir: PathBuf::from("/cache"),
+        };
+        assert_eq!(
+            cache.blob_path("sha256:abc123"),
+            Some(PathBuf::from("/cache/sha256/abc123"))
+        );
+        assert_eq!(cache.blob_path("sha256:../etc"), None);
+        assert_eq!(cache.blob_path("abc123"), None);
+    }
+
+    #[tokio::test]
+    async fn test_insert_is_idempotent() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = BlobCache::new(temp.path().join("cache")).unwrap();
+        let src = temp.path().join("blob");
+        std::fs::write(&src, b"layer").unwrap();
+
+        let digest = "sha256:0123abcd";
+        assert!(!cache.contains(digest));
+        cache.insert(digest, &src).await;
+        cache.insert(digest, &src).await;
+        assert!(cache.contains(digest));
+        assert_eq!(
+            std::fs::read(temp.path().join("cache/sha256/0123abcd")).unwrap(),
+            b"layer"
+        );
+    }
+}
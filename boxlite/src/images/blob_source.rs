@@ -15,7 +15,7 @@
 use std::path::{Path, PathBuf};
 
 use crate::disk::{Disk, DiskFormat};
-use crate::images::archive::extract_layer_tarball_streaming;
+use crate::images::archive::{WhiteoutMode, extract_layer_tarball};
 use crate::images::storage::ImageStorage;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
@@ -94,6 +94,16 @@ impl BlobSource {
         }
     }
 
+    /// Get cached erofs image for a single layer, if available.
+    pub fn layer_erofs_image(&self, layer_digest: &str) -> Option<Disk> {
+        let path = match self {
+            Self::Store(s) => s.storage.disk_image_path(layer_digest, DiskFormat::Erofs),
+            Self::LocalBundle(l) => l.disk_image_path(layer_digest, DiskFormat::Erofs),
+        };
+        path.exists()
+            .then(|| Disk::new(path, DiskFormat::Erofs, true))
+    }
+
     /// Install disk image to source-specific cache.
     ///
     /// This method is async because `fs::rename` can block on network filesystems
@@ -331,7 +341,7 @@ impl LocalBundleBlobSource {
         })?;
 
         // Extract tarball
-        if let Err(e) = extract_layer_tarball_streaming(tarball_path, &temp_path) {
+        if let Err(e) = extract_layer_tarball(tarball_path, &temp_path, WhiteoutMode::Preserve) {
             let _ = std::fs::remove_dir_all(&temp_path);
             return Err(e);
         }
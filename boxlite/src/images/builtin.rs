@@ -0,0 +1,244 @@
+//! Built-in busybox image.
+//!
+//! With the `builtin-image` feature, a static busybox is embedded at build
+//! time (see `BOXLITE_BUSYBOX` in `build.rs`) and assembled into a one-layer OCI image the first time it is
+//! used, so boxes can start without a registry. The image is then stored and
+//! indexed like a pulled one under [`images::BUILTIN`].
+
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256};
+
+use super::store::ImageStore;
+use crate::runtime::constants::images;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Static busybox for the target architecture, empty if the crate was built
+/// without the `builtin-image` feature.
+#[cfg(feature = "builtin-image")]
+static BUSYBOX: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/busybox"));
+#[cfg(not(feature = "builtin-image"))]
+static BUSYBOX: &[u8] = &[];
+
+/// Applets linked to busybox in `/bin`.
+const APPLETS: &[&str] = &[
+    "[",
+    "ash",
+    "awk",
+    "basename",
+    "cat",
+    "chmod",
+    "chown",
+    "cp",
+    "cut",
+    "date",
+    "dd",
+    "df",
+    "dirname",
+    "du",
+    "echo",
+    "env",
+    "expr",
+    "false",
+    "find",
+    "free",
+    "grep",
+    "gunzip",
+    "gzip",
+    "head",
+    "hostname",
+    "id",
+    "ip",
+    "kill",
+    "less",
+    "ln",
+    "ls",
+    "md5sum",
+    "mkdir",
+    "mount",
+    "mv",
+    "nc",
+    "nslookup",
+    "ping",
+    "printf",
+    "ps",
+    "pwd",
+    "readlink",
+    "realpath",
+    "rm",
+    "rmdir",
+    "sed",
+    "seq",
+    "sh",
+    "sha256sum",
+    "sleep",
+    "sort",
+    "stat",
+    "sync",
+    "tail",
+    "tar",
+    "tee",
+    "test",
+    "top",
+    "touch",
+    "tr",
+    "true",
+    "umount",
+    "uname",
+    "uniq",
+    "vi",
+    "wc",
+    "wget",
+    "which",
+    "whoami",
+    "xargs",
+    "yes",
+];
+
+const DIRS: &[&str] = &[
+    "bin", "dev", "etc", "home", "proc", "root", "run", "sys", "tmp", "usr", "usr/bin", "var",
+    "var/tmp",
+];
+
+const FILES: &[(&str, &str)] = &[
+    (
+        "etc/passwd",
+        "root:x:0:0:root:/root:/bin/sh\nnobody:x:65534:65534:nobody:/nonexistent:/bin/false\n",
+    ),
+    ("etc/group", "root:x:0:\nnobody:x:65534:\n"),
+    ("etc/hosts", "127.0.0.1\tlocalhost\n::1\tlocalhost\n"),
+];
+
+/// Config and layer blobs of the built-in image.
+pub(super) struct BuiltinImage {
+    pub(super) config: Vec<u8>,
+    /// Gzip-compressed layer tarball
+    pub(super) layer: Vec<u8>,
+}
+
+/// Whether this build embeds busybox, i.e. whether [`images::BUILTIN`] can
+/// be used.
+pub(crate) fn is_available() -> bool {
+    !BUSYBOX.is_empty()
+}
+
+/// Assemble the built-in image from the embedded busybox.
+pub(super) fn build() -> BoxliteResult<BuiltinImage> {
+    if !is_available() {
+        return Err(BoxliteError::Unsupported(format!(
+            "image {} is not available: boxlite was built without the builtin-image feature",
+            images::BUILTIN
+        )));
+    }
+    build_from(BUSYBOX)
+}
+
+/// The layer is reproducible (fixed owners and mtimes), so every build of
+/// the same busybox yields the same digests.
+fn build_from(busybox: &[u8]) -> BoxliteResult<BuiltinImage> {
+    let tar = layer_tarball(busybox)?;
+    let diff_id = format!("sha256:{:x}", Sha256::digest(&tar));
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar)?;
+    let layer = encoder.finish()?;
+
+    let (os, arch) = ImageStore::detect_platform();
+    let config = serde_json::json!({
+        "architecture": arch,
+        "os": os,
+        "config": {
+            "Env": ["PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"],
+            "Cmd": ["sh"],
+        },
+        "rootfs": {
+            "type": "layers",
+            "diff_ids": [diff_id],
+        },
+    });
+
+    Ok(BuiltinImage {
+        config: serde_json::to_vec(&config)?,
+        layer,
+    })
+}
+
+fn layer_tarball(busybox: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(Vec::new());
+
+    for dir in DIRS {
+        let mode = if dir.ends_with("tmp") { 0o1777 } else { 0o755 };
+        let mut header = entry_header(tar::EntryType::Directory, mode, 0);
+        builder.append_data(&mut header, dir, std::io::empty())?;
+    }
+
+    let mut header = entry_header(tar::EntryType::Regular, 0o755, busybox.len() as u64);
+    builder.append_data(&mut header, "bin/busybox", busybox)?;
+    for applet in APPLETS {
+        let mut header = entry_header(tar::EntryType::Symlink, 0o777, 0);
+        builder.append_link(&mut header, format!("bin/{}", applet), "busybox")?;
+    }
+
+    for (path, content) in FILES {
+        let mut header = entry_header(tar::EntryType::Regular, 0o644, content.len() as u64);
+        builder.append_data(&mut header, path, content.as_bytes())?;
+    }
+
+    builder.into_inner()
+}
+
+/// Header owned by root with a zero mtime.
+fn entry_header(entry_type: tar::EntryType, mode: u32, size: u64) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(entry_type);
+    header.set_mode(mode);
+    header.set_size(size);
+    header.set_uid(0);
+    header.set_gid(0);
+    header.set_mtime(0);
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(layer: &[u8]) -> Vec<(String, tar::EntryType)> {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(layer));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let path = entry.path().unwrap().display().to_string();
+                (path, entry.header().entry_type())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builtin_layer_links_applets_to_busybox() {
+        let image = build_from(b"\x7fELF fake busybox").unwrap();
+        let entries = entries(&image.layer);
+
+        assert!(entries.contains(&("bin/busybox".to_string(), tar::EntryType::Regular)));
+        assert!(entries.contains(&("bin/sh".to_string(), tar::EntryType::Symlink)));
+        assert!(entries.contains(&("tmp".to_string(), tar::EntryType::Directory)));
+        assert!(entries.contains(&("etc/passwd".to_string(), tar::EntryType::Regular)));
+
+        let config: serde_json::Value = serde_json::from_slice(&image.config).unwrap();
+        assert_eq!(config["os"], "linux");
+        assert_eq!(config["config"]["Cmd"][0], "sh");
+        assert_eq!(config["rootfs"]["diff_ids"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn builtin_image_is_reproducible() {
+        let first = build_from(b"busybox").unwrap();
+        let second = build_from(b"busybox").unwrap();
+        assert_eq!(first.layer, second.layer);
+        assert_eq!(first.config, second.config);
+    }
+}
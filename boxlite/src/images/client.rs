@@ -0,0 +1,163 @@
+//! OCI registry client setup: proxies, extra CAs and insecure registries.
+
+use crate::runtime::options::BoxliteOptions;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use oci_client::client::{Certificate, CertificateEncoding, ClientConfig, ClientProtocol};
+use oci_client::errors::OciDistributionError;
+use std::error::Error as _;
+
+/// Build the registry client for a runtime.
+///
+/// - `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` (or their lowercase forms) are
+///   read from the environment.
+/// - Certificates in `registry_ca_bundle` are trusted in addition to the
+///   system roots.
+/// - Registries in `insecure_registries` are contacted over plain HTTP.
+pub(crate) fn build_client(options: &BoxliteOptions) -> BoxliteResult<oci_client::Client> {
+    let mut extra_root_certificates = Vec::new();
+    if let Some(ref path) = options.registry_ca_bundle {
+        let pem = std::fs::read_to_string(path).map_err(|e| {
+            BoxliteError::Config(format!(
+                "failed to read registry CA bundle {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let certs = split_pem_bundle(&pem);
+        if certs.is_empty() {
+            return Err(BoxliteError::Config(format!(
+                "registry CA bundle {} contains no PEM certificates",
+                path.display()
+            )));
+        }
+        tracing::debug!(
+            "Trusting {} extra registry CA certificate(s) from {}",
+            certs.len(),
+            path.display()
+        );
+        extra_root_certificates = certs
+            .into_iter()
+            .map(|data| Certificate {
+                encoding: CertificateEncoding::Pem,
+                data: data.into_bytes(),
+            })
+            .collect();
+    }
+
+    let protocol = if options.insecure_registries.is_empty() {
+        ClientProtocol::Https
+    } else {
+        tracing::warn!(
+            registries = ?options.insecure_registries,
+            "Pulling from insecure registries over plain HTTP"
+        );
+        ClientProtocol::HttpsExcept(options.insecure_registries.clone())
+    };
+
+    let config = ClientConfig {
+        protocol,
+        extra_root_certificates,
+        https_proxy: env_var(&["HTTPS_PROXY", "https_proxy"]),
+        http_proxy: env_var(&["HTTP_PROXY", "http_proxy"]),
+        no_proxy: env_var(&["NO_PROXY", "no_proxy"]),
+        ..Default::default()
+    };
+    Ok(oci_client::Client::new(config))
+}
+
+/// First non-empty value among `names`.
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Split a PEM bundle into individual certificates.
+fn split_pem_bundle(pem: &str) -> Vec<String> {
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut certs = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find("-----BEGIN CERTIFICATE-----") {
+        let Some(end) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + end + END.len();
+        certs.push(format!("{}\n", &rest[start..end]));
+        rest = &rest[end..];
+    }
+    certs
+}
+
+/// Format a registry error, adding a hint when TLS verification failed.
+pub(crate) fn describe_error(context: &str, err: &OciDistributionError) -> String {
+    if is_tls_error(err) {
+        format!(
+            "{context}: {err}\nTLS verification failed. If the registry uses a private CA, set \
+             `registry_ca_bundle` to its certificate bundle; if it only serves plain HTTP, add \
+             it to `insecure_registries`."
+        )
+    } else {
+        format!("{context}: {err}")
+    }
+}
+
+/// Whether a certificate problem is anywhere in the error chain.
+fn is_tls_error(err: &OciDistributionError) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(err);
+    while let Some(e) = source {
+        let msg = e.to_string().to_lowercase();
+        if msg.contains("certificate") || msg.contains("unknownissuer") {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERT_A: &str = "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----";
+    const CERT_B: &str = "-----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----";
+
+    #[test]
+    fn test_split_pem_bundle() {
+        let bundle = format!("# corporate roots\n{}\n\n{}\n", CERT_A, CERT_B);
+        let certs = split_pem_bundle(&bundle);
+        assert_eq!(
+            certs,
+            vec![format!("{}\n", CERT_A), format!("{}\n", CERT_B)]
+        );
+        assert!(split_pem_bundle("not a certificate").is_empty());
+    }
+
+    #[test]
+    fn test_build_client_rejects_empty_ca_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ca.pem");
+        std::fs::write(&path, "garbage").unwrap();
+
+        let options = BoxliteOptions {
+            registry_ca_bundle: Some(path),
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_client(&options),
+            Err(BoxliteError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_describe_error_adds_tls_hint() {
+        let err = OciDistributionError::IoError(std::io::Error::other(
+            "invalid peer certificate: UnknownIssuer",
+        ));
+        assert!(describe_error("failed to pull manifest", &err).contains("registry_ca_bundle"));
+
+        let err = OciDistributionError::IoError(std::io::ErrorKind::ConnectionReset.into());
+        assert!(!describe_error("failed to pull manifest", &err).contains("registry_ca_bundle"));
+    }
+}
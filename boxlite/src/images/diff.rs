@@ -0,0 +1,390 @@
+//! File-level diff between images, or between an image and a box rootfs.
+//!
+//! An image's files are read from the headers of its layer tarballs, bottom
+//! to top, applying whiteouts like layer extraction does; nothing is
+//! unpacked. Files and symlinks are compared by size, modification time,
+//! link target, mode and owner. Directories only show up through the files
+//! under them. Each file of an image remembers the layer that last wrote it,
+//! which is where a changed file comes from.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::Serialize;
+use tar::{Archive, EntryType};
+
+use super::ImageObject;
+use super::archive::{
+    OPAQUE_WHITEOUT, is_aufs_metadata, is_estargz_metadata, normalize_entry_path,
+    open_layer_tarball,
+};
+
+/// Files that differ between two root filesystems.
+///
+/// Paths are relative to the root, `/`-separated and sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImageDiff {
+    /// Only in the new root filesystem
+    pub added: Vec<String>,
+    /// In both, with another size, modification time, link target, mode
+    /// or owner
+    pub modified: Vec<String>,
+    /// Only in the old root filesystem
+    pub removed: Vec<String>,
+    /// Digest of the image layer each changed path comes from: the layer
+    /// that wrote it in the new root filesystem, or in the old one for paths
+    /// a box changed or removed
+    pub provenance: BTreeMap<String, String>,
+}
+
+impl ImageDiff {
+    /// Whether both root filesystems have the same files.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// What a file is compared by, and where it comes from.
+#[derive(Debug, Clone)]
+pub(crate) struct FileMeta {
+    pub(crate) size: u64,
+    pub(crate) mtime: i64,
+    pub(crate) link_target: Option<String>,
+    /// Permission bits; `None` if unknown, like the owner
+    pub(crate) mode: Option<u32>,
+    pub(crate) uid: Option<u32>,
+    pub(crate) gid: Option<u32>,
+    /// Digest of the layer that last wrote the file; `None` outside images
+    pub(crate) layer: Option<String>,
+}
+
+impl FileMeta {
+    /// Whether `other` is another file. Mode and owner count when both sides
+    /// know them; the mode of a symlink doesn't.
+    fn differs(&self, other: &FileMeta) -> bool {
+        let known_differ =
+            |a: Option<u32>, b: Option<u32>| matches!((a, b), (Some(a), Some(b)) if a != b);
+        self.size != other.size
+            || self.mtime != other.mtime
+            || self.link_target != other.link_target
+            || (self.link_target.is_none() && known_differ(self.mode, other.mode))
+            || known_differ(self.uid, other.uid)
+            || known_differ(self.gid, other.gid)
+    }
+}
+
+/// Files and symlinks of a root filesystem by relative path.
+pub(crate) type FileTree = BTreeMap<String, FileMeta>;
+
+/// Files of `image`, read from its layers.
+pub(crate) async fn image_tree(image: &ImageObject) -> BoxliteResult<FileTree> {
+    let layers: Vec<(String, PathBuf)> = image
+        .layer_digests()
+        .into_iter()
+        .map(str::to_string)
+        .zip(image.layer_tarballs())
+        .collect();
+    tokio::task::spawn_blocking(move || layers_tree(&layers))
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+}
+
+/// Compare `old` to `new`.
+pub(crate) fn diff_trees(old: &FileTree, new: &FileTree) -> ImageDiff {
+    let mut diff = ImageDiff::default();
+    for (path, meta) in new {
+        let layer = match old.get(path) {
+            None => {
+                diff.added.push(path.clone());
+                meta.layer.as_ref()
+            }
+            Some(old_meta) if old_meta.differs(meta) => {
+                diff.modified.push(path.clone());
+                meta.layer.as_ref().or(old_meta.layer.as_ref())
+            }
+            Some(_) => None,
+        };
+        if let Some(layer) = layer {
+            diff.provenance.insert(path.clone(), layer.clone());
+        }
+    }
+    for (path, meta) in old {
+        if new.contains_key(path) {
+            continue;
+        }
+        diff.removed.push(path.clone());
+        if let Some(layer) = &meta.layer {
+            diff.provenance.insert(path.clone(), layer.clone());
+        }
+    }
+    diff
+}
+
+/// Files of `layers`, `(digest, tarball)` ordered bottom to top.
+fn layers_tree(layers: &[(String, PathBuf)]) -> BoxliteResult<FileTree> {
+    let mut tree = FileTree::new();
+    for (digest, tarball) in layers {
+        apply_layer(&mut tree, digest, tarball)?;
+    }
+    Ok(tree)
+}
+
+/// An entry of a layer, applied once its whiteouts are.
+enum Change {
+    File(String, FileMeta),
+    Hardlink(String, String),
+    Dir(String),
+}
+
+fn apply_layer(tree: &mut FileTree, digest: &str, tarball: &Path) -> BoxliteResult<()> {
+    let tar_error = |e: std::io::Error| {
+        BoxliteError::Storage(format!("Failed to read layer {}: {}", tarball.display(), e))
+    };
+
+    // Whiteouts hide files of lower layers only, wherever they are in the tar
+    let mut changes = Vec::new();
+    let mut archive = Archive::new(open_layer_tarball(tarball)?);
+    for entry in archive.entries().map_err(tar_error)? {
+        let entry = entry.map_err(tar_error)?;
+        let Some(path) = normalize_entry_path(&entry.path().map_err(tar_error)?) else {
+            continue;
+        };
+        if path.as_os_str().is_empty() || is_estargz_metadata(&path) || is_aufs_metadata(&path) {
+            continue;
+        }
+        let path = path.to_string_lossy().into_owned();
+
+        let (dir, name) = match path.rsplit_once('/') {
+            Some((dir, name)) => (Some(dir), name),
+            None => (None, path.as_str()),
+        };
+        if name == OPAQUE_WHITEOUT {
+            match dir {
+                Some(dir) => remove_under(tree, dir),
+                None => tree.clear(),
+            }
+            continue;
+        }
+        if let Some(hidden) = name.strip_prefix(".wh.") {
+            let hidden = match dir {
+                Some(dir) => format!("{}/{}", dir, hidden),
+                None => hidden.to_string(),
+            };
+            tree.remove(&hidden);
+            remove_under(tree, &hidden);
+            continue;
+        }
+
+        let header = entry.header();
+        let meta = |size, link_target| FileMeta {
+            size,
+            mtime: header.mtime().unwrap_or(0) as i64,
+            link_target,
+            mode: header.mode().ok().map(|mode| mode & 0o7777),
+            uid: header.uid().ok().and_then(|uid| u32::try_from(uid).ok()),
+            gid: header.gid().ok().and_then(|gid| u32::try_from(gid).ok()),
+            layer: Some(digest.to_string()),
+        };
+        match header.entry_type() {
+            EntryType::Directory => changes.push(Change::Dir(path)),
+            EntryType::Regular | EntryType::Continuous | EntryType::GNUSparse => {
+                let meta = meta(header.size().unwrap_or(0), None);
+                changes.push(Change::File(path, meta));
+            }
+            EntryType::Symlink => {
+                let target = entry
+                    .link_name()
+                    .map_err(tar_error)?
+                    .map(|target| target.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let meta = meta(target.len() as u64, Some(target));
+                changes.push(Change::File(path, meta));
+            }
+            EntryType::Link => {
+                let target = entry
+                    .link_name()
+                    .map_err(tar_error)?
+                    .and_then(|target| normalize_entry_path(&target));
+                if let Some(target) = target {
+                    changes.push(Change::Hardlink(
+                        path,
+                        target.to_string_lossy().into_owned(),
+                    ));
+                }
+            }
+            // Devices and FIFOs aren't compared
+            _ => {}
+        }
+    }
+
+    for change in changes {
+        match change {
+            Change::File(path, meta) => insert(tree, path, meta),
+            Change::Hardlink(path, target) => {
+                if let Some(mut meta) = tree.get(&target).cloned() {
+                    meta.layer = Some(digest.to_string());
+                    insert(tree, path, meta);
+                }
+            }
+            // A directory replaces a file at its path
+            Change::Dir(path) => {
+                remove_ancestors(tree, &path);
+                tree.remove(&path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Add a file, replacing whatever was at its path or above it.
+fn insert(tree: &mut FileTree, path: String, meta: FileMeta) {
+    remove_ancestors(tree, &path);
+    remove_under(tree, &path);
+    tree.insert(path, meta);
+}
+
+/// Remove the files that the directories above `path` replace.
+fn remove_ancestors(tree: &mut FileTree, path: &str) {
+    for (end, _) in path.match_indices('/') {
+        tree.remove(&path[..end]);
+    }
+}
+
+/// Remove the files under directory `dir`.
+fn remove_under(tree: &mut FileTree, dir: &str) {
+    let prefix = format!("{}/", dir);
+    let under: Vec<String> = tree
+        .range(prefix.clone()..)
+        .take_while(|(path, _)| path.starts_with(&prefix))
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in under {
+        tree.remove(&path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write an uncompressed layer of `(path, content)` files; a path ending
+    /// in `/` is a directory. The layer's digest is its name.
+    fn write_layer(dir: &Path, name: &str, entries: &[(&str, &[u8])]) -> (String, PathBuf) {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_path(path).unwrap();
+            if path.ends_with('/') {
+                header.set_entry_type(EntryType::Directory);
+                header.set_mode(0o755);
+            } else {
+                header.set_mode(0o644);
+            }
+            header.set_size(content.len() as u64);
+            header.set_mtime(1_700_000_000);
+            header.set_cksum();
+            builder.append(&header, *content).unwrap();
+        }
+        let tarball = dir.join(name);
+        std::fs::write(&tarball, builder.into_inner().unwrap()).unwrap();
+        (name.to_string(), tarball)
+    }
+
+    #[test]
+    fn test_layers_tree_applies_whiteouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = write_layer(
+            dir.path(),
+            "base.tar",
+            &[
+                ("etc/", b""),
+                ("etc/passwd", b"root"),
+                ("etc/hosts", b"localhost"),
+                ("app/", b""),
+                ("app/old.js", b"old"),
+                ("opt", b"a file"),
+            ],
+        );
+        let top = write_layer(
+            dir.path(),
+            "top.tar",
+            &[
+                ("etc/.wh.hosts", b""),
+                ("app/new.js", b"new"),
+                ("app/.wh..wh..opq", b""),
+                ("opt/", b""),
+                ("opt/tool", b"bin"),
+            ],
+        );
+
+        let tree = layers_tree(&[base.clone(), top]).unwrap();
+        let paths: Vec<&str> = tree.keys().map(String::as_str).collect();
+        assert_eq!(paths, ["app/new.js", "etc/passwd", "opt/tool"]);
+
+        let base = layers_tree(&[base]).unwrap();
+        let diff = diff_trees(&base, &tree);
+        assert_eq!(diff.added, ["app/new.js", "opt/tool"]);
+        assert!(diff.modified.is_empty());
+        assert_eq!(diff.removed, ["app/old.js", "etc/hosts", "opt"]);
+        assert!(diff_trees(&tree, &tree).is_empty());
+
+        // Each path comes from the layer that wrote it
+        assert_eq!(diff.provenance["app/new.js"], "top.tar");
+        assert_eq!(diff.provenance["etc/hosts"], "base.tar");
+        assert_eq!(tree["etc/passwd"].layer.as_deref(), Some("base.tar"));
+        assert_eq!(tree["etc/passwd"].mode, Some(0o644));
+    }
+
+    #[test]
+    fn test_diff_trees_compares_metadata() {
+        let meta = |size, link_target: Option<&str>| FileMeta {
+            size,
+            mtime: 0,
+            link_target: link_target.map(str::to_string),
+            mode: Some(0o644),
+            uid: Some(0),
+            gid: Some(0),
+            layer: Some("base".to_string()),
+        };
+        let old = FileTree::from([
+            ("bin/sh".to_string(), meta(7, Some("busybox"))),
+            ("bin/ls".to_string(), meta(7, Some("busybox"))),
+            ("etc/motd".to_string(), meta(10, None)),
+            ("etc/os-release".to_string(), meta(20, None)),
+            ("etc/shadow".to_string(), meta(30, None)),
+            ("usr/bin/su".to_string(), meta(40, None)),
+            ("var/log/app".to_string(), meta(0, None)),
+        ]);
+        let mut new = FileTree::from([
+            ("bin/sh".to_string(), meta(4, Some("bash"))),
+            ("bin/ls".to_string(), meta(7, Some("busybox"))),
+            ("etc/motd".to_string(), meta(10, None)),
+            ("etc/os-release".to_string(), meta(21, None)),
+            ("etc/shadow".to_string(), meta(30, None)),
+            ("usr/bin/su".to_string(), meta(40, None)),
+            ("var/log/app".to_string(), meta(0, None)),
+        ]);
+        new.get_mut("bin/ls").unwrap().mode = Some(0o777);
+        new.get_mut("etc/shadow").unwrap().mode = Some(0o666);
+        new.get_mut("usr/bin/su").unwrap().mode = Some(0o4755);
+        new.get_mut("var/log/app").unwrap().uid = Some(1000);
+        // Unknown mode and owner, as in a box rootfs listed by an old guest
+        let motd = new.get_mut("etc/motd").unwrap();
+        (motd.mode, motd.uid, motd.gid, motd.layer) = (None, None, None, None);
+
+        let diff = diff_trees(&old, &new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(
+            diff.modified,
+            [
+                "bin/sh",
+                "etc/os-release",
+                "etc/shadow",
+                "usr/bin/su",
+                "var/log/app"
+            ]
+        );
+        assert_eq!(diff.provenance["etc/shadow"], "base");
+    }
+}
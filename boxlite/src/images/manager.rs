@@ -9,17 +9,28 @@
 //! - `ImageStore` handles all locking internally
 //! - `ImageObject` uses `BlobSource` for blob access
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
 
 use chrono::{DateTime, Utc};
 
 use super::blob_source::{BlobSource, LocalBundleBlobSource, StoreBlobSource};
+use super::builtin;
+use super::diff::{self, ImageDiff};
 use super::object::ImageObject;
-use crate::db::Database;
+use super::storage::ImageStorage;
+use crate::db::{CachedImage, Database};
+use crate::images::blob_cache::BlobCache;
+use crate::images::client;
+use crate::images::retry::RegistryRetry;
 use crate::images::store::{ImageStore, SharedImageStore};
-use crate::runtime::types::ImageInfo;
-use boxlite_shared::errors::BoxliteResult;
+use crate::runtime::constants::images;
+use crate::runtime::options::{BoxliteOptions, ImagePullPolicy};
+use crate::runtime::progress::ProgressObservers;
+use crate::runtime::types::{Bytes, ImageInfo};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use oci_client::Reference;
 use std::str::FromStr;
 
@@ -39,6 +50,10 @@ pub(super) struct ImageManifest {
 pub(super) struct LayerInfo {
     pub(super) digest: String,
     pub(super) media_type: String,
+    /// External download locations (foreign/non-distributable layers only)
+    pub(super) urls: Option<Vec<String>>,
+    /// Compressed size from the manifest (0 if unknown)
+    pub(super) size: u64,
 }
 
 // ============================================================================
@@ -62,7 +77,13 @@ pub(super) struct LayerInfo {
 ///
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// let db = Database::open(&PathBuf::from("/tmp/boxlite.db"))?;
-/// let manager = ImageManager::new(PathBuf::from("/tmp/images"), db, vec![])?;
+/// let manager = ImageManager::new(
+///     PathBuf::from("/tmp/images"),
+///     db,
+///     &BoxliteOptions::default(),
+///     Arc::default(),
+///     ProgressObservers::default(),
+/// )?;
 ///
 /// // Pull an image
 /// let image = manager.pull("python:alpine").await?;
@@ -90,9 +111,31 @@ impl ImageManager {
     /// # Arguments
     /// * `images_dir` - Directory for image cache
     /// * `db` - Database for image index
-    /// * `registries` - Registries to search for unqualified images (tried in order)
-    pub fn new(images_dir: PathBuf, db: Database, registries: Vec<String>) -> BoxliteResult<Self> {
-        let store = Arc::new(ImageStore::new(images_dir, db, registries)?);
+    /// * `options` - Runtime options (registries, verification, retry, TLS)
+    /// * `retries` - Counter incremented on every registry retry
+    /// * `progress` - Observers of registry pulls
+    pub(crate) fn new(
+        images_dir: PathBuf,
+        db: Database,
+        options: &BoxliteOptions,
+        retries: Arc<AtomicU64>,
+        progress: ProgressObservers,
+    ) -> BoxliteResult<Self> {
+        let store = ImageStore::new(
+            images_dir,
+            db,
+            options.image_registries.clone(),
+            &options.image_verification,
+        )?
+        .with_client(client::build_client(options)?)
+        .with_retry(RegistryRetry::new(options.registry_retry.clone(), retries))
+        .with_mirrors(options.registry_mirrors.clone())
+        .with_progress(progress);
+        let store = match options.blob_cache_dir {
+            Some(ref dir) => store.with_blob_cache(BlobCache::new(dir.clone())?),
+            None => store,
+        };
+        let store = Arc::new(store);
         Ok(Self { store })
     }
 
@@ -104,7 +147,27 @@ impl ImageManager {
     /// Thread Safety: `ImageStore` handles locking internally. Multiple
     /// concurrent pulls of the same image will only download once.
     pub async fn pull(&self, image_ref: &str) -> BoxliteResult<ImageObject> {
-        let manifest = self.store.pull(image_ref).await?;
+        self.pull_with_policy(image_ref, ImagePullPolicy::IfNotPresent)
+            .await
+    }
+
+    /// Pull an OCI image, consulting the cache and registry according to `policy`.
+    ///
+    /// - `Always`: re-resolves from the registry (digest references use the cache)
+    /// - `IfNotPresent`: same as `pull()`
+    /// - `Never`: returns `NotFound` unless the image is already cached
+    ///
+    /// The built-in image (`images::BUILTIN`) never touches a registry; it is
+    /// assembled from the embedded busybox if it is not cached.
+    pub async fn pull_with_policy(
+        &self,
+        image_ref: &str,
+        policy: ImagePullPolicy,
+    ) -> BoxliteResult<ImageObject> {
+        if image_ref == images::BUILTIN {
+            return self.builtin().await;
+        }
+        let manifest = self.store.pull(image_ref, policy).await?;
         let storage = self.store.storage().await;
         let blob_source = BlobSource::Store(StoreBlobSource::new(storage));
 
@@ -115,42 +178,238 @@ impl ImageManager {
         ))
     }
 
+    /// The built-in busybox image, adding it to the cache on first use.
+    ///
+    /// Returns `Unsupported` if the crate was built without busybox.
+    pub async fn builtin(&self) -> BoxliteResult<ImageObject> {
+        let manifest = match self.store.cached(images::BUILTIN).await? {
+            Some(manifest) => manifest,
+            None => {
+                let image = builtin::build()?;
+                self.store
+                    .import(images::BUILTIN, &image.config, &[image.layer])
+                    .await?
+            }
+        };
+        let storage = self.store.storage().await;
+        let blob_source = BlobSource::Store(StoreBlobSource::new(storage));
+
+        Ok(ImageObject::new(
+            images::BUILTIN.to_string(),
+            manifest,
+            blob_source,
+        ))
+    }
+
     /// List all cached images.
+    ///
+    /// Sizes and build times are read from the image store; `in_use_by` is
+    /// left at 0 for the runtime to fill in.
     pub async fn list(&self) -> BoxliteResult<Vec<ImageInfo>> {
         let raw_images = self.store.list().await?;
+        let storage = self.store.storage().await;
+        // Sizes and build times come from walking and reading image files
+        tokio::task::spawn_blocking(move || {
+            raw_images
+                .into_iter()
+                .map(|(reference, cached)| image_info(&storage, reference, cached))
+                .collect()
+        })
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))
+    }
+
+    /// The cached image `image_ref` resolves to, like one entry of `list()`.
+    ///
+    /// Returns `None` if the image is not cached.
+    pub async fn info(&self, image_ref: &str) -> BoxliteResult<Option<ImageInfo>> {
+        let Some(reference) = self.store.resolve_cached(image_ref).await? else {
+            return Ok(None);
+        };
+        let Some(cached) = self.store.get(&reference).await? else {
+            return Ok(None);
+        };
+        let storage = self.store.storage().await;
+        tokio::task::spawn_blocking(move || Some(image_info(&storage, reference, cached)))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))
+    }
+
+    /// Resolve an image reference to the reference it is cached under.
+    ///
+    /// Returns `None` if the image is not cached.
+    pub async fn resolve_cached(&self, image_ref: &str) -> BoxliteResult<Option<String>> {
+        self.store.resolve_cached(image_ref).await
+    }
+
+    /// Files added, modified and removed from cached image `old_ref` to
+    /// cached image `new_ref`.
+    ///
+    /// Returns `NotFound` if either image is not in the local cache.
+    pub async fn diff(&self, old_ref: &str, new_ref: &str) -> BoxliteResult<ImageDiff> {
+        let old = self.cached_image(old_ref).await?;
+        let new = self.cached_image(new_ref).await?;
+        if old.layer_digests() == new.layer_digests() {
+            return Ok(ImageDiff::default());
+        }
+        let old_tree = diff::image_tree(&old).await?;
+        let new_tree = diff::image_tree(&new).await?;
+        Ok(diff::diff_trees(&old_tree, &new_tree))
+    }
+
+    /// Digest of the layer that provides each of `paths` in cached image
+    /// `image_ref`. Paths that aren't files or symlinks of the image are left
+    /// out.
+    ///
+    /// Returns `NotFound` if the image is not in the local cache.
+    pub async fn provenance(
+        &self,
+        image_ref: &str,
+        paths: &[String],
+    ) -> BoxliteResult<BTreeMap<String, String>> {
+        let image = self.cached_image(image_ref).await?;
+        let tree = diff::image_tree(&image).await?;
+        Ok(paths
+            .iter()
+            .filter_map(|path| {
+                let layer = tree.get(path.trim_matches('/'))?.layer.clone()?;
+                Some((path.clone(), layer))
+            })
+            .collect())
+    }
 
-        let mut images = Vec::with_capacity(raw_images.len());
-        for (reference, cached) in raw_images {
-            // If parsing fails, default to UNIX_EPOCH to signal error
-            let cached_at = DateTime::parse_from_rfc3339(&cached.cached_at)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|e| {
-                    tracing::warn!("Invalid cached_at timestamp: {}, using epoch", e);
-                    DateTime::<Utc>::from(std::time::SystemTime::UNIX_EPOCH)
-                });
-
-            let (repository, tag) = match Reference::from_str(&reference) {
-                Ok(r) => (
-                    r.repository().to_string(),
-                    r.tag().unwrap_or("latest").to_string(),
-                ),
-                Err(_) => {
-                    // Fallback if reference stored in DB is invalid
-                    (reference.clone(), "<none>".to_string())
-                }
-            };
-
-            images.push(ImageInfo {
-                reference,
-                repository,
-                tag,
-                id: cached.manifest_digest,
-                cached_at,
-                size: None, // Size calculation is expensive now? omitted for list temporarily
+    /// Cached image `image_ref`, without pulling it.
+    ///
+    /// Returns `NotFound` if the image is not in the local cache.
+    pub(crate) async fn cached_image(&self, image_ref: &str) -> BoxliteResult<ImageObject> {
+        let not_found =
+            || BoxliteError::NotFound(format!("image not found in local cache: {}", image_ref));
+        let reference = self
+            .store
+            .resolve_cached(image_ref)
+            .await?
+            .ok_or_else(not_found)?;
+        let manifest = self.store.cached(&reference).await?.ok_or_else(not_found)?;
+        let storage = self.store.storage().await;
+        let blob_source = BlobSource::Store(StoreBlobSource::new(storage));
+
+        Ok(ImageObject::new(reference, manifest, blob_source))
+    }
+
+    /// Add `target` as another reference to the cached image `source`.
+    ///
+    /// Returns the resolved target reference, or `NotFound` if `source` is
+    /// not in the local cache.
+    pub async fn tag(&self, source: &str, target: &str) -> BoxliteResult<String> {
+        match self.store.tag(source, target).await? {
+            Some(resolved) => {
+                tracing::info!(source, target = %resolved, "Tagged image");
+                Ok(resolved)
+            }
+            None => Err(BoxliteError::NotFound(format!(
+                "image not found in local cache: {}",
+                source
+            ))),
+        }
+    }
+
+    /// Manifest digest of each cached reference.
+    pub(crate) async fn cached_digests(&self) -> BoxliteResult<HashMap<String, String>> {
+        Ok(self
+            .store
+            .list()
+            .await?
+            .into_iter()
+            .map(|(reference, cached)| (reference, cached.manifest_digest))
+            .collect())
+    }
+
+    /// Remove a cached image reference.
+    ///
+    /// `users` is the number of boxes per manifest digest. The last reference
+    /// to content used by boxes, and pinned references, are refused unless
+    /// `force` is set; removing one of several references to the same
+    /// content only untags it. Image files are deleted once no other
+    /// reference uses them, except for content still used by boxes, whose
+    /// disks are backed by it.
+    ///
+    /// Returns the removed reference.
+    pub async fn remove(
+        &self,
+        image_ref: &str,
+        force: bool,
+        users: &HashMap<String, usize>,
+    ) -> BoxliteResult<String> {
+        let Some(reference) = self.store.resolve_cached(image_ref).await? else {
+            return Err(BoxliteError::NotFound(format!(
+                "image not found in local cache: {}",
+                image_ref
+            )));
+        };
+        let cached = self.store.list().await?;
+        let Some(removed) = cached
+            .iter()
+            .find_map(|(r, image)| (*r == reference).then_some(image))
+        else {
+            return Err(BoxliteError::NotFound(format!(
+                "image not found in local cache: {}",
+                image_ref
+            )));
+        };
+        let in_use_by = users.get(&removed.manifest_digest).copied().unwrap_or(0);
+
+        if !force {
+            let shared = cached.iter().any(|(r, image)| {
+                *r != reference && image.manifest_digest == removed.manifest_digest
             });
+            if in_use_by > 0 && !shared {
+                return Err(BoxliteError::InvalidState(format!(
+                    "image {} is used by {} box(es); remove them first or use force",
+                    reference, in_use_by
+                )));
+            }
+            if removed.pinned {
+                return Err(BoxliteError::InvalidState(format!(
+                    "image {} is pinned; unpin it first or use force",
+                    reference
+                )));
+            }
         }
 
-        Ok(images)
+        if let Some((cached, freed)) = self.store.remove(&reference, in_use_by).await? {
+            tracing::info!(
+                image = %reference,
+                id = %cached.manifest_digest,
+                freed_bytes = freed,
+                "Removed image"
+            );
+        }
+        Ok(reference)
+    }
+
+    /// Pin a cached image so it is protected from pruning.
+    ///
+    /// The image must already be in the local cache (pull or prewarm it first).
+    pub async fn pin(&self, image_ref: &str) -> BoxliteResult<()> {
+        self.set_pinned(image_ref, true).await
+    }
+
+    /// Remove the pin from a cached image, making it eligible for pruning again.
+    pub async fn unpin(&self, image_ref: &str) -> BoxliteResult<()> {
+        self.set_pinned(image_ref, false).await
+    }
+
+    async fn set_pinned(&self, image_ref: &str, pinned: bool) -> BoxliteResult<()> {
+        match self.store.set_pinned(image_ref, pinned).await? {
+            Some(resolved) => {
+                tracing::info!(image = %resolved, pinned, "Updated image pin");
+                Ok(())
+            }
+            None => Err(BoxliteError::NotFound(format!(
+                "image not found in local cache: {}",
+                image_ref
+            ))),
+        }
     }
 
     /// Load an OCI/Docker image from a local directory.
@@ -189,3 +448,39 @@ impl ImageManager {
         Ok(ImageObject::new(reference, manifest, blob_source))
     }
 }
+
+/// Listing entry of a cached image.
+fn image_info(storage: &ImageStorage, reference: String, cached: CachedImage) -> ImageInfo {
+    // If parsing fails, default to UNIX_EPOCH to signal error
+    let cached_at = DateTime::parse_from_rfc3339(&cached.cached_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|e| {
+            tracing::warn!("Invalid cached_at timestamp: {}, using epoch", e);
+            DateTime::<Utc>::from(std::time::SystemTime::UNIX_EPOCH)
+        });
+
+    let (repository, tag) = match Reference::from_str(&reference) {
+        Ok(r) => (
+            r.repository().to_string(),
+            r.tag().unwrap_or("latest").to_string(),
+        ),
+        Err(_) => {
+            // Fallback if reference stored in DB is invalid
+            (reference.clone(), "<none>".to_string())
+        }
+    };
+
+    let sizes = storage.image_sizes(&cached.layers);
+    ImageInfo {
+        reference,
+        repository,
+        tag,
+        id: cached.manifest_digest,
+        cached_at,
+        created: storage.image_created(&cached.config_digest),
+        pinned: cached.pinned,
+        size: Some(Bytes::from_bytes(sizes.compressed)),
+        disk_size: Some(Bytes::from_bytes(sizes.on_disk)),
+        in_use_by: 0,
+    }
+}
@@ -1,13 +1,25 @@
 mod archive;
+mod blob_cache;
 mod blob_source;
+mod builtin;
+mod client;
 mod config;
+mod diff;
 mod manager;
 mod object;
+mod retry;
 mod storage;
 mod store;
+mod verify;
 
 pub use archive::extract_layer_tarball_streaming;
+pub(crate) use archive::{
+    CONTAINERS_OVERRIDE_XATTR, OverrideFileType, OverrideStat, unshadow_xattr_name,
+};
+pub(crate) use builtin::is_available as builtin_available;
 pub use config::ContainerImageConfig;
+pub use diff::ImageDiff;
+pub(crate) use diff::{FileMeta, FileTree, diff_trees, image_tree};
 pub use manager::ImageManager;
 pub use object::ImageObject;
 
@@ -114,6 +126,15 @@ impl Iterator for ReferenceIter<'_> {
         let registry = &self.registries[self.index];
         self.index += 1;
 
+        // Keep digest pins: the digest identifies the content, not the tag
+        if let Some(digest) = self.base_ref.digest() {
+            return Some(Reference::with_digest(
+                registry.clone(),
+                self.base_ref.repository().to_string(),
+                digest.to_string(),
+            ));
+        }
+
         let tag = self.base_ref.tag().unwrap_or("latest").to_string();
         Some(Reference::with_tag(
             registry.clone(),
@@ -199,6 +220,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_digest_preserved_across_registries() {
+        let digest = "sha256:9a1c4d2a2d5c6bb0d9a3c8f2c1e6a3b4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0";
+        let registries = vec!["ghcr.io".to_string(), "quay.io".to_string()];
+        let iter = ReferenceIter::new(&format!("alpine@{}", digest), &registries).unwrap();
+        let refs: Vec<Reference> = iter.collect();
+
+        assert_eq!(refs.len(), 2);
+        for r in &refs {
+            assert_eq!(r.digest(), Some(digest));
+        }
+        assert_eq!(refs[0].registry(), "ghcr.io");
+        assert_eq!(refs[1].registry(), "quay.io");
+    }
+
     #[test]
     fn test_qualified_bypasses_registries() {
         let registries = vec!["ghcr.io".to_string()];
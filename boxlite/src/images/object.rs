@@ -8,6 +8,7 @@ use std::path::PathBuf;
 
 use super::blob_source::BlobSource;
 use super::manager::ImageManifest;
+use super::storage::image_digest_for_layers;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 // ============================================================================
@@ -170,13 +171,13 @@ impl ImageObject {
     /// This is used as a cache key for base disks - same layers = same base disk.
     /// Uses SHA256 hash of concatenated layer digests.
     pub(crate) fn compute_image_digest(&self) -> String {
-        use sha2::{Digest, Sha256};
-
-        let mut hasher = Sha256::new();
-        for layer in &self.manifest.layers {
-            hasher.update(layer.digest.as_bytes());
-        }
-        format!("sha256:{:x}", hasher.finalize())
+        let digests: Vec<String> = self
+            .manifest
+            .layers
+            .iter()
+            .map(|l| l.digest.clone())
+            .collect();
+        image_digest_for_layers(&digests)
     }
 
     /// Get existing disk image if available.
@@ -202,6 +203,25 @@ impl ImageObject {
             .await
     }
 
+    /// Get the cached erofs image for one layer, if it has been built.
+    pub fn layer_erofs_image(&self, layer_digest: &str) -> Option<crate::disk::Disk> {
+        self.blob_source.layer_erofs_image(layer_digest)
+    }
+
+    /// Install an erofs image as the cached image for one layer.
+    ///
+    /// Layer images are keyed by layer digest, so images sharing a layer
+    /// share its erofs image.
+    pub async fn install_layer_erofs_image(
+        &self,
+        layer_digest: &str,
+        disk: crate::disk::Disk,
+    ) -> boxlite_shared::BoxliteResult<crate::disk::Disk> {
+        self.blob_source
+            .install_disk_image(layer_digest, disk)
+            .await
+    }
+
     // ========================================================================
     // INSPECTION
     // ========================================================================
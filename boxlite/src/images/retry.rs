@@ -0,0 +1,212 @@
+//! Retry with exponential backoff for registry requests.
+//!
+//! Only transient failures are retried: 5xx/408/429 responses, timeouts,
+//! refused or reset connections. Authentication failures, missing
+//! manifests and other 4xx responses fail immediately.
+
+use crate::runtime::options::RegistryRetryOptions;
+use oci_client::errors::OciDistributionError;
+use rand::Rng;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Retry policy shared by all registry requests of an `ImageStore`.
+#[derive(Clone, Debug)]
+pub(crate) struct RegistryRetry {
+    options: RegistryRetryOptions,
+    /// Retries performed (runtime metric `registry_retries_total`)
+    retries: Arc<AtomicU64>,
+}
+
+impl Default for RegistryRetry {
+    fn default() -> Self {
+        Self::new(RegistryRetryOptions::default(), Arc::default())
+    }
+}
+
+impl RegistryRetry {
+    pub(crate) fn new(options: RegistryRetryOptions, retries: Arc<AtomicU64>) -> Self {
+        Self { options, retries }
+    }
+
+    /// Attempts per request, including the first (at least 1).
+    pub(crate) fn max_attempts(&self) -> u32 {
+        self.options.max_attempts.max(1)
+    }
+
+    /// Delay before retry number `retry` (1-based), with jitter.
+    ///
+    /// The base delay doubles every retry up to `max_backoff_ms`; the actual
+    /// delay is picked uniformly from the upper half of it so that clients
+    /// failing together don't retry in lockstep.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let base = self.base_backoff_ms(retry);
+        let jittered = rand::rng().random_range(base / 2..=base);
+        Duration::from_millis(jittered)
+    }
+
+    fn base_backoff_ms(&self, retry: u32) -> u64 {
+        let exponent = retry.saturating_sub(1).min(20);
+        self.options
+            .initial_backoff_ms
+            .saturating_mul(1 << exponent)
+            .min(self.options.max_backoff_ms)
+    }
+
+    /// Count a retry and wait before it.
+    pub(crate) async fn wait_before_retry(&self, retry: u32, what: &str) {
+        let delay = self.backoff(retry);
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        tracing::info!(
+            "Retrying {} in {:?} (attempt {}/{})",
+            what,
+            delay,
+            retry + 1,
+            self.max_attempts()
+        );
+        tokio::time::sleep(delay).await;
+    }
+
+    /// Run a registry request, retrying transient failures.
+    pub(crate) async fn run<T, F, Fut>(
+        &self,
+        what: &str,
+        mut op: F,
+    ) -> Result<T, OciDistributionError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, OciDistributionError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_attempts() && is_retriable(&e) => {
+                    tracing::warn!("{} failed (attempt {}): {}", what, attempt, e);
+                    self.wait_before_retry(attempt, what).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Whether a registry error is worth retrying.
+pub(crate) fn is_retriable(err: &OciDistributionError) -> bool {
+    match err {
+        OciDistributionError::ServerError { code, .. } => is_retriable_status(*code),
+        OciDistributionError::RequestError(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.is_body()
+                || e.status().is_some_and(|s| is_retriable_status(s.as_u16()))
+        }
+        OciDistributionError::IoError(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::ConnectionRefused
+                | std::io::ErrorKind::TimedOut
+                | std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::Interrupted
+        ),
+        _ => false,
+    }
+}
+
+/// 5xx, 408 Request Timeout and 429 Too Many Requests are transient.
+fn is_retriable_status(code: u16) -> bool {
+    code >= 500 || code == 408 || code == 429
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry(max_attempts: u32) -> RegistryRetry {
+        RegistryRetry::new(
+            RegistryRetryOptions {
+                max_attempts,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 4,
+            },
+            Arc::default(),
+        )
+    }
+
+    fn server_error(code: u16) -> OciDistributionError {
+        OciDistributionError::ServerError {
+            code,
+            url: "https://registry.example/v2/".to_string(),
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_retriable_status_codes() {
+        assert!(is_retriable(&server_error(500)));
+        assert!(is_retriable(&server_error(503)));
+        assert!(is_retriable(&server_error(429)));
+        assert!(!is_retriable(&server_error(401)));
+        assert!(!is_retriable(&server_error(404)));
+        assert!(is_retriable(&OciDistributionError::IoError(
+            std::io::ErrorKind::ConnectionReset.into()
+        )));
+        assert!(!is_retriable(&OciDistributionError::IoError(
+            std::io::ErrorKind::PermissionDenied.into()
+        )));
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let retry = retry(5);
+        assert_eq!(retry.base_backoff_ms(1), 1);
+        assert_eq!(retry.base_backoff_ms(2), 2);
+        assert_eq!(retry.base_backoff_ms(3), 4);
+        assert_eq!(retry.base_backoff_ms(10), 4);
+
+        let delay = retry.backoff(3);
+        assert!(delay >= Duration::from_millis(2) && delay <= Duration::from_millis(4));
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_transient_errors() {
+        let retry = retry(3);
+        let mut calls = 0;
+        let result = retry
+            .run("manifest fetch", || {
+                calls += 1;
+                let fail = calls < 3;
+                async move {
+                    if fail {
+                        Err(server_error(502))
+                    } else {
+                        Ok(calls)
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(retry.retries.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_fails_fast_on_fatal_errors() {
+        let retry = retry(3);
+        let mut calls = 0;
+        let result: Result<(), _> = retry
+            .run("manifest fetch", || {
+                calls += 1;
+                async { Err(server_error(404)) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(retry.retries.load(Ordering::Relaxed), 0);
+    }
+}
@@ -13,6 +13,7 @@ use std::path::{Path, PathBuf};
 
 use oci_client::manifest::OciManifest;
 
+use crate::db::CachedImage;
 use crate::images::archive;
 use crate::runtime::layout::ImageFilesystemLayout;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
@@ -229,7 +230,11 @@ impl ImageStorage {
         })?;
 
         // Extract tarball to temp directory - keep .wh.* files!
-        if let Err(e) = archive::extract_layer_tarball_streaming(tarball_path, &temp_path) {
+        if let Err(e) = archive::extract_layer_tarball(
+            tarball_path,
+            &temp_path,
+            archive::WhiteoutMode::Preserve,
+        ) {
             // Clean up temp dir on extraction failure
             let _ = std::fs::remove_dir_all(&temp_path);
             return Err(e);
@@ -320,7 +325,6 @@ impl ImageStorage {
     /// Load config blob from disk.
     ///
     /// **Mutability**: Immutable - reads file only, no state changes.
-    #[allow(dead_code)]
     pub fn load_config(&self, digest: &str) -> BoxliteResult<String> {
         let config_path = self.config_path(digest);
 
@@ -479,6 +483,181 @@ impl ImageStorage {
 
         None
     }
+
+    // ========================================================================
+    // IMAGE USAGE [immutable]
+    // ========================================================================
+
+    /// Disk usage of an image's layers.
+    ///
+    /// `compressed` counts the layer tarballs; `on_disk` adds extracted
+    /// layers and the disk images built from them. Layers shared with other
+    /// images are counted for each image.
+    pub fn image_sizes(&self, layers: &[String]) -> ImageSizes {
+        use crate::disk::DiskFormat;
+
+        let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+
+        let compressed: u64 = layers
+            .iter()
+            .map(|digest| file_size(&self.layer_tarball_path(digest)))
+            .sum();
+
+        let mut on_disk = compressed;
+        for digest in layers {
+            on_disk += dir_size(&self.layer_extracted_path(digest));
+            on_disk += file_size(&self.disk_image_path(digest, DiskFormat::Erofs));
+        }
+        if let Some((path, _)) = self.find_disk_image(&image_digest_for_layers(layers)) {
+            on_disk += file_size(&path);
+        }
+
+        ImageSizes {
+            compressed,
+            on_disk,
+        }
+    }
+
+    /// Build time recorded in an image's config blob (`created`), if any.
+    pub fn image_created(&self, config_digest: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        let config = self.load_config(config_digest).ok()?;
+        let config: serde_json::Value = serde_json::from_str(&config).ok()?;
+        let created = config.get("created")?.as_str()?;
+        chrono::DateTime::parse_from_rfc3339(created)
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+    }
+}
+
+impl ImageStorage {
+    // ========================================================================
+    // IMAGE REMOVAL [mutating]
+    // ========================================================================
+
+    /// Move aside the files of `removed` that no image in `remaining` uses,
+    /// for [`remove_detached`](Self::remove_detached) to delete.
+    ///
+    /// Covers the manifest, config, layer tarballs, extracted layers, EROFS
+    /// layer images and the flattened disk image. Each is renamed next to
+    /// where it was, which is quick, so the caller can hold the store lock
+    /// while a pull of the same content would otherwise race the deletion.
+    /// Failures are logged. Returns the moved paths.
+    pub fn detach_unreferenced(
+        &self,
+        removed: &CachedImage,
+        remaining: &[CachedImage],
+    ) -> Vec<PathBuf> {
+        use crate::disk::DiskFormat;
+        use std::collections::HashSet;
+
+        let used_layers: HashSet<&str> = remaining
+            .iter()
+            .flat_map(|img| img.layers.iter().map(String::as_str))
+            .collect();
+
+        let mut paths = Vec::new();
+        if !remaining
+            .iter()
+            .any(|img| img.manifest_digest == removed.manifest_digest)
+        {
+            paths.push(self.manifest_path(&removed.manifest_digest));
+        }
+        if !remaining
+            .iter()
+            .any(|img| img.config_digest == removed.config_digest)
+        {
+            paths.push(self.config_path(&removed.config_digest));
+        }
+        for digest in &removed.layers {
+            if !used_layers.contains(digest.as_str()) {
+                paths.push(self.layer_tarball_path(digest));
+                paths.push(self.layer_extracted_path(digest));
+                paths.push(self.disk_image_path(digest, DiskFormat::Erofs));
+            }
+        }
+        let image_digest = image_digest_for_layers(&removed.layers);
+        if !remaining
+            .iter()
+            .any(|img| image_digest_for_layers(&img.layers) == image_digest)
+        {
+            paths.push(self.disk_image_path(&image_digest, DiskFormat::Ext4));
+            paths.push(self.disk_image_path(&image_digest, DiskFormat::Qcow2));
+        }
+
+        paths.iter().filter_map(|path| detach_path(path)).collect()
+    }
+
+    /// Delete files moved aside by
+    /// [`detach_unreferenced`](Self::detach_unreferenced). Deletion is best
+    /// effort; failures are logged. Returns the number of bytes freed.
+    pub fn remove_detached(paths: &[PathBuf]) -> u64 {
+        paths.iter().map(|path| remove_path(path)).sum()
+    }
+}
+
+/// Rename `path` to a unique sibling, or `None` if there is nothing there.
+fn detach_path(path: &Path) -> Option<PathBuf> {
+    std::fs::symlink_metadata(path).ok()?;
+    let mut name = path.file_name()?.to_os_string();
+    name.push(format!(".{}.removing", uuid::Uuid::new_v4().simple()));
+    let detached = path.with_file_name(name);
+    match std::fs::rename(path, &detached) {
+        Ok(()) => Some(detached),
+        Err(e) => {
+            tracing::warn!("Failed to remove {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Remove a file or directory tree, returning the bytes freed.
+fn remove_path(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    let (size, result) = if metadata.is_dir() {
+        (dir_size(path), std::fs::remove_dir_all(path))
+    } else {
+        (metadata.len(), std::fs::remove_file(path))
+    };
+    match result {
+        Ok(()) => size,
+        Err(e) => {
+            tracing::warn!("Failed to remove {}: {}", path.display(), e);
+            0
+        }
+    }
+}
+
+/// Layer and disk usage of one image, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageSizes {
+    pub compressed: u64,
+    pub on_disk: u64,
+}
+
+/// Cache key of the flattened disk image built from `layers`.
+///
+/// Same layers = same base disk: SHA256 of the concatenated layer digests.
+pub(crate) fn image_digest_for_layers(layers: &[String]) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for digest in layers {
+        hasher.update(digest.as_bytes());
+    }
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+/// Total size of regular files under `dir` (0 if it doesn't exist).
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|m| m.len())
+        .sum()
 }
 
 // ============================================================================
@@ -536,7 +715,6 @@ impl StagedDownload {
         &self.staged_path
     }
 
-    #[allow(unused)]
     pub fn final_path(&self) -> &Path {
         &self.final_path
     }
@@ -657,6 +835,66 @@ mod tests {
         assert_eq!(path, temp_dir.path().join("configs/sha256-config1.json"));
     }
 
+    #[test]
+    fn test_image_sizes_and_created() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ImageStorage::new(temp_dir.path().to_path_buf()).unwrap();
+        let layers = vec!["sha256:layer1".to_string(), "sha256:layer2".to_string()];
+
+        std::fs::write(store.layer_tarball_path(&layers[0]), vec![0u8; 100]).unwrap();
+        std::fs::write(store.layer_tarball_path(&layers[1]), vec![0u8; 50]).unwrap();
+        let extracted = store.layer_extracted_path(&layers[0]);
+        std::fs::create_dir_all(extracted.join("bin")).unwrap();
+        std::fs::write(extracted.join("bin/sh"), vec![0u8; 30]).unwrap();
+
+        let sizes = store.image_sizes(&layers);
+        assert_eq!(sizes.compressed, 150);
+        assert_eq!(sizes.on_disk, 180);
+
+        assert_eq!(store.image_created("sha256:config1"), None);
+        std::fs::write(
+            store.config_path("sha256:config1"),
+            r#"{"created":"2025-01-02T03:04:05Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            store.image_created("sha256:config1").unwrap().to_rfc3339(),
+            "2025-01-02T03:04:05+00:00"
+        );
+    }
+
+    #[test]
+    fn test_detach_unreferenced_keeps_shared_layers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ImageStorage::new(temp_dir.path().to_path_buf()).unwrap();
+        let image = |manifest: &str, layers: &[&str]| CachedImage {
+            manifest_digest: manifest.to_string(),
+            config_digest: format!("{}-config", manifest),
+            layers: layers.iter().map(|l| l.to_string()).collect(),
+            cached_at: String::new(),
+            complete: true,
+            pinned: false,
+        };
+        let removed = image("sha256:m1", &["sha256:base", "sha256:app"]);
+        let remaining = image("sha256:m2", &["sha256:base"]);
+
+        for layer in ["sha256:base", "sha256:app"] {
+            std::fs::write(store.layer_tarball_path(layer), vec![0u8; 10]).unwrap();
+        }
+        std::fs::write(store.manifest_path("sha256:m1"), "{}").unwrap();
+
+        let detached = store.detach_unreferenced(&removed, std::slice::from_ref(&remaining));
+        assert_eq!(detached.len(), 2);
+        assert!(store.has_layer("sha256:base"));
+        assert!(!store.has_layer("sha256:app"));
+        assert!(!store.has_manifest("sha256:m1"));
+        assert!(detached.iter().all(|path| path.exists()));
+
+        let freed = ImageStorage::remove_detached(&detached);
+        assert_eq!(freed, 12);
+        assert!(detached.iter().all(|path| !path.exists()));
+    }
+
     #[test]
     fn test_has_manifest() {
         let temp_dir = tempfile::tempdir().unwrap();
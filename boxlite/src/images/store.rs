@@ -14,17 +14,30 @@
 //! - `layer_extracted()` - Get extracted layer path (extracts if needed)
 
 use crate::db::{CachedImage, Database, ImageIndexStore};
+use crate::images::archive::{LayerCompression, is_foreign_layer};
+use crate::images::blob_cache::BlobCache;
+use crate::images::client;
 use crate::images::manager::{ImageManifest, LayerInfo};
-use crate::images::storage::ImageStorage;
+use crate::images::retry::{self, RegistryRetry};
+use crate::images::storage::{ImageStorage, StagedDownload};
+use crate::images::verify::{self, ImageVerifier};
+use crate::runtime::options::{ImagePullPolicy, ImageVerificationOptions};
+use crate::runtime::progress::{ProgressEvent, ProgressObservers};
 use boxlite_shared::{BoxliteError, BoxliteResult};
 use oci_client::Reference;
+use oci_client::errors::OciDistributionError;
 use oci_client::manifest::{
-    ImageIndexEntry, OciDescriptor, OciImageIndex, OciImageManifest as ClientOciImageManifest,
+    IMAGE_CONFIG_MEDIA_TYPE, IMAGE_LAYER_GZIP_MEDIA_TYPE, ImageIndexEntry, OCI_IMAGE_MEDIA_TYPE,
+    OciDescriptor, OciImageIndex, OciImageManifest as ClientOciImageManifest, OciManifest,
 };
 use oci_client::secrets::RegistryAuth;
 use oci_spec::image::MediaType;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::AsyncWrite;
 use tokio::sync::RwLock;
 
 // ============================================================================
@@ -69,7 +82,7 @@ impl ImageStoreInner {
 /// let store = Arc::new(ImageStore::new(images_dir)?);
 ///
 /// // Pull image (thread-safe, releases lock during download)
-/// let manifest = store.pull("python:alpine").await?;
+/// let manifest = store.pull("python:alpine", ImagePullPolicy::IfNotPresent).await?;
 ///
 /// // Create BlobSource for accessing layers
 /// let storage = store.storage().await;
@@ -83,6 +96,16 @@ pub struct ImageStore {
     /// Registries to search for unqualified image references.
     /// Tried in order; first successful pull wins.
     registries: Vec<String>,
+    /// Signature verification policy applied to registry pulls.
+    verifier: ImageVerifier,
+    /// Retry policy for transient registry failures.
+    retry: RegistryRetry,
+    /// Mirrors per registry host, tried in order when the registry fails.
+    mirrors: HashMap<String, Vec<String>>,
+    /// Pull-through blob cache shared with other runtime homes.
+    blob_cache: Option<BlobCache>,
+    /// Observers of registry pulls.
+    progress: ProgressObservers,
 }
 
 impl std::fmt::Debug for ImageStore {
@@ -98,15 +121,57 @@ impl ImageStore {
     /// * `images_dir` - Directory for image cache
     /// * `db` - Database for image index
     /// * `registries` - Registries to search for unqualified images (tried in order)
-    pub fn new(images_dir: PathBuf, db: Database, registries: Vec<String>) -> BoxliteResult<Self> {
+    /// * `verification` - Signature verification policy
+    pub fn new(
+        images_dir: PathBuf,
+        db: Database,
+        registries: Vec<String>,
+        verification: &ImageVerificationOptions,
+    ) -> BoxliteResult<Self> {
+        let verifier = ImageVerifier::new(verification)?;
         let inner = ImageStoreInner::new(images_dir, db)?;
         Ok(Self {
             client: oci_client::Client::new(Default::default()),
             inner: RwLock::new(inner),
             registries,
+            verifier,
+            retry: RegistryRetry::default(),
+            mirrors: HashMap::new(),
+            blob_cache: None,
+            progress: ProgressObservers::default(),
         })
     }
 
+    /// Fail over to `mirrors` (keyed by registry host) when a registry fails.
+    pub(crate) fn with_mirrors(mut self, mirrors: HashMap<String, Vec<String>>) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    /// Share downloaded blobs through `blob_cache`.
+    pub(crate) fn with_blob_cache(mut self, blob_cache: BlobCache) -> Self {
+        self.blob_cache = Some(blob_cache);
+        self
+    }
+
+    /// Report registry pulls to `progress`.
+    pub(crate) fn with_progress(mut self, progress: ProgressObservers) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Use `client` for registry requests (proxy and TLS settings).
+    pub(crate) fn with_client(mut self, client: oci_client::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Use `retry` for registry requests instead of the default policy.
+    pub(crate) fn with_retry(mut self, retry: RegistryRetry) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Get shared reference to image storage for BlobSource creation.
     ///
     /// This allows creating `StoreBlobSource` that can outlive the lock.
@@ -142,17 +207,41 @@ impl ImageStore {
     /// 3. If not cached, downloads from registry (releases lock during I/O)
     /// 4. Tries each registry candidate in order until one succeeds
     ///
+    /// `policy` controls whether the cache and/or the registry are consulted.
+    /// Digest-pinned references are always served from cache when present.
+    ///
+    /// When signature verification is enabled the manifest is always resolved
+    /// from the registry (layer blobs are still reused from cache), so the
+    /// `Never` policy is rejected.
+    ///
     /// Thread-safe: Multiple concurrent pulls of the same image will only
     /// download once; others will get the cached result.
-    pub async fn pull(&self, image_ref: &str) -> BoxliteResult<ImageManifest> {
+    pub async fn pull(
+        &self,
+        image_ref: &str,
+        policy: ImagePullPolicy,
+    ) -> BoxliteResult<ImageManifest> {
         use super::ReferenceIter;
 
         tracing::debug!(
             image_ref = %image_ref,
             registries = ?self.registries,
+            ?policy,
             "Starting image pull with registry fallback"
         );
 
+        if self.verifier.is_enabled() && policy == ImagePullPolicy::Never {
+            return Err(BoxliteError::Config(format!(
+                "cannot use image '{}' with pull policy 'never': signature verification requires registry access",
+                image_ref
+            )));
+        }
+
+        // Digest references are immutable, so a cached copy is never stale.
+        // Signatures can be revoked or rotated, so verification bypasses the cache.
+        let use_cache = !self.verifier.is_enabled()
+            && (policy != ImagePullPolicy::Always || image_ref.contains('@'));
+
         // Parse image reference and create iterator over registry candidates
         let candidates = ReferenceIter::new(image_ref, &self.registries)
             .map_err(|e| BoxliteError::Storage(format!("invalid image reference: {e}")))?;
@@ -163,7 +252,7 @@ impl ImageStore {
             let ref_str = reference.whole();
 
             // Fast path: check cache with read lock
-            {
+            if use_cache {
                 let inner = self.inner.read().await;
                 if let Some(manifest) = self.try_load_cached(&inner, &ref_str)? {
                     tracing::info!("Using cached image: {}", ref_str);
@@ -171,9 +260,13 @@ impl ImageStore {
                 }
             } // Read lock released
 
+            if policy == ImagePullPolicy::Never {
+                continue;
+            }
+
             // Slow path: pull from registry
             tracing::info!("Pulling image from registry: {}", ref_str);
-            match self.pull_from_registry(&reference).await {
+            match self.pull_with_mirrors(&reference).await {
                 Ok(manifest) => {
                     if !errors.is_empty() {
                         tracing::info!(
@@ -196,6 +289,13 @@ impl ImageStore {
             }
         }
 
+        if policy == ImagePullPolicy::Never {
+            return Err(BoxliteError::NotFound(format!(
+                "image '{}' is not present locally and pull policy is 'never'",
+                image_ref
+            )));
+        }
+
         // All candidates failed - format comprehensive error message
         if errors.is_empty() {
             Err(BoxliteError::Storage(format!(
@@ -230,6 +330,130 @@ impl ImageStore {
         inner.index.list_all()
     }
 
+    /// Pin or unpin a cached image.
+    ///
+    /// Resolves `image_ref` against the configured registries the same way
+    /// `pull()` does, and updates the first cached candidate.
+    ///
+    /// Returns the resolved reference, or `None` if the image is not cached.
+    pub async fn set_pinned(&self, image_ref: &str, pinned: bool) -> BoxliteResult<Option<String>> {
+        use super::ReferenceIter;
+
+        let candidates = ReferenceIter::new(image_ref, &self.registries)
+            .map_err(|e| BoxliteError::Storage(format!("invalid image reference: {e}")))?;
+
+        let inner = self.inner.read().await;
+        for reference in candidates {
+            let ref_str = reference.whole();
+            if inner.index.set_pinned(&ref_str, pinned)? {
+                return Ok(Some(ref_str));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Resolve `image_ref` to the reference it is cached under.
+    ///
+    /// Uses the same candidate order as `pull()`. Returns `None` if no
+    /// candidate is cached or the reference is invalid.
+    pub async fn resolve_cached(&self, image_ref: &str) -> BoxliteResult<Option<String>> {
+        use super::ReferenceIter;
+
+        let Ok(candidates) = ReferenceIter::new(image_ref, &self.registries) else {
+            return Ok(None);
+        };
+
+        let inner = self.inner.read().await;
+        for reference in candidates {
+            let ref_str = reference.whole();
+            if inner.index.get(&ref_str)?.is_some() {
+                return Ok(Some(ref_str));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The index entry of a cached reference, as returned by `resolve_cached()`.
+    pub async fn get(&self, reference: &str) -> BoxliteResult<Option<CachedImage>> {
+        let inner = self.inner.read().await;
+        inner.index.get(reference)
+    }
+
+    /// Add `target` as another reference to the cached image `source`.
+    ///
+    /// Unqualified targets are recorded under the first configured registry,
+    /// so they resolve the same way as pulled images. Returns the resolved
+    /// target reference, or `None` if `source` is not cached.
+    pub async fn tag(&self, source: &str, target: &str) -> BoxliteResult<Option<String>> {
+        use super::ReferenceIter;
+
+        let target_ref = ReferenceIter::new(target, &self.registries)
+            .map_err(|e| BoxliteError::InvalidArgument(format!("invalid image reference: {e}")))?
+            .next()
+            .map(|r| r.whole())
+            .ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!("invalid image reference: {target}"))
+            })?;
+
+        let Some(source_ref) = self.resolve_cached(source).await? else {
+            return Ok(None);
+        };
+        if source_ref == target_ref {
+            return Ok(Some(target_ref));
+        }
+
+        let inner = self.inner.write().await;
+        let Some(mut cached) = inner.index.get(&source_ref)? else {
+            return Ok(None);
+        };
+        // Pins belong to references, not content
+        cached.pinned = false;
+        cached.cached_at = chrono::Utc::now().to_rfc3339();
+        inner.index.upsert(&target_ref, &cached)?;
+
+        Ok(Some(target_ref))
+    }
+
+    /// Remove the cached image `reference` from the index.
+    ///
+    /// `in_use_by` is the number of boxes using the image's content. Unless
+    /// boxes use it, files no other cached image uses are deleted as well,
+    /// after the lock is released. Returns the removed entry and the bytes
+    /// freed, or `None` if the reference is not cached.
+    pub async fn remove(
+        &self,
+        reference: &str,
+        in_use_by: usize,
+    ) -> BoxliteResult<Option<(CachedImage, u64)>> {
+        let (cached, detached) = {
+            let inner = self.inner.write().await;
+            let Some(cached) = inner.index.get(reference)? else {
+                return Ok(None);
+            };
+            inner.index.remove(reference)?;
+
+            let detached = if in_use_by == 0 {
+                let remaining: Vec<CachedImage> = inner
+                    .index
+                    .list_all()?
+                    .into_iter()
+                    .map(|(_, img)| img)
+                    .collect();
+                inner.storage.detach_unreferenced(&cached, &remaining)
+            } else {
+                Vec::new()
+            };
+            (cached, detached)
+        };
+
+        let freed = tokio::task::spawn_blocking(move || ImageStorage::remove_detached(&detached))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?;
+        Ok(Some((cached, freed)))
+    }
+
     /// Load an OCI image from a local directory.
     ///
     /// Reads OCI layout files (index.json, manifest blob) using oci-spec types
@@ -313,6 +537,73 @@ impl ImageStore {
         })
     }
 
+    /// The cached image for `image_ref`, if it is complete in the store.
+    ///
+    /// Unlike `pull()` with `Never`, this skips registry resolution and
+    /// signature policy entirely.
+    pub(super) async fn cached(&self, image_ref: &str) -> BoxliteResult<Option<ImageManifest>> {
+        let inner = self.inner.read().await;
+        self.try_load_cached(&inner, image_ref)
+    }
+
+    /// Store an image assembled in-process and index it under `image_ref`.
+    ///
+    /// `layers` are gzip-compressed tarballs. Blobs are written like pulled
+    /// ones, so the image is listed, pinned and removed like any other.
+    pub(super) async fn import(
+        &self,
+        image_ref: &str,
+        config: &[u8],
+        layers: &[Vec<u8>],
+    ) -> BoxliteResult<ImageManifest> {
+        let storage = self.storage().await;
+        let mut descriptors = Vec::with_capacity(layers.len());
+        for layer in layers {
+            let digest = sha256_digest(layer);
+            if !storage.has_layer(&digest) {
+                commit_blob(storage.stage_layer_download(&digest).await?, layer).await?;
+            }
+            descriptors.push(OciDescriptor {
+                media_type: IMAGE_LAYER_GZIP_MEDIA_TYPE.to_string(),
+                digest,
+                size: layer.len() as i64,
+                ..Default::default()
+            });
+        }
+
+        let config_digest = sha256_digest(config);
+        if !storage.has_config(&config_digest) {
+            commit_blob(storage.stage_config_download(&config_digest).await?, config).await?;
+        }
+
+        let manifest = ClientOciImageManifest {
+            media_type: Some(OCI_IMAGE_MEDIA_TYPE.to_string()),
+            config: OciDescriptor {
+                media_type: IMAGE_CONFIG_MEDIA_TYPE.to_string(),
+                digest: config_digest.clone(),
+                size: config.len() as i64,
+                ..Default::default()
+            },
+            layers: descriptors,
+            ..Default::default()
+        };
+        let manifest_digest = sha256_digest(&serde_json::to_vec(&manifest)?);
+        storage.save_manifest(&OciManifest::Image(manifest.clone()), &manifest_digest)?;
+
+        let image_manifest = ImageManifest {
+            manifest_digest,
+            layers: Self::layers_from_image(&manifest),
+            config_digest,
+        };
+        self.update_index(image_ref, &image_manifest).await?;
+        tracing::info!(
+            image = %image_ref,
+            manifest = %image_manifest.manifest_digest,
+            "Imported image into the store"
+        );
+        Ok(image_manifest)
+    }
+
     /// Get an ImageManifest digest from the descriptor.
     ///
     /// Handles at most two levels (like containerd):
@@ -495,17 +786,80 @@ impl ImageStore {
     // INTERNAL: Registry Operations (releases lock during I/O)
     // ========================================================================
 
+    /// Pull from the reference's registry, failing over to its mirrors.
+    ///
+    /// Mirrors are only tried when the registry itself fails with a storage
+    /// (network or registry) error. The image is cached under the original
+    /// reference whichever source served it.
+    async fn pull_with_mirrors(&self, reference: &Reference) -> BoxliteResult<ImageManifest> {
+        let cache_key = reference.whole();
+        let mirrors = self
+            .mirrors
+            .get(reference.registry())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let primary_error = match self.pull_from_registry(reference, &cache_key).await {
+            Err(BoxliteError::Storage(msg)) if !mirrors.is_empty() => msg,
+            result => return result,
+        };
+
+        let mut failures = vec![format!("{}: {}", reference.registry(), primary_error)];
+        for mirror in mirrors {
+            tracing::warn!(
+                registry = %reference.registry(),
+                mirror = %mirror,
+                "Registry failed, trying mirror"
+            );
+            let result = match mirror_reference(reference, mirror) {
+                Ok(mirror_ref) => self.pull_from_registry(&mirror_ref, &cache_key).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(manifest) => {
+                    tracing::info!("Pulled {} from mirror {}", cache_key, mirror);
+                    return Ok(manifest);
+                }
+                Err(e) => failures.push(format!("{}: {}", mirror, e)),
+            }
+        }
+
+        Err(BoxliteError::Storage(format!(
+            "registry and all mirrors failed:\n    {}",
+            failures.join("\n    ")
+        )))
+    }
+
     /// Pull image from registry using a typed Reference.
     ///
     /// This method handles the actual network I/O - manifest pull, layer download, etc.
     /// Lock is released during network I/O to allow other operations.
-    async fn pull_from_registry(&self, reference: &Reference) -> BoxliteResult<ImageManifest> {
+    /// The result is indexed under `cache_key`.
+    async fn pull_from_registry(
+        &self,
+        reference: &Reference,
+        cache_key: &str,
+    ) -> BoxliteResult<ImageManifest> {
         // Step 1: Pull manifest (no lock needed - uses self.client)
-        let (manifest, manifest_digest_str) = self
-            .client
-            .pull_manifest(reference, &RegistryAuth::Anonymous)
-            .await
-            .map_err(|e| BoxliteError::Storage(format!("failed to pull manifest: {e}")))?;
+        let (manifest, manifest_digest_str) = self.pull_manifest(reference).await.map_err(|e| {
+            BoxliteError::Storage(client::describe_error("failed to pull manifest", &e))
+        })?;
+
+        // Digest-pinned references must resolve to exactly that content
+        if let Some(expected) = reference.digest()
+            && expected != manifest_digest_str
+        {
+            return Err(BoxliteError::Image(format!(
+                "digest mismatch for {}: registry returned {}",
+                reference.whole(),
+                manifest_digest_str
+            )));
+        }
+
+        if self.verifier.is_enabled() {
+            let outcome = self.verify_signature(reference, &manifest_digest_str).await;
+            self.verifier.enforce(&reference.whole(), outcome)?;
+        }
 
         // Step 2: Save manifest (quick write lock)
         {
@@ -520,8 +874,13 @@ impl ImageStore {
             .extract_image_manifest(reference, &manifest, manifest_digest_str)
             .await?;
 
+        self.progress.emit(|| ProgressEvent::PullStarted {
+            image: cache_key.to_string(),
+            layers: image_manifest.layers.len(),
+        });
+
         // Step 4: Download layers (no lock during download, atomic file writes)
-        self.download_layers(reference, &image_manifest.layers)
+        self.download_layers(reference, cache_key, &image_manifest.layers)
             .await?;
 
         // Step 5: Download config (no lock during download)
@@ -529,12 +888,85 @@ impl ImageStore {
             .await?;
 
         // Step 6: Update index using reference.whole() as the cache key
-        self.update_index(&reference.whole(), &image_manifest)
-            .await?;
+        self.update_index(cache_key, &image_manifest).await?;
+
+        self.progress.emit(|| ProgressEvent::PullDone {
+            image: cache_key.to_string(),
+        });
 
         Ok(image_manifest)
     }
 
+    /// Fetch a manifest, retrying transient registry failures.
+    async fn pull_manifest(
+        &self,
+        reference: &Reference,
+    ) -> Result<(OciManifest, String), OciDistributionError> {
+        let auth = RegistryAuth::Anonymous;
+        self.retry
+            .run(&format!("manifest fetch for {}", reference.whole()), || {
+                self.client.pull_manifest(reference, &auth)
+            })
+            .await
+    }
+
+    /// Look up the cosign signature for `manifest_digest` and check it.
+    ///
+    /// Succeeds if any signature layer verifies against a trusted key.
+    async fn verify_signature(
+        &self,
+        reference: &Reference,
+        manifest_digest: &str,
+    ) -> BoxliteResult<()> {
+        let signature_ref = Reference::with_tag(
+            reference.registry().to_string(),
+            reference.repository().to_string(),
+            verify::signature_tag(manifest_digest)?,
+        );
+
+        let (signature_manifest, _) = self
+            .pull_manifest(&signature_ref)
+            .await
+            .map_err(|e| BoxliteError::Image(format!("no signature found: {e}")))?;
+
+        let oci_client::manifest::OciManifest::Image(signature_image) = signature_manifest else {
+            return Err(BoxliteError::Image(
+                "signature artifact is not an image manifest".into(),
+            ));
+        };
+
+        for layer in &signature_image.layers {
+            let Some(signature) = layer
+                .annotations
+                .as_ref()
+                .and_then(|a| a.get(verify::SIGNATURE_ANNOTATION))
+            else {
+                continue;
+            };
+
+            let mut payload = Vec::new();
+            if let Err(e) = self
+                .client
+                .pull_blob(&signature_ref, layer, &mut payload)
+                .await
+            {
+                tracing::debug!("Failed to fetch signature payload {}: {}", layer.digest, e);
+                continue;
+            }
+
+            if self
+                .verifier
+                .verify_payload(&payload, signature, manifest_digest)
+            {
+                return Ok(());
+            }
+        }
+
+        Err(BoxliteError::Image(
+            "no signature matches the trusted keys".into(),
+        ))
+    }
+
     /// Update index with newly pulled image.
     async fn update_index(&self, image_ref: &str, manifest: &ImageManifest) -> BoxliteResult<()> {
         let inner = self.inner.read().await;
@@ -545,6 +977,7 @@ impl ImageStore {
             layers: manifest.layers.iter().map(|l| l.digest.clone()).collect(),
             cached_at: chrono::Utc::now().to_rfc3339(),
             complete: true,
+            pinned: false,
         };
 
         inner.index.upsert(image_ref, &cached_image)?;
@@ -586,6 +1019,8 @@ impl ImageStore {
             .map(|layer| LayerInfo {
                 digest: layer.digest.clone(),
                 media_type: layer.media_type.clone(),
+                urls: layer.urls.clone(),
+                size: u64::try_from(layer.size).unwrap_or(0),
             })
             .collect()
     }
@@ -615,11 +1050,13 @@ impl ImageStore {
             "Pulling platform-specific manifest: {}",
             platform_manifest.digest
         );
-        let (platform_image, platform_digest) = self
-            .client
-            .pull_manifest(&platform_reference, &RegistryAuth::Anonymous)
-            .await
-            .map_err(|e| BoxliteError::Storage(format!("failed to pull platform manifest: {e}")))?;
+        let (platform_image, platform_digest) =
+            self.pull_manifest(&platform_reference).await.map_err(|e| {
+                BoxliteError::Storage(client::describe_error(
+                    "failed to pull platform manifest",
+                    &e,
+                ))
+            })?;
 
         // Save platform manifest (quick lock)
         {
@@ -645,7 +1082,7 @@ impl ImageStore {
         }
     }
 
-    fn detect_platform() -> (&'static str, &'static str) {
+    pub(super) fn detect_platform() -> (&'static str, &'static str) {
         let os = "linux";
         let arch = match std::env::consts::ARCH {
             "aarch64" => "arm64",
@@ -698,10 +1135,16 @@ impl ImageStore {
     async fn download_layers(
         &self,
         reference: &Reference,
+        image: &str,
         layers: &[LayerInfo],
     ) -> BoxliteResult<()> {
         use futures::future::join_all;
 
+        // Reject layer formats we can't extract before downloading anything
+        for layer in layers {
+            LayerCompression::from_media_type(&layer.media_type)?;
+        }
+
         // Check which layers need downloading (quick read lock)
         let layers_to_download: Vec<_> = {
             let inner = self.inner.read().await;
@@ -714,6 +1157,7 @@ impl ImageStore {
                     match inner.storage.verify_layer(&layer.digest).await {
                         Ok(true) => {
                             tracing::debug!("Layer tarball cached and verified: {}", layer.digest);
+                            self.report_layer_done(image, layer);
                         }
                         _ => {
                             tracing::warn!(
@@ -741,9 +1185,11 @@ impl ImageStore {
         );
 
         // Download in parallel (no lock held)
-        let download_futures = layers_to_download
-            .iter()
-            .map(|layer| self.download_layer(reference, layer));
+        let download_futures = layers_to_download.iter().map(|layer| async move {
+            self.download_layer(reference, image, layer).await?;
+            self.report_layer_done(image, layer);
+            Ok::<(), BoxliteError>(())
+        });
 
         let results = join_all(download_futures).await;
 
@@ -754,21 +1200,50 @@ impl ImageStore {
         Ok(())
     }
 
-    async fn download_layer(&self, reference: &Reference, layer: &LayerInfo) -> BoxliteResult<()> {
-        const MAX_RETRIES: u32 = 3;
+    fn report_layer_done(&self, image: &str, layer: &LayerInfo) {
+        self.progress.emit(|| ProgressEvent::LayerDone {
+            image: image.to_string(),
+            digest: layer.digest.clone(),
+        });
+    }
+
+    async fn download_layer(
+        &self,
+        reference: &Reference,
+        image: &str,
+        layer: &LayerInfo,
+    ) -> BoxliteResult<()> {
+        let max_attempts = self.retry.max_attempts();
 
         tracing::info!("Downloading layer: {}", layer.digest);
+        if is_foreign_layer(&layer.media_type) {
+            tracing::info!(
+                "Layer {} is non-distributable, fetching from {:?}",
+                layer.digest,
+                layer.urls
+            );
+        }
+
+        if let Some(ref cache) = self.blob_cache
+            && cache.contains(&layer.digest)
+        {
+            let staged = {
+                let inner = self.inner.read().await;
+                inner.storage.stage_layer_download(&layer.digest).await?
+            };
+            if cache.restore(&layer.digest, staged).await {
+                tracing::info!("Restored layer from blob cache: {}", layer.digest);
+                return Ok(());
+            }
+        }
 
         let mut last_error = None;
 
-        for attempt in 1..=MAX_RETRIES {
+        for attempt in 1..=max_attempts {
             if attempt > 1 {
-                tracing::info!(
-                    "Retrying layer download (attempt {}/{}): {}",
-                    attempt,
-                    MAX_RETRIES,
-                    layer.digest
-                );
+                self.retry
+                    .wait_before_retry(attempt - 1, &format!("layer download {}", layer.digest))
+                    .await;
             }
 
             // Stage download (quick read lock for path computation)
@@ -785,6 +1260,7 @@ impl ImageStore {
                     }
                 }
             };
+            let final_path = staged.final_path().to_path_buf();
 
             // Download (no lock)
             match self
@@ -795,16 +1271,25 @@ impl ImageStore {
                         digest: layer.digest.clone(),
                         media_type: layer.media_type.clone(),
                         size: 0,
-                        urls: None,
+                        urls: layer.urls.clone(),
                         annotations: None,
                     },
-                    staged.file(),
+                    ProgressWriter {
+                        inner: staged.file(),
+                        progress: &self.progress,
+                        image,
+                        layer,
+                        written: 0,
+                    },
                 )
                 .await
             {
                 Ok(_) => match staged.commit().await {
                     Ok(true) => {
                         tracing::info!("Downloaded and verified layer: {}", layer.digest);
+                        if let Some(ref cache) = self.blob_cache {
+                            cache.insert(&layer.digest, &final_path).await;
+                        }
                         return Ok(());
                     }
                     Ok(false) => {
@@ -823,8 +1308,14 @@ impl ImageStore {
                 },
                 Err(e) => {
                     tracing::warn!("Layer download failed (attempt {}): {}", attempt, e);
-                    last_error = Some(format!("failed to pull layer {}: {e}", layer.digest));
+                    last_error = Some(client::describe_error(
+                        &format!("failed to pull layer {}", layer.digest),
+                        &e,
+                    ));
                     staged.abort().await;
+                    if !retry::is_retriable(&e) {
+                        break;
+                    }
                 }
             }
         }
@@ -848,35 +1339,64 @@ impl ImageStore {
             }
         }
 
+        if let Some(ref cache) = self.blob_cache
+            && cache.contains(config_digest)
+        {
+            let staged = {
+                let inner = self.inner.read().await;
+                inner.storage.stage_config_download(config_digest).await?
+            };
+            if cache.restore(config_digest, staged).await {
+                tracing::debug!("Restored config blob from blob cache: {}", config_digest);
+                return Ok(());
+            }
+        }
+
         tracing::debug!("Downloading config blob: {}", config_digest);
 
-        // Start staged download (quick read lock)
-        let mut staged = {
-            let inner = self.inner.read().await;
-            inner.storage.stage_config_download(config_digest).await?
-        };
+        let what = format!("config download {}", config_digest);
+        let mut attempt = 1;
+        let mut staged = loop {
+            // Start staged download (quick read lock)
+            let mut staged = {
+                let inner = self.inner.read().await;
+                inner.storage.stage_config_download(config_digest).await?
+            };
 
-        // Download to temp file (no lock)
-        if let Err(e) = self
-            .client
-            .pull_blob(
-                reference,
-                &OciDescriptor {
-                    digest: config_digest.to_string(),
-                    media_type: "application/vnd.oci.image.config.v1+json".to_string(),
-                    size: 0,
-                    urls: None,
-                    annotations: None,
-                },
-                staged.file(),
-            )
-            .await
-        {
-            staged.abort().await;
-            return Err(BoxliteError::Storage(format!("failed to pull config: {e}")));
-        }
+            // Download to temp file (no lock)
+            match self
+                .client
+                .pull_blob(
+                    reference,
+                    &OciDescriptor {
+                        digest: config_digest.to_string(),
+                        media_type: "application/vnd.oci.image.config.v1+json".to_string(),
+                        size: 0,
+                        urls: None,
+                        annotations: None,
+                    },
+                    staged.file(),
+                )
+                .await
+            {
+                Ok(_) => break staged,
+                Err(e) => {
+                    staged.abort().await;
+                    if attempt >= self.retry.max_attempts() || !retry::is_retriable(&e) {
+                        return Err(BoxliteError::Storage(client::describe_error(
+                            "failed to pull config",
+                            &e,
+                        )));
+                    }
+                    tracing::warn!("Config download failed (attempt {}): {}", attempt, e);
+                    self.retry.wait_before_retry(attempt, &what).await;
+                    attempt += 1;
+                }
+            }
+        };
 
         // Verify and commit (atomic move to final location)
+        let final_path = staged.final_path().to_path_buf();
         if !staged.commit().await? {
             return Err(BoxliteError::Storage(format!(
                 "Config blob verification failed for {}",
@@ -884,6 +1404,10 @@ impl ImageStore {
             )));
         }
 
+        if let Some(ref cache) = self.blob_cache {
+            cache.insert(config_digest, &final_path).await;
+        }
+
         Ok(())
     }
 
@@ -911,19 +1435,102 @@ impl ImageStore {
 
         let config_digest_str = oci_manifest.config.digest.clone();
 
-        let layers: Vec<LayerInfo> = oci_manifest
-            .layers
-            .iter()
-            .map(|layer| LayerInfo {
-                digest: layer.digest.clone(),
-                media_type: layer.media_type.clone(),
-            })
-            .collect();
+        let layers = Self::layers_from_image(&oci_manifest);
 
         Ok((config_digest_str, layers))
     }
 }
 
+/// Writer that reports the bytes of a layer written so far.
+struct ProgressWriter<'a, W> {
+    inner: W,
+    progress: &'a ProgressObservers,
+    image: &'a str,
+    layer: &'a LayerInfo,
+    written: u64,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for ProgressWriter<'_, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.written += n as u64;
+            this.progress.emit(|| ProgressEvent::LayerProgress {
+                image: this.image.to_string(),
+                digest: this.layer.digest.clone(),
+                current: this.written,
+                total: this.layer.size,
+            });
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Point `reference` at `mirror` (a host, optionally with a path prefix).
+fn mirror_reference(reference: &Reference, mirror: &str) -> BoxliteResult<Reference> {
+    let mut image = format!(
+        "{}/{}",
+        mirror.trim_end_matches('/'),
+        reference.repository()
+    );
+    if let Some(tag) = reference.tag() {
+        image.push(':');
+        image.push_str(tag);
+    }
+    if let Some(digest) = reference.digest() {
+        image.push('@');
+        image.push_str(digest);
+    }
+    image
+        .parse()
+        .map_err(|e| BoxliteError::Config(format!("invalid registry mirror '{}': {}", mirror, e)))
+}
+
+/// `sha256:<hex>` digest of `bytes`.
+fn sha256_digest(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
+
+/// Write `bytes` to a staged blob and move it into place.
+async fn commit_blob(mut staged: StagedDownload, bytes: &[u8]) -> BoxliteResult<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let written = async {
+        staged.file().write_all(bytes).await?;
+        staged.file().flush().await
+    }
+    .await;
+    if let Err(e) = written {
+        staged.abort().await;
+        return Err(BoxliteError::Storage(format!(
+            "Failed to write blob: {}",
+            e
+        )));
+    }
+    let path = staged.final_path().to_path_buf();
+    if !staged.commit().await? {
+        return Err(BoxliteError::Storage(format!(
+            "Blob digest mismatch: {}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
 // ============================================================================
 // SHARED TYPE ALIAS
 // ============================================================================
@@ -1071,7 +1678,13 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store = ImageStore::new(
+            images_dir.clone(),
+            db,
+            vec![],
+            &ImageVerificationOptions::default(),
+        )
+        .unwrap();
 
         // Load from local
         let manifest = store.load_from_local(bundle_dir.clone()).await.unwrap();
@@ -1083,6 +1696,114 @@ mod tests {
         assert!(!manifest.manifest_digest.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_import_indexes_image() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let store = ImageStore::new(
+            temp_dir.path().join("images"),
+            db,
+            vec![],
+            &ImageVerificationOptions::default(),
+        )
+        .unwrap();
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &create_minimal_tarball()).unwrap();
+        let layer = encoder.finish().unwrap();
+        let config = br#"{"architecture":"amd64","os":"linux"}"#;
+
+        let manifest = store
+            .import("boxlite.local/test:1", config, std::slice::from_ref(&layer))
+            .await
+            .unwrap();
+        assert_eq!(manifest.layers.len(), 1);
+        assert_eq!(manifest.layers[0].digest, sha256_digest(&layer));
+        assert_eq!(manifest.config_digest, sha256_digest(config));
+
+        // Importing again reuses the stored blobs
+        let again = store
+            .import("boxlite.local/test:1", config, &[layer])
+            .await
+            .unwrap();
+        assert_eq!(again.manifest_digest, manifest.manifest_digest);
+
+        let cached = store.cached("boxlite.local/test:1").await.unwrap();
+        assert_eq!(cached.unwrap().manifest_digest, manifest.manifest_digest);
+        assert!(
+            store
+                .cached("boxlite.local/test:2")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let cached = store
+            .pull("boxlite.local/test:1", ImagePullPolicy::Never)
+            .await
+            .unwrap();
+        assert_eq!(cached.manifest_digest, manifest.manifest_digest);
+        assert_eq!(store.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tag_and_remove() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let store = ImageStore::new(
+            temp_dir.path().join("images"),
+            db,
+            vec![],
+            &ImageVerificationOptions::default(),
+        )
+        .unwrap();
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &create_minimal_tarball()).unwrap();
+        let layer = encoder.finish().unwrap();
+        let config = br#"{"architecture":"amd64","os":"linux"}"#;
+        store
+            .import("boxlite.local/test:1", config, std::slice::from_ref(&layer))
+            .await
+            .unwrap();
+        let layer_digest = sha256_digest(&layer);
+
+        // Tagging an image as itself changes nothing, not even its pin
+        store
+            .set_pinned("boxlite.local/test:1", true)
+            .await
+            .unwrap();
+        let same = store
+            .tag("boxlite.local/test:1", "boxlite.local/test:1")
+            .await
+            .unwrap();
+        assert_eq!(same.as_deref(), Some("boxlite.local/test:1"));
+        let list = store.list().await.unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].1.pinned);
+
+        store
+            .tag("boxlite.local/test:1", "boxlite.local/test:2")
+            .await
+            .unwrap();
+        let (_, freed) = store
+            .remove("boxlite.local/test:1", 0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(freed, 0);
+        assert!(store.storage().await.has_layer(&layer_digest));
+
+        // Content still used by a box is kept
+        store
+            .remove("boxlite.local/test:2", 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(store.storage().await.has_layer(&layer_digest));
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_load_from_local_no_blob_import() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -1095,7 +1816,13 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store = ImageStore::new(
+            images_dir.clone(),
+            db,
+            vec![],
+            &ImageVerificationOptions::default(),
+        )
+        .unwrap();
 
         // Load from local
         let _manifest = store.load_from_local(bundle_dir.clone()).await.unwrap();
@@ -1130,7 +1857,13 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store = ImageStore::new(
+            images_dir.clone(),
+            db,
+            vec![],
+            &ImageVerificationOptions::default(),
+        )
+        .unwrap();
 
         // Load should fail
         let result = store.load_from_local(bundle_dir).await;
@@ -1156,7 +1889,13 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store = ImageStore::new(
+            images_dir.clone(),
+            db,
+            vec![],
+            &ImageVerificationOptions::default(),
+        )
+        .unwrap();
 
         // Load should fail
         let result = store.load_from_local(bundle_dir).await;
@@ -1164,4 +1903,55 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("index.json"));
     }
+
+    #[tokio::test]
+    async fn test_progress_writer_reports_bytes_written() {
+        use std::sync::Mutex;
+        use tokio::io::AsyncWriteExt;
+
+        let progress = ProgressObservers::default();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        progress.register(Arc::new(move |event| {
+            if let ProgressEvent::LayerProgress { current, total, .. } = event {
+                sink.lock().unwrap().push((*current, *total));
+            }
+        }));
+        let layer = LayerInfo {
+            digest: format!("sha256:{}", "a".repeat(64)),
+            media_type: "application/vnd.oci.image.layer.v1.tar+gzip".into(),
+            urls: None,
+            size: 10,
+        };
+
+        let mut written = Vec::new();
+        let mut writer = ProgressWriter {
+            inner: &mut written,
+            progress: &progress,
+            image: "alpine:latest",
+            layer: &layer,
+            written: 0,
+        };
+        writer.write_all(b"abcd").await.unwrap();
+        writer.write_all(b"efghij").await.unwrap();
+
+        assert_eq!(written, b"abcdefghij");
+        assert_eq!(*seen.lock().unwrap(), [(4, 10), (10, 10)]);
+    }
+
+    #[test]
+    fn test_mirror_reference() {
+        let reference: Reference = "docker.io/library/alpine:3.19".parse().unwrap();
+        let mirrored = mirror_reference(&reference, "mirror.gcr.io").unwrap();
+        assert_eq!(mirrored.registry(), "mirror.gcr.io");
+        assert_eq!(mirrored.repository(), "library/alpine");
+        assert_eq!(mirrored.tag(), Some("3.19"));
+
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let reference: Reference = format!("ghcr.io/org/app@{}", digest).parse().unwrap();
+        let mirrored = mirror_reference(&reference, "registry.local:5000/ghcr/").unwrap();
+        assert_eq!(mirrored.registry(), "registry.local:5000");
+        assert_eq!(mirrored.repository(), "ghcr/org/app");
+        assert_eq!(mirrored.digest(), Some(digest.as_str()));
+    }
 }
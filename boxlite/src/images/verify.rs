@@ -0,0 +1,267 @@
+//! Image signature verification.
+//!
+//! Implements cosign key-based verification. Cosign stores signatures in the
+//! image's repository as an OCI artifact tagged `sha256-<hex>.sig`. Each layer
+//! of that artifact is a "simple signing" JSON payload, with the base64 ECDSA
+//! signature in the `dev.cosignproject.cosign/signature` annotation.
+//!
+//! A signature is accepted when it verifies against one of the trusted public
+//! keys and its payload names the manifest digest being pulled.
+//!
+//! Keyless (Fulcio/Rekor) verification is not supported.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
+
+use crate::runtime::options::{ImageVerificationMode, ImageVerificationOptions};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Layer annotation holding the base64 signature of the layer payload.
+pub(super) const SIGNATURE_ANNOTATION: &str = "dev.cosignproject.cosign/signature";
+
+/// DER header of a SubjectPublicKeyInfo wrapping an uncompressed P-256 point.
+const P256_SPKI_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+    0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+/// Verifies image signatures according to the configured policy.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ImageVerifier {
+    mode: ImageVerificationMode,
+    /// Trusted keys as uncompressed P-256 points.
+    keys: Vec<Vec<u8>>,
+}
+
+impl ImageVerifier {
+    /// Build a verifier, loading the configured public keys.
+    ///
+    /// Fails if verification is enabled without any keys, or a key can't be read.
+    pub(crate) fn new(options: &ImageVerificationOptions) -> BoxliteResult<Self> {
+        if options.mode == ImageVerificationMode::Disabled {
+            return Ok(Self::default());
+        }
+
+        if options.public_keys.is_empty() {
+            return Err(BoxliteError::Config(
+                "image verification is enabled but no public keys are configured".into(),
+            ));
+        }
+
+        let keys = options
+            .public_keys
+            .iter()
+            .map(|path| {
+                let pem = std::fs::read_to_string(path).map_err(|e| {
+                    BoxliteError::Config(format!(
+                        "Failed to read public key {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                parse_public_key_pem(&pem).map_err(|e| {
+                    BoxliteError::Config(format!("Invalid public key {}: {}", path.display(), e))
+                })
+            })
+            .collect::<BoxliteResult<Vec<_>>>()?;
+
+        Ok(Self {
+            mode: options.mode,
+            keys,
+        })
+    }
+
+    /// Whether signatures are checked at all.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.mode != ImageVerificationMode::Disabled
+    }
+
+    /// Check one signed payload against the trusted keys and expected digest.
+    pub(crate) fn verify_payload(
+        &self,
+        payload: &[u8],
+        signature_b64: &str,
+        manifest_digest: &str,
+    ) -> bool {
+        let Ok(signature) = BASE64.decode(signature_b64.trim()) else {
+            return false;
+        };
+
+        let signed_by_trusted_key = self.keys.iter().any(|key| {
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, key)
+                .verify(payload, &signature)
+                .is_ok()
+        });
+
+        signed_by_trusted_key && payload_digest(payload).as_deref() == Some(manifest_digest)
+    }
+
+    /// Apply the policy to a verification outcome.
+    ///
+    /// Enforce mode turns a failure into an error; warn mode logs and continues.
+    pub(crate) fn enforce(&self, image_ref: &str, outcome: BoxliteResult<()>) -> BoxliteResult<()> {
+        match (outcome, self.mode) {
+            (Ok(()), _) => {
+                tracing::info!(image = %image_ref, "Image signature verified");
+                Ok(())
+            }
+            (Err(e), ImageVerificationMode::Enforce) => Err(BoxliteError::Image(format!(
+                "refusing unverified image '{}': {}",
+                image_ref, e
+            ))),
+            (Err(e), _) => {
+                tracing::warn!(image = %image_ref, "Image signature verification failed: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Tag under which cosign stores the signatures for `digest`.
+pub(super) fn signature_tag(digest: &str) -> BoxliteResult<String> {
+    let (algorithm, hex) = digest
+        .split_once(':')
+        .ok_or_else(|| BoxliteError::Image(format!("invalid digest: {}", digest)))?;
+    Ok(format!("{}-{}.sig", algorithm, hex))
+}
+
+/// Extract the signed manifest digest from a simple signing payload.
+fn payload_digest(payload: &[u8]) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(payload).ok()?;
+    value
+        .pointer("/critical/image/docker-manifest-digest")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Parse a PEM `PUBLIC KEY` holding an ECDSA P-256 key into its raw point.
+fn parse_public_key_pem(pem: &str) -> Result<Vec<u8>, String> {
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "-----BEGIN PUBLIC KEY-----")
+        .skip(1)
+        .take_while(|line| *line != "-----END PUBLIC KEY-----")
+        .collect();
+
+    if body.is_empty() {
+        return Err("expected a PEM 'PUBLIC KEY' block".into());
+    }
+
+    let der = BASE64
+        .decode(body)
+        .map_err(|e| format!("invalid base64: {}", e))?;
+
+    match der.strip_prefix(&P256_SPKI_PREFIX[..]) {
+        Some(point) if point.len() == 65 => Ok(point.to_vec()),
+        _ => Err("only ECDSA P-256 keys are supported".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test key pair generated with openssl; the signature below signs TEST_PAYLOAD:
+    //   openssl ecparam -name prime256v1 -genkey -noout -out key.pem
+    //   openssl dgst -sha256 -sign key.pem payload.json | base64
+    const TEST_KEY_PEM: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEbmOUFVxQJAFyaeZobIupy6pn9u0R
+xgILaDqWw70XVq60/0P1gExo1vnKEWj0+yS/+oIh/vGPuITxC3UcxPymdw==
+-----END PUBLIC KEY-----
+";
+
+    const TEST_PAYLOAD: &[u8] = br#"{"critical":{"identity":{"docker-reference":"ghcr.io/foo/bar"},"image":{"docker-manifest-digest":"sha256:abc"},"type":"cosign container image signature"},"optional":null}"#;
+
+    const TEST_SIGNATURE: &str = "MEUCIC7laDn9nLWd+3CllX/BaWgnF8Cezs6o45SSq62HOmtHAiEA+IeFRSohkii99bMRbBX03ITGtnpP6eBy5mn0zAh/TbA=";
+
+    fn test_verifier(mode: ImageVerificationMode) -> ImageVerifier {
+        ImageVerifier {
+            mode,
+            keys: vec![parse_public_key_pem(TEST_KEY_PEM).unwrap()],
+        }
+    }
+
+    #[test]
+    fn test_signature_tag() {
+        assert_eq!(signature_tag("sha256:abc123").unwrap(), "sha256-abc123.sig");
+        assert!(signature_tag("abc123").is_err());
+    }
+
+    #[test]
+    fn test_parse_public_key_pem() {
+        let point = parse_public_key_pem(TEST_KEY_PEM).unwrap();
+        assert_eq!(point.len(), 65);
+        assert_eq!(point[0], 0x04); // uncompressed point marker
+    }
+
+    #[test]
+    fn test_parse_public_key_pem_rejects_garbage() {
+        assert!(parse_public_key_pem("not a key").is_err());
+        assert!(
+            parse_public_key_pem("-----BEGIN PUBLIC KEY-----\nAAAA\n-----END PUBLIC KEY-----\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_payload_digest() {
+        assert_eq!(payload_digest(TEST_PAYLOAD).as_deref(), Some("sha256:abc"));
+        assert_eq!(payload_digest(b"{}"), None);
+    }
+
+    #[test]
+    fn test_verifier_requires_keys() {
+        let options = ImageVerificationOptions {
+            mode: ImageVerificationMode::Enforce,
+            public_keys: vec![],
+        };
+        assert!(ImageVerifier::new(&options).is_err());
+    }
+
+    #[test]
+    fn test_disabled_verifier() {
+        let verifier = ImageVerifier::new(&ImageVerificationOptions::default()).unwrap();
+        assert!(!verifier.is_enabled());
+    }
+
+    #[test]
+    fn test_enforce_rejects_failed_verification() {
+        let verifier = test_verifier(ImageVerificationMode::Enforce);
+        let err = verifier
+            .enforce("alpine", Err(BoxliteError::Image("unsigned".into())))
+            .unwrap_err();
+        assert!(err.to_string().contains("refusing unverified image"));
+
+        let verifier = test_verifier(ImageVerificationMode::Warn);
+        assert!(
+            verifier
+                .enforce("alpine", Err(BoxliteError::Image("unsigned".into())))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_verify_payload() {
+        let verifier = test_verifier(ImageVerificationMode::Enforce);
+        assert!(verifier.verify_payload(TEST_PAYLOAD, TEST_SIGNATURE, "sha256:abc"));
+    }
+
+    #[test]
+    fn test_verify_payload_rejects_other_digest() {
+        // Valid signature, but for a different image
+        let verifier = test_verifier(ImageVerificationMode::Enforce);
+        assert!(!verifier.verify_payload(TEST_PAYLOAD, TEST_SIGNATURE, "sha256:def"));
+    }
+
+    #[test]
+    fn test_verify_payload_rejects_bad_signature() {
+        let verifier = test_verifier(ImageVerificationMode::Enforce);
+        assert!(!verifier.verify_payload(TEST_PAYLOAD, "not-base64!", "sha256:abc"));
+        assert!(!verifier.verify_payload(TEST_PAYLOAD, "AAAA", "sha256:abc"));
+
+        let tampered = String::from_utf8_lossy(TEST_PAYLOAD).replace("foo/bar", "foo/baz");
+        assert!(!verifier.verify_payload(tampered.as_bytes(), TEST_SIGNATURE, "sha256:abc"));
+    }
+}
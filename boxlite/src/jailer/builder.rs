@@ -47,6 +47,19 @@ pub struct Jailer {
     pub(crate) box_id: String,
     /// Box directory path
     pub(crate) box_dir: PathBuf,
+    /// Unix socket directory, when outside the box directory
+    pub(crate) socket_dir: Option<PathBuf>,
+    /// Other host paths the sandbox gives access to
+    pub(crate) allowed_paths: Vec<AllowedPath>,
+}
+
+/// A host path outside the box directory that the sandboxed shim uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedPath {
+    /// File or directory on the host, at the same path in the sandbox
+    pub path: PathBuf,
+    /// Whether the shim may write to it
+    pub writable: bool,
 }
 
 impl Jailer {
@@ -61,6 +74,8 @@ impl Jailer {
             volumes: Vec::new(),
             box_id: box_id.into(),
             box_dir: box_dir.into(),
+            socket_dir: None,
+            allowed_paths: Vec::new(),
         }
     }
 
@@ -96,6 +111,24 @@ impl Jailer {
         self
     }
 
+    /// Set the Unix socket directory when it lives outside the box directory.
+    ///
+    /// The sandbox makes it writable like the box directory.
+    pub fn with_socket_dir(mut self, socket_dir: Option<PathBuf>) -> Self {
+        self.socket_dir = socket_dir;
+        self
+    }
+
+    /// Give the sandbox access to a host path outside the box directory,
+    /// read-only unless `writable`.
+    pub fn with_allowed_path(mut self, path: impl Into<PathBuf>, writable: bool) -> Self {
+        self.allowed_paths.push(AllowedPath {
+            path: path.into(),
+            writable,
+        });
+        self
+    }
+
     // ─────────────────────────────────────────────────────────────────────
     // Getters
     // ─────────────────────────────────────────────────────────────────────
@@ -125,6 +158,16 @@ impl Jailer {
         &self.box_dir
     }
 
+    /// Get the Unix socket directory, if outside the box directory.
+    pub fn socket_dir(&self) -> Option<&Path> {
+        self.socket_dir.as_deref()
+    }
+
+    /// Get the host paths allowed besides the box directory.
+    pub fn allowed_paths(&self) -> &[AllowedPath] {
+        &self.allowed_paths
+    }
+
     /// Get the resource limits.
     pub fn resource_limits(&self) -> &ResourceLimits {
         &self.security.resource_limits
@@ -183,6 +226,7 @@ impl Jailer {
 pub struct JailerBuilder {
     security: SecurityOptions,
     volumes: Vec<VolumeSpec>,
+    allowed_paths: Vec<AllowedPath>,
     box_id: Option<String>,
     box_dir: Option<PathBuf>,
 }
@@ -199,6 +243,7 @@ impl JailerBuilder {
         Self {
             security: SecurityOptions::default(),
             volumes: Vec::new(),
+            allowed_paths: Vec::new(),
             box_id: None,
             box_dir: None,
         }
@@ -252,6 +297,19 @@ impl JailerBuilder {
         self
     }
 
+    /// Give the sandbox access to a host path outside the box directory.
+    ///
+    /// # Arguments
+    /// * `path` - File or directory on the host
+    /// * `writable` - Whether the shim may write to it
+    pub fn allow_path(&mut self, path: impl Into<PathBuf>, writable: bool) -> &mut Self {
+        self.allowed_paths.push(AllowedPath {
+            path: path.into(),
+            writable,
+        });
+        self
+    }
+
     /// Enable or disable jailer isolation.
     ///
     /// Shorthand for modifying `security.jailer_enabled`.
@@ -298,6 +356,8 @@ impl JailerBuilder {
             volumes: self.volumes.clone(),
             box_id,
             box_dir,
+            socket_dir: None,
+            allowed_paths: self.allowed_paths.clone(),
         })
     }
 }
@@ -391,15 +451,49 @@ mod tests {
                 host_path: "/data".to_string(),
                 guest_path: "/mnt/data".to_string(),
                 read_only: true,
+                driver: None,
+                options: Vec::new(),
+                chown: None,
             })
             .add_volume(VolumeSpec {
                 host_path: "/output".to_string(),
                 guest_path: "/mnt/output".to_string(),
                 read_only: false,
+                driver: None,
+                options: Vec::new(),
+                chown: None,
             })
             .build()
             .expect("Should build successfully");
 
         assert_eq!(jailer.volumes().len(), 2);
     }
+
+    #[test]
+    fn test_allowed_paths() {
+        let jailer = Jailer::new("test-box", "/tmp/box")
+            .with_allowed_path("/recordings", false)
+            .with_allowed_path("/output", true);
+        assert_eq!(
+            jailer.allowed_paths(),
+            [
+                AllowedPath {
+                    path: PathBuf::from("/recordings"),
+                    writable: false,
+                },
+                AllowedPath {
+                    path: PathBuf::from("/output"),
+                    writable: true,
+                },
+            ]
+        );
+
+        let jailer = JailerBuilder::new()
+            .box_id("test-box")
+            .box_dir("/tmp/box")
+            .allow_path("/recordings", false)
+            .build()
+            .expect("Should build successfully");
+        assert_eq!(jailer.allowed_paths().len(), 1);
+    }
 }
@@ -241,6 +241,26 @@ impl Jailer {
         bwrap.bind(&self.box_dir, &self.box_dir);
         tracing::debug!(box_dir = %self.box_dir.display(), "bwrap: mounted box directory");
 
+        // Unix sockets placed outside the box directory (short socket paths)
+        if let Some(socket_dir) = &self.socket_dir {
+            bwrap.bind(socket_dir, socket_dir);
+            tracing::debug!(socket_dir = %socket_dir.display(), "bwrap: mounted socket directory");
+        }
+
+        // Other host paths the shim was given (read-only unless writable)
+        for allowed in &self.allowed_paths {
+            if allowed.writable {
+                bwrap.bind(&allowed.path, &allowed.path);
+            } else {
+                bwrap.ro_bind(&allowed.path, &allowed.path);
+            }
+            tracing::debug!(
+                path = %allowed.path.display(),
+                writable = allowed.writable,
+                "bwrap: mounted allowed path"
+            );
+        }
+
         // Get boxlite home directory for other mounts
         if let Some(boxes_dir) = self.box_dir.parent()
             && let Some(home_dir) = boxes_dir.parent()
@@ -305,11 +325,30 @@ impl Jailer {
     #[cfg(target_os = "macos")]
     fn build_command_macos(&self, binary: &Path, args: &[String]) -> Command {
         use crate::jailer::platform::macos;
+        use crate::runtime::options::VolumeSpec;
 
         let mut cmd = if macos::is_sandbox_available() {
             tracing::info!("Building sandbox-exec isolated command");
+            // A socket dir outside the box dir needs the same write access as
+            // a writable volume, and allowed paths the access they were given
+            let mut volumes = self.volumes.clone();
+            let allowed = self
+                .socket_dir
+                .iter()
+                .map(|dir| (dir, false))
+                .chain(self.allowed_paths.iter().map(|a| (&a.path, !a.writable)));
+            for (path, read_only) in allowed {
+                volumes.push(VolumeSpec {
+                    host_path: path.to_string_lossy().into_owned(),
+                    guest_path: String::new(),
+                    read_only,
+                    driver: None,
+                    options: Vec::new(),
+                    chown: None,
+                });
+            }
             let (sandbox_cmd, sandbox_args) =
-                macos::get_sandbox_exec_args(&self.security, &self.box_dir, binary, &self.volumes);
+                macos::get_sandbox_exec_args(&self.security, &self.box_dir, binary, &volumes);
             let mut cmd = Command::new(sandbox_cmd);
             cmd.args(sandbox_args);
             cmd.arg(binary);
@@ -90,7 +90,7 @@ pub(crate) mod shim_copy;
 // ============================================================================
 
 // Core types
-pub use builder::{Jailer, JailerBuilder};
+pub use builder::{AllowedPath, Jailer, JailerBuilder};
 pub use config::{ResourceLimits, SecurityOptions};
 pub use error::{ConfigError, IsolationError, JailerError, SystemError};
 pub use platform::{PlatformIsolation, SpawnIsolation};
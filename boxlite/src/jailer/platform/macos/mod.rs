@@ -369,7 +369,7 @@ fn build_dynamic_read_volumes(
     // Add user volumes
     for vol in volumes {
         let path = canonicalize_or_original(Path::new(&vol.host_path));
-        let ro_marker = if vol.read_only { " (ro)" } else { " (rw)" };
+        let ro_marker = if vol.is_read_only() { " (ro)" } else { " (rw)" };
         policy.push_str(&format!(
             "    (subpath \"{}\")  ; {}{}\n",
             path.display(),
@@ -415,7 +415,7 @@ fn build_dynamic_write_paths(box_dir: &Path, volumes: &[VolumeSpec]) -> String {
     ));
 
     // Writable user volumes (read_only=false)
-    for vol in volumes.iter().filter(|v| !v.read_only) {
+    for vol in volumes.iter().filter(|v| !v.is_read_only()) {
         let path = canonicalize_or_original(Path::new(&vol.host_path));
         policy.push_str(&format!(
             "    (subpath \"{}\")  ; -> {}\n",
@@ -593,11 +593,17 @@ mod tests {
                 host_path: "/data/input".to_string(),
                 guest_path: "/mnt/input".to_string(),
                 read_only: true,
+                driver: None,
+                options: Vec::new(),
+                chown: None,
             },
             VolumeSpec {
                 host_path: "/data/output".to_string(),
                 guest_path: "/mnt/output".to_string(),
                 read_only: false,
+                driver: None,
+                options: Vec::new(),
+                chown: None,
             },
         ];
 
@@ -620,11 +626,17 @@ mod tests {
                 host_path: "/data/input".to_string(),
                 guest_path: "/mnt/input".to_string(),
                 read_only: true, // Should NOT be in write policy
+                driver: None,
+                options: Vec::new(),
+                chown: None,
             },
             VolumeSpec {
                 host_path: "/data/output".to_string(),
                 guest_path: "/mnt/output".to_string(),
                 read_only: false, // Should be in write policy
+                driver: None,
+                options: Vec::new(),
+                chown: None,
             },
         ];
         let box_dir = PathBuf::from("/Users/test/.boxlite/boxes/test-box");
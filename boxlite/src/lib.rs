@@ -8,12 +8,14 @@ use tracing_subscriber::EnvFilter;
 // Global guard for tracing-appender to keep the writer thread alive
 static LOG_GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
 
+pub mod blocking;
 pub mod jailer;
 pub mod litebox;
 pub mod lock;
 pub mod metrics;
 pub mod net;
 pub mod pipeline;
+pub mod plugins;
 pub mod runtime;
 pub mod util;
 pub mod vmm;
@@ -28,24 +30,49 @@ mod volumes;
 
 pub use litebox::LiteBox;
 pub use portal::GuestSession;
-pub use runtime::BoxliteRuntime;
+pub use portal::calls::{PortalCall, in_flight_portal_calls};
+pub use runtime::batch::{BoxCreateResult, CreateManyStream};
+pub use runtime::{AsyncRuntime, BoxliteRuntime};
 
 pub use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+pub use disk::{DiskFileUsage, DiskFormat, DiskUsage, RootfsSize};
+pub use images::ImageDiff;
 pub use litebox::{
-    BoxCommand, CopyOptions, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, ExecutionId,
+    BoxCommand, CollectedOutput, ConflictPolicy, CopyOptions, ExecLimit, ExecLimits, ExecLines,
+    ExecLogOptions, ExecOutputStream, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution,
+    ExecutionId, ExecutionInfo, ExecutionLogs, InitHook, InitHookContext, InitStage, OutputChunk,
+    PackageManager, ProvisionSpec, SyncOptions, SyncStatus, WorkspaceSync,
 };
-pub use metrics::{BoxMetrics, RuntimeMetrics};
+pub use metrics::{BoxMetrics, ExecLabelMetrics, RuntimeMetrics};
+pub use net::{NetConnection, NetProtocol};
+pub use runtime::connection_prompt::{ConnectionDecision, ConnectionRequest};
+pub use runtime::events::{ConnectionAudit, EventKind, ExecAudit, RuntimeEvent};
+pub use runtime::exec_cache::{CachedExecResult, ExecCacheEntry, ExecCacheLimits};
 use runtime::layout::FilesystemLayout;
+pub use runtime::leaks::{CleanupLeak, LeakKind};
+pub use runtime::networks::{NetworkInfo, NetworkMember};
 pub use runtime::options::{
-    BoxOptions, BoxliteOptions, ResourceLimits, RootfsSpec, SecurityOptions,
+    BoxOptions, BoxOptionsBuilder, BoxliteOptions, DbDurability, EventDestination, EventSinkSpec,
+    HookSpec, ImagePullPolicy, ImageVerificationMode, ImageVerificationOptions, LifecycleHooks,
+    NetworkMode, PluginSpec, RegistryRetryOptions, ResourceLimits, RootfsSpec, SecurityOptions,
+    SecurityPreset, StorageDriver, SwapBacking, Ulimit, VolumeChown, VolumeSpecBuilder,
 };
+pub use runtime::plan::{BoxPlan, ImagePlanStatus};
+pub use runtime::prefetch::{ImagePrefetchStatus, PrefetchCandidate};
+pub use runtime::progress::ProgressEvent;
+pub use runtime::run_once::{RunOutcome, RunTimings};
+pub use runtime::shutdown::{BoxShutdownOutcome, BoxShutdownResult, ShutdownPhase, ShutdownReport};
 pub use runtime::types::ContainerID;
-pub use runtime::types::{BoxID, BoxInfo, BoxState, BoxStateInfo, BoxStatus};
+pub use runtime::types::{
+    BoxFilter, BoxID, BoxInfo, BoxState, BoxStateInfo, BoxStatus, BoxTimings, GuestAgentInfo,
+    ProjectInfo, RootfsBuildTimings, StatusTransition,
+};
 
 /// Initialize tracing for Boxlite using the provided filesystem layout.
 ///
 /// Logs are written to `<layout.home_dir()>/logs/boxlite.log` with daily rotation.
-/// Uses the `RUST_LOG` environment variable for filtering (defaults to `info`).
+/// Uses the `RUST_LOG` environment variable for filtering (defaults to `info`);
+/// see `BoxliteRuntime::set_log_level()` to change it later.
 /// Idempotent: subsequent calls return immediately once initialized.
 pub fn init_logging_for(layout: &FilesystemLayout) -> BoxliteResult<()> {
     let logs_dir = layout.logs_dir();
@@ -67,7 +94,9 @@ pub fn init_logging_for(layout: &FilesystemLayout) -> BoxliteResult<()> {
 
         // If global default subscriber is already set, this will return an error.
         // We ignore it to avoid interfering with host-configured tracing.
-        util::register_to_tracing(non_blocking, env_filter);
+        if let Some(handle) = util::register_to_tracing(non_blocking, env_filter) {
+            let _ = runtime::log_level::LOG_FILTER.set(handle);
+        }
 
         guard
     });
@@ -5,26 +5,45 @@
 // ============================================================================
 
 use std::sync::Arc;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use parking_lot::RwLock;
 use tar;
 use tokio::sync::OnceCell;
 use tokio_util::sync::CancellationToken;
 
+use boxlite_shared::constants::capabilities;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 use super::config::BoxConfig;
-use super::exec::{BoxCommand, ExecStderr, ExecStdin, ExecStdout, Execution};
-use super::state::BoxState;
-use crate::disk::Disk;
+use super::exec::{
+    BoxCommand, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, ExecutionId,
+    ExecutionInfo, ExecutionLogs,
+};
+use super::exec_slots::{ExecSlot, ExecSlots};
+use super::state::{BoxState, GuestAgentInfo, StatusTransition};
+use super::trace_context;
+use crate::disk::{self, Disk, DiskFileUsage, DiskUsage, RootfsSize};
 #[cfg(target_os = "linux")]
 use crate::fs::BindMountHandle;
-use crate::litebox::copy::CopyOptions;
+use crate::images::{FileMeta, FileTree, ImageDiff, diff_trees, image_tree};
+use crate::litebox::copy::{CopyOptions, walk_unignored};
 use crate::lock::LockGuard;
-use crate::metrics::{BoxMetrics, BoxMetricsStorage};
+use crate::metrics::{BoxMetrics, BoxMetricsStorage, GAUGE_SAMPLE_INTERVAL, VmGauges};
+use crate::net::NetConnection;
+use crate::plugins;
 use crate::portal::GuestSession;
+use crate::portal::interfaces::{ContainerState, ExecutionInterface, FilesInterface};
+use crate::runtime::connection_prompt::PromptServer;
+use crate::runtime::constants::images;
+use crate::runtime::events::{EventKind, ExecAudit, RuntimeEvent};
+use crate::runtime::hooks::{self, HookEvent};
+use crate::runtime::networks;
+use crate::runtime::options::{HookSpec, RootfsSpec, StorageDriver};
+use crate::runtime::reservations::Resources;
 use crate::runtime::rt_impl::SharedRuntimeImpl;
+use crate::runtime::sockets;
 use crate::runtime::types::BoxStatus;
 use crate::vmm::controller::VmmHandler;
 use crate::{BoxID, BoxInfo};
@@ -36,6 +55,16 @@ use crate::{BoxID, BoxInfo};
 /// Shared reference to BoxImpl.
 pub type SharedBoxImpl = Arc<BoxImpl>;
 
+/// How long an exec waits for the container of a starting box.
+const CONTAINER_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between container status checks while waiting.
+const CONTAINER_READY_POLL: Duration = Duration::from_millis(50);
+
+/// Longest pause before waiting again for an execution holding a slot,
+/// after the connection to the guest failed.
+const EXEC_SLOT_RETRY_MAX: Duration = Duration::from_secs(5);
+
 // ============================================================================
 // LIVE STATE
 // ============================================================================
@@ -46,11 +75,15 @@ pub type SharedBoxImpl = Arc<BoxImpl>;
 /// Separated from BoxImpl to allow operations like `info()` without initializing LiveState.
 pub(crate) struct LiveState {
     // VM process control
-    handler: std::sync::Mutex<Box<dyn VmmHandler>>,
+    handler: Arc<std::sync::Mutex<Box<dyn VmmHandler>>>,
     guest_session: GuestSession,
+    guest_agent: Option<GuestAgentInfo>,
+    /// Set once the guest reported the container running
+    container_ready: OnceCell<()>,
 
     // Metrics
     metrics: BoxMetricsStorage,
+    gauges: Arc<VmGauges>,
 
     // Disk resources (kept for lifecycle management)
     _container_rootfs_disk: Disk,
@@ -68,15 +101,19 @@ impl LiveState {
     pub(crate) fn new(
         handler: Box<dyn VmmHandler>,
         guest_session: GuestSession,
+        guest_agent: Option<GuestAgentInfo>,
         metrics: BoxMetricsStorage,
         container_rootfs_disk: Disk,
         guest_rootfs_disk: Option<Disk>,
         #[cfg(target_os = "linux")] bind_mount: Option<BindMountHandle>,
     ) -> Self {
         Self {
-            handler: std::sync::Mutex::new(handler),
+            handler: Arc::new(std::sync::Mutex::new(handler)),
             guest_session,
+            guest_agent,
+            container_ready: OnceCell::new(),
             metrics,
+            gauges: Arc::new(VmGauges::default()),
             _container_rootfs_disk: container_rootfs_disk,
             guest_rootfs_disk,
             #[cfg(target_os = "linux")]
@@ -100,6 +137,8 @@ pub(crate) struct BoxImpl {
     /// Cancellation token for this box (child of runtime's token).
     /// When cancelled (via stop() or runtime shutdown), all operations abort gracefully.
     pub(crate) shutdown_token: CancellationToken,
+    /// Slots of running executions (`max_concurrent_execs`).
+    exec_slots: ExecSlots,
 
     // --- Lazily initialized ---
     live: OnceCell<LiveState>,
@@ -125,11 +164,16 @@ impl BoxImpl {
         runtime: SharedRuntimeImpl,
         shutdown_token: CancellationToken,
     ) -> Self {
+        let exec_slots = ExecSlots::new(
+            config.options.max_concurrent_execs,
+            config.options.queue_execs,
+        );
         Self {
             config,
             state: RwLock::new(state),
             runtime,
             shutdown_token,
+            exec_slots,
             live: OnceCell::new(),
         }
     }
@@ -151,6 +195,10 @@ impl BoxImpl {
         BoxInfo::new(&self.config, &state)
     }
 
+    pub(crate) fn history(&self) -> Vec<StatusTransition> {
+        self.state.read().history.clone()
+    }
+
     // ========================================================================
     // OPERATIONS (require LiveState)
     // ========================================================================
@@ -162,6 +210,23 @@ impl BoxImpl {
     ///
     /// This is idempotent - calling start() on a Running box is a no-op.
     pub(crate) async fn start(&self) -> BoxliteResult<()> {
+        self.check_startable()?;
+
+        // Trigger lazy initialization (this does the actual work)
+        let _ = self.live_state().await?;
+
+        Ok(())
+    }
+
+    /// Start the box without starting its dependencies.
+    async fn start_alone(&self) -> BoxliteResult<()> {
+        self.check_startable()?;
+        let _ = self.init_live(false).await?;
+        Ok(())
+    }
+
+    /// Fail unless the box is running or can be started.
+    fn check_startable(&self) -> BoxliteResult<()> {
         // Check if already shutdown (via stop() or runtime shutdown)
         if self.shutdown_token.is_cancelled() {
             return Err(BoxliteError::Stopped(
@@ -170,10 +235,10 @@ impl BoxImpl {
         }
 
         // Check current status
-        let status = self.state.read().status;
+        let status = self.state.read().status();
 
         // Idempotent: already running
-        if status == BoxStatus::Running {
+        if status.is_running() {
             return Ok(());
         }
 
@@ -185,23 +250,371 @@ impl BoxImpl {
             )));
         }
 
-        // Trigger lazy initialization (this does the actual work)
-        let _ = self.live_state().await?;
-
         Ok(())
     }
 
     pub(crate) async fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution> {
-        use boxlite_shared::constants::executor as executor_const;
+        self.check_not_stopped()?;
+        let slot = self.acquire_exec_slot().await?;
+        let live = self.live_state().await?;
+        self.check_command_capabilities(&command)?;
+        self.wait_container_ready(live).await?;
+        let command = self.prepare_command(command);
 
-        // Check if box is stopped before proceeding (via stop() or runtime shutdown)
+        let mut exec_interface = live.guest_session.execution().await?;
+        let audit = self.exec_audit(&command, false);
+        let labels = command.labels.clone();
+        let result = exec_interface
+            .exec(command, self.shutdown_token.clone())
+            .await;
+        self.record_exec(live, &labels, result.is_err());
+        self.emit_exec_event(
+            audit,
+            result
+                .as_ref()
+                .map(|components| components.execution_id.as_str()),
+        );
+
+        let components = result?;
+        self.hold_until_exit(exec_interface.clone(), &components.execution_id, slot);
+        Ok(Execution::new(
+            components.execution_id,
+            exec_interface,
+            components.result_rx,
+            components.stdin_tx.map(ExecStdin::new),
+            Some(ExecStdout::new(
+                components.stdout_rx,
+                components.stream_end.clone(),
+            )),
+            Some(ExecStderr::new(components.stderr_rx, components.stream_end)),
+        ))
+    }
+
+    pub(crate) async fn exec_detached(&self, command: BoxCommand) -> BoxliteResult<ExecutionId> {
+        self.check_not_stopped()?;
+        let slot = self.acquire_exec_slot().await?;
+        let live = self.live_state().await?;
+        self.check_command_capabilities(&command)?;
+        self.wait_container_ready(live).await?;
+        let command = self.prepare_command(command);
+
+        let mut exec_interface = live.guest_session.execution().await?;
+        let result = exec_interface.start(&command).await;
+        self.record_exec(live, &command.labels, result.is_err());
+        self.emit_exec_event(
+            self.exec_audit(&command, true),
+            result.as_ref().map(|id| id.as_str()),
+        );
+
+        let execution_id = result?;
+        self.hold_until_exit(exec_interface, &execution_id, slot);
+        Ok(execution_id)
+    }
+
+    /// Wait until the guest reports the container running, once per start.
+    ///
+    /// Guards execs right after `start()` (or an attach) against racing the
+    /// container's init. Fails with the guest's diagnostics if the init
+    /// process exited, and with `Stopped` if the box stops meanwhile. Agents
+    /// without `Container.Status` are not waited for.
+    async fn wait_container_ready(&self, live: &LiveState) -> BoxliteResult<()> {
+        let supported = live
+            .guest_agent
+            .as_ref()
+            .is_some_and(|agent| agent.supports(capabilities::CONTAINER_STATUS));
+        if !supported {
+            return Ok(());
+        }
+
+        live.container_ready
+            .get_or_try_init(|| async {
+                let mut container = live.guest_session.container().await?;
+                let deadline = tokio::time::Instant::now() + CONTAINER_READY_TIMEOUT;
+                loop {
+                    match container.status(self.container_id()).await? {
+                        ContainerState::Running => return Ok(()),
+                        ContainerState::Exited(diagnostics) => {
+                            return Err(BoxliteError::InvalidState(format!(
+                                "Container of box {} exited: {}",
+                                self.config.id, diagnostics
+                            )));
+                        }
+                        ContainerState::Pending => {}
+                    }
+                    if tokio::time::Instant::now() >= deadline {
+                        return Err(BoxliteError::InvalidState(format!(
+                            "Container of box {} not running after {:?}",
+                            self.config.id, CONTAINER_READY_TIMEOUT
+                        )));
+                    }
+                    tokio::select! {
+                        _ = tokio::time::sleep(CONTAINER_READY_POLL) => {}
+                        _ = self.shutdown_token.cancelled() => {
+                            return Err(BoxliteError::Cancelled(
+                                "Box stopped while waiting for its container".into(),
+                            ));
+                        }
+                    }
+                }
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Take an execution slot, waiting (if queueing) until one frees up or
+    /// the box stops.
+    async fn acquire_exec_slot(&self) -> BoxliteResult<ExecSlot> {
+        tokio::select! {
+            slot = self.exec_slots.acquire() => slot,
+            _ = self.shutdown_token.cancelled() => Err(BoxliteError::Cancelled(
+                "Box stopped while waiting for an execution slot".into(),
+            )),
+        }
+    }
+
+    /// Release `slot` once the execution's process exits or the box stops.
+    ///
+    /// A failed connection to the guest says nothing about the process, so
+    /// the wait is retried; the slot is also released if the guest no longer
+    /// knows the execution, or can't wait for it.
+    fn hold_until_exit(
+        &self,
+        mut exec_interface: ExecutionInterface,
+        execution_id: &str,
+        slot: ExecSlot,
+    ) {
+        let execution_id = execution_id.to_string();
+        let shutdown_token = self.shutdown_token.clone();
+        tokio::spawn(async move {
+            let mut backoff = CONTAINER_READY_POLL;
+            loop {
+                let result = tokio::select! {
+                    result = exec_interface.wait(&execution_id) => result,
+                    _ = shutdown_token.cancelled() => break,
+                };
+                match result {
+                    Err(BoxliteError::Rpc(e) | BoxliteError::RpcTransport(e)) => {
+                        tracing::debug!(
+                            execution_id = %execution_id,
+                            error = %e,
+                            "Waiting for execution failed, retrying"
+                        );
+                    }
+                    _ => break,
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = shutdown_token.cancelled() => break,
+                }
+                backoff = (backoff * 2).min(EXEC_SLOT_RETRY_MAX);
+            }
+            drop(slot);
+        });
+    }
+
+    pub(crate) async fn attach_execution(&self, execution_id: &str) -> BoxliteResult<Execution> {
+        self.check_not_stopped()?;
+        let live = self.live_state().await?;
+
+        let mut exec_interface = live.guest_session.execution().await?;
+        let components =
+            exec_interface.attach(execution_id.to_string(), self.shutdown_token.clone());
+        Ok(Execution::new(
+            components.execution_id,
+            exec_interface,
+            components.result_rx,
+            None,
+            Some(ExecStdout::new(
+                components.stdout_rx,
+                components.stream_end.clone(),
+            )),
+            Some(ExecStderr::new(components.stderr_rx, components.stream_end)),
+        ))
+    }
+
+    pub(crate) async fn executions(&self) -> BoxliteResult<Vec<ExecutionInfo>> {
+        self.check_not_stopped()?;
+        let live = self.live_state().await?;
+        self.require_capability(capabilities::EXEC_LIST, "listing executions")?;
+        live.guest_session.execution().await?.list().await
+    }
+
+    pub(crate) async fn wait_execution(&self, execution_id: &str) -> BoxliteResult<ExecResult> {
+        self.check_not_stopped()?;
+        let live = self.live_state().await?;
+        live.guest_session
+            .execution()
+            .await?
+            .wait(execution_id)
+            .await
+    }
+
+    pub(crate) async fn kill_execution(
+        &self,
+        execution_id: &str,
+        signal: i32,
+    ) -> BoxliteResult<()> {
+        self.check_not_stopped()?;
+        let live = self.live_state().await?;
+        live.guest_session
+            .execution()
+            .await?
+            .kill(execution_id, signal)
+            .await
+    }
+
+    pub(crate) fn execution_logs(&self, execution_id: &str) -> BoxliteResult<ExecutionLogs> {
+        if execution_id.is_empty()
+            || execution_id == "."
+            || execution_id == ".."
+            || execution_id.contains(['/', '\\'])
+        {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "invalid execution ID: {:?}",
+                execution_id
+            )));
+        }
+
+        let layout = self
+            .runtime
+            .layout
+            .box_layout(self.config.id.as_str(), false)?;
+        ExecutionLogs::from_dir(&layout.exec_log_dir(execution_id))
+    }
+
+    /// Host usage of the box's container and guest rootfs disks.
+    pub(crate) fn disk_usage(&self) -> BoxliteResult<DiskUsage> {
+        let layout = self
+            .runtime
+            .layout
+            .box_layout(self.config.id.as_str(), false)?;
+        let mut disks = Vec::new();
+        for path in [
+            layout.disk_path(),
+            layout.raw_disk_path(),
+            layout.guest_disk_path(),
+        ] {
+            disks.extend(DiskFileUsage::read(&path)?);
+        }
+        Ok(DiskUsage { disks })
+    }
+
+    /// Scan the container disk overlay for the rootfs sizes, `None` if the
+    /// box never started.
+    pub(crate) fn rootfs_size(&self) -> BoxliteResult<Option<RootfsSize>> {
+        if self.config.storage_driver == StorageDriver::Reflink {
+            // A raw reflink copy doesn't record which blocks the box wrote
+            return Ok(None);
+        }
+        let layout = self
+            .runtime
+            .layout
+            .box_layout(self.config.id.as_str(), false)?;
+        let overlay = layout.disk_path();
+        if !overlay.exists() {
+            return Ok(None);
+        }
+        let ext4_rootfs = self.config.storage_driver == StorageDriver::Ext4;
+        disk::rootfs_size(&overlay, ext4_rootfs).map(Some)
+    }
+
+    /// Fetch the guest agent's recent log lines (all buffered lines if `tail` is None).
+    pub(crate) async fn guest_logs(&self, tail: Option<usize>) -> BoxliteResult<Vec<String>> {
+        self.check_not_stopped()?;
+        let status = self.state.read().status();
+        if !status.is_running() {
+            return Err(BoxliteError::InvalidState(format!(
+                "box {} is not running (status: {})",
+                self.config.id,
+                status.as_str()
+            )));
+        }
+
+        // The guest treats 0 as "everything"
+        let tail = match tail {
+            None => 0,
+            Some(0) => return Ok(Vec::new()),
+            Some(n) => n.min(u32::MAX as usize) as u32,
+        };
+
+        let live = self.live_state().await?;
+        self.require_capability(capabilities::LOGS, "reading guest logs")?;
+        let mut guest = live.guest_session.guest().await?;
+        guest.logs(tail).await
+    }
+
+    /// List the box's active TCP/UDP connections, as seen by the guest.
+    pub(crate) async fn net_connections(&self) -> BoxliteResult<Vec<NetConnection>> {
+        self.check_not_stopped()?;
+        let status = self.state.read().status();
+        if !status.is_running() {
+            return Err(BoxliteError::InvalidState(format!(
+                "box {} is not running (status: {})",
+                self.config.id,
+                status.as_str()
+            )));
+        }
+
+        let live = self.live_state().await?;
+        self.require_capability(capabilities::NET_CONNECTIONS, "listing connections")?;
+        let mut guest = live.guest_session.guest().await?;
+        guest
+            .net_connections()
+            .await?
+            .into_iter()
+            .map(NetConnection::try_from)
+            .collect()
+    }
+
+    /// Kill the VM process without waiting for the guest (SIGKILL), and
+    /// record the box as stopped.
+    ///
+    /// Used when a graceful stop didn't finish in time.
+    pub(crate) fn kill_vm(&self) -> BoxliteResult<()> {
+        let pid = self.state.read().pid;
+        if let Some(pid) = pid
+            && unsafe { libc::kill(pid as i32, libc::SIGKILL) } != 0
+        {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                return Err(BoxliteError::Internal(format!(
+                    "failed to kill VM process {}: {}",
+                    pid, err
+                )));
+            }
+        }
+        self.runtime.reservations.release(&self.config.id);
+
+        {
+            let mut state = self.state.write();
+            state.mark_stop_with_reason("killed after stop timed out");
+            if state.lock_id.is_some() {
+                match self.runtime.box_manager.save_box(&self.config.id, &state) {
+                    // Removed while stopping
+                    Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        self.runtime
+            .events
+            .emit(|| RuntimeEvent::new(EventKind::BoxStopped, self.info()));
+        Ok(())
+    }
+
+    /// Fail if the handle was invalidated by stop() or runtime shutdown.
+    fn check_not_stopped(&self) -> BoxliteResult<()> {
         if self.shutdown_token.is_cancelled() {
             return Err(BoxliteError::Stopped(
                 "Handle invalidated after stop(). Use runtime.get() to get a new handle.".into(),
             ));
         }
+        Ok(())
+    }
 
-        let live = self.live_state().await?;
+    /// Apply box-level defaults (container ID env, working directory) to a command.
+    fn prepare_command(&self, command: BoxCommand) -> BoxCommand {
+        use boxlite_shared::constants::executor as executor_const;
 
         // Inject container ID into environment if not already set
         let command = if command
@@ -219,40 +632,66 @@ impl BoxImpl {
         };
 
         // Set working directory from BoxOptions if not set in command
-        let command = match (&command.working_dir, &self.config.options.working_dir) {
+        let mut command = match (&command.working_dir, &self.config.options.working_dir) {
             (None, Some(dir)) => command.working_dir(dir),
             _ => command,
         };
+        // Older agents don't know the field; leave it unset for them
+        if self.agent_supports(capabilities::EXEC_RETENTION) {
+            command.retention_secs = self.config.options.exec_retention_secs.unwrap_or(0);
+        }
+        if self.config.options.propagate_trace_context {
+            command = trace_context::inject(command, &self.config.id);
+        }
+        command
+    }
 
-        let mut exec_interface = live.guest_session.execution().await?;
-        let result = exec_interface
-            .exec(command, self.shutdown_token.clone())
-            .await;
-
-        // Instrument metrics
+    /// Instrument exec metrics.
+    fn record_exec(&self, live: &LiveState, labels: &[String], failed: bool) {
         live.metrics.increment_commands_executed();
+        live.metrics.record_labeled_exec(labels, failed);
         self.runtime
             .runtime_metrics
             .total_commands
             .fetch_add(1, Ordering::Relaxed);
 
-        if result.is_err() {
+        if failed {
             live.metrics.increment_exec_errors();
             self.runtime
                 .runtime_metrics
                 .total_exec_errors
                 .fetch_add(1, Ordering::Relaxed);
         }
+    }
 
-        let components = result?;
-        Ok(Execution::new(
-            components.execution_id,
-            exec_interface,
-            components.result_rx,
-            Some(ExecStdin::new(components.stdin_tx)),
-            Some(ExecStdout::new(components.stdout_rx)),
-            Some(ExecStderr::new(components.stderr_rx)),
-        ))
+    /// Audit record of a command about to run, before its outcome is known.
+    fn exec_audit(&self, command: &BoxCommand, detached: bool) -> Option<ExecAudit> {
+        if !self.runtime.events.is_enabled() {
+            return None;
+        }
+        Some(ExecAudit {
+            execution_id: None,
+            command: command.command.clone(),
+            args: command.args.clone(),
+            working_dir: command.working_dir.clone(),
+            tty: command.tty,
+            detached,
+            error: None,
+        })
+    }
+
+    fn emit_exec_event(&self, audit: Option<ExecAudit>, outcome: Result<&str, &BoxliteError>) {
+        let Some(mut audit) = audit else {
+            return;
+        };
+        match outcome {
+            Ok(execution_id) => audit.execution_id = Some(execution_id.to_string()),
+            Err(e) => audit.error = Some(e.to_string()),
+        }
+        self.runtime.events.emit(|| RuntimeEvent {
+            exec: Some(audit),
+            ..RuntimeEvent::new(EventKind::Exec, self.info())
+        });
     }
 
     pub(crate) async fn metrics(&self) -> BoxliteResult<BoxMetrics> {
@@ -264,38 +703,138 @@ impl BoxImpl {
         }
 
         let live = self.live_state().await?;
-        let handler = live
-            .handler
-            .lock()
-            .map_err(|e| BoxliteError::Internal(format!("handler lock poisoned: {}", e)))?;
-        let raw = handler.metrics()?;
-
-        Ok(BoxMetrics::from_storage(
+        let mut metrics = BoxMetrics::from_storage(
             &live.metrics,
-            raw.cpu_percent,
-            raw.memory_bytes,
+            live.gauges.cpu_percent(),
+            live.gauges.memory_bytes(),
             None,
             None,
             None,
             None,
-        ))
+        );
+        metrics.execs_in_flight = self.exec_slots.in_flight();
+
+        // Cgroup counters exist only when the container has cgroup limits
+        let has_limits =
+            self.config.options.cpus.is_some() || self.config.options.memory_mib.is_some();
+        let has_swap = self.config.options.swap_mib.is_some();
+        if (has_limits || has_swap) && self.agent_supports(capabilities::CONTAINER_STATS) {
+            let mut container = live.guest_session.container().await?;
+            match container.stats(self.container_id()).await {
+                Ok(stats) => {
+                    if let Some(cgroup) = stats.cgroup {
+                        metrics.set_cgroup_stats(cgroup);
+                    }
+                    if has_swap {
+                        metrics.set_swap_stats(stats.swap_used_bytes, stats.swap_out_bytes);
+                    }
+                }
+                Err(e) => {
+                    tracing::debug!(box_id = %self.config.id, error = %e, "Failed to get container cgroup stats")
+                }
+            }
+        }
+
+        Ok(metrics)
+    }
+
+    /// Sample the VM's CPU and memory usage until the box stops.
+    ///
+    /// `metrics()` reads the last sample, so it never refreshes the process
+    /// table itself.
+    fn sample_gauges(&self, live: &LiveState) {
+        let handler = Arc::clone(&live.handler);
+        let gauges = Arc::clone(&live.gauges);
+        let shutdown_token = self.shutdown_token.clone();
+        let box_id = self.config.id.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(GAUGE_SAMPLE_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = shutdown_token.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
+                let sample = match handler.lock() {
+                    Ok(handler) => handler.metrics(),
+                    Err(_) => break,
+                };
+                match sample {
+                    Ok(sample) => gauges.store(&sample),
+                    Err(e) => {
+                        tracing::debug!(box_id = %box_id, "Failed to sample VM gauges: {}", e)
+                    }
+                }
+            }
+        });
+    }
+
+    /// Executions started through this handle that haven't exited.
+    pub(crate) fn execs_in_flight(&self) -> u64 {
+        self.exec_slots.in_flight()
+    }
+
+    /// Zero this box's exec and I/O counters; runtime-wide totals are kept.
+    pub(crate) fn reset_metrics(&self) -> BoxliteResult<()> {
+        self.check_not_stopped()?;
+        // Without live state nothing has been counted yet
+        if let Some(live) = self.live.get() {
+            live.metrics.reset();
+        }
+        Ok(())
     }
 
     pub(crate) async fn stop(&self) -> BoxliteResult<()> {
+        let was_running = self.state.read().status().is_running();
+        self.stop_alone().await?;
+        if was_running && self.config.options.stop_dependencies {
+            self.stop_dependencies().await;
+        }
+        Ok(())
+    }
+
+    /// Move a running box to Stopping, so that other processes see the stop
+    /// in progress.
+    fn mark_stopping(&self) {
+        let mut state = self.state.write();
+        if !state.status().can_stop()
+            || state
+                .transition_with_reason(BoxStatus::Stopping, "stopping")
+                .is_err()
+        {
+            return;
+        }
+        if state.lock_id.is_some()
+            && let Err(e) = self.runtime.box_manager.save_box(&self.config.id, &state)
+        {
+            tracing::warn!(box_id = %self.config.id, "Failed to save box status: {}", e);
+        }
+    }
+
+    /// Stop the box without stopping its dependencies.
+    async fn stop_alone(&self) -> BoxliteResult<()> {
         // Early exit if already stopped (idempotent, prevents double-counting)
         // Note: We check status, not shutdown_token, because the token may be cancelled
         // by runtime.shutdown() before stop() is called on each box.
-        if self.state.read().status == BoxStatus::Stopped {
+        if self.state.read().status() == BoxStatus::Stopped {
             return Ok(());
         }
 
+        if self.live.get().is_some() {
+            self.run_hooks(HookEvent::PreStop, &self.runtime.hooks.pre_stop)
+                .await;
+        }
+
         // Cancel the token - signals all in-flight operations to abort
         self.shutdown_token.cancel();
 
+        let unresponsive = self.state.read().status() == BoxStatus::Unresponsive;
+        self.mark_stopping();
+
         // Only try to stop VM if LiveState exists
         if let Some(live) = self.live.get() {
-            // Gracefully shut down guest
-            if let Ok(mut guest) = live.guest_session.guest().await {
+            // Gracefully shut down guest, unless it already stopped answering
+            if !unresponsive && let Ok(mut guest) = live.guest_session.guest().await {
                 let _ = guest.shutdown().await;
             }
 
@@ -303,6 +842,8 @@ impl BoxImpl {
             if let Ok(mut handler) = live.handler.lock() {
                 handler.stop()?;
             }
+
+            self.release_plugin_resources().await;
         }
 
         // Clean up PID file (single source of truth)
@@ -323,14 +864,15 @@ impl BoxImpl {
             );
         }
 
+        self.runtime.reservations.release(&self.config.id);
+
         // Check if box was persisted
         let was_persisted = self.state.read().lock_id.is_some();
 
         // Update state
         {
             let mut state = self.state.write();
-            state.set_status(BoxStatus::Stopped);
-            state.set_pid(None);
+            state.mark_stop_with_reason("stopped");
 
             if was_persisted {
                 // Box was persisted - sync to DB
@@ -349,7 +891,7 @@ impl BoxImpl {
                 }
             } else {
                 // Box was never started - persist now so it survives restarts
-                self.runtime.box_manager.add_box(&self.config, &state)?;
+                self.runtime.box_manager.add_box(&self.config, &mut state)?;
             }
         }
 
@@ -358,6 +900,9 @@ impl BoxImpl {
             .invalidate_box_impl(self.id(), self.config.name.as_deref());
 
         tracing::info!("Stopped box {}", self.id());
+        self.runtime
+            .events
+            .emit(|| RuntimeEvent::new(EventKind::BoxStopped, self.info()));
 
         // Increment runtime-wide stopped counter
         self.runtime
@@ -365,8 +910,14 @@ impl BoxImpl {
             .boxes_stopped
             .fetch_add(1, Ordering::Relaxed);
 
+        self.leave_networks().await;
+
         if self.config.options.auto_remove {
-            self.runtime.remove_box(self.id(), false)?;
+            let runtime = Arc::clone(&self.runtime);
+            let box_id = self.id().clone();
+            tokio::task::spawn_blocking(move || runtime.remove_box(&box_id, false))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
         }
 
         Ok(())
@@ -399,6 +950,7 @@ impl BoxImpl {
 
         // Ensure box is running
         let live = self.live_state().await?;
+        self.require_capability(capabilities::FILES, "copying files")?;
 
         if host_src.is_dir() {
             opts.validate_for_dir()?;
@@ -448,6 +1000,7 @@ impl BoxImpl {
 
         // Ensure box is running
         let live = self.live_state().await?;
+        self.require_capability(capabilities::FILES, "copying files")?;
 
         if container_src.is_empty() {
             return Err(BoxliteError::Config("source path cannot be empty".into()));
@@ -475,13 +1028,420 @@ impl BoxImpl {
         Ok(())
     }
 
+    /// Files service of the running box, for workspace sync.
+    pub(crate) async fn sync_files(&self) -> BoxliteResult<FilesInterface> {
+        self.check_not_stopped()?;
+        let live = self.live_state().await?;
+        self.require_capability(capabilities::FILE_SYNC, "syncing files")?;
+        live.guest_session.files().await
+    }
+
+    /// Files changed in the container rootfs since `image`, by default the
+    /// box's own image.
+    pub(crate) async fn rootfs_diff(&self, image: Option<&str>) -> BoxliteResult<ImageDiff> {
+        self.check_not_stopped()?;
+        let live = self.live_state().await?;
+        self.require_capability(capabilities::FILE_SYNC, "diffing the rootfs")?;
+
+        let image_manager = &self.runtime.image_manager;
+        let image = match (image, &self.config.options.rootfs) {
+            (Some(image_ref), _) => image_manager.cached_image(image_ref).await?,
+            (None, RootfsSpec::Image(image_ref)) => image_manager.cached_image(image_ref).await?,
+            (None, RootfsSpec::Builtin) => image_manager.cached_image(images::BUILTIN).await?,
+            (None, RootfsSpec::RootfsPath(path)) => {
+                image_manager
+                    .load_from_local(path.into(), format!("local:{}", path))
+                    .await?
+            }
+            (None, RootfsSpec::DiskImage(path, _)) => {
+                return Err(BoxliteError::InvalidArgument(format!(
+                    "box was created from disk image {}, not an image; name one to diff against",
+                    path
+                )));
+            }
+        };
+        let from = image_tree(&image).await?;
+
+        let rootfs: FileTree = live
+            .guest_session
+            .files()
+            .await?
+            .list_tree("/", Some(self.container_id()))
+            .await?
+            .into_iter()
+            .map(|entry| {
+                let meta = FileMeta {
+                    size: entry.size,
+                    mtime: entry.mtime,
+                    link_target: entry.link_target,
+                    mode: entry.mode,
+                    uid: entry.uid,
+                    gid: entry.gid,
+                    layer: None,
+                };
+                (entry.path, meta)
+            })
+            .collect();
+        Ok(diff_trees(&from, &rootfs))
+    }
+
+    // ========================================================================
+    // NETWORKS (internal)
+    // ========================================================================
+
+    /// Write the box's network entries and add it to those of the running
+    /// boxes it shares a network with.
+    ///
+    /// Failures are logged; the entries catch up on the next start or stop
+    /// on the network.
+    async fn join_networks(&self, live: &LiveState) {
+        if self.config.options.networks.is_empty() {
+            return;
+        }
+        let boxes = self.runtime.refresh_networks(&self.config).await;
+        if boxes.is_empty() {
+            return;
+        }
+        let entries = networks::host_entries(&networks::peers(&self.config, &boxes));
+        if let Err(e) = self.write_network_hosts(live, &entries).await {
+            tracing::warn!(box_id = %self.config.id, "Failed to write network hosts: {}", e);
+        }
+    }
+
+    /// Answer the box's connection prompts while it runs in this runtime.
+    ///
+    /// Also started when attaching to a running box: a runtime still
+    /// answering keeps the socket, one that is gone is taken over. Without
+    /// either, gvproxy denies connections to new destinations.
+    fn serve_connection_prompts(&self) {
+        if !self.config.options.prompt_connections {
+            return;
+        }
+        let runtime = Arc::clone(&self.runtime);
+        let info = self.info();
+        let server = PromptServer {
+            socket_path: self
+                .config
+                .ready_socket_path
+                .with_file_name(sockets::PROMPT_SOCKET),
+            box_home: self.config.box_home.clone(),
+            box_id: self.config.id.to_string(),
+            box_name: self.config.name.clone(),
+            prompt: self.runtime.connection_prompt.clone(),
+            on_decision: Box::new(move |audit| {
+                runtime.events.emit(|| RuntimeEvent {
+                    connection: Some(audit),
+                    ..RuntimeEvent::new(EventKind::Connection, info.clone())
+                })
+            }),
+        };
+        if let Err(e) = server.spawn(self.shutdown_token.clone()) {
+            tracing::warn!(box_id = %self.config.id, "New connections will be denied: {}", e);
+        }
+    }
+
+    /// Remove the stopped box from the entries of the boxes it shares a
+    /// network with.
+    async fn leave_networks(&self) {
+        if self.runtime.shutdown_token.is_cancelled() {
+            return;
+        }
+        self.runtime.refresh_networks(&self.config).await;
+    }
+
+    /// Replace the peer names the box's DNS resolver answers for, attaching
+    /// to it if another process started it. Boxes not running are skipped.
+    pub(crate) async fn set_network_hosts(
+        &self,
+        entries: &[(String, String)],
+    ) -> BoxliteResult<()> {
+        let live = match self.live.get() {
+            Some(live) => live,
+            None if self.state.read().status().is_running() => Box::pin(self.live_state()).await?,
+            None => return Ok(()),
+        };
+        self.write_network_hosts(live, entries).await
+    }
+
+    async fn write_network_hosts(
+        &self,
+        live: &LiveState,
+        entries: &[(String, String)],
+    ) -> BoxliteResult<()> {
+        self.require_capability(capabilities::UPDATE_HOSTS, "network name resolution")?;
+        live.guest_session
+            .container()
+            .await?
+            .update_hosts(self.container_id(), entries)
+            .await
+    }
+
     // ========================================================================
     // LIVE STATE INITIALIZATION (internal)
     // ========================================================================
 
-    /// Get LiveState, lazily initializing it if needed.
+    /// Run lifecycle hooks whose failures are only logged.
+    async fn run_hooks(&self, event: HookEvent, hook_list: &[HookSpec]) {
+        if hook_list.is_empty() {
+            return;
+        }
+        let hook_list = hook_list.to_vec();
+        let info = self.info();
+        let _ =
+            tokio::task::spawn_blocking(move || hooks::run_hooks_logged(event, &hook_list, &info))
+                .await;
+    }
+
+    /// Unmount driver volumes and disconnect the driver network, if any.
+    async fn release_plugin_resources(&self) {
+        if !plugins::uses_drivers(&self.config.options) {
+            return;
+        }
+        let runtime = Arc::clone(&self.runtime);
+        let box_id = self.config.id.to_string();
+        let options = self.config.options.clone();
+        let _ =
+            tokio::task::spawn_blocking(move || runtime.plugins.release(&box_id, &options)).await;
+    }
+
+    /// Get LiveState, lazily initializing it (after starting dependencies) if needed.
     async fn live_state(&self) -> BoxliteResult<&LiveState> {
-        self.live.get_or_try_init(|| self.init_live_state()).await
+        self.init_live(true).await
+    }
+
+    /// Get LiveState, initializing it if needed. A box this starts (rather
+    /// than attaches to) then joins its networks and runs its post_start
+    /// hooks.
+    ///
+    /// Joining happens outside the initialization, so that boxes starting
+    /// together can update each other's entries.
+    async fn init_live(&self, with_dependencies: bool) -> BoxliteResult<&LiveState> {
+        let started = AtomicBool::new(false);
+        let live = self
+            .live
+            .get_or_try_init(|| async {
+                if with_dependencies {
+                    self.start_dependencies().await?;
+                }
+                started.store(!self.state.read().status().is_running(), Ordering::Relaxed);
+                let live = self.init_live_state().await?;
+                self.serve_connection_prompts();
+                Ok::<_, BoxliteError>(live)
+            })
+            .await?;
+        if started.load(Ordering::Relaxed) {
+            self.join_networks(live).await;
+            // Outside the initialization, so hooks can use the box
+            self.run_hooks(HookEvent::PostStart, &self.runtime.hooks.post_start)
+                .await;
+        }
+        Ok(live)
+    }
+
+    /// Fail with `Unsupported` if the box's guest agent is known to lack
+    /// `capability`, instead of letting the RPC fail as unimplemented.
+    fn require_capability(&self, capability: &str, operation: &str) -> BoxliteResult<()> {
+        let state = self.state.read();
+        match &state.guest_agent {
+            Some(agent) if !agent.supports(capability) => Err(BoxliteError::Unsupported(format!(
+                "{} needs a newer guest agent than box {} runs (version {}); \
+                 recreate the box to use the current guest rootfs",
+                operation, self.config.id, agent.version
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the box's guest agent serves `capability`, assuming it does
+    /// when the agent is not known yet.
+    fn agent_supports(&self, capability: &str) -> bool {
+        self.state
+            .read()
+            .guest_agent
+            .as_ref()
+            .is_none_or(|agent| agent.supports(capability))
+    }
+
+    fn check_command_capabilities(&self, command: &BoxCommand) -> BoxliteResult<()> {
+        if command.tty {
+            self.require_capability(capabilities::PTY, "a TTY execution")?;
+        }
+        if !command.fds.is_empty() {
+            self.require_capability(capabilities::RELAY_FD, "passing file descriptors")?;
+        }
+        // Older agents drop the request fields they don't know
+        if command.output_log.is_some() {
+            self.require_capability(capabilities::EXEC_OUTPUT_LOG, "recording output logs")?;
+        }
+        if command.limits.is_some() {
+            self.require_capability(capabilities::EXEC_LIMITS, "execution limits")?;
+        }
+        if command.create_workdir {
+            self.require_capability(capabilities::CREATE_WORKDIR, "creating the workdir")?;
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of a heartbeat, moving the box between Running
+    /// and Unresponsive. Returns whether the status changed.
+    pub(crate) fn set_responsive(&self, responsive: bool) -> bool {
+        let (from, to, kind, reason) = if responsive {
+            (
+                BoxStatus::Unresponsive,
+                BoxStatus::Running,
+                EventKind::BoxResponsive,
+                "heartbeat answered",
+            )
+        } else {
+            (
+                BoxStatus::Running,
+                BoxStatus::Unresponsive,
+                EventKind::BoxUnresponsive,
+                "heartbeat missed",
+            )
+        };
+        {
+            let mut state = self.state.write();
+            if state.status() != from || state.transition_with_reason(to, reason).is_err() {
+                return false;
+            }
+            if let Err(e) = self.runtime.box_manager.save_box(&self.config.id, &state) {
+                tracing::warn!(box_id = %self.config.id, "Failed to save box status: {}", e);
+            }
+        }
+        self.runtime
+            .events
+            .emit(|| RuntimeEvent::new(kind, self.info()));
+        true
+    }
+
+    /// Start `depends_on` boxes, transitively and in dependency order.
+    async fn start_dependencies(&self) -> BoxliteResult<()> {
+        if self.config.options.depends_on.is_empty() || self.state.read().status().is_running() {
+            return Ok(());
+        }
+
+        for dep_id in self.dependency_order().await? {
+            let dep = self.runtime.get(dep_id.as_str()).await?.ok_or_else(|| {
+                BoxliteError::NotFound(format!("dependency {} of box {}", dep_id, self.config.id))
+            })?;
+            tracing::info!(box_id = %self.config.id, dependency = %dep_id, "Starting dependency");
+            // Boxed: starting a box can start its dependencies
+            Box::pin(dep.inner.start_alone()).await?;
+        }
+        Ok(())
+    }
+
+    /// Stop dependencies no other running box needs, dependents first.
+    ///
+    /// Failures are logged; this box has already stopped.
+    async fn stop_dependencies(&self) {
+        let order = match self.dependency_order().await {
+            Ok(order) => order,
+            Err(e) => {
+                tracing::warn!(box_id = %self.config.id, "Not stopping dependencies: {}", e);
+                return;
+            }
+        };
+
+        for dep_id in order.into_iter().rev() {
+            let in_use = self.dependents(&dep_id).await.map(|dependents| {
+                dependents
+                    .iter()
+                    .any(|(id, status)| id != self.id() && status.is_running())
+            });
+            match in_use {
+                Ok(false) => {}
+                Ok(true) => continue,
+                Err(e) => {
+                    tracing::warn!(dependency = %dep_id, "Failed to check dependents: {}", e);
+                    continue;
+                }
+            }
+            let result = match self.runtime.get(dep_id.as_str()).await {
+                Ok(Some(dep)) => dep.inner.stop_alone().await,
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!(
+                    box_id = %self.config.id,
+                    dependency = %dep_id,
+                    "Failed to stop dependency: {}",
+                    e
+                );
+            }
+        }
+    }
+
+    async fn dependency_order(&self) -> BoxliteResult<Vec<BoxID>> {
+        let runtime = Arc::clone(&self.runtime);
+        let box_id = self.config.id.clone();
+        tokio::task::spawn_blocking(move || runtime.dependency_order(&box_id))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+    }
+
+    async fn dependents(&self, box_id: &BoxID) -> BoxliteResult<Vec<(BoxID, BoxStatus)>> {
+        let runtime = Arc::clone(&self.runtime);
+        let box_id = box_id.clone();
+        tokio::task::spawn_blocking(move || runtime.dependents(&box_id))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+    }
+
+    /// Initialize LiveState, reserving the box's CPUs and memory first.
+    ///
+    /// Reattached boxes already hold their reservation from recovery; if
+    /// their VM has died since, it is released and the box recorded stopped.
+    async fn init_live_state(&self) -> BoxliteResult<LiveState> {
+        let is_reattach = self.state.read().status().is_running();
+        if !is_reattach {
+            self.runtime
+                .reservations
+                .reserve(&self.config.id, Resources::of(&self.config))?;
+        }
+
+        let result = self.build_live_state().await;
+        if result.is_err() {
+            if !is_reattach {
+                self.runtime.reservations.release(&self.config.id);
+            } else if !self.vm_alive() {
+                self.record_vm_gone().await;
+            }
+        }
+        result
+    }
+
+    /// Whether the box's recorded VM process is still running.
+    fn vm_alive(&self) -> bool {
+        let pid = self.state.read().pid;
+        pid.is_some_and(|pid| {
+            crate::util::is_process_alive(pid)
+                && crate::util::is_same_process(pid, self.config.id.as_str())
+        })
+    }
+
+    /// Record a box whose VM died while it was recorded as running, freeing
+    /// what the running VM held.
+    async fn record_vm_gone(&self) {
+        self.runtime.reservations.release(&self.config.id);
+        {
+            let mut state = self.state.write();
+            state.mark_stop_with_reason("process gone at reattach");
+            if let Err(e) = self.runtime.box_manager.save_box(&self.config.id, &state) {
+                tracing::warn!(
+                    box_id = %self.config.id,
+                    error = %e,
+                    "Failed to record the box as stopped"
+                );
+            }
+        }
+        tracing::warn!(box_id = %self.config.id, "Box process died, marked as Stopped");
+        self.runtime
+            .events
+            .emit(|| RuntimeEvent::new(EventKind::BoxStopped, self.info()));
+        self.leave_networks().await;
     }
 
     /// Initialize LiveState via BoxBuilder.
@@ -493,19 +1453,23 @@ impl BoxImpl {
     ///
     /// Note: Lock is allocated in create(), not here. DB persistence also
     /// happens in create().
-    async fn init_live_state(&self) -> BoxliteResult<LiveState> {
+    async fn build_live_state(&self) -> BoxliteResult<LiveState> {
         use super::BoxBuilder;
         use crate::util::read_pid_file;
         use std::sync::Arc;
 
         let state = self.state.read().clone();
-        let is_first_start = state.status == BoxStatus::Configured;
+        let is_first_start = state.status() == BoxStatus::Configured;
+        let is_reattach = state.status().is_running();
+        // Refuse before spawning a VM that couldn't be recorded as running
+        state.check_transition(BoxStatus::Running)?;
 
         // Retrieve the lock (allocated in create())
         let lock_id = state.lock_id.ok_or_else(|| {
             BoxliteError::Internal(format!(
                 "box {} is missing lock_id (status: {:?})",
-                self.config.id, state.status
+                self.config.id,
+                state.status()
             ))
         })?;
         let locker = self.runtime.lock_manager.retrieve(lock_id)?;
@@ -525,7 +1489,16 @@ impl BoxImpl {
         // operations succeed. If any operation fails, the guard's Drop will
         // cleanup the VM process and directory.
         let builder = BoxBuilder::new(Arc::clone(&self.runtime), self.config.clone(), state)?;
-        let (live_state, mut cleanup_guard) = builder.build().await?;
+        let (live_state, mut cleanup_guard) = match builder.build().await {
+            Ok(built) => built,
+            Err(e) => {
+                // Undo driver volume mounts and network setup of the failed start
+                if !is_reattach {
+                    self.release_plugin_resources().await;
+                }
+                return Err(e);
+            }
+        };
 
         // Read PID from file (single source of truth) and update state.
         //
@@ -547,7 +1520,17 @@ impl BoxImpl {
 
             let mut state = self.state.write();
             state.set_pid(Some(pid));
-            state.set_status(BoxStatus::Running);
+            state.transition_with_reason(
+                BoxStatus::Running,
+                if is_reattach { "reattached" } else { "started" },
+            )?;
+            // Reattaching doesn't boot anything; keep the last start's timings
+            if !is_reattach {
+                state.timings = Some(live_state.metrics.timings());
+            }
+            if live_state.guest_agent.is_some() {
+                state.guest_agent = live_state.guest_agent.clone();
+            }
 
             // Save to DB (cache for queries and recovery)
             self.runtime.box_manager.save_box(&self.config.id, &state)?;
@@ -562,12 +1545,36 @@ impl BoxImpl {
         // All operations succeeded - disarm the cleanup guard
         cleanup_guard.disarm();
 
+        self.runtime.watch_heartbeats(
+            self.config.id.clone(),
+            &self.config.options,
+            live_state.guest_session.clone(),
+            self.shutdown_token.clone(),
+        );
+        self.sample_gauges(&live_state);
+        if self.config.options.swap_mib.is_some()
+            && self.agent_supports(capabilities::CONTAINER_STATS)
+        {
+            self.runtime.watch_swap(
+                self.config.id.clone(),
+                self.container_id().to_string(),
+                live_state.guest_session.clone(),
+                self.shutdown_token.clone(),
+            );
+        }
+
         tracing::info!(
             box_id = %self.config.id,
             "Box started successfully (first_start={})",
             is_first_start
         );
 
+        if !is_reattach {
+            self.runtime
+                .events
+                .emit(|| RuntimeEvent::new(EventKind::BoxStarted, self.info()));
+        }
+
         // Lock is automatically released when _guard drops
         Ok(live_state)
     }
@@ -582,6 +1589,7 @@ fn build_tar_from_host(
     let tar_path = tar_path.to_path_buf();
     let follow = opts.follow_symlinks;
     let include_parent = opts.include_parent;
+    let respect_ignore_files = opts.respect_ignore_files;
 
     tokio::task::block_in_place(|| {
         let tar_file = std::fs::File::create(&tar_path).map_err(|e| {
@@ -602,9 +1610,12 @@ fn build_tar_from_host(
             } else {
                 std::ffi::OsStr::new(".").to_owned()
             };
-            builder
-                .append_dir_all(base, &src)
-                .map_err(|e| BoxliteError::Storage(format!("failed to archive dir: {}", e)))?;
+            let archived = if respect_ignore_files {
+                append_unignored(&mut builder, std::path::Path::new(&base), &src, follow)
+            } else {
+                builder.append_dir_all(base, &src)
+            };
+            archived.map_err(|e| BoxliteError::Storage(format!("failed to archive dir: {}", e)))?;
         } else {
             let name = src
                 .file_name()
@@ -620,6 +1631,29 @@ fn build_tar_from_host(
     })
 }
 
+/// Like `append_dir_all`, leaving out what the ignore files in `src` exclude.
+fn append_unignored(
+    builder: &mut tar::Builder<std::fs::File>,
+    base: &std::path::Path,
+    src: &std::path::Path,
+    follow: bool,
+) -> std::io::Result<()> {
+    for entry in walk_unignored(src, follow) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let rel = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(std::io::Error::other)?;
+        let name = base.join(rel);
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            builder.append_dir(&name, entry.path())?;
+        } else {
+            builder.append_path_with_name(entry.path(), &name)?;
+        }
+    }
+    Ok(())
+}
+
 fn extract_tar_to_host(
     tar_path: &std::path::Path,
     dest: &std::path::Path,
@@ -681,4 +1715,33 @@ mod tests {
             assert_eq!(data, "hello");
         });
     }
+
+    #[test]
+    fn tar_respects_ignore_files() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let tmp = TempDir::new().unwrap();
+            let src_dir = tmp.path().join("src");
+            std::fs::create_dir_all(src_dir.join("node_modules")).unwrap();
+            std::fs::write(src_dir.join("node_modules/dep.js"), b"").unwrap();
+            std::fs::write(src_dir.join("app.js"), b"").unwrap();
+            std::fs::write(src_dir.join(".boxliteignore"), b"node_modules\n").unwrap();
+
+            let tar_path = tmp.path().join("out.tar");
+            let opts = CopyOptions::default().respect_ignore_files(true);
+            build_tar_from_host(&src_dir, &tar_path, &opts).unwrap();
+
+            let dest_dir = tmp.path().join("dest");
+            std::fs::create_dir(&dest_dir).unwrap();
+            extract_tar_to_host(&tar_path, &dest_dir, true).unwrap();
+
+            assert!(dest_dir.join("src/app.js").exists());
+            assert!(!dest_dir.join("src/node_modules").exists());
+        });
+    }
 }
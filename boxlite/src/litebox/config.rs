@@ -1,9 +1,11 @@
 use crate::BoxID;
+use crate::disk::DiskFormat;
 use crate::runtime::types::ContainerID;
 use boxlite_shared::Transport;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Container runtime configuration.
 ///
@@ -28,6 +30,9 @@ pub struct BoxConfig {
     pub name: Option<String>,
     /// Creation timestamp (UTC).
     pub created_at: DateTime<Utc>,
+    /// Project (namespace) the box belongs to.
+    #[serde(default = "crate::runtime::constants::projects::default_project")]
+    pub project: String,
 
     // === Container Configuration ===
     /// Container configuration (id).
@@ -38,6 +43,11 @@ pub struct BoxConfig {
     /// These are preserved to allow proper restart with the same configuration.
     pub options: crate::runtime::options::BoxOptions,
 
+    /// Container rootfs storage driver, fixed at creation so restarts reuse
+    /// the same on-disk layout.
+    #[serde(default)]
+    pub storage_driver: crate::runtime::options::StorageDriver,
+
     // === Runtime-Generated Configuration ===
     /// VMM engine type.
     pub engine_kind: crate::vmm::VmmKind,
@@ -47,4 +57,175 @@ pub struct BoxConfig {
     pub box_home: PathBuf,
     /// Ready signal socket path.
     pub ready_socket_path: PathBuf,
+    /// Non-fatal issues found at creation (`runtime::warnings`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Disk image the rootfs came from, for `RootfsSpec::DiskImage`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_image: Option<DiskImageSource>,
+}
+
+/// A disk image a box was created from, as found at creation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskImageSource {
+    /// Host path of the image.
+    pub path: PathBuf,
+    /// Format of the image, checked against its header.
+    pub format: DiskFormat,
+    /// File size in bytes.
+    pub size: u64,
+    /// Last modification of the file, to tell if it changed since.
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl DiskImageSource {
+    /// Check that `path` is a `format` disk image and record what it is.
+    pub(crate) fn inspect(path: &str, format: DiskFormat) -> BoxliteResult<Self> {
+        let path = Path::new(path);
+        let metadata = std::fs::metadata(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                BoxliteError::NotFound(format!("disk image {}", path.display()))
+            }
+            _ => BoxliteError::Storage(format!("Failed to read {}: {}", path.display(), e)),
+        })?;
+        if !metadata.is_file() {
+            return Err(BoxliteError::Config(format!(
+                "disk image {} is not a file",
+                path.display()
+            )));
+        }
+
+        let probed = DiskFormat::probe(path).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        if probed != Some(format) {
+            let found = probed.map_or("an unknown format", |probed| probed.as_str());
+            return Err(BoxliteError::Config(format!(
+                "disk image {} is {}, not {}",
+                path.display(),
+                found,
+                format.as_str()
+            )));
+        }
+        if format == DiskFormat::Qcow2 {
+            crate::disk::Qcow2Helper::check_self_contained(path)?;
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            size: metadata.len(),
+            modified_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+        })
+    }
+
+    /// Check the image again before the box uses it, failing if it is no
+    /// longer the file the box was created from: the box's disk only holds
+    /// its changes over the image, which would no longer match.
+    pub(crate) fn verify(&self) -> BoxliteResult<()> {
+        let current = Self::inspect(&self.path.to_string_lossy(), self.format)?;
+        if current.size != self.size || current.modified_at != self.modified_at {
+            return Err(BoxliteError::Config(format!(
+                "disk image {} changed since the box was created (size {} -> {} bytes, modified {} -> {}); \
+                 restore the original image or recreate the box",
+                self.path.display(),
+                self.size,
+                current.size,
+                format_modified(self.modified_at),
+                format_modified(current.modified_at),
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn format_modified(modified_at: Option<DateTime<Utc>>) -> String {
+    modified_at.map_or_else(|| "unknown".to_string(), |at| at.to_rfc3339())
+}
+
+impl BoxConfig {
+    /// When the box expires (`BoxOptions.ttl_secs` after creation).
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let ttl = i64::try_from(self.options.ttl_secs?).ok()?;
+        self.created_at
+            .checked_add_signed(chrono::TimeDelta::try_seconds(ttl)?)
+    }
+
+    /// Image reference, `rootfs:<path>` for a prepared rootfs or
+    /// `disk:<path>` for a disk image.
+    pub fn image(&self) -> String {
+        use crate::runtime::constants::images;
+        use crate::runtime::options::RootfsSpec;
+
+        match &self.options.rootfs {
+            RootfsSpec::Image(r) => r.clone(),
+            RootfsSpec::RootfsPath(p) => format!("rootfs:{}", p),
+            RootfsSpec::Builtin => images::BUILTIN.to_string(),
+            RootfsSpec::DiskImage(p, _) => format!("disk:{}", p),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ext4_image(dir: &Path) -> PathBuf {
+        let mut image = vec![0u8; 4096];
+        image[1080..1082].copy_from_slice(&0xEF53u16.to_le_bytes());
+        let path = dir.join("base.ext4");
+        std::fs::write(&path, image).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_inspect_disk_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = ext4_image(dir.path());
+
+        let source = DiskImageSource::inspect(path.to_str().unwrap(), DiskFormat::Ext4).unwrap();
+        assert_eq!(source.path, path);
+        assert_eq!(source.size, 4096);
+        assert!(source.modified_at.is_some());
+        source.verify().unwrap();
+    }
+
+    #[test]
+    fn test_inspect_format_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = ext4_image(dir.path());
+
+        let err = DiskImageSource::inspect(path.to_str().unwrap(), DiskFormat::Qcow2).unwrap_err();
+        assert!(matches!(err, BoxliteError::Config(_)));
+        assert!(err.to_string().contains("is ext4, not qcow2"), "{err}");
+    }
+
+    #[test]
+    fn test_inspect_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.qcow2");
+
+        let err = DiskImageSource::inspect(path.to_str().unwrap(), DiskFormat::Qcow2).unwrap_err();
+        assert!(matches!(err, BoxliteError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_verify_detects_changed_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = ext4_image(dir.path());
+        let source = DiskImageSource::inspect(path.to_str().unwrap(), DiskFormat::Ext4).unwrap();
+
+        let mut image = std::fs::read(&path).unwrap();
+        image.extend_from_slice(&[0u8; 4096]);
+        std::fs::write(&path, image).unwrap();
+        let err = source.verify().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("changed since the box was created"),
+            "{err}"
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(source.verify(), Err(BoxliteError::NotFound(_))));
+    }
 }
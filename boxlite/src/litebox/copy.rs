@@ -1,5 +1,11 @@
+use std::path::Path;
+
 use crate::BoxliteError;
 
+/// Ignore file read in every directory of a copied workspace, on top of
+/// `.gitignore`. Same syntax as `.gitignore`.
+pub const BOXLITE_IGNORE_FILE: &str = ".boxliteignore";
+
 /// Options controlling copy behavior.
 #[derive(Debug, Clone)]
 pub struct CopyOptions {
@@ -11,6 +17,9 @@ pub struct CopyOptions {
     pub follow_symlinks: bool,
     /// When copying out, include the parent directory in the archive (docker cp semantics).
     pub include_parent: bool,
+    /// Skip paths matched by `.boxliteignore` and `.gitignore` files in the
+    /// copied directory (copies into the box only).
+    pub respect_ignore_files: bool,
 }
 
 impl Default for CopyOptions {
@@ -20,6 +29,7 @@ impl Default for CopyOptions {
             overwrite: true,
             follow_symlinks: false,
             include_parent: true,
+            respect_ignore_files: false,
         }
     }
 }
@@ -45,6 +55,11 @@ impl CopyOptions {
         self
     }
 
+    pub fn respect_ignore_files(mut self, respect: bool) -> Self {
+        self.respect_ignore_files = respect;
+        self
+    }
+
     pub fn validate_for_dir(&self) -> Result<(), BoxliteError> {
         if !self.recursive {
             return Err(BoxliteError::Config(
@@ -54,3 +69,64 @@ impl CopyOptions {
         Ok(())
     }
 }
+
+/// Walk `root` skipping what its `.boxliteignore` and `.gitignore` files
+/// exclude, like git would (nested files, negation, directory patterns).
+///
+/// Only ignore files inside `root` count: not its parents, `.git/info/exclude`
+/// or the global git excludes, so the result doesn't depend on where `root`
+/// is checked out. Hidden files are included. `root` itself comes first.
+pub(crate) fn walk_unignored(root: &Path, follow_symlinks: bool) -> ignore::Walk {
+    ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .ignore(false)
+        .parents(false)
+        .git_global(false)
+        .git_exclude(false)
+        .require_git(false)
+        .follow_links(follow_symlinks)
+        .add_custom_ignore_filename(BOXLITE_IGNORE_FILE)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walked(root: &Path) -> Vec<String> {
+        walk_unignored(root, false)
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let rel = entry.path().strip_prefix(root).ok()?;
+                (!rel.as_os_str().is_empty()).then(|| rel.display().to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_respects_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(root.join("src/target")).unwrap();
+        std::fs::write(root.join("node_modules/pkg/index.js"), "").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("src/target/out"), "").unwrap();
+        std::fs::write(root.join("debug.log"), "").unwrap();
+        std::fs::write(root.join("keep.log"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "node_modules/\n*.log\n!keep.log\n").unwrap();
+        std::fs::write(root.join("src").join(BOXLITE_IGNORE_FILE), "target\n").unwrap();
+
+        assert_eq!(
+            walked(root),
+            [
+                ".gitignore",
+                "keep.log",
+                "src",
+                "src/.boxliteignore",
+                "src/main.rs"
+            ]
+        );
+    }
+}
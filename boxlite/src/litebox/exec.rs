@@ -3,10 +3,14 @@
 //! Type definitions for executing commands in a box.
 //! The actual execution logic is in BoxImpl::exec().
 
-use crate::portal::interfaces::ExecutionInterface;
-use boxlite_shared::errors::BoxliteResult;
+use crate::portal::interfaces::{ExecutionInterface, SeqChunk, StreamEnd};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::{DateTime, Utc};
 use futures::Stream;
+use std::os::fd::OwnedFd;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -33,7 +37,25 @@ pub struct BoxCommand {
     pub(crate) env: Option<Vec<(String, String)>>,
     pub(crate) timeout: Option<Duration>,
     pub(crate) working_dir: Option<String>,
+    pub(crate) create_workdir: bool,
     pub(crate) tty: bool,
+    pub(crate) output_log: Option<ExecLogOptions>,
+    pub(crate) limits: Option<ExecLimits>,
+    pub(crate) fds: Vec<HostFd>,
+    /// Seconds the guest keeps the execution after exit (0 = until the box stops)
+    pub(crate) retention_secs: u64,
+    /// Metric labels; host-side only, never sent to the guest
+    pub(crate) labels: Vec<String>,
+    /// Execution ID asked of the guest (None: the guest picks one)
+    pub(crate) execution_id: Option<String>,
+}
+
+/// A host file descriptor given to a command (see [`BoxCommand::pass_fd`]).
+#[derive(Clone, Debug)]
+pub(crate) struct HostFd {
+    pub(crate) fd: Arc<OwnedFd>,
+    /// fd number in the process
+    pub(crate) guest_fd: u32,
 }
 
 impl BoxCommand {
@@ -45,7 +67,14 @@ impl BoxCommand {
             env: None,
             timeout: None,
             working_dir: None,
+            create_workdir: false,
             tty: false,
+            output_log: None,
+            limits: None,
+            fds: vec![],
+            retention_secs: 0,
+            labels: vec![],
+            execution_id: None,
         }
     }
 
@@ -85,6 +114,16 @@ impl BoxCommand {
         self
     }
 
+    /// Create the working directory (and its parents) if it doesn't exist,
+    /// owned by the user the command runs as.
+    ///
+    /// Without this, a missing working directory fails the exec with
+    /// [`BoxliteError::WorkdirNotFound`] before the process is spawned.
+    pub fn create_workdir(mut self, create: bool) -> Self {
+        self.create_workdir = create;
+        self
+    }
+
     /// Enable TTY (pseudo-terminal) for interactive sessions.
     ///
     /// Terminal size is auto-detected from the current terminal.
@@ -92,6 +131,183 @@ impl BoxCommand {
         self.tty = enable;
         self
     }
+
+    /// Record stdout/stderr to log files in the box directory.
+    ///
+    /// Output is written by the guest even while nobody is attached, so
+    /// detached executions keep their output. Read it back with
+    /// [`LiteBox::execution_logs`](crate::LiteBox::execution_logs).
+    pub fn record_output(mut self, options: ExecLogOptions) -> Self {
+        self.output_log = Some(options);
+        self
+    }
+
+    /// Run under CPU time, wall time and output limits (0 = unlimited).
+    ///
+    /// The guest enforces the limits: the process is killed when one is
+    /// exceeded, output past `max_output_bytes` (stdout and stderr combined)
+    /// is dropped, and [`ExecResult::limit_exceeded`] says which limit hit.
+    pub fn limits(mut self, cpu_secs: u64, wall_secs: u64, max_output_bytes: u64) -> Self {
+        self.limits = Some(ExecLimits {
+            cpu_secs,
+            wall_secs,
+            max_output_bytes,
+        });
+        self
+    }
+
+    /// Count this execution under `label` (e.g. `"step=compile"`) in the box metrics.
+    ///
+    /// Can be called more than once; each label gets its own counters in
+    /// [`BoxMetrics::labeled`](crate::BoxMetrics::labeled). Empty labels are ignored.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        let label = label.into();
+        if !label.is_empty() && !self.labels.contains(&label) {
+            self.labels.push(label);
+        }
+        self
+    }
+
+    /// Give the process a host file descriptor as fd `guest_fd` (3 or higher).
+    ///
+    /// The fd can't cross into the VM, so the guest gives the process a
+    /// stand-in and the data is relayed while the execution is attached:
+    /// - a pipe or connected stream socket becomes a unix socket
+    /// - a listening socket (e.g. for socket activation) becomes a TCP
+    ///   listener on the box's loopback; each connection accepted on
+    ///   `host_fd` is relayed to it
+    ///
+    /// Other fds (regular files, datagram sockets, ...) make `exec()` fail
+    /// with [`BoxliteError::Unsupported`], as does passing fds to
+    /// `exec_detached()`. The command owns `host_fd` from now on.
+    pub fn pass_fd(mut self, host_fd: impl Into<OwnedFd>, guest_fd: u32) -> Self {
+        self.fds.push(HostFd {
+            fd: Arc::new(host_fd.into()),
+            guest_fd,
+        });
+        self
+    }
+}
+
+/// Resource limits for a command (0 = unlimited).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecLimits {
+    /// CPU time in seconds.
+    pub cpu_secs: u64,
+    /// Elapsed time in seconds.
+    pub wall_secs: u64,
+    /// Bytes of stdout and stderr combined.
+    pub max_output_bytes: u64,
+}
+
+/// Execution limit that terminated a process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecLimit {
+    /// Used more CPU time than `cpu_secs`.
+    Cpu,
+    /// Ran longer than `wall_secs`.
+    Wall,
+    /// Produced more than `max_output_bytes`; output was truncated.
+    Output,
+}
+
+/// Size caps for recorded execution output.
+///
+/// Each stream is written to `{name}.log` and rotated to `{name}.log.1`,
+/// `{name}.log.2`, ... when it would exceed `max_file_bytes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecLogOptions {
+    /// Rotate when a file would exceed this many bytes (0 = never rotate).
+    pub max_file_bytes: u64,
+    /// Files kept per stream, including the current one.
+    pub max_files: u32,
+}
+
+impl Default for ExecLogOptions {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 10 * 1024 * 1024,
+            max_files: 3,
+        }
+    }
+}
+
+/// Recorded output files of an execution, oldest first.
+///
+/// The log directory is shared read-write with the guest, so only regular
+/// files are listed; symlinks the guest plants there are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionLogs {
+    pub stdout: Vec<PathBuf>,
+    pub stderr: Vec<PathBuf>,
+}
+
+impl ExecutionLogs {
+    /// Collect the log files in an execution's log directory.
+    pub(crate) fn from_dir(dir: &Path) -> BoxliteResult<Self> {
+        let is_dir = std::fs::symlink_metadata(dir).is_ok_and(|meta| meta.is_dir());
+        if !is_dir {
+            return Err(BoxliteError::NotFound(format!(
+                "no recorded output at {}",
+                dir.display()
+            )));
+        }
+        Ok(Self {
+            stdout: Self::stream_files(dir, "stdout")?,
+            stderr: Self::stream_files(dir, "stderr")?,
+        })
+    }
+
+    /// Open a recorded log file without following symlinks.
+    ///
+    /// Fails unless `path` is a regular file, so a link swapped in by the
+    /// guest after listing can't redirect reads to another host file.
+    pub fn open(path: &Path) -> std::io::Result<std::fs::File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NOFOLLOW | libc::O_NONBLOCK)
+            .open(path)?;
+        if !file.metadata()?.is_file() {
+            return Err(std::io::Error::other(format!(
+                "{} is not a regular file",
+                path.display()
+            )));
+        }
+        Ok(file)
+    }
+
+    fn stream_files(dir: &Path, name: &str) -> BoxliteResult<Vec<PathBuf>> {
+        let current = format!("{}.log", name);
+        let rotated_prefix = format!("{}.", current);
+
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to read {}: {}", dir.display(), e))
+        })?;
+        let mut rotated: Vec<(u32, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                let index = file_name
+                    .to_str()?
+                    .strip_prefix(&rotated_prefix)?
+                    .parse()
+                    .ok()?;
+                Some((index, entry.path()))
+            })
+            .collect();
+        // Highest index is the oldest
+        rotated.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let mut files: Vec<PathBuf> = rotated.into_iter().map(|(_, path)| path).collect();
+        let current = dir.join(current);
+        if std::fs::symlink_metadata(&current).is_ok_and(|meta| meta.is_file()) {
+            files.push(current);
+        }
+        Ok(files)
+    }
 }
 
 /// Handle to a running command execution.
@@ -128,8 +344,10 @@ pub struct Execution {
 
 pub(crate) struct ExecutionInner {
     interface: ExecutionInterface,
-    result_rx: mpsc::UnboundedReceiver<ExecResult>,
+    result_rx: mpsc::UnboundedReceiver<BoxliteResult<ExecResult>>,
     cached_result: Option<ExecResult>,
+    /// Why the wait was cancelled, so later waits fail the same way
+    cancelled: Option<String>,
 
     /// Standard input stream (write-only).
     stdin: Option<ExecStdin>,
@@ -149,7 +367,7 @@ impl Execution {
     pub(crate) fn new(
         execution_id: ExecutionId,
         interface: ExecutionInterface,
-        result_rx: mpsc::UnboundedReceiver<ExecResult>,
+        result_rx: mpsc::UnboundedReceiver<BoxliteResult<ExecResult>>,
         stdin: Option<ExecStdin>,
         stdout: Option<ExecStdout>,
         stderr: Option<ExecStderr>,
@@ -158,6 +376,7 @@ impl Execution {
             interface,
             result_rx,
             cached_result: None,
+            cancelled: None,
             stdin,
             stdout,
             stderr,
@@ -198,10 +417,42 @@ impl Execution {
         })
     }
 
+    /// Take stdout split into lines (can only be called once, shares the
+    /// stream with [`Execution::stdout`]).
+    pub fn stdout_lines(&mut self) -> Option<ExecLines> {
+        self.stdout().map(ExecStdout::lines)
+    }
+
+    /// Take stderr split into lines (can only be called once, shares the
+    /// stream with [`Execution::stderr`]).
+    pub fn stderr_lines(&mut self) -> Option<ExecLines> {
+        self.stderr().map(ExecStderr::lines)
+    }
+
+    /// Take stdout and stderr as one stream, in the order the guest
+    /// produced them (can only be called once).
+    ///
+    /// Returns `None` if either stream was already taken.
+    pub fn output(&mut self) -> Option<ExecOutputStream> {
+        futures::executor::block_on(async {
+            let mut inner = self.inner.lock().await;
+            if inner.stdout.is_none() || inner.stderr.is_none() {
+                return None;
+            }
+            Some(ExecOutputStream::new(
+                inner.stdout.take()?,
+                inner.stderr.take()?,
+            ))
+        })
+    }
+
     /// Wait for the execution to complete.
     ///
     /// Returns the exit status once the execution finishes. If the result is
     /// already cached, returns immediately. Otherwise, waits for result from channel.
+    ///
+    /// Fails with [`BoxliteError::Cancelled`] if the box stopped or the
+    /// runtime shut down before the execution exited.
     pub async fn wait(&mut self) -> BoxliteResult<ExecResult> {
         let mut inner = self.inner.lock().await;
 
@@ -209,19 +460,53 @@ impl Execution {
         if let Some(result) = &inner.cached_result {
             return Ok(result.clone());
         }
+        if let Some(reason) = &inner.cancelled {
+            return Err(BoxliteError::Cancelled(reason.clone()));
+        }
+
+        // Try to receive from result channel (non-blocking), else await it
+        let status = match inner.result_rx.try_recv() {
+            Ok(status) => status,
+            Err(_) => inner
+                .result_rx
+                .recv()
+                .await
+                .ok_or_else(|| BoxliteError::Internal("Result channel closed".into()))?,
+        };
+        match status {
+            Ok(status) => {
+                inner.cached_result = Some(status.clone());
+                Ok(status)
+            }
+            Err(BoxliteError::Cancelled(reason)) => {
+                inner.cancelled = Some(reason.clone());
+                Err(BoxliteError::Cancelled(reason))
+            }
+            Err(e) => Err(e),
+        }
+    }
 
-        // Try to receive from result channel (non-blocking)
-        if let Ok(status) = inner.result_rx.try_recv() {
-            inner.cached_result = Some(status.clone());
-            return Ok(status);
+    /// Read stdout and stderr to the end, then wait for the process to exit.
+    ///
+    /// Streams that were already taken read as empty.
+    pub async fn collect_output(&mut self) -> BoxliteResult<CollectedOutput> {
+        use futures::StreamExt;
+
+        async fn read<S: Stream<Item = String> + Unpin>(stream: Option<S>) -> String {
+            match stream {
+                Some(stream) => stream.collect::<Vec<_>>().await.concat(),
+                None => String::new(),
+            }
         }
 
-        // Await next result
-        let status = inner.result_rx.recv().await.ok_or_else(|| {
-            boxlite_shared::BoxliteError::Internal("Result channel closed".into())
-        })?;
-        inner.cached_result = Some(status.clone());
-        Ok(status)
+        let (stdout, stderr) = (self.stdout(), self.stderr());
+        let (stdout, stderr) = tokio::join!(read(stdout), read(stderr));
+        let result = self.wait().await?;
+        Ok(CollectedOutput {
+            stdout,
+            stderr,
+            result,
+        })
     }
 
     /// Kill the process (sends SIGKILL).
@@ -244,6 +529,17 @@ impl Execution {
     }
 }
 
+/// Output of an execution run to completion, from
+/// [`Execution::collect_output`].
+#[derive(Clone, Debug)]
+pub struct CollectedOutput {
+    /// Everything the process wrote to stdout.
+    pub stdout: String,
+    /// Everything the process wrote to stderr.
+    pub stderr: String,
+    pub result: ExecResult,
+}
+
 /// Exit status of a process.
 #[derive(Clone, Debug)]
 pub struct ExecResult {
@@ -253,6 +549,8 @@ pub struct ExecResult {
     /// (e.g., container init death causing PID namespace teardown).
     /// None if the process exited normally.
     pub error_message: Option<String>,
+    /// Limit from [`BoxCommand::limits`] that terminated the process, if any.
+    pub limit_exceeded: Option<ExecLimit>,
 }
 
 impl ExecResult {
@@ -266,6 +564,30 @@ impl ExecResult {
     }
 }
 
+/// An execution the guest agent knows about, from [`LiteBox::executions`].
+///
+/// [`LiteBox::executions`]: crate::LiteBox::executions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionInfo {
+    pub id: ExecutionId,
+    pub program: String,
+    pub args: Vec<String>,
+    /// Process ID in the guest
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// Exit code once the process exited; negative signal number if a
+    /// signal terminated it. `None` while running.
+    pub exit_code: Option<i32>,
+    pub exited_at: Option<DateTime<Utc>>,
+}
+
+impl ExecutionInfo {
+    /// Whether the process is still running.
+    pub fn is_running(&self) -> bool {
+        self.exit_code.is_none()
+    }
+}
+
 /// Standard input stream (write-only).
 pub struct ExecStdin {
     sender: Option<mpsc::UnboundedSender<Vec<u8>>>,
@@ -308,12 +630,25 @@ impl ExecStdin {
 
 /// Standard output stream (read-only).
 pub struct ExecStdout {
-    receiver: mpsc::UnboundedReceiver<String>,
+    receiver: mpsc::UnboundedReceiver<SeqChunk>,
+    end: StreamEnd,
 }
 
 impl ExecStdout {
-    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<String>) -> Self {
-        Self { receiver }
+    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<SeqChunk>, end: StreamEnd) -> Self {
+        Self { receiver, end }
+    }
+
+    /// Split into lines instead of chunks as received.
+    pub fn lines(self) -> ExecLines {
+        ExecLines::new(self.receiver, self.end)
+    }
+
+    /// Why the stream ended before the execution's output did:
+    /// [`BoxliteError::Cancelled`] if the box stopped or the runtime shut
+    /// down. `None` while streaming and once the whole output was read.
+    pub fn error(&self) -> Option<BoxliteError> {
+        self.end.error()
     }
 }
 
@@ -321,18 +656,33 @@ impl Stream for ExecStdout {
     type Item = String;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.receiver.poll_recv(cx)
+        self.receiver
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|(_, data)| String::from_utf8_lossy(&data).into_owned()))
     }
 }
 
 /// Standard error stream (read-only).
 pub struct ExecStderr {
-    receiver: mpsc::UnboundedReceiver<String>,
+    receiver: mpsc::UnboundedReceiver<SeqChunk>,
+    end: StreamEnd,
 }
 
 impl ExecStderr {
-    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<String>) -> Self {
-        Self { receiver }
+    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<SeqChunk>, end: StreamEnd) -> Self {
+        Self { receiver, end }
+    }
+
+    /// Split into lines instead of chunks as received.
+    pub fn lines(self) -> ExecLines {
+        ExecLines::new(self.receiver, self.end)
+    }
+
+    /// Why the stream ended before the execution's output did:
+    /// [`BoxliteError::Cancelled`] if the box stopped or the runtime shut
+    /// down. `None` while streaming and once the whole output was read.
+    pub fn error(&self) -> Option<BoxliteError> {
+        self.end.error()
     }
 }
 
@@ -340,6 +690,359 @@ impl Stream for ExecStderr {
     type Item = String;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.receiver.poll_recv(cx)
+        self.receiver
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|(_, data)| String::from_utf8_lossy(&data).into_owned()))
+    }
+}
+
+/// Stdout or stderr of an execution as lines (read-only).
+///
+/// Each item is one line ending with its terminator as the process wrote it
+/// (`\n` or `\r\n`, never translated). Only the last line, when the process
+/// didn't terminate it, and pieces of overlong lines come without one.
+///
+/// Bytes are decoded per line, so UTF-8 sequences split across chunks come
+/// out whole; invalid UTF-8 becomes U+FFFD. A line longer than
+/// [`max_line_len`](Self::max_line_len) is yielded in pieces of at most that
+/// many bytes, cut at character boundaries, so a process that never writes a
+/// newline can't grow the buffer without bound.
+pub struct ExecLines {
+    receiver: mpsc::UnboundedReceiver<SeqChunk>,
+    end: StreamEnd,
+    buf: Vec<u8>,
+    /// Bytes of `buf` known to contain no newline.
+    scanned: usize,
+    max_line_len: usize,
+    closed: bool,
+}
+
+impl ExecLines {
+    /// Default for [`max_line_len`](Self::max_line_len): 1 MiB.
+    pub const DEFAULT_MAX_LINE_LEN: usize = 1024 * 1024;
+
+    fn new(receiver: mpsc::UnboundedReceiver<SeqChunk>, end: StreamEnd) -> Self {
+        Self {
+            receiver,
+            end,
+            buf: Vec::new(),
+            scanned: 0,
+            max_line_len: Self::DEFAULT_MAX_LINE_LEN,
+            closed: false,
+        }
+    }
+
+    /// Set the longest line yielded whole, in bytes including the terminator
+    /// (at least 4, the longest UTF-8 character).
+    pub fn max_line_len(mut self, max: usize) -> Self {
+        self.max_line_len = max.max(4);
+        self
+    }
+
+    /// Why the stream ended before the execution's output did:
+    /// [`BoxliteError::Cancelled`] if the box stopped or the runtime shut
+    /// down. `None` while streaming and once the whole output was read.
+    pub fn error(&self) -> Option<BoxliteError> {
+        self.end.error()
+    }
+
+    /// Split the next complete line (or overlong piece) off the buffer.
+    fn next_line(&mut self) -> Option<String> {
+        let limit = self.buf.len().min(self.max_line_len);
+        if let Some(pos) = self.buf[self.scanned.min(limit)..limit]
+            .iter()
+            .position(|&b| b == b'\n')
+        {
+            return Some(self.take(self.scanned.min(limit) + pos + 1));
+        }
+        if self.buf.len() <= self.max_line_len {
+            self.scanned = self.buf.len();
+            return None;
+        }
+
+        // Overlong: cut before a UTF-8 continuation byte never splits a character
+        let mut cut = self.max_line_len;
+        while cut > 0 && (self.buf[cut] & 0xC0) == 0x80 {
+            cut -= 1;
+        }
+        Some(self.take(if cut == 0 { self.max_line_len } else { cut }))
+    }
+
+    fn take(&mut self, len: usize) -> String {
+        let rest = self.buf.split_off(len);
+        let line = std::mem::replace(&mut self.buf, rest);
+        self.scanned = 0;
+        String::from_utf8(line)
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+    }
+}
+
+impl Stream for ExecLines {
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(line) = self.next_line() {
+                return Poll::Ready(Some(line));
+            }
+            if self.closed {
+                if self.buf.is_empty() {
+                    return Poll::Ready(None);
+                }
+                // Unterminated last line
+                let len = self.buf.len();
+                return Poll::Ready(Some(self.take(len)));
+            }
+            match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some((_, data))) => self.buf.extend_from_slice(&data),
+                Poll::Ready(None) => self.closed = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A chunk of output from [`Execution::output`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputChunk {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+}
+
+impl OutputChunk {
+    /// The raw bytes of the chunk.
+    pub fn data(&self) -> &[u8] {
+        match self {
+            OutputChunk::Stdout(data) | OutputChunk::Stderr(data) => data,
+        }
+    }
+
+    /// Whether the chunk was written to stderr.
+    pub fn is_stderr(&self) -> bool {
+        matches!(self, OutputChunk::Stderr(_))
+    }
+}
+
+/// Stdout and stderr of an execution merged in guest order (read-only).
+///
+/// Chunks are not split or joined, and bytes are passed through as-is.
+pub struct ExecOutputStream {
+    stdout: Option<mpsc::UnboundedReceiver<SeqChunk>>,
+    stderr: Option<mpsc::UnboundedReceiver<SeqChunk>>,
+    next_stdout: Option<SeqChunk>,
+    next_stderr: Option<SeqChunk>,
+    end: StreamEnd,
+}
+
+impl ExecOutputStream {
+    fn new(stdout: ExecStdout, stderr: ExecStderr) -> Self {
+        Self {
+            stdout: Some(stdout.receiver),
+            stderr: Some(stderr.receiver),
+            next_stdout: None,
+            next_stderr: None,
+            end: stdout.end,
+        }
+    }
+
+    /// Why the stream ended before the execution's output did:
+    /// [`BoxliteError::Cancelled`] if the box stopped or the runtime shut
+    /// down. `None` while streaming and once the whole output was read.
+    pub fn error(&self) -> Option<BoxliteError> {
+        self.end.error()
+    }
+
+    /// Receive the next chunk of one side unless one is already waiting.
+    fn fill(
+        receiver: &mut Option<mpsc::UnboundedReceiver<SeqChunk>>,
+        next: &mut Option<SeqChunk>,
+        cx: &mut Context<'_>,
+    ) {
+        if next.is_some() {
+            return;
+        }
+        if let Some(rx) = receiver {
+            match rx.poll_recv(cx) {
+                Poll::Ready(Some(chunk)) => *next = Some(chunk),
+                Poll::Ready(None) => *receiver = None,
+                Poll::Pending => {}
+            }
+        }
+    }
+}
+
+impl Stream for ExecOutputStream {
+    type Item = OutputChunk;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        Self::fill(&mut this.stdout, &mut this.next_stdout, cx);
+        Self::fill(&mut this.stderr, &mut this.next_stderr, cx);
+        // Both sides are fed by one task in order: a stdout chunk older than
+        // the stderr chunk just received has been sent by now.
+        Self::fill(&mut this.stdout, &mut this.next_stdout, cx);
+
+        let stdout_first = match (&this.next_stdout, &this.next_stderr) {
+            (Some((out, _)), Some((err, _))) => out < err,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) if this.stdout.is_none() && this.stderr.is_none() => {
+                return Poll::Ready(None);
+            }
+            (None, None) => return Poll::Pending,
+        };
+        Poll::Ready(if stdout_first {
+            this.next_stdout
+                .take()
+                .map(|(_, data)| OutputChunk::Stdout(data))
+        } else {
+            this.next_stderr
+                .take()
+                .map(|(_, data)| OutputChunk::Stderr(data))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_cut_short_stream_reports_cancelled() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let end = StreamEnd::default();
+        let mut lines = ExecStdout::new(rx, end.clone()).lines();
+        tx.send((0, b"partial\n".to_vec())).unwrap();
+        assert_eq!(lines.next().await.as_deref(), Some("partial\n"));
+        assert!(lines.error().is_none());
+
+        end.cancel("box stopped".into());
+        drop(tx);
+        assert_eq!(lines.next().await, None);
+        assert!(matches!(lines.error(), Some(BoxliteError::Cancelled(_))));
+    }
+
+    #[tokio::test]
+    async fn test_output_stream_keeps_guest_order() {
+        let (stdout_tx, stdout_rx) = mpsc::unbounded_channel();
+        let (stderr_tx, stderr_rx) = mpsc::unbounded_channel();
+        let mut output = ExecOutputStream::new(
+            ExecStdout::new(stdout_rx, StreamEnd::default()),
+            ExecStderr::new(stderr_rx, StreamEnd::default()),
+        );
+
+        // Sent out of order across the two channels
+        stderr_tx.send((1, b"err1".to_vec())).unwrap();
+        stdout_tx.send((0, b"out0".to_vec())).unwrap();
+        stdout_tx.send((2, b"out2\xff".to_vec())).unwrap();
+        stderr_tx.send((3, b"err3".to_vec())).unwrap();
+        drop(stdout_tx);
+        drop(stderr_tx);
+
+        let chunks: Vec<_> = output.by_ref().collect().await;
+        assert_eq!(
+            chunks,
+            [
+                OutputChunk::Stdout(b"out0".to_vec()),
+                OutputChunk::Stderr(b"err1".to_vec()),
+                OutputChunk::Stdout(b"out2\xff".to_vec()),
+                OutputChunk::Stderr(b"err3".to_vec()),
+            ]
+        );
+        assert!(output.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lines_reassemble_chunks() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let lines = ExecStdout::new(rx, StreamEnd::default()).lines();
+
+        // "é" (0xC3 0xA9) split across chunks, CRLF kept, last line unterminated
+        for (seq, chunk) in [&b"caf\xC3"[..], b"\xA9\none", b"\r\ntwo\nthr", b"ee"]
+            .into_iter()
+            .enumerate()
+        {
+            tx.send((seq as u64, chunk.to_vec())).unwrap();
+        }
+        drop(tx);
+
+        let lines: Vec<_> = lines.collect().await;
+        assert_eq!(lines, ["café\n", "one\r\n", "two\n", "three"]);
+    }
+
+    #[tokio::test]
+    async fn test_lines_cut_overlong_at_char_boundary() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let lines = ExecStdout::new(rx, StreamEnd::default())
+            .lines()
+            .max_line_len(5);
+
+        tx.send((0, "abcdéfg\nhi\n".as_bytes().to_vec())).unwrap();
+        drop(tx);
+
+        let lines: Vec<_> = lines.collect().await;
+        assert_eq!(lines, ["abcd", "éfg\n", "hi\n"]);
+    }
+
+    #[test]
+    fn test_execution_logs_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "stdout.log",
+            "stdout.log.1",
+            "stdout.log.2",
+            "stdout.log.10",
+            "stderr.log",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let logs = ExecutionLogs::from_dir(dir.path()).unwrap();
+        let names: Vec<_> = logs
+            .stdout
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "stdout.log.10",
+                "stdout.log.2",
+                "stdout.log.1",
+                "stdout.log"
+            ]
+        );
+        assert_eq!(logs.stderr, vec![dir.path().join("stderr.log")]);
+    }
+
+    #[test]
+    fn test_execution_logs_skip_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("secret");
+        std::fs::write(&secret, "host data").unwrap();
+        let logs_dir = dir.path().join("exec");
+        std::fs::create_dir(&logs_dir).unwrap();
+        std::os::unix::fs::symlink(&secret, logs_dir.join("stdout.log")).unwrap();
+        std::os::unix::fs::symlink(&secret, logs_dir.join("stdout.log.1")).unwrap();
+        std::fs::write(logs_dir.join("stderr.log"), "").unwrap();
+
+        let logs = ExecutionLogs::from_dir(&logs_dir).unwrap();
+        assert!(logs.stdout.is_empty());
+        assert_eq!(logs.stderr, vec![logs_dir.join("stderr.log")]);
+        assert!(ExecutionLogs::open(&logs_dir.join("stdout.log")).is_err());
+        assert!(ExecutionLogs::open(&logs_dir.join("stderr.log")).is_ok());
+
+        let linked_dir = dir.path().join("linked");
+        std::os::unix::fs::symlink(&logs_dir, &linked_dir).unwrap();
+        let err = ExecutionLogs::from_dir(&linked_dir).unwrap_err();
+        assert!(matches!(err, BoxliteError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_execution_logs_missing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = ExecutionLogs::from_dir(&dir.path().join("missing")).unwrap_err();
+        assert!(matches!(err, BoxliteError::NotFound(_)));
     }
 }
@@ -0,0 +1,127 @@
+//! Limit on the executions a box runs at once.
+//!
+//! Each execution holds an [`ExecSlot`] from `exec()` until its process
+//! exits. Slots are counted in this process only; the guest agent has no
+//! limit of its own.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Slots for a box's executions (`BoxOptions::max_concurrent_execs`).
+pub(crate) struct ExecSlots {
+    /// Semaphore and its size; `None` when unlimited
+    limit: Option<(Arc<Semaphore>, u32)>,
+    /// Wait for a slot rather than fail when all are taken
+    queue: bool,
+    in_flight: Arc<AtomicU64>,
+}
+
+impl ExecSlots {
+    pub(crate) fn new(max_concurrent_execs: Option<u32>, queue: bool) -> Self {
+        Self {
+            limit: max_concurrent_execs.map(|max| (Arc::new(Semaphore::new(max as usize)), max)),
+            queue,
+            in_flight: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Take a slot, waiting for one if queueing is enabled.
+    ///
+    /// Fails with `TooManyExecs` when all slots are taken and queueing is off.
+    pub(crate) async fn acquire(&self) -> BoxliteResult<ExecSlot> {
+        let permit = match &self.limit {
+            None => None,
+            Some((semaphore, _)) if self.queue => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| BoxliteError::Internal("exec slots closed".into()))?,
+            ),
+            Some((semaphore, max)) => {
+                Some(semaphore.clone().try_acquire_owned().map_err(|_| {
+                    BoxliteError::TooManyExecs(format!(
+                        "box already runs {} executions (max_concurrent_execs)",
+                        max
+                    ))
+                })?)
+            }
+        };
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        Ok(ExecSlot {
+            _permit: permit,
+            in_flight: self.in_flight.clone(),
+        })
+    }
+
+    /// Executions holding a slot.
+    pub(crate) fn in_flight(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+}
+
+/// A running execution's slot, released on drop.
+pub(crate) struct ExecSlot {
+    _permit: Option<OwnedSemaphorePermit>,
+    in_flight: Arc<AtomicU64>,
+}
+
+impl Drop for ExecSlot {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_limit_without_queue() {
+        let slots = ExecSlots::new(Some(2), false);
+        let first = slots.acquire().await.unwrap();
+        let _second = slots.acquire().await.unwrap();
+        assert_eq!(slots.in_flight(), 2);
+        assert!(matches!(
+            slots.acquire().await,
+            Err(BoxliteError::TooManyExecs(_))
+        ));
+
+        drop(first);
+        assert_eq!(slots.in_flight(), 1);
+        slots.acquire().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queue_waits_for_slot() {
+        let slots = Arc::new(ExecSlots::new(Some(1), true));
+        let first = slots.acquire().await.unwrap();
+
+        let waiter = tokio::spawn({
+            let slots = slots.clone();
+            async move { slots.acquire().await.map(|_| ()) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        drop(first);
+        waiter.await.unwrap().unwrap();
+        assert_eq!(slots.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_counts_in_flight() {
+        let slots = ExecSlots::new(None, false);
+        let held: Vec<_> = futures::future::try_join_all((0..100).map(|_| slots.acquire()))
+            .await
+            .unwrap();
+        assert_eq!(slots.in_flight(), 100);
+        drop(held);
+        assert_eq!(slots.in_flight(), 0);
+    }
+}
@@ -0,0 +1,233 @@
+//! Boot diagnostic bundle.
+//!
+//! When VmmSpawn, GuestConnect or GuestInit fails, the files that explain
+//! why are about to be deleted with the box directory. Before that happens
+//! they're packed into `~/.boxlite/diagnostics/{box_id}-{timestamp}.tar.gz`
+//! and the bundle path is appended to the returned error.
+//!
+//! Bundle contents (missing files are skipped):
+//! - `error.txt`: the error and the task that failed
+//! - `timings.json`: when each task started, relative to the pipeline start
+//! - `vm-config.json`: the config rendered for the VM
+//! - `box-options.json`: the options the box was created with
+//! - `shim.stderr`: hypervisor/shim stderr
+//! - `console.log`: guest console output
+
+use super::types::InitPipelineContext;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::path::{Path, PathBuf};
+
+/// Tasks whose failure triggers a bundle.
+const BOOT_TASKS: &[&str] = &["vmm_spawn", "guest_connect", "guest_init"];
+
+/// Only the end of large log files is kept.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Name of the task that failed, if it was a boot task.
+///
+/// Boot tasks run in sequential stages, so the last task to start is the one
+/// that failed.
+fn failed_boot_task(ctx: &InitPipelineContext) -> Option<&str> {
+    let (name, _) = ctx.task_starts.last()?;
+    BOOT_TASKS.contains(&name.as_str()).then_some(name.as_str())
+}
+
+/// Collect the bundle for a failed boot and return the error with its path.
+///
+/// Collection is best effort: if it fails, the original error is returned.
+pub(super) fn attach_bundle(ctx: &InitPipelineContext, err: BoxliteError) -> BoxliteError {
+    let Some(failed_task) = failed_boot_task(ctx) else {
+        return err;
+    };
+
+    match write_bundle(ctx, failed_task, &err) {
+        Ok(path) => {
+            tracing::warn!(
+                box_id = %ctx.config.id,
+                "Box failed to boot, diagnostic bundle written to {}",
+                path.display()
+            );
+            append_to_message(err, &format!("\ndiagnostic bundle: {}", path.display()))
+        }
+        Err(e) => {
+            tracing::warn!(box_id = %ctx.config.id, "Failed to write diagnostic bundle: {}", e);
+            err
+        }
+    }
+}
+
+fn write_bundle(
+    ctx: &InitPipelineContext,
+    failed_task: &str,
+    err: &BoxliteError,
+) -> BoxliteResult<PathBuf> {
+    let layout = &ctx.runtime.layout;
+    let box_id = ctx.config.id.as_str();
+
+    let mut files: Vec<(&str, Vec<u8>)> = vec![
+        (
+            "error.txt",
+            format!("task: {}\nerror: {}\n", failed_task, err).into_bytes(),
+        ),
+        ("timings.json", timings_json(ctx).into_bytes()),
+    ];
+    if let Some(ref config) = ctx.vm_config_json {
+        files.push(("vm-config.json", config.clone().into_bytes()));
+    }
+    if let Ok(options) = serde_json::to_vec_pretty(&ctx.config.options) {
+        files.push(("box-options.json", options));
+    }
+    if let Some(ref box_layout) = ctx.layout
+        && let Some(stderr) = read_tail(&box_layout.shim_stderr_path())
+    {
+        files.push(("shim.stderr", stderr));
+    }
+    let console_path = layout.logs_dir().join(format!("{}-console.log", box_id));
+    if let Some(console) = read_tail(&console_path) {
+        files.push(("console.log", console));
+    }
+
+    let dir = layout.diagnostics_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| BoxliteError::Storage(format!("failed to create {}: {}", dir.display(), e)))?;
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let path = dir.join(format!("{}-{}.tar.gz", box_id, timestamp));
+    write_tar_gz(&path, &files)?;
+    Ok(path)
+}
+
+fn timings_json(ctx: &InitPipelineContext) -> String {
+    let tasks: Vec<_> = ctx
+        .task_starts
+        .iter()
+        .map(|(name, offset)| {
+            serde_json::json!({
+                "task": name,
+                "started_ms": offset.as_millis() as u64,
+            })
+        })
+        .collect();
+    let timings = serde_json::json!({
+        "tasks": tasks,
+        "failed_after_ms": ctx.started_at.elapsed().as_millis() as u64,
+    });
+    serde_json::to_string_pretty(&timings).unwrap_or_default()
+}
+
+/// Read a file, keeping only its last `MAX_LOG_BYTES`.
+fn read_tail(path: &Path) -> Option<Vec<u8>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    if len > MAX_LOG_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_LOG_BYTES)).ok()?;
+    }
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+fn write_tar_gz(path: &Path, files: &[(&str, Vec<u8>)]) -> BoxliteResult<()> {
+    let storage_err = |e: std::io::Error| {
+        BoxliteError::Storage(format!("failed to write {}: {}", path.display(), e))
+    };
+
+    let file = std::fs::File::create(path).map_err(storage_err)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    for (name, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, data.as_slice())
+            .map_err(storage_err)?;
+    }
+    builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(storage_err)?;
+    Ok(())
+}
+
+/// Append text to the message of an error, keeping its variant.
+fn append_to_message(err: BoxliteError, extra: &str) -> BoxliteError {
+    match err {
+        BoxliteError::Engine(msg) => BoxliteError::Engine(msg + extra),
+        BoxliteError::Config(msg) => BoxliteError::Config(msg + extra),
+        BoxliteError::Storage(msg) => BoxliteError::Storage(msg + extra),
+        BoxliteError::Portal(msg) => BoxliteError::Portal(msg + extra),
+        BoxliteError::Network(msg) => BoxliteError::Network(msg + extra),
+        BoxliteError::Rpc(msg) => BoxliteError::Rpc(msg + extra),
+        BoxliteError::RpcTransport(msg) => BoxliteError::RpcTransport(msg + extra),
+        BoxliteError::Internal(msg) => BoxliteError::Internal(msg + extra),
+        BoxliteError::Execution(msg) => BoxliteError::Execution(msg + extra),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_tar_gz_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.tar.gz");
+        let files = vec![
+            ("error.txt", b"task: vmm_spawn\n".to_vec()),
+            ("shim.stderr", b"krun: failed\n".to_vec()),
+        ];
+        write_tar_gz(&path, &files).unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(std::fs::File::open(&path).unwrap()));
+        let mut entries = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            entries.push((name, content));
+        }
+        assert_eq!(
+            entries,
+            vec![
+                ("error.txt".to_string(), "task: vmm_spawn\n".to_string()),
+                ("shim.stderr".to_string(), "krun: failed\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_tail_truncates_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("console.log");
+        let mut data = vec![b'a'; MAX_LOG_BYTES as usize];
+        data.extend_from_slice(b"end");
+        std::fs::write(&path, &data).unwrap();
+
+        let tail = read_tail(&path).unwrap();
+        assert_eq!(tail.len(), MAX_LOG_BYTES as usize);
+        assert!(tail.ends_with(b"end"));
+        assert!(read_tail(&dir.path().join("missing")).is_none());
+    }
+
+    #[test]
+    fn test_append_to_message_keeps_variant() {
+        let err = append_to_message(BoxliteError::Engine("spawn failed".into()), " (more)");
+        assert!(matches!(err, BoxliteError::Engine(ref msg) if msg == "spawn failed (more)"));
+
+        let err = append_to_message(BoxliteError::NotFound("x".into()), " (more)");
+        assert!(matches!(err, BoxliteError::NotFound(ref msg) if msg == "x"));
+    }
+}
@@ -0,0 +1,376 @@
+//! Embedder hooks around the init stages.
+//!
+//! Hooks registered with `BoxliteRuntime::add_init_hook()` run before and
+//! after each task of the plan (see the module docs of `init`), in the order
+//! they were added, with the box's options for this start. `configure_vm`
+//! runs inside `VmmSpawn`, between building the VM's `InstanceSpec` and
+//! starting it. An error from any hook fails the start like a failing task:
+//! the box's partial state is cleaned up and nothing is spawned past it.
+//! Options a hook changed are validated again before the stage reads them.
+
+use super::tasks::InitCtx;
+use crate::BoxID;
+use crate::pipeline::{BoxedTask, PipelineTask};
+use crate::runtime::options::BoxOptions;
+use crate::vmm::InstanceSpec;
+use async_trait::async_trait;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::sync::{Arc, RwLock};
+
+/// An init task, as passed to [`InitHook`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InitStage {
+    /// Box directory layout
+    Filesystem,
+    /// Image pull and container disk
+    ContainerRootfs,
+    /// Guest rootfs disk
+    GuestRootfs,
+    /// VM configuration and spawn
+    VmmSpawn,
+    /// Attach to the VM of a running box
+    VmmAttach,
+    /// Wait for the guest agent
+    GuestConnect,
+    /// Container setup in the guest
+    GuestInit,
+}
+
+impl InitStage {
+    /// Name of the task, as in progress events and metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Filesystem => "filesystem_setup",
+            Self::ContainerRootfs => "container_rootfs_prep",
+            Self::GuestRootfs => "guest_rootfs_init",
+            Self::VmmSpawn => "vmm_spawn",
+            Self::VmmAttach => "vmm_attach",
+            Self::GuestConnect => "guest_connect",
+            Self::GuestInit => "guest_init",
+        }
+    }
+
+    fn from_task_name(name: &str) -> Option<Self> {
+        [
+            Self::Filesystem,
+            Self::ContainerRootfs,
+            Self::GuestRootfs,
+            Self::VmmSpawn,
+            Self::VmmAttach,
+            Self::GuestConnect,
+            Self::GuestInit,
+        ]
+        .into_iter()
+        .find(|stage| stage.as_str() == name)
+    }
+}
+
+/// What a hook sees of the box being started.
+pub struct InitHookContext<'a> {
+    pub box_id: &'a BoxID,
+    pub box_name: Option<&'a str>,
+    pub stage: InitStage,
+    /// Options of this start. Changes apply to the stages that haven't read
+    /// them yet (volumes and env up to `VmmSpawn`, for instance) and are not
+    /// saved with the box.
+    pub options: &'a mut BoxOptions,
+}
+
+/// Extension point of the init pipeline, for embedders.
+///
+/// Every method defaults to doing nothing; returning an error aborts the
+/// start.
+#[async_trait]
+pub trait InitHook: Send + Sync {
+    /// Before a stage runs.
+    async fn before_stage(&self, _ctx: &mut InitHookContext<'_>) -> BoxliteResult<()> {
+        Ok(())
+    }
+
+    /// After a stage succeeded.
+    async fn after_stage(&self, _ctx: &mut InitHookContext<'_>) -> BoxliteResult<()> {
+        Ok(())
+    }
+
+    /// Adjust the VM configuration (guest entrypoint, filesystem shares,
+    /// block devices) before the VM is spawned.
+    async fn configure_vm(&self, _box_id: &BoxID, _spec: &mut InstanceSpec) -> BoxliteResult<()> {
+        Ok(())
+    }
+}
+
+/// The hooks of a runtime.
+#[derive(Clone, Default)]
+pub(crate) struct InitHooks {
+    hooks: Arc<RwLock<Vec<Arc<dyn InitHook>>>>,
+}
+
+impl InitHooks {
+    pub(crate) fn add(&self, hook: Arc<dyn InitHook>) {
+        self.hooks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(hook);
+    }
+
+    fn snapshot(&self) -> Vec<Arc<dyn InitHook>> {
+        self.hooks.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Run `configure_vm` of every hook on `spec`.
+    pub(crate) async fn configure_vm(
+        &self,
+        box_id: &BoxID,
+        spec: &mut InstanceSpec,
+    ) -> BoxliteResult<()> {
+        for hook in self.snapshot() {
+            hook.configure_vm(box_id, spec).await?;
+        }
+        Ok(())
+    }
+
+    /// Wrap `task` so the hooks run around it; unchanged without hooks.
+    pub(crate) fn wrap(&self, task: BoxedTask<InitCtx>) -> BoxedTask<InitCtx> {
+        let hooks = self.snapshot();
+        match InitStage::from_task_name(task.name()) {
+            Some(stage) if !hooks.is_empty() => Box::new(HookedTask { task, stage, hooks }),
+            _ => task,
+        }
+    }
+}
+
+impl std::fmt::Debug for InitHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InitHooks").finish()
+    }
+}
+
+struct HookedTask {
+    task: BoxedTask<InitCtx>,
+    stage: InitStage,
+    hooks: Vec<Arc<dyn InitHook>>,
+}
+
+#[async_trait]
+impl PipelineTask<InitCtx> for HookedTask {
+    async fn run(self: Box<Self>, ctx: InitCtx) -> BoxliteResult<()> {
+        let HookedTask { task, stage, hooks } = *self;
+        run_hooks(&hooks, stage, &ctx, false).await?;
+        task.run(ctx.clone()).await?;
+        run_hooks(&hooks, stage, &ctx, true).await
+    }
+
+    fn name(&self) -> &str {
+        self.task.name()
+    }
+}
+
+async fn run_hooks(
+    hooks: &[Arc<dyn InitHook>],
+    stage: InitStage,
+    ctx: &InitCtx,
+    after: bool,
+) -> BoxliteResult<()> {
+    // Hooks get a copy, so the context isn't locked while they run
+    let (box_id, box_name, mut options) = {
+        let ctx = ctx.lock().await;
+        let config = &ctx.config;
+        (
+            config.id.clone(),
+            config.name.clone(),
+            config.options.clone(),
+        )
+    };
+    let changed = run_stage_hooks(
+        hooks,
+        stage,
+        &box_id,
+        box_name.as_deref(),
+        &mut options,
+        after,
+    )
+    .await?;
+    if changed {
+        ctx.lock().await.config.options = options;
+    }
+    Ok(())
+}
+
+/// Run the hooks of `stage` on `options`; whether they changed them.
+///
+/// Changed options must pass [`BoxOptions::sanitize`] again.
+async fn run_stage_hooks(
+    hooks: &[Arc<dyn InitHook>],
+    stage: InitStage,
+    box_id: &BoxID,
+    box_name: Option<&str>,
+    options: &mut BoxOptions,
+    after: bool,
+) -> BoxliteResult<bool> {
+    let snapshot = |options: &BoxOptions| {
+        serde_json::to_value(options)
+            .map_err(|e| BoxliteError::Internal(format!("Failed to serialize box options: {}", e)))
+    };
+    let before = snapshot(options)?;
+    let mut hook_ctx = InitHookContext {
+        box_id,
+        box_name,
+        stage,
+        options,
+    };
+    for hook in hooks {
+        let result = if after {
+            hook.after_stage(&mut hook_ctx).await
+        } else {
+            hook.before_stage(&mut hook_ctx).await
+        };
+        result.inspect_err(|e| {
+            tracing::warn!(
+                box_id = %hook_ctx.box_id,
+                stage = stage.as_str(),
+                "Init hook failed: {}",
+                e
+            )
+        })?;
+    }
+
+    if snapshot(options)? == before {
+        return Ok(false);
+    }
+    options.sanitize().inspect_err(|e| {
+        tracing::warn!(
+            box_id = %box_id,
+            stage = stage.as_str(),
+            "Init hook left invalid options: {}",
+            e
+        )
+    })?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tasks::{
+        ContainerRootfsTask, FilesystemTask, GuestConnectTask, GuestInitTask, GuestRootfsTask,
+        VmmAttachTask, VmmSpawnTask,
+    };
+    use super::*;
+
+    #[test]
+    fn test_every_task_has_a_stage() {
+        let tasks: Vec<BoxedTask<InitCtx>> = vec![
+            Box::new(FilesystemTask),
+            Box::new(ContainerRootfsTask),
+            Box::new(GuestRootfsTask),
+            Box::new(VmmSpawnTask),
+            Box::new(VmmAttachTask),
+            Box::new(GuestConnectTask),
+            Box::new(GuestInitTask),
+        ];
+        for task in tasks {
+            let stage = InitStage::from_task_name(task.name()).unwrap();
+            assert_eq!(stage.as_str(), task.name());
+        }
+    }
+
+    #[test]
+    fn test_wrap_without_hooks_keeps_task() {
+        struct Noop;
+        impl InitHook for Noop {}
+
+        let hooks = InitHooks::default();
+        assert_eq!(hooks.wrap(Box::new(VmmSpawnTask)).name(), "vmm_spawn");
+        hooks.add(Arc::new(Noop));
+        assert_eq!(hooks.wrap(Box::new(VmmSpawnTask)).name(), "vmm_spawn");
+        assert_eq!(hooks.snapshot().len(), 1);
+    }
+
+    /// Records the stages it saw and sets an env var on each.
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<(InitStage, bool)>>);
+
+    #[async_trait]
+    impl InitHook for Recorder {
+        async fn before_stage(&self, ctx: &mut InitHookContext<'_>) -> BoxliteResult<()> {
+            self.0.lock().unwrap().push((ctx.stage, false));
+            ctx.options
+                .env
+                .push(("STAGE".to_string(), ctx.stage.as_str().to_string()));
+            Ok(())
+        }
+
+        async fn after_stage(&self, ctx: &mut InitHookContext<'_>) -> BoxliteResult<()> {
+            self.0.lock().unwrap().push((ctx.stage, true));
+            Ok(())
+        }
+    }
+
+    struct Veto;
+
+    #[async_trait]
+    impl InitHook for Veto {
+        async fn before_stage(&self, _ctx: &mut InitHookContext<'_>) -> BoxliteResult<()> {
+            Err(BoxliteError::Config("vetoed".to_string()))
+        }
+    }
+
+    struct Detach;
+
+    #[async_trait]
+    impl InitHook for Detach {
+        async fn before_stage(&self, ctx: &mut InitHookContext<'_>) -> BoxliteResult<()> {
+            ctx.options.detach = true;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stage_hooks_run_in_order_and_can_veto() {
+        let box_id = BoxID::new();
+        let recorder = Arc::new(Recorder::default());
+        let hooks: Vec<Arc<dyn InitHook>> = vec![recorder.clone(), Arc::new(Veto)];
+        let mut options = BoxOptions::default();
+
+        let stage = InitStage::VmmSpawn;
+        let err = run_stage_hooks(&hooks, stage, &box_id, None, &mut options, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BoxliteError::Config(_)));
+        assert_eq!(*recorder.0.lock().unwrap(), [(stage, false)]);
+
+        let changed = run_stage_hooks(&hooks[..1], stage, &box_id, None, &mut options, true)
+            .await
+            .unwrap();
+        assert!(!changed);
+        let changed = run_stage_hooks(&hooks[..1], stage, &box_id, None, &mut options, false)
+            .await
+            .unwrap();
+        assert!(changed);
+        assert!(
+            options
+                .env
+                .contains(&("STAGE".to_string(), "vmm_spawn".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stage_hooks_revalidate_options() {
+        let box_id = BoxID::new();
+        let hooks: Vec<Arc<dyn InitHook>> = vec![Arc::new(Detach)];
+        let mut options = BoxOptions {
+            auto_remove: true,
+            ..Default::default()
+        };
+
+        let result = run_stage_hooks(
+            &hooks,
+            InitStage::Filesystem,
+            &box_id,
+            None,
+            &mut options,
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(BoxliteError::Config(_))));
+    }
+}
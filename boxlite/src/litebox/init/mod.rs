@@ -26,12 +26,20 @@
 //!   2. GuestConnect         (reconnect to guest)
 //! ```
 //!
-//! `CleanupGuard` provides RAII cleanup on failure.
+//! `CleanupGuard` provides RAII cleanup on failure. If VmmSpawn or a Guest
+//! task fails, a diagnostic bundle is collected first (see `diagnostics`).
+//! Embedders can run code around each task with an `InitHook` (see `hooks`).
 
+mod diagnostics;
+mod hooks;
 mod tasks;
 mod types;
 
 pub(crate) use crate::litebox::box_impl::LiveState;
+pub(crate) use hooks::InitHooks;
+pub use hooks::{InitHook, InitHookContext, InitStage};
+pub(crate) use tasks::prewarm_image;
+pub(crate) use types::resolve_user_volumes;
 
 use crate::litebox::BoxStatus;
 use crate::litebox::config::BoxConfig;
@@ -39,10 +47,12 @@ use crate::metrics::BoxMetricsStorage;
 use crate::pipeline::{
     BoxedTask, ExecutionPlan, PipelineBuilder, PipelineExecutor, PipelineMetrics, Stage,
 };
+use crate::runtime::progress::ProgressEvent;
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use crate::runtime::types::BoxState;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::Mutex;
 
 use tasks::{
@@ -55,8 +65,8 @@ use types::InitPipelineContext;
 // EXECUTION PLAN
 // ============================================================================
 
-/// Get execution plan based on BoxStatus.
-fn get_execution_plan(status: BoxStatus) -> ExecutionPlan<InitCtx> {
+/// Get execution plan based on BoxStatus, with `hooks` around each task.
+fn get_execution_plan(status: BoxStatus, hooks: &InitHooks) -> ExecutionPlan<InitCtx> {
     let stages: Vec<Stage<BoxedTask<InitCtx>>> = match status {
         BoxStatus::Configured => vec![
             // First start: Full pipeline
@@ -86,7 +96,7 @@ fn get_execution_plan(status: BoxStatus) -> ExecutionPlan<InitCtx> {
             // GuestInit must run - new VM process has fresh guest daemon
             Stage::sequential(vec![Box::new(GuestInitTask)]),
         ],
-        BoxStatus::Running => vec![
+        BoxStatus::Running | BoxStatus::Unresponsive => vec![
             // Reattach: Attach to existing VM process and connect to guest
             Stage::sequential(vec![Box::new(VmmAttachTask)]),
             Stage::sequential(vec![Box::new(GuestConnectTask)]),
@@ -94,10 +104,24 @@ fn get_execution_plan(status: BoxStatus) -> ExecutionPlan<InitCtx> {
         _ => panic!("Invalid BoxStatus for initialization: {:?}", status),
     };
 
+    let stages = stages
+        .into_iter()
+        .map(|stage| Stage {
+            tasks: stage
+                .tasks
+                .into_iter()
+                .map(|task| hooks.wrap(task))
+                .collect(),
+            execution: stage.execution,
+        })
+        .collect();
     ExecutionPlan::new(stages)
 }
 
-fn box_metrics_from_pipeline(pipeline_metrics: &PipelineMetrics) -> BoxMetricsStorage {
+fn box_metrics_from_pipeline(
+    pipeline_metrics: &PipelineMetrics,
+    config_build_ms: Option<u128>,
+) -> BoxMetricsStorage {
     let mut metrics = BoxMetricsStorage::new();
 
     if let Some(duration_ms) = pipeline_metrics.task_duration_ms("filesystem_setup") {
@@ -109,15 +133,18 @@ fn box_metrics_from_pipeline(pipeline_metrics: &PipelineMetrics) -> BoxMetricsSt
     if let Some(duration_ms) = pipeline_metrics.task_duration_ms("guest_rootfs_init") {
         metrics.set_stage_guest_rootfs(duration_ms);
     }
+    // VmmSpawn builds the config, then spawns the VM
+    if let Some(duration_ms) = config_build_ms {
+        metrics.set_stage_box_config(duration_ms);
+    }
     if let Some(duration_ms) = pipeline_metrics.task_duration_ms("vmm_spawn") {
-        metrics.set_stage_box_spawn(duration_ms);
+        metrics.set_stage_box_spawn(duration_ms.saturating_sub(config_build_ms.unwrap_or(0)));
     }
     if let Some(duration_ms) = pipeline_metrics.task_duration_ms("vmm_attach") {
         metrics.set_stage_box_spawn(duration_ms);
     }
-    if let Some(_duration_ms) = pipeline_metrics.task_duration_ms("guest_connect") {
-        // Track guest connection time
-        // Could add a new metric field if needed
+    if let Some(duration_ms) = pipeline_metrics.task_duration_ms("guest_connect") {
+        metrics.set_guest_boot_duration(duration_ms);
     }
     if let Some(duration_ms) = pipeline_metrics.task_duration_ms("guest_init") {
         metrics.set_stage_container_init(duration_ms);
@@ -185,9 +212,10 @@ impl BoxBuilder {
             state,
         } = self;
 
-        let status = state.status;
+        let status = state.status();
         let reuse_rootfs = status == BoxStatus::Stopped;
-        let skip_guest_wait = status == BoxStatus::Running;
+        let skip_guest_wait = status.is_running();
+        let box_id = config.id.to_string();
 
         let ctx = InitPipelineContext::new(config, runtime.clone(), reuse_rootfs, skip_guest_wait);
         let ctx = Arc::new(Mutex::new(ctx));
@@ -195,9 +223,30 @@ impl BoxBuilder {
         // Note: Guard stays armed until caller disarms it after DB persist succeeds.
         // This ensures cleanup happens even if operations after build() fail.
 
-        let plan = get_execution_plan(status);
+        let plan = get_execution_plan(status, &runtime.init_hooks);
+        let steps = plan.task_count();
         let pipeline = PipelineBuilder::from_plan(plan);
-        let pipeline_metrics = PipelineExecutor::execute(pipeline, Arc::clone(&ctx)).await?;
+        let step = AtomicUsize::new(0);
+        let on_task_start = |name: &str| {
+            let step = step.fetch_add(1, Ordering::Relaxed) + 1;
+            runtime.progress.emit(|| ProgressEvent::BootStep {
+                box_id: box_id.clone(),
+                name: name.to_string(),
+                step,
+                steps,
+            });
+        };
+        let result =
+            PipelineExecutor::execute_observed(pipeline, Arc::clone(&ctx), on_task_start).await;
+        let pipeline_metrics = match result {
+            Ok(metrics) => metrics,
+            Err(e) => {
+                // Collect the bundle while the box dir still exists; the guard
+                // deletes it when ctx is dropped.
+                let ctx = ctx.lock().await;
+                return Err(diagnostics::attach_bundle(&ctx, e));
+            }
+        };
 
         let mut ctx = ctx.lock().await;
         let total_create_duration_ms = total_start.elapsed().as_millis();
@@ -206,10 +255,14 @@ impl BoxBuilder {
             .take_handler()
             .ok_or_else(|| BoxliteError::Internal("handler was not set".into()))?;
 
-        let mut metrics = box_metrics_from_pipeline(&pipeline_metrics);
+        let mut metrics = box_metrics_from_pipeline(&pipeline_metrics, ctx.config_build_ms);
         metrics.set_total_create_duration(total_create_duration_ms);
+        if let Some(build) = ctx.rootfs_build {
+            metrics.set_rootfs_build(build);
+        }
 
         metrics.log_init_stages();
+        runtime.progress.emit(|| ProgressEvent::BootDone { box_id });
 
         // Note: Guard is NOT disarmed here. Caller is responsible for disarming
         // after all operations succeed (including DB persist).
@@ -221,7 +274,7 @@ impl BoxBuilder {
             .ok_or_else(|| BoxliteError::Internal("guest_connect task must run first".into()))?;
 
         // Get disks from context (for Running, create disk reference directly)
-        let (container_disk, guest_disk) = if status == BoxStatus::Running {
+        let (container_disk, guest_disk) = if status.is_running() {
             // Reattach: create disk reference to existing qcow2
             use crate::disk::DiskFormat;
             let disk = crate::disk::Disk::new(
@@ -253,6 +306,7 @@ impl BoxBuilder {
         let live_state = LiveState::new(
             handler,
             guest_session,
+            ctx.guest_agent.take(),
             metrics,
             container_disk,
             guest_disk,
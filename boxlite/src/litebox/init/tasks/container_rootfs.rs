@@ -1,21 +1,34 @@
 //! Task: Container rootfs preparation.
 //!
 //! Pulls container image and prepares container rootfs:
-//! - Disk-based: Creates ext4 disk image from merged layers (fast boot)
+//! - Disk-based: Creates ext4 disk image from merged layers (fast boot), with
+//!   a qcow2 overlay per box, or a reflink copy of it (`StorageDriver::Reflink`)
+//! - Erofs: Builds one read-only erofs image per layer, stacked by guest-side
+//!   overlayfs over an empty upper disk (`StorageDriver::Erofs`)
 //! - Overlayfs: Extracts layers for guest-side overlayfs (flexible)
+//! - Disk image (`RootfsSpec::DiskImage`): Overlays the user's ext4 or qcow2
+//!   image with a qcow2 disk per box, without any OCI image
 //!
 //! For restart (reuse_rootfs=true), opens existing COW disk instead of creating new.
 
 use super::{InitCtx, log_task_error, task_start};
-use crate::disk::{BackingFormat, Disk, DiskFormat, Qcow2Helper, create_ext4_from_dir};
+use crate::disk::constants::{erofs::MAX_LAYERS, qcow2::DEFAULT_DISK_SIZE_GB};
+use crate::disk::{
+    BackingFormat, Disk, DiskFormat, Qcow2Helper, create_erofs_from_dir, create_ext4_from_dir,
+    reflink_disk,
+};
 use crate::images::ContainerImageConfig;
+use crate::litebox::RootfsBuildTimings;
+use crate::litebox::config::DiskImageSource;
 use crate::litebox::init::types::{ContainerRootfsPrepResult, USE_DISK_ROOTFS, USE_OVERLAYFS};
+use crate::net::recorder;
 use crate::pipeline::PipelineTask;
 use crate::runtime::layout::BoxFilesystemLayout;
-use crate::runtime::options::RootfsSpec;
+use crate::runtime::options::{BoxOptions, ImagePullPolicy, RootfsSpec, StorageDriver};
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use async_trait::async_trait;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::path::{Path, PathBuf};
 
 pub struct ContainerRootfsTask;
 
@@ -35,6 +48,9 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
             entrypoint_override,
             cmd_override,
             user_override,
+            pull_policy,
+            storage_driver,
+            disk_image,
         ) = {
             let ctx = ctx.lock().await;
             let layout = ctx
@@ -43,7 +59,7 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
                 .ok_or_else(|| BoxliteError::Internal("filesystem task must run first".into()))?;
             (
                 ctx.config.options.rootfs.clone(),
-                ctx.config.options.env.clone(),
+                container_env(&ctx.config.options),
                 ctx.runtime.clone(),
                 layout,
                 ctx.reuse_rootfs,
@@ -51,10 +67,13 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
                 ctx.config.options.entrypoint.clone(),
                 ctx.config.options.cmd.clone(),
                 ctx.config.options.user.clone(),
+                ctx.config.options.pull_policy,
+                ctx.config.storage_driver,
+                ctx.config.disk_image.clone(),
             )
         };
 
-        let (container_image_config, disk) = run_container_rootfs(
+        let (container_image_config, disk, layer_disks, rootfs_build) = run_container_rootfs(
             &rootfs_spec,
             &env,
             &runtime,
@@ -64,6 +83,9 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
             entrypoint_override.as_deref(),
             cmd_override.as_deref(),
             user_override.as_deref(),
+            pull_policy,
+            storage_driver,
+            disk_image.as_ref(),
         )
         .await
         .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
@@ -71,6 +93,8 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
         let mut ctx = ctx.lock().await;
         ctx.container_image_config = Some(container_image_config);
         ctx.container_disk = Some(disk);
+        ctx.container_layer_disks = layer_disks;
+        ctx.rootfs_build = rootfs_build;
 
         Ok(())
     }
@@ -80,7 +104,22 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
     }
 }
 
+/// Environment set over the image's: the proxy of a recording
+/// `network_mode`, then the user's, which wins.
+fn container_env(options: &BoxOptions) -> Vec<(String, String)> {
+    let mut env = match options.network_mode.recording() {
+        Some(_) => recorder::guest_env(),
+        None => Vec::new(),
+    };
+    env.extend(options.env.iter().cloned());
+    env
+}
+
 /// Pull image and prepare rootfs, then create or reuse COW disk.
+///
+/// Returns the erofs layer images alongside the disk for `StorageDriver::Erofs`
+/// (empty for ext4, where the disk holds the whole rootfs), and how the rootfs
+/// was built when no cached disk image could be used.
 #[allow(clippy::too_many_arguments)]
 async fn run_container_rootfs(
     rootfs_spec: &RootfsSpec,
@@ -92,28 +131,61 @@ async fn run_container_rootfs(
     entrypoint_override: Option<&[String]>,
     cmd_override: Option<&[String]>,
     user_override: Option<&str>,
-) -> BoxliteResult<(ContainerImageConfig, Disk)> {
-    let disk_path = layout.disk_path();
-
-    // For restart, reuse existing COW disk
-    if reuse_rootfs {
-        tracing::info!(
-            disk_path = %disk_path.display(),
-            "Restart mode: reusing existing container rootfs disk"
+    pull_policy: ImagePullPolicy,
+    storage_driver: StorageDriver,
+    disk_image: Option<&DiskImageSource>,
+) -> BoxliteResult<(
+    ContainerImageConfig,
+    Disk,
+    Vec<PathBuf>,
+    Option<RootfsBuildTimings>,
+)> {
+    if let RootfsSpec::DiskImage(..) = rootfs_spec {
+        let source = disk_image.ok_or_else(|| {
+            BoxliteError::Internal("disk image box has no recorded disk image".into())
+        })?;
+        // The box's disk only holds its changes over the image
+        source.verify()?;
+
+        // Without an image config, default to a root shell
+        let mut container_image_config = ContainerImageConfig::default();
+        if !env.is_empty() {
+            container_image_config.merge_env(env.to_vec());
+        }
+        apply_user_overrides(
+            &mut container_image_config,
+            entrypoint_override,
+            cmd_override,
+            user_override,
         );
 
-        if !disk_path.exists() {
-            return Err(BoxliteError::Storage(format!(
-                "Cannot restart: container rootfs disk not found at {}",
-                disk_path.display()
-            )));
-        }
+        let disk = if reuse_rootfs {
+            reuse_disk(&layout.disk_path(), DiskFormat::Qcow2)?
+        } else {
+            let rootfs_result = prepare_disk_image(source)?;
+            create_cow_disk(&rootfs_result, layout, disk_size_gb, StorageDriver::Ext4)?
+        };
+        return Ok((container_image_config, disk, Vec::new(), None));
+    }
+
+    let (disk_path, disk_format) = match storage_driver {
+        StorageDriver::Reflink => (layout.raw_disk_path(), DiskFormat::Ext4),
+        StorageDriver::Ext4 | StorageDriver::Erofs => (layout.disk_path(), DiskFormat::Qcow2),
+    };
 
-        let disk = Disk::new(disk_path.clone(), DiskFormat::Qcow2, true);
+    // For restart, reuse existing COW disk
+    if reuse_rootfs {
+        let disk = reuse_disk(&disk_path, disk_format)?;
 
         // Load container config
         let image = match rootfs_spec {
-            RootfsSpec::Image(r) => pull_image(runtime, r).await?,
+            RootfsSpec::Image(r) => {
+                runtime
+                    .image_manager
+                    .pull_with_policy(r, pull_policy)
+                    .await?
+            }
+            RootfsSpec::Builtin => runtime.image_manager.builtin().await?,
             RootfsSpec::RootfsPath(path) => {
                 let bundle_dir = std::path::Path::new(path);
 
@@ -129,7 +201,18 @@ async fn run_container_rootfs(
                     .load_from_local(bundle_dir.to_path_buf(), format!("local:{}", path))
                     .await?
             }
+            RootfsSpec::DiskImage(..) => {
+                return Err(BoxliteError::Internal(
+                    "disk image rootfs has no OCI image".into(),
+                ));
+            }
         };
+        // Layer images are shared caches; rebuild any that were pruned
+        let layer_disks = match storage_driver {
+            StorageDriver::Ext4 | StorageDriver::Reflink => Vec::new(),
+            StorageDriver::Erofs => prepare_erofs_layers(runtime, &image).await?,
+        };
+
         let image_config = image.load_config().await?;
         let mut container_image_config = ContainerImageConfig::from_oci_config(&image_config)?;
         if !env.is_empty() {
@@ -142,12 +225,18 @@ async fn run_container_rootfs(
             user_override,
         );
 
-        return Ok((container_image_config, disk));
+        return Ok((container_image_config, disk, layer_disks, None));
     }
 
     // Fresh start: pull or load image
     let image = match rootfs_spec {
-        RootfsSpec::Image(r) => pull_image(runtime, r).await?,
+        RootfsSpec::Image(r) => {
+            runtime
+                .image_manager
+                .pull_with_policy(r, pull_policy)
+                .await?
+        }
+        RootfsSpec::Builtin => runtime.image_manager.builtin().await?,
         RootfsSpec::RootfsPath(path) => {
             let bundle_dir = std::path::Path::new(path);
 
@@ -163,10 +252,19 @@ async fn run_container_rootfs(
                 .load_from_local(bundle_dir.to_path_buf(), format!("local:{}", path))
                 .await?
         }
+        RootfsSpec::DiskImage(..) => {
+            return Err(BoxliteError::Internal(
+                "disk image rootfs has no OCI image".into(),
+            ));
+        }
     };
 
     // Prepare rootfs from image
-    let rootfs_result = if USE_DISK_ROOTFS {
+    let rootfs_result = if storage_driver == StorageDriver::Erofs {
+        ContainerRootfsPrepResult::ErofsLayers {
+            layer_disks: prepare_erofs_layers(runtime, &image).await?,
+        }
+    } else if USE_DISK_ROOTFS {
         prepare_disk_rootfs(runtime, &image).await?
     } else if USE_OVERLAYFS {
         prepare_overlayfs_layers(&image).await?
@@ -189,9 +287,63 @@ async fn run_container_rootfs(
         user_override,
     );
 
-    let disk = create_cow_disk(&rootfs_result, layout, disk_size_gb)?;
+    let disk = create_cow_disk(&rootfs_result, layout, disk_size_gb, storage_driver)?;
+    let (layer_disks, rootfs_build) = match rootfs_result {
+        ContainerRootfsPrepResult::ErofsLayers { layer_disks } => (layer_disks, None),
+        ContainerRootfsPrepResult::DiskImage { build, .. } => (Vec::new(), build),
+        _ => (Vec::new(), None),
+    };
+
+    Ok((container_image_config, disk, layer_disks, rootfs_build))
+}
+
+/// Open the COW disk of a box being restarted.
+fn reuse_disk(disk_path: &Path, disk_format: DiskFormat) -> BoxliteResult<Disk> {
+    tracing::info!(
+        disk_path = %disk_path.display(),
+        "Restart mode: reusing existing container rootfs disk"
+    );
+
+    if !disk_path.exists() {
+        return Err(BoxliteError::Storage(format!(
+            "Cannot restart: container rootfs disk not found at {}",
+            disk_path.display()
+        )));
+    }
+
+    Ok(Disk::new(disk_path.to_path_buf(), disk_format, true))
+}
+
+/// Use the user's disk image, checked by the caller, as the base of the
+/// box's COW disk, sized by its virtual size for qcow2.
+fn prepare_disk_image(source: &DiskImageSource) -> BoxliteResult<ContainerRootfsPrepResult> {
+    let (backing_format, disk_size) = match source.format {
+        DiskFormat::Qcow2 => (
+            BackingFormat::Qcow2,
+            Qcow2Helper::qcow2_virtual_size(&source.path)?,
+        ),
+        DiskFormat::Ext4 => (BackingFormat::Raw, source.size),
+        // Rejected by BoxOptions::sanitize() before start
+        DiskFormat::Erofs => {
+            return Err(BoxliteError::Internal(
+                "erofs disk image passed validation".into(),
+            ));
+        }
+    };
+
+    tracing::info!(
+        "Using disk image {} ({}, {}MB)",
+        source.path.display(),
+        source.format.as_str(),
+        disk_size / (1024 * 1024)
+    );
 
-    Ok((container_image_config, disk))
+    Ok(ContainerRootfsPrepResult::DiskImage {
+        base_disk_path: source.path.clone(),
+        backing_format,
+        disk_size,
+        build: None,
+    })
 }
 
 /// Create COW disk from base rootfs.
@@ -201,15 +353,19 @@ async fn run_container_rootfs(
 /// * `layout` - Box filesystem layout for disk paths
 /// * `disk_size_gb` - Optional user-specified disk size in GB. If set, the COW disk
 ///   will have this virtual size (or the base disk size, whichever is larger).
+/// * `storage_driver` - `Reflink` reflinks the base disk instead of overlaying it
 fn create_cow_disk(
     rootfs_result: &ContainerRootfsPrepResult,
     layout: &crate::runtime::layout::BoxFilesystemLayout,
     disk_size_gb: Option<u64>,
+    storage_driver: StorageDriver,
 ) -> BoxliteResult<Disk> {
     match rootfs_result {
         ContainerRootfsPrepResult::DiskImage {
             base_disk_path,
+            backing_format,
             disk_size: base_disk_size,
+            ..
         } => {
             // Calculate target disk size: use max of user-specified size and base disk size
             let target_disk_size = if let Some(size_gb) = disk_size_gb {
@@ -219,11 +375,26 @@ fn create_cow_disk(
                 *base_disk_size
             };
 
+            if storage_driver == StorageDriver::Reflink {
+                let raw_path = layout.raw_disk_path();
+                let temp_disk = reflink_disk(base_disk_path, &raw_path, target_disk_size)?;
+                let disk = Disk::new(temp_disk.leak(), DiskFormat::Ext4, true);
+
+                tracing::info!(
+                    disk = %raw_path.display(),
+                    base_disk = %base_disk_path.display(),
+                    size_mb = target_disk_size / (1024 * 1024),
+                    "Reflinked container rootfs disk (persistent)"
+                );
+
+                return Ok(disk);
+            }
+
             let qcow2_helper = Qcow2Helper::new();
             let cow_disk_path = layout.disk_path();
             let temp_disk = qcow2_helper.create_cow_child_disk(
                 base_disk_path,
-                BackingFormat::Raw,
+                *backing_format,
                 &cow_disk_path,
                 target_disk_size,
             )?;
@@ -243,6 +414,24 @@ fn create_cow_disk(
 
             Ok(disk)
         }
+        ContainerRootfsPrepResult::ErofsLayers { .. } => {
+            // Empty upper disk; the guest formats it on first boot
+            let size_gb = disk_size_gb.unwrap_or(DEFAULT_DISK_SIZE_GB);
+            let cow_disk_path = layout.disk_path();
+            let disk = Qcow2Helper::new().create_disk_with_size(
+                &cow_disk_path,
+                size_gb * 1024 * 1024 * 1024,
+                true,
+            )?;
+
+            tracing::info!(
+                upper_disk = %cow_disk_path.display(),
+                virtual_size_gb = size_gb,
+                "Created container overlay upper disk (persistent)"
+            );
+
+            Ok(disk)
+        }
         ContainerRootfsPrepResult::Layers { .. } => Err(BoxliteError::Internal(
             "Layers mode requires overlayfs - disk creation not applicable".into(),
         )),
@@ -253,6 +442,9 @@ fn create_cow_disk(
 }
 
 /// Apply user overrides to container image config (entrypoint, CMD, and user).
+///
+/// Like Docker, overriding the entrypoint drops the image's CMD, which was
+/// written as arguments for the image's own entrypoint.
 fn apply_user_overrides(
     config: &mut ContainerImageConfig,
     entrypoint_override: Option<&[String]>,
@@ -261,6 +453,7 @@ fn apply_user_overrides(
 ) {
     if let Some(ep) = entrypoint_override {
         config.entrypoint = ep.to_vec();
+        config.cmd.clear();
     }
     if let Some(cmd) = cmd_override {
         config.cmd = cmd.to_vec();
@@ -270,12 +463,29 @@ fn apply_user_overrides(
     }
 }
 
-async fn pull_image(
+/// Pull an image and build its cached base rootfs ahead of box creation.
+///
+/// Runs the same preparation as a fresh start, minus the per-box COW disk,
+/// so later creates from this image only pay for the overlay.
+pub(crate) async fn prewarm_image(
     runtime: &crate::runtime::SharedRuntimeImpl,
     image_ref: &str,
-) -> BoxliteResult<crate::images::ImageObject> {
-    // ImageManager has internal locking - direct access
-    runtime.image_manager.pull(image_ref).await
+    pull_policy: ImagePullPolicy,
+) -> BoxliteResult<()> {
+    let image = runtime
+        .image_manager
+        .pull_with_policy(image_ref, pull_policy)
+        .await?;
+
+    if runtime.storage_driver == StorageDriver::Erofs {
+        prepare_erofs_layers(runtime, &image).await?;
+    } else if USE_DISK_ROOTFS {
+        prepare_disk_rootfs(runtime, &image).await?;
+    } else if USE_OVERLAYFS {
+        prepare_overlayfs_layers(&image).await?;
+    }
+
+    Ok(())
 }
 
 async fn prepare_overlayfs_layers(
@@ -315,6 +525,73 @@ async fn prepare_overlayfs_layers(
     })
 }
 
+/// Build (or reuse cached) read-only erofs images for every image layer.
+///
+/// Layer images are cached by layer digest, so only layers no other image
+/// has used yet cost anything; nothing is copied per box.
+///
+/// Returns the layer image paths, bottom layer first.
+async fn prepare_erofs_layers(
+    runtime: &crate::runtime::SharedRuntimeImpl,
+    image: &crate::images::ImageObject,
+) -> BoxliteResult<Vec<PathBuf>> {
+    let digests: Vec<String> = image
+        .layer_digests()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    if digests.is_empty() {
+        return Err(BoxliteError::Storage(
+            "No layers found for erofs rootfs".into(),
+        ));
+    }
+    if digests.len() > MAX_LAYERS {
+        return Err(BoxliteError::Config(format!(
+            "Image has {} layers but the erofs storage driver supports at most {}; \
+             use the ext4 storage driver for this image",
+            digests.len(),
+            MAX_LAYERS
+        )));
+    }
+
+    // Only extract when some layer image still has to be built
+    let missing = digests
+        .iter()
+        .any(|digest| image.layer_erofs_image(digest).is_none());
+    let extracted = if missing {
+        image.layer_extracted().await?
+    } else {
+        Vec::new()
+    };
+
+    let mut layer_disks = Vec::with_capacity(digests.len());
+    for (index, digest) in digests.iter().enumerate() {
+        if let Some(disk) = image.layer_erofs_image(digest) {
+            layer_disks.push(disk.leak());
+            continue;
+        }
+
+        tracing::info!("Creating erofs image for layer {}", digest);
+
+        let temp_dir = tempfile::tempdir_in(runtime.layout.temp_dir()).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to create temp directory: {}", e))
+        })?;
+        let source = extracted[index].clone();
+        let output = temp_dir.path().join("layer.erofs");
+        let disk = tokio::task::spawn_blocking(move || create_erofs_from_dir(&source, &output))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("Erofs creation task failed: {}", e)))??;
+
+        let installed = image.install_layer_erofs_image(digest, disk).await?;
+        layer_disks.push(installed.leak());
+    }
+
+    tracing::info!("Prepared {} erofs layer images", layer_disks.len());
+
+    Ok(layer_disks)
+}
+
 /// Prepare disk-based rootfs from image layers.
 ///
 /// This function:
@@ -343,7 +620,9 @@ async fn prepare_disk_rootfs(
 
         return Ok(ContainerRootfsPrepResult::DiskImage {
             base_disk_path: disk_path,
+            backing_format: BackingFormat::Raw,
             disk_size,
+            build: None,
         });
     }
 
@@ -366,10 +645,19 @@ async fn prepare_disk_rootfs(
     let merged_path = temp_dir.path().join("merged");
 
     // Use RootfsBuilder to merge layers
+    let materialize_start = std::time::Instant::now();
     let builder = crate::rootfs::RootfsBuilder::new();
-    let _prepared = builder.prepare(merged_path.clone(), image).await?;
+    let prepared = builder.prepare(merged_path.clone(), image).await?;
+    let build = RootfsBuildTimings {
+        materialize_ms: materialize_start.elapsed().as_millis() as u64,
+        reflinked_bytes: prepared.copy.reflinked_bytes,
+        copied_bytes: prepared.copy.copied_bytes,
+    };
 
     tracing::info!(
+        materialize_ms = build.materialize_ms,
+        reflinked_bytes = build.reflinked_bytes,
+        copied_bytes = build.copied_bytes,
         "Merged {} layers into temporary directory",
         layer_paths.len()
     );
@@ -408,6 +696,45 @@ async fn prepare_disk_rootfs(
 
     Ok(ContainerRootfsPrepResult::DiskImage {
         base_disk_path: final_path,
+        backing_format: BackingFormat::Raw,
         disk_size,
+        build: Some(build),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image_config() -> ContainerImageConfig {
+        ContainerImageConfig {
+            entrypoint: vec!["dockerd-entrypoint.sh".to_string()],
+            cmd: vec!["--tls=false".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_entrypoint_override_drops_image_cmd() {
+        let mut config = image_config();
+        apply_user_overrides(&mut config, Some(&["dockerd".to_string()]), None, None);
+        assert_eq!(config.final_cmd(), ["dockerd"]);
+
+        let mut config = image_config();
+        let cmd = ["--iptables=false".to_string()];
+        apply_user_overrides(
+            &mut config,
+            Some(&["dockerd".to_string()]),
+            Some(&cmd),
+            None,
+        );
+        assert_eq!(config.final_cmd(), ["dockerd", "--iptables=false"]);
+    }
+
+    #[test]
+    fn test_cmd_override_keeps_image_entrypoint() {
+        let mut config = image_config();
+        apply_user_overrides(&mut config, None, Some(&["--debug".to_string()]), None);
+        assert_eq!(config.final_cmd(), ["dockerd-entrypoint.sh", "--debug"]);
+    }
+}
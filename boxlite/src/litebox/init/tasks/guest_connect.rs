@@ -7,6 +7,7 @@
 //! Races guest readiness against shim process death for fast failure detection.
 
 use super::{InitCtx, log_task_error, task_start};
+use crate::litebox::GuestAgentInfo;
 use crate::pipeline::PipelineTask;
 use crate::portal::GuestSession;
 use async_trait::async_trait;
@@ -45,9 +46,19 @@ impl PipelineTask<InitCtx> for GuestConnectTask {
 
         tracing::debug!(box_id = %box_id, "Guest is ready, creating session");
         let guest_session = GuestSession::new(transport);
+        let guest_agent = handshake(&guest_session).await;
+        if let Some(agent) = &guest_agent {
+            tracing::info!(
+                box_id = %box_id,
+                version = %agent.version,
+                capabilities = ?agent.capabilities,
+                "Connected to guest agent"
+            );
+        }
 
         let mut ctx = ctx.lock().await;
         ctx.guest_session = Some(guest_session);
+        ctx.guest_agent = guest_agent;
 
         Ok(())
     }
@@ -57,6 +68,25 @@ impl PipelineTask<InitCtx> for GuestConnectTask {
     }
 }
 
+/// Ask the agent for its version and capabilities.
+///
+/// A failed handshake doesn't fail the start: the agent's capabilities are
+/// then unknown and no operation is refused up front.
+async fn handshake(session: &GuestSession) -> Option<GuestAgentInfo> {
+    let result = async { session.guest().await?.ping().await };
+    match tokio::time::timeout(Duration::from_secs(5), result).await {
+        Ok(Ok(agent)) => Some(agent),
+        Ok(Err(e)) => {
+            tracing::warn!("Guest agent handshake failed: {}", e);
+            None
+        }
+        Err(_) => {
+            tracing::warn!("Guest agent handshake timed out");
+            None
+        }
+    }
+}
+
 /// Wait for guest to signal readiness, racing against shim process death.
 ///
 /// Uses `tokio::select!` to detect three conditions:
@@ -4,14 +4,21 @@
 //! Builds guest volumes from volume manager, uses rootfs config from vmm_config stage.
 
 use super::{InitCtx, log_task_error, task_start};
-use crate::images::ContainerImageConfig;
 use crate::pipeline::PipelineTask;
 use crate::portal::GuestSession;
-use crate::portal::interfaces::{ContainerRootfsInitConfig, GuestInitConfig, NetworkInitConfig};
+use crate::portal::interfaces::{
+    ContainerHostsConfig, ContainerInitConfig, ContainerResourceLimits, GuestInitConfig,
+    NetworkInitConfig, SwapInitConfig,
+};
+use crate::runtime::options::SwapBacking;
 use crate::runtime::types::ContainerID;
-use crate::volumes::{ContainerMount, GuestVolumeManager};
 use async_trait::async_trait;
+use boxlite_shared::constants::capabilities;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::time::Duration;
+
+/// Guest agent log lines appended to a failed init's error
+const FAILURE_LOG_TAIL: u32 = 30;
 
 pub struct GuestInitTask;
 
@@ -21,59 +28,84 @@ impl PipelineTask<InitCtx> for GuestInitTask {
         let task_name = self.name();
         let box_id = task_start(&ctx, task_name).await;
 
-        let (
-            guest_session,
-            container_image_config,
-            container_id,
-            volume_mgr,
-            rootfs_init,
-            container_mounts,
-        ) =
+        let (guest_session, container_id, guest_init_config, container_init_config) = {
+            let mut ctx = ctx.lock().await;
+            let swap = ctx.config.options.swap_mib.map(|mib| SwapInitConfig {
+                size_bytes: u64::from(mib) * 1024 * 1024,
+                zram: ctx.config.options.swap_backing == SwapBacking::Zram,
+            });
+            // Agents without swap support would boot without it
+            if swap.is_some()
+                && let Some(agent) = &ctx.guest_agent
+                && !agent.supports(capabilities::SWAP)
             {
-                let mut ctx = ctx.lock().await;
-                let guest_session = ctx
-                    .guest_session
-                    .take()
-                    .ok_or_else(|| BoxliteError::Internal("connect task must run first".into()))?;
-                let container_image_config = ctx
-                    .container_image_config
-                    .clone()
-                    .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?;
-                let volume_mgr = ctx.volume_mgr.take().ok_or_else(|| {
-                    BoxliteError::Internal("vmm_spawn task must run first".into())
-                })?;
-                let rootfs_init = ctx.rootfs_init.take().ok_or_else(|| {
-                    BoxliteError::Internal("vmm_spawn task must run first".into())
-                })?;
-                let container_mounts = ctx.container_mounts.take().ok_or_else(|| {
-                    BoxliteError::Internal("vmm_spawn task must run first".into())
-                })?;
-                (
-                    guest_session,
-                    container_image_config,
-                    ctx.config.container.id.clone(),
-                    volume_mgr,
-                    rootfs_init,
-                    container_mounts,
-                )
+                let e = BoxliteError::Unsupported(format!(
+                    "swap_mib needs a newer guest agent than box {} runs (version {}); \
+                     recreate the box to use the current guest rootfs",
+                    box_id, agent.version
+                ));
+                log_task_error(&box_id, task_name, &e);
+                return Err(e);
+            }
+            let guest_session = ctx
+                .guest_session
+                .take()
+                .ok_or_else(|| BoxliteError::Internal("connect task must run first".into()))?;
+            let image_config = ctx
+                .container_image_config
+                .clone()
+                .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?;
+            let vmm_spawn_first = || BoxliteError::Internal("vmm_spawn task must run first".into());
+            let volumes = ctx
+                .volume_mgr
+                .as_ref()
+                .ok_or_else(vmm_spawn_first)?
+                .build_guest_mounts();
+            let rootfs = ctx.rootfs_init.clone().ok_or_else(vmm_spawn_first)?;
+            let mounts = ctx.container_mounts.clone().ok_or_else(vmm_spawn_first)?;
+            let options = &ctx.config.options;
+            let guest_init_config = GuestInitConfig {
+                volumes,
+                network: Some(NetworkInitConfig {
+                    interface: "eth0".to_string(),
+                    ip: Some("192.168.127.2/24".to_string()),
+                    gateway: Some("192.168.127.1".to_string()),
+                }),
+                sysctls: options.sysctls.clone(),
+                swap,
+            };
+            let container_init_config = ContainerInitConfig {
+                image_config,
+                rootfs,
+                mounts,
+                resources: ContainerResourceLimits::from_vm_size(options.cpus, options.memory_mib),
+                hosts: ContainerHostsConfig::for_box(ctx.config.name.as_deref(), options),
+                shm_size_mib: options.shm_size_mib,
+                ulimits: options.ulimits.clone(),
             };
+            (
+                guest_session,
+                ctx.config.container.id.clone(),
+                guest_init_config,
+                container_init_config,
+            )
+        };
 
-        run_guest_init(
+        if let Err(e) = run_guest_init(
             guest_session.clone(),
-            &container_image_config,
             &container_id,
-            &volume_mgr,
-            &rootfs_init,
-            &container_mounts,
+            guest_init_config,
+            container_init_config,
         )
         .await
-        .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+        {
+            let e = attach_guest_logs(&guest_session, e).await;
+            log_task_error(&box_id, task_name, &e);
+            return Err(e);
+        }
 
         let mut ctx = ctx.lock().await;
         ctx.guest_session = Some(guest_session);
-        ctx.volume_mgr = Some(volume_mgr);
-        ctx.rootfs_init = Some(rootfs_init);
-        ctx.container_mounts = Some(container_mounts);
 
         Ok(())
     }
@@ -86,44 +118,48 @@ impl PipelineTask<InitCtx> for GuestInitTask {
 /// Initialize guest and start container.
 async fn run_guest_init(
     guest_session: GuestSession,
-    container_image_config: &ContainerImageConfig,
     container_id: &ContainerID,
-    volume_mgr: &GuestVolumeManager,
-    rootfs_init: &ContainerRootfsInitConfig,
-    container_mounts: &[ContainerMount],
+    guest_init_config: GuestInitConfig,
+    container_init_config: ContainerInitConfig,
 ) -> BoxliteResult<()> {
-    let container_id_str = container_id.as_str();
-
-    // Build guest volumes from volume manager
-    let guest_volumes = volume_mgr.build_guest_mounts();
-
-    let guest_init_config = GuestInitConfig {
-        volumes: guest_volumes,
-        network: Some(NetworkInitConfig {
-            interface: "eth0".to_string(),
-            ip: Some("192.168.127.2/24".to_string()),
-            gateway: Some("192.168.127.1".to_string()),
-        }),
-    };
-
-    // Step 1: Guest Init (volumes + network)
+    // Step 1: Guest Init (volumes + network + sysctls + swap)
     tracing::info!("Sending guest initialization request");
     let mut guest_interface = guest_session.guest().await?;
     guest_interface.init(guest_init_config).await?;
     tracing::info!("Guest initialized successfully");
 
-    // Step 2: Container Init (rootfs + container image config + user volume mounts)
+    // Step 2: Container Init (rootfs + container image config + user volume mounts + limits)
     tracing::info!("Sending container configuration to guest");
     let mut container_interface = guest_session.container().await?;
     let returned_id = container_interface
-        .init(
-            container_id_str,
-            container_image_config.clone(),
-            rootfs_init.clone(),
-            container_mounts.to_vec(),
-        )
+        .init(container_id.as_str(), container_init_config)
         .await?;
     tracing::info!(container_id = %returned_id, "Container initialized");
 
     Ok(())
 }
+
+/// Append the guest agent's last log lines to a guest init error.
+///
+/// Best-effort: if the agent doesn't answer quickly, the error is returned as is.
+async fn attach_guest_logs(guest_session: &GuestSession, err: BoxliteError) -> BoxliteError {
+    let fetch = async {
+        let mut guest = guest_session.guest().await?;
+        guest.logs(FAILURE_LOG_TAIL).await
+    };
+    let lines = match tokio::time::timeout(Duration::from_secs(2), fetch).await {
+        Ok(Ok(lines)) if !lines.is_empty() => lines,
+        _ => return err,
+    };
+
+    let tail = format!(
+        "\nguest agent log (last {} lines):\n{}",
+        lines.len(),
+        lines.join("\n")
+    );
+    match err {
+        BoxliteError::Internal(msg) => BoxliteError::Internal(msg + &tail),
+        BoxliteError::Rpc(msg) => BoxliteError::Rpc(msg + &tail),
+        other => other,
+    }
+}
@@ -40,7 +40,12 @@ use tokio::sync::Mutex;
 pub type InitCtx = Arc<Mutex<InitPipelineContext>>;
 
 async fn task_start(ctx: &InitCtx, task_name: &str) -> BoxID {
-    let box_id = { ctx.lock().await.config.id.clone() };
+    let box_id = {
+        let mut ctx = ctx.lock().await;
+        let offset = ctx.started_at.elapsed();
+        ctx.task_starts.push((task_name.to_string(), offset));
+        ctx.config.id.clone()
+    };
     tracing::debug!(box_id = %box_id, task = %task_name, "Executing task");
     box_id
 }
@@ -50,6 +55,7 @@ fn log_task_error(box_id: &BoxID, task_name: &str, err: &BoxliteError) {
 }
 
 pub use container_rootfs::ContainerRootfsTask;
+pub(crate) use container_rootfs::prewarm_image;
 pub use filesystem::FilesystemTask;
 pub use guest_connect::GuestConnectTask;
 pub use guest_init::GuestInitTask;
@@ -6,24 +6,30 @@
 use super::{InitCtx, log_task_error, task_start};
 use crate::disk::DiskFormat;
 use crate::images::ContainerImageConfig;
-use crate::litebox::init::types::resolve_user_volumes;
-use crate::net::NetworkBackendConfig;
+use crate::litebox::init::types::{
+    resolve_http_recorder, resolve_persistent_cache, resolve_user_volumes,
+};
+use crate::net::{NetworkBackendConfig, NetworkBackendEndpoint};
 use crate::pipeline::PipelineTask;
+use crate::plugins::NetworkRequest;
+use crate::portal::interfaces::ContainerRootfsInitConfig;
 use crate::runtime::constants::{guest_paths, mount_tags};
 use crate::runtime::guest_rootfs::{GuestRootfs, Strategy};
 use crate::runtime::layout::BoxFilesystemLayout;
-use crate::runtime::options::BoxOptions;
+use crate::runtime::options::{BoxOptions, NetworkSpec};
 use crate::runtime::rt_impl::SharedRuntimeImpl;
+use crate::runtime::sockets;
 use crate::runtime::types::{BoxID, ContainerID};
 use crate::util::find_binary;
 use crate::vmm::controller::{ShimController, VmmController, VmmHandler};
 use crate::vmm::{Entrypoint, InstanceSpec, VmmKind};
-use crate::volumes::{ContainerMount, ContainerVolumeManager, GuestVolumeManager};
+use crate::volumes::{ContainerMount, ContainerVolumeManager, GuestVolumeManager, UserVolume};
 use async_trait::async_trait;
 use boxlite_shared::Transport;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct VmmSpawnTask;
 
@@ -35,12 +41,17 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
 
         // Gather all inputs from previous tasks
         let (
+            box_name,
+            expires_at,
             options,
             layout,
             container_image_config,
-            container_disk_path,
+            (container_disk_path, container_disk_format),
+            container_layer_disks,
             guest_disk_path,
             container_id,
+            transport,
+            ready_socket_path,
             runtime,
             reuse_rootfs,
         ) = {
@@ -53,39 +64,67 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
                 .container_image_config
                 .clone()
                 .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?;
-            let container_disk_path = ctx
+            let container_disk = ctx
                 .container_disk
                 .as_ref()
-                .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?
-                .path()
-                .to_path_buf();
+                .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?;
+            let container_disk = (container_disk.path().to_path_buf(), container_disk.format());
             let guest_disk_path = ctx.guest_disk.as_ref().map(|d| d.path().to_path_buf());
             (
+                ctx.config.name.clone(),
+                ctx.config.expires_at(),
                 ctx.config.options.clone(),
                 layout,
                 container_image_config,
-                container_disk_path,
+                container_disk,
+                ctx.container_layer_disks.clone(),
                 guest_disk_path,
                 ctx.config.container.id.clone(),
+                ctx.config.transport.clone(),
+                ctx.config.ready_socket_path.clone(),
                 ctx.runtime.clone(),
                 ctx.reuse_rootfs,
             )
         };
 
+        // Mount driver volumes so the config and jailer see host directories
+        let options = mount_driver_volumes(&runtime, &box_id, options)
+            .await
+            .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+
         // Build config and get outputs
-        let (instance_spec, volume_mgr, rootfs_init, container_mounts) = build_config(
+        let config_start = std::time::Instant::now();
+        let (mut instance_spec, volume_mgr, rootfs_init, container_mounts) = build_config(
             &box_id,
+            box_name.as_deref(),
+            expires_at,
             &options,
             &layout,
             &container_image_config,
             &container_disk_path,
+            container_disk_format,
+            &container_layer_disks,
             guest_disk_path.as_deref(),
             &container_id,
+            transport,
+            Transport::unix(ready_socket_path),
             &runtime,
             reuse_rootfs,
         )
         .await
         .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+        runtime
+            .init_hooks
+            .configure_vm(&box_id, &mut instance_spec)
+            .await
+            .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+
+        {
+            let mut ctx = ctx.lock().await;
+            ctx.config_build_ms = Some(config_start.elapsed().as_millis());
+            // Keep the rendered config for the boot diagnostic bundle
+            ctx.vm_config_json = serde_json::to_string_pretty(&instance_spec).ok();
+        }
 
         // Spawn VM
         let handler = spawn_vm(&box_id, &instance_spec, &options)
@@ -109,25 +148,53 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
 #[allow(clippy::too_many_arguments)]
 async fn build_config(
     box_id: &BoxID,
+    box_name: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
     options: &BoxOptions,
     layout: &BoxFilesystemLayout,
     container_image_config: &ContainerImageConfig,
     container_disk_path: &Path,
+    container_disk_format: DiskFormat,
+    container_layer_disks: &[PathBuf],
     guest_disk_path: Option<&Path>,
     container_id: &ContainerID,
+    transport: Transport,
+    ready_transport: Transport,
     runtime: &SharedRuntimeImpl,
     reuse_rootfs: bool,
 ) -> BoxliteResult<(
     InstanceSpec,
     GuestVolumeManager,
-    crate::portal::interfaces::ContainerRootfsInitConfig,
+    ContainerRootfsInitConfig,
     Vec<ContainerMount>,
 )> {
-    // Transport setup
-    let transport = Transport::unix(layout.socket_path());
-    let ready_transport = Transport::unix(layout.ready_socket_path());
+    // Transport setup: the socket dir may live outside the box dir
+    // (see runtime::sockets), e.g. on a tmpfs cleared by a reboot
+    for t in [&transport, &ready_transport] {
+        if let Transport::Unix { socket_path } = t
+            && let Some(dir) = socket_path.parent()
+        {
+            sockets::check_socket_path(socket_path)?;
+            sockets::prepare(dir)?;
+        }
+    }
 
-    let user_volumes = resolve_user_volumes(&options.volumes)?;
+    let mut user_volumes = resolve_user_volumes(&options.volumes)?;
+    if let Some(ref cache_path) = options.persistent_cache {
+        user_volumes.push(resolve_persistent_cache(
+            &runtime.layout.cache_dir(),
+            box_name,
+            cache_path,
+        )?);
+    }
+    // The recording proxy runs in gvproxy; the container trusts its CA
+    let mut http_recorder = None;
+    if let Some((config, ca_volume)) =
+        resolve_http_recorder(&options.network_mode, &layout.recorder_dir())?
+    {
+        user_volumes.push(ca_volume);
+        http_recorder = Some(config);
+    }
 
     // Prepare container directories (image/, rw/, rootfs/)
     let container_layout = layout.shared_layout().container(container_id.as_str());
@@ -139,29 +206,30 @@ async fn build_config(
     // SHARED virtiofs - needed by all strategies
     volume_mgr.add_fs_share(mount_tags::SHARED, layout.shared_dir(), None, false, None);
 
-    // Add container rootfs disk (COW overlay workflow):
-    // 1. Base disk: Pre-built ext4 image with container layers merged
-    // 2. COW disk: QCOW2 overlay with copy-on-write semantics
-    //    - Inherits formatted ext4 from base (need_format=false)
-    //    - May have larger virtual size if disk_size_gb specified
-    // 3. Guest mount: Only resize on fresh start, not restart
-    //    - Fresh start with custom size: resize2fs expands filesystem
-    //    - Restart: filesystem already at correct size, skip resize
-    let need_resize = options.disk_size_gb.is_some() && !reuse_rootfs;
-    let rootfs_device = volume_mgr.add_block_device(
-        container_disk_path,
-        DiskFormat::Qcow2,
+    // Recorded exec output, written by the guest
+    volume_mgr.add_fs_share(
+        mount_tags::EXEC_LOGS,
+        layout.exec_logs_dir(),
+        None,
         false,
         None,
-        false,       // need_format: COW child inherits formatted base
-        need_resize, // need_resize: only on fresh start with custom disk size
     );
 
-    // Update rootfs_init with actual device path and resize flag
-    let rootfs_init = crate::portal::interfaces::ContainerRootfsInitConfig::DiskImage {
-        device: rootfs_device,
-        need_format: false, // COW child uses pre-formatted base
-        need_resize,        // Only on fresh start with custom disk size
+    let rootfs_init = if container_layer_disks.is_empty() {
+        add_disk_rootfs(
+            &mut volume_mgr,
+            container_disk_path,
+            container_disk_format,
+            options,
+            reuse_rootfs,
+        )
+    } else {
+        add_erofs_rootfs(
+            &mut volume_mgr,
+            container_disk_path,
+            container_layer_disks,
+            reuse_rootfs,
+        )
     };
 
     // Add user volumes via ContainerVolumeManager
@@ -169,11 +237,15 @@ async fn build_config(
     for vol in &user_volumes {
         container_mgr.add_volume(
             container_id.as_str(),
-            &vol.tag,
-            &vol.tag,
-            vol.host_path.clone(),
-            &vol.guest_path,
-            vol.read_only,
+            UserVolume {
+                volume_name: &vol.tag,
+                tag: &vol.tag,
+                host_path: vol.host_path.clone(),
+                container_path: &vol.guest_path,
+                read_only: vol.read_only,
+                options: &vol.options,
+                map_user: vol.map_user,
+            },
         );
     }
     let container_mounts = container_mgr.build_container_mounts();
@@ -195,7 +267,31 @@ async fn build_config(
         build_guest_entrypoint(&transport, &ready_transport, &guest_rootfs, options)?;
 
     // Network configuration
-    let network_config = build_network_config(container_image_config, options);
+    let mut network_config = build_network_config(container_image_config, options);
+    if options.prompt_connections
+        && let Some(config) = network_config.as_mut()
+        && let Transport::Unix { socket_path } = &ready_transport
+    {
+        // Served by the host while the box runs (see runtime::connection_prompt)
+        config.connection_prompt_socket = Some(socket_path.with_file_name(sockets::PROMPT_SOCKET));
+    }
+    if let Some(config) = network_config.as_mut() {
+        config.http_recorder = http_recorder;
+        config.host_loopback = options.host_loopback;
+        if !options.networks.is_empty() {
+            // Rewritten by the host as peers come and go (see runtime::networks)
+            config.peers_file = Some(layout.network_peers_path());
+        }
+    }
+    let mut network_backend_endpoint = None;
+    if let NetworkSpec::Custom(ref driver_name) = options.network {
+        let port_mappings = network_config
+            .take()
+            .map(|config| config.port_mappings)
+            .unwrap_or_default();
+        network_backend_endpoint =
+            Some(connect_driver_network(runtime, box_id, driver_name, port_mappings).await?);
+    }
 
     // Use runtime home for logs (not box_home)
     let runtime_home = runtime.layout.home_dir();
@@ -217,16 +313,85 @@ async fn build_config(
         ready_transport: ready_transport.clone(),
         guest_rootfs,
         network_config,
-        network_backend_endpoint: None,
+        network_backend_endpoint,
         home_dir: runtime_home.to_path_buf(),
         console_output: Some(logs_dir.join(format!("{}-console.log", box_id))),
         detach: options.detach,
         parent_pid: std::process::id(),
+        expires_at,
     };
 
     Ok((instance_spec, volume_mgr, rootfs_init, container_mounts))
 }
 
+/// Attach the container rootfs COW disk (ext4 and reflink storage drivers).
+///
+/// 1. Base disk: Pre-built ext4 image with container layers merged
+/// 2. COW disk: QCOW2 overlay with copy-on-write semantics, or a raw reflink
+///    copy sharing the base's blocks (reflink storage driver)
+///    - Inherits formatted ext4 from base (need_format=false)
+///    - May have larger virtual size if disk_size_gb specified
+/// 3. Guest mount: Only resize on fresh start, not restart
+///    - Fresh start with custom size: resize2fs expands filesystem
+///    - Restart: filesystem already at correct size, skip resize
+fn add_disk_rootfs(
+    volume_mgr: &mut GuestVolumeManager,
+    container_disk_path: &Path,
+    container_disk_format: DiskFormat,
+    options: &BoxOptions,
+    reuse_rootfs: bool,
+) -> ContainerRootfsInitConfig {
+    let need_resize = options.disk_size_gb.is_some() && !reuse_rootfs;
+    let rootfs_device = volume_mgr.add_block_device(
+        container_disk_path,
+        container_disk_format, // qcow2 overlay, or raw reflink copy (reflink driver)
+        false,
+        None,
+        false,       // need_format: COW child inherits formatted base
+        need_resize, // need_resize: only on fresh start with custom disk size
+    );
+
+    ContainerRootfsInitConfig::DiskImage {
+        device: rootfs_device,
+        need_format: false, // COW child uses pre-formatted base
+        need_resize,        // Only on fresh start with custom disk size
+    }
+}
+
+/// Attach the upper disk and read-only erofs layer images (erofs storage driver).
+///
+/// The upper disk starts empty and is formatted by the guest on first boot;
+/// on restart it already holds the box's writes.
+fn add_erofs_rootfs(
+    volume_mgr: &mut GuestVolumeManager,
+    upper_disk_path: &Path,
+    layer_disks: &[PathBuf],
+    reuse_rootfs: bool,
+) -> ContainerRootfsInitConfig {
+    let need_format = !reuse_rootfs;
+    let upper_device = volume_mgr.add_block_device(
+        upper_disk_path,
+        DiskFormat::Qcow2,
+        false,
+        None,
+        false, // formatted by the rootfs strategy, not as a guest volume
+        false,
+    );
+
+    let layer_devices = layer_disks
+        .iter()
+        .map(|layer| {
+            volume_mgr.add_block_device(layer, DiskFormat::Erofs, true, None, false, false)
+        })
+        .collect();
+
+    ContainerRootfsInitConfig::ErofsOverlay {
+        layer_devices,
+        upper_device,
+        need_format,
+    }
+}
+
 /// Configure guest rootfs with device path from volume manager.
 fn configure_guest_rootfs(
     mut guest_rootfs: GuestRootfs,
@@ -290,14 +455,20 @@ fn build_guest_entrypoint(
         env.push(("RUST_BACKTRACE".to_string(), rust_backtrace));
     }
 
+    let mut args = vec![
+        "--listen".to_string(),
+        listen_uri,
+        "--notify".to_string(),
+        ready_notify_uri,
+    ];
+    if let Some(ref level) = options.guest_log_level {
+        args.push("--log-level".to_string());
+        args.push(level.clone());
+    }
+
     Ok(Entrypoint {
         executable: format!("{}/boxlite-guest", guest_paths::BIN_DIR),
-        args: vec![
-            "--listen".to_string(),
-            listen_uri,
-            "--notify".to_string(),
-            ready_notify_uri,
-        ],
+        args,
         env,
     })
 }
@@ -341,6 +512,50 @@ fn build_network_config(
     Some(NetworkBackendConfig::new(final_mappings))
 }
 
+/// Mount volumes provided by volume drivers, returning options that refer
+/// to their host directories.
+async fn mount_driver_volumes(
+    runtime: &SharedRuntimeImpl,
+    box_id: &BoxID,
+    mut options: BoxOptions,
+) -> BoxliteResult<BoxOptions> {
+    if options.volumes.iter().all(|v| v.driver.is_none()) {
+        return Ok(options);
+    }
+    let runtime = runtime.clone();
+    let box_id = box_id.to_string();
+    tokio::task::spawn_blocking(move || {
+        runtime.plugins.mount_volumes(&box_id, &mut options)?;
+        Ok(options)
+    })
+    .await
+    .map_err(|e| BoxliteError::Internal(format!("volume mount task failed: {}", e)))?
+}
+
+/// Have a network driver set up networking, returning the VM's endpoint.
+async fn connect_driver_network(
+    runtime: &SharedRuntimeImpl,
+    box_id: &BoxID,
+    driver_name: &str,
+    port_mappings: Vec<(u16, u16)>,
+) -> BoxliteResult<NetworkBackendEndpoint> {
+    let runtime = runtime.clone();
+    let driver_name = driver_name.to_string();
+    let request = NetworkRequest {
+        box_id: box_id.to_string(),
+        port_mappings,
+    };
+    tokio::task::spawn_blocking(move || {
+        tracing::info!(box_id = %request.box_id, driver = %driver_name, "Using network driver");
+        runtime
+            .plugins
+            .network_driver(&driver_name)?
+            .connect(&request)
+    })
+    .await
+    .map_err(|e| BoxliteError::Internal(format!("network connect task failed: {}", e)))?
+}
+
 /// Spawn VM subprocess and return handler.
 async fn spawn_vm(
     box_id: &BoxID,
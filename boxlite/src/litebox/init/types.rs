@@ -1,21 +1,24 @@
 //! Type definitions for initialization pipeline.
 
 use crate::BoxID;
-use crate::disk::Disk;
+use crate::disk::{BackingFormat, Disk};
 #[cfg(target_os = "linux")]
 use crate::fs::BindMountHandle;
 use crate::images::ContainerImageConfig;
 use crate::litebox::config::BoxConfig;
+use crate::litebox::{GuestAgentInfo, RootfsBuildTimings};
+use crate::net::recorder::{self, HttpRecorderConfig};
 use crate::portal::GuestSession;
 use crate::portal::interfaces::ContainerRootfsInitConfig;
 use crate::runtime::layout::BoxFilesystemLayout;
-use crate::runtime::options::VolumeSpec;
+use crate::runtime::options::{NetworkMode, VolumeChown, VolumeSpec};
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use crate::vmm::controller::VmmHandler;
 use crate::volumes::{ContainerMount, GuestVolumeManager};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
 /// Switch between merged and overlayfs rootfs strategies.
 /// - true: overlayfs (allows COW writes, keeps layers separate)
@@ -37,6 +40,12 @@ pub struct ResolvedVolume {
     pub host_path: PathBuf,
     pub guest_path: String,
     pub read_only: bool,
+    /// Options applied to the container mount besides ro/rw
+    /// (noexec, nosuid, nodev, uid=, gid=).
+    pub options: Vec<String>,
+    /// Map the container's user to the owner of the directory
+    /// (`VolumeChown::HostUser`).
+    pub map_user: bool,
 }
 
 pub fn resolve_user_volumes(volumes: &[VolumeSpec]) -> BoxliteResult<Vec<ResolvedVolume>> {
@@ -72,7 +81,7 @@ pub fn resolve_user_volumes(volumes: &[VolumeSpec]) -> BoxliteResult<Vec<Resolve
             tag = %tag,
             host_path = %resolved_path.display(),
             guest_path = %vol.guest_path,
-            read_only = vol.read_only,
+            options = ?vol.effective_options(),
             "Resolved user volume"
         );
 
@@ -80,13 +89,110 @@ pub fn resolve_user_volumes(volumes: &[VolumeSpec]) -> BoxliteResult<Vec<Resolve
             tag,
             host_path: resolved_path,
             guest_path: vol.guest_path.clone(),
-            read_only: vol.read_only,
+            read_only: vol.is_read_only(),
+            options: vol.mount_options(),
+            map_user: vol.chown == Some(VolumeChown::HostUser),
         });
     }
 
     Ok(resolved)
 }
 
+/// Resolve the persistent cache volume for a named box.
+///
+/// Creates `<cache_dir>/<name>` on first use; it is never removed with the box.
+pub fn resolve_persistent_cache(
+    cache_dir: &Path,
+    box_name: Option<&str>,
+    guest_path: &str,
+) -> BoxliteResult<ResolvedVolume> {
+    let name = box_name
+        .ok_or_else(|| BoxliteError::Config("persistent_cache requires a named box".into()))?;
+
+    // The name becomes a directory component; refuse anything that could escape
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(BoxliteError::Config(format!(
+            "box name '{}' can't be used as a persistent cache directory",
+            name
+        )));
+    }
+
+    let host_path = cache_dir.join(name);
+    std::fs::create_dir_all(&host_path).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to create persistent cache directory {}: {}",
+            host_path.display(),
+            e
+        ))
+    })?;
+
+    tracing::debug!(
+        host_path = %host_path.display(),
+        guest_path = %guest_path,
+        "Resolved persistent cache volume"
+    );
+
+    Ok(ResolvedVolume {
+        tag: "cache".to_string(),
+        host_path,
+        guest_path: guest_path.to_string(),
+        read_only: false,
+        options: Vec::new(),
+        map_user: false,
+    })
+}
+
+/// Resolve the HTTP recorder of a box with a recording `network_mode`: the
+/// proxy config and the volume sharing its CA certificate with the container.
+///
+/// A `Record` directory is created if needed; a `Replay` one must exist.
+pub fn resolve_http_recorder(
+    network_mode: &NetworkMode,
+    recorder_dir: &Path,
+) -> BoxliteResult<Option<(HttpRecorderConfig, ResolvedVolume)>> {
+    let Some((dir, replay)) = network_mode.recording() else {
+        return Ok(None);
+    };
+
+    if replay && !dir.is_dir() {
+        return Err(BoxliteError::Config(format!(
+            "network_mode replay directory {} does not exist",
+            dir.display()
+        )));
+    }
+    let mut dirs = vec![recorder_dir];
+    if !replay {
+        dirs.push(dir);
+    }
+    for path in dirs {
+        std::fs::create_dir_all(path).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to create HTTP recorder directory {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    }
+
+    tracing::debug!(dir = %dir.display(), replay, "Resolved HTTP recorder");
+
+    let config = HttpRecorderConfig {
+        replay,
+        dir: dir.to_path_buf(),
+        ca_cert: recorder_dir.join(recorder::CA_FILE),
+        port: recorder::PROXY_PORT,
+    };
+    let volume = ResolvedVolume {
+        tag: "recorder".to_string(),
+        host_path: recorder_dir.to_path_buf(),
+        guest_path: recorder::CA_GUEST_DIR.to_string(),
+        read_only: true,
+        options: Vec::new(),
+        map_user: false,
+    };
+    Ok(Some((config, volume)))
+}
+
 /// Result of rootfs preparation - either merged, separate layers, or disk image.
 #[derive(Debug)]
 pub enum ContainerRootfsPrepResult {
@@ -104,10 +210,21 @@ pub enum ContainerRootfsPrepResult {
     /// Disk image containing the complete rootfs
     /// The disk is attached as a block device and mounted directly
     DiskImage {
-        /// Path to the base ext4 disk image (cached, shared across boxes)
+        /// Path to the base disk image (cached and shared across boxes, or
+        /// the user's `RootfsSpec::DiskImage`)
         base_disk_path: PathBuf,
+        /// Format of the base disk, backing the box's qcow2 overlay
+        backing_format: BackingFormat,
         /// Size of the disk in bytes (for creating COW overlay)
         disk_size: u64,
+        /// How the disk's rootfs was built (None when the cached disk was used)
+        build: Option<RootfsBuildTimings>,
+    },
+    /// One cached erofs image per layer (bottom first), stacked by guest-side overlayfs
+    /// over an empty per-box upper disk
+    ErofsLayers {
+        /// Paths to the per-layer erofs images (cached, shared across boxes)
+        layer_disks: Vec<PathBuf>,
     },
 }
 
@@ -188,7 +305,7 @@ impl Drop for CleanupGuard {
         // First mark as crashed so remove_box() doesn't fail the active check
         // TODO(@DorianZheng) Check if this is necessary
         if let Ok(mut state) = self.runtime.box_manager.update_box(&self.box_id) {
-            state.mark_stop();
+            state.mark_stop_with_reason("initialization failed");
             let _ = self.runtime.box_manager.save_box(&self.box_id, &state);
         }
         if let Err(e) = self.runtime.box_manager.remove_box(&self.box_id) {
@@ -218,11 +335,26 @@ pub struct InitPipelineContext {
     pub layout: Option<BoxFilesystemLayout>,
     pub container_image_config: Option<ContainerImageConfig>,
     pub container_disk: Option<Disk>,
+    /// Read-only erofs layer images (erofs storage driver only; empty otherwise).
+    pub container_layer_disks: Vec<PathBuf>,
     pub guest_disk: Option<Disk>,
     pub volume_mgr: Option<GuestVolumeManager>,
     pub rootfs_init: Option<ContainerRootfsInitConfig>,
     pub container_mounts: Option<Vec<ContainerMount>>,
     pub guest_session: Option<GuestSession>,
+    /// Version and capabilities the agent reported on connect.
+    pub guest_agent: Option<GuestAgentInfo>,
+
+    /// When the pipeline started (for the boot diagnostic bundle).
+    pub started_at: Instant,
+    /// Tasks in the order they started, with their offset from `started_at`.
+    pub task_starts: Vec<(String, Duration)>,
+    /// VM config rendered by VmmSpawn, as JSON.
+    pub vm_config_json: Option<String>,
+    /// Time VmmSpawn spent building the VM config.
+    pub config_build_ms: Option<u128>,
+    /// How ContainerRootfs built the rootfs, if it had to.
+    pub rootfs_build: Option<RootfsBuildTimings>,
 
     #[cfg(target_os = "linux")]
     pub bind_mount: Option<BindMountHandle>,
@@ -245,11 +377,18 @@ impl InitPipelineContext {
             layout: None,
             container_image_config: None,
             container_disk: None,
+            container_layer_disks: Vec::new(),
             guest_disk: None,
             volume_mgr: None,
             rootfs_init: None,
             container_mounts: None,
             guest_session: None,
+            guest_agent: None,
+            started_at: Instant::now(),
+            task_starts: Vec::new(),
+            vm_config_json: None,
+            config_build_ms: None,
+            rootfs_build: None,
             #[cfg(target_os = "linux")]
             bind_mount: None,
         }
@@ -1,23 +1,33 @@
 //! Box state backend.
 //!
-//! Pure database access layer for box persistence.
-//! No in-memory cache - queries go directly to database.
+//! Database access layer for box persistence. Queries go directly to the
+//! database, except that `list_info` reuses the `BoxInfo` of boxes whose
+//! status and name haven't changed since they were last listed.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 use crate::db::BoxStore;
 use crate::litebox::config::BoxConfig;
-use crate::runtime::types::{BoxID, BoxState};
+use crate::runtime::networks;
+use crate::runtime::types::{BoxFilter, BoxID, BoxInfo, BoxState, ProjectInfo};
 
 /// State backend for box persistence.
 ///
-/// Pure database access layer for box state.
-/// All queries go directly to database - no in-memory cache.
+/// Database access layer for box state.
+///
+/// Every write goes through the manager, which drops the written box from
+/// the `BoxInfo` cache. The runtime lock keeps other processes from writing
+/// the same database.
 #[derive(Clone)]
 pub struct BoxManager {
     store: Arc<BoxStore>,
+    /// `BoxInfo` of listed boxes, by ID
+    info_cache: Arc<RwLock<HashMap<String, BoxInfo>>>,
+    /// Held while adding a box, so two boxes can't take one peer address
+    add_lock: Arc<Mutex<()>>,
 }
 
 impl std::fmt::Debug for BoxManager {
@@ -31,6 +41,8 @@ impl BoxManager {
     pub fn new(store: BoxStore) -> Self {
         Self {
             store: Arc::new(store),
+            info_cache: Arc::new(RwLock::new(HashMap::new())),
+            add_lock: Arc::new(Mutex::new(())),
         }
     }
 
@@ -39,7 +51,12 @@ impl BoxManager {
     // ========================================================================
 
     /// Add a new box to the database.
-    pub fn add_box(&self, config: &BoxConfig, state: &BoxState) -> BoxliteResult<()> {
+    ///
+    /// A box on networks without a peer address gets one here, which it
+    /// keeps until it is removed.
+    pub fn add_box(&self, config: &BoxConfig, state: &mut BoxState) -> BoxliteResult<()> {
+        let _adding = self.add_lock.lock().unwrap_or_else(|e| e.into_inner());
+
         // Check name uniqueness if name is set
         if let Some(ref name) = config.name
             && self.lookup_box_id(name)?.is_some()
@@ -58,12 +75,16 @@ impl BoxManager {
             )));
         }
 
+        if !config.options.networks.is_empty() && state.peer_ip.is_none() {
+            state.peer_ip = networks::free_peer_ip(&config.project, &self.store.list_all()?);
+        }
+
         self.store.save(config, state)?;
 
         tracing::debug!(
             box_id = %config.id,
             name = ?config.name,
-            status = ?state.status,
+            status = ?state.status(),
             "Added box to state"
         );
 
@@ -78,12 +99,30 @@ impl BoxManager {
         }
 
         self.store.delete(id.as_str())?;
+        self.invalidate(id);
 
         tracing::debug!(box_id = %id, "Removed box from state");
 
         Ok(())
     }
 
+    /// Database tables still holding rows of a box.
+    pub fn leftover_tables(&self, id: &BoxID) -> BoxliteResult<Vec<&'static str>> {
+        self.store.tables_with_rows(id.as_str())
+    }
+
+    /// Delete whatever rows of a box are left.
+    pub fn purge_box(&self, id: &BoxID) -> BoxliteResult<()> {
+        self.store.purge(id.as_str())?;
+        self.invalidate(id);
+        Ok(())
+    }
+
+    /// IDs of box state rows without a box configuration.
+    pub fn orphaned_state_ids(&self) -> BoxliteResult<Vec<String>> {
+        self.store.orphaned_state_ids()
+    }
+
     /// Get a box by exact ID.
     pub fn box_by_id(&self, id: &BoxID) -> BoxliteResult<Option<(BoxConfig, BoxState)>> {
         self.store.load(id.as_str())
@@ -144,15 +183,80 @@ impl BoxManager {
         self.store.list_all()
     }
 
+    /// Get the info of the boxes passing `filter` (in one project, if
+    /// given), newest first.
+    ///
+    /// Reads only the queryable columns, then parses the stored JSON just for
+    /// boxes that aren't cached or whose status changed.
+    pub fn list_info(
+        &self,
+        project: Option<&str>,
+        filter: &BoxFilter,
+    ) -> BoxliteResult<Vec<BoxInfo>> {
+        let summaries = self.store.list_summaries(project, filter)?;
+
+        let mut infos = Vec::with_capacity(summaries.len());
+        let mut stale = Vec::new();
+        {
+            let cache = self.info_cache.read().unwrap();
+            for summary in &summaries {
+                match cache.get(&summary.id) {
+                    Some(info)
+                        if info.status.as_str() == summary.status && info.name == summary.name =>
+                    {
+                        infos.push(info.clone())
+                    }
+                    _ => stale.push(summary.id.as_str()),
+                }
+            }
+        }
+
+        if !stale.is_empty() {
+            let mut cache = self.info_cache.write().unwrap();
+            for id in stale {
+                // Removed since the summaries were read
+                let Some((config, state)) = self.store.load(id)? else {
+                    continue;
+                };
+                let info = BoxInfo::new(&config, &state);
+                cache.insert(id.to_string(), info.clone());
+                infos.push(info);
+            }
+            infos.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        }
+
+        Ok(infos)
+    }
+
+    /// Drop a box from the `BoxInfo` cache after writing it.
+    fn invalidate(&self, id: &BoxID) {
+        self.info_cache.write().unwrap().remove(id.as_str());
+    }
+
+    /// Get every project that has boxes, sorted by name.
+    pub fn projects(&self) -> BoxliteResult<Vec<ProjectInfo>> {
+        Ok(self
+            .store
+            .project_counts()?
+            .into_iter()
+            .map(|(name, boxes, running)| ProjectInfo {
+                name,
+                boxes,
+                running,
+            })
+            .collect())
+    }
+
     /// Save box state to the database.
     ///
     /// Reads state from the provided BoxState and persists to DB.
     pub fn save_box(&self, id: &BoxID, state: &BoxState) -> BoxliteResult<()> {
         self.store.update_state(id.as_str(), state)?;
+        self.invalidate(id);
 
         tracing::trace!(
             box_id = %id,
-            status = ?state.status,
+            status = ?state.status(),
             "Saved box state to database"
         );
 
@@ -168,6 +272,11 @@ impl BoxManager {
             .ok_or_else(|| BoxliteError::NotFound(id.to_string()))
     }
 
+    /// Commit state writes grouped by batched durability.
+    pub fn flush(&self) -> BoxliteResult<()> {
+        self.store.flush()
+    }
+
     // ========================================================================
     // Recovery helpers
     // ========================================================================
@@ -181,6 +290,7 @@ impl BoxManager {
         if is_reboot {
             tracing::info!("Detected system reboot, resetting active boxes to stopped");
             let reset_ids = self.store.reset_active_boxes_after_reboot()?;
+            self.info_cache.write().unwrap().clear();
             for id in &reset_ids {
                 tracing::info!(box_id = %id, "Reset box to stopped after reboot");
             }
@@ -215,6 +325,7 @@ mod tests {
             id: BoxID::parse(id).unwrap(),
             name: None,
             created_at: Utc::now(),
+            project: "default".to_string(),
             container: ContainerRuntimeConfig {
                 id: ContainerID::new(),
             },
@@ -224,16 +335,19 @@ mod tests {
                 memory_mib: Some(512),
                 ..Default::default()
             },
+            storage_driver: Default::default(),
             engine_kind: VmmKind::Libkrun,
             transport: Transport::unix(PathBuf::from("/tmp/test.sock")),
             box_home: PathBuf::from("/tmp/box"),
             ready_socket_path: PathBuf::from("/tmp/ready"),
+            warnings: Vec::new(),
+            disk_image: None,
         }
     }
 
     fn create_test_state(status: BoxStatus) -> BoxState {
         let mut state = BoxState::new();
-        state.set_status(status);
+        state.recover_to(status, "test");
         state
     }
 
@@ -247,13 +361,13 @@ mod tests {
         let store = create_test_store();
         let manager = BoxManager::new(store);
         let config = create_test_config(TEST_ID_1);
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
-        manager.add_box(&config, &state).unwrap();
+        manager.add_box(&config, &mut state).unwrap();
 
         let (retrieved_config, retrieved_state) = manager.box_by_id(&config.id).unwrap().unwrap();
         assert_eq!(retrieved_config.id, config.id);
-        assert_eq!(retrieved_state.status, BoxStatus::Configured);
+        assert_eq!(retrieved_state.status(), BoxStatus::Configured);
     }
 
     #[test]
@@ -261,10 +375,10 @@ mod tests {
         let store = create_test_store();
         let manager = BoxManager::new(store);
         let config = create_test_config(TEST_ID_1);
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
-        manager.add_box(&config, &state).unwrap();
-        let result = manager.add_box(&config, &state);
+        manager.add_box(&config, &mut state).unwrap();
+        let result = manager.add_box(&config, &mut state);
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
@@ -277,14 +391,14 @@ mod tests {
 
         let mut config1 = create_test_config(TEST_ID_1);
         config1.name = Some("my-box".to_string());
-        let state1 = BoxState::new();
+        let mut state1 = BoxState::new();
 
         let mut config2 = create_test_config(TEST_ID_2);
         config2.name = Some("my-box".to_string());
-        let state2 = BoxState::new();
+        let mut state2 = BoxState::new();
 
-        manager.add_box(&config1, &state1).unwrap();
-        let result = manager.add_box(&config2, &state2);
+        manager.add_box(&config1, &mut state1).unwrap();
+        let result = manager.add_box(&config2, &mut state2);
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
@@ -295,10 +409,10 @@ mod tests {
         let store = create_test_store();
         let manager = BoxManager::new(store);
         let config = create_test_config(TEST_ID_1);
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
         assert!(!manager.has_box(&config.id).unwrap());
-        manager.add_box(&config, &state).unwrap();
+        manager.add_box(&config, &mut state).unwrap();
         assert!(manager.has_box(&config.id).unwrap());
     }
 
@@ -309,9 +423,9 @@ mod tests {
 
         let mut config = create_test_config(TEST_ID_1);
         config.name = Some("my-box".to_string());
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
-        manager.add_box(&config, &state).unwrap();
+        manager.add_box(&config, &mut state).unwrap();
 
         let result = manager.lookup_box("my-box").unwrap();
         assert!(result.is_some());
@@ -323,9 +437,9 @@ mod tests {
         let store = create_test_store();
         let manager = BoxManager::new(store);
         let config = create_test_config(TEST_ID_1);
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
-        manager.add_box(&config, &state).unwrap();
+        manager.add_box(&config, &mut state).unwrap();
 
         // Use first 12 chars as prefix
         let result = manager.lookup_box(&TEST_ID_1[..12]).unwrap();
@@ -340,10 +454,10 @@ mod tests {
 
         // Use IDs with same prefix
         manager
-            .add_box(&create_test_config(TEST_ID_1), &BoxState::new())
+            .add_box(&create_test_config(TEST_ID_1), &mut BoxState::new())
             .unwrap();
         manager
-            .add_box(&create_test_config(TEST_ID_2), &BoxState::new())
+            .add_box(&create_test_config(TEST_ID_2), &mut BoxState::new())
             .unwrap();
 
         // Common prefix for TEST_ID_1 and TEST_ID_2
@@ -365,19 +479,19 @@ mod tests {
         manager
             .add_box(
                 &create_test_config(TEST_ID_1),
-                &create_test_state(BoxStatus::Running),
+                &mut create_test_state(BoxStatus::Running),
             )
             .unwrap();
         manager
             .add_box(
                 &create_test_config(TEST_ID_2),
-                &create_test_state(BoxStatus::Stopped),
+                &mut create_test_state(BoxStatus::Stopped),
             )
             .unwrap();
         manager
             .add_box(
                 &create_test_config(TEST_ID_3),
-                &create_test_state(BoxStatus::Running),
+                &mut create_test_state(BoxStatus::Running),
             )
             .unwrap();
 
@@ -390,32 +504,106 @@ mod tests {
         let store = create_test_store();
         let manager = BoxManager::new(store);
         let config = create_test_config(TEST_ID_1);
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
-        manager.add_box(&config, &state).unwrap();
+        manager.add_box(&config, &mut state).unwrap();
         manager.remove_box(&config.id).unwrap();
 
         assert!(manager.box_by_id(&config.id).unwrap().is_none());
     }
 
+    #[test]
+    fn test_list_info_cache() {
+        let store = create_test_store();
+        let manager = BoxManager::new(store);
+        let config = create_test_config(TEST_ID_1);
+        manager
+            .add_box(&config, &mut create_test_state(BoxStatus::Configured))
+            .unwrap();
+
+        let infos = manager.list_info(None, &BoxFilter::default()).unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].status, BoxStatus::Configured);
+        assert!(manager.info_cache.read().unwrap().contains_key(TEST_ID_1));
+
+        // A state write is visible on the next list
+        let mut running = create_test_state(BoxStatus::Running);
+        running.set_pid(Some(42));
+        manager.save_box(&config.id, &running).unwrap();
+        let infos = manager.list_info(None, &BoxFilter::default()).unwrap();
+        assert_eq!(infos[0].status, BoxStatus::Running);
+        assert_eq!(infos[0].pid, Some(42));
+
+        assert!(
+            manager
+                .list_info(Some("other"), &BoxFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+
+        manager.remove_box(&config.id).unwrap();
+        assert!(
+            manager
+                .list_info(None, &BoxFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(manager.info_cache.read().unwrap().is_empty());
+    }
+
     #[test]
     fn test_save_and_update_box() {
         let store = create_test_store();
         let manager = BoxManager::new(store);
         let config = create_test_config(TEST_ID_1);
-        let state = BoxState::new();
+        let mut state = BoxState::new();
 
-        manager.add_box(&config, &state).unwrap();
+        manager.add_box(&config, &mut state).unwrap();
 
         // Save new state
         let mut new_state = BoxState::new();
-        new_state.set_status(BoxStatus::Running);
+        new_state.transition_to(BoxStatus::Running).unwrap();
         new_state.set_pid(Some(12345));
         manager.save_box(&config.id, &new_state).unwrap();
 
         // Update (load from DB)
         let loaded_state = manager.update_box(&config.id).unwrap();
-        assert_eq!(loaded_state.status, BoxStatus::Running);
+        assert_eq!(loaded_state.status(), BoxStatus::Running);
         assert_eq!(loaded_state.pid, Some(12345));
     }
+
+    #[test]
+    fn test_add_box_gives_networked_boxes_free_peer_ips() {
+        let store = create_test_store();
+        let manager = BoxManager::new(store);
+        let on_network = |id| {
+            let mut config = create_test_config(id);
+            config.options.networks = vec!["backend".to_string()];
+            config
+        };
+
+        let (older, newer) = (on_network(TEST_ID_1), on_network(TEST_ID_2));
+        manager.add_box(&older, &mut BoxState::new()).unwrap();
+        manager.add_box(&newer, &mut BoxState::new()).unwrap();
+        let peer_ip = |id: &BoxID| manager.box_by_id(id).unwrap().unwrap().1.peer_ip;
+        assert_eq!(peer_ip(&older.id), Some("192.168.127.128".parse().unwrap()));
+        assert_eq!(peer_ip(&newer.id), Some("192.168.127.129".parse().unwrap()));
+
+        // Removing a box frees its address; the others keep theirs
+        manager.remove_box(&older.id).unwrap();
+        let mut state = BoxState::new();
+        manager.add_box(&on_network(TEST_ID_3), &mut state).unwrap();
+        assert_eq!(state.peer_ip, Some("192.168.127.128".parse().unwrap()));
+        assert_eq!(peer_ip(&newer.id), Some("192.168.127.129".parse().unwrap()));
+
+        // Boxes on no network get none
+        let mut state = BoxState::new();
+        manager
+            .add_box(
+                &create_test_config("01HJK4TNRPQSXYZ8WM6NCVT9R4"),
+                &mut state,
+            )
+            .unwrap();
+        assert_eq!(state.peer_ip, None);
+    }
 }
@@ -6,20 +6,38 @@ pub(crate) mod box_impl;
 pub(crate) mod config;
 pub mod copy;
 mod exec;
+mod exec_slots;
 mod init;
 mod manager;
+mod provision;
 mod state;
+mod sync;
+mod trace_context;
 
 pub use copy::CopyOptions;
-pub use exec::{BoxCommand, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, ExecutionId};
+pub(crate) use exec::HostFd;
+pub use exec::{
+    BoxCommand, CollectedOutput, ExecLimit, ExecLimits, ExecLines, ExecLogOptions,
+    ExecOutputStream, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, ExecutionId,
+    ExecutionInfo, ExecutionLogs, OutputChunk,
+};
 pub(crate) use manager::BoxManager;
-pub use state::{BoxState, BoxStatus};
+pub use provision::{PackageManager, ProvisionSpec};
+pub use state::{
+    BoxState, BoxStatus, BoxTimings, GuestAgentInfo, RootfsBuildTimings, StatusTransition,
+};
+pub use sync::{ConflictPolicy, SyncOptions, SyncStatus, WorkspaceSync};
 
 pub(crate) use box_impl::SharedBoxImpl;
-pub(crate) use init::BoxBuilder;
+pub(crate) use init::{BoxBuilder, InitHooks, prewarm_image, resolve_user_volumes};
+pub use init::{InitHook, InitHookContext, InitStage};
 
+use crate::disk::{DiskUsage, RootfsSize};
+use crate::images::ImageDiff;
 use crate::metrics::BoxMetrics;
-use crate::{BoxID, BoxInfo};
+use crate::net::NetConnection;
+use crate::runtime::exec_cache::{self, CachedExecResult};
+use crate::{BoxID, BoxInfo, BoxOptions};
 use boxlite_shared::errors::BoxliteResult;
 pub use config::BoxConfig;
 use std::path::Path;
@@ -63,6 +81,23 @@ impl LiteBox {
         self.inner.info()
     }
 
+    /// Last status transitions of the box (up to 64), oldest first.
+    ///
+    /// Persisted with its state, so they survive restarts of the runtime.
+    pub fn history(&self) -> Vec<StatusTransition> {
+        self.inner.history()
+    }
+
+    /// Options the box was created with.
+    pub fn options(&self) -> &BoxOptions {
+        &self.inner.config.options
+    }
+
+    /// Configuration the box was created with.
+    pub(crate) fn config(&self) -> &BoxConfig {
+        &self.inner.config
+    }
+
     /// Start the box (initialize VM).
     ///
     /// For Configured boxes: initializes VM for the first time.
@@ -78,14 +113,148 @@ impl LiteBox {
         self.inner.exec(command).await
     }
 
+    /// Start a command in the background and return its execution ID.
+    ///
+    /// The command keeps running after this returns. Use
+    /// [`attach_execution`](Self::attach_execution) with the ID, from this or
+    /// another process, to stream its output, wait for it, or kill it.
+    pub async fn exec_detached(&self, command: BoxCommand) -> BoxliteResult<ExecutionId> {
+        self.inner.exec_detached(command).await
+    }
+
+    /// Attach to an execution started earlier in this box.
+    ///
+    /// Output is delivered to one attached handle at a time. While nobody is
+    /// attached it stays buffered in the guest pipe, so a command that writes
+    /// a lot blocks until someone attaches. The returned handle has no stdin;
+    /// dropping it detaches without stopping the process.
+    pub async fn attach_execution(&self, execution_id: &str) -> BoxliteResult<Execution> {
+        self.inner.attach_execution(execution_id).await
+    }
+
+    /// List the executions the guest agent knows about, oldest first.
+    ///
+    /// Covers every execution of the running box, whichever handle or
+    /// process started it: running ones, and exited ones until
+    /// `BoxOptions::exec_retention_secs` after they exit.
+    pub async fn executions(&self) -> BoxliteResult<Vec<ExecutionInfo>> {
+        self.inner.executions().await
+    }
+
+    /// Wait for an execution of this box to exit, by ID.
+    ///
+    /// Returns immediately for an execution that already exited and is still
+    /// retained. Fails with `NotFound` for an unknown ID.
+    pub async fn wait_execution(&self, execution_id: &str) -> BoxliteResult<ExecResult> {
+        self.inner.wait_execution(execution_id).await
+    }
+
+    /// Send a signal to an execution of this box, by ID.
+    pub async fn kill_execution(&self, execution_id: &str, signal: i32) -> BoxliteResult<()> {
+        self.inner.kill_execution(execution_id, signal).await
+    }
+
+    /// Get the recorded output files of an execution.
+    ///
+    /// Only executions started with [`BoxCommand::record_output`] have logs.
+    /// Logs are kept until the box is removed and can be read while the box
+    /// is stopped.
+    pub fn execution_logs(&self, execution_id: &str) -> BoxliteResult<ExecutionLogs> {
+        self.inner.execution_logs(execution_id)
+    }
+
+    /// Host disk space used by the box's disks.
+    ///
+    /// Reports the blocks allocated on the host next to the virtual size the
+    /// guest sees, for the container rootfs and guest rootfs overlays. Works
+    /// while the box is stopped; empty if it never started.
+    pub fn disk_usage(&self) -> BoxliteResult<DiskUsage> {
+        self.inner.disk_usage()
+    }
+
+    /// Size of the container rootfs, like `docker inspect --size`: bytes the
+    /// box wrote on top of its image and bytes in use in the filesystem.
+    ///
+    /// Scans the allocation tables of the container disk, so it costs more
+    /// than [`disk_usage`](Self::disk_usage). Works while the box is stopped;
+    /// `None` if it never started, or uses the reflink storage driver, whose
+    /// raw copy doesn't record what the box wrote.
+    pub fn rootfs_size(&self) -> BoxliteResult<Option<RootfsSize>> {
+        self.inner.rootfs_size()
+    }
+
+    /// Files the box added, modified and removed in its rootfs since an
+    /// image, by default the one it was created from.
+    ///
+    /// Compares the files and symlinks of the running container by size,
+    /// modification time, link target, mode and owner, leaving out mounts.
+    /// The box must be running, and the image in the local cache.
+    pub async fn rootfs_diff(&self, image: Option<&str>) -> BoxliteResult<ImageDiff> {
+        self.inner.rootfs_diff(image).await
+    }
+
+    /// Fetch the guest agent's own recent log lines, oldest first.
+    ///
+    /// The agent keeps its last 2000 log lines in memory; `tail` limits the
+    /// result to the most recent `n`. The box must be running. Raise the
+    /// detail with `BoxOptions::guest_log_level`.
+    pub async fn guest_logs(&self, tail: Option<usize>) -> BoxliteResult<Vec<String>> {
+        self.inner.guest_logs(tail).await
+    }
+
+    /// List the box's active TCP/UDP connections.
+    ///
+    /// A snapshot of the sockets in the guest network namespace (shared with
+    /// the container), with TCP byte counters. Listening sockets are left
+    /// out. The box must be running.
+    pub async fn net_connections(&self) -> BoxliteResult<Vec<NetConnection>> {
+        self.inner.net_connections().await
+    }
+
     pub async fn metrics(&self) -> BoxliteResult<BoxMetrics> {
         self.inner.metrics().await
     }
 
+    /// Zero the box's exec and I/O counters, including labeled ones.
+    ///
+    /// Lets a long-lived box report each task separately. Runtime-wide
+    /// totals and startup timings are left untouched.
+    pub fn reset_metrics(&self) -> BoxliteResult<()> {
+        self.inner.reset_metrics()
+    }
+
     pub async fn stop(&self) -> BoxliteResult<()> {
         self.inner.stop().await
     }
 
+    /// Run a command, or return its output from an earlier run on the same
+    /// rootfs.
+    ///
+    /// Results are keyed by the command (program, arguments, env, working
+    /// directory), the box's env, user and working directory, and a digest
+    /// of the container disk's contents, so any change to the rootfs runs
+    /// the command again. Meant for expensive idempotent commands such as
+    /// dependency resolution; volumes and the network are not part of the
+    /// key. The command gets no stdin and can't use a TTY.
+    ///
+    /// Each call flushes the guest's writes and, if the disk changed since
+    /// it was last hashed, hashes the blocks the box wrote on top of its
+    /// image with the VM paused. Only results of commands that exit 0 are
+    /// cached by default, and output is kept up to the cache's `max_bytes`.
+    /// See `BoxliteOptions::exec_cache` for eviction.
+    pub async fn exec_cached(&self, command: BoxCommand) -> BoxliteResult<CachedExecResult> {
+        exec_cache::exec_cached(self, &self.inner.runtime, command).await
+    }
+
+    /// Install packages with the box's package manager (apk, apt, dnf or yum).
+    ///
+    /// Returns the package manager used. Starts the box if needed, like
+    /// `exec()`. Provisioning of the same box from several callers is
+    /// serialized by a lock in the box; the box must run as root.
+    pub async fn provision(&self, spec: ProvisionSpec) -> BoxliteResult<PackageManager> {
+        provision::provision(self, spec).await
+    }
+
     /// Copy files/directories from host into the container rootfs.
     pub async fn copy_into(
         &self,
@@ -109,6 +278,47 @@ impl LiteBox {
             .copy_out(container_src.as_ref(), host_dst.as_ref(), opts)
             .await
     }
+
+    /// Keep a host directory and a container directory in sync, both ways.
+    ///
+    /// For hosts without virtio-fs volumes. Both sides are polled every
+    /// `SyncOptions::interval` and only changed files are transferred;
+    /// files changed on both sides are settled by `SyncOptions::conflict`.
+    /// Starts the box if needed. Syncing runs in the background until the
+    /// returned handle is stopped or dropped, or the box stops.
+    pub async fn sync(
+        &self,
+        host_dir: impl AsRef<Path>,
+        container_dir: impl AsRef<str>,
+        opts: SyncOptions,
+    ) -> BoxliteResult<WorkspaceSync> {
+        sync::start(
+            self.inner.clone(),
+            host_dir.as_ref(),
+            container_dir.as_ref(),
+            opts,
+        )
+        .await
+    }
+
+    /// Run a single round of [`sync`](Self::sync) and return what it did.
+    ///
+    /// Without the state of earlier rounds, a file that differs between the
+    /// two sides counts as a conflict, and nothing is deleted.
+    pub async fn sync_once(
+        &self,
+        host_dir: impl AsRef<Path>,
+        container_dir: impl AsRef<str>,
+        opts: SyncOptions,
+    ) -> BoxliteResult<SyncStatus> {
+        sync::sync_once(
+            self.inner.clone(),
+            host_dir.as_ref(),
+            container_dir.as_ref(),
+            opts,
+        )
+        .await
+    }
 }
 
 // ============================================================================
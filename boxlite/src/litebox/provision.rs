@@ -0,0 +1,256 @@
+//! Package installation inside a box ([`LiteBox::provision`]).
+//!
+//! The install runs as one shell script in the box: it takes a lock (so
+//! concurrent provisioning, from this or another process, waits its turn),
+//! picks the first package manager it finds and installs the packages, which
+//! are passed as arguments rather than spliced into the script.
+//!
+//! The lock is an `flock` on a file, which the kernel drops when the script
+//! dies. Without the `flock` tool it is a directory holding the owner's PID,
+//! taken over once that process is gone.
+//!
+//! [`LiteBox::provision`]: crate::LiteBox::provision
+
+use std::time::Duration;
+
+use super::LiteBox;
+use super::exec::BoxCommand;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Lock file in the box held while provisioning.
+const LOCK_PATH: &str = "/tmp/.boxlite-provision.lock";
+
+/// How long to wait for another provisioning to finish, by default.
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Lines of package manager output kept in error messages.
+const ERROR_TAIL_LINES: usize = 20;
+
+// Script exit codes, from sysexits.h
+const EXIT_UNSUPPORTED: i32 = 69;
+const EXIT_LOCKED: i32 = 75;
+const EXIT_NOT_ROOT: i32 = 77;
+
+/// Arguments: lock file, lock timeout in seconds, packages.
+const SCRIPT: &str = r#"
+lock="$1"; timeout="$2"; shift 2
+if command -v flock >/dev/null 2>&1; then
+    exec 9>"$lock" && flock -w "$timeout" 9 || exit 75
+else
+    waited=0
+    until mkdir "$lock.d" 2>/dev/null; do
+        owner=$(cat "$lock.d/pid" 2>/dev/null)
+        if [ -n "$owner" ] && ! kill -0 "$owner" 2>/dev/null; then
+            rm -rf "$lock.d"
+            continue
+        fi
+        [ "$waited" -ge "$timeout" ] && exit 75
+        sleep 1; waited=$((waited + 1))
+    done
+    echo $$ >"$lock.d/pid"
+    trap 'rm -rf "$lock.d"' EXIT
+fi
+[ "$(id -u)" = 0 ] || exit 77
+if command -v apk >/dev/null 2>&1; then
+    echo apk
+    apk add --no-cache "$@" >&2
+elif command -v apt-get >/dev/null 2>&1; then
+    echo apt
+    export DEBIAN_FRONTEND=noninteractive
+    { apt-get update -q && apt-get install -y -q --no-install-recommends "$@"; } >&2
+elif command -v dnf >/dev/null 2>&1; then
+    echo dnf
+    dnf install -y "$@" >&2
+elif command -v microdnf >/dev/null 2>&1; then
+    echo dnf
+    microdnf install -y "$@" >&2
+elif command -v yum >/dev/null 2>&1; then
+    echo yum
+    yum install -y "$@" >&2
+else
+    exit 69
+fi
+"#;
+
+/// Packages to install with [`LiteBox::provision`](crate::LiteBox::provision).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # async fn example(litebox: &boxlite::LiteBox) -> boxlite::BoxliteResult<()> {
+/// use boxlite::ProvisionSpec;
+///
+/// let manager = litebox
+///     .provision(ProvisionSpec::new().package("git").package("python3"))
+///     .await?;
+/// println!("installed with {}", manager);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProvisionSpec {
+    /// Package names, as the box's package manager knows them.
+    pub packages: Vec<String>,
+    /// How long to wait for another provisioning of the box to finish
+    /// (default: 5 minutes).
+    pub lock_timeout: Option<Duration>,
+}
+
+impl ProvisionSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a package.
+    pub fn package(mut self, name: impl Into<String>) -> Self {
+        self.packages.push(name.into());
+        self
+    }
+
+    /// Add packages.
+    pub fn packages<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.packages.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set how long to wait for another provisioning to finish.
+    pub fn lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
+    fn validate(&self) -> BoxliteResult<()> {
+        if self.packages.is_empty() {
+            return Err(BoxliteError::InvalidArgument(
+                "no packages to install".to_string(),
+            ));
+        }
+        // A leading '-' would be taken as an option by the package manager
+        if let Some(name) = self.packages.iter().find(|name| {
+            name.is_empty() || name.starts_with('-') || name.chars().any(char::is_whitespace)
+        }) {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "invalid package name {:?}",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Package manager used to provision a box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    /// Alpine
+    Apk,
+    /// Debian, Ubuntu
+    Apt,
+    /// Fedora, RHEL 8+ (also microdnf)
+    Dnf,
+    /// CentOS 7, older RHEL
+    Yum,
+}
+
+impl PackageManager {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "apk" => Some(Self::Apk),
+            "apt" => Some(Self::Apt),
+            "dnf" => Some(Self::Dnf),
+            "yum" => Some(Self::Yum),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Apk => "apk",
+            Self::Apt => "apt",
+            Self::Dnf => "dnf",
+            Self::Yum => "yum",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Install `spec.packages` in `litebox`, returning the package manager used.
+pub(super) async fn provision(
+    litebox: &LiteBox,
+    spec: ProvisionSpec,
+) -> BoxliteResult<PackageManager> {
+    spec.validate()?;
+    let lock_timeout = spec.lock_timeout.unwrap_or(DEFAULT_LOCK_TIMEOUT);
+
+    let command = BoxCommand::new("sh")
+        .args(["-c", SCRIPT, "boxlite-provision", LOCK_PATH])
+        .arg(lock_timeout.as_secs().to_string())
+        .args(&spec.packages);
+    let output = litebox.exec(command).await?.collect_output().await?;
+    let (stdout, stderr) = (output.stdout, output.stderr);
+
+    match output.result.exit_code {
+        0 => stdout
+            .lines()
+            .find_map(|line| PackageManager::from_name(line.trim()))
+            .ok_or_else(|| {
+                BoxliteError::Internal(format!(
+                    "provisioning did not report a package manager: {:?}",
+                    stdout
+                ))
+            }),
+        EXIT_UNSUPPORTED => Err(BoxliteError::Unsupported(
+            "no supported package manager (apk, apt-get, dnf, yum) in the box".to_string(),
+        )),
+        EXIT_LOCKED => Err(BoxliteError::InvalidState(format!(
+            "box is still being provisioned after {}s",
+            lock_timeout.as_secs()
+        ))),
+        EXIT_NOT_ROOT => Err(BoxliteError::InvalidState(
+            "packages can only be installed when the box runs as root".to_string(),
+        )),
+        code => Err(BoxliteError::Execution(format!(
+            "installing {} failed with exit code {}:\n{}",
+            spec.packages.join(" "),
+            code,
+            tail(&stderr, ERROR_TAIL_LINES)
+        ))),
+    }
+}
+
+/// The last `n` lines of `text`.
+fn tail(text: &str, n: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(n)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provision_spec_validate() {
+        assert!(ProvisionSpec::new().package("git").validate().is_ok());
+        assert!(ProvisionSpec::new().validate().is_err());
+        for name in ["", "--allow-untrusted", "git python3"] {
+            assert!(
+                ProvisionSpec::new().package(name).validate().is_err(),
+                "{:?} accepted",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("a\nb\nc\n", 2), "b\nc");
+        assert_eq!(tail("a", 5), "a");
+        assert_eq!(tail("", 5), "");
+    }
+}
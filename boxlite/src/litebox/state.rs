@@ -4,9 +4,15 @@
 
 use crate::ContainerID;
 use crate::lock::LockId;
+use boxlite_shared::constants;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+
+/// Transitions kept in a box's status history; older ones are dropped.
+const MAX_STATUS_HISTORY: usize = 64;
 
 /// Lifecycle status of a box.
 ///
@@ -19,7 +25,7 @@ use serde::{Deserialize, Serialize};
 /// start()  → Running (VM initialized)
 /// stop()   → Stopped (VM terminated, can restart)
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum BoxStatus {
     /// Cannot determine box state (error recovery).
@@ -32,6 +38,11 @@ pub enum BoxStatus {
     /// Box is running and guest server is accepting commands.
     Running,
 
+    /// The VM is running but its guest agent stopped answering heartbeats
+    /// (`BoxOptions::heartbeat_interval_secs`). Returns to Running when it
+    /// answers again.
+    Unresponsive,
+
     /// Box is shutting down gracefully (transient state).
     Stopping,
 
@@ -43,11 +54,12 @@ pub enum BoxStatus {
 impl BoxStatus {
     /// Check if this status represents an active VM (process is running).
     pub fn is_active(&self) -> bool {
-        matches!(self, BoxStatus::Running)
+        matches!(self, BoxStatus::Running | BoxStatus::Unresponsive)
     }
 
+    /// Whether the VM is running, answering or not.
     pub fn is_running(&self) -> bool {
-        matches!(self, BoxStatus::Running)
+        matches!(self, BoxStatus::Running | BoxStatus::Unresponsive)
     }
 
     pub fn is_configured(&self) -> bool {
@@ -71,9 +83,9 @@ impl BoxStatus {
     }
 
     /// Check if stop() can be called from this state.
-    /// Only running boxes can be stopped.
+    /// Only running boxes, answering or not, can be stopped.
     pub fn can_stop(&self) -> bool {
-        matches!(self, BoxStatus::Running)
+        matches!(self, BoxStatus::Running | BoxStatus::Unresponsive)
     }
 
     /// Check if remove() can be called from this state.
@@ -90,7 +102,10 @@ impl BoxStatus {
     pub fn can_exec(&self) -> bool {
         matches!(
             self,
-            BoxStatus::Configured | BoxStatus::Running | BoxStatus::Stopped
+            BoxStatus::Configured
+                | BoxStatus::Running
+                | BoxStatus::Unresponsive
+                | BoxStatus::Stopped
         )
     }
 
@@ -109,6 +124,12 @@ impl BoxStatus {
             (Running, Stopping) |
             (Running, Stopped) |
             (Running, Unknown) |
+            // Running ⇄ Unresponsive (heartbeats stop / resume)
+            (Running, Unresponsive) |
+            (Unresponsive, Running) |
+            (Unresponsive, Stopping) |
+            (Unresponsive, Stopped) |
+            (Unresponsive, Unknown) |
             // Stopping → Stopped (complete) or Unknown (error)
             (Stopping, Stopped) |
             (Stopping, Unknown) |
@@ -124,6 +145,7 @@ impl BoxStatus {
             BoxStatus::Unknown => "unknown",
             BoxStatus::Configured => "configured",
             BoxStatus::Running => "running",
+            BoxStatus::Unresponsive => "unresponsive",
             BoxStatus::Stopping => "stopping",
             BoxStatus::Stopped => "stopped",
         }
@@ -140,6 +162,7 @@ impl std::str::FromStr for BoxStatus {
             // Legacy: support "starting" for backward compatibility with existing databases
             "starting" => Ok(BoxStatus::Configured),
             "running" => Ok(BoxStatus::Running),
+            "unresponsive" => Ok(BoxStatus::Unresponsive),
             "stopping" => Ok(BoxStatus::Stopping),
             "stopped" => Ok(BoxStatus::Stopped),
             _ => Err(()),
@@ -159,8 +182,8 @@ impl std::fmt::Display for BoxStatus {
 /// State transitions are validated before applying.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoxState {
-    /// Current lifecycle status.
-    pub status: BoxStatus,
+    /// Current lifecycle status, changed only through validated transitions.
+    status: BoxStatus,
     pub pid: Option<u32>,
     pub container_id: Option<ContainerID>,
     /// Last state change timestamp (UTC).
@@ -170,6 +193,108 @@ pub struct BoxState {
     /// Allocated when the box is first initialized (not at creation time).
     /// Used to retrieve the lock across process restarts.
     pub lock_id: Option<LockId>,
+    /// Stage durations of the last start (None until the box has started).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<BoxTimings>,
+    /// Guest agent met at the last start or attach (None until then).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest_agent: Option<GuestAgentInfo>,
+    /// Last status transitions, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<StatusTransition>,
+    /// Address the box's network peers reach it at, given when the box is
+    /// added and kept until it is removed (None for boxes on no network).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_ip: Option<Ipv4Addr>,
+}
+
+/// A change of a box's status, kept for debugging its lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StatusTransition {
+    /// When the status changed (UTC)
+    pub at: DateTime<Utc>,
+    pub from: BoxStatus,
+    pub to: BoxStatus,
+    /// What caused it, e.g. "started" or "heartbeat missed"
+    pub reason: String,
+}
+
+/// Duration of each initialization stage of a box start, in milliseconds.
+///
+/// Container and guest rootfs preparation run in parallel, so the stages
+/// can add up to more than `total_ms`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BoxTimings {
+    /// Box directory setup
+    pub filesystem_ms: Option<u64>,
+    /// Container image pull and rootfs disk preparation
+    pub rootfs_ms: Option<u64>,
+    /// Guest rootfs preparation
+    pub guest_rootfs_ms: Option<u64>,
+    /// VM config build
+    pub config_ms: Option<u64>,
+    /// VM subprocess spawn
+    pub spawn_ms: Option<u64>,
+    /// Guest agent boot and container initialization
+    pub guest_ms: Option<u64>,
+    /// Whole start, from the first stage to the box being ready
+    pub total_ms: Option<u64>,
+    /// Rootfs built from the image layers during `rootfs_ms` (None when a
+    /// cached rootfs disk was reused)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rootfs_build: Option<RootfsBuildTimings>,
+}
+
+/// How a container rootfs was materialized from the extracted image layers.
+///
+/// On filesystems with reflinks (APFS, btrfs, XFS) the files share blocks
+/// with the layer cache instead of being copied, which is what makes
+/// `materialize_ms` small; compare it across hosts to see the difference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RootfsBuildTimings {
+    /// Stacking the layers into the rootfs directory
+    pub materialize_ms: u64,
+    /// Bytes reflinked from the layer cache
+    pub reflinked_bytes: u64,
+    /// Bytes copied because the filesystem could not reflink them
+    pub copied_bytes: u64,
+}
+
+/// Version and capabilities of a box's guest agent, as reported when the
+/// host connects.
+///
+/// Boxes keep the guest rootfs they were created with, so a box may run an
+/// older agent than the host. Operations its agent lacks fail with
+/// `Unsupported` instead of an RPC error.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GuestAgentInfo {
+    /// Agent version
+    pub version: String,
+    /// Features the agent serves (`boxlite_shared::constants::capabilities`)
+    pub capabilities: Vec<String>,
+}
+
+impl GuestAgentInfo {
+    /// From a ping answer; agents that predate capability reporting send
+    /// none and get the legacy set.
+    pub(crate) fn from_ping(version: String, capabilities: Vec<String>) -> Self {
+        let capabilities = if capabilities.is_empty() {
+            constants::capabilities::LEGACY
+                .iter()
+                .map(|c| c.to_string())
+                .collect()
+        } else {
+            capabilities
+        };
+        Self {
+            version,
+            capabilities,
+        }
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
 }
 
 impl BoxState {
@@ -182,9 +307,18 @@ impl BoxState {
             container_id: None,
             last_updated: Utc::now(),
             lock_id: None,
+            timings: None,
+            guest_agent: None,
+            history: Vec::new(),
+            peer_ip: None,
         }
     }
 
+    /// Current lifecycle status.
+    pub fn status(&self) -> BoxStatus {
+        self.status
+    }
+
     /// Set lock ID and update timestamp.
     pub fn set_lock_id(&mut self, lock_id: LockId) {
         self.lock_id = Some(lock_id);
@@ -195,27 +329,72 @@ impl BoxState {
     ///
     /// Returns error if the transition is not valid.
     pub fn transition_to(&mut self, new_status: BoxStatus) -> BoxliteResult<()> {
-        if !self.status.can_transition_to(new_status) {
+        self.transition_with_reason(new_status, "")
+    }
+
+    /// Attempt state transition with validation, recording it in the
+    /// history with `reason`.
+    ///
+    /// Returns error if the transition is not valid. Moving to the current
+    /// status is a no-op.
+    pub fn transition_with_reason(
+        &mut self,
+        new_status: BoxStatus,
+        reason: impl Into<String>,
+    ) -> BoxliteResult<()> {
+        self.check_transition(new_status)?;
+        if new_status != self.status {
+            self.record(new_status, reason.into());
+        }
+        Ok(())
+    }
+
+    /// Check that the box may move to `new_status`, without moving it.
+    pub fn check_transition(&self, new_status: BoxStatus) -> BoxliteResult<()> {
+        if new_status != self.status && !self.status.can_transition_to(new_status) {
             return Err(BoxliteError::InvalidState(format!(
                 "Cannot transition from {} to {}",
                 self.status, new_status
             )));
         }
-
-        self.status = new_status;
-        self.last_updated = Utc::now();
         Ok(())
     }
 
-    /// Force set status without validation (for recovery/internal use).
+    /// Move to `status` found during recovery, passing through Unknown when
+    /// the current status can't move there directly.
+    pub(crate) fn recover_to(&mut self, status: BoxStatus, reason: &str) {
+        if self.transition_with_reason(status, reason).is_err() {
+            // Every status may move to Unknown, and Unknown to any status
+            let _ = self.transition_with_reason(BoxStatus::Unknown, reason);
+            let _ = self.transition_with_reason(status, reason);
+        }
+    }
+
+    /// Force set status (for recovery/internal use).
+    #[deprecated(note = "use `transition_to`, which validates the transition")]
     pub fn force_status(&mut self, status: BoxStatus) {
-        self.status = status;
-        self.last_updated = Utc::now();
+        self.recover_to(status, "");
     }
 
-    /// Set status directly (alias for force_status, used by manager).
+    /// Set status directly (alias for force_status).
+    #[deprecated(note = "use `transition_to`, which validates the transition")]
     pub fn set_status(&mut self, status: BoxStatus) {
-        self.force_status(status);
+        self.recover_to(status, "");
+    }
+
+    fn record(&mut self, to: BoxStatus, reason: String) {
+        let now = Utc::now();
+        if self.history.len() >= MAX_STATUS_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(StatusTransition {
+            at: now,
+            from: self.status,
+            to,
+            reason,
+        });
+        self.status = to;
+        self.last_updated = now;
     }
 
     /// Set PID and update timestamp.
@@ -230,7 +409,15 @@ impl BoxState {
     /// since the rootfs is preserved and can be restarted.
     /// PID is cleared since the process is no longer alive.
     pub fn mark_stop(&mut self) {
-        self.status = BoxStatus::Stopped;
+        self.mark_stop_with_reason("");
+    }
+
+    /// Mark box as stopped, recording it in the history with `reason`.
+    ///
+    /// Every status may move to Stopped, so this can't fail.
+    pub fn mark_stop_with_reason(&mut self, reason: impl Into<String>) {
+        let stopped = self.transition_with_reason(BoxStatus::Stopped, reason);
+        debug_assert!(stopped.is_ok(), "{:?}", stopped);
         self.pid = None;
         self.last_updated = Utc::now();
     }
@@ -240,8 +427,8 @@ impl BoxState {
     /// Active boxes become Stopped since VM rootfs is preserved.
     /// PID is cleared since all processes are gone after reboot.
     pub fn reset_for_reboot(&mut self) {
-        if self.status.is_active() {
-            self.status = BoxStatus::Stopped;
+        if self.status.is_active() || self.status.is_transient() {
+            self.mark_stop_with_reason("host rebooted");
         }
         self.pid = None;
         self.last_updated = Utc::now();
@@ -260,9 +447,10 @@ mod tests {
 
     #[test]
     fn test_status_is_active() {
-        // Only Running is active (VM process running)
+        // Only Running and Unresponsive are active (VM process running)
         assert!(!BoxStatus::Configured.is_active());
         assert!(BoxStatus::Running.is_active());
+        assert!(BoxStatus::Unresponsive.is_active());
         assert!(!BoxStatus::Stopping.is_active());
         assert!(!BoxStatus::Stopped.is_active());
         assert!(!BoxStatus::Unknown.is_active());
@@ -289,9 +477,10 @@ mod tests {
 
     #[test]
     fn test_status_can_stop() {
-        // Only Running boxes can be stopped
+        // Only Running boxes, answering or not, can be stopped
         assert!(!BoxStatus::Configured.can_stop());
         assert!(BoxStatus::Running.can_stop());
+        assert!(BoxStatus::Unresponsive.can_stop());
         assert!(!BoxStatus::Stopping.can_stop());
         assert!(!BoxStatus::Stopped.can_stop());
         assert!(!BoxStatus::Unknown.can_stop());
@@ -302,6 +491,7 @@ mod tests {
         // Configured and Stopped trigger implicit start
         assert!(BoxStatus::Configured.can_exec());
         assert!(BoxStatus::Running.can_exec());
+        assert!(BoxStatus::Unresponsive.can_exec());
         assert!(!BoxStatus::Stopping.can_exec());
         assert!(BoxStatus::Stopped.can_exec());
         assert!(!BoxStatus::Unknown.can_exec());
@@ -319,6 +509,12 @@ mod tests {
         assert!(BoxStatus::Running.can_transition_to(BoxStatus::Stopped));
         assert!(!BoxStatus::Running.can_transition_to(BoxStatus::Configured));
 
+        // Unresponsive transitions
+        assert!(BoxStatus::Running.can_transition_to(BoxStatus::Unresponsive));
+        assert!(BoxStatus::Unresponsive.can_transition_to(BoxStatus::Running));
+        assert!(BoxStatus::Unresponsive.can_transition_to(BoxStatus::Stopped));
+        assert!(!BoxStatus::Stopped.can_transition_to(BoxStatus::Unresponsive));
+
         // Stopping transitions
         assert!(BoxStatus::Stopping.can_transition_to(BoxStatus::Stopped));
         assert!(!BoxStatus::Stopping.can_transition_to(BoxStatus::Running));
@@ -366,6 +562,82 @@ mod tests {
         let result = state.transition_to(BoxStatus::Stopping);
         assert!(result.is_err());
         assert_eq!(state.status, BoxStatus::Configured); // Unchanged
+        assert!(state.history.is_empty());
+    }
+
+    #[test]
+    fn test_status_history() {
+        let mut state = BoxState::new();
+        state
+            .transition_with_reason(BoxStatus::Running, "started")
+            .unwrap();
+        // Same status: not a transition
+        state
+            .transition_with_reason(BoxStatus::Running, "started")
+            .unwrap();
+        state.mark_stop_with_reason("stopped");
+
+        let steps: Vec<_> = state
+            .history
+            .iter()
+            .map(|t| (t.from, t.to, t.reason.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (BoxStatus::Configured, BoxStatus::Running, "started"),
+                (BoxStatus::Running, BoxStatus::Stopped, "stopped"),
+            ]
+        );
+
+        // Persisted with the state, bounded
+        for _ in 0..MAX_STATUS_HISTORY {
+            state
+                .transition_with_reason(BoxStatus::Running, "started")
+                .unwrap();
+            state.mark_stop_with_reason("stopped");
+        }
+        let loaded: BoxState =
+            serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(loaded.history.len(), MAX_STATUS_HISTORY);
+        assert_eq!(loaded.history, state.history);
+    }
+
+    #[test]
+    fn test_mark_stop_from_every_status() {
+        use BoxStatus::*;
+        for status in [
+            Unknown,
+            Configured,
+            Running,
+            Unresponsive,
+            Stopping,
+            Stopped,
+        ] {
+            let mut state = BoxState::new();
+            state.status = status;
+            state.pid = Some(12345);
+            state.mark_stop_with_reason("test");
+            assert_eq!(state.status, Stopped, "from {}", status);
+            assert_eq!(state.pid, None);
+        }
+    }
+
+    #[test]
+    fn test_recover_to_goes_through_unknown() {
+        let mut state = BoxState::new();
+        state.status = BoxStatus::Stopping;
+        state.recover_to(BoxStatus::Running, "recovered");
+
+        assert_eq!(state.status, BoxStatus::Running);
+        let steps: Vec<_> = state.history.iter().map(|t| (t.from, t.to)).collect();
+        assert_eq!(
+            steps,
+            [
+                (BoxStatus::Stopping, BoxStatus::Unknown),
+                (BoxStatus::Unknown, BoxStatus::Running),
+            ]
+        );
     }
 
     #[test]
@@ -409,6 +681,7 @@ mod tests {
         assert_eq!(BoxStatus::Unknown.as_str(), "unknown");
         assert_eq!(BoxStatus::Configured.as_str(), "configured");
         assert_eq!(BoxStatus::Running.as_str(), "running");
+        assert_eq!(BoxStatus::Unresponsive.as_str(), "unresponsive");
         assert_eq!(BoxStatus::Stopping.as_str(), "stopping");
         assert_eq!(BoxStatus::Stopped.as_str(), "stopped");
     }
@@ -420,8 +693,41 @@ mod tests {
         // Legacy support: "starting" maps to Configured
         assert_eq!("starting".parse(), Ok(BoxStatus::Configured));
         assert_eq!("running".parse(), Ok(BoxStatus::Running));
+        assert_eq!("unresponsive".parse(), Ok(BoxStatus::Unresponsive));
         assert_eq!("stopping".parse(), Ok(BoxStatus::Stopping));
         assert_eq!("stopped".parse(), Ok(BoxStatus::Stopped));
         assert!("invalid".parse::<BoxStatus>().is_err());
     }
+
+    #[test]
+    fn test_state_timings_round_trip() {
+        // States persisted before timings existed still load
+        let mut state = BoxState::new();
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("timings"));
+        assert_eq!(
+            serde_json::from_str::<BoxState>(&json).unwrap().timings,
+            None
+        );
+
+        state.timings = Some(BoxTimings {
+            spawn_ms: Some(120),
+            total_ms: Some(900),
+            ..Default::default()
+        });
+        let json = serde_json::to_string(&state).unwrap();
+        let loaded: BoxState = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.timings, state.timings);
+    }
+
+    #[test]
+    fn test_guest_agent_legacy_capabilities() {
+        let agent = GuestAgentInfo::from_ping("0.4.0".into(), vec![]);
+        assert!(agent.supports(constants::capabilities::FILES));
+        assert!(!agent.supports(constants::capabilities::EXEC_LIST));
+
+        let agent = GuestAgentInfo::from_ping("0.6.0".into(), vec!["exec_list".into()]);
+        assert!(agent.supports(constants::capabilities::EXEC_LIST));
+        assert!(!agent.supports(constants::capabilities::FILES));
+    }
 }
@@ -0,0 +1,924 @@
+//! Two-way workspace sync between a host directory and a guest directory.
+//!
+//! For hosts where a virtio-fs volume isn't available. Both sides are
+//! polled: the host directory is walked, the guest one listed with
+//! `Files.ListTree`, and each side is compared with the state both agreed on
+//! after the last round. Only files that changed are transferred, as one tar
+//! per round into the box and one download per file out of it; deletions are
+//! replayed with `Files.Remove`.
+//!
+//! Files and symlinks are synced; directories follow the files in them, so
+//! empty directories are not. A file counts as changed when its size or
+//! modification time (in seconds) differs, a symlink when its target does.
+//!
+//! Guest paths aren't trusted: those that could leave the synced directory
+//! are ignored, and files pulled to the host are written entry by entry
+//! under parents checked not to be symlinks.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use boxlite_shared::{BoxliteError, BoxliteResult, TreeEntry};
+use chrono::{DateTime, Utc};
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use super::box_impl::SharedBoxImpl;
+use super::copy::{BOXLITE_IGNORE_FILE, walk_unignored};
+use crate::portal::interfaces::FilesInterface;
+
+/// Files service accepts at most 512 MiB per upload; stay well under it.
+const MAX_PUSH_BATCH_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Which side wins when a file changed on both since the last round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep the version with the later modification time; a change beats a
+    /// deletion.
+    #[default]
+    Newer,
+    /// Keep the host version.
+    Host,
+    /// Keep the guest version.
+    Guest,
+    /// Leave both sides alone and report the path in
+    /// [`SyncStatus::conflicts`] until one side is made to match the other.
+    Skip,
+}
+
+/// Options controlling workspace sync.
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Time between two rounds.
+    pub interval: Duration,
+    /// What to do with files changed on both sides.
+    pub conflict: ConflictPolicy,
+    /// Leave out paths matched by `.boxliteignore` and `.gitignore` files in
+    /// the host directory, on both sides.
+    pub respect_ignore_files: bool,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            conflict: ConflictPolicy::default(),
+            respect_ignore_files: false,
+        }
+    }
+}
+
+impl SyncOptions {
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn conflict(mut self, conflict: ConflictPolicy) -> Self {
+        self.conflict = conflict;
+        self
+    }
+
+    pub fn respect_ignore_files(mut self, respect: bool) -> Self {
+        self.respect_ignore_files = respect;
+        self
+    }
+}
+
+/// Progress of a workspace sync.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncStatus {
+    /// Whether rounds are still running. Sync ends when stopped or when the
+    /// box stops.
+    pub active: bool,
+    /// Rounds completed.
+    pub rounds: u64,
+    /// When the last round completed.
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Files and symlinks in sync after the last round.
+    pub files: usize,
+    /// Files copied from the host into the box.
+    pub pushed: u64,
+    /// Files copied from the box to the host.
+    pub pulled: u64,
+    /// Bytes copied from the host into the box.
+    pub bytes_pushed: u64,
+    /// Bytes copied from the box to the host.
+    pub bytes_pulled: u64,
+    /// Files deleted in the box because they were deleted on the host.
+    pub removed_in_guest: u64,
+    /// Files deleted on the host because they were deleted in the box.
+    pub removed_on_host: u64,
+    /// Conflicts settled by the conflict policy.
+    pub conflicts_resolved: u64,
+    /// Paths changed on both sides and left alone (`ConflictPolicy::Skip`).
+    pub conflicts: Vec<String>,
+    /// Error of the last round, if it failed. Failed rounds are retried.
+    pub last_error: Option<String>,
+}
+
+/// Handle to a running workspace sync.
+///
+/// Dropping it stops the sync after the round in progress.
+pub struct WorkspaceSync {
+    status: Arc<Mutex<SyncStatus>>,
+    stop: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl WorkspaceSync {
+    /// Current progress.
+    pub fn status(&self) -> SyncStatus {
+        self.status.lock().clone()
+    }
+
+    /// Stop syncing, waiting for the round in progress to finish.
+    pub async fn stop(mut self) -> SyncStatus {
+        self.stop.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+        self.status()
+    }
+}
+
+impl Drop for WorkspaceSync {
+    fn drop(&mut self) {
+        self.stop.cancel();
+    }
+}
+
+/// Start syncing `host_dir` and `guest_dir` every `opts.interval`.
+pub(crate) async fn start(
+    inner: SharedBoxImpl,
+    host_dir: &Path,
+    guest_dir: &str,
+    opts: SyncOptions,
+) -> BoxliteResult<WorkspaceSync> {
+    let mut engine = SyncEngine::new(inner, host_dir, guest_dir, opts)?;
+    // Fail early on a box that can't sync rather than in the first round
+    engine.inner.sync_files().await?;
+
+    let status = engine.status.clone();
+    status.lock().active = true;
+    let stop = CancellationToken::new();
+    let stopped = stop.clone();
+    let task = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(engine.opts.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = stopped.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            match engine.round().await {
+                Ok(()) => {}
+                Err(BoxliteError::Stopped(e) | BoxliteError::Cancelled(e)) => {
+                    engine.status.lock().last_error = Some(e);
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(box_id = %engine.inner.config.id, "Workspace sync round failed: {}", e);
+                    engine.status.lock().last_error = Some(e.to_string());
+                }
+            }
+        }
+        engine.status.lock().active = false;
+    });
+
+    Ok(WorkspaceSync {
+        status,
+        stop,
+        task: Some(task),
+    })
+}
+
+/// Sync `host_dir` and `guest_dir` once.
+pub(crate) async fn sync_once(
+    inner: SharedBoxImpl,
+    host_dir: &Path,
+    guest_dir: &str,
+    opts: SyncOptions,
+) -> BoxliteResult<SyncStatus> {
+    let mut engine = SyncEngine::new(inner, host_dir, guest_dir, opts)?;
+    engine.round().await?;
+    Ok(engine.status.lock().clone())
+}
+
+/// What identifies a version of a file or symlink on either side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileEntry {
+    size: u64,
+    mtime: i64,
+    link_target: Option<String>,
+}
+
+impl FileEntry {
+    /// Whether both are the same version. Symlink times aren't kept by
+    /// transfers, so symlinks compare by target only.
+    fn same_as(&self, other: &FileEntry) -> bool {
+        match (&self.link_target, &other.link_target) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self.size == other.size && self.mtime == other.mtime,
+            _ => false,
+        }
+    }
+}
+
+impl From<TreeEntry> for FileEntry {
+    fn from(entry: TreeEntry) -> Self {
+        Self {
+            size: entry.size,
+            mtime: entry.mtime,
+            link_target: entry.link_target,
+        }
+    }
+}
+
+/// Files and symlinks by `/`-separated path relative to the synced directory.
+type Tree = BTreeMap<String, FileEntry>;
+
+fn same(a: Option<&FileEntry>, b: Option<&FileEntry>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.same_as(b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Transfers needed to bring both sides in line with each other.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Plan {
+    push: Vec<String>,
+    pull: Vec<String>,
+    remove_in_guest: Vec<String>,
+    remove_on_host: Vec<String>,
+    conflicts: Vec<String>,
+    resolved: u64,
+}
+
+/// Compare both sides with `base`, the state they agreed on after the last
+/// round. Paths already in sync are recorded in `base` right away.
+fn plan(host: &Tree, guest: &Tree, base: &mut Tree, policy: ConflictPolicy) -> Plan {
+    let paths: BTreeSet<String> = host
+        .keys()
+        .chain(guest.keys())
+        .chain(base.keys())
+        .cloned()
+        .collect();
+
+    let mut plan = Plan::default();
+    for path in paths {
+        let h = host.get(&path);
+        let g = guest.get(&path);
+        if same(h, g) {
+            match h {
+                Some(entry) => base.insert(path, entry.clone()),
+                None => base.remove(&path),
+            };
+            continue;
+        }
+
+        let b = base.get(&path);
+        let host_changed = !same(h, b);
+        let guest_changed = !same(g, b);
+        let host_wins = match (host_changed, guest_changed) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => {
+                let host_wins = match policy {
+                    ConflictPolicy::Host => true,
+                    ConflictPolicy::Guest => false,
+                    ConflictPolicy::Newer => match (h, g) {
+                        (Some(h), Some(g)) => h.mtime >= g.mtime,
+                        (h, _) => h.is_some(),
+                    },
+                    ConflictPolicy::Skip => {
+                        plan.conflicts.push(path);
+                        continue;
+                    }
+                };
+                plan.resolved += 1;
+                host_wins
+            }
+        };
+        match (host_wins, h.is_some(), g.is_some()) {
+            (true, true, _) => plan.push.push(path),
+            (true, false, _) => plan.remove_in_guest.push(path),
+            (false, _, true) => plan.pull.push(path),
+            (false, _, false) => plan.remove_on_host.push(path),
+        }
+    }
+    plan
+}
+
+struct SyncEngine {
+    inner: SharedBoxImpl,
+    host_dir: PathBuf,
+    guest_dir: String,
+    opts: SyncOptions,
+    base: Tree,
+    status: Arc<Mutex<SyncStatus>>,
+}
+
+impl SyncEngine {
+    fn new(
+        inner: SharedBoxImpl,
+        host_dir: &Path,
+        guest_dir: &str,
+        opts: SyncOptions,
+    ) -> BoxliteResult<Self> {
+        if !host_dir.is_dir() {
+            return Err(BoxliteError::Config(format!(
+                "host directory {} does not exist",
+                host_dir.display()
+            )));
+        }
+        if !guest_dir.starts_with('/') {
+            return Err(BoxliteError::Config(format!(
+                "guest directory must be absolute: {:?}",
+                guest_dir
+            )));
+        }
+        if opts.interval.is_zero() {
+            return Err(BoxliteError::Config(
+                "sync interval must be positive".into(),
+            ));
+        }
+        Ok(Self {
+            inner,
+            host_dir: host_dir.to_path_buf(),
+            guest_dir: match guest_dir.trim_end_matches('/') {
+                "" => "/".to_string(),
+                dir => dir.to_string(),
+            },
+            opts,
+            base: Tree::new(),
+            status: Arc::default(),
+        })
+    }
+
+    /// Compare both sides and transfer what changed.
+    async fn round(&mut self) -> BoxliteResult<()> {
+        let mut files = self.inner.sync_files().await?;
+        let container_id = self.inner.container_id().to_string();
+
+        let host_dir = self.host_dir.clone();
+        let respect_ignore_files = self.opts.respect_ignore_files;
+        let (host, ignored) =
+            tokio::task::spawn_blocking(move || scan_host(&host_dir, respect_ignore_files))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("task join error: {}", e)))??;
+        let guest: Tree = files
+            .list_tree(&self.guest_dir, Some(&container_id))
+            .await?
+            .into_iter()
+            .filter(|entry| {
+                let safe = is_safe_path(&entry.path);
+                if !safe {
+                    tracing::warn!(
+                        box_id = %self.inner.config.id,
+                        path = %entry.path,
+                        "Ignoring guest path outside the synced directory"
+                    );
+                }
+                safe && !ignored.is_ignored(&entry.path)
+            })
+            .map(|entry| (entry.path.clone(), entry.into()))
+            .collect();
+
+        let plan = plan(&host, &guest, &mut self.base, self.opts.conflict);
+
+        let mut bytes_pushed = 0;
+        for batch in push_batches(&plan.push, &host) {
+            bytes_pushed += self.push(&mut files, &container_id, batch).await?;
+            for path in batch {
+                self.base.insert(path.clone(), host[path].clone());
+            }
+        }
+
+        if !plan.remove_in_guest.is_empty() {
+            files
+                .remove(
+                    &self.guest_dir,
+                    Some(&container_id),
+                    plan.remove_in_guest.clone(),
+                )
+                .await?;
+            for path in &plan.remove_in_guest {
+                self.base.remove(path);
+            }
+        }
+
+        // Before pulling, so a pulled file never lands under a symlink the
+        // guest since replaced with a directory
+        if !plan.remove_on_host.is_empty() {
+            let host_dir = self.host_dir.clone();
+            let paths = plan.remove_on_host.clone();
+            tokio::task::spawn_blocking(move || remove_on_host(&host_dir, &paths))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("task join error: {}", e)))??;
+            for path in &plan.remove_on_host {
+                self.base.remove(path);
+            }
+        }
+
+        let mut bytes_pulled = 0;
+        for path in &plan.pull {
+            self.pull(&mut files, &container_id, path).await?;
+            bytes_pulled += guest[path].size;
+            self.base.insert(path.clone(), guest[path].clone());
+        }
+
+        let changed = plan.push.len()
+            + plan.pull.len()
+            + plan.remove_in_guest.len()
+            + plan.remove_on_host.len();
+        if changed > 0 {
+            tracing::debug!(
+                box_id = %self.inner.config.id,
+                pushed = plan.push.len(),
+                pulled = plan.pull.len(),
+                removed_in_guest = plan.remove_in_guest.len(),
+                removed_on_host = plan.remove_on_host.len(),
+                "Workspace sync round"
+            );
+        }
+
+        let mut status = self.status.lock();
+        status.rounds += 1;
+        status.last_sync = Some(Utc::now());
+        status.files = self.base.len();
+        status.pushed += plan.push.len() as u64;
+        status.pulled += plan.pull.len() as u64;
+        status.bytes_pushed += bytes_pushed;
+        status.bytes_pulled += bytes_pulled;
+        status.removed_in_guest += plan.remove_in_guest.len() as u64;
+        status.removed_on_host += plan.remove_on_host.len() as u64;
+        status.conflicts_resolved += plan.resolved;
+        status.conflicts = plan.conflicts;
+        status.last_error = None;
+        Ok(())
+    }
+
+    /// Upload host files as one tar. Returns the bytes archived.
+    async fn push(
+        &self,
+        files: &mut FilesInterface,
+        container_id: &str,
+        paths: &[String],
+    ) -> BoxliteResult<u64> {
+        let temp_tar = self.inner.runtime.layout.temp_dir().join(format!(
+            "sync-in-{}-{}.tar",
+            self.inner.config.id.as_str(),
+            uuid::Uuid::new_v4()
+        ));
+
+        let host_dir = self.host_dir.clone();
+        let tar_path = temp_tar.clone();
+        let paths = paths.to_vec();
+        let bytes = tokio::task::spawn_blocking(move || -> std::io::Result<u64> {
+            let mut builder = tar::Builder::new(std::fs::File::create(&tar_path)?);
+            builder.follow_symlinks(false);
+            for path in &paths {
+                builder.append_path_with_name(host_dir.join(path), path)?;
+            }
+            builder.into_inner()?;
+            Ok(std::fs::metadata(&tar_path)?.len())
+        })
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("task join error: {}", e)))?
+        .map_err(|e| BoxliteError::Storage(format!("failed to archive changed files: {}", e)))?;
+
+        let uploaded = files
+            .upload_tar(&temp_tar, &self.guest_dir, Some(container_id), true, true)
+            .await;
+        let _ = tokio::fs::remove_file(&temp_tar).await;
+        uploaded.map(|()| bytes)
+    }
+
+    /// Download one guest file next to where it belongs on the host.
+    async fn pull(
+        &self,
+        files: &mut FilesInterface,
+        container_id: &str,
+        path: &str,
+    ) -> BoxliteResult<()> {
+        let temp_tar = self.inner.runtime.layout.temp_dir().join(format!(
+            "sync-out-{}-{}.tar",
+            self.inner.config.id.as_str(),
+            uuid::Uuid::new_v4()
+        ));
+        let src = format!("{}/{}", self.guest_dir, path);
+        files
+            .download_tar(&src, Some(container_id), false, false, &temp_tar)
+            .await?;
+
+        let host_dir = self.host_dir.clone();
+        let rel = path.to_string();
+        let tar_path = temp_tar.clone();
+        let extracted =
+            tokio::task::spawn_blocking(move || extract_pulled(&host_dir, &rel, &tar_path))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("task join error: {}", e)))?;
+        let _ = tokio::fs::remove_file(&temp_tar).await;
+        extracted.map_err(|e| {
+            BoxliteError::Storage(format!(
+                "failed to extract {}: {}",
+                self.host_dir.join(path).display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Split the files to push into uploads of at most `MAX_PUSH_BATCH_BYTES`.
+fn push_batches<'a>(paths: &'a [String], host: &Tree) -> Vec<&'a [String]> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, path) in paths.iter().enumerate() {
+        let size = host[path].size;
+        if i > start && bytes + size > MAX_PUSH_BATCH_BYTES {
+            batches.push(&paths[start..i]);
+            start = i;
+            bytes = 0;
+        }
+        bytes += size;
+    }
+    if start < paths.len() {
+        batches.push(&paths[start..]);
+    }
+    batches
+}
+
+/// Ignore files found in the host directory, to filter the guest side with.
+#[derive(Default)]
+struct IgnoreRules {
+    /// Matchers by the directory (relative, `/`-separated) they apply to.
+    by_dir: BTreeMap<String, Gitignore>,
+}
+
+impl IgnoreRules {
+    /// Whether a path is ignored, the deepest ignore file deciding like git.
+    fn is_ignored(&self, path: &str) -> bool {
+        let mut dir = path;
+        loop {
+            dir = dir.rfind('/').map_or("", |idx| &dir[..idx]);
+            if let Some(matcher) = self.by_dir.get(dir) {
+                let rel = path
+                    .strip_prefix(dir)
+                    .unwrap_or(path)
+                    .trim_start_matches('/');
+                match matcher.matched_path_or_any_parents(rel, false) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if dir.is_empty() {
+                return false;
+            }
+        }
+    }
+}
+
+/// Walk the host directory. Returns its files and symlinks, and the ignore
+/// rules it contains when `respect_ignore_files` is set.
+fn scan_host(root: &Path, respect_ignore_files: bool) -> BoxliteResult<(Tree, IgnoreRules)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let scan_err = |e: &dyn std::fmt::Display| {
+        BoxliteError::Storage(format!("failed to scan {}: {}", root.display(), e))
+    };
+
+    let mut tree = Tree::new();
+    let mut ignore_dirs = BTreeMap::new();
+    let paths: Vec<PathBuf> = if respect_ignore_files {
+        walk_unignored(root, false)
+            .map(|entry| entry.map(|e| e.into_path()).map_err(|e| scan_err(&e)))
+            .collect::<BoxliteResult<_>>()?
+    } else {
+        walkdir::WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .map(|entry| entry.map(|e| e.into_path()).map_err(|e| scan_err(&e)))
+            .collect::<BoxliteResult<_>>()?
+    };
+
+    for path in paths {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let metadata = std::fs::symlink_metadata(&path).map_err(|e| scan_err(&e))?;
+        let file_type = metadata.file_type();
+        if !file_type.is_file() && !file_type.is_symlink() {
+            continue;
+        }
+        let rel = rel.to_string_lossy().into_owned();
+        let link_target = if file_type.is_symlink() {
+            let target = std::fs::read_link(&path).map_err(|e| scan_err(&e))?;
+            Some(target.to_string_lossy().into_owned())
+        } else {
+            None
+        };
+
+        let name = path.file_name().unwrap_or_default();
+        if respect_ignore_files && (name == BOXLITE_IGNORE_FILE || name == ".gitignore") {
+            let dir = rel.rfind('/').map_or("", |idx| &rel[..idx]);
+            ignore_dirs.insert(dir.to_string(), path.parent().unwrap_or(root).to_path_buf());
+        }
+
+        tree.insert(
+            rel,
+            FileEntry {
+                size: metadata.len(),
+                mtime: metadata.mtime(),
+                link_target,
+            },
+        );
+    }
+
+    let mut rules = IgnoreRules::default();
+    for (dir, abs_dir) in ignore_dirs {
+        // Added last, .boxliteignore takes precedence like it does in the walk
+        let mut builder = GitignoreBuilder::new(&abs_dir);
+        for name in [".gitignore", BOXLITE_IGNORE_FILE] {
+            let file = abs_dir.join(name);
+            if file.is_file()
+                && let Some(e) = builder.add(&file)
+            {
+                tracing::warn!("Ignoring unreadable rules in {}: {}", file.display(), e);
+            }
+        }
+        let matcher = builder.build().map_err(|e| scan_err(&e))?;
+        rules.by_dir.insert(dir, matcher);
+    }
+    Ok((tree, rules))
+}
+
+/// Whether a guest path is relative and made of plain names only, so that it
+/// can't leave the synced directory.
+fn is_safe_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Directory that holds `rel` under `root`, without going through symlinks.
+///
+/// Missing directories are created with `create`; otherwise `None` is
+/// returned for them. A parent that is a symlink or a file is an error.
+fn host_parent(root: &Path, rel: &str, create: bool) -> std::io::Result<Option<PathBuf>> {
+    let mut dir = root.to_path_buf();
+    let parent = Path::new(rel).parent().unwrap_or(Path::new(""));
+    for component in parent.components() {
+        dir.push(component);
+        match std::fs::symlink_metadata(&dir) {
+            Ok(metadata) if metadata.file_type().is_dir() => {}
+            Ok(_) => {
+                return Err(std::io::Error::other(format!(
+                    "{} is not a directory",
+                    dir.display()
+                )));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                match std::fs::create_dir(&dir) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Some(dir))
+}
+
+/// Write the single file or symlink of a downloaded tar at `rel` under
+/// `root`. It is unpacked next to its destination, then renamed over it, so
+/// an existing symlink there is replaced rather than followed.
+fn extract_pulled(root: &Path, rel: &str, tar_path: &Path) -> std::io::Result<()> {
+    let name = Path::new(rel)
+        .file_name()
+        .ok_or_else(|| std::io::Error::other(format!("invalid path {:?}", rel)))?;
+    let parent = host_parent(root, rel, true)?.unwrap_or_else(|| root.to_path_buf());
+
+    let mut archive = tar::Archive::new(std::fs::File::open(tar_path)?);
+    let mut entries = archive.entries()?;
+    let mut entry = entries
+        .next()
+        .ok_or_else(|| std::io::Error::other("download is empty"))??;
+    match entry.header().entry_type() {
+        tar::EntryType::Regular | tar::EntryType::Continuous | tar::EntryType::Symlink => {}
+        other => {
+            return Err(std::io::Error::other(format!(
+                "expected a file or symlink, got {:?}",
+                other
+            )));
+        }
+    }
+
+    let staged = parent.join(format!(
+        ".{}.boxlite-sync-{}",
+        name.to_string_lossy(),
+        uuid::Uuid::new_v4()
+    ));
+    let placed = entry
+        .unpack(&staged)
+        .and_then(|_| std::fs::rename(&staged, parent.join(name)));
+    if placed.is_err() {
+        let _ = std::fs::remove_file(&staged);
+    }
+    placed?;
+    if entries.next().is_some() {
+        return Err(std::io::Error::other("download has more than one entry"));
+    }
+    Ok(())
+}
+
+/// Remove the files deleted in the box, skipping those whose parent is
+/// gone or isn't a real directory.
+fn remove_on_host(root: &Path, paths: &[String]) -> BoxliteResult<()> {
+    for path in paths {
+        let Ok(Some(parent)) = host_parent(root, path, false) else {
+            continue;
+        };
+        let target = parent.join(Path::new(path).file_name().unwrap_or_default());
+        match std::fs::remove_file(&target) {
+            Ok(()) => remove_empty_parents(&target, root),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(BoxliteError::Storage(format!(
+                    "failed to remove {}: {}",
+                    target.display(),
+                    e
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove the directories above `path` that are left empty, up to `root`.
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(size: u64, mtime: i64) -> FileEntry {
+        FileEntry {
+            size,
+            mtime,
+            link_target: None,
+        }
+    }
+
+    fn tree(entries: &[(&str, FileEntry)]) -> Tree {
+        entries
+            .iter()
+            .map(|(path, entry)| (path.to_string(), entry.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unsafe_guest_paths() {
+        assert!(is_safe_path("src/main.rs"));
+        assert!(!is_safe_path(""));
+        assert!(!is_safe_path("/etc/passwd"));
+        assert!(!is_safe_path("../outside"));
+        assert!(!is_safe_path("src/../../outside"));
+    }
+
+    #[test]
+    fn test_pull_does_not_follow_host_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("workspace");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("target"), root.join("file")).unwrap();
+
+        let tar_path = dir.path().join("pull.tar");
+        let mut builder = tar::Builder::new(std::fs::File::create(&tar_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_mtime(1_700_000_000);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "file", &b"data"[..])
+            .unwrap();
+        builder.into_inner().unwrap();
+
+        // Through a symlinked parent
+        assert!(extract_pulled(&root, "link/file", &tar_path).is_err());
+        // Over a symlink: replaced, not followed
+        extract_pulled(&root, "file", &tar_path).unwrap();
+        assert!(!outside.join("target").exists());
+        assert_eq!(std::fs::read(root.join("file")).unwrap(), b"data");
+
+        std::fs::write(outside.join("kept"), "data").unwrap();
+        let paths = ["link/kept", "missing/file", "file"].map(String::from);
+        remove_on_host(&root, &paths).unwrap();
+        assert!(outside.join("kept").exists());
+        assert!(!root.join("file").exists());
+    }
+
+    #[test]
+    fn test_plan_follows_the_side_that_changed() {
+        let mut base = tree(&[
+            ("kept", file(1, 10)),
+            ("edited", file(1, 10)),
+            ("gone", file(1, 10)),
+        ]);
+        let host = tree(&[
+            ("kept", file(1, 10)),
+            ("edited", file(2, 20)),
+            ("new", file(3, 30)),
+        ]);
+        let guest = tree(&[
+            ("kept", file(1, 10)),
+            ("edited", file(1, 10)),
+            ("gone", file(1, 10)),
+            ("made", file(4, 40)),
+        ]);
+
+        let plan = plan(&host, &guest, &mut base, ConflictPolicy::Newer);
+        assert_eq!(plan.push, ["edited", "new"]);
+        assert_eq!(plan.pull, ["made"]);
+        assert_eq!(plan.remove_in_guest, ["gone"]);
+        assert!(plan.remove_on_host.is_empty());
+        assert_eq!(plan.resolved, 0);
+        assert!(base.contains_key("kept"));
+    }
+
+    #[test]
+    fn test_plan_conflict_policies() {
+        let host = tree(&[("both", file(1, 30)), ("deleted", file(1, 10))]);
+        let guest = tree(&[("both", file(2, 20))]);
+        let base = tree(&[("both", file(9, 1)), ("deleted", file(9, 1))]);
+
+        let newer = plan(&host, &guest, &mut base.clone(), ConflictPolicy::Newer);
+        assert_eq!(newer.push, ["both", "deleted"]);
+        assert_eq!(newer.resolved, 2);
+
+        let guest_wins = plan(&host, &guest, &mut base.clone(), ConflictPolicy::Guest);
+        assert_eq!(guest_wins.pull, ["both"]);
+        assert_eq!(guest_wins.remove_on_host, ["deleted"]);
+
+        let skip = plan(&host, &guest, &mut base.clone(), ConflictPolicy::Skip);
+        assert_eq!(skip.conflicts, ["both", "deleted"]);
+        assert!(skip.push.is_empty() && skip.pull.is_empty());
+    }
+
+    #[test]
+    fn test_symlinks_compare_by_target() {
+        let link = |target: &str, mtime| FileEntry {
+            size: 1,
+            mtime,
+            link_target: Some(target.to_string()),
+        };
+        assert!(link("a", 1).same_as(&link("a", 2)));
+        assert!(!link("a", 1).same_as(&link("b", 1)));
+        assert!(!link("a", 1).same_as(&file(1, 1)));
+    }
+
+    #[test]
+    fn test_ignore_rules_apply_to_guest_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n!keep.log\n").unwrap();
+        std::fs::write(root.join("src").join(BOXLITE_IGNORE_FILE), "target/\n").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+
+        let (host, rules) = scan_host(root, true).unwrap();
+        assert!(host.contains_key("src/main.rs"));
+        assert!(rules.is_ignored("debug.log"));
+        assert!(rules.is_ignored("src/debug.log"));
+        assert!(!rules.is_ignored("keep.log"));
+        assert!(rules.is_ignored("src/target/out"));
+        assert!(!rules.is_ignored("target/out"));
+        assert!(!rules.is_ignored("src/main.rs"));
+    }
+}
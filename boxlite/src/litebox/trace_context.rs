@@ -0,0 +1,117 @@
+//! IDs passed to commands for log correlation.
+//!
+//! With `BoxOptions.propagate_trace_context`, the runtime picks the
+//! execution ID itself instead of letting the guest do it, so that it can be
+//! handed to the command along with the box ID. The W3C `traceparent` uses
+//! the box's ULID, 128 bits like a trace ID, as the trace ID and the ID of
+//! the host's current `tracing` span as the parent span ID: every command of
+//! a box shares a trace, and hangs off the span that started it.
+
+use super::exec::BoxCommand;
+use crate::BoxID;
+use sha2::{Digest, Sha256};
+
+/// Box ID variable
+pub(crate) const BOX_ID_ENV: &str = "BOXLITE_BOX_ID";
+
+/// Execution ID variable
+pub(crate) const EXECUTION_ID_ENV: &str = "BOXLITE_EXECUTION_ID";
+
+/// W3C trace context variable, as read by OpenTelemetry SDKs
+pub(crate) const TRACEPARENT_ENV: &str = "traceparent";
+
+/// Choose the execution ID of `command` and pass it, the box ID and (inside
+/// an enabled `tracing` span) the trace context to the command.
+pub(crate) fn inject(mut command: BoxCommand, box_id: &BoxID) -> BoxCommand {
+    let execution_id = uuid::Uuid::new_v4();
+    let trace_id = trace_id(box_id);
+    let span_id = tracing::Span::current()
+        .id()
+        .map(|id| format!("{:016x}", id.into_u64()));
+
+    let mut env = vec![
+        (BOX_ID_ENV, box_id.as_str().to_string()),
+        (EXECUTION_ID_ENV, execution_id.to_string()),
+    ];
+    if let Some(span_id) = &span_id {
+        env.push((TRACEPARENT_ENV, format!("00-{}-{}-01", trace_id, span_id)));
+    }
+    for (key, value) in env {
+        let set = command
+            .env
+            .as_ref()
+            .is_some_and(|env| env.iter().any(|(k, _)| k == key));
+        if !set {
+            command = command.env(key, value);
+        }
+    }
+
+    tracing::debug!(
+        box_id = %box_id,
+        execution_id = %execution_id,
+        trace_id = %trace_id,
+        span_id = span_id.as_deref(),
+        program = %command.command,
+        "Starting execution"
+    );
+    command.execution_id = Some(execution_id.to_string());
+    command
+}
+
+/// Trace ID of a box: its ULID as 32 hex digits.
+fn trace_id(box_id: &BoxID) -> String {
+    match ulid::Ulid::from_string(box_id.as_str()) {
+        Ok(ulid) if ulid.0 != 0 => format!("{:032x}", ulid.0),
+        // IDs that aren't ULIDs are hashed to the same width
+        _ => hex::encode(&Sha256::digest(box_id.as_str().as_bytes())[..16]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(command: &'a BoxCommand, key: &str) -> Option<&'a str> {
+        command
+            .env
+            .as_ref()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_inject_passes_ids_and_traceparent() {
+        let box_id = BoxID::new();
+        let subscriber = tracing_subscriber::registry();
+        let (command, span_id) = tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("host");
+            let _entered = span.enter();
+            let command = inject(BoxCommand::new("true"), &box_id);
+            (command, span.id().unwrap().into_u64())
+        });
+        let execution_id = command.execution_id.clone().unwrap();
+        assert_eq!(env(&command, BOX_ID_ENV), Some(box_id.as_str()));
+        assert_eq!(env(&command, EXECUTION_ID_ENV), Some(execution_id.as_str()));
+
+        let traceparent = env(&command, TRACEPARENT_ENV).unwrap();
+        let parts: Vec<&str> = traceparent.split('-').collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!((parts[0], parts[3]), ("00", "01"));
+        assert_eq!(parts[1], trace_id(&box_id));
+        assert_eq!(parts[1].len(), 32);
+        assert_eq!(parts[2], format!("{:016x}", span_id));
+
+        // Outside of a span there is no parent to name
+        let without = inject(BoxCommand::new("true"), &box_id);
+        assert_eq!(env(&without, TRACEPARENT_ENV), None);
+        assert!(env(&without, EXECUTION_ID_ENV).is_some());
+    }
+
+    #[test]
+    fn test_inject_keeps_command_variables() {
+        let command = BoxCommand::new("true").env(TRACEPARENT_ENV, "00-caller");
+        let command = inject(command, &BoxID::new());
+        assert_eq!(env(&command, TRACEPARENT_ENV), Some("00-caller"));
+    }
+}
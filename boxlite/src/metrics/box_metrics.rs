@@ -1,11 +1,19 @@
 //! Per-box metrics (individual LiteBox statistics).
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::portal::interfaces::container::ContainerCgroupStats;
+use crate::vmm::controller::VmmMetrics;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// How often the VM process gauges (CPU, memory) are sampled.
+pub(crate) const GAUGE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Storage for per-box metrics.
 ///
 /// Stored in `BoxMetadata`, one instance per box.
-/// All counters are monotonic (never decrease).
+/// Counters only increase until [`reset`](Self::reset) zeroes them.
 #[derive(Default, Debug)]
 pub struct BoxMetricsStorage {
     /// Commands executed on this box
@@ -16,6 +24,9 @@ pub struct BoxMetricsStorage {
     pub(crate) bytes_sent: AtomicU64,
     /// Bytes received from this box (via stdout/stderr)
     pub(crate) bytes_received: AtomicU64,
+    /// Exec counters per command label (see `BoxCommand::label`); the map
+    /// is only locked for writing when a label is first seen
+    pub(crate) labeled: RwLock<BTreeMap<String, LabelCounters>>,
 
     // Timing metrics (set once, never change)
     /// Total time from create() call to LiteBox ready (includes all stages)
@@ -36,6 +47,8 @@ pub struct BoxMetricsStorage {
     pub(crate) stage_box_spawn_ms: Option<u128>,
     /// Time to initialize container inside guest (Stage 6)
     pub(crate) stage_container_init_ms: Option<u128>,
+    /// Rootfs build breakdown within Stage 2, when the rootfs was built
+    pub(crate) rootfs_build: Option<crate::litebox::RootfsBuildTimings>,
 }
 
 impl Clone for BoxMetricsStorage {
@@ -45,6 +58,13 @@ impl Clone for BoxMetricsStorage {
             exec_errors: AtomicU64::new(self.exec_errors.load(Ordering::Relaxed)),
             bytes_sent: AtomicU64::new(self.bytes_sent.load(Ordering::Relaxed)),
             bytes_received: AtomicU64::new(self.bytes_received.load(Ordering::Relaxed)),
+            labeled: RwLock::new(
+                self.labeled
+                    .read()
+                    .iter()
+                    .map(|(label, counters)| (label.clone(), counters.snapshot().into()))
+                    .collect(),
+            ),
             total_create_duration_ms: self.total_create_duration_ms,
             guest_boot_duration_ms: self.guest_boot_duration_ms,
             stage_filesystem_setup_ms: self.stage_filesystem_setup_ms,
@@ -53,6 +73,7 @@ impl Clone for BoxMetricsStorage {
             stage_box_config_ms: self.stage_box_config_ms,
             stage_box_spawn_ms: self.stage_box_spawn_ms,
             stage_container_init_ms: self.stage_container_init_ms,
+            rootfs_build: self.rootfs_build,
         }
     }
 }
@@ -69,7 +90,6 @@ impl BoxMetricsStorage {
     }
 
     /// Set guest boot duration (called once after guest is ready).
-    #[allow(dead_code)] // API designed but not yet wired up
     pub(crate) fn set_guest_boot_duration(&mut self, duration_ms: u128) {
         self.guest_boot_duration_ms = Some(duration_ms);
     }
@@ -90,7 +110,6 @@ impl BoxMetricsStorage {
     }
 
     /// Set box config build stage duration.
-    #[allow(dead_code)] // API designed but not yet wired up
     pub(crate) fn set_stage_box_config(&mut self, duration_ms: u128) {
         self.stage_box_config_ms = Some(duration_ms);
     }
@@ -105,6 +124,30 @@ impl BoxMetricsStorage {
         self.stage_container_init_ms = Some(duration_ms);
     }
 
+    /// Set how the rootfs was built during image preparation.
+    pub(crate) fn set_rootfs_build(&mut self, build: crate::litebox::RootfsBuildTimings) {
+        self.rootfs_build = Some(build);
+    }
+
+    /// Stage durations in the form persisted with the box state.
+    pub(crate) fn timings(&self) -> crate::litebox::BoxTimings {
+        let ms = |d: Option<u128>| d.map(|d| d as u64);
+        let guest_ms = match (self.guest_boot_duration_ms, self.stage_container_init_ms) {
+            (None, None) => None,
+            (boot, init) => Some(boot.unwrap_or(0) + init.unwrap_or(0)),
+        };
+        crate::litebox::BoxTimings {
+            filesystem_ms: ms(self.stage_filesystem_setup_ms),
+            rootfs_ms: ms(self.stage_image_prepare_ms),
+            guest_rootfs_ms: ms(self.stage_guest_rootfs_ms),
+            config_ms: ms(self.stage_box_config_ms),
+            spawn_ms: ms(self.stage_box_spawn_ms),
+            guest_ms: ms(guest_ms),
+            total_ms: ms(self.total_create_duration_ms),
+            rootfs_build: self.rootfs_build,
+        }
+    }
+
     /// Log init stage durations for debugging.
     pub(crate) fn log_init_stages(&self) {
         tracing::debug!(
@@ -129,6 +172,37 @@ impl BoxMetricsStorage {
         self.exec_errors.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Count an execution under each of its labels.
+    pub(crate) fn record_labeled_exec(&self, labels: &[String], failed: bool) {
+        if labels.is_empty() {
+            return;
+        }
+        {
+            let labeled = self.labeled.read();
+            if labels.iter().all(|label| labeled.contains_key(label)) {
+                for label in labels {
+                    labeled[label].record(failed);
+                }
+                return;
+            }
+        }
+        let mut labeled = self.labeled.write();
+        for label in labels {
+            labeled.entry(label.clone()).or_default().record(failed);
+        }
+    }
+
+    /// Zero the exec and I/O counters and drop the labeled ones.
+    ///
+    /// Timings are kept: they describe how the box started, not its workload.
+    pub(crate) fn reset(&self) {
+        self.commands_executed.store(0, Ordering::Relaxed);
+        self.exec_errors.store(0, Ordering::Relaxed);
+        self.bytes_sent.store(0, Ordering::Relaxed);
+        self.bytes_received.store(0, Ordering::Relaxed);
+        self.labeled.write().clear();
+    }
+
     /// Add bytes sent to counter.
     #[allow(dead_code)]
     pub(crate) fn add_bytes_sent(&self, bytes: u64) {
@@ -142,20 +216,105 @@ impl BoxMetricsStorage {
     }
 }
 
+/// Exec counters of the commands that carried one label.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecLabelMetrics {
+    /// Commands executed with this label
+    pub commands_executed_total: u64,
+    /// Command execution errors with this label
+    pub exec_errors_total: u64,
+}
+
+/// Live counters behind [`ExecLabelMetrics`].
+#[derive(Debug, Default)]
+pub(crate) struct LabelCounters {
+    commands_executed: AtomicU64,
+    exec_errors: AtomicU64,
+}
+
+impl LabelCounters {
+    fn record(&self, failed: bool) {
+        self.commands_executed.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.exec_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> ExecLabelMetrics {
+        ExecLabelMetrics {
+            commands_executed_total: self.commands_executed.load(Ordering::Relaxed),
+            exec_errors_total: self.exec_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl From<ExecLabelMetrics> for LabelCounters {
+    fn from(metrics: ExecLabelMetrics) -> Self {
+        Self {
+            commands_executed: AtomicU64::new(metrics.commands_executed_total),
+            exec_errors: AtomicU64::new(metrics.exec_errors_total),
+        }
+    }
+}
+
+/// Last sample of the VM process's CPU and memory usage.
+///
+/// CPU usage compares two snapshots of the process, so a sampler task
+/// refreshes both every [`GAUGE_SAMPLE_INTERVAL`] and `metrics()` only
+/// reads them.
+#[derive(Debug, Default)]
+pub(crate) struct VmGauges {
+    sampled: AtomicBool,
+    /// `f32` bits
+    cpu_percent: AtomicU32,
+    memory_bytes: AtomicU64,
+}
+
+impl VmGauges {
+    /// Keep `sample`; a sample without values clears the gauges.
+    pub(crate) fn store(&self, sample: &VmmMetrics) {
+        match (sample.cpu_percent, sample.memory_bytes) {
+            (Some(cpu_percent), Some(memory_bytes)) => {
+                self.cpu_percent
+                    .store(cpu_percent.to_bits(), Ordering::Relaxed);
+                self.memory_bytes.store(memory_bytes, Ordering::Relaxed);
+                self.sampled.store(true, Ordering::Relaxed);
+            }
+            _ => self.sampled.store(false, Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn cpu_percent(&self) -> Option<f32> {
+        self.sampled
+            .load(Ordering::Relaxed)
+            .then(|| f32::from_bits(self.cpu_percent.load(Ordering::Relaxed)))
+    }
+
+    pub(crate) fn memory_bytes(&self) -> Option<u64> {
+        self.sampled
+            .load(Ordering::Relaxed)
+            .then(|| self.memory_bytes.load(Ordering::Relaxed))
+    }
+}
+
 /// Handle for querying per-box metrics.
 ///
 /// Snapshot of metrics at query time.
-/// All counters are monotonic and never reset.
+/// Counters are monotonic until `LiteBox::reset_metrics()` zeroes them.
 #[derive(Clone, Debug)]
 pub struct BoxMetrics {
     /// Commands executed on this box
     pub commands_executed_total: u64,
     /// Command execution errors on this box
     pub exec_errors_total: u64,
+    /// Executions started through this runtime that are still running
+    pub execs_in_flight: u64,
     /// Bytes sent to this box (via stdin)
     pub bytes_sent_total: u64,
     /// Bytes received from this box (via stdout/stderr)
     pub bytes_received_total: u64,
+    /// Exec counters per command label, e.g. `"step=compile"`
+    pub labeled: BTreeMap<String, ExecLabelMetrics>,
     /// Total time from create() call to LiteBox ready (milliseconds)
     pub total_create_duration_ms: Option<u128>,
     /// Time from box subprocess spawn to guest agent ready (milliseconds)
@@ -172,6 +331,28 @@ pub struct BoxMetrics {
     pub network_tcp_connections: Option<u64>,
     /// Total TCP connection errors
     pub network_tcp_errors: Option<u64>,
+    /// Periods in which the container hit its CPU limit
+    pub cpu_throttled_periods_total: Option<u64>,
+    /// Total time the container was throttled by its CPU limit (microseconds)
+    pub cpu_throttled_usec_total: Option<u64>,
+    /// Times the container's memory usage hit its limit
+    pub memory_limit_hits_total: Option<u64>,
+    /// Processes OOM-killed for exceeding the container's memory limit
+    pub memory_oom_kills_total: Option<u64>,
+    /// Container memory minus inactive page cache (bytes)
+    pub memory_working_set_bytes: Option<u64>,
+    /// Container page cache (bytes)
+    pub memory_cache_bytes: Option<u64>,
+    /// Processes and threads in the container
+    pub pids_current: Option<u64>,
+    /// Bytes the container read from block devices
+    pub io_read_bytes_total: Option<u64>,
+    /// Bytes the container wrote to block devices
+    pub io_write_bytes_total: Option<u64>,
+    /// Swap in use in the VM (bytes)
+    pub swap_used_bytes: Option<u64>,
+    /// Bytes the VM swapped out since it started
+    pub swap_out_bytes_total: Option<u64>,
 
     // Stage-level timing breakdown
     /// Time to create box directory structure (milliseconds)
@@ -186,6 +367,8 @@ pub struct BoxMetrics {
     pub stage_box_spawn_ms: Option<u128>,
     /// Time to initialize container inside guest (milliseconds)
     pub stage_container_init_ms: Option<u128>,
+    /// How the rootfs was built from the image layers, if it was
+    pub rootfs_build: Option<crate::litebox::RootfsBuildTimings>,
 }
 
 impl BoxMetrics {
@@ -202,8 +385,15 @@ impl BoxMetrics {
         Self {
             commands_executed_total: storage.commands_executed.load(Ordering::Relaxed),
             exec_errors_total: storage.exec_errors.load(Ordering::Relaxed),
+            execs_in_flight: 0,
             bytes_sent_total: storage.bytes_sent.load(Ordering::Relaxed),
             bytes_received_total: storage.bytes_received.load(Ordering::Relaxed),
+            labeled: storage
+                .labeled
+                .read()
+                .iter()
+                .map(|(label, counters)| (label.clone(), counters.snapshot()))
+                .collect(),
             total_create_duration_ms: storage.total_create_duration_ms,
             guest_boot_duration_ms: storage.guest_boot_duration_ms,
             cpu_percent,
@@ -212,19 +402,50 @@ impl BoxMetrics {
             network_bytes_received,
             network_tcp_connections,
             network_tcp_errors,
+            cpu_throttled_periods_total: None,
+            cpu_throttled_usec_total: None,
+            memory_limit_hits_total: None,
+            memory_oom_kills_total: None,
+            memory_working_set_bytes: None,
+            memory_cache_bytes: None,
+            pids_current: None,
+            io_read_bytes_total: None,
+            io_write_bytes_total: None,
+            swap_used_bytes: None,
+            swap_out_bytes_total: None,
             stage_filesystem_setup_ms: storage.stage_filesystem_setup_ms,
             stage_image_prepare_ms: storage.stage_image_prepare_ms,
             stage_guest_rootfs_ms: storage.stage_guest_rootfs_ms,
             stage_box_config_ms: storage.stage_box_config_ms,
             stage_box_spawn_ms: storage.stage_box_spawn_ms,
             stage_container_init_ms: storage.stage_container_init_ms,
+            rootfs_build: storage.rootfs_build,
         }
     }
 
+    /// Fill in usage and throttling counters from the container's cgroup.
+    pub(crate) fn set_cgroup_stats(&mut self, stats: ContainerCgroupStats) {
+        self.cpu_throttled_periods_total = Some(stats.cpu_nr_throttled);
+        self.cpu_throttled_usec_total = Some(stats.cpu_throttled_usec);
+        self.memory_limit_hits_total = Some(stats.memory_max_events);
+        self.memory_oom_kills_total = Some(stats.memory_oom_kills);
+        self.memory_working_set_bytes = Some(stats.memory_working_set_bytes);
+        self.memory_cache_bytes = Some(stats.memory_cache_bytes);
+        self.pids_current = stats.pids_current;
+        self.io_read_bytes_total = stats.io_read_bytes;
+        self.io_write_bytes_total = stats.io_write_bytes;
+    }
+
+    /// Fill in the VM's swap counters.
+    pub(crate) fn set_swap_stats(&mut self, used_bytes: u64, out_bytes: u64) {
+        self.swap_used_bytes = Some(used_bytes);
+        self.swap_out_bytes_total = Some(out_bytes);
+    }
+
     /// Total commands executed on this box.
     ///
     /// Incremented on every `exec()` call.
-    /// Never decreases (monotonic counter).
+    /// Only decreases when `LiteBox::reset_metrics()` zeroes it.
     pub fn commands_executed_total(&self) -> u64 {
         self.commands_executed_total
     }
@@ -232,21 +453,35 @@ impl BoxMetrics {
     /// Total command execution errors on this box.
     ///
     /// Incremented when `exec()` returns error.
-    /// Never decreases (monotonic counter).
+    /// Only decreases when `LiteBox::reset_metrics()` zeroes it.
     pub fn exec_errors_total(&self) -> u64 {
         self.exec_errors_total
     }
 
+    /// Exec counters of the commands started with a given label.
+    ///
+    /// Labels never used (or used only before a reset) are absent.
+    pub fn labeled(&self, label: &str) -> ExecLabelMetrics {
+        self.labeled.get(label).copied().unwrap_or_default()
+    }
+
+    /// Executions started through this runtime whose process hasn't exited.
+    ///
+    /// Counted against `BoxOptions::max_concurrent_execs`; can go down.
+    pub fn execs_in_flight(&self) -> u64 {
+        self.execs_in_flight
+    }
+
     /// Total bytes sent to this box (stdin).
     ///
-    /// Never decreases (monotonic counter).
+    /// Only decreases when `LiteBox::reset_metrics()` zeroes it.
     pub fn bytes_sent_total(&self) -> u64 {
         self.bytes_sent_total
     }
 
     /// Total bytes received from this box (stdout/stderr).
     ///
-    /// Never decreases (monotonic counter).
+    /// Only decreases when `LiteBox::reset_metrics()` zeroes it.
     pub fn bytes_received_total(&self) -> u64 {
         self.bytes_received_total
     }
@@ -310,6 +545,89 @@ impl BoxMetrics {
         self.network_tcp_errors
     }
 
+    /// Periods in which the container was throttled by its CPU limit.
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits.
+    pub fn cpu_throttled_periods_total(&self) -> Option<u64> {
+        self.cpu_throttled_periods_total
+    }
+
+    /// Total time the container was throttled by its CPU limit (microseconds).
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits.
+    pub fn cpu_throttled_usec_total(&self) -> Option<u64> {
+        self.cpu_throttled_usec_total
+    }
+
+    /// Times the container's memory usage hit its limit.
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits.
+    pub fn memory_limit_hits_total(&self) -> Option<u64> {
+        self.memory_limit_hits_total
+    }
+
+    /// Processes OOM-killed inside the container for exceeding its memory limit.
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits.
+    pub fn memory_oom_kills_total(&self) -> Option<u64> {
+        self.memory_oom_kills_total
+    }
+
+    /// Container memory the kernel can't easily reclaim: usage minus
+    /// inactive page cache (bytes).
+    ///
+    /// Closer to what triggers the OOM killer than `memory_bytes`.
+    /// Returns None if the box has no `cpus`/`memory_mib` limits.
+    pub fn memory_working_set_bytes(&self) -> Option<u64> {
+        self.memory_working_set_bytes
+    }
+
+    /// Page cache charged to the container (bytes).
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits.
+    pub fn memory_cache_bytes(&self) -> Option<u64> {
+        self.memory_cache_bytes
+    }
+
+    /// Processes and threads currently in the container.
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits, or the
+    /// guest kernel doesn't enable the pids controller.
+    pub fn pids_current(&self) -> Option<u64> {
+        self.pids_current
+    }
+
+    /// Total bytes the container read from block devices.
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits, or the
+    /// guest kernel doesn't enable the io controller.
+    pub fn io_read_bytes_total(&self) -> Option<u64> {
+        self.io_read_bytes_total
+    }
+
+    /// Total bytes the container wrote to block devices.
+    ///
+    /// Returns None if the box has no `cpus`/`memory_mib` limits, or the
+    /// guest kernel doesn't enable the io controller.
+    pub fn io_write_bytes_total(&self) -> Option<u64> {
+        self.io_write_bytes_total
+    }
+
+    /// Swap in use in the VM (bytes).
+    ///
+    /// Returns None if the box has no `swap_mib`.
+    pub fn swap_used_bytes(&self) -> Option<u64> {
+        self.swap_used_bytes
+    }
+
+    /// Total bytes the VM swapped out since it started.
+    ///
+    /// A fast-growing value means the box needs more `memory_mib`.
+    /// Returns None if the box has no `swap_mib`.
+    pub fn swap_out_bytes_total(&self) -> Option<u64> {
+        self.swap_out_bytes_total
+    }
+
     // Stage-level timing getters
 
     /// Time to create box directory structure (milliseconds).
@@ -364,4 +682,136 @@ impl BoxMetrics {
     pub fn stage_container_init_ms(&self) -> Option<u128> {
         self.stage_container_init_ms
     }
+
+    /// How the rootfs was materialized during Stage 2.
+    ///
+    /// Returns None when the image's cached rootfs disk was reused.
+    pub fn rootfs_build(&self) -> Option<crate::litebox::RootfsBuildTimings> {
+        self.rootfs_build
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labeled_counters_accumulate_and_reset() {
+        let storage = BoxMetricsStorage::new();
+        let compile = vec!["step=compile".to_string()];
+        storage.increment_commands_executed();
+        storage.record_labeled_exec(&compile, false);
+        storage.increment_commands_executed();
+        storage.record_labeled_exec(&compile, true);
+        storage.record_labeled_exec(&[], true);
+
+        let metrics = BoxMetrics::from_storage(&storage, None, None, None, None, None, None);
+        assert_eq!(metrics.commands_executed_total(), 2);
+        assert_eq!(
+            metrics.labeled("step=compile"),
+            ExecLabelMetrics {
+                commands_executed_total: 2,
+                exec_errors_total: 1,
+            }
+        );
+        assert_eq!(metrics.labeled("step=test"), ExecLabelMetrics::default());
+
+        storage.reset();
+        let metrics = BoxMetrics::from_storage(&storage, None, None, None, None, None, None);
+        assert_eq!(metrics.commands_executed_total(), 0);
+        assert!(metrics.labeled.is_empty());
+    }
+
+    #[test]
+    fn labeled_counters_are_exact_across_threads() {
+        let storage = std::sync::Arc::new(BoxMetricsStorage::new());
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let storage = storage.clone();
+                std::thread::spawn(move || {
+                    let labels = vec!["step=build".to_string(), format!("worker={}", worker)];
+                    for i in 0..1000 {
+                        storage.increment_commands_executed();
+                        storage.record_labeled_exec(&labels, i % 10 == 0);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let metrics = BoxMetrics::from_storage(&storage, None, None, None, None, None, None);
+        assert_eq!(metrics.commands_executed_total(), 4000);
+        assert_eq!(
+            metrics.labeled("step=build"),
+            ExecLabelMetrics {
+                commands_executed_total: 4000,
+                exec_errors_total: 400,
+            }
+        );
+        assert_eq!(metrics.labeled("worker=3").commands_executed_total, 1000);
+    }
+
+    #[test]
+    fn vm_gauges_follow_samples() {
+        let gauges = VmGauges::default();
+        assert_eq!(gauges.cpu_percent(), None);
+
+        gauges.store(&VmmMetrics {
+            cpu_percent: Some(12.5),
+            memory_bytes: Some(4096),
+            disk_bytes: None,
+        });
+        assert_eq!(gauges.cpu_percent(), Some(12.5));
+        assert_eq!(gauges.memory_bytes(), Some(4096));
+
+        // The VM process went away
+        gauges.store(&VmmMetrics::default());
+        assert_eq!(gauges.memory_bytes(), None);
+    }
+
+    /// Exec metrics of 8 threads running labeled execs, the box's exec hot
+    /// path. A lock taken per exec shows up as contention here.
+    ///
+    /// `cargo test -p boxlite --lib --release bench_record_exec -- --ignored --nocapture`
+    #[test]
+    #[ignore] // Benchmark
+    fn bench_record_exec() {
+        use std::time::Instant;
+
+        const THREADS: u32 = 8;
+        const EXECS: u32 = 200_000;
+        let storage = std::sync::Arc::new(BoxMetricsStorage::new());
+
+        let start = Instant::now();
+        let workers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let storage = storage.clone();
+                std::thread::spawn(move || {
+                    let labels = vec!["step=test".to_string()];
+                    for _ in 0..EXECS {
+                        storage.increment_commands_executed();
+                        storage.record_labeled_exec(std::hint::black_box(&labels), false);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        let per_exec = start.elapsed() / (THREADS * EXECS);
+
+        let metrics = BoxMetrics::from_storage(&storage, None, None, None, None, None, None);
+        assert_eq!(
+            metrics.labeled("step=test").commands_executed_total,
+            u64::from(THREADS * EXECS)
+        );
+        println!("{} threads: {:?} per exec", THREADS, per_exec);
+        assert!(
+            per_exec < Duration::from_micros(1),
+            "recording an exec took {:?}",
+            per_exec
+        );
+    }
 }
@@ -6,7 +6,8 @@
 //!
 //! # Design
 //!
-//! All counters are monotonic (never decrease). Delta calculation is the caller's
+//! All counters are monotonic; per-box ones can only be zeroed with
+//! `LiteBox::reset_metrics()`. Delta calculation is the caller's
 //! responsibility. Future `boxlite-metrics` crate may provide helpers (deferred).
 //!
 //! # Example
@@ -33,5 +34,6 @@
 mod box_metrics;
 mod runtime_metrics;
 
-pub use box_metrics::{BoxMetrics, BoxMetricsStorage};
+pub use box_metrics::{BoxMetrics, BoxMetricsStorage, ExecLabelMetrics};
+pub(crate) use box_metrics::{GAUGE_SAMPLE_INTERVAL, VmGauges};
 pub use runtime_metrics::{RuntimeMetrics, RuntimeMetricsStorage};
@@ -6,7 +6,8 @@ use std::sync::atomic::{AtomicU64, Ordering};
 /// Storage for runtime-wide metrics.
 ///
 /// Stored in `RuntimeState`, shared across all operations.
-/// All counters are monotonic (never decrease).
+/// All counters are monotonic (never decrease); the reservation gauges
+/// follow the running boxes.
 #[derive(Clone, Default)]
 pub struct RuntimeMetricsStorage {
     /// Total boxes created since runtime startup
@@ -19,6 +20,18 @@ pub struct RuntimeMetricsStorage {
     pub(crate) total_commands: Arc<AtomicU64>,
     /// Total command execution errors across all boxes
     pub(crate) total_exec_errors: Arc<AtomicU64>,
+    /// Total retries of registry requests during image pulls
+    pub(crate) registry_retries: Arc<AtomicU64>,
+    /// Total events not delivered to an event sink
+    pub(crate) events_dropped: Arc<AtomicU64>,
+    /// vCPUs reserved by running boxes
+    pub(crate) reserved_cpus: Arc<AtomicU64>,
+    /// Memory in MiB reserved by running boxes
+    pub(crate) reserved_memory_mib: Arc<AtomicU64>,
+    /// Configured CPU capacity (None: unlimited)
+    pub(crate) cpu_capacity: Option<u64>,
+    /// Configured memory capacity in MiB (None: unlimited)
+    pub(crate) memory_capacity_mib: Option<u64>,
 }
 
 impl RuntimeMetricsStorage {
@@ -92,6 +105,56 @@ impl RuntimeMetrics {
     pub fn total_exec_errors(&self) -> u64 {
         self.storage.total_exec_errors.load(Ordering::Relaxed)
     }
+
+    /// Total retries of registry requests (manifest and blob fetches).
+    ///
+    /// Incremented each time a transient registry failure is retried.
+    /// Never decreases (monotonic counter).
+    pub fn registry_retries_total(&self) -> u64 {
+        self.storage.registry_retries.load(Ordering::Relaxed)
+    }
+
+    /// Total events never delivered to an event sink (buffer full or
+    /// retries exhausted), summed over sinks.
+    ///
+    /// Never decreases (monotonic counter).
+    pub fn events_dropped_total(&self) -> u64 {
+        self.storage.events_dropped.load(Ordering::Relaxed)
+    }
+
+    /// vCPUs reserved by running boxes.
+    pub fn reserved_cpus(&self) -> u64 {
+        self.storage.reserved_cpus.load(Ordering::Relaxed)
+    }
+
+    /// Memory in MiB reserved by running boxes.
+    pub fn reserved_memory_mib(&self) -> u64 {
+        self.storage.reserved_memory_mib.load(Ordering::Relaxed)
+    }
+
+    /// CPU capacity from `BoxliteOptions.cpu_capacity` (None: unlimited).
+    pub fn cpu_capacity(&self) -> Option<u64> {
+        self.storage.cpu_capacity
+    }
+
+    /// Memory capacity in MiB from `BoxliteOptions.memory_capacity_mib`.
+    pub fn memory_capacity_mib(&self) -> Option<u64> {
+        self.storage.memory_capacity_mib
+    }
+
+    /// Reserved share of the CPU capacity (may exceed 1.0 when overcommitted).
+    pub fn cpu_utilization(&self) -> Option<f64> {
+        self.cpu_capacity()
+            .filter(|&capacity| capacity > 0)
+            .map(|capacity| self.reserved_cpus() as f64 / capacity as f64)
+    }
+
+    /// Reserved share of the memory capacity (may exceed 1.0 when overcommitted).
+    pub fn memory_utilization(&self) -> Option<f64> {
+        self.memory_capacity_mib()
+            .filter(|&capacity| capacity > 0)
+            .map(|capacity| self.reserved_memory_mib() as f64 / capacity as f64)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,95 @@
+//! Per-connection network stats reported by the guest.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// Transport protocol of a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetProtocol {
+    Tcp,
+    Udp,
+}
+
+impl NetProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetProtocol::Tcp => "tcp",
+            NetProtocol::Udp => "udp",
+        }
+    }
+}
+
+/// An active TCP or UDP socket inside a box.
+///
+/// Listening TCP sockets are not included. Unconnected UDP sockets have an
+/// unspecified remote address and the `UNCONN` state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetConnection {
+    pub protocol: NetProtocol,
+    pub local_address: SocketAddr,
+    pub remote_address: SocketAddr,
+    /// Kernel socket state, e.g. `ESTABLISHED` or `TIME_WAIT`
+    pub state: String,
+    /// Bytes sent and acknowledged by the peer (TCP only)
+    pub bytes_sent: Option<u64>,
+    /// Bytes received (TCP only)
+    pub bytes_received: Option<u64>,
+}
+
+impl TryFrom<boxlite_shared::NetConnection> for NetConnection {
+    type Error = BoxliteError;
+
+    fn try_from(conn: boxlite_shared::NetConnection) -> BoxliteResult<Self> {
+        let protocol = match conn.protocol() {
+            boxlite_shared::NetProtocol::Tcp => NetProtocol::Tcp,
+            boxlite_shared::NetProtocol::Udp => NetProtocol::Udp,
+            boxlite_shared::NetProtocol::Unspecified => {
+                return Err(BoxliteError::Internal(
+                    "guest reported a connection without a protocol".to_string(),
+                ));
+            }
+        };
+        let parse = |addr: &str| {
+            addr.parse::<SocketAddr>().map_err(|e| {
+                BoxliteError::Internal(format!("guest reported invalid address {}: {}", addr, e))
+            })
+        };
+        Ok(Self {
+            protocol,
+            local_address: parse(&conn.local_address)?,
+            remote_address: parse(&conn.remote_address)?,
+            state: conn.state,
+            bytes_sent: conn.bytes_sent,
+            bytes_received: conn.bytes_received,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_proto() {
+        let conn = NetConnection::try_from(boxlite_shared::NetConnection {
+            protocol: boxlite_shared::NetProtocol::Tcp as i32,
+            local_address: "192.168.127.2:40312".into(),
+            remote_address: "[2606:4700::1111]:443".into(),
+            state: "ESTABLISHED".into(),
+            bytes_sent: Some(517),
+            bytes_received: Some(4096),
+        })
+        .unwrap();
+        assert_eq!(conn.protocol, NetProtocol::Tcp);
+        assert_eq!(conn.remote_address.port(), 443);
+        assert_eq!(conn.bytes_received, Some(4096));
+
+        let err = NetConnection::try_from(boxlite_shared::NetConnection {
+            local_address: "nonsense".into(),
+            ..Default::default()
+        });
+        assert!(matches!(err, Err(BoxliteError::Internal(_))));
+    }
+}
@@ -13,6 +13,31 @@ pub const GATEWAY_IP: &str = "192.168.127.1";
 /// Guest IP address (assigned via DHCP static lease)
 pub const GUEST_IP: &str = "192.168.127.2";
 
+/// Host address as seen from the guest
+///
+/// gvproxy translates it to the host's 127.0.0.1 when the box's gvproxy
+/// config sets `host_ip`.
+pub const HOST_IP: &str = "192.168.127.254";
+
+/// Addresses of the boxes sharing a user-defined network
+///
+/// gvproxy translates each to the host's 127.0.0.1, letting through only
+/// the ports the box at that address publishes.
+pub const PEER_SUBNET: &str = "192.168.127.128/26";
+
+/// First address of [`PEER_SUBNET`]
+pub const PEER_IP_FIRST: [u8; 4] = [192, 168, 127, 128];
+
+/// Number of addresses in [`PEER_SUBNET`]
+pub const PEER_IP_COUNT: usize = 64;
+
+/// DNS zone served by gvproxy for names BoxLite defines
+pub const INTERNAL_DNS_ZONE: &str = "boxlite.internal";
+
+/// Name boxes with `BoxOptions.host_loopback` resolve to [`HOST_IP`], for
+/// reaching host services without knowing the virtual network layout
+pub const HOST_NAME: &str = "host.boxlite.internal";
+
 /// Gateway MAC address
 ///
 /// This MAC is used by gvproxy's virtual network interface.
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::net::recorder::HttpRecorderConfig;
+
 /// Local DNS zone configuration
 ///
 /// Defines local DNS records served by the gateway's embedded DNS server.
@@ -10,8 +12,19 @@ use serde::{Deserialize, Serialize};
 pub struct DnsZone {
     /// Zone name (e.g., "myapp.local.", "." for root)
     pub name: String,
-    /// Default IP for unmatched queries in this zone
+    /// Default IP for unmatched queries in this zone, empty for none
     pub default_ip: String,
+    /// Names in the zone, relative to it
+    #[serde(default)]
+    pub records: Vec<DnsRecord>,
+}
+
+/// A record of a [`DnsZone`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsRecord {
+    /// Name relative to the zone (e.g., "host")
+    pub name: String,
+    pub ip: String,
 }
 
 /// Port mapping configuration
@@ -44,6 +57,11 @@ pub struct GvproxyConfig {
     /// Guest MAC address
     pub guest_mac: String,
 
+    /// Virtual IP translated to the host's 127.0.0.1, exposing every
+    /// service the host listens on there to the guest. None by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_ip: Option<String>,
+
     /// MTU for the virtual network
     pub mtu: u16,
 
@@ -64,6 +82,24 @@ pub struct GvproxyConfig {
     /// Set via config or BOXLITE_NET_CAPTURE_FILE environment variable
     #[serde(skip_serializing_if = "Option::is_none")]
     pub capture_file: Option<String>,
+
+    /// Unix socket the host answers connection prompts on
+    /// When set, new outbound connections are held back until the host allows them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_prompt_socket: Option<String>,
+
+    /// HTTP proxy on the gateway recording or replaying responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_recorder: Option<HttpRecorderConfig>,
+
+    /// Addresses of the boxes on the same user-defined networks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_subnet: Option<String>,
+
+    /// File listing the address of each network peer and the ports it
+    /// publishes, rewritten by the host as peers come and go
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peers_file: Option<String>,
 }
 
 impl Default for GvproxyConfig {
@@ -76,12 +112,17 @@ impl Default for GvproxyConfig {
             gateway_mac: GATEWAY_MAC_STRING.to_string(),
             guest_ip: GUEST_IP.to_string(),
             guest_mac: GUEST_MAC_STRING.to_string(),
+            host_ip: None,
             mtu: DEFAULT_MTU,
             port_mappings: Vec::new(),
             dns_zones: Vec::new(),
             dns_search_domains: DNS_SEARCH_DOMAINS.iter().map(|s| s.to_string()).collect(),
             debug: false,
             capture_file: None,
+            connection_prompt_socket: None,
+            http_recorder: None,
+            peer_subnet: None,
+            peers_file: None,
         }
     }
 }
@@ -169,6 +210,49 @@ impl GvproxyConfig {
         self.capture_file = Some(capture_file);
         self
     }
+
+    /// Ask the host, over `socket`, before each new outbound connection
+    pub fn with_connection_prompt(mut self, socket: String) -> Self {
+        self.connection_prompt_socket = Some(socket);
+        self
+    }
+
+    /// Serve an HTTP proxy on the gateway that records or replays responses
+    pub fn with_http_recorder(mut self, recorder: HttpRecorderConfig) -> Self {
+        self.http_recorder = Some(recorder);
+        self
+    }
+
+    /// Route the host's 127.0.0.1 to the guest at
+    /// [`HOST_IP`](crate::net::constants::HOST_IP), resolved as
+    /// [`HOST_NAME`](crate::net::constants::HOST_NAME)
+    ///
+    /// Every service listening on the host's loopback becomes reachable
+    /// from the guest.
+    pub fn with_host_loopback(mut self) -> Self {
+        use crate::net::constants::{HOST_IP, INTERNAL_DNS_ZONE};
+
+        self.host_ip = Some(HOST_IP.to_string());
+        self.dns_zones.push(DnsZone {
+            name: format!("{}.", INTERNAL_DNS_ZONE),
+            default_ip: String::new(),
+            // HOST_NAME, relative to the zone
+            records: vec![DnsRecord {
+                name: "host".to_string(),
+                ip: HOST_IP.to_string(),
+            }],
+        });
+        self
+    }
+
+    /// Give the boxes on the same user-defined networks addresses in
+    /// [`PEER_SUBNET`](crate::net::constants::PEER_SUBNET), reachable on
+    /// the ports `peers_file` lists
+    pub fn with_peers(mut self, peers_file: String) -> Self {
+        self.peer_subnet = Some(crate::net::constants::PEER_SUBNET.to_string());
+        self.peers_file = Some(peers_file);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -180,10 +264,29 @@ mod tests {
         let config = GvproxyConfig::default();
         assert_eq!(config.subnet, "192.168.127.0/24");
         assert_eq!(config.gateway_ip, "192.168.127.1");
+        assert_eq!(config.host_ip, None);
         assert_eq!(config.guest_ip, "192.168.127.2");
         assert_eq!(config.mtu, 1500);
         assert!(!config.debug);
         assert!(config.dns_zones.is_empty());
+        assert_eq!(config.peers_file, None);
+    }
+
+    #[test]
+    fn test_host_loopback_builder() {
+        let config = GvproxyConfig::default().with_host_loopback();
+        assert_eq!(config.host_ip.as_deref(), Some("192.168.127.254"));
+        assert_eq!(config.dns_zones.len(), 1);
+        assert_eq!(config.dns_zones[0].name, "boxlite.internal.");
+        assert_eq!(config.dns_zones[0].records[0].name, "host");
+        assert_eq!(config.dns_zones[0].records[0].ip, "192.168.127.254");
+    }
+
+    #[test]
+    fn test_peers_builder() {
+        let config = GvproxyConfig::default().with_peers("/tmp/peers.json".to_string());
+        assert_eq!(config.peer_subnet.as_deref(), Some("192.168.127.128/26"));
+        assert_eq!(config.peers_file.as_deref(), Some("/tmp/peers.json"));
     }
 
     #[test]
@@ -56,9 +56,7 @@
 //! ```no_run
 //! use boxlite::net::{NetworkBackendConfig, GvisorTapBackend, NetworkBackend};
 //!
-//! let config = NetworkBackendConfig {
-//!     port_mappings: vec![(8080, 80), (8443, 443)],
-//! };
+//! let config = NetworkBackendConfig::new(vec![(8080, 80), (8443, 443)]);
 //!
 //! // Create backend - logs from gvproxy will appear in tracing
 //! let backend = GvisorTapBackend::new(config)?;
@@ -80,7 +78,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 // Re-export public API
-pub use config::{DnsZone, GvproxyConfig, PortMapping};
+pub use config::{DnsRecord, DnsZone, GvproxyConfig, PortMapping};
 pub use instance::GvproxyInstance;
 pub use logging::init_logging;
 pub use stats::{NetworkStats, TcpStats};
@@ -127,9 +125,7 @@ impl GvisorTapBackend {
     /// ```no_run
     /// use boxlite::net::{NetworkBackendConfig, GvisorTapBackend};
     ///
-    /// let config = NetworkBackendConfig {
-    ///     port_mappings: vec![(8080, 80), (8443, 443)],
-    /// };
+    /// let config = NetworkBackendConfig::new(vec![(8080, 80), (8443, 443)]);
     ///
     /// let backend = GvisorTapBackend::new(config)?;
     /// # Ok::<(), boxlite_shared::errors::BoxliteError>(())
@@ -141,7 +137,20 @@ impl GvisorTapBackend {
         );
 
         // Create gvproxy instance with port mappings
-        let instance = Arc::new(GvproxyInstance::new(&config.port_mappings)?);
+        let mut gvproxy_config = GvproxyConfig::new(config.port_mappings.clone());
+        if let Some(socket) = &config.connection_prompt_socket {
+            gvproxy_config = gvproxy_config.with_connection_prompt(socket.display().to_string());
+        }
+        if let Some(recorder) = &config.http_recorder {
+            gvproxy_config = gvproxy_config.with_http_recorder(recorder.clone());
+        }
+        if config.host_loopback {
+            gvproxy_config = gvproxy_config.with_host_loopback();
+        }
+        if let Some(peers_file) = &config.peers_file {
+            gvproxy_config = gvproxy_config.with_peers(peers_file.display().to_string());
+        }
+        let instance = Arc::new(GvproxyInstance::from_config(&gvproxy_config)?);
 
         // Start background stats logging thread
         instance::start_stats_logging(Arc::downgrade(&instance));
@@ -175,9 +184,7 @@ impl GvisorTapBackend {
     /// ```no_run
     /// use boxlite::net::{NetworkBackendConfig, GvisorTapBackend};
     ///
-    /// let config = NetworkBackendConfig {
-    ///     port_mappings: vec![(8080, 80)],
-    /// };
+    /// let config = NetworkBackendConfig::new(vec![(8080, 80)]);
     /// let backend = GvisorTapBackend::new(config)?;
     ///
     /// // Get stats
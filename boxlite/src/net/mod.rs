@@ -10,7 +10,9 @@
 use boxlite_shared::errors::BoxliteResult;
 use std::path::PathBuf;
 
+pub mod connections;
 pub mod constants;
+pub mod recorder;
 
 #[cfg(feature = "libslirp-backend")]
 mod libslirp;
@@ -18,6 +20,8 @@ mod libslirp;
 #[cfg(feature = "gvproxy-backend")]
 pub mod gvproxy;
 
+pub use connections::{NetConnection, NetProtocol};
+
 #[cfg(feature = "libslirp-backend")]
 pub use libslirp::LibslirpBackend;
 
@@ -50,11 +54,33 @@ pub enum NetworkBackendEndpoint {
 pub struct NetworkBackendConfig {
     /// Port mappings: (host_port, guest_port)
     pub port_mappings: Vec<(u16, u16)>,
+    /// Unix socket the host answers connection prompts on, for boxes with
+    /// `BoxOptions.prompt_connections`
+    #[serde(default)]
+    pub connection_prompt_socket: Option<PathBuf>,
+    /// Proxy recording or replaying HTTP(S) responses, for boxes with
+    /// `BoxOptions.network_mode`
+    #[serde(default)]
+    pub http_recorder: Option<recorder::HttpRecorderConfig>,
+    /// Addresses and published ports of the boxes sharing a user-defined
+    /// network, for boxes with `BoxOptions.networks`
+    #[serde(default)]
+    pub peers_file: Option<PathBuf>,
+    /// Translate the host address to the host's loopback, for boxes with
+    /// `BoxOptions.host_loopback`
+    #[serde(default)]
+    pub host_loopback: bool,
 }
 
 impl NetworkBackendConfig {
     pub fn new(port_mappings: Vec<(u16, u16)>) -> Self {
-        Self { port_mappings }
+        Self {
+            port_mappings,
+            connection_prompt_socket: None,
+            http_recorder: None,
+            peers_file: None,
+            host_loopback: false,
+        }
     }
 }
 
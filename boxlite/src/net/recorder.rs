@@ -0,0 +1,84 @@
+//! HTTP(S) record and replay.
+//!
+//! Boxes with `BoxOptions.network_mode` set to `Record` or `Replay` get an
+//! HTTP proxy from gvproxy, listening on the gateway, in their proxy
+//! environment (see `gvproxy-bridge/recorder.go`). The proxy saves each
+//! response in the recording directory, or answers from it without reaching
+//! the network. HTTPS is terminated by the proxy with certificates from a CA
+//! it creates on start; the box trusts that CA through `SSL_CERT_FILE` and
+//! the variables of common runtimes that don't read it.
+//!
+//! Only clients that honor the proxy variables are recorded. Names only the
+//! box's own DNS resolver answers for, such as members of its networks, can't
+//! be reached through the proxy and are best added to `NO_PROXY`.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::net::constants::{GATEWAY_IP, HOST_IP, HOST_NAME};
+
+/// Port of the proxy on the gateway
+pub const PROXY_PORT: u16 = 3128;
+
+/// Directory of the proxy CA certificate in the container
+pub const CA_GUEST_DIR: &str = "/etc/boxlite/recorder";
+
+/// File name of the proxy CA certificate
+pub const CA_FILE: &str = "ca.pem";
+
+/// Configuration of the recording proxy, passed to gvproxy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRecorderConfig {
+    /// Answer from `dir` instead of the network
+    pub replay: bool,
+    /// Directory of the recorded responses
+    pub dir: PathBuf,
+    /// Where the proxy writes its CA certificate
+    pub ca_cert: PathBuf,
+    /// Port of the proxy on the gateway
+    pub port: u16,
+}
+
+/// Environment pointing the box at the proxy and its CA.
+pub fn guest_env() -> Vec<(String, String)> {
+    let proxy = format!("http://{}:{}", GATEWAY_IP, PROXY_PORT);
+    let ca_cert = format!("{}/{}", CA_GUEST_DIR, CA_FILE);
+    let no_proxy = format!("localhost,127.0.0.1,::1,{},{}", HOST_NAME, HOST_IP);
+
+    let mut env = Vec::new();
+    for name in ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"] {
+        env.push((name.to_string(), proxy.clone()));
+    }
+    for name in ["NO_PROXY", "no_proxy"] {
+        env.push((name.to_string(), no_proxy.clone()));
+    }
+    // OpenSSL and Go, then the runtimes and tools with their own bundles
+    for name in [
+        "SSL_CERT_FILE",
+        "CURL_CA_BUNDLE",
+        "REQUESTS_CA_BUNDLE",
+        "NODE_EXTRA_CA_CERTS",
+    ] {
+        env.push((name.to_string(), ca_cert.clone()));
+    }
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guest_env_points_at_gateway_proxy() {
+        let env = guest_env();
+        let get = |name: &str| {
+            env.iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("HTTPS_PROXY"), Some("http://192.168.127.1:3128"));
+        assert_eq!(get("SSL_CERT_FILE"), Some("/etc/boxlite/recorder/ca.pem"));
+        assert!(get("NO_PROXY").unwrap().contains("host.boxlite.internal"));
+    }
+}
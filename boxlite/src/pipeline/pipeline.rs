@@ -22,6 +22,11 @@ impl<Ctx> ExecutionPlan<Ctx> {
     pub fn stages(self) -> Vec<Stage<BoxedTask<Ctx>>> {
         self.stages
     }
+
+    /// Number of tasks across all stages.
+    pub fn task_count(&self) -> usize {
+        self.stages.iter().map(|stage| stage.tasks.len()).sum()
+    }
 }
 
 pub struct Pipeline<Ctx> {
@@ -60,6 +65,21 @@ impl PipelineExecutor {
     where
         Ctx: Clone,
     {
+        Self::execute_observed(pipeline, ctx, |_| {}).await
+    }
+
+    /// Execute a pipeline, calling `on_task_start` with each task's name just
+    /// before it runs.
+    pub async fn execute_observed<Ctx, F>(
+        pipeline: Pipeline<Ctx>,
+        ctx: Ctx,
+        on_task_start: F,
+    ) -> BoxliteResult<PipelineMetrics>
+    where
+        Ctx: Clone,
+        F: Fn(&str) + Sync,
+    {
+        let on_task_start = &on_task_start;
         let total_start = Instant::now();
         let mut stage_metrics = Vec::new();
 
@@ -73,6 +93,7 @@ impl PipelineExecutor {
                         let ctx = ctx.clone();
                         async move {
                             let name = task.name().to_string();
+                            on_task_start(&name);
                             let task_start = Instant::now();
                             task.run(ctx).await?;
                             Ok::<TaskMetrics, boxlite_shared::errors::BoxliteError>(TaskMetrics {
@@ -87,6 +108,7 @@ impl PipelineExecutor {
                     let mut task_metrics = Vec::new();
                     for task in stage.tasks {
                         let name = task.name().to_string();
+                        on_task_start(&name);
                         let task_start = Instant::now();
                         task.run(ctx.clone()).await?;
                         task_metrics.push(TaskMetrics {
@@ -112,3 +134,51 @@ impl PipelineExecutor {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::task::PipelineTask;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    struct Noop(&'static str);
+
+    #[async_trait]
+    impl PipelineTask<()> for Noop {
+        async fn run(self: Box<Self>, _ctx: ()) -> BoxliteResult<()> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    fn tasks(names: &[&'static str]) -> Vec<BoxedTask<()>> {
+        names
+            .iter()
+            .map(|name| Box::new(Noop(name)) as BoxedTask<()>)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_execute_observed_reports_every_task() {
+        let plan = ExecutionPlan::new(vec![
+            Stage::sequential(tasks(&["a", "b"])),
+            Stage::parallel(tasks(&["c", "d"])),
+        ]);
+        assert_eq!(plan.task_count(), 4);
+
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let metrics = PipelineExecutor::execute_observed(PipelineBuilder::from_plan(plan), (), {
+            let started = started.clone();
+            move |name: &str| started.lock().unwrap().push(name.to_string())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(*started.lock().unwrap(), ["a", "b", "c", "d"]);
+        assert_eq!(metrics.stages.len(), 2);
+    }
+}
@@ -0,0 +1,344 @@
+//! External driver plugins over JSON-RPC.
+//!
+//! A plugin is a separate process listening on a Unix socket. Every call
+//! opens a connection, sends one JSON-RPC 2.0 request as a single line and
+//! reads one response line:
+//!
+//! ```text
+//! -> {"jsonrpc":"2.0","id":1,"method":"VolumeDriver.Mount","params":{"box_id":"...","name":"db","read_only":false}}
+//! <- {"jsonrpc":"2.0","id":1,"result":{"path":"/mnt/san/db"}}
+//! ```
+//!
+//! Methods:
+//! - `Plugin.Activate` → `{"implements": ["VolumeDriver", "NetworkDriver"]}`
+//! - `VolumeDriver.Mount` (a `VolumeRequest`) → `{"path": "/host/dir"}`
+//! - `VolumeDriver.Unmount` (a `VolumeRequest`) → `{}`
+//! - `NetworkDriver.Connect` (a `NetworkRequest`) →
+//!   `{"path": "/run/net.sock", "connection_type": "UnixStream"}`; the
+//!   optional `mac_address` (six bytes) defaults to `GUEST_MAC`
+//! - `NetworkDriver.Disconnect` (`{"box_id": "..."}`) → `{}`
+//!
+//! Failures are reported as `{"error": {"code": ..., "message": "..."}}`.
+//! `Plugin.Activate` is called once, on first use, so plugins may start
+//! after the runtime.
+
+use super::{NetworkDriver, NetworkRequest, VolumeDriver, VolumeRequest};
+use crate::net::constants::GUEST_MAC;
+use crate::net::{ConnectionType, NetworkBackendEndpoint};
+use crate::runtime::options::PluginSpec;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Interface name a plugin reports for volume drivers.
+pub const VOLUME_DRIVER: &str = "VolumeDriver";
+
+/// Interface name a plugin reports for network drivers.
+pub const NETWORK_DRIVER: &str = "NetworkDriver";
+
+/// Timeout for plugins that don't set `timeout_secs`.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    #[serde(default)]
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ActivateResult {
+    implements: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MountResult {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ConnectResult {
+    path: PathBuf,
+    #[serde(default = "default_connection_type")]
+    connection_type: ConnectionType,
+    #[serde(default)]
+    mac_address: Option<[u8; 6]>,
+}
+
+fn default_connection_type() -> ConnectionType {
+    ConnectionType::UnixStream
+}
+
+/// Client for one external plugin.
+pub(crate) struct ExternalPlugin {
+    name: String,
+    socket: PathBuf,
+    timeout: Duration,
+    /// Interfaces reported by `Plugin.Activate`, once it succeeded
+    implements: Mutex<Option<Vec<String>>>,
+}
+
+impl ExternalPlugin {
+    pub(crate) fn new(spec: &PluginSpec) -> Self {
+        Self {
+            name: spec.name.clone(),
+            socket: spec.socket.clone(),
+            timeout: spec
+                .timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_TIMEOUT),
+            implements: Mutex::new(None),
+        }
+    }
+
+    /// Check that the plugin implements `interface`, activating it if needed.
+    pub(crate) fn require(&self, interface: &str) -> BoxliteResult<()> {
+        let mut implements = self.implements.lock().unwrap_or_else(|e| e.into_inner());
+        if implements.is_none() {
+            let result: ActivateResult = self.call("Plugin.Activate", serde_json::json!({}))?;
+            tracing::info!(
+                plugin = %self.name,
+                implements = ?result.implements,
+                "Activated plugin"
+            );
+            *implements = Some(result.implements);
+        }
+        if implements
+            .as_ref()
+            .is_some_and(|list| list.iter().any(|i| i == interface))
+        {
+            Ok(())
+        } else {
+            Err(BoxliteError::Config(format!(
+                "plugin '{}' does not implement {}",
+                self.name, interface
+            )))
+        }
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> BoxliteResult<T> {
+        let result = self.request(method, params).map_err(|e| {
+            BoxliteError::Internal(format!("plugin '{}' {} failed: {}", self.name, method, e))
+        })?;
+        match result {
+            Ok(value) => serde_json::from_value(value).map_err(|e| {
+                BoxliteError::Internal(format!(
+                    "plugin '{}' returned an invalid {} result: {}",
+                    self.name, method, e
+                ))
+            }),
+            Err(error) => Err(BoxliteError::Execution(format!(
+                "plugin '{}' {} failed ({}): {}",
+                self.name, method, error.code, error.message
+            ))),
+        }
+    }
+
+    /// Send one request; the outer error is a transport or protocol failure.
+    fn request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> std::io::Result<Result<serde_json::Value, RpcError>> {
+        let stream = UnixStream::connect(&self.socket)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        (&stream).write_all(&line)?;
+
+        let mut response = String::new();
+        BufReader::new(&stream).read_line(&mut response)?;
+        if response.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "connection closed without a response",
+            ));
+        }
+        let response: Response = serde_json::from_str(&response)?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Ok(Err(error)),
+            (Some(result), None) => Ok(Ok(result)),
+            // `"result": null` deserializes as None; treat it as empty
+            (None, None) => Ok(Ok(serde_json::json!({}))),
+        }
+    }
+}
+
+fn to_params<T: serde::Serialize>(params: &T) -> BoxliteResult<serde_json::Value> {
+    serde_json::to_value(params)
+        .map_err(|e| BoxliteError::Internal(format!("failed to encode plugin request: {}", e)))
+}
+
+impl VolumeDriver for ExternalPlugin {
+    fn mount(&self, request: &VolumeRequest) -> BoxliteResult<PathBuf> {
+        let result: MountResult = self.call("VolumeDriver.Mount", to_params(request)?)?;
+        Ok(result.path)
+    }
+
+    fn unmount(&self, request: &VolumeRequest) -> BoxliteResult<()> {
+        let _: serde_json::Value = self.call("VolumeDriver.Unmount", to_params(request)?)?;
+        Ok(())
+    }
+}
+
+impl NetworkDriver for ExternalPlugin {
+    fn connect(&self, request: &NetworkRequest) -> BoxliteResult<NetworkBackendEndpoint> {
+        let result: ConnectResult = self.call("NetworkDriver.Connect", to_params(request)?)?;
+        Ok(NetworkBackendEndpoint::UnixSocket {
+            path: result.path,
+            connection_type: result.connection_type,
+            mac_address: result.mac_address.unwrap_or(GUEST_MAC),
+        })
+    }
+
+    fn disconnect(&self, box_id: &str) -> BoxliteResult<()> {
+        let _: serde_json::Value = self.call(
+            "NetworkDriver.Disconnect",
+            serde_json::json!({ "box_id": box_id }),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    /// Serve `responses` in order, one per connection, returning the requests.
+    fn serve(
+        listener: UnixListener,
+        responses: Vec<&'static str>,
+    ) -> std::thread::JoinHandle<Vec<serde_json::Value>> {
+        std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                requests.push(serde_json::from_str(&line).unwrap());
+                (&stream).write_all(response.as_bytes()).unwrap();
+                (&stream).write_all(b"\n").unwrap();
+            }
+            requests
+        })
+    }
+
+    fn plugin(socket: PathBuf) -> ExternalPlugin {
+        ExternalPlugin::new(&PluginSpec {
+            name: "san".into(),
+            socket,
+            timeout_secs: Some(5),
+        })
+    }
+
+    #[test]
+    fn test_volume_mount_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("san.sock");
+        let server = serve(
+            UnixListener::bind(&socket).unwrap(),
+            vec![
+                r#"{"jsonrpc":"2.0","id":1,"result":{"implements":["VolumeDriver"]}}"#,
+                r#"{"jsonrpc":"2.0","id":1,"result":{"path":"/mnt/san/db"}}"#,
+            ],
+        );
+
+        let plugin = plugin(socket);
+        plugin.require(VOLUME_DRIVER).unwrap();
+        let path = plugin
+            .mount(&VolumeRequest {
+                box_id: "box-a".into(),
+                name: "db".into(),
+                read_only: true,
+            })
+            .unwrap();
+        assert_eq!(path, PathBuf::from("/mnt/san/db"));
+        // Activation is cached, and the plugin didn't report NetworkDriver
+        assert!(matches!(
+            plugin.require(NETWORK_DRIVER),
+            Err(BoxliteError::Config(_))
+        ));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0]["method"], "Plugin.Activate");
+        assert_eq!(requests[1]["method"], "VolumeDriver.Mount");
+        assert_eq!(requests[1]["params"]["name"], "db");
+        assert_eq!(requests[1]["params"]["read_only"], true);
+    }
+
+    #[test]
+    fn test_network_connect_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("net.sock");
+        let server = serve(
+            UnixListener::bind(&socket).unwrap(),
+            vec![r#"{"jsonrpc":"2.0","id":1,"result":{"path":"/run/overlay.sock"}}"#],
+        );
+
+        let endpoint = plugin(socket)
+            .connect(&NetworkRequest {
+                box_id: "box-a".into(),
+                port_mappings: vec![(8080, 80)],
+            })
+            .unwrap();
+        let NetworkBackendEndpoint::UnixSocket {
+            path,
+            connection_type,
+            mac_address,
+        } = endpoint;
+        assert_eq!(path, PathBuf::from("/run/overlay.sock"));
+        assert!(matches!(connection_type, ConnectionType::UnixStream));
+        assert_eq!(mac_address, GUEST_MAC);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0]["params"]["port_mappings"][0][0], 8080);
+    }
+
+    #[test]
+    fn test_plugin_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("san.sock");
+        let server = serve(
+            UnixListener::bind(&socket).unwrap(),
+            vec![r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"LUN offline"}}"#],
+        );
+
+        let plugin = plugin(socket.clone());
+        let err = plugin.disconnect("box-a").unwrap_err();
+        assert!(matches!(err, BoxliteError::Execution(ref msg) if msg.contains("LUN offline")));
+        server.join().unwrap();
+
+        // Nobody listening any more
+        std::fs::remove_file(&socket).unwrap();
+        assert!(matches!(
+            plugin.require(VOLUME_DRIVER),
+            Err(BoxliteError::Internal(_))
+        ));
+    }
+}
@@ -0,0 +1,389 @@
+//! Volume and network driver plugins.
+//!
+//! Drivers let boxes use storage and networking that boxlite doesn't
+//! implement itself, such as SAN-backed volumes or a custom overlay:
+//! - A `VolumeSpec` with `driver` set is mounted by that [`VolumeDriver`] on
+//!   the host before the VM starts; the returned directory is shared into the
+//!   box like any other volume.
+//! - `NetworkSpec::Custom(name)` makes the named [`NetworkDriver`] provide the
+//!   socket the VM's network device connects to, instead of gvproxy. The
+//!   network must serve the topology in [`crate::net::constants`].
+//!
+//! Drivers come from two places:
+//! - In-process drivers register at compile time with `inventory::submit!`,
+//!   the same way VMM engines do (see `vmm::registry`). Crates linked into
+//!   the binary can add drivers this way, typically behind a cargo feature.
+//! - External plugins are separate processes listed in
+//!   `BoxliteOptions.plugins` and called over JSON-RPC (see [`external`]).
+//!
+//! Driver calls are blocking; async callers should use `spawn_blocking`.
+
+pub mod external;
+
+use crate::net::NetworkBackendEndpoint;
+use crate::runtime::options::{BoxOptions, NetworkSpec, PluginSpec};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use external::ExternalPlugin;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// A driver volume being mounted or unmounted for a box.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct VolumeRequest {
+    pub box_id: String,
+    /// Driver-specific volume name (`VolumeSpec.host_path`)
+    pub name: String,
+    pub read_only: bool,
+}
+
+/// Provides volumes backed by storage boxlite doesn't manage.
+pub trait VolumeDriver: Send + Sync {
+    /// Make the volume available on the host.
+    ///
+    /// Returns the host directory to share into the box.
+    fn mount(&self, request: &VolumeRequest) -> BoxliteResult<PathBuf>;
+
+    /// Release a volume mounted by `mount`, after the box has stopped.
+    ///
+    /// Also called when a start fails, possibly before `mount`.
+    fn unmount(&self, request: &VolumeRequest) -> BoxliteResult<()>;
+}
+
+/// A box being connected to a driver network.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct NetworkRequest {
+    pub box_id: String,
+    /// Port mappings: (host_port, guest_port)
+    pub port_mappings: Vec<(u16, u16)>,
+}
+
+/// Provides the network backend a box's VM connects to.
+pub trait NetworkDriver: Send + Sync {
+    /// Set up networking for a box and return the socket its VM connects to.
+    fn connect(&self, request: &NetworkRequest) -> BoxliteResult<NetworkBackendEndpoint>;
+
+    /// Tear down networking for a box, after it has stopped.
+    ///
+    /// Also called when a start fails, possibly before `connect`.
+    fn disconnect(&self, box_id: &str) -> BoxliteResult<()>;
+}
+
+/// Compile-time registration of an in-process volume driver.
+///
+/// ```ignore
+/// inventory::submit! {
+///     VolumeDriverRegistration { name: "san", driver: &SanVolumeDriver }
+/// }
+/// ```
+pub struct VolumeDriverRegistration {
+    pub name: &'static str,
+    pub driver: &'static dyn VolumeDriver,
+}
+
+inventory::collect!(VolumeDriverRegistration);
+
+/// Compile-time registration of an in-process network driver.
+pub struct NetworkDriverRegistration {
+    pub name: &'static str,
+    pub driver: &'static dyn NetworkDriver,
+}
+
+inventory::collect!(NetworkDriverRegistration);
+
+/// Names of the volume drivers registered at compile time.
+pub fn builtin_volume_drivers() -> Vec<&'static str> {
+    inventory::iter::<VolumeDriverRegistration>()
+        .map(|r| r.name)
+        .collect()
+}
+
+/// Names of the network drivers registered at compile time.
+pub fn builtin_network_drivers() -> Vec<&'static str> {
+    inventory::iter::<NetworkDriverRegistration>()
+        .map(|r| r.name)
+        .collect()
+}
+
+/// Whether a box needs any volume or network driver.
+pub(crate) fn uses_drivers(options: &BoxOptions) -> bool {
+    options.volumes.iter().any(|v| v.driver.is_some())
+        || matches!(options.network, NetworkSpec::Custom(_))
+}
+
+/// Drivers available to a runtime: compile-time registrations plus the
+/// external plugins from its options.
+pub(crate) struct PluginRegistry {
+    external: HashMap<String, ExternalPlugin>,
+}
+
+impl PluginRegistry {
+    pub(crate) fn new(specs: &[PluginSpec]) -> BoxliteResult<Self> {
+        let builtin: Vec<_> = builtin_volume_drivers()
+            .into_iter()
+            .chain(builtin_network_drivers())
+            .collect();
+
+        let mut external = HashMap::new();
+        for spec in specs {
+            if spec.name.is_empty() {
+                return Err(BoxliteError::Config("plugin name must not be empty".into()));
+            }
+            if !spec.socket.is_absolute() {
+                return Err(BoxliteError::Config(format!(
+                    "plugin '{}' socket must be an absolute path, got: {}",
+                    spec.name,
+                    spec.socket.display()
+                )));
+            }
+            if builtin.contains(&spec.name.as_str()) || external.contains_key(&spec.name) {
+                return Err(BoxliteError::Config(format!(
+                    "plugin name '{}' is already registered",
+                    spec.name
+                )));
+            }
+            external.insert(spec.name.clone(), ExternalPlugin::new(spec));
+        }
+        Ok(Self { external })
+    }
+
+    pub(crate) fn volume_driver(&self, name: &str) -> BoxliteResult<&dyn VolumeDriver> {
+        if let Some(registration) =
+            inventory::iter::<VolumeDriverRegistration>().find(|r| r.name == name)
+        {
+            return Ok(registration.driver);
+        }
+        match self.external.get(name) {
+            Some(plugin) => {
+                plugin.require(external::VOLUME_DRIVER)?;
+                Ok(plugin)
+            }
+            None => Err(BoxliteError::NotFound(format!(
+                "volume driver '{}' is not registered",
+                name
+            ))),
+        }
+    }
+
+    pub(crate) fn network_driver(&self, name: &str) -> BoxliteResult<&dyn NetworkDriver> {
+        if let Some(registration) =
+            inventory::iter::<NetworkDriverRegistration>().find(|r| r.name == name)
+        {
+            return Ok(registration.driver);
+        }
+        match self.external.get(name) {
+            Some(plugin) => {
+                plugin.require(external::NETWORK_DRIVER)?;
+                Ok(plugin)
+            }
+            None => Err(BoxliteError::NotFound(format!(
+                "network driver '{}' is not registered",
+                name
+            ))),
+        }
+    }
+
+    /// Mount a box's driver volumes, replacing their names with the host
+    /// directories the drivers returned.
+    ///
+    /// Volumes mounted before a failure are unmounted again.
+    pub(crate) fn mount_volumes(
+        &self,
+        box_id: &str,
+        options: &mut BoxOptions,
+    ) -> BoxliteResult<()> {
+        let mut mounted: Vec<(String, VolumeRequest)> = Vec::new();
+        for volume in options.volumes.iter_mut() {
+            let Some(driver_name) = volume.driver.clone() else {
+                continue;
+            };
+            let request = VolumeRequest {
+                box_id: box_id.to_string(),
+                name: volume.host_path.clone(),
+                read_only: volume.is_read_only(),
+            };
+            let result = self
+                .volume_driver(&driver_name)
+                .and_then(|driver| driver.mount(&request));
+            match result {
+                Ok(path) => {
+                    tracing::debug!(
+                        box_id,
+                        driver = %driver_name,
+                        volume = %request.name,
+                        path = %path.display(),
+                        "Mounted driver volume"
+                    );
+                    volume.host_path = path.to_string_lossy().into_owned();
+                    volume.driver = None;
+                    mounted.push((driver_name, request));
+                }
+                Err(e) => {
+                    for (name, request) in &mounted {
+                        self.unmount_logged(name, request);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Unmount a box's driver volumes and disconnect its driver network.
+    ///
+    /// Failures are logged; the box is already stopped.
+    pub(crate) fn release(&self, box_id: &str, options: &BoxOptions) {
+        for volume in &options.volumes {
+            if let Some(ref driver_name) = volume.driver {
+                let request = VolumeRequest {
+                    box_id: box_id.to_string(),
+                    name: volume.host_path.clone(),
+                    read_only: volume.is_read_only(),
+                };
+                self.unmount_logged(driver_name, &request);
+            }
+        }
+        if let NetworkSpec::Custom(ref driver_name) = options.network {
+            let result = self
+                .network_driver(driver_name)
+                .and_then(|driver| driver.disconnect(box_id));
+            if let Err(e) = result {
+                tracing::warn!(box_id, driver = %driver_name, "Failed to disconnect network: {}", e);
+            }
+        }
+    }
+
+    fn unmount_logged(&self, driver_name: &str, request: &VolumeRequest) {
+        let result = self
+            .volume_driver(driver_name)
+            .and_then(|driver| driver.unmount(request));
+        if let Err(e) = result {
+            tracing::warn!(
+                box_id = %request.box_id,
+                driver = %driver_name,
+                volume = %request.name,
+                "Failed to unmount volume: {}",
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::VolumeSpec;
+    use std::sync::Mutex;
+
+    /// Mounts every volume at `/mnt/<name>` and records calls.
+    struct RecordingDriver {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl VolumeDriver for RecordingDriver {
+        fn mount(&self, request: &VolumeRequest) -> BoxliteResult<PathBuf> {
+            if request.name == "broken" {
+                return Err(BoxliteError::Storage("no such volume".into()));
+            }
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("mount {}", request.name));
+            Ok(PathBuf::from("/mnt").join(&request.name))
+        }
+
+        fn unmount(&self, request: &VolumeRequest) -> BoxliteResult<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("unmount {}", request.name));
+            Ok(())
+        }
+    }
+
+    static RECORDING: RecordingDriver = RecordingDriver {
+        calls: Mutex::new(Vec::new()),
+    };
+
+    inventory::submit! {
+        VolumeDriverRegistration { name: "test-recording", driver: &RECORDING }
+    }
+
+    fn volume(name: &str, driver: Option<&str>) -> VolumeSpec {
+        VolumeSpec {
+            host_path: name.into(),
+            guest_path: format!("/data/{}", name),
+            read_only: false,
+            driver: driver.map(String::from),
+            options: Vec::new(),
+            chown: None,
+        }
+    }
+
+    fn plugin(name: &str, socket: &str) -> PluginSpec {
+        PluginSpec {
+            name: name.into(),
+            socket: socket.into(),
+            timeout_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_registry_rejects_invalid_plugins() {
+        assert!(PluginRegistry::new(&[plugin("san", "/run/san.sock")]).is_ok());
+        for specs in [
+            vec![plugin("san", "san.sock")],
+            vec![plugin("", "/run/san.sock")],
+            vec![plugin("test-recording", "/run/san.sock")],
+            vec![plugin("san", "/run/a.sock"), plugin("san", "/run/b.sock")],
+        ] {
+            assert!(matches!(
+                PluginRegistry::new(&specs),
+                Err(BoxliteError::Config(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_driver() {
+        let registry = PluginRegistry::new(&[]).unwrap();
+        assert!(matches!(
+            registry.volume_driver("nope"),
+            Err(BoxliteError::NotFound(_))
+        ));
+        assert!(matches!(
+            registry.network_driver("test-recording"),
+            Err(BoxliteError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_mount_volumes_rewrites_driver_volumes() {
+        let registry = PluginRegistry::new(&[]).unwrap();
+        let mut options = BoxOptions {
+            volumes: vec![
+                volume("/host/src", None),
+                volume("db", Some("test-recording")),
+            ],
+            ..Default::default()
+        };
+
+        registry.mount_volumes("box-a", &mut options).unwrap();
+        assert_eq!(options.volumes[0].host_path, "/host/src");
+        assert_eq!(options.volumes[1].host_path, "/mnt/db");
+        assert_eq!(options.volumes[1].driver, None);
+
+        let mut options = BoxOptions {
+            volumes: vec![
+                volume("cache", Some("test-recording")),
+                volume("broken", Some("test-recording")),
+            ],
+            ..Default::default()
+        };
+        assert!(registry.mount_volumes("box-b", &mut options).is_err());
+
+        let calls = RECORDING.calls.lock().unwrap();
+        assert!(calls.contains(&"mount db".to_string()));
+        // The volume mounted before the failure was released again
+        assert!(calls.contains(&"unmount cache".to_string()));
+    }
+}
@@ -0,0 +1,141 @@
+//! In-flight portal calls, for diagnosing hangs.
+//!
+//! Every gRPC call to a guest goes through a [`PortalChannel`], which
+//! records the method from the request until the guest answers (the
+//! response headers, for streaming calls). [`in_flight_portal_calls`] lists
+//! what is outstanding across all boxes, e.g. for the C SDK's watchdog.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use tonic::body::BoxBody;
+use tonic::codegen::http;
+use tonic::transport::Channel;
+use tower::Service;
+
+/// Outstanding calls by sequence number, so they list oldest first.
+static IN_FLIGHT: Mutex<BTreeMap<u64, InFlight>> = Mutex::new(BTreeMap::new());
+
+static NEXT_CALL: AtomicU64 = AtomicU64::new(0);
+
+struct InFlight {
+    target: String,
+    method: String,
+    started: Instant,
+}
+
+/// A gRPC call to a guest that hasn't been answered yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortalCall {
+    /// Transport URI of the guest, e.g. `unix:///.../boxes/<id>/sockets/box.sock`
+    pub target: String,
+    /// gRPC path, e.g. `/boxlite.v1.Container/Stats`
+    pub method: String,
+    /// Time since the call was sent
+    pub elapsed: Duration,
+}
+
+/// The portal calls waiting for a guest, oldest first.
+pub fn in_flight_portal_calls() -> Vec<PortalCall> {
+    let calls = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+    calls
+        .values()
+        .map(|call| PortalCall {
+            target: call.target.clone(),
+            method: call.method.clone(),
+            elapsed: call.started.elapsed(),
+        })
+        .collect()
+}
+
+/// Channel to a guest that registers its calls in [`in_flight_portal_calls`].
+#[derive(Clone)]
+pub struct PortalChannel {
+    inner: Channel,
+    target: String,
+}
+
+impl PortalChannel {
+    pub fn new(inner: Channel, target: String) -> Self {
+        Self { inner, target }
+    }
+}
+
+impl Service<http::Request<BoxBody>> for PortalChannel {
+    type Response = http::Response<BoxBody>;
+    type Error = tonic::transport::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        let call = CallGuard::register(&self.target, request.uri().path());
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await;
+            drop(call);
+            response
+        })
+    }
+}
+
+/// Removes its call from the registry when answered or cancelled.
+struct CallGuard(u64);
+
+impl CallGuard {
+    fn register(target: &str, method: &str) -> Self {
+        let id = NEXT_CALL.fetch_add(1, Ordering::Relaxed);
+        IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            id,
+            InFlight {
+                target: target.to_string(),
+                method: method.to_string(),
+                started: Instant::now(),
+            },
+        );
+        Self(id)
+    }
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_guard_tracks_until_dropped() {
+        let target = format!("unix:///test/{}", NEXT_CALL.load(Ordering::Relaxed));
+        let mine = || {
+            in_flight_portal_calls()
+                .into_iter()
+                .filter(|call| call.target == target)
+                .map(|call| call.method)
+                .collect::<Vec<_>>()
+        };
+
+        let first = CallGuard::register(&target, "/boxlite.v1.Guest/Ping");
+        let second = CallGuard::register(&target, "/boxlite.v1.Container/Stats");
+        assert_eq!(
+            mine(),
+            ["/boxlite.v1.Guest/Ping", "/boxlite.v1.Container/Stats"]
+        );
+        drop(first);
+        assert_eq!(mine(), ["/boxlite.v1.Container/Stats"]);
+        drop(second);
+        assert!(mine().is_empty());
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Converts Transport to tonic Channel with lazy initialization.
 
+use super::calls::PortalChannel;
 use boxlite_shared::{BoxliteError, BoxliteResult, Transport};
 use hyper_util::rt::TokioIo;
 use std::sync::Arc;
@@ -28,13 +29,16 @@ impl Connection {
     }
 
     /// Get or establish the channel.
-    pub async fn channel(&self) -> BoxliteResult<Channel> {
+    pub async fn channel(&self) -> BoxliteResult<PortalChannel> {
         let channel = self
             .channel
             .get_or_try_init(|| async { connect_transport(&self.transport).await })
             .await?;
 
-        Ok(channel.clone())
+        Ok(PortalChannel::new(
+            channel.clone(),
+            self.transport.to_string(),
+        ))
     }
 }
 
@@ -1,14 +1,160 @@
 //! Container service interface.
 
+use crate::portal::calls::PortalChannel;
 use boxlite_shared::{
     BindMount, BoxliteError, BoxliteResult, ContainerClient,
-    ContainerConfig as ProtoContainerConfig, ContainerInitRequest, DiskRootfs, MergedRootfs,
-    OverlayRootfs, RootfsInit, container_init_response,
+    ContainerConfig as ProtoContainerConfig, ContainerInitRequest, ContainerResources,
+    ContainerState as ProtoContainerState, ContainerStatsRequest, ContainerStatusRequest,
+    ContainerUpdateHostsRequest, DiskRootfs, ErofsOverlayRootfs, HostEntry, MergedRootfs,
+    OverlayRootfs, RootfsInit, Ulimit as ProtoUlimit, container_init_response,
 };
-use tonic::transport::Channel;
 
+use crate::runtime::constants::agent_reservation;
 use crate::volumes::ContainerMount;
 
+/// Cgroup limits for the container inside the guest.
+///
+/// Derived from the box's `cpus`/`memory_mib` minus the guest agent's
+/// reservation. Only dimensions the user set explicitly are limited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContainerResourceLimits {
+    /// memory.max in bytes
+    pub memory_max_bytes: Option<u64>,
+    /// CPU time allowed per `agent_reservation::CPU_PERIOD_US`, in microseconds
+    pub cpu_quota_us: Option<u64>,
+}
+
+impl ContainerResourceLimits {
+    /// Compute limits from box sizing options.
+    ///
+    /// Small VMs keep at least half of each resource for the container.
+    pub fn from_vm_size(cpus: Option<u8>, memory_mib: Option<u32>) -> Self {
+        let memory_max_bytes = memory_mib.map(|mib| {
+            let container_mib = mib
+                .saturating_sub(agent_reservation::MEMORY_MIB)
+                .max(mib / 2);
+            container_mib as u64 * 1024 * 1024
+        });
+        let cpu_quota_us = cpus.map(|cpus| {
+            let millicores = cpus as u32 * 1000;
+            let container_millicores = millicores
+                .saturating_sub(agent_reservation::CPU_MILLICORES)
+                .max(millicores / 2);
+            container_millicores as u64 * agent_reservation::CPU_PERIOD_US / 1000
+        });
+        Self {
+            memory_max_bytes,
+            cpu_quota_us,
+        }
+    }
+
+    fn into_proto(self) -> Option<ContainerResources> {
+        if self.memory_max_bytes.is_none() && self.cpu_quota_us.is_none() {
+            return None;
+        }
+        Some(ContainerResources {
+            memory_max_bytes: self.memory_max_bytes,
+            cpu_quota_us: self.cpu_quota_us,
+            cpu_period_us: agent_reservation::CPU_PERIOD_US,
+        })
+    }
+}
+
+/// Hostname and extra `/etc/hosts` entries for the container.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContainerHostsConfig {
+    pub hostname: String,
+    /// `(hostname, ip)` pairs
+    pub extra_hosts: Vec<(String, String)>,
+}
+
+impl ContainerHostsConfig {
+    /// Resolve the container hostname from the box options, falling back to
+    /// a hostname derived from the box name.
+    ///
+    /// With `host_loopback` on the built-in network, the container also
+    /// resolves [`HOST_NAME`](crate::net::constants::HOST_NAME) to the host,
+    /// unless an extra host already names it.
+    pub fn for_box(box_name: Option<&str>, options: &crate::runtime::options::BoxOptions) -> Self {
+        use crate::net::constants::{HOST_IP, HOST_NAME};
+        use crate::runtime::options::NetworkSpec;
+
+        let mut extra_hosts = options.extra_hosts.clone();
+        if options.host_loopback
+            && matches!(options.network, NetworkSpec::Isolated)
+            && !extra_hosts
+                .iter()
+                .any(|(hostname, _)| hostname == HOST_NAME)
+        {
+            extra_hosts.push((HOST_NAME.to_string(), HOST_IP.to_string()));
+        }
+        Self {
+            hostname: options
+                .hostname
+                .clone()
+                .unwrap_or_else(|| default_hostname(box_name)),
+            extra_hosts,
+        }
+    }
+}
+
+/// Box name lowercased, with characters not allowed in a hostname replaced
+/// by `-`. Empty (the guest then uses `boxlite`) for unnamed boxes.
+pub(crate) fn default_hostname(box_name: Option<&str>) -> String {
+    let Some(name) = box_name else {
+        return String::new();
+    };
+    let hostname: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .take(63)
+        .collect();
+    hostname.trim_matches('-').to_string()
+}
+
+/// Cgroup counters for a container with resource limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContainerCgroupStats {
+    pub cpu_nr_throttled: u64,
+    pub cpu_throttled_usec: u64,
+    pub memory_max_events: u64,
+    pub memory_oom_kills: u64,
+    pub memory_working_set_bytes: u64,
+    pub memory_cache_bytes: u64,
+    /// None if the pids controller isn't enabled
+    pub pids_current: Option<u64>,
+    /// None if the io controller isn't enabled
+    pub io_read_bytes: Option<u64>,
+    pub io_write_bytes: Option<u64>,
+}
+
+/// Counters returned by `Container.Stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContainerStats {
+    /// `None` if the container runs without resource limits
+    pub cgroup: Option<ContainerCgroupStats>,
+    /// Swap in use in the VM; zero from agents without swap support
+    pub swap_used_bytes: u64,
+    /// Bytes the VM swapped out since it booted
+    pub swap_out_bytes: u64,
+}
+
+/// State of a container as reported by `Container.Status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerState {
+    /// `Container.Init` hasn't started the container yet
+    Pending,
+    Running,
+    /// The init process exited; why, as far as the guest can tell
+    Exited(String),
+}
+
 /// Container rootfs initialization strategy.
 /// Guest constructs paths from container_id using its own layout knowledge.
 #[derive(Debug, Clone)]
@@ -33,6 +179,15 @@ pub enum ContainerRootfsInitConfig {
         /// Whether to resize filesystem after mounting to fill disk
         need_resize: bool,
     },
+    /// Read-only erofs layer devices stacked with overlayfs over a writable upper disk
+    ErofsOverlay {
+        /// Erofs block device paths, bottom layer first (e.g., ["/dev/vdb", "/dev/vdc"])
+        layer_devices: Vec<String>,
+        /// Writable block device for overlayfs upper/work dirs
+        upper_device: String,
+        /// Whether to format the upper device (first boot only)
+        need_format: bool,
+    },
 }
 
 impl ContainerRootfsInitConfig {
@@ -65,18 +220,50 @@ impl ContainerRootfsInitConfig {
                     need_resize,
                 })),
             },
+            ContainerRootfsInitConfig::ErofsOverlay {
+                layer_devices,
+                upper_device,
+                need_format,
+            } => RootfsInit {
+                strategy: Some(boxlite_shared::rootfs_init::Strategy::ErofsOverlay(
+                    ErofsOverlayRootfs {
+                        layer_devices,
+                        upper_device,
+                        need_format,
+                    },
+                )),
+            },
         }
     }
 }
 
+/// Everything the guest needs to create and start the container.
+#[derive(Debug, Clone)]
+pub struct ContainerInitConfig {
+    /// Image-derived container config (entrypoint, env, workdir)
+    pub image_config: crate::images::ContainerImageConfig,
+    /// Rootfs initialization strategy
+    pub rootfs: ContainerRootfsInitConfig,
+    /// Bind mounts from guest VM paths into container
+    pub mounts: Vec<ContainerMount>,
+    /// Cgroup limits (no cgroup is created if none are set)
+    pub resources: ContainerResourceLimits,
+    /// Hostname and extra `/etc/hosts` entries
+    pub hosts: ContainerHostsConfig,
+    /// Size of `/dev/shm` (guest default if `None`)
+    pub shm_size_mib: Option<u32>,
+    /// Resource limits for the container's processes
+    pub ulimits: Vec<crate::runtime::options::Ulimit>,
+}
+
 /// Container service interface.
 pub struct ContainerInterface {
-    client: ContainerClient<Channel>,
+    client: ContainerClient<PortalChannel>,
 }
 
 impl ContainerInterface {
     /// Create from a channel.
-    pub fn new(channel: Channel) -> Self {
+    pub fn new(channel: PortalChannel) -> Self {
         Self {
             client: ContainerClient::new(channel),
         }
@@ -86,24 +273,47 @@ impl ContainerInterface {
     ///
     /// # Arguments
     /// * `container_id` - Container ID (generated by host)
-    /// * `image_config` - Image-derived container config (entrypoint, env, workdir)
-    /// * `rootfs` - Rootfs initialization strategy
-    /// * `mounts` - Bind mounts from guest VM paths into container
+    /// * `config` - Image config, rootfs, mounts and limits of the container
     ///
     /// # Returns
     /// Container ID on success
     pub async fn init(
         &mut self,
         container_id: &str,
-        image_config: crate::images::ContainerImageConfig,
-        rootfs: ContainerRootfsInitConfig,
-        mounts: Vec<ContainerMount>,
+        config: ContainerInitConfig,
     ) -> BoxliteResult<String> {
+        let ContainerInitConfig {
+            image_config,
+            rootfs,
+            mounts,
+            resources,
+            hosts,
+            shm_size_mib,
+            ulimits,
+        } = config;
         let proto_config = ProtoContainerConfig {
             entrypoint: image_config.final_cmd(),
             env: image_config.env.clone(),
             workdir: image_config.working_dir.clone(),
             user: image_config.user.clone(),
+            hostname: hosts.hostname.clone(),
+            extra_hosts: hosts
+                .extra_hosts
+                .iter()
+                .map(|(hostname, ip)| HostEntry {
+                    hostname: hostname.clone(),
+                    ip: ip.clone(),
+                })
+                .collect(),
+            shm_size_mib,
+            ulimits: ulimits
+                .iter()
+                .map(|u| ProtoUlimit {
+                    name: u.name.clone(),
+                    soft: u.soft,
+                    hard: u.hard,
+                })
+                .collect(),
         };
 
         // Convert ContainerMount to proto BindMount
@@ -114,6 +324,8 @@ impl ContainerInterface {
                 volume_name: m.volume_name,
                 destination: m.destination,
                 read_only: m.read_only,
+                options: m.options,
+                map_user: m.map_user,
             })
             .collect();
 
@@ -127,6 +339,10 @@ impl ContainerInterface {
             env_count = image_config.env.len(),
             rootfs = ?rootfs,
             mounts_count = proto_mounts.len(),
+            resources = ?resources,
+            hosts = ?hosts,
+            shm_size_mib = ?shm_size_mib,
+            ulimits = ?ulimits,
             "Container configuration"
         );
 
@@ -135,6 +351,7 @@ impl ContainerInterface {
             container_config: Some(proto_config),
             rootfs: Some(rootfs.into_proto()),
             mounts: proto_mounts,
+            resources: resources.into_proto(),
         };
 
         let response = self.client.init(request).await?.into_inner();
@@ -156,4 +373,145 @@ impl ContainerInterface {
             )),
         }
     }
+
+    /// Get cgroup usage and throttling counters for a container, and the
+    /// VM's swap counters.
+    pub async fn stats(&mut self, container_id: &str) -> BoxliteResult<ContainerStats> {
+        let response = self
+            .client
+            .stats(ContainerStatsRequest {
+                container_id: container_id.to_string(),
+            })
+            .await?
+            .into_inner();
+
+        let cgroup = response.limited.then(|| ContainerCgroupStats {
+            cpu_nr_throttled: response.cpu_nr_throttled,
+            cpu_throttled_usec: response.cpu_throttled_usec,
+            memory_max_events: response.memory_max_events,
+            memory_oom_kills: response.memory_oom_kills,
+            memory_working_set_bytes: response.memory_working_set_bytes,
+            memory_cache_bytes: response.memory_cache_bytes,
+            pids_current: response.pids_current,
+            io_read_bytes: response.io_read_bytes,
+            io_write_bytes: response.io_write_bytes,
+        });
+        Ok(ContainerStats {
+            cgroup,
+            swap_used_bytes: response.swap_used_bytes,
+            swap_out_bytes: response.swap_out_bytes,
+        })
+    }
+
+    /// Get whether a container's init process is up.
+    pub async fn status(&mut self, container_id: &str) -> BoxliteResult<ContainerState> {
+        let response = self
+            .client
+            .status(ContainerStatusRequest {
+                container_id: container_id.to_string(),
+            })
+            .await?
+            .into_inner();
+
+        match response.state() {
+            ProtoContainerState::Running => Ok(ContainerState::Running),
+            ProtoContainerState::Exited => Ok(ContainerState::Exited(response.diagnostics)),
+            ProtoContainerState::Pending | ProtoContainerState::Unspecified => {
+                Ok(ContainerState::Pending)
+            }
+        }
+    }
+
+    /// Replace the network peer names the guest's DNS resolver answers for.
+    ///
+    /// # Arguments
+    /// * `entries` - `(hostname, ip)` pairs, replacing those of the previous update
+    pub async fn update_hosts(
+        &mut self,
+        container_id: &str,
+        entries: &[(String, String)],
+    ) -> BoxliteResult<()> {
+        self.client
+            .update_hosts(ContainerUpdateHostsRequest {
+                container_id: container_id.to_string(),
+                entries: entries
+                    .iter()
+                    .map(|(hostname, ip)| HostEntry {
+                        hostname: hostname.clone(),
+                        ip: ip.clone(),
+                    })
+                    .collect(),
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_limits_unset_when_vm_size_unset() {
+        let limits = ContainerResourceLimits::from_vm_size(None, None);
+        assert_eq!(limits, ContainerResourceLimits::default());
+        assert!(limits.into_proto().is_none());
+    }
+
+    #[test]
+    fn test_limits_subtract_agent_reservation() {
+        let limits = ContainerResourceLimits::from_vm_size(Some(2), Some(1024));
+        assert_eq!(limits.memory_max_bytes, Some(960 * MIB));
+        // 1.9 CPUs of a 100ms period
+        assert_eq!(limits.cpu_quota_us, Some(190_000));
+
+        let proto = limits.into_proto().unwrap();
+        assert_eq!(proto.cpu_period_us, 100_000);
+    }
+
+    #[test]
+    fn test_hostname_defaults_to_box_name() {
+        let options = crate::runtime::options::BoxOptions::default();
+        let hosts = ContainerHostsConfig::for_box(Some("My_Web.App"), &options);
+        assert_eq!(hosts.hostname, "my-web-app");
+        assert_eq!(default_hostname(Some("__")), "");
+        assert_eq!(default_hostname(None), "");
+
+        let options = crate::runtime::options::BoxOptions {
+            hostname: Some("api".to_string()),
+            extra_hosts: vec![("db".to_string(), "10.0.0.5".to_string())],
+            ..Default::default()
+        };
+        let hosts = ContainerHostsConfig::for_box(Some("web"), &options);
+        assert_eq!(hosts.hostname, "api");
+        assert_eq!(
+            hosts.extra_hosts,
+            vec![("db".to_string(), "10.0.0.5".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_host_loopback_adds_host_name() {
+        let options = crate::runtime::options::BoxOptions {
+            host_loopback: true,
+            ..Default::default()
+        };
+        let hosts = ContainerHostsConfig::for_box(None, &options);
+        assert_eq!(
+            hosts.extra_hosts,
+            vec![(
+                "host.boxlite.internal".to_string(),
+                "192.168.127.254".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_limits_keep_half_on_small_vms() {
+        let limits = ContainerResourceLimits::from_vm_size(None, Some(100));
+        assert_eq!(limits.memory_max_bytes, Some(50 * MIB));
+        assert_eq!(limits.cpu_quota_us, None);
+    }
 }
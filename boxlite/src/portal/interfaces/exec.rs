@@ -1,36 +1,92 @@
 //! Execution service interface.
 //!
 //! High-level API for execution operations (unary Exec + output-only Attach +
-//! blocking Wait).
+//! blocking Wait). Executions live in the guest registry, so a detached
+//! execution can be attached to again by ID.
 
-use crate::litebox::{BoxCommand, ExecResult};
+use super::passed_fds::{self, Relay};
+use crate::litebox::{BoxCommand, ExecLimit, ExecResult, ExecutionInfo};
+use crate::portal::calls::PortalChannel;
 use boxlite_shared::{
-    AttachRequest, BoxliteError, BoxliteResult, ExecOutput, ExecRequest, ExecStdin,
-    ExecutionClient, KillRequest, WaitRequest, WaitResponse, exec_output,
+    AttachRequest, BoxliteError, BoxliteResult, ExecError, ExecOutput, ExecRequest, ExecStdin,
+    ExecutionClient, ExecutionSummary, KillRequest, ListExecutionsRequest, WaitRequest,
+    WaitResponse, exec_output,
 };
+use std::sync::{Arc, OnceLock};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
-use tonic::transport::Channel;
 
 /// Execution service interface.
 #[derive(Clone)]
 pub struct ExecutionInterface {
-    client: ExecutionClient<Channel>,
+    client: ExecutionClient<PortalChannel>,
+}
+
+/// Output chunk with its position in the guest's output, counted across
+/// stdout and stderr, so the two streams can be merged back in order.
+pub type SeqChunk = (u64, Vec<u8>);
+
+/// Why an execution's output streams ended before its output did, shared by
+/// the attach task and the streams.
+#[derive(Clone, Debug, Default)]
+pub struct StreamEnd(Arc<OnceLock<String>>);
+
+impl StreamEnd {
+    pub(crate) fn cancel(&self, reason: String) {
+        let _ = self.0.set(reason);
+    }
+
+    /// `Cancelled` if the box stopped or the runtime shut down while the
+    /// output was streamed.
+    pub fn error(&self) -> Option<BoxliteError> {
+        self.0
+            .get()
+            .map(|reason| BoxliteError::Cancelled(reason.clone()))
+    }
 }
 
 /// Components for building an Execution.
 pub struct ExecComponents {
     pub execution_id: String,
-    pub stdin_tx: mpsc::UnboundedSender<Vec<u8>>,
-    pub stdout_rx: mpsc::UnboundedReceiver<String>,
-    pub stderr_rx: mpsc::UnboundedReceiver<String>,
-    pub result_rx: mpsc::UnboundedReceiver<ExecResult>,
+    pub stdin_tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    pub stdout_rx: mpsc::UnboundedReceiver<SeqChunk>,
+    pub stderr_rx: mpsc::UnboundedReceiver<SeqChunk>,
+    /// Set when the output streams were cut short
+    pub stream_end: StreamEnd,
+    /// Exit status, or `Cancelled` if the box stopped first
+    pub result_rx: mpsc::UnboundedReceiver<BoxliteResult<ExecResult>>,
+}
+
+impl ExecComponents {
+    /// Components of an execution that ended without starting.
+    fn finished(execution_id: String, result: ExecResult) -> Self {
+        let (_, stdout_rx) = mpsc::unbounded_channel();
+        let (_, stderr_rx) = mpsc::unbounded_channel();
+        let (result_tx, result_rx) = mpsc::unbounded_channel();
+        let _ = result_tx.send(Ok(result));
+        Self {
+            execution_id,
+            stdin_tx: None,
+            stdout_rx,
+            stderr_rx,
+            stream_end: StreamEnd::default(),
+            result_rx,
+        }
+    }
+}
+
+/// Outcome of an Exec call.
+enum Started {
+    /// The process runs under this execution ID.
+    Running(String),
+    /// The program couldn't be run (exit code 126 or 127).
+    Failed(String, ExecResult),
 }
 
 impl ExecutionInterface {
     /// Create from a channel.
-    pub fn new(channel: Channel) -> Self {
+    pub fn new(channel: PortalChannel) -> Self {
         Self {
             client: ExecutionClient::new(channel),
         }
@@ -46,30 +102,114 @@ impl ExecutionInterface {
         command: BoxCommand,
         shutdown_token: CancellationToken,
     ) -> BoxliteResult<ExecComponents> {
-        // Create channels
+        let relays = passed_fds::validate(&command.fds)?;
+        let execution_id = match self.start_with_fds(&command, &relays).await? {
+            Started::Running(execution_id) => execution_id,
+            Started::Failed(execution_id, result) => {
+                return Ok(ExecComponents::finished(execution_id, result));
+            }
+        };
+
         let (stdin_tx, stdin_rx) = mpsc::unbounded_channel::<Vec<u8>>();
-        let (stdout_tx, stdout_rx) = mpsc::unbounded_channel::<String>();
-        let (stderr_tx, stderr_rx) = mpsc::unbounded_channel::<String>();
-        let (result_tx, result_rx) = mpsc::unbounded_channel();
 
-        // Build request
-        let request = ExecProtocol::build_exec_request(&command);
+        // Spawn stdin pump (no cancellation needed - closes when stdin_tx is dropped)
+        ExecProtocol::spawn_stdin(self.client.clone(), execution_id.clone(), stdin_rx);
+
+        // Relay passed fds; listeners stop accepting once the process exits
+        let exited = shutdown_token.child_token();
+        if !relays.is_empty() {
+            passed_fds::spawn_relays(
+                self.client.clone(),
+                execution_id.clone(),
+                relays,
+                exited.clone(),
+                shutdown_token.clone(),
+            );
+        }
+
+        let components = self.attach_until(execution_id, shutdown_token, Some(exited));
+        Ok(ExecComponents {
+            stdin_tx: Some(stdin_tx),
+            ..components
+        })
+    }
+
+    /// Start a command without attaching to it.
+    ///
+    /// The process keeps running in the guest until it exits or is killed;
+    /// use [`attach`](Self::attach) with the returned ID to follow it.
+    ///
+    /// Passed fds are relayed by the caller that is attached, so commands
+    /// with [`BoxCommand::pass_fd`] can't be started detached.
+    pub async fn start(&mut self, command: &BoxCommand) -> BoxliteResult<String> {
+        if !command.fds.is_empty() {
+            return Err(BoxliteError::Unsupported(
+                "passed fds are relayed while attached; use exec() instead of exec_detached()"
+                    .into(),
+            ));
+        }
+        match self.start_with_fds(command, &[]).await? {
+            Started::Running(execution_id) => Ok(execution_id),
+            Started::Failed(_, result) => Err(BoxliteError::Execution(format!(
+                "{} (exit code {})",
+                result.error_message.unwrap_or_default(),
+                result.exit_code
+            ))),
+        }
+    }
+
+    async fn start_with_fds(
+        &mut self,
+        command: &BoxCommand,
+        relays: &[Relay],
+    ) -> BoxliteResult<Started> {
+        let request = ExecProtocol::build_exec_request(command, relays);
 
         tracing::debug!(?command, "Starting execution");
 
-        // Start execution
         let exec_response = self.client.exec(request).await?.into_inner();
         if let Some(err) = exec_response.error {
+            if let Some(result) = ExecProtocol::map_spawn_failure(&err) {
+                return Ok(Started::Failed(exec_response.execution_id, result));
+            }
+            if err.reason == "workdir_not_found" {
+                return Err(BoxliteError::WorkdirNotFound(format!(
+                    "{} (use BoxCommand::create_workdir to create it)",
+                    err.detail
+                )));
+            }
             return Err(BoxliteError::Internal(format!(
                 "{}: {}",
                 err.reason, err.detail
             )));
         }
 
-        let execution_id = exec_response.execution_id.clone();
+        Ok(Started::Running(exec_response.execution_id))
+    }
 
-        // Spawn stdin pump (no cancellation needed - closes when stdin_tx is dropped)
-        ExecProtocol::spawn_stdin(self.client.clone(), execution_id.clone(), stdin_rx);
+    /// Attach to output and exit status of an existing execution.
+    ///
+    /// The returned components have no stdin: input can only be sent by the
+    /// caller that started the execution.
+    pub fn attach(
+        &mut self,
+        execution_id: String,
+        shutdown_token: CancellationToken,
+    ) -> ExecComponents {
+        self.attach_until(execution_id, shutdown_token, None)
+    }
+
+    /// Attach, cancelling `exited` once the execution's exit status is known.
+    fn attach_until(
+        &mut self,
+        execution_id: String,
+        shutdown_token: CancellationToken,
+        exited: Option<CancellationToken>,
+    ) -> ExecComponents {
+        let (stdout_tx, stdout_rx) = mpsc::unbounded_channel::<SeqChunk>();
+        let (stderr_tx, stderr_rx) = mpsc::unbounded_channel::<SeqChunk>();
+        let (result_tx, result_rx) = mpsc::unbounded_channel();
+        let stream_end = StreamEnd::default();
 
         // Spawn attach fanout (cancellable)
         ExecProtocol::spawn_attach(
@@ -77,6 +217,7 @@ impl ExecutionInterface {
             execution_id.clone(),
             stdout_tx,
             stderr_tx,
+            stream_end.clone(),
             shutdown_token.clone(),
         );
 
@@ -86,28 +227,48 @@ impl ExecutionInterface {
             execution_id.clone(),
             result_tx,
             shutdown_token,
+            exited,
         );
 
-        Ok(ExecComponents {
+        ExecComponents {
             execution_id,
-            stdin_tx,
+            stdin_tx: None,
             stdout_rx,
             stderr_rx,
+            stream_end,
             result_rx,
-        })
+        }
     }
 
     /// Wait for execution to complete.
-    #[allow(dead_code)] // API method for future use
     pub async fn wait(&mut self, execution_id: &str) -> BoxliteResult<ExecResult> {
         let request = WaitRequest {
             execution_id: execution_id.to_string(),
         };
 
-        let response = self.client.wait(request).await?.into_inner();
+        let response = self
+            .client
+            .wait(request)
+            .await
+            .map_err(ExecProtocol::map_status)?
+            .into_inner();
         Ok(ExecProtocol::map_wait_response(response))
     }
 
+    /// List executions known to the guest, oldest first.
+    pub async fn list(&mut self) -> BoxliteResult<Vec<ExecutionInfo>> {
+        let response = self
+            .client
+            .list(ListExecutionsRequest {})
+            .await?
+            .into_inner();
+        Ok(response
+            .executions
+            .into_iter()
+            .map(ExecProtocol::map_summary)
+            .collect())
+    }
+
     /// Kill execution (send signal).
     pub async fn kill(&mut self, execution_id: &str, signal: i32) -> BoxliteResult<()> {
         let request = KillRequest {
@@ -115,7 +276,12 @@ impl ExecutionInterface {
             signal,
         };
 
-        let response = self.client.kill(request).await?.into_inner();
+        let response = self
+            .client
+            .kill(request)
+            .await
+            .map_err(ExecProtocol::map_status)?
+            .into_inner();
 
         if response.success {
             Ok(())
@@ -166,11 +332,11 @@ impl ExecutionInterface {
 struct ExecProtocol;
 
 impl ExecProtocol {
-    fn build_exec_request(command: &BoxCommand) -> ExecRequest {
-        use boxlite_shared::TtyConfig;
+    fn build_exec_request(command: &BoxCommand, relays: &[Relay]) -> ExecRequest {
+        use boxlite_shared::{ExecLimits, OutputLogConfig, TtyConfig};
 
         ExecRequest {
-            execution_id: None,
+            execution_id: command.execution_id.clone(),
             program: command.command.clone(),
             args: command.args.clone(),
             env: command
@@ -180,6 +346,7 @@ impl ExecProtocol {
                 .into_iter()
                 .collect(),
             workdir: command.working_dir.clone().unwrap_or_default(),
+            create_workdir: command.create_workdir,
             timeout_ms: command.timeout.map(|d| d.as_millis() as u64).unwrap_or(0),
             tty: if command.tty {
                 let (rows, cols) = crate::util::get_terminal_size();
@@ -192,10 +359,75 @@ impl ExecProtocol {
             } else {
                 None
             },
+            output_log: command.output_log.map(|options| OutputLogConfig {
+                max_file_bytes: options.max_file_bytes,
+                max_files: options.max_files,
+            }),
+            limits: command.limits.map(|limits| ExecLimits {
+                cpu_secs: limits.cpu_secs,
+                wall_secs: limits.wall_secs,
+                max_output_bytes: limits.max_output_bytes,
+            }),
+            fds: relays.iter().map(Relay::to_proto).collect(),
+            retention_secs: command.retention_secs,
+        }
+    }
+
+    /// Error of an execution whose box stopped before it exited.
+    fn cancelled(execution_id: &str) -> BoxliteError {
+        BoxliteError::Cancelled(format!(
+            "execution {} was not waited for: the box stopped or the runtime shut down",
+            execution_id
+        ))
+    }
+
+    /// Report an unknown execution ID as `NotFound`.
+    fn map_status(status: tonic::Status) -> BoxliteError {
+        match status.code() {
+            tonic::Code::NotFound => BoxliteError::NotFound(status.message().to_string()),
+            _ => status.into(),
+        }
+    }
+
+    fn map_summary(summary: ExecutionSummary) -> ExecutionInfo {
+        let time = |ms: u64| chrono::DateTime::from_timestamp_millis(ms as i64).unwrap_or_default();
+        let exit_code = match (summary.running, summary.signal) {
+            (true, _) => None,
+            (false, 0) => Some(summary.exit_code),
+            (false, signal) => Some(-signal),
+        };
+        ExecutionInfo {
+            id: summary.execution_id,
+            program: summary.program,
+            args: summary.args,
+            pid: summary.pid,
+            started_at: time(summary.started_at_ms),
+            exit_code,
+            exited_at: (!summary.running).then(|| time(summary.exited_at_ms)),
         }
     }
 
+    /// Exit status for a program the guest couldn't run, as shells report it.
+    fn map_spawn_failure(err: &ExecError) -> Option<ExecResult> {
+        let exit_code = match err.reason.as_str() {
+            "program_not_found" => 127,
+            "program_not_executable" => 126,
+            _ => return None,
+        };
+        Some(ExecResult {
+            exit_code,
+            error_message: Some(err.detail.clone()),
+            limit_exceeded: None,
+        })
+    }
+
     fn map_wait_response(resp: WaitResponse) -> ExecResult {
+        let limit_exceeded = match resp.limit_exceeded() {
+            boxlite_shared::ExecLimit::None => None,
+            boxlite_shared::ExecLimit::Cpu => Some(ExecLimit::Cpu),
+            boxlite_shared::ExecLimit::Wall => Some(ExecLimit::Wall),
+            boxlite_shared::ExecLimit::Output => Some(ExecLimit::Output),
+        };
         let code = if resp.signal != 0 {
             -resp.signal
         } else {
@@ -209,17 +441,27 @@ impl ExecProtocol {
         ExecResult {
             exit_code: code,
             error_message,
+            limit_exceeded,
         }
     }
 
     fn spawn_attach(
-        mut client: ExecutionClient<Channel>,
+        mut client: ExecutionClient<PortalChannel>,
         execution_id: String,
-        stdout_tx: mpsc::UnboundedSender<String>,
-        stderr_tx: mpsc::UnboundedSender<String>,
+        stdout_tx: mpsc::UnboundedSender<SeqChunk>,
+        stderr_tx: mpsc::UnboundedSender<SeqChunk>,
+        stream_end: StreamEnd,
         shutdown_token: CancellationToken,
     ) {
         tokio::spawn(async move {
+            let mut seq = 0u64;
+            // Set before the senders drop, so streams that end see it
+            let cancel = || {
+                stream_end.cancel(format!(
+                    "output of execution {} was cut short: the box stopped or the runtime shut down",
+                    execution_id
+                ))
+            };
             let request = AttachRequest {
                 execution_id: execution_id.clone(),
             };
@@ -229,6 +471,7 @@ impl ExecProtocol {
                 biased;
                 _ = shutdown_token.cancelled() => {
                     tracing::debug!(execution_id = %execution_id, "Attach cancelled during connect");
+                    cancel();
                     return;
                 }
                 result = client.attach(request) => result,
@@ -250,6 +493,7 @@ impl ExecProtocol {
                                     message_count,
                                     "Attach stream cancelled during shutdown"
                                 );
+                                cancel();
                                 break;
                             }
                             msg = stream.message() => msg,
@@ -258,7 +502,7 @@ impl ExecProtocol {
                         match output.transpose() {
                             Some(Ok(output)) => {
                                 message_count += 1;
-                                Self::route_output(output, &stdout_tx, &stderr_tx);
+                                Self::route_output(output, &mut seq, &stdout_tx, &stderr_tx);
                             }
                             Some(Err(e)) => {
                                 tracing::debug!(
@@ -267,7 +511,8 @@ impl ExecProtocol {
                                     message_count,
                                     "Attach stream error, breaking"
                                 );
-                                let _ = stderr_tx.send(format!("Attach stream error: {}", e));
+                                let message = format!("Attach stream error: {}", e);
+                                let _ = stderr_tx.send((seq, message.into_bytes()));
                                 break;
                             }
                             None => {
@@ -285,7 +530,7 @@ impl ExecProtocol {
                 }
                 Err(e) => {
                     tracing::debug!(execution_id = %execution_id, error = %e, "Attach failed");
-                    let _ = stderr_tx.send(format!("Attach failed: {}", e));
+                    let _ = stderr_tx.send((seq, format!("Attach failed: {}", e).into_bytes()));
                 }
             }
         });
@@ -293,31 +538,33 @@ impl ExecProtocol {
 
     fn route_output(
         output: ExecOutput,
-        stdout_tx: &mpsc::UnboundedSender<String>,
-        stderr_tx: &mpsc::UnboundedSender<String>,
+        seq: &mut u64,
+        stdout_tx: &mpsc::UnboundedSender<SeqChunk>,
+        stderr_tx: &mpsc::UnboundedSender<SeqChunk>,
     ) {
         match output.event {
             Some(exec_output::Event::Stdout(chunk)) => {
-                let stdout_data = String::from_utf8_lossy(&chunk.data).to_string();
-                tracing::trace!(?stdout_data, "Received exec stdout");
-                let _ = stdout_tx.send(stdout_data);
+                tracing::trace!(stdout_data = ?String::from_utf8_lossy(&chunk.data), "Received exec stdout");
+                let _ = stdout_tx.send((*seq, chunk.data));
             }
             Some(exec_output::Event::Stderr(chunk)) => {
-                let stderr_data = String::from_utf8_lossy(&chunk.data).to_string();
-                tracing::trace!(?stderr_data, "Received exec stderr");
-                let _ = stderr_tx.send(stderr_data);
+                tracing::trace!(stderr_data = ?String::from_utf8_lossy(&chunk.data), "Received exec stderr");
+                let _ = stderr_tx.send((*seq, chunk.data));
             }
-            None => {}
+            None => return,
         }
+        *seq += 1;
     }
 
     fn spawn_wait(
-        mut client: ExecutionClient<Channel>,
+        mut client: ExecutionClient<PortalChannel>,
         execution_id: String,
-        result_tx: mpsc::UnboundedSender<ExecResult>,
+        result_tx: mpsc::UnboundedSender<BoxliteResult<ExecResult>>,
         shutdown_token: CancellationToken,
+        exited: Option<CancellationToken>,
     ) {
         tokio::spawn(async move {
+            let _exited = exited.map(CancellationToken::drop_guard);
             let request = WaitRequest {
                 execution_id: execution_id.clone(),
             };
@@ -327,9 +574,7 @@ impl ExecProtocol {
                 biased;
                 _ = shutdown_token.cancelled() => {
                     tracing::debug!(execution_id = %execution_id, "Wait cancelled during shutdown");
-                    // Send a special result indicating cancellation
-                    // Using exit code -1 to indicate abnormal termination
-                    let _ = result_tx.send(ExecResult { exit_code: -1, error_message: None });
+                    let _ = result_tx.send(Err(Self::cancelled(&execution_id)));
                     return;
                 }
                 result = client.wait(request) => result,
@@ -338,7 +583,7 @@ impl ExecProtocol {
             match result {
                 Ok(resp) => {
                     let mapped = Self::map_wait_response(resp.into_inner());
-                    let _ = result_tx.send(mapped);
+                    let _ = result_tx.send(Ok(mapped));
                 }
                 Err(e) => {
                     tracing::error!(
@@ -346,17 +591,14 @@ impl ExecProtocol {
                         error = %e,
                         "Wait failed"
                     );
-                    let _ = result_tx.send(ExecResult {
-                        exit_code: -1,
-                        error_message: None,
-                    });
+                    let _ = result_tx.send(Err(Self::map_status(e)));
                 }
             }
         });
     }
 
     fn spawn_stdin(
-        mut client: ExecutionClient<Channel>,
+        mut client: ExecutionClient<PortalChannel>,
         execution_id: String,
         mut stdin_rx: mpsc::UnboundedReceiver<Vec<u8>>,
     ) {
@@ -408,6 +650,30 @@ mod tests {
     use super::*;
     use std::time::Duration;
 
+    #[test]
+    fn test_map_summary() {
+        let running = ExecProtocol::map_summary(ExecutionSummary {
+            execution_id: "exec-1".into(),
+            program: "sleep".into(),
+            args: vec!["60".into()],
+            pid: 42,
+            started_at_ms: 1_700_000_000_000,
+            running: true,
+            ..Default::default()
+        });
+        assert!(running.is_running());
+        assert_eq!(running.exited_at, None);
+        assert_eq!(running.started_at.timestamp(), 1_700_000_000);
+
+        let killed = ExecProtocol::map_summary(ExecutionSummary {
+            signal: 9,
+            exited_at_ms: 1_700_000_001_000,
+            ..Default::default()
+        });
+        assert_eq!(killed.exit_code, Some(-9));
+        assert_eq!(killed.exited_at.unwrap().timestamp(), 1_700_000_001);
+    }
+
     /// Test that CancellationToken correctly signals cancelled state.
     #[tokio::test]
     async fn test_cancellation_token_basic() {
@@ -525,7 +791,7 @@ mod tests {
     }
 
     /// Test simulating spawn_wait cancellation behavior.
-    /// When token is cancelled, the result channel should receive exit_code -1.
+    /// When token is cancelled, the result channel should receive `Cancelled`.
     #[tokio::test]
     async fn test_spawn_wait_cancellation_sends_result() {
         let token = CancellationToken::new();
@@ -537,7 +803,7 @@ mod tests {
             tokio::select! {
                 biased;
                 _ = token_clone.cancelled() => {
-                    let _ = result_tx.send(ExecResult { exit_code: -1, error_message: None });
+                    let _ = result_tx.send(Err(ExecProtocol::cancelled("exec-1")));
                 }
                 _ = tokio::time::sleep(Duration::from_secs(3600)) => {
                     // Would normally wait for gRPC response
@@ -553,9 +819,8 @@ mod tests {
         handle.await.unwrap();
 
         // Should have received cancellation result
-        let result = result_rx.recv().await;
-        assert!(result.is_some());
-        assert_eq!(result.unwrap().exit_code, -1);
+        let result: BoxliteResult<ExecResult> = result_rx.recv().await.unwrap();
+        assert!(matches!(result, Err(BoxliteError::Cancelled(ref msg)) if msg.contains("exec-1")));
     }
 
     /// Test simulating spawn_attach cancellation behavior.
@@ -563,8 +828,8 @@ mod tests {
     #[tokio::test]
     async fn test_spawn_attach_cancellation_exits() {
         let token = CancellationToken::new();
-        let (stdout_tx, _stdout_rx) = mpsc::unbounded_channel::<String>();
-        let (_stderr_tx, _stderr_rx) = mpsc::unbounded_channel::<String>();
+        let (stdout_tx, _stdout_rx) = mpsc::unbounded_channel::<SeqChunk>();
+        let (_stderr_tx, _stderr_rx) = mpsc::unbounded_channel::<SeqChunk>();
 
         // Simulate spawn_attach's cancellation handling in streaming loop
         let token_clone = token.clone();
@@ -578,7 +843,7 @@ mod tests {
                     }
                     _ = tokio::time::sleep(Duration::from_millis(10)) => {
                         // Simulate receiving output
-                        let _ = stdout_tx.send("output".to_string());
+                        let _ = stdout_tx.send((iterations, b"output".to_vec()));
                         iterations += 1;
                     }
                 }
@@ -653,4 +918,51 @@ mod tests {
         assert_eq!(result1.unwrap(), Some("cancelled"));
         assert_eq!(result2.unwrap(), Some("cancelled"));
     }
+
+    #[test]
+    fn test_spawn_failure_exit_codes() {
+        let failure = |reason: &str| {
+            ExecProtocol::map_spawn_failure(&ExecError {
+                reason: reason.to_string(),
+                detail: "foo: command not found".to_string(),
+            })
+        };
+        let result = failure("program_not_found").unwrap();
+        assert_eq!(result.exit_code, 127);
+        assert_eq!(
+            result.error_message.as_deref(),
+            Some("foo: command not found")
+        );
+        assert_eq!(failure("program_not_executable").unwrap().exit_code, 126);
+        assert!(failure("spawn_failed").is_none());
+    }
+
+    #[test]
+    fn test_exec_limits_round_trip() {
+        let command = BoxCommand::new("python3").limits(2, 10, 4096);
+        let limits = ExecProtocol::build_exec_request(&command, &[])
+            .limits
+            .unwrap();
+        assert_eq!(
+            (limits.cpu_secs, limits.wall_secs, limits.max_output_bytes),
+            (2, 10, 4096)
+        );
+        assert!(
+            ExecProtocol::build_exec_request(&BoxCommand::new("ls"), &[])
+                .limits
+                .is_none()
+        );
+
+        let result = ExecProtocol::map_wait_response(WaitResponse {
+            signal: 9,
+            limit_exceeded: boxlite_shared::ExecLimit::Output as i32,
+            ..Default::default()
+        });
+        assert_eq!(result.exit_code, -9);
+        assert_eq!(result.limit_exceeded, Some(ExecLimit::Output));
+        assert_eq!(
+            ExecProtocol::map_wait_response(WaitResponse::default()).limit_exceeded,
+            None
+        );
+    }
 }
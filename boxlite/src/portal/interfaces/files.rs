@@ -1,22 +1,26 @@
 //! Files service interface.
 //!
-//! Provides tar-based upload/download to the guest container rootfs.
-
-use boxlite_shared::{BoxliteError, BoxliteResult, DownloadRequest, FilesClient, UploadChunk};
+//! Provides tar-based upload/download to the guest container rootfs, and
+//! the listing and removal used by workspace sync.
+
+use crate::portal::calls::PortalChannel;
+use boxlite_shared::{
+    BoxliteError, BoxliteResult, DownloadRequest, FilesClient, ListTreeRequest, RemoveRequest,
+    TreeEntry, UploadChunk,
+};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tonic::transport::Channel;
 
 const CHUNK_SIZE: usize = 1 << 20; // 1 MiB
 
 /// Files service interface.
 pub struct FilesInterface {
-    client: FilesClient<Channel>,
+    client: FilesClient<PortalChannel>,
 }
 
 impl FilesInterface {
     /// Create from a channel.
-    pub fn new(channel: Channel) -> Self {
+    pub fn new(channel: PortalChannel) -> Self {
         Self {
             client: FilesClient::new(channel),
         }
@@ -132,6 +136,46 @@ impl FilesInterface {
 
         Ok(())
     }
+
+    /// List the files and symlinks under a guest directory, without the
+    /// mounts under it.
+    pub async fn list_tree(
+        &mut self,
+        path: &str,
+        container_id: Option<&str>,
+    ) -> BoxliteResult<Vec<TreeEntry>> {
+        let request = ListTreeRequest {
+            path: path.to_string(),
+            container_id: container_id.unwrap_or_default().to_string(),
+        };
+        let mut stream = self
+            .client
+            .list_tree(request)
+            .await
+            .map_err(map_tonic_err)?
+            .into_inner();
+        let mut entries = Vec::new();
+        while let Some(response) = stream.message().await.map_err(map_tonic_err)? {
+            entries.extend(response.entries);
+        }
+        Ok(entries)
+    }
+
+    /// Remove files and symlinks, given relative to a guest directory.
+    pub async fn remove(
+        &mut self,
+        path: &str,
+        container_id: Option<&str>,
+        paths: Vec<String>,
+    ) -> BoxliteResult<()> {
+        let request = RemoveRequest {
+            path: path.to_string(),
+            container_id: container_id.unwrap_or_default().to_string(),
+            paths,
+        };
+        self.client.remove(request).await.map_err(map_tonic_err)?;
+        Ok(())
+    }
 }
 
 fn map_tonic_err(err: tonic::Status) -> BoxliteError {
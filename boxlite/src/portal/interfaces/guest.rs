@@ -1,19 +1,23 @@
 //! Guest service interface.
 
+use crate::portal::calls::PortalChannel;
 use boxlite_shared::{
     BlockDeviceSource, BoxliteError, BoxliteResult, Filesystem, GuestClient, GuestInitRequest,
-    NetworkInit, PingRequest, ShutdownRequest, VirtiofsSource, Volume, guest_init_response,
+    GuestLogsRequest, NetConnection, NetConnectionsRequest, NetworkInit, PingRequest,
+    ShutdownRequest, SwapInit, VirtiofsSource, Volume, guest_init_response,
 };
-use tonic::transport::Channel;
+use std::collections::HashMap;
+
+use crate::litebox::GuestAgentInfo;
 
 /// Guest service interface.
 pub struct GuestInterface {
-    client: GuestClient<Channel>,
+    client: GuestClient<PortalChannel>,
 }
 
 impl GuestInterface {
     /// Create from a channel.
-    pub fn new(channel: Channel) -> Self {
+    pub fn new(channel: PortalChannel) -> Self {
         Self {
             client: GuestClient::new(channel),
         }
@@ -22,12 +26,14 @@ impl GuestInterface {
     /// Initialize guest environment.
     ///
     /// This must be called first after connection, before Container.Init.
-    /// Sets up volumes (virtiofs + block devices) and network.
+    /// Sets up volumes (virtiofs + block devices), network, sysctls and swap.
     pub async fn init(&mut self, config: GuestInitConfig) -> BoxliteResult<()> {
         tracing::debug!("Sending GuestInit request");
         tracing::trace!(
             volumes = config.volumes.len(),
             network = ?config.network,
+            sysctls = ?config.sysctls,
+            swap = ?config.swap,
             "Guest init configuration"
         );
 
@@ -38,6 +44,11 @@ impl GuestInterface {
                 ip: n.ip,
                 gateway: n.gateway,
             }),
+            sysctls: config.sysctls,
+            swap: config.swap.map(|s| SwapInit {
+                size_bytes: s.size_bytes,
+                zram: s.zram,
+            }),
         };
 
         let response = self.client.init(request).await?.into_inner();
@@ -60,11 +71,13 @@ impl GuestInterface {
         }
     }
 
-    /// Ping the guest (health check).
-    #[allow(dead_code)] // API method for future health checks
-    pub async fn ping(&mut self) -> BoxliteResult<()> {
-        let _response = self.client.ping(PingRequest {}).await?;
-        Ok(())
+    /// Ping the guest (heartbeat); returns the agent's version and capabilities.
+    pub async fn ping(&mut self) -> BoxliteResult<GuestAgentInfo> {
+        let response = self.client.ping(PingRequest {}).await?.into_inner();
+        Ok(GuestAgentInfo::from_ping(
+            response.version,
+            response.capabilities,
+        ))
     }
 
     /// Shutdown the guest agent.
@@ -72,6 +85,21 @@ impl GuestInterface {
         let _response = self.client.shutdown(ShutdownRequest {}).await?;
         Ok(())
     }
+
+    /// Fetch the guest agent's most recent log lines (all buffered if `tail` is 0).
+    pub async fn logs(&mut self, tail: u32) -> BoxliteResult<Vec<String>> {
+        let response = self.client.logs(GuestLogsRequest { tail }).await?;
+        Ok(response.into_inner().lines)
+    }
+
+    /// List active TCP/UDP sockets in the guest network namespace.
+    pub async fn net_connections(&mut self) -> BoxliteResult<Vec<NetConnection>> {
+        let response = self
+            .client
+            .net_connections(NetConnectionsRequest {})
+            .await?;
+        Ok(response.into_inner().connections)
+    }
 }
 
 /// Configuration for guest initialization.
@@ -81,6 +109,18 @@ pub struct GuestInitConfig {
     pub volumes: Vec<VolumeConfig>,
     /// Network configuration (optional)
     pub network: Option<NetworkInitConfig>,
+    /// Kernel parameters to set, keyed by dotted name
+    pub sysctls: HashMap<String, String>,
+    /// Swap to enable (optional)
+    pub swap: Option<SwapInitConfig>,
+}
+
+/// Swap configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct SwapInitConfig {
+    pub size_bytes: u64,
+    /// zram instead of a swap file
+    pub zram: bool,
 }
 
 /// Volume configuration.
@@ -6,8 +6,12 @@ pub mod container;
 pub mod exec;
 pub mod files;
 pub mod guest;
+mod passed_fds;
 
-pub use container::{ContainerInterface, ContainerRootfsInitConfig};
-pub use exec::ExecutionInterface;
+pub use container::{
+    ContainerHostsConfig, ContainerInitConfig, ContainerInterface, ContainerResourceLimits,
+    ContainerRootfsInitConfig, ContainerState,
+};
+pub use exec::{ExecutionInterface, SeqChunk, StreamEnd};
 pub use files::FilesInterface;
-pub use guest::{GuestInitConfig, GuestInterface, NetworkInitConfig, VolumeConfig};
+pub use guest::{GuestInitConfig, GuestInterface, NetworkInitConfig, SwapInitConfig, VolumeConfig};
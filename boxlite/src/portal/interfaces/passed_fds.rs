@@ -0,0 +1,520 @@
+//! Relaying host fds passed to an execution ([`BoxCommand::pass_fd`]).
+//!
+//! The guest gives the process a stand-in for each fd (see `ExecRequest.fds`)
+//! and every relay is an `Execution.RelayFd` call. The host fds may be
+//! blocking pipes or sockets owned by the caller, so their data is moved by
+//! threads that poll them, checking for cancellation between polls.
+//!
+//! [`BoxCommand::pass_fd`]: crate::BoxCommand::pass_fd
+
+use crate::litebox::HostFd;
+use crate::portal::calls::PortalChannel;
+use boxlite_shared::{
+    BoxliteError, BoxliteResult, ExecutionClient, FdChunk, PassedFd, PassedFdKind,
+};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+
+/// Bytes read from a host fd per relayed chunk.
+const RELAY_CHUNK_SIZE: usize = 32 * 1024;
+
+/// How often a reader waiting for data checks whether it should stop.
+const POLL_INTERVAL_MS: i32 = 200;
+
+/// A validated passed fd.
+#[derive(Debug)]
+pub(super) struct Relay {
+    fd: OwnedFd,
+    guest_fd: u32,
+    kind: RelayKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RelayKind {
+    Stream { readable: bool, writable: bool },
+    Listener,
+}
+
+/// Check that `fds` can be passed and duplicate them for relaying.
+pub(super) fn validate(fds: &[HostFd]) -> BoxliteResult<Vec<Relay>> {
+    let mut seen = HashSet::new();
+    fds.iter()
+        .map(|host_fd| {
+            if host_fd.guest_fd < 3 {
+                return Err(BoxliteError::InvalidArgument(format!(
+                    "cannot pass fd as {}: stdin, stdout and stderr are set up by exec",
+                    host_fd.guest_fd
+                )));
+            }
+            if !seen.insert(host_fd.guest_fd) {
+                return Err(BoxliteError::InvalidArgument(format!(
+                    "fd {} is passed more than once",
+                    host_fd.guest_fd
+                )));
+            }
+            let kind = relay_kind(&host_fd.fd, host_fd.guest_fd)?;
+            let fd = host_fd.fd.try_clone().map_err(|e| {
+                BoxliteError::Internal(format!("Failed to duplicate passed fd: {}", e))
+            })?;
+            Ok(Relay {
+                fd,
+                guest_fd: host_fd.guest_fd,
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn relay_kind(fd: &OwnedFd, guest_fd: u32) -> BoxliteResult<RelayKind> {
+    let raw = fd.as_raw_fd();
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(raw, &mut stat) } != 0 {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "fd passed as {} is not open: {}",
+            guest_fd,
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    match stat.st_mode & libc::S_IFMT {
+        libc::S_IFIFO => {
+            let flags = unsafe { libc::fcntl(raw, libc::F_GETFL) };
+            let access = flags & libc::O_ACCMODE;
+            Ok(RelayKind::Stream {
+                readable: access != libc::O_WRONLY,
+                writable: access != libc::O_RDONLY,
+            })
+        }
+        libc::S_IFSOCK => {
+            if socket_option(raw, libc::SO_TYPE) != Some(libc::SOCK_STREAM) {
+                return Err(BoxliteError::Unsupported(format!(
+                    "fd passed as {} is not a stream socket; only stream sockets can be relayed",
+                    guest_fd
+                )));
+            }
+            if socket_option(raw, libc::SO_ACCEPTCONN) == Some(1) {
+                Ok(RelayKind::Listener)
+            } else {
+                Ok(RelayKind::Stream {
+                    readable: true,
+                    writable: true,
+                })
+            }
+        }
+        _ => Err(BoxliteError::Unsupported(format!(
+            "fd passed as {} is not a pipe or socket; copy files into the box instead",
+            guest_fd
+        ))),
+    }
+}
+
+fn socket_option(fd: RawFd, option: libc::c_int) -> Option<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            option,
+            &mut value as *mut libc::c_int as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (ret == 0).then_some(value)
+}
+
+impl Relay {
+    /// The fd as described to the guest.
+    pub(super) fn to_proto(&self) -> PassedFd {
+        let kind = match self.kind {
+            RelayKind::Stream { .. } => PassedFdKind::Stream,
+            RelayKind::Listener => PassedFdKind::Listener,
+        };
+        PassedFd {
+            fd: self.guest_fd,
+            kind: kind as i32,
+        }
+    }
+}
+
+/// Start relaying the passed fds of a started execution.
+///
+/// Stream relays run until both sides are closed or `shutdown_token` is
+/// cancelled; listeners stop accepting when `exited` is cancelled.
+pub(super) fn spawn_relays(
+    client: ExecutionClient<PortalChannel>,
+    execution_id: String,
+    relays: Vec<Relay>,
+    exited: CancellationToken,
+    shutdown_token: CancellationToken,
+) {
+    for relay in relays {
+        let client = client.clone();
+        let execution_id = execution_id.clone();
+        match relay.kind {
+            RelayKind::Stream { readable, writable } => {
+                tokio::spawn(relay_stream(
+                    client,
+                    execution_id,
+                    File::from(relay.fd),
+                    relay.guest_fd,
+                    (readable, writable),
+                    shutdown_token.clone(),
+                ));
+            }
+            RelayKind::Listener => {
+                tokio::spawn(relay_listener(
+                    client,
+                    execution_id,
+                    relay.fd,
+                    relay.guest_fd,
+                    exited.clone(),
+                    shutdown_token.clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// Relay a stream fd over one `RelayFd` call.
+async fn relay_stream(
+    mut client: ExecutionClient<PortalChannel>,
+    execution_id: String,
+    file: File,
+    guest_fd: u32,
+    (readable, writable): (bool, bool),
+    shutdown_token: CancellationToken,
+) {
+    let done = shutdown_token.child_token();
+    let _stop_reader = done.clone().drop_guard();
+
+    let (tx, rx) = mpsc::channel::<FdChunk>(8);
+    let _ = tx
+        .send(FdChunk {
+            execution_id: execution_id.clone(),
+            fd: guest_fd,
+            ..Default::default()
+        })
+        .await;
+    if readable {
+        match file.try_clone() {
+            Ok(reader) => spawn_reader(reader, tx, done.clone()),
+            Err(e) => {
+                tracing::warn!(
+                    execution_id = %execution_id,
+                    fd = guest_fd,
+                    error = %e,
+                    "Failed to relay passed fd"
+                );
+                return;
+            }
+        }
+    } else {
+        let _ = tx.send(close_chunk()).await;
+        drop(tx);
+    }
+
+    let response = tokio::select! {
+        _ = done.cancelled() => return,
+        response = client.relay_fd(ReceiverStream::new(rx)) => response,
+    };
+    let mut inbound = match response {
+        Ok(response) => response.into_inner(),
+        Err(e) => {
+            tracing::warn!(
+                execution_id = %execution_id,
+                fd = guest_fd,
+                error = %e,
+                "RelayFd failed"
+            );
+            return;
+        }
+    };
+
+    let writer = writable.then(|| spawn_writer(file));
+    loop {
+        let chunk = tokio::select! {
+            _ = done.cancelled() => break,
+            chunk = inbound.message() => chunk,
+        };
+        match chunk {
+            Ok(Some(chunk)) => {
+                if let Some(writer) = &writer
+                    && !chunk.data.is_empty()
+                {
+                    let _ = writer.send(Some(chunk.data));
+                }
+                if chunk.close {
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                tracing::debug!(
+                    execution_id = %execution_id,
+                    fd = guest_fd,
+                    error = %e,
+                    "RelayFd stream error"
+                );
+                break;
+            }
+        }
+    }
+    if let Some(writer) = writer {
+        let _ = writer.send(None);
+    }
+}
+
+/// Relay each connection accepted on a listening fd.
+async fn relay_listener(
+    client: ExecutionClient<PortalChannel>,
+    execution_id: String,
+    fd: OwnedFd,
+    guest_fd: u32,
+    exited: CancellationToken,
+    shutdown_token: CancellationToken,
+) {
+    let listener = match Listener::from_fd(fd) {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::warn!(
+                execution_id = %execution_id,
+                fd = guest_fd,
+                error = %e,
+                "Failed to relay passed listener"
+            );
+            return;
+        }
+    };
+
+    loop {
+        let accepted = tokio::select! {
+            _ = exited.cancelled() => return,
+            accepted = listener.accept() => accepted,
+        };
+        match accepted {
+            Ok(conn) => {
+                tokio::spawn(relay_stream(
+                    client.clone(),
+                    execution_id.clone(),
+                    File::from(conn),
+                    guest_fd,
+                    (true, true),
+                    shutdown_token.clone(),
+                ));
+            }
+            Err(e) => {
+                tracing::warn!(
+                    execution_id = %execution_id,
+                    fd = guest_fd,
+                    error = %e,
+                    "Accept on passed listener failed"
+                );
+                return;
+            }
+        }
+    }
+}
+
+enum Listener {
+    Tcp(tokio::net::TcpListener),
+    Unix(tokio::net::UnixListener),
+}
+
+impl Listener {
+    fn from_fd(fd: OwnedFd) -> std::io::Result<Self> {
+        let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        if unsafe {
+            libc::getsockname(
+                fd.as_raw_fd(),
+                &mut addr as *mut libc::sockaddr_storage as *mut libc::sockaddr,
+                &mut len,
+            )
+        } != 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        if addr.ss_family as libc::c_int == libc::AF_UNIX {
+            let listener = std::os::unix::net::UnixListener::from(fd);
+            listener.set_nonblocking(true)?;
+            Ok(Listener::Unix(tokio::net::UnixListener::from_std(
+                listener,
+            )?))
+        } else {
+            let listener = std::net::TcpListener::from(fd);
+            listener.set_nonblocking(true)?;
+            Ok(Listener::Tcp(tokio::net::TcpListener::from_std(listener)?))
+        }
+    }
+
+    /// Accept a connection, as a blocking fd.
+    async fn accept(&self) -> std::io::Result<OwnedFd> {
+        match self {
+            Listener::Tcp(listener) => {
+                let stream = listener.accept().await?.0.into_std()?;
+                stream.set_nonblocking(false)?;
+                Ok(stream.into())
+            }
+            Listener::Unix(listener) => {
+                let stream = listener.accept().await?.0.into_std()?;
+                stream.set_nonblocking(false)?;
+                Ok(stream.into())
+            }
+        }
+    }
+}
+
+fn close_chunk() -> FdChunk {
+    FdChunk {
+        close: true,
+        ..Default::default()
+    }
+}
+
+/// Read `file` into `tx` until EOF, then send a close chunk.
+fn spawn_reader(mut file: File, tx: mpsc::Sender<FdChunk>, done: CancellationToken) {
+    std::thread::spawn(move || {
+        let mut buf = vec![0u8; RELAY_CHUNK_SIZE];
+        loop {
+            if done.is_cancelled() || tx.is_closed() {
+                return;
+            }
+            let mut pollfd = libc::pollfd {
+                fd: file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            match unsafe { libc::poll(&mut pollfd, 1, POLL_INTERVAL_MS) } {
+                0 => continue,
+                n if n < 0 => {
+                    if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    break;
+                }
+                _ => {}
+            }
+            match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let chunk = FdChunk {
+                        data: buf[..n].to_vec(),
+                        ..Default::default()
+                    };
+                    if tx.blocking_send(chunk).is_err() {
+                        return;
+                    }
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::Interrupted | std::io::ErrorKind::WouldBlock
+                    ) => {}
+                Err(_) => break,
+            }
+        }
+        let _ = tx.blocking_send(close_chunk());
+    });
+}
+
+/// Write chunks sent to the returned channel to `file`; `None` closes it
+/// for writing.
+fn spawn_writer(mut file: File) -> std::sync::mpsc::Sender<Option<Vec<u8>>> {
+    let (tx, rx) = std::sync::mpsc::channel::<Option<Vec<u8>>>();
+    std::thread::spawn(move || {
+        for data in rx {
+            match data {
+                Some(data) => {
+                    if file.write_all(&data).is_err() {
+                        return;
+                    }
+                }
+                None => break,
+            }
+        }
+        // Tells a socket peer we're done; fails harmlessly on pipes
+        unsafe { libc::shutdown(file.as_raw_fd(), libc::SHUT_WR) };
+    });
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn host_fd(fd: impl Into<OwnedFd>, guest_fd: u32) -> HostFd {
+        HostFd {
+            fd: Arc::new(fd.into()),
+            guest_fd,
+        }
+    }
+
+    #[test]
+    fn test_validate_kinds() {
+        let (read_end, write_end) = std::io::pipe().unwrap();
+        let (stream, _peer) = std::os::unix::net::UnixStream::pair().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let relays = validate(&[
+            host_fd(read_end, 3),
+            host_fd(write_end, 4),
+            host_fd(stream, 5),
+            host_fd(listener, 6),
+        ])
+        .unwrap();
+        let kinds: Vec<RelayKind> = relays.iter().map(|relay| relay.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RelayKind::Stream {
+                    readable: true,
+                    writable: false
+                },
+                RelayKind::Stream {
+                    readable: false,
+                    writable: true
+                },
+                RelayKind::Stream {
+                    readable: true,
+                    writable: true
+                },
+                RelayKind::Listener,
+            ]
+        );
+        assert_eq!(relays[3].to_proto().kind(), PassedFdKind::Listener);
+    }
+
+    #[test]
+    fn test_validate_rejects() {
+        let file = tempfile::tempfile().unwrap();
+        assert!(matches!(
+            validate(&[host_fd(file, 3)]),
+            Err(BoxliteError::Unsupported(_))
+        ));
+
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        assert!(matches!(
+            validate(&[host_fd(socket, 3)]),
+            Err(BoxliteError::Unsupported(_))
+        ));
+
+        let (a, b) = std::os::unix::net::UnixStream::pair().unwrap();
+        assert!(matches!(
+            validate(&[host_fd(a, 2)]),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+        let (c, _d) = std::os::unix::net::UnixStream::pair().unwrap();
+        assert!(matches!(
+            validate(&[host_fd(b, 3), host_fd(c, 3)]),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 //! Host-side portal for communicating with guests via tonic/gRPC.
 
+pub mod calls;
 pub mod connection;
 pub mod interfaces;
 pub mod session;
@@ -1,7 +1,7 @@
 //! Unified rootfs builder for all preparation needs.
 
 use crate::images::{ImageObject, extract_layer_tarball_streaming};
-use crate::rootfs::{CopyMode, CopyMountOptions, copy_based_mount};
+use crate::rootfs::{CopyMode, CopyMountOptions, CopyStats, copy_based_mount};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use std::path::{Path, PathBuf};
 
@@ -104,6 +104,8 @@ impl RootfsBuilder {
             extracted_layers.len()
         );
 
+        let mut stats = CopyStats::default();
+
         // Stack layers directly to destination
         // IMPORTANT: Whiteouts are processed INLINE during copy (not as separate phase)
         // When copying a layer, .wh.* files delete corresponding files from destination
@@ -127,6 +129,7 @@ impl RootfsBuilder {
                         ignore_chown_errors: false,
                     },
                 )?;
+                stats = mount.stats;
 
                 // Unmount (no-op)
                 mount.unmount()?;
@@ -150,6 +153,7 @@ impl RootfsBuilder {
         tracing::info!("✅ Rootfs prepared at {}", dest.display());
         Ok(PreparedRootfs {
             path: dest.to_path_buf(),
+            copy: stats,
         })
     }
 
@@ -193,7 +197,10 @@ impl RootfsBuilder {
         crate::rootfs::operations::fix_rootfs_permissions(dest)?;
 
         tracing::info!("✅ Rootfs prepared using extraction-based mount");
-        Ok(PreparedRootfs { path: dest.clone() })
+        Ok(PreparedRootfs {
+            path: dest.clone(),
+            copy: CopyStats::default(),
+        })
     }
 }
 
@@ -206,6 +213,10 @@ impl Default for RootfsBuilder {
 /// Simple data holder for prepared rootfs path (no cleanup responsibility)
 pub struct PreparedRootfs {
     pub path: PathBuf,
+    /// How the base layer's files were materialized (upper layers are
+    /// stacked with `cp --reflink=auto` and not counted; zero when the
+    /// layers were extracted instead)
+    pub copy: CopyStats,
 }
 
 /// Circular symlink info for deferred handling
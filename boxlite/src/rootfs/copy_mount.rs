@@ -8,7 +8,9 @@
 //! - Preserves all file types: regular, directory, symlink, fifo, socket, device
 //! - Preserves permissions, ownership, timestamps, xattrs
 //! - Hardlink detection and preservation
-//! - Platform-specific optimizations (Linux: copy_file_range, macOS: standard copy)
+//! - Copy-on-write reflinks (Linux: FICLONE on btrfs/XFS, macOS: clonefile on
+//!   APFS), falling back to byte copies (copy_file_range on Linux) when the
+//!   filesystem can't share blocks
 //! - Proper error handling with cleanup on failure
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
@@ -61,11 +63,26 @@ impl Default for CopyMountOptions {
     }
 }
 
+/// How the content of regular files reached the destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyStats {
+    /// Files sharing their blocks with the source through a reflink
+    pub reflinked_files: u64,
+    /// Bytes in reflinked files
+    pub reflinked_bytes: u64,
+    /// Files whose content was copied
+    pub copied_files: u64,
+    /// Bytes copied
+    pub copied_bytes: u64,
+}
+
 /// Result of a copy-based mount operation
 pub struct CopyMount {
     /// Path to the mounted (copied) directory
     #[allow(dead_code)]
     pub path: PathBuf,
+    /// Reflinked and copied file content
+    pub stats: CopyStats,
 }
 
 impl CopyMount {
@@ -113,8 +130,9 @@ impl CopyMount {
 /// * `CopyMount` - Handle to the mounted directory
 ///
 /// # Platform Notes
-/// * **Linux**: Uses optimized copy with copy_file_range when available
-/// * **macOS**: Uses standard copy operations
+/// * **Linux**: Reflinks files with FICLONE (btrfs, XFS), otherwise copies
+///   them with copy_file_range
+/// * **macOS**: Reflinks files with clonefile (APFS), otherwise copies them
 ///
 /// # Example
 /// ```ignore
@@ -227,16 +245,25 @@ pub fn copy_based_mount(
 
     // Copy parent directory contents to mount_dir
     // This is the core operation: dirCopy(parentDir, dir)
-    if let Err(e) = dir_copy(parent_dir, mount_dir, options) {
-        // Clean up on error (VFS uses defer for this)
-        let _ = fs::remove_dir_all(mount_dir);
-        return Err(e);
-    }
+    let stats = match dir_copy(parent_dir, mount_dir, options) {
+        Ok(stats) => stats,
+        Err(e) => {
+            // Clean up on error (VFS uses defer for this)
+            let _ = fs::remove_dir_all(mount_dir);
+            return Err(e);
+        }
+    };
 
-    tracing::info!("✅ Copy-based mount created at {}", mount_dir.display());
+    tracing::info!(
+        reflinked_bytes = stats.reflinked_bytes,
+        copied_bytes = stats.copied_bytes,
+        "✅ Copy-based mount created at {}",
+        mount_dir.display()
+    );
 
     Ok(CopyMount {
         path: mount_dir.to_path_buf(),
+        stats,
     })
 }
 
@@ -255,9 +282,10 @@ pub fn copy_based_mount(
 /// * `src_dir` - Source directory
 /// * `dst_dir` - Destination directory (must exist)
 /// * `options` - Copy options
-fn dir_copy(src_dir: &Path, dst_dir: &Path, options: CopyMountOptions) -> BoxliteResult<()> {
+fn dir_copy(src_dir: &Path, dst_dir: &Path, options: CopyMountOptions) -> BoxliteResult<CopyStats> {
     // Track copied files by inode to handle hardlinks
     let mut copied_files: HashMap<FileId, PathBuf> = HashMap::new();
+    let mut copier = FileCopier::default();
 
     // Track directories to set mtimes later (VFS does this)
     let mut dirs_to_set_mtimes: Vec<(PathBuf, SystemTime, SystemTime)> = Vec::new();
@@ -328,7 +356,7 @@ fn dir_copy(src_dir: &Path, dst_dir: &Path, options: CopyMountOptions) -> Boxlit
                 true
             } else {
                 // New file, copy content
-                copy_regular_file(src_path, &dst_path, &metadata)?;
+                copier.copy(src_path, &dst_path, &metadata)?;
                 copied_files.insert(file_id, dst_path.clone());
                 false
             };
@@ -414,26 +442,132 @@ fn dir_copy(src_dir: &Path, dst_dir: &Path, options: CopyMountOptions) -> Boxlit
         set_symlink_times(dir_path, *atime, *mtime)?;
     }
 
+    Ok(copier.stats)
+}
+
+/// Whether the destination filesystem takes reflinks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ReflinkSupport {
+    #[default]
+    Unknown,
+    Supported,
+    Unsupported,
+}
+
+/// Copies regular files, reflinking them when the filesystem allows.
+///
+/// Like VFS: FICLONE/clonefile first, then a byte copy (std uses
+/// copy_file_range on Linux). Support is learned from the first file, so a
+/// filesystem without reflinks costs one failed attempt per tree.
+#[derive(Default)]
+struct FileCopier {
+    reflink: ReflinkSupport,
+    stats: CopyStats,
+}
+
+impl FileCopier {
+    /// Copy a regular file's content from src to dst
+    fn copy(&mut self, src: &Path, dst: &Path, metadata: &fs::Metadata) -> BoxliteResult<()> {
+        if self.reflink != ReflinkSupport::Unsupported {
+            match reflink_file(src, dst) {
+                Ok(()) => {
+                    self.reflink = ReflinkSupport::Supported;
+                    self.stats.reflinked_files += 1;
+                    self.stats.reflinked_bytes += metadata.len();
+                    return Ok(());
+                }
+                Err(e) if is_reflink_unsupported(&e) => {
+                    if self.reflink == ReflinkSupport::Unknown {
+                        tracing::debug!(
+                            "Reflinks unavailable for {} ({}), copying file content",
+                            dst.display(),
+                            e
+                        );
+                        self.reflink = ReflinkSupport::Unsupported;
+                    }
+                }
+                Err(e) => {
+                    return Err(BoxliteError::Storage(format!(
+                        "Failed to reflink file {} -> {}: {}",
+                        src.display(),
+                        dst.display(),
+                        e
+                    )));
+                }
+            }
+        }
+
+        fs::copy(src, dst).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to copy file {} -> {}: {}",
+                src.display(),
+                dst.display(),
+                e
+            ))
+        })?;
+        self.stats.copied_files += 1;
+        self.stats.copied_bytes += metadata.len();
+
+        Ok(())
+    }
+}
+
+/// Errors meaning the filesystem (or the pair of them) can't reflink.
+fn is_reflink_unsupported(e: &std::io::Error) -> bool {
+    // ENOTSUP and EOPNOTSUPP are the same value on Linux but not on macOS
+    const UNSUPPORTED: [i32; 6] = [
+        libc::EOPNOTSUPP,
+        libc::ENOTSUP,
+        libc::EXDEV,
+        libc::EINVAL,
+        libc::ENOTTY,
+        libc::ENOSYS,
+    ];
+    e.raw_os_error()
+        .is_some_and(|code| UNSUPPORTED.contains(&code))
+}
+
+/// Create dst sharing src's blocks (btrfs, XFS).
+#[cfg(target_os = "linux")]
+pub(crate) fn reflink_file(src: &Path, dst: &Path) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let src_file = fs::File::open(src)?;
+    let dst_file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dst)?;
+    if unsafe { libc::ioctl(dst_file.as_raw_fd(), libc::FICLONE, src_file.as_raw_fd()) } != 0 {
+        let err = std::io::Error::last_os_error();
+        drop(dst_file);
+        let _ = fs::remove_file(dst);
+        return Err(err);
+    }
     Ok(())
 }
 
-/// Copy a regular file's content from src to dst
-fn copy_regular_file(src: &Path, dst: &Path, _metadata: &fs::Metadata) -> BoxliteResult<()> {
-    // VFS tries: FICLONE ioctl -> copy_file_range -> legacy copy
-    // We'll use Rust's standard copy which is efficient enough
+/// Create dst sharing src's blocks (APFS).
+#[cfg(target_os = "macos")]
+pub(crate) fn reflink_file(src: &Path, dst: &Path) -> std::io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
 
-    fs::copy(src, dst).map_err(|e| {
-        BoxliteError::Storage(format!(
-            "Failed to copy file {} -> {}: {}",
-            src.display(),
-            dst.display(),
-            e
-        ))
-    })?;
+    /// From <sys/clonefile.h>
+    const CLONE_NOFOLLOW: u32 = 0x0001;
 
+    let src = CString::new(src.as_os_str().as_bytes())?;
+    let dst = CString::new(dst.as_os_str().as_bytes())?;
+    if unsafe { libc::clonefile(src.as_ptr(), dst.as_ptr(), CLONE_NOFOLLOW) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
     Ok(())
 }
 
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub(crate) fn reflink_file(_src: &Path, _dst: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::from_raw_os_error(libc::ENOTSUP))
+}
+
 /// Copy metadata (permissions, ownership, timestamps, xattrs) from src to dst
 fn copy_metadata(
     src: &Path,
@@ -606,6 +740,40 @@ mod tests {
         copy_mount.unmount().unwrap();
     }
 
+    #[test]
+    fn test_copy_counts_reflinked_and_copied_content() {
+        let temp = TempDir::new().unwrap();
+        let parent = temp.path().join("parent");
+        let mount = temp.path().join("mount");
+
+        fs::create_dir(&parent).unwrap();
+        fs::write(parent.join("a"), "12345").unwrap();
+        fs::write(parent.join("b"), "123").unwrap();
+        fs::hard_link(parent.join("a"), parent.join("a-link")).unwrap();
+
+        let copy_mount = copy_based_mount(&parent, &mount, CopyMountOptions::default()).unwrap();
+
+        // Either path is fine depending on the test filesystem; hardlinks
+        // are linked, not copied again
+        let stats = copy_mount.stats;
+        assert_eq!(stats.reflinked_files + stats.copied_files, 2);
+        assert_eq!(stats.reflinked_bytes + stats.copied_bytes, 8);
+        assert_eq!(fs::read_to_string(mount.join("b")).unwrap(), "123");
+    }
+
+    #[test]
+    fn test_reflink_unsupported_errors() {
+        for code in [libc::EOPNOTSUPP, libc::EXDEV, libc::EINVAL] {
+            assert!(is_reflink_unsupported(&std::io::Error::from_raw_os_error(
+                code
+            )));
+        }
+        assert!(!is_reflink_unsupported(&std::io::Error::from_raw_os_error(
+            libc::ENOSPC
+        )));
+        assert!(!is_reflink_unsupported(&std::io::Error::other("other")));
+    }
+
     #[test]
     fn test_copy_preserves_permissions() {
         let temp = TempDir::new().unwrap();
@@ -8,5 +8,6 @@ mod dns;
 pub(crate) mod operations;
 
 pub use builder::RootfsBuilder;
-pub use copy_mount::{CopyMode, CopyMountOptions, copy_based_mount};
+pub(crate) use copy_mount::reflink_file;
+pub use copy_mount::{CopyMode, CopyMountOptions, CopyStats, copy_based_mount};
 pub use dns::configure_container_dns;
@@ -0,0 +1,152 @@
+//! Executor-independent handle to a runtime.
+//!
+//! `BoxliteRuntime` and `LiteBox` futures must be polled inside a Tokio
+//! runtime: they spawn tasks, use Tokio timers and talk gRPC through Tokio
+//! I/O. [`AsyncRuntime`] pins that work to a Tokio runtime chosen once, and
+//! hands back futures that can be awaited from any executor (async-std,
+//! smol, `futures::executor`, or another Tokio runtime) without blocking a
+//! thread.
+
+use std::future::Future;
+
+use tokio::runtime::Handle;
+
+use crate::litebox::LiteBox;
+use crate::metrics::RuntimeMetrics;
+use crate::runtime::BoxliteRuntime;
+use crate::runtime::options::{BoxOptions, BoxliteOptions};
+use crate::runtime::shutdown::ShutdownReport;
+use crate::runtime::types::{BoxFilter, BoxInfo};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// A [`BoxliteRuntime`] whose work runs on a given Tokio runtime.
+///
+/// Every method spawns its work on that Tokio runtime and awaits the result,
+/// so the returned futures are executor-agnostic. The Tokio runtime must be
+/// multi-threaded, or a current-thread runtime that the embedder keeps
+/// driving.
+///
+/// Cheap to clone; clones share the runtime.
+///
+/// # Example
+///
+/// ```no_run
+/// use boxlite::{AsyncRuntime, BoxOptions, BoxliteOptions};
+///
+/// // The embedder's own Tokio runtime
+/// let tokio_rt = tokio::runtime::Runtime::new()?;
+/// let runtime = AsyncRuntime::new(BoxliteOptions::default(), tokio_rt.handle().clone())?;
+///
+/// // Awaited from a different executor
+/// futures::executor::block_on(async {
+///     let litebox = runtime.create(BoxOptions::default(), None).await?;
+///     let id = litebox.id().clone();
+///     runtime.run(move |_| async move { litebox.start().await }).await?;
+///     runtime.remove(id.as_str(), true).await
+/// })?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone)]
+pub struct AsyncRuntime {
+    runtime: BoxliteRuntime,
+    handle: Handle,
+}
+
+impl AsyncRuntime {
+    /// Create a runtime whose work runs on `handle`.
+    ///
+    /// Fails like [`BoxliteRuntime::new`].
+    pub fn new(options: BoxliteOptions, handle: Handle) -> BoxliteResult<Self> {
+        // Tasks started while the runtime initializes belong on `handle` too
+        let _guard = handle.enter();
+        Ok(Self {
+            runtime: BoxliteRuntime::new(options)?,
+            handle,
+        })
+    }
+
+    /// Wrap an existing runtime, running its work on `handle`.
+    pub fn from_runtime(runtime: BoxliteRuntime, handle: Handle) -> Self {
+        Self { runtime, handle }
+    }
+
+    /// The wrapped runtime, for use from inside the Tokio runtime.
+    pub fn runtime(&self) -> &BoxliteRuntime {
+        &self.runtime
+    }
+
+    /// The Tokio runtime the work runs on.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Run `f` on the Tokio runtime and await its result.
+    ///
+    /// This reaches everything the facade doesn't wrap, such as `LiteBox`
+    /// and `Execution` methods.
+    pub async fn run<F, Fut, T>(&self, f: F) -> BoxliteResult<T>
+    where
+        F: FnOnce(BoxliteRuntime) -> Fut,
+        Fut: Future<Output = BoxliteResult<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.handle
+            .spawn(f(self.runtime.clone()))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("Runtime task failed: {}", e)))?
+    }
+
+    /// See [`BoxliteRuntime::create`].
+    pub async fn create(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+    ) -> BoxliteResult<LiteBox> {
+        self.run(|rt| async move { rt.create(options, name).await })
+            .await
+    }
+
+    /// See [`BoxliteRuntime::get`].
+    pub async fn get(&self, id_or_name: &str) -> BoxliteResult<Option<LiteBox>> {
+        let id_or_name = id_or_name.to_string();
+        self.run(|rt| async move { rt.get(&id_or_name).await })
+            .await
+    }
+
+    /// See [`BoxliteRuntime::get_info`].
+    pub async fn get_info(&self, id_or_name: &str) -> BoxliteResult<Option<BoxInfo>> {
+        let id_or_name = id_or_name.to_string();
+        self.run(|rt| async move { rt.get_info(&id_or_name).await })
+            .await
+    }
+
+    /// See [`BoxliteRuntime::list_info`].
+    pub async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
+        self.run(|rt| async move { rt.list_info().await }).await
+    }
+
+    /// See [`BoxliteRuntime::list_info_filtered`].
+    pub async fn list_info_filtered(&self, filter: &BoxFilter) -> BoxliteResult<Vec<BoxInfo>> {
+        let filter = filter.clone();
+        self.run(|rt| async move { rt.list_info_filtered(&filter).await })
+            .await
+    }
+
+    /// See [`BoxliteRuntime::metrics`].
+    pub async fn metrics(&self) -> BoxliteResult<RuntimeMetrics> {
+        self.run(|rt| async move { Ok(rt.metrics().await) }).await
+    }
+
+    /// See [`BoxliteRuntime::remove`].
+    pub async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        let id_or_name = id_or_name.to_string();
+        self.run(move |rt| async move { rt.remove(&id_or_name, force).await })
+            .await
+    }
+
+    /// See [`BoxliteRuntime::shutdown`].
+    pub async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<ShutdownReport> {
+        self.run(move |rt| async move { rt.shutdown(timeout).await })
+            .await
+    }
+}
@@ -0,0 +1,131 @@
+//! Batch box creation types.
+//!
+//! `BoxliteRuntime::create_many()` creates and starts many boxes at once and
+//! reports each box as soon as it is ready (or has failed), rather than
+//! waiting for the whole batch.
+
+use crate::litebox::LiteBox;
+use crate::runtime::constants::images;
+use crate::runtime::options::{BoxOptions, ImagePullPolicy, RootfsSpec};
+use boxlite_shared::errors::BoxliteResult;
+use futures::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+/// Outcome of creating a single box within a batch.
+pub struct BoxCreateResult {
+    /// Position of this box's options in the list passed to `create_many()`.
+    pub index: usize,
+    /// The started box, or the error that prevented it from starting.
+    pub result: BoxliteResult<LiteBox>,
+}
+
+/// Stream of per-box results from `create_many()`.
+///
+/// Results arrive in completion order, not input order. Use
+/// [`BoxCreateResult::index`] to correlate a result with its options.
+/// The stream ends once every box in the batch has been reported.
+///
+/// Dropping the stream stops the batch: boxes not yet started are skipped.
+pub struct CreateManyStream {
+    receiver: mpsc::UnboundedReceiver<BoxCreateResult>,
+}
+
+impl CreateManyStream {
+    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<BoxCreateResult>) -> Self {
+        Self { receiver }
+    }
+}
+
+impl Stream for CreateManyStream {
+    type Item = BoxCreateResult;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Image reference a box will be created from, if it uses a cached image.
+pub(crate) fn image_ref(options: &BoxOptions) -> Option<&str> {
+    match &options.rootfs {
+        RootfsSpec::Image(r) => Some(r.as_str()),
+        RootfsSpec::Builtin => Some(images::BUILTIN),
+        RootfsSpec::RootfsPath(_) | RootfsSpec::DiskImage(..) => None,
+    }
+}
+
+/// Distinct image references used by a batch, in first-seen order.
+///
+/// Each of these is resolved once for the whole batch instead of once per box,
+/// using the pull policy of the first box that references it.
+pub(crate) fn distinct_images(options_list: &[BoxOptions]) -> Vec<(String, ImagePullPolicy)> {
+    let mut images: Vec<(String, ImagePullPolicy)> = Vec::new();
+    for options in options_list {
+        let Some(image) = image_ref(options) else {
+            continue;
+        };
+        if !images.iter().any(|(seen, _)| seen == image) {
+            images.push((image.to_string(), options.pull_policy));
+        }
+    }
+    images
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image_opts(image: &str) -> BoxOptions {
+        BoxOptions {
+            rootfs: RootfsSpec::Image(image.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_distinct_images_dedupes_in_order() {
+        let options = vec![
+            image_opts("python:alpine"),
+            image_opts("alpine:latest"),
+            image_opts("python:alpine"),
+            BoxOptions {
+                rootfs: RootfsSpec::RootfsPath("/tmp/rootfs".to_string()),
+                ..Default::default()
+            },
+            image_opts("alpine:latest"),
+        ];
+
+        assert_eq!(
+            distinct_images(&options),
+            vec![
+                ("python:alpine".to_string(), ImagePullPolicy::IfNotPresent),
+                ("alpine:latest".to_string(), ImagePullPolicy::IfNotPresent),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distinct_images_keeps_first_policy() {
+        let options = vec![
+            BoxOptions {
+                pull_policy: ImagePullPolicy::Never,
+                ..image_opts("alpine:latest")
+            },
+            BoxOptions {
+                pull_policy: ImagePullPolicy::Always,
+                ..image_opts("alpine:latest")
+            },
+        ];
+
+        assert_eq!(
+            distinct_images(&options),
+            vec![("alpine:latest".to_string(), ImagePullPolicy::Never)]
+        );
+    }
+
+    #[test]
+    fn test_distinct_images_empty() {
+        assert!(distinct_images(&[]).is_empty());
+    }
+}
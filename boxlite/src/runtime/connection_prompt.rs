@@ -0,0 +1,378 @@
+//! Interactive approval of outbound connections.
+//!
+//! gvproxy holds back the first connection of a box with
+//! `BoxOptions.prompt_connections` to each new destination and asks the host
+//! over the box's `net.sock` (see `gvproxy-bridge/prompt.go`). The runtime
+//! that started or first attached to the box answers from the decisions
+//! remembered for the box, or asks the callback registered with
+//! `BoxliteRuntime::on_connection_prompt()` and remembers its answer in the
+//! box directory. Without a callback the connection is denied, and asked
+//! about again on its next attempt.
+//!
+//! Once that process exits, connections to new destinations are denied
+//! until another runtime attaches to the box and takes the socket over.
+//!
+//! Each request is a JSON line on its own Unix connection,
+//! `{"protocol": "tcp", "address": "93.184.216.34:443"}`, answered with
+//! `{"allow": true}`.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio_util::sync::CancellationToken;
+
+use crate::net::NetProtocol;
+use crate::runtime::events::ConnectionAudit;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// File in the box directory holding the remembered decisions.
+pub(crate) const DECISIONS_FILE: &str = "connections.json";
+
+/// A box's first connection to a destination, held back until decided.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnectionRequest {
+    pub box_id: String,
+    pub box_name: Option<String>,
+    pub protocol: NetProtocol,
+    pub address: SocketAddr,
+}
+
+/// Answer to a [`ConnectionRequest`], remembered for the box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionDecision {
+    Allow,
+    Deny,
+}
+
+/// Callback registered with `BoxliteRuntime::on_connection_prompt()`.
+pub(crate) type ConnectionPromptCallback =
+    Arc<dyn Fn(&ConnectionRequest) -> ConnectionDecision + Send + Sync>;
+
+/// The prompt callback shared by the runtime and its boxes.
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionPrompt {
+    callback: Arc<RwLock<Option<ConnectionPromptCallback>>>,
+}
+
+impl ConnectionPrompt {
+    /// Replace the callback.
+    pub(crate) fn set(&self, callback: ConnectionPromptCallback) {
+        *self.callback.write().unwrap_or_else(|e| e.into_inner()) = Some(callback);
+    }
+
+    fn callback(&self) -> Option<ConnectionPromptCallback> {
+        self.callback
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl std::fmt::Debug for ConnectionPrompt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionPrompt").finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct PromptRequest {
+    protocol: NetProtocol,
+    address: SocketAddr,
+}
+
+#[derive(Serialize)]
+struct PromptResponse {
+    allow: bool,
+}
+
+/// Decisions remembered for a box, keyed by `protocol/ip:port`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Decisions(BTreeMap<String, ConnectionDecision>);
+
+impl Decisions {
+    fn key(protocol: NetProtocol, address: SocketAddr) -> String {
+        format!("{}/{}", protocol.as_str(), address)
+    }
+
+    /// Missing or unreadable files start out empty.
+    fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read(path) else {
+            return Self::default();
+        };
+        serde_json::from_slice(&content).unwrap_or_else(|e| {
+            tracing::warn!(path = %path.display(), "Ignoring unreadable connection decisions: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Replace the file at `path` with `content`, atomically.
+    fn write(path: &Path, content: &[u8]) -> BoxliteResult<()> {
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)
+            .and_then(|()| std::fs::rename(&tmp, path))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&tmp);
+                BoxliteError::Storage(format!(
+                    "failed to write connection decisions {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+}
+
+/// Everything the server of one box needs.
+pub(crate) struct PromptServer {
+    pub(crate) socket_path: PathBuf,
+    pub(crate) box_home: PathBuf,
+    pub(crate) box_id: String,
+    pub(crate) box_name: Option<String>,
+    pub(crate) prompt: ConnectionPrompt,
+    /// Called with every decision, for the event sinks
+    pub(crate) on_decision: Box<dyn Fn(ConnectionAudit) + Send + Sync>,
+}
+
+struct ServerState {
+    server: PromptServer,
+    decisions: Mutex<Decisions>,
+    /// Orders writes of the decisions file, so the newest one lands last
+    saving: tokio::sync::Mutex<()>,
+}
+
+impl PromptServer {
+    /// Bind the socket and answer prompts until `stop` is cancelled.
+    ///
+    /// Leaves the socket to the process already answering on it, if any;
+    /// a socket left behind by a process that exited is taken over.
+    pub(crate) fn spawn(self, stop: CancellationToken) -> BoxliteResult<()> {
+        if std::os::unix::net::UnixStream::connect(&self.socket_path).is_ok() {
+            tracing::debug!(
+                box_id = %self.box_id,
+                "Connection prompts are answered by another process"
+            );
+            return Ok(());
+        }
+        let _ = std::fs::remove_file(&self.socket_path);
+        let listener = UnixListener::bind(&self.socket_path).map_err(|e| {
+            BoxliteError::Network(format!(
+                "failed to bind connection prompt socket {}: {}",
+                self.socket_path.display(),
+                e
+            ))
+        })?;
+        let state = Arc::new(ServerState {
+            decisions: Mutex::new(Decisions::load(&self.box_home.join(DECISIONS_FILE))),
+            saving: tokio::sync::Mutex::new(()),
+            server: self,
+        });
+
+        tokio::spawn(async move {
+            loop {
+                let stream = tokio::select! {
+                    _ = stop.cancelled() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => stream,
+                        Err(e) => {
+                            tracing::warn!(box_id = %state.server.box_id, "Connection prompt accept failed: {}", e);
+                            continue;
+                        }
+                    },
+                };
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    if let Err(e) = state.answer(stream).await {
+                        tracing::warn!(box_id = %state.server.box_id, "Connection prompt failed: {}", e);
+                    }
+                });
+            }
+            let _ = std::fs::remove_file(&state.server.socket_path);
+        });
+        Ok(())
+    }
+}
+
+impl ServerState {
+    async fn answer(self: &Arc<Self>, stream: UnixStream) -> BoxliteResult<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).await?;
+        let request: PromptRequest = serde_json::from_str(&line)
+            .map_err(|e| BoxliteError::Network(format!("invalid connection prompt: {}", e)))?;
+
+        let allow = self.decide(request).await?;
+        let mut response = serde_json::to_vec(&PromptResponse { allow })?;
+        response.push(b'\n');
+        writer.write_all(&response).await?;
+        Ok(())
+    }
+
+    async fn decide(self: &Arc<Self>, request: PromptRequest) -> BoxliteResult<bool> {
+        let key = Decisions::key(request.protocol, request.address);
+        let remembered = self.decisions.lock().unwrap().0.get(&key).copied();
+
+        let decision = match remembered {
+            Some(decision) => Some(decision),
+            None => match self.server.prompt.callback() {
+                Some(callback) => {
+                    let request = ConnectionRequest {
+                        box_id: self.server.box_id.clone(),
+                        box_name: self.server.box_name.clone(),
+                        protocol: request.protocol,
+                        address: request.address,
+                    };
+                    // The callback may wait for the user
+                    let decision = tokio::task::spawn_blocking(move || callback(&request))
+                        .await
+                        .map_err(|e| {
+                            BoxliteError::Internal(format!("connection prompt panicked: {}", e))
+                        })?;
+                    self.decisions.lock().unwrap().0.insert(key, decision);
+                    self.save().await?;
+                    Some(decision)
+                }
+                None => None,
+            },
+        };
+
+        let allowed = decision == Some(ConnectionDecision::Allow);
+        tracing::info!(
+            box_id = %self.server.box_id,
+            protocol = request.protocol.as_str(),
+            address = %request.address,
+            allowed,
+            "Outbound connection {}",
+            if allowed { "allowed" } else { "denied" }
+        );
+        (self.server.on_decision)(ConnectionAudit {
+            protocol: request.protocol,
+            address: request.address.to_string(),
+            allowed,
+            remembered: remembered.is_some(),
+        });
+        Ok(allowed)
+    }
+
+    /// Write the remembered decisions to the box directory.
+    async fn save(&self) -> BoxliteResult<()> {
+        let _saving = self.saving.lock().await;
+        let content = serde_json::to_vec_pretty(&*self.decisions.lock().unwrap())?;
+        let path = self.server.box_home.join(DECISIONS_FILE);
+        tokio::task::spawn_blocking(move || Decisions::write(&path, &content))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(dir: &Path, prompt: ConnectionPrompt) -> (PromptServer, Arc<Mutex<Vec<bool>>>) {
+        let audits = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&audits);
+        let server = PromptServer {
+            socket_path: dir.join("net.sock"),
+            box_home: dir.to_path_buf(),
+            box_id: "box".to_string(),
+            box_name: None,
+            prompt,
+            on_decision: Box::new(move |audit: ConnectionAudit| {
+                seen.lock().unwrap().push(audit.allowed)
+            }),
+        };
+        (server, audits)
+    }
+
+    async fn ask(socket: &Path, request: &str) -> String {
+        let mut stream = UnixStream::connect(socket).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await.unwrap();
+        line
+    }
+
+    #[tokio::test]
+    async fn test_decisions_are_asked_once_and_remembered() {
+        let dir = tempfile::tempdir().unwrap();
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let prompt = ConnectionPrompt::default();
+        let calls = Arc::clone(&asked);
+        prompt.set(Arc::new(move |request: &ConnectionRequest| {
+            calls.lock().unwrap().push(request.address);
+            if request.address.port() == 443 {
+                ConnectionDecision::Allow
+            } else {
+                ConnectionDecision::Deny
+            }
+        }));
+        let (server, audits) = server(dir.path(), prompt);
+        let socket = server.socket_path.clone();
+        let stop = CancellationToken::new();
+        server.spawn(stop.clone()).unwrap();
+
+        let https = r#"{"protocol": "tcp", "address": "93.184.216.34:443"}"#.to_string() + "\n";
+        let ssh = r#"{"protocol": "tcp", "address": "93.184.216.34:22"}"#.to_string() + "\n";
+        assert_eq!(ask(&socket, &https).await.trim(), r#"{"allow":true}"#);
+        assert_eq!(ask(&socket, &ssh).await.trim(), r#"{"allow":false}"#);
+        assert_eq!(ask(&socket, &https).await.trim(), r#"{"allow":true}"#);
+
+        assert_eq!(asked.lock().unwrap().len(), 2);
+        assert_eq!(*audits.lock().unwrap(), vec![true, false, true]);
+        let saved = Decisions::load(&dir.path().join(DECISIONS_FILE));
+        assert_eq!(
+            saved.0.get("tcp/93.184.216.34:22"),
+            Some(&ConnectionDecision::Deny)
+        );
+        stop.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_denied_without_callback() {
+        let dir = tempfile::tempdir().unwrap();
+        let (server, _) = server(dir.path(), ConnectionPrompt::default());
+        let socket = server.socket_path.clone();
+        let stop = CancellationToken::new();
+        server.spawn(stop.clone()).unwrap();
+
+        let dns = r#"{"protocol": "udp", "address": "1.1.1.1:53"}"#.to_string() + "\n";
+        assert_eq!(ask(&socket, &dns).await.trim(), r#"{"allow":false}"#);
+        // Not remembered: the next attempt asks again
+        assert!(!dir.path().join(DECISIONS_FILE).exists());
+        stop.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_socket_left_to_live_server_and_taken_over_when_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let allow = ConnectionPrompt::default();
+        allow.set(Arc::new(|_: &ConnectionRequest| ConnectionDecision::Allow));
+        let (first, _) = server(dir.path(), allow);
+        let socket = first.socket_path.clone();
+        let first_stop = CancellationToken::new();
+        first.spawn(first_stop.clone()).unwrap();
+
+        // A second process attaching leaves the live server in place
+        let (second, _) = server(dir.path(), ConnectionPrompt::default());
+        let second_stop = CancellationToken::new();
+        second.spawn(second_stop.clone()).unwrap();
+        let https = r#"{"protocol": "tcp", "address": "93.184.216.34:443"}"#.to_string() + "\n";
+        assert_eq!(ask(&socket, &https).await.trim(), r#"{"allow":true}"#);
+
+        // A process that exited without cleaning up leaves a dead socket,
+        // which the next process to attach takes over
+        first_stop.cancel();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        let (third, _) = server(dir.path(), ConnectionPrompt::default());
+        third.spawn(second_stop.clone()).unwrap();
+        let ssh = r#"{"protocol": "tcp", "address": "93.184.216.34:22"}"#.to_string() + "\n";
+        assert_eq!(ask(&socket, &ssh).await.trim(), r#"{"allow":false}"#);
+        second_stop.cancel();
+    }
+}
@@ -19,6 +19,18 @@ pub mod envs {
     pub const BOXLITE_HOME: &str = "BOXLITE_HOME";
 }
 
+/// Box projects (namespaces)
+pub mod projects {
+    /// Project of boxes created without `default_project`, and of boxes
+    /// created before projects existed
+    pub const DEFAULT: &str = "default";
+
+    /// [`DEFAULT`] as a serde default, for records written before projects
+    pub(crate) fn default_project() -> String {
+        DEFAULT.to_string()
+    }
+}
+
 /// Container images used by the runtime
 pub mod images {
     /// Default container image when none is specified
@@ -26,6 +38,10 @@ pub mod images {
 
     /// Base image for VM init rootfs (must include mkfs.ext4 for disk formatting)
     pub const INIT_ROOTFS: &str = "debian:bookworm-slim";
+
+    /// Busybox image embedded in the crate, used by `RootfsSpec::Builtin`.
+    /// Never pulled from a registry.
+    pub const BUILTIN: &str = "boxlite.local/busybox:builtin";
 }
 
 /// Filesystem and mount options
@@ -46,10 +62,37 @@ pub mod vm_defaults {
     /// Default memory in MiB allocated to a Box
     pub const DEFAULT_MEMORY_MIB: u32 = 2048;
 
+    /// Memory in MiB below which a box gets a creation warning
+    pub const MIN_RECOMMENDED_MEMORY_MIB: u32 = 256;
+
     /// Default disk size in GB for the container rootfs (sparse, grows as needed)
     pub const DEFAULT_DISK_SIZE_GB: u64 = 10;
 }
 
+/// Guest resources held back from the container for the guest agent.
+///
+/// When a box sets `cpus`/`memory_mib`, the container's cgroup gets the VM's
+/// size minus these, so a runaway workload can't starve the agent.
+pub mod agent_reservation {
+    /// Memory reserved for the guest agent and kernel (in MiB)
+    pub const MEMORY_MIB: u32 = 64;
+
+    /// CPU reserved for the guest agent (in millicores)
+    pub const CPU_MILLICORES: u32 = 100;
+
+    /// cgroup cpu.max accounting period (in microseconds)
+    pub const CPU_PERIOD_US: u64 = 100_000;
+}
+
+/// Batch creation defaults (`create_many`)
+pub mod batch_defaults {
+    /// Minimum delay between consecutive VM boots in a batch (in ms).
+    ///
+    /// Spreads COW disk creation and shim spawns so a large batch doesn't
+    /// saturate the disk with simultaneous boots.
+    pub const BOOT_STAGGER_MS: u64 = 100;
+}
+
 /// File naming patterns
 pub mod filenames {
     use crate::runtime::layout::dirs;
@@ -1,13 +1,28 @@
 //! High-level sandbox runtime structures.
 
-use std::sync::OnceLock;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
-use crate::litebox::LiteBox;
+use crate::images::ImageDiff;
+use crate::litebox::{BoxCommand, InitHook, LiteBox};
 use crate::metrics::RuntimeMetrics;
-use crate::runtime::options::{BoxOptions, BoxliteOptions};
+use crate::runtime::batch::CreateManyStream;
+use crate::runtime::connection_prompt::{ConnectionDecision, ConnectionRequest};
+use crate::runtime::constants::images;
+use crate::runtime::exec_cache::ExecCacheEntry;
+use crate::runtime::leaks::CleanupLeak;
+use crate::runtime::log_level;
+use crate::runtime::networks::NetworkInfo;
+use crate::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+use crate::runtime::plan::{self, BoxPlan};
+use crate::runtime::prefetch::{self, ImagePrefetchStatus};
+use crate::runtime::progress::ProgressEvent;
 use crate::runtime::rt_impl::{RuntimeImpl, SharedRuntimeImpl};
+use crate::runtime::run_once::{self, RunOutcome};
+use crate::runtime::shutdown::{ShutdownPhase, ShutdownReport};
 use crate::runtime::signal_handler::install_signal_handler;
-use crate::runtime::types::BoxInfo;
+use crate::runtime::types::{BoxFilter, BoxInfo, ProjectInfo};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 // ============================================================================
 // GLOBAL DEFAULT RUNTIME
@@ -86,7 +101,8 @@ impl BoxliteRuntime {
     ///
     /// **Signal Handling**: On first call, this also installs SIGTERM and SIGINT
     /// handlers that will gracefully shutdown all boxes before exiting. This is
-    /// the recommended way to use BoxLite for simple applications.
+    /// the recommended way to use BoxLite for simple applications. SIGUSR1
+    /// applies the log level requested with [`log_level::request_log_level`].
     ///
     /// For applications that need custom signal handling, use `BoxliteRuntime::new()`
     /// instead and call `shutdown()` manually in your signal handler.
@@ -118,6 +134,8 @@ impl BoxliteRuntime {
         install_signal_handler(move || async move {
             let _ = rt_impl.shutdown(None).await;
         });
+        // SIGUSR1 applies a log level requested by `boxlite debug set-log-level`
+        log_level::listen(rt.rt_impl.layout.home_dir());
 
         rt
     }
@@ -211,6 +229,98 @@ impl BoxliteRuntime {
         self.rt_impl.get_or_create(options, name).await
     }
 
+    /// Check what `create()` would do with `options` without creating a box.
+    ///
+    /// Runs the same validation as `create()` (options, name conflicts,
+    /// dependencies, CPU/memory capacity) plus the checks `start()` makes
+    /// on volumes and the rootfs, and returns the resolved resources and
+    /// image. The image is only looked up in the cache unless `pull` is set,
+    /// in which case it is pulled according to the box's pull policy.
+    /// `pre_create` hooks are not run.
+    pub async fn plan(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        pull: bool,
+    ) -> BoxliteResult<BoxPlan> {
+        plan::plan(&self.rt_impl, options, name, pull).await
+    }
+
+    /// Create and start many boxes for fan-out workloads.
+    ///
+    /// Each distinct image is pulled once for the whole batch. Boxes are then
+    /// created and started with at most `parallelism` in flight (minimum 1),
+    /// with VM boots staggered to avoid disk contention.
+    ///
+    /// Returns a stream yielding one [`BoxCreateResult`] per box as it becomes
+    /// ready or fails. Results arrive in completion order; use `index` to map
+    /// them back to `options_list`. A box that fails to start is removed.
+    ///
+    /// [`BoxCreateResult`]: crate::runtime::batch::BoxCreateResult
+    pub async fn create_many(
+        &self,
+        options_list: Vec<BoxOptions>,
+        parallelism: usize,
+    ) -> BoxliteResult<CreateManyStream> {
+        self.rt_impl.create_many(options_list, parallelism).await
+    }
+
+    /// Run one command in a fresh box and remove the box again.
+    ///
+    /// Creates a box from `options`, starts it, runs `command` to completion
+    /// and returns its exit code and buffered output. The box is removed
+    /// however the run ends (`auto_remove` is forced on and `detach` off),
+    /// in the background if the returned future is dropped early.
+    ///
+    /// `timeout` bounds the whole run, boot included; when it passes, the
+    /// box is removed and an `Execution` error returned. A non-zero exit code
+    /// is not an error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() -> boxlite::BoxliteResult<()> {
+    /// use std::time::Duration;
+    /// use boxlite::{BoxCommand, BoxOptions, BoxliteRuntime};
+    ///
+    /// let runtime = BoxliteRuntime::with_defaults()?;
+    /// let outcome = runtime
+    ///     .run_once(
+    ///         BoxOptions::default(),
+    ///         BoxCommand::new("echo").arg("hello"),
+    ///         Some(Duration::from_secs(60)),
+    ///     )
+    ///     .await?;
+    /// assert_eq!(outcome.stdout, "hello\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_once(
+        &self,
+        options: BoxOptions,
+        command: BoxCommand,
+        timeout: Option<Duration>,
+    ) -> BoxliteResult<RunOutcome> {
+        run_once::run_once(self, options, command, timeout).await
+    }
+
+    /// Create `count` new boxes that start from the current disk state of a
+    /// box, e.g. to branch a sandbox at a decision point.
+    ///
+    /// The source must be running or stopped. A running source is synced and
+    /// briefly paused while its disk overlays are copied, then keeps running;
+    /// copies share blocks with the source where the filesystem supports
+    /// reflinks. Fails if a running source can't be synced. Only disk state
+    /// is forked: each fork boots fresh, without the source's processes or
+    /// memory.
+    ///
+    /// Forks are unnamed, `Stopped` boxes with the source's options, minus
+    /// published ports and the persistent cache. They diverge from the first
+    /// `start()` or `exec()`. On error, forks created so far are removed.
+    pub async fn fork(&self, id_or_name: &str, count: usize) -> BoxliteResult<Vec<LiteBox>> {
+        self.rt_impl.fork(id_or_name, count).await
+    }
+
     /// Get a handle to an existing box by ID or name.
     ///
     /// The `id_or_name` parameter can be either:
@@ -226,8 +336,32 @@ impl BoxliteRuntime {
     }
 
     /// List all boxes, sorted by creation time (newest first).
+    ///
+    /// With `BoxliteOptions.default_project` set, only boxes of that project.
     pub async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
-        self.rt_impl.list_info().await
+        self.rt_impl.list_info(&BoxFilter::default()).await
+    }
+
+    /// List the boxes passing `filter`, sorted by creation time (newest
+    /// first).
+    ///
+    /// Cheaper than filtering `list_info()` when few boxes match.
+    pub async fn list_info_filtered(&self, filter: &BoxFilter) -> BoxliteResult<Vec<BoxInfo>> {
+        self.rt_impl.list_info(filter).await
+    }
+
+    /// List projects that have boxes, with box counts.
+    pub async fn list_projects(&self) -> BoxliteResult<Vec<ProjectInfo>> {
+        self.rt_impl.list_projects().await
+    }
+
+    /// Members of a user-defined network (see [`BoxOptions::networks`]) in
+    /// the runtime's project, with the name and address each running member
+    /// resolves to.
+    ///
+    /// Returns `NotFound` if no box joined the network.
+    pub async fn inspect_network(&self, name: &str) -> BoxliteResult<NetworkInfo> {
+        self.rt_impl.inspect_network(name).await
     }
 
     /// Check if a box with the given ID or name exists.
@@ -242,7 +376,24 @@ impl BoxliteRuntime {
 
     /// Remove a box completely by ID or name.
     pub async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
-        self.rt_impl.remove(id_or_name, force)
+        // Removing deletes the box directory and runs post_remove hooks
+        let this = Arc::clone(&self.rt_impl);
+        let id_or_name = id_or_name.to_string();
+        tokio::task::spawn_blocking(move || this.remove(&id_or_name, force))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+    }
+
+    /// Find what removed boxes left behind: box directories, database rows
+    /// and socket directories of boxes no longer in the database.
+    ///
+    /// Only reports; removing a box already cleans up its own leftovers.
+    pub async fn find_leaks(&self) -> BoxliteResult<Vec<CleanupLeak>> {
+        // Walks the boxes and sockets directories
+        let this = Arc::clone(&self.rt_impl);
+        tokio::task::spawn_blocking(move || this.find_leaks())
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
     }
 
     // ========================================================================
@@ -257,6 +408,9 @@ impl BoxliteRuntime {
     /// After calling this method, the runtime is permanently shut down and
     /// will return errors for any new operations (like `create()`).
     ///
+    /// Returns what happened to each box (stopped, killed after the timeout,
+    /// or failed). Calling it again returns an empty report.
+    ///
     /// # Arguments
     ///
     /// * `timeout` - Seconds to wait before force-killing each box:
@@ -276,7 +430,10 @@ impl BoxliteRuntime {
     ///     // ... create and use boxes ...
     ///
     ///     // On signal or shutdown request:
-    ///     runtime.shutdown(None).await?; // Default 10s timeout
+    ///     let report = runtime.shutdown(None).await?; // Default 10s timeout
+    ///     for result in &report.boxes {
+    ///         println!("{}: {:?}", result.box_id, result.outcome);
+    ///     }
     ///     // or
     ///     runtime.shutdown(Some(30)).await?; // 30s timeout
     ///     // or
@@ -285,10 +442,91 @@ impl BoxliteRuntime {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<()> {
+    pub async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<ShutdownReport> {
         self.rt_impl.shutdown(timeout).await
     }
 
+    /// Register a callback invoked at each shutdown phase.
+    ///
+    /// Phases are reported in order (`Draining`, `StoppingBoxes`, `Done`) by
+    /// whichever task runs the shutdown, including the signal handler of the
+    /// default runtime. Callbacks should return quickly.
+    pub fn on_shutdown<F>(&self, callback: F)
+    where
+        F: Fn(ShutdownPhase) + Send + Sync + 'static,
+    {
+        self.rt_impl.on_shutdown(Arc::new(callback));
+    }
+
+    /// Register a callback invoked as images are pulled and boxes boot.
+    ///
+    /// Events are reported from the task doing the work (possibly several
+    /// concurrently), so callbacks should return quickly. See [`ProgressEvent`].
+    pub fn on_progress<F>(&self, callback: F)
+    where
+        F: Fn(&ProgressEvent) + Send + Sync + 'static,
+    {
+        self.rt_impl.on_progress(Arc::new(callback));
+    }
+
+    /// Set the callback deciding the outbound connections of boxes with
+    /// `BoxOptions.prompt_connections`, replacing any earlier one.
+    ///
+    /// It is called, on a blocking thread, once per new destination of a box
+    /// running in this runtime; the decision is remembered for the box.
+    /// Boxes started by a runtime without a callback have their new
+    /// connections denied. See [`ConnectionRequest`].
+    pub fn on_connection_prompt<F>(&self, callback: F)
+    where
+        F: Fn(&ConnectionRequest) -> ConnectionDecision + Send + Sync + 'static,
+    {
+        self.rt_impl.on_connection_prompt(Arc::new(callback));
+    }
+
+    /// Add a hook run around the init stages of every box this runtime
+    /// starts from now on, after the hooks added before it.
+    ///
+    /// Hooks can change the options of a start, adjust the VM configuration,
+    /// or fail the start by returning an error. See [`InitHook`].
+    pub fn add_init_hook<H>(&self, hook: H)
+    where
+        H: InitHook + 'static,
+    {
+        self.rt_impl.init_hooks.add(Arc::new(hook));
+    }
+
+    /// Replace the log filter without restarting, e.g. `"debug"` or
+    /// `"info,boxlite::portal=trace"` (`RUST_LOG` syntax).
+    ///
+    /// The filter is process-wide, shared by all runtimes. Fails with
+    /// `InvalidArgument` for a malformed filter and `Unsupported` when the
+    /// application installed its own tracing subscriber before the runtime.
+    pub fn set_log_level(&self, filter: &str) -> BoxliteResult<()> {
+        log_level::set_filter(filter)
+    }
+
+    /// Entries of the `LiteBox::exec_cached()` cache, most recently used first.
+    pub fn exec_cache_entries(&self) -> BoxliteResult<Vec<ExecCacheEntry>> {
+        self.rt_impl.exec_cache.entries()
+    }
+
+    /// Remove one exec cache entry. Returns false if there was none.
+    pub fn remove_exec_cache_entry(&self, key: &str) -> BoxliteResult<bool> {
+        self.rt_impl.exec_cache.remove(key)
+    }
+
+    /// Remove every exec cache entry, returning how many were removed.
+    pub fn clear_exec_cache(&self) -> BoxliteResult<usize> {
+        self.rt_impl.exec_cache.clear()
+    }
+
+    /// Evict exec cache entries beyond `BoxliteOptions::exec_cache`, least
+    /// recently used first. Runs after every cached result as well; returns
+    /// how many entries were removed.
+    pub fn prune_exec_cache(&self) -> BoxliteResult<usize> {
+        self.rt_impl.exec_cache.prune()
+    }
+
     // ========================================================================
     // IMAGE OPERATIONS (delegate to ImageManager)
     // ========================================================================
@@ -311,6 +549,112 @@ impl BoxliteRuntime {
         self.rt_impl.image_manager.pull(image_ref).await
     }
 
+    /// Prepare images ahead of time so creating boxes from them is fast.
+    ///
+    /// Pulls each image (if not cached), extracts its layers, and builds the
+    /// base rootfs disk that boxes overlay. Use this at startup to move pull
+    /// cost out of the critical path for latency-sensitive box creation.
+    pub async fn prewarm(&self, image_refs: &[String]) -> BoxliteResult<()> {
+        self.rt_impl.prewarm(image_refs).await
+    }
+
+    /// Turn background prefetch of frequently used images on or off.
+    ///
+    /// The setting is stored in the home directory's database and applies to
+    /// every runtime using it. See [`crate::runtime::prefetch`] for when
+    /// images are refreshed and how pull policies are respected.
+    pub fn set_image_prefetch(&self, enabled: bool) -> BoxliteResult<()> {
+        self.rt_impl.image_usage.set_prefetch_enabled(enabled)
+    }
+
+    /// Whether image prefetch is enabled, how its last round went, and which
+    /// images the next round refreshes.
+    pub fn image_prefetch_status(&self) -> BoxliteResult<ImagePrefetchStatus> {
+        prefetch::status(&self.rt_impl)
+    }
+
+    /// Refresh the frequently used images now, whether or not background
+    /// prefetch is enabled. Returns how many were pulled or up to date.
+    pub async fn prefetch_images(&self) -> BoxliteResult<u32> {
+        if self.rt_impl.shutdown_token.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "Cannot prefetch images: runtime has been shut down".into(),
+            ));
+        }
+        Ok(prefetch::run_round(&self.rt_impl, true).await?.unwrap_or(0))
+    }
+
+    /// Pin a cached image so it is protected from pruning.
+    ///
+    /// Returns `NotFound` if the image is not in the local cache.
+    pub async fn pin_image(&self, image_ref: &str) -> BoxliteResult<()> {
+        self.rt_impl.image_manager.pin(image_ref).await
+    }
+
+    /// Unpin a cached image, making it eligible for pruning again.
+    ///
+    /// Returns `NotFound` if the image is not in the local cache.
+    pub async fn unpin_image(&self, image_ref: &str) -> BoxliteResult<()> {
+        self.rt_impl.image_manager.unpin(image_ref).await
+    }
+
+    /// Add `target` as another reference to the cached image `source`.
+    ///
+    /// Returns the resolved target reference, or `NotFound` if `source` is
+    /// not in the local cache.
+    pub async fn tag_image(&self, source: &str, target: &str) -> BoxliteResult<String> {
+        self.rt_impl.image_manager.tag(source, target).await
+    }
+
+    /// Files added, modified and removed from cached image `old_ref` to
+    /// cached image `new_ref`.
+    ///
+    /// Reads the layers' file listings; nothing is extracted. Returns
+    /// `NotFound` if either image is not in the local cache.
+    pub async fn diff_images(&self, old_ref: &str, new_ref: &str) -> BoxliteResult<ImageDiff> {
+        self.rt_impl.image_manager.diff(old_ref, new_ref).await
+    }
+
+    /// Digest of the layer of cached image `image_ref` that last wrote each
+    /// of `paths`, to find where a file of the image comes from.
+    ///
+    /// Paths that aren't files or symlinks of the image are left out.
+    /// Returns `NotFound` if the image is not in the local cache.
+    pub async fn file_provenance(
+        &self,
+        image_ref: &str,
+        paths: &[String],
+    ) -> BoxliteResult<BTreeMap<String, String>> {
+        self.rt_impl
+            .image_manager
+            .provenance(image_ref, paths)
+            .await
+    }
+
+    /// Remove a cached image.
+    ///
+    /// Fails with `InvalidState` if it is the last reference to content
+    /// boxes were created from, or it is pinned, unless `force` is set.
+    /// Forcing only untags images still used by boxes; their files are kept.
+    /// Returns the removed reference.
+    pub async fn remove_image(&self, image_ref: &str, force: bool) -> BoxliteResult<String> {
+        let users = self.image_users().await?;
+        let reference = self
+            .rt_impl
+            .image_manager
+            .remove(image_ref, force, &users)
+            .await?;
+        // Otherwise prefetch would pull it back
+        if let Err(e) = self
+            .rt_impl
+            .image_usage
+            .forget(&[image_ref, reference.as_str()])
+        {
+            tracing::debug!(image = %reference, "Failed to forget image use: {}", e);
+        }
+        Ok(reference)
+    }
+
     /// List all cached images.
     ///
     /// Returns a list of images available in the local content store.
@@ -321,7 +665,39 @@ impl BoxliteRuntime {
     ///
     /// Returns a vector of `ImageInfo` structs containing metadata for all cached images.
     pub async fn list_images(&self) -> BoxliteResult<Vec<crate::runtime::types::ImageInfo>> {
-        self.rt_impl.image_manager.list().await
+        let mut images = self.rt_impl.image_manager.list().await?;
+        let in_use = self.image_users().await?;
+        for image in &mut images {
+            image.in_use_by = in_use.get(&image.id).copied().unwrap_or(0);
+        }
+
+        Ok(images)
+    }
+
+    /// Number of boxes per cached manifest digest.
+    ///
+    /// Each box's image is resolved the same way it was when the box was
+    /// created; references to the same content share one count.
+    async fn image_users(&self) -> BoxliteResult<HashMap<String, usize>> {
+        let this = Arc::clone(&self.rt_impl);
+        let boxes = tokio::task::spawn_blocking(move || this.box_manager.all_boxes(false))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
+        let digests = self.rt_impl.image_manager.cached_digests().await?;
+        let mut in_use: HashMap<String, usize> = HashMap::new();
+        for (config, _) in boxes {
+            let image_ref = match config.options.rootfs {
+                RootfsSpec::Image(ref image_ref) => image_ref.as_str(),
+                RootfsSpec::Builtin => images::BUILTIN,
+                RootfsSpec::RootfsPath(_) | RootfsSpec::DiskImage(..) => continue,
+            };
+            if let Some(reference) = self.rt_impl.image_manager.resolve_cached(image_ref).await?
+                && let Some(digest) = digests.get(&reference)
+            {
+                *in_use.entry(digest.clone()).or_default() += 1;
+            }
+        }
+        Ok(in_use)
     }
 }
 
@@ -0,0 +1,108 @@
+//! Box dependency ordering.
+//!
+//! `BoxOptions.depends_on` links boxes into a graph. Starting a box starts
+//! its dependencies first, so the graph is walked depth-first into an order
+//! where every box comes after its own dependencies. Cycles are rejected.
+
+use crate::runtime::types::BoxID;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::collections::HashSet;
+
+/// Transitive dependencies of `root`, each after its own dependencies.
+///
+/// `root` itself is not included. `deps_of` returns a box's direct
+/// dependencies.
+pub(crate) fn dependency_order<F>(root: &BoxID, mut deps_of: F) -> BoxliteResult<Vec<BoxID>>
+where
+    F: FnMut(&BoxID) -> BoxliteResult<Vec<BoxID>>,
+{
+    let mut walk = Walk {
+        path: Vec::new(),
+        done: HashSet::new(),
+        order: Vec::new(),
+    };
+    walk.visit(root, &mut deps_of)?;
+    // The root finishes last
+    walk.order.pop();
+    Ok(walk.order)
+}
+
+struct Walk {
+    /// Boxes on the current path from the root, for cycle detection
+    path: Vec<BoxID>,
+    done: HashSet<BoxID>,
+    order: Vec<BoxID>,
+}
+
+impl Walk {
+    fn visit<F>(&mut self, id: &BoxID, deps_of: &mut F) -> BoxliteResult<()>
+    where
+        F: FnMut(&BoxID) -> BoxliteResult<Vec<BoxID>>,
+    {
+        if self.done.contains(id) {
+            return Ok(());
+        }
+        if let Some(pos) = self.path.iter().position(|p| p == id) {
+            let cycle: Vec<&str> = self.path[pos..]
+                .iter()
+                .chain(std::iter::once(id))
+                .map(|id| id.as_str())
+                .collect();
+            return Err(BoxliteError::InvalidState(format!(
+                "dependency cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        self.path.push(id.clone());
+        for dep in deps_of(id)? {
+            self.visit(&dep, deps_of)?;
+        }
+        self.path.pop();
+
+        self.done.insert(id.clone());
+        self.order.push(id.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn graph(edges: &[(&BoxID, &[&BoxID])]) -> HashMap<BoxID, Vec<BoxID>> {
+        edges
+            .iter()
+            .map(|(id, deps)| ((*id).clone(), deps.iter().map(|d| (*d).clone()).collect()))
+            .collect()
+    }
+
+    fn order(root: &BoxID, graph: &HashMap<BoxID, Vec<BoxID>>) -> BoxliteResult<Vec<BoxID>> {
+        dependency_order(root, |id| Ok(graph.get(id).cloned().unwrap_or_default()))
+    }
+
+    #[test]
+    fn test_dependencies_come_first() {
+        let (app, api, db, cache) = (BoxID::new(), BoxID::new(), BoxID::new(), BoxID::new());
+        // app -> api -> {db, cache}, app -> db
+        let g = graph(&[(&app, &[&api, &db]), (&api, &[&db, &cache])]);
+
+        assert_eq!(order(&app, &g).unwrap(), vec![db.clone(), cache, api]);
+        assert!(
+            dependency_order(&db, |_| Ok(Vec::new()))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_cycle_is_rejected() {
+        let (a, b, c) = (BoxID::new(), BoxID::new(), BoxID::new());
+        let g = graph(&[(&a, &[&b]), (&b, &[&c]), (&c, &[&b])]);
+
+        let err = order(&a, &g).unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidState(ref msg)
+            if msg == &format!("dependency cycle: {b} -> {c} -> {b}")));
+    }
+}
@@ -0,0 +1,605 @@
+//! Runtime event export.
+//!
+//! Box lifecycle events and exec audit records are sent to the sinks in
+//! `BoxliteOptions.event_sinks`. Each sink has its own worker thread fed by
+//! a bounded queue: emitting never blocks, and a full queue drops the event.
+//! Workers deliver events in batches (a JSON array per webhook request, one
+//! JSON line per event for files) and retry failed batches with exponential
+//! backoff. When the runtime is dropped, queued events get one last delivery
+//! attempt, for up to `FLUSH_TIMEOUT`.
+
+use crate::net::NetProtocol;
+use crate::runtime::leaks::CleanupLeak;
+use crate::runtime::options::{EventDestination, EventSinkSpec};
+use crate::runtime::types::BoxInfo;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Queue size for sinks that don't set `buffer_size`.
+const DEFAULT_BUFFER_SIZE: usize = 1000;
+
+/// Retries for sinks that don't set `max_retries`.
+const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Most events delivered in one batch.
+const MAX_BATCH: usize = 100;
+
+/// How long a worker waits for more events before delivering a batch.
+const BATCH_WINDOW: Duration = Duration::from_millis(500);
+
+/// Backoff before the first retry; doubled per attempt up to `MAX_BACKOFF`.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Timeout of one webhook request.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long dropping the sinks waits for the workers to flush; workers
+/// still delivering after that are left to finish on their own.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a retrying worker checks whether the runtime is gone.
+const CLOSING_POLL: Duration = Duration::from_millis(100);
+
+/// What happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    /// The box was created (persisted, not yet started).
+    #[serde(rename = "box.created")]
+    BoxCreated,
+    /// The box's VM booted.
+    #[serde(rename = "box.started")]
+    BoxStarted,
+    /// The box was stopped.
+    #[serde(rename = "box.stopped")]
+    BoxStopped,
+    /// The box was removed.
+    #[serde(rename = "box.removed")]
+    BoxRemoved,
+    /// The box's guest agent stopped answering heartbeats.
+    #[serde(rename = "box.unresponsive")]
+    BoxUnresponsive,
+    /// An unresponsive box's guest agent answered again.
+    #[serde(rename = "box.responsive")]
+    BoxResponsive,
+    /// The box's VM started swapping out more than 8 MiB/s.
+    #[serde(rename = "box.swapping")]
+    BoxSwapping,
+    /// A command was run in the box (audit record).
+    #[serde(rename = "exec")]
+    Exec,
+    /// Artifacts of a removed box were still there after removal.
+    #[serde(rename = "cleanup_leak")]
+    CleanupLeak,
+    /// An outbound connection of a box with `prompt_connections` was
+    /// allowed or denied.
+    #[serde(rename = "net.connection")]
+    Connection,
+}
+
+/// A runtime event, as delivered to sinks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RuntimeEvent {
+    pub time: DateTime<Utc>,
+    pub kind: EventKind,
+    /// The box the event is about, as of the event.
+    #[serde(rename = "box")]
+    pub box_info: BoxInfo,
+    /// Set for `EventKind::Exec`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<ExecAudit>,
+    /// Set for `EventKind::CleanupLeak`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leaks: Vec<CleanupLeak>,
+    /// Set for `EventKind::Connection`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection: Option<ConnectionAudit>,
+}
+
+impl RuntimeEvent {
+    pub(crate) fn new(kind: EventKind, box_info: BoxInfo) -> Self {
+        Self {
+            time: Utc::now(),
+            kind,
+            box_info,
+            exec: None,
+            leaks: Vec::new(),
+            connection: None,
+        }
+    }
+}
+
+/// Audit record of a command run in a box.
+///
+/// Environment variables are left out since they often carry secrets.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ExecAudit {
+    /// Set when the command was started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_id: Option<String>,
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    pub tty: bool,
+    /// Started with `exec_detached()`.
+    pub detached: bool,
+    /// Why the command could not be started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Decision on an outbound connection.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectionAudit {
+    pub protocol: NetProtocol,
+    /// Destination, as `ip:port`
+    pub address: String,
+    pub allowed: bool,
+    /// Taken from the box's earlier decisions rather than asked.
+    pub remembered: bool,
+}
+
+/// The configured sinks of a runtime.
+pub(crate) struct EventSinks {
+    workers: Vec<SinkWorker>,
+    dropped: Arc<AtomicU64>,
+    /// Set when the sinks are dropped, so workers stop retrying
+    closing: Arc<AtomicBool>,
+}
+
+struct SinkWorker {
+    name: String,
+    tx: Option<SyncSender<RuntimeEvent>>,
+    thread: Option<JoinHandle<()>>,
+    /// Set while events are being dropped, so the overflow is logged once
+    overflowing: AtomicBool,
+}
+
+impl EventSinks {
+    /// Validate the sink specs and start a worker per sink.
+    ///
+    /// `dropped` counts events that were never delivered.
+    pub(crate) fn new(specs: &[EventSinkSpec], dropped: Arc<AtomicU64>) -> BoxliteResult<Self> {
+        for spec in specs {
+            validate(spec)?;
+        }
+
+        let closing = Arc::new(AtomicBool::new(false));
+        let mut workers = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let name = sink_name(&spec.destination);
+            let (tx, rx) =
+                std::sync::mpsc::sync_channel(spec.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE));
+            let deliverer = Deliverer::new(&spec.destination)?;
+            let max_retries = spec.max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
+            let dropped = Arc::clone(&dropped);
+            let worker_closing = Arc::clone(&closing);
+            let thread_name = name.clone();
+            let thread = std::thread::Builder::new()
+                .name("boxlite-events".into())
+                .spawn(move || {
+                    run_worker(
+                        thread_name,
+                        rx,
+                        deliverer,
+                        max_retries,
+                        dropped,
+                        worker_closing,
+                    )
+                })
+                .map_err(|e| {
+                    BoxliteError::Internal(format!("failed to start event sink worker: {}", e))
+                })?;
+            workers.push(SinkWorker {
+                name,
+                tx: Some(tx),
+                thread: Some(thread),
+                overflowing: AtomicBool::new(false),
+            });
+        }
+
+        Ok(Self {
+            workers,
+            dropped,
+            closing,
+        })
+    }
+
+    /// Whether any sink is configured.
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.workers.is_empty()
+    }
+
+    /// Queue an event for every sink. Never blocks.
+    ///
+    /// The event is only built when a sink is configured.
+    pub(crate) fn emit(&self, make_event: impl FnOnce() -> RuntimeEvent) {
+        if !self.is_enabled() {
+            return;
+        }
+        let event = make_event();
+        for worker in &self.workers {
+            let Some(tx) = &worker.tx else {
+                continue;
+            };
+            match tx.try_send(event.clone()) {
+                Ok(()) => worker.overflowing.store(false, Ordering::Relaxed),
+                Err(e) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    if !worker.overflowing.swap(true, Ordering::Relaxed) {
+                        match e {
+                            TrySendError::Full(_) => tracing::warn!(
+                                sink = %worker.name,
+                                "Event sink buffer full, dropping events"
+                            ),
+                            TrySendError::Disconnected(_) => tracing::warn!(
+                                sink = %worker.name,
+                                "Event sink worker exited, dropping events"
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for EventSinks {
+    fn drop(&mut self) {
+        // Closing the queues makes the workers flush what's left and exit
+        self.closing.store(true, Ordering::Relaxed);
+        for worker in &mut self.workers {
+            worker.tx.take();
+        }
+        let deadline = Instant::now() + FLUSH_TIMEOUT;
+        for worker in &mut self.workers {
+            let Some(thread) = worker.thread.take() else {
+                continue;
+            };
+            while !thread.is_finished() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            if thread.is_finished() {
+                let _ = thread.join();
+            } else {
+                tracing::warn!(sink = %worker.name, "Event sink still delivering, not waiting for it");
+            }
+        }
+    }
+}
+
+fn validate(spec: &EventSinkSpec) -> BoxliteResult<()> {
+    if spec.buffer_size == Some(0) {
+        return Err(BoxliteError::Config(
+            "event sink buffer_size must be greater than 0".into(),
+        ));
+    }
+    match &spec.destination {
+        EventDestination::Webhook { url, .. } => {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(BoxliteError::Config(format!(
+                    "event sink webhook url must be http(s), got: {}",
+                    url
+                )));
+            }
+        }
+        EventDestination::File { path } => {
+            if !path.is_absolute() {
+                return Err(BoxliteError::Config(format!(
+                    "event sink file path must be absolute, got: {}",
+                    path.display()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sink_name(destination: &EventDestination) -> String {
+    match destination {
+        EventDestination::Webhook { url, .. } => url.clone(),
+        EventDestination::File { path } => path.display().to_string(),
+    }
+}
+
+fn run_worker(
+    name: String,
+    rx: Receiver<RuntimeEvent>,
+    mut deliverer: Deliverer,
+    max_retries: u32,
+    dropped: Arc<AtomicU64>,
+    closing: Arc<AtomicBool>,
+) {
+    loop {
+        // Wait for the first event, then give the batch a moment to fill
+        let Ok(first) = rx.recv() else {
+            return;
+        };
+        let mut batch = vec![first];
+        let deadline = Instant::now() + BATCH_WINDOW;
+        let mut closed = false;
+        while batch.len() < MAX_BATCH {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(wait) {
+                Ok(event) => batch.push(event),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        // Once the runtime is gone, make one attempt and don't hold up its drop
+        let retries = if closed { 0 } else { max_retries };
+        if let Err(e) = deliver_with_retry(&mut deliverer, &batch, retries, &closing) {
+            dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+            tracing::warn!(sink = %name, "Dropped {} events: {}", batch.len(), e);
+        }
+        if closed {
+            // Drain anything that was queued behind the batch
+            let rest: Vec<RuntimeEvent> = rx.try_iter().collect();
+            if !rest.is_empty()
+                && let Err(e) = deliverer.deliver(&rest)
+            {
+                dropped.fetch_add(rest.len() as u64, Ordering::Relaxed);
+                tracing::warn!(sink = %name, "Dropped {} events: {}", rest.len(), e);
+            }
+            return;
+        }
+    }
+}
+
+fn deliver_with_retry(
+    deliverer: &mut Deliverer,
+    batch: &[RuntimeEvent],
+    max_retries: u32,
+    closing: &AtomicBool,
+) -> Result<(), String> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match deliverer.deliver(batch) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= max_retries || closing.load(Ordering::Relaxed) => return Err(e),
+            Err(e) => {
+                tracing::debug!("Event delivery failed (attempt {}): {}", attempt + 1, e);
+                // Give up on the backoff as soon as the runtime is gone
+                let retry_at = Instant::now() + backoff;
+                while !closing.load(Ordering::Relaxed) && Instant::now() < retry_at {
+                    std::thread::sleep(
+                        CLOSING_POLL.min(retry_at.saturating_duration_since(Instant::now())),
+                    );
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+enum Deliverer {
+    Webhook {
+        url: String,
+        headers: HashMap<String, String>,
+        client: reqwest::Client,
+        rt: tokio::runtime::Runtime,
+    },
+    File {
+        path: PathBuf,
+    },
+}
+
+impl Deliverer {
+    fn new(destination: &EventDestination) -> BoxliteResult<Self> {
+        match destination {
+            EventDestination::Webhook { url, headers } => {
+                let client = reqwest::Client::builder()
+                    .timeout(WEBHOOK_TIMEOUT)
+                    .build()
+                    .map_err(|e| {
+                        BoxliteError::Internal(format!("failed to build webhook client: {}", e))
+                    })?;
+                // The worker thread has no runtime of its own
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| {
+                        BoxliteError::Internal(format!("failed to build event sink runtime: {}", e))
+                    })?;
+                Ok(Deliverer::Webhook {
+                    url: url.clone(),
+                    headers: headers.clone(),
+                    client,
+                    rt,
+                })
+            }
+            EventDestination::File { path } => Ok(Deliverer::File { path: path.clone() }),
+        }
+    }
+
+    fn deliver(&mut self, batch: &[RuntimeEvent]) -> Result<(), String> {
+        match self {
+            Deliverer::Webhook {
+                url,
+                headers,
+                client,
+                rt,
+            } => {
+                let mut request = client.post(url.as_str()).json(batch);
+                for (key, value) in headers.iter() {
+                    request = request.header(key, value);
+                }
+                let response = rt.block_on(request.send()).map_err(|e| e.to_string())?;
+                if !response.status().is_success() {
+                    return Err(format!("webhook returned {}", response.status()));
+                }
+                Ok(())
+            }
+            Deliverer::File { path } => {
+                let mut lines = Vec::new();
+                for event in batch {
+                    serde_json::to_writer(&mut lines, event).map_err(|e| e.to_string())?;
+                    lines.push(b'\n');
+                }
+                // Reopened per batch so the file can be rotated underneath us
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&*path)
+                    .map_err(|e| format!("failed to open {}: {}", path.display(), e))?;
+                file.write_all(&lines)
+                    .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::types::{BoxID, BoxStatus};
+
+    fn event(kind: EventKind) -> RuntimeEvent {
+        let now = Utc::now();
+        RuntimeEvent::new(
+            kind,
+            BoxInfo {
+                id: BoxID::new(),
+                name: Some("web".into()),
+                project: "default".into(),
+                status: BoxStatus::Running,
+                created_at: now,
+                last_updated: now,
+                pid: None,
+                image: "alpine:latest".into(),
+                cpus: 1,
+                memory_mib: 512,
+                labels: Default::default(),
+                depends_on: Vec::new(),
+                expires_at: None,
+                timings: None,
+                guest_agent: None,
+                warnings: Vec::new(),
+            },
+        )
+    }
+
+    fn file_sink(path: PathBuf) -> EventSinkSpec {
+        EventSinkSpec {
+            destination: EventDestination::File { path },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_file_sink_writes_jsonl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let sinks = EventSinks::new(&[file_sink(path.clone())], Arc::clone(&dropped)).unwrap();
+        sinks.emit(|| event(EventKind::BoxCreated));
+        let mut exec = event(EventKind::Exec);
+        exec.exec = Some(ExecAudit {
+            execution_id: Some("exec-1".into()),
+            command: "echo".into(),
+            args: vec!["hi".into()],
+            ..Default::default()
+        });
+        sinks.emit(|| exec);
+        // Dropping flushes the queue
+        drop(sinks);
+
+        let output = std::fs::read_to_string(&path).unwrap();
+        let events: Vec<RuntimeEvent> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EventKind::BoxCreated);
+        assert!(output.contains("\"kind\":\"box.created\""));
+        assert_eq!(events[1].exec.as_ref().unwrap().args, vec!["hi"]);
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_failed_delivery_is_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory can't be opened for appending
+        let dropped = Arc::new(AtomicU64::new(0));
+        let sinks =
+            EventSinks::new(&[file_sink(dir.path().to_path_buf())], Arc::clone(&dropped)).unwrap();
+        sinks.emit(|| event(EventKind::BoxStopped));
+        drop(sinks);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_drop_does_not_wait_for_retries() {
+        let dir = tempfile::tempdir().unwrap();
+        let dropped = Arc::new(AtomicU64::new(0));
+        let spec = EventSinkSpec {
+            max_retries: Some(10),
+            ..file_sink(dir.path().to_path_buf())
+        };
+        let sinks = EventSinks::new(&[spec], Arc::clone(&dropped)).unwrap();
+        sinks.emit(|| event(EventKind::BoxStopped));
+        // Let the worker fail its first attempt and start backing off
+        std::thread::sleep(BATCH_WINDOW + Duration::from_millis(200));
+
+        let start = Instant::now();
+        drop(sinks);
+        assert!(start.elapsed() < FLUSH_TIMEOUT);
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_invalid_sinks_are_rejected() {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let webhook = EventSinkSpec {
+            destination: EventDestination::Webhook {
+                url: "ftp://example.com".into(),
+                headers: HashMap::new(),
+            },
+            ..Default::default()
+        };
+        for spec in [
+            webhook,
+            file_sink(PathBuf::from("relative.jsonl")),
+            EventSinkSpec {
+                buffer_size: Some(0),
+                ..file_sink(PathBuf::from("/tmp/events.jsonl"))
+            },
+        ] {
+            assert!(matches!(
+                EventSinks::new(&[spec], Arc::clone(&dropped)),
+                Err(BoxliteError::Config(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_spec_deserializes_from_config() {
+        let spec: EventSinkSpec = serde_json::from_str(
+            r#"{"type": "webhook", "url": "https://audit.local/hook",
+                "headers": {"Authorization": "Bearer x"}, "max_retries": 2}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            spec.destination,
+            EventDestination::Webhook { ref url, .. } if url == "https://audit.local/hook"
+        ));
+        assert_eq!(spec.max_retries, Some(2));
+    }
+}
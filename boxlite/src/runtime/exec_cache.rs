@@ -0,0 +1,714 @@
+//! Results of commands cached by the rootfs they ran on.
+//!
+//! `LiteBox::exec_cached()` looks a command up by a key made of the command
+//! line, its env and working directory, the box's env, user and working
+//! directory, and the digest of the container disk it runs on (see
+//! `disk::rootfs_digest`). On a miss the command runs and, if it exited 0,
+//! its output is stored under the digest of the disk before and after the
+//! run, so that an idempotent command that writes to the rootfs (a lock
+//! file, a package cache) hits on its next run too.
+//!
+//! Hashing the disk means pausing the VM, so the digest of each overlay is
+//! kept with the overlay's size and modification time, and reused while
+//! they are unchanged.
+//!
+//! Volumes, the network and the clock are not part of the key: only cache
+//! commands whose result depends on the rootfs alone. Entries are JSON
+//! files in `~/.boxlite/exec-cache`, shared by the boxes of the home, and
+//! are evicted least recently used first once a limit of
+//! `BoxliteOptions.exec_cache` is exceeded.
+
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use filetime::FileTime;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::disk;
+use crate::litebox::{BoxCommand, LiteBox};
+use crate::runtime::fork::PausedVm;
+use crate::runtime::options::StorageDriver;
+use crate::runtime::rt_impl::{RuntimeImpl, flush_guest};
+use crate::runtime::types::BoxStatus;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Limits of the exec cache, enforced after every stored result and by
+/// `BoxliteRuntime::prune_exec_cache()`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecCacheLimits {
+    /// Entries kept.
+    ///
+    /// Default: 1000
+    pub max_entries: usize,
+
+    /// Bytes of entries kept. Larger results are not cached.
+    ///
+    /// Default: 268435456 (256 MiB)
+    pub max_bytes: u64,
+
+    /// Seconds an entry is kept after its last use. `None` keeps entries
+    /// until the other limits evict them.
+    ///
+    /// Default: None
+    pub max_idle_secs: Option<u64>,
+
+    /// Cache results with a non-zero exit code too. Off by default, since
+    /// failures are often transient.
+    ///
+    /// Default: false
+    pub cache_failures: bool,
+}
+
+impl Default for ExecCacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            max_bytes: 256 * 1024 * 1024,
+            max_idle_secs: None,
+            cache_failures: false,
+        }
+    }
+}
+
+/// Output of `LiteBox::exec_cached()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedExecResult {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether the result came from the cache instead of a run
+    pub cached: bool,
+    /// Whether output past `ExecCacheLimits::max_bytes` was dropped. Such
+    /// results are not cached.
+    pub truncated: bool,
+}
+
+impl CachedExecResult {
+    /// Returns true if the exit code was 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// An entry of the exec cache (see `BoxliteRuntime::exec_cache_entries()`).
+#[derive(Clone, Debug, Serialize)]
+pub struct ExecCacheEntry {
+    /// Names the entry for `BoxliteRuntime::remove_exec_cache_entry()`
+    pub key: String,
+    /// Command and arguments
+    pub command: Vec<String>,
+    /// Box the command ran in
+    pub box_id: String,
+    /// Digest of the container disk the entry answers for
+    pub rootfs_digest: String,
+    pub exit_code: i32,
+    /// Size of the entry on disk
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+}
+
+/// A stored result, one JSON file per key.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    command: Vec<String>,
+    box_id: String,
+    rootfs_digest: String,
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+    created_at: DateTime<Utc>,
+}
+
+/// What a result depends on besides the rootfs.
+#[derive(Serialize)]
+struct CommandKey<'a> {
+    command: &'a str,
+    args: &'a [String],
+    env: Option<&'a [(String, String)]>,
+    working_dir: Option<&'a str>,
+    box_env: &'a [(String, String)],
+    box_user: Option<&'a str>,
+    box_working_dir: Option<&'a str>,
+}
+
+impl CommandKey<'_> {
+    fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("command key serializes")
+    }
+}
+
+/// Key of the command encoded as `command` run on the disk with `rootfs_digest`.
+fn cache_key(command: &[u8], rootfs_digest: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(command);
+    hasher.update(rootfs_digest.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// The exec cache of a runtime home.
+#[derive(Clone, Debug)]
+pub(crate) struct ExecCache {
+    dir: PathBuf,
+    limits: ExecCacheLimits,
+    /// Last digest of each overlay, with the state of the file it was taken at
+    digests: Arc<Mutex<HashMap<PathBuf, (DiskGeneration, String)>>>,
+}
+
+/// State of an overlay file; a write to the disk changes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DiskGeneration {
+    ino: u64,
+    len: u64,
+    modified: SystemTime,
+}
+
+impl DiskGeneration {
+    /// Modification times closer to now than this may not change on the
+    /// next write, with coarse filesystem timestamps.
+    const SETTLE: Duration = Duration::from_secs(1);
+
+    fn of(path: &Path) -> BoxliteResult<Self> {
+        let metadata = std::fs::metadata(path).map_err(|e| storage_error(path, e))?;
+        Ok(Self {
+            ino: metadata.ino(),
+            len: metadata.len(),
+            modified: metadata.modified().map_err(|e| storage_error(path, e))?,
+        })
+    }
+
+    /// Whether a later write is sure to change the generation.
+    fn settled(&self) -> bool {
+        SystemTime::now()
+            .duration_since(self.modified)
+            .is_ok_and(|age| age >= Self::SETTLE)
+    }
+}
+
+/// An entry file, as seen by eviction.
+struct EntryFile {
+    key: String,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl ExecCache {
+    pub(crate) fn new(dir: PathBuf, limits: ExecCacheLimits) -> Self {
+        Self {
+            dir,
+            limits,
+            digests: Arc::default(),
+        }
+    }
+
+    /// Digest of `overlay` if it hasn't changed since it was last taken.
+    fn known_digest(&self, overlay: &Path) -> BoxliteResult<Option<String>> {
+        let generation = DiskGeneration::of(overlay)?;
+        let digests = self.digests.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(digests
+            .get(overlay)
+            .filter(|(known, _)| *known == generation)
+            .map(|(_, digest)| digest.clone()))
+    }
+
+    /// Keep the digest of `overlay` taken at `generation`.
+    fn remember_digest(&self, overlay: &Path, generation: DiskGeneration, digest: &str) {
+        let mut digests = self.digests.lock().unwrap_or_else(|e| e.into_inner());
+        if generation.settled() {
+            digests.insert(overlay.to_path_buf(), (generation, digest.to_string()));
+        } else {
+            digests.remove(overlay);
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The result stored under `key`, marked as just used.
+    fn get(&self, key: &str) -> Option<Record> {
+        let path = self.path(key);
+        let content = std::fs::read(&path).ok()?;
+        match serde_json::from_slice(&content) {
+            Ok(record) => {
+                let _ = filetime::set_file_mtime(&path, FileTime::now());
+                Some(record)
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Dropping unreadable exec cache entry: {}", e);
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    fn put(&self, key: &str, record: &Record) -> BoxliteResult<()> {
+        let content = serde_json::to_vec(record)?;
+        if content.len() as u64 > self.limits.max_bytes {
+            tracing::debug!(key, size = content.len(), "Exec result too large to cache");
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir).map_err(|e| storage_error(&self.dir, e))?;
+        // Readers never see a partial entry
+        let tmp = self
+            .dir
+            .join(format!(".{}.{}.tmp", key, std::process::id()));
+        std::fs::write(&tmp, content).map_err(|e| storage_error(&tmp, e))?;
+        std::fs::rename(&tmp, self.path(key)).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            storage_error(&self.path(key), e)
+        })
+    }
+
+    fn files(&self) -> BoxliteResult<Vec<EntryFile>> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(storage_error(&self.dir, e)),
+        };
+        let mut files = Vec::new();
+        for dir_entry in dir.flatten() {
+            let name = dir_entry.file_name();
+            let Some(key) = name.to_str().and_then(|name| name.strip_suffix(".json")) else {
+                continue;
+            };
+            if !valid_key(key) {
+                continue;
+            }
+            let Ok(metadata) = dir_entry.metadata() else {
+                continue;
+            };
+            files.push(EntryFile {
+                key: key.to_string(),
+                size: metadata.len(),
+                last_used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+        // Most recently used first
+        files.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+        Ok(files)
+    }
+
+    /// All entries, most recently used first.
+    pub(crate) fn entries(&self) -> BoxliteResult<Vec<ExecCacheEntry>> {
+        let mut entries = Vec::new();
+        for file in self.files()? {
+            let path = self.path(&file.key);
+            // Removed since listed, or unreadable
+            let Some(record) = std::fs::read(&path)
+                .ok()
+                .and_then(|content| serde_json::from_slice::<Record>(&content).ok())
+            else {
+                continue;
+            };
+            entries.push(ExecCacheEntry {
+                key: file.key,
+                command: record.command,
+                box_id: record.box_id,
+                rootfs_digest: record.rootfs_digest,
+                exit_code: record.exit_code,
+                size_bytes: file.size,
+                created_at: record.created_at,
+                last_used: file.last_used.into(),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Remove the entry `key`; false if there was none.
+    pub(crate) fn remove(&self, key: &str) -> BoxliteResult<bool> {
+        if !valid_key(key) {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "invalid exec cache key {:?}",
+                key
+            )));
+        }
+        let path = self.path(key);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(storage_error(&path, e)),
+        }
+    }
+
+    /// Remove every entry, returning how many there were.
+    pub(crate) fn clear(&self) -> BoxliteResult<usize> {
+        let files = self.files()?;
+        for file in &files {
+            let path = self.path(&file.key);
+            std::fs::remove_file(&path).map_err(|e| storage_error(&path, e))?;
+        }
+        Ok(files.len())
+    }
+
+    /// Evict idle entries, then the least recently used ones until the
+    /// count and size limits are met. Returns how many were removed.
+    pub(crate) fn prune(&self) -> BoxliteResult<usize> {
+        let idle_since = self
+            .limits
+            .max_idle_secs
+            .and_then(|secs| SystemTime::now().checked_sub(Duration::from_secs(secs)));
+
+        let (mut kept, mut kept_bytes, mut removed) = (0usize, 0u64, 0usize);
+        for file in self.files()? {
+            let idle = idle_since.is_some_and(|since| file.last_used < since);
+            let fits =
+                kept < self.limits.max_entries && kept_bytes + file.size <= self.limits.max_bytes;
+            if !idle && fits {
+                kept += 1;
+                kept_bytes += file.size;
+                continue;
+            }
+            match std::fs::remove_file(self.path(&file.key)) {
+                Ok(()) => removed += 1,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(storage_error(&self.path(&file.key), e)),
+            }
+        }
+        if removed > 0 {
+            tracing::debug!(removed, kept, kept_bytes, "Pruned exec cache");
+        }
+        Ok(removed)
+    }
+}
+
+/// Run `command` in `litebox`, or answer it from the cache of `runtime`.
+pub(crate) async fn exec_cached(
+    litebox: &LiteBox,
+    runtime: &RuntimeImpl,
+    command: BoxCommand,
+) -> BoxliteResult<CachedExecResult> {
+    if command.tty || !command.fds.is_empty() {
+        return Err(BoxliteError::InvalidArgument(
+            "cached commands can't use a TTY or passed file descriptors".to_string(),
+        ));
+    }
+    let options = litebox.options();
+    let argv: Vec<String> = std::iter::once(command.command.clone())
+        .chain(command.args.iter().cloned())
+        .collect();
+    let command_key = CommandKey {
+        command: &command.command,
+        args: &command.args,
+        env: command.env.as_deref(),
+        working_dir: command.working_dir.as_deref(),
+        box_env: &options.env,
+        box_user: options.user.as_deref(),
+        box_working_dir: options.working_dir.as_deref(),
+    }
+    .encode();
+    let driver = litebox.config().storage_driver;
+    if driver != StorageDriver::Ext4 {
+        return Err(BoxliteError::Unsupported(format!(
+            "cached execution digests the qcow2 overlay; not available with the {:?} \
+             storage driver",
+            driver
+        )));
+    }
+    let overlay = runtime
+        .layout
+        .box_layout(litebox.id().as_str(), false)?
+        .disk_path();
+    let cache = &runtime.exec_cache;
+
+    let before = rootfs_digest(litebox, cache, &overlay).await?;
+    let key = cache_key(&command_key, &before);
+    let found = {
+        let (cache, key) = (cache.clone(), key.clone());
+        blocking(move || Ok(cache.get(&key))).await?
+    };
+    if let Some(record) = found {
+        tracing::debug!(box_id = %litebox.id(), key = %key, "Exec cache hit");
+        return Ok(CachedExecResult {
+            exit_code: record.exit_code,
+            stdout: record.stdout,
+            stderr: record.stderr,
+            cached: true,
+            truncated: false,
+        });
+    }
+
+    let mut execution = litebox.exec(command).await?;
+    // Cached commands get no input
+    drop(execution.stdin());
+    let (stdout, stderr) = (execution.stdout(), execution.stderr());
+    let limit = cache.limits.max_bytes;
+    let ((stdout, stdout_truncated), (stderr, stderr_truncated)) =
+        tokio::join!(collect(stdout, limit), collect(stderr, limit));
+    let result = execution.wait().await?;
+    let output = CachedExecResult {
+        exit_code: result.exit_code,
+        stdout,
+        stderr,
+        cached: false,
+        truncated: stdout_truncated || stderr_truncated,
+    };
+
+    // Killed by a signal, a limit or its timeout: not the rootfs's doing
+    if result.exit_code < 0 || result.error_message.is_some() || result.limit_exceeded.is_some() {
+        return Ok(output);
+    }
+    if output.exit_code != 0 && !cache.limits.cache_failures {
+        return Ok(output);
+    }
+    if output.truncated {
+        tracing::debug!(box_id = %litebox.id(), "Exec output too large to cache");
+        return Ok(output);
+    }
+    let after = rootfs_digest(litebox, cache, &overlay).await?;
+    let mut digests = vec![before];
+    if after != digests[0] {
+        digests.push(after);
+    }
+    let records: Vec<(String, Record)> = digests
+        .into_iter()
+        .map(|rootfs_digest| {
+            let key = cache_key(&command_key, &rootfs_digest);
+            let record = Record {
+                command: argv.clone(),
+                box_id: litebox.id().as_str().to_string(),
+                rootfs_digest,
+                exit_code: output.exit_code,
+                stdout: output.stdout.clone(),
+                stderr: output.stderr.clone(),
+                created_at: Utc::now(),
+            };
+            (key, record)
+        })
+        .collect();
+    let cache = cache.clone();
+    let box_id = litebox.id().clone();
+    blocking(move || {
+        for (key, record) in &records {
+            if let Err(e) = cache.put(key, record) {
+                tracing::warn!(box_id = %box_id, "Failed to cache exec result: {}", e);
+            }
+        }
+        if let Err(e) = cache.prune() {
+            tracing::warn!("Failed to prune exec cache: {}", e);
+        }
+        Ok(())
+    })
+    .await?;
+    Ok(output)
+}
+
+/// Digest of the box's container disk, with the guest's writes flushed.
+///
+/// The disk is hashed with the VM paused, unless it hasn't changed since
+/// its digest was last taken.
+async fn rootfs_digest(
+    litebox: &LiteBox,
+    cache: &ExecCache,
+    overlay: &Path,
+) -> BoxliteResult<String> {
+    flush_guest(litebox).await?;
+    let info = litebox.info();
+    let pid = match (info.status, info.pid) {
+        (BoxStatus::Running, Some(pid)) => Some(pid),
+        _ => None,
+    };
+    let (cache, overlay) = (cache.clone(), overlay.to_path_buf());
+    blocking(move || {
+        if let Some(digest) = cache.known_digest(&overlay)? {
+            return Ok(digest);
+        }
+        let _paused = pid.map(PausedVm::pause).transpose()?;
+        let generation = DiskGeneration::of(&overlay)?;
+        let digest = disk::rootfs_digest(&overlay)?;
+        cache.remember_digest(&overlay, generation, &digest);
+        Ok(digest)
+    })
+    .await
+}
+
+/// Run blocking cache work off the async runtime.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> BoxliteResult<T> + Send + 'static,
+) -> BoxliteResult<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+}
+
+/// Collect a stream, keeping at most `limit` bytes. Returns whether output
+/// was dropped; the rest of the stream is still drained.
+async fn collect<S: Stream<Item = String> + Unpin>(
+    stream: Option<S>,
+    limit: u64,
+) -> (String, bool) {
+    let Some(mut stream) = stream else {
+        return (String::new(), false);
+    };
+    let (mut output, mut truncated) = (String::new(), false);
+    while let Some(chunk) = stream.next().await {
+        if truncated {
+            continue;
+        }
+        if (output.len() + chunk.len()) as u64 > limit {
+            truncated = true;
+            continue;
+        }
+        output.push_str(&chunk);
+    }
+    (output, truncated)
+}
+
+/// Keys are hex SHA-256 digests, which also keeps them inside the directory.
+fn valid_key(key: &str) -> bool {
+    key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn storage_error(path: &Path, e: std::io::Error) -> BoxliteError {
+    BoxliteError::Storage(format!("exec cache {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(stdout: &str) -> Record {
+        Record {
+            command: vec!["true".to_string()],
+            box_id: "box".to_string(),
+            rootfs_digest: "0".repeat(64),
+            exit_code: 0,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn key(n: u8) -> String {
+        hex::encode([n; 32])
+    }
+
+    #[test]
+    fn test_command_key_covers_command_and_rootfs() {
+        let args = vec!["install".to_string()];
+        let command = CommandKey {
+            command: "npm",
+            args: &args,
+            env: None,
+            working_dir: None,
+            box_env: &[],
+            box_user: None,
+            box_working_dir: None,
+        };
+        let key = cache_key(&command.encode(), "a");
+        assert!(valid_key(&key));
+        assert_eq!(cache_key(&command.encode(), "a"), key);
+        assert_ne!(cache_key(&command.encode(), "b"), key);
+        let other = CommandKey {
+            working_dir: Some("/app"),
+            ..command
+        };
+        assert_ne!(cache_key(&other.encode(), "a"), key);
+    }
+
+    #[test]
+    fn test_put_get_and_manage() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ExecCache::new(dir.path().join("exec-cache"), ExecCacheLimits::default());
+        assert!(cache.get(&key(1)).is_none());
+        assert!(cache.entries().unwrap().is_empty());
+
+        cache.put(&key(1), &record("one")).unwrap();
+        cache.put(&key(2), &record("two")).unwrap();
+        assert_eq!(cache.get(&key(1)).unwrap().stdout, "one");
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, vec!["true".to_string()]);
+
+        assert!(cache.remove(&key(1)).unwrap());
+        assert!(!cache.remove(&key(1)).unwrap());
+        assert!(cache.remove("../boxes").is_err());
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = ExecCacheLimits {
+            max_entries: 2,
+            ..Default::default()
+        };
+        let cache = ExecCache::new(dir.path().to_path_buf(), limits);
+        for n in 1..=3 {
+            cache.put(&key(n), &record("out")).unwrap();
+            let used = FileTime::from_unix_time(1_000_000 + n as i64, 0);
+            filetime::set_file_mtime(cache.path(&key(n)), used).unwrap();
+        }
+        // Using the oldest entry keeps it
+        cache.get(&key(1)).unwrap();
+
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert!(cache.get(&key(2)).is_none());
+        assert!(cache.get(&key(1)).is_some());
+        assert!(cache.get(&key(3)).is_some());
+    }
+
+    #[test]
+    fn test_prune_evicts_idle_and_oversized() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = ExecCacheLimits {
+            max_idle_secs: Some(3600),
+            ..Default::default()
+        };
+        let cache = ExecCache::new(dir.path().to_path_buf(), limits);
+        cache.put(&key(1), &record("old")).unwrap();
+        filetime::set_file_mtime(cache.path(&key(1)), FileTime::from_unix_time(0, 0)).unwrap();
+        cache.put(&key(2), &record("new")).unwrap();
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert_eq!(cache.entries().unwrap().len(), 1);
+
+        let small = ExecCache::new(
+            dir.path().to_path_buf(),
+            ExecCacheLimits {
+                max_bytes: 16,
+                ..Default::default()
+            },
+        );
+        small.put(&key(3), &record("too large")).unwrap();
+        assert!(small.get(&key(3)).is_none());
+    }
+
+    #[test]
+    fn test_digest_reused_until_disk_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ExecCache::new(dir.path().join("exec-cache"), ExecCacheLimits::default());
+        let overlay = dir.path().join("disk.qcow2");
+        std::fs::write(&overlay, b"clusters").unwrap();
+
+        // Just written: a write in the same timestamp tick could go unseen
+        cache.remember_digest(&overlay, DiskGeneration::of(&overlay).unwrap(), "a");
+        assert_eq!(cache.known_digest(&overlay).unwrap(), None);
+
+        filetime::set_file_mtime(&overlay, FileTime::from_unix_time(1_000_000, 0)).unwrap();
+        cache.remember_digest(&overlay, DiskGeneration::of(&overlay).unwrap(), "a");
+        assert_eq!(cache.known_digest(&overlay).unwrap().as_deref(), Some("a"));
+
+        std::fs::write(&overlay, b"more clusters").unwrap();
+        assert_eq!(cache.known_digest(&overlay).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_collect_bounds_output() {
+        let chunks = || futures::stream::iter(["abc".to_string(), "def".to_string()]);
+        assert_eq!(
+            collect(Some(chunks()), 6).await,
+            ("abcdef".to_string(), false)
+        );
+        assert_eq!(collect(Some(chunks()), 4).await, ("abc".to_string(), true));
+        let none = None::<futures::stream::Empty<String>>;
+        assert_eq!(collect(none, 4).await, (String::new(), false));
+    }
+}
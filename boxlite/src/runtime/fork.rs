@@ -0,0 +1,150 @@
+//! Disk copying behind `BoxliteRuntime::fork`.
+//!
+//! A box's state between restarts lives in its qcow2 overlays: the container
+//! rootfs disk and the guest rootfs disk. Both are backed by shared base
+//! images, so a fork only copies the overlays. Copies use reflinks
+//! (APFS clonefile, btrfs/XFS reflink) where the filesystem supports them.
+//! With the reflink storage driver the container disk is a raw reflink copy
+//! of the base image instead, and is reflinked again.
+//!
+//! A running box keeps writing to its overlays, so the VM is paused with
+//! SIGSTOP while they are copied.
+
+use std::path::Path;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Container rootfs overlay (see `BoxFilesystemLayout::disk_path`).
+const CONTAINER_DISK: &str = "disk.qcow2";
+
+/// Raw container disk of the reflink storage driver
+/// (see `BoxFilesystemLayout::raw_disk_path`).
+const RAW_CONTAINER_DISK: &str = "disk.raw";
+
+/// Guest rootfs overlay.
+const GUEST_DISK: &str = "guest-rootfs.qcow2";
+
+/// Copy the disk overlays of the box at `src_home` into `dst_home`.
+///
+/// Fails when the box has no container disk, i.e. it never started.
+pub(crate) fn copy_disks(src_home: &Path, dst_home: &Path) -> BoxliteResult<()> {
+    if !src_home.join(CONTAINER_DISK).exists() && !src_home.join(RAW_CONTAINER_DISK).exists() {
+        return Err(BoxliteError::InvalidState(
+            "box has no disk to fork; start it first".to_string(),
+        ));
+    }
+    std::fs::create_dir_all(dst_home)
+        .map_err(|e| BoxliteError::Storage(format!("failed to create box dir: {e}")))?;
+
+    for name in [CONTAINER_DISK, RAW_CONTAINER_DISK, GUEST_DISK] {
+        let src = src_home.join(name);
+        if src.exists() {
+            clone_file(&src, &dst_home.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy a file, sharing its blocks with the original where possible.
+#[cfg(target_os = "macos")]
+fn clone_file(src: &Path, dst: &Path) -> BoxliteResult<()> {
+    // std::fs::copy uses clonefile on APFS
+    std::fs::copy(src, dst).map(|_| ()).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "failed to copy {} to {}: {}",
+            src.display(),
+            dst.display(),
+            e
+        ))
+    })
+}
+
+/// Copy a file, sharing its blocks with the original where possible.
+#[cfg(not(target_os = "macos"))]
+fn clone_file(src: &Path, dst: &Path) -> BoxliteResult<()> {
+    use std::process::Command;
+
+    // cp keeps the overlays sparse when the filesystem can't reflink
+    let output = Command::new("cp")
+        .args(["--reflink=auto", "--sparse=always", "--"])
+        .arg(src)
+        .arg(dst)
+        .output()
+        .map_err(|e| BoxliteError::Storage(format!("Failed to execute cp: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(BoxliteError::Storage(format!(
+            "cp {} -> {} failed: {}",
+            src.display(),
+            dst.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// A VM process stopped with SIGSTOP; continued when dropped.
+pub(crate) struct PausedVm {
+    pid: u32,
+}
+
+impl PausedVm {
+    pub(crate) fn pause(pid: u32) -> BoxliteResult<Self> {
+        if unsafe { libc::kill(pid as i32, libc::SIGSTOP) } != 0 {
+            return Err(BoxliteError::Internal(format!(
+                "failed to pause VM process {}: {}",
+                pid,
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(Self { pid })
+    }
+}
+
+impl Drop for PausedVm {
+    fn drop(&mut self) {
+        if unsafe { libc::kill(self.pid as i32, libc::SIGCONT) } != 0 {
+            tracing::warn!(
+                pid = self.pid,
+                error = %std::io::Error::last_os_error(),
+                "Failed to resume paused VM process"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_disks() {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dst) = (dir.path().join("src"), dir.path().join("dst"));
+        std::fs::create_dir(&src).unwrap();
+
+        assert!(matches!(
+            copy_disks(&src, &dst),
+            Err(BoxliteError::InvalidState(_))
+        ));
+
+        std::fs::write(src.join(CONTAINER_DISK), b"rootfs").unwrap();
+        std::fs::write(src.join("console.log"), b"log").unwrap();
+        copy_disks(&src, &dst).unwrap();
+
+        assert_eq!(std::fs::read(dst.join(CONTAINER_DISK)).unwrap(), b"rootfs");
+        assert!(!dst.join(GUEST_DISK).exists());
+        assert!(!dst.join("console.log").exists());
+
+        // Reflink storage driver: raw container disk only
+        let (raw_src, raw_dst) = (dir.path().join("raw_src"), dir.path().join("raw_dst"));
+        std::fs::create_dir(&raw_src).unwrap();
+        std::fs::write(raw_src.join(RAW_CONTAINER_DISK), b"raw").unwrap();
+        copy_disks(&raw_src, &raw_dst).unwrap();
+        assert_eq!(
+            std::fs::read(raw_dst.join(RAW_CONTAINER_DISK)).unwrap(),
+            b"raw"
+        );
+        assert!(!raw_dst.join(CONTAINER_DISK).exists());
+    }
+}
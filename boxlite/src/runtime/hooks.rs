@@ -0,0 +1,292 @@
+//! Host-side lifecycle hooks.
+//!
+//! Runs the commands configured in `BoxliteOptions.hooks` around box
+//! lifecycle events. Each hook gets the box's `BoxInfo` as JSON on stdin and
+//! `BOXLITE_HOOK=<event>` in its environment, and is killed when it exceeds
+//! its timeout.
+//!
+//! Hooks are run synchronously; async callers should use `spawn_blocking`.
+
+use crate::runtime::options::HookSpec;
+use crate::runtime::types::BoxInfo;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Timeout for hooks that don't set `timeout_secs`.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a running hook is polled for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long to keep reading a failed hook's stderr after it exits, in case
+/// a process it left behind still holds the pipe.
+const STDERR_GRACE: Duration = Duration::from_secs(1);
+
+/// Time all hooks of an event run by `run_hooks_logged` get together, so
+/// they can't hold up the operation that triggered them indefinitely.
+const LOGGED_HOOKS_DEADLINE: Duration = Duration::from_secs(60);
+
+/// Box lifecycle event a hook runs for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HookEvent {
+    PreCreate,
+    PostStart,
+    PreStop,
+    PostRemove,
+}
+
+impl HookEvent {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::PreCreate => "pre_create",
+            HookEvent::PostStart => "post_start",
+            HookEvent::PreStop => "pre_stop",
+            HookEvent::PostRemove => "post_remove",
+        }
+    }
+}
+
+/// Run hooks in order, stopping at the first failure.
+pub(crate) fn run_hooks(event: HookEvent, hooks: &[HookSpec], info: &BoxInfo) -> BoxliteResult<()> {
+    if hooks.is_empty() {
+        return Ok(());
+    }
+    let input = serde_json::to_vec(info)
+        .map_err(|e| BoxliteError::Internal(format!("failed to serialize box info: {}", e)))?;
+    for hook in hooks {
+        run_hook(event, hook, &input, None)?;
+    }
+    Ok(())
+}
+
+/// Run every hook, logging failures instead of returning them.
+///
+/// For events whose operation has already happened or must not be blocked.
+pub(crate) fn run_hooks_logged(event: HookEvent, hooks: &[HookSpec], info: &BoxInfo) {
+    if hooks.is_empty() {
+        return;
+    }
+    let input = match serde_json::to_vec(info) {
+        Ok(input) => input,
+        Err(e) => {
+            tracing::warn!(box_id = %info.id, "Failed to serialize box info for hooks: {}", e);
+            return;
+        }
+    };
+    let deadline = Instant::now() + LOGGED_HOOKS_DEADLINE;
+    for hook in hooks {
+        if let Err(e) = run_hook(event, hook, &input, Some(deadline)) {
+            tracing::warn!(box_id = %info.id, "{}", e);
+        }
+    }
+}
+
+/// Run one hook, killing it after its timeout or at `deadline`, whichever
+/// comes first.
+fn run_hook(
+    event: HookEvent,
+    hook: &HookSpec,
+    input: &[u8],
+    deadline: Option<Instant>,
+) -> BoxliteResult<()> {
+    let name = format!("{} hook {}", event.as_str(), hook.path.display());
+    let mut timeout = hook
+        .timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TIMEOUT);
+    if let Some(deadline) = deadline {
+        timeout = timeout.min(deadline.saturating_duration_since(Instant::now()));
+        if timeout.is_zero() {
+            return Err(BoxliteError::Execution(format!(
+                "{} skipped: earlier {} hooks used up their {}s",
+                name,
+                event.as_str(),
+                LOGGED_HOOKS_DEADLINE.as_secs()
+            )));
+        }
+    }
+
+    let mut cmd = Command::new(&hook.path);
+    cmd.args(&hook.args)
+        .env("BOXLITE_HOOK", event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    for var in &hook.env {
+        match var.split_once('=') {
+            Some((key, value)) => cmd.env(key, value),
+            None => cmd.env(var, ""),
+        };
+    }
+
+    tracing::debug!("Running {}", name);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| BoxliteError::Execution(format!("failed to run {}: {}", name, e)))?;
+
+    // Feed stdin and drain stderr on threads so neither pipe can block the hook
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_vec();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+    let stderr = child.stderr.take().map(|mut stderr| {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            let _ = tx.send(output);
+        });
+        rx
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(BoxliteError::Execution(format!(
+                    "{} timed out after {}s",
+                    name,
+                    timeout.as_secs()
+                )));
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                return Err(BoxliteError::Execution(format!(
+                    "failed to wait for {}: {}",
+                    name, e
+                )));
+            }
+        }
+    };
+
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = stderr
+        .and_then(|output| output.recv_timeout(STDERR_GRACE).ok())
+        .unwrap_or_default();
+    Err(BoxliteError::Execution(format!(
+        "{} failed ({}): {}",
+        name,
+        status,
+        stderr.trim()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::types::{BoxID, BoxStatus};
+
+    fn info() -> BoxInfo {
+        BoxInfo {
+            id: BoxID::new(),
+            name: Some("web".into()),
+            project: "default".into(),
+            status: BoxStatus::Configured,
+            created_at: chrono::Utc::now(),
+            last_updated: chrono::Utc::now(),
+            pid: None,
+            image: "alpine:latest".into(),
+            cpus: 2,
+            memory_mib: 512,
+            labels: Default::default(),
+            depends_on: Vec::new(),
+            expires_at: None,
+            timings: None,
+            guest_agent: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn sh(script: &str, timeout_secs: Option<u64>) -> HookSpec {
+        HookSpec {
+            path: "/bin/sh".into(),
+            args: vec!["-c".into(), script.into()],
+            env: vec!["GREETING=hello".into()],
+            timeout_secs,
+        }
+    }
+
+    #[test]
+    fn test_hook_receives_info_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let script = format!(
+            "cat > {0}; echo \"$BOXLITE_HOOK $GREETING\" >> {0}",
+            out.display()
+        );
+
+        run_hooks(HookEvent::PostStart, &[sh(&script, None)], &info()).unwrap();
+
+        let output = std::fs::read_to_string(&out).unwrap();
+        assert!(output.contains("\"status\":\"configured\""));
+        assert!(output.ends_with("post_start hello\n"));
+    }
+
+    #[test]
+    fn test_failing_hook_stops_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let hooks = [
+            sh("echo denied >&2; exit 3", None),
+            sh(&format!("touch {}", marker.display()), None),
+        ];
+
+        let err = run_hooks(HookEvent::PreCreate, &hooks, &info()).unwrap_err();
+        assert!(err.to_string().contains("denied"));
+        assert!(!marker.exists());
+
+        run_hooks_logged(HookEvent::PostRemove, &hooks, &info());
+        assert!(marker.exists());
+    }
+
+    #[test]
+    fn test_failed_hook_does_not_wait_for_its_children() {
+        let start = Instant::now();
+        // The background sleep keeps stderr open after the hook exits
+        let hook = sh("sleep 10 & echo denied >&2; exit 1", None);
+        let err = run_hooks(HookEvent::PreCreate, &[hook], &info()).unwrap_err();
+        assert!(err.to_string().contains("failed"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_logged_hooks_share_a_deadline() {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let start = Instant::now();
+        let err = run_hook(
+            HookEvent::PostRemove,
+            &sh("sleep 10", None),
+            b"{}",
+            Some(deadline),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let err = run_hook(
+            HookEvent::PostRemove,
+            &sh("true", None),
+            b"{}",
+            Some(deadline),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("skipped"));
+    }
+
+    #[test]
+    fn test_hook_timeout() {
+        let start = Instant::now();
+        let err = run_hooks(HookEvent::PreStop, &[sh("sleep 10", Some(1))], &info()).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -41,6 +41,15 @@ pub mod dirs {
 
     /// Subdirectory for per-entity locks
     pub const LOCKS_DIR: &str = "locks";
+
+    /// Subdirectory for persistent per-name box caches
+    pub const CACHE_DIR: &str = "cache";
+
+    /// Subdirectory for boot diagnostic bundles
+    pub const DIAGNOSTICS_DIR: &str = "diagnostics";
+
+    /// Subdirectory for cached exec results
+    pub const EXEC_CACHE_DIR: &str = "exec-cache";
 }
 
 /// Configuration for filesystem layout behavior.
@@ -95,6 +104,11 @@ impl FilesystemLayout {
         &self.home_dir
     }
 
+    /// Whether `isolate_mounts` can be honored on this host.
+    pub fn is_bind_mount_supported(&self) -> bool {
+        self.config.is_bind_mount_supported()
+    }
+
     pub fn db_dir(&self) -> PathBuf {
         self.home_dir.join(dirs::DB_DIR)
     }
@@ -131,6 +145,30 @@ impl FilesystemLayout {
         self.home_dir.join(dirs::LOCKS_DIR)
     }
 
+    /// Persistent box caches: ~/.boxlite/cache
+    ///
+    /// Each named box using `persistent_cache` gets `cache/<name>/`, which is
+    /// kept when the box is removed.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.home_dir.join(dirs::CACHE_DIR)
+    }
+
+    /// Boot diagnostic bundles: ~/.boxlite/diagnostics
+    ///
+    /// Holds `{box_id}-{timestamp}.tar.gz` archives collected when a box
+    /// fails to boot. They are never removed automatically.
+    pub fn diagnostics_dir(&self) -> PathBuf {
+        self.home_dir.join(dirs::DIAGNOSTICS_DIR)
+    }
+
+    /// Cached exec results: ~/.boxlite/exec-cache
+    ///
+    /// One `{key}.json` file per result of `LiteBox::exec_cached()`, shared
+    /// by the boxes of this home. The file's mtime is when it was last used.
+    pub fn exec_cache_dir(&self) -> PathBuf {
+        self.home_dir.join(dirs::EXEC_CACHE_DIR)
+    }
+
     /// Temporary directory for transient files: ~/.boxlite/tmp
     /// Used for disk image creation and other operations that need
     /// temp files on the same filesystem as the final destination.
@@ -222,6 +260,7 @@ impl FilesystemLayout {
 /// │           │   └── work/   # Overlayfs work
 /// │           └── rootfs/     # Final rootfs (overlayfs merged)
 /// ├── shared/             # Guest-visible (ro bind mount → mounts/)
+/// ├── exec-logs/{exec_id}/  # Recorded exec output (rw share, written by guest)
 /// ├── root.qcow2          # Data disk
 /// └── console.log         # Kernel/init output
 /// ```
@@ -258,6 +297,9 @@ impl BoxFilesystemLayout {
     // ========================================================================
 
     /// Sockets directory: ~/.boxlite/boxes/{box_id}/sockets
+    ///
+    /// Default location only: boxes with too deep a home keep their sockets
+    /// elsewhere (see `BoxConfig.transport`).
     pub fn sockets_dir(&self) -> PathBuf {
         self.box_dir.join(dirs::SOCKETS_DIR)
     }
@@ -316,6 +358,39 @@ impl BoxFilesystemLayout {
         self.box_dir.join(shared_dirs::SHARED)
     }
 
+    // ========================================================================
+    // EXECUTION LOGS
+    // ========================================================================
+
+    /// Execution logs directory: ~/.boxlite/boxes/{box_id}/exec-logs
+    ///
+    /// Shared read-write with the guest (tag EXEC_LOGS), which writes the
+    /// recorded output of executions here.
+    pub fn exec_logs_dir(&self) -> PathBuf {
+        self.box_dir.join(shared_dirs::EXEC_LOGS)
+    }
+
+    /// Recorded output of one execution: ~/.boxlite/boxes/{box_id}/exec-logs/{execution_id}
+    pub fn exec_log_dir(&self, execution_id: &str) -> PathBuf {
+        self.exec_logs_dir().join(execution_id)
+    }
+
+    /// Recording proxy directory: ~/.boxlite/boxes/{box_id}/recorder
+    ///
+    /// gvproxy writes the CA certificate of the box's HTTP recorder here,
+    /// shared read-only with the container (see `net::recorder`).
+    pub fn recorder_dir(&self) -> PathBuf {
+        self.box_dir.join("recorder")
+    }
+
+    /// Network peers file: ~/.boxlite/boxes/{box_id}/network-peers.json
+    ///
+    /// Addresses and published ports of the boxes sharing a user-defined
+    /// network with this one, read by gvproxy (see `runtime::networks`).
+    pub fn network_peers_path(&self) -> PathBuf {
+        self.box_dir.join("network-peers.json")
+    }
+
     // ========================================================================
     // DISK AND CONSOLE
     // ========================================================================
@@ -325,6 +400,17 @@ impl BoxFilesystemLayout {
         self.box_dir.join("disk.qcow2")
     }
 
+    /// Raw container disk path of the reflink storage driver:
+    /// ~/.boxlite/boxes/{box_id}/disk.raw
+    pub fn raw_disk_path(&self) -> PathBuf {
+        self.box_dir.join("disk.raw")
+    }
+
+    /// Guest rootfs overlay path: ~/.boxlite/boxes/{box_id}/guest-rootfs.qcow2
+    pub fn guest_disk_path(&self) -> PathBuf {
+        self.box_dir.join("guest-rootfs.qcow2")
+    }
+
     /// Console output path: ~/.boxlite/boxes/{box_id}/console.log
     ///
     /// Captures kernel and init output for debugging.
@@ -332,6 +418,14 @@ impl BoxFilesystemLayout {
         self.box_dir.join("console.log")
     }
 
+    /// Shim stderr path: ~/.boxlite/boxes/{box_id}/shim.stderr
+    ///
+    /// Captures hypervisor output that happens before (or instead of) the
+    /// shim's own tracing, e.g. libkrun failing to start the VM.
+    pub fn shim_stderr_path(&self) -> PathBuf {
+        self.box_dir.join("shim.stderr")
+    }
+
     /// PID file path: ~/.boxlite/boxes/{box_id}/shim.pid
     ///
     /// Written by the shim process in pre_exec (after fork, before exec).
@@ -350,6 +444,7 @@ impl BoxFilesystemLayout {
     /// Creates:
     /// - sockets/
     /// - mounts/ (via SharedGuestLayout base)
+    /// - exec-logs/
     ///
     /// Note: shared/ is NOT created here - it will be created as a bind mount
     /// (Linux) or symlink (macOS) in the filesystem stage.
@@ -363,6 +458,9 @@ impl BoxFilesystemLayout {
         std::fs::create_dir_all(self.mounts_dir())
             .map_err(|e| BoxliteError::Storage(format!("failed to create mounts dir: {e}")))?;
 
+        std::fs::create_dir_all(self.exec_logs_dir())
+            .map_err(|e| BoxliteError::Storage(format!("failed to create exec-logs dir: {e}")))?;
+
         // shared/ is created by create_bind_mount() - don't create it here
         // On Linux: bind mount from mounts/
         // On macOS: symlink to mounts/
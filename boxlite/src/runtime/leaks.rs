@@ -0,0 +1,196 @@
+//! Leftovers of removed boxes.
+//!
+//! Removing a box deletes its database rows, its home directory (disks,
+//! logs, sockets) and a socket directory placed outside the home. A failed
+//! step is only logged, so after removal the runtime looks for what is left,
+//! removes it again and reports it as an `EventKind::CleanupLeak` event.
+//! [`BoxliteRuntime::find_leaks`](crate::BoxliteRuntime::find_leaks) looks
+//! for leftovers of every box no longer in the database.
+
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::layout::{dirs, shared_dirs};
+
+/// What was left behind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeakKind {
+    /// Unix socket
+    Socket,
+    /// Disk image (qcow2, raw, ext4)
+    Disk,
+    /// Console, shim or execution log
+    Log,
+    /// Any other file
+    File,
+    /// Directory with no files left in it
+    Directory,
+    /// Database row
+    DbRow,
+}
+
+/// An artifact of a removed box that was still there.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupLeak {
+    /// Box the artifact belonged to
+    pub box_id: String,
+    pub kind: LeakKind,
+    /// Path on the host, or the table of a database row
+    pub location: String,
+    /// Whether the leftover was removed once found
+    pub cleaned: bool,
+}
+
+impl CleanupLeak {
+    pub(crate) fn db_row(box_id: &str, table: &str) -> Self {
+        Self {
+            box_id: box_id.to_string(),
+            kind: LeakKind::DbRow,
+            location: table.to_string(),
+            cleaned: false,
+        }
+    }
+}
+
+/// Files left under `dir`, or `dir` itself if it only holds directories.
+///
+/// Empty if `dir` doesn't exist. Symlinks are reported, not followed.
+pub(crate) fn scan_dir(box_id: &str, dir: &Path) -> Vec<CleanupLeak> {
+    if std::fs::symlink_metadata(dir).is_err() {
+        return Vec::new();
+    }
+    let mut files = Vec::new();
+    collect_files(dir, &mut files);
+
+    let leak = |kind, path: &Path| CleanupLeak {
+        box_id: box_id.to_string(),
+        kind,
+        location: path.display().to_string(),
+        cleaned: false,
+    };
+    if files.is_empty() {
+        return vec![leak(LeakKind::Directory, dir)];
+    }
+    files
+        .iter()
+        .map(|(path, is_socket)| leak(classify(path, *is_socket), path))
+        .collect()
+}
+
+/// Remove `dir` and everything in it, making read-only directories
+/// writable if a first attempt fails. Returns whether it is gone.
+pub(crate) fn force_remove_dir(dir: &Path) -> bool {
+    if std::fs::remove_dir_all(dir).is_ok() || !dir.exists() {
+        return true;
+    }
+    make_writable(dir);
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(path = %dir.display(), error = %e, "Failed to remove leftover directory");
+            false
+        }
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, bool)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_files(&entry.path(), files);
+        } else {
+            files.push((entry.path(), file_type.is_socket()));
+        }
+    }
+}
+
+fn classify(path: &Path, is_socket: bool) -> LeakKind {
+    if is_socket {
+        return LeakKind::Socket;
+    }
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("qcow2" | "raw" | "ext4" | "img") => return LeakKind::Disk,
+        Some("log") => return LeakKind::Log,
+        _ => {}
+    }
+    let in_logs = path
+        .components()
+        .any(|c| c.as_os_str() == dirs::LOGS_DIR || c.as_os_str() == shared_dirs::EXEC_LOGS);
+    if in_logs {
+        LeakKind::Log
+    } else {
+        LeakKind::File
+    }
+}
+
+fn make_writable(dir: &Path) {
+    if let Ok(meta) = std::fs::symlink_metadata(dir)
+        && meta.is_dir()
+    {
+        let mut perms = meta.permissions();
+        perms.set_mode(perms.mode() | 0o700);
+        let _ = std::fs::set_permissions(dir, perms);
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                make_writable(&entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_classifies_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("box");
+        std::fs::create_dir_all(home.join("disks")).unwrap();
+        std::fs::create_dir_all(home.join("exec-logs/exec-1")).unwrap();
+        std::fs::write(home.join("disks/disk.qcow2"), "").unwrap();
+        std::fs::write(home.join("exec-logs/exec-1/stdout"), "").unwrap();
+        std::fs::write(home.join("console.log"), "").unwrap();
+        std::os::unix::net::UnixListener::bind(home.join("box.sock")).unwrap();
+
+        let mut kinds: Vec<_> = scan_dir("box-1", &home)
+            .into_iter()
+            .map(|l| l.kind)
+            .collect();
+        kinds.sort_by_key(|k| format!("{:?}", k));
+        assert_eq!(
+            kinds,
+            vec![
+                LeakKind::Disk,
+                LeakKind::Log,
+                LeakKind::Log,
+                LeakKind::Socket
+            ]
+        );
+
+        assert!(force_remove_dir(&home));
+        assert!(scan_dir("box-1", &home).is_empty());
+    }
+
+    #[test]
+    fn test_force_remove_read_only_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("box");
+        std::fs::create_dir_all(home.join("sockets")).unwrap();
+        std::fs::write(home.join("sockets/stale"), "").unwrap();
+        std::fs::set_permissions(home.join("sockets"), std::fs::Permissions::from_mode(0o500))
+            .unwrap();
+
+        assert_eq!(scan_dir("box-1", &home)[0].kind, LeakKind::File);
+        assert!(force_remove_dir(&home));
+        assert!(!home.exists());
+    }
+}
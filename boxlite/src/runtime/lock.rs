@@ -85,6 +85,15 @@ impl RuntimeLock {
             compile_error!("Windows file locking not yet implemented");
         }
 
+        // Record the holder for `holder_pid()`
+        {
+            use std::io::Write;
+            let mut file = &file;
+            let _ = file
+                .set_len(0)
+                .and_then(|_| write!(file, "{}", std::process::id()));
+        }
+
         tracing::debug!(lock_path = %lock_path.display(), "Acquired runtime lock");
 
         Ok(RuntimeLock {
@@ -97,6 +106,22 @@ impl RuntimeLock {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// PID of the process holding the lock on `home_dir`, if it is held.
+    pub fn holder_pid(home_dir: &Path) -> Option<u32> {
+        use std::io::Read;
+        use std::os::unix::io::AsRawFd;
+
+        let mut file = File::open(home_dir.join(".lock")).ok()?;
+        // Probe with a shared lock: it only fails while someone holds the lock
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_SH | libc::LOCK_NB) } == 0 {
+            unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) };
+            return None;
+        }
+        let mut pid = String::new();
+        file.read_to_string(&mut pid).ok()?;
+        pid.trim().parse().ok()
+    }
 }
 
 impl Drop for RuntimeLock {
@@ -148,6 +173,21 @@ mod tests {
         assert!(err_msg.contains("Another BoxliteRuntime"));
     }
 
+    #[test]
+    fn test_holder_pid() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(RuntimeLock::holder_pid(temp_dir.path()), None);
+
+        let lock = RuntimeLock::acquire(temp_dir.path()).unwrap();
+        assert_eq!(
+            RuntimeLock::holder_pid(temp_dir.path()),
+            Some(std::process::id())
+        );
+
+        drop(lock);
+        assert_eq!(RuntimeLock::holder_pid(temp_dir.path()), None);
+    }
+
     #[test]
     fn test_lock_released_on_drop() {
         let temp_dir = TempDir::new().unwrap();
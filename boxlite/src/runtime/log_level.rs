@@ -0,0 +1,173 @@
+//! Changing the log filter of a running runtime.
+//!
+//! `BoxliteRuntime::set_log_level()` swaps the `EnvFilter` of the subscriber
+//! installed by `init_logging_for()`, without a restart. Another process does
+//! the same through `request_log_level()` (`boxlite debug set-log-level`): it
+//! writes the filter to `{home}/logs/log-level` and sends SIGUSR1 to the
+//! runtime, which applies the file.
+//!
+//! Only the default runtime (`BoxliteRuntime::default_runtime()`) handles
+//! SIGUSR1, as it also owns SIGTERM/SIGINT. It records its PID in
+//! `{home}/logs/log-level.pid`; other processes are never signalled, since
+//! SIGUSR1 would terminate them.
+
+use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+use crate::runtime::layout::dirs;
+use crate::runtime::lock::RuntimeLock;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Filter handle of the subscriber installed by `init_logging_for()`.
+pub(crate) static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Filter requested by `request_log_level()`, in the logs directory.
+const LEVEL_FILE: &str = "log-level";
+
+/// PID of the process handling SIGUSR1, in the logs directory.
+const PID_FILE: &str = "log-level.pid";
+
+/// Whether the SIGUSR1 listener runs (install only once).
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// Replace the log filter of this process (`RUST_LOG` syntax).
+pub(crate) fn set_filter(filter: &str) -> BoxliteResult<()> {
+    let env_filter = parse(filter)?;
+    let handle = LOG_FILTER.get().ok_or_else(|| {
+        BoxliteError::Unsupported(
+            "logging is set up by the application, not boxlite; \
+             change the filter of its tracing subscriber instead"
+                .to_string(),
+        )
+    })?;
+    handle
+        .reload(env_filter)
+        .map_err(|e| BoxliteError::Internal(format!("failed to reload log filter: {}", e)))?;
+    tracing::info!(filter, "Log filter changed");
+    Ok(())
+}
+
+/// Ask the default runtime using `home_dir`, in another process, to switch
+/// to `filter`. Returns the PID of the signalled process.
+///
+/// Fails with `NotFound` when no such runtime is running.
+pub fn request_log_level(home_dir: &Path, filter: &str) -> BoxliteResult<u32> {
+    parse(filter)?;
+    let logs_dir = home_dir.join(dirs::LOGS_DIR);
+    let pid = listener_pid(home_dir, &logs_dir).ok_or_else(|| {
+        BoxliteError::NotFound(format!(
+            "no runtime handling log level requests is using {}",
+            home_dir.display()
+        ))
+    })?;
+
+    std::fs::write(logs_dir.join(LEVEL_FILE), filter)
+        .map_err(|e| BoxliteError::Storage(format!("failed to write log level: {}", e)))?;
+    if unsafe { libc::kill(pid as i32, libc::SIGUSR1) } != 0 {
+        return Err(BoxliteError::Internal(format!(
+            "failed to signal runtime process {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(pid)
+}
+
+/// Apply `{home}/logs/log-level` on every SIGUSR1.
+///
+/// Does nothing if boxlite doesn't own the subscriber or the listener already
+/// runs.
+pub(crate) fn listen(home_dir: &Path) {
+    use signal_hook::consts::signal::SIGUSR1;
+    use signal_hook::iterator::Signals;
+
+    if LOG_FILTER.get().is_none()
+        || LISTENING
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+    {
+        return;
+    }
+
+    let mut signals = match Signals::new([SIGUSR1]) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!("Failed to register SIGUSR1 handler: {}", e);
+            LISTENING.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+
+    let logs_dir = home_dir.join(dirs::LOGS_DIR);
+    if let Err(e) = std::fs::write(logs_dir.join(PID_FILE), std::process::id().to_string()) {
+        tracing::warn!("Failed to write {}: {}", PID_FILE, e);
+    }
+
+    std::thread::Builder::new()
+        .name("boxlite-log-level".into())
+        .spawn(move || {
+            for _ in signals.forever() {
+                let result = std::fs::read_to_string(logs_dir.join(LEVEL_FILE))
+                    .map_err(|e| BoxliteError::Storage(e.to_string()))
+                    .and_then(|filter| set_filter(filter.trim()));
+                if let Err(e) = result {
+                    tracing::warn!("Failed to apply requested log level: {}", e);
+                }
+            }
+        })
+        .expect("Failed to spawn log level handler thread");
+}
+
+fn parse(filter: &str) -> BoxliteResult<EnvFilter> {
+    EnvFilter::try_new(filter).map_err(|e| {
+        BoxliteError::InvalidArgument(format!("invalid log filter '{}': {}", filter, e))
+    })
+}
+
+/// PID in the PID file, if that process still holds the runtime lock.
+///
+/// The PID file outlives its process, and the PID may have been reused.
+fn listener_pid(home_dir: &Path, logs_dir: &Path) -> Option<u32> {
+    let pid: u32 = std::fs::read_to_string(logs_dir.join(PID_FILE))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    (RuntimeLock::holder_pid(home_dir)? == pid).then_some(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            request_log_level(dir.path(), "boxlite=loud"),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_no_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs_dir = dir.path().join(dirs::LOGS_DIR);
+        std::fs::create_dir_all(&logs_dir).unwrap();
+        assert!(matches!(
+            request_log_level(dir.path(), "debug"),
+            Err(BoxliteError::NotFound(_))
+        ));
+
+        // A PID file left behind by a runtime that exited is ignored
+        std::fs::write(logs_dir.join(PID_FILE), std::process::id().to_string()).unwrap();
+        assert!(matches!(
+            request_log_level(dir.path(), "debug"),
+            Err(BoxliteError::NotFound(_))
+        ));
+        assert!(!logs_dir.join(LEVEL_FILE).exists());
+    }
+}
@@ -1,13 +1,34 @@
+pub mod batch;
+pub mod connection_prompt;
 pub mod constants;
+pub(crate) mod dependencies;
+pub mod events;
+pub mod exec_cache;
+pub(crate) mod fork;
 pub(crate) mod guest_rootfs;
+pub(crate) mod hooks;
 pub mod layout;
+pub mod leaks;
 pub(crate) mod lock;
+pub mod log_level;
+pub mod networks;
 pub mod options;
+pub mod plan;
+pub mod prefetch;
+pub mod progress;
+pub(crate) mod reservations;
+pub mod run_once;
+pub mod schema;
+pub mod shutdown;
 pub(crate) mod signal_handler;
+pub(crate) mod sockets;
 pub mod types;
+pub(crate) mod warnings;
 
+mod async_runtime;
 mod core;
 pub(crate) mod rt_impl;
 
+pub use async_runtime::AsyncRuntime;
 pub use core::BoxliteRuntime;
 pub(crate) use rt_impl::SharedRuntimeImpl;
@@ -0,0 +1,398 @@
+//! User-defined networks.
+//!
+//! A box joins a network by naming it in `BoxOptions.networks`. Networks
+//! have no state of their own: the members of a network are the boxes of a
+//! project that name it.
+//!
+//! Each member has its own address in [`PEER_SUBNET`], stored in its state:
+//! the lowest one free among the project's boxes when it is added, kept
+//! while it stops and starts and freed when it is removed. A running
+//! member's DNS resolver, in its guest, answers for the names of the named
+//! running members with their addresses, and its peers file lists the TCP
+//! ports each publishes. gvproxy translates the addresses to the host's
+//! 127.0.0.1 and lets through only those ports, so the rest of the host's
+//! loopback stays out of reach. Both are rewritten whenever a member starts,
+//! stops, crashes or is removed.
+
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+use serde::Serialize;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::litebox::config::BoxConfig;
+use crate::net::constants::{PEER_IP_COUNT, PEER_IP_FIRST, PEER_SUBNET};
+use crate::portal::interfaces::container::default_hostname;
+use crate::runtime::options::{PortProtocol, PortSpec};
+use crate::runtime::types::{BoxState, BoxStatus};
+
+/// A user-defined network and its members.
+#[derive(Clone, Debug, Serialize)]
+pub struct NetworkInfo {
+    pub name: String,
+    pub project: String,
+    /// Boxes that joined the network, running or not, by creation time
+    pub members: Vec<NetworkMember>,
+}
+
+/// A box on a user-defined network.
+#[derive(Clone, Debug, Serialize)]
+pub struct NetworkMember {
+    pub id: String,
+    pub name: Option<String>,
+    pub status: BoxStatus,
+    /// Name other members resolve the box by; unnamed boxes have none
+    pub hostname: Option<String>,
+    /// Address the hostname resolves to, while the box runs
+    pub address: Option<String>,
+    /// Ports the box publishes, and so serves on the network
+    pub ports: Vec<PortSpec>,
+}
+
+impl NetworkMember {
+    fn of(config: &BoxConfig, state: &BoxState) -> Self {
+        let hostname = config
+            .name
+            .as_deref()
+            .map(|name| default_hostname(Some(name)))
+            .filter(|hostname| !hostname.is_empty());
+        Self {
+            id: config.id.to_string(),
+            name: config.name.clone(),
+            status: state.status(),
+            address: state
+                .peer_ip
+                .filter(|_| hostname.is_some() && state.status().is_running())
+                .map(|ip| ip.to_string()),
+            hostname,
+            ports: config.options.ports.clone(),
+        }
+    }
+
+    /// The box as its peers reach it, if it has a hostname and an address.
+    fn peer(&self) -> Option<NetworkPeer> {
+        Some(NetworkPeer {
+            hostname: self.hostname.clone()?,
+            ip: self.address.clone()?,
+            ports: self
+                .ports
+                .iter()
+                .filter(|port| matches!(port.protocol, PortProtocol::Tcp))
+                .map(|port| port.host_port.unwrap_or(port.guest_port))
+                .collect(),
+        })
+    }
+}
+
+/// A running box on the networks of another, as that box reaches it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct NetworkPeer {
+    #[serde(skip)]
+    pub hostname: String,
+    pub ip: String,
+    /// Host ports the peer publishes over TCP
+    pub ports: Vec<u16>,
+}
+
+/// Members of `network` in `project` among `boxes`.
+pub(crate) fn network_info(
+    network: &str,
+    project: &str,
+    boxes: &[(BoxConfig, BoxState)],
+) -> NetworkInfo {
+    let mut members: Vec<(&BoxConfig, NetworkMember)> = boxes
+        .iter()
+        .filter(|(config, _)| is_member(config, network, project))
+        .map(|(config, state)| (config, NetworkMember::of(config, state)))
+        .collect();
+    members.sort_by_key(|(config, _)| config.created_at);
+    NetworkInfo {
+        name: network.to_string(),
+        project: project.to_string(),
+        members: members.into_iter().map(|(_, member)| member).collect(),
+    }
+}
+
+/// Peers of the box with `config`: one per running named member of its
+/// networks, the first box to take a hostname keeping it.
+pub(crate) fn peers(config: &BoxConfig, boxes: &[(BoxConfig, BoxState)]) -> Vec<NetworkPeer> {
+    let mut peers: Vec<NetworkPeer> = Vec::new();
+    for network in &config.options.networks {
+        let info = network_info(network, &config.project, boxes);
+        for peer in info.members.iter().filter_map(NetworkMember::peer) {
+            if !peers.iter().any(|p| p.hostname == peer.hostname) {
+                peers.push(peer);
+            }
+        }
+    }
+    peers
+}
+
+/// Names a box's DNS resolver answers for: each peer's hostname and address.
+pub(crate) fn host_entries(peers: &[NetworkPeer]) -> Vec<(String, String)> {
+    peers
+        .iter()
+        .map(|peer| (peer.hostname.clone(), peer.ip.clone()))
+        .collect()
+}
+
+/// Replace the peers file gvproxy reads at `path`.
+///
+/// gvproxy notices a new file rather than new content, so the file is
+/// written aside and renamed over the old one.
+pub(crate) fn write_peers_file(path: &Path, peers: &[NetworkPeer]) -> BoxliteResult<()> {
+    #[derive(Serialize)]
+    struct PeersFile<'a> {
+        peers: &'a [NetworkPeer],
+    }
+
+    let content = serde_json::to_vec(&PeersFile { peers })?;
+    let tmp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+    let storage_error =
+        |e: std::io::Error| BoxliteError::Storage(format!("{}: {}", path.display(), e));
+    std::fs::write(&tmp, content).map_err(storage_error)?;
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        storage_error(e)
+    })
+}
+
+/// Whether the boxes with `a` and `b` share a network.
+pub(crate) fn share_network(a: &BoxConfig, b: &BoxConfig) -> bool {
+    a.project == b.project
+        && a.options
+            .networks
+            .iter()
+            .any(|network| b.options.networks.contains(network))
+}
+
+/// The lowest address in [`PEER_SUBNET`] no box of `project` among `boxes`
+/// holds, for a box joining networks. None once the subnet is used up.
+pub(crate) fn free_peer_ip(project: &str, boxes: &[(BoxConfig, BoxState)]) -> Option<Ipv4Addr> {
+    let taken: HashSet<Ipv4Addr> = boxes
+        .iter()
+        .filter(|(config, _)| config.project == project)
+        .filter_map(|(_, state)| state.peer_ip)
+        .collect();
+    let first = u32::from(Ipv4Addr::from(PEER_IP_FIRST));
+    let free = (first..first + PEER_IP_COUNT as u32)
+        .map(Ipv4Addr::from)
+        .find(|ip| !taken.contains(ip));
+    if free.is_none() {
+        tracing::warn!(
+            project,
+            "All addresses in {} are taken; the box is on its networks without one",
+            PEER_SUBNET
+        );
+    }
+    free
+}
+
+fn is_member(config: &BoxConfig, network: &str, project: &str) -> bool {
+    config.project == project && config.options.networks.iter().any(|n| n == network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::litebox::config::ContainerRuntimeConfig;
+    use crate::runtime::options::BoxOptions;
+    use crate::runtime::types::{BoxID, ContainerID};
+    use boxlite_shared::Transport;
+    use chrono::{Duration, Utc};
+    use std::path::PathBuf;
+
+    fn member(
+        name: Option<&str>,
+        project: &str,
+        networks: &[&str],
+        status: BoxStatus,
+        age_secs: i64,
+    ) -> (BoxConfig, BoxState) {
+        let mut member = member_with_ports(name, project, networks, status, age_secs);
+        member.0.options.ports.clear();
+        member
+    }
+
+    fn member_with_ports(
+        name: Option<&str>,
+        project: &str,
+        networks: &[&str],
+        status: BoxStatus,
+        age_secs: i64,
+    ) -> (BoxConfig, BoxState) {
+        let config = BoxConfig {
+            id: BoxID::new(),
+            name: name.map(str::to_string),
+            created_at: Utc::now() - Duration::seconds(age_secs),
+            project: project.to_string(),
+            container: ContainerRuntimeConfig {
+                id: ContainerID::new(),
+            },
+            options: BoxOptions {
+                networks: networks.iter().map(|n| n.to_string()).collect(),
+                ports: vec![
+                    "8080:80".parse().unwrap(),
+                    "9000".parse().unwrap(),
+                    "5353:53/udp".parse().unwrap(),
+                ],
+                ..Default::default()
+            },
+            storage_driver: Default::default(),
+            engine_kind: crate::vmm::VmmKind::Libkrun,
+            transport: Transport::unix(PathBuf::from("/tmp/boxlite.sock")),
+            box_home: PathBuf::from("/tmp/box"),
+            ready_socket_path: PathBuf::from("/tmp/ready.sock"),
+            warnings: Vec::new(),
+            disk_image: None,
+        };
+        let mut state = BoxState::new();
+        let _ = state.transition_to(status);
+        (config, state)
+    }
+
+    /// Add `member` to `boxes`, with the address `BoxManager::add_box`
+    /// would give it.
+    fn add(boxes: &mut Vec<(BoxConfig, BoxState)>, member: (BoxConfig, BoxState)) {
+        let (config, mut state) = member;
+        if !config.options.networks.is_empty() {
+            state.peer_ip = free_peer_ip(&config.project, boxes);
+        }
+        boxes.push((config, state));
+    }
+
+    fn added(members: Vec<(BoxConfig, BoxState)>) -> Vec<(BoxConfig, BoxState)> {
+        let mut boxes = Vec::new();
+        for member in members {
+            add(&mut boxes, member);
+        }
+        boxes
+    }
+
+    #[test]
+    fn test_host_entries_list_running_named_members() {
+        let boxes = added(vec![
+            member(Some("web"), "default", &["backend"], BoxStatus::Running, 5),
+            member(
+                Some("API_Box"),
+                "default",
+                &["backend"],
+                BoxStatus::Running,
+                4,
+            ),
+            member(Some("db"), "default", &["backend"], BoxStatus::Stopped, 3),
+            member(None, "default", &["backend"], BoxStatus::Running, 2),
+            member(Some("cache"), "default", &["other"], BoxStatus::Running, 1),
+            member(Some("queue"), "ci", &["backend"], BoxStatus::Running, 0),
+        ]);
+
+        let entries = host_entries(&peers(&boxes[0].0, &boxes));
+        assert_eq!(
+            entries,
+            vec![
+                ("web".to_string(), "192.168.127.128".to_string()),
+                ("api-box".to_string(), "192.168.127.129".to_string()),
+            ]
+        );
+
+        let info = network_info("backend", "default", &boxes);
+        assert_eq!(info.members.len(), 4);
+        assert_eq!(info.members[2].hostname.as_deref(), Some("db"));
+        assert_eq!(info.members[2].address, None);
+        assert_eq!(info.members[3].hostname, None);
+        assert_eq!(info.members[3].address, None);
+
+        assert!(share_network(&boxes[0].0, &boxes[2].0));
+        assert!(!share_network(&boxes[0].0, &boxes[4].0));
+        assert!(!share_network(&boxes[0].0, &boxes[5].0));
+    }
+
+    #[test]
+    fn test_peers_keep_their_address_and_tcp_ports() {
+        let boxes = added(vec![
+            member(Some("db"), "default", &["backend"], BoxStatus::Stopped, 3),
+            member_with_ports(Some("web"), "default", &["backend"], BoxStatus::Running, 2),
+            member(
+                Some("worker"),
+                "default",
+                &["backend"],
+                BoxStatus::Running,
+                1,
+            ),
+        ]);
+
+        // The stopped box keeps its address, so the others keep theirs
+        let peers = peers(&boxes[2].0, &boxes);
+        assert_eq!(
+            peers,
+            vec![
+                NetworkPeer {
+                    hostname: "web".to_string(),
+                    ip: "192.168.127.129".to_string(),
+                    ports: vec![8080, 9000],
+                },
+                NetworkPeer {
+                    hostname: "worker".to_string(),
+                    ip: "192.168.127.130".to_string(),
+                    ports: vec![],
+                },
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("network-peers.json");
+        write_peers_file(&path, &peers).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!({"peers": [
+                {"ip": "192.168.127.129", "ports": [8080, 9000]},
+                {"ip": "192.168.127.130", "ports": []},
+            ]})
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_peers_keep_their_address_when_an_older_member_is_removed() {
+        let mut boxes = added(vec![
+            member(Some("db"), "default", &["backend"], BoxStatus::Stopped, 3),
+            member(Some("web"), "default", &["backend"], BoxStatus::Running, 2),
+            member(
+                Some("worker"),
+                "default",
+                &["backend"],
+                BoxStatus::Running,
+                1,
+            ),
+        ]);
+        let web = peers(&boxes[2].0, &boxes)[0].clone();
+        assert_eq!(web.ip, "192.168.127.129");
+
+        boxes.remove(0);
+        assert_eq!(peers(&boxes[1].0, &boxes)[0], web);
+
+        // The removed box's address goes to the next box added
+        add(
+            &mut boxes,
+            member(
+                Some("cache"),
+                "default",
+                &["backend"],
+                BoxStatus::Running,
+                0,
+            ),
+        );
+        assert_eq!(boxes[2].1.peer_ip, Some(Ipv4Addr::new(192, 168, 127, 128)));
+        assert_eq!(peers(&boxes[1].0, &boxes)[0], web);
+
+        // Other projects number their boxes apart
+        assert_eq!(
+            free_peer_ip("ci", &boxes),
+            Some(Ipv4Addr::new(192, 168, 127, 128))
+        );
+    }
+}
@@ -1,11 +1,14 @@
 //! Configuration for Boxlite.
 
+use crate::disk::DiskFormat;
 use crate::runtime::constants::envs as const_envs;
+use crate::runtime::exec_cache::ExecCacheLimits;
 use crate::runtime::layout::dirs as const_dirs;
-use boxlite_shared::errors::BoxliteResult;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 // ============================================================================
 // Security Options
@@ -15,7 +18,7 @@ use std::path::PathBuf;
 ///
 /// These options control how the boxlite-shim process is isolated from the host.
 /// Different presets are available for different security requirements.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct SecurityOptions {
     /// Enable jailer isolation.
     ///
@@ -117,7 +120,7 @@ pub struct SecurityOptions {
 }
 
 /// Resource limits for the jailed process.
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ResourceLimits {
     /// Maximum number of open file descriptors (RLIMIT_NOFILE).
     #[serde(default)]
@@ -279,6 +282,29 @@ impl SecurityOptions {
     }
 }
 
+/// Named [`SecurityOptions`] preset, for configuration files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecurityPreset {
+    /// [`SecurityOptions::development`]
+    Development,
+    /// [`SecurityOptions::standard`]
+    Standard,
+    /// [`SecurityOptions::maximum`]
+    Maximum,
+}
+
+impl SecurityPreset {
+    /// The options of this preset.
+    pub fn options(self) -> SecurityOptions {
+        match self {
+            Self::Development => SecurityOptions::development(),
+            Self::Standard => SecurityOptions::standard(),
+            Self::Maximum => SecurityOptions::maximum(),
+        }
+    }
+}
+
 // ============================================================================
 // Security Options Builder (C-BUILDER: Non-consuming builder pattern)
 // ============================================================================
@@ -527,6 +553,406 @@ pub struct BoxliteOptions {
     /// ```
     #[serde(default)]
     pub image_registries: Vec<String>,
+
+    /// Signature verification policy for pulled images.
+    ///
+    /// Disabled by default. See [`ImageVerificationOptions`].
+    #[serde(default)]
+    pub image_verification: ImageVerificationOptions,
+
+    /// Retry policy for transient registry failures during image pulls.
+    ///
+    /// See [`RegistryRetryOptions`].
+    #[serde(default)]
+    pub registry_retry: RegistryRetryOptions,
+
+    /// PEM file with extra CA certificates trusted for registry TLS.
+    ///
+    /// Use this for registries signed by a private CA. System roots stay
+    /// trusted.
+    #[serde(default)]
+    pub registry_ca_bundle: Option<PathBuf>,
+
+    /// Registries contacted over plain HTTP instead of HTTPS
+    /// (e.g., `"localhost:5000"`).
+    ///
+    /// Proxies come from the `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY`
+    /// environment variables.
+    #[serde(default)]
+    pub insecure_registries: Vec<String>,
+
+    /// Mirrors per registry, tried in order when the registry itself is
+    /// unreachable or failing.
+    ///
+    /// Keys are registry hosts as they appear in resolved references
+    /// (e.g., `"docker.io"`); values are mirror hosts, optionally with a path
+    /// prefix (e.g., `"registry.local:5000/dockerhub"`).
+    #[serde(default)]
+    pub registry_mirrors: HashMap<String, Vec<String>>,
+
+    /// Pull-through blob cache directory, shared across runtime homes.
+    ///
+    /// Downloaded layer and config blobs are also stored here, and later
+    /// pulls (from any home using the same directory) copy them from here
+    /// instead of the registry. Disabled when `None`.
+    #[serde(default)]
+    pub blob_cache_dir: Option<PathBuf>,
+
+    /// How image layers are exposed to the guest as the container rootfs.
+    ///
+    /// Applies to boxes created by this runtime; existing boxes keep the
+    /// driver they were created with.
+    #[serde(default)]
+    pub storage_driver: StorageDriver,
+
+    /// Host commands run at box lifecycle events. See [`LifecycleHooks`].
+    #[serde(default)]
+    pub hooks: LifecycleHooks,
+
+    /// External volume and network driver plugins. See [`PluginSpec`].
+    #[serde(default)]
+    pub plugins: Vec<PluginSpec>,
+
+    /// Project (namespace) this runtime works in.
+    ///
+    /// When set, new boxes are created in this project and `list_info()`
+    /// and `remove()` only see boxes of this project. When `None`, new
+    /// boxes go to the `"default"` project and all projects are visible.
+    #[serde(default)]
+    pub default_project: Option<String>,
+
+    /// vCPUs available to boxes of this runtime.
+    ///
+    /// Running boxes reserve their vCPUs against this capacity; creating or
+    /// starting a box that would reserve more than
+    /// `cpu_capacity * overcommit_ratio` fails with `ResourceExhausted`.
+    /// `None` (default) doesn't limit CPUs.
+    #[serde(default)]
+    pub cpu_capacity: Option<u32>,
+
+    /// Memory in MiB available to boxes of this runtime (see `cpu_capacity`).
+    #[serde(default)]
+    pub memory_capacity_mib: Option<u64>,
+
+    /// How far reservations may exceed the capacities (default 1.0, no overcommit).
+    #[serde(default = "default_overcommit_ratio")]
+    pub overcommit_ratio: f64,
+
+    /// Destinations that receive lifecycle events and exec audit records.
+    /// See [`EventSinkSpec`].
+    #[serde(default)]
+    pub event_sinks: Vec<EventSinkSpec>,
+
+    /// How box state writes are made durable. See [`DbDurability`].
+    #[serde(default)]
+    pub db_durability: DbDurability,
+
+    /// Security preset for boxes created with the default
+    /// `BoxOptions.security`.
+    ///
+    /// Boxes that set their own security options keep them. `None` (default)
+    /// leaves `SecurityOptions::default()` in place.
+    #[serde(default)]
+    pub default_security: Option<SecurityPreset>,
+
+    /// Pull policy for boxes created with the default `BoxOptions.pull_policy`
+    /// (`IfNotPresent`).
+    #[serde(default)]
+    pub default_pull_policy: Option<ImagePullPolicy>,
+
+    /// Directory for the Unix sockets of new boxes (`{socket_dir}/{box_id}/`).
+    ///
+    /// Socket paths are limited to 103 bytes on macOS and 107 on Linux. By
+    /// default sockets live in the box directory, or in `$XDG_RUNTIME_DIR`
+    /// (falling back to `/tmp`) when `home_dir` is too deep for that. Set this
+    /// to place them elsewhere; creating a box fails if the paths would still
+    /// be too long.
+    #[serde(default)]
+    pub socket_dir: Option<PathBuf>,
+
+    /// Limits of the cache behind `LiteBox::exec_cached()`. See
+    /// [`ExecCacheLimits`].
+    #[serde(default)]
+    pub exec_cache: ExecCacheLimits,
+}
+
+/// Host commands run around box lifecycle events, like OCI runtime hooks.
+///
+/// Each hook gets the box's `BoxInfo` as JSON on stdin. Hooks of one event
+/// run in order. A failing `pre_create` hook aborts box creation; failures
+/// of the other hooks are logged and ignored.
+///
+/// # Example
+///
+/// ```ignore
+/// BoxliteOptions {
+///     hooks: LifecycleHooks {
+///         post_start: vec![HookSpec {
+///             path: PathBuf::from("/usr/local/bin/register-box"),
+///             args: vec!["--inventory".into(), "https://inventory.local".into()],
+///             ..Default::default()
+///         }],
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleHooks {
+    /// Before a box is persisted by `create()`.
+    pub pre_create: Vec<HookSpec>,
+    /// After a box's VM has booted.
+    pub post_start: Vec<HookSpec>,
+    /// Before a running box is stopped.
+    pub pre_stop: Vec<HookSpec>,
+    /// After a box has been removed.
+    pub post_remove: Vec<HookSpec>,
+}
+
+/// A single lifecycle hook command.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HookSpec {
+    /// Absolute path of the executable.
+    pub path: PathBuf,
+
+    /// Arguments passed to the executable.
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Extra environment variables (`KEY=VALUE`), added to the runtime's.
+    #[serde(default)]
+    pub env: Vec<String>,
+
+    /// Seconds before the hook is killed and counted as failed.
+    ///
+    /// Default: 30
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// An external driver plugin, reached over JSON-RPC on a Unix socket.
+///
+/// Boxes select the plugin's drivers by `name`: `VolumeSpec.driver` for
+/// volumes, `NetworkSpec::Custom` for networking. The protocol is described
+/// in [`crate::plugins::external`].
+///
+/// # Example
+///
+/// ```ignore
+/// BoxliteOptions {
+///     plugins: vec![PluginSpec {
+///         name: "san".into(),
+///         socket: PathBuf::from("/run/boxlite/plugins/san.sock"),
+///         timeout_secs: None,
+///     }],
+///     ..Default::default()
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PluginSpec {
+    /// Driver name boxes refer to.
+    pub name: String,
+
+    /// Absolute path of the Unix socket the plugin listens on.
+    pub socket: PathBuf,
+
+    /// Seconds to wait for each plugin call.
+    ///
+    /// Default: 30
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// A destination for runtime events (see [`crate::runtime::events`]).
+///
+/// Events are buffered in memory per sink and delivered in the background
+/// with retries, so a slow or unreachable sink never blocks box operations.
+/// Events that don't fit the buffer, or whose delivery keeps failing, are
+/// dropped and counted in `RuntimeMetrics::events_dropped_total()`.
+///
+/// # Example
+///
+/// ```ignore
+/// BoxliteOptions {
+///     event_sinks: vec![EventSinkSpec {
+///         destination: EventDestination::Webhook {
+///             url: "https://audit.example.com/boxlite".into(),
+///             headers: HashMap::from([("Authorization".into(), "Bearer ...".into())]),
+///         },
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EventSinkSpec {
+    /// Where events are sent.
+    #[serde(flatten)]
+    pub destination: EventDestination,
+
+    /// Events buffered while the sink is slow or unreachable.
+    ///
+    /// Default: 1000
+    #[serde(default)]
+    pub buffer_size: Option<usize>,
+
+    /// Delivery retries of a batch before it is dropped.
+    ///
+    /// Default: 5
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+}
+
+/// Destination of an [`EventSinkSpec`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EventDestination {
+    /// POST batches of events as a JSON array to an HTTP(S) endpoint.
+    ///
+    /// Non-2xx responses are retried.
+    Webhook {
+        url: String,
+        /// Extra request headers, e.g. `Authorization`.
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Append events as JSON lines to a local file (absolute path).
+    File { path: PathBuf },
+}
+
+impl Default for EventDestination {
+    fn default() -> Self {
+        EventDestination::File {
+            path: PathBuf::new(),
+        }
+    }
+}
+
+/// Container rootfs storage strategy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageDriver {
+    /// Flatten all layers into a cached ext4 image, with a qcow2 COW overlay per box.
+    #[default]
+    Ext4,
+    /// Attach each layer as a read-only erofs image and combine them with
+    /// overlayfs inside the guest; box writes go to a per-box ext4 upper disk.
+    ///
+    /// Box creation doesn't copy the image, and layer images are shared by
+    /// every box using them. Requires `mkfs.erofs` on the host (1.7+ when rootless).
+    Erofs,
+    /// Like `Ext4`, but each box gets a reflink copy of the cached image,
+    /// sharing its blocks, instead of a qcow2 overlay.
+    ///
+    /// Needs a home on a filesystem with reflinks (btrfs, XFS, ZFS 2.2+ with
+    /// block cloning, APFS); elsewhere the runtime falls back to `Ext4` with a
+    /// warning. Creating and forking a box stay instant and the VM's disk I/O
+    /// skips the qcow2 layer. These are file copies, not filesystem snapshots.
+    Reflink,
+}
+
+/// How box state writes reach disk, trading crash safety for speed.
+///
+/// Create and remove storms spend most of their time waiting for fsync with
+/// `Full`. Whatever the mode, a crash never corrupts the database: it only
+/// loses the most recent writes, and recovery at the next start reconciles
+/// box statuses with the VM processes it finds. A box whose creation was lost
+/// is forgotten and its directory removed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbDurability {
+    /// Every write is on disk when it returns (SQLite `synchronous=FULL`).
+    #[default]
+    Full,
+    /// Writes are committed at once but only synced to disk at WAL
+    /// checkpoints (`synchronous=NORMAL`). A runtime crash loses nothing; a
+    /// power failure or OS crash can lose the latest writes.
+    Normal,
+    /// Like `Normal`, and writes are grouped into one transaction committed
+    /// every 100 ms and at shutdown. A runtime crash can also lose up to the
+    /// last 100 ms of writes.
+    Batched,
+}
+
+/// How strictly image signatures are checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageVerificationMode {
+    /// Don't check signatures.
+    #[default]
+    Disabled,
+    /// Check signatures and log a warning for unsigned or invalid images.
+    Warn,
+    /// Refuse to use images without a valid signature.
+    Enforce,
+}
+
+/// Image signature verification policy (cosign key-based signatures).
+///
+/// When enabled, every pull resolves the manifest from the registry and looks
+/// up its cosign signature, so the local cache only serves layer blobs.
+/// This is incompatible with `ImagePullPolicy::Never`.
+///
+/// # Example
+///
+/// ```ignore
+/// BoxliteOptions {
+///     image_verification: ImageVerificationOptions {
+///         mode: ImageVerificationMode::Enforce,
+///         public_keys: vec![PathBuf::from("/etc/boxlite/cosign.pub")],
+///     },
+///     ..Default::default()
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ImageVerificationOptions {
+    #[serde(default)]
+    pub mode: ImageVerificationMode,
+
+    /// PEM-encoded ECDSA P-256 public keys (as produced by `cosign generate-key-pair`).
+    ///
+    /// A signature from any of these keys is accepted.
+    #[serde(default)]
+    pub public_keys: Vec<PathBuf>,
+}
+
+/// Retry policy for registry requests (manifest and blob fetches).
+///
+/// Transient failures (5xx, 408, 429, timeouts, refused or reset
+/// connections) are retried with exponential backoff and jitter.
+/// Authentication failures and other 4xx responses are not retried.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistryRetryOptions {
+    /// Attempts per request, including the first. 1 disables retries.
+    ///
+    /// Default: 4
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, in milliseconds. Doubles every retry.
+    ///
+    /// Default: 500
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound on the delay between retries, in milliseconds.
+    ///
+    /// Default: 10000
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RegistryRetryOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+        }
+    }
+}
+
+fn default_overcommit_ratio() -> f64 {
+    1.0
 }
 
 fn default_home_dir() -> PathBuf {
@@ -544,14 +970,41 @@ impl Default for BoxliteOptions {
         Self {
             home_dir: default_home_dir(),
             image_registries: Vec::new(),
+            image_verification: ImageVerificationOptions::default(),
+            registry_retry: RegistryRetryOptions::default(),
+            registry_ca_bundle: None,
+            insecure_registries: Vec::new(),
+            registry_mirrors: HashMap::new(),
+            blob_cache_dir: None,
+            storage_driver: StorageDriver::default(),
+            hooks: LifecycleHooks::default(),
+            plugins: Vec::new(),
+            default_project: None,
+            cpu_capacity: None,
+            memory_capacity_mib: None,
+            overcommit_ratio: default_overcommit_ratio(),
+            event_sinks: Vec::new(),
+            db_durability: DbDurability::default(),
+            default_security: None,
+            default_pull_policy: None,
+            socket_dir: None,
+            exec_cache: ExecCacheLimits::default(),
         }
     }
 }
 
 /// Options used when constructing a box.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct BoxOptions {
+    /// Number of vCPUs for the VM.
+    ///
+    /// When set, the container's cgroup is also capped at this many CPUs
+    /// minus the guest agent's reservation.
     pub cpus: Option<u8>,
+    /// Memory in MiB for the VM.
+    ///
+    /// When set, the container's cgroup is also capped at this size minus
+    /// the guest agent's reservation.
     pub memory_mib: Option<u32>,
     /// Disk size in GB for the container rootfs (sparse, grows as needed).
     ///
@@ -559,12 +1012,83 @@ pub struct BoxOptions {
     /// If set, the COW overlay will have this virtual size, allowing
     /// the container to write more data than the base image size.
     pub disk_size_gb: Option<u64>,
+    /// Size of the container's `/dev/shm` in MiB (64 if `None`).
+    ///
+    /// Chromium-based workloads typically need 1 GiB or more.
+    #[serde(default)]
+    pub shm_size_mib: Option<u32>,
+    /// Swap for the VM in MiB, on top of `memory_mib` (none if `None`).
+    ///
+    /// Lets bursty workloads outgrow their memory for a while instead of
+    /// being OOM-killed. The runtime logs a warning and emits a
+    /// `box.swapping` event when the VM starts swapping out heavily; usage
+    /// is in `BoxMetrics::swap_used_bytes`.
+    #[serde(default)]
+    pub swap_mib: Option<u32>,
+    /// What backs `swap_mib`.
+    #[serde(default)]
+    pub swap_backing: SwapBacking,
     pub working_dir: Option<String>,
     pub env: Vec<(String, String)>,
     pub rootfs: RootfsSpec,
     pub volumes: Vec<VolumeSpec>,
     pub network: NetworkSpec,
     pub ports: Vec<PortSpec>,
+    /// Container hostname.
+    ///
+    /// Defaults to the box name with characters not allowed in a hostname
+    /// replaced by `-`, or `boxlite` for unnamed boxes.
+    #[serde(default)]
+    pub hostname: Option<String>,
+
+    /// Extra `/etc/hosts` entries in the container, as `(hostname, ip)` pairs.
+    #[serde(default)]
+    pub extra_hosts: Vec<(String, String)>,
+
+    /// User-defined networks the box joins.
+    ///
+    /// Running boxes that share a network resolve each other's names: the
+    /// DNS resolver of each answers for the other named members, updated as
+    /// they start and stop. Each name resolves to an address of its own, which
+    /// reaches the TCP ports that box publishes with [`ports`](Self::ports)
+    /// and nothing else. Networks are scoped to the box's project.
+    /// Not supported with a [`NetworkSpec::Custom`] network.
+    #[serde(default)]
+    pub networks: Vec<String>,
+
+    /// Let the box reach the host's loopback interface.
+    ///
+    /// The box resolves `host.boxlite.internal` to an address the network
+    /// translates to the host's `127.0.0.1`. This exposes every service
+    /// listening on the host's loopback to the box, including ones that
+    /// rely on binding to localhost to stay private, so leave it off for
+    /// untrusted workloads. Not supported with a [`NetworkSpec::Custom`]
+    /// network.
+    #[serde(default)]
+    pub host_loopback: bool,
+
+    /// Ask before each new outbound connection.
+    ///
+    /// The first connection to a destination (IP, port and protocol) is held
+    /// back until the callback registered with
+    /// `BoxliteRuntime::on_connection_prompt` allows or denies it; the
+    /// decision is remembered for the box, across restarts. Without a
+    /// callback, connections are denied. DNS through the gateway is never
+    /// held back. Not supported with a [`NetworkSpec::Custom`] network.
+    #[serde(default)]
+    pub prompt_connections: bool,
+
+    /// Record the box's HTTP(S) responses, or replay recorded ones.
+    ///
+    /// With [`NetworkMode::Record`] or [`NetworkMode::Replay`], the box's
+    /// `HTTP_PROXY` and `HTTPS_PROXY` point at a proxy on the gateway that
+    /// saves each response in the directory, or serves the saved ones
+    /// without reaching the network. HTTPS is decrypted by the proxy, with a
+    /// certificate authority the box trusts through `SSL_CERT_FILE`. Not
+    /// supported with a [`NetworkSpec::Custom`] network.
+    #[serde(default)]
+    pub network_mode: NetworkMode,
+
     /// Enable bind mount isolation for the shared mounts directory.
     ///
     /// When true, creates a read-only bind mount from `mounts/` to `shared/`,
@@ -606,10 +1130,27 @@ pub struct BoxOptions {
     #[serde(default)]
     pub security: SecurityOptions,
 
+    /// Kernel parameters set in the guest at boot, e.g.
+    /// `vm.max_map_count=262144`.
+    ///
+    /// Only parameters in an allowlist can be set: the `net.core.*`,
+    /// `net.ipv4.*` and `net.ipv6.*` namespaces and selected `vm.*`, `fs.*`
+    /// and `kernel.*` limits (see `boxlite_shared::constants::sysctls`).
+    #[serde(default)]
+    pub sysctls: HashMap<String, String>,
+
+    /// Resource limits for the processes in the container (the box's main
+    /// process and every execution), like `docker run --ulimit`.
+    ///
+    /// Unlike `security.resource_limits`, which applies to the host-side
+    /// shim, these apply inside the guest.
+    #[serde(default)]
+    pub ulimits: Vec<Ulimit>,
+
     /// Override the image's ENTRYPOINT directive.
     ///
-    /// When set, completely replaces the image's ENTRYPOINT.
-    /// Use with `cmd` to build the full command:
+    /// When set, completely replaces the image's ENTRYPOINT and, as in
+    /// Docker, drops the image's CMD. Use with `cmd` to pass arguments:
     ///   Final execution = entrypoint + cmd
     ///
     /// Example: For `docker:dind`, bypass the failing entrypoint script:
@@ -632,6 +1173,105 @@ pub struct BoxOptions {
     /// If None, uses the image's USER directive (defaults to root).
     #[serde(default)]
     pub user: Option<String>,
+
+    /// When to fetch the rootfs image from its registry.
+    ///
+    /// Defaults to `IfNotPresent`. Ignored for `RootfsSpec::RootfsPath` and
+    /// `RootfsSpec::DiskImage`.
+    #[serde(default)]
+    pub pull_policy: ImagePullPolicy,
+
+    /// Guest path to mount the box's persistent cache directory at (e.g. `/cache`).
+    ///
+    /// The host directory is `~/.boxlite/cache/<name>`, keyed by box name, so
+    /// it outlives the box and is mounted again into the next box created with
+    /// the same name. Useful for package-manager caches. Requires a named box.
+    #[serde(default)]
+    pub persistent_cache: Option<String>,
+
+    /// Log filter for the guest agent (e.g. `debug`, `info,boxlite_guest=trace`).
+    ///
+    /// Takes precedence over a `RUST_LOG` inherited from the host. Defaults
+    /// to `info`. Recent agent logs can be read with `LiteBox::guest_logs()`.
+    #[serde(default)]
+    pub guest_log_level: Option<String>,
+
+    /// Boxes (IDs or names) this box depends on.
+    ///
+    /// Starting this box first starts its dependencies, transitively and in
+    /// dependency order. Dependencies must exist when the box is created;
+    /// names are resolved to IDs then, and boxes with dependents can only
+    /// be removed with `force`.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// Stop this box's dependencies when it stops.
+    ///
+    /// Dependencies still needed by another running box keep running.
+    #[serde(default)]
+    pub stop_dependencies: bool,
+
+    /// Seconds after creation at which the box is stopped and removed.
+    ///
+    /// Expiry applies regardless of activity and survives runtime restarts:
+    /// a runtime started after the deadline removes the box during recovery,
+    /// and the box's shim shuts the VM down on time even without a runtime.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+
+    /// Most executions the box runs at once (unlimited if `None`).
+    ///
+    /// An execution counts from `exec()` until its process exits. Beyond
+    /// the limit `exec()` fails with `TooManyExecs`, or waits for a slot
+    /// with `queue_execs`. The limit is kept per runtime: executions started
+    /// by other processes on the same box are not counted.
+    #[serde(default)]
+    pub max_concurrent_execs: Option<u32>,
+
+    /// Wait for a free slot instead of failing when `max_concurrent_execs`
+    /// is reached.
+    #[serde(default)]
+    pub queue_execs: bool,
+
+    /// Seconds the guest keeps an execution after it exits.
+    ///
+    /// Until then any handle can list it, wait on it and read its exit
+    /// status by ID. If `None`, executions are kept until the box stops.
+    /// Must be at least 1.
+    #[serde(default)]
+    pub exec_retention_secs: Option<u64>,
+
+    /// Pass the box and execution IDs to every command as environment.
+    ///
+    /// Commands get `BOXLITE_BOX_ID` and `BOXLITE_EXECUTION_ID`, and, when
+    /// started inside an enabled `tracing` span, a W3C `traceparent` whose
+    /// trace ID is the box ID and whose parent ID is that span's ID. The
+    /// runtime logs the same IDs at debug level when it starts the command,
+    /// so the command's logs can be matched with the host's. Variables the command
+    /// sets itself are kept.
+    #[serde(default)]
+    pub propagate_trace_context: bool,
+
+    /// Seconds between heartbeats to the guest agent (no watchdog if `None`).
+    ///
+    /// While the box runs, the runtime that started or attached to it pings
+    /// the agent at this interval. Once no ping has been answered for
+    /// `unresponsive_after_secs`, the box is marked `Unresponsive` and a
+    /// `box.unresponsive` event is emitted; the next answer marks it
+    /// `Running` again.
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u64>,
+
+    /// Seconds without an answered heartbeat before the box is marked
+    /// `Unresponsive` (three heartbeat intervals if `None`).
+    #[serde(default)]
+    pub unresponsive_after_secs: Option<u64>,
+
+    /// Restart the box when it becomes `Unresponsive`.
+    ///
+    /// Ignored for `auto_remove` boxes, which stopping would remove.
+    #[serde(default)]
+    pub restart_unresponsive: bool,
 }
 
 fn default_auto_remove() -> bool {
@@ -648,19 +1288,43 @@ impl Default for BoxOptions {
             cpus: None,
             memory_mib: None,
             disk_size_gb: None,
+            shm_size_mib: None,
+            swap_mib: None,
+            swap_backing: SwapBacking::default(),
             working_dir: None,
             env: Vec::new(),
             rootfs: RootfsSpec::default(),
             volumes: Vec::new(),
             network: NetworkSpec::default(),
             ports: Vec::new(),
+            hostname: None,
+            extra_hosts: Vec::new(),
+            networks: Vec::new(),
+            host_loopback: false,
+            prompt_connections: false,
+            network_mode: NetworkMode::Live,
             isolate_mounts: false,
             auto_remove: default_auto_remove(),
             detach: default_detach(),
             security: SecurityOptions::default(),
+            sysctls: HashMap::new(),
+            ulimits: Vec::new(),
             entrypoint: None,
             cmd: None,
             user: None,
+            pull_policy: ImagePullPolicy::default(),
+            persistent_cache: None,
+            guest_log_level: None,
+            depends_on: Vec::new(),
+            stop_dependencies: false,
+            ttl_secs: None,
+            max_concurrent_execs: None,
+            queue_execs: false,
+            exec_retention_secs: None,
+            propagate_trace_context: false,
+            heartbeat_interval_secs: None,
+            unresponsive_after_secs: None,
+            restart_unresponsive: false,
         }
     }
 }
@@ -670,7 +1334,19 @@ impl BoxOptions {
     ///
     /// Validates option combinations:
     /// - `auto_remove=true` with `detach=true` is invalid (detached boxes need manual lifecycle control)
-    /// - `isolate_mounts=true` is only supported on Linux
+    /// - `persistent_cache` must be an absolute guest path
+    /// - a `DiskImage` rootfs must be an absolute host path to an ext4 or
+    ///   qcow2 image
+    /// - `ttl_secs` must be positive
+    /// - `shm_size_mib` and `swap_mib` must be positive
+    /// - `max_concurrent_execs` must be positive
+    /// - `heartbeat_interval_secs` and `unresponsive_after_secs` must be positive
+    /// - volume `options` must be known mount options
+    /// - `hostname` and `extra_hosts` names must be valid hostnames, and
+    ///   `extra_hosts` addresses valid IP addresses
+    /// - `networks` must be valid hostnames and need the built-in network
+    /// - `sysctls` must be allowlisted parameters with single-line values
+    /// - `ulimits` must be known limits, each set once, with soft <= hard
     pub fn sanitize(&self) -> BoxliteResult<()> {
         // Validate auto_remove + detach combination
         // A detached box that auto-removes doesn't make practical sense:
@@ -685,50 +1361,1069 @@ impl BoxOptions {
             ));
         }
 
-        #[cfg(not(target_os = "linux"))]
-        if self.isolate_mounts {
-            return Err(boxlite_shared::errors::BoxliteError::Unsupported(
-                "isolate_mounts is only supported on Linux".to_string(),
-            ));
+        if let Some(ref cache_path) = self.persistent_cache
+            && !cache_path.starts_with('/')
+        {
+            return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                "persistent_cache must be an absolute guest path, got '{}'",
+                cache_path
+            )));
         }
-        Ok(())
-    }
-}
 
-/// How to populate the box root filesystem.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
-pub enum RootfsSpec {
-    /// Pull/resolve this registry image reference.
-    Image(String),
-    /// Use an already prepared rootfs at the given host path.
-    RootfsPath(String),
-}
+        if let RootfsSpec::DiskImage(path, format) = &self.rootfs {
+            if !Path::new(path).is_absolute() {
+                return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                    "disk image must be an absolute host path, got '{}'",
+                    path
+                )));
+            }
+            if *format == DiskFormat::Erofs {
+                return Err(boxlite_shared::errors::BoxliteError::Config(
+                    "disk image must be ext4 or qcow2; erofs images are read-only layers"
+                        .to_string(),
+                ));
+            }
+        }
 
-impl Default for RootfsSpec {
-    fn default() -> Self {
-        Self::Image("alpine:latest".into())
-    }
-}
+        if self.ttl_secs == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "ttl_secs must be greater than zero".to_string(),
+            ));
+        }
 
-/// Filesystem mount specification.
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
-pub struct VolumeSpec {
-    pub host_path: String,
-    pub guest_path: String,
-    pub read_only: bool,
-}
+        if self.shm_size_mib == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "shm_size_mib must be greater than zero".to_string(),
+            ));
+        }
 
-/// Network isolation options.
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
-pub enum NetworkSpec {
-    #[default]
-    Isolated,
-    // Host,
-    // Custom(String),
-}
+        // The guest reads 0 as "keep until the box stops"
+        if self.exec_retention_secs == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "exec_retention_secs must be at least one second".to_string(),
+            ));
+        }
 
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
-pub enum PortProtocol {
+        if self.swap_mib == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "swap_mib must be greater than zero".to_string(),
+            ));
+        }
+
+        for volume in &self.volumes {
+            validate_volume_options(volume)
+                .map_err(boxlite_shared::errors::BoxliteError::Config)?;
+        }
+
+        if let Some(ref hostname) = self.hostname {
+            validate_container_hostname(hostname)
+                .map_err(boxlite_shared::errors::BoxliteError::Config)?;
+        }
+        for (hostname, ip) in &self.extra_hosts {
+            validate_host_entry(hostname, ip)
+                .map_err(boxlite_shared::errors::BoxliteError::Config)?;
+        }
+        for network in &self.networks {
+            validate_network_name(network).map_err(boxlite_shared::errors::BoxliteError::Config)?;
+        }
+        if !self.networks.is_empty()
+            && let NetworkSpec::Custom(driver) = &self.network
+        {
+            return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                "networks need the built-in network, but the box uses network driver '{}'",
+                driver
+            )));
+        }
+        if self.host_loopback
+            && let NetworkSpec::Custom(driver) = &self.network
+        {
+            return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                "host_loopback needs the built-in network, but the box uses network driver '{}'",
+                driver
+            )));
+        }
+        if self.prompt_connections
+            && let NetworkSpec::Custom(driver) = &self.network
+        {
+            return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                "prompt_connections needs the built-in network, but the box uses network driver '{}'",
+                driver
+            )));
+        }
+        if let Some((dir, _)) = self.network_mode.recording() {
+            if let NetworkSpec::Custom(driver) = &self.network {
+                return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                    "network_mode needs the built-in network, but the box uses network driver '{}'",
+                    driver
+                )));
+            }
+            if !dir.is_absolute() {
+                return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                    "network_mode directory must be an absolute path, got '{}'",
+                    dir.display()
+                )));
+            }
+        }
+        if self.prompt_connections && matches!(self.network_mode, NetworkMode::Record(_)) {
+            // The proxy reaches the network from the host, past the prompts
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "prompt_connections can't be combined with recording network_mode".to_string(),
+            ));
+        }
+
+        for (key, value) in &self.sysctls {
+            validate_sysctl(key, value).map_err(boxlite_shared::errors::BoxliteError::Config)?;
+        }
+
+        for (i, ulimit) in self.ulimits.iter().enumerate() {
+            ulimit
+                .validate()
+                .map_err(boxlite_shared::errors::BoxliteError::Config)?;
+            if self.ulimits[..i].iter().any(|u| u.name == ulimit.name) {
+                return Err(boxlite_shared::errors::BoxliteError::Config(format!(
+                    "ulimit '{}' is set more than once",
+                    ulimit.name
+                )));
+            }
+        }
+
+        if self.max_concurrent_execs == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "max_concurrent_execs must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.heartbeat_interval_secs == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "heartbeat_interval_secs must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.unresponsive_after_secs == Some(0) {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "unresponsive_after_secs must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create a [`BoxOptionsBuilder`] starting from default options.
+    pub fn builder() -> BoxOptionsBuilder {
+        BoxOptionsBuilder::new()
+    }
+}
+
+// ============================================================================
+// Box Options Builder
+// ============================================================================
+
+/// Builder for [`BoxOptions`] with typed setters and validation in `build()`.
+///
+/// Setters that parse or convert their input (ports, fractional CPUs, byte
+/// sizes) remember the first invalid value; [`build`](Self::build) reports
+/// it, then checks the result with [`BoxOptions::sanitize`], so an invalid
+/// combination fails here rather than inside `create()`. Non-consuming, like
+/// [`SecurityOptionsBuilder`].
+///
+/// # Example
+///
+/// ```
+/// use boxlite::{BoxOptions, VolumeSpecBuilder};
+///
+/// let options = BoxOptions::builder()
+///     .image("python:3.12-slim")
+///     .cpus_fraction(1.5)
+///     .memory_mib(512)
+///     .port("8080:80/tcp")
+///     .volume(VolumeSpecBuilder::new("/srv/data", "/data").read_only(true).build()?)
+///     .env("PYTHONUNBUFFERED", "1")
+///     .build()?;
+/// assert_eq!(options.cpus, Some(2));
+/// # Ok::<(), boxlite::BoxliteError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoxOptionsBuilder {
+    inner: BoxOptions,
+    /// First invalid value passed to a setter.
+    error: Option<String>,
+}
+
+impl Default for BoxOptionsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoxOptionsBuilder {
+    /// Create a builder starting from default options.
+    pub fn new() -> Self {
+        Self {
+            inner: BoxOptions::default(),
+            error: None,
+        }
+    }
+
+    fn invalid(&mut self, message: String) -> &mut Self {
+        self.error.get_or_insert(message);
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Root filesystem
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Use this registry image reference as the rootfs.
+    pub fn image(&mut self, image: impl Into<String>) -> &mut Self {
+        self.inner.rootfs = RootfsSpec::Image(image.into());
+        self
+    }
+
+    /// Use an already prepared rootfs at this host path.
+    pub fn rootfs_path(&mut self, path: impl Into<String>) -> &mut Self {
+        self.inner.rootfs = RootfsSpec::RootfsPath(path.into());
+        self
+    }
+
+    /// Boot from this ext4 or qcow2 disk image on the host.
+    pub fn disk_image(&mut self, path: impl Into<String>, format: DiskFormat) -> &mut Self {
+        self.inner.rootfs = RootfsSpec::DiskImage(path.into(), format);
+        self
+    }
+
+    /// Use the busybox image embedded in the crate as the rootfs.
+    pub fn builtin_rootfs(&mut self) -> &mut Self {
+        self.inner.rootfs = RootfsSpec::Builtin;
+        self
+    }
+
+    /// Set when to fetch the image from its registry.
+    pub fn pull_policy(&mut self, policy: ImagePullPolicy) -> &mut Self {
+        self.inner.pull_policy = policy;
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Resources
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Set the number of vCPUs.
+    pub fn cpus(&mut self, cpus: u8) -> &mut Self {
+        if cpus == 0 {
+            return self.invalid("cpus must be at least 1".to_string());
+        }
+        self.inner.cpus = Some(cpus);
+        self
+    }
+
+    /// Set the CPU count from a fraction such as `0.5` or `1.5`.
+    ///
+    /// The VM gets whole vCPUs, so the value is rounded up.
+    pub fn cpus_fraction(&mut self, cpus: f64) -> &mut Self {
+        if !cpus.is_finite() || cpus <= 0.0 || cpus.ceil() > f64::from(u8::MAX) {
+            return self.invalid(format!(
+                "cpus must be between 0 and {}, got {}",
+                u8::MAX,
+                cpus
+            ));
+        }
+        self.inner.cpus = Some(cpus.ceil() as u8);
+        self
+    }
+
+    /// Set the VM memory in MiB.
+    pub fn memory_mib(&mut self, mib: u32) -> &mut Self {
+        if mib == 0 {
+            return self.invalid("memory must be greater than zero".to_string());
+        }
+        self.inner.memory_mib = Some(mib);
+        self
+    }
+
+    /// Set the VM memory in bytes, rounded up to whole MiB.
+    pub fn memory_bytes(&mut self, bytes: u64) -> &mut Self {
+        match u32::try_from(bytes.div_ceil(1024 * 1024)) {
+            Ok(mib) => self.memory_mib(mib),
+            Err(_) => self.invalid(format!("memory of {} bytes is too large", bytes)),
+        }
+    }
+
+    /// Set the container rootfs disk size in GB.
+    pub fn disk_size_gb(&mut self, gb: u64) -> &mut Self {
+        if gb == 0 {
+            return self.invalid("disk size must be greater than zero".to_string());
+        }
+        self.inner.disk_size_gb = Some(gb);
+        self
+    }
+
+    /// Set the size of the container's `/dev/shm` in MiB.
+    pub fn shm_size_mib(&mut self, mib: u32) -> &mut Self {
+        if mib == 0 {
+            return self.invalid("shm size must be greater than zero".to_string());
+        }
+        self.inner.shm_size_mib = Some(mib);
+        self
+    }
+
+    /// Give the VM `mib` MiB of swap, backed by `backing`.
+    pub fn swap_mib(&mut self, mib: u32, backing: SwapBacking) -> &mut Self {
+        if mib == 0 {
+            return self.invalid("swap size must be greater than zero".to_string());
+        }
+        self.inner.swap_mib = Some(mib);
+        self.inner.swap_backing = backing;
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Process
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Set the working directory (an absolute guest path).
+    pub fn working_dir(&mut self, dir: impl Into<String>) -> &mut Self {
+        let dir = dir.into();
+        if !dir.starts_with('/') {
+            return self.invalid(format!(
+                "working_dir must be an absolute guest path, got '{}'",
+                dir
+            ));
+        }
+        self.inner.working_dir = Some(dir);
+        self
+    }
+
+    /// Add an environment variable.
+    pub fn env(&mut self, key: impl Into<String>, val: impl Into<String>) -> &mut Self {
+        let key = key.into();
+        if key.is_empty() || key.contains('=') {
+            return self.invalid(format!("invalid environment variable name '{}'", key));
+        }
+        self.inner.env.push((key, val.into()));
+        self
+    }
+
+    /// Override the image's ENTRYPOINT.
+    pub fn entrypoint(&mut self, entrypoint: Vec<String>) -> &mut Self {
+        self.inner.entrypoint = Some(entrypoint);
+        self
+    }
+
+    /// Override the image's CMD.
+    pub fn cmd(&mut self, cmd: Vec<String>) -> &mut Self {
+        self.inner.cmd = Some(cmd);
+        self
+    }
+
+    /// Run as this user (`<name|uid>[:<group|gid>]`).
+    pub fn user(&mut self, user: impl Into<String>) -> &mut Self {
+        self.inner.user = Some(user.into());
+        self
+    }
+
+    /// Set a kernel parameter in the guest, e.g. `("vm.max_map_count", "262144")`.
+    pub fn sysctl(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        let (key, value) = (key.into(), value.into());
+        if let Err(e) = validate_sysctl(&key, &value) {
+            return self.invalid(e);
+        }
+        self.inner.sysctls.insert(key, value);
+        self
+    }
+
+    /// Set a resource limit for the processes in the container, replacing
+    /// an earlier one with the same name.
+    pub fn ulimit(&mut self, ulimit: Ulimit) -> &mut Self {
+        if let Err(e) = ulimit.validate() {
+            return self.invalid(e);
+        }
+        self.inner.ulimits.retain(|u| u.name != ulimit.name);
+        self.inner.ulimits.push(ulimit);
+        self
+    }
+
+    /// Set the guest agent log filter.
+    pub fn guest_log_level(&mut self, level: impl Into<String>) -> &mut Self {
+        self.inner.guest_log_level = Some(level.into());
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Storage and network
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Add a volume, usually made with [`VolumeSpecBuilder`].
+    pub fn volume(&mut self, volume: VolumeSpec) -> &mut Self {
+        if let Err(e) = validate_volume(&volume) {
+            return self.invalid(e);
+        }
+        self.inner.volumes.push(volume);
+        self
+    }
+
+    /// Mount the box's persistent cache directory at this guest path.
+    pub fn persistent_cache(&mut self, guest_path: impl Into<String>) -> &mut Self {
+        self.inner.persistent_cache = Some(guest_path.into());
+        self
+    }
+
+    /// Enable bind mount isolation for the shared mounts directory.
+    pub fn isolate_mounts(&mut self, enabled: bool) -> &mut Self {
+        self.inner.isolate_mounts = enabled;
+        self
+    }
+
+    /// Set the network.
+    pub fn network(&mut self, network: NetworkSpec) -> &mut Self {
+        self.inner.network = network;
+        self
+    }
+
+    /// Publish a port from a spec such as `8080:80/tcp` (see [`PortSpec`]'s
+    /// `FromStr`).
+    pub fn port(&mut self, spec: &str) -> &mut Self {
+        match spec.parse::<PortSpec>() {
+            Ok(port) => self.port_spec(port),
+            Err(e) => self.invalid(e.to_string()),
+        }
+    }
+
+    /// Publish a port.
+    pub fn port_spec(&mut self, port: PortSpec) -> &mut Self {
+        if port.guest_port == 0 {
+            return self.invalid("guest port must be 1-65535".to_string());
+        }
+        self.inner.ports.push(port);
+        self
+    }
+
+    /// Set the container hostname.
+    pub fn hostname(&mut self, hostname: impl Into<String>) -> &mut Self {
+        let hostname = hostname.into();
+        if let Err(e) = validate_container_hostname(&hostname) {
+            return self.invalid(e);
+        }
+        self.inner.hostname = Some(hostname);
+        self
+    }
+
+    /// Add an `/etc/hosts` entry resolving `hostname` to `ip`.
+    pub fn add_host(&mut self, hostname: impl Into<String>, ip: impl Into<String>) -> &mut Self {
+        let (hostname, ip) = (hostname.into(), ip.into());
+        if let Err(e) = validate_host_entry(&hostname, &ip) {
+            return self.invalid(e);
+        }
+        self.inner.extra_hosts.push((hostname, ip));
+        self
+    }
+
+    /// Join a user-defined network (see [`BoxOptions::networks`]).
+    pub fn join_network(&mut self, network: impl Into<String>) -> &mut Self {
+        let network = network.into();
+        if let Err(e) = validate_network_name(&network) {
+            return self.invalid(e);
+        }
+        if !self.inner.networks.contains(&network) {
+            self.inner.networks.push(network);
+        }
+        self
+    }
+
+    /// Let the box reach the host's loopback interface (see
+    /// [`BoxOptions::host_loopback`]).
+    pub fn host_loopback(&mut self, enabled: bool) -> &mut Self {
+        self.inner.host_loopback = enabled;
+        self
+    }
+
+    /// Ask before new outbound connections (see
+    /// [`BoxOptions::prompt_connections`]).
+    pub fn prompt_connections(&mut self, enabled: bool) -> &mut Self {
+        self.inner.prompt_connections = enabled;
+        self
+    }
+
+    /// Record or replay HTTP(S) responses (see [`BoxOptions::network_mode`]).
+    pub fn network_mode(&mut self, mode: NetworkMode) -> &mut Self {
+        self.inner.network_mode = mode;
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Lifecycle
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Remove the box when it stops.
+    pub fn auto_remove(&mut self, enabled: bool) -> &mut Self {
+        self.inner.auto_remove = enabled;
+        self
+    }
+
+    /// Keep the box running after the creating process exits.
+    pub fn detach(&mut self, enabled: bool) -> &mut Self {
+        self.inner.detach = enabled;
+        self
+    }
+
+    /// Stop and remove the box this long after creation (whole seconds).
+    pub fn ttl(&mut self, ttl: std::time::Duration) -> &mut Self {
+        self.inner.ttl_secs = Some(ttl.as_secs());
+        self
+    }
+
+    /// Add a box (ID or name) this box depends on.
+    pub fn depends_on(&mut self, id_or_name: impl Into<String>) -> &mut Self {
+        self.inner.depends_on.push(id_or_name.into());
+        self
+    }
+
+    /// Stop dependencies when this box stops.
+    pub fn stop_dependencies(&mut self, enabled: bool) -> &mut Self {
+        self.inner.stop_dependencies = enabled;
+        self
+    }
+
+    /// Limit how many executions the box runs at once.
+    pub fn max_concurrent_execs(&mut self, limit: u32) -> &mut Self {
+        self.inner.max_concurrent_execs = Some(limit);
+        self
+    }
+
+    /// Wait for a free slot when the execution limit is reached.
+    pub fn queue_execs(&mut self, enabled: bool) -> &mut Self {
+        self.inner.queue_execs = enabled;
+        self
+    }
+
+    /// Forget executions this long after they exit (whole seconds; under a
+    /// second fails `build()`).
+    pub fn exec_retention(&mut self, retention: std::time::Duration) -> &mut Self {
+        self.inner.exec_retention_secs = Some(retention.as_secs());
+        self
+    }
+
+    /// Pass box and execution IDs to commands (see
+    /// [`BoxOptions::propagate_trace_context`]).
+    pub fn propagate_trace_context(&mut self, enabled: bool) -> &mut Self {
+        self.inner.propagate_trace_context = enabled;
+        self
+    }
+
+    /// Ping the guest agent this often (whole seconds).
+    pub fn heartbeat_interval(&mut self, interval: std::time::Duration) -> &mut Self {
+        self.inner.heartbeat_interval_secs = Some(interval.as_secs());
+        self
+    }
+
+    /// Mark the box unresponsive after this long without an answered
+    /// heartbeat (whole seconds).
+    pub fn unresponsive_after(&mut self, window: std::time::Duration) -> &mut Self {
+        self.inner.unresponsive_after_secs = Some(window.as_secs());
+        self
+    }
+
+    /// Restart the box when it becomes unresponsive.
+    pub fn restart_unresponsive(&mut self, enabled: bool) -> &mut Self {
+        self.inner.restart_unresponsive = enabled;
+        self
+    }
+
+    /// Set the security isolation options.
+    pub fn security(&mut self, security: SecurityOptions) -> &mut Self {
+        self.inner.security = security;
+        self
+    }
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Build
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Build and validate the configured [`BoxOptions`].
+    ///
+    /// Fails with `InvalidArgument` for the first invalid setter value, or
+    /// with the error of [`BoxOptions::sanitize`].
+    pub fn build(&self) -> BoxliteResult<BoxOptions> {
+        if let Some(message) = &self.error {
+            return Err(BoxliteError::InvalidArgument(message.clone()));
+        }
+        self.inner.sanitize()?;
+        Ok(self.inner.clone())
+    }
+}
+
+/// How to populate the box root filesystem.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub enum RootfsSpec {
+    /// Pull/resolve this registry image reference.
+    Image(String),
+    /// Use an already prepared rootfs at the given host path.
+    RootfsPath(String),
+    /// Use the busybox image embedded in the crate; works offline.
+    Builtin,
+    /// Boot from a disk image built elsewhere (e.g. by Packer), skipping
+    /// the OCI pipeline. The image must hold the whole root filesystem as
+    /// unpartitioned ext4, in a qcow2 file or not, and stay at this absolute
+    /// host path for the box's lifetime: it is never written, each box
+    /// writes to a qcow2 overlay on top of it.
+    DiskImage(String, DiskFormat),
+}
+
+impl RootfsSpec {
+    /// Whether this build embeds the image behind `RootfsSpec::Builtin`.
+    pub fn builtin_available() -> bool {
+        crate::images::builtin_available()
+    }
+}
+
+impl Default for RootfsSpec {
+    fn default() -> Self {
+        Self::Image("alpine:latest".into())
+    }
+}
+
+/// When to fetch an image from its registry at box creation.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImagePullPolicy {
+    /// Always re-check the registry, picking up tag updates.
+    ///
+    /// Digest-pinned references (`image@sha256:...`) are immutable and are
+    /// served from the local cache when present.
+    Always,
+    /// Use the cached image if present, otherwise pull it.
+    #[default]
+    IfNotPresent,
+    /// Only use the local cache; fail if the image isn't cached.
+    ///
+    /// For air-gapped hosts and deterministic builds.
+    Never,
+}
+
+impl ImagePullPolicy {
+    /// Name accepted by `FromStr` and used in serialized options.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::IfNotPresent => "if-not-present",
+            Self::Never => "never",
+        }
+    }
+}
+
+impl std::str::FromStr for ImagePullPolicy {
+    type Err = boxlite_shared::errors::BoxliteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(Self::Always),
+            "if-not-present" | "missing" => Ok(Self::IfNotPresent),
+            "never" => Ok(Self::Never),
+            _ => Err(boxlite_shared::errors::BoxliteError::InvalidArgument(
+                format!(
+                    "invalid pull policy '{}': expected always, if-not-present, or never",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// Filesystem mount specification.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct VolumeSpec {
+    /// Host directory, or the volume name when `driver` is set.
+    pub host_path: String,
+    pub guest_path: String,
+    pub read_only: bool,
+    /// Volume driver that mounts the volume on the host (see [`crate::plugins`]).
+    #[serde(default)]
+    pub driver: Option<String>,
+    /// Mount options: `ro`, `noexec`, `nosuid`, `nodev`, `uid=<n>[-<m>]`,
+    /// `gid=<n>[-<m>]`.
+    ///
+    /// `ro` is the same as `read_only`. `uid`/`gid` show files owned by the
+    /// volume directory's owner as owned by that user or group in the box
+    /// (an idmapped mount; the guest kernel must support it for virtiofs).
+    /// A range `<n>-<m>` maps as many ids from the owner's up to `n`
+    /// through `m`.
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// How file ownership is mapped between the host and the box.
+    ///
+    /// Without it ids pass through unchanged, so files the box writes as
+    /// root are root-owned on the host.
+    #[serde(default)]
+    pub chown: Option<VolumeChown>,
+}
+
+/// Ownership mapping of a volume, done with an idmapped mount in the guest.
+///
+/// Files of the host directory's owner appear owned by the mapped user in
+/// the box, and files that user creates are owned by the directory's owner
+/// on the host. Files of other owners appear as `nobody`.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeChown {
+    /// Map the user the box's process runs as to the host user owning the
+    /// directory, so the host user can edit what the box produces.
+    HostUser,
+    /// Map this box uid and gid to the owner of the directory.
+    Map { uid: u32, gid: u32 },
+}
+
+/// Flags [`VolumeSpec::options`] may hold besides `uid=`/`gid=`.
+const VOLUME_FLAGS: &[&str] = &["ro", "noexec", "nosuid", "nodev"];
+
+/// Whether `value` is an id `<n>` or an id range `<n>-<m>` of at most
+/// `u32::MAX` ids.
+fn is_id_range(value: &str) -> bool {
+    match value.split_once('-') {
+        Some((first, last)) => match (first.parse::<u32>(), last.parse::<u32>()) {
+            (Ok(first), Ok(last)) => first <= last && last - first < u32::MAX,
+            _ => false,
+        },
+        None => value.parse::<u32>().is_ok(),
+    }
+}
+
+impl VolumeSpec {
+    /// Whether the volume is mounted read-only, by `read_only` or the `ro` option.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || self.options.iter().any(|o| o == "ro")
+    }
+
+    /// Options the volume is mounted with: `ro` or `rw`, then the rest of
+    /// `options` in order.
+    pub fn effective_options(&self) -> Vec<String> {
+        let mode = if self.is_read_only() { "ro" } else { "rw" };
+        std::iter::once(mode.to_string())
+            .chain(self.mount_options())
+            .collect()
+    }
+
+    /// Options of the container mount besides ro/rw, with a `Map` chown
+    /// given as `uid=`/`gid=`.
+    pub(crate) fn mount_options(&self) -> Vec<String> {
+        let mut options: Vec<String> = self
+            .options
+            .iter()
+            .filter(|o| *o != "ro")
+            .cloned()
+            .collect();
+        if let Some(VolumeChown::Map { uid, gid }) = self.chown {
+            options.push(format!("uid={}", uid));
+            options.push(format!("gid={}", gid));
+        }
+        options
+    }
+}
+
+/// Builder for a validated [`VolumeSpec`].
+///
+/// ```
+/// use boxlite::VolumeSpecBuilder;
+///
+/// let volume = VolumeSpecBuilder::new("/srv/data", "/data")
+///     .read_only(true)
+///     .build()?;
+/// # Ok::<(), boxlite::BoxliteError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct VolumeSpecBuilder {
+    inner: VolumeSpec,
+}
+
+impl VolumeSpecBuilder {
+    /// Mount `host_path` (or the named volume, with a driver) at `guest_path`.
+    pub fn new(host_path: impl Into<String>, guest_path: impl Into<String>) -> Self {
+        Self {
+            inner: VolumeSpec {
+                host_path: host_path.into(),
+                guest_path: guest_path.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Mount read-only.
+    pub fn read_only(&mut self, read_only: bool) -> &mut Self {
+        self.inner.read_only = read_only;
+        self
+    }
+
+    /// Have this volume driver provide the volume.
+    pub fn driver(&mut self, driver: impl Into<String>) -> &mut Self {
+        self.inner.driver = Some(driver.into());
+        self
+    }
+
+    /// Add a mount option (`ro`, `noexec`, `nosuid`, `nodev`, `uid=<n>[-<m>]`,
+    /// `gid=<n>[-<m>]`).
+    pub fn option(&mut self, option: impl Into<String>) -> &mut Self {
+        self.inner.options.push(option.into());
+        self
+    }
+
+    /// Map file ownership between the host and the box.
+    pub fn chown(&mut self, chown: VolumeChown) -> &mut Self {
+        self.inner.chown = Some(chown);
+        self
+    }
+
+    /// Build the volume, checking its paths.
+    pub fn build(&self) -> BoxliteResult<VolumeSpec> {
+        validate_volume(&self.inner).map_err(BoxliteError::InvalidArgument)?;
+        Ok(self.inner.clone())
+    }
+}
+
+fn validate_volume(volume: &VolumeSpec) -> Result<(), String> {
+    if volume.host_path.is_empty() {
+        return Err(format!(
+            "volume for '{}' has no host path or volume name",
+            volume.guest_path
+        ));
+    }
+    if !volume.guest_path.starts_with('/') {
+        return Err(format!(
+            "volume guest path must be absolute, got '{}'",
+            volume.guest_path
+        ));
+    }
+    validate_volume_options(volume)
+}
+
+fn validate_volume_options(volume: &VolumeSpec) -> Result<(), String> {
+    for option in &volume.options {
+        let valid = match option.split_once('=') {
+            Some(("uid" | "gid", id)) => is_id_range(id),
+            Some(_) => false,
+            None => VOLUME_FLAGS.contains(&option.as_str()),
+        };
+        if !valid {
+            return Err(format!(
+                "invalid option '{}' for volume '{}'; expected ro, noexec, nosuid, nodev, uid=<n>[-<m>] or gid=<n>[-<m>]",
+                option, volume.guest_path
+            ));
+        }
+        if volume.chown.is_some() && (option.starts_with("uid=") || option.starts_with("gid=")) {
+            return Err(format!(
+                "volume '{}' sets both chown and the '{}' option",
+                volume.guest_path, option
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check a hostname against RFC 1123: at most 253 characters of
+/// dot-separated labels of up to 63 letters, digits and inner hyphens.
+fn validate_hostname(hostname: &str) -> Result<(), String> {
+    let valid = !hostname.is_empty()
+        && hostname.len() <= 253
+        && hostname.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if !valid {
+        return Err(format!("invalid hostname '{}'", hostname));
+    }
+    Ok(())
+}
+
+/// A container hostname also has to fit the kernel's 64-byte UTS name.
+fn validate_container_hostname(hostname: &str) -> Result<(), String> {
+    validate_hostname(hostname)?;
+    if hostname.len() > 64 {
+        return Err(format!(
+            "hostname '{}' is longer than the 64 bytes Linux allows",
+            hostname
+        ));
+    }
+    Ok(())
+}
+
+/// Network names follow the hostname rules.
+fn validate_network_name(network: &str) -> Result<(), String> {
+    validate_hostname(network).map_err(|_| format!("invalid network name '{}'", network))
+}
+
+fn validate_host_entry(hostname: &str, ip: &str) -> Result<(), String> {
+    validate_hostname(hostname)?;
+    if ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(format!(
+            "invalid IP address '{}' for host '{}'",
+            ip, hostname
+        ));
+    }
+    Ok(())
+}
+
+fn validate_sysctl(key: &str, value: &str) -> Result<(), String> {
+    if !boxlite_shared::constants::sysctls::is_allowed(key) {
+        return Err(format!("sysctl '{}' is not allowed", key));
+    }
+    if value.trim().is_empty() || value.contains(['\n', '\r']) {
+        return Err(format!("invalid value '{}' for sysctl '{}'", value, key));
+    }
+    Ok(())
+}
+
+/// Limits [`Ulimit`] can set.
+pub const ULIMIT_NAMES: &[&str] = &["nofile", "nproc", "core", "memlock"];
+
+/// Resource limit (rlimit) for the processes in a box's container.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct Ulimit {
+    /// One of [`ULIMIT_NAMES`]
+    pub name: String,
+    /// Soft limit ([`Ulimit::UNLIMITED`] for no limit)
+    pub soft: u64,
+    /// Hard limit ([`Ulimit::UNLIMITED`] for no limit)
+    pub hard: u64,
+}
+
+impl Ulimit {
+    /// No limit (`RLIM_INFINITY`).
+    pub const UNLIMITED: u64 = u64::MAX;
+
+    /// A limit with these soft and hard values.
+    pub fn new(name: impl Into<String>, soft: u64, hard: u64) -> Self {
+        Self {
+            name: name.into(),
+            soft,
+            hard,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !ULIMIT_NAMES.contains(&self.name.as_str()) {
+            return Err(format!(
+                "unknown ulimit '{}'; expected one of {}",
+                self.name,
+                ULIMIT_NAMES.join(", ")
+            ));
+        }
+        if self.soft > self.hard {
+            return Err(format!(
+                "ulimit '{}' soft limit {} exceeds hard limit {}",
+                self.name, self.soft, self.hard
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Ulimit {
+    type Err = BoxliteError;
+
+    /// Parse `name=soft[:hard]` as `docker run --ulimit` takes it, e.g.
+    /// `nofile=1024:4096`. Without a hard limit, it equals the soft one;
+    /// `unlimited` or `-1` means no limit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            BoxliteError::InvalidArgument(format!(
+                "invalid ulimit '{}' (expected name=soft[:hard])",
+                s
+            ))
+        };
+        let parse_value = |value: &str| match value {
+            "unlimited" | "-1" => Ok(Self::UNLIMITED),
+            _ => value.parse::<u64>().map_err(|_| invalid()),
+        };
+
+        let (name, values) = s.trim().split_once('=').ok_or_else(invalid)?;
+        let (soft, hard) = match values.split_once(':') {
+            Some((soft, hard)) => (parse_value(soft)?, parse_value(hard)?),
+            None => {
+                let value = parse_value(values)?;
+                (value, value)
+            }
+        };
+        let ulimit = Self::new(name, soft, hard);
+        ulimit.validate().map_err(BoxliteError::InvalidArgument)?;
+        Ok(ulimit)
+    }
+}
+
+/// Where the pages of [`BoxOptions::swap_mib`] go.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapBacking {
+    /// A swap file on the guest rootfs disk, created at each start: adds
+    /// real capacity at the cost of disk I/O.
+    #[default]
+    File,
+    /// Compressed RAM (zram): fast, but takes memory from the VM, so it
+    /// only stretches `memory_mib` by the workload's compression ratio.
+    Zram,
+}
+
+/// What happens to a box's HTTP(S) requests (see
+/// [`BoxOptions::network_mode`]).
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema,
+)]
+pub enum NetworkMode {
+    /// Requests go to the network.
+    #[default]
+    Live,
+    /// Requests go to the network and their responses are saved in the
+    /// directory, which is created if needed.
+    Record(PathBuf),
+    /// Requests are answered from a directory saved by `Record`; those it
+    /// has no response for fail with `502 Bad Gateway`. The box can't open
+    /// connections to anything but the proxy.
+    Replay(PathBuf),
+}
+
+impl NetworkMode {
+    /// Directory of the recorded responses, and whether they're replayed.
+    pub fn recording(&self) -> Option<(&Path, bool)> {
+        match self {
+            Self::Live => None,
+            Self::Record(dir) => Some((dir, false)),
+            Self::Replay(dir) => Some((dir, true)),
+        }
+    }
+}
+
+/// Network isolation options.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub enum NetworkSpec {
+    #[default]
+    Isolated,
+    // Host,
+    /// Network provided by the named network driver (see [`crate::plugins`]).
+    Custom(String),
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub enum PortProtocol {
     #[default]
     Tcp,
     Udp,
@@ -740,7 +2435,7 @@ fn default_protocol() -> PortProtocol {
 }
 
 /// Port mapping specification (host -> guest).
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct PortSpec {
     pub host_port: Option<u16>, // None/0 => dynamically assigned
     pub guest_port: u16,
@@ -749,6 +2444,58 @@ pub struct PortSpec {
     pub host_ip: Option<String>, // Optional bind IP, defaults to 0.0.0.0/:: if None
 }
 
+impl std::str::FromStr for PortSpec {
+    type Err = BoxliteError;
+
+    /// Parse `[hostPort:]boxPort[/tcp|udp]`, e.g. `8080:80/tcp` or `80`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = BoxliteError::InvalidArgument;
+        let parse_port = |port: &str| match port.parse::<u16>() {
+            Ok(0) => Err(invalid("port must be 1-65535".to_string())),
+            Ok(n) => Ok(n),
+            Err(_) => Err(invalid(format!("invalid port number {:?}", port))),
+        };
+
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(invalid("empty port spec".to_string()));
+        }
+        let (rest, protocol) = match s.split_once('/') {
+            Some((rest, proto)) => {
+                let protocol = if proto.eq_ignore_ascii_case("tcp") {
+                    PortProtocol::Tcp
+                } else if proto.eq_ignore_ascii_case("udp") {
+                    PortProtocol::Udp
+                } else {
+                    return Err(invalid(format!(
+                        "invalid protocol {:?}; use tcp or udp",
+                        proto
+                    )));
+                };
+                (rest.trim(), protocol)
+            }
+            None => (s, PortProtocol::Tcp),
+        };
+        let parts: Vec<&str> = rest.splitn(2, ':').map(str::trim).collect();
+        let (host_port, guest_port) = match parts.as_slice() {
+            [guest] => (None, parse_port(guest)?),
+            [host, guest] => (Some(parse_port(host)?), parse_port(guest)?),
+            _ => {
+                return Err(invalid(format!(
+                    "invalid port spec {:?}; use hostPort:boxPort or boxPort[/tcp]",
+                    s
+                )));
+            }
+        };
+        Ok(PortSpec {
+            host_port,
+            guest_port,
+            protocol,
+            host_ip: None,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -779,6 +2526,129 @@ mod tests {
         assert!(!opts.detach, "detach should default to false via serde");
     }
 
+    #[test]
+    fn test_box_options_builder() {
+        let volume = VolumeSpecBuilder::new("/srv/data", "/data")
+            .read_only(true)
+            .build()
+            .unwrap();
+        let opts = BoxOptions::builder()
+            .image("python:3.12-slim")
+            .cpus_fraction(1.5)
+            .memory_bytes(512 * 1024 * 1024 + 1)
+            .port("8080:80/tcp")
+            .port("53/udp")
+            .volume(volume)
+            .env("A", "1")
+            .build()
+            .unwrap();
+        assert!(matches!(opts.rootfs, RootfsSpec::Image(ref image) if image == "python:3.12-slim"));
+        assert_eq!(opts.cpus, Some(2));
+        assert_eq!(opts.memory_mib, Some(513));
+        assert_eq!(opts.ports.len(), 2);
+        assert_eq!(opts.ports[0].host_port, Some(8080));
+        assert_eq!(opts.ports[1].host_port, None);
+        assert!(matches!(opts.ports[1].protocol, PortProtocol::Udp));
+        assert!(opts.volumes[0].read_only);
+        assert_eq!(opts.env, vec![("A".to_string(), "1".to_string())]);
+    }
+
+    #[test]
+    fn test_box_options_builder_rejects() {
+        let invalid = |builder: &BoxOptionsBuilder| {
+            matches!(builder.build(), Err(BoxliteError::InvalidArgument(_)))
+        };
+        assert!(invalid(BoxOptions::builder().port("80:80/sctp")));
+        assert!(invalid(BoxOptions::builder().port("0:80")));
+        assert!(invalid(BoxOptions::builder().cpus_fraction(0.0)));
+        assert!(invalid(BoxOptions::builder().cpus_fraction(300.0)));
+        assert!(invalid(BoxOptions::builder().memory_bytes(u64::MAX)));
+        assert!(invalid(BoxOptions::builder().working_dir("app")));
+        assert!(invalid(BoxOptions::builder().env("A=B", "1")));
+        // The first invalid value is reported
+        let err = BoxOptions::builder().cpus(0).port("x").build().unwrap_err();
+        assert!(err.to_string().contains("cpus"));
+
+        assert!(VolumeSpecBuilder::new("/srv", "data").build().is_err());
+        assert!(VolumeSpecBuilder::new("", "/data").build().is_err());
+        assert!(
+            VolumeSpecBuilder::new("/srv", "/data")
+                .option("exec")
+                .build()
+                .is_err()
+        );
+        assert!(
+            VolumeSpecBuilder::new("/srv", "/data")
+                .option("uid=me")
+                .build()
+                .is_err()
+        );
+        assert!(
+            VolumeSpecBuilder::new("/srv", "/data")
+                .option("uid=100000-165535")
+                .build()
+                .is_ok()
+        );
+        assert!(
+            VolumeSpecBuilder::new("/srv", "/data")
+                .option("gid=2000-1000")
+                .build()
+                .is_err()
+        );
+
+        // Combinations are checked like create() does
+        assert!(matches!(
+            BoxOptions::builder().detach(true).build(),
+            Err(BoxliteError::Config(_))
+        ));
+        assert!(
+            BoxOptions::builder()
+                .detach(true)
+                .auto_remove(false)
+                .build()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_pull_policy_parse() {
+        assert_eq!(
+            "always".parse::<ImagePullPolicy>().unwrap(),
+            ImagePullPolicy::Always
+        );
+        assert_eq!(
+            "if-not-present".parse::<ImagePullPolicy>().unwrap(),
+            ImagePullPolicy::IfNotPresent
+        );
+        assert_eq!(
+            "missing".parse::<ImagePullPolicy>().unwrap(),
+            ImagePullPolicy::IfNotPresent
+        );
+        assert_eq!(
+            "never".parse::<ImagePullPolicy>().unwrap(),
+            ImagePullPolicy::Never
+        );
+        assert!("sometimes".parse::<ImagePullPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_pull_policy_serde() {
+        let json = r#"{
+            "rootfs": {"Image": "alpine:latest"},
+            "env": [],
+            "volumes": [],
+            "network": "Isolated",
+            "ports": [],
+            "pull_policy": "never"
+        }"#;
+        let opts: BoxOptions = serde_json::from_str(json).unwrap();
+        assert_eq!(opts.pull_policy, ImagePullPolicy::Never);
+        assert_eq!(
+            BoxOptions::default().pull_policy,
+            ImagePullPolicy::IfNotPresent
+        );
+    }
+
     #[test]
     fn test_box_options_serde_explicit_values() {
         let json = r#"{
@@ -860,6 +2730,394 @@ mod tests {
         assert!(opts3.sanitize().is_ok());
     }
 
+    #[test]
+    fn test_sanitize_persistent_cache_path() {
+        let opts = BoxOptions {
+            persistent_cache: Some("/cache".into()),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_ok());
+
+        let opts = BoxOptions {
+            persistent_cache: Some("cache".into()),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_disk_image() {
+        let disk_image = |path: &str, format| BoxOptions {
+            rootfs: RootfsSpec::DiskImage(path.into(), format),
+            ..Default::default()
+        };
+        assert!(
+            disk_image("/images/base.qcow2", DiskFormat::Qcow2)
+                .sanitize()
+                .is_ok()
+        );
+        assert!(
+            disk_image("/images/base.ext4", DiskFormat::Ext4)
+                .sanitize()
+                .is_ok()
+        );
+        assert!(
+            disk_image("base.qcow2", DiskFormat::Qcow2)
+                .sanitize()
+                .is_err()
+        );
+        assert!(
+            disk_image("/images/layer.erofs", DiskFormat::Erofs)
+                .sanitize()
+                .is_err()
+        );
+
+        let json = serde_json::to_value(RootfsSpec::DiskImage(
+            "/images/base.qcow2".into(),
+            DiskFormat::Qcow2,
+        ))
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"DiskImage": ["/images/base.qcow2", "qcow2"]})
+        );
+    }
+
+    #[test]
+    fn test_sanitize_ttl() {
+        let opts = BoxOptions {
+            ttl_secs: Some(7200),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_ok());
+
+        let opts = BoxOptions {
+            ttl_secs: Some(0),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_max_concurrent_execs() {
+        let opts = BoxOptions::builder()
+            .max_concurrent_execs(4)
+            .build()
+            .unwrap();
+        assert_eq!(opts.max_concurrent_execs, Some(4));
+
+        let opts = BoxOptions {
+            max_concurrent_execs: Some(0),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_volume_effective_options() {
+        let volume = VolumeSpecBuilder::new("/srv", "/data")
+            .option("noexec")
+            .option("ro")
+            .option("uid=1000")
+            .build()
+            .unwrap();
+        assert!(volume.is_read_only());
+        assert_eq!(volume.effective_options(), vec!["ro", "noexec", "uid=1000"]);
+
+        let volume = VolumeSpecBuilder::new("/srv", "/data").build().unwrap();
+        assert_eq!(volume.effective_options(), vec!["rw"]);
+    }
+
+    #[test]
+    fn test_volume_chown() {
+        let volume = VolumeSpecBuilder::new("/srv", "/data")
+            .chown(VolumeChown::Map {
+                uid: 1000,
+                gid: 100,
+            })
+            .build()
+            .unwrap();
+        assert_eq!(
+            volume.effective_options(),
+            vec!["rw", "uid=1000", "gid=100"]
+        );
+
+        let json = serde_json::to_string(&volume.chown).unwrap();
+        assert_eq!(json, r#"{"map":{"uid":1000,"gid":100}}"#);
+        let host_user: VolumeChown = serde_json::from_str(r#""host_user""#).unwrap();
+        assert_eq!(host_user, VolumeChown::HostUser);
+
+        assert!(
+            VolumeSpecBuilder::new("/srv", "/data")
+                .chown(VolumeChown::HostUser)
+                .option("uid=0")
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sanitize_heartbeat() {
+        let opts = BoxOptions::builder()
+            .heartbeat_interval(std::time::Duration::from_secs(5))
+            .unresponsive_after(std::time::Duration::from_secs(30))
+            .build()
+            .unwrap();
+        assert_eq!(opts.heartbeat_interval_secs, Some(5));
+        assert_eq!(opts.unresponsive_after_secs, Some(30));
+
+        let result = BoxOptions::builder()
+            .heartbeat_interval(std::time::Duration::from_millis(500))
+            .build();
+        assert!(result.is_err(), "sub-second interval rounds to zero");
+
+        let opts = BoxOptions {
+            unresponsive_after_secs: Some(0),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_shm_size() {
+        let opts = BoxOptions::builder().shm_size_mib(1024).build().unwrap();
+        assert_eq!(opts.shm_size_mib, Some(1024));
+        assert!(BoxOptions::builder().shm_size_mib(0).build().is_err());
+
+        let opts = BoxOptions {
+            shm_size_mib: Some(0),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_swap() {
+        let opts = BoxOptions::builder()
+            .swap_mib(2048, SwapBacking::Zram)
+            .build()
+            .unwrap();
+        assert_eq!(opts.swap_mib, Some(2048));
+        assert_eq!(opts.swap_backing, SwapBacking::Zram);
+        assert!(
+            BoxOptions::builder()
+                .swap_mib(0, SwapBacking::File)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sanitize_exec_retention() {
+        let opts = BoxOptions::builder()
+            .exec_retention(std::time::Duration::from_secs(90))
+            .build()
+            .unwrap();
+        assert_eq!(opts.exec_retention_secs, Some(90));
+        assert!(
+            BoxOptions::builder()
+                .exec_retention(std::time::Duration::from_millis(500))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_ulimit_from_str() {
+        let ulimit: Ulimit = "nofile=1024:4096".parse().unwrap();
+        assert_eq!(ulimit, Ulimit::new("nofile", 1024, 4096));
+
+        let ulimit: Ulimit = "nproc=512".parse().unwrap();
+        assert_eq!(ulimit, Ulimit::new("nproc", 512, 512));
+
+        let ulimit: Ulimit = "core=unlimited".parse().unwrap();
+        assert_eq!(ulimit.soft, Ulimit::UNLIMITED);
+        let ulimit: Ulimit = "memlock=-1:-1".parse().unwrap();
+        assert_eq!(ulimit.hard, Ulimit::UNLIMITED);
+
+        for spec in ["nofile", "nofile=abc", "stack=1024", "nofile=4096:1024"] {
+            assert!(spec.parse::<Ulimit>().is_err(), "{:?} should fail", spec);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_ulimits() {
+        let opts = BoxOptions::builder()
+            .ulimit(Ulimit::new("nofile", 1024, 1024))
+            .ulimit(Ulimit::new("nofile", 65536, 65536))
+            .build()
+            .unwrap();
+        assert_eq!(opts.ulimits, vec![Ulimit::new("nofile", 65536, 65536)]);
+
+        let opts = BoxOptions {
+            ulimits: vec![Ulimit::new("nproc", 10, 10), Ulimit::new("nproc", 20, 20)],
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_sysctls() {
+        let opts = BoxOptions::builder()
+            .sysctl("vm.max_map_count", "262144")
+            .sysctl("net.core.somaxconn", "4096")
+            .sysctl("net.ipv4.ip_local_port_range", "1024 65000")
+            .build()
+            .unwrap();
+        assert_eq!(opts.sysctls.len(), 3);
+        assert_eq!(opts.sysctls["vm.max_map_count"], "262144");
+
+        for key in [
+            "kernel.panic",
+            "vm.drop_caches",
+            "net.core",
+            "net.core../x",
+            "",
+        ] {
+            assert!(
+                BoxOptions::builder().sysctl(key, "1").build().is_err(),
+                "sysctl {:?} should be rejected",
+                key
+            );
+        }
+        assert!(
+            BoxOptions::builder()
+                .sysctl("vm.swappiness", "")
+                .build()
+                .is_err()
+        );
+
+        let opts = BoxOptions {
+            sysctls: HashMap::from([("fs.file-max".to_string(), "1\n2".to_string())]),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_hostname_and_extra_hosts() {
+        let opts = BoxOptions::builder()
+            .hostname("web-1.internal")
+            .add_host("db", "10.0.0.5")
+            .add_host("db6", "fd00::5")
+            .build()
+            .unwrap();
+        assert_eq!(opts.hostname.as_deref(), Some("web-1.internal"));
+        assert_eq!(opts.extra_hosts.len(), 2);
+
+        let long_labels = format!("{}.{}", "a".repeat(63), "b".repeat(63));
+        for hostname in [
+            "",
+            "-web",
+            "web_1",
+            "a..b",
+            &"a".repeat(64),
+            &format!("{}.internal", "a".repeat(60)),
+        ] {
+            assert!(
+                BoxOptions::builder().hostname(hostname).build().is_err(),
+                "hostname {:?} should be rejected",
+                hostname
+            );
+        }
+        // The 63-character limit is per label; /etc/hosts names can be longer
+        assert!(
+            BoxOptions::builder()
+                .hostname(format!("{}.internal", "a".repeat(54)))
+                .add_host(&long_labels, "10.0.0.5")
+                .build()
+                .is_ok()
+        );
+        assert!(
+            BoxOptions::builder()
+                .add_host(format!("{}.{}", "a".repeat(63), "b".repeat(64)), "10.0.0.5")
+                .build()
+                .is_err()
+        );
+        assert!(
+            BoxOptions::builder()
+                .add_host("db", "10.0.0")
+                .build()
+                .is_err()
+        );
+        assert!(
+            BoxOptions::builder()
+                .add_host("d b", "10.0.0.5")
+                .build()
+                .is_err()
+        );
+
+        let opts = BoxOptions {
+            extra_hosts: vec![("db".to_string(), "not-an-ip".to_string())],
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_networks() {
+        let opts = BoxOptions::builder()
+            .join_network("backend")
+            .join_network("backend")
+            .build()
+            .unwrap();
+        assert_eq!(opts.networks, vec!["backend".to_string()]);
+
+        assert!(
+            BoxOptions::builder()
+                .join_network("my net")
+                .build()
+                .is_err()
+        );
+
+        let opts = BoxOptions {
+            networks: vec!["backend".to_string()],
+            network: NetworkSpec::Custom("cni".to_string()),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+
+        let opts = BoxOptions {
+            prompt_connections: true,
+            network: NetworkSpec::Custom("cni".to_string()),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_network_mode() {
+        let opts = BoxOptions::builder()
+            .network_mode(NetworkMode::Replay(PathBuf::from("/srv/cassettes")))
+            .build()
+            .unwrap();
+        assert_eq!(
+            opts.network_mode.recording(),
+            Some((Path::new("/srv/cassettes"), true))
+        );
+
+        assert!(
+            BoxOptions::builder()
+                .network_mode(NetworkMode::Record(PathBuf::from("cassettes")))
+                .build()
+                .is_err()
+        );
+
+        let opts = BoxOptions {
+            network_mode: NetworkMode::Record(PathBuf::from("/srv/cassettes")),
+            prompt_connections: true,
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+
+        let opts = BoxOptions {
+            network_mode: NetworkMode::Replay(PathBuf::from("/srv/cassettes")),
+            network: NetworkSpec::Custom("cni".to_string()),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
     // ========================================================================
     // SecurityOptionsBuilder tests
     // ========================================================================
@@ -0,0 +1,179 @@
+//! Dry run of box creation.
+//!
+//! [`BoxliteRuntime::plan`](crate::BoxliteRuntime::plan) runs the checks
+//! `create()` and `start()` would run on a set of options and reports what
+//! the box would get, without creating it. Configuration errors (an invalid
+//! option, a name already taken, a missing volume directory, an image that
+//! the pull policy forbids pulling) surface here in milliseconds instead of
+//! after a long provisioning run.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::litebox::resolve_user_volumes;
+use crate::runtime::constants::images;
+use crate::runtime::constants::vm_defaults::{
+    DEFAULT_CPUS, DEFAULT_DISK_SIZE_GB, DEFAULT_MEMORY_MIB,
+};
+use crate::runtime::options::{
+    BoxOptions, ImagePullPolicy, PortSpec, RootfsSpec, StorageDriver, VolumeSpec,
+};
+use crate::runtime::rt_impl::SharedRuntimeImpl;
+
+/// What creating a box with a set of options would do.
+#[derive(Clone, Debug, Serialize)]
+pub struct BoxPlan {
+    pub name: Option<String>,
+    pub project: String,
+    /// Image reference or rootfs directory the box boots from
+    pub image: String,
+    pub image_status: ImagePlanStatus,
+    /// Manifest digest, if the image is cached
+    pub image_id: Option<String>,
+    /// Compressed size of the image layers in bytes, if the image is cached
+    pub image_size: Option<u64>,
+    pub cpus: u8,
+    pub memory_mib: u32,
+    /// Virtual size of the container disk. The disk is grown to at least
+    /// the image size.
+    pub disk_size_gb: u64,
+    pub storage_driver: StorageDriver,
+    /// Volumes, host directories resolved to absolute paths
+    pub volumes: Vec<VolumeSpec>,
+    pub ports: Vec<PortSpec>,
+    /// IDs of the boxes started before this one
+    pub depends_on: Vec<String>,
+    pub pull_policy: ImagePullPolicy,
+}
+
+/// How the box's rootfs would be obtained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImagePlanStatus {
+    /// Cached; starting the box uses it as is
+    Cached,
+    /// Cached, but the `Always` pull policy checks the registry on start
+    Refresh,
+    /// Not cached; starting the box pulls it
+    Pull,
+    /// Pulled while planning
+    Pulled,
+    /// A rootfs directory or disk image on the host
+    Local,
+}
+
+pub(crate) async fn plan(
+    runtime: &SharedRuntimeImpl,
+    options: BoxOptions,
+    name: Option<String>,
+    pull: bool,
+) -> BoxliteResult<BoxPlan> {
+    let config = runtime.plan_config(options, name)?;
+    let options = &config.options;
+
+    // Driver volumes are only mounted at start; their names stay as given
+    let mut volumes = options.volumes.clone();
+    for volume in volumes.iter_mut().filter(|v| v.driver.is_none()) {
+        let resolved = resolve_user_volumes(std::slice::from_ref(volume))?;
+        volume.host_path = resolved[0].host_path.display().to_string();
+    }
+
+    let mut plan = BoxPlan {
+        name: config.name.clone(),
+        project: config.project.clone(),
+        image: String::new(),
+        image_status: ImagePlanStatus::Local,
+        image_id: None,
+        image_size: None,
+        cpus: options.cpus.unwrap_or(DEFAULT_CPUS),
+        memory_mib: options.memory_mib.unwrap_or(DEFAULT_MEMORY_MIB),
+        disk_size_gb: options.disk_size_gb.unwrap_or(DEFAULT_DISK_SIZE_GB),
+        storage_driver: config.storage_driver,
+        volumes,
+        ports: options.ports.clone(),
+        depends_on: options.depends_on.clone(),
+        pull_policy: options.pull_policy,
+    };
+
+    match &options.rootfs {
+        RootfsSpec::RootfsPath(path) => {
+            if !Path::new(path).is_dir() {
+                return Err(BoxliteError::NotFound(format!("rootfs directory {}", path)));
+            }
+            plan.image = path.clone();
+        }
+        RootfsSpec::DiskImage(path, _) => {
+            // Checked with the rest of the config by plan_config()
+            plan.image = path.clone();
+            plan.image_size = config.disk_image.as_ref().map(|source| source.size);
+        }
+        RootfsSpec::Image(image) => {
+            plan.image = image.clone();
+            plan_image(runtime, &mut plan, image, options.pull_policy, pull).await?;
+        }
+        RootfsSpec::Builtin => {
+            plan.image = images::BUILTIN.to_string();
+            if !RootfsSpec::builtin_available()
+                && runtime
+                    .image_manager
+                    .resolve_cached(images::BUILTIN)
+                    .await?
+                    .is_none()
+            {
+                return Err(BoxliteError::Unsupported(format!(
+                    "image {} is not available: boxlite was built without BOXLITE_BUSYBOX",
+                    images::BUILTIN
+                )));
+            }
+            // Never fetched from a registry, only assembled locally
+            plan_image(
+                runtime,
+                &mut plan,
+                images::BUILTIN,
+                ImagePullPolicy::IfNotPresent,
+                pull,
+            )
+            .await?;
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Fill in the image fields of `plan`, pulling the image first if `pull`.
+async fn plan_image(
+    runtime: &SharedRuntimeImpl,
+    plan: &mut BoxPlan,
+    image: &str,
+    policy: ImagePullPolicy,
+    pull: bool,
+) -> BoxliteResult<()> {
+    let manager = &runtime.image_manager;
+    let cached = manager.resolve_cached(image).await?;
+
+    plan.image_status = if pull && (cached.is_none() || policy == ImagePullPolicy::Always) {
+        manager.pull_with_policy(image, policy).await?;
+        ImagePlanStatus::Pulled
+    } else if cached.is_none() {
+        if policy == ImagePullPolicy::Never {
+            return Err(BoxliteError::NotFound(format!(
+                "image {} is not cached and the pull policy is never",
+                image
+            )));
+        }
+        ImagePlanStatus::Pull
+    } else if policy == ImagePullPolicy::Always {
+        ImagePlanStatus::Refresh
+    } else {
+        ImagePlanStatus::Cached
+    };
+
+    if let Some(info) = manager.info(image).await? {
+        plan.image_id = Some(info.id);
+        plan.image_size = info.size.map(|size| size.as_bytes());
+    }
+    Ok(())
+}
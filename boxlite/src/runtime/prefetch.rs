@@ -0,0 +1,196 @@
+//! Background refresh of frequently used images.
+//!
+//! Every box created from an image counts as a use of that reference. With
+//! prefetch enabled (see
+//! [`BoxliteRuntime::set_image_prefetch`](crate::BoxliteRuntime::set_image_prefetch)),
+//! a runtime that has been up for a while checks whether it is idle, meaning
+//! no execution is running in its boxes, and then pulls the most used
+//! references again. Each image is pulled with the pull policy its boxes used:
+//! - `Always`: the tag is re-resolved and updated layers are downloaded
+//! - `IfNotPresent`: only images missing from the cache are pulled
+//! - `Never`: the image is left alone
+//!
+//! Removing an image drops its counts, so it isn't pulled back.
+//!
+//! The setting and the time of the last round live in the database, so at
+//! most one round per [`PREFETCH_INTERVAL`] runs across all processes
+//! sharing a home directory.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::db::ImageUsage;
+use crate::litebox::prewarm_image;
+use crate::runtime::options::ImagePullPolicy;
+use crate::runtime::rt_impl::SharedRuntimeImpl;
+
+/// Minimum time between two prefetch rounds.
+pub const PREFETCH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Images refreshed per round, most used first.
+pub const PREFETCH_MAX_IMAGES: usize = 5;
+
+/// Images unused for longer than this are no longer refreshed.
+pub const PREFETCH_USAGE_WINDOW: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// How often a runtime checks whether a round is due. Also how long it must
+/// be up before its first round, so short-lived CLI commands never prefetch.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// State of background image prefetch.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImagePrefetchStatus {
+    pub enabled: bool,
+    /// When the last round started
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// Images pulled or found up to date in the last round
+    pub last_refreshed: u32,
+    /// First error of the last round
+    pub last_error: Option<String>,
+    /// Images the next round refreshes, most used first
+    pub candidates: Vec<PrefetchCandidate>,
+}
+
+/// A frequently used image reference.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrefetchCandidate {
+    pub reference: String,
+    /// Boxes created from the reference
+    pub use_count: u64,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Pull policy of the last box created from it
+    pub pull_policy: ImagePullPolicy,
+}
+
+impl From<ImageUsage> for PrefetchCandidate {
+    fn from(usage: ImageUsage) -> Self {
+        Self {
+            reference: usage.reference,
+            use_count: usage.use_count,
+            last_used_at: parse_time(&usage.last_used_at),
+            pull_policy: usage.pull_policy,
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// References the next round refreshes.
+pub(crate) fn candidates(runtime: &SharedRuntimeImpl) -> BoxliteResult<Vec<PrefetchCandidate>> {
+    let since = Utc::now() - PREFETCH_USAGE_WINDOW;
+    Ok(runtime
+        .image_usage
+        .most_used(&since.to_rfc3339(), PREFETCH_MAX_IMAGES)?
+        .into_iter()
+        .filter(|usage| usage.pull_policy != ImagePullPolicy::Never)
+        .map(Into::into)
+        .collect())
+}
+
+pub(crate) fn status(runtime: &SharedRuntimeImpl) -> BoxliteResult<ImagePrefetchStatus> {
+    let state = runtime.image_usage.prefetch_state()?;
+    Ok(ImagePrefetchStatus {
+        enabled: state.enabled,
+        last_run_at: state.last_run_at.as_deref().and_then(parse_time),
+        last_refreshed: state.last_refreshed,
+        last_error: state.last_error,
+        candidates: candidates(runtime)?,
+    })
+}
+
+/// Refresh the most used images.
+///
+/// Unless `force` is set, skips the round (returning `None`) when one
+/// started less than [`PREFETCH_INTERVAL`] ago. Failed pulls don't stop the
+/// round; the first error is recorded in the status.
+pub(crate) async fn run_round(
+    runtime: &SharedRuntimeImpl,
+    force: bool,
+) -> BoxliteResult<Option<u32>> {
+    let now = Utc::now();
+    let not_before = if force { now } else { now - PREFETCH_INTERVAL };
+    if !runtime
+        .image_usage
+        .claim_prefetch_round(&now.to_rfc3339(), &not_before.to_rfc3339())?
+    {
+        return Ok(None);
+    }
+
+    // Counts too old to make a reference a candidate are never read again
+    runtime
+        .image_usage
+        .prune(&(now - PREFETCH_USAGE_WINDOW).to_rfc3339())?;
+
+    let mut refreshed = 0;
+    let mut first_error: Option<BoxliteError> = None;
+    for candidate in candidates(runtime)? {
+        if runtime.shutdown_token.is_cancelled() {
+            break;
+        }
+        match prewarm_image(runtime, &candidate.reference, candidate.pull_policy).await {
+            Ok(()) => {
+                tracing::debug!(image = %candidate.reference, "Prefetched image");
+                refreshed += 1;
+            }
+            Err(e) => {
+                tracing::warn!(image = %candidate.reference, "Failed to prefetch image: {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    let error = first_error.map(|e| e.to_string());
+    runtime
+        .image_usage
+        .finish_prefetch_round(refreshed, error.as_deref())?;
+    Ok(Some(refreshed))
+}
+
+/// Run prefetch rounds while the runtime is alive, enabled and idle.
+pub(crate) fn start(runtime: &SharedRuntimeImpl) {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let this = Arc::downgrade(runtime);
+    let shutdown = runtime.shutdown_token.clone();
+
+    handle.spawn(async move {
+        let start = tokio::time::Instant::now() + CHECK_INTERVAL;
+        let mut ticker = tokio::time::interval_at(start, CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = ticker.tick() => {}
+            }
+            let Some(runtime) = this.upgrade() else {
+                return;
+            };
+            if !runtime.is_idle() {
+                continue;
+            }
+            match runtime.image_usage.prefetch_state() {
+                Ok(state) if state.enabled => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::debug!("Failed to read image prefetch state: {}", e);
+                    continue;
+                }
+            }
+            match run_round(&runtime, false).await {
+                Ok(Some(refreshed)) => tracing::info!(refreshed, "Image prefetch round finished"),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Image prefetch round failed: {}", e),
+            }
+        }
+    });
+}
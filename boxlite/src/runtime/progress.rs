@@ -0,0 +1,105 @@
+//! Progress of image pulls and box boot.
+//!
+//! Embedders register observers with `BoxliteRuntime::on_progress()` to
+//! render progress bars or logs while an image is downloaded or a box starts.
+
+use std::sync::{Arc, RwLock};
+
+/// A step of a long-running runtime operation, reported to `on_progress`
+/// observers.
+///
+/// Pull events are only reported when the registry is contacted; images
+/// served from the local cache produce none.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Started downloading `image`, which has `layers` layers.
+    PullStarted { image: String, layers: usize },
+    /// Bytes of a layer written so far. `total` is the size from the
+    /// manifest (0 if unknown).
+    LayerProgress {
+        image: String,
+        digest: String,
+        current: u64,
+        total: u64,
+    },
+    /// A layer was downloaded and verified, or was already present.
+    LayerDone { image: String, digest: String },
+    /// All layers and the config of `image` are stored locally.
+    PullDone { image: String },
+    /// A box boot step started. `name` is the init task name, e.g.
+    /// `container_rootfs_prep`, `vmm_spawn` or `guest_connect`; `step` counts
+    /// from 1 to `steps`.
+    BootStep {
+        box_id: String,
+        name: String,
+        step: usize,
+        steps: usize,
+    },
+    /// The box finished booting.
+    BootDone { box_id: String },
+}
+
+/// Callback registered with `BoxliteRuntime::on_progress()`.
+pub(crate) type ProgressCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// Observers shared by the runtime, its image store and its boxes.
+#[derive(Clone, Default)]
+pub(crate) struct ProgressObservers {
+    callbacks: Arc<RwLock<Vec<ProgressCallback>>>,
+}
+
+impl ProgressObservers {
+    pub(crate) fn register(&self, callback: ProgressCallback) {
+        self.callbacks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(callback);
+    }
+
+    /// Report an event; `event` is only built if someone is observing.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> ProgressEvent) {
+        let callbacks = self.callbacks.read().unwrap_or_else(|e| e.into_inner());
+        if callbacks.is_empty() {
+            return;
+        }
+        let event = event();
+        for callback in callbacks.iter() {
+            callback(&event);
+        }
+    }
+}
+
+impl std::fmt::Debug for ProgressObservers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressObservers").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_emit() {
+        let observers = ProgressObservers::default();
+        // Nothing is built without observers
+        observers.emit(|| unreachable!());
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        observers.register(Arc::new(move |event| {
+            sink.lock().unwrap().push(event.clone())
+        }));
+        observers.clone().emit(|| ProgressEvent::PullDone {
+            image: "alpine:latest".into(),
+        });
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![ProgressEvent::PullDone {
+                image: "alpine:latest".into()
+            }]
+        );
+    }
+}
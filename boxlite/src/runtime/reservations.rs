@@ -0,0 +1,211 @@
+//! CPU and memory reservation accounting.
+//!
+//! Every running box reserves its vCPUs and memory against the runtime's
+//! capacity (`BoxliteOptions.cpu_capacity` / `memory_capacity_mib`). A box
+//! that would push reservations past `capacity * overcommit_ratio` is
+//! rejected with `ResourceExhausted`. Totals are mirrored into the runtime
+//! metrics so utilization can be reported.
+
+use crate::litebox::config::BoxConfig;
+use crate::metrics::RuntimeMetricsStorage;
+use crate::runtime::constants::vm_defaults::{DEFAULT_CPUS, DEFAULT_MEMORY_MIB};
+use crate::runtime::options::BoxliteOptions;
+use crate::runtime::types::BoxID;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::Ordering;
+
+/// Resources a box reserves while running.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Resources {
+    pub(crate) cpus: u64,
+    pub(crate) memory_mib: u64,
+}
+
+impl Resources {
+    pub(crate) fn of(config: &BoxConfig) -> Self {
+        Self {
+            cpus: config.options.cpus.unwrap_or(DEFAULT_CPUS) as u64,
+            memory_mib: config.options.memory_mib.unwrap_or(DEFAULT_MEMORY_MIB) as u64,
+        }
+    }
+}
+
+pub(crate) struct Reservations {
+    /// Reservation limits (capacity * overcommit ratio); None is unlimited
+    cpu_limit: Option<u64>,
+    memory_limit_mib: Option<u64>,
+    boxes: Mutex<HashMap<BoxID, Resources>>,
+    metrics: RuntimeMetricsStorage,
+}
+
+impl Reservations {
+    pub(crate) fn new(
+        options: &BoxliteOptions,
+        metrics: RuntimeMetricsStorage,
+    ) -> BoxliteResult<Self> {
+        let ratio = options.overcommit_ratio;
+        if !ratio.is_finite() || ratio <= 0.0 {
+            return Err(BoxliteError::Config(format!(
+                "overcommit_ratio must be a positive number, got {}",
+                ratio
+            )));
+        }
+        let limit = |capacity: u64| (capacity as f64 * ratio).floor() as u64;
+        Ok(Self {
+            cpu_limit: options.cpu_capacity.map(|c| limit(u64::from(c))),
+            memory_limit_mib: options.memory_capacity_mib.map(limit),
+            boxes: Mutex::new(HashMap::new()),
+            metrics,
+        })
+    }
+
+    /// Check that `request` fits next to the current reservations.
+    pub(crate) fn check(&self, request: Resources) -> BoxliteResult<()> {
+        let boxes = self.boxes.lock().unwrap_or_else(|e| e.into_inner());
+        self.check_locked(&boxes, request)
+    }
+
+    /// Reserve resources for a box that is starting. Idempotent per box.
+    pub(crate) fn reserve(&self, box_id: &BoxID, request: Resources) -> BoxliteResult<()> {
+        let mut boxes = self.boxes.lock().unwrap_or_else(|e| e.into_inner());
+        if boxes.contains_key(box_id) {
+            return Ok(());
+        }
+        self.check_locked(&boxes, request)?;
+        boxes.insert(box_id.clone(), request);
+        self.publish(&boxes);
+        Ok(())
+    }
+
+    /// Record a box that is already running, even if it exceeds the limits.
+    pub(crate) fn adopt(&self, box_id: &BoxID, resources: Resources) {
+        let mut boxes = self.boxes.lock().unwrap_or_else(|e| e.into_inner());
+        boxes.insert(box_id.clone(), resources);
+        self.publish(&boxes);
+    }
+
+    /// Release a box's reservation, if it has one.
+    pub(crate) fn release(&self, box_id: &BoxID) {
+        let mut boxes = self.boxes.lock().unwrap_or_else(|e| e.into_inner());
+        if boxes.remove(box_id).is_some() {
+            self.publish(&boxes);
+        }
+    }
+
+    fn check_locked(
+        &self,
+        boxes: &HashMap<BoxID, Resources>,
+        request: Resources,
+    ) -> BoxliteResult<()> {
+        let reserved = total(boxes);
+        if let Some(limit) = self.cpu_limit
+            && reserved.cpus + request.cpus > limit
+        {
+            return Err(BoxliteError::ResourceExhausted(format!(
+                "{} CPUs requested, {} of {} reserved by running boxes",
+                request.cpus, reserved.cpus, limit
+            )));
+        }
+        if let Some(limit) = self.memory_limit_mib
+            && reserved.memory_mib + request.memory_mib > limit
+        {
+            return Err(BoxliteError::ResourceExhausted(format!(
+                "{} MiB memory requested, {} of {} MiB reserved by running boxes",
+                request.memory_mib, reserved.memory_mib, limit
+            )));
+        }
+        Ok(())
+    }
+
+    fn publish(&self, boxes: &HashMap<BoxID, Resources>) {
+        let reserved = total(boxes);
+        self.metrics
+            .reserved_cpus
+            .store(reserved.cpus, Ordering::Relaxed);
+        self.metrics
+            .reserved_memory_mib
+            .store(reserved.memory_mib, Ordering::Relaxed);
+    }
+}
+
+fn total(boxes: &HashMap<BoxID, Resources>) -> Resources {
+    boxes
+        .values()
+        .fold(Resources::default(), |sum, r| Resources {
+            cpus: sum.cpus + r.cpus,
+            memory_mib: sum.memory_mib + r.memory_mib,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reservations(cpus: u32, memory_mib: u64, ratio: f64) -> Reservations {
+        let options = BoxliteOptions {
+            cpu_capacity: Some(cpus),
+            memory_capacity_mib: Some(memory_mib),
+            overcommit_ratio: ratio,
+            ..Default::default()
+        };
+        Reservations::new(&options, RuntimeMetricsStorage::new()).unwrap()
+    }
+
+    fn res(cpus: u64, memory_mib: u64) -> Resources {
+        Resources { cpus, memory_mib }
+    }
+
+    #[test]
+    fn test_reserve_until_exhausted() {
+        let r = reservations(4, 4096, 1.0);
+        let (a, b) = (BoxID::new(), BoxID::new());
+
+        r.reserve(&a, res(2, 1024)).unwrap();
+        // Reserving the same box again doesn't count twice
+        r.reserve(&a, res(2, 1024)).unwrap();
+        assert!(matches!(
+            r.reserve(&b, res(3, 1024)),
+            Err(BoxliteError::ResourceExhausted(_))
+        ));
+        assert!(matches!(
+            r.check(res(1, 4000)),
+            Err(BoxliteError::ResourceExhausted(_))
+        ));
+        r.reserve(&b, res(2, 1024)).unwrap();
+        assert_eq!(r.metrics.reserved_cpus.load(Ordering::Relaxed), 4);
+        assert_eq!(r.metrics.reserved_memory_mib.load(Ordering::Relaxed), 2048);
+
+        r.release(&a);
+        r.check(res(2, 1024)).unwrap();
+        assert_eq!(r.metrics.reserved_cpus.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_overcommit_ratio() {
+        let r = reservations(4, 4096, 1.5);
+        r.reserve(&BoxID::new(), res(6, 6144)).unwrap();
+        r.check(res(1, 0)).unwrap_err();
+
+        // Running boxes found at startup are adopted past the limit
+        r.adopt(&BoxID::new(), res(2, 0));
+        assert_eq!(r.metrics.reserved_cpus.load(Ordering::Relaxed), 8);
+
+        let options = BoxliteOptions {
+            overcommit_ratio: 0.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Reservations::new(&options, RuntimeMetricsStorage::new()),
+            Err(BoxliteError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_unlimited_without_capacity() {
+        let r =
+            Reservations::new(&BoxliteOptions::default(), RuntimeMetricsStorage::new()).unwrap();
+        r.reserve(&BoxID::new(), res(1024, 1 << 30)).unwrap();
+    }
+}
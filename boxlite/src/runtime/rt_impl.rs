@@ -1,25 +1,55 @@
-use crate::db::{BoxStore, Database};
+use crate::db::{BoxStore, Database, ImageUsageStore};
 use crate::images::ImageManager;
 use crate::init_logging_for;
 use crate::litebox::config::BoxConfig;
-use crate::litebox::{BoxManager, LiteBox, SharedBoxImpl};
+use crate::litebox::{BoxCommand, BoxManager, InitHooks, LiteBox, SharedBoxImpl, prewarm_image};
 use crate::lock::{FileLockManager, LockManager};
 use crate::metrics::{RuntimeMetrics, RuntimeMetricsStorage};
-use crate::runtime::constants::filenames;
+use crate::plugins::PluginRegistry;
+use crate::portal::GuestSession;
+use crate::runtime::batch::{self, BoxCreateResult, CreateManyStream};
+use crate::runtime::connection_prompt::{ConnectionPrompt, ConnectionPromptCallback};
+use crate::runtime::constants::{batch_defaults, projects};
+use crate::runtime::dependencies;
+use crate::runtime::events::{EventKind, EventSinks, RuntimeEvent};
+use crate::runtime::exec_cache::ExecCache;
+use crate::runtime::fork;
 use crate::runtime::guest_rootfs::GuestRootfs;
+use crate::runtime::hooks::{self, HookEvent};
 use crate::runtime::layout::{FilesystemLayout, FsLayoutConfig};
+use crate::runtime::leaks::{self, CleanupLeak};
 use crate::runtime::lock::RuntimeLock;
-use crate::runtime::options::{BoxOptions, BoxliteOptions};
+use crate::runtime::networks::{self, NetworkInfo};
+use crate::runtime::options::{
+    BoxOptions, BoxliteOptions, ImagePullPolicy, LifecycleHooks, RootfsSpec, SecurityOptions,
+    SecurityPreset, StorageDriver,
+};
+use crate::runtime::prefetch;
+use crate::runtime::progress::{ProgressCallback, ProgressObservers};
+use crate::runtime::reservations::{Reservations, Resources};
+use crate::runtime::shutdown::{
+    BoxShutdownOutcome, BoxShutdownResult, ShutdownCallback, ShutdownPhase, ShutdownReport,
+};
 use crate::runtime::signal_handler::timeout_to_duration;
-use crate::runtime::types::{BoxID, BoxInfo, BoxState, BoxStatus, ContainerID};
+use crate::runtime::sockets;
+use crate::runtime::types::{
+    BoxFilter, BoxID, BoxInfo, BoxState, BoxStatus, ContainerID, ProjectInfo, validate_project_name,
+};
+use crate::runtime::warnings;
 use crate::vmm::VmmKind;
 use boxlite_shared::{BoxliteError, BoxliteResult, Transport};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock, Weak};
-use tokio::sync::OnceCell;
+use tokio::sync::{OnceCell, mpsc};
 use tokio_util::sync::CancellationToken;
 
+/// How often [`RuntimeImpl::watch_swap`] samples a box's swap-out counter
+const SWAP_SAMPLE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Swap-out rate above which a box counts as swapping heavily
+const HEAVY_SWAP_OUT_BYTES_PER_SEC: u64 = 8 * 1024 * 1024;
+
 /// Internal runtime state protected by single lock.
 ///
 /// **Shared via Arc**: This is the actual shared state that can be cloned cheaply.
@@ -56,6 +86,36 @@ pub struct RuntimeImpl {
     pub(crate) guest_rootfs: Arc<OnceCell<GuestRootfs>>,
     /// Runtime-wide metrics (AtomicU64 based, lock-free)
     pub(crate) runtime_metrics: RuntimeMetricsStorage,
+    /// CPU/memory reserved by running boxes against the configured capacity
+    pub(crate) reservations: Reservations,
+    /// Container rootfs strategy for newly created boxes (immutable after init)
+    pub(crate) storage_driver: StorageDriver,
+    /// Host commands run at box lifecycle events (immutable after init)
+    pub(crate) hooks: LifecycleHooks,
+    /// Volume and network drivers (external plugins are internally synchronized)
+    pub(crate) plugins: PluginRegistry,
+    /// Lifecycle event and exec audit export (internally synchronized)
+    pub(crate) events: EventSinks,
+    /// Pull and boot progress observers (see `BoxliteRuntime::on_progress`)
+    pub(crate) progress: ProgressObservers,
+    /// Decides outbound connections (see `BoxliteRuntime::on_connection_prompt`)
+    pub(crate) connection_prompt: ConnectionPrompt,
+    /// Embedder hooks around init tasks (see `BoxliteRuntime::add_init_hook`)
+    pub(crate) init_hooks: InitHooks,
+    /// Results of `LiteBox::exec_cached()` (file-backed, immutable after init)
+    pub(crate) exec_cache: ExecCache,
+    /// Security preset for boxes left at the default security options
+    /// (immutable after init)
+    pub(crate) default_security: Option<SecurityPreset>,
+    /// Pull policy for boxes left at the default pull policy (immutable after init)
+    pub(crate) default_pull_policy: Option<ImagePullPolicy>,
+    /// Image use counts and prefetch settings (database-backed)
+    pub(crate) image_usage: ImageUsageStore,
+    /// Directory for the Unix sockets of new boxes (immutable after init)
+    pub(crate) socket_dir: Option<std::path::PathBuf>,
+    /// Project new boxes are created in and lists/removals are limited to
+    /// (None: "default" project, no limit). Immutable after init.
+    pub(crate) project: Option<String>,
 
     /// Per-entity lock manager for multiprocess-safe locking.
     ///
@@ -75,6 +135,8 @@ pub struct RuntimeImpl {
     /// Use `.is_cancelled()` for sync checks, `.cancelled()` for async select!.
     /// Child tokens are passed to each box via `.child_token()`.
     pub(crate) shutdown_token: CancellationToken,
+    /// Callbacks notified of each shutdown phase (see `BoxliteRuntime::on_shutdown`).
+    pub(crate) shutdown_callbacks: std::sync::Mutex<Vec<ShutdownCallback>>,
 }
 
 /// Synchronized state protected by RwLock.
@@ -89,6 +151,14 @@ pub struct SynchronizedState {
     active_boxes_by_name: HashMap<String, Weak<crate::litebox::box_impl::BoxImpl>>,
 }
 
+/// Where a fork's disks are copied from.
+enum CopyFrom<'a> {
+    /// The source box, paused while copying if it is running.
+    Box(&'a LiteBox),
+    /// The home directory of a stopped box (an earlier fork).
+    Stopped(std::path::PathBuf),
+}
+
 impl RuntimeImpl {
     // ========================================================================
     // CONSTRUCTION
@@ -115,6 +185,28 @@ impl RuntimeImpl {
             )));
         }
 
+        for hook in [
+            &options.hooks.pre_create,
+            &options.hooks.post_start,
+            &options.hooks.pre_stop,
+            &options.hooks.post_remove,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !hook.path.is_absolute() {
+                return Err(BoxliteError::Config(format!(
+                    "hook path must be absolute, got: {}",
+                    hook.path.display()
+                )));
+            }
+        }
+
+        let plugins = PluginRegistry::new(&options.plugins)?;
+        if let Some(ref project) = options.default_project {
+            validate_project_name(project)?;
+        }
+
         // Configure bind mount support based on platform
         #[cfg(target_os = "linux")]
         let fs_config = FsLayoutConfig::with_bind_mount();
@@ -153,7 +245,11 @@ impl RuntimeImpl {
             }
         }
 
-        let db = Database::open(&layout.db_dir().join("boxlite.db")).map_err(|e| {
+        let db = Database::open_with_durability(
+            &layout.db_dir().join("boxlite.db"),
+            options.db_durability,
+        )
+        .map_err(|e| {
             BoxliteError::Storage(format!(
                 "Failed to initialize database at {}: {}",
                 layout.db_dir().join("boxlite.db").display(),
@@ -161,17 +257,31 @@ impl RuntimeImpl {
             ))
         })?;
 
-        let image_manager =
-            ImageManager::new(layout.images_dir(), db.clone(), options.image_registries).map_err(
-                |e| {
-                    BoxliteError::Storage(format!(
-                        "Failed to initialize image manager at {}: {}",
-                        layout.images_dir().display(),
-                        e
-                    ))
-                },
-            )?;
+        let runtime_metrics = RuntimeMetricsStorage {
+            cpu_capacity: options.cpu_capacity.map(u64::from),
+            memory_capacity_mib: options.memory_capacity_mib,
+            ..RuntimeMetricsStorage::new()
+        };
+        let reservations = Reservations::new(&options, runtime_metrics.clone())?;
+        let events = EventSinks::new(&options.event_sinks, runtime_metrics.events_dropped.clone())?;
+
+        let progress = ProgressObservers::default();
+        let image_manager = ImageManager::new(
+            layout.images_dir(),
+            db.clone(),
+            &options,
+            runtime_metrics.registry_retries.clone(),
+            progress.clone(),
+        )
+        .map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to initialize image manager at {}: {}",
+                layout.images_dir().display(),
+                e
+            ))
+        })?;
 
+        let image_usage = ImageUsageStore::new(db.clone());
         let box_store = BoxStore::new(db);
 
         // Initialize lock manager for per-entity multiprocess-safe locking
@@ -189,6 +299,9 @@ impl RuntimeImpl {
             "Initialized lock manager"
         );
 
+        let exec_cache = ExecCache::new(layout.exec_cache_dir(), options.exec_cache);
+        let storage_driver = resolve_storage_driver(options.storage_driver, &layout);
+
         let inner = Arc::new(Self {
             sync_state: RwLock::new(SynchronizedState {
                 active_boxes_by_id: HashMap::new(),
@@ -198,16 +311,33 @@ impl RuntimeImpl {
             image_manager,
             layout,
             guest_rootfs: Arc::new(OnceCell::new()),
-            runtime_metrics: RuntimeMetricsStorage::new(),
+            runtime_metrics,
+            reservations,
+            storage_driver,
+            hooks: options.hooks,
+            plugins,
+            events,
+            progress,
+            connection_prompt: ConnectionPrompt::default(),
+            init_hooks: InitHooks::default(),
+            exec_cache,
+            default_security: options.default_security,
+            default_pull_policy: options.default_pull_policy,
+            image_usage,
+            socket_dir: options.socket_dir,
+            project: options.default_project,
             lock_manager,
             _runtime_lock: runtime_lock,
             shutdown_token: CancellationToken::new(),
+            shutdown_callbacks: std::sync::Mutex::new(Vec::new()),
         });
 
         tracing::debug!("initialized runtime");
 
         // Recover boxes from database
         inner.recover_boxes()?;
+        inner.schedule_recovered_expiries()?;
+        prefetch::start(&inner);
 
         Ok(inner)
     }
@@ -267,26 +397,31 @@ impl RuntimeImpl {
         if let Some(ref name) = name
             && let Some((config, state)) = self.box_manager.lookup_box(name)?
         {
-            if reuse_existing {
+            if reuse_existing && self.in_scope(&config.project) {
                 let (box_impl, _) = self.get_or_create_box_impl(config, state);
                 return Ok((LiteBox::new(box_impl), false));
-            } else {
-                return Err(BoxliteError::InvalidArgument(format!(
-                    "box with name '{}' already exists",
-                    name
-                )));
             }
+            return Err(self.name_taken(name, &config));
         }
 
-        // Initialize box variables with defaults
-        let (config, mut state) = self.init_box_variables(&options, name.clone());
+        let (config, mut state) = self.configure_box(options, name.clone())?;
+
+        if !self.hooks.pre_create.is_empty() {
+            let hook_list = self.hooks.pre_create.clone();
+            let info = BoxInfo::new(&config, &state);
+            tokio::task::spawn_blocking(move || {
+                hooks::run_hooks(HookEvent::PreCreate, &hook_list, &info)
+            })
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
+        }
 
         // Allocate lock for this box
         let lock_id = self.lock_manager.allocate()?;
         state.set_lock_id(lock_id);
 
         // Persist to database immediately (status = Configured)
-        if let Err(e) = self.box_manager.add_box(&config, &state) {
+        if let Err(e) = self.box_manager.add_box(&config, &mut state) {
             // Clean up the allocated lock on failure
             if let Err(free_err) = self.lock_manager.free(lock_id) {
                 tracing::error!(
@@ -335,9 +470,411 @@ impl RuntimeImpl {
             .boxes_created
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
+        if let Some(expires_at) = box_impl.config.expires_at() {
+            self.schedule_expiry(box_impl.config.id.clone(), expires_at);
+        }
+        // The stored config has the runtime's default pull policy applied
+        let stored = &box_impl.config.options;
+        if let RootfsSpec::Image(image) = &stored.rootfs
+            && let Err(e) =
+                self.image_usage
+                    .record_use(image, stored.pull_policy, &Utc::now().to_rfc3339())
+        {
+            tracing::debug!(image = %image, "Failed to record image use: {}", e);
+        }
+        self.events
+            .emit(|| RuntimeEvent::new(EventKind::BoxCreated, box_impl.info()));
+
         Ok((LiteBox::new(box_impl), true))
     }
 
+    /// Run the checks `create()` makes before persisting a box and return the
+    /// config the box would get. Nothing is created and `pre_create` hooks
+    /// are not run.
+    pub(crate) fn plan_config(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+    ) -> BoxliteResult<BoxConfig> {
+        if self.shutdown_token.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "Cannot plan box: runtime has been shut down".into(),
+            ));
+        }
+
+        if let Some(ref name) = name
+            && let Some((config, _)) = self.box_manager.lookup_box(name)?
+        {
+            return Err(self.name_taken(name, &config));
+        }
+
+        let (config, _) = self.configure_box(options, name)?;
+        // create() defers this to start(); a plan reports it up front
+        config.options.sanitize()?;
+        Ok(config)
+    }
+
+    /// Error for creating a box named `name` while `existing` has the name.
+    fn name_taken(&self, name: &str, existing: &BoxConfig) -> BoxliteError {
+        if self.in_scope(&existing.project) {
+            BoxliteError::InvalidArgument(format!("box with name '{}' already exists", name))
+        } else {
+            BoxliteError::InvalidArgument(format!(
+                "box with name '{}' already exists in project '{}'",
+                name, existing.project
+            ))
+        }
+    }
+
+    /// The config and initial state a new box would get, after the checks
+    /// `create()` and `plan()` share. The name must be free.
+    fn configure_box(
+        &self,
+        mut options: BoxOptions,
+        name: Option<String>,
+    ) -> BoxliteResult<(BoxConfig, BoxState)> {
+        // The persistent cache directory is keyed by box name
+        if options.persistent_cache.is_some() && name.is_none() {
+            return Err(BoxliteError::Config(
+                "persistent_cache requires a named box".into(),
+            ));
+        }
+
+        self.apply_box_defaults(&mut options);
+
+        // Store dependencies by ID so a later box reusing a name can't take over
+        options.depends_on = self.resolve_dependencies(&options.depends_on)?;
+
+        let (config, state) = self.init_box_variables(&options, name)?;
+        self.reservations.check(Resources::of(&config))?;
+        Ok((config, state))
+    }
+
+    /// Create and start many boxes, streaming per-box results as they complete.
+    ///
+    /// Distinct images are pulled once up front and shared by the whole batch.
+    /// Boxes are then created and started with at most `parallelism` in flight,
+    /// and VM boots are staggered so large batches don't hit the disk at once.
+    pub async fn create_many(
+        self: &Arc<Self>,
+        options_list: Vec<BoxOptions>,
+        parallelism: usize,
+    ) -> BoxliteResult<CreateManyStream> {
+        if self.shutdown_token.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "Cannot create boxes: runtime has been shut down".into(),
+            ));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            this.run_create_many(options_list, parallelism.max(1), tx)
+                .await;
+        });
+
+        Ok(CreateManyStream::new(rx))
+    }
+
+    /// Batch worker behind `create_many()`.
+    async fn run_create_many(
+        self: Arc<Self>,
+        options_list: Vec<BoxOptions>,
+        parallelism: usize,
+        tx: mpsc::UnboundedSender<BoxCreateResult>,
+    ) {
+        use futures::StreamExt;
+
+        let total = options_list.len();
+
+        // Phase 1: Prewarm each distinct image once, concurrently.
+        // Boxes created afterwards reuse the cached base disk instead of the registry.
+        let images = batch::distinct_images(&options_list);
+        let this = &self;
+        let pulls = images.iter().map(|(image, policy)| async move {
+            let error = prewarm_image(this, image, *policy).await.err();
+            (image.clone(), error.map(|e| e.to_string()))
+        });
+        let pull_errors: HashMap<String, String> = futures::future::join_all(pulls)
+            .await
+            .into_iter()
+            .filter_map(|(image, error)| error.map(|e| (image, e)))
+            .collect();
+
+        tracing::info!(
+            boxes = total,
+            images = images.len(),
+            failed_images = pull_errors.len(),
+            parallelism,
+            "Resolved images for batch create"
+        );
+
+        // Phase 2: Create and boot boxes with bounded concurrency
+        let last_boot = Arc::new(tokio::sync::Mutex::new(None));
+        let mut results = futures::stream::iter(options_list.into_iter().enumerate())
+            .map(|(index, options)| {
+                let this = Arc::clone(&self);
+                let last_boot = Arc::clone(&last_boot);
+                let pull_error = batch::image_ref(&options).and_then(|image| {
+                    pull_errors
+                        .get(image)
+                        .map(|e| (image.to_string(), e.clone()))
+                });
+                async move {
+                    let result = match pull_error {
+                        Some((image, e)) => Err(BoxliteError::Image(format!(
+                            "failed to pull {}: {}",
+                            image, e
+                        ))),
+                        None => this.create_and_boot(options, &last_boot).await,
+                    };
+                    BoxCreateResult { index, result }
+                }
+            })
+            .buffer_unordered(parallelism);
+
+        while let Some(item) = results.next().await {
+            if tx.send(item).is_err() {
+                tracing::debug!("Batch create stream dropped, stopping remaining boxes");
+                break;
+            }
+        }
+    }
+
+    /// Create one box of a batch and start it, waiting for its boot slot.
+    async fn create_and_boot(
+        self: &Arc<Self>,
+        options: BoxOptions,
+        last_boot: &tokio::sync::Mutex<Option<tokio::time::Instant>>,
+    ) -> BoxliteResult<LiteBox> {
+        let litebox = self.create(options, None).await?;
+
+        // Hold the slot lock while waiting so boots are spaced one at a time
+        {
+            let mut last = last_boot.lock().await;
+            if let Some(prev) = *last {
+                let stagger = std::time::Duration::from_millis(batch_defaults::BOOT_STAGGER_MS);
+                tokio::time::sleep_until(prev + stagger).await;
+            }
+            *last = Some(tokio::time::Instant::now());
+        }
+
+        if let Err(e) = litebox.start().await {
+            // The caller only sees the error, so it can't remove the box itself
+            let this = Arc::clone(self);
+            let id = litebox.id().clone();
+            match tokio::task::spawn_blocking(move || this.remove_box(&id, true)).await {
+                // An auto_remove box is already gone once its start failed
+                Ok(Ok(())) | Ok(Err(BoxliteError::NotFound(_))) => {}
+                Ok(Err(remove_err)) => tracing::warn!(
+                    box_id = %litebox.id(),
+                    error = %remove_err,
+                    "Failed to remove box after start error"
+                ),
+                Err(join_err) => tracing::warn!(
+                    box_id = %litebox.id(),
+                    "Removing box after start error failed: {}",
+                    join_err
+                ),
+            }
+            return Err(e);
+        }
+        Ok(litebox)
+    }
+
+    /// Create `count` stopped boxes from the disk state of a box.
+    ///
+    /// A running source is synced and paused while its disks are copied into
+    /// the first fork; the other forks copy from that one.
+    pub async fn fork(
+        self: &Arc<Self>,
+        id_or_name: &str,
+        count: usize,
+    ) -> BoxliteResult<Vec<LiteBox>> {
+        if self.shutdown_token.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "Cannot fork box: runtime has been shut down".into(),
+            ));
+        }
+        if count == 0 {
+            return Err(BoxliteError::InvalidArgument(
+                "fork count must be at least 1".into(),
+            ));
+        }
+
+        let source = self
+            .get(id_or_name)
+            .await?
+            .ok_or_else(|| BoxliteError::NotFound(id_or_name.to_string()))?;
+        let source_config = self.box_config(source.id())?;
+        if !self.in_scope(&source_config.project) {
+            return Err(BoxliteError::NotFound(id_or_name.to_string()));
+        }
+
+        let mut forks: Vec<LiteBox> = Vec::with_capacity(count);
+        let mut result = Ok(());
+        for _ in 0..count {
+            let copy_from = match forks.first() {
+                Some(first) => CopyFrom::Stopped(self.box_config(first.id())?.box_home),
+                None => CopyFrom::Box(&source),
+            };
+            match self.create_fork(&source_config, copy_from).await {
+                Ok(litebox) => forks.push(litebox),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        if let Err(e) = result {
+            for litebox in &forks {
+                let this = Arc::clone(self);
+                let id = litebox.id().clone();
+                let removed = tokio::task::spawn_blocking(move || this.remove_box(&id, true))
+                    .await
+                    .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))
+                    .and_then(|r| r);
+                if let Err(remove_err) = removed {
+                    tracing::warn!(
+                        box_id = %litebox.id(),
+                        error = %remove_err,
+                        "Failed to remove fork after fork error"
+                    );
+                }
+            }
+            return Err(e);
+        }
+
+        tracing::info!(
+            source = %source_config.id,
+            count,
+            "Forked box"
+        );
+        Ok(forks)
+    }
+
+    /// Create one fork of `source`, copying the disks `copy_from` names.
+    async fn create_fork(
+        self: &Arc<Self>,
+        source: &BoxConfig,
+        copy_from: CopyFrom<'_>,
+    ) -> BoxliteResult<LiteBox> {
+        let mut options = source.options.clone();
+        // The cache is keyed by box name, and forks are unnamed
+        options.persistent_cache = None;
+        // A host port can only be published by one box
+        options.ports.clear();
+
+        let (mut config, mut state) = self.init_box_variables(&options, None)?;
+        // The rootfs on disk is laid out for the source's container and driver
+        config.container = source.container.clone();
+        config.storage_driver = source.storage_driver;
+        config.disk_image = source.disk_image.clone();
+        self.reservations.check(Resources::of(&config))?;
+        // Stopped, so that starting reuses the copied disks
+        state.transition_with_reason(BoxStatus::Stopped, format!("forked from {}", source.id))?;
+
+        let copied = match copy_from {
+            CopyFrom::Box(litebox) => {
+                let info = litebox.info();
+                match (info.status, info.pid) {
+                    (BoxStatus::Running, Some(pid)) => {
+                        // Flush the guest page cache before freezing the VM; a
+                        // copy without it could miss writes the box already made
+                        flush_guest(litebox).await.map_err(|e| {
+                            BoxliteError::InvalidState(format!(
+                                "cannot fork box {}: failed to sync it: {}",
+                                source.id, e
+                            ))
+                        })?;
+                        let src = source.box_home.clone();
+                        let dst = config.box_home.clone();
+                        tokio::task::spawn_blocking(move || {
+                            let _paused = fork::PausedVm::pause(pid)?;
+                            fork::copy_disks(&src, &dst)
+                        })
+                        .await
+                    }
+                    (BoxStatus::Stopped, _) => {
+                        let (src, dst) = (source.box_home.clone(), config.box_home.clone());
+                        tokio::task::spawn_blocking(move || fork::copy_disks(&src, &dst)).await
+                    }
+                    (status, _) => {
+                        return Err(BoxliteError::InvalidState(format!(
+                            "cannot fork box {} while it is {}",
+                            source.id, status
+                        )));
+                    }
+                }
+            }
+            CopyFrom::Stopped(src) => {
+                let dst = config.box_home.clone();
+                tokio::task::spawn_blocking(move || fork::copy_disks(&src, &dst)).await
+            }
+        };
+        let copied = copied
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))
+            .and_then(|r| r);
+        if let Err(e) = copied {
+            let _ = std::fs::remove_dir_all(&config.box_home);
+            return Err(e);
+        }
+
+        let lock_id = match self.lock_manager.allocate() {
+            Ok(lock_id) => lock_id,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&config.box_home);
+                return Err(e);
+            }
+        };
+        state.set_lock_id(lock_id);
+        if let Err(e) = self.box_manager.add_box(&config, &mut state) {
+            if let Err(free_err) = self.lock_manager.free(lock_id) {
+                tracing::error!(
+                    lock_id = %lock_id,
+                    error = %free_err,
+                    "Failed to free lock after DB persist error"
+                );
+            }
+            let _ = std::fs::remove_dir_all(&config.box_home);
+            return Err(e);
+        }
+
+        let (box_impl, _) = self.get_or_create_box_impl(config, state);
+        self.runtime_metrics
+            .boxes_created
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let Some(expires_at) = box_impl.config.expires_at() {
+            self.schedule_expiry(box_impl.config.id.clone(), expires_at);
+        }
+        self.events
+            .emit(|| RuntimeEvent::new(EventKind::BoxCreated, box_impl.info()));
+
+        Ok(LiteBox::new(box_impl))
+    }
+
+    /// Pull images and prepare their base rootfs disks ahead of box creation.
+    ///
+    /// Images are prewarmed concurrently. Fails with the first error encountered;
+    /// images that completed before the failure stay cached.
+    pub async fn prewarm(self: &Arc<Self>, image_refs: &[String]) -> BoxliteResult<()> {
+        if self.shutdown_token.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "Cannot prewarm images: runtime has been shut down".into(),
+            ));
+        }
+
+        futures::future::try_join_all(image_refs.iter().map(|image| async move {
+            prewarm_image(self, image, ImagePullPolicy::IfNotPresent).await?;
+            tracing::info!(image = %image, "Prewarmed image");
+            Ok::<_, BoxliteError>(())
+        }))
+        .await?;
+
+        Ok(())
+    }
+
     /// Get a handle to an existing box by ID or name.
     ///
     /// Returns a LiteBox handle that can be used to operate on the box.
@@ -395,8 +932,27 @@ impl RuntimeImpl {
     }
 
     /// Remove a box completely by ID or name.
-    pub fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+    pub fn remove(self: &Arc<Self>, id_or_name: &str, force: bool) -> BoxliteResult<()> {
         let box_id = self.resolve_id(id_or_name)?;
+        if let Some(ref scope) = self.project
+            && self.box_project(&box_id)? != *scope
+        {
+            return Err(BoxliteError::NotFound(format!(
+                "{} in project '{}'",
+                id_or_name, scope
+            )));
+        }
+        if !force {
+            let dependents = self.dependents(&box_id)?;
+            if !dependents.is_empty() {
+                let ids: Vec<&str> = dependents.iter().map(|(id, _)| id.as_str()).collect();
+                return Err(BoxliteError::InvalidState(format!(
+                    "box {} is a dependency of {}; remove those first or use force",
+                    id_or_name,
+                    ids.join(", ")
+                )));
+            }
+        }
         self.remove_box(&box_id, force)
     }
 
@@ -442,24 +998,25 @@ impl RuntimeImpl {
         Ok(None)
     }
 
-    /// List all boxes, sorted by creation time (newest first).
+    /// List the boxes passing `filter`, sorted by creation time (newest first).
     ///
     /// Includes both persisted boxes (from database) and in-memory boxes
     /// (created but not yet persisted).
-    pub async fn list_info(self: &Arc<Self>) -> BoxliteResult<Vec<BoxInfo>> {
+    pub async fn list_info(self: &Arc<Self>, filter: &BoxFilter) -> BoxliteResult<Vec<BoxInfo>> {
         use std::collections::HashSet;
 
-        // Get boxes from database - run on blocking thread pool
+        // Get boxes from database (through the BoxInfo cache) - run on
+        // blocking thread pool
         let this = Arc::clone(self);
-        let db_boxes = tokio::task::spawn_blocking(move || this.box_manager.all_boxes(true))
-            .await
-            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
+        let db_filter = filter.clone();
+        let mut infos = tokio::task::spawn_blocking(move || {
+            this.box_manager
+                .list_info(this.project.as_deref(), &db_filter)
+        })
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
 
-        let mut seen_ids: HashSet<BoxID> = db_boxes.iter().map(|(c, _)| c.id.clone()).collect();
-        let mut infos: Vec<_> = db_boxes
-            .into_iter()
-            .map(|(config, state)| BoxInfo::new(&config, &state))
-            .collect();
+        let mut seen_ids: HashSet<BoxID> = infos.iter().map(|info| info.id.clone()).collect();
 
         // Add in-memory boxes not yet persisted
         {
@@ -467,8 +1024,12 @@ impl RuntimeImpl {
             for (box_id, weak) in &sync.active_boxes_by_id {
                 if !seen_ids.contains(box_id)
                     && let Some(strong) = weak.upgrade()
+                    && self.in_scope(&strong.config.project)
                 {
-                    infos.push(strong.info());
+                    let info = strong.info();
+                    if filter.matches(&info) {
+                        infos.push(info);
+                    }
                     seen_ids.insert(box_id.clone());
                 }
             }
@@ -479,6 +1040,28 @@ impl RuntimeImpl {
         Ok(infos)
     }
 
+    /// List projects that have boxes, sorted by name.
+    pub async fn list_projects(self: &Arc<Self>) -> BoxliteResult<Vec<ProjectInfo>> {
+        let this = Arc::clone(self);
+        tokio::task::spawn_blocking(move || this.box_manager.projects())
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+    }
+
+    /// Members of a user-defined network in the runtime's project.
+    pub async fn inspect_network(self: &Arc<Self>, name: &str) -> BoxliteResult<NetworkInfo> {
+        let this = Arc::clone(self);
+        let boxes = tokio::task::spawn_blocking(move || this.box_manager.all_boxes(true))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
+        let project = self.project.as_deref().unwrap_or(projects::DEFAULT);
+        let info = networks::network_info(name, project, &boxes);
+        if info.members.is_empty() {
+            return Err(BoxliteError::NotFound(format!("network '{}'", name)));
+        }
+        Ok(info)
+    }
+
     /// Check if a box with the given ID or name exists.
     ///
     /// Checks in-memory cache first (for boxes not yet persisted), then database.
@@ -514,6 +1097,59 @@ impl RuntimeImpl {
         Ok(db_result.is_some())
     }
 
+    /// Find artifacts of boxes that are no longer in the database.
+    ///
+    /// Looks for box directories, box state rows and, under the configured
+    /// `socket_dir`, socket directories without a box. Nothing is removed.
+    /// Socket directories whose agent socket still accepts connections are
+    /// skipped, since they belong to a running box of another home sharing
+    /// the `socket_dir`.
+    pub fn find_leaks(&self) -> BoxliteResult<Vec<CleanupLeak>> {
+        use std::collections::HashSet;
+
+        let mut known: HashSet<String> = self
+            .box_manager
+            .all_boxes(false)?
+            .into_iter()
+            .map(|(config, _)| config.id.to_string())
+            .collect();
+        {
+            let sync = self.sync_state.read().unwrap();
+            known.extend(sync.active_boxes_by_id.keys().map(|id| id.to_string()));
+        }
+
+        let subdirs = |dir: &std::path::Path| -> Vec<(String, std::path::PathBuf)> {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return Vec::new();
+            };
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+                .filter_map(|entry| Some((entry.file_name().to_str()?.to_string(), entry.path())))
+                .filter(|(name, _)| !known.contains(name))
+                .collect()
+        };
+
+        let mut found = Vec::new();
+        for (box_id, dir) in subdirs(&self.layout.boxes_dir()) {
+            found.extend(leaks::scan_dir(&box_id, &dir));
+        }
+        for box_id in self.box_manager.orphaned_state_ids()? {
+            found.push(CleanupLeak::db_row(&box_id, "box_state"));
+        }
+        if let Some(socket_dir) = &self.socket_dir {
+            for (box_id, dir) in subdirs(socket_dir) {
+                let in_use =
+                    std::os::unix::net::UnixStream::connect(dir.join(sockets::AGENT_SOCKET))
+                        .is_ok();
+                if BoxID::is_valid(&box_id) && !in_use {
+                    found.extend(leaks::scan_dir(&box_id, &dir));
+                }
+            }
+        }
+        Ok(found)
+    }
+
     // ========================================================================
     // PUBLIC API - METRICS
     // ========================================================================
@@ -539,16 +1175,17 @@ impl RuntimeImpl {
     ///
     /// # Returns
     /// Ok(()) if all boxes stopped successfully, Err if any box failed to stop.
-    pub async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<()> {
+    pub async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<ShutdownReport> {
         // Check if already shut down (idempotent)
         if self.shutdown_token.is_cancelled() {
-            return Ok(());
+            return Ok(ShutdownReport::default());
         }
 
         tracing::info!("Initiating runtime shutdown");
 
         // Cancel the shutdown token - marks shutdown and signals all in-flight operations
         self.shutdown_token.cancel();
+        self.notify_shutdown(ShutdownPhase::Draining);
 
         // Collect all active boxes
         let active_boxes: Vec<SharedBoxImpl> = {
@@ -559,9 +1196,12 @@ impl RuntimeImpl {
                 .collect()
         };
 
+        self.notify_shutdown(ShutdownPhase::StoppingBoxes);
         if active_boxes.is_empty() {
             tracing::info!("No active boxes to shutdown");
-            return Ok(());
+            self.flush_db();
+            self.notify_shutdown(ShutdownPhase::Done);
+            return Ok(ShutdownReport::default());
         }
 
         tracing::info!(count = active_boxes.len(), "Stopping active boxes");
@@ -570,47 +1210,73 @@ impl RuntimeImpl {
         let timeout_duration = timeout_to_duration(timeout);
 
         // Stop all boxes concurrently
-        let stop_futures = active_boxes.iter().map(|box_impl| {
-            let box_id = box_impl.id().to_string();
-            async move {
-                let result = if let Some(duration) = timeout_duration {
-                    tokio::time::timeout(duration, box_impl.stop()).await
-                } else {
-                    // Infinite timeout
-                    Ok(box_impl.stop().await)
-                };
-                (box_id, result)
-            }
-        });
-
-        let results = futures::future::join_all(stop_futures).await;
+        let stop_futures = active_boxes.iter().map(|box_impl| async move {
+            let result = if let Some(duration) = timeout_duration {
+                tokio::time::timeout(duration, box_impl.stop()).await
+            } else {
+                // Infinite timeout
+                Ok(box_impl.stop().await)
+            };
 
-        // Check for errors
-        let mut errors = Vec::new();
-        for (box_id, result) in results {
-            match result {
+            let outcome = match result {
                 Ok(Ok(())) => {
-                    tracing::debug!(box_id = %box_id, "Box stopped gracefully");
+                    tracing::debug!(box_id = %box_impl.id(), "Box stopped gracefully");
+                    BoxShutdownOutcome::Stopped
                 }
                 Ok(Err(e)) => {
-                    tracing::warn!(box_id = %box_id, error = %e, "Box stop failed");
-                    errors.push(format!("{}: {}", box_id, e));
+                    tracing::warn!(box_id = %box_impl.id(), error = %e, "Box stop failed");
+                    BoxShutdownOutcome::Failed(e.to_string())
                 }
                 Err(_) => {
-                    tracing::warn!(box_id = %box_id, "Box stop timed out");
-                    errors.push(format!("{}: timeout", box_id));
+                    tracing::warn!(box_id = %box_impl.id(), "Box stop timed out, killing VM");
+                    match box_impl.kill_vm() {
+                        Ok(()) => BoxShutdownOutcome::Killed,
+                        Err(e) => BoxShutdownOutcome::Failed(format!("timeout, then {}", e)),
+                    }
                 }
+            };
+            BoxShutdownResult {
+                box_id: box_impl.id().clone(),
+                outcome,
             }
+        });
+
+        let report = ShutdownReport {
+            boxes: futures::future::join_all(stop_futures).await,
+        };
+
+        self.flush_db();
+        tracing::info!(clean = report.is_clean(), "Runtime shutdown complete");
+        self.notify_shutdown(ShutdownPhase::Done);
+        Ok(report)
+    }
+
+    /// Commit state writes grouped by batched durability.
+    fn flush_db(&self) {
+        if let Err(e) = self.box_manager.flush() {
+            tracing::warn!(error = %e, "Failed to flush database at shutdown");
         }
+    }
 
-        if errors.is_empty() {
-            tracing::info!("Runtime shutdown complete");
-            Ok(())
-        } else {
-            Err(BoxliteError::Internal(format!(
-                "Shutdown completed with errors: {}",
-                errors.join(", ")
-            )))
+    /// Register a callback for shutdown phases.
+    pub fn on_shutdown(&self, callback: ShutdownCallback) {
+        self.shutdown_callbacks.lock().unwrap().push(callback);
+    }
+
+    /// Register an observer of pull and boot progress.
+    pub fn on_progress(&self, callback: ProgressCallback) {
+        self.progress.register(callback);
+    }
+
+    /// Set the callback deciding outbound connections.
+    pub fn on_connection_prompt(&self, callback: ConnectionPromptCallback) {
+        self.connection_prompt.set(callback);
+    }
+
+    fn notify_shutdown(&self, phase: ShutdownPhase) {
+        let callbacks = self.shutdown_callbacks.lock().unwrap().clone();
+        for callback in callbacks {
+            callback(phase);
         }
     }
 
@@ -648,6 +1314,127 @@ impl RuntimeImpl {
             .ok_or_else(|| BoxliteError::NotFound(id_or_name.to_string()))
     }
 
+    /// Apply the runtime's default security preset and pull policy to
+    /// `options` wherever it leaves them at their built-in defaults.
+    fn apply_box_defaults(&self, options: &mut BoxOptions) {
+        if let Some(preset) = self.default_security
+            && options.security == SecurityOptions::default()
+        {
+            options.security = preset.options();
+        }
+        if let Some(policy) = self.default_pull_policy
+            && options.pull_policy == ImagePullPolicy::default()
+        {
+            options.pull_policy = policy;
+        }
+    }
+
+    /// Whether no execution is running in a box with a live handle.
+    pub(crate) fn is_idle(&self) -> bool {
+        let sync = self.sync_state.read().unwrap();
+        sync.active_boxes_by_id
+            .values()
+            .filter_map(Weak::upgrade)
+            .all(|box_impl| box_impl.execs_in_flight() == 0)
+    }
+
+    /// Whether boxes of `project` are visible to this runtime.
+    fn in_scope(&self, project: &str) -> bool {
+        self.project.as_deref().is_none_or(|scope| scope == project)
+    }
+
+    /// Project of a box.
+    fn box_project(&self, box_id: &BoxID) -> BoxliteResult<String> {
+        self.box_config(box_id).map(|config| config.project)
+    }
+
+    /// The box's cached `BoxImpl`, if a handle to it is alive.
+    fn active_box(&self, box_id: &BoxID) -> Option<SharedBoxImpl> {
+        let sync = self.sync_state.read().unwrap();
+        sync.active_boxes_by_id
+            .get(box_id)
+            .and_then(|weak| weak.upgrade())
+    }
+
+    /// Config of a box, checking active boxes before the database.
+    fn box_config(&self, box_id: &BoxID) -> BoxliteResult<BoxConfig> {
+        {
+            let sync = self.sync_state.read().unwrap();
+            if let Some(strong) = sync
+                .active_boxes_by_id
+                .get(box_id)
+                .and_then(|weak| weak.upgrade())
+            {
+                return Ok(strong.config.clone());
+            }
+        }
+        self.box_manager
+            .box_by_id(box_id)?
+            .map(|(config, _)| config)
+            .ok_or_else(|| BoxliteError::NotFound(box_id.to_string()))
+    }
+
+    /// Resolve `depends_on` entries (IDs or names) to box IDs.
+    fn resolve_dependencies(&self, depends_on: &[String]) -> BoxliteResult<Vec<String>> {
+        let mut ids: Vec<String> = Vec::with_capacity(depends_on.len());
+        for dep in depends_on {
+            let box_id = match self.resolve_id(dep) {
+                Ok(box_id) => box_id,
+                Err(BoxliteError::NotFound(_)) => {
+                    return Err(BoxliteError::NotFound(format!("dependency '{}'", dep)));
+                }
+                Err(e) => return Err(e),
+            };
+            let project = self.box_project(&box_id)?;
+            if !self.in_scope(&project) {
+                return Err(BoxliteError::InvalidArgument(format!(
+                    "dependency '{}' is in project '{}'",
+                    dep, project
+                )));
+            }
+            if !ids.iter().any(|id| id == box_id.as_str()) {
+                ids.push(box_id.to_string());
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Transitive dependencies of a box, each after its own dependencies.
+    pub(crate) fn dependency_order(&self, box_id: &BoxID) -> BoxliteResult<Vec<BoxID>> {
+        dependencies::dependency_order(box_id, |id| {
+            self.box_config(id)?
+                .options
+                .depends_on
+                .iter()
+                .map(|dep| {
+                    BoxID::parse(dep).ok_or_else(|| {
+                        BoxliteError::Internal(format!(
+                            "box {} has an invalid dependency ID '{}'",
+                            id, dep
+                        ))
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Boxes whose `depends_on` includes `box_id`, with their status.
+    pub(crate) fn dependents(&self, box_id: &BoxID) -> BoxliteResult<Vec<(BoxID, BoxStatus)>> {
+        Ok(self
+            .box_manager
+            .all_boxes(true)?
+            .into_iter()
+            .filter(|(config, _)| {
+                config
+                    .options
+                    .depends_on
+                    .iter()
+                    .any(|dep| dep == box_id.as_str())
+            })
+            .map(|(config, state)| (config.id, state.status()))
+            .collect())
+    }
+
     /// Remove a box from the runtime (internal implementation).
     ///
     /// This is the internal implementation called by both `BoxliteRuntime::remove()`
@@ -663,35 +1450,44 @@ impl RuntimeImpl {
     /// # Errors
     /// - Box not found
     /// - Box is active and force=false
-    pub(crate) fn remove_box(&self, id: &BoxID, force: bool) -> BoxliteResult<()> {
+    pub(crate) fn remove_box(self: &Arc<Self>, id: &BoxID, force: bool) -> BoxliteResult<()> {
         tracing::debug!(box_id = %id, force = force, "RuntimeInnerImpl::remove_box called");
 
         // Try to get box from database first
         if let Some((config, state)) = self.box_manager.box_by_id(id)? {
             // Box exists in database - handle as before
             let mut state = state;
-            if state.status.is_active() {
+            if state.status().is_active() {
                 if force {
                     // Force mode: kill the process directly
                     if let Some(pid) = state.pid {
                         tracing::info!(box_id = %id, pid = pid, "Force killing active box");
                         crate::util::kill_process(pid);
                     }
+                    // The killed box never stopped, so release what drivers
+                    // attached to it here
+                    if crate::plugins::uses_drivers(&config.options) {
+                        self.plugins.release(id.as_str(), &config.options);
+                    }
                     // Update status to stopped and save
-                    state.set_status(BoxStatus::Stopped);
-                    state.set_pid(None);
+                    state.mark_stop_with_reason("force removed");
                     self.box_manager.save_box(id, &state)?;
+                    self.events.emit(|| {
+                        RuntimeEvent::new(EventKind::BoxStopped, BoxInfo::new(&config, &state))
+                    });
                 } else {
                     // Non-force mode: error on active box
                     return Err(BoxliteError::InvalidState(format!(
                         "cannot remove active box {} (status: {:?}). Use force=true to stop first",
-                        id, state.status
+                        id,
+                        state.status()
                     )));
                 }
             }
 
             // Remove from BoxManager (database-first)
             self.box_manager.remove_box(id)?;
+            self.reservations.release(id);
 
             // Free the lock if one was allocated
             if let Some(lock_id) = state.lock_id {
@@ -712,9 +1508,9 @@ impl RuntimeImpl {
             }
 
             // Delete box directory
-            let box_home = config.box_home;
+            let box_home = &config.box_home;
             if box_home.exists()
-                && let Err(e) = std::fs::remove_dir_all(&box_home)
+                && let Err(e) = std::fs::remove_dir_all(box_home)
             {
                 tracing::warn!(
                     box_id = %id,
@@ -723,11 +1519,18 @@ impl RuntimeImpl {
                     "Failed to cleanup box directory"
                 );
             }
+            sockets::cleanup(&config);
 
             // Invalidate cache
             self.invalidate_box_impl(id, config.name.as_deref());
+            self.leave_networks(&config);
 
             tracing::info!(box_id = %id, "Removed box");
+            let info = BoxInfo::new(&config, &state);
+            self.verify_removed(&config, &info);
+            self.events
+                .emit(|| RuntimeEvent::new(EventKind::BoxRemoved, info.clone()));
+            hooks::run_hooks_logged(HookEvent::PostRemove, &self.hooks.post_remove, &info);
             return Ok(());
         }
 
@@ -742,10 +1545,11 @@ impl RuntimeImpl {
         if let Some(box_impl) = box_impl {
             // Box exists in-memory only (not yet started/persisted)
             let state = box_impl.state.read();
-            if state.status.is_active() && !force {
+            if state.status().is_active() && !force {
                 return Err(BoxliteError::InvalidState(format!(
                     "cannot remove active box {} (status: {:?}). Use force=true to stop first",
-                    id, state.status
+                    id,
+                    state.status()
                 )));
             }
             drop(state);
@@ -765,8 +1569,12 @@ impl RuntimeImpl {
                     "Failed to cleanup box directory"
                 );
             }
+            sockets::cleanup(&box_impl.config);
 
             tracing::info!(box_id = %id, "Removed in-memory box");
+            self.verify_removed(&box_impl.config, &box_impl.info());
+            self.events
+                .emit(|| RuntimeEvent::new(EventKind::BoxRemoved, box_impl.info()));
             return Ok(());
         }
 
@@ -774,6 +1582,395 @@ impl RuntimeImpl {
         Err(BoxliteError::NotFound(id.to_string()))
     }
 
+    /// Check that nothing of a removed box is left, and remove what is.
+    ///
+    /// The removal steps above only log their failures. Leftovers found here
+    /// are logged and reported in an `EventKind::CleanupLeak` event.
+    fn verify_removed(&self, config: &BoxConfig, info: &BoxInfo) {
+        let id = &config.id;
+        let mut found = Vec::new();
+
+        match self.box_manager.leftover_tables(id) {
+            Ok(tables) if !tables.is_empty() => {
+                let cleaned = match self.box_manager.purge_box(id) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        tracing::warn!(box_id = %id, error = %e, "Failed to purge leftover rows");
+                        false
+                    }
+                };
+                found.extend(tables.into_iter().map(|table| CleanupLeak {
+                    cleaned,
+                    ..CleanupLeak::db_row(id.as_str(), table)
+                }));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(box_id = %id, error = %e, "Failed to check for leftover rows");
+            }
+        }
+
+        let dirs = std::iter::once(config.box_home.as_path()).chain(sockets::external_dir(config));
+        for dir in dirs {
+            let mut leftovers = leaks::scan_dir(id.as_str(), dir);
+            if leftovers.is_empty() {
+                continue;
+            }
+            let cleaned = leaks::force_remove_dir(dir);
+            for leak in &mut leftovers {
+                leak.cleaned = cleaned;
+            }
+            found.extend(leftovers);
+        }
+
+        if found.is_empty() {
+            return;
+        }
+        for leak in &found {
+            tracing::warn!(
+                box_id = %id,
+                kind = ?leak.kind,
+                location = %leak.location,
+                cleaned = leak.cleaned,
+                "Removed box left an artifact behind"
+            );
+        }
+        self.events.emit(|| RuntimeEvent {
+            leaks: found,
+            ..RuntimeEvent::new(EventKind::CleanupLeak, info.clone())
+        });
+    }
+
+    // ========================================================================
+    // INTERNAL - EXPIRY
+    // ========================================================================
+
+    /// Stop and remove a box once its TTL runs out.
+    ///
+    /// The timer is dropped when the runtime shuts down; the next runtime
+    /// reschedules it, or removes the box during recovery if it has expired.
+    fn schedule_expiry(self: &Arc<Self>, box_id: BoxID, expires_at: DateTime<Utc>) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!(
+                box_id = %box_id,
+                "No async runtime to schedule box expiry; it will be enforced on next start"
+            );
+            return;
+        };
+        let delay = (expires_at - Utc::now()).to_std().unwrap_or_default();
+        let this = Arc::downgrade(self);
+        let shutdown = self.shutdown_token.clone();
+
+        handle.spawn(async move {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            if let Some(this) = this.upgrade() {
+                this.expire_box(box_id).await;
+            }
+        });
+    }
+
+    /// Schedule expiry for recovered boxes that have a TTL.
+    fn schedule_recovered_expiries(self: &Arc<Self>) -> BoxliteResult<()> {
+        for (config, _) in self.box_manager.all_boxes(true)? {
+            if let Some(expires_at) = config.expires_at() {
+                self.schedule_expiry(config.id, expires_at);
+            }
+        }
+        Ok(())
+    }
+
+    async fn expire_box(self: &Arc<Self>, box_id: BoxID) {
+        tracing::info!(box_id = %box_id, "Box TTL expired, stopping and removing");
+
+        // Stop through the box first so the guest shuts down cleanly
+        if let Ok(Some(litebox)) = self.get(box_id.as_str()).await
+            && litebox.info().status.is_active()
+            && let Err(e) = litebox.stop().await
+        {
+            tracing::warn!(box_id = %box_id, "Failed to stop expired box: {}", e);
+        }
+
+        let this = Arc::clone(self);
+        let id = box_id.clone();
+        match tokio::task::spawn_blocking(move || this.remove_box(&id, true)).await {
+            // An auto_remove box is already gone once stopped
+            Ok(Ok(())) | Ok(Err(BoxliteError::NotFound(_))) => {}
+            Ok(Err(e)) => {
+                tracing::warn!(box_id = %box_id, "Failed to remove expired box: {}", e)
+            }
+            Err(e) => tracing::warn!(box_id = %box_id, "Expiry task failed: {}", e),
+        }
+    }
+
+    /// Ping a started box's guest agent every `heartbeat_interval_secs`.
+    ///
+    /// A ping not answered within the interval is missed. Once none was
+    /// answered for `unresponsive_after_secs` the box is marked Unresponsive,
+    /// and restarted with `restart_unresponsive`. Ends when the box stops.
+    pub(crate) fn watch_heartbeats(
+        self: &Arc<Self>,
+        box_id: BoxID,
+        options: &BoxOptions,
+        session: GuestSession,
+        box_shutdown: CancellationToken,
+    ) {
+        let Some(interval_secs) = options.heartbeat_interval_secs else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!(box_id = %box_id, "No async runtime to watch box heartbeats");
+            return;
+        };
+        let interval = std::time::Duration::from_secs(interval_secs);
+        let window = options
+            .unresponsive_after_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(interval * 3);
+        // Stopping an auto_remove box removes it, so it can't be restarted
+        let restart = options.restart_unresponsive && !options.auto_remove;
+        let this = Arc::downgrade(self);
+
+        handle.spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last_answer = tokio::time::Instant::now();
+            loop {
+                tokio::select! {
+                    _ = box_shutdown.cancelled() => return,
+                    _ = ticker.tick() => {}
+                }
+                let ping = async { session.guest().await?.ping().await };
+                let answered = tokio::select! {
+                    _ = box_shutdown.cancelled() => return,
+                    result = tokio::time::timeout(interval, ping) => matches!(result, Ok(Ok(_))),
+                };
+
+                let Some(runtime) = this.upgrade() else {
+                    return;
+                };
+                let Some(box_impl) = runtime.active_box(&box_id) else {
+                    return;
+                };
+                if answered {
+                    last_answer = tokio::time::Instant::now();
+                    box_impl.set_responsive(true);
+                    continue;
+                }
+                let silent = last_answer.elapsed();
+                if silent >= window && box_impl.set_responsive(false) {
+                    tracing::warn!(
+                        box_id = %box_id,
+                        "Guest agent hasn't answered heartbeats for {:?}",
+                        silent
+                    );
+                    if restart {
+                        drop(box_impl);
+                        runtime.restart_unresponsive(&box_id).await;
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Warn while a box with swap swaps out faster than
+    /// [`HEAVY_SWAP_OUT_BYTES_PER_SEC`], emitting `box.swapping` when it
+    /// starts to.
+    pub(crate) fn watch_swap(
+        self: &Arc<Self>,
+        box_id: BoxID,
+        container_id: String,
+        session: GuestSession,
+        box_shutdown: CancellationToken,
+    ) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let this = Arc::downgrade(self);
+
+        handle.spawn(async move {
+            let mut ticker = tokio::time::interval(SWAP_SAMPLE_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last: Option<u64> = None;
+            let mut heavy = false;
+            loop {
+                tokio::select! {
+                    _ = box_shutdown.cancelled() => return,
+                    _ = ticker.tick() => {}
+                }
+                let stats = async { session.container().await?.stats(&container_id).await };
+                let stats = match stats.await {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        tracing::debug!(box_id = %box_id, "Failed to sample swap: {}", e);
+                        continue;
+                    }
+                };
+                let Some(previous) = last.replace(stats.swap_out_bytes) else {
+                    continue;
+                };
+                let rate =
+                    stats.swap_out_bytes.saturating_sub(previous) / SWAP_SAMPLE_INTERVAL.as_secs();
+                let now_heavy = rate >= HEAVY_SWAP_OUT_BYTES_PER_SEC;
+                if now_heavy == heavy {
+                    continue;
+                }
+                heavy = now_heavy;
+                if !heavy {
+                    tracing::info!(box_id = %box_id, "Box stopped swapping heavily");
+                    continue;
+                }
+                tracing::warn!(
+                    box_id = %box_id,
+                    swap_out_mib_per_sec = rate / (1024 * 1024),
+                    swap_used_mib = stats.swap_used_bytes / (1024 * 1024),
+                    "Box is swapping heavily; it needs more memory_mib"
+                );
+                let Some(runtime) = this.upgrade() else {
+                    return;
+                };
+                let Some(box_impl) = runtime.active_box(&box_id) else {
+                    return;
+                };
+                runtime
+                    .events
+                    .emit(|| RuntimeEvent::new(EventKind::BoxSwapping, box_impl.info()));
+            }
+        });
+    }
+
+    /// Stop and start a box whose guest agent stopped answering.
+    async fn restart_unresponsive(self: &Arc<Self>, box_id: &BoxID) {
+        tracing::warn!(box_id = %box_id, "Restarting unresponsive box");
+        let result = async {
+            if let Some(litebox) = self.get(box_id.as_str()).await? {
+                litebox.stop().await?;
+            }
+            // Stopping dropped the cached box, so this starts a fresh one
+            if let Some(litebox) = self.get(box_id.as_str()).await? {
+                litebox.start().await?;
+            }
+            Ok::<_, BoxliteError>(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(box_id = %box_id, "Failed to restart unresponsive box: {}", e);
+        }
+    }
+
+    // ========================================================================
+    // INTERNAL - NETWORKS
+    // ========================================================================
+
+    /// Bring the networks of the box with `config` up to date after it
+    /// started or stopped: the peers files of their running members, then
+    /// the names the DNS resolvers of those other than the box itself
+    /// answer for.
+    ///
+    /// Returns all boxes, empty if they couldn't be read. Failures are
+    /// logged; the entries catch up on the next change on the network.
+    pub(crate) async fn refresh_networks(
+        self: &Arc<Self>,
+        config: &BoxConfig,
+    ) -> Vec<(BoxConfig, BoxState)> {
+        if config.options.networks.is_empty() {
+            return Vec::new();
+        }
+        let this = Arc::clone(self);
+        let changed = config.clone();
+        let boxes =
+            match tokio::task::spawn_blocking(move || this.write_network_peers(&changed)).await {
+                Ok(Ok(boxes)) => boxes,
+                Ok(Err(e)) => {
+                    tracing::warn!(box_id = %config.id, "Failed to update networks: {}", e);
+                    return Vec::new();
+                }
+                Err(e) => {
+                    tracing::warn!(box_id = %config.id, "Network update task failed: {}", e);
+                    return Vec::new();
+                }
+            };
+        self.update_network_hosts(config, &boxes).await;
+        boxes
+    }
+
+    /// Take a box that crashed or was removed off its networks.
+    ///
+    /// The peers files are rewritten before returning, so the box's address
+    /// stops reaching its ports; the peers' DNS resolvers are updated in the
+    /// background when called within an async runtime.
+    pub(crate) fn leave_networks(self: &Arc<Self>, config: &BoxConfig) {
+        if config.options.networks.is_empty() {
+            return;
+        }
+        let boxes = match self.write_network_peers(config) {
+            Ok(boxes) => boxes,
+            Err(e) => {
+                tracing::warn!(box_id = %config.id, "Failed to leave networks: {}", e);
+                return;
+            }
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let this = Arc::clone(self);
+        let config = config.clone();
+        handle.spawn(async move { this.update_network_hosts(&config, &boxes).await });
+    }
+
+    /// Rewrite the peers files of the running boxes sharing a network with
+    /// the box with `config`, returning all boxes.
+    fn write_network_peers(&self, config: &BoxConfig) -> BoxliteResult<Vec<(BoxConfig, BoxState)>> {
+        let boxes = self.box_manager.all_boxes(true)?;
+        for (peer, state) in &boxes {
+            if !state.status().is_running() || !networks::share_network(config, peer) {
+                continue;
+            }
+            let path = self
+                .layout
+                .box_layout(peer.id.as_str(), false)?
+                .network_peers_path();
+            if let Err(e) = networks::write_peers_file(&path, &networks::peers(peer, &boxes)) {
+                tracing::warn!(box_id = %peer.id, "Failed to write network peers: {}", e);
+            }
+        }
+        Ok(boxes)
+    }
+
+    /// Replace the peer names answered by the DNS resolvers of the running
+    /// boxes other than the one with `config` sharing a network with it.
+    async fn update_network_hosts(
+        self: &Arc<Self>,
+        config: &BoxConfig,
+        boxes: &[(BoxConfig, BoxState)],
+    ) {
+        for (peer, state) in boxes {
+            if peer.id == config.id
+                || !state.status().is_running()
+                || !networks::share_network(config, peer)
+            {
+                continue;
+            }
+            let entries = networks::host_entries(&networks::peers(peer, boxes));
+            let result = match self.get(peer.id.as_str()).await {
+                Ok(Some(litebox)) => litebox.inner.set_network_hosts(&entries).await,
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!(
+                    box_id = %config.id,
+                    peer = %peer.id,
+                    "Failed to update network hosts: {}",
+                    e
+                );
+            }
+        }
+    }
+
     // ========================================================================
     // INTERNAL - INITIALIZATION
     // ========================================================================
@@ -786,8 +1983,8 @@ impl RuntimeImpl {
         &self,
         options: &BoxOptions,
         name: Option<String>,
-    ) -> (BoxConfig, BoxState) {
-        use crate::litebox::config::ContainerRuntimeConfig;
+    ) -> BoxliteResult<(BoxConfig, BoxState)> {
+        use crate::litebox::config::{ContainerRuntimeConfig, DiskImageSource};
 
         // Generate unique ID (26 chars, ULID format, sortable by time)
         let box_id = BoxID::new();
@@ -800,33 +1997,52 @@ impl RuntimeImpl {
 
         // Derive paths from ID (computed from layout + ID)
         let box_home = self.layout.boxes_dir().join(box_id.as_str());
-        let socket_path = filenames::unix_socket_path(self.layout.home_dir(), box_id.as_str());
-        let ready_socket_path = box_home.join("sockets").join("ready.sock");
+        let socket_dir =
+            sockets::socket_dir(&box_home, box_id.as_str(), self.socket_dir.as_deref())?;
+        let socket_path = socket_dir.join(sockets::AGENT_SOCKET);
+        let ready_socket_path = socket_dir.join(sockets::READY_SOCKET);
 
         // Create container runtime config
         let container = ContainerRuntimeConfig { id: container_id };
 
+        // A disk image holds the whole rootfs, so it gets an ext4-style
+        // overlay; it can't be split into layers nor cloned across filesystems
+        let (disk_image, storage_driver) = match &options.rootfs {
+            RootfsSpec::DiskImage(path, format) => (
+                Some(DiskImageSource::inspect(path, *format)?),
+                StorageDriver::Ext4,
+            ),
+            _ => (None, self.storage_driver),
+        };
+
         // Create config with defaults + user options
         let config = BoxConfig {
             id: box_id,
             name,
             created_at: now,
+            project: self
+                .project
+                .clone()
+                .unwrap_or_else(|| projects::DEFAULT.to_string()),
             container,
             options: options.clone(),
+            storage_driver,
             engine_kind: VmmKind::Libkrun,
             transport: Transport::unix(socket_path),
             box_home,
             ready_socket_path,
+            warnings: warnings::create_warnings(options, self.layout.is_bind_mount_supported()),
+            disk_image,
         };
 
         // Create initial state (status = Configured)
         let state = BoxState::new();
 
-        (config, state)
+        Ok((config, state))
     }
 
     /// Recover boxes from persistent storage on runtime startup.
-    fn recover_boxes(&self) -> BoxliteResult<()> {
+    fn recover_boxes(self: &Arc<Self>) -> BoxliteResult<()> {
         use crate::util::{is_process_alive, is_same_process};
 
         // Check for system reboot and reset active boxes
@@ -844,9 +2060,11 @@ impl RuntimeImpl {
         self.cleanup_orphaned_directories()?;
 
         let persisted = self.box_manager.all_boxes(true)?;
+        let now = Utc::now();
 
         // Phase 1: Clean up boxes that shouldn't persist
         // - auto_remove=true boxes: these are ephemeral and shouldn't survive restarts
+        // - Expired boxes: their TTL ran out while no runtime was watching
         // - Orphaned active boxes: was Running but directory is missing (crashed mid-operation)
         //
         // Note: We don't remove Configured or Stopped boxes without directories because:
@@ -861,12 +2079,22 @@ impl RuntimeImpl {
                     "Removing auto_remove=true box during recovery"
                 );
                 true
-            } else if state.status.is_active() && !config.box_home.exists() {
+            } else if config.expires_at().is_some_and(|at| at <= now) {
+                tracing::info!(box_id = %config.id, "Removing expired box during recovery");
+                // The shim normally stops itself at expiry; make sure it's gone
+                if let Some(pid) = state.pid
+                    && is_process_alive(pid)
+                    && is_same_process(pid, config.id.as_str())
+                {
+                    crate::util::kill_process(pid);
+                }
+                true
+            } else if state.status().is_active() && !config.box_home.exists() {
                 // Only remove orphaned boxes that were in an active state
                 // Stopped boxes might not have a directory if never started
                 tracing::warn!(
                     box_id = %config.id,
-                    status = ?state.status,
+                    status = ?state.status(),
                     box_home = %config.box_home.display(),
                     "Removing orphaned active box (directory missing) during recovery"
                 );
@@ -880,6 +2108,9 @@ impl RuntimeImpl {
             }
         }
 
+        // Boxes gone or found dead, to take off their networks
+        let mut left_networks: Vec<BoxConfig> = Vec::new();
+
         // Remove invalid boxes from database and cleanup their directories
         for box_id in &boxes_to_remove {
             // Find the config to get box_home path
@@ -895,6 +2126,8 @@ impl RuntimeImpl {
                         "Failed to cleanup box directory during recovery"
                     );
                 }
+                sockets::cleanup(config);
+                left_networks.push(config.clone());
             }
 
             // Remove from database
@@ -909,7 +2142,7 @@ impl RuntimeImpl {
 
         if !boxes_to_remove.is_empty() {
             tracing::info!(
-                "Cleaned up {} boxes during recovery (auto_remove or orphaned)",
+                "Cleaned up {} boxes during recovery (auto_remove, expired or orphaned)",
                 boxes_to_remove.len()
             );
         }
@@ -921,7 +2154,7 @@ impl RuntimeImpl {
 
         for (config, mut state) in persisted {
             let box_id = &config.id;
-            let original_status = state.status;
+            let original_status = state.status();
 
             // Reclaim the lock for this box if one was allocated
             if let Some(lock_id) = state.lock_id {
@@ -957,7 +2190,7 @@ impl RuntimeImpl {
                         if is_process_alive(pid) && is_same_process(pid, box_id.as_str()) {
                             // Process is alive and it's our boxlite-shim - box stays Running
                             state.set_pid(Some(pid));
-                            state.set_status(BoxStatus::Running);
+                            state.recover_to(BoxStatus::Running, "recovered from PID file");
                             tracing::info!(
                                 box_id = %box_id,
                                 pid = pid,
@@ -966,7 +2199,7 @@ impl RuntimeImpl {
                         } else {
                             // Process died or PID was reused - clean up and mark as Stopped
                             let _ = std::fs::remove_file(&pid_file);
-                            state.mark_stop();
+                            state.mark_stop_with_reason("process gone at recovery");
                             tracing::warn!(
                                 box_id = %box_id,
                                 pid = pid,
@@ -977,7 +2210,7 @@ impl RuntimeImpl {
                     Err(e) => {
                         // Can't read PID file - clean up and mark as Stopped
                         let _ = std::fs::remove_file(&pid_file);
-                        state.mark_stop();
+                        state.mark_stop_with_reason("unreadable PID file at recovery");
                         tracing::warn!(
                             box_id = %box_id,
                             error = %e,
@@ -988,19 +2221,34 @@ impl RuntimeImpl {
             } else {
                 // No PID file - box was stopped gracefully or never started
                 // Note: Configured boxes won't have a PID file (this is expected)
-                if state.status == BoxStatus::Running {
-                    state.set_status(BoxStatus::Stopped);
+                if state.status().is_running() || state.status().is_transient() {
+                    state.mark_stop_with_reason("no PID file at recovery");
                     tracing::warn!(
                         box_id = %box_id,
-                        "Box was Running but no PID file found, marked as Stopped"
+                        "Box was {} but no PID file found, marked as Stopped",
+                        original_status
                     );
                 }
             }
 
             // Save updated state to database if changed
-            if state.status != original_status {
+            if state.status() != original_status {
                 self.box_manager.save_box(box_id, &state)?;
             }
+
+            if state.status().is_running() {
+                self.reservations.adopt(box_id, Resources::of(&config));
+            } else if original_status.is_running() {
+                // The VM died while no runtime was watching
+                self.events.emit(|| {
+                    RuntimeEvent::new(EventKind::BoxStopped, BoxInfo::new(&config, &state))
+                });
+                left_networks.push(config);
+            }
+        }
+
+        for config in &left_networks {
+            self.leave_networks(config);
         }
 
         tracing::info!("Box recovery complete");
@@ -1173,6 +2421,36 @@ impl RuntimeImpl {
     }
 }
 
+/// Run `sync` in a box, flushing its page cache to disk.
+pub(crate) async fn flush_guest(litebox: &LiteBox) -> BoxliteResult<()> {
+    let result = litebox.exec(BoxCommand::new("sync")).await?.wait().await?;
+    if result.exit_code != 0 {
+        return Err(BoxliteError::Execution(format!(
+            "sync exited with code {}",
+            result.exit_code
+        )));
+    }
+    Ok(())
+}
+
+/// The storage driver new boxes use: `Reflink` only when the home can
+/// reflink files, `Ext4` otherwise.
+fn resolve_storage_driver(driver: StorageDriver, layout: &FilesystemLayout) -> StorageDriver {
+    if driver != StorageDriver::Reflink {
+        return driver;
+    }
+    if crate::disk::can_reflink(&layout.boxes_dir()) {
+        tracing::info!("Reflink storage driver: box disks are reflink copies");
+        return driver;
+    }
+    tracing::warn!(
+        home = %layout.home_dir().display(),
+        "Reflink storage driver needs a home on a filesystem with reflinks \
+         (btrfs, XFS, ZFS 2.2+ with block cloning, APFS); using the ext4 driver"
+    );
+    StorageDriver::Ext4
+}
+
 impl std::fmt::Debug for RuntimeImpl {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RuntimeInner")
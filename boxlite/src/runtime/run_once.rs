@@ -0,0 +1,199 @@
+//! One-shot runs.
+//!
+//! `BoxliteRuntime::run_once()` creates a box, runs one command in it and
+//! removes the box again, returning the buffered output. The box is removed
+//! whether the run succeeds, fails or times out, and also when the caller
+//! drops the run before it finishes.
+
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+
+use crate::litebox::{BoxCommand, LiteBox};
+use crate::runtime::BoxliteRuntime;
+use crate::runtime::options::BoxOptions;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Result of `BoxliteRuntime::run_once()`.
+#[derive(Clone, Debug)]
+pub struct RunOutcome {
+    /// Exit code of the command (negative signal number if it was killed).
+    pub exit_code: i32,
+    /// Everything the command wrote to stdout.
+    pub stdout: String,
+    /// Everything the command wrote to stderr.
+    pub stderr: String,
+    pub timings: RunTimings,
+}
+
+impl RunOutcome {
+    /// Returns true if the exit code was 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// How long each phase of a one-shot run took.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunTimings {
+    /// Creating and booting the box
+    pub start: Duration,
+    /// Running the command, until its output closed and it exited
+    pub exec: Duration,
+    /// Stopping and removing the box
+    pub cleanup: Duration,
+    /// The whole run
+    pub total: Duration,
+}
+
+pub(crate) async fn run_once(
+    runtime: &BoxliteRuntime,
+    mut options: BoxOptions,
+    command: BoxCommand,
+    timeout: Option<Duration>,
+) -> BoxliteResult<RunOutcome> {
+    let started = Instant::now();
+    // The box lives only for this call
+    options.auto_remove = true;
+    options.detach = false;
+
+    // Created in a task of its own so a box is never created unseen by the
+    // guard, even when this future is dropped in the middle of create()
+    let mut guard = BoxGuard {
+        runtime: runtime.clone(),
+        stage: Stage::Creating(tokio::spawn({
+            let runtime = runtime.clone();
+            async move { runtime.create(options, None).await }
+        })),
+    };
+    let litebox = guard.created().await?;
+    let run = run_command(litebox, command);
+    let result = match timeout {
+        Some(limit) => tokio::time::timeout(limit, run).await.unwrap_or_else(|_| {
+            Err(BoxliteError::Execution(format!(
+                "run did not finish within {}s",
+                limit.as_secs_f64()
+            )))
+        }),
+        None => run.await,
+    };
+
+    let cleanup_started = Instant::now();
+    if let Stage::Created(litebox) = &guard.stage {
+        cleanup(runtime, litebox).await;
+    }
+    guard.stage = Stage::Done;
+    let cleanup = cleanup_started.elapsed();
+
+    let (exit_code, stdout, stderr, mut timings) = result?;
+    timings.cleanup = cleanup;
+    timings.total = started.elapsed();
+    Ok(RunOutcome {
+        exit_code,
+        stdout,
+        stderr,
+        timings,
+    })
+}
+
+/// Start the box and run `command` in it.
+async fn run_command(
+    litebox: &LiteBox,
+    command: BoxCommand,
+) -> BoxliteResult<(i32, String, String, RunTimings)> {
+    let started = Instant::now();
+    litebox.start().await?;
+    let start = started.elapsed();
+
+    let exec_started = Instant::now();
+    let output = litebox.exec(command).await?.collect_output().await?;
+
+    let timings = RunTimings {
+        start,
+        exec: exec_started.elapsed(),
+        ..Default::default()
+    };
+    Ok((
+        output.result.exit_code,
+        output.stdout,
+        output.stderr,
+        timings,
+    ))
+}
+
+/// Where the one-shot box is.
+enum Stage {
+    Creating(JoinHandle<BoxliteResult<LiteBox>>),
+    Created(LiteBox),
+    Done,
+}
+
+/// Removes the one-shot box in the background if `run_once` is dropped
+/// before it cleans up itself.
+struct BoxGuard {
+    runtime: BoxliteRuntime,
+    stage: Stage,
+}
+
+impl BoxGuard {
+    /// Wait for the box to be created.
+    async fn created(&mut self) -> BoxliteResult<&LiteBox> {
+        if let Stage::Creating(task) = &mut self.stage {
+            let created = task
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("create task failed: {}", e)));
+            match created {
+                Ok(Ok(litebox)) => self.stage = Stage::Created(litebox),
+                Ok(Err(e)) | Err(e) => {
+                    self.stage = Stage::Done;
+                    return Err(e);
+                }
+            }
+        }
+        match &self.stage {
+            Stage::Created(litebox) => Ok(litebox),
+            _ => Err(BoxliteError::Internal(
+                "one-shot box already removed".into(),
+            )),
+        }
+    }
+}
+
+impl Drop for BoxGuard {
+    fn drop(&mut self) {
+        let stage = std::mem::replace(&mut self.stage, Stage::Done);
+        if matches!(stage, Stage::Done) {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let runtime = self.runtime.clone();
+        handle.spawn(async move {
+            let litebox = match stage {
+                Stage::Creating(task) => match task.await {
+                    Ok(Ok(litebox)) => litebox,
+                    _ => return,
+                },
+                Stage::Created(litebox) => litebox,
+                Stage::Done => return,
+            };
+            cleanup(&runtime, &litebox).await;
+        });
+    }
+}
+
+/// Stop the box, which removes it (auto_remove), or remove it by force if
+/// stopping fails.
+async fn cleanup(runtime: &BoxliteRuntime, litebox: &LiteBox) {
+    let box_id = litebox.id().clone();
+    if let Err(e) = litebox.stop().await {
+        tracing::warn!(box_id = %box_id, error = %e, "Failed to stop one-shot box");
+    }
+    match runtime.remove(box_id.as_str(), true).await {
+        Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+        Err(e) => {
+            tracing::warn!(box_id = %box_id, error = %e, "Failed to remove one-shot box");
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! JSON Schemas of the JSON contract shared with the SDKs.
+//!
+//! SDKs pass [`BoxOptions`] to the runtime and receive [`BoxInfo`] back as
+//! JSON (serde's representation of these types). The schemas describe that
+//! representation, so SDKs can generate their types from them instead of
+//! keeping hand-written copies in sync. `boxlite schema` prints them.
+
+use schemars::schema_for;
+
+use crate::runtime::options::BoxOptions;
+use crate::runtime::types::BoxInfo;
+
+/// JSON Schema of [`BoxOptions`].
+pub fn box_options_schema() -> serde_json::Value {
+    serde_json::to_value(schema_for!(BoxOptions)).expect("schema is serializable")
+}
+
+/// JSON Schema of [`BoxInfo`].
+pub fn box_info_schema() -> serde_json::Value {
+    serde_json::to_value(schema_for!(BoxInfo)).expect("schema is serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Map, Value};
+
+    /// Check that `value` only has properties the schema declares, and all
+    /// the ones it requires.
+    fn assert_matches_schema(value: &Value, schema: &Value) {
+        let properties = schema["properties"].as_object().unwrap();
+        let object = value.as_object().unwrap();
+        for key in object.keys() {
+            assert!(properties.contains_key(key), "'{}' not in schema", key);
+        }
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let required = required.as_str().unwrap();
+            assert!(object.contains_key(required), "'{}' missing", required);
+        }
+    }
+
+    /// The required properties of `value`, as an SDK that only sets them
+    /// would send it.
+    fn required_only(value: &Value, schema: &Value) -> Value {
+        let mut object = Map::new();
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let key = required.as_str().unwrap();
+            object.insert(key.to_string(), value[key].clone());
+        }
+        Value::Object(object)
+    }
+
+    #[test]
+    fn test_box_options_defaults_round_trip() {
+        let schema = box_options_schema();
+        let defaults = serde_json::to_value(BoxOptions::default()).unwrap();
+        assert_matches_schema(&defaults, &schema);
+
+        let parsed: BoxOptions = serde_json::from_value(defaults.clone()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), defaults);
+
+        // Omitted optional properties take the same defaults as in Rust
+        let minimal = required_only(&defaults, &schema);
+        let parsed: BoxOptions = serde_json::from_value(minimal).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), defaults);
+    }
+
+    #[test]
+    fn test_box_info_round_trip() {
+        let schema = box_info_schema();
+        let json = serde_json::json!({
+            "id": "01HJK4TNRPQSXYZ8WM6NCVT9R5",
+            "name": "web",
+            "status": "running",
+            "created_at": "2025-01-01T00:00:00Z",
+            "last_updated": "2025-01-01T00:00:01Z",
+            "pid": 42,
+            "image": "alpine:latest",
+            "cpus": 2,
+            "memory_mib": 512,
+            "labels": {},
+        });
+        let info: BoxInfo = serde_json::from_value(json).unwrap();
+        let serialized = serde_json::to_value(&info).unwrap();
+        assert_matches_schema(&serialized, &schema);
+
+        let reparsed: BoxInfo = serde_json::from_value(serialized.clone()).unwrap();
+        assert_eq!(serde_json::to_value(reparsed).unwrap(), serialized);
+    }
+}
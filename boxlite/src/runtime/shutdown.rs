@@ -0,0 +1,87 @@
+//! Shutdown phases and results reported by `BoxliteRuntime::shutdown()`.
+//!
+//! Embedders register callbacks with `BoxliteRuntime::on_shutdown()` to
+//! observe each phase, and get a per-box report once shutdown finishes.
+
+use crate::runtime::types::BoxID;
+use std::sync::Arc;
+
+/// Phase of a runtime shutdown, reported to `on_shutdown` callbacks in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownPhase {
+    /// New operations are rejected and in-flight ones are cancelled.
+    Draining,
+    /// Active boxes are being stopped.
+    StoppingBoxes,
+    /// All boxes have been handled; the runtime is unusable from now on.
+    Done,
+}
+
+/// What happened to one box during shutdown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoxShutdownOutcome {
+    /// The box stopped within the timeout.
+    Stopped,
+    /// The box didn't stop within the timeout and its VM process was killed.
+    Killed,
+    /// Stopping the box failed.
+    Failed(String),
+}
+
+/// Shutdown result for one box.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoxShutdownResult {
+    pub box_id: BoxID,
+    pub outcome: BoxShutdownOutcome,
+}
+
+/// Per-box results of a runtime shutdown.
+///
+/// Empty when there were no active boxes or the runtime was already shut down.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub boxes: Vec<BoxShutdownResult>,
+}
+
+impl ShutdownReport {
+    /// Returns true if every box stopped gracefully.
+    pub fn is_clean(&self) -> bool {
+        self.boxes
+            .iter()
+            .all(|b| b.outcome == BoxShutdownOutcome::Stopped)
+    }
+}
+
+/// Callback registered with `BoxliteRuntime::on_shutdown()`.
+pub(crate) type ShutdownCallback = Arc<dyn Fn(ShutdownPhase) + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_is_clean() {
+        let stopped = BoxShutdownResult {
+            box_id: BoxID::new(),
+            outcome: BoxShutdownOutcome::Stopped,
+        };
+        let killed = BoxShutdownResult {
+            box_id: BoxID::new(),
+            outcome: BoxShutdownOutcome::Killed,
+        };
+
+        assert!(ShutdownReport::default().is_clean());
+        assert!(
+            ShutdownReport {
+                boxes: vec![stopped.clone()]
+            }
+            .is_clean()
+        );
+        assert!(
+            !ShutdownReport {
+                boxes: vec![stopped, killed]
+            }
+            .is_clean()
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Placement of a box's Unix sockets.
+//!
+//! The host reaches the guest agent through two Unix sockets that libkrun
+//! bridges to vsock ports. `sockaddr_un.sun_path` holds 104 bytes on macOS
+//! and 108 on Linux (NUL included), which deep home directories overflow,
+//! e.g. a tempdir under `/var/folders/...` on macOS. A new box's sockets go
+//! to the first of:
+//!
+//! 1. `{BoxliteOptions.socket_dir}/{box_id}/`, when configured
+//! 2. `{box_home}/sockets/`, when the paths fit
+//! 3. `$XDG_RUNTIME_DIR/boxlite/{box_id}/`, or `/tmp/boxlite-{uid}/{box_id}/`
+//!    without `XDG_RUNTIME_DIR`
+//!
+//! Abstract socket addresses (Linux) are not used: libkrun binds and
+//! connects its vsock bridges by filesystem path.
+
+use std::path::{Path, PathBuf};
+
+use crate::litebox::config::BoxConfig;
+use crate::runtime::layout::dirs;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Guest agent socket.
+pub(crate) const AGENT_SOCKET: &str = "box.sock";
+
+/// Guest ready notification socket (the longest name).
+pub(crate) const READY_SOCKET: &str = "ready.sock";
+
+/// Connection prompts from gvproxy, for boxes with `prompt_connections`.
+pub(crate) const PROMPT_SOCKET: &str = "net.sock";
+
+#[cfg(target_os = "macos")]
+const SUN_PATH_LEN: usize = 104;
+#[cfg(not(target_os = "macos"))]
+const SUN_PATH_LEN: usize = 108;
+
+/// Longest usable socket path in bytes (`sun_path` minus the NUL).
+pub(crate) const MAX_SOCKET_PATH_LEN: usize = SUN_PATH_LEN - 1;
+
+/// Pick the socket directory for a new box.
+///
+/// Fails when even the chosen directory's paths are too long.
+pub(crate) fn socket_dir(
+    box_home: &Path,
+    box_id: &str,
+    configured: Option<&Path>,
+) -> BoxliteResult<PathBuf> {
+    if let Some(dir) = configured {
+        let dir = dir.join(box_id);
+        check_socket_path(&dir.join(READY_SOCKET))?;
+        return Ok(dir);
+    }
+
+    let dir = box_home.join(dirs::SOCKETS_DIR);
+    if dir.join(READY_SOCKET).as_os_str().len() <= MAX_SOCKET_PATH_LEN {
+        return Ok(dir);
+    }
+
+    let dir = runtime_dir().join(box_id);
+    tracing::debug!(
+        box_id,
+        socket_dir = %dir.display(),
+        "Box home too deep for Unix sockets, using runtime dir"
+    );
+    check_socket_path(&dir.join(READY_SOCKET))?;
+    Ok(dir)
+}
+
+/// Fail with an error naming `path` if it doesn't fit in `sun_path`.
+pub(crate) fn check_socket_path(path: &Path) -> BoxliteResult<()> {
+    let len = path.as_os_str().len();
+    if len > MAX_SOCKET_PATH_LEN {
+        return Err(BoxliteError::Config(format!(
+            "Unix socket path {} is {} bytes, over the {}-byte limit of this platform; \
+             set BoxliteOptions.socket_dir to a shorter directory",
+            path.display(),
+            len,
+            MAX_SOCKET_PATH_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Create a socket directory (mode 0700) before its sockets are bound.
+///
+/// Directories under `/tmp` could have been created by another user first,
+/// so the directory must end up owned by us. A directory in the shared
+/// [`runtime_dir`] also needs that runtime dir to be private: anyone who can
+/// write to it could swap our directory for theirs.
+pub(crate) fn prepare(dir: &Path) -> BoxliteResult<()> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let runtime_dir = runtime_dir();
+    if dir.starts_with(&runtime_dir) {
+        prepare_private_dir(&runtime_dir)?;
+    }
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .map_err(|e| {
+            BoxliteError::Storage(format!(
+                "failed to create socket dir {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+    check_owned_dir(dir, false)
+}
+
+/// Create `dir` (mode 0700) unless it exists, then check that it is a real
+/// directory only we can use.
+fn prepare_private_dir(dir: &Path) -> BoxliteResult<()> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            BoxliteError::Storage(format!("failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    // Not recursive: an existing directory must be checked, not trusted
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => {
+            return Err(BoxliteError::Storage(format!(
+                "failed to create socket dir {}: {}",
+                dir.display(),
+                e
+            )));
+        }
+    }
+    check_owned_dir(dir, true)
+}
+
+/// Fail unless `dir` is a directory (not a symlink to one) owned by us and,
+/// if `private`, closed to group and others.
+fn check_owned_dir(dir: &Path, private: bool) -> BoxliteResult<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let meta = std::fs::symlink_metadata(dir).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "failed to stat socket dir {}: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    let uid = unsafe { libc::geteuid() };
+    if !meta.is_dir() || meta.uid() != uid {
+        return Err(BoxliteError::Storage(format!(
+            "socket dir {} is not a directory owned by uid {}",
+            dir.display(),
+            uid
+        )));
+    }
+    if private && meta.mode() & 0o077 != 0 {
+        return Err(BoxliteError::Storage(format!(
+            "socket dir {} has mode {:o}; it must be 0700",
+            dir.display(),
+            meta.mode() & 0o7777
+        )));
+    }
+    Ok(())
+}
+
+/// A box's socket directory, if it lives outside its box home.
+pub(crate) fn external_dir(config: &BoxConfig) -> Option<&Path> {
+    config
+        .ready_socket_path
+        .parent()
+        .filter(|dir| !dir.starts_with(&config.box_home))
+}
+
+/// Remove a box's socket directory if it lives outside its box home.
+pub(crate) fn cleanup(config: &BoxConfig) {
+    let Some(dir) = external_dir(config) else {
+        return;
+    };
+    if !dir.exists() {
+        return;
+    }
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        tracing::warn!(
+            box_id = %config.id,
+            path = %dir.display(),
+            error = %e,
+            "Failed to cleanup socket directory"
+        );
+    }
+}
+
+/// Short per-user directory for sockets of deep box homes.
+pub(crate) fn runtime_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir.join("boxlite"),
+        // Not std::env::temp_dir(): on macOS that is itself deep under /var/folders
+        _ => PathBuf::from("/tmp").join(format!("boxlite-{}", unsafe { libc::geteuid() })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOX_ID: &str = "01JJNH8SK3R7Y2E0BQ5M1VWXYZ";
+
+    #[test]
+    fn test_short_home_keeps_sockets_in_box_home() {
+        let box_home = Path::new("/home/user/.boxlite/boxes").join(BOX_ID);
+        let dir = socket_dir(&box_home, BOX_ID, None).unwrap();
+        assert_eq!(dir, box_home.join("sockets"));
+    }
+
+    #[test]
+    fn test_deep_home_uses_runtime_dir() {
+        let box_home = Path::new("/")
+            .join("deep".repeat(30))
+            .join("boxes")
+            .join(BOX_ID);
+        let dir = socket_dir(&box_home, BOX_ID, None).unwrap();
+        assert!(dir.starts_with(runtime_dir()));
+        assert!(dir.ends_with(BOX_ID));
+        assert!(dir.join(READY_SOCKET).as_os_str().len() <= MAX_SOCKET_PATH_LEN);
+    }
+
+    #[test]
+    fn test_configured_dir_too_long_names_path() {
+        let configured = Path::new("/").join("x".repeat(MAX_SOCKET_PATH_LEN));
+        let err = socket_dir(Path::new("/b"), BOX_ID, Some(&configured)).unwrap_err();
+        match err {
+            BoxliteError::Config(msg) => {
+                assert!(msg.contains(&configured.display().to_string()));
+                assert!(msg.contains("socket_dir"));
+            }
+            other => panic!("expected Config error, got {other:?}"),
+        }
+
+        let dir = socket_dir(Path::new("/b"), BOX_ID, Some(Path::new("/run/bl"))).unwrap();
+        assert_eq!(dir, Path::new("/run/bl").join(BOX_ID));
+    }
+
+    #[test]
+    fn test_prepare_and_bind() {
+        let base = tempfile::tempdir().unwrap();
+        let dir = base.path().join(BOX_ID);
+        prepare(&dir).unwrap();
+        // Idempotent across restarts
+        prepare(&dir).unwrap();
+
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let socket = dir.join(READY_SOCKET);
+        if check_socket_path(&socket).is_ok() {
+            std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_private_dir_rejects_shared_and_symlinked_dirs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let base = tempfile::tempdir().unwrap();
+        let dir = base.path().join("boxlite-1000");
+        prepare_private_dir(&dir).unwrap();
+        prepare_private_dir(&dir).unwrap();
+
+        // Left open by whoever created it first
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(prepare_private_dir(&dir).is_err());
+
+        let link = base.path().join("link");
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(prepare_private_dir(&link).is_err());
+    }
+}
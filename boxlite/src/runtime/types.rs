@@ -1,9 +1,11 @@
 //! Core data types for box lifecycle management.
 
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::{DateTime, Utc};
 use rand::RngCore;
 use rusqlite::ToSql;
 use rusqlite::types::{ToSqlOutput, ValueRef};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -11,7 +13,9 @@ use std::fmt;
 use std::hash::Hash;
 
 // Re-export status types from litebox module
-pub use crate::litebox::{BoxState, BoxStatus};
+pub use crate::litebox::{
+    BoxState, BoxStatus, BoxTimings, GuestAgentInfo, RootfsBuildTimings, StatusTransition,
+};
 
 // ============================================================================
 // RESOURCE LIMIT TYPES (C-NEWTYPE: Semantic newtypes for distinct concepts)
@@ -207,7 +211,7 @@ impl fmt::Display for Seconds {
 /// assert_eq!(id.as_str().len(), 26);
 /// assert_eq!(id.short().len(), 8);
 /// ```
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct BoxID(String);
 
 impl BoxID {
@@ -386,7 +390,7 @@ impl AsRef<str> for ContainerID {
 }
 
 /// Public metadata about a box (returned by list operations).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BoxInfo {
     /// Unique box identifier (ULID).
     pub id: BoxID,
@@ -394,6 +398,10 @@ pub struct BoxInfo {
     /// User-defined name (optional).
     pub name: Option<String>,
 
+    /// Project (namespace) the box belongs to.
+    #[serde(default = "crate::runtime::constants::projects::default_project")]
+    pub project: String,
+
     /// Current lifecycle status.
     pub status: BoxStatus,
 
@@ -417,31 +425,75 @@ pub struct BoxInfo {
 
     /// User-defined labels for filtering and organization.
     pub labels: HashMap<String, String>,
+
+    /// IDs of the boxes this box depends on.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// When the box is stopped and removed (None without a TTL).
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Stage durations of the last start (None until the box has started).
+    #[serde(default)]
+    pub timings: Option<BoxTimings>,
+
+    /// Version and capabilities of the guest agent (None until started).
+    #[serde(default)]
+    pub guest_agent: Option<GuestAgentInfo>,
+
+    /// Non-fatal issues found when the box was created, e.g. an option
+    /// this host ignores.
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
 
 impl BoxInfo {
     /// Create BoxInfo from config and state.
     pub fn new(config: &crate::litebox::config::BoxConfig, state: &BoxState) -> Self {
-        use crate::runtime::options::RootfsSpec;
-
         Self {
             id: config.id.clone(),
             name: config.name.clone(),
-            status: state.status,
+            project: config.project.clone(),
+            status: state.status(),
             created_at: config.created_at,
             last_updated: state.last_updated,
             pid: state.pid,
-            image: match &config.options.rootfs {
-                RootfsSpec::Image(r) => r.clone(),
-                RootfsSpec::RootfsPath(p) => format!("rootfs:{}", p),
-            },
+            image: config.image(),
             cpus: config.options.cpus.unwrap_or(2),
             memory_mib: config.options.memory_mib.unwrap_or(512),
             labels: HashMap::new(),
+            depends_on: config.options.depends_on.clone(),
+            expires_at: config.expires_at(),
+            timings: state.timings.clone(),
+            guest_agent: state.guest_agent.clone(),
+            warnings: config.warnings.clone(),
         }
     }
 }
 
+/// Which boxes `list_info_filtered()` returns.
+///
+/// Empty fields match every box. Filters are answered from indexed database
+/// columns, without loading the boxes that don't match.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BoxFilter {
+    /// Boxes in any of these statuses.
+    pub statuses: Vec<BoxStatus>,
+
+    /// Boxes created from this image reference, as given at creation
+    /// (`BoxInfo.image`).
+    pub image: Option<String>,
+}
+
+impl BoxFilter {
+    /// Whether `info` passes the filter.
+    pub fn matches(&self, info: &BoxInfo) -> bool {
+        (self.statuses.is_empty() || self.statuses.contains(&info.status))
+            && self.image.as_ref().is_none_or(|image| *image == info.image)
+    }
+}
+
 impl PartialEq for BoxInfo {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -452,6 +504,8 @@ impl PartialEq for BoxInfo {
             && self.cpus == other.cpus
             && self.memory_mib == other.memory_mib
             && self.labels == other.labels
+            && self.depends_on == other.depends_on
+            && self.expires_at == other.expires_at
     }
 }
 
@@ -479,8 +533,8 @@ impl BoxStateInfo {
     /// Create BoxStateInfo from internal BoxState.
     pub fn new(state: &BoxState) -> Self {
         Self {
-            status: state.status,
-            running: state.status.is_running(),
+            status: state.status(),
+            running: state.status().is_running(),
             pid: state.pid,
         }
     }
@@ -520,11 +574,73 @@ pub struct ImageInfo {
     pub id: String,
 
     /// When this image was pulled/cached locally.
-    /// Note: This is NOT the image build time (which requires reading config blob).
+    /// Note: This is NOT the image build time; see `created`.
     pub cached_at: DateTime<Utc>,
 
-    /// Image size in bytes (if available)
+    /// Image build time from the config blob (if recorded)
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+
+    /// Whether the image is pinned (protected from pruning)
+    #[serde(default)]
+    pub pinned: bool,
+
+    /// Compressed size of the image layers in bytes (if available)
     pub size: Option<Bytes>,
+
+    /// Disk usage in bytes: layer tarballs, extracted layers and disk images
+    /// built from them (if available)
+    #[serde(default)]
+    pub disk_size: Option<Bytes>,
+
+    /// Number of boxes created from this image's content, through this
+    /// reference or another one with the same manifest digest
+    #[serde(default)]
+    pub in_use_by: usize,
+}
+
+impl ImageInfo {
+    /// Whether the image is neither used by a box nor pinned.
+    pub fn is_dangling(&self) -> bool {
+        self.in_use_by == 0 && !self.pinned
+    }
+}
+
+// ============================================================================
+// PROJECT INFO
+// ============================================================================
+
+/// A project (namespace) and how many boxes it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    /// Project name
+    pub name: String,
+
+    /// Number of boxes in the project
+    pub boxes: usize,
+
+    /// Number of those boxes that are running
+    pub running: usize,
+}
+
+/// Check that a project name is usable: 1-63 characters of ASCII letters,
+/// digits, `-`, `_` and `.`, starting with a letter or digit.
+pub(crate) fn validate_project_name(name: &str) -> BoxliteResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 63
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(BoxliteError::InvalidArgument(format!(
+            "invalid project name '{}': use 1-63 letters, digits, '-', '_' or '.', \
+             starting with a letter or digit",
+            name
+        )))
+    }
 }
 
 // ============================================================================
@@ -596,6 +712,16 @@ mod tests {
 
     // BoxStatus and BoxState tests are in litebox/state
 
+    #[test]
+    fn test_validate_project_name() {
+        for name in ["default", "ci-42", "team.web", "a_b"] {
+            assert!(validate_project_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "-x", ".hidden", "a/b", "has space", &"x".repeat(64)] {
+            assert!(validate_project_name(name).is_err(), "{}", name);
+        }
+    }
+
     #[test]
     fn test_config_state_to_info() {
         let now = Utc::now();
@@ -604,6 +730,7 @@ mod tests {
             id: box_id,
             name: None,
             created_at: now,
+            project: "default".to_string(),
             container: ContainerRuntimeConfig {
                 id: ContainerID::new(),
             },
@@ -613,10 +740,13 @@ mod tests {
                 memory_mib: Some(1024),
                 ..Default::default()
             },
+            storage_driver: Default::default(),
             engine_kind: crate::vmm::VmmKind::Libkrun,
             transport: Transport::unix(PathBuf::from("/tmp/boxlite.sock")),
             box_home: PathBuf::from("/tmp/box"),
             ready_socket_path: PathBuf::from("/tmp/ready.sock"),
+            warnings: Vec::new(),
+            disk_image: None,
         };
 
         let mut state = BoxState::new();
@@ -626,12 +756,19 @@ mod tests {
         let info = BoxInfo::new(&config, &state);
 
         assert_eq!(info.id, config.id);
-        assert_eq!(info.status, state.status);
+        assert_eq!(info.status, state.status());
         assert_eq!(info.created_at, config.created_at);
+        assert_eq!(info.project, "default");
         assert_eq!(info.pid, state.pid);
         assert_eq!(info.image, "python:3.11");
         assert_eq!(info.cpus, 4);
         assert_eq!(info.memory_mib, 1024);
+
+        // Info serialized before projects existed is in the default project
+        let mut json = serde_json::to_value(&info).unwrap();
+        json.as_object_mut().unwrap().remove("project");
+        let info: BoxInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(info.project, "default");
     }
 
     #[test]
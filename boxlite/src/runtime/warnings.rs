@@ -0,0 +1,103 @@
+//! Non-fatal issues found when a box is created.
+//!
+//! Options that will work, but not quite as asked (a setting this host
+//! ignores, a port that may fail to bind, too little memory to be
+//! comfortable), don't fail `create()`. They are kept in the box's config
+//! and reported as `BoxInfo::warnings`, so callers can show them.
+
+use crate::runtime::constants::vm_defaults::{DEFAULT_MEMORY_MIB, MIN_RECOMMENDED_MEMORY_MIB};
+use crate::runtime::options::BoxOptions;
+
+/// Warnings for creating a box with `options`.
+pub(crate) fn create_warnings(options: &BoxOptions, bind_mount_supported: bool) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let memory_mib = options.memory_mib.unwrap_or(DEFAULT_MEMORY_MIB);
+    if memory_mib < MIN_RECOMMENDED_MEMORY_MIB {
+        warnings.push(format!(
+            "memory_mib {} is below the recommended {} MiB; the guest may run out of memory",
+            memory_mib, MIN_RECOMMENDED_MEMORY_MIB
+        ));
+    }
+
+    if options.isolate_mounts && !bind_mount_supported {
+        warnings.push(
+            "isolate_mounts is ignored: bind mounts are not supported on this host".to_string(),
+        );
+    }
+
+    let unprivileged_start = unprivileged_port_start();
+    for port in &options.ports {
+        // Without a host port the guest port is forwarded as is
+        let host_port = port
+            .host_port
+            .filter(|&p| p != 0)
+            .unwrap_or(port.guest_port);
+        if host_port < unprivileged_start {
+            warnings.push(format!(
+                "host port {} is privileged; binding it may fail without root",
+                host_port
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Lowest port this process may bind; 0 when it may bind any.
+#[cfg(target_os = "linux")]
+fn unprivileged_port_start() -> u16 {
+    if unsafe { libc::geteuid() } == 0 {
+        return 0;
+    }
+    std::fs::read_to_string("/proc/sys/net/ipv4/ip_unprivileged_port_start")
+        .ok()
+        .and_then(|start| start.trim().parse().ok())
+        .unwrap_or(1024)
+}
+
+/// macOS lets any user bind low ports on all addresses.
+#[cfg(not(target_os = "linux"))]
+fn unprivileged_port_start() -> u16 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::PortSpec;
+
+    #[test]
+    fn test_create_warnings() {
+        let options = BoxOptions::default();
+        assert!(create_warnings(&options, true).is_empty());
+
+        let options = BoxOptions {
+            memory_mib: Some(64),
+            isolate_mounts: true,
+            ..Default::default()
+        };
+        let warnings = create_warnings(&options, false);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("memory_mib 64"));
+        assert!(warnings[1].starts_with("isolate_mounts is ignored"));
+        assert!(create_warnings(&options, true)[0].starts_with("memory_mib"));
+
+        let options = BoxOptions {
+            ports: vec![
+                "8080:80".parse::<PortSpec>().unwrap(),
+                "80".parse::<PortSpec>().unwrap(),
+            ],
+            ..Default::default()
+        };
+        let warnings = create_warnings(&options, true);
+        if unprivileged_port_start() > 80 {
+            assert_eq!(
+                warnings,
+                ["host port 80 is privileged; binding it may fail without root"]
+            );
+        } else {
+            assert!(warnings.is_empty());
+        }
+    }
+}
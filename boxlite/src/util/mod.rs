@@ -10,7 +10,7 @@ use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use tracing_appender::non_blocking::NonBlocking;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, fmt};
+use tracing_subscriber::{EnvFilter, Registry, fmt, reload};
 
 pub use process::{is_process_alive, is_same_process, kill_process, read_pid_file};
 
@@ -117,8 +117,16 @@ pub fn configure_library_env(cmd: &mut Command, addr: *const libc::c_void) {
     }
 }
 
-pub fn register_to_tracing(non_blocking: NonBlocking, env_filter: EnvFilter) {
-    let _ = tracing_subscriber::registry()
+/// Install the global subscriber, writing to `non_blocking`.
+///
+/// Returns a handle for swapping the filter later, or None if a global
+/// subscriber was already set.
+pub fn register_to_tracing(
+    non_blocking: NonBlocking,
+    env_filter: EnvFilter,
+) -> Option<reload::Handle<EnvFilter, Registry>> {
+    let (env_filter, handle) = reload::Layer::new(env_filter);
+    tracing_subscriber::registry()
         .with(env_filter)
         .with(
             fmt::layer()
@@ -129,7 +137,9 @@ pub fn register_to_tracing(non_blocking: NonBlocking, env_filter: EnvFilter) {
                 .with_line_number(false)
                 .with_ansi(false),
         )
-        .try_init();
+        .try_init()
+        .ok()
+        .map(|_| handle)
 }
 
 /// Inject guest binary into a rootfs directory.
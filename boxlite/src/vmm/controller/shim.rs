@@ -275,11 +275,12 @@ impl VmmController for ShimController {
             ready_transport: config.ready_transport.clone(),
             guest_rootfs: config.guest_rootfs.clone(),
             network_config: config.network_config.clone(), // Pass port mappings to subprocess (shim creates gvproxy)
-            network_backend_endpoint: None, // Will be populated by shim (not serialized)
+            network_backend_endpoint: config.network_backend_endpoint.clone(), // Set by a network driver, otherwise populated by shim
             home_dir: config.home_dir.clone(),
             console_output: config.console_output.clone(),
             detach: config.detach,
             parent_pid: config.parent_pid,
+            expires_at: config.expires_at,
         };
 
         // Serialize the config for passing to subprocess
@@ -306,6 +307,10 @@ impl VmmController for ShimController {
 
         // Measure subprocess spawn time
         let shim_spawn_start = Instant::now();
+        let socket_dir = match &config.transport {
+            boxlite_shared::Transport::Unix { socket_path } => socket_path.parent(),
+            _ => None,
+        };
         let child = spawn_subprocess(
             &self.binary_path,
             self.engine_type,
@@ -313,6 +318,7 @@ impl VmmController for ShimController {
             &config.home_dir,
             self.box_id.as_str(),
             &self.options,
+            socket_dir,
         )?;
         // spawn_duration: time to create Box subprocess
         let shim_spawn_duration = shim_spawn_start.elapsed();
@@ -7,12 +7,15 @@ use std::{
 
 use crate::jailer::Jailer;
 use crate::runtime::layout::FilesystemLayout;
-use crate::runtime::options::BoxOptions;
+use crate::runtime::options::{BoxOptions, RootfsSpec};
 use crate::util::configure_library_env;
 use crate::vmm::VmmKind;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use libkrun_sys::krun_create_ctx;
 
+/// Size past which the shim's stderr file is rotated when the box starts.
+const STDERR_ROTATE_BYTES: u64 = 1024 * 1024;
+
 /// Spawns a subprocess with jailer isolation.
 ///
 /// # Arguments
@@ -22,6 +25,7 @@ use libkrun_sys::krun_create_ctx;
 /// * `home_dir` - BoxLite home directory
 /// * `box_id` - Unique box identifier
 /// * `options` - Box options (includes security and volumes)
+/// * `socket_dir` - Directory of the box's Unix sockets
 ///
 /// # Returns
 /// * `Ok(Child)` - Successfully spawned subprocess
@@ -33,6 +37,7 @@ pub(crate) fn spawn_subprocess(
     home_dir: &Path,
     box_id: &str,
     options: &BoxOptions,
+    socket_dir: Option<&Path>,
 ) -> BoxliteResult<Child> {
     // Build shim arguments
     let shim_args = vec![
@@ -47,10 +52,46 @@ pub(crate) fn spawn_subprocess(
     let layout = FilesystemLayout::new(home_dir.to_path_buf(), FsLayoutConfig::default());
     let box_dir = layout.boxes_dir().join(box_id);
 
-    // Create Jailer with security options and volumes
-    let jailer = Jailer::new(box_id, &box_dir)
+    // Create Jailer with security options and volumes. Sockets outside the
+    // box dir (see runtime::sockets) must be reachable from the sandbox too.
+    let mut jailer = Jailer::new(box_id, &box_dir)
         .with_security(options.security.clone())
-        .with_volumes(options.volumes.clone());
+        .with_volumes(options.volumes.clone())
+        .with_socket_dir(
+            socket_dir
+                .filter(|dir| !dir.starts_with(&box_dir))
+                .map(Path::to_path_buf),
+        );
+
+    // The HTTP recorder in gvproxy reads (replay) or writes (record) its
+    // directory from the shim, which can only bind one that exists
+    if let Some((dir, replay)) = options.network_mode.recording() {
+        if !replay {
+            use std::os::unix::fs::DirBuilderExt;
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .map_err(|e| {
+                    BoxliteError::Storage(format!(
+                        "Failed to create recording directory {}: {}",
+                        dir.display(),
+                        e
+                    ))
+                })?;
+        }
+        jailer = jailer.with_allowed_path(dir, !replay);
+    }
+
+    // The box's disk is a COW overlay over a user's disk image, which the
+    // shim opens as its backing file (by its canonical path) and never writes
+    if let RootfsSpec::DiskImage(path, _) = &options.rootfs {
+        let path = Path::new(path);
+        jailer = jailer.with_allowed_path(
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+            false,
+        );
+    }
 
     // Setup pre-spawn isolation (cgroups on Linux, no-op on macOS)
     jailer.setup_pre_spawn()?;
@@ -69,12 +110,16 @@ pub(crate) fn spawn_subprocess(
     // Set library search paths for bundled dependencies
     configure_library_env(&mut cmd, krun_create_ctx as *const libc::c_void);
 
-    // Use null for all stdio to support detach/reattach without pipe issues.
+    // Avoid pipes for stdio to support detach/reattach without pipe issues.
     // - stdin: prevents libkrun from affecting parent's stdin
-    // - stdout/stderr: prevents SIGPIPE when LogStreamHandler is dropped on detach
+    // - stdout: prevents SIGPIPE when LogStreamHandler is dropped on detach
+    // - stderr: appended to a file in the box dir, so hypervisor errors end
+    //   up in the boot diagnostic bundle
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::null());
-    cmd.stderr(Stdio::null());
+    cmd.stderr(open_stderr_file(
+        &layout.box_layout(box_id, false)?.shim_stderr_path(),
+    ));
 
     cmd.spawn().map_err(|e| {
         let err_msg = format!(
@@ -86,3 +131,62 @@ pub(crate) fn spawn_subprocess(
         BoxliteError::Engine(err_msg)
     })
 }
+
+/// Open the shim's stderr file, falling back to null if it can't be created.
+///
+/// A file that grew past [`STDERR_ROTATE_BYTES`] over earlier starts is
+/// first moved to `<name>.1`, replacing the previous one, so a box that is
+/// restarted often keeps at most two of them.
+fn open_stderr_file(path: &Path) -> Stdio {
+    rotate_if_large(path, STDERR_ROTATE_BYTES);
+    match std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        Ok(file) => Stdio::from(file),
+        Err(e) => {
+            tracing::warn!("Failed to open {}: {}", path.display(), e);
+            Stdio::null()
+        }
+    }
+}
+
+fn rotate_if_large(path: &Path, max_bytes: u64) {
+    let large = std::fs::metadata(path).is_ok_and(|meta| meta.len() > max_bytes);
+    if !large {
+        return;
+    }
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    if let Err(e) = std::fs::rename(path, &rotated) {
+        tracing::warn!("Failed to rotate {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_if_large() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shim.stderr");
+        let rotated = dir.path().join("shim.stderr.1");
+
+        std::fs::write(&path, "small").unwrap();
+        rotate_if_large(&path, 16);
+        assert!(path.exists());
+        assert!(!rotated.exists());
+
+        std::fs::write(&rotated, "older").unwrap();
+        std::fs::write(&path, "a".repeat(17)).unwrap();
+        rotate_if_large(&path, 16);
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap(), "a".repeat(17));
+
+        // Missing files are left alone
+        rotate_if_large(&path, 16);
+        assert!(!path.exists());
+    }
+}
@@ -166,9 +166,10 @@ pub struct InstanceSpec {
     /// The shim creates the network backend (gvproxy) from this config,
     /// ensuring networking survives detach operations.
     pub network_config: Option<crate::net::NetworkBackendConfig>,
-    /// Network backend endpoint (socket path) - populated by shim after creating gvproxy.
-    /// This is not serialized; it's set in-process by the shim before calling the engine.
-    #[serde(skip)]
+    /// Network backend endpoint (socket path) - populated by shim after creating gvproxy,
+    /// or set by the host when a network driver provides the network (`network_config`
+    /// is then None, so the shim doesn't start gvproxy).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub network_backend_endpoint: Option<crate::net::NetworkBackendEndpoint>,
     /// Home directory for boxlite runtime (~/.boxlite or BOXLITE_HOME)
     pub home_dir: PathBuf,
@@ -180,6 +181,10 @@ pub struct InstanceSpec {
     /// PID of the parent process that spawned this box.
     /// Used by watchdog to detect when parent exits (if detach=false).
     pub parent_pid: u32,
+    /// When the box's TTL runs out (`BoxOptions.ttl_secs`).
+    /// The watchdog shuts the VM down at this time, even if the runtime is gone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Entrypoint configuration that the guest should run.
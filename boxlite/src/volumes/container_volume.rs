@@ -23,6 +23,29 @@ pub struct ContainerMount {
     pub destination: String,
     /// Read-only mount
     pub read_only: bool,
+    /// Other mount options (noexec, nosuid, nodev, uid=, gid=)
+    pub options: Vec<String>,
+    /// Map the container's user to the owner of the source directory
+    pub map_user: bool,
+}
+
+/// User volume to share with the guest and bind mount into the container.
+#[derive(Debug, Clone)]
+pub struct UserVolume<'a> {
+    /// Volume identifier (e.g., "data", "config")
+    pub volume_name: &'a str,
+    /// Virtiofs tag name (e.g., "uservol0")
+    pub tag: &'a str,
+    /// Path on host to share
+    pub host_path: PathBuf,
+    /// Mount point in container (user-specified)
+    pub container_path: &'a str,
+    /// Whether the mount is read-only
+    pub read_only: bool,
+    /// Other options of the container mount
+    pub options: &'a [String],
+    /// Whether to map the container's user to the directory owner
+    pub map_user: bool,
 }
 
 /// Manages container-level volume configuration.
@@ -56,35 +79,25 @@ impl<'a> ContainerVolumeManager<'a> {
     ///
     /// # Arguments
     /// * `container_id` - Container ID for path construction
-    /// * `volume_name` - Volume identifier (e.g., "data", "config")
-    /// * `tag` - Virtiofs tag name (e.g., "uservol0")
-    /// * `host_path` - Path on host to share
-    /// * `container_path` - Mount point in container (user-specified)
-    /// * `read_only` - Whether the mount is read-only
-    pub fn add_volume(
-        &mut self,
-        container_id: &str,
-        volume_name: &str,
-        tag: &str,
-        host_path: PathBuf,
-        container_path: &str,
-        read_only: bool,
-    ) {
+    /// * `volume` - The volume and where to mount it in the container
+    pub fn add_volume(&mut self, container_id: &str, volume: UserVolume<'_>) {
         // Add virtiofs share to guest with container_id
         // Guest will mount at convention path: /run/boxlite/shared/containers/{container_id}/volumes/{tag}
         self.guest.add_fs_share(
-            tag,
-            host_path,
+            volume.tag,
+            volume.host_path,
             None,
-            read_only,
+            volume.read_only,
             Some(container_id.to_string()),
         );
 
         // Record container bind mount - guest constructs source path from convention
         self.container_mounts.push(ContainerMount {
-            volume_name: volume_name.to_string(),
-            destination: container_path.to_string(),
-            read_only,
+            volume_name: volume.volume_name.to_string(),
+            destination: volume.container_path.to_string(),
+            read_only: volume.read_only,
+            options: volume.options.to_vec(),
+            map_user: volume.map_user,
         });
     }
 
@@ -97,6 +110,8 @@ impl<'a> ContainerVolumeManager<'a> {
             volume_name: volume_name.to_string(),
             destination: container_path.to_string(),
             read_only,
+            options: Vec::new(),
+            map_user: false,
         });
     }
 
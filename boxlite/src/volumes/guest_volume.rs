@@ -160,10 +160,10 @@ impl GuestVolumeManager {
         let mut block_devices = BlockDevices::new();
         for entry in &self.block_devices {
             // Map disk format to VMM block format:
-            // - Ext4 filesystem → Raw block image
+            // - Ext4/Erofs filesystem → Raw block image
             // - Qcow2 → Qcow2 (COW format)
             let vmm_format = match entry.format {
-                DiskFormat::Ext4 => crate::vmm::DiskFormat::Raw,
+                DiskFormat::Ext4 | DiskFormat::Erofs => crate::vmm::DiskFormat::Raw,
                 DiskFormat::Qcow2 => crate::vmm::DiskFormat::Qcow2,
             };
             block_devices.add(BlockDevice {
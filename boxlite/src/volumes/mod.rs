@@ -7,5 +7,5 @@
 mod container_volume;
 mod guest_volume;
 
-pub use container_volume::{ContainerMount, ContainerVolumeManager};
+pub use container_volume::{ContainerMount, ContainerVolumeManager, UserVolume};
 pub use guest_volume::GuestVolumeManager;
@@ -6,7 +6,9 @@
 use boxlite::BoxCommand;
 use boxlite::BoxliteRuntime;
 use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+use boxlite::{BoxShutdownOutcome, ShutdownPhase};
 use boxlite_shared::BoxliteError;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tempfile::TempDir;
 
@@ -28,6 +30,7 @@ impl TestContext {
         let options = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         Self {
@@ -642,6 +645,43 @@ async fn test_create_after_shutdown_returns_stopped() {
     assert!(matches!(result, Err(BoxliteError::Stopped(_))));
 }
 
+/// Test that shutdown reports each phase to callbacks and each box's outcome.
+#[tokio::test]
+async fn test_shutdown_reports_phases_and_boxes() {
+    let ctx = TestContext::new();
+
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    let phases = Arc::new(Mutex::new(Vec::new()));
+    let recorded = phases.clone();
+    ctx.runtime
+        .on_shutdown(move |phase| recorded.lock().unwrap().push(phase));
+
+    let report = ctx.runtime.shutdown(Some(10)).await.unwrap();
+
+    assert_eq!(
+        *phases.lock().unwrap(),
+        [
+            ShutdownPhase::Draining,
+            ShutdownPhase::StoppingBoxes,
+            ShutdownPhase::Done
+        ]
+    );
+    assert_eq!(report.boxes.len(), 1);
+    assert_eq!(&report.boxes[0].box_id, handle.id());
+    assert_eq!(report.boxes[0].outcome, BoxShutdownOutcome::Stopped);
+    assert!(report.is_clean());
+
+    // Second shutdown is a no-op with an empty report
+    let report = ctx.runtime.shutdown(Some(10)).await.unwrap();
+    assert!(report.boxes.is_empty());
+}
+
 /// Test that wait() returns promptly when box is stopped.
 ///
 /// This is the key test for the cancellation token implementation.
@@ -0,0 +1,37 @@
+//! Integration tests for forking boxes.
+
+use boxlite::runtime::options::BoxliteOptions;
+use boxlite::{BoxOptions, BoxliteError};
+use tempfile::TempDir;
+
+#[test]
+fn test_fork_requires_started_box() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = boxlite::blocking::BoxliteRuntime::new(config).unwrap();
+    let litebox = runtime
+        .create(BoxOptions::default(), Some("fork-source".into()))
+        .unwrap();
+
+    assert!(matches!(
+        runtime.fork("missing", 1),
+        Err(BoxliteError::NotFound(_))
+    ));
+    assert!(matches!(
+        runtime.fork("fork-source", 0),
+        Err(BoxliteError::InvalidArgument(_))
+    ));
+    // A box that never started has no disk to copy
+    assert!(matches!(
+        runtime.fork("fork-source", 2),
+        Err(BoxliteError::InvalidState(_))
+    ));
+    assert_eq!(runtime.list_info().unwrap().len(), 1);
+
+    runtime.remove(litebox.id().as_str(), true).unwrap();
+}
@@ -21,6 +21,7 @@ impl TestContext {
         let options = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         Self {
@@ -584,6 +585,7 @@ async fn boxes_persist_across_runtime_restart() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         let litebox = runtime
@@ -612,6 +614,7 @@ async fn boxes_persist_across_runtime_restart() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -642,6 +645,7 @@ async fn multiple_boxes_persist_and_recover_without_lock_errors() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -700,6 +704,7 @@ async fn multiple_boxes_persist_and_recover_without_lock_errors() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime after restart");
 
@@ -875,6 +880,7 @@ async fn recovery_removes_auto_remove_true_boxes() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -924,6 +930,7 @@ async fn recovery_removes_auto_remove_true_boxes() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime after restart");
 
@@ -963,6 +970,7 @@ async fn recovery_removes_orphaned_stopped_boxes_without_directory() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -997,6 +1005,7 @@ async fn recovery_removes_orphaned_stopped_boxes_without_directory() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime after restart");
 
@@ -1022,3 +1031,79 @@ async fn recovery_removes_orphaned_stopped_boxes_without_directory() {
         runtime.remove(box_id.as_str(), false).await.unwrap();
     }
 }
+
+// ============================================================================
+// PROGRESS TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn start_reports_pull_and_boot_progress() {
+    use boxlite::ProgressEvent;
+    use std::sync::{Arc, Mutex};
+
+    let ctx = TestContext::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    ctx.runtime
+        .on_progress(move |event| sink.lock().unwrap().push(event.clone()));
+
+    let handle = ctx
+        .runtime
+        .create(
+            BoxOptions {
+                rootfs: RootfsSpec::Image("alpine:latest".into()),
+                auto_remove: false,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+    let box_id = handle.id().to_string();
+
+    let events = events.lock().unwrap().clone();
+    // The home is fresh, so the image comes from the registry
+    let pull_started = events
+        .iter()
+        .position(|e| matches!(e, ProgressEvent::PullStarted { layers, .. } if *layers > 0))
+        .expect("no PullStarted event");
+    let pull_done = events
+        .iter()
+        .position(|e| matches!(e, ProgressEvent::PullDone { .. }))
+        .expect("no PullDone event");
+    assert!(pull_started < pull_done);
+    assert!(
+        events[pull_started..pull_done]
+            .iter()
+            .any(|e| matches!(e, ProgressEvent::LayerDone { .. }))
+    );
+
+    // Boot steps count up to the last one, then the box is done
+    let steps: Vec<(usize, usize)> = events
+        .iter()
+        .filter_map(|e| match e {
+            ProgressEvent::BootStep {
+                box_id: id,
+                step,
+                steps,
+                ..
+            } if *id == box_id => Some((*step, *steps)),
+            _ => None,
+        })
+        .collect();
+    assert!(!steps.is_empty());
+    let total = steps[0].1;
+    assert_eq!(
+        steps.iter().map(|(step, _)| *step).collect::<Vec<_>>(),
+        (1..=total).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        events.last(),
+        Some(&ProgressEvent::BootDone {
+            box_id: box_id.clone()
+        })
+    );
+
+    ctx.runtime.remove(&box_id, true).await.unwrap();
+}
@@ -0,0 +1,38 @@
+//! Integration tests for user-defined networks between boxes.
+
+use boxlite::runtime::options::BoxliteOptions;
+use boxlite::{BoxOptions, BoxliteError};
+use tempfile::TempDir;
+
+#[test]
+fn test_inspect_network_lists_members() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = boxlite::blocking::BoxliteRuntime::new(config).unwrap();
+
+    let on_backend = || BoxOptions {
+        networks: vec!["backend".to_string()],
+        ..Default::default()
+    };
+    runtime.create(on_backend(), Some("api".into())).unwrap();
+    runtime.create(on_backend(), None).unwrap();
+    runtime
+        .create(BoxOptions::default(), Some("other".into()))
+        .unwrap();
+
+    let info = runtime.inspect_network("backend").unwrap();
+    assert_eq!(info.members.len(), 2);
+    assert_eq!(info.members[0].name.as_deref(), Some("api"));
+    assert_eq!(info.members[0].hostname.as_deref(), Some("api"));
+    // Not running, so not resolvable
+    assert!(info.members.iter().all(|m| m.address.is_none()));
+
+    assert!(matches!(
+        runtime.inspect_network("frontend"),
+        Err(BoxliteError::NotFound(_))
+    ));
+}
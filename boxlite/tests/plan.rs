@@ -0,0 +1,61 @@
+//! Integration tests for planning box creation without creating anything.
+
+use boxlite::runtime::options::BoxliteOptions;
+use boxlite::{BoxOptions, BoxliteError, ImagePlanStatus, ImagePullPolicy, RootfsSpec};
+use tempfile::TempDir;
+
+#[test]
+fn test_plan_creates_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = boxlite::blocking::BoxliteRuntime::new(config).unwrap();
+
+    let plan = runtime
+        .plan(
+            BoxOptions {
+                memory_mib: Some(512),
+                ..Default::default()
+            },
+            Some("planned".into()),
+            false,
+        )
+        .unwrap();
+    assert_eq!(plan.name.as_deref(), Some("planned"));
+    assert_eq!(plan.cpus, 1);
+    assert_eq!(plan.memory_mib, 512);
+    assert_eq!(plan.image_status, ImagePlanStatus::Pull);
+    assert!(plan.image_id.is_none());
+    assert!(runtime.list_info().unwrap().is_empty());
+
+    // Errors create() or start() would hit are reported up front
+    let uncached = BoxOptions {
+        pull_policy: ImagePullPolicy::Never,
+        ..Default::default()
+    };
+    assert!(matches!(
+        runtime.plan(uncached, None, false),
+        Err(BoxliteError::NotFound(_))
+    ));
+    let missing_rootfs = BoxOptions {
+        rootfs: RootfsSpec::RootfsPath("/nonexistent/rootfs".into()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        runtime.plan(missing_rootfs, None, false),
+        Err(BoxliteError::NotFound(_))
+    ));
+
+    let existing = runtime
+        .create(BoxOptions::default(), Some("planned".into()))
+        .unwrap();
+    assert!(matches!(
+        runtime.plan(BoxOptions::default(), Some("planned".into()), false),
+        Err(BoxliteError::InvalidArgument(_))
+    ));
+    runtime.remove(existing.id().as_str(), true).unwrap();
+}
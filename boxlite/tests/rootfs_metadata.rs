@@ -0,0 +1,224 @@
+//! Integration tests for file metadata preservation in prepared root filesystems.
+//!
+//! Images depend on setuid bits, non-root ownership, device nodes and file
+//! capabilities surviving extraction and disk image creation, including when
+//! the runtime is rootless.
+
+use std::path::Path;
+
+use boxlite::BoxCommand;
+use boxlite::BoxliteRuntime;
+use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+
+// ============================================================================
+// TEST FIXTURES
+// ============================================================================
+
+/// Test context with isolated runtime and automatic cleanup.
+struct TestContext {
+    runtime: BoxliteRuntime,
+    _temp_dir: TempDir,
+}
+
+impl TestContext {
+    fn new() -> Self {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let options = BoxliteOptions {
+            home_dir: temp_dir.path().to_path_buf(),
+            image_registries: vec![],
+            ..Default::default()
+        };
+        let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
+        Self {
+            runtime,
+            _temp_dir: temp_dir,
+        }
+    }
+}
+
+/// Run `stat -c <format> <path>` in a fresh alpine box and return the output.
+async fn stat_in_alpine(ctx: &TestContext, format: &str, path: &str) -> String {
+    let handle = ctx
+        .runtime
+        .create(
+            BoxOptions {
+                rootfs: RootfsSpec::Image("alpine:latest".into()),
+                auto_remove: false,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    let mut execution = handle
+        .exec(BoxCommand::new("stat").args(["-c", format, path]))
+        .await
+        .unwrap();
+    let mut stdout = execution.stdout().unwrap();
+
+    let mut output = String::new();
+    while let Some(line) = stdout.next().await {
+        output.push_str(&line);
+    }
+    let result = execution.wait().await.unwrap();
+    assert_eq!(result.exit_code, 0, "stat {} failed", path);
+
+    let _ = ctx.runtime.remove(handle.id().as_str(), true).await;
+    output.trim().to_string()
+}
+
+/// `security.capability` value for `cap_net_raw+ep` (VFS_CAP_REVISION_2).
+const CAP_NET_RAW_EP: [u8; 20] = [
+    0x01, 0x00, 0x00, 0x02, // revision 2, effective
+    0x00, 0x20, 0x00, 0x00, // permitted: CAP_NET_RAW (13)
+    0x00, 0x00, 0x00, 0x00, // inheritable
+    0x00, 0x00, 0x00, 0x00, // permitted (high word)
+    0x00, 0x00, 0x00, 0x00, // inheritable (high word)
+];
+
+/// Write `bytes` as a blob of the OCI layout at `bundle`; returns its digest.
+fn write_blob(bundle: &Path, bytes: &[u8]) -> String {
+    let hex = format!("{:x}", Sha256::digest(bytes));
+    std::fs::write(bundle.join("blobs/sha256").join(&hex), bytes).unwrap();
+    format!("sha256:{}", hex)
+}
+
+/// Build an OCI layout at `bundle` holding alpine plus a layer with
+/// `/usr/local/bin/capped`, which has the file capability `cap_net_raw+ep`.
+async fn alpine_with_capped_file(ctx: &TestContext, bundle: &Path) {
+    std::fs::create_dir_all(bundle.join("blobs/sha256")).unwrap();
+    std::fs::write(
+        bundle.join("oci-layout"),
+        r#"{"imageLayoutVersion":"1.0.0"}"#,
+    )
+    .unwrap();
+
+    let alpine = ctx.runtime.pull_image("alpine:latest").await.unwrap();
+    let mut layers = Vec::new();
+    for tarball in alpine.layer_tarballs() {
+        let bytes = std::fs::read(tarball).unwrap();
+        layers.push(serde_json::json!({
+            "mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+            "digest": write_blob(bundle, &bytes),
+            "size": bytes.len(),
+        }));
+    }
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder
+        .append_pax_extensions([("SCHILY.xattr.security.capability", &CAP_NET_RAW_EP[..])])
+        .unwrap();
+    let mut header = tar::Header::new_ustar();
+    header.set_path("usr/local/bin/capped").unwrap();
+    header.set_size(0);
+    header.set_mode(0o755);
+    header.set_cksum();
+    builder.append(&header, std::io::empty()).unwrap();
+    let layer = builder.into_inner().unwrap();
+    let layer_digest = write_blob(bundle, &layer);
+    layers.push(serde_json::json!({
+        "mediaType": "application/vnd.oci.image.layer.v1.tar",
+        "digest": layer_digest,
+        "size": layer.len(),
+    }));
+
+    let mut config = serde_json::to_value(alpine.load_config().await.unwrap()).unwrap();
+    config["rootfs"]["diff_ids"]
+        .as_array_mut()
+        .unwrap()
+        .push(layer_digest.into());
+    let config = serde_json::to_vec(&config).unwrap();
+
+    let manifest = serde_json::to_vec(&serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "config": {
+            "mediaType": "application/vnd.oci.image.config.v1+json",
+            "digest": write_blob(bundle, &config),
+            "size": config.len(),
+        },
+        "layers": layers,
+    }))
+    .unwrap();
+    let index = serde_json::json!({
+        "schemaVersion": 2,
+        "manifests": [{
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "digest": write_blob(bundle, &manifest),
+            "size": manifest.len(),
+        }],
+    });
+    std::fs::write(bundle.join("index.json"), index.to_string()).unwrap();
+}
+
+// ============================================================================
+// METADATA TESTS
+// ============================================================================
+
+/// alpine ships /bin/bbsuid as setuid root (mode 4111).
+#[tokio::test]
+async fn test_setuid_bit_preserved() {
+    let ctx = TestContext::new();
+    assert_eq!(stat_in_alpine(&ctx, "%a %u", "/bin/bbsuid").await, "4111 0");
+}
+
+/// /etc/shadow belongs to the shadow group, not root.
+#[tokio::test]
+async fn test_non_root_group_preserved() {
+    let ctx = TestContext::new();
+    assert_eq!(
+        stat_in_alpine(&ctx, "%a %G", "/etc/shadow").await,
+        "640 shadow"
+    );
+}
+
+/// File capabilities (e.g. ping's cap_net_raw) are kept in the disk rootfs.
+#[tokio::test]
+async fn test_file_capability_preserved() {
+    let ctx = TestContext::new();
+    let bundle = TempDir::new().unwrap();
+    alpine_with_capped_file(&ctx, bundle.path()).await;
+
+    let handle = ctx
+        .runtime
+        .create(
+            BoxOptions {
+                rootfs: RootfsSpec::RootfsPath(bundle.path().to_str().unwrap().into()),
+                auto_remove: false,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    let script = "apk add -q attr >/dev/null && \
+                  getfattr -e hex -n security.capability /usr/local/bin/capped";
+    let output = handle
+        .exec(BoxCommand::new("sh").args(["-c", script]))
+        .await
+        .unwrap()
+        .collect_output()
+        .await
+        .unwrap();
+    assert_eq!(output.result.exit_code, 0, "{}", output.stderr);
+    let expected: String = CAP_NET_RAW_EP
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    assert!(
+        output
+            .stdout
+            .contains(&format!("security.capability=0x{}", expected)),
+        "{}",
+        output.stdout
+    );
+
+    let _ = ctx.runtime.remove(handle.id().as_str(), true).await;
+}
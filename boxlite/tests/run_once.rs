@@ -0,0 +1,31 @@
+//! Integration tests for one-shot boxes (`run_once`).
+
+use boxlite::runtime::options::BoxliteOptions;
+use boxlite::{BoxCommand, BoxOptions, RootfsSpec};
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[test]
+fn test_run_once_removes_box_on_failure() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = boxlite::blocking::BoxliteRuntime::new(config).unwrap();
+    let options = BoxOptions {
+        rootfs: RootfsSpec::RootfsPath("/nonexistent/rootfs".into()),
+        auto_remove: false,
+        ..Default::default()
+    };
+
+    let result = runtime.run_once(
+        options,
+        BoxCommand::new("true"),
+        Some(Duration::from_secs(60)),
+    );
+    assert!(result.is_err());
+    assert!(runtime.list_info().unwrap().is_empty());
+}
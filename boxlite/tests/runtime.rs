@@ -1,7 +1,8 @@
-//! Integration tests for runtime initialization and locking behavior.
+//! Integration tests for runtime initialization and locking, the async and
+//! blocking facades, and runtime-wide settings (box defaults, log level).
 
-use boxlite::BoxliteRuntime;
 use boxlite::runtime::options::BoxliteOptions;
+use boxlite::{AsyncRuntime, BoxliteRuntime};
 use std::thread;
 use std::time::Duration;
 use tempfile::TempDir;
@@ -14,6 +15,7 @@ fn test_runtime_prevents_concurrent_access() {
     let config1 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let runtime1 = BoxliteRuntime::new(config1).unwrap();
 
@@ -21,6 +23,7 @@ fn test_runtime_prevents_concurrent_access() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let result = BoxliteRuntime::new(config2);
     assert!(result.is_err());
@@ -36,6 +39,7 @@ fn test_runtime_prevents_concurrent_access() {
     let config3 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime2 = BoxliteRuntime::new(config3).unwrap();
 }
@@ -49,6 +53,7 @@ fn test_runtime_lock_released_on_drop() {
         let config = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let _runtime = BoxliteRuntime::new(config).unwrap();
     } // Lock released here
@@ -57,6 +62,7 @@ fn test_runtime_lock_released_on_drop() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime2 = BoxliteRuntime::new(config2).unwrap();
 }
@@ -70,6 +76,7 @@ fn test_runtime_lock_across_threads() {
     let config1 = BoxliteOptions {
         home_dir: dir_path.clone(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime1 = BoxliteRuntime::new(config1).unwrap();
 
@@ -79,6 +86,7 @@ fn test_runtime_lock_across_threads() {
         let config = BoxliteOptions {
             home_dir: dir_clone,
             image_registries: vec![],
+            ..Default::default()
         };
         BoxliteRuntime::new(config)
     });
@@ -96,6 +104,7 @@ fn test_different_home_dirs_independent() {
     let config1 = BoxliteOptions {
         home_dir: temp_dir1.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime1 = BoxliteRuntime::new(config1).unwrap();
 
@@ -103,6 +112,7 @@ fn test_different_home_dirs_independent() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir2.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime2 = BoxliteRuntime::new(config2).unwrap();
 
@@ -118,6 +128,7 @@ fn test_lock_file_created() {
     let config = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime = BoxliteRuntime::new(config).unwrap();
 
@@ -133,6 +144,7 @@ fn test_lock_survives_short_operations() {
     let config1 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let runtime = BoxliteRuntime::new(config1).unwrap();
 
@@ -143,9 +155,120 @@ fn test_lock_survives_short_operations() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let result = BoxliteRuntime::new(config2);
     assert!(result.is_err());
 
     drop(runtime);
 }
+
+#[test]
+fn test_async_runtime_outside_tokio() {
+    let temp_dir = TempDir::new().unwrap();
+    let tokio_rt = tokio::runtime::Runtime::new().unwrap();
+
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = AsyncRuntime::new(config, tokio_rt.handle().clone()).unwrap();
+
+    // Awaited from an executor that isn't Tokio
+    futures::executor::block_on(async {
+        assert!(runtime.list_info().await.unwrap().is_empty());
+        assert!(runtime.get("missing").await.unwrap().is_none());
+        let exists = runtime
+            .run(|rt| async move { rt.exists("missing").await })
+            .await
+            .unwrap();
+        assert!(!exists);
+        assert!(runtime.shutdown(None).await.unwrap().boxes.is_empty());
+    });
+}
+
+#[test]
+fn test_blocking_runtime() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = boxlite::blocking::BoxliteRuntime::new(config).unwrap();
+
+    assert!(runtime.list_info().unwrap().is_empty());
+    assert!(runtime.get("missing").unwrap().is_none());
+    assert!(!runtime.exists("missing").unwrap());
+    assert!(!runtime.block_on(runtime.inner().exists("missing")).unwrap());
+    assert!(runtime.shutdown(None).unwrap().boxes.is_empty());
+}
+
+#[test]
+fn test_runtime_box_defaults() {
+    use boxlite::{BoxOptions, ImagePullPolicy, SecurityOptions, SecurityPreset};
+
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        default_security: Some(SecurityPreset::Maximum),
+        default_pull_policy: Some(ImagePullPolicy::Always),
+        ..Default::default()
+    };
+    let runtime = boxlite::blocking::BoxliteRuntime::new(config).unwrap();
+
+    // Options left at their defaults take the runtime's
+    let defaulted = runtime.create(BoxOptions::default(), None).unwrap();
+    assert_eq!(defaulted.options().security, SecurityOptions::maximum());
+    assert_eq!(defaulted.options().pull_policy, ImagePullPolicy::Always);
+
+    // Options a box sets itself are kept
+    let explicit = runtime
+        .create(
+            BoxOptions {
+                security: SecurityOptions::development(),
+                pull_policy: ImagePullPolicy::Never,
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+    assert_eq!(explicit.options().security, SecurityOptions::development());
+    assert_eq!(explicit.options().pull_policy, ImagePullPolicy::Never);
+
+    runtime.remove(defaulted.id().as_str(), true).unwrap();
+    runtime.remove(explicit.id().as_str(), true).unwrap();
+}
+
+#[test]
+fn test_runtime_set_log_level() {
+    use boxlite::BoxliteError;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = BoxliteOptions {
+        home_dir: temp_dir.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    };
+    let runtime = BoxliteRuntime::new(config).unwrap();
+
+    // No other subscriber is installed in this test binary
+    runtime
+        .set_log_level("debug,boxlite::portal=trace")
+        .unwrap();
+    runtime.set_log_level("info").unwrap();
+    assert!(matches!(
+        runtime.set_log_level("boxlite=loud"),
+        Err(BoxliteError::InvalidArgument(_))
+    ));
+
+    // Another process can only signal a default runtime, not this one
+    assert!(matches!(
+        boxlite::runtime::log_level::request_log_level(temp_dir.path(), "debug"),
+        Err(BoxliteError::NotFound(_))
+    ));
+}
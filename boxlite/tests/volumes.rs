@@ -0,0 +1,155 @@
+//! Integration tests for volume mount options.
+//!
+//! Options are applied to the container's bind mount in the guest, so they
+//! are only observable from inside a running box.
+
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+use boxlite::BoxliteRuntime;
+use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec, VolumeSpec};
+use boxlite::{BoxCommand, LiteBox, VolumeSpecBuilder};
+use futures::StreamExt;
+use tempfile::TempDir;
+
+// ============================================================================
+// TEST FIXTURES
+// ============================================================================
+
+/// Test context with isolated runtime and automatic cleanup.
+struct TestContext {
+    runtime: BoxliteRuntime,
+    _temp_dir: TempDir,
+}
+
+impl TestContext {
+    fn new() -> Self {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let options = BoxliteOptions {
+            home_dir: temp_dir.path().to_path_buf(),
+            image_registries: vec![],
+            ..Default::default()
+        };
+        let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
+        Self {
+            runtime,
+            _temp_dir: temp_dir,
+        }
+    }
+
+    /// Start an alpine box with `volume` mounted.
+    async fn start_with(&self, volume: VolumeSpec) -> LiteBox {
+        let handle = self
+            .runtime
+            .create(
+                BoxOptions {
+                    rootfs: RootfsSpec::Image("alpine:latest".into()),
+                    volumes: vec![volume],
+                    auto_remove: false,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        handle.start().await.unwrap();
+        handle
+    }
+
+    async fn remove(&self, handle: &LiteBox) {
+        let _ = self.runtime.remove(handle.id().as_str(), true).await;
+    }
+}
+
+/// Run `sh -c script` in the box and return its exit code and stdout.
+async fn sh(handle: &LiteBox, script: &str) -> (i32, String) {
+    let mut execution = handle
+        .exec(BoxCommand::new("sh").args(["-c", script]))
+        .await
+        .unwrap();
+    let mut stdout = execution.stdout().unwrap();
+
+    let mut output = String::new();
+    while let Some(line) = stdout.next().await {
+        output.push_str(&line);
+    }
+    let result = execution.wait().await.unwrap();
+    (result.exit_code, output.trim().to_string())
+}
+
+// ============================================================================
+// MOUNT OPTION TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn test_read_only_volume_rejects_writes() {
+    let ctx = TestContext::new();
+    let dir = TempDir::new().unwrap();
+    let volume = VolumeSpecBuilder::new(dir.path().to_str().unwrap(), "/data")
+        .option("ro")
+        .build()
+        .unwrap();
+    let handle = ctx.start_with(volume).await;
+
+    let (code, _) = sh(&handle, "touch /data/file").await;
+    assert_ne!(code, 0, "writing to a read-only volume succeeded");
+    assert!(!dir.path().join("file").exists());
+
+    ctx.remove(&handle).await;
+}
+
+#[tokio::test]
+async fn test_noexec_volume_refuses_to_run_programs() {
+    let ctx = TestContext::new();
+    let dir = TempDir::new().unwrap();
+    let script = dir.path().join("hello.sh");
+    std::fs::write(&script, "#!/bin/sh\necho hello\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let volume = VolumeSpecBuilder::new(dir.path().to_str().unwrap(), "/data")
+        .option("noexec")
+        .build()
+        .unwrap();
+    let handle = ctx.start_with(volume).await;
+
+    let (code, _) = sh(&handle, "/data/hello.sh").await;
+    assert_eq!(code, 126, "a program on a noexec volume ran");
+    // Reading still works
+    let (code, output) = sh(&handle, "cat /data/hello.sh").await;
+    assert_eq!(code, 0);
+    assert!(output.contains("echo hello"));
+
+    ctx.remove(&handle).await;
+}
+
+/// `uid=<n>-<m>` maps the directory owner's id to `n` and the ids after it
+/// to the rest of the range.
+#[tokio::test]
+async fn test_uid_range_maps_owner_and_following_ids() {
+    let ctx = TestContext::new();
+    let dir = TempDir::new().unwrap();
+    let owner = std::fs::metadata(dir.path()).unwrap();
+    std::fs::write(dir.path().join("owned"), "").unwrap();
+    // Giving a file away needs root; check the rest of the range only then
+    let shifted = dir.path().join("shifted");
+    std::fs::write(&shifted, "").unwrap();
+    let chowned =
+        std::os::unix::fs::chown(&shifted, Some(owner.uid() + 5), Some(owner.gid() + 5)).is_ok();
+
+    let volume = VolumeSpecBuilder::new(dir.path().to_str().unwrap(), "/data")
+        .option("uid=2000-2009")
+        .option("gid=3000-3009")
+        .build()
+        .unwrap();
+    let handle = ctx.start_with(volume).await;
+
+    let (code, output) = sh(&handle, "stat -c '%u %g' /data/owned").await;
+    assert_eq!(code, 0);
+    assert_eq!(output, "2000 3000");
+    if chowned {
+        let (code, output) = sh(&handle, "stat -c '%u %g' /data/shifted").await;
+        assert_eq!(code, 0);
+        assert_eq!(output, "2005 3005");
+    }
+
+    ctx.remove(&handle).await;
+}
@@ -74,63 +74,3 @@ pub fn all_capabilities() -> HashSet<Capability> {
     .into_iter()
     .collect()
 }
-
-/// Convert capabilities to string names for libcontainer API
-///
-/// Returns capability names in "CAP_NAME" format (e.g., "CAP_CHOWN").
-pub fn capability_names() -> Vec<String> {
-    vec![
-        // File operations (CAP 0-4)
-        "CAP_CHOWN".to_string(),
-        "CAP_DAC_OVERRIDE".to_string(),
-        "CAP_DAC_READ_SEARCH".to_string(),
-        "CAP_FOWNER".to_string(),
-        "CAP_FSETID".to_string(),
-        // Process capabilities (CAP 5-9)
-        "CAP_KILL".to_string(),
-        "CAP_SETGID".to_string(),
-        "CAP_SETUID".to_string(),
-        "CAP_SETPCAP".to_string(),
-        "CAP_LINUX_IMMUTABLE".to_string(),
-        // Network capabilities (CAP 10-13)
-        "CAP_NET_BIND_SERVICE".to_string(),
-        "CAP_NET_BROADCAST".to_string(),
-        "CAP_NET_ADMIN".to_string(),
-        "CAP_NET_RAW".to_string(),
-        // IPC capabilities (CAP 14-15)
-        "CAP_IPC_LOCK".to_string(),
-        "CAP_IPC_OWNER".to_string(),
-        // System operations (CAP 16-26)
-        "CAP_SYS_MODULE".to_string(),
-        "CAP_SYS_RAWIO".to_string(),
-        "CAP_SYS_CHROOT".to_string(),
-        "CAP_SYS_PTRACE".to_string(),
-        "CAP_SYS_PACCT".to_string(),
-        "CAP_SYS_ADMIN".to_string(),
-        "CAP_SYS_BOOT".to_string(),
-        "CAP_SYS_NICE".to_string(),
-        "CAP_SYS_RESOURCE".to_string(),
-        "CAP_SYS_TIME".to_string(),
-        "CAP_SYS_TTY_CONFIG".to_string(),
-        // Device operations (CAP 27)
-        "CAP_MKNOD".to_string(),
-        // File leases (CAP 28)
-        "CAP_LEASE".to_string(),
-        // Audit capabilities (CAP 29-30)
-        "CAP_AUDIT_WRITE".to_string(),
-        "CAP_AUDIT_CONTROL".to_string(),
-        // Filesystem capabilities (CAP 31)
-        "CAP_SETFCAP".to_string(),
-        // MAC (CAP 32-33)
-        "CAP_MAC_OVERRIDE".to_string(),
-        "CAP_MAC_ADMIN".to_string(),
-        // Modern capabilities (CAP 34-40)
-        "CAP_SYSLOG".to_string(),
-        "CAP_WAKE_ALARM".to_string(),
-        "CAP_BLOCK_SUSPEND".to_string(),
-        "CAP_AUDIT_READ".to_string(),
-        "CAP_PERFMON".to_string(),
-        "CAP_BPF".to_string(),
-        "CAP_CHECKPOINT_RESTORE".to_string(),
-    ]
-}
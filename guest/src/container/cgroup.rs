@@ -0,0 +1,191 @@
+//! Cgroup v2 resource limits for the container
+//!
+//! Cgroups are off by default (mounting cgroup2 costs ~105ms at startup, see
+//! spec.rs). They're only set up when the host asks for CPU/memory limits, so
+//! a busy container can't starve the guest agent of the VM's resources.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use nix::mount::{mount, MsFlags};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Guest cgroup2 mount point
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// CPU/memory limits for the container cgroup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// memory.max in bytes
+    pub memory_max_bytes: Option<u64>,
+    /// CPU time allowed per period, in microseconds
+    pub cpu_quota_us: Option<u64>,
+    /// CPU accounting period in microseconds
+    pub cpu_period_us: u64,
+}
+
+/// Usage and throttling counters read from the container cgroup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CgroupStats {
+    pub cpu_usage_usec: u64,
+    pub cpu_nr_periods: u64,
+    pub cpu_nr_throttled: u64,
+    pub cpu_throttled_usec: u64,
+    pub memory_current_bytes: u64,
+    /// memory.current minus inactive file pages (what reclaim can't easily free)
+    pub memory_working_set_bytes: u64,
+    /// Page cache (`file` in memory.stat)
+    pub memory_cache_bytes: u64,
+    /// Times usage hit memory.max and reclaim was forced
+    pub memory_max_events: u64,
+    pub memory_oom_kills: u64,
+    /// None if the pids controller isn't enabled
+    pub pids_current: Option<u64>,
+    /// Summed over devices; None if the io controller isn't enabled
+    pub io_read_bytes: Option<u64>,
+    pub io_write_bytes: Option<u64>,
+}
+
+/// Cgroup path of a container, relative to the cgroup2 root
+pub fn cgroups_path(container_id: &str) -> String {
+    format!("/boxlite/{}", container_id)
+}
+
+fn cgroup_dir(container_id: &str) -> PathBuf {
+    Path::new(CGROUP_ROOT).join(cgroups_path(container_id).trim_start_matches('/'))
+}
+
+/// Mount cgroup2 at /sys/fs/cgroup unless it's already mounted.
+pub fn ensure_cgroup2_mounted() -> BoxliteResult<()> {
+    let mounted = std::fs::read_to_string("/proc/mounts")
+        .map(|mounts| {
+            mounts.lines().any(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                parts.len() >= 3 && parts[1] == CGROUP_ROOT && parts[2] == "cgroup2"
+            })
+        })
+        .unwrap_or(false);
+    if mounted {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(CGROUP_ROOT)
+        .map_err(|e| BoxliteError::Internal(format!("Failed to create {}: {}", CGROUP_ROOT, e)))?;
+    mount(
+        Some("cgroup2"),
+        CGROUP_ROOT,
+        Some("cgroup2"),
+        MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC,
+        None::<&str>,
+    )
+    .map_err(|e| BoxliteError::Internal(format!("Failed to mount cgroup2: {}", e)))?;
+
+    tracing::info!("Mounted cgroup2 on {}", CGROUP_ROOT);
+    Ok(())
+}
+
+/// Read usage and throttling counters for a container's cgroup.
+pub fn read_stats(container_id: &str) -> BoxliteResult<CgroupStats> {
+    let dir = cgroup_dir(container_id);
+    let read = |name: &str| {
+        std::fs::read_to_string(dir.join(name)).map_err(|e| {
+            BoxliteError::Internal(format!(
+                "Failed to read {}: {}",
+                dir.join(name).display(),
+                e
+            ))
+        })
+    };
+
+    let cpu = read("cpu.stat")?;
+    let cpu = parse_flat_keyed(&cpu);
+    let memory_events = read("memory.events")?;
+    let memory_events = parse_flat_keyed(&memory_events);
+    let memory_current = read("memory.current")?.trim().parse().unwrap_or(0);
+    let memory_stat = read("memory.stat")?;
+    let memory_stat = parse_flat_keyed(&memory_stat);
+    // No limit needs the pids or io controller, so their files may be missing
+    let pids_current = read("pids.current")
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+    let io = read("io.stat").ok().map(|s| parse_io_stat(&s));
+
+    let get = |map: &HashMap<&str, u64>, key: &str| map.get(key).copied().unwrap_or(0);
+    Ok(CgroupStats {
+        cpu_usage_usec: get(&cpu, "usage_usec"),
+        cpu_nr_periods: get(&cpu, "nr_periods"),
+        cpu_nr_throttled: get(&cpu, "nr_throttled"),
+        cpu_throttled_usec: get(&cpu, "throttled_usec"),
+        memory_current_bytes: memory_current,
+        memory_working_set_bytes: memory_current.saturating_sub(get(&memory_stat, "inactive_file")),
+        memory_cache_bytes: get(&memory_stat, "file"),
+        memory_max_events: get(&memory_events, "max"),
+        memory_oom_kills: get(&memory_events, "oom_kill"),
+        pids_current,
+        io_read_bytes: io.map(|(read, _)| read),
+        io_write_bytes: io.map(|(_, write)| write),
+    })
+}
+
+/// Parse a cgroup v2 flat-keyed file ("key value" per line).
+fn parse_flat_keyed(content: &str) -> HashMap<&str, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key, value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Sum `rbytes` and `wbytes` over the devices of an io.stat file
+/// ("MAJ:MIN rbytes=N wbytes=N rios=N ..." per line).
+fn parse_io_stat(content: &str) -> (u64, u64) {
+    let mut read = 0u64;
+    let mut write = 0u64;
+    for field in content
+        .lines()
+        .flat_map(|line| line.split_whitespace().skip(1))
+    {
+        let Some((key, value)) = field.split_once('=') else {
+            continue;
+        };
+        let value: u64 = value.parse().unwrap_or(0);
+        match key {
+            "rbytes" => read = read.saturating_add(value),
+            "wbytes" => write = write.saturating_add(value),
+            _ => {}
+        }
+    }
+    (read, write)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flat_keyed() {
+        let content = "usage_usec 1200\nnr_periods 10\nnr_throttled 3\nthrottled_usec 450\n";
+        let map = parse_flat_keyed(content);
+        assert_eq!(map.get("usage_usec"), Some(&1200));
+        assert_eq!(map.get("nr_throttled"), Some(&3));
+        assert_eq!(map.get("throttled_usec"), Some(&450));
+        assert_eq!(map.get("missing"), None);
+    }
+
+    #[test]
+    fn test_parse_io_stat() {
+        let content = "253:0 rbytes=4096 wbytes=1000 rios=1 wios=2 dbytes=0 dios=0\n\
+                       253:16 rbytes=100 wbytes=24 rios=3 wios=4 dbytes=0 dios=0\n";
+        assert_eq!(parse_io_stat(content), (4196, 1024));
+        assert_eq!(parse_io_stat(""), (0, 0));
+    }
+
+    #[test]
+    fn test_cgroup_dir() {
+        assert_eq!(
+            cgroup_dir("abc"),
+            PathBuf::from("/sys/fs/cgroup/boxlite/abc")
+        );
+    }
+}
@@ -3,14 +3,15 @@
 //! Provides a builder pattern for spawning processes inside containers,
 //! following the `std::process::Command` pattern.
 
-use super::capabilities::capability_names;
+use super::spec::{build_exec_process_spec, Ulimit};
+use crate::fd_shim::FdShim;
 use crate::service::exec::exec_handle::{ExecHandle, PtyConfig};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use libcontainer::container::builder::ContainerBuilder;
 use libcontainer::syscall::syscall::SyscallType;
 use nix::unistd::Pid;
 use std::collections::HashMap;
-use std::os::unix::io::OwnedFd;
+use std::os::unix::io::{OwnedFd, RawFd};
 use std::path::PathBuf;
 
 /// Command builder
@@ -52,6 +53,9 @@ pub struct ContainerCommand {
     /// Resolved (uid, gid) from container init, propagated to exec processes.
     user: (u32, u32),
 
+    /// Container resource limits, set in the process spec.
+    ulimits: Vec<Ulimit>,
+
     /// Working directory (None = use default "/")
     cwd: Option<String>,
 
@@ -60,6 +64,9 @@ pub struct ContainerCommand {
 
     /// PTY configuration (set via with_pty())
     pty_config: Option<PtyConfig>,
+
+    /// Passed fds as (fd in the agent, fd in the process) (set via with_passed_fds())
+    passed_fds: Vec<(RawFd, RawFd)>,
 }
 
 impl ContainerCommand {
@@ -72,15 +79,18 @@ impl ContainerCommand {
         state_root: PathBuf,
         env: HashMap<String, String>,
         user: (u32, u32),
+        ulimits: Vec<Ulimit>,
     ) -> Self {
         Self {
             program: None,
             args: Vec::new(),
             env,
             user,
+            ulimits,
             cwd: None,
             console_socket: None,
             pty_config: None,
+            passed_fds: Vec::new(),
             id,
             state_root,
         }
@@ -96,6 +106,14 @@ impl ContainerCommand {
         self
     }
 
+    /// Give the process extra fds, as (fd in the agent, fd in the process)
+    ///
+    /// The process is started through [`crate::fd_shim`], which places them.
+    pub fn with_passed_fds(mut self, mapping: Vec<(RawFd, RawFd)>) -> Self {
+        self.passed_fds = mapping;
+        self
+    }
+
     /// Set the program to execute
     ///
     /// # Example
@@ -307,6 +325,18 @@ impl ContainerCommand {
         let mut container_args = vec![program.clone()];
         container_args.extend_from_slice(self.args.as_slice());
 
+        // libcontainer can't place fds at chosen numbers; the shim does.
+        // Dropped (closing its inheritable copies) once the process is spawned.
+        let fd_shim = if self.passed_fds.is_empty() {
+            None
+        } else {
+            let shim = FdShim::prepare(&self.passed_fds).map_err(|e| {
+                BoxliteError::Internal(format!("Failed to prepare passed fds: {}", e))
+            })?;
+            container_args = shim.args(&container_args);
+            Some(shim)
+        };
+
         // Build container
         let mut builder = ContainerBuilder::new(self.id.to_string(), SyscallType::default())
             .with_root_path(self.state_root.clone())
@@ -317,6 +347,10 @@ impl ContainerCommand {
             .validate_id()
             .map_err(|e| BoxliteError::Internal(format!("Invalid container ID: {}", e)))?;
 
+        if let Some(shim) = &fd_shim {
+            builder = builder.with_preserved_fds(shim.preserved_fds());
+        }
+
         // Add pipes if provided
         if let Some((stdin, stdout, stderr)) = pipes {
             builder = builder
@@ -353,46 +387,70 @@ impl ContainerCommand {
             );
         }
 
+        // libcontainer only sets a tenant's rlimits from a process spec, so
+        // the whole process is passed as one
         let (uid, gid) = self.user;
-
-        let pid = builder
+        let env: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        let process = build_exec_process_spec(
+            &container_args,
+            &env,
+            self.cwd.as_deref().unwrap_or("/"),
+            uid,
+            gid,
+            &self.ulimits,
+        )?;
+        let process_path = self
+            .state_root
+            .join(&self.id)
+            .join(format!("exec-{}.json", uuid::Uuid::new_v4()));
+        let process_json = serde_json::to_vec(&process).map_err(|e| {
+            BoxliteError::Internal(format!("Failed to serialize process spec: {}", e))
+        })?;
+        std::fs::write(&process_path, process_json).map_err(|e| {
+            BoxliteError::Internal(format!(
+                "Failed to write process spec {}: {}",
+                process_path.display(),
+                e
+            ))
+        })?;
+
+        let result = builder
             .as_tenant()
-            .with_capabilities(capability_names())
-            .with_no_new_privs(false)
+            .with_process(Some(&process_path))
             .with_detach(false)
-            .with_cwd(self.cwd.clone().or(Some("/".parse().unwrap())))
-            .with_env(self.env.clone())
-            .with_container_args(container_args.clone())
-            .with_user(Some(uid))
-            .with_group(Some(gid))
-            .build()
-            .map_err(|e| {
+            .build();
+        let _ = std::fs::remove_file(&process_path);
+        let pid = result.map_err(|e| {
+            tracing::error!(
+                container_id = %self.id,
+                program = %program,
+                args = ?container_args,
+                error = %e,
+                state_root = %self.state_root.display(),
+                "Libcontainer build() failed - likely container status issue"
+            );
+
+            // Try to get container status after failure
+            let container_state_path = self.state_root.join(&self.id);
+            if let Ok(container) =
+                libcontainer::container::Container::load(container_state_path.clone())
+            {
                 tracing::error!(
                     container_id = %self.id,
-                    program = %program,
-                    args = ?container_args,
-                    error = %e,
-                    state_root = %self.state_root.display(),
-                    "Libcontainer build() failed - likely container status issue"
+                    status = ?container.status(),
+                    "Container status after exec failure"
                 );
+            }
 
-                // Try to get container status after failure
-                let container_state_path = self.state_root.join(&self.id);
-                if let Ok(container) =
-                    libcontainer::container::Container::load(container_state_path.clone())
-                {
-                    tracing::error!(
-                        container_id = %self.id,
-                        status = ?container.status(),
-                        "Container status after exec failure"
-                    );
-                }
-
-                BoxliteError::Internal(format!(
-                    "Failed to spawn '{}' with args {:?}: {}",
-                    program, container_args, e
-                ))
-            })?;
+            BoxliteError::Internal(format!(
+                "Failed to spawn '{}' with args {:?}: {}",
+                program, container_args, e
+            ))
+        })?;
 
         tracing::debug!(
             container_id = %self.id,
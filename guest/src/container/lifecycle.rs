@@ -3,8 +3,9 @@
 //! Provides container creation, startup, and status checking using libcontainer.
 //! Follows the OCI Runtime Specification.
 
+use super::cgroup::{self, CgroupStats, ResourceLimits};
 use super::command::ContainerCommand;
-use super::spec::UserMount;
+use super::spec::{ContainerHosts, Ulimit, UserMount};
 use super::stdio::ContainerStdio;
 use super::{kill, spec, start};
 use crate::layout::GuestLayout;
@@ -15,6 +16,31 @@ use libcontainer::signal::Signal;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// What [`Container::start`] creates the container from.
+#[derive(Debug)]
+pub struct ContainerStartConfig {
+    /// Path to container root filesystem
+    pub rootfs: PathBuf,
+    /// Command and arguments for container init process
+    pub entrypoint: Vec<String>,
+    /// Environment variables in "KEY=VALUE" format
+    pub env: Vec<String>,
+    /// Working directory inside container
+    pub workdir: PathBuf,
+    /// Image USER directive (name, uid or uid:gid)
+    pub user: String,
+    /// Hostname, extra `/etc/hosts` entries and DNS server
+    pub hosts: ContainerHosts,
+    /// Size of the container's `/dev/shm`
+    pub shm_size_mib: u32,
+    /// Resource limits for the init process and executions
+    pub ulimits: Vec<Ulimit>,
+    /// Bind mounts from guest VM paths into container
+    pub user_mounts: Vec<UserMount>,
+    /// CPU/memory limits; mounts cgroup2 and gives the container its own cgroup
+    pub resources: Option<ResourceLimits>,
+}
+
 /// OCI container
 ///
 /// Manages the lifecycle of an OCI-compliant container using libcontainer.
@@ -23,15 +49,27 @@ use std::path::{Path, PathBuf};
 /// # Example
 ///
 /// ```no_run
-/// # use guest::container::Container;
+/// # use guest::container::{Container, ContainerHosts, ContainerStartConfig};
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// // Create and start container
 /// let container = Container::start(
 ///     "my-container",
-///     "/rootfs",
-///     vec!["sh".to_string()],
-///     vec!["PATH=/bin:/usr/bin".to_string()],
-///     "/",
+///     ContainerStartConfig {
+///         rootfs: "/rootfs".into(),
+///         entrypoint: vec!["sh".to_string()],
+///         env: vec!["PATH=/bin:/usr/bin".to_string()],
+///         workdir: "/".into(),
+///         user: String::new(),
+///         hosts: ContainerHosts {
+///             hostname: "boxlite".to_string(),
+///             extra_hosts: Vec::new(),
+///             nameserver: "192.168.127.1".to_string(),
+///         },
+///         shm_size_mib: 64,
+///         ulimits: Vec::new(),
+///         user_mounts: Vec::new(),
+///         resources: None,
+///     },
 /// )?;
 ///
 /// // Execute command
@@ -48,6 +86,10 @@ pub struct Container {
     env: HashMap<String, String>,
     /// Resolved (uid, gid) from image USER directive, propagated to exec commands.
     user: (u32, u32),
+    /// Resource limits, also set on exec commands.
+    ulimits: Vec<Ulimit>,
+    /// Cgroup limits, if the container runs in its own cgroup.
+    resources: Option<ResourceLimits>,
     /// Stdio pipes that keep init process alive.
     /// Dropping this closes pipes → init gets EOF → init exits.
     #[allow(dead_code)]
@@ -70,11 +112,7 @@ impl Container {
     /// # Arguments
     ///
     /// - `container_id`: Unique container identifier
-    /// - `rootfs`: Path to container root filesystem
-    /// - `entrypoint`: Command and arguments for container init process
-    /// - `env`: Environment variables in "KEY=VALUE" format
-    /// - `workdir`: Working directory inside container
-    /// - `user_mounts`: Bind mounts from guest VM paths into container
+    /// - `config`: Rootfs, init process, hosts, mounts and limits
     ///
     /// # Errors
     ///
@@ -82,17 +120,21 @@ impl Container {
     /// - Failed to create container directory
     /// - Failed to create or start container
     /// - Init process exited immediately
-    pub fn start(
-        container_id: &str,
-        rootfs: impl AsRef<Path>,
-        entrypoint: Vec<String>,
-        env: Vec<String>,
-        workdir: impl AsRef<Path>,
-        user: &str,
-        user_mounts: Vec<UserMount>,
-    ) -> BoxliteResult<Self> {
-        let rootfs = rootfs.as_ref();
-        let workdir = workdir.as_ref();
+    pub fn start(container_id: &str, config: ContainerStartConfig) -> BoxliteResult<Self> {
+        let ContainerStartConfig {
+            rootfs,
+            entrypoint,
+            env,
+            workdir,
+            user,
+            hosts,
+            shm_size_mib,
+            ulimits,
+            user_mounts,
+            resources,
+        } = config;
+        let rootfs = rootfs.as_path();
+        let workdir = workdir.as_path();
 
         // Use GuestLayout for all paths (per-container directories)
         let layout = GuestLayout::new();
@@ -118,7 +160,7 @@ impl Container {
         let rootfs_str = rootfs
             .to_str()
             .ok_or_else(|| BoxliteError::Internal("Invalid rootfs path".to_string()))?;
-        let (uid, gid) = spec::resolve_user(rootfs_str, user)?;
+        let (uid, gid) = spec::resolve_user(rootfs_str, &user)?;
 
         // Create OCI bundle at /run/boxlite/containers/{cid}/
         // create_oci_bundle creates bundle_root/{cid}/, so pass containers_dir
@@ -131,9 +173,18 @@ impl Container {
             uid,
             gid,
             &layout.containers_dir(),
+            &hosts,
+            shm_size_mib,
+            &ulimits,
             &user_mounts,
+            resources.as_ref(),
         )?;
 
+        // libcontainer creates the container cgroup under the guest's cgroup2 root
+        if resources.is_some() {
+            cgroup::ensure_cgroup2_mounted()?;
+        }
+
         // Create stdio pipes before container creation.
         // These keep the init process alive by holding stdin open.
         let (stdio, init_fds) = ContainerStdio::new()?;
@@ -148,6 +199,8 @@ impl Container {
             bundle_path,
             env: env_map,
             user: (uid, gid),
+            ulimits,
+            resources,
             stdio,
             is_shutdown: std::sync::atomic::AtomicBool::new(false),
         })
@@ -209,6 +262,39 @@ impl Container {
         &self.id
     }
 
+    /// Read cgroup usage and throttling counters.
+    ///
+    /// Returns `None` if the container was started without resource limits
+    /// (no cgroup of its own).
+    pub fn cgroup_stats(&self) -> BoxliteResult<Option<CgroupStats>> {
+        if self.resources.is_none() {
+            return Ok(None);
+        }
+        cgroup::read_stats(&self.id).map(Some)
+    }
+
+    /// The container's filesystem as its processes see it, mounts included
+    /// (`/proc/{init}/root`).
+    pub fn root_dir(&self) -> BoxliteResult<PathBuf> {
+        let container = LibContainer::load(self.container_state_path()).map_err(|e| {
+            BoxliteError::Internal(format!("Failed to load container {}: {}", self.id, e))
+        })?;
+        let pid = container.pid().ok_or_else(|| {
+            BoxliteError::InvalidState(format!("container {} has no init process", self.id))
+        })?;
+        Ok(PathBuf::from(format!("/proc/{}/root", pid)))
+    }
+
+    /// Value of an environment variable set for the container's processes.
+    pub fn env_var(&self, name: &str) -> Option<String> {
+        self.env.get(name).cloned()
+    }
+
+    /// The (uid, gid) executions run as, from the image's USER.
+    pub fn user(&self) -> (u32, u32) {
+        self.user
+    }
+
     /// Create a command builder for executing processes in this container
     ///
     /// Returns a Command builder. Use `.cmd()` to set the program to execute.
@@ -234,6 +320,7 @@ impl Container {
             self.state_root.clone(),
             self.env.clone(),
             self.user,
+            self.ulimits.clone(),
         )
     }
 
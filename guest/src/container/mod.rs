@@ -64,6 +64,8 @@
 #[cfg(target_os = "linux")]
 mod capabilities;
 #[cfg(target_os = "linux")]
+mod cgroup;
+#[cfg(target_os = "linux")]
 mod command;
 #[cfg(target_os = "linux")]
 mod console_socket;
@@ -79,6 +81,8 @@ mod start;
 mod stdio;
 
 #[cfg(target_os = "linux")]
-pub use lifecycle::Container;
+pub use cgroup::ResourceLimits;
+#[cfg(target_os = "linux")]
+pub use lifecycle::{Container, ContainerStartConfig};
 #[cfg(target_os = "linux")]
-pub use spec::UserMount;
+pub use spec::{ContainerHosts, Ulimit, UlimitResource, UserMount};
@@ -3,13 +3,15 @@
 //! Creates OCI-compliant runtime specifications following the runtime-spec standard.
 
 use super::capabilities::all_capabilities;
+use super::cgroup::{self, ResourceLimits};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use std::path::Path;
 
 use oci_spec::runtime::{
-    LinuxBuilder, LinuxCapabilitiesBuilder, LinuxIdMappingBuilder, LinuxNamespaceBuilder,
-    LinuxNamespaceType, Mount, MountBuilder, PosixRlimitBuilder, PosixRlimitType, ProcessBuilder,
-    RootBuilder, Spec, SpecBuilder, UserBuilder,
+    LinuxBuilder, LinuxCapabilitiesBuilder, LinuxCpuBuilder, LinuxIdMapping, LinuxIdMappingBuilder,
+    LinuxMemoryBuilder, LinuxNamespaceBuilder, LinuxNamespaceType, LinuxResourcesBuilder, Mount,
+    MountBuilder, PosixRlimitBuilder, PosixRlimitType, ProcessBuilder, RootBuilder, Spec,
+    SpecBuilder, UserBuilder,
 };
 
 /// User-specified bind mount for container
@@ -21,6 +23,151 @@ pub struct UserMount {
     pub destination: String,
     /// Read-only mount
     pub read_only: bool,
+    /// Other mount options: noexec, nosuid, nodev, uid=<n>[-<m>],
+    /// gid=<n>[-<m>]
+    pub options: Vec<String>,
+    /// Map the container's user to the owner of the source directory
+    pub map_user: bool,
+}
+
+impl UserMount {
+    /// OCI mount options: bind, ro/rw, then the flags among `options`.
+    fn oci_options(&self) -> Vec<String> {
+        let mode = if self.read_only { "ro" } else { "rw" };
+        let mut options = vec!["bind".to_string(), mode.to_string()];
+        options.extend(self.options.iter().filter(|o| !o.contains('=')).cloned());
+        if self.map_user || self.id("uid").is_some() || self.id("gid").is_some() {
+            options.push("idmap".to_string());
+        }
+        options
+    }
+
+    /// Value of the `uid=` or `gid=` option: the first id and how many ids
+    /// the range holds (1 unless given as `<n>-<m>`).
+    fn id(&self, key: &str) -> Option<(u32, u32)> {
+        self.options.iter().find_map(|option| {
+            let (k, v) = option.split_once('=')?;
+            if k != key {
+                return None;
+            }
+            let (first, last): (u32, u32) = match v.split_once('-') {
+                Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+                None => {
+                    let id = v.parse().ok()?;
+                    (id, id)
+                }
+            };
+            let count = last.checked_sub(first)?.checked_add(1)?;
+            Some((first, count))
+        })
+    }
+
+    /// Idmap showing files of the source directory's owner as owned by
+    /// `uid=`/`gid=` (the owner's own ids if only one is given), or by the
+    /// container's `user` with `map_user`. An `<n>-<m>` range maps as many
+    /// ids from the owner's up, to `n` through `m`.
+    fn id_mappings(
+        &self,
+        user: (u32, u32),
+    ) -> BoxliteResult<Option<(Vec<LinuxIdMapping>, Vec<LinuxIdMapping>)>> {
+        use std::os::unix::fs::MetadataExt;
+
+        let (uid, gid) = if self.map_user {
+            (Some((user.0, 1)), Some((user.1, 1)))
+        } else {
+            (self.id("uid"), self.id("gid"))
+        };
+        if uid.is_none() && gid.is_none() {
+            return Ok(None);
+        }
+        let meta = std::fs::metadata(&self.source).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to stat volume {}: {}", self.source, e))
+        })?;
+        let mapping = |host_id: u32, (container_id, size): (u32, u32)| {
+            if host_id.checked_add(size - 1).is_none() {
+                return Err(BoxliteError::InvalidArgument(format!(
+                    "id range of {} ids from {} for volume {} goes past the last id",
+                    size, host_id, self.destination
+                )));
+            }
+            LinuxIdMappingBuilder::default()
+                .host_id(host_id)
+                .container_id(container_id)
+                .size(size)
+                .build()
+                .map_err(|e| BoxliteError::Internal(format!("Failed to build mount idmap: {}", e)))
+        };
+        Ok(Some((
+            vec![mapping(meta.uid(), uid.unwrap_or((meta.uid(), 1)))?],
+            vec![mapping(meta.gid(), gid.unwrap_or((meta.gid(), 1)))?],
+        )))
+    }
+}
+
+/// Hostname, static `/etc/hosts` entries and DNS server for the container
+#[derive(Debug, Clone)]
+pub struct ContainerHosts {
+    /// Hostname set in the UTS namespace and `/etc/hostname`
+    pub hostname: String,
+    /// Extra `(hostname, ip)` entries appended to `/etc/hosts`
+    pub extra_hosts: Vec<(String, String)>,
+    /// DNS server in `/etc/resolv.conf`
+    pub nameserver: String,
+}
+
+/// Resource limit (`--ulimit`) for the container's processes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ulimit {
+    pub resource: UlimitResource,
+    pub soft: u64,
+    pub hard: u64,
+}
+
+/// Resources a [`Ulimit`] can limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UlimitResource {
+    Nofile,
+    Nproc,
+    Core,
+    Memlock,
+}
+
+impl UlimitResource {
+    /// Resource for a host-side limit name (`nofile`, `nproc`, `core`, `memlock`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nofile" => Some(Self::Nofile),
+            "nproc" => Some(Self::Nproc),
+            "core" => Some(Self::Core),
+            "memlock" => Some(Self::Memlock),
+            _ => None,
+        }
+    }
+
+    fn oci_type(self) -> PosixRlimitType {
+        match self {
+            Self::Nofile => PosixRlimitType::RlimitNofile,
+            Self::Nproc => PosixRlimitType::RlimitNproc,
+            Self::Core => PosixRlimitType::RlimitCore,
+            Self::Memlock => PosixRlimitType::RlimitMemlock,
+        }
+    }
+}
+
+/// Process specification for an execution in a running container.
+///
+/// Same user, capabilities and rlimits as the container's init process;
+/// libcontainer only applies rlimits to tenant processes given as a spec.
+pub(super) fn build_exec_process_spec(
+    args: &[String],
+    env: &[String],
+    cwd: &str,
+    uid: u32,
+    gid: u32,
+    ulimits: &[Ulimit],
+) -> BoxliteResult<oci_spec::runtime::Process> {
+    let caps = build_default_capabilities()?;
+    build_process_spec(args, env, cwd, uid, gid, caps, ulimits)
 }
 
 /// Create OCI runtime specification with default configuration
@@ -35,10 +182,10 @@ pub struct UserMount {
 /// - Resource limits (rlimits)
 /// - No new privileges disabled (allows sudo)
 ///
-/// NOTE: Cgroups are disabled for performance (~105ms savings on container startup).
-/// Since we're inside a VM with single-tenant isolation, cgroup resource limits
-/// provide minimal benefit. See comments in build_default_namespaces() and
-/// build_standard_mounts() to re-enable if needed.
+/// NOTE: Cgroups are disabled for performance (~105ms savings on container startup)
+/// unless `resources` is set, in which case the container gets its own cgroup
+/// (/boxlite/{cid}) with those CPU/memory limits. See comments in
+/// build_default_namespaces() and build_standard_mounts().
 #[allow(clippy::too_many_arguments)]
 pub fn create_oci_spec(
     container_id: &str,
@@ -48,51 +195,54 @@ pub fn create_oci_spec(
     workdir: &str,
     uid: u32,
     gid: u32,
+    hostname: &str,
     bundle_path: &Path,
+    shm_size_mib: u32,
+    ulimits: &[Ulimit],
     user_mounts: &[UserMount],
+    resources: Option<&ResourceLimits>,
 ) -> BoxliteResult<Spec> {
     let caps = build_default_capabilities()?;
     let namespaces = build_default_namespaces()?;
-    let mut mounts = build_standard_mounts(bundle_path)?;
+    let mut mounts = build_standard_mounts(bundle_path, shm_size_mib)?;
 
     // Add user-specified bind mounts
     for user_mount in user_mounts {
-        let options = if user_mount.read_only {
-            vec!["bind".to_string(), "ro".to_string()]
-        } else {
-            vec!["bind".to_string(), "rw".to_string()]
-        };
+        let options = user_mount.oci_options();
 
-        mounts.push(
-            MountBuilder::default()
-                .destination(&user_mount.destination)
-                .typ("bind")
-                .source(&user_mount.source)
-                .options(options)
-                .build()
-                .map_err(|e| {
-                    BoxliteError::Internal(format!(
-                        "Failed to build user mount {} → {}: {}",
-                        user_mount.source, user_mount.destination, e
-                    ))
-                })?,
-        );
+        let mut builder = MountBuilder::default();
+        builder = builder
+            .destination(&user_mount.destination)
+            .typ("bind")
+            .source(&user_mount.source)
+            .options(options.clone());
+        if let Some((uid_mappings, gid_mappings)) = user_mount.id_mappings((uid, gid))? {
+            builder = builder
+                .uid_mappings(uid_mappings)
+                .gid_mappings(gid_mappings);
+        }
+        mounts.push(builder.build().map_err(|e| {
+            BoxliteError::Internal(format!(
+                "Failed to build user mount {} → {}: {}",
+                user_mount.source, user_mount.destination, e
+            ))
+        })?);
 
         tracing::debug!(
             source = %user_mount.source,
             destination = %user_mount.destination,
-            read_only = user_mount.read_only,
+            options = ?options,
             "Added user bind mount to OCI spec"
         );
     }
 
-    let process = build_process_spec(entrypoint, env, workdir, uid, gid, caps)?;
+    let process = build_process_spec(entrypoint, env, workdir, uid, gid, caps, ulimits)?;
     let root = build_root_spec(rootfs)?;
-    let linux = build_linux_spec(container_id, namespaces)?;
+    let linux = build_linux_spec(container_id, namespaces, resources)?;
 
     SpecBuilder::default()
         .version("1.0.2")
-        .hostname("boxlite")
+        .hostname(hostname)
         .root(root)
         .mounts(mounts)
         .process(process)
@@ -309,6 +459,7 @@ fn build_process_spec(
     uid: u32,
     gid: u32,
     caps: oci_spec::runtime::LinuxCapabilities,
+    ulimits: &[Ulimit],
 ) -> BoxliteResult<oci_spec::runtime::Process> {
     let user = UserBuilder::default()
         .uid(uid)
@@ -317,15 +468,28 @@ fn build_process_spec(
         .map_err(|e| BoxliteError::Internal(format!("Failed to build user spec: {}", e)))?;
 
     // Build rlimits
-    // Set NOFILE to 1048576 to match Docker's defaults
+    // Set NOFILE to 1048576 to match Docker's defaults, unless the box sets it
     // This allows applications to open many files/connections (databases, web servers, etc.)
-    #[allow(unused)]
-    let rlimits = vec![PosixRlimitBuilder::default()
-        .typ(PosixRlimitType::RlimitNofile)
-        .hard(1024u64 * 1024u64)
-        .soft(1024u64 * 1024u64)
-        .build()
-        .map_err(|e| BoxliteError::Internal(format!("Failed to build rlimit: {}", e)))?];
+    let mut limits = vec![Ulimit {
+        resource: UlimitResource::Nofile,
+        soft: 1024u64 * 1024u64,
+        hard: 1024u64 * 1024u64,
+    }];
+    for ulimit in ulimits {
+        limits.retain(|l| l.resource != ulimit.resource);
+        limits.push(*ulimit);
+    }
+    let rlimits = limits
+        .iter()
+        .map(|l| {
+            PosixRlimitBuilder::default()
+                .typ(l.resource.oci_type())
+                .hard(l.hard)
+                .soft(l.soft)
+                .build()
+                .map_err(|e| BoxliteError::Internal(format!("Failed to build rlimit: {}", e)))
+        })
+        .collect::<BoxliteResult<Vec<_>>>()?;
 
     ProcessBuilder::default()
         .terminal(false)
@@ -353,6 +517,7 @@ fn build_root_spec(rootfs: &str) -> BoxliteResult<oci_spec::runtime::Root> {
 fn build_linux_spec(
     container_id: &str,
     namespaces: Vec<oci_spec::runtime::LinuxNamespace>,
+    resources: Option<&ResourceLimits>,
 ) -> BoxliteResult<oci_spec::runtime::Linux> {
     // UID/GID mappings for user namespace
     // Map full range of UIDs/GIDs to allow non-root users (nginx=33, etc.)
@@ -395,24 +560,56 @@ fn build_linux_spec(
         "/proc/sysrq-trigger".to_string(),
     ];
 
-    // NOTE: Cgroup path disabled for performance (see cgroup mount comment above)
-    // Re-enable together with cgroup namespace and mount if resource limits are needed.
-    // let cgroups_path = format!("/boxlite/{}", container_id);
-    let _ = container_id; // Suppress unused warning
-
-    LinuxBuilder::default()
+    let mut builder = LinuxBuilder::default()
         .namespaces(namespaces)
         .uid_mappings(uid_mappings)
-        .gid_mappings(gid_mappings)
-        // .masked_paths(masked_paths)
-        // .readonly_paths(readonly_paths)
-        // .cgroups_path(cgroups_path)
+        .gid_mappings(gid_mappings);
+    // .masked_paths(masked_paths)
+    // .readonly_paths(readonly_paths)
+
+    // NOTE: Cgroup path only set when resource limits are requested (see cgroup
+    // mount comment in build_standard_mounts). The guest mounts cgroup2 first.
+    if let Some(resources) = resources {
+        builder = builder
+            .cgroups_path(cgroup::cgroups_path(container_id))
+            .resources(build_resources_spec(resources)?);
+    }
+
+    builder
         .build()
         .map_err(|e| BoxliteError::Internal(format!("Failed to build linux spec: {}", e)))
 }
 
+/// Build cgroup resource limits
+fn build_resources_spec(
+    limits: &ResourceLimits,
+) -> BoxliteResult<oci_spec::runtime::LinuxResources> {
+    let mut builder = LinuxResourcesBuilder::default();
+
+    if let Some(max) = limits.memory_max_bytes {
+        let memory = LinuxMemoryBuilder::default()
+            .limit(max as i64)
+            .build()
+            .map_err(|e| BoxliteError::Internal(format!("Failed to build memory limit: {}", e)))?;
+        builder = builder.memory(memory);
+    }
+
+    if let Some(quota) = limits.cpu_quota_us {
+        let cpu = LinuxCpuBuilder::default()
+            .quota(quota as i64)
+            .period(limits.cpu_period_us)
+            .build()
+            .map_err(|e| BoxliteError::Internal(format!("Failed to build CPU limit: {}", e)))?;
+        builder = builder.cpu(cpu);
+    }
+
+    builder
+        .build()
+        .map_err(|e| BoxliteError::Internal(format!("Failed to build resources spec: {}", e)))
+}
+
 /// Build standard mounts for container filesystem
-fn build_standard_mounts(bundle_path: &Path) -> BoxliteResult<Vec<Mount>> {
+fn build_standard_mounts(bundle_path: &Path, shm_size_mib: u32) -> BoxliteResult<Vec<Mount>> {
     let mut mounts = vec![
         // /proc - Process information
         MountBuilder::default()
@@ -460,7 +657,7 @@ fn build_standard_mounts(bundle_path: &Path) -> BoxliteResult<Vec<Mount>> {
                 "noexec".to_string(),
                 "nodev".to_string(),
                 "mode=1777".to_string(),
-                "size=65536k".to_string(),
+                format!("size={}m", shm_size_mib),
             ])
             .build()
             .map_err(|e| {
@@ -960,4 +1157,142 @@ mod tests {
         let err = resolve_user(r, "short").unwrap_err().to_string();
         assert!(err.contains("User 'short' not found"), "got: {}", err);
     }
+
+    #[test]
+    fn test_user_mount_oci_options() {
+        let user_mount = UserMount {
+            source: "/run/boxlite/shared/volumes/data".to_string(),
+            destination: "/data".to_string(),
+            read_only: true,
+            options: vec!["noexec".to_string(), "uid=1000".to_string()],
+            map_user: false,
+        };
+        assert_eq!(user_mount.oci_options(), ["bind", "ro", "noexec", "idmap"]);
+        assert_eq!(user_mount.id("uid"), Some((1000, 1)));
+        assert_eq!(user_mount.id("gid"), None);
+
+        let plain = UserMount {
+            read_only: false,
+            options: Vec::new(),
+            ..user_mount
+        };
+        assert_eq!(plain.oci_options(), ["bind", "rw"]);
+        assert!(plain.id_mappings((1000, 1000)).unwrap().is_none());
+
+        let source = tempfile::tempdir().unwrap();
+        let mapped = UserMount {
+            source: source.path().to_string_lossy().to_string(),
+            map_user: true,
+            ..plain
+        };
+        assert_eq!(mapped.oci_options(), ["bind", "rw", "idmap"]);
+        let (uid_mappings, gid_mappings) = mapped.id_mappings((1000, 1001)).unwrap().unwrap();
+        assert_eq!(uid_mappings[0].container_id(), 1000);
+        assert_eq!(gid_mappings[0].container_id(), 1001);
+        assert_eq!(uid_mappings[0].size(), 1);
+
+        let ranged = UserMount {
+            map_user: false,
+            options: vec!["uid=1000-66535".to_string(), "gid=100".to_string()],
+            ..mapped
+        };
+        assert_eq!(ranged.oci_options(), ["bind", "rw", "idmap"]);
+        let (uid_mappings, gid_mappings) = ranged.id_mappings((0, 0)).unwrap().unwrap();
+        assert_eq!(
+            (uid_mappings[0].container_id(), uid_mappings[0].size()),
+            (1000, 65536)
+        );
+        assert_eq!(
+            (gid_mappings[0].container_id(), gid_mappings[0].size()),
+            (100, 1)
+        );
+
+        let reversed = UserMount {
+            options: vec!["uid=2000-1000".to_string()],
+            ..ranged
+        };
+        assert_eq!(reversed.id("uid"), None);
+    }
+
+    // ==================
+    // Resource limits
+    // ==================
+
+    #[test]
+    fn test_linux_spec_without_resources_has_no_cgroup() {
+        let linux = build_linux_spec("cid", vec![], None).unwrap();
+        assert!(linux.cgroups_path().is_none());
+    }
+
+    #[test]
+    fn test_linux_spec_with_resources() {
+        let limits = ResourceLimits {
+            memory_max_bytes: Some(448 * 1024 * 1024),
+            cpu_quota_us: Some(90_000),
+            cpu_period_us: 100_000,
+        };
+        let linux = build_linux_spec("cid", vec![], Some(&limits)).unwrap();
+        assert_eq!(
+            linux.cgroups_path().as_deref(),
+            Some(Path::new("/boxlite/cid"))
+        );
+
+        let resources = linux.resources().as_ref().unwrap();
+        let memory = resources.memory().as_ref().unwrap();
+        assert_eq!(memory.limit(), Some(448 * 1024 * 1024));
+        let cpu = resources.cpu().as_ref().unwrap();
+        assert_eq!(cpu.quota(), Some(90_000));
+        assert_eq!(cpu.period(), Some(100_000));
+    }
+
+    #[test]
+    fn test_process_spec_ulimits() {
+        let caps = build_default_capabilities().unwrap();
+        let ulimits = [
+            Ulimit {
+                resource: UlimitResource::Nofile,
+                soft: 4096,
+                hard: 8192,
+            },
+            Ulimit {
+                resource: UlimitResource::Core,
+                soft: 0,
+                hard: 0,
+            },
+        ];
+        let process =
+            build_process_spec(&["sh".to_string()], &[], "/", 0, 0, caps, &ulimits).unwrap();
+        let rlimits = process.rlimits().as_ref().unwrap();
+        assert_eq!(rlimits.len(), 2);
+        let nofile = rlimits
+            .iter()
+            .find(|r| r.typ() == PosixRlimitType::RlimitNofile)
+            .unwrap();
+        assert_eq!((nofile.soft(), nofile.hard()), (4096, 8192));
+
+        let caps = build_default_capabilities().unwrap();
+        let process = build_process_spec(&["sh".to_string()], &[], "/", 0, 0, caps, &[]).unwrap();
+        let rlimits = process.rlimits().as_ref().unwrap();
+        assert_eq!(rlimits[0].soft(), 1024 * 1024);
+
+        // Executions get the same limits as the init process
+        let caps = build_default_capabilities().unwrap();
+        let init = build_process_spec(&["sh".to_string()], &[], "/", 0, 0, caps, &ulimits).unwrap();
+        let exec = build_exec_process_spec(&["ls".to_string()], &[], "/tmp", 1000, 1000, &ulimits)
+            .unwrap();
+        assert_eq!(exec.rlimits(), init.rlimits());
+        assert_eq!(exec.user().uid(), 1000);
+        assert_eq!(exec.cwd(), Path::new("/tmp"));
+    }
+
+    #[test]
+    fn test_standard_mounts_shm_size() {
+        let mounts = build_standard_mounts(Path::new("/bundle"), 512).unwrap();
+        let shm = mounts
+            .iter()
+            .find(|m| m.destination() == Path::new("/dev/shm"))
+            .unwrap();
+        let options = shm.options().as_ref().unwrap();
+        assert!(options.contains(&"size=512m".to_string()));
+    }
 }
@@ -3,6 +3,7 @@
 //! Provides setup, validation, and execution functions for starting containers.
 //! Separated from container.rs to group by lifecycle phase (Prepare → Execute).
 
+use super::cgroup::ResourceLimits;
 use super::spec;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use libcontainer::container::builder::ContainerBuilder;
@@ -43,18 +44,16 @@ pub(crate) fn validate_container_inputs(
 /// Create /etc/hosts, /etc/hostname and /etc/resolv.conf files for the container
 pub(crate) fn create_container_etc_files(
     bundle_path: &Path,
-    _container_id: &str,
+    hosts: &spec::ContainerHosts,
 ) -> BoxliteResult<()> {
-    const DEFAULT_HOSTNAME: &str = "boxlite";
-
     // Create /etc/hostname
     let hostname_path = bundle_path.join("hostname");
-    fs::write(&hostname_path, format!("{}\n", DEFAULT_HOSTNAME))
+    fs::write(&hostname_path, format!("{}\n", hosts.hostname))
         .map_err(|e| BoxliteError::Internal(format!("Failed to create hostname file: {}", e)))?;
 
-    // Create /etc/hosts with localhost and hostname entries
+    // Create /etc/hosts with localhost, hostname and user-provided entries
     let hosts_path = bundle_path.join("hosts");
-    let hosts_content = format!(
+    let mut hosts_content = format!(
         "127.0.0.1\tlocalhost\n\
          ::1\t\tlocalhost ip6-localhost ip6-loopback\n\
          fe00::0\t\tip6-localnet\n\
@@ -62,16 +61,20 @@ pub(crate) fn create_container_etc_files(
          ff02::1\t\tip6-allnodes\n\
          ff02::2\t\tip6-allrouters\n\
          127.0.1.1\t{}\n",
-        DEFAULT_HOSTNAME
+        hosts.hostname
     );
+    for (hostname, ip) in &hosts.extra_hosts {
+        hosts_content.push_str(&format!("{}\t{}\n", ip, hostname));
+    }
     fs::write(&hosts_path, hosts_content)
         .map_err(|e| BoxliteError::Internal(format!("Failed to create hosts file: {}", e)))?;
 
-    // Create /etc/resolv.conf with gateway as DNS server
+    // Create /etc/resolv.conf with the guest's DNS resolver, which answers
+    // the names of network peers and forwards the rest to the gateway
     let resolv_conf_path = bundle_path.join("resolv.conf");
     let resolv_conf_content = format!(
-        "# Generated by BoxLite Guest\n# DNS queries forwarded to gateway\nnameserver {}\nsearch localdomain\n",
-        "192.168.127.1" // TODO: Use constant when guest can access boxlite constants
+        "# Generated by BoxLite Guest\nnameserver {}\nsearch localdomain\n",
+        hosts.nameserver
     );
     fs::write(&resolv_conf_path, resolv_conf_content)
         .map_err(|e| BoxliteError::Internal(format!("Failed to create resolv.conf file: {}", e)))?;
@@ -97,7 +100,11 @@ pub(crate) fn create_oci_bundle(
     uid: u32,
     gid: u32,
     bundle_root: &Path,
+    hosts: &spec::ContainerHosts,
+    shm_size_mib: u32,
+    ulimits: &[spec::Ulimit],
     user_mounts: &[spec::UserMount],
+    resources: Option<&ResourceLimits>,
 ) -> BoxliteResult<PathBuf> {
     let bundle_path = bundle_root.join(container_id);
 
@@ -111,7 +118,7 @@ pub(crate) fn create_oci_bundle(
 
     // Create /etc/hosts, /etc/hostname and /etc/resolv.conf files
     // These will be bind-mounted into the container to provide hostname and DNS resolution
-    create_container_etc_files(&bundle_path, container_id)?;
+    create_container_etc_files(&bundle_path, hosts)?;
 
     let spec = spec::create_oci_spec(
         container_id,
@@ -125,8 +132,12 @@ pub(crate) fn create_oci_bundle(
             .ok_or_else(|| BoxliteError::Internal("Invalid workdir path".to_string()))?,
         uid,
         gid,
+        &hosts.hostname,
         &bundle_path,
+        shm_size_mib,
+        ulimits,
         user_mounts,
+        resources,
     )?;
     let config_path = bundle_path.join("config.json");
 
@@ -0,0 +1,350 @@
+//! DNS resolver for the names of network peers.
+//!
+//! Containers send their DNS queries to the resolver on [`RESOLVER_IP`]. It
+//! answers `A` queries for the hostnames of the box's network peers, set by
+//! the host through the UpdateHosts RPC as peers start and stop, and forwards
+//! every other query to gvproxy's DNS server on the gateway.
+//!
+//! The container shares the guest's network namespace, so the resolver sits
+//! on the loopback and nothing outside the guest reaches it. Peer answers
+//! carry a short TTL: a peer's address disappears when it stops.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tracing::{debug, info, warn};
+
+/// Address the resolver listens on (port 53, UDP and TCP)
+pub const RESOLVER_IP: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 53);
+
+/// gvproxy's DNS server, answering everything else
+// TODO: Use constant when guest can access boxlite constants
+const UPSTREAM_IP: Ipv4Addr = Ipv4Addr::new(192, 168, 127, 1);
+
+const DNS_PORT: u16 = 53;
+const HEADER_LEN: usize = 12;
+const MAX_UDP_MESSAGE: usize = 4096;
+const TYPE_A: u16 = 1;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// TTL of peer answers, in seconds
+const PEER_TTL: u32 = 5;
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time a TCP client may stay idle between queries
+const TCP_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The guest's resolver, shared by the services.
+#[derive(Clone, Default)]
+pub struct Resolver {
+    /// Peer hostnames, lowercased, and their addresses
+    names: Arc<RwLock<HashMap<String, Ipv4Addr>>>,
+    started: Arc<AtomicBool>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start answering on [`RESOLVER_IP`]. Needs the loopback up.
+    pub async fn start(&self) -> io::Result<()> {
+        let addr = SocketAddr::from((RESOLVER_IP, DNS_PORT));
+        let udp = UdpSocket::bind(addr).await?;
+        let tcp = TcpListener::bind(addr).await?;
+        tokio::spawn(self.clone().serve_udp(udp));
+        tokio::spawn(self.clone().serve_tcp(tcp));
+        self.started.store(true, Ordering::SeqCst);
+        info!(%addr, "DNS resolver started");
+        Ok(())
+    }
+
+    /// DNS server for containers' `/etc/resolv.conf`: the resolver once
+    /// started, gvproxy's otherwise.
+    pub fn nameserver(&self) -> Ipv4Addr {
+        if self.started.load(Ordering::SeqCst) {
+            RESOLVER_IP
+        } else {
+            UPSTREAM_IP
+        }
+    }
+
+    /// Replace the peer names answered, as `(hostname, ip)` pairs.
+    pub fn set_names(&self, entries: &[(String, String)]) {
+        let mut names = HashMap::new();
+        for (hostname, ip) in entries {
+            match ip.parse::<Ipv4Addr>() {
+                Ok(ip) => {
+                    names.insert(hostname.to_ascii_lowercase(), ip);
+                }
+                Err(_) => warn!(%hostname, %ip, "Ignoring peer with an invalid IPv4 address"),
+            }
+        }
+        *self.names.write().unwrap_or_else(|e| e.into_inner()) = names;
+    }
+
+    /// The response to `query` if it asks for a peer name, `None` to forward.
+    ///
+    /// Peers only have IPv4 addresses: other types get an empty answer
+    /// rather than one from upstream.
+    fn answer(&self, query: &[u8]) -> Option<Vec<u8>> {
+        let (name, qtype, end) = question(query)?;
+        let ip = *self
+            .names
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&name)?;
+
+        // The header and question, without the query's other sections
+        let mut response = query[..end].to_vec();
+        // Response, authoritative, recursion desired as asked, and available
+        response[2] = 0x80 | 0x04 | (query[2] & 0x01);
+        response[3] = 0x80;
+        let answers: u16 = if matches!(qtype, TYPE_A | TYPE_ANY) {
+            1
+        } else {
+            0
+        };
+        response[6..8].copy_from_slice(&answers.to_be_bytes());
+        response[8..12].fill(0);
+        if answers == 1 {
+            // Name: pointer to the question's
+            response.extend_from_slice(&[0xC0, HEADER_LEN as u8]);
+            response.extend_from_slice(&TYPE_A.to_be_bytes());
+            response.extend_from_slice(&CLASS_IN.to_be_bytes());
+            response.extend_from_slice(&PEER_TTL.to_be_bytes());
+            response.extend_from_slice(&4u16.to_be_bytes());
+            response.extend_from_slice(&ip.octets());
+        }
+        Some(response)
+    }
+
+    async fn serve_udp(self, socket: UdpSocket) {
+        let socket = Arc::new(socket);
+        let mut buf = vec![0u8; MAX_UDP_MESSAGE];
+        loop {
+            let (len, client) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    warn!("DNS resolver failed to receive: {}", e);
+                    continue;
+                }
+            };
+            let query = buf[..len].to_vec();
+            let resolver = self.clone();
+            let socket = Arc::clone(&socket);
+            tokio::spawn(async move {
+                let response = match resolver.answer(&query) {
+                    Some(response) => Ok(response),
+                    None => forward_udp(&query).await,
+                };
+                match response {
+                    Ok(response) => {
+                        if let Err(e) = socket.send_to(&response, client).await {
+                            debug!(%client, "Failed to send DNS response: {}", e);
+                        }
+                    }
+                    Err(e) => debug!(%client, "Failed to forward DNS query: {}", e),
+                }
+            });
+        }
+    }
+
+    async fn serve_tcp(self, listener: TcpListener) {
+        loop {
+            let (stream, client) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("DNS resolver failed to accept: {}", e);
+                    continue;
+                }
+            };
+            let resolver = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = resolver.serve_connection(stream).await {
+                    debug!(%client, "DNS connection failed: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Answer the queries of a TCP client, forwarding over one upstream
+    /// connection opened on the first query to forward.
+    async fn serve_connection(&self, mut client: TcpStream) -> io::Result<()> {
+        let mut upstream: Option<TcpStream> = None;
+        loop {
+            let query =
+                match tokio::time::timeout(TCP_IDLE_TIMEOUT, read_message(&mut client)).await {
+                    Ok(Ok(Some(query))) => query,
+                    Ok(Ok(None)) | Err(_) => return Ok(()),
+                    Ok(Err(e)) => return Err(e),
+                };
+            let response = match self.answer(&query) {
+                Some(response) => response,
+                None => {
+                    if upstream.is_none() {
+                        let addr = SocketAddr::from((UPSTREAM_IP, DNS_PORT));
+                        upstream = Some(TcpStream::connect(addr).await?);
+                    }
+                    let upstream = upstream.as_mut().expect("connected above");
+                    write_message(upstream, &query).await?;
+                    tokio::time::timeout(UPSTREAM_TIMEOUT, read_message(upstream))
+                        .await
+                        .map_err(|_| timed_out())??
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                "upstream DNS server closed the connection",
+                            )
+                        })?
+                }
+            };
+            write_message(&mut client, &response).await?;
+        }
+    }
+}
+
+/// The lowercased name, type and end offset of the question of `query`, if
+/// it is a standard query for one name of class IN.
+fn question(query: &[u8]) -> Option<(String, u16, usize)> {
+    if query.len() < HEADER_LEN {
+        return None;
+    }
+    // QR 0 (a query) and opcode 0 (standard), with one question
+    let flags = u16::from_be_bytes([query[2], query[3]]);
+    let questions = u16::from_be_bytes([query[4], query[5]]);
+    if flags & 0xF800 != 0 || questions != 1 {
+        return None;
+    }
+
+    let mut labels = Vec::new();
+    let mut pos = HEADER_LEN;
+    loop {
+        let len = *query.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        // Compression pointers don't appear in a lone question
+        if len > 63 {
+            return None;
+        }
+        let label = std::str::from_utf8(query.get(pos..pos + len)?).ok()?;
+        labels.push(label.to_ascii_lowercase());
+        pos += len;
+    }
+    let field = |at: usize| Some(u16::from_be_bytes([*query.get(at)?, *query.get(at + 1)?]));
+    let qtype = field(pos)?;
+    if field(pos + 2)? != CLASS_IN {
+        return None;
+    }
+    Some((labels.join("."), qtype, pos + 4))
+}
+
+async fn forward_udp(query: &[u8]) -> io::Result<Vec<u8>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect((UPSTREAM_IP, DNS_PORT)).await?;
+    socket.send(query).await?;
+    let mut buf = vec![0u8; MAX_UDP_MESSAGE];
+    let len = tokio::time::timeout(UPSTREAM_TIMEOUT, socket.recv(&mut buf))
+        .await
+        .map_err(|_| timed_out())??;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// Read a length-prefixed message; `None` if the stream ended before one.
+async fn read_message(stream: &mut TcpStream) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 2];
+    match stream.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut message = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut message).await?;
+    Ok(Some(message))
+}
+
+async fn write_message(stream: &mut TcpStream, message: &[u8]) -> io::Result<()> {
+    let len = u16::try_from(message.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "DNS message too long"))?;
+    let mut framed = Vec::with_capacity(2 + message.len());
+    framed.extend_from_slice(&len.to_be_bytes());
+    framed.extend_from_slice(message);
+    stream.write_all(&framed).await
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "upstream DNS server timed out")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A recursive query for `name`, with an EDNS record after the question.
+    fn query(name: &str, qtype: u16) -> Vec<u8> {
+        let mut query = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 1];
+        for label in name.split('.') {
+            query.push(label.len() as u8);
+            query.extend_from_slice(label.as_bytes());
+        }
+        query.push(0);
+        query.extend_from_slice(&qtype.to_be_bytes());
+        query.extend_from_slice(&CLASS_IN.to_be_bytes());
+        query.extend_from_slice(&[0, 0, 41, 16, 0, 0, 0, 0, 0, 0, 0]);
+        query
+    }
+
+    fn resolver() -> Resolver {
+        let resolver = Resolver::new();
+        resolver.set_names(&[
+            ("api-box".to_string(), "192.168.127.129".to_string()),
+            ("bad".to_string(), "not-an-ip".to_string()),
+        ]);
+        resolver
+    }
+
+    #[test]
+    fn test_answers_peer_names() {
+        let response = resolver().answer(&query("API-Box", TYPE_A)).unwrap();
+
+        let question_end = HEADER_LEN + "API-Box".len() + 2 + 4;
+        assert_eq!(&response[..2], &[0x12, 0x34]);
+        assert_eq!(&response[2..4], &[0x85, 0x80]);
+        assert_eq!(&response[4..12], &[0, 1, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(
+            &response[question_end..],
+            &[0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 5, 0, 4, 192, 168, 127, 129]
+        );
+    }
+
+    #[test]
+    fn test_other_types_of_peer_names_get_no_answers() {
+        let response = resolver().answer(&query("api-box", 28)).unwrap();
+        assert_eq!(&response[4..12], &[0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(response.len(), HEADER_LEN + "api-box".len() + 2 + 4);
+    }
+
+    #[test]
+    fn test_forwards_other_queries() {
+        let resolver = resolver();
+        assert!(resolver.answer(&query("example.com", TYPE_A)).is_none());
+        assert!(resolver.answer(&query("bad", TYPE_A)).is_none());
+        assert!(resolver.answer(&query("api-box", TYPE_A)[..20]).is_none());
+
+        // Responses aren't queries
+        let mut response = query("api-box", TYPE_A);
+        response[2] |= 0x80;
+        assert!(resolver.answer(&response).is_none());
+
+        resolver.set_names(&[]);
+        assert!(resolver.answer(&query("api-box", TYPE_A)).is_none());
+    }
+}
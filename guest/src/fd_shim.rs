@@ -0,0 +1,171 @@
+//! `fd-shim`: starts a container process with passed fds at chosen numbers.
+//!
+//! libcontainer keeps inherited fds open in a tenant process
+//! (`preserved_fds`), but only at the numbers they already have. So an
+//! execution with passed fds runs the agent binary first, inside the
+//! container, as
+//! `/proc/self/fd/<agent> fd-shim <source>:<target>... -- <program> <args>...`.
+//! The shim moves each source to its target, closes every other fd above
+//! stderr and execs the program, which keeps the shim's PID.
+
+use std::convert::Infallible;
+use std::fs::File;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::process::CommandExt;
+
+/// First agent argument selecting the shim.
+pub const SUBCOMMAND: &str = "fd-shim";
+
+/// Inheritable copies of the agent binary and the passed fds, for one spawn.
+///
+/// The copies aren't close-on-exec, so processes spawned by other threads
+/// meanwhile inherit them too; drop this as soon as the process is spawned.
+pub struct FdShim {
+    agent: OwnedFd,
+    /// Inheritable copy of each source, with its target
+    mapping: Vec<(OwnedFd, RawFd)>,
+}
+
+impl FdShim {
+    /// Prepare a shim placing each `(source, target)` of `mapping`.
+    pub fn prepare(mapping: &[(RawFd, RawFd)]) -> io::Result<Self> {
+        let agent = inheritable(File::open("/proc/self/exe")?.as_raw_fd())?;
+        let mapping = mapping
+            .iter()
+            .map(|&(source, target)| Ok((inheritable(source)?, target)))
+            .collect::<io::Result<_>>()?;
+        Ok(Self { agent, mapping })
+    }
+
+    /// libcontainer `preserved_fds` that keeps the copies open.
+    ///
+    /// libcontainer keeps fds 3 to 3 + `preserved_fds` - 1.
+    pub fn preserved_fds(&self) -> i32 {
+        let highest = self
+            .mapping
+            .iter()
+            .map(|(fd, _)| fd.as_raw_fd())
+            .chain([self.agent.as_raw_fd()])
+            .max()
+            .unwrap_or(2);
+        highest - 2
+    }
+
+    /// Container args running `args` (program first) through the shim.
+    pub fn args(&self, args: &[String]) -> Vec<String> {
+        let mut shim_args = vec![
+            format!("/proc/self/fd/{}", self.agent.as_raw_fd()),
+            SUBCOMMAND.to_string(),
+        ];
+        shim_args.extend(
+            self.mapping
+                .iter()
+                .map(|(fd, target)| format!("{}:{}", fd.as_raw_fd(), target)),
+        );
+        shim_args.push("--".to_string());
+        shim_args.extend_from_slice(args);
+        shim_args
+    }
+}
+
+/// Duplicate `fd` without close-on-exec.
+fn inheritable(fd: RawFd) -> io::Result<OwnedFd> {
+    let raw = nix::unistd::dup(fd).map_err(io::Error::from)?;
+    Ok(unsafe { OwnedFd::from_raw_fd(raw) })
+}
+
+/// Run the shim with the arguments following `fd-shim`. Only returns by
+/// exiting.
+pub fn run(args: &[String]) -> ! {
+    let e = match place_and_exec(args) {
+        Ok(never) => match never {},
+        Err(e) => e,
+    };
+    eprintln!("boxlite fd-shim: {}", e);
+    // Same status as a shell that can't run a command
+    std::process::exit(127)
+}
+
+fn place_and_exec(args: &[String]) -> io::Result<Infallible> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+    let separator = args
+        .iter()
+        .position(|arg| arg == "--")
+        .ok_or_else(|| invalid("missing -- before the command".into()))?;
+    let mapping = args[..separator]
+        .iter()
+        .map(|pair| parse_pair(pair).ok_or_else(|| invalid(format!("invalid fd pair {:?}", pair))))
+        .collect::<io::Result<Vec<_>>>()?;
+    let command = &args[separator + 1..];
+    let program = command
+        .first()
+        .ok_or_else(|| invalid("missing command".into()))?;
+
+    // Move the sources above every number in use first, so placing one
+    // never overwrites another
+    let above = mapping
+        .iter()
+        .map(|&(source, target)| source.max(target))
+        .max()
+        .unwrap_or(2)
+        + 1;
+    let mut moved = Vec::with_capacity(mapping.len());
+    for &(source, target) in &mapping {
+        let fd = nix::fcntl::fcntl(source, nix::fcntl::FcntlArg::F_DUPFD_CLOEXEC(above))
+            .map_err(io::Error::from)?;
+        moved.push((fd, target));
+    }
+    for &(fd, target) in &moved {
+        nix::unistd::dup2(fd, target).map_err(io::Error::from)?;
+    }
+
+    // Nothing else libcontainer preserved may reach the program
+    let open: Vec<RawFd> = std::fs::read_dir("/proc/self/fd")?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    for fd in open {
+        if fd > 2 && !moved.iter().any(|&(_, target)| target == fd) {
+            let _ = nix::unistd::close(fd);
+        }
+    }
+
+    Err(std::process::Command::new(program)
+        .args(&command[1..])
+        .exec())
+}
+
+/// Parse `<source>:<target>`.
+fn parse_pair(pair: &str) -> Option<(RawFd, RawFd)> {
+    let (source, target) = pair.split_once(':')?;
+    let (source, target) = (source.parse().ok()?, target.parse().ok()?);
+    (source > 2 && target > 2).then_some((source, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pair() {
+        assert_eq!(parse_pair("7:3"), Some((7, 3)));
+        assert_eq!(parse_pair("7:2"), None);
+        assert_eq!(parse_pair("7"), None);
+        assert_eq!(parse_pair("x:3"), None);
+    }
+
+    #[test]
+    fn test_args() {
+        let (ours, _theirs) = std::os::unix::net::UnixStream::pair().unwrap();
+        let shim = FdShim::prepare(&[(ours.as_raw_fd(), 3)]).unwrap();
+        let args = shim.args(&["nc".to_string(), "-l".to_string()]);
+
+        assert_eq!(args[1], SUBCOMMAND);
+        assert_eq!(args[3], "--");
+        assert_eq!(&args[4..], ["nc", "-l"]);
+        let (source, target) = parse_pair(&args[2]).unwrap();
+        assert_eq!(target, 3);
+        assert!(shim.preserved_fds() >= source - 2);
+    }
+}
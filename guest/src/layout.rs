@@ -16,6 +16,8 @@ use std::path::{Path, PathBuf};
 /// ```text
 /// /run/boxlite/containers/{cid}/
 /// ├── rootfs/            # Container rootfs (bind → shared/containers/{cid}/rootfs)
+/// ├── layers/{n}/        # erofs layer mounts (erofs storage driver)
+/// ├── overlay/{upper,work}  # upper disk mount (erofs storage driver)
 /// └── state/             # libcontainer state directory
 /// ```
 #[derive(Clone, Debug)]
@@ -42,6 +44,20 @@ impl ContainerLayout {
         self.root.join(dirs::ROOTFS)
     }
 
+    /// Layer mount point: /run/boxlite/containers/{cid}/layers/{index}
+    ///
+    /// Read-only erofs mount of one image layer (0 = bottom).
+    pub fn layer_dir(&self, index: usize) -> PathBuf {
+        self.root.join("layers").join(index.to_string())
+    }
+
+    /// Upper disk mount point: /run/boxlite/containers/{cid}/overlay
+    ///
+    /// Holds the overlayfs `upper/` and `work/` dirs for the erofs storage driver.
+    pub fn overlay_dir(&self) -> PathBuf {
+        self.root.join("overlay")
+    }
+
     /// Prepare container directory.
     pub fn prepare(&self) -> std::io::Result<()> {
         std::fs::create_dir_all(self.rootfs_dir())
@@ -155,6 +171,22 @@ impl GuestLayout {
         ContainerLayout::new(self.container_bundle_dir(container_id))
     }
 
+    // ========================================================================
+    // EXECUTION LOGS
+    // ========================================================================
+
+    /// Execution logs directory: /run/boxlite/exec-logs
+    ///
+    /// Read-write virtio-fs mount of the box's exec-logs/ directory on the host.
+    pub fn exec_logs_dir(&self) -> PathBuf {
+        self.base.join(dirs::EXEC_LOGS)
+    }
+
+    /// Output log directory for one execution: /run/boxlite/exec-logs/{execution_id}
+    pub fn exec_log_dir(&self, execution_id: &str) -> PathBuf {
+        self.exec_logs_dir().join(execution_id)
+    }
+
     // ========================================================================
     // PREPARATION
     // ========================================================================
@@ -188,6 +220,14 @@ mod tests {
             container.rootfs_dir().to_str().unwrap(),
             "/run/boxlite/containers/main/rootfs"
         );
+        assert_eq!(
+            container.layer_dir(0).to_str().unwrap(),
+            "/run/boxlite/containers/main/layers/0"
+        );
+        assert_eq!(
+            container.overlay_dir().to_str().unwrap(),
+            "/run/boxlite/containers/main/overlay"
+        );
     }
 
     // ========================================================================
@@ -250,6 +290,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_guest_layout_exec_log_dir() {
+        let layout = GuestLayout::new();
+        assert_eq!(
+            layout.exec_log_dir("abc").to_str().unwrap(),
+            "/run/boxlite/exec-logs/abc"
+        );
+    }
+
     #[test]
     fn test_guest_layout_shared_access() {
         let layout = GuestLayout::new();
@@ -0,0 +1,118 @@
+//! In-memory ring buffer of the agent's own log output
+//!
+//! The agent logs to stderr, which only reaches the host's console log file.
+//! Lines are also kept here so the host can fetch recent agent logs over
+//! Guest.Logs, e.g. to explain why Guest.Init failed.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Maximum number of log lines kept
+const MAX_LINES: usize = 2000;
+
+/// Shared buffer of the most recent agent log lines.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    inner: Arc<Mutex<LogRing>>,
+}
+
+#[derive(Default)]
+struct LogRing {
+    lines: VecDeque<String>,
+    /// Bytes of a line whose newline hasn't been written yet
+    partial: Vec<u8>,
+}
+
+impl LogBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&self, data: &[u8]) {
+        let Ok(mut ring) = self.inner.lock() else {
+            return;
+        };
+        for byte in data {
+            if *byte == b'\n' {
+                let line = String::from_utf8_lossy(&ring.partial).into_owned();
+                ring.partial.clear();
+                if ring.lines.len() == MAX_LINES {
+                    ring.lines.pop_front();
+                }
+                ring.lines.push_back(line);
+            } else {
+                ring.partial.push(*byte);
+            }
+        }
+    }
+
+    /// Return the last `n` complete lines, oldest first (all lines if `n` is 0).
+    pub fn tail(&self, n: usize) -> Vec<String> {
+        let Ok(ring) = self.inner.lock() else {
+            return Vec::new();
+        };
+        let skip = if n == 0 {
+            0
+        } else {
+            ring.lines.len().saturating_sub(n)
+        };
+        ring.lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Writer that sends log output to stderr and the buffer.
+pub struct TeeWriter {
+    buffer: LogBuffer,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(buf)?;
+        self.buffer.push(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = TeeWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        TeeWriter {
+            buffer: self.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_returns_complete_lines() {
+        let buffer = LogBuffer::new();
+        buffer.push(b"one\ntwo\nthr");
+        assert_eq!(buffer.tail(0), vec!["one", "two"]);
+
+        buffer.push(b"ee\n");
+        assert_eq!(buffer.tail(2), vec!["two", "three"]);
+        assert_eq!(buffer.tail(10).len(), 3);
+    }
+
+    #[test]
+    fn test_buffer_drops_oldest_lines() {
+        let buffer = LogBuffer::new();
+        for i in 0..MAX_LINES + 5 {
+            buffer.push(format!("line {}\n", i).as_bytes());
+        }
+
+        let lines = buffer.tail(0);
+        assert_eq!(lines.len(), MAX_LINES);
+        assert_eq!(lines[0], "line 5");
+    }
+}
@@ -6,10 +6,18 @@ compile_error!("BoxLite guest is Linux-only; build with a Linux target");
 #[cfg(target_os = "linux")]
 mod container;
 #[cfg(target_os = "linux")]
+mod dns;
+#[cfg(target_os = "linux")]
+mod fd_shim;
+#[cfg(target_os = "linux")]
 mod layout;
 #[cfg(target_os = "linux")]
+mod log_buffer;
+#[cfg(target_os = "linux")]
 mod mounts;
 #[cfg(target_os = "linux")]
+mod net_connections;
+#[cfg(target_os = "linux")]
 mod network;
 #[cfg(target_os = "linux")]
 mod overlayfs;
@@ -17,6 +25,10 @@ mod overlayfs;
 mod service;
 #[cfg(target_os = "linux")]
 mod storage;
+#[cfg(target_os = "linux")]
+mod swap;
+#[cfg(target_os = "linux")]
+mod sysctl;
 
 #[cfg(target_os = "linux")]
 use boxlite_shared::errors::BoxliteResult;
@@ -49,11 +61,37 @@ struct GuestArgs {
     ///   --notify unix:///var/run/boxlite-ready.sock
     #[arg(short, long)]
     notify: Option<String>,
+
+    /// Log filter (e.g. "debug", "info,boxlite_guest=trace")
+    ///
+    /// Takes precedence over RUST_LOG. Defaults to "info".
+    #[arg(long)]
+    log_level: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+fn main() -> BoxliteResult<()> {
+    // Container processes with passed fds start as the shim. Checked before
+    // the runtime exists: the shim closes fds it doesn't know about.
+    let argv: Vec<String> = std::env::args().collect();
+    if argv.get(1).map(String::as_str) == Some(fd_shim::SUBCOMMAND) {
+        fd_shim::run(&argv[2..]);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| {
+            boxlite_shared::errors::BoxliteError::Internal(format!(
+                "Failed to start runtime: {}",
+                e
+            ))
+        })?
+        .block_on(run_agent())
 }
 
 #[cfg(target_os = "linux")]
-#[tokio::main]
-async fn main() -> BoxliteResult<()> {
+async fn run_agent() -> BoxliteResult<()> {
     // Early diagnostic - visible even if tracing fails
     eprintln!("[BOOT] BoxLite guest agent starting");
 
@@ -63,17 +101,27 @@ async fn main() -> BoxliteResult<()> {
         std::process::exit(1);
     }));
 
+    // Parse command-line arguments with clap
+    let args = GuestArgs::parse();
+
     eprintln!("[BOOT] Initializing tracing");
 
-    // Initialize tracing subscriber - respects RUST_LOG env var
-    // Default to "info" level if RUST_LOG is not set (for visibility)
+    // Initialize tracing subscriber - --log-level, then RUST_LOG, then "info".
+    // Output goes to stderr (console log) and to an in-memory buffer for Guest.Logs.
+    let log_buffer = log_buffer::LogBuffer::new();
+    let env_filter = match &args.log_level {
+        Some(level) => tracing_subscriber::EnvFilter::try_new(level).unwrap_or_else(|e| {
+            eprintln!("[ERROR] Invalid --log-level '{}': {}", level, e);
+            tracing_subscriber::EnvFilter::new("info")
+        }),
+        None => tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+    };
     if let Err(e) = tracing_subscriber::fmt()
         .with_target(true) // Show module names
-        .with_writer(std::io::stderr)
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
+        .with_ansi(false)
+        .with_writer(log_buffer.clone())
+        .with_env_filter(env_filter)
         .try_init()
     {
         eprintln!("[ERROR] Failed to initialize tracing: {}", e);
@@ -86,11 +134,9 @@ async fn main() -> BoxliteResult<()> {
     // Needed because virtio-fs doesn't support open-unlink-fstat pattern
     mounts::mount_essential_tmpfs()?;
 
-    // Parse command-line arguments with clap
-    let args = GuestArgs::parse();
     info!(
-        "Arguments parsed: listen={}, notify={:?}",
-        args.listen, args.notify
+        "Arguments parsed: listen={}, notify={:?}, log_level={:?}",
+        args.listen, args.notify, args.log_level
     );
 
     // Prepare guest layout directories
@@ -101,7 +147,7 @@ async fn main() -> BoxliteResult<()> {
     // Start server in uninitialized state
     // All initialization (mounts, rootfs, network) will happen via Guest.Init RPC
     info!("Starting guest server on: {}", args.listen);
-    let server = GuestServer::new(layout);
+    let server = GuestServer::new(layout, log_buffer);
     server.run(args.listen, args.notify).await
 }
 
@@ -115,6 +161,7 @@ mod tests {
         let args = GuestArgs {
             listen: "vsock://2695".to_string(),
             notify: Some("vsock://2696".to_string()),
+            log_level: None,
         };
         assert_eq!(args.listen, "vsock://2695");
         assert_eq!(args.notify, Some("vsock://2696".to_string()));
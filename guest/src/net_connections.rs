@@ -0,0 +1,300 @@
+//! Active TCP/UDP connections in the guest network namespace.
+//!
+//! The container shares the guest's network namespace, so the sockets listed
+//! here are the ones the workload opened. Addresses and states come from
+//! `/proc/net/{tcp,tcp6,udp,udp6}`; TCP byte counters come from a
+//! `sock_diag` dump (`tcpi_bytes_acked` / `tcpi_bytes_received`), matched by
+//! socket inode. Listening TCP sockets are skipped.
+
+use boxlite_shared::{NetConnection, NetProtocol};
+use nix::libc;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+const TCP_LISTEN: u8 = 0x0A;
+const TCP_CLOSE: u8 = 0x07;
+
+/// List active connections.
+///
+/// Byte counters are left unset if the `sock_diag` dump fails.
+pub fn list() -> std::io::Result<Vec<NetConnection>> {
+    let tcp_bytes = tcp_bytes_by_inode().unwrap_or_else(|e| {
+        tracing::debug!("sock_diag dump failed, omitting byte counters: {}", e);
+        HashMap::new()
+    });
+
+    let mut connections = Vec::new();
+    for (path, protocol) in [
+        ("/proc/net/tcp", NetProtocol::Tcp),
+        ("/proc/net/tcp6", NetProtocol::Tcp),
+        ("/proc/net/udp", NetProtocol::Udp),
+        ("/proc/net/udp6", NetProtocol::Udp),
+    ] {
+        let table = match std::fs::read_to_string(path) {
+            Ok(table) => table,
+            // IPv6 may be disabled
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for line in table.lines().skip(1) {
+            let Some(entry) = parse_line(line) else {
+                continue;
+            };
+            if protocol == NetProtocol::Tcp && entry.state == TCP_LISTEN {
+                continue;
+            }
+            let bytes = match protocol {
+                NetProtocol::Tcp => tcp_bytes.get(&entry.inode).copied(),
+                _ => None,
+            };
+            connections.push(NetConnection {
+                protocol: protocol as i32,
+                local_address: entry.local.to_string(),
+                remote_address: entry.remote.to_string(),
+                state: state_name(protocol, entry.state).to_string(),
+                bytes_sent: bytes.map(|(sent, _)| sent),
+                bytes_received: bytes.map(|(_, received)| received),
+            });
+        }
+    }
+    Ok(connections)
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    local: SocketAddr,
+    remote: SocketAddr,
+    state: u8,
+    inode: u64,
+}
+
+/// Parse one row of `/proc/net/{tcp,udp}[6]`.
+///
+/// Columns: `sl local_address rem_address st tx_queue:rx_queue tr:tm->when
+/// retrnsmt uid timeout inode ...`
+fn parse_line(line: &str) -> Option<Entry> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+    Some(Entry {
+        local: parse_address(fields[1])?,
+        remote: parse_address(fields[2])?,
+        state: u8::from_str_radix(fields[3], 16).ok()?,
+        inode: fields[9].parse().ok()?,
+    })
+}
+
+/// Parse `ADDR:PORT`, where ADDR is the kernel's hex dump of the address as
+/// native-endian 32-bit words and PORT is hex in host order.
+fn parse_address(s: &str) -> Option<SocketAddr> {
+    let (addr, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for chunk in addr.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    let ip = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+fn state_name(protocol: NetProtocol, state: u8) -> &'static str {
+    if protocol == NetProtocol::Udp {
+        // UDP only uses ESTABLISHED (connected) and CLOSE (unconnected)
+        return if state == TCP_CLOSE {
+            "UNCONN"
+        } else {
+            "ESTABLISHED"
+        };
+    }
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }
+}
+
+// sock_diag constants (linux/sock_diag.h, linux/inet_diag.h)
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+const INET_DIAG_INFO: u16 = 2;
+/// `struct inet_diag_msg` size; attributes follow it
+const INET_DIAG_MSG_LEN: usize = 72;
+const INET_DIAG_MSG_INODE_OFFSET: usize = 68;
+/// Offsets of `tcpi_bytes_acked` and `tcpi_bytes_received` in `struct tcp_info`
+const TCPI_BYTES_ACKED_OFFSET: usize = 120;
+const TCPI_BYTES_RECEIVED_OFFSET: usize = 128;
+const NLMSG_HDR_LEN: usize = 16;
+/// `struct inet_diag_req_v2` size
+const INET_DIAG_REQ_LEN: usize = 56;
+
+/// Dump `(bytes_sent, bytes_received)` for every TCP socket, keyed by inode.
+fn tcp_bytes_by_inode() -> std::io::Result<HashMap<u64, (u64, u64)>> {
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            libc::NETLINK_SOCK_DIAG,
+        )
+    };
+    if fd == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut bytes = HashMap::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        send_dump_request(&fd, family as u8)?;
+        read_dump(&fd, &mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+fn send_dump_request(fd: &OwnedFd, family: u8) -> std::io::Result<()> {
+    // nlmsghdr + inet_diag_req_v2 (family, protocol, ext, pad, states, sockid)
+    let len = NLMSG_HDR_LEN + INET_DIAG_REQ_LEN;
+    let mut req = Vec::with_capacity(len);
+    req.extend_from_slice(&(len as u32).to_ne_bytes());
+    req.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    req.extend_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+    req.extend_from_slice(&1u32.to_ne_bytes()); // seq
+    req.extend_from_slice(&0u32.to_ne_bytes()); // pid
+    req.push(family);
+    req.push(libc::IPPROTO_TCP as u8);
+    req.push(1 << (INET_DIAG_INFO - 1));
+    req.push(0);
+    req.extend_from_slice(&u32::MAX.to_ne_bytes()); // all states
+    req.extend_from_slice(&[0u8; 48]); // sockid
+
+    let sent = unsafe { libc::send(fd.as_raw_fd(), req.as_ptr().cast(), req.len(), 0) };
+    if sent == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn read_dump(fd: &OwnedFd, bytes: &mut HashMap<u64, (u64, u64)>) -> std::io::Result<()> {
+    let mut buf = vec![0u8; 32 * 1024];
+    loop {
+        let n = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+        if n == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if n == 0 {
+            return Ok(());
+        }
+        let buf = &buf[..n as usize];
+        let mut offset = 0;
+        while offset + NLMSG_HDR_LEN <= buf.len() {
+            let len = read_u32(buf, offset) as usize;
+            let kind = u16::from_ne_bytes([buf[offset + 4], buf[offset + 5]]);
+            if len < NLMSG_HDR_LEN || offset + len > buf.len() {
+                break;
+            }
+            match kind as i32 {
+                libc::NLMSG_DONE => return Ok(()),
+                libc::NLMSG_ERROR => {
+                    let errno = read_u32(buf, offset + NLMSG_HDR_LEN) as i32;
+                    return Err(std::io::Error::from_raw_os_error(-errno));
+                }
+                _ => {
+                    if let Some((inode, counters)) =
+                        parse_diag_msg(&buf[offset + NLMSG_HDR_LEN..offset + len])
+                    {
+                        bytes.insert(inode, counters);
+                    }
+                }
+            }
+            offset += align4(len);
+        }
+    }
+}
+
+/// Extract the inode and byte counters from an `inet_diag_msg` and its
+/// attributes.
+fn parse_diag_msg(msg: &[u8]) -> Option<(u64, (u64, u64))> {
+    if msg.len() < INET_DIAG_MSG_LEN {
+        return None;
+    }
+    let inode = u64::from(read_u32(msg, INET_DIAG_MSG_INODE_OFFSET));
+    let mut offset = INET_DIAG_MSG_LEN;
+    while offset + 4 <= msg.len() {
+        let len = u16::from_ne_bytes([msg[offset], msg[offset + 1]]) as usize;
+        let kind = u16::from_ne_bytes([msg[offset + 2], msg[offset + 3]]);
+        if len < 4 || offset + len > msg.len() {
+            break;
+        }
+        let info = &msg[offset + 4..offset + len];
+        if kind == INET_DIAG_INFO && info.len() >= TCPI_BYTES_RECEIVED_OFFSET + 8 {
+            return Some((
+                inode,
+                (
+                    read_u64(info, TCPI_BYTES_ACKED_OFFSET),
+                    read_u64(info, TCPI_BYTES_RECEIVED_OFFSET),
+                ),
+            ));
+        }
+        offset += align4(len);
+    }
+    None
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(buf: &[u8], offset: usize) -> u64 {
+    u64::from_ne_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
+
+fn align4(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_line() {
+        let ip = u32::from_ne_bytes([10, 0, 2, 15]);
+        let line = format!(
+            "   1: {:08X}:D431 0100007F:0050 01 00000000:00000000 00:00000000 00000000     0        0 4242 1",
+            ip
+        );
+        let entry = parse_line(&line).unwrap();
+        assert_eq!(entry.local, "10.0.2.15:54321".parse().unwrap());
+        assert_eq!(entry.remote.port(), 80);
+        assert_eq!(entry.state, 0x01);
+        assert_eq!(entry.inode, 4242);
+
+        let loopback6: String = Ipv6Addr::LOCALHOST
+            .octets()
+            .chunks(4)
+            .map(|w| format!("{:08X}", u32::from_ne_bytes(w.try_into().unwrap())))
+            .collect();
+        assert_eq!(
+            parse_address(&format!("{}:01BB", loopback6)),
+            Some("[::1]:443".parse().unwrap())
+        );
+
+        assert!(parse_line("  sl  local_address rem_address   st").is_none());
+        assert_eq!(state_name(NetProtocol::Udp, TCP_CLOSE), "UNCONN");
+        assert_eq!(state_name(NetProtocol::Tcp, 0x06), "TIME_WAIT");
+    }
+}
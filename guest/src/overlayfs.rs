@@ -19,9 +19,11 @@ fn ensure_clean_dir(path: &str) -> BoxliteResult<()> {
 #[cfg(target_os = "linux")]
 /// Mount overlayfs from explicit paths.
 ///
-/// This is the primary API used by Guest.Init handler.
+/// Used by the erofs rootfs strategy in Container.Init.
 /// Guest doesn't understand what these paths mean - it just mounts.
 ///
+/// Existing `upper_dir` contents are kept so a restarted box sees its writes.
+///
 /// # Arguments
 /// * `lower_dirs` - Paths to lower layers (bottom to top order)
 /// * `upper_dir` - Writable upper layer path
@@ -52,11 +54,12 @@ pub fn mount_overlayfs_direct(
     tracing::info!("  workdir:  {}", work_dir);
     tracing::info!("  merged:   {}", merged_dir);
 
-    // Ensure directories exist and are clean
     // work_dir MUST be empty for overlayfs to mount successfully
     ensure_clean_dir(work_dir)?;
-    ensure_clean_dir(upper_dir)?;
-    ensure_clean_dir(merged_dir)?;
+    for dir in [upper_dir, merged_dir] {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create directory {}: {}", dir, e))?;
+    }
 
     // Mount overlayfs using nix API
     use std::ffi::CString;
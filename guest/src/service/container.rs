@@ -1,29 +1,38 @@
 #![cfg(target_os = "linux")]
 //! Container service implementation.
 //!
-//! Handles OCI container lifecycle (Init RPC).
+//! Handles OCI container lifecycle (Init and Status RPCs), cgroup stats
+//! (Stats RPC) and the network peer names of the DNS resolver (UpdateHosts
+//! RPC).
 
 use std::path::Path;
 
 use crate::service::server::GuestServer;
 use boxlite_shared::{
     container_init_response, rootfs_init, Container as ContainerService, ContainerInitError,
-    ContainerInitRequest, ContainerInitResponse, ContainerInitSuccess, Filesystem, RootfsInit,
+    ContainerInitRequest, ContainerInitResponse, ContainerInitSuccess, ContainerResources,
+    ContainerState, ContainerStatsRequest, ContainerStatsResponse, ContainerStatusRequest,
+    ContainerStatusResponse, ContainerUpdateHostsRequest, ContainerUpdateHostsResponse, Filesystem,
+    RootfsInit,
 };
 use nix::mount::{mount, MsFlags};
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info};
 
-use crate::container::{Container, UserMount};
+use crate::container::{
+    Container, ContainerHosts, ContainerStartConfig, ResourceLimits, Ulimit, UlimitResource,
+    UserMount,
+};
 use crate::layout::GuestLayout;
 use crate::storage::block_device::BlockDeviceMount;
 
 /// Prepare container rootfs based on the initialization strategy.
 ///
-/// Handles three strategies:
+/// Handles four strategies:
 /// - Merged: Shared rootfs already exists (no-op)
 /// - Overlay: Bind-mount layers to diff dir, create overlayfs
 /// - Disk: Mount block device to shared rootfs
+/// - ErofsOverlay: Mount erofs layer devices, overlay them onto shared rootfs
 fn prepare_rootfs(
     rootfs_init: &RootfsInit,
     container_id: &str,
@@ -81,10 +90,66 @@ fn prepare_rootfs(
 
             Ok(())
         }
+        Some(rootfs_init::Strategy::ErofsOverlay(erofs)) => {
+            info!(
+                "Rootfs strategy: erofs overlay ({} layers, upper={})",
+                erofs.layer_devices.len(),
+                erofs.upper_device
+            );
+
+            let container_layout = layout.container(container_id);
+
+            let mut lower_dirs = Vec::with_capacity(erofs.layer_devices.len());
+            for (index, device) in erofs.layer_devices.iter().enumerate() {
+                let layer_dir = container_layout.layer_dir(index);
+                BlockDeviceMount::mount(
+                    Path::new(device),
+                    &layer_dir,
+                    Filesystem::Erofs,
+                    false,
+                    false,
+                )
+                .map_err(|e| format!("Failed to mount erofs layer {}: {}", device, e))?;
+                lower_dirs.push(layer_dir.to_string_lossy().into_owned());
+            }
+
+            let overlay_dir = container_layout.overlay_dir();
+            BlockDeviceMount::mount(
+                Path::new(&erofs.upper_device),
+                &overlay_dir,
+                Filesystem::Ext4,
+                erofs.need_format,
+                false,
+            )
+            .map_err(|e| format!("Failed to mount overlay upper disk: {}", e))?;
+
+            crate::overlayfs::mount_overlayfs_direct(
+                &lower_dirs,
+                &overlay_dir.join("upper").to_string_lossy(),
+                &overlay_dir.join("work").to_string_lossy(),
+                &shared_rootfs.to_string_lossy(),
+            )
+            .map_err(|e| format!("Failed to mount rootfs overlay: {}", e))?;
+
+            Ok(())
+        }
         None => Err("Missing rootfs strategy in Container.Init request".to_string()),
     }
 }
 
+/// Convert proto resources to cgroup limits; `None` if no limit is set.
+fn resource_limits(resources: Option<&ContainerResources>) -> Option<ResourceLimits> {
+    let resources = resources?;
+    if resources.memory_max_bytes.is_none() && resources.cpu_quota_us.is_none() {
+        return None;
+    }
+    Some(ResourceLimits {
+        memory_max_bytes: resources.memory_max_bytes,
+        cpu_quota_us: resources.cpu_quota_us,
+        cpu_period_us: resources.cpu_period_us,
+    })
+}
+
 #[tonic::async_trait]
 impl ContainerService for GuestServer {
     async fn init(
@@ -201,13 +266,56 @@ impl ContainerService for GuestServer {
                     source: source.to_string_lossy().to_string(),
                     destination: m.destination.clone(),
                     read_only: m.read_only,
+                    options: m.options.clone(),
+                    map_user: m.map_user,
                 }
             })
             .collect();
 
+        let resources = resource_limits(init_req.resources.as_ref());
+        let shm_size_mib = config
+            .shm_size_mib
+            .unwrap_or(boxlite_shared::constants::container::DEFAULT_SHM_SIZE_MIB);
+
+        let mut ulimits = Vec::with_capacity(config.ulimits.len());
+        for ulimit in &config.ulimits {
+            let Some(resource) = UlimitResource::from_name(&ulimit.name) else {
+                error!("Unknown ulimit: {}", ulimit.name);
+                return Ok(Response::new(ContainerInitResponse {
+                    result: Some(container_init_response::Result::Error(ContainerInitError {
+                        reason: format!("Unknown ulimit '{}'", ulimit.name),
+                    })),
+                }));
+            };
+            ulimits.push(Ulimit {
+                resource,
+                soft: ulimit.soft,
+                hard: ulimit.hard,
+            });
+        }
+
+        let hosts = ContainerHosts {
+            hostname: if config.hostname.is_empty() {
+                boxlite_shared::constants::container::DEFAULT_HOSTNAME.to_string()
+            } else {
+                config.hostname.clone()
+            },
+            extra_hosts: config
+                .extra_hosts
+                .iter()
+                .map(|h| (h.hostname.clone(), h.ip.clone()))
+                .collect(),
+            nameserver: self.resolver.nameserver().to_string(),
+        };
+
         debug!(
             entrypoint = ?config.entrypoint,
             workdir = %config.workdir,
+            hostname = %hosts.hostname,
+            shm_size_mib,
+            ulimits = ?ulimits,
+            extra_hosts = hosts.extra_hosts.len(),
+            resources = ?resources,
             env_count = config.env.len(),
             shared_rootfs = %shared_rootfs.display(),
             bundle_rootfs = %bundle_rootfs.display(),
@@ -224,15 +332,19 @@ impl ContainerService for GuestServer {
             entrypoint = ?config.entrypoint,
             "Starting OCI container with pipe-based stdio"
         );
-        match Container::start(
-            &container_id,
-            &bundle_rootfs,
-            config.entrypoint,
-            config.env,
-            &config.workdir,
-            &config.user,
+        let start_config = ContainerStartConfig {
+            rootfs: bundle_rootfs,
+            entrypoint: config.entrypoint,
+            env: config.env,
+            workdir: config.workdir.into(),
+            user: config.user,
+            hosts,
+            shm_size_mib,
+            ulimits,
             user_mounts,
-        ) {
+            resources,
+        };
+        match Container::start(&container_id, start_config) {
             Ok(mut container) => {
                 debug!(container_id = %container_id, "Container started, checking if init process is running");
                 // Verify container init process is running
@@ -282,4 +394,113 @@ impl ContainerService for GuestServer {
             }
         }
     }
+
+    async fn stats(
+        &self,
+        request: Request<ContainerStatsRequest>,
+    ) -> Result<Response<ContainerStatsResponse>, Status> {
+        let container_id = request.into_inner().container_id;
+        let container = self
+            .containers
+            .lock()
+            .await
+            .get(&container_id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("Container not found: {}", container_id)))?;
+
+        let stats = container
+            .lock()
+            .await
+            .cgroup_stats()
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let swap = crate::swap::stats();
+        let response = match stats {
+            Some(stats) => ContainerStatsResponse {
+                limited: true,
+                cpu_usage_usec: stats.cpu_usage_usec,
+                cpu_nr_periods: stats.cpu_nr_periods,
+                cpu_nr_throttled: stats.cpu_nr_throttled,
+                cpu_throttled_usec: stats.cpu_throttled_usec,
+                memory_current_bytes: stats.memory_current_bytes,
+                memory_max_events: stats.memory_max_events,
+                memory_oom_kills: stats.memory_oom_kills,
+                memory_working_set_bytes: stats.memory_working_set_bytes,
+                memory_cache_bytes: stats.memory_cache_bytes,
+                pids_current: stats.pids_current,
+                io_read_bytes: stats.io_read_bytes,
+                io_write_bytes: stats.io_write_bytes,
+                swap_used_bytes: swap.used_bytes,
+                swap_out_bytes: swap.out_bytes,
+            },
+            None => ContainerStatsResponse {
+                swap_used_bytes: swap.used_bytes,
+                swap_out_bytes: swap.out_bytes,
+                ..Default::default()
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn update_hosts(
+        &self,
+        request: Request<ContainerUpdateHostsRequest>,
+    ) -> Result<Response<ContainerUpdateHostsResponse>, Status> {
+        let request = request.into_inner();
+        if !self
+            .containers
+            .lock()
+            .await
+            .contains_key(&request.container_id)
+        {
+            return Err(Status::not_found(format!(
+                "Container not found: {}",
+                request.container_id
+            )));
+        }
+
+        let network_hosts: Vec<(String, String)> = request
+            .entries
+            .into_iter()
+            .map(|entry| (entry.hostname, entry.ip))
+            .collect();
+        debug!(
+            container_id = %request.container_id,
+            entries = network_hosts.len(),
+            "Updating network hosts"
+        );
+        self.resolver.set_names(&network_hosts);
+        Ok(Response::new(ContainerUpdateHostsResponse {}))
+    }
+
+    async fn status(
+        &self,
+        request: Request<ContainerStatusRequest>,
+    ) -> Result<Response<ContainerStatusResponse>, Status> {
+        let container_id = request.into_inner().container_id;
+        // Registered once Container.Init has started it
+        let container = self.containers.lock().await.get(&container_id).cloned();
+
+        let response = match container {
+            None => ContainerStatusResponse {
+                state: ContainerState::Pending as i32,
+                diagnostics: String::new(),
+            },
+            Some(container) => {
+                let mut container = container.lock().await;
+                if container.is_running() {
+                    ContainerStatusResponse {
+                        state: ContainerState::Running as i32,
+                        diagnostics: String::new(),
+                    }
+                } else {
+                    ContainerStatusResponse {
+                        state: ContainerState::Exited as i32,
+                        diagnostics: container.diagnose_exit(),
+                    }
+                }
+            }
+        };
+        Ok(Response::new(response))
+    }
 }
@@ -322,6 +322,19 @@ impl ExecHandle {
         self.stderr.take()
     }
 
+    /// Put back a stdout stream taken by [`ExecHandle::stdout`]
+    ///
+    /// Used when an attached client goes away so a later attach can resume
+    /// reading where it left off.
+    pub fn restore_stdout(&mut self, stdout: ExecStdout) {
+        self.stdout = Some(stdout);
+    }
+
+    /// Put back a stderr stream taken by [`ExecHandle::stderr`]
+    pub fn restore_stderr(&mut self, stderr: ExecStderr) {
+        self.stderr = Some(stderr);
+    }
+
     /// Wait for process to complete
     ///
     /// This consumes the handle and blocks until the process exits.
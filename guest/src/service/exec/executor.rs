@@ -6,6 +6,7 @@
 
 use crate::container::Container;
 use crate::service::exec::exec_handle::{ExecHandle, PtyConfig};
+use crate::service::exec::passed_fds::{self, PassedFds};
 use async_trait::async_trait;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use boxlite_shared::ExecRequest;
@@ -15,8 +16,9 @@ use tokio::sync::Mutex;
 /// Executor spawns processes.
 #[async_trait]
 pub trait Executor: Send + Sync {
-    /// Spawn process from ExecRequest.
-    async fn spawn(&self, req: &ExecRequest) -> BoxliteResult<ExecHandle>;
+    /// Spawn process from ExecRequest, giving it the stand-ins of its
+    /// passed fds.
+    async fn spawn(&self, req: &ExecRequest, fds: &PassedFds) -> BoxliteResult<ExecHandle>;
 }
 
 /// Executes commands inside OCI container.
@@ -37,7 +39,7 @@ impl ContainerExecutor {
 
 #[async_trait]
 impl Executor for ContainerExecutor {
-    async fn spawn(&self, req: &ExecRequest) -> BoxliteResult<ExecHandle> {
+    async fn spawn(&self, req: &ExecRequest, fds: &PassedFds) -> BoxliteResult<ExecHandle> {
         // Build the command while holding the lock
         let cmd = {
             let container = self.container.lock().await;
@@ -61,6 +63,10 @@ impl Executor for ContainerExecutor {
                 });
             }
 
+            if !fds.is_empty() {
+                cmd = cmd.with_passed_fds(fds.mapping());
+            }
+
             cmd
         }; // Release container lock before spawn
 
@@ -73,7 +79,7 @@ pub struct GuestExecutor;
 
 #[async_trait]
 impl Executor for GuestExecutor {
-    async fn spawn(&self, req: &ExecRequest) -> BoxliteResult<ExecHandle> {
+    async fn spawn(&self, req: &ExecRequest, fds: &PassedFds) -> BoxliteResult<ExecHandle> {
         if let Some(tty) = &req.tty {
             let config = PtyConfig {
                 rows: tty.rows as u16,
@@ -81,15 +87,15 @@ impl Executor for GuestExecutor {
                 x_pixels: tty.x_pixels as u16,
                 y_pixels: tty.y_pixels as u16,
             };
-            spawn_with_pty(req, config)
+            spawn_with_pty(req, config, fds)
         } else {
-            spawn_with_pipes(req)
+            spawn_with_pipes(req, fds)
         }
     }
 }
 
 /// Spawn process with pipes (standard mode).
-fn spawn_with_pipes(req: &ExecRequest) -> BoxliteResult<ExecHandle> {
+fn spawn_with_pipes(req: &ExecRequest, fds: &PassedFds) -> BoxliteResult<ExecHandle> {
     use nix::unistd::Pid;
     use std::os::unix::io::{AsRawFd, FromRawFd};
     use tokio::process::Command;
@@ -120,6 +126,13 @@ fn spawn_with_pipes(req: &ExecRequest) -> BoxliteResult<ExecHandle> {
         cmd.stderr(std::process::Stdio::from_raw_fd(stderr_write.as_raw_fd()));
     }
 
+    if !fds.is_empty() {
+        let mapping = fds.mapping();
+        unsafe {
+            cmd.pre_exec(move || passed_fds::place(&mapping));
+        }
+    }
+
     let child = cmd
         .spawn()
         .map_err(|e| BoxliteError::Internal(format!("Failed to spawn '{}': {}", req.program, e)))?;
@@ -143,7 +156,11 @@ fn spawn_with_pipes(req: &ExecRequest) -> BoxliteResult<ExecHandle> {
 }
 
 /// Spawn process with PTY (interactive mode).
-fn spawn_with_pty(req: &ExecRequest, config: PtyConfig) -> BoxliteResult<ExecHandle> {
+fn spawn_with_pty(
+    req: &ExecRequest,
+    config: PtyConfig,
+    fds: &PassedFds,
+) -> BoxliteResult<ExecHandle> {
     use nix::pty::{openpty, OpenptyResult, Winsize};
     use nix::unistd::{dup, Pid};
     use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
@@ -207,6 +224,13 @@ fn spawn_with_pty(req: &ExecRequest, config: PtyConfig) -> BoxliteResult<ExecHan
         });
     }
 
+    if !fds.is_empty() {
+        let mapping = fds.mapping();
+        unsafe {
+            cmd.pre_exec(move || passed_fds::place(&mapping));
+        }
+    }
+
     let child = cmd
         .spawn()
         .map_err(|e| BoxliteError::Internal(format!("Failed to spawn '{}': {}", req.program, e)))?;
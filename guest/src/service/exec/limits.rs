@@ -0,0 +1,123 @@
+//! Execution limits.
+//!
+//! Enforces `ExecLimits` for an execution:
+//! - CPU time via `RLIMIT_CPU` on the process (SIGXCPU, then SIGKILL)
+//! - Wall time via a watcher that kills the process
+//! - Output via a byte budget shared by stdout and stderr; output past the
+//!   budget is dropped and the process killed
+
+use crate::service::exec::state::ExecutionState;
+use boxlite_shared::ExecLimit;
+use nix::sys::signal::Signal;
+use nix::unistd::Pid;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::info;
+
+/// Seconds between the soft CPU limit (SIGXCPU) and the hard one (SIGKILL),
+/// for processes that catch SIGXCPU.
+const CPU_HARD_LIMIT_GRACE_SECS: u64 = 1;
+
+/// Limit the CPU time of a spawned process.
+///
+/// Applied right after spawn; CPU time is cumulative, so time used before
+/// the limit was set still counts.
+pub(super) fn apply_cpu_limit(pid: Pid, cpu_secs: u64) -> std::io::Result<()> {
+    let limit = nix::libc::rlimit {
+        rlim_cur: cpu_secs,
+        rlim_max: cpu_secs.saturating_add(CPU_HARD_LIMIT_GRACE_SECS),
+    };
+    let ret = unsafe {
+        nix::libc::prlimit(
+            pid.as_raw(),
+            nix::libc::RLIMIT_CPU,
+            &limit,
+            std::ptr::null_mut(),
+        )
+    };
+    if ret == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Whether a process that used `cpu_time` ran into a CPU limit of `cpu_secs`.
+///
+/// A process that catches SIGXCPU keeps running until the hard limit kills it
+/// with SIGKILL, which on its own looks like any other kill.
+pub(super) fn hit_cpu_limit(cpu_time: Duration, cpu_secs: u64) -> bool {
+    cpu_time >= Duration::from_secs(cpu_secs)
+}
+
+/// Kill the process once it has run for `wall` and record the limit.
+pub(super) fn start_wall_limit_watcher(
+    exec_state: ExecutionState,
+    exec_id: String,
+    wall: Duration,
+) {
+    tokio::spawn(async move {
+        tokio::time::sleep(wall).await;
+        if exec_state.has_exited() {
+            return;
+        }
+
+        // Record first so a Wait woken by the kill sees the limit
+        exec_state.record_limit(ExecLimit::Wall);
+        if exec_state.kill(Signal::SIGKILL).await {
+            info!(execution_id = %exec_id, "killed on wall time limit");
+        }
+    });
+}
+
+/// Bytes of output an execution may still produce.
+pub(super) struct OutputBudget {
+    remaining: AtomicU64,
+}
+
+impl OutputBudget {
+    pub(super) fn new(max_bytes: u64) -> Self {
+        Self {
+            remaining: AtomicU64::new(max_bytes),
+        }
+    }
+
+    /// Take up to `len` bytes from the budget.
+    ///
+    /// Returns how many bytes may be forwarded and whether the budget ran
+    /// out before all of them.
+    pub(super) fn take(&self, len: usize) -> (usize, bool) {
+        let len = len as u64;
+        let previous = self
+            .remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                Some(remaining.saturating_sub(len))
+            })
+            .unwrap_or(0);
+        let granted = previous.min(len);
+        (granted as usize, granted < len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_budget_truncates() {
+        let budget = OutputBudget::new(10);
+        assert_eq!(budget.take(4), (4, false));
+        assert_eq!(budget.take(6), (6, false));
+        assert_eq!(budget.take(0), (0, false));
+        assert_eq!(budget.take(1), (0, true));
+
+        let budget = OutputBudget::new(5);
+        assert_eq!(budget.take(8), (5, true));
+    }
+
+    #[test]
+    fn test_hit_cpu_limit() {
+        assert!(hit_cpu_limit(Duration::from_millis(2_900), 2));
+        assert!(hit_cpu_limit(Duration::from_secs(2), 2));
+        assert!(!hit_cpu_limit(Duration::from_millis(400), 2));
+    }
+}
@@ -10,8 +10,11 @@
 //!
 //! - **Protocol Layer** (mod.rs): gRPC service implementation
 //! - **Executor Layer** (executor.rs): Process spawning abstraction
-//! - **Lifecycle Layer** (timeout.rs): Process management
+//! - **Lifecycle Layer** (timeout.rs, limits.rs): Process management
 //! - **State Layer** (registry.rs, state.rs): Execution state
+//! - **Output Layer** (output_log.rs): Output recording to log files
+//! - **Passed fds** (passed_fds.rs): Host fds relayed to the process
+//! - **Preflight** (preflight.rs): Workdir and program checks before spawning
 //! - **Types** (types.rs): Shared types
 //!
 //! Each file has a single, clear responsibility.
@@ -19,6 +22,10 @@
 #[cfg(target_os = "linux")]
 pub mod exec_handle;
 pub(in crate::service) mod executor;
+mod limits;
+mod output_log;
+pub(in crate::service) mod passed_fds;
+mod preflight;
 pub(in crate::service) mod registry;
 mod state;
 mod timeout;
@@ -27,10 +34,12 @@ mod timeout;
 pub(crate) use state::InitHealthCheck;
 
 use crate::service::exec::executor::{ContainerExecutor, GuestExecutor};
+use crate::service::exec::passed_fds::PassedFds;
 use crate::service::server::GuestServer;
 use boxlite_shared::{
-    constants::executor as executor_const, AttachRequest, ExecError, ExecOutput, ExecRequest,
-    ExecResponse, ExecStdin, Execution, KillRequest, KillResponse, ResizeTtyRequest,
+    constants::executor as executor_const, AttachRequest, BoxliteError, ExecError, ExecLimit,
+    ExecOutput, ExecRequest, ExecResponse, ExecStdin, Execution, FdChunk, KillRequest,
+    KillResponse, ListExecutionsRequest, ListExecutionsResponse, ResizeTtyRequest,
     ResizeTtyResponse, SendInputAck, WaitRequest, WaitResponse,
 };
 use futures::stream::Stream;
@@ -129,6 +138,40 @@ impl Execution for GuestServer {
         }
     }
 
+    type RelayFdStream = Pin<Box<dyn Stream<Item = Result<FdChunk, Status>> + Send + 'static>>;
+
+    async fn relay_fd(
+        &self,
+        request: Request<Streaming<FdChunk>>,
+    ) -> Result<Response<Self::RelayFdStream>, Status> {
+        let mut stream = request.into_inner();
+
+        // First message must carry execution_id and fd
+        let first = stream
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("Empty relay stream"))?;
+
+        let exec_id = first.execution_id.clone();
+        if exec_id.is_empty() {
+            return Err(Status::invalid_argument("execution_id is required"));
+        }
+        debug!(execution_id = %exec_id, fd = first.fd, "relay_fd request");
+
+        // Get state from registry
+        let state = self
+            .registry
+            .get(&exec_id)
+            .await
+            .ok_or_else(|| Status::not_found(format!("Execution not found: {}", exec_id)))?;
+
+        let rx = state.relay_fd(first, stream).await?;
+
+        Ok(Response::new(
+            Box::pin(ReceiverStream::new(rx)) as Self::RelayFdStream
+        ))
+    }
+
     async fn wait(&self, request: Request<WaitRequest>) -> Result<Response<WaitResponse>, Status> {
         use exec_handle::ExitStatus;
 
@@ -180,12 +223,24 @@ impl Execution for GuestServer {
             }
         };
 
+        // A process that exceeds RLIMIT_CPU dies of SIGXCPU, or of SIGKILL at
+        // the hard limit if it catches SIGXCPU
+        let limit_exceeded = state.limit_exceeded().or_else(|| {
+            let cpu = match exit_status {
+                ExitStatus::Signal(nix::sys::signal::Signal::SIGXCPU) => true,
+                ExitStatus::Signal(nix::sys::signal::Signal::SIGKILL) => state.hit_cpu_limit(),
+                _ => false,
+            };
+            cpu.then_some(ExecLimit::Cpu)
+        });
+
         Ok(Response::new(WaitResponse {
             exit_code,
             signal,
             timed_out: false,
             duration_ms: 0,
             error_message,
+            limit_exceeded: limit_exceeded.unwrap_or(ExecLimit::None) as i32,
         }))
     }
 
@@ -288,6 +343,18 @@ impl Execution for GuestServer {
             }
         }
     }
+
+    async fn list(
+        &self,
+        _request: Request<ListExecutionsRequest>,
+    ) -> Result<Response<ListExecutionsResponse>, Status> {
+        let mut executions = Vec::new();
+        for (exec_id, state) in self.registry.list().await {
+            executions.push(state.summary(exec_id).await);
+        }
+        executions.sort_by_key(|e| e.started_at_ms);
+        Ok(Response::new(ListExecutionsResponse { executions }))
+    }
 }
 
 /// Spawn execution (orchestrates full lifecycle).
@@ -298,33 +365,90 @@ async fn spawn_execution(
 ) -> Result<ExecResponse, ExecResponse> {
     let started_at_ms = now_ms();
 
+    // Step 0: Create output logs before spawning so a bad log dir fails the exec
+    let output_logs = match req.output_log {
+        Some(ref config) => Some(create_output_logs(server, &execution_id, config)?),
+        None => None,
+    };
+
+    // Stand-ins for host fds, placed in the process by the executor
+    let passed_fds = PassedFds::create(&req.fds)
+        .map_err(|e| spawn_error(&execution_id, format!("Failed to set up passed fds: {}", e)))?;
+
     // Step 1: Spawn process using executor selected by BOXLITE_EXECUTOR env var
-    let (child, container_ref) = spawn_with_executor(server, &req, &execution_id).await?;
+    let (child, container_ref) =
+        spawn_with_executor(server, &req, &execution_id, &passed_fds).await?;
 
     let pid = child.pid().as_raw() as u32;
+    let exec_limits = req.limits.unwrap_or_default();
+
+    // Step 2: Limit CPU time before the process gets far
+    if exec_limits.cpu_secs > 0 {
+        if let Err(e) = limits::apply_cpu_limit(child.pid(), exec_limits.cpu_secs) {
+            let _ = child.kill(nix::sys::signal::Signal::SIGKILL);
+            return Err(spawn_error(
+                &execution_id,
+                format!("Failed to set CPU limit: {}", e),
+            ));
+        }
+    }
 
-    // Step 2: Create execution state and register
+    // Step 3: Create execution state and register
     // If running inside a container, pass the init health checker for death detection
-    let state = match container_ref {
+    let mut state = match container_ref {
         Some(container) => {
             let health: std::sync::Arc<tokio::sync::Mutex<dyn InitHealthCheck>> = container;
             state::ExecutionState::new_with_init_health(child, health)
         }
         None => state::ExecutionState::new(child),
     };
+    if exec_limits.cpu_secs > 0 {
+        state = state.with_cpu_limit(exec_limits.cpu_secs);
+    }
+    if exec_limits.max_output_bytes > 0 {
+        state = state.with_output_limit(exec_limits.max_output_bytes);
+    }
+    if !passed_fds.is_empty() {
+        state = state.with_relays(passed_fds.into_relays());
+    }
+    state = state.with_meta(state::ExecutionMeta {
+        program: req.program.clone(),
+        args: req.args.clone(),
+        started_at_ms,
+    });
+    if let Some((stdout_log, stderr_log)) = output_logs {
+        if let Err(e) = state
+            .start_recording(&execution_id, stdout_log, stderr_log)
+            .await
+        {
+            warn!(execution_id = %execution_id, error = %e, "Failed to start output recording");
+        }
+    }
     server
         .registry
         .register(execution_id.clone(), state.clone())
         .await;
+    server.registry.retire_on_exit(
+        execution_id.clone(),
+        state.clone(),
+        (req.retention_secs > 0).then(|| std::time::Duration::from_secs(req.retention_secs)),
+    );
 
-    // Step 3: Start timeout watcher (if requested)
+    // Step 4: Start timeout and wall time watchers (if requested)
     if req.timeout_ms > 0 {
         timeout::start_timeout_watcher(
-            state,
+            state.clone(),
             execution_id.clone(),
             std::time::Duration::from_millis(req.timeout_ms),
         );
     }
+    if exec_limits.wall_secs > 0 {
+        limits::start_wall_limit_watcher(
+            state,
+            execution_id.clone(),
+            std::time::Duration::from_secs(exec_limits.wall_secs),
+        );
+    }
 
     Ok(ExecResponse {
         execution_id,
@@ -334,6 +458,74 @@ async fn spawn_execution(
     })
 }
 
+/// Create the stdout/stderr logs for a recorded execution.
+fn create_output_logs(
+    server: &GuestServer,
+    execution_id: &str,
+    config: &boxlite_shared::OutputLogConfig,
+) -> Result<(output_log::RotatingLog, output_log::RotatingLog), ExecResponse> {
+    if !output_log::is_valid_execution_id(execution_id) {
+        return Err(error_response(
+            execution_id.to_string(),
+            "invalid_execution_id",
+            "Execution ID cannot be used as a log directory name",
+        ));
+    }
+
+    let dir = server.layout.exec_log_dir(execution_id);
+    let create = || -> std::io::Result<_> {
+        std::fs::create_dir_all(&dir)?;
+        Ok((
+            output_log::RotatingLog::create(&dir, "stdout", config)?,
+            output_log::RotatingLog::create(&dir, "stderr", config)?,
+        ))
+    };
+    create().map_err(|e| {
+        spawn_error(
+            execution_id,
+            format!("Failed to create output logs in {}: {}", dir.display(), e),
+        )
+    })
+}
+
+/// Check (or create) the workdir and find the program under `root`, so
+/// these fail with their own reasons instead of an opaque spawn error.
+///
+/// `default_path` is the `PATH` of the process unless the request sets one;
+/// `user` is the (uid, gid) it runs as.
+fn preflight(
+    root: &std::path::Path,
+    req: &ExecRequest,
+    default_path: Option<String>,
+    user: (u32, u32),
+    execution_id: &str,
+) -> Result<(), ExecResponse> {
+    if !req.workdir.is_empty() {
+        preflight::prepare_workdir(root, &req.workdir, req.create_workdir, user).map_err(|e| {
+            match e {
+                BoxliteError::WorkdirNotFound(dir) => {
+                    error_response(execution_id.to_string(), "workdir_not_found", &dir)
+                }
+                e => spawn_error(execution_id, e.to_string()),
+            }
+        })?;
+    }
+
+    let path_var = req.env.get("PATH").cloned().or(default_path);
+    let cwd = if req.workdir.is_empty() {
+        "/"
+    } else {
+        &req.workdir
+    };
+    preflight::check_program(root, &req.program, cwd, path_var.as_deref()).map_err(|e| {
+        error_response(
+            execution_id.to_string(),
+            e.reason(),
+            &e.message(&req.program),
+        )
+    })
+}
+
 fn error_response(id: String, reason: &str, detail: &str) -> ExecResponse {
     ExecResponse {
         execution_id: id,
@@ -379,6 +571,7 @@ async fn spawn_with_executor(
     server: &GuestServer,
     req: &ExecRequest,
     execution_id: &str,
+    passed_fds: &PassedFds,
 ) -> Result<
     (
         exec_handle::ExecHandle,
@@ -394,8 +587,18 @@ async fn spawn_with_executor(
         Some(executor_const::GUEST) | None | Some("") => {
             // Guest executor (explicit or default)
             debug!(execution_id = %execution_id, "Using GuestExecutor");
+            preflight(
+                std::path::Path::new("/"),
+                req,
+                std::env::var("PATH").ok(),
+                (
+                    nix::unistd::geteuid().as_raw(),
+                    nix::unistd::getegid().as_raw(),
+                ),
+                execution_id,
+            )?;
             let handle = GuestExecutor
-                .spawn(req)
+                .spawn(req, passed_fds)
                 .await
                 .map_err(|e| spawn_error(execution_id, e.to_string()))?;
             Ok((handle, None))
@@ -427,9 +630,21 @@ async fn spawn_with_executor(
                     )
                 })?
             };
+            // Without an init process the spawn below fails with diagnostics
+            let (root, path, user) = {
+                let container = container_arc.lock().await;
+                (
+                    container.root_dir(),
+                    container.env_var("PATH"),
+                    container.user(),
+                )
+            };
+            if let Ok(root) = root {
+                preflight(&root, req, path, user, execution_id)?;
+            }
             let executor = ContainerExecutor::new(container_arc);
             let container_ref = executor.container_ref();
-            let handle = match executor.spawn(req).await {
+            let handle = match executor.spawn(req, passed_fds).await {
                 Ok(h) => h,
                 Err(e) => {
                     // Check if container init died — provide actionable diagnostics
@@ -0,0 +1,148 @@
+//! Output recording.
+//!
+//! Tees execution stdout/stderr into size-capped, rotated files under
+//! /run/boxlite/exec-logs/{execution_id}/, a read-write virtio-fs share of the
+//! box's exec-logs/ directory, so output is kept even with no client attached.
+
+use boxlite_shared::OutputLogConfig;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Check that an execution ID is usable as a directory name.
+pub(super) fn is_valid_execution_id(execution_id: &str) -> bool {
+    !execution_id.is_empty()
+        && execution_id != "."
+        && execution_id != ".."
+        && !execution_id.contains('/')
+}
+
+/// Log file for one output stream, rotated by size.
+///
+/// Writes go to `{name}.log`; on rotation it becomes `{name}.log.1`, the
+/// previous `.1` becomes `.2`, and so on up to `max_files - 1`.
+pub(super) struct RotatingLog {
+    path: PathBuf,
+    max_file_bytes: u64,
+    max_files: u32,
+    file: File,
+    written: u64,
+}
+
+impl RotatingLog {
+    /// Create `{dir}/{name}.log`, truncating any existing file.
+    pub fn create(dir: &Path, name: &str, config: &OutputLogConfig) -> io::Result<Self> {
+        let path = dir.join(format!("{}.log", name));
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            max_file_bytes: config.max_file_bytes,
+            max_files: config.max_files.max(1),
+            file,
+            written: 0,
+        })
+    }
+
+    /// Append a chunk, rotating first if it would overflow the current file.
+    ///
+    /// Chunks are never split, so a file can exceed the cap by one chunk.
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.max_file_bytes > 0
+            && self.written > 0
+            && self.written + data.len() as u64 > self.max_file_bytes
+        {
+            self.rotate()?;
+        }
+        self.file.write_all(data)?;
+        self.written += data.len() as u64;
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files > 1 {
+            for index in (1..self.max_files - 1).rev() {
+                match std::fs::rename(self.rotated_path(index), self.rotated_path(index + 1)) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_file_bytes: u64, max_files: u32) -> OutputLogConfig {
+        OutputLogConfig {
+            max_file_bytes,
+            max_files,
+        }
+    }
+
+    #[test]
+    fn test_is_valid_execution_id() {
+        assert!(is_valid_execution_id(
+            "0b6a7c1e-2f4d-4a53-9d0e-1c2b3a4d5e6f"
+        ));
+        assert!(!is_valid_execution_id(""));
+        assert!(!is_valid_execution_id(".."));
+        assert!(!is_valid_execution_id("../etc"));
+    }
+
+    #[test]
+    fn test_rotating_log_rotates_and_caps_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RotatingLog::create(dir.path(), "stdout", &config(4, 3)).unwrap();
+
+        for chunk in [b"aaa", b"bbb", b"ccc", b"ddd"] {
+            log.write(chunk).unwrap();
+        }
+
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("stdout.log"), "ddd");
+        assert_eq!(read("stdout.log.1"), "ccc");
+        assert_eq!(read("stdout.log.2"), "bbb");
+        assert!(!dir.path().join("stdout.log.3").exists());
+    }
+
+    #[test]
+    fn test_rotating_log_unlimited() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RotatingLog::create(dir.path(), "stderr", &config(0, 1)).unwrap();
+
+        log.write(b"hello ").unwrap();
+        log.write(b"world").unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join("stderr.log")).unwrap();
+        assert_eq!(content, "hello world");
+    }
+
+    #[test]
+    fn test_rotating_log_single_file_truncates() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RotatingLog::create(dir.path(), "stdout", &config(4, 1)).unwrap();
+
+        log.write(b"old").unwrap();
+        log.write(b"new").unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join("stdout.log")).unwrap();
+        assert_eq!(content, "new");
+        assert!(!dir.path().join("stdout.log.1").exists());
+    }
+}
@@ -0,0 +1,266 @@
+//! Host file descriptors passed to an execution (`ExecRequest.fds`).
+//!
+//! Host fds can't cross into the VM, so each one gets a guest-side stand-in
+//! that the host relays over `Execution.RelayFd`:
+//! - stream fds become one end of a unix socketpair; the other end is relayed
+//! - listening sockets become a TCP listener on loopback; each connection the
+//!   host accepts is relayed to a new connection to it
+//!
+//! The stand-ins are placed at the requested fd numbers with `pre_exec` for
+//! guest executions and with [`crate::fd_shim`] for container executions.
+
+use boxlite_shared::{FdChunk, PassedFd, PassedFdKind};
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tonic::{Status, Streaming};
+
+/// Bytes read from a stand-in per relayed chunk.
+const RELAY_CHUNK_SIZE: usize = 32 * 1024;
+
+/// Guest side of a passed fd.
+pub(super) enum RelayEnd {
+    /// Our end of the socketpair (None once a relay took it)
+    Stream(Option<UnixStream>),
+    /// Address of the process's listener
+    Listener(SocketAddr),
+}
+
+/// Stand-ins for the passed fds of one execution.
+#[derive(Default)]
+pub(crate) struct PassedFds {
+    /// Process side of each stand-in, with the fd number it must get
+    targets: Vec<(OwnedFd, u32)>,
+    /// Guest side, by process fd number
+    relays: HashMap<u32, RelayEnd>,
+}
+
+impl PassedFds {
+    /// Create stand-ins for `fds`.
+    pub(crate) fn create(fds: &[PassedFd]) -> io::Result<Self> {
+        let mut passed = Self::default();
+        let Some(highest) = fds.iter().map(|fd| fd.fd).max() else {
+            return Ok(passed);
+        };
+
+        for fd in fds {
+            if fd.fd < 3 || fd.fd > i32::MAX as u32 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid passed fd number {}", fd.fd),
+                ));
+            }
+            if passed.relays.contains_key(&fd.fd) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("fd {} is passed more than once", fd.fd),
+                ));
+            }
+
+            let (process_side, relay) = match fd.kind() {
+                PassedFdKind::Stream => {
+                    let (process_side, ours) = UnixStream::pair()?;
+                    (OwnedFd::from(process_side), RelayEnd::Stream(Some(ours)))
+                }
+                PassedFdKind::Listener => {
+                    let listener = TcpListener::bind("127.0.0.1:0")?;
+                    let addr = listener.local_addr()?;
+                    (OwnedFd::from(listener), RelayEnd::Listener(addr))
+                }
+            };
+            // Above every target, so placing one never overwrites another
+            let process_side = dup_above(&process_side, highest as RawFd + 1)?;
+            passed.targets.push((process_side, fd.fd));
+            passed.relays.insert(fd.fd, relay);
+        }
+        Ok(passed)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// `(source, target)` pairs placing the stand-ins in a child process.
+    ///
+    /// Sources are close-on-exec and numbered above every target.
+    pub(crate) fn mapping(&self) -> Vec<(RawFd, RawFd)> {
+        self.targets
+            .iter()
+            .map(|(fd, target)| (fd.as_raw_fd(), *target as RawFd))
+            .collect()
+    }
+
+    /// Close the process side (the process has its own copies by now) and
+    /// keep the ends to relay.
+    pub(super) fn into_relays(self) -> HashMap<u32, RelayEnd> {
+        self.relays
+    }
+}
+
+/// Place stand-ins in a child process, from `pre_exec`.
+///
+/// Only calls async-signal-safe functions.
+pub(crate) fn place(mapping: &[(RawFd, RawFd)]) -> io::Result<()> {
+    for &(source, target) in mapping {
+        if unsafe { nix::libc::dup2(source, target) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn dup_above(fd: &OwnedFd, min: RawFd) -> io::Result<OwnedFd> {
+    use nix::fcntl::{fcntl, FcntlArg};
+
+    let raw = fcntl(fd.as_raw_fd(), FcntlArg::F_DUPFD_CLOEXEC(min)).map_err(io::Error::from)?;
+    Ok(unsafe { OwnedFd::from_raw_fd(raw) })
+}
+
+impl RelayEnd {
+    /// Take what a new relay of process fd `fd` needs.
+    ///
+    /// A stream can only be relayed once; a listener once per connection.
+    pub(super) fn claim(&mut self, fd: u32) -> Result<RelayEnd, Status> {
+        match self {
+            RelayEnd::Stream(stream) => stream
+                .take()
+                .map(|stream| RelayEnd::Stream(Some(stream)))
+                .ok_or_else(|| Status::already_exists(format!("fd {} is already relayed", fd))),
+            RelayEnd::Listener(addr) => Ok(RelayEnd::Listener(*addr)),
+        }
+    }
+}
+
+/// Relay a claimed passed fd for a `RelayFd` call whose first message was
+/// `first`.
+///
+/// Returns the stream of chunks for the host.
+pub(super) async fn relay(
+    end: RelayEnd,
+    first: FdChunk,
+    inbound: Streaming<FdChunk>,
+) -> Result<mpsc::Receiver<Result<FdChunk, Status>>, Status> {
+    match end {
+        RelayEnd::Stream(stream) => {
+            let stream = stream.ok_or_else(|| {
+                Status::already_exists(format!("fd {} is already relayed", first.fd))
+            })?;
+            stream.set_nonblocking(true)?;
+            let stream = tokio::net::UnixStream::from_std(stream)?;
+            Ok(pump(stream, first, inbound))
+        }
+        RelayEnd::Listener(addr) => {
+            let stream = tokio::net::TcpStream::connect(addr).await.map_err(|e| {
+                Status::unavailable(format!("fd {} is not accepting: {}", first.fd, e))
+            })?;
+            Ok(pump(stream, first, inbound))
+        }
+    }
+}
+
+/// Copy bytes between the host (`inbound` / the returned stream) and `io`.
+fn pump<S>(
+    io: S,
+    first: FdChunk,
+    mut inbound: Streaming<FdChunk>,
+) -> mpsc::Receiver<Result<FdChunk, Status>>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (mut reader, mut writer) = tokio::io::split(io);
+    let (tx, rx) = mpsc::channel(8);
+
+    // Host -> process
+    tokio::spawn(async move {
+        let mut chunk = Some(first);
+        while let Some(c) = chunk {
+            if !c.data.is_empty() && writer.write_all(&c.data).await.is_err() {
+                break;
+            }
+            if c.close {
+                break;
+            }
+            chunk = inbound.message().await.ok().flatten();
+        }
+        let _ = writer.shutdown().await;
+    });
+
+    // Process -> host
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; RELAY_CHUNK_SIZE];
+        loop {
+            let chunk = match reader.read(&mut buf).await {
+                Ok(0) | Err(_) => FdChunk {
+                    close: true,
+                    ..Default::default()
+                },
+                Ok(n) => FdChunk {
+                    data: buf[..n].to_vec(),
+                    ..Default::default()
+                },
+            };
+            let close = chunk.close;
+            if tx.send(Ok(chunk)).await.is_err() || close {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    fn passed(fd: u32, kind: PassedFdKind) -> PassedFd {
+        PassedFd {
+            fd,
+            kind: kind as i32,
+        }
+    }
+
+    #[test]
+    fn test_invalid_fds_are_rejected() {
+        assert!(PassedFds::create(&[passed(2, PassedFdKind::Stream)]).is_err());
+        assert!(PassedFds::create(&[
+            passed(3, PassedFdKind::Stream),
+            passed(3, PassedFdKind::Listener),
+        ])
+        .is_err());
+        assert!(PassedFds::create(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stand_ins() {
+        let fds = PassedFds::create(&[
+            passed(3, PassedFdKind::Stream),
+            passed(200, PassedFdKind::Listener),
+        ])
+        .unwrap();
+        for (source, target) in fds.mapping() {
+            assert!(source > 200, "source {} for target {}", source, target);
+        }
+        let process_side = fds.targets[0].0.try_clone().unwrap();
+
+        let mut relays = fds.into_relays();
+        let Some(RelayEnd::Stream(Some(ours))) = relays.get_mut(&3) else {
+            panic!("fd 3 should be a stream");
+        };
+        let mut process_side = UnixStream::from(process_side);
+        process_side.write_all(b"ping").unwrap();
+        let mut buf = [0u8; 4];
+        ours.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let Some(RelayEnd::Listener(addr)) = relays.get(&200) else {
+            panic!("fd 200 should be a listener");
+        };
+        assert!(addr.ip().is_loopback());
+    }
+}
@@ -0,0 +1,264 @@
+//! Checks before spawning: working directory and program.
+//!
+//! A missing workdir or program otherwise fails deep in libcontainer (or
+//! `std::process`) with an error that doesn't say which. Paths are resolved
+//! in the root the process will see, following symlinks within that root:
+//! `/proc/{init}/root` for containers, so their mounts count, `/` otherwise.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::ffi::OsString;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Component, Path, PathBuf};
+
+/// Symlinks followed before giving up (Linux MAXSYMLINKS).
+const MAX_SYMLINKS: usize = 40;
+
+/// Search path when the process has no `PATH`.
+const DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Why a program can't be run, with the exit code shells use for it.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum ProgramError {
+    /// Exit code 127
+    NotFound,
+    /// Exit code 126: found, but a directory or without execute permission
+    NotExecutable,
+}
+
+impl ProgramError {
+    /// `ExecError.reason` sent to the host.
+    pub(super) fn reason(&self) -> &'static str {
+        match self {
+            ProgramError::NotFound => "program_not_found",
+            ProgramError::NotExecutable => "program_not_executable",
+        }
+    }
+
+    /// Message in the style of shells.
+    pub(super) fn message(&self, program: &str) -> String {
+        match self {
+            ProgramError::NotFound => format!("{}: command not found", program),
+            ProgramError::NotExecutable => format!("{}: permission denied", program),
+        }
+    }
+}
+
+/// Check that `workdir` is a directory under `root`, creating it and its
+/// parents if `create` is set, owned by `owner` (uid, gid) so the process
+/// can write to them.
+///
+/// Fails with `WorkdirNotFound` if a component is missing and `create` is
+/// not set. Relative paths are taken from the root, the default cwd.
+pub(super) fn prepare_workdir(
+    root: &Path,
+    workdir: &str,
+    create: bool,
+    owner: (u32, u32),
+) -> BoxliteResult<()> {
+    let path =
+        resolve(root, Path::new(workdir), create.then_some(owner)).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => BoxliteError::WorkdirNotFound(workdir.to_string()),
+            io::ErrorKind::NotADirectory => BoxliteError::InvalidArgument(format!(
+                "working directory {}: not a directory",
+                workdir
+            )),
+            _ => BoxliteError::Internal(format!(
+                "Failed to check working directory {}: {}",
+                workdir, e
+            )),
+        })?;
+    if !path.is_dir() {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "working directory {}: not a directory",
+            workdir
+        )));
+    }
+    Ok(())
+}
+
+/// Find `program` the way `execvp` would: as a path (relative to `cwd`) if
+/// it contains a `/`, else in each directory of `path_var`.
+pub(super) fn check_program(
+    root: &Path,
+    program: &str,
+    cwd: &str,
+    path_var: Option<&str>,
+) -> Result<(), ProgramError> {
+    if program.is_empty() {
+        return Err(ProgramError::NotFound);
+    }
+    if program.contains('/') {
+        return check_executable(root, &Path::new(cwd).join(program));
+    }
+
+    // Like execvp, report a permission problem only if nothing runnable is found
+    let mut error = ProgramError::NotFound;
+    for dir in path_var.unwrap_or(DEFAULT_PATH).split(':') {
+        let dir = if dir.is_empty() { cwd } else { dir };
+        match check_executable(root, &Path::new(dir).join(program)) {
+            Ok(()) => return Ok(()),
+            Err(ProgramError::NotExecutable) => error = ProgramError::NotExecutable,
+            Err(ProgramError::NotFound) => {}
+        }
+    }
+    Err(error)
+}
+
+fn check_executable(root: &Path, path: &Path) -> Result<(), ProgramError> {
+    let meta = resolve(root, path, None)
+        .and_then(std::fs::metadata)
+        .map_err(|_| ProgramError::NotFound)?;
+    if meta.is_dir() || meta.permissions().mode() & 0o111 == 0 {
+        return Err(ProgramError::NotExecutable);
+    }
+    Ok(())
+}
+
+/// Resolve `path` under `root` as the kernel would for a process whose root
+/// is `root`, returning the path to use from here.
+///
+/// Missing components are created as directories owned by `create`'s
+/// (uid, gid) when it is set.
+fn resolve(root: &Path, path: &Path, create: Option<(u32, u32)>) -> io::Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    let mut pending = components(path);
+    let mut symlinks = 0;
+
+    while let Some(name) = pending.pop() {
+        if name == ".." {
+            resolved.pop();
+            continue;
+        }
+
+        let candidate = resolved.join(&name);
+        let host_path = root.join(&candidate);
+        match std::fs::symlink_metadata(&host_path) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                symlinks += 1;
+                if symlinks > MAX_SYMLINKS {
+                    return Err(io::Error::from(nix::errno::Errno::ELOOP));
+                }
+                let target = std::fs::read_link(&host_path)?;
+                if target.is_absolute() {
+                    resolved = PathBuf::new();
+                }
+                pending.extend(components(&target));
+            }
+            Ok(_) => resolved = candidate,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let Some((uid, gid)) = create else {
+                    return Err(e);
+                };
+                match std::fs::create_dir(&host_path) {
+                    Ok(()) => {
+                        std::os::unix::fs::lchown(&host_path, Some(uid), Some(gid))?;
+                        resolved = candidate;
+                    }
+                    // Created by a concurrent execution: look at it again
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => pending.push(name),
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(root.join(resolved))
+}
+
+/// Path components to visit, last first (popped from the end).
+fn components(path: &Path) -> Vec<OsString> {
+    let mut names: Vec<OsString> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_os_string()),
+            Component::ParentDir => Some(OsString::from("..")),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => None,
+        })
+        .collect();
+    names.reverse();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The test process's own uid and gid
+    fn owner() -> (u32, u32) {
+        (
+            nix::unistd::geteuid().as_raw(),
+            nix::unistd::getegid().as_raw(),
+        )
+    }
+
+    #[test]
+    fn test_missing_workdir() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("app")).unwrap();
+
+        prepare_workdir(root.path(), "/app", false, owner()).unwrap();
+        assert!(matches!(
+            prepare_workdir(root.path(), "/app/missing", false, owner()),
+            Err(BoxliteError::WorkdirNotFound(dir)) if dir == "/app/missing"
+        ));
+
+        std::fs::write(root.path().join("file"), "").unwrap();
+        assert!(matches!(
+            prepare_workdir(root.path(), "/file", true, owner()),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_create_follows_symlinks_within_root() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("data")).unwrap();
+        // Absolute target: resolved in the root, not the guest's /
+        std::os::unix::fs::symlink("/data", root.path().join("workspace")).unwrap();
+
+        prepare_workdir(root.path(), "/workspace/b/c", true, owner()).unwrap();
+        assert!(root.path().join("data/b/c").is_dir());
+        prepare_workdir(root.path(), "workspace/./b/../b/c", false, owner()).unwrap();
+    }
+
+    #[test]
+    fn test_created_workdir_is_owned_by_process_user() {
+        use std::os::unix::fs::MetadataExt;
+
+        // Only root can give directories away
+        let expected = if nix::unistd::geteuid().is_root() {
+            (1000, 1000)
+        } else {
+            owner()
+        };
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("home")).unwrap();
+        prepare_workdir(root.path(), "/home/app/src", true, expected).unwrap();
+        for dir in ["home/app", "home/app/src"] {
+            let meta = std::fs::metadata(root.path().join(dir)).unwrap();
+            assert_eq!((meta.uid(), meta.gid()), expected);
+        }
+    }
+
+    #[test]
+    fn test_check_program() {
+        let root = tempfile::tempdir().unwrap();
+        let bin = root.path().join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        std::fs::write(bin.join("tool"), "").unwrap();
+        std::fs::set_permissions(bin.join("tool"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(bin.join("data"), "").unwrap();
+        std::os::unix::fs::symlink("/bin/tool", bin.join("alias")).unwrap();
+
+        let check = |program: &str| check_program(root.path(), program, "/", Some("/usr/bin:/bin"));
+        assert_eq!(check("tool"), Ok(()));
+        assert_eq!(check("alias"), Ok(()));
+        assert_eq!(check("/bin/tool"), Ok(()));
+        assert_eq!(check("bin/tool"), Ok(()));
+        assert_eq!(check("missing"), Err(ProgramError::NotFound));
+        assert_eq!(check("./tool"), Err(ProgramError::NotFound));
+        assert_eq!(check("data"), Err(ProgramError::NotExecutable));
+        assert_eq!(check("/bin"), Err(ProgramError::NotExecutable));
+    }
+}
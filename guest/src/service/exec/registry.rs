@@ -1,15 +1,18 @@
 //! Execution state registry.
 //!
 //! Manages the state of all active executions, providing thread-safe access
-//! to execution metadata, I/O channels, and completion status.
+//! to execution metadata, I/O channels, and completion status. Executions
+//! stay registered after they exit, so any client can still wait on them,
+//! until their retention period runs out.
 
 use crate::service::exec::state::ExecutionState;
 use nix::sys::signal::{kill, Signal};
 use nix::unistd::Pid;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 /// Registry of active executions.
 ///
@@ -43,6 +46,40 @@ impl ExecutionRegistry {
         self.executions.lock().await.insert(exec_id, state);
     }
 
+    /// All executions, running or retained.
+    pub async fn list(&self) -> Vec<(String, ExecutionState)> {
+        self.executions
+            .lock()
+            .await
+            .iter()
+            .map(|(id, state)| (id.clone(), state.clone()))
+            .collect()
+    }
+
+    /// Reap the execution when it exits, then forget it after `retention`.
+    ///
+    /// Without a retention the execution is kept until the agent stops.
+    pub fn retire_on_exit(
+        &self,
+        exec_id: String,
+        state: ExecutionState,
+        retention: Option<Duration>,
+    ) {
+        let registry = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = state.wait_process().await {
+                warn!(execution_id = %exec_id, error = %e, "Failed to reap execution");
+                return;
+            }
+            let Some(retention) = retention else {
+                return;
+            };
+            tokio::time::sleep(retention).await;
+            registry.executions.lock().await.remove(&exec_id);
+            debug!(execution_id = %exec_id, "Execution retention expired");
+        });
+    }
+
     /// Gracefully shutdown all running executions.
     ///
     /// Sends SIGTERM first, waits for exit with timeout, then SIGKILL if needed.
@@ -53,6 +90,10 @@ impl ExecutionRegistry {
         {
             let executions = self.executions.lock().await;
             for (exec_id, state) in executions.iter() {
+                // Pids of reaped executions may have been reused
+                if state.has_exited() {
+                    continue;
+                }
                 if let Some(pid) = state.get_pid().await {
                     let pid_i32 = pid as i32;
                     // Check if process is still alive (signal 0 doesn't send anything)
@@ -1,11 +1,17 @@
-use crate::service::exec::exec_handle::ExecHandle;
-use boxlite_shared::ExecOutput;
+use crate::service::exec::exec_handle::{ExecHandle, ExitStatus};
+use crate::service::exec::limits::{self, OutputBudget};
+use crate::service::exec::output_log::RotatingLog;
+use crate::service::exec::passed_fds::{self, RelayEnd};
+use boxlite_shared::{exec_output, ExecLimit, ExecOutput, ExecutionSummary, FdChunk};
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
 use std::os::unix::io::AsRawFd;
-use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, OnceCell};
 use tokio::task::JoinHandle;
 use tonic::Status;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Abstraction for checking container init health.
 ///
@@ -21,12 +27,40 @@ pub(crate) trait InitHealthCheck: Send + Sync {
     fn diagnose_exit(&mut self) -> String;
 }
 
+/// Output held for the first attach of a recorded execution, so a client
+/// attaching right after Exec doesn't miss the start of the output.
+const RECORDING_BACKLOG_BYTES: usize = 64 * 1024;
+
+/// Forwarding state of a recorded execution.
+struct Recording {
+    /// Attached client, if any.
+    client: Option<mpsc::Sender<Result<ExecOutput, Status>>>,
+    /// Output produced before the first attach (None once attached).
+    backlog: Option<Vec<ExecOutput>>,
+    backlog_bytes: usize,
+    /// Output streams still being recorded.
+    open_streams: usize,
+}
+
+/// What an execution runs and when it started, reported by List.
+#[derive(Default)]
+pub(super) struct ExecutionMeta {
+    pub(super) program: String,
+    pub(super) args: Vec<String>,
+    pub(super) started_at_ms: u64,
+}
+
 /// Inner state that requires synchronization.
 struct Inner {
     /// The process handle (owns pid, pty_controller, stdin, stdout, stderr)
     handle: Option<ExecHandle>,
-    /// Stdout/stderr forwarding tasks (set on attach)
+    /// Stdout/stderr forwarding tasks (set on attach or start_recording)
     output_tasks: Vec<JoinHandle<()>>,
+    /// Set when output is recorded to log files (see start_recording)
+    recording: Option<Recording>,
+    /// Output read for a client that detached before it was sent, delivered
+    /// to the next attach.
+    unsent: Vec<ExecOutput>,
     /// Timeout flag
     #[allow(dead_code)] // Will be used for timeout handling
     timed_out: bool,
@@ -38,10 +72,27 @@ struct Inner {
 /// Execution state.
 ///
 /// Handle owns pid, pty_controller, stdin, stdout, stderr.
-/// stdin is taken on send_input(), stdout/stderr are taken on attach().
+/// stdin is taken on send_input(), stdout/stderr are taken on attach() and
+/// handed back when the attached client disconnects, so detached executions
+/// can be re-attached later.
 #[derive(Clone)]
 pub(crate) struct ExecutionState {
     inner: Arc<Mutex<Inner>>,
+    /// Exit status, reaped once and shared by every Wait call.
+    exit_status: Arc<OnceCell<ExitStatus>>,
+    /// When the process was reaped.
+    exited_at_ms: Arc<OnceLock<u64>>,
+    meta: Arc<ExecutionMeta>,
+    /// Remaining output allowed by `ExecLimits.max_output_bytes`, if set.
+    output_budget: Option<Arc<OutputBudget>>,
+    /// First execution limit that terminated the process.
+    limit_exceeded: Arc<OnceLock<ExecLimit>>,
+    /// `ExecLimits.cpu_secs`, if set.
+    cpu_limit_secs: Option<u64>,
+    /// CPU time the process used, known once it is reaped.
+    cpu_time: Arc<OnceLock<Duration>>,
+    /// Guest side of passed fds, by process fd number.
+    relays: Arc<std::sync::Mutex<HashMap<u32, RelayEnd>>>,
 }
 
 impl ExecutionState {
@@ -50,12 +101,22 @@ impl ExecutionState {
         let inner = Inner {
             handle: Some(handle),
             output_tasks: Vec::new(),
+            recording: None,
+            unsent: Vec::new(),
             timed_out: false,
             init_health: None,
         };
 
         Self {
             inner: Arc::new(Mutex::new(inner)),
+            exit_status: Arc::new(OnceCell::new()),
+            exited_at_ms: Arc::new(OnceLock::new()),
+            meta: Arc::default(),
+            output_budget: None,
+            limit_exceeded: Arc::new(OnceLock::new()),
+            cpu_limit_secs: None,
+            cpu_time: Arc::new(OnceLock::new()),
+            relays: Arc::default(),
         }
     }
 
@@ -70,15 +131,131 @@ impl ExecutionState {
         let inner = Inner {
             handle: Some(handle),
             output_tasks: Vec::new(),
+            recording: None,
+            unsent: Vec::new(),
             timed_out: false,
             init_health: Some(init_health),
         };
 
         Self {
             inner: Arc::new(Mutex::new(inner)),
+            exit_status: Arc::new(OnceCell::new()),
+            exited_at_ms: Arc::new(OnceLock::new()),
+            meta: Arc::default(),
+            output_budget: None,
+            limit_exceeded: Arc::new(OnceLock::new()),
+            cpu_limit_secs: None,
+            cpu_time: Arc::new(OnceLock::new()),
+            relays: Arc::default(),
         }
     }
 
+    /// Record what the execution runs, for List.
+    pub(super) fn with_meta(mut self, meta: ExecutionMeta) -> Self {
+        self.meta = Arc::new(meta);
+        self
+    }
+
+    /// Note the CPU limit applied to the process, to tell its SIGKILL apart.
+    pub(super) fn with_cpu_limit(mut self, cpu_secs: u64) -> Self {
+        self.cpu_limit_secs = Some(cpu_secs);
+        self
+    }
+
+    /// Cut output off, and kill the process, after `max_bytes` of output.
+    pub(super) fn with_output_limit(mut self, max_bytes: u64) -> Self {
+        self.output_budget = Some(Arc::new(OutputBudget::new(max_bytes)));
+        self
+    }
+
+    /// Keep the guest side of the execution's passed fds for `relay_fd`.
+    pub(super) fn with_relays(mut self, relays: HashMap<u32, RelayEnd>) -> Self {
+        self.relays = Arc::new(std::sync::Mutex::new(relays));
+        self
+    }
+
+    /// Relay a passed fd to the host.
+    ///
+    /// `first` is the first message of the `RelayFd` call and names the fd.
+    pub(super) async fn relay_fd(
+        &self,
+        first: FdChunk,
+        inbound: tonic::Streaming<FdChunk>,
+    ) -> Result<mpsc::Receiver<Result<FdChunk, Status>>, Status> {
+        let end = {
+            let mut relays = self.relays.lock().unwrap_or_else(|e| e.into_inner());
+            relays
+                .get_mut(&first.fd)
+                .ok_or_else(|| Status::not_found(format!("fd {} was not passed", first.fd)))?
+                .claim(first.fd)?
+        };
+        passed_fds::relay(end, first, inbound).await
+    }
+
+    /// Record that an execution limit terminated the process.
+    ///
+    /// Only the first limit is kept.
+    pub(super) fn record_limit(&self, limit: ExecLimit) {
+        let _ = self.limit_exceeded.set(limit);
+    }
+
+    /// Execution limit that terminated the process, if any.
+    pub(super) fn limit_exceeded(&self) -> Option<ExecLimit> {
+        self.limit_exceeded.get().copied()
+    }
+
+    /// Whether the reaped process used up its CPU limit.
+    pub(super) fn hit_cpu_limit(&self) -> bool {
+        match (self.cpu_limit_secs, self.cpu_time.get()) {
+            (Some(cpu_secs), Some(used)) => limits::hit_cpu_limit(*used, cpu_secs),
+            _ => false,
+        }
+    }
+
+    /// Whether the process has been reaped.
+    pub(super) fn has_exited(&self) -> bool {
+        self.exit_status.initialized()
+    }
+
+    /// Status of the execution as reported by List.
+    pub(super) async fn summary(&self, execution_id: String) -> ExecutionSummary {
+        let (exit_code, signal) = match self.exit_status.get() {
+            Some(ExitStatus::Code(code)) => (*code, 0),
+            Some(ExitStatus::Signal(sig)) => (0, *sig as i32),
+            None => (0, 0),
+        };
+        ExecutionSummary {
+            execution_id,
+            program: self.meta.program.clone(),
+            args: self.meta.args.clone(),
+            pid: self.get_pid().await.unwrap_or(0),
+            started_at_ms: self.meta.started_at_ms,
+            running: !self.has_exited(),
+            exit_code,
+            signal,
+            exited_at_ms: self.exited_at_ms.get().copied().unwrap_or(0),
+        }
+    }
+
+    /// Apply the output limit to a chunk of output.
+    ///
+    /// Returns the part of the chunk that may be forwarded, or None once the
+    /// budget is spent. The process is killed when its output is cut off.
+    async fn limit_output(&self, mut chunk: Vec<u8>) -> Option<Vec<u8>> {
+        let Some(budget) = &self.output_budget else {
+            return Some(chunk);
+        };
+        let (granted, exceeded) = budget.take(chunk.len());
+        if exceeded {
+            chunk.truncate(granted);
+            if self.limit_exceeded().is_none() {
+                self.record_limit(ExecLimit::Output);
+                self.kill(nix::sys::signal::Signal::SIGKILL).await;
+            }
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    }
+
     /// Check if the container init process died.
     ///
     /// Returns `Some(diagnosis)` if init is dead, `None` if alive or no health checker.
@@ -93,7 +270,6 @@ impl ExecutionState {
     }
 
     /// Get PID for execution.
-    #[allow(dead_code)] // API completeness
     pub async fn get_pid(&self) -> Option<u32> {
         let inner = self.inner.lock().await;
         inner.handle.as_ref().map(|h| h.pid().as_raw() as u32)
@@ -154,12 +330,18 @@ impl ExecutionState {
 
     /// Wait for process to exit.
     ///
-    /// Gets pid from handle and waits using waitpid.
-    pub async fn wait_process(
-        &self,
-    ) -> Result<crate::service::exec::exec_handle::ExitStatus, Status> {
-        use crate::service::exec::exec_handle::ExitStatus;
-        use nix::sys::wait::{waitpid, WaitStatus};
+    /// The first caller reaps the process with waitpid; concurrent and later
+    /// callers get the same cached status.
+    pub async fn wait_process(&self) -> Result<ExitStatus, Status> {
+        self.exit_status
+            .get_or_try_init(|| self.reap())
+            .await
+            .copied()
+    }
+
+    /// Reap the process (gets pid from handle and waits using wait4).
+    async fn reap(&self) -> Result<ExitStatus, Status> {
+        use nix::sys::wait::WaitStatus;
 
         // Get pid from handle
         let pid = {
@@ -172,38 +354,65 @@ impl ExecutionState {
         };
 
         // Wait for process (blocking call in spawn_blocking)
-        let result = tokio::task::spawn_blocking(move || waitpid(pid, None))
+        let (result, cpu_time) = tokio::task::spawn_blocking(move || wait_with_cpu_time(pid))
             .await
             .map_err(|e| Status::internal(format!("spawn_blocking failed: {}", e)))?
-            .map_err(|e| Status::internal(format!("waitpid failed: {}", e)))?;
-
-        match result {
-            WaitStatus::Exited(_, code) => Ok(ExitStatus::Code(code)),
-            WaitStatus::Signaled(_, sig, _) => Ok(ExitStatus::Signal(sig)),
-            other => Err(Status::internal(format!(
-                "Unexpected wait status: {:?}",
-                other
-            ))),
-        }
+            .map_err(|e| Status::internal(format!("wait4 failed: {}", e)))?;
+        let _ = self.cpu_time.set(cpu_time);
+
+        let status = match result {
+            WaitStatus::Exited(_, code) => ExitStatus::Code(code),
+            WaitStatus::Signaled(_, sig, _) => ExitStatus::Signal(sig),
+            other => {
+                return Err(Status::internal(format!(
+                    "Unexpected wait status: {:?}",
+                    other
+                )))
+            }
+        };
+        let _ = self.exited_at_ms.set(super::now_ms());
+        Ok(status)
     }
 
     /// Attach to execution output.
     ///
     /// Takes stdout/stderr from handle and starts forwarding tasks.
-    /// Returns stream of output chunks.
+    /// Returns stream of output chunks. Only one client can be attached at a
+    /// time; when it goes away the streams are returned to the handle.
+    ///
+    /// For recorded executions the recording tasks keep the streams and the
+    /// client is registered as their sink instead.
     pub async fn attach(
         &self,
         exec_id: &str,
     ) -> Result<mpsc::Receiver<Result<ExecOutput, Status>>, Status> {
-        use boxlite_shared::{exec_output, Stderr, Stdout};
-        use futures::StreamExt;
-
-        let (tx, rx) = mpsc::channel(100);
+        use boxlite_shared::{Stderr, Stdout};
 
         // Take stdout/stderr from handle
-        let (stdout, stderr) = {
+        let (tx, rx, stdout, stderr) = {
             let mut inner = self.inner.lock().await;
 
+            if let Some(recording) = inner.recording.as_mut() {
+                if recording
+                    .client
+                    .as_ref()
+                    .is_some_and(|client| !client.is_closed())
+                {
+                    return Err(Status::already_exists("Already attached"));
+                }
+
+                let backlog = recording.backlog.take().unwrap_or_default();
+                let (tx, rx) = mpsc::channel(100 + backlog.len());
+                for msg in backlog {
+                    let _ = tx.try_send(Ok(msg));
+                }
+                // Once all output has ended, dropping tx ends the stream
+                recording.client = (recording.open_streams > 0).then_some(tx);
+                return Ok(rx);
+            }
+
+            // Forwarding tasks end as soon as their client goes away
+            inner.output_tasks.retain(|task| !task.is_finished());
             if !inner.output_tasks.is_empty() {
                 return Err(Status::already_exists("Already attached"));
             }
@@ -212,50 +421,36 @@ impl ExecutionState {
                 .handle
                 .as_mut()
                 .ok_or_else(|| Status::failed_precondition("Handle not available"))?;
-
             let stdout = handle.stdout();
             let stderr = handle.stderr();
 
-            (stdout, stderr)
+            let unsent = std::mem::take(&mut inner.unsent);
+            let (tx, rx) = mpsc::channel(100 + unsent.len());
+            for msg in unsent {
+                let _ = tx.try_send(Ok(msg));
+            }
+
+            (tx, rx, stdout, stderr)
         };
 
-        // Spawn forwarding tasks
         let mut tasks = Vec::new();
-
-        // Spawn stdout forwarding task
-        let exec_id_string = exec_id.to_string();
-        if let Some(mut stdout) = stdout {
-            let tx = tx.clone();
-            let handle = tokio::spawn(async move {
-                while let Some(chunk) = stdout.next().await {
-                    let msg = ExecOutput {
-                        event: Some(exec_output::Event::Stdout(Stdout { data: chunk })),
-                    };
-                    if tx.send(Ok(msg)).await.is_err() {
-                        break;
-                    }
-                }
-                info!(execution = ?exec_id_string, "Stdout forwarding task ended");
-            });
-            tasks.push(handle);
+        if let Some(stdout) = stdout {
+            tasks.push(self.spawn_forwarder(
+                exec_id,
+                stdout,
+                tx.clone(),
+                |data| exec_output::Event::Stdout(Stdout { data }),
+                ExecHandle::restore_stdout,
+            ));
         }
-
-        // Spawn stderr forwarding task
-        let exec_id_string = exec_id.to_string();
-        if let Some(mut stderr) = stderr {
-            let tx = tx.clone();
-            let handle = tokio::spawn(async move {
-                while let Some(chunk) = stderr.next().await {
-                    let msg = ExecOutput {
-                        event: Some(exec_output::Event::Stderr(Stderr { data: chunk })),
-                    };
-                    if tx.send(Ok(msg)).await.is_err() {
-                        break;
-                    }
-                }
-                info!(execution = ?exec_id_string, "Stderr forwarding task ended");
-            });
-            tasks.push(handle);
+        if let Some(stderr) = stderr {
+            tasks.push(self.spawn_forwarder(
+                exec_id,
+                stderr,
+                tx,
+                |data| exec_output::Event::Stderr(Stderr { data }),
+                ExecHandle::restore_stderr,
+            ));
         }
 
         // Store tasks
@@ -267,10 +462,191 @@ impl ExecutionState {
         Ok(rx)
     }
 
+    /// Forward an output stream to an attached client.
+    ///
+    /// When the client goes away the stream is handed back to the handle, and
+    /// a chunk that could not be sent is kept for the next attach.
+    fn spawn_forwarder<S>(
+        &self,
+        exec_id: &str,
+        mut stream: S,
+        tx: mpsc::Sender<Result<ExecOutput, Status>>,
+        event: fn(Vec<u8>) -> exec_output::Event,
+        restore: fn(&mut ExecHandle, S),
+    ) -> JoinHandle<()>
+    where
+        S: Stream<Item = Vec<u8>> + Unpin + Send + 'static,
+    {
+        let state = self.clone();
+        let exec_id = exec_id.to_string();
+        tokio::spawn(async move {
+            loop {
+                // Watch for the client going away even while the process is quiet
+                let next = tokio::select! {
+                    chunk = stream.next() => Some(chunk),
+                    _ = tx.closed() => None,
+                };
+                let Some(chunk) = next else {
+                    state.detach(stream, restore, None).await;
+                    break;
+                };
+                let Some(chunk) = chunk else {
+                    break;
+                };
+                let Some(chunk) = state.limit_output(chunk).await else {
+                    continue;
+                };
+                let msg = ExecOutput {
+                    event: Some(event(chunk)),
+                };
+                if let Err(mpsc::error::SendError(Ok(msg))) = tx.send(Ok(msg)).await {
+                    state.detach(stream, restore, Some(msg)).await;
+                    break;
+                }
+            }
+            info!(execution = ?exec_id, "Output forwarding task ended");
+        })
+    }
+
+    /// Keep a stream, and its unsent chunk, for the next attach.
+    async fn detach<S>(
+        &self,
+        stream: S,
+        restore: fn(&mut ExecHandle, S),
+        unsent: Option<ExecOutput>,
+    ) {
+        let mut inner = self.inner.lock().await;
+        inner.unsent.extend(unsent);
+        if let Some(handle) = inner.handle.as_mut() {
+            restore(handle, stream);
+        }
+    }
+
+    /// Record output to log files.
+    ///
+    /// Takes stdout/stderr from handle and starts tasks that append every chunk
+    /// to its log and forward it to the attached client, if any. Output keeps
+    /// flowing while nobody is attached.
+    pub async fn start_recording(
+        &self,
+        exec_id: &str,
+        stdout_log: RotatingLog,
+        stderr_log: RotatingLog,
+    ) -> Result<(), Status> {
+        use boxlite_shared::{Stderr, Stdout};
+
+        let (stdout, stderr) = {
+            let mut inner = self.inner.lock().await;
+            let handle = inner
+                .handle
+                .as_mut()
+                .ok_or_else(|| Status::failed_precondition("Handle not available"))?;
+            let streams = (handle.stdout(), handle.stderr());
+            inner.recording = Some(Recording {
+                client: None,
+                backlog: Some(Vec::new()),
+                backlog_bytes: 0,
+                open_streams: streams.0.is_some() as usize + streams.1.is_some() as usize,
+            });
+            streams
+        };
+
+        let mut tasks = Vec::new();
+        if let Some(stdout) = stdout {
+            tasks.push(self.spawn_recorder(exec_id, stdout, stdout_log, |data| {
+                exec_output::Event::Stdout(Stdout { data })
+            }));
+        }
+        // PTY mode merges stderr into stdout; stderr.log stays empty
+        if let Some(stderr) = stderr {
+            tasks.push(self.spawn_recorder(exec_id, stderr, stderr_log, |data| {
+                exec_output::Event::Stderr(Stderr { data })
+            }));
+        }
+
+        self.inner.lock().await.output_tasks = tasks;
+        Ok(())
+    }
+
+    fn spawn_recorder<S>(
+        &self,
+        exec_id: &str,
+        mut stream: S,
+        mut log: RotatingLog,
+        event: fn(Vec<u8>) -> exec_output::Event,
+    ) -> JoinHandle<()>
+    where
+        S: Stream<Item = Vec<u8>> + Unpin + Send + 'static,
+    {
+        let state = self.clone();
+        let exec_id = exec_id.to_string();
+        tokio::spawn(async move {
+            while let Some(chunk) = stream.next().await {
+                let Some(chunk) = state.limit_output(chunk).await else {
+                    continue;
+                };
+                if let Err(e) = log.write(&chunk) {
+                    warn!(execution_id = %exec_id, error = %e, "Failed to write output log");
+                }
+
+                let len = chunk.len();
+                let msg = ExecOutput {
+                    event: Some(event(chunk)),
+                };
+                let client = {
+                    let mut inner = state.inner.lock().await;
+                    let Some(recording) = inner.recording.as_mut() else {
+                        break;
+                    };
+                    match (&recording.client, &mut recording.backlog) {
+                        (Some(client), _) => Some((client.clone(), msg)),
+                        (None, Some(backlog))
+                            if recording.backlog_bytes + len <= RECORDING_BACKLOG_BYTES =>
+                        {
+                            backlog.push(msg);
+                            recording.backlog_bytes += len;
+                            None
+                        }
+                        _ => None,
+                    }
+                };
+
+                if let Some((client, msg)) = client {
+                    if client.send(Ok(msg)).await.is_err() {
+                        // Client detached: keep recording until the next attach
+                        let mut inner = state.inner.lock().await;
+                        if let Some(recording) = inner.recording.as_mut() {
+                            if recording
+                                .client
+                                .as_ref()
+                                .is_some_and(|current| current.same_channel(&client))
+                            {
+                                recording.client = None;
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Last stream to end closes the attached client's stream
+            if let Some(recording) = state.inner.lock().await.recording.as_mut() {
+                recording.open_streams -= 1;
+                if recording.open_streams == 0 {
+                    recording.client = None;
+                }
+            }
+            info!(execution = ?exec_id, "Output recording task ended");
+        })
+    }
+
     /// Kill process with signal.
     ///
     /// Returns true if signal was sent, false if already exited.
     pub async fn kill(&self, signal: nix::sys::signal::Signal) -> bool {
+        // Once reaped, the pid may belong to another process
+        if self.has_exited() {
+            return false;
+        }
         let inner = self.inner.lock().await;
 
         if let Some(ref handle) = inner.handle {
@@ -319,3 +695,65 @@ impl ExecutionState {
         Ok(())
     }
 }
+
+/// Wait for `pid` like waitpid, also returning the CPU time it used.
+fn wait_with_cpu_time(
+    pid: nix::unistd::Pid,
+) -> nix::Result<(nix::sys::wait::WaitStatus, Duration)> {
+    let mut status = 0;
+    let mut usage = std::mem::MaybeUninit::<nix::libc::rusage>::zeroed();
+    let ret = unsafe { nix::libc::wait4(pid.as_raw(), &mut status, 0, usage.as_mut_ptr()) };
+    if ret == -1 {
+        return Err(nix::errno::Errno::last());
+    }
+    let usage = unsafe { usage.assume_init() };
+    let time = |tv: nix::libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    let status = nix::sys::wait::WaitStatus::from_raw(pid, status)?;
+    Ok((status, time(usage.ru_utime) + time(usage.ru_stime)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::unistd::{pipe, Pid};
+    use std::io::Write;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_reattach_quiet_execution() {
+        let (stdin, _stdin_write) = pipe().unwrap();
+        let (stdout, stdout_write) = pipe().unwrap();
+        let handle = ExecHandle::new(Pid::this(), stdin, stdout, None);
+        let state = ExecutionState::new(handle);
+
+        // Detach before the process prints anything
+        let first = state.attach("exec").await.unwrap();
+        drop(first);
+
+        let mut second = None;
+        for _ in 0..50 {
+            match state.attach("exec").await {
+                Ok(rx) => {
+                    second = Some(rx);
+                    break;
+                }
+                Err(status) => {
+                    assert_eq!(status.code(), tonic::Code::AlreadyExists);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }
+        }
+        let mut second = second.expect("quiet execution should be re-attachable");
+
+        std::fs::File::from(stdout_write)
+            .write_all(b"hello")
+            .unwrap();
+        let msg = second.recv().await.unwrap().unwrap();
+        match msg.event {
+            Some(exec_output::Event::Stdout(out)) => assert_eq!(out.data, b"hello"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
@@ -2,11 +2,13 @@
 //! Files service implementation.
 //!
 //! Provides tar-based upload/download between host and the single container
-//! running inside the guest.
+//! running inside the guest, and the listing and removal used by workspace
+//! sync.
 
 use crate::service::server::GuestServer;
 use boxlite_shared::{
-    files_server::Files, DownloadChunk, DownloadRequest, UploadChunk, UploadResponse,
+    files_server::Files, DownloadChunk, DownloadRequest, ListTreeRequest, ListTreeResponse,
+    RemoveRequest, RemoveResponse, TreeEntry, UploadChunk, UploadResponse,
 };
 use std::path::{Path, PathBuf};
 use tokio::fs::File;
@@ -18,6 +20,7 @@ use tracing::info;
 
 const CHUNK_SIZE: usize = 1 << 20; // 1 MiB
 const MAX_UPLOAD_BYTES: u64 = 512 * 1024 * 1024; // 512 MiB safety cap
+const LIST_TREE_BATCH: usize = 512; // entries per message, well under 4 MiB
 
 #[tonic::async_trait]
 impl Files for GuestServer {
@@ -255,6 +258,106 @@ impl Files for GuestServer {
 
         Ok(Response::new(ReceiverStream::new(rx)))
     }
+
+    type ListTreeStream = ReceiverStream<Result<ListTreeResponse, Status>>;
+
+    async fn list_tree(
+        &self,
+        request: Request<ListTreeRequest>,
+    ) -> Result<Response<Self::ListTreeStream>, Status> {
+        let req = request.into_inner();
+        let container_id = self
+            .resolve_container_id(req.container_id.as_str())
+            .await
+            .map_err(Status::failed_precondition)?;
+        let root = self.container_rootfs(&container_id, &req.path)?;
+
+        // Stream the listing in batches; the last one is sent even if empty
+        let (tx, rx) = mpsc::channel::<Result<ListTreeResponse, Status>>(4);
+        tokio::task::spawn_blocking(move || {
+            let send = |batch: &mut Vec<TreeEntry>| {
+                let entries = std::mem::replace(batch, Vec::with_capacity(LIST_TREE_BATCH));
+                tx.blocking_send(Ok(ListTreeResponse { entries }))
+                    .map_err(|_| "client went away".to_string())
+            };
+            let mut batch = Vec::with_capacity(LIST_TREE_BATCH);
+            let result = match std::fs::metadata(&root) {
+                Ok(metadata) if metadata.is_dir() => {
+                    use std::os::unix::fs::MetadataExt;
+
+                    list_tree_recursive(&root, &root, metadata.dev(), &mut |entry| {
+                        batch.push(entry);
+                        if batch.len() < LIST_TREE_BATCH {
+                            return Ok(());
+                        }
+                        send(&mut batch)
+                    })
+                    .and_then(|()| send(&mut batch))
+                }
+                _ => send(&mut batch),
+            };
+            if let Err(e) = result {
+                let _ = tx.blocking_send(Err(Status::internal(e)));
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn remove(
+        &self,
+        request: Request<RemoveRequest>,
+    ) -> Result<Response<RemoveResponse>, Status> {
+        let req = request.into_inner();
+        let container_id = self
+            .resolve_container_id(req.container_id.as_str())
+            .await
+            .map_err(Status::failed_precondition)?;
+        let root = self.container_rootfs(&container_id, &req.path)?;
+
+        let mut targets = Vec::with_capacity(req.paths.len());
+        for path in &req.paths {
+            let rel = Path::new(path);
+            if rel.is_absolute()
+                || rel
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                return Err(Status::invalid_argument(format!(
+                    "path must be relative without ..: {}",
+                    path
+                )));
+            }
+            targets.push(root.join(rel));
+        }
+
+        let count = targets.len();
+        tokio::task::spawn_blocking(move || {
+            for target in targets {
+                // A symlinked directory could point anywhere in the guest
+                if has_symlinked_parent(&target, &root) {
+                    continue;
+                }
+                match std::fs::remove_file(&target) {
+                    Ok(()) => remove_empty_parents(&target, &root),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(format!("remove {}: {}", target.display(), e)),
+                }
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| Status::internal(format!("task join error: {}", e)))?
+        .map_err(Status::internal)?;
+
+        info!(
+            path = %req.path,
+            count,
+            container_id = %container_id,
+            "removed paths"
+        );
+        Ok(Response::new(RemoveResponse {}))
+    }
 }
 
 impl GuestServer {
@@ -337,3 +440,136 @@ fn append_dir_recursive(
     }
     Ok(())
 }
+
+/// Pass the files and symlinks under `dir` to `emit`, with paths relative to
+/// `root`. Directories on another device than `dev` are mounts and skipped.
+fn list_tree_recursive(
+    root: &Path,
+    dir: &Path,
+    dev: u64,
+    emit: &mut dyn FnMut(TreeEntry) -> Result<(), String>,
+) -> Result<(), String> {
+    use std::os::unix::fs::MetadataExt;
+
+    for entry in std::fs::read_dir(dir).map_err(|e| format!("read_dir {}: {}", dir.display(), e))? {
+        let path = entry.map_err(|e| format!("readdir: {}", e))?.path();
+        let metadata = std::fs::symlink_metadata(&path)
+            .map_err(|e| format!("stat {}: {}", path.display(), e))?;
+        let file_type = metadata.file_type();
+        if file_type.is_dir() {
+            // Overlay files can report their layer's device, directories
+            // always report the overlay's, so only directories tell mounts
+            if metadata.dev() == dev {
+                list_tree_recursive(root, &path, dev, emit)?;
+            }
+            continue;
+        }
+        if !file_type.is_file() && !file_type.is_symlink() {
+            continue;
+        }
+        let link_target = if file_type.is_symlink() {
+            let target = std::fs::read_link(&path)
+                .map_err(|e| format!("readlink {}: {}", path.display(), e))?;
+            Some(target.to_string_lossy().into_owned())
+        } else {
+            None
+        };
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        emit(TreeEntry {
+            path: rel.to_string_lossy().into_owned(),
+            size: metadata.len(),
+            mtime: metadata.mtime(),
+            link_target,
+            mode: Some(metadata.mode() & 0o7777),
+            uid: Some(metadata.uid()),
+            gid: Some(metadata.gid()),
+        })?;
+    }
+    Ok(())
+}
+
+/// Whether a directory between `root` and `path` is a symlink (or gone).
+fn has_symlinked_parent(path: &Path, root: &Path) -> bool {
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    let Ok(rel) = parent.strip_prefix(root) else {
+        return true;
+    };
+    let mut dir = root.to_path_buf();
+    for component in rel.components() {
+        dir.push(component);
+        match std::fs::symlink_metadata(&dir) {
+            Ok(metadata) if metadata.file_type().is_dir() => {}
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// Remove the directories above `path` that are left empty, up to `root`.
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn test_list_tree_and_remove_empty_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/b/file"), "data").unwrap();
+        std::os::unix::fs::symlink("b/file", root.join("a/link")).unwrap();
+
+        let dev = std::fs::metadata(root).unwrap().dev();
+        let mut entries = Vec::new();
+        list_tree_recursive(root, root, dev, &mut |entry| {
+            entries.push(entry);
+            Ok(())
+        })
+        .unwrap();
+        entries.sort_by(|x, y| x.path.cmp(&y.path));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "a/b/file");
+        assert_eq!(entries[0].size, 4);
+        assert_eq!(entries[0].uid, Some(std::fs::metadata(root).unwrap().uid()));
+        assert_eq!(entries[1].link_target.as_deref(), Some("b/file"));
+
+        // Nothing is listed from another device
+        entries.clear();
+        list_tree_recursive(root, root, dev + 1, &mut |entry| {
+            entries.push(entry);
+            Ok(())
+        })
+        .unwrap();
+        assert!(entries.is_empty());
+
+        std::fs::remove_file(root.join("a/b/file")).unwrap();
+        remove_empty_parents(&root.join("a/b/file"), root);
+        assert!(!root.join("a/b").exists());
+        assert!(root.join("a").exists());
+    }
+
+    #[test]
+    fn test_has_symlinked_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::os::unix::fs::symlink("/etc", root.join("a/etc")).unwrap();
+
+        assert!(!has_symlinked_parent(&root.join("a/b/file"), root));
+        assert!(!has_symlinked_parent(&root.join("file"), root));
+        assert!(has_symlinked_parent(&root.join("a/etc/passwd"), root));
+        assert!(has_symlinked_parent(&root.join("missing/file"), root));
+    }
+}
@@ -1,15 +1,17 @@
 //! Guest service implementation.
 //!
-//! Handles guest initialization and management (Init, Ping, Shutdown RPCs).
+//! Handles guest initialization and management (Init, Ping, Shutdown, Logs, NetConnections RPCs).
 
 use crate::service::server::GuestServer;
+use boxlite_shared::constants::capabilities;
 use boxlite_shared::{
     guest_init_response, Guest as GuestService, GuestInitError, GuestInitRequest,
-    GuestInitResponse, GuestInitSuccess, PingRequest, PingResponse, ShutdownRequest,
+    GuestInitResponse, GuestInitSuccess, GuestLogsRequest, GuestLogsResponse,
+    NetConnectionsRequest, NetConnectionsResponse, PingRequest, PingResponse, ShutdownRequest,
     ShutdownResponse,
 };
 use tonic::{Request, Response, Status};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 #[tonic::async_trait]
 impl GuestService for GuestServer {
@@ -17,7 +19,9 @@ impl GuestService for GuestServer {
     ///
     /// This must be called first after connection. It:
     /// 1. Mounts all volumes (virtiofs + block devices)
-    /// 2. Configures network (if specified)
+    /// 2. Configures network (if specified) and starts the DNS resolver
+    /// 3. Sets kernel parameters (sysctls)
+    /// 4. Enables swap (if specified)
     ///
     /// Note: Rootfs setup is handled by Container.Init.
     async fn init(
@@ -67,6 +71,44 @@ impl GuestService for GuestServer {
                     })),
                 }));
             }
+
+            // Containers fall back to the gateway's DNS server without it
+            if let Err(e) = self.resolver.start().await {
+                warn!(
+                    "Failed to start DNS resolver, network peer names won't resolve: {}",
+                    e
+                );
+            }
+        }
+
+        // Step 3: Set kernel parameters
+        if !req.sysctls.is_empty() {
+            info!("Setting {} sysctls", req.sysctls.len());
+            if let Err(e) = crate::sysctl::apply(&req.sysctls) {
+                error!("Failed to set sysctls: {}", e);
+                return Ok(Response::new(GuestInitResponse {
+                    result: Some(guest_init_response::Result::Error(GuestInitError {
+                        reason: format!("Failed to set sysctls: {}", e),
+                    })),
+                }));
+            }
+        }
+
+        // Step 4: Enable swap
+        if let Some(swap) = req.swap {
+            info!(
+                "Enabling {} MiB of swap (zram: {})",
+                swap.size_bytes / (1024 * 1024),
+                swap.zram
+            );
+            if let Err(e) = crate::swap::enable(swap.size_bytes, swap.zram) {
+                error!("Failed to enable swap: {}", e);
+                return Ok(Response::new(GuestInitResponse {
+                    result: Some(guest_init_response::Result::Error(GuestInitError {
+                        reason: format!("Failed to enable swap: {}", e),
+                    })),
+                }));
+            }
         }
 
         // Mark as initialized
@@ -82,6 +124,7 @@ impl GuestService for GuestServer {
         debug!("Received ping request");
         Ok(Response::new(PingResponse {
             version: env!("CARGO_PKG_VERSION").to_string(),
+            capabilities: capabilities::ALL.iter().map(|c| c.to_string()).collect(),
         }))
     }
 
@@ -119,4 +162,25 @@ impl GuestService for GuestServer {
         info!("Graceful shutdown complete");
         Ok(Response::new(ShutdownResponse {}))
     }
+
+    async fn logs(
+        &self,
+        request: Request<GuestLogsRequest>,
+    ) -> Result<Response<GuestLogsResponse>, Status> {
+        let tail = request.into_inner().tail as usize;
+        Ok(Response::new(GuestLogsResponse {
+            lines: self.log_buffer.tail(tail),
+        }))
+    }
+
+    async fn net_connections(
+        &self,
+        _request: Request<NetConnectionsRequest>,
+    ) -> Result<Response<NetConnectionsResponse>, Status> {
+        let connections = tokio::task::spawn_blocking(crate::net_connections::list)
+            .await
+            .map_err(|e| Status::internal(format!("connection listing panicked: {}", e)))?
+            .map_err(|e| Status::internal(format!("failed to list connections: {}", e)))?;
+        Ok(Response::new(NetConnectionsResponse { connections }))
+    }
 }
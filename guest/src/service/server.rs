@@ -1,5 +1,7 @@
 use crate::container::Container;
+use crate::dns::Resolver;
 use crate::layout::GuestLayout;
+use crate::log_buffer::LogBuffer;
 use crate::service::exec::registry::ExecutionRegistry;
 use boxlite_shared::{BoxliteResult, Transport};
 use std::collections::HashMap;
@@ -35,6 +37,13 @@ pub(crate) struct GuestServer {
 
     /// Execution registry for tracking running executions
     pub registry: ExecutionRegistry,
+
+    /// Recent agent log lines (served by Guest.Logs)
+    pub log_buffer: LogBuffer,
+
+    /// DNS resolver for network peer names (started by Guest.Init,
+    /// names set by Container.UpdateHosts)
+    pub resolver: Resolver,
 }
 
 impl GuestServer {
@@ -42,12 +51,14 @@ impl GuestServer {
     ///
     /// Server starts uninitialized. Guest.Init must be called first to setup
     /// the environment, then Container.Init to start the container.
-    pub fn new(layout: GuestLayout) -> Self {
+    pub fn new(layout: GuestLayout, log_buffer: LogBuffer) -> Self {
         Self {
             layout,
             init_state: Arc::new(Mutex::new(GuestInitState::default())),
             containers: Arc::new(Mutex::new(HashMap::new())),
             registry: ExecutionRegistry::new(),
+            log_buffer,
+            resolver: Resolver::new(),
         }
     }
 
@@ -69,7 +69,11 @@ impl BlockDeviceMount {
         // - MS_NODIRATIME: Don't update directory access times
         // These flags significantly reduce I/O overhead, especially for read-heavy
         // workloads. Access time tracking is rarely needed in container contexts.
-        let mount_flags = MsFlags::MS_NOATIME | MsFlags::MS_NODIRATIME;
+        let mut mount_flags = MsFlags::MS_NOATIME | MsFlags::MS_NODIRATIME;
+        if filesystem == Filesystem::Erofs {
+            // erofs is read-only; the device is attached read-only too
+            mount_flags |= MsFlags::MS_RDONLY;
+        }
 
         // Mount using nix
         mount(
@@ -94,7 +98,9 @@ impl BlockDeviceMount {
         }
 
         // Fix ownership if needed (fallback in case debugfs didn't run on host)
-        super::perms::OwnershipFixer::fix_if_needed(mount_point)?;
+        if filesystem != Filesystem::Erofs {
+            super::perms::OwnershipFixer::fix_if_needed(mount_point)?;
+        }
 
         tracing::info!(
             "Mounted block device: {} → {}",
@@ -264,6 +270,7 @@ impl BlockDeviceMount {
 fn filesystem_to_str(fs: Filesystem) -> &'static str {
     match fs {
         Filesystem::Ext4 => "ext4",
+        Filesystem::Erofs => "erofs",
         Filesystem::Unspecified => "ext4", // Default to ext4
     }
 }
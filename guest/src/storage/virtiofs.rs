@@ -4,6 +4,7 @@ use std::path::Path;
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use nix::mount::{mount, MsFlags};
+use nix::sys::statvfs::{statvfs, FsFlags};
 
 pub struct VirtiofsMount;
 
@@ -47,6 +48,10 @@ impl VirtiofsMount {
             ))
         })?;
 
+        if read_only {
+            Self::verify_read_only(tag, mount_point)?;
+        }
+
         tracing::info!(
             "Mounted virtiofs: {} → {} ({})",
             tag,
@@ -55,4 +60,24 @@ impl VirtiofsMount {
         );
         Ok(())
     }
+
+    /// Check the kernel reports the mount read-only, so a volume asked to
+    /// be read-only is never silently writable.
+    fn verify_read_only(tag: &str, mount_point: &Path) -> BoxliteResult<()> {
+        let stat = statvfs(mount_point).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to stat mount {}: {}",
+                mount_point.display(),
+                e
+            ))
+        })?;
+        if !stat.flags().contains(FsFlags::ST_RDONLY) {
+            return Err(BoxliteError::Storage(format!(
+                "Read-only volume {} is mounted writable at {}",
+                tag,
+                mount_point.display()
+            )));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,226 @@
+//! Swap space for the VM
+//!
+//! Enabled during guest init when the box has `swap_mib`, either as a swap
+//! file on the guest rootfs or as a zram device (compressed RAM). The swap
+//! header is written here so the guest rootfs doesn't need `mkswap`.
+//!
+//! Swap applies to the whole VM: the container's cgroup leaves
+//! `memory.swap.max` unlimited, so it can use all of it. The host watches
+//! the swap-out counter reported here to warn about heavy swapping.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use nix::fcntl::{fallocate, FallocateFlags};
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+/// Swap file on the guest rootfs
+const SWAP_FILE: &str = "/swapfile";
+
+/// zram device used for swap
+const ZRAM_DEVICE: &str = "/dev/zram0";
+
+/// zram sysfs directory
+const ZRAM_SYSFS: &str = "/sys/block/zram0";
+
+/// Signature at the end of the first page (swap header version 1)
+const SWAP_MAGIC: &[u8] = b"SWAPSPACE2";
+
+/// VM-wide swap counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SwapStats {
+    /// SwapTotal minus SwapFree
+    pub used_bytes: u64,
+    /// Bytes swapped out since boot
+    pub out_bytes: u64,
+}
+
+/// Enable `size_bytes` of swap.
+pub fn enable(size_bytes: u64, zram: bool) -> BoxliteResult<()> {
+    let page_size = page_size();
+    let size_bytes = size_bytes - size_bytes % page_size;
+    if size_bytes < 2 * page_size {
+        return Err(BoxliteError::Config(format!(
+            "swap of {} bytes is too small",
+            size_bytes
+        )));
+    }
+
+    let device = if zram {
+        fs::write(
+            Path::new(ZRAM_SYSFS).join("disksize"),
+            size_bytes.to_string(),
+        )
+        .map_err(|e| {
+            BoxliteError::Internal(format!(
+                "Failed to size {} (no zram in kernel?): {}",
+                ZRAM_DEVICE, e
+            ))
+        })?;
+        let mut device = OpenOptions::new()
+            .write(true)
+            .open(ZRAM_DEVICE)
+            .map_err(|e| io_error(ZRAM_DEVICE, e))?;
+        write_header(&mut device, size_bytes, page_size).map_err(|e| io_error(ZRAM_DEVICE, e))?;
+        ZRAM_DEVICE
+    } else {
+        create_swap_file(SWAP_FILE, size_bytes, page_size).map_err(|e| io_error(SWAP_FILE, e))?;
+        SWAP_FILE
+    };
+
+    let path = CString::new(Path::new(device).as_os_str().as_bytes())
+        .map_err(|e| BoxliteError::Internal(e.to_string()))?;
+    if unsafe { nix::libc::swapon(path.as_ptr(), 0) } != 0 {
+        return Err(BoxliteError::Internal(format!(
+            "swapon {} failed: {}",
+            device,
+            std::io::Error::last_os_error()
+        )));
+    }
+    tracing::info!(
+        device,
+        size_mib = size_bytes / (1024 * 1024),
+        "Enabled swap"
+    );
+    Ok(())
+}
+
+/// Read the VM's swap counters; zero without swap.
+pub fn stats() -> SwapStats {
+    let meminfo = fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    let kib = |key: &str| {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|rest| {
+                rest.trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+            .unwrap_or(0)
+    };
+    SwapStats {
+        used_bytes: kib("SwapTotal").saturating_sub(kib("SwapFree")) * 1024,
+        out_bytes: swap_out_pages() * page_size(),
+    }
+}
+
+/// Size a swap file without holes and write its header.
+///
+/// A swap file of the same size left by an earlier boot is kept; one of
+/// another size is replaced, so a changed swap_mib applies.
+fn create_swap_file(path: &str, size_bytes: u64, page_size: u64) -> std::io::Result<()> {
+    if is_swap_file(path, size_bytes, page_size) {
+        return Ok(());
+    }
+    let _ = fs::remove_file(path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    fallocate(
+        file.as_raw_fd(),
+        FallocateFlags::empty(),
+        0,
+        size_bytes as nix::libc::off_t,
+    )?;
+    write_header(&mut file, size_bytes, page_size)?;
+    file.sync_all()
+}
+
+/// Whether `path` is a swap file of `size_bytes`.
+fn is_swap_file(path: &str, size_bytes: u64, page_size: u64) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    let mut page = vec![0u8; page_size as usize];
+    file.metadata()
+        .is_ok_and(|metadata| metadata.len() == size_bytes)
+        && file.read_exact(&mut page).is_ok()
+        && page == header(size_bytes, page_size)
+}
+
+/// Write a version 1 swap header (what `mkswap` writes) to the first page.
+fn write_header(out: &mut File, size_bytes: u64, page_size: u64) -> std::io::Result<()> {
+    out.seek(SeekFrom::Start(0))?;
+    out.write_all(&header(size_bytes, page_size))?;
+    out.flush()
+}
+
+fn header(size_bytes: u64, page_size: u64) -> Vec<u8> {
+    let mut page = vec![0u8; page_size as usize];
+    let last_page = (size_bytes / page_size - 1) as u32;
+    // The first 1024 bytes are left for a boot sector
+    page[1024..1028].copy_from_slice(&1u32.to_ne_bytes());
+    page[1028..1032].copy_from_slice(&last_page.to_ne_bytes());
+    // nr_badpages stays 0; uuid and label are optional
+    page[1036..1052].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    let magic_at = page.len() - SWAP_MAGIC.len();
+    page[magic_at..].copy_from_slice(SWAP_MAGIC);
+    page
+}
+
+/// `pswpout` of /proc/vmstat
+fn swap_out_pages() -> u64 {
+    fs::read_to_string("/proc/vmstat")
+        .ok()
+        .and_then(|vmstat| {
+            vmstat
+                .lines()
+                .find_map(|line| line.strip_prefix("pswpout ")?.trim().parse().ok())
+        })
+        .unwrap_or(0)
+}
+
+fn page_size() -> u64 {
+    match unsafe { nix::libc::sysconf(nix::libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as u64,
+        _ => 4096,
+    }
+}
+
+fn io_error(path: &str, e: impl std::fmt::Display) -> BoxliteError {
+    BoxliteError::Internal(format!("Failed to set up swap on {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_layout() {
+        let page = header(64 * 4096, 4096);
+        assert_eq!(page.len(), 4096);
+        assert_eq!(&page[4086..], SWAP_MAGIC);
+        assert_eq!(u32::from_ne_bytes(page[1024..1028].try_into().unwrap()), 1);
+        assert_eq!(u32::from_ne_bytes(page[1028..1032].try_into().unwrap()), 63);
+        assert!(page[..1024].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_create_swap_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("swapfile");
+        let path = path.to_str().unwrap();
+        create_swap_file(path, 16 * 4096, 4096).unwrap();
+        let metadata = fs::metadata(path).unwrap();
+        assert_eq!(metadata.len(), 16 * 4096);
+        let data = fs::read(path).unwrap();
+        assert_eq!(&data[4096 - SWAP_MAGIC.len()..4096], SWAP_MAGIC);
+
+        // Kept across boots while the size doesn't change
+        create_swap_file(path, 16 * 4096, 4096).unwrap();
+        assert_eq!(fs::read(path).unwrap(), data);
+        create_swap_file(path, 32 * 4096, 4096).unwrap();
+        assert_eq!(fs::metadata(path).unwrap().len(), 32 * 4096);
+        assert!(is_swap_file(path, 32 * 4096, 4096));
+        assert!(!is_swap_file(path, 16 * 4096, 4096));
+    }
+}
@@ -0,0 +1,65 @@
+//! Kernel parameter (sysctl) tuning
+//!
+//! Writes the box's `sysctls` to /proc/sys during guest init, before the
+//! container starts. The container shares the guest kernel, so the values
+//! apply to it as well.
+
+use boxlite_shared::constants::sysctls;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Set each parameter, in name order.
+///
+/// Parameters outside the allowlist are rejected even though the host has
+/// already checked them.
+pub fn apply(params: &HashMap<String, String>) -> BoxliteResult<()> {
+    let mut keys: Vec<&String> = params.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let value = &params[key];
+        if !sysctls::is_allowed(key) {
+            return Err(BoxliteError::Config(format!(
+                "sysctl '{}' is not allowed",
+                key
+            )));
+        }
+        let path = proc_path(key);
+        fs::write(&path, value).map_err(|e| {
+            BoxliteError::Internal(format!("Failed to set {} to '{}': {}", key, value, e))
+        })?;
+        tracing::info!(key = %key, value = %value, "Set sysctl");
+    }
+
+    Ok(())
+}
+
+/// `net.core.somaxconn` → `/proc/sys/net/core/somaxconn`
+fn proc_path(key: &str) -> PathBuf {
+    PathBuf::from("/proc/sys").join(key.replace('.', "/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_path() {
+        assert_eq!(
+            proc_path("net.core.somaxconn"),
+            PathBuf::from("/proc/sys/net/core/somaxconn")
+        );
+        assert_eq!(
+            proc_path("vm.max_map_count"),
+            PathBuf::from("/proc/sys/vm/max_map_count")
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_disallowed() {
+        let params = HashMap::from([("kernel.panic".to_string(), "1".to_string())]);
+        assert!(apply(&params).is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use std::env;
 use std::path::PathBuf;
 
+/// Where cbindgen's output ends; op declarations go before it.
+const EXTERN_C_END: &str = "#ifdef __cplusplus\n}  // extern \"C\"";
+
 fn main() {
     let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let output_file = PathBuf::from(&crate_dir).join("include").join("boxlite.h");
@@ -10,6 +13,7 @@ fn main() {
         .expect("Failed to create include directory");
 
     // Generate C header from Rust code
+    let mut header = Vec::new();
     cbindgen::Builder::new()
         .with_crate(&crate_dir)
         .with_language(cbindgen::Language::C)
@@ -19,7 +23,59 @@ fn main() {
         .with_cpp_compat(true)
         .generate()
         .expect("Unable to generate C bindings")
-        .write_to_file(&output_file);
+        .write(&mut header);
+    let mut header = String::from_utf8(header).expect("cbindgen output is not UTF-8");
+
+    // cbindgen doesn't expand macros, so declare the `ops!` functions here
+    let ops = std::fs::read_to_string(PathBuf::from(&crate_dir).join("src").join("ops.rs"))
+        .expect("Failed to read src/ops.rs");
+    let at = header
+        .rfind(EXTERN_C_END)
+        .expect("cbindgen output has no extern \"C\" block");
+    header.insert_str(at, &op_declarations(&ops));
+
+    if std::fs::read_to_string(&output_file).ok().as_deref() != Some(header.as_str()) {
+        std::fs::write(&output_file, header).expect("Failed to write boxlite.h");
+    }
 
     println!("cargo:rerun-if-changed=src/");
 }
+
+/// C declarations, with their doc comments, of the `"name" => c_function:`
+/// entries in ops.rs.
+fn op_declarations(ops: &str) -> String {
+    let mut out = String::new();
+    let mut doc = Vec::new();
+    for line in ops.lines().map(str::trim) {
+        if let Some(text) = line.strip_prefix("///") {
+            doc.push(text.strip_prefix(' ').unwrap_or(text));
+            continue;
+        }
+        let c_fn = line
+            .strip_prefix('"')
+            .and_then(|rest| rest.split_once("\" => "))
+            .and_then(|(_, rest)| rest.split_once(':'))
+            .map(|(c_fn, _)| c_fn.trim());
+        if let Some(c_fn) = c_fn {
+            out.push_str("/**\n");
+            for text in &doc {
+                if text.is_empty() {
+                    out.push_str(" *\n");
+                } else {
+                    out.push_str(&format!(" * {}\n", text));
+                }
+            }
+            out.push_str(" */\n");
+            let head = format!("enum BoxliteErrorCode {}(", c_fn);
+            let indent = " ".repeat(head.len());
+            out.push_str(&format!(
+                "{head}struct CBoxliteRuntime *runtime,\n\
+                 {indent}const char *params_json,\n\
+                 {indent}char **out_json,\n\
+                 {indent}struct CBoxliteError *out_error);\n\n"
+            ));
+        }
+        doc.clear();
+    }
+    out
+}
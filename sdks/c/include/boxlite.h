@@ -8,6 +8,28 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * Major version of the C ABI. Bumped when a function signature, struct
+ * layout or enum value changes incompatibly.
+ */
+#define BOXLITE_ABI_VERSION_MAJOR 1
+
+/**
+ * Minor version of the C ABI. Bumped when functions, error codes or struct
+ * types are added.
+ */
+#define BOXLITE_ABI_VERSION_MINOR 10
+
+/**
+ * Patch version of the C ABI. Bumped for fixes that keep the ABI.
+ */
+#define BOXLITE_ABI_VERSION_PATCH 0
+
+/**
+ * `boxlite_batch()` flag: run the requests concurrently instead of in order.
+ */
+#define BOXLITE_BATCH_CONCURRENT 1
+
 /**
  * Error codes returned by BoxLite C API functions.
  *
@@ -83,6 +105,27 @@ typedef enum BoxliteErrorCode {
    * RPC error
    */
   Rpc = 16,
+  /**
+   * Not enough capacity for the requested resources
+   */
+  ResourceExhausted = 17,
+  /**
+   * The caller was built against an incompatible ABI version
+   */
+  AbiMismatch = 18,
+  /**
+   * Working directory of an execution doesn't exist
+   */
+  WorkdirNotFound = 19,
+  /**
+   * The box is at its limit of concurrent executions
+   */
+  TooManyExecs = 20,
+  /**
+   * Work in progress was cut short by its box stopping or the runtime
+   * shutting down
+   */
+  Cancelled = 21,
 } BoxliteErrorCode;
 
 /**
@@ -117,6 +160,15 @@ typedef struct CBoxliteError {
   char *message;
 } CBoxliteError;
 
+/**
+ * A C ABI version (semver).
+ */
+typedef struct BoxliteAbiVersion {
+  uint32_t major;
+  uint32_t minor;
+  uint32_t patch;
+} BoxliteAbiVersion;
+
 /**
  * Result structure for simple API command execution
  */
@@ -126,10 +178,49 @@ typedef struct CBoxliteExecResult {
   char *stderr_text;
 } CBoxliteExecResult;
 
+/**
+ * Receives the JSON report of a blocked call; the string is only valid
+ * during the callback.
+ */
+typedef void (*DiagnosticCallback)(const char*, void*);
+
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
 
+/**
+ * Report C calls that stay blocked longer than `timeout_ms`
+ *
+ * Every function of this library blocks its thread until the work is done.
+ * With a watchdog set, a background thread reports each call still blocked
+ * after `timeout_ms`, once: it logs the report at error level and passes it
+ * to `callback` (if not NULL) as JSON:
+ *
+ * ```json
+ * {"call": "sdks/c/src/ffi.rs:659:41", "thread": "main", "elapsed_ms": 30012,
+ *  "runtime": {"workers": 8, "alive_tasks": 14, "global_queue_depth": 0},
+ *  "portal_calls": [{"target": "unix:///.../box.sock",
+ *                    "method": "/boxlite.v1.Execution/Wait", "elapsed_ms": 30009}],
+ *  "tasks": null}
+ * ```
+ *
+ * `call` is the call site in this library, `portal_calls` the requests
+ * still waiting for a guest (of any box, oldest first), and `tasks` the
+ * Tokio task traces when the library was built with the `taskdump` feature.
+ * The blocked call keeps waiting. The callback runs on the watchdog thread;
+ * the report is only valid during the callback. A `timeout_ms` of 0 turns
+ * the watchdog off.
+ *
+ * # Example
+ * ```c
+ * void on_blocked(const char *report, void *user_data) {
+ *     fprintf(stderr, "boxlite call blocked: %s\n", report);
+ * }
+ * boxlite_set_watchdog(30000, on_blocked, NULL);
+ * ```
+ */
+void boxlite_set_watchdog(uint64_t timeout_ms, DiagnosticCallback callback, void *user_data);
+
 /**
  * Get BoxLite version string
  *
@@ -138,6 +229,15 @@ extern "C" {
  */
 const char *boxlite_version(void);
 
+/**
+ * Get the C ABI version of this library
+ *
+ * Unlike `boxlite_version()`, this only changes with the C interface.
+ * Compare it with the `BOXLITE_ABI_VERSION_*` constants of the header the
+ * caller was built with, or let `boxlite_runtime_new_with_abi()` do it.
+ */
+struct BoxliteAbiVersion boxlite_abi_version(void);
+
 /**
  * Create a new BoxLite runtime
  *
@@ -169,6 +269,49 @@ enum BoxliteErrorCode boxlite_runtime_new(const char *home_dir,
                                           struct CBoxliteRuntime **out_runtime,
                                           struct CBoxliteError *out_error);
 
+/**
+ * Create a new BoxLite runtime from JSON-encoded BoxliteOptions
+ *
+ * Takes the same options as the CLI's `--config` file, e.g. registries,
+ * `default_security` (`"development"`, `"standard"` or `"maximum"`) and
+ * `default_pull_policy`. Omitted fields keep their defaults; NULL uses
+ * all defaults.
+ *
+ * # Example
+ * ```c
+ * const char *opts = "{\"image_registries\":[\"ghcr.io\"],"
+ *                    "\"default_security\":\"maximum\"}";
+ * BoxliteErrorCode code = boxlite_runtime_new_from_json(opts, &runtime, &error);
+ * ```
+ */
+enum BoxliteErrorCode boxlite_runtime_new_from_json(const char *options_json,
+                                                    struct CBoxliteRuntime **out_runtime,
+                                                    struct CBoxliteError *out_error);
+
+/**
+ * Create a new BoxLite runtime, checking the caller's ABI version first
+ *
+ * Like `boxlite_runtime_new()`, but fails with `AbiMismatch` unless
+ * `caller_abi` (the `BOXLITE_ABI_VERSION_*` constants the caller was built
+ * with) is supported by this library: the major versions must match and
+ * the caller's minor version must not be newer than the library's.
+ *
+ * # Example
+ * ```c
+ * BoxliteAbiVersion abi = {BOXLITE_ABI_VERSION_MAJOR, BOXLITE_ABI_VERSION_MINOR,
+ *                          BOXLITE_ABI_VERSION_PATCH};
+ * BoxliteErrorCode code = boxlite_runtime_new_with_abi(abi, NULL, NULL, &runtime, &error);
+ * if (code == AbiMismatch) {
+ *     fprintf(stderr, "libboxlite too old or too new: %s\n", error.message);
+ * }
+ * ```
+ */
+enum BoxliteErrorCode boxlite_runtime_new_with_abi(struct BoxliteAbiVersion caller_abi,
+                                                   const char *home_dir,
+                                                   const char *registries_json,
+                                                   struct CBoxliteRuntime **out_runtime,
+                                                   struct CBoxliteError *out_error);
+
 /**
  * Create a new box with the given options (JSON)
  *
@@ -226,6 +369,63 @@ enum BoxliteErrorCode boxlite_execute(struct CBoxHandle *handle,
                                       int *out_exit_code,
                                       struct CBoxliteError *out_error);
 
+/**
+ * Execute a command in a box, passing its output to the callback line by line
+ *
+ * Same as `boxlite_execute`, except that each callback receives one whole
+ * line of stdout or stderr, including its terminator (`\n` or `\r\n`) as
+ * the command wrote it. The last line comes without a terminator if the
+ * command didn't write one, and lines over 1 MiB are passed in pieces.
+ * The line is NUL-terminated, but can contain NUL bytes itself; `len` is
+ * its length without the terminating NUL.
+ *
+ * # Arguments
+ * * `handle` - Box handle
+ * * `command` - Command to execute
+ * * `args_json` - JSON array of arguments, e.g.: `["arg1", "arg2"]`
+ * * `callback` - Optional callback for each line (line, len, is_stderr, user_data)
+ * * `user_data` - User data passed to callback
+ * * `out_exit_code` - Output parameter for command exit code
+ * * `out_error` - Output parameter for error information
+ *
+ * # Returns
+ * BoxliteErrorCode::Ok on success, error code on failure
+ */
+enum BoxliteErrorCode boxlite_execute_lines(struct CBoxHandle *handle,
+                                            const char *command,
+                                            const char *args_json,
+                                            void (*callback)(const char*, size_t, int, void*),
+                                            void *user_data,
+                                            int *out_exit_code,
+                                            struct CBoxliteError *out_error);
+
+/**
+ * Execute a command in a box, streaming stdout and stderr to one callback
+ * in the order the command produced them
+ *
+ * Unlike `boxlite_execute`, chunks are passed as raw bytes with a length, so
+ * binary output and NUL bytes are preserved.
+ *
+ * # Arguments
+ * * `handle` - Box handle
+ * * `command` - Command to execute
+ * * `args_json` - JSON array of arguments, e.g.: `["arg1", "arg2"]`
+ * * `callback` - Optional callback for output (data, len, is_stderr, user_data)
+ * * `user_data` - User data passed to callback
+ * * `out_exit_code` - Output parameter for command exit code
+ * * `out_error` - Output parameter for error information
+ *
+ * # Returns
+ * BoxliteErrorCode::Ok on success, error code on failure
+ */
+enum BoxliteErrorCode boxlite_execute_output(struct CBoxHandle *handle,
+                                             const char *command,
+                                             const char *args_json,
+                                             void (*callback)(const uint8_t*, uintptr_t, int, void*),
+                                             void *user_data,
+                                             int *out_exit_code,
+                                             struct CBoxliteError *out_error);
+
 /**
  * Stop a box
  *
@@ -342,6 +542,7 @@ enum BoxliteErrorCode boxlite_runtime_metrics(struct CBoxliteRuntime *runtime,
  *
  * After calling this method, the runtime is permanently shut down and
  * will return errors for any new operations (like `create()`).
+ * Use `boxlite_runtime_shutdown_report` to learn which boxes were killed.
  *
  * # Arguments
  * * `runtime` - BoxLite runtime instance
@@ -358,6 +559,29 @@ enum BoxliteErrorCode boxlite_runtime_shutdown(struct CBoxliteRuntime *runtime,
                                                int timeout,
                                                struct CBoxliteError *out_error);
 
+/**
+ * Shut down a runtime like `boxlite_runtime_shutdown`, and report what
+ * happened to each box.
+ *
+ * The report is a JSON object: `clean` is true when every box stopped
+ * gracefully, and `boxes` lists `{"box_id", "outcome", "error"}` per active
+ * box, with `outcome` one of `stopped`, `killed` or `failed` (`error` set).
+ *
+ * # Arguments
+ * * `runtime` - BoxLite runtime instance
+ * * `timeout` - Seconds to wait before force-killing each box, as for
+ *   `boxlite_runtime_shutdown`
+ * * `out_json` - Output parameter for the JSON report
+ * * `out_error` - Output parameter for error information
+ *
+ * # Returns
+ * BoxliteErrorCode::Ok on success, error code on failure
+ */
+enum BoxliteErrorCode boxlite_runtime_shutdown_report(struct CBoxliteRuntime *runtime,
+                                                      int timeout,
+                                                      char **out_json,
+                                                      struct CBoxliteError *out_error);
+
 /**
  * Get box info from handle as JSON
  *
@@ -411,6 +635,48 @@ enum BoxliteErrorCode boxlite_start_box(struct CBoxHandle *handle, struct CBoxli
  */
 char *boxlite_box_id(struct CBoxHandle *handle);
 
+/**
+ * Run several operations in one call
+ *
+ * For bindings that would otherwise cross the FFI boundary once per
+ * operation. `requests_json` is a JSON array of requests; each has an `op`
+ * and an optional `id`, echoed in its response. The operations are those of
+ * the `boxlite_op_*()` functions, with the same fields:
+ *
+ * | `op` | Fields | Result |
+ * |------|--------|--------|
+ * | `create` | `options` (BoxOptions), `name` | `{"id"}` |
+ * | `start`, `stop` | `box` (ID or name) | `null` |
+ * | `remove` | `box`, `force` | `null` |
+ * | `exec` | `box`, `command`, `args` | `{"exit_code", "stdout", "stderr"}` |
+ * | `info` | `box` | as `boxlite_box_info()` |
+ * | `metrics` | `box` | as `boxlite_box_metrics()` |
+ *
+ * Requests run in order, each after the previous one finished, or all at
+ * once with `BOXLITE_BATCH_CONCURRENT` in `flags`. A failing request
+ * doesn't stop the others. `out_responses_json` receives a JSON array with
+ * one response per request, in request order: `{"id", "result"}` or
+ * `{"id", "error": {"code", "message"}}`, `code` being a `BoxliteErrorCode`
+ * (caller must free with `boxlite_free_string`). The function itself only
+ * fails for invalid arguments or when `requests_json` isn't an array.
+ *
+ * # Example
+ * ```c
+ * const char *requests =
+ *     "[{\"id\":1,\"op\":\"create\",\"name\":\"w1\","
+ *     "\"options\":{\"rootfs\":{\"Image\":\"alpine:3.19\"}}},"
+ *     "{\"id\":2,\"op\":\"exec\",\"box\":\"w1\",\"command\":\"echo\",\"args\":[\"hi\"]},"
+ *     "{\"id\":3,\"op\":\"remove\",\"box\":\"w1\",\"force\":true}]";
+ * char *responses = NULL;
+ * BoxliteErrorCode code = boxlite_batch(runtime, requests, 0, &responses, &error);
+ * ```
+ */
+enum BoxliteErrorCode boxlite_batch(struct CBoxliteRuntime *runtime,
+                                    const char *requests_json,
+                                    uint32_t flags,
+                                    char **out_responses_json,
+                                    struct CBoxliteError *out_error);
+
 /**
  * Create and start a box using simple API
  */
@@ -455,6 +721,78 @@ void boxlite_free_string(char *str);
  */
 void boxlite_error_free(struct CBoxliteError *error);
 
+/**
+ * Create a box.
+ *
+ * Fields: `options` (BoxOptions), `name` (optional). Result: `{"id"}` of
+ * the new box.
+ */
+enum BoxliteErrorCode boxlite_op_create(struct CBoxliteRuntime *runtime,
+                                        const char *params_json,
+                                        char **out_json,
+                                        struct CBoxliteError *out_error);
+
+/**
+ * Start a box.
+ *
+ * Fields: `box` (ID or name). Result: `null`.
+ */
+enum BoxliteErrorCode boxlite_op_start(struct CBoxliteRuntime *runtime,
+                                       const char *params_json,
+                                       char **out_json,
+                                       struct CBoxliteError *out_error);
+
+/**
+ * Stop a box.
+ *
+ * Fields: `box` (ID or name). Result: `null`.
+ */
+enum BoxliteErrorCode boxlite_op_stop(struct CBoxliteRuntime *runtime,
+                                      const char *params_json,
+                                      char **out_json,
+                                      struct CBoxliteError *out_error);
+
+/**
+ * Remove a box.
+ *
+ * Fields: `box` (ID or name), `force` (optional). Result: `null`.
+ */
+enum BoxliteErrorCode boxlite_op_remove(struct CBoxliteRuntime *runtime,
+                                        const char *params_json,
+                                        char **out_json,
+                                        struct CBoxliteError *out_error);
+
+/**
+ * Run a command in a box and collect its output.
+ *
+ * Fields: `box` (ID or name), `command`, `args` (optional). Result:
+ * `{"exit_code", "stdout", "stderr"}`, as `boxlite_simple_run()`.
+ */
+enum BoxliteErrorCode boxlite_op_exec(struct CBoxliteRuntime *runtime,
+                                      const char *params_json,
+                                      char **out_json,
+                                      struct CBoxliteError *out_error);
+
+/**
+ * Get a box's info.
+ *
+ * Fields: `box` (ID or name). Result: as `boxlite_box_info()`.
+ */
+enum BoxliteErrorCode boxlite_op_info(struct CBoxliteRuntime *runtime,
+                                      const char *params_json,
+                                      char **out_json,
+                                      struct CBoxliteError *out_error);
+
+/**
+ * Get a box's metrics.
+ *
+ * Fields: `box` (ID or name). Result: as `boxlite_box_metrics()`.
+ */
+enum BoxliteErrorCode boxlite_op_metrics(struct CBoxliteRuntime *runtime,
+                                         const char *params_json,
+                                         char **out_json,
+                                         struct CBoxliteError *out_error);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif  // __cplusplus
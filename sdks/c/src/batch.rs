@@ -0,0 +1,94 @@
+//! Operations of `boxlite_batch()`.
+//!
+//! A batch is a JSON array of requests, each naming an operation in `op`
+//! and, optionally, an `id` echoed in its response. The responses come back
+//! as a JSON array in request order, JSON-RPC style: `{"id", "result"}` for
+//! a success and `{"id", "error": {"code", "message"}}` for a failure, with
+//! `code` a `BoxliteErrorCode`. A failing request doesn't stop the others.
+//! The operations are those of `ops.rs`.
+
+use boxlite::BoxliteError;
+use boxlite::runtime::BoxliteRuntime;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::ffi::error_to_code;
+use crate::ops::Op;
+
+/// One request of a batch.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Echoed in the response
+    #[serde(default)]
+    id: Value,
+    #[serde(flatten)]
+    op: Op,
+}
+
+/// Run `requests` one after the other, or all at once with `concurrent`.
+pub(crate) async fn run(runtime: &BoxliteRuntime, requests: Vec<Value>, concurrent: bool) -> Value {
+    let mut responses = Vec::with_capacity(requests.len());
+    if concurrent {
+        let pending = requests
+            .into_iter()
+            .map(|request| respond(runtime, request));
+        responses = futures::future::join_all(pending).await;
+    } else {
+        for request in requests {
+            responses.push(respond(runtime, request).await);
+        }
+    }
+    Value::Array(responses)
+}
+
+async fn respond(runtime: &BoxliteRuntime, request: Value) -> Value {
+    // Keep the ID of requests that don't parse
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let result = match serde_json::from_value::<Request>(request) {
+        Ok(request) => request.op.execute(runtime).await,
+        Err(e) => Err(BoxliteError::InvalidArgument(format!(
+            "Invalid batch request: {}",
+            e
+        ))),
+    };
+    match result {
+        Ok(result) => json!({ "id": id, "result": result }),
+        Err(e) => json!({
+            "id": id,
+            "error": { "code": error_to_code(&e) as i32, "message": e.to_string() },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_requests() {
+        let request: Request = serde_json::from_value(json!({
+            "id": 7,
+            "op": "exec",
+            "box": "web",
+            "command": "echo",
+            "args": ["hi"],
+        }))
+        .unwrap();
+        assert_eq!(request.id, json!(7));
+        assert!(matches!(
+            request.op,
+            Op::Exec { ref target, ref command, ref args }
+                if target == "web" && command == "echo" && args == &["hi"]
+        ));
+
+        let request: Request =
+            serde_json::from_value(json!({ "op": "remove", "box": "web" })).unwrap();
+        assert_eq!(request.id, Value::Null);
+        assert!(matches!(request.op, Op::Remove { force: false, .. }));
+
+        assert!(
+            serde_json::from_value::<Request>(json!({ "op": "reboot", "box": "web" })).is_err()
+        );
+        assert!(serde_json::from_value::<Request>(json!({ "op": "stop" })).is_err());
+    }
+}
@@ -15,19 +15,29 @@
 #![allow(clippy::doc_overindented_list_items)]
 
 use std::ffi::{CStr, CString};
+use std::future::Future;
 use std::os::raw::{c_char, c_int, c_void};
+use std::panic::Location;
 use std::ptr;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
-use tokio::runtime::Runtime as TokioRuntime;
+use tokio::runtime::{Handle, Runtime as TokioRuntime};
 
 use boxlite::BoxID;
+use boxlite::BoxMetrics;
+use boxlite::BoxShutdownOutcome;
 use boxlite::BoxliteError;
+use boxlite::ShutdownReport;
 use boxlite::litebox::LiteBox;
 use boxlite::runtime::BoxliteRuntime;
 use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
 use boxlite::runtime::types::{BoxInfo, BoxStatus};
 
+use crate::batch;
+use crate::ops::Op;
+use crate::watchdog::{self, DiagnosticCallback};
+
 // ============================================================================
 // Error Code Enum - Maps to BoxliteError variants
 // ============================================================================
@@ -73,6 +83,17 @@ pub enum BoxliteErrorCode {
     Portal = 15,
     /// RPC error
     Rpc = 16,
+    /// Not enough capacity for the requested resources
+    ResourceExhausted = 17,
+    /// The caller was built against an incompatible ABI version
+    AbiMismatch = 18,
+    /// Working directory of an execution doesn't exist
+    WorkdirNotFound = 19,
+    /// The box is at its limit of concurrent executions
+    TooManyExecs = 20,
+    /// Work in progress was cut short by its box stopping or the runtime
+    /// shutting down
+    Cancelled = 21,
 }
 
 /// Extended error information for C API.
@@ -99,7 +120,7 @@ impl Default for CBoxliteError {
 /// Opaque handle to a BoxliteRuntime instance
 pub struct CBoxliteRuntime {
     runtime: BoxliteRuntime,
-    tokio_rt: Arc<TokioRuntime>,
+    tokio_rt: AsyncExecutor,
 }
 
 /// Opaque handle to a running box
@@ -107,7 +128,7 @@ pub struct CBoxHandle {
     handle: LiteBox,
     #[allow(dead_code)]
     box_id: BoxID,
-    tokio_rt: Arc<TokioRuntime>,
+    tokio_rt: AsyncExecutor,
 }
 
 /// Opaque handle for simple API (auto-manages runtime)
@@ -115,7 +136,106 @@ pub struct CBoxliteSimple {
     runtime: BoxliteRuntime,
     handle: Option<LiteBox>,
     box_id: Option<BoxID>,
-    tokio_rt: Arc<TokioRuntime>,
+    tokio_rt: AsyncExecutor,
+}
+
+// ============================================================================
+// Async Executor
+// ============================================================================
+
+/// Tokio runtime of the embedding application, set by [`set_tokio_handle`].
+static BORROWED_HANDLE: OnceLock<Handle> = OnceLock::new();
+
+/// Use the embedding application's Tokio runtime for runtimes created
+/// afterwards, instead of starting one per `boxlite_runtime_new` /
+/// `boxlite_simple_new`.
+///
+/// For Rust applications linking this library. The runtime must be
+/// multi-threaded, and the C functions must not be called from its worker
+/// threads (they block). Returns false if a handle was already set.
+pub fn set_tokio_handle(handle: Handle) -> bool {
+    BORROWED_HANDLE.set(handle).is_ok()
+}
+
+/// The Tokio runtime C calls block on: owned by the handle, or borrowed.
+#[derive(Clone)]
+enum AsyncExecutor {
+    Owned(Arc<TokioRuntime>),
+    Borrowed(Handle),
+}
+
+impl AsyncExecutor {
+    /// The borrowed runtime if one was set, otherwise a new one.
+    fn new() -> Result<Self, BoxliteError> {
+        if let Some(handle) = BORROWED_HANDLE.get() {
+            return Ok(AsyncExecutor::Borrowed(handle.clone()));
+        }
+        TokioRuntime::new()
+            .map(|rt| AsyncExecutor::Owned(Arc::new(rt)))
+            .map_err(|e| BoxliteError::Internal(format!("Failed to create async runtime: {}", e)))
+    }
+
+    /// Block on `future`, under the watchdog if one is set.
+    #[track_caller]
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let _watch = watchdog::watch(Location::caller(), self.handle());
+        match self {
+            AsyncExecutor::Owned(rt) => rt.block_on(future),
+            AsyncExecutor::Borrowed(handle) => handle.block_on(future),
+        }
+    }
+
+    /// Run `f` in the runtime's context, so it can spawn tasks.
+    fn enter<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _guard = self.handle().enter();
+        f()
+    }
+
+    fn handle(&self) -> &Handle {
+        match self {
+            AsyncExecutor::Owned(rt) => rt.handle(),
+            AsyncExecutor::Borrowed(handle) => handle,
+        }
+    }
+}
+
+/// Report C calls that stay blocked longer than `timeout_ms`
+///
+/// Every function of this library blocks its thread until the work is done.
+/// With a watchdog set, a background thread reports each call still blocked
+/// after `timeout_ms`, once: it logs the report at error level and passes it
+/// to `callback` (if not NULL) as JSON:
+///
+/// ```json
+/// {"call": "sdks/c/src/ffi.rs:659:41", "thread": "main", "elapsed_ms": 30012,
+///  "runtime": {"workers": 8, "alive_tasks": 14, "global_queue_depth": 0},
+///  "portal_calls": [{"target": "unix:///.../box.sock",
+///                    "method": "/boxlite.v1.Execution/Wait", "elapsed_ms": 30009}],
+///  "tasks": null}
+/// ```
+///
+/// `call` is the call site in this library, `portal_calls` the requests
+/// still waiting for a guest (of any box, oldest first), and `tasks` the
+/// Tokio task traces when the library was built with the `taskdump` feature.
+/// The blocked call keeps waiting. The callback runs on the watchdog thread;
+/// the report is only valid during the callback. A `timeout_ms` of 0 turns
+/// the watchdog off.
+///
+/// # Example
+/// ```c
+/// void on_blocked(const char *report, void *user_data) {
+///     fprintf(stderr, "boxlite call blocked: %s\n", report);
+/// }
+/// boxlite_set_watchdog(30000, on_blocked, NULL);
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_set_watchdog(
+    timeout_ms: u64,
+    callback: Option<DiagnosticCallback>,
+    user_data: *mut c_void,
+) {
+    let timeout = (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms));
+    watchdog::configure(timeout, callback, user_data);
 }
 
 // ============================================================================
@@ -123,7 +243,7 @@ pub struct CBoxliteSimple {
 // ============================================================================
 
 /// Map BoxliteError to BoxliteErrorCode
-fn error_to_code(err: &BoxliteError) -> BoxliteErrorCode {
+pub(crate) fn error_to_code(err: &BoxliteError) -> BoxliteErrorCode {
     match err {
         BoxliteError::Internal(_) => BoxliteErrorCode::Internal,
         BoxliteError::NotFound(_) => BoxliteErrorCode::NotFound,
@@ -143,6 +263,10 @@ fn error_to_code(err: &BoxliteError) -> BoxliteErrorCode {
         BoxliteError::Portal(_) => BoxliteErrorCode::Portal,
         BoxliteError::Rpc(_) | BoxliteError::RpcTransport(_) => BoxliteErrorCode::Rpc,
         BoxliteError::MetadataError(_) => BoxliteErrorCode::Internal,
+        BoxliteError::ResourceExhausted(_) => BoxliteErrorCode::ResourceExhausted,
+        BoxliteError::WorkdirNotFound(_) => BoxliteErrorCode::WorkdirNotFound,
+        BoxliteError::TooManyExecs(_) => BoxliteErrorCode::TooManyExecs,
+        BoxliteError::Cancelled(_) => BoxliteErrorCode::Cancelled,
     }
 }
 
@@ -198,13 +322,14 @@ fn status_to_string(status: BoxStatus) -> &'static str {
         BoxStatus::Unknown => "unknown",
         BoxStatus::Configured => "configured",
         BoxStatus::Running => "running",
+        BoxStatus::Unresponsive => "unresponsive",
         BoxStatus::Stopping => "stopping",
         BoxStatus::Stopped => "stopped",
     }
 }
 
 /// Convert BoxInfo to JSON with nested state structure
-fn box_info_to_json(info: &BoxInfo) -> serde_json::Value {
+pub(crate) fn box_info_to_json(info: &BoxInfo) -> serde_json::Value {
     serde_json::json!({
         "id": info.id.to_string(),
         "name": info.name,
@@ -216,10 +341,80 @@ fn box_info_to_json(info: &BoxInfo) -> serde_json::Value {
         "created_at": info.created_at.to_rfc3339(),
         "image": info.image,
         "cpus": info.cpus,
-        "memory_mib": info.memory_mib
+        "memory_mib": info.memory_mib,
+        "warnings": info.warnings
+    })
+}
+
+/// Convert ShutdownReport to JSON
+fn shutdown_report_to_json(report: &ShutdownReport) -> serde_json::Value {
+    let boxes: Vec<serde_json::Value> = report
+        .boxes
+        .iter()
+        .map(|result| {
+            let (outcome, error) = match &result.outcome {
+                BoxShutdownOutcome::Stopped => ("stopped", None),
+                BoxShutdownOutcome::Killed => ("killed", None),
+                BoxShutdownOutcome::Failed(error) => ("failed", Some(error)),
+            };
+            serde_json::json!({
+                "box_id": result.box_id.to_string(),
+                "outcome": outcome,
+                "error": error
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "clean": report.is_clean(),
+        "boxes": boxes
+    })
+}
+
+/// Convert BoxMetrics to JSON
+pub(crate) fn box_metrics_to_json(metrics: &BoxMetrics) -> serde_json::Value {
+    serde_json::json!({
+        "cpu_percent": metrics.cpu_percent,
+        "memory_bytes": metrics.memory_bytes,
+        "commands_executed_total": metrics.commands_executed_total,
+        "exec_errors_total": metrics.exec_errors_total,
+        "execs_in_flight": metrics.execs_in_flight,
+        "bytes_sent_total": metrics.bytes_sent_total,
+        "bytes_received_total": metrics.bytes_received_total,
+        "labeled": metrics.labeled.iter().map(|(label, counters)| {
+            (label.clone(), serde_json::json!({
+                "commands_executed_total": counters.commands_executed_total,
+                "exec_errors_total": counters.exec_errors_total
+            }))
+        }).collect::<serde_json::Map<_, _>>(),
+        "total_create_duration_ms": metrics.total_create_duration_ms,
+        "guest_boot_duration_ms": metrics.guest_boot_duration_ms,
+        "network_bytes_sent": metrics.network_bytes_sent,
+        "network_bytes_received": metrics.network_bytes_received,
+        "network_tcp_connections": metrics.network_tcp_connections,
+        "network_tcp_errors": metrics.network_tcp_errors,
+        "cpu_throttled_periods_total": metrics.cpu_throttled_periods_total,
+        "cpu_throttled_usec_total": metrics.cpu_throttled_usec_total,
+        "memory_limit_hits_total": metrics.memory_limit_hits_total,
+        "memory_oom_kills_total": metrics.memory_oom_kills_total,
+        "memory_working_set_bytes": metrics.memory_working_set_bytes,
+        "memory_cache_bytes": metrics.memory_cache_bytes,
+        "pids_current": metrics.pids_current,
+        "io_read_bytes_total": metrics.io_read_bytes_total,
+        "io_write_bytes_total": metrics.io_write_bytes_total,
+        "swap_used_bytes": metrics.swap_used_bytes,
+        "swap_out_bytes_total": metrics.swap_out_bytes_total
     })
 }
 
+/// Run a command to completion, collecting its output
+pub(crate) async fn run_collecting(
+    handle: &LiteBox,
+    cmd: boxlite::BoxCommand,
+) -> Result<(i32, String, String), BoxliteError> {
+    let output = handle.exec(cmd).await?.collect_output().await?;
+    Ok((output.result.exit_code, output.stdout, output.stderr))
+}
+
 /// Get BoxLite version string
 ///
 /// # Returns
@@ -230,6 +425,59 @@ pub extern "C" fn boxlite_version() -> *const c_char {
     concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
 }
 
+// ============================================================================
+// ABI Version
+// ============================================================================
+
+/// Major version of the C ABI. Bumped when a function signature, struct
+/// layout or enum value changes incompatibly.
+pub const BOXLITE_ABI_VERSION_MAJOR: u32 = 1;
+/// Minor version of the C ABI. Bumped when functions, error codes or struct
+/// types are added.
+pub const BOXLITE_ABI_VERSION_MINOR: u32 = 10;
+/// Patch version of the C ABI. Bumped for fixes that keep the ABI.
+pub const BOXLITE_ABI_VERSION_PATCH: u32 = 0;
+
+/// A C ABI version (semver).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxliteAbiVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl BoxliteAbiVersion {
+    /// ABI version of this library.
+    const CURRENT: Self = Self {
+        major: BOXLITE_ABI_VERSION_MAJOR,
+        minor: BOXLITE_ABI_VERSION_MINOR,
+        patch: BOXLITE_ABI_VERSION_PATCH,
+    };
+
+    /// Whether a caller built against `self` can use this library: same
+    /// major version, and nothing newer than this library provides.
+    fn is_supported(&self) -> bool {
+        self.major == Self::CURRENT.major && self.minor <= Self::CURRENT.minor
+    }
+}
+
+impl std::fmt::Display for BoxliteAbiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Get the C ABI version of this library
+///
+/// Unlike `boxlite_version()`, this only changes with the C interface.
+/// Compare it with the `BOXLITE_ABI_VERSION_*` constants of the header the
+/// caller was built with, or let `boxlite_runtime_new_with_abi()` do it.
+#[unsafe(no_mangle)]
+pub extern "C" fn boxlite_abi_version() -> BoxliteAbiVersion {
+    BoxliteAbiVersion::CURRENT
+}
+
 /// Create a new BoxLite runtime
 ///
 /// # Arguments
@@ -266,16 +514,6 @@ pub unsafe extern "C" fn boxlite_runtime_new(
         return BoxliteErrorCode::InvalidArgument;
     }
 
-    // Create tokio runtime
-    let tokio_rt = match TokioRuntime::new() {
-        Ok(rt) => Arc::new(rt),
-        Err(e) => {
-            let err = BoxliteError::Internal(format!("Failed to create async runtime: {}", e));
-            write_error(out_error, err);
-            return BoxliteErrorCode::Internal;
-        }
-    };
-
     // Parse options
     let mut options = BoxliteOptions::default();
     if !home_dir.is_null() {
@@ -306,8 +544,73 @@ pub unsafe extern "C" fn boxlite_runtime_new(
         }
     }
 
-    // Create runtime
-    let runtime = match BoxliteRuntime::new(options) {
+    create_runtime(options, out_runtime, out_error)
+}
+
+/// Create a new BoxLite runtime from JSON-encoded BoxliteOptions
+///
+/// Takes the same options as the CLI's `--config` file, e.g. registries,
+/// `default_security` (`"development"`, `"standard"` or `"maximum"`) and
+/// `default_pull_policy`. Omitted fields keep their defaults; NULL uses
+/// all defaults.
+///
+/// # Example
+/// ```c
+/// const char *opts = "{\"image_registries\":[\"ghcr.io\"],"
+///                    "\"default_security\":\"maximum\"}";
+/// BoxliteErrorCode code = boxlite_runtime_new_from_json(opts, &runtime, &error);
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_runtime_new_from_json(
+    options_json: *const c_char,
+    out_runtime: *mut *mut CBoxliteRuntime,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if out_runtime.is_null() {
+        write_error(out_error, null_pointer_error("out_runtime"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    let options = if options_json.is_null() {
+        BoxliteOptions::default()
+    } else {
+        let options_str = match c_str_to_string(options_json) {
+            Ok(s) => s,
+            Err(e) => {
+                write_error(out_error, e);
+                return BoxliteErrorCode::InvalidArgument;
+            }
+        };
+        match serde_json::from_str::<BoxliteOptions>(&options_str) {
+            Ok(options) => options,
+            Err(e) => {
+                let err = BoxliteError::Internal(format!("Invalid runtime options JSON: {}", e));
+                write_error(out_error, err);
+                return BoxliteErrorCode::Internal;
+            }
+        }
+    };
+
+    create_runtime(options, out_runtime, out_error)
+}
+
+/// Start the tokio runtime and the BoxLite runtime, writing it to `out_runtime`.
+unsafe fn create_runtime(
+    options: BoxliteOptions,
+    out_runtime: *mut *mut CBoxliteRuntime,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    // Create (or borrow) tokio runtime
+    let tokio_rt = match AsyncExecutor::new() {
+        Ok(rt) => rt,
+        Err(e) => {
+            write_error(out_error, e);
+            return BoxliteErrorCode::Internal;
+        }
+    };
+
+    // Inside the runtime: creating one spawns its background tasks
+    let runtime = match tokio_rt.enter(|| BoxliteRuntime::new(options)) {
         Ok(rt) => rt,
         Err(e) => {
             let code = error_to_code(&e);
@@ -320,6 +623,49 @@ pub unsafe extern "C" fn boxlite_runtime_new(
     BoxliteErrorCode::Ok
 }
 
+/// Create a new BoxLite runtime, checking the caller's ABI version first
+///
+/// Like `boxlite_runtime_new()`, but fails with `AbiMismatch` unless
+/// `caller_abi` (the `BOXLITE_ABI_VERSION_*` constants the caller was built
+/// with) is supported by this library: the major versions must match and
+/// the caller's minor version must not be newer than the library's.
+///
+/// # Example
+/// ```c
+/// BoxliteAbiVersion abi = {BOXLITE_ABI_VERSION_MAJOR, BOXLITE_ABI_VERSION_MINOR,
+///                          BOXLITE_ABI_VERSION_PATCH};
+/// BoxliteErrorCode code = boxlite_runtime_new_with_abi(abi, NULL, NULL, &runtime, &error);
+/// if (code == AbiMismatch) {
+///     fprintf(stderr, "libboxlite too old or too new: %s\n", error.message);
+/// }
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_runtime_new_with_abi(
+    caller_abi: BoxliteAbiVersion,
+    home_dir: *const c_char,
+    registries_json: *const c_char,
+    out_runtime: *mut *mut CBoxliteRuntime,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if !caller_abi.is_supported() {
+        if !out_error.is_null() {
+            let message = format!(
+                "caller was built against C ABI {}, but this libboxlite provides {}",
+                caller_abi,
+                BoxliteAbiVersion::CURRENT
+            );
+            *out_error = CBoxliteError {
+                code: BoxliteErrorCode::AbiMismatch,
+                message: CString::new(message)
+                    .map(CString::into_raw)
+                    .unwrap_or(ptr::null_mut()),
+            };
+        }
+        return BoxliteErrorCode::AbiMismatch;
+    }
+    boxlite_runtime_new(home_dir, registries_json, out_runtime, out_error)
+}
+
 /// Create a new box with the given options (JSON)
 ///
 /// # Arguments
@@ -440,15 +786,263 @@ pub unsafe extern "C" fn boxlite_execute(
         return BoxliteErrorCode::InvalidArgument;
     }
 
-    let handle_ref = &mut *handle;
-
+    let handle_ref = &mut *handle;
+
+    let cmd = match exec_command(command, args_json, out_error) {
+        Ok(cmd) => cmd,
+        Err(code) => return code,
+    };
+
+    // Execute command using new API
+    let result = handle_ref.tokio_rt.block_on(async {
+        let mut execution = handle_ref.handle.exec(cmd).await?;
+
+        // Stream output to callback if provided
+        if let Some(cb) = callback {
+            use futures::StreamExt;
+
+            // Take stdout and stderr
+            let mut stdout = execution.stdout();
+            let mut stderr = execution.stderr();
+
+            // Read both streams
+            loop {
+                tokio::select! {
+                    Some(line) = async {
+                        match &mut stdout {
+                            Some(s) => s.next().await,
+                            None => None,
+                        }
+                    } => {
+                        let c_text = CString::new(line).unwrap_or_default();
+                        cb(c_text.as_ptr(), 0, user_data); // 0 = stdout
+                    }
+                    Some(line) = async {
+                        match &mut stderr {
+                            Some(s) => s.next().await,
+                            None => None,
+                        }
+                    } => {
+                        let c_text = CString::new(line).unwrap_or_default();
+                        cb(c_text.as_ptr(), 1, user_data); // 1 = stderr
+                    }
+                    else => break,
+                }
+            }
+        }
+
+        // Wait for execution to complete
+        let status = execution.wait().await?;
+        Ok::<i32, BoxliteError>(status.exit_code)
+    });
+
+    match result {
+        Ok(exit_code) => {
+            *out_exit_code = exit_code;
+            BoxliteErrorCode::Ok
+        }
+        Err(e) => {
+            let code = error_to_code(&e);
+            write_error(out_error, e);
+            code
+        }
+    }
+}
+
+/// Execute a command in a box, passing its output to the callback line by line
+///
+/// Same as `boxlite_execute`, except that each callback receives one whole
+/// line of stdout or stderr, including its terminator (`\n` or `\r\n`) as
+/// the command wrote it. The last line comes without a terminator if the
+/// command didn't write one, and lines over 1 MiB are passed in pieces.
+/// The line is NUL-terminated, but can contain NUL bytes itself; `len` is
+/// its length without the terminating NUL.
+///
+/// # Arguments
+/// * `handle` - Box handle
+/// * `command` - Command to execute
+/// * `args_json` - JSON array of arguments, e.g.: `["arg1", "arg2"]`
+/// * `callback` - Optional callback for each line (line, len, is_stderr, user_data)
+/// * `user_data` - User data passed to callback
+/// * `out_exit_code` - Output parameter for command exit code
+/// * `out_error` - Output parameter for error information
+///
+/// # Returns
+/// BoxliteErrorCode::Ok on success, error code on failure
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_execute_lines(
+    handle: *mut CBoxHandle,
+    command: *const c_char,
+    args_json: *const c_char,
+    callback: Option<extern "C" fn(*const c_char, usize, c_int, *mut c_void)>,
+    user_data: *mut c_void,
+    out_exit_code: *mut c_int,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if handle.is_null() {
+        write_error(out_error, null_pointer_error("handle"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    if out_exit_code.is_null() {
+        write_error(out_error, null_pointer_error("out_exit_code"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    let handle_ref = &mut *handle;
+
+    let cmd = match exec_command(command, args_json, out_error) {
+        Ok(cmd) => cmd,
+        Err(code) => return code,
+    };
+
+    let result = handle_ref.tokio_rt.block_on(async {
+        let mut execution = handle_ref.handle.exec(cmd).await?;
+
+        if let Some(cb) = callback {
+            use futures::StreamExt;
+
+            // NUL-terminated, but passed with its length: the line may hold NULs
+            let send = |line: String, is_stderr: c_int| {
+                let mut bytes = line.into_bytes();
+                let len = bytes.len();
+                bytes.push(0);
+                cb(bytes.as_ptr() as *const c_char, len, is_stderr, user_data);
+            };
+            let mut stdout = execution.stdout_lines();
+            let mut stderr = execution.stderr_lines();
+
+            loop {
+                tokio::select! {
+                    Some(line) = async {
+                        match &mut stdout {
+                            Some(s) => s.next().await,
+                            None => None,
+                        }
+                    } => send(line, 0), // 0 = stdout
+                    Some(line) = async {
+                        match &mut stderr {
+                            Some(s) => s.next().await,
+                            None => None,
+                        }
+                    } => send(line, 1), // 1 = stderr
+                    else => break,
+                }
+            }
+        }
+
+        let status = execution.wait().await?;
+        Ok::<i32, BoxliteError>(status.exit_code)
+    });
+
+    match result {
+        Ok(exit_code) => {
+            *out_exit_code = exit_code;
+            BoxliteErrorCode::Ok
+        }
+        Err(e) => {
+            let code = error_to_code(&e);
+            write_error(out_error, e);
+            code
+        }
+    }
+}
+
+/// Execute a command in a box, streaming stdout and stderr to one callback
+/// in the order the command produced them
+///
+/// Unlike `boxlite_execute`, chunks are passed as raw bytes with a length, so
+/// binary output and NUL bytes are preserved.
+///
+/// # Arguments
+/// * `handle` - Box handle
+/// * `command` - Command to execute
+/// * `args_json` - JSON array of arguments, e.g.: `["arg1", "arg2"]`
+/// * `callback` - Optional callback for output (data, len, is_stderr, user_data)
+/// * `user_data` - User data passed to callback
+/// * `out_exit_code` - Output parameter for command exit code
+/// * `out_error` - Output parameter for error information
+///
+/// # Returns
+/// BoxliteErrorCode::Ok on success, error code on failure
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_execute_output(
+    handle: *mut CBoxHandle,
+    command: *const c_char,
+    args_json: *const c_char,
+    callback: Option<extern "C" fn(*const u8, usize, c_int, *mut c_void)>,
+    user_data: *mut c_void,
+    out_exit_code: *mut c_int,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if handle.is_null() {
+        write_error(out_error, null_pointer_error("handle"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    if out_exit_code.is_null() {
+        write_error(out_error, null_pointer_error("out_exit_code"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    let handle_ref = &mut *handle;
+
+    let cmd = match exec_command(command, args_json, out_error) {
+        Ok(cmd) => cmd,
+        Err(code) => return code,
+    };
+
+    let result = handle_ref.tokio_rt.block_on(async {
+        let mut execution = handle_ref.handle.exec(cmd).await?;
+
+        if let Some(cb) = callback
+            && let Some(mut output) = execution.output()
+        {
+            use futures::StreamExt;
+
+            while let Some(chunk) = output.next().await {
+                let data = chunk.data();
+                cb(
+                    data.as_ptr(),
+                    data.len(),
+                    chunk.is_stderr() as c_int,
+                    user_data,
+                );
+            }
+        }
+
+        let status = execution.wait().await?;
+        Ok::<i32, BoxliteError>(status.exit_code)
+    });
+
+    match result {
+        Ok(exit_code) => {
+            *out_exit_code = exit_code;
+            BoxliteErrorCode::Ok
+        }
+        Err(e) => {
+            let code = error_to_code(&e);
+            write_error(out_error, e);
+            code
+        }
+    }
+}
+
+/// Build the command of the execute functions from C arguments.
+///
+/// On failure the error is written to `out_error` and its code returned.
+unsafe fn exec_command(
+    command: *const c_char,
+    args_json: *const c_char,
+    out_error: *mut CBoxliteError,
+) -> Result<boxlite::BoxCommand, BoxliteErrorCode> {
     // Parse command
     let cmd_str = match c_str_to_string(command) {
         Ok(s) => s,
         Err(e) => {
             let code = error_to_code(&e);
             write_error(out_error, e);
-            return code;
+            return Err(code);
         }
     };
 
@@ -460,76 +1054,20 @@ pub unsafe extern "C" fn boxlite_execute(
                 Err(e) => {
                     let err = BoxliteError::Internal(format!("Invalid args JSON: {}", e));
                     write_error(out_error, err);
-                    return BoxliteErrorCode::InvalidArgument;
+                    return Err(BoxliteErrorCode::InvalidArgument);
                 }
             },
             Err(e) => {
                 let code = error_to_code(&e);
                 write_error(out_error, e);
-                return code;
+                return Err(code);
             }
         }
     } else {
         vec![]
     };
 
-    let mut cmd = boxlite::BoxCommand::new(cmd_str);
-    cmd = cmd.args(args);
-
-    // Execute command using new API
-    let result = handle_ref.tokio_rt.block_on(async {
-        let mut execution = handle_ref.handle.exec(cmd).await?;
-
-        // Stream output to callback if provided
-        if let Some(cb) = callback {
-            use futures::StreamExt;
-
-            // Take stdout and stderr
-            let mut stdout = execution.stdout();
-            let mut stderr = execution.stderr();
-
-            // Read both streams
-            loop {
-                tokio::select! {
-                    Some(line) = async {
-                        match &mut stdout {
-                            Some(s) => s.next().await,
-                            None => None,
-                        }
-                    } => {
-                        let c_text = CString::new(line).unwrap_or_default();
-                        cb(c_text.as_ptr(), 0, user_data); // 0 = stdout
-                    }
-                    Some(line) = async {
-                        match &mut stderr {
-                            Some(s) => s.next().await,
-                            None => None,
-                        }
-                    } => {
-                        let c_text = CString::new(line).unwrap_or_default();
-                        cb(c_text.as_ptr(), 1, user_data); // 1 = stderr
-                    }
-                    else => break,
-                }
-            }
-        }
-
-        // Wait for execution to complete
-        let status = execution.wait().await?;
-        Ok::<i32, BoxliteError>(status.exit_code)
-    });
-
-    match result {
-        Ok(exit_code) => {
-            *out_exit_code = exit_code;
-            BoxliteErrorCode::Ok
-        }
-        Err(e) => {
-            let code = error_to_code(&e);
-            write_error(out_error, e);
-            code
-        }
-    }
+    Ok(boxlite::BoxCommand::new(cmd_str).args(args))
 }
 
 /// Stop a box
@@ -864,7 +1402,9 @@ pub unsafe extern "C" fn boxlite_runtime_metrics(
         "boxes_failed_total": metrics.boxes_failed_total(),
         "num_running_boxes": metrics.num_running_boxes(),
         "total_commands_executed": metrics.total_commands_executed(),
-        "total_exec_errors": metrics.total_exec_errors()
+        "total_exec_errors": metrics.total_exec_errors(),
+        "registry_retries_total": metrics.registry_retries_total(),
+        "events_dropped_total": metrics.events_dropped_total()
     });
 
     let json_str = match serde_json::to_string(&json) {
@@ -889,6 +1429,25 @@ pub unsafe extern "C" fn boxlite_runtime_metrics(
     }
 }
 
+/// Shut down a runtime; `timeout` as in `boxlite_runtime_shutdown`.
+unsafe fn shutdown_runtime(
+    runtime: *mut CBoxliteRuntime,
+    timeout: c_int,
+) -> Result<ShutdownReport, BoxliteError> {
+    if runtime.is_null() {
+        return Err(null_pointer_error("runtime"));
+    }
+
+    let runtime_ref = &*runtime;
+
+    // C API: 0 = default (maps to Rust None), positive = timeout, -1 = infinite
+    let timeout_opt = if timeout == 0 { None } else { Some(timeout) };
+
+    runtime_ref
+        .tokio_rt
+        .block_on(runtime_ref.runtime.shutdown(timeout_opt))
+}
+
 /// Gracefully shutdown all boxes in this runtime.
 ///
 /// This method stops all running boxes, waiting up to `timeout` seconds
@@ -896,6 +1455,7 @@ pub unsafe extern "C" fn boxlite_runtime_metrics(
 ///
 /// After calling this method, the runtime is permanently shut down and
 /// will return errors for any new operations (like `create()`).
+/// Use `boxlite_runtime_shutdown_report` to learn which boxes were killed.
 ///
 /// # Arguments
 /// * `runtime` - BoxLite runtime instance
@@ -913,26 +1473,71 @@ pub unsafe extern "C" fn boxlite_runtime_shutdown(
     timeout: c_int,
     out_error: *mut CBoxliteError,
 ) -> BoxliteErrorCode {
-    if runtime.is_null() {
-        write_error(out_error, null_pointer_error("runtime"));
-        return BoxliteErrorCode::InvalidArgument;
+    match shutdown_runtime(runtime, timeout) {
+        Ok(_report) => BoxliteErrorCode::Ok,
+        Err(e) => {
+            let code = error_to_code(&e);
+            write_error(out_error, e);
+            code
+        }
     }
+}
 
-    let runtime_ref = &*runtime;
-
-    // C API: 0 = default (maps to Rust None), positive = timeout, -1 = infinite
-    let timeout_opt = if timeout == 0 { None } else { Some(timeout) };
-
-    let result = runtime_ref
-        .tokio_rt
-        .block_on(runtime_ref.runtime.shutdown(timeout_opt));
+/// Shut down a runtime like `boxlite_runtime_shutdown`, and report what
+/// happened to each box.
+///
+/// The report is a JSON object: `clean` is true when every box stopped
+/// gracefully, and `boxes` lists `{"box_id", "outcome", "error"}` per active
+/// box, with `outcome` one of `stopped`, `killed` or `failed` (`error` set).
+///
+/// # Arguments
+/// * `runtime` - BoxLite runtime instance
+/// * `timeout` - Seconds to wait before force-killing each box, as for
+///   `boxlite_runtime_shutdown`
+/// * `out_json` - Output parameter for the JSON report
+/// * `out_error` - Output parameter for error information
+///
+/// # Returns
+/// BoxliteErrorCode::Ok on success, error code on failure
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_runtime_shutdown_report(
+    runtime: *mut CBoxliteRuntime,
+    timeout: c_int,
+    out_json: *mut *mut c_char,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if out_json.is_null() {
+        write_error(out_error, null_pointer_error("out_json"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
 
-    match result {
-        Ok(()) => BoxliteErrorCode::Ok,
+    let report = match shutdown_runtime(runtime, timeout) {
+        Ok(report) => report,
         Err(e) => {
             let code = error_to_code(&e);
             write_error(out_error, e);
-            code
+            return code;
+        }
+    };
+
+    let json_str = match serde_json::to_string(&shutdown_report_to_json(&report)) {
+        Ok(s) => s,
+        Err(e) => {
+            let err = BoxliteError::Internal(format!("JSON serialization failed: {}", e));
+            write_error(out_error, err);
+            return BoxliteErrorCode::Internal;
+        }
+    };
+
+    match CString::new(json_str) {
+        Ok(s) => {
+            *out_json = s.into_raw();
+            BoxliteErrorCode::Ok
+        }
+        Err(e) => {
+            let err = BoxliteError::Internal(format!("CString conversion failed: {}", e));
+            write_error(out_error, err);
+            BoxliteErrorCode::Internal
         }
     }
 }
@@ -1016,20 +1621,7 @@ pub unsafe extern "C" fn boxlite_box_metrics(
 
     match result {
         Ok(metrics) => {
-            let json = serde_json::json!({
-                "cpu_percent": metrics.cpu_percent,
-                "memory_bytes": metrics.memory_bytes,
-                "commands_executed_total": metrics.commands_executed_total,
-                "exec_errors_total": metrics.exec_errors_total,
-                "bytes_sent_total": metrics.bytes_sent_total,
-                "bytes_received_total": metrics.bytes_received_total,
-                "total_create_duration_ms": metrics.total_create_duration_ms,
-                "guest_boot_duration_ms": metrics.guest_boot_duration_ms,
-                "network_bytes_sent": metrics.network_bytes_sent,
-                "network_bytes_received": metrics.network_bytes_received,
-                "network_tcp_connections": metrics.network_tcp_connections,
-                "network_tcp_errors": metrics.network_tcp_errors
-            });
+            let json = box_metrics_to_json(&metrics);
 
             let json_str = match serde_json::to_string(&json) {
                 Ok(s) => s,
@@ -1114,6 +1706,173 @@ pub unsafe extern "C" fn boxlite_box_id(handle: *mut CBoxHandle) -> *mut c_char
     }
 }
 
+// ============================================================================
+// Batch API
+// ============================================================================
+
+/// `boxlite_batch()` flag: run the requests concurrently instead of in order.
+pub const BOXLITE_BATCH_CONCURRENT: u32 = 1;
+
+/// Run several operations in one call
+///
+/// For bindings that would otherwise cross the FFI boundary once per
+/// operation. `requests_json` is a JSON array of requests; each has an `op`
+/// and an optional `id`, echoed in its response. The operations are those of
+/// the `boxlite_op_*()` functions, with the same fields:
+///
+/// | `op` | Fields | Result |
+/// |------|--------|--------|
+/// | `create` | `options` (BoxOptions), `name` | `{"id"}` |
+/// | `start`, `stop` | `box` (ID or name) | `null` |
+/// | `remove` | `box`, `force` | `null` |
+/// | `exec` | `box`, `command`, `args` | `{"exit_code", "stdout", "stderr"}` |
+/// | `info` | `box` | as `boxlite_box_info()` |
+/// | `metrics` | `box` | as `boxlite_box_metrics()` |
+///
+/// Requests run in order, each after the previous one finished, or all at
+/// once with `BOXLITE_BATCH_CONCURRENT` in `flags`. A failing request
+/// doesn't stop the others. `out_responses_json` receives a JSON array with
+/// one response per request, in request order: `{"id", "result"}` or
+/// `{"id", "error": {"code", "message"}}`, `code` being a `BoxliteErrorCode`
+/// (caller must free with `boxlite_free_string`). The function itself only
+/// fails for invalid arguments or when `requests_json` isn't an array.
+///
+/// # Example
+/// ```c
+/// const char *requests =
+///     "[{\"id\":1,\"op\":\"create\",\"name\":\"w1\","
+///     "\"options\":{\"rootfs\":{\"Image\":\"alpine:3.19\"}}},"
+///     "{\"id\":2,\"op\":\"exec\",\"box\":\"w1\",\"command\":\"echo\",\"args\":[\"hi\"]},"
+///     "{\"id\":3,\"op\":\"remove\",\"box\":\"w1\",\"force\":true}]";
+/// char *responses = NULL;
+/// BoxliteErrorCode code = boxlite_batch(runtime, requests, 0, &responses, &error);
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn boxlite_batch(
+    runtime: *mut CBoxliteRuntime,
+    requests_json: *const c_char,
+    flags: u32,
+    out_responses_json: *mut *mut c_char,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if runtime.is_null() {
+        write_error(out_error, null_pointer_error("runtime"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+    if out_responses_json.is_null() {
+        write_error(out_error, null_pointer_error("out_responses_json"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    let runtime_ref = &*runtime;
+
+    let requests_str = match c_str_to_string(requests_json) {
+        Ok(s) => s,
+        Err(e) => {
+            write_error(out_error, e);
+            return BoxliteErrorCode::InvalidArgument;
+        }
+    };
+    let requests: Vec<serde_json::Value> = match serde_json::from_str(&requests_str) {
+        Ok(requests) => requests,
+        Err(e) => {
+            let err = BoxliteError::InvalidArgument(format!("Invalid batch JSON: {}", e));
+            write_error(out_error, err);
+            return BoxliteErrorCode::InvalidArgument;
+        }
+    };
+
+    let concurrent = flags & BOXLITE_BATCH_CONCURRENT != 0;
+    let responses =
+        runtime_ref
+            .tokio_rt
+            .block_on(batch::run(&runtime_ref.runtime, requests, concurrent));
+
+    match CString::new(responses.to_string()) {
+        Ok(s) => {
+            *out_responses_json = s.into_raw();
+            BoxliteErrorCode::Ok
+        }
+        Err(e) => {
+            let err = BoxliteError::Internal(format!("CString conversion failed: {}", e));
+            write_error(out_error, err);
+            BoxliteErrorCode::Internal
+        }
+    }
+}
+
+/// Run the operation `name` for its `boxlite_op_*()` function (see `ops.rs`).
+pub(crate) unsafe fn run_op(
+    runtime: *mut CBoxliteRuntime,
+    name: &str,
+    params_json: *const c_char,
+    out_json: *mut *mut c_char,
+    out_error: *mut CBoxliteError,
+) -> BoxliteErrorCode {
+    if runtime.is_null() {
+        write_error(out_error, null_pointer_error("runtime"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+    if out_json.is_null() {
+        write_error(out_error, null_pointer_error("out_json"));
+        return BoxliteErrorCode::InvalidArgument;
+    }
+
+    let runtime_ref = &*runtime;
+
+    let params_str = if params_json.is_null() {
+        "{}".to_string()
+    } else {
+        match c_str_to_string(params_json) {
+            Ok(s) => s,
+            Err(e) => {
+                write_error(out_error, e);
+                return BoxliteErrorCode::InvalidArgument;
+            }
+        }
+    };
+    // The fields of a batch request, with the operation's name as its `op`
+    let op = match serde_json::from_str(&params_str) {
+        Ok(serde_json::Value::Object(mut fields)) => {
+            fields.insert("op".to_string(), name.into());
+            serde_json::from_value::<Op>(serde_json::Value::Object(fields))
+                .map_err(|e| format!("Invalid {} parameters: {}", name, e))
+        }
+        Ok(_) => Err("params_json must be a JSON object".to_string()),
+        Err(e) => Err(format!("Invalid params JSON: {}", e)),
+    };
+    let op = match op {
+        Ok(op) => op,
+        Err(message) => {
+            write_error(out_error, BoxliteError::InvalidArgument(message));
+            return BoxliteErrorCode::InvalidArgument;
+        }
+    };
+
+    let result = runtime_ref
+        .tokio_rt
+        .block_on(op.execute(&runtime_ref.runtime));
+
+    match result {
+        Ok(value) => match CString::new(value.to_string()) {
+            Ok(s) => {
+                *out_json = s.into_raw();
+                BoxliteErrorCode::Ok
+            }
+            Err(e) => {
+                let err = BoxliteError::Internal(format!("CString conversion failed: {}", e));
+                write_error(out_error, err);
+                BoxliteErrorCode::Internal
+            }
+        },
+        Err(e) => {
+            let code = error_to_code(&e);
+            write_error(out_error, e);
+            code
+        }
+    }
+}
+
 // ============================================================================
 // Simple Convenience API
 // ============================================================================
@@ -1152,16 +1911,15 @@ pub unsafe extern "C" fn boxlite_simple_new(
         }
     };
 
-    let tokio_rt = match TokioRuntime::new() {
-        Ok(rt) => Arc::new(rt),
+    let tokio_rt = match AsyncExecutor::new() {
+        Ok(rt) => rt,
         Err(e) => {
-            let err = BoxliteError::Internal(format!("Failed to create async runtime: {}", e));
-            write_error(out_error, err);
+            write_error(out_error, e);
             return BoxliteErrorCode::Internal;
         }
     };
 
-    let runtime = match BoxliteRuntime::new(BoxliteOptions::default()) {
+    let runtime = match tokio_rt.enter(|| BoxliteRuntime::new(BoxliteOptions::default())) {
         Ok(rt) => rt,
         Err(e) => {
             write_error(out_error, e);
@@ -1266,49 +2024,8 @@ pub unsafe extern "C" fn boxlite_simple_run(
         }
     };
 
-    let result = simple_ref.tokio_rt.block_on(async {
-        let mut cmd = boxlite::BoxCommand::new(cmd_str);
-        cmd = cmd.args(arg_vec);
-
-        let mut execution = handle.exec(cmd).await?;
-
-        use futures::StreamExt;
-        let mut stdout_lines = Vec::new();
-        let mut stderr_lines = Vec::new();
-
-        let mut stdout_stream = execution.stdout();
-        let mut stderr_stream = execution.stderr();
-
-        loop {
-            tokio::select! {
-                Some(line) = async {
-                    match &mut stdout_stream {
-                        Some(s) => s.next().await,
-                        None => None,
-                    }
-                } => {
-                    stdout_lines.push(line);
-                }
-                Some(line) = async {
-                    match &mut stderr_stream {
-                        Some(s) => s.next().await,
-                        None => None,
-                    }
-                } => {
-                    stderr_lines.push(line);
-                }
-                else => break,
-            }
-        }
-
-        let status = execution.wait().await?;
-
-        Ok::<(i32, String, String), BoxliteError>((
-            status.exit_code,
-            stdout_lines.join("\n"),
-            stderr_lines.join("\n"),
-        ))
-    });
+    let cmd = boxlite::BoxCommand::new(cmd_str).args(arg_vec);
+    let result = simple_ref.tokio_rt.block_on(run_collecting(handle, cmd));
 
     match result {
         Ok((exit_code, stdout, stderr)) => {
@@ -1425,6 +2142,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_abi_version_check() {
+        let current = boxlite_abi_version();
+        assert_eq!(current.major, BOXLITE_ABI_VERSION_MAJOR);
+        assert!(current.is_supported());
+
+        let newer_minor = BoxliteAbiVersion {
+            minor: current.minor + 1,
+            ..current
+        };
+        assert!(!newer_minor.is_supported());
+        let other_major = BoxliteAbiVersion {
+            major: current.major + 1,
+            ..current
+        };
+        assert!(!other_major.is_supported());
+
+        unsafe {
+            let mut runtime = ptr::null_mut();
+            let mut error = CBoxliteError::default();
+            let code = boxlite_runtime_new_with_abi(
+                other_major,
+                ptr::null(),
+                ptr::null(),
+                &mut runtime,
+                &mut error,
+            );
+            assert_eq!(code, BoxliteErrorCode::AbiMismatch);
+            assert_eq!(error.code, BoxliteErrorCode::AbiMismatch);
+            assert!(runtime.is_null());
+            assert!(!error.message.is_null());
+            boxlite_error_free(&mut error);
+        }
+    }
+
     #[test]
     fn test_error_code_mapping() {
         assert_eq!(
@@ -1517,6 +2269,7 @@ mod tests {
         assert_eq!(status_to_string(BoxStatus::Unknown), "unknown");
         assert_eq!(status_to_string(BoxStatus::Configured), "configured");
         assert_eq!(status_to_string(BoxStatus::Running), "running");
+        assert_eq!(status_to_string(BoxStatus::Unresponsive), "unresponsive");
         assert_eq!(status_to_string(BoxStatus::Stopping), "stopping");
         assert_eq!(status_to_string(BoxStatus::Stopped), "stopped");
     }
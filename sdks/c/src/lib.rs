@@ -3,7 +3,10 @@
 //! This crate provides C FFI bindings for the BoxLite runtime,
 //! building the C shared library and static library artifacts.
 
+mod batch;
 pub mod ffi;
+pub mod ops;
+mod watchdog;
 
 // Re-export all FFI symbols
 pub use ffi::*;
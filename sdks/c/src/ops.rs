@@ -0,0 +1,169 @@
+//! Box operations exposed to C, declared once.
+//!
+//! The `ops!` invocation below is the single list of operations. Each entry
+//! generates:
+//! - a variant of [`Op`], run by `boxlite_batch()` for requests whose `op`
+//!   is the entry's name
+//! - a `boxlite_op_<name>()` C function running that operation alone
+//!
+//! so an operation added here is available both ways. The C functions all
+//! take the operation's fields as a JSON object (`params_json`, NULL for
+//! none) and return its result as JSON in `out_json`, like a batch response's
+//! `result`. cbindgen doesn't expand macros; build.rs reads this list to
+//! declare them in boxlite.h.
+
+use boxlite::BoxliteError;
+use boxlite::litebox::LiteBox;
+use boxlite::runtime::BoxliteRuntime;
+use boxlite::runtime::options::BoxOptions;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::os::raw::c_char;
+
+use crate::ffi::{
+    BoxliteErrorCode, CBoxliteError, CBoxliteRuntime, box_info_to_json, box_metrics_to_json,
+    run_collecting, run_op,
+};
+
+/// Generate [`Op`], its dispatch and one C function per operation.
+///
+/// Entry syntax: `"name" => c_function: Variant { fields } |runtime| body`,
+/// where `body` runs with the fields bound and returns the JSON result.
+macro_rules! ops {
+    ($(
+        $(#[doc = $doc:literal])*
+        $name:literal => $c_fn:ident: $variant:ident {
+            $($(#[$attr:meta])* $field:ident: $ty:ty),* $(,)?
+        } |$runtime:ident| $body:block
+    )*) => {
+        /// An operation, named by `op` in a batch request.
+        #[derive(Debug, Deserialize)]
+        #[serde(tag = "op")]
+        pub(crate) enum Op {
+            $(
+                $(#[doc = $doc])*
+                #[serde(rename = $name)]
+                $variant { $($(#[$attr])* $field: $ty),* },
+            )*
+        }
+
+        impl Op {
+            pub(crate) async fn execute(self, runtime: &BoxliteRuntime) -> Result<Value, BoxliteError> {
+                match self {
+                    $(Op::$variant { $($field),* } => {
+                        let $runtime = runtime;
+                        $body
+                    })*
+                }
+            }
+        }
+
+        $(
+            $(#[doc = $doc])*
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn $c_fn(
+                runtime: *mut CBoxliteRuntime,
+                params_json: *const c_char,
+                out_json: *mut *mut c_char,
+                out_error: *mut CBoxliteError,
+            ) -> BoxliteErrorCode {
+                unsafe { run_op(runtime, $name, params_json, out_json, out_error) }
+            }
+        )*
+    };
+}
+
+ops! {
+    /// Create a box.
+    ///
+    /// Fields: `options` (BoxOptions), `name` (optional). Result: `{"id"}` of
+    /// the new box.
+    "create" => boxlite_op_create: Create {
+        options: BoxOptions,
+        #[serde(default)]
+        name: Option<String>,
+    } |runtime| {
+        let litebox = runtime.create(options, name).await?;
+        Ok(json!({ "id": litebox.id().as_str() }))
+    }
+
+    /// Start a box.
+    ///
+    /// Fields: `box` (ID or name). Result: `null`.
+    "start" => boxlite_op_start: Start {
+        #[serde(rename = "box")]
+        target: String,
+    } |runtime| {
+        lookup(runtime, &target).await?.start().await?;
+        Ok(Value::Null)
+    }
+
+    /// Stop a box.
+    ///
+    /// Fields: `box` (ID or name). Result: `null`.
+    "stop" => boxlite_op_stop: Stop {
+        #[serde(rename = "box")]
+        target: String,
+    } |runtime| {
+        lookup(runtime, &target).await?.stop().await?;
+        Ok(Value::Null)
+    }
+
+    /// Remove a box.
+    ///
+    /// Fields: `box` (ID or name), `force` (optional). Result: `null`.
+    "remove" => boxlite_op_remove: Remove {
+        #[serde(rename = "box")]
+        target: String,
+        #[serde(default)]
+        force: bool,
+    } |runtime| {
+        runtime.remove(&target, force).await?;
+        Ok(Value::Null)
+    }
+
+    /// Run a command in a box and collect its output.
+    ///
+    /// Fields: `box` (ID or name), `command`, `args` (optional). Result:
+    /// `{"exit_code", "stdout", "stderr"}`, as `boxlite_simple_run()`.
+    "exec" => boxlite_op_exec: Exec {
+        #[serde(rename = "box")]
+        target: String,
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    } |runtime| {
+        let litebox = lookup(runtime, &target).await?;
+        let command = boxlite::BoxCommand::new(command).args(args);
+        let (exit_code, stdout, stderr) = run_collecting(&litebox, command).await?;
+        Ok(json!({ "exit_code": exit_code, "stdout": stdout, "stderr": stderr }))
+    }
+
+    /// Get a box's info.
+    ///
+    /// Fields: `box` (ID or name). Result: as `boxlite_box_info()`.
+    "info" => boxlite_op_info: Info {
+        #[serde(rename = "box")]
+        target: String,
+    } |runtime| {
+        Ok(box_info_to_json(&lookup(runtime, &target).await?.info()))
+    }
+
+    /// Get a box's metrics.
+    ///
+    /// Fields: `box` (ID or name). Result: as `boxlite_box_metrics()`.
+    "metrics" => boxlite_op_metrics: Metrics {
+        #[serde(rename = "box")]
+        target: String,
+    } |runtime| {
+        let metrics = lookup(runtime, &target).await?.metrics().await?;
+        Ok(box_metrics_to_json(&metrics))
+    }
+}
+
+async fn lookup(runtime: &BoxliteRuntime, id_or_name: &str) -> Result<LiteBox, BoxliteError> {
+    runtime
+        .get(id_or_name)
+        .await?
+        .ok_or_else(|| BoxliteError::NotFound(id_or_name.to_string()))
+}
@@ -0,0 +1,276 @@
+//! Watchdog for C calls that stay blocked.
+//!
+//! Every C function blocks its thread on a Tokio runtime until the work is
+//! done, so a hang anywhere below (a stuck guest, a deadlocked task) shows
+//! up as a call that never returns. Once enabled with
+//! `boxlite_set_watchdog()`, a background thread checks the calls in
+//! progress and reports each one that outlives the timeout, once: the call
+//! site, the state of its runtime, the portal calls still waiting for a
+//! guest and, with the `taskdump` feature, the Tokio task list. The report
+//! is logged and passed to the diagnostic callback as JSON. The blocked
+//! call itself keeps waiting.
+
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_void};
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant};
+
+use tokio::runtime::Handle;
+
+/// Receives the JSON report of a blocked call; the string is only valid
+/// during the callback.
+pub type DiagnosticCallback = extern "C" fn(*const c_char, *mut c_void);
+
+/// Upper bound of the interval between checks.
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a task dump may take; workers blocked in a deadlock never
+/// yield to it.
+#[cfg(feature = "taskdump")]
+const TASK_DUMP_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct Config {
+    timeout: Duration,
+    callback: Option<DiagnosticCallback>,
+    /// `*mut c_void` of the caller, kept as an address to be `Send`
+    user_data: usize,
+}
+
+static CONFIG: Mutex<Option<Config>> = Mutex::new(None);
+
+/// C calls in progress by sequence number.
+static CALLS: Mutex<BTreeMap<u64, BlockedCall>> = Mutex::new(BTreeMap::new());
+
+static NEXT_CALL: AtomicU64 = AtomicU64::new(0);
+
+static MONITOR: Once = Once::new();
+
+struct BlockedCall {
+    location: &'static Location<'static>,
+    thread: String,
+    started: Instant,
+    handle: Handle,
+    reported: bool,
+}
+
+/// Enable the watchdog with `timeout`, or disable it with `None`.
+pub(crate) fn configure(
+    timeout: Option<Duration>,
+    callback: Option<DiagnosticCallback>,
+    user_data: *mut c_void,
+) {
+    let config = timeout.map(|timeout| Config {
+        timeout,
+        callback,
+        user_data: user_data as usize,
+    });
+    let enabled = config.is_some();
+    *CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = config;
+
+    if enabled {
+        MONITOR.call_once(|| {
+            let spawned = std::thread::Builder::new()
+                .name("boxlite-watchdog".to_string())
+                .spawn(monitor);
+            if let Err(e) = spawned {
+                tracing::error!("Failed to start the watchdog thread: {}", e);
+            }
+        });
+    }
+}
+
+/// Register a blocking call on `handle` until the guard is dropped; `None`
+/// while the watchdog is off.
+pub(crate) fn watch(location: &'static Location<'static>, handle: &Handle) -> Option<WatchGuard> {
+    if CONFIG.lock().unwrap_or_else(|e| e.into_inner()).is_none() {
+        return None;
+    }
+
+    let id = NEXT_CALL.fetch_add(1, Ordering::Relaxed);
+    let thread = std::thread::current();
+    let thread = match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    };
+    CALLS.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        id,
+        BlockedCall {
+            location,
+            thread,
+            started: Instant::now(),
+            handle: handle.clone(),
+            reported: false,
+        },
+    );
+    Some(WatchGuard(id))
+}
+
+/// Ends the watch of a call.
+pub(crate) struct WatchGuard(u64);
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        let call = CALLS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.0);
+        if let Some(call) = call.filter(|call| call.reported) {
+            tracing::warn!(
+                call = %call.location,
+                elapsed_ms = call.started.elapsed().as_millis() as u64,
+                "Blocked C call returned"
+            );
+        }
+    }
+}
+
+fn monitor() {
+    loop {
+        let interval = CONFIG
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map_or(MAX_CHECK_INTERVAL, |config| {
+                (config.timeout / 4).clamp(Duration::from_millis(10), MAX_CHECK_INTERVAL)
+            });
+        std::thread::sleep(interval);
+        check();
+    }
+}
+
+/// Report the calls that just passed the timeout.
+fn check() {
+    let (timeout, callback, user_data) = match &*CONFIG.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(config) => (config.timeout, config.callback, config.user_data),
+        None => return,
+    };
+
+    let overdue: Vec<_> = CALLS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values_mut()
+        .filter(|call| !call.reported && call.started.elapsed() >= timeout)
+        .map(|call| {
+            call.reported = true;
+            (
+                call.location,
+                call.thread.clone(),
+                call.started,
+                call.handle.clone(),
+            )
+        })
+        .collect();
+
+    for (location, thread, started, handle) in overdue {
+        let report = report(location, &thread, started.elapsed(), &handle);
+        tracing::error!(
+            call = %location,
+            thread = %thread,
+            "C call blocked for over {:?}: {}",
+            timeout,
+            report
+        );
+        if let Some(callback) = callback
+            && let Ok(report) = CString::new(report.to_string())
+        {
+            callback(report.as_ptr(), user_data as *mut c_void);
+        }
+    }
+}
+
+fn report(
+    location: &Location<'_>,
+    thread: &str,
+    elapsed: Duration,
+    handle: &Handle,
+) -> serde_json::Value {
+    let metrics = handle.metrics();
+    let portal_calls: Vec<_> = boxlite::in_flight_portal_calls()
+        .into_iter()
+        .map(|call| {
+            serde_json::json!({
+                "target": call.target,
+                "method": call.method,
+                "elapsed_ms": call.elapsed.as_millis() as u64,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "call": location.to_string(),
+        "thread": thread,
+        "elapsed_ms": elapsed.as_millis() as u64,
+        "runtime": {
+            "workers": metrics.num_workers(),
+            "alive_tasks": metrics.num_alive_tasks(),
+            "global_queue_depth": metrics.global_queue_depth(),
+        },
+        "portal_calls": portal_calls,
+        "tasks": task_dump(handle),
+    })
+}
+
+/// Traces of the runtime's tasks, if they can be taken in time.
+#[cfg(feature = "taskdump")]
+fn task_dump(handle: &Handle) -> Option<String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = handle.clone();
+    // Left behind if the runtime never lets the dump finish
+    std::thread::spawn(move || {
+        let dump = handle.block_on(handle.dump());
+        let tasks: Vec<String> = dump
+            .tasks()
+            .iter()
+            .map(|task| format!("task {}:\n{}", task.id(), task.trace()))
+            .collect();
+        let _ = tx.send(tasks.join("\n"));
+    });
+    rx.recv_timeout(TASK_DUMP_TIMEOUT).ok()
+}
+
+#[cfg(not(feature = "taskdump"))]
+fn task_dump(_handle: &Handle) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    static REPORTS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn count_report(report: *const c_char, _user_data: *mut c_void) {
+        let report = unsafe { std::ffi::CStr::from_ptr(report) }
+            .to_str()
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_str(report).unwrap();
+        // Other tests may block on a runtime meanwhile
+        if report["call"].as_str().unwrap().contains("watchdog.rs")
+            && report["runtime"]["workers"].as_u64().unwrap() >= 1
+        {
+            REPORTS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_watchdog_reports_blocked_call_once() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        configure(
+            Some(Duration::from_millis(50)),
+            Some(count_report),
+            std::ptr::null_mut(),
+        );
+
+        let guard = watch(Location::caller(), rt.handle()).unwrap();
+        std::thread::sleep(Duration::from_millis(400));
+        drop(guard);
+        assert_eq!(REPORTS.load(Ordering::SeqCst), 1);
+
+        configure(None, None, std::ptr::null_mut());
+        assert!(watch(Location::caller(), rt.handle()).is_none());
+    }
+}
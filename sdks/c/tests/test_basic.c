@@ -66,6 +66,31 @@ void test_runtime_with_registries() {
     boxlite_runtime_free(runtime);
 }
 
+void test_runtime_from_json() {
+    printf("\nTEST: Runtime from JSON options\n");
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    const char* options =
+        "{\"image_registries\":[\"docker.io\"],"
+        "\"default_security\":\"standard\","
+        "\"default_pull_policy\":\"always\"}";
+
+    BoxliteErrorCode code = boxlite_runtime_new_from_json(options, &runtime, &error);
+    assert(code == Ok);
+    assert(runtime != NULL);
+    printf("  ✓ Runtime created from JSON options\n");
+    boxlite_runtime_free(runtime);
+
+    runtime = NULL;
+    code = boxlite_runtime_new_from_json("{\"default_security\":\"paranoid\"}", &runtime, &error);
+    assert(code != Ok);
+    assert(runtime == NULL);
+    assert(error.message != NULL);
+    printf("  ✓ Unknown preset rejected: %s\n", error.message);
+    boxlite_error_free(&error);
+}
+
 void test_runtime_shutdown() {
     printf("\nTEST: Runtime shutdown\n");
 
@@ -83,6 +108,53 @@ void test_runtime_shutdown() {
     boxlite_runtime_free(runtime);
 }
 
+void test_runtime_shutdown_report() {
+    printf("\nTEST: Runtime shutdown report\n");
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    BoxliteErrorCode code = boxlite_runtime_new(NULL, NULL, &runtime, &error);
+    assert(code == Ok);
+
+    char* report = NULL;
+    code = boxlite_runtime_shutdown_report(runtime, 0, &report, &error);
+    assert(code == Ok);
+    assert(report != NULL);
+    // No active boxes: nothing was killed
+    assert(strstr(report, "\"clean\":true") != NULL);
+    assert(strstr(report, "\"boxes\":[]") != NULL);
+    printf("  ✓ Shutdown report: %s\n", report);
+
+    boxlite_free_string(report);
+    boxlite_runtime_free(runtime);
+}
+
+static int watchdog_reports = 0;
+
+static void on_blocked_call(const char* report, void* user_data) {
+    assert(strstr(report, "\"call\"") != NULL);
+    assert(strstr(report, "\"portal_calls\"") != NULL);
+    (*(int*)user_data)++;
+}
+
+void test_watchdog() {
+    printf("\nTEST: Watchdog\n");
+
+    // Reports any call that takes over 1ms, so some of these may be reported
+    boxlite_set_watchdog(1, on_blocked_call, &watchdog_reports);
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    BoxliteErrorCode code = boxlite_runtime_new(NULL, NULL, &runtime, &error);
+    assert(code == Ok);
+    code = boxlite_runtime_shutdown(runtime, 0, &error);
+    assert(code == Ok);
+    boxlite_runtime_free(runtime);
+
+    boxlite_set_watchdog(0, NULL, NULL);
+    printf("  ✓ Watchdog set and cleared (%d reports)\n", watchdog_reports);
+}
+
 void test_error_string_cleanup() {
     printf("\nTEST: Error string cleanup\n");
 
@@ -122,12 +194,15 @@ int main() {
     test_runtime_creation();
     test_runtime_with_custom_home();
     test_runtime_with_registries();
+    test_runtime_from_json();
     test_runtime_shutdown();
+    test_runtime_shutdown_report();
+    test_watchdog();
     test_error_string_cleanup();
     test_null_safety();
 
     printf("\n═══════════════════════════════════════\n");
-    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 7);
+    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 9);
     printf("═══════════════════════════════════════\n");
 
     return 0;
@@ -34,6 +34,29 @@ void test_error_struct_default() {
     printf("  ✓ Default error struct is OK with NULL message\n");
 }
 
+void test_abi_version() {
+    printf("\nTEST: ABI version negotiation\n");
+
+    BoxliteAbiVersion abi = boxlite_abi_version();
+    assert(abi.major == BOXLITE_ABI_VERSION_MAJOR);
+    assert(abi.minor == BOXLITE_ABI_VERSION_MINOR);
+
+    // A caller built against a newer minor version needs functions we lack
+    BoxliteAbiVersion newer = abi;
+    newer.minor += 1;
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    BoxliteErrorCode code = boxlite_runtime_new_with_abi(newer, NULL, NULL, &runtime, &error);
+
+    assert(code == AbiMismatch);
+    assert(error.code == AbiMismatch);
+    assert(runtime == NULL);
+    assert(error.message != NULL);
+    printf("  ✓ Newer caller rejected: %s\n", error.message);
+
+    boxlite_error_free(&error);
+}
+
 void test_invalid_json_error() {
     printf("\nTEST: Invalid JSON error\n");
 
@@ -75,6 +98,72 @@ void test_not_found_error() {
     boxlite_runtime_free(runtime);
 }
 
+void test_batch_errors() {
+    printf("\nTEST: Batch errors\n");
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    const char* temp_dir = "/tmp/boxlite-test-errors-batch";
+    BoxliteErrorCode code = boxlite_runtime_new(temp_dir, NULL, &runtime, &error);
+    assert(code == Ok);
+
+    // Not an array: the whole call fails
+    char* responses = NULL;
+    code = boxlite_batch(runtime, "{\"op\": \"info\"}", 0, &responses, &error);
+    assert(code == InvalidArgument);
+    assert(responses == NULL);
+    printf("  ✓ Non-array batch rejected: %s\n", error.message);
+    boxlite_error_free(&error);
+
+    // Failing requests are reported per request
+    const char* requests =
+        "[{\"id\": \"a\", \"op\": \"info\", \"box\": \"nonexistent-box-id\"},"
+        " {\"id\": \"b\", \"op\": \"reboot\"}]";
+    code = boxlite_batch(runtime, requests, 0, &responses, &error);
+    assert(code == Ok);
+    assert(responses != NULL);
+    assert(strstr(responses, "\"id\":\"a\"") != NULL);
+    assert(strstr(responses, "\"code\":2") != NULL);  // NotFound
+    assert(strstr(responses, "\"code\":5") != NULL);  // InvalidArgument
+    printf("  ✓ Per-request errors: %s\n", responses);
+
+    boxlite_free_string(responses);
+    boxlite_runtime_free(runtime);
+}
+
+void test_op_errors() {
+    printf("\nTEST: Operation errors\n");
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    const char* temp_dir = "/tmp/boxlite-test-errors-op";
+    BoxliteErrorCode code = boxlite_runtime_new(temp_dir, NULL, &runtime, &error);
+    assert(code == Ok);
+
+    // Fields must be a JSON object
+    char* json = NULL;
+    code = boxlite_op_info(runtime, "[\"nonexistent-box-id\"]", &json, &error);
+    assert(code == InvalidArgument);
+    assert(json == NULL);
+    printf("  ✓ Non-object params rejected: %s\n", error.message);
+    boxlite_error_free(&error);
+
+    // Missing required field
+    code = boxlite_op_info(runtime, NULL, &json, &error);
+    assert(code == InvalidArgument);
+    assert(json == NULL);
+    printf("  ✓ Missing field rejected: %s\n", error.message);
+    boxlite_error_free(&error);
+
+    code = boxlite_op_info(runtime, "{\"box\": \"nonexistent-box-id\"}", &json, &error);
+    assert(code == NotFound);
+    assert(json == NULL);
+    printf("  ✓ NotFound error: %s\n", error.message);
+
+    boxlite_error_free(&error);
+    boxlite_runtime_free(runtime);
+}
+
 void test_invalid_argument_simple_api() {
     printf("\nTEST: InvalidArgument error (simple API)\n");
 
@@ -219,8 +308,11 @@ int main() {
 
     test_error_codes();
     test_error_struct_default();
+    test_abi_version();
     test_invalid_json_error();
     test_not_found_error();
+    test_batch_errors();
+    test_op_errors();
     test_invalid_argument_simple_api();
     test_invalid_argument_null_output();
     test_error_free_safety();
@@ -228,7 +320,7 @@ int main() {
     test_multiple_errors();
 
     printf("\n═══════════════════════════════════════\n");
-    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 9);
+    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 12);
     printf("═══════════════════════════════════════\n");
 
     return 0;
@@ -140,6 +140,120 @@ void test_streaming_both() {
     boxlite_runtime_free(runtime);
 }
 
+static int line_count = 0;
+static int partial_lines = 0;
+static int nul_lines = 0;
+
+void line_callback(const char* line, size_t len, int is_stderr, void* user_data) {
+    line_count++;
+    if (len == 0 || line[len - 1] != '\n') {
+        partial_lines++;
+    }
+    if (strlen(line) < len) {
+        nul_lines++;
+    }
+}
+
+void test_streaming_lines() {
+    printf("\nTEST: Streaming whole lines\n");
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    const char* temp_dir = "/tmp/boxlite-test-streaming-lines";
+    BoxliteErrorCode code = boxlite_runtime_new(temp_dir, NULL, &runtime, &error);
+    assert(code == Ok);
+    assert(runtime != NULL);
+
+    const char* options = "{\"rootfs\":{\"Image\":\"alpine:3.19\"},\"env\":[],\"volumes\":[],\"network\":\"Isolated\",\"ports\":[],\"auto_remove\":false}";
+    CBoxHandle* box = NULL;
+    code = boxlite_create_box(runtime, options, &box, &error);
+    assert(code == Ok);
+    assert(box != NULL);
+
+    // Lines written in pieces, and a last line without newline
+    const char* args = "[\"-c\", \"printf 'a'; sleep 0.2; printf 'b\\\\nc'; sleep 0.2; printf 'd\\\\ne'\"]";
+    line_count = 0;
+    partial_lines = 0;
+    int exit_code = 0;
+    code = boxlite_execute_lines(box, "/bin/sh", args, line_callback, NULL, &exit_code, &error);
+
+    assert(code == Ok);
+    assert(exit_code == 0);
+    printf("  ✓ Lines: %d\n", line_count);
+    assert(line_count == 3);
+    assert(partial_lines == 1);
+
+    // A NUL inside a line is passed on, not lost
+    const char* nul_args = "[\"-c\", \"printf 'a\\\\000b\\\\n'\"]";
+    line_count = 0;
+    partial_lines = 0;
+    nul_lines = 0;
+    code = boxlite_execute_lines(box, "/bin/sh", nul_args, line_callback, NULL, &exit_code, &error);
+    assert(code == Ok);
+    assert(line_count == 1);
+    assert(partial_lines == 0);
+    assert(nul_lines == 1);
+
+    // Cleanup
+    char* id = boxlite_box_id(box);
+    boxlite_remove(runtime, id, 1, &error);
+    boxlite_free_string(id);
+    boxlite_runtime_free(runtime);
+}
+
+typedef struct {
+    char streams[64];
+    char buffer[4096];
+} OrderedContext;
+
+void ordered_callback(const uint8_t* data, size_t len, int is_stderr, void* user_data) {
+    OrderedContext* ctx = (OrderedContext*)user_data;
+    size_t n = strlen(ctx->streams);
+    if (n < sizeof(ctx->streams) - 1) {
+        ctx->streams[n] = is_stderr ? 'E' : 'O';
+    }
+    size_t used = strlen(ctx->buffer);
+    if (len > sizeof(ctx->buffer) - used - 1) {
+        len = sizeof(ctx->buffer) - used - 1;
+    }
+    memcpy(ctx->buffer + used, data, len);
+}
+
+void test_streaming_ordered() {
+    printf("\nTEST: Streaming stdout and stderr in order\n");
+
+    CBoxliteRuntime* runtime = NULL;
+    CBoxliteError error = {0};
+    const char* temp_dir = "/tmp/boxlite-test-streaming-ordered";
+    BoxliteErrorCode code = boxlite_runtime_new(temp_dir, NULL, &runtime, &error);
+    assert(code == Ok);
+    assert(runtime != NULL);
+
+    const char* options = "{\"rootfs\":{\"Image\":\"alpine:3.19\"},\"env\":[],\"volumes\":[],\"network\":\"Isolated\",\"ports\":[],\"auto_remove\":false}";
+    CBoxHandle* box = NULL;
+    code = boxlite_create_box(runtime, options, &box, &error);
+    assert(code == Ok);
+    assert(box != NULL);
+
+    // Pauses keep each line in its own chunk
+    const char* args = "[\"-c\", \"echo one; sleep 0.2; echo two >&2; sleep 0.2; echo three\"]";
+    OrderedContext ctx = {0};
+    int exit_code = 0;
+    code = boxlite_execute_output(box, "/bin/sh", args, ordered_callback, &ctx, &exit_code, &error);
+
+    assert(code == Ok);
+    assert(exit_code == 0);
+    printf("  ✓ Streams: %s\n", ctx.streams);
+    assert(strcmp(ctx.streams, "OEO") == 0);
+    assert(strcmp(ctx.buffer, "one\ntwo\nthree\n") == 0);
+
+    // Cleanup
+    char* id = boxlite_box_id(box);
+    boxlite_remove(runtime, id, 1, &error);
+    boxlite_free_string(id);
+    boxlite_runtime_free(runtime);
+}
+
 typedef struct {
     int count;
     char buffer[4096];
@@ -267,9 +381,11 @@ int main() {
     test_streaming_with_context();
     test_streaming_large_output();
     test_streaming_no_callback();
+    test_streaming_ordered();
+    test_streaming_lines();
 
     printf("\n═══════════════════════════════════════\n");
-    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 6);
+    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 8);
     printf("═══════════════════════════════════════\n");
 
     return 0;
@@ -102,6 +102,14 @@ export interface SimpleBoxOptions {
    * If not set, uses the image's USER directive (defaults to root "0:0").
    */
   user?: string;
+
+  /**
+   * Guest path to mount a persistent cache directory at (e.g. "/cache").
+   *
+   * The directory is keyed by box name and survives box removal, so the next
+   * box with the same name sees the same contents. Requires `name`.
+   */
+  persistentCache?: string;
 }
 
 /**
@@ -196,6 +204,7 @@ export class SimpleBox {
       entrypoint: options.entrypoint,
       cmd: options.cmd,
       user: options.user,
+      persistentCache: options.persistentCache,
     };
 
     this._name = options.name;
@@ -7,6 +7,7 @@ pub struct JsCopyOptions {
     pub overwrite: Option<bool>,
     pub follow_symlinks: Option<bool>,
     pub include_parent: Option<bool>,
+    pub respect_ignore_files: Option<bool>,
 }
 
 pub fn into_copy_options(opts: Option<JsCopyOptions>) -> boxlite::CopyOptions {
@@ -24,6 +25,9 @@ pub fn into_copy_options(opts: Option<JsCopyOptions>) -> boxlite::CopyOptions {
         if let Some(v) = opt.include_parent {
             o.include_parent = v;
         }
+        if let Some(v) = opt.respect_ignore_files {
+            o.respect_ignore_files = v;
+        }
     }
     o
 }
@@ -1,4 +1,5 @@
 use boxlite::runtime::types::{BoxInfo, BoxStatus};
+use boxlite::{BoxShutdownOutcome, ShutdownReport};
 use napi_derive::napi;
 
 // ============================================================================
@@ -27,6 +28,7 @@ fn status_to_string(status: BoxStatus) -> String {
         BoxStatus::Unknown => "unknown",
         BoxStatus::Configured => "configured",
         BoxStatus::Running => "running",
+        BoxStatus::Unresponsive => "unresponsive",
         BoxStatus::Stopping => "stopping",
         BoxStatus::Stopped => "stopped",
     }
@@ -85,3 +87,56 @@ impl From<BoxInfo> for JsBoxInfo {
         }
     }
 }
+
+// ============================================================================
+// ShutdownReport - Per-box outcomes of a runtime shutdown
+// ============================================================================
+
+/// What happened to one box during a runtime shutdown.
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct JsBoxShutdownResult {
+    /// Box identifier
+    pub box_id: String,
+
+    /// "stopped", "killed" (didn't stop within the timeout) or "failed"
+    pub outcome: String,
+
+    /// Why stopping failed (only for "failed")
+    pub error: Option<String>,
+}
+
+/// Per-box results of a runtime shutdown.
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct JsShutdownReport {
+    /// Whether every box stopped gracefully
+    pub clean: bool,
+
+    /// One result per box that was active
+    pub boxes: Vec<JsBoxShutdownResult>,
+}
+
+impl From<ShutdownReport> for JsShutdownReport {
+    fn from(report: ShutdownReport) -> Self {
+        Self {
+            clean: report.is_clean(),
+            boxes: report
+                .boxes
+                .into_iter()
+                .map(|result| {
+                    let (outcome, error) = match result.outcome {
+                        BoxShutdownOutcome::Stopped => ("stopped", None),
+                        BoxShutdownOutcome::Killed => ("killed", None),
+                        BoxShutdownOutcome::Failed(error) => ("failed", Some(error)),
+                    };
+                    JsBoxShutdownResult {
+                        box_id: result.box_id.to_string(),
+                        outcome: outcome.to_string(),
+                        error,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
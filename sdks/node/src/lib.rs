@@ -18,7 +18,7 @@ mod util;
 pub use box_handle::JsBox;
 pub use copy::JsCopyOptions;
 pub use exec::{JsExecResult, JsExecStderr, JsExecStdin, JsExecStdout, JsExecution};
-pub use info::JsBoxInfo;
+pub use info::{JsBoxInfo, JsBoxShutdownResult, JsShutdownReport};
 pub use metrics::{JsBoxMetrics, JsRuntimeMetrics};
 pub use options::{JsBoxOptions, JsEnvVar, JsOptions, JsPortSpec, JsVolumeSpec};
 pub use runtime::JsBoxlite; // re-export for dist bundling
@@ -18,6 +18,8 @@ pub struct JsRuntimeMetrics {
     pub total_commands_executed: f64,
     /// Total command execution errors across all boxes
     pub total_exec_errors: f64,
+    /// Total retries of registry requests during image pulls
+    pub registry_retries_total: f64,
 }
 
 impl From<RuntimeMetrics> for JsRuntimeMetrics {
@@ -28,6 +30,7 @@ impl From<RuntimeMetrics> for JsRuntimeMetrics {
             num_running_boxes: m.num_running_boxes() as f64,
             total_commands_executed: m.total_commands_executed() as f64,
             total_exec_errors: m.total_exec_errors() as f64,
+            registry_retries_total: m.registry_retries_total() as f64,
         }
     }
 }
@@ -44,6 +47,8 @@ pub struct JsBoxMetrics {
     pub commands_executed_total: f64,
     /// Command execution errors on this box
     pub exec_errors_total: f64,
+    /// Executions started by this process that are still running
+    pub execs_in_flight: f64,
     /// Bytes sent to this box (via stdin)
     pub bytes_sent_total: f64,
     /// Bytes received from this box (via stdout/stderr)
@@ -71,6 +76,30 @@ pub struct JsBoxMetrics {
     /// Total TCP connection errors
     pub network_tcp_errors: Option<f64>,
 
+    // Container cgroup usage and throttling (only with cpus/memoryMib limits)
+    /// Periods in which the container hit its CPU limit
+    pub cpu_throttled_periods_total: Option<f64>,
+    /// Total time the container was throttled by its CPU limit (microseconds)
+    pub cpu_throttled_usec_total: Option<f64>,
+    /// Times the container's memory usage hit its limit
+    pub memory_limit_hits_total: Option<f64>,
+    /// Processes OOM-killed for exceeding the container's memory limit
+    pub memory_oom_kills_total: Option<f64>,
+    /// Container memory minus inactive page cache (bytes)
+    pub memory_working_set_bytes: Option<f64>,
+    /// Container page cache (bytes)
+    pub memory_cache_bytes: Option<f64>,
+    /// Processes and threads in the container
+    pub pids_current: Option<f64>,
+    /// Bytes the container read from block devices
+    pub io_read_bytes_total: Option<f64>,
+    /// Bytes the container wrote to block devices
+    pub io_write_bytes_total: Option<f64>,
+    /// Swap in use in the VM (bytes)
+    pub swap_used_bytes: Option<f64>,
+    /// Bytes the VM swapped out since it started
+    pub swap_out_bytes_total: Option<f64>,
+
     // Stage-level timing breakdown
     /// Time to create box directory structure (milliseconds)
     pub stage_filesystem_setup_ms: Option<f64>,
@@ -92,6 +121,7 @@ impl From<BoxMetrics> for JsBoxMetrics {
             // Execution metrics
             commands_executed_total: m.commands_executed_total as f64,
             exec_errors_total: m.exec_errors_total as f64,
+            execs_in_flight: m.execs_in_flight as f64,
             bytes_sent_total: m.bytes_sent_total as f64,
             bytes_received_total: m.bytes_received_total as f64,
 
@@ -109,6 +139,19 @@ impl From<BoxMetrics> for JsBoxMetrics {
             network_tcp_connections: m.network_tcp_connections.map(|v| v as f64),
             network_tcp_errors: m.network_tcp_errors.map(|v| v as f64),
 
+            // Container cgroup usage and throttling
+            cpu_throttled_periods_total: m.cpu_throttled_periods_total.map(|v| v as f64),
+            cpu_throttled_usec_total: m.cpu_throttled_usec_total.map(|v| v as f64),
+            memory_limit_hits_total: m.memory_limit_hits_total.map(|v| v as f64),
+            memory_oom_kills_total: m.memory_oom_kills_total.map(|v| v as f64),
+            memory_working_set_bytes: m.memory_working_set_bytes.map(|v| v as f64),
+            memory_cache_bytes: m.memory_cache_bytes.map(|v| v as f64),
+            pids_current: m.pids_current.map(|v| v as f64),
+            io_read_bytes_total: m.io_read_bytes_total.map(|v| v as f64),
+            io_write_bytes_total: m.io_write_bytes_total.map(|v| v as f64),
+            swap_used_bytes: m.swap_used_bytes.map(|v| v as f64),
+            swap_out_bytes_total: m.swap_out_bytes_total.map(|v| v as f64),
+
             // Stage timing (convert u128 to f64 for JavaScript)
             stage_filesystem_setup_ms: m.stage_filesystem_setup_ms.map(|v| v as f64),
             stage_image_prepare_ms: m.stage_image_prepare_ms.map(|v| v as f64),
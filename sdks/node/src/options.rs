@@ -80,8 +80,8 @@ pub struct JsBoxOptions {
 
     /// Override image ENTRYPOINT directive.
     ///
-    /// When set, completely replaces the image's ENTRYPOINT.
-    /// Use with `cmd` to build the full command:
+    /// When set, completely replaces the image's ENTRYPOINT and, as in
+    /// Docker, drops the image's CMD. Use with `cmd` to pass arguments:
     ///   Final execution = entrypoint + cmd
     pub entrypoint: Option<Vec<String>>,
 
@@ -94,6 +94,14 @@ pub struct JsBoxOptions {
     /// Username or UID (format: <name|uid>[:<group|gid>]).
     /// If None, uses the image's USER directive (defaults to root).
     pub user: Option<String>,
+
+    /// Guest path for the box's persistent cache directory (e.g. "/cache").
+    ///
+    /// Kept across box recreations with the same name. Requires a named box.
+    pub persistent_cache: Option<String>,
+
+    /// Log filter for the guest agent (e.g. "debug").
+    pub guest_log_level: Option<String>,
 }
 
 /// Environment variable specification.
@@ -126,6 +134,10 @@ impl From<JsVolumeSpec> for VolumeSpec {
             host_path: v.host_path,
             guest_path: v.guest_path,
             read_only: v.read_only.unwrap_or(false),
+            // Not exposed in JS API yet
+            options: Vec::new(),
+            chown: None,
+            driver: None,
         }
     }
 }
@@ -216,19 +228,43 @@ impl From<JsBoxOptions> for BoxOptions {
             cpus: js_opts.cpus,
             memory_mib: js_opts.memory_mib,
             disk_size_gb: js_opts.disk_size_gb.map(|v| v as u64),
+            shm_size_mib: None,               // Not exposed in JS API yet
+            swap_mib: None,                   // Not exposed in JS API yet
+            swap_backing: Default::default(), // Not exposed in JS API yet
             working_dir: js_opts.working_dir,
             env,
             rootfs,
             volumes,
             network,
             ports,
-            isolate_mounts: false, // Not exposed in JS API yet
+            hostname: None,                   // Not exposed in JS API yet
+            extra_hosts: Vec::new(),          // Not exposed in JS API yet
+            networks: Vec::new(),             // Not exposed in JS API yet
+            host_loopback: false,             // Not exposed in JS API yet
+            prompt_connections: false,        // Not exposed in JS API yet
+            network_mode: Default::default(), // Not exposed in JS API yet
+            isolate_mounts: false,            // Not exposed in JS API yet
             auto_remove: js_opts.auto_remove.unwrap_or(false),
             detach: js_opts.detach.unwrap_or(false),
             security: Default::default(), // Use default security options
+            sysctls: Default::default(),  // Not exposed in JS API yet
+            ulimits: Vec::new(),          // Not exposed in JS API yet
             entrypoint: js_opts.entrypoint,
             cmd: js_opts.cmd,
             user: js_opts.user,
+            pull_policy: Default::default(), // Not exposed in JS API yet
+            persistent_cache: js_opts.persistent_cache,
+            guest_log_level: js_opts.guest_log_level,
+            depends_on: Vec::new(),         // Not exposed in JS API yet
+            stop_dependencies: false,       // Not exposed in JS API yet
+            ttl_secs: None,                 // Not exposed in JS API yet
+            max_concurrent_execs: None,     // Not exposed in JS API yet
+            queue_execs: false,             // Not exposed in JS API yet
+            exec_retention_secs: None,      // Not exposed in JS API yet
+            propagate_trace_context: false, // Not exposed in JS API yet
+            heartbeat_interval_secs: None,  // Not exposed in JS API yet
+            unresponsive_after_secs: None,  // Not exposed in JS API yet
+            restart_unresponsive: false,    // Not exposed in JS API yet
         }
     }
 }
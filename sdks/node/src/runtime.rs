@@ -5,7 +5,7 @@ use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
 use crate::box_handle::JsBox;
-use crate::info::JsBoxInfo;
+use crate::info::{JsBoxInfo, JsShutdownReport};
 use crate::metrics::JsRuntimeMetrics;
 use crate::options::{JsBoxOptions, JsOptions};
 use crate::util::map_err;
@@ -292,10 +292,14 @@ impl JsBoxlite {
     ///   - Positive number - Wait that many seconds
     ///   - `-1` - Wait indefinitely (no timeout)
     ///
+    /// # Returns
+    /// A report with the outcome of each box that was active
+    ///
     /// # Example
     /// ```javascript
     /// // Default 10s timeout
-    /// await runtime.shutdown();
+    /// const report = await runtime.shutdown();
+    /// if (!report.clean) console.warn(report.boxes);
     ///
     /// // Custom 30s timeout
     /// await runtime.shutdown(30);
@@ -304,9 +308,13 @@ impl JsBoxlite {
     /// await runtime.shutdown(-1);
     /// ```
     #[napi]
-    pub async fn shutdown(&self, timeout: Option<i32>) -> Result<()> {
+    pub async fn shutdown(&self, timeout: Option<i32>) -> Result<JsShutdownReport> {
         let runtime = Arc::clone(&self.runtime);
-        runtime.shutdown(timeout).await.map_err(map_err)
+        runtime
+            .shutdown(timeout)
+            .await
+            .map(JsShutdownReport::from)
+            .map_err(map_err)
     }
 }
 
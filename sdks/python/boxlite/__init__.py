@@ -18,6 +18,8 @@ try:
         ExecStderr,
         BoxInfo,
         BoxStateInfo,
+        ShutdownReport,
+        BoxShutdownResult,
         RuntimeMetrics,
         BoxMetrics,
         CopyOptions,
@@ -35,6 +37,8 @@ try:
         "ExecStderr",
         "BoxInfo",
         "BoxStateInfo",
+        "ShutdownReport",
+        "BoxShutdownResult",
         "RuntimeMetrics",
         "BoxMetrics",
         "CopyOptions",
@@ -12,7 +12,14 @@ from greenlet import greenlet
 
 if TYPE_CHECKING:
     from ._box import SyncBox
-    from ..boxlite import Boxlite, BoxOptions, BoxInfo, RuntimeMetrics, Options
+    from ..boxlite import (
+        Boxlite,
+        BoxOptions,
+        BoxInfo,
+        RuntimeMetrics,
+        Options,
+        ShutdownReport,
+    )
 
 __all__ = ["SyncBoxlite"]
 
@@ -359,7 +366,7 @@ class SyncBoxlite:
         """
         self._sync(self._boxlite.remove(id_or_name, force))
 
-    def shutdown(self, timeout: Optional[int] = None) -> None:
+    def shutdown(self, timeout: Optional[int] = None) -> "ShutdownReport":
         """
         Gracefully shutdown all boxes in this runtime.
 
@@ -374,8 +381,11 @@ class SyncBoxlite:
                 - None (default) - Use default timeout (10 seconds)
                 - Positive integer - Wait that many seconds
                 - -1 - Wait indefinitely (no timeout)
+
+        Returns:
+            ShutdownReport with the outcome of each box that was active.
         """
-        self._sync(self._boxlite.shutdown(timeout))
+        return self._sync(self._boxlite.shutdown(timeout))
 
     # ─────────────────────────────────────────────────────────────────────────
     # Properties for internal use by SyncBox/SyncExecution
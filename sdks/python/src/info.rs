@@ -1,4 +1,4 @@
-use boxlite::{BoxInfo, BoxStateInfo, BoxStatus};
+use boxlite::{BoxInfo, BoxShutdownOutcome, BoxStateInfo, BoxStatus, ShutdownReport};
 use pyo3::prelude::*;
 
 // ============================================================================
@@ -33,6 +33,7 @@ fn status_to_string(status: BoxStatus) -> String {
         BoxStatus::Unknown => "unknown",
         BoxStatus::Configured => "configured",
         BoxStatus::Running => "running",
+        BoxStatus::Unresponsive => "unresponsive",
         BoxStatus::Stopping => "stopping",
         BoxStatus::Stopped => "stopped",
     }
@@ -111,3 +112,75 @@ impl From<BoxInfo> for PyBoxInfo {
         }
     }
 }
+
+// ============================================================================
+// ShutdownReport - Per-box outcomes of a runtime shutdown
+// ============================================================================
+
+#[pyclass(name = "BoxShutdownResult")]
+#[derive(Clone)]
+pub(crate) struct PyBoxShutdownResult {
+    #[pyo3(get)]
+    pub(crate) box_id: String,
+    /// "stopped", "killed" or "failed"
+    #[pyo3(get)]
+    pub(crate) outcome: String,
+    #[pyo3(get)]
+    pub(crate) error: Option<String>,
+}
+
+#[pymethods]
+impl PyBoxShutdownResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "BoxShutdownResult(box_id={:?}, outcome={:?}, error={:?})",
+            self.box_id, self.outcome, self.error
+        )
+    }
+}
+
+#[pyclass(name = "ShutdownReport")]
+#[derive(Clone)]
+pub(crate) struct PyShutdownReport {
+    #[pyo3(get)]
+    pub(crate) boxes: Vec<PyBoxShutdownResult>,
+}
+
+#[pymethods]
+impl PyShutdownReport {
+    /// True if every box stopped gracefully.
+    fn is_clean(&self) -> bool {
+        self.boxes.iter().all(|b| b.outcome == "stopped")
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ShutdownReport(clean={}, boxes={})",
+            self.is_clean(),
+            self.boxes.len()
+        )
+    }
+}
+
+impl From<ShutdownReport> for PyShutdownReport {
+    fn from(report: ShutdownReport) -> Self {
+        PyShutdownReport {
+            boxes: report
+                .boxes
+                .into_iter()
+                .map(|result| {
+                    let (outcome, error) = match result.outcome {
+                        BoxShutdownOutcome::Stopped => ("stopped", None),
+                        BoxShutdownOutcome::Killed => ("killed", None),
+                        BoxShutdownOutcome::Failed(error) => ("failed", Some(error)),
+                    };
+                    PyBoxShutdownResult {
+                        box_id: result.box_id.to_string(),
+                        outcome: outcome.to_string(),
+                        error,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
@@ -10,7 +10,7 @@ mod util;
 
 use crate::box_handle::PyBox;
 use crate::exec::{PyExecStderr, PyExecStdin, PyExecStdout, PyExecution};
-use crate::info::{PyBoxInfo, PyBoxStateInfo};
+use crate::info::{PyBoxInfo, PyBoxShutdownResult, PyBoxStateInfo, PyShutdownReport};
 use crate::metrics::{PyBoxMetrics, PyRuntimeMetrics};
 use crate::options::{PyBoxOptions, PyCopyOptions, PyOptions, PySecurityOptions};
 use crate::runtime::PyBoxlite;
@@ -29,6 +29,8 @@ fn boxlite_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyExecStderr>()?;
     m.add_class::<PyBoxInfo>()?;
     m.add_class::<PyBoxStateInfo>()?;
+    m.add_class::<PyShutdownReport>()?;
+    m.add_class::<PyBoxShutdownResult>()?;
     m.add_class::<PyRuntimeMetrics>()?;
     m.add_class::<PyBoxMetrics>()?;
     m.add_class::<PyCopyOptions>()?;
@@ -14,18 +14,21 @@ pub(crate) struct PyRuntimeMetrics {
     pub(crate) total_commands_executed: u64,
     #[pyo3(get)]
     pub(crate) total_exec_errors: u64,
+    #[pyo3(get)]
+    pub(crate) registry_retries_total: u64,
 }
 
 #[pymethods]
 impl PyRuntimeMetrics {
     fn __repr__(&self) -> String {
         format!(
-            "RuntimeMetrics(boxes_created={}, boxes_failed={}, running={}, commands={}, errors={})",
+            "RuntimeMetrics(boxes_created={}, boxes_failed={}, running={}, commands={}, errors={}, registry_retries={})",
             self.boxes_created_total,
             self.boxes_failed_total,
             self.num_running_boxes,
             self.total_commands_executed,
-            self.total_exec_errors
+            self.total_exec_errors,
+            self.registry_retries_total
         )
     }
 }
@@ -38,6 +41,7 @@ impl From<RuntimeMetrics> for PyRuntimeMetrics {
             num_running_boxes: metrics.num_running_boxes(),
             total_commands_executed: metrics.total_commands_executed(),
             total_exec_errors: metrics.total_exec_errors(),
+            registry_retries_total: metrics.registry_retries_total(),
         }
     }
 }
@@ -50,6 +54,8 @@ pub(crate) struct PyBoxMetrics {
     #[pyo3(get)]
     pub(crate) exec_errors_total: u64,
     #[pyo3(get)]
+    pub(crate) execs_in_flight: u64,
+    #[pyo3(get)]
     pub(crate) bytes_sent_total: u64,
     #[pyo3(get)]
     pub(crate) bytes_received_total: u64,
@@ -69,6 +75,29 @@ pub(crate) struct PyBoxMetrics {
     pub(crate) network_tcp_connections: Option<u64>,
     #[pyo3(get)]
     pub(crate) network_tcp_errors: Option<u64>,
+    // Container cgroup usage and throttling (only with cpus/memory_mib limits)
+    #[pyo3(get)]
+    pub(crate) cpu_throttled_periods_total: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) cpu_throttled_usec_total: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) memory_limit_hits_total: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) memory_oom_kills_total: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) memory_working_set_bytes: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) memory_cache_bytes: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) pids_current: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) io_read_bytes_total: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) io_write_bytes_total: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) swap_used_bytes: Option<u64>,
+    #[pyo3(get)]
+    pub(crate) swap_out_bytes_total: Option<u64>,
     // Stage-level timing breakdown
     #[pyo3(get)]
     pub(crate) stage_filesystem_setup_ms: Option<u128>,
@@ -104,6 +133,7 @@ impl From<BoxMetrics> for PyBoxMetrics {
         PyBoxMetrics {
             commands_executed_total: metrics.commands_executed_total(),
             exec_errors_total: metrics.exec_errors_total(),
+            execs_in_flight: metrics.execs_in_flight(),
             bytes_sent_total: metrics.bytes_sent_total(),
             bytes_received_total: metrics.bytes_received_total(),
             total_create_duration_ms: metrics.total_create_duration_ms(),
@@ -114,6 +144,17 @@ impl From<BoxMetrics> for PyBoxMetrics {
             network_bytes_received: metrics.network_bytes_received(),
             network_tcp_connections: metrics.network_tcp_connections(),
             network_tcp_errors: metrics.network_tcp_errors(),
+            cpu_throttled_periods_total: metrics.cpu_throttled_periods_total(),
+            cpu_throttled_usec_total: metrics.cpu_throttled_usec_total(),
+            memory_limit_hits_total: metrics.memory_limit_hits_total(),
+            memory_oom_kills_total: metrics.memory_oom_kills_total(),
+            memory_working_set_bytes: metrics.memory_working_set_bytes(),
+            memory_cache_bytes: metrics.memory_cache_bytes(),
+            pids_current: metrics.pids_current(),
+            io_read_bytes_total: metrics.io_read_bytes_total(),
+            io_write_bytes_total: metrics.io_write_bytes_total(),
+            swap_used_bytes: metrics.swap_used_bytes(),
+            swap_out_bytes_total: metrics.swap_out_bytes_total(),
             stage_filesystem_setup_ms: metrics.stage_filesystem_setup_ms(),
             stage_image_prepare_ms: metrics.stage_image_prepare_ms(),
             stage_guest_rootfs_ms: metrics.stage_guest_rootfs_ms(),
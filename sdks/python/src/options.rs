@@ -69,6 +69,8 @@ pub struct PyCopyOptions {
     pub follow_symlinks: bool,
     #[pyo3(get, set)]
     pub include_parent: bool,
+    #[pyo3(get, set)]
+    pub respect_ignore_files: bool,
 }
 
 #[pymethods]
@@ -79,15 +81,23 @@ impl PyCopyOptions {
             recursive = true,
             overwrite = true,
             follow_symlinks = false,
-            include_parent = true
+            include_parent = true,
+            respect_ignore_files = false
         )
     )]
-    fn new(recursive: bool, overwrite: bool, follow_symlinks: bool, include_parent: bool) -> Self {
+    fn new(
+        recursive: bool,
+        overwrite: bool,
+        follow_symlinks: bool,
+        include_parent: bool,
+        respect_ignore_files: bool,
+    ) -> Self {
         Self {
             recursive,
             overwrite,
             follow_symlinks,
             include_parent,
+            respect_ignore_files,
         }
     }
 }
@@ -99,6 +109,7 @@ impl From<PyCopyOptions> for CopyOptions {
             overwrite: opt.overwrite,
             follow_symlinks: opt.follow_symlinks,
             include_parent: opt.include_parent,
+            respect_ignore_files: opt.respect_ignore_files,
         }
     }
 }
@@ -319,7 +330,8 @@ pub(crate) struct PyBoxOptions {
     #[pyo3(get, set)]
     pub(crate) detach: Option<bool>,
     /// Override the image's ENTRYPOINT directive.
-    /// When set, completely replaces the image's ENTRYPOINT.
+    /// When set, completely replaces the image's ENTRYPOINT and drops the
+    /// image's CMD, like Docker.
     /// Example: `entrypoint=["dockerd"]` with `docker:dind`
     #[pyo3(get, set)]
     pub(crate) entrypoint: Option<Vec<String>>,
@@ -331,6 +343,13 @@ pub(crate) struct PyBoxOptions {
     /// If None, uses the image's USER directive (defaults to root).
     #[pyo3(get, set)]
     pub(crate) user: Option<String>,
+    /// Guest path for a persistent cache directory (e.g. `/cache`), kept
+    /// across recreations of the box with the same name.
+    #[pyo3(get, set)]
+    pub(crate) persistent_cache: Option<String>,
+    /// Log filter for the guest agent (e.g. `debug`).
+    #[pyo3(get, set)]
+    pub(crate) guest_log_level: Option<String>,
     /// Security isolation options for the box.
     #[pyo3(get, set)]
     pub(crate) security: Option<PySecurityOptions>,
@@ -356,6 +375,8 @@ impl PyBoxOptions {
         cmd=None,
         user=None,
         security=None,
+        persistent_cache=None,
+        guest_log_level=None,
     ))]
     #[allow(clippy::too_many_arguments)]
     fn new(
@@ -375,6 +396,8 @@ impl PyBoxOptions {
         cmd: Option<Vec<String>>,
         user: Option<String>,
         security: Option<PySecurityOptions>,
+        persistent_cache: Option<String>,
+        guest_log_level: Option<String>,
     ) -> Self {
         Self {
             image,
@@ -392,6 +415,8 @@ impl PyBoxOptions {
             entrypoint,
             cmd,
             user,
+            persistent_cache,
+            guest_log_level,
             security,
         }
     }
@@ -446,6 +471,8 @@ impl From<PyBoxOptions> for BoxOptions {
             entrypoint: py_opts.entrypoint,
             cmd: py_opts.cmd,
             user: py_opts.user,
+            persistent_cache: py_opts.persistent_cache,
+            guest_log_level: py_opts.guest_log_level,
             ..Default::default()
         };
 
@@ -480,6 +507,10 @@ impl From<PyVolumeSpec> for VolumeSpec {
             host_path: v.host,
             guest_path: v.guest,
             read_only: v.read_only,
+            // Not exposed in Python API yet
+            options: Vec::new(),
+            chown: None,
+            driver: None,
         }
     }
 }
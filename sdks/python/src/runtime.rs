@@ -4,7 +4,7 @@ use boxlite::BoxliteRuntime;
 use pyo3::prelude::*;
 
 use crate::box_handle::PyBox;
-use crate::info::PyBoxInfo;
+use crate::info::{PyBoxInfo, PyShutdownReport};
 use crate::metrics::PyRuntimeMetrics;
 use crate::options::{PyBoxOptions, PyOptions};
 use crate::util::map_err;
@@ -175,12 +175,15 @@ impl PyBoxlite {
     ///         - None (default) - Use default timeout (10 seconds)
     ///         - Positive integer - Wait that many seconds
     ///         - -1 - Wait indefinitely (no timeout)
+    ///
+    /// Returns:
+    ///     ShutdownReport with the outcome of each box that was active.
     #[pyo3(signature = (timeout=None))]
     fn shutdown<'py>(&self, py: Python<'py>, timeout: Option<i32>) -> PyResult<Bound<'py, PyAny>> {
         let runtime = Arc::clone(&self.runtime);
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            runtime.shutdown(timeout).await.map_err(map_err)?;
-            Ok(())
+            let report = runtime.shutdown(timeout).await.map_err(map_err)?;
+            Ok(PyShutdownReport::from(report))
         })
     }
 
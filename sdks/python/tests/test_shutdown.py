@@ -34,7 +34,8 @@ class TestShutdown:
         """Shutdown can be called multiple times safely."""
         runtime = boxlite.Boxlite.default()
         await runtime.shutdown()
-        await runtime.shutdown()  # Should not fail
+        report = await runtime.shutdown()  # Should not fail
+        assert report.is_clean() and report.boxes == []
 
     @pytest.mark.asyncio
     async def test_shutdown_multiple_boxes(self):
@@ -52,7 +53,9 @@ class TestShutdown:
         assert metrics.num_running_boxes >= 3
 
         # Shutdown all
-        await runtime.shutdown(timeout=10)
+        report = await runtime.shutdown(timeout=10)
+        assert len(report.boxes) >= 3
+        assert all(b.outcome in ("stopped", "killed") for b in report.boxes)
 
     @pytest.mark.asyncio
     async def test_operations_fail_after_shutdown(self):